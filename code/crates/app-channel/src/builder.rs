@@ -11,12 +11,11 @@ use eyre::Result;
 use tokio::sync::mpsc::{self, Sender};
 
 use malachitebft_app::types::codec::HasEncodedLen;
-use malachitebft_engine::network::{NetworkIdentity, NetworkRef};
+use malachitebft_engine::network::{NetworkIdentity, NetworkRef, PeerFilter};
 use malachitebft_engine::sync::SyncRef;
 use malachitebft_engine::util::events::TxEvent;
 use malachitebft_engine::util::output_port::{OutputPort, OutputPortSubscriberTrait};
 use malachitebft_engine::wal::WalRef;
-use malachitebft_signing::{Signer, Verifier};
 
 use crate::app::config::NodeConfig;
 use crate::app::metrics::{Metrics, SharedRegistry};
@@ -25,10 +24,14 @@ use crate::app::spawn::{
 };
 use crate::app::types::codec;
 use crate::app::types::core::Context;
+use crate::channel::ChannelConfig;
 use crate::msgs::NetworkMsg;
+use crate::notifications::{TxNotification, DEFAULT_NOTIFICATIONS_CAPACITY};
 use crate::spawn::{spawn_host_actor, spawn_network_actor};
 use crate::{Channels, EngineHandle};
 
+pub use crate::app::builder::ConsensusContext;
+
 pub enum NoCodec {}
 
 impl<T> codec::Codec<T> for NoCodec {
@@ -65,42 +68,25 @@ impl<Codec> WalContext<Codec> {
 pub struct NetworkContext<Codec> {
     pub identity: NetworkIdentity,
     pub codec: Codec,
+    /// Filter deciding which peers may connect to the node, taking precedence over
+    /// the allow-list file of the P2P configuration.
+    pub peer_filter: Option<Arc<dyn PeerFilter>>,
 }
 
 impl<Codec> NetworkContext<Codec> {
     pub fn new(identity: NetworkIdentity, codec: Codec) -> Self {
-        Self { identity, codec }
-    }
-}
-
-/// Context for spawning the Consensus actor.
-pub struct ConsensusContext<Ctx: Context> {
-    pub address: Ctx::Address,
-    pub verifier: Box<dyn Verifier<Ctx>>,
-    pub signer: Option<Box<dyn Signer<Ctx>>>,
-}
-
-impl<Ctx: Context> ConsensusContext<Ctx> {
-    /// Create a consensus context for a validator node (has both verifier and signer).
-    pub fn new_validator(
-        address: Ctx::Address,
-        verifier: Box<dyn Verifier<Ctx>>,
-        signer: Box<dyn Signer<Ctx>>,
-    ) -> Self {
         Self {
-            address,
-            verifier,
-            signer: Some(signer),
+            identity,
+            codec,
+            peer_filter: None,
         }
     }
 
-    /// Create a consensus context for a full (non-validator) node (verifier only).
-    pub fn new_full_node(address: Ctx::Address, verifier: Box<dyn Verifier<Ctx>>) -> Self {
-        Self {
-            address,
-            verifier,
-            signer: None,
-        }
+    /// Only let the peers allowed by the given filter connect to the node,
+    /// eg. the members of a permissioned network.
+    pub fn with_peer_filter(mut self, peer_filter: Arc<dyn PeerFilter>) -> Self {
+        self.peer_filter = Some(peer_filter);
+        self
     }
 }
 
@@ -115,14 +101,35 @@ impl<Codec> SyncContext<Codec> {
     }
 }
 
-/// Context for request channels.
+/// Context for the channels between the engine and the application.
 pub struct RequestContext {
+    /// Capacity of the channels for requests sent by the application
     pub channel_size: usize,
+    /// Capacities of the channel for messages sent by consensus to the application
+    pub app_channel: ChannelConfig,
+    /// Number of notifications retained for the subscribers which fall behind
+    pub notifications_capacity: usize,
 }
 
 impl RequestContext {
     pub fn new(channel_size: usize) -> Self {
-        Self { channel_size }
+        Self {
+            channel_size,
+            app_channel: ChannelConfig::default(),
+            notifications_capacity: DEFAULT_NOTIFICATIONS_CAPACITY,
+        }
+    }
+
+    /// Set the capacities of the channel for messages sent by consensus to the application.
+    pub fn with_app_channel(mut self, app_channel: ChannelConfig) -> Self {
+        self.app_channel = app_channel;
+        self
+    }
+
+    /// Set the number of notifications retained for the subscribers which fall behind.
+    pub fn with_notifications_capacity(mut self, capacity: usize) -> Self {
+        self.notifications_capacity = capacity;
+        self
     }
 }
 
@@ -753,7 +760,9 @@ where
             NetworkBuilder::Custom(custom) => custom,
             NetworkBuilder::Default(network_ctx) => {
                 spawn_network_actor(
+                    &self.ctx,
                     network_ctx.identity,
+                    network_ctx.peer_filter,
                     self.config.consensus(),
                     self.config.value_sync(),
                     &registry,
@@ -767,12 +776,27 @@ where
         let wal = match wal_builder {
             WalBuilder::Custom(wal_ref) => wal_ref,
             WalBuilder::Default(wal_ctx) => {
-                spawn_wal_actor(&self.ctx, wal_ctx.codec, &wal_ctx.path, &registry).await?
+                spawn_wal_actor(
+                    &self.ctx,
+                    wal_ctx.codec,
+                    &wal_ctx.path,
+                    self.config.consensus().wal_storage,
+                    self.config.consensus().wal_encryption_key_file.as_deref(),
+                    &registry,
+                )
+                .await?
             }
         };
 
         // 3. Host actor (use the default channel-based Connector)
-        let (connector, rx_consensus) = spawn_host_actor(metrics.clone()).await?;
+        let tx_notification = TxNotification::new(request_ctx.notifications_capacity);
+        let (connector, rx_consensus, sync_paused) = spawn_host_actor(
+            metrics.clone(),
+            request_ctx.app_channel,
+            tx_notification.clone(),
+            &registry,
+        )
+        .await?;
 
         let tx_event = TxEvent::new();
         let sync_port = Arc::new(OutputPort::new());
@@ -790,6 +814,7 @@ where
             sync_port.clone(),
             metrics,
             tx_event.clone(),
+            Arc::clone(&consensus_ctx.clock),
         )
         .await?;
 
@@ -805,14 +830,17 @@ where
                     sync_ctx.codec,
                     self.config.value_sync(),
                     &registry,
+                    tx_event.clone(),
+                    consensus_ctx.clock,
                 )
                 .await?
             }
         };
 
-        // Subscribe sync actor to the sync port
+        // Subscribe sync actor to the sync port, and pause it while the application falls behind
         if let Some(sync) = &sync {
             sync.subscribe_to_port(&sync_port);
+            crate::run::spawn_sync_backpressure_task(sync_paused, sync.clone());
         }
 
         // 6. Node actor
@@ -823,6 +851,8 @@ where
             wal,
             sync,
             connector,
+            tx_event.clone(),
+            self.config.consensus(),
         )
         .await?;
 
@@ -838,6 +868,7 @@ where
             consensus: rx_consensus,
             network: tx_network,
             events: tx_event,
+            notifications: tx_notification,
             requests: tx_request,
             net_requests: tx_net_request,
         };
@@ -858,6 +889,7 @@ mod byzantine {
     use malachitebft_engine::sync::SyncCodec;
     use malachitebft_engine_byzantine::{
         ByzantineConfig, ByzantineNetworkProxy, ConflictingValueFn, ConflictingVoteValueFn,
+        ProposalPartPositionFn,
     };
     use malachitebft_signing::Signer;
 
@@ -886,6 +918,10 @@ mod byzantine {
         /// equivocation. If `None`, non-nil votes are equivocated to nil
         /// and nil votes are left alone (nothing to equivocate to).
         pub conflicting_vote_value_fn: Option<ConflictingVoteValueFn<Ctx>>,
+        /// Optional function finding the height and round of a stream of
+        /// proposal parts from its first part, for withholding proposal parts.
+        /// If `None`, proposal parts are never withheld.
+        pub proposal_part_position_fn: Option<ProposalPartPositionFn<Ctx>>,
     }
 
     // Byzantine-mode Network installer — mirrors `with_custom_network`'s
@@ -951,7 +987,9 @@ mod byzantine {
             let registry = SharedRegistry::global().with_moniker(self.config.moniker());
 
             let (real_network, tx_network) = spawn_network_actor(
+                &self.ctx,
                 byz.identity,
+                None,
                 self.config.consensus(),
                 self.config.value_sync(),
                 &registry,
@@ -971,6 +1009,7 @@ mod byzantine {
                 span,
                 byz.conflicting_value_fn,
                 byz.conflicting_vote_value_fn,
+                byz.proposal_part_position_fn,
             )
             .await
             {
@@ -1023,6 +1062,10 @@ mod tests {
         fn value_sync_mut(&mut self) -> &mut malachitebft_config::ValueSyncConfig {
             todo!()
         }
+
+        fn metrics(&self) -> &malachitebft_config::MetricsConfig {
+            todo!()
+        }
     }
 
     // All default actors
@@ -0,0 +1,461 @@
+//! Bounded channel carrying the messages sent by consensus to the application.
+//!
+//! Messages are grouped into classes, each with its own capacity, so that a slow
+//! application which falls behind on one class of messages (eg. sync requests) does not
+//! hold up the others, and the number of messages waiting for the application stays bounded.
+
+use core::fmt;
+use std::sync::Arc;
+
+use thiserror::Error;
+use tokio::sync::{mpsc, watch, OwnedSemaphorePermit, Semaphore};
+
+use crate::app::metrics::prometheus::encoding::{EncodeLabelSet, EncodeLabelValue};
+use crate::app::metrics::prometheus::metrics::counter::Counter;
+use crate::app::metrics::prometheus::metrics::family::Family;
+use crate::app::metrics::SharedRegistry;
+use crate::app::types::core::Context;
+use crate::msgs::AppMsg;
+
+// Make prometheus_client available for the derive macros
+use crate::app::metrics::prometheus as prometheus_client;
+
+/// Class of a message sent by consensus to the application.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, EncodeLabelValue)]
+pub enum MessageClass {
+    /// Messages on the critical path of consensus, eg. `GetValue` or `Decided`
+    Consensus,
+    /// Proposals and proposal parts received from the network
+    Proposals,
+    /// Messages serving value sync, eg. `GetDecidedValues` or `ProcessSyncedValue`
+    Sync,
+}
+
+impl MessageClass {
+    /// All message classes.
+    pub const ALL: [MessageClass; 3] = [
+        MessageClass::Consensus,
+        MessageClass::Proposals,
+        MessageClass::Sync,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Whether messages of this class are rejected when the class is at capacity,
+    /// rather than waiting for the application to catch up.
+    ///
+    /// Consensus cannot make progress without the replies to the messages on its critical path,
+    /// so these always wait, and so do proposal parts, which are only streamed once.
+    /// Sync requests are retried, so these are rejected instead, and consensus is replied to
+    /// with [`HostError::Backpressure`](malachitebft_engine::host::HostError::Backpressure).
+    pub fn can_overflow(self) -> bool {
+        match self {
+            MessageClass::Consensus | MessageClass::Proposals => false,
+            MessageClass::Sync => true,
+        }
+    }
+}
+
+impl fmt::Display for MessageClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageClass::Consensus => write!(f, "consensus"),
+            MessageClass::Proposals => write!(f, "proposals"),
+            MessageClass::Sync => write!(f, "sync"),
+        }
+    }
+}
+
+/// Capacities of the channel between consensus and the application, per message class.
+///
+/// The capacity of a class bounds the number of messages of that class which have been sent
+/// to the application and not yet replied to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ChannelConfig {
+    /// Capacity for messages on the critical path of consensus
+    pub consensus: usize,
+    /// Capacity for proposals and proposal parts
+    pub proposals: usize,
+    /// Capacity for value sync messages
+    pub sync: usize,
+    /// Number of pending value sync messages at which sync stops requesting values from peers,
+    /// until the application has caught up with half of them. Never pause sync if `None`.
+    pub sync_pause_threshold: Option<usize>,
+}
+
+impl ChannelConfig {
+    /// Capacity of the given message class, at least one.
+    pub fn capacity(&self, class: MessageClass) -> usize {
+        let capacity = match class {
+            MessageClass::Consensus => self.consensus,
+            MessageClass::Proposals => self.proposals,
+            MessageClass::Sync => self.sync,
+        };
+
+        capacity.max(1)
+    }
+
+    /// Total capacity of the channel, across all message classes.
+    pub fn total(&self) -> usize {
+        MessageClass::ALL
+            .iter()
+            .map(|class| self.capacity(*class))
+            .sum()
+    }
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            consensus: 32,
+            proposals: 64,
+            sync: 32,
+            sync_pause_threshold: Some(24),
+        }
+    }
+}
+
+/// Error returned to the engine when a message cannot be delivered to the application.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Error)]
+pub enum BackpressureError {
+    /// The application has too many pending messages of this class
+    #[error("The application channel is full for {0} messages")]
+    Full(MessageClass),
+    /// The application channel is closed (typically because the application has stopped)
+    #[error("The application channel is closed")]
+    Closed,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct ClassLabels {
+    class: MessageClass,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ChannelMetrics {
+    /// Number of messages which found their class at capacity, per message class
+    full: Family<ClassLabels, Counter>,
+    /// Number of messages rejected because their class was at capacity, per message class
+    overflow: Family<ClassLabels, Counter>,
+}
+
+impl ChannelMetrics {
+    pub fn register(registry: &SharedRegistry) -> Self {
+        let metrics = Self::default();
+
+        registry.with_prefix("malachitebft_app_channel", |registry| {
+            registry.register(
+                "full",
+                "Number of messages to the application which found their class at capacity",
+                metrics.full.clone(),
+            );
+
+            registry.register(
+                "overflow",
+                "Number of messages to the application rejected because their class was at capacity",
+                metrics.overflow.clone(),
+            );
+        });
+
+        metrics
+    }
+
+    fn inc_full(&self, class: MessageClass) {
+        self.full.get_or_create(&ClassLabels { class }).inc();
+    }
+
+    fn inc_overflow(&self, class: MessageClass) {
+        self.overflow.get_or_create(&ClassLabels { class }).inc();
+    }
+}
+
+/// Decides whether sync should be paused from the number of pending sync messages.
+#[derive(Debug)]
+struct SyncBackpressure {
+    /// Remaining capacity for sync messages
+    available: Arc<Semaphore>,
+    /// Capacity for sync messages
+    capacity: usize,
+    /// Number of pending sync messages at which sync is paused, if any
+    threshold: Option<usize>,
+    /// Whether sync should be paused
+    paused: watch::Sender<bool>,
+}
+
+impl SyncBackpressure {
+    /// Pause sync once the number of pending sync messages reaches the threshold,
+    /// and resume it once it has fallen to half the threshold, so that sync is not
+    /// paused and resumed again for every message.
+    fn update(&self) {
+        let Some(threshold) = self.threshold else {
+            return;
+        };
+
+        let pending = self
+            .capacity
+            .saturating_sub(self.available.available_permits());
+
+        self.paused.send_if_modified(|paused| {
+            let pause = if *paused {
+                pending > threshold / 2
+            } else {
+                pending >= threshold
+            };
+
+            core::mem::replace(paused, pause) != pause
+        });
+    }
+}
+
+/// Permit held while a message is pending in the application,
+/// releasing capacity for its class once dropped.
+#[derive(Debug)]
+pub struct Permit {
+    permit: Option<OwnedSemaphorePermit>,
+    sync_backpressure: Option<Arc<SyncBackpressure>>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        // Release the capacity before checking whether sync can be resumed
+        drop(self.permit.take());
+
+        if let Some(sync_backpressure) = &self.sync_backpressure {
+            sync_backpressure.update();
+        }
+    }
+}
+
+/// Sending half of the channel between consensus and the application.
+pub struct AppSender<Ctx: Context> {
+    sender: mpsc::Sender<AppMsg<Ctx>>,
+    capacities: [Arc<Semaphore>; 3],
+    sync_backpressure: Arc<SyncBackpressure>,
+    metrics: ChannelMetrics,
+}
+
+impl<Ctx: Context> AppSender<Ctx> {
+    /// Send a message to the application, once there is capacity for its class.
+    ///
+    /// The returned permit must be held until the application has replied to the message.
+    pub async fn send(&self, msg: AppMsg<Ctx>) -> Result<Permit, BackpressureError> {
+        let class = msg.class();
+        let capacity = &self.capacities[class.index()];
+
+        let permit = match Arc::clone(capacity).try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                self.metrics.inc_full(class);
+
+                if class.can_overflow() {
+                    self.metrics.inc_overflow(class);
+                    return Err(BackpressureError::Full(class));
+                }
+
+                Arc::clone(capacity)
+                    .acquire_owned()
+                    .await
+                    .map_err(|_| BackpressureError::Closed)?
+            }
+        };
+
+        self.sender
+            .send(msg)
+            .await
+            .map_err(|_| BackpressureError::Closed)?;
+
+        let sync_backpressure = (class == MessageClass::Sync).then(|| {
+            self.sync_backpressure.update();
+            Arc::clone(&self.sync_backpressure)
+        });
+
+        Ok(Permit {
+            permit: Some(permit),
+            sync_backpressure,
+        })
+    }
+
+    /// Whether sync should be paused, as the application has too many pending sync messages.
+    pub fn sync_paused(&self) -> watch::Receiver<bool> {
+        self.sync_backpressure.paused.subscribe()
+    }
+}
+
+/// Create a channel between consensus and the application with the given capacities.
+pub fn app_channel<Ctx: Context>(
+    config: ChannelConfig,
+    metrics: ChannelMetrics,
+) -> (AppSender<Ctx>, mpsc::Receiver<AppMsg<Ctx>>) {
+    // Messages are only sent once a permit has been acquired for their class,
+    // so the underlying channel never fills up.
+    let (sender, receiver) = mpsc::channel(config.total());
+
+    let capacities =
+        MessageClass::ALL.map(|class| Arc::new(Semaphore::new(config.capacity(class))));
+
+    let sync_backpressure = Arc::new(SyncBackpressure {
+        available: Arc::clone(&capacities[MessageClass::Sync.index()]),
+        capacity: config.capacity(MessageClass::Sync),
+        threshold: config.sync_pause_threshold,
+        paused: watch::Sender::new(false),
+    });
+
+    let sender = AppSender {
+        sender,
+        capacities,
+        sync_backpressure,
+        metrics,
+    };
+
+    (sender, receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use malachitebft_test::{Address, Height, TestContext, Value};
+    use tokio::sync::oneshot;
+
+    use crate::app::types::core::Round;
+
+    fn get_history_min_height() -> (AppMsg<TestContext>, oneshot::Receiver<Height>) {
+        let (reply, rx) = oneshot::channel();
+        (AppMsg::GetHistoryMinHeight { reply }, rx)
+    }
+
+    #[tokio::test]
+    async fn sync_messages_overflow_at_capacity() {
+        let config = ChannelConfig {
+            sync: 2,
+            ..Default::default()
+        };
+
+        let metrics = ChannelMetrics::default();
+        let (sender, mut receiver) = app_channel::<TestContext>(config, metrics.clone());
+
+        let first = sender.send(get_history_min_height().0).await.unwrap();
+        let _second = sender.send(get_history_min_height().0).await.unwrap();
+
+        assert_eq!(
+            sender.send(get_history_min_height().0).await.unwrap_err(),
+            BackpressureError::Full(MessageClass::Sync)
+        );
+
+        let labels = ClassLabels {
+            class: MessageClass::Sync,
+        };
+        assert_eq!(metrics.full.get_or_create(&labels).get(), 1);
+        assert_eq!(metrics.overflow.get_or_create(&labels).get(), 1);
+
+        // Capacity is released once the application has replied
+        assert!(receiver.recv().await.is_some());
+        drop(first);
+        assert!(sender.send(get_history_min_height().0).await.is_ok());
+
+        // Other classes are unaffected
+        let (reply, _rx) = oneshot::channel();
+        assert!(sender.send(AppMsg::ConsensusReady { reply }).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn proposal_parts_wait_at_capacity() {
+        let config = ChannelConfig {
+            proposals: 1,
+            ..Default::default()
+        };
+
+        let metrics = ChannelMetrics::default();
+        let (sender, mut receiver) = app_channel::<TestContext>(config, metrics.clone());
+
+        let (reply, _rx) = oneshot::channel();
+        let first = sender
+            .send(AppMsg::ReceivedProposal {
+                height: Height::new(1),
+                round: Round::new(0),
+                valid_round: Round::Nil,
+                proposer: Address::new([0; 20]),
+                value: Value::new(1),
+                reply,
+            })
+            .await
+            .unwrap();
+
+        let (reply, _rx) = oneshot::channel();
+        let second = sender.send(AppMsg::ReceivedProposal {
+            height: Height::new(1),
+            round: Round::new(0),
+            valid_round: Round::Nil,
+            proposer: Address::new([0; 20]),
+            value: Value::new(2),
+            reply,
+        });
+        tokio::pin!(second);
+
+        // The second proposal waits for the application to reply to the first one
+        assert!(tokio::time::timeout(Duration::from_millis(50), &mut second)
+            .await
+            .is_err());
+
+        assert!(receiver.recv().await.is_some());
+        drop(first);
+        assert!(second.await.is_ok());
+
+        let labels = ClassLabels {
+            class: MessageClass::Proposals,
+        };
+        assert_eq!(metrics.full.get_or_create(&labels).get(), 1);
+        assert_eq!(metrics.overflow.get_or_create(&labels).get(), 0);
+    }
+
+    #[tokio::test]
+    async fn sync_is_paused_while_the_application_falls_behind() {
+        let config = ChannelConfig {
+            sync: 8,
+            sync_pause_threshold: Some(4),
+            ..Default::default()
+        };
+
+        let (sender, _receiver) = app_channel::<TestContext>(config, ChannelMetrics::default());
+        let paused = sender.sync_paused();
+
+        let mut permits = Vec::new();
+        for _ in 0..3 {
+            permits.push(sender.send(get_history_min_height().0).await.unwrap());
+        }
+        assert!(!*paused.borrow());
+
+        permits.push(sender.send(get_history_min_height().0).await.unwrap());
+        assert!(*paused.borrow());
+
+        // Sync is resumed once the application caught up with half of the pending messages
+        permits.pop();
+        assert!(*paused.borrow());
+        permits.pop();
+        assert!(!*paused.borrow());
+
+        // Other classes do not count towards the threshold
+        for _ in 0..4 {
+            let (reply, _rx) = oneshot::channel();
+            permits.push(sender.send(AppMsg::ConsensusReady { reply }).await.unwrap());
+        }
+        assert!(!*paused.borrow());
+    }
+
+    #[tokio::test]
+    async fn closed_channel() {
+        let (sender, receiver) =
+            app_channel::<TestContext>(ChannelConfig::default(), ChannelMetrics::default());
+
+        drop(receiver);
+
+        assert_eq!(
+            sender.send(get_history_min_height().0).await.unwrap_err(),
+            BackpressureError::Closed
+        );
+    }
+}
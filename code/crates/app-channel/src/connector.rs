@@ -1,15 +1,16 @@
 //! Implementation of a host actor for bridiging consensus and the application via a set of channels.
 
-use ractor::{async_trait, Actor, ActorProcessingErr, ActorRef, SpawnErr};
-use tokio::sync::mpsc;
+use ractor::{async_trait, Actor, ActorProcessingErr, ActorRef, RpcReplyPort, SpawnErr};
 use tokio::sync::oneshot;
-use tracing::error;
+use tracing::{error, warn};
 
-use malachitebft_engine::host::HostMsg;
+use malachitebft_engine::host::{HostError, HostMsg, Next};
 
 use crate::app::metrics::Metrics;
 use crate::app::types::core::Context;
+use crate::channel::{AppSender, BackpressureError, Permit};
 use crate::msgs::AppMsg;
+use crate::notifications::TxNotification;
 
 /// Actor for bridging consensus and the application via a set of channels.
 ///
@@ -19,7 +20,8 @@ pub struct Connector<Ctx>
 where
     Ctx: Context,
 {
-    sender: mpsc::Sender<AppMsg<Ctx>>,
+    sender: AppSender<Ctx>,
+    notifications: TxNotification<Ctx>,
 
     // TODO: add some metrics
     #[allow(dead_code)]
@@ -30,18 +32,28 @@ impl<Ctx> Connector<Ctx>
 where
     Ctx: Context,
 {
-    pub fn new(sender: mpsc::Sender<AppMsg<Ctx>>, metrics: Metrics) -> Self {
-        Connector { sender, metrics }
+    pub fn new(
+        sender: AppSender<Ctx>,
+        notifications: TxNotification<Ctx>,
+        metrics: Metrics,
+    ) -> Self {
+        Connector {
+            sender,
+            notifications,
+            metrics,
+        }
     }
 
     pub async fn spawn(
-        sender: mpsc::Sender<AppMsg<Ctx>>,
+        sender: AppSender<Ctx>,
+        notifications: TxNotification<Ctx>,
         metrics: Metrics,
     ) -> Result<ActorRef<HostMsg<Ctx>>, SpawnErr>
     where
         Ctx: Context,
     {
-        let (actor_ref, _) = Actor::spawn(None, Self::new(sender, metrics), ()).await?;
+        let (actor_ref, _) =
+            Actor::spawn(None, Self::new(sender, notifications, metrics), ()).await?;
         Ok(actor_ref)
     }
 }
@@ -59,10 +71,23 @@ where
         match msg {
             HostMsg::ConsensusReady { reply_to } => {
                 let (reply, rx) = oneshot::channel();
-                self.sender.send(AppMsg::ConsensusReady { reply }).await?;
+                let permit = self.sender.send(AppMsg::ConsensusReady { reply }).await?;
+                let notifications = self.notifications.clone();
+
+                tokio::spawn(async move {
+                    let _permit = permit;
 
-                let (start_height, updates) = rx.await?;
-                reply_to.send((start_height, updates))?;
+                    match rx.await {
+                        Ok((height, params)) => {
+                            notifications.started_height(height, &params.validator_set);
+
+                            if let Err(e) = reply_to.send((height, params)) {
+                                error!("ConsensusReady: connector failed to send reply: {e}");
+                            }
+                        }
+                        Err(_) => error!("ConsensusReady: application dropped the reply channel"),
+                    }
+                });
             }
 
             HostMsg::StartedRound {
@@ -74,7 +99,8 @@ where
             } => {
                 let (reply_value, rx) = oneshot::channel();
 
-                self.sender
+                let permit = self
+                    .sender
                     .send(AppMsg::StartedRound {
                         height,
                         round,
@@ -84,7 +110,7 @@ where
                     })
                     .await?;
 
-                reply_to.send(rx.await?)?;
+                forward_reply("StartedRound", permit, rx, reply_to);
             }
 
             HostMsg::GetValue {
@@ -95,7 +121,8 @@ where
             } => {
                 let (reply, rx) = oneshot::channel();
 
-                self.sender
+                let permit = self
+                    .sender
                     .send(AppMsg::GetValue {
                         height,
                         round,
@@ -104,7 +131,13 @@ where
                     })
                     .await?;
 
-                reply_to.send(rx.await?)?;
+                forward_reply("GetValue", permit, rx, reply_to);
+            }
+
+            HostMsg::CancelGetValue { height, round } => {
+                self.sender
+                    .send(AppMsg::CancelGetValue { height, round })
+                    .await?;
             }
 
             HostMsg::ExtendVote {
@@ -115,7 +148,8 @@ where
             } => {
                 let (reply, rx) = oneshot::channel();
 
-                self.sender
+                let permit = self
+                    .sender
                     .send(AppMsg::ExtendVote {
                         height,
                         round,
@@ -124,7 +158,7 @@ where
                     })
                     .await?;
 
-                reply_to.send(rx.await?)?;
+                forward_reply("ExtendVote", permit, rx, reply_to);
             }
 
             HostMsg::VerifyVoteExtension {
@@ -136,7 +170,8 @@ where
             } => {
                 let (reply, rx) = oneshot::channel();
 
-                self.sender
+                let permit = self
+                    .sender
                     .send(AppMsg::VerifyVoteExtension {
                         height,
                         round,
@@ -146,7 +181,7 @@ where
                     })
                     .await?;
 
-                reply_to.send(rx.await?)?;
+                forward_reply("VerifyVoteExtension", permit, rx, reply_to);
             }
 
             HostMsg::RestreamValue {
@@ -164,17 +199,72 @@ where
                         address,
                         value_id,
                     })
-                    .await?
+                    .await?;
             }
 
-            HostMsg::GetHistoryMinHeight { reply_to } => {
+            HostMsg::GetTimeoutOverrides {
+                height,
+                round,
+                reply_to,
+            } => {
+                let (reply, rx) = oneshot::channel();
+
+                let permit = self
+                    .sender
+                    .send(AppMsg::GetTimeoutOverrides {
+                        height,
+                        round,
+                        reply,
+                    })
+                    .await?;
+
+                forward_reply("GetTimeoutOverrides", permit, rx, reply_to);
+            }
+
+            HostMsg::GetConsensusParams { height, reply_to } => {
                 let (reply, rx) = oneshot::channel();
 
+                let permit = self
+                    .sender
+                    .send(AppMsg::GetConsensusParams { height, reply })
+                    .await?;
+
+                forward_reply("GetConsensusParams", permit, rx, reply_to);
+            }
+
+            HostMsg::RoundAlert {
+                height,
+                round,
+                halted,
+            } => {
                 self.sender
-                    .send(AppMsg::GetHistoryMinHeight { reply })
+                    .send(AppMsg::RoundAlert {
+                        height,
+                        round,
+                        halted,
+                    })
                     .await?;
+            }
 
-                reply_to.send(rx.await?)?;
+            HostMsg::GetHistoryMinHeight { reply_to } => {
+                let (reply, rx) = oneshot::channel();
+
+                self.send_or_reject(
+                    "GetHistoryMinHeight",
+                    AppMsg::GetHistoryMinHeight { reply },
+                    rx,
+                    reply_to,
+                )
+                .await?;
+            }
+
+            HostMsg::Prune { retain_height } => {
+                match self.sender.send(AppMsg::Prune { retain_height }).await {
+                    Ok(_) => {}
+                    // The values are pruned the next time the height moves up
+                    Err(e @ BackpressureError::Full(_)) => warn!("Prune: {e}, dropping it"),
+                    Err(e) => return Err(e.into()),
+                }
             }
 
             HostMsg::ReceivedProposalPart {
@@ -184,13 +274,46 @@ where
             } => {
                 let (reply, rx) = oneshot::channel();
 
-                self.sender
+                let permit = self
+                    .sender
                     .send(AppMsg::ReceivedProposalPart { from, part, reply })
                     .await?;
 
-                if let Some(value) = rx.await? {
-                    reply_to.send(value)?;
-                }
+                tokio::spawn(async move {
+                    let _permit = permit;
+
+                    // The application only replies once the proposal is complete
+                    if let Ok(Some(value)) = rx.await {
+                        if let Err(e) = reply_to.send(value) {
+                            error!("ReceivedProposalPart: connector failed to send reply: {e}");
+                        }
+                    }
+                });
+            }
+
+            HostMsg::ReceivedProposal {
+                height,
+                round,
+                valid_round,
+                proposer,
+                value,
+                reply_to,
+            } => {
+                let (reply, rx) = oneshot::channel();
+
+                let permit = self
+                    .sender
+                    .send(AppMsg::ReceivedProposal {
+                        height,
+                        round,
+                        valid_round,
+                        proposer,
+                        value,
+                        reply,
+                    })
+                    .await?;
+
+                forward_reply("ReceivedProposal", permit, rx, reply_to);
             }
 
             HostMsg::Decided {
@@ -200,17 +323,23 @@ where
             } => {
                 let (reply, rx) = oneshot::channel();
 
-                self.sender
+                let permit = self
+                    .sender
                     .send(AppMsg::Decided {
-                        certificate,
+                        certificate: certificate.clone(),
                         extensions,
                         reply,
                     })
                     .await?;
 
-                // Do not block processing of other messages while the app commits the decision
+                let notifications = self.notifications.clone();
+
                 tokio::spawn(async move {
+                    let _permit = permit;
+
                     if let Ok(()) = rx.await {
+                        notifications.decided(&certificate);
+
                         if let Err(e) = reply_to.send(()) {
                             error!("Decided: connector failed to send ack: {e}");
                         }
@@ -226,7 +355,8 @@ where
             } => {
                 let (reply, rx) = oneshot::channel();
 
-                self.sender
+                let permit = self
+                    .sender
                     .send(AppMsg::Finalized {
                         certificate,
                         extensions,
@@ -235,9 +365,15 @@ where
                     })
                     .await?;
 
-                // Do not block processing of other messages while waiting for the next height
+                let notifications = self.notifications.clone();
+
                 tokio::spawn(async move {
+                    let _permit = permit;
+
                     if let Ok(next) = rx.await {
+                        let (Next::Start(height, params) | Next::Restart(height, params)) = &next;
+                        notifications.started_height(*height, &params.validator_set);
+
                         if let Err(e) = reply_to.send(next) {
                             error!("Finalized: connector failed to send StartHeight: {e}");
                         }
@@ -248,11 +384,13 @@ where
             HostMsg::GetDecidedValues { range, reply_to } => {
                 let (reply, rx) = oneshot::channel();
 
-                self.sender
-                    .send(AppMsg::GetDecidedValues { range, reply })
-                    .await?;
-
-                reply_to.send(rx.await?)?;
+                self.send_or_reject(
+                    "GetDecidedValues",
+                    AppMsg::GetDecidedValues { range, reply },
+                    rx,
+                    reply_to,
+                )
+                .await?;
             }
 
             HostMsg::ProcessSyncedValue {
@@ -264,22 +402,123 @@ where
             } => {
                 let (reply, rx) = oneshot::channel();
 
-                self.sender
-                    .send(AppMsg::ProcessSyncedValue {
+                self.send_or_reject(
+                    "ProcessSyncedValue",
+                    AppMsg::ProcessSyncedValue {
                         height,
                         round,
                         proposer,
                         value_bytes,
                         reply,
-                    })
-                    .await?;
+                    },
+                    rx,
+                    reply_to,
+                )
+                .await?;
+            }
 
-                reply_to.send(rx.await?)?;
+            HostMsg::ProcessSyncedValues { values, reply_to } => {
+                let (reply, rx) = oneshot::channel();
+
+                self.send_or_reject(
+                    "ProcessSyncedValues",
+                    AppMsg::ProcessSyncedValues { values, reply },
+                    rx,
+                    reply_to,
+                )
+                .await?;
+            }
+
+            HostMsg::ProcessBackfilledValues { values, reply_to } => {
+                let (reply, rx) = oneshot::channel();
+
+                self.send_or_reject(
+                    "ProcessBackfilledValues",
+                    AppMsg::ProcessBackfilledValues { values, reply },
+                    rx,
+                    reply_to,
+                )
+                .await?;
             }
         };
 
         Ok(())
     }
+
+    /// Send a message which the application may reject when it has too many pending messages
+    /// of its class, and forward the reply of the application to consensus once it is available.
+    ///
+    /// If the message is rejected, consensus is replied to with [`HostError::Backpressure`]
+    /// right away, instead of waiting for a reply which would never come.
+    async fn send_or_reject<T>(
+        &self,
+        name: &'static str,
+        msg: AppMsg<Ctx>,
+        rx: oneshot::Receiver<T>,
+        reply_to: RpcReplyPort<Result<T, HostError>>,
+    ) -> Result<(), BackpressureError>
+    where
+        T: Send + 'static,
+    {
+        match self.sender.send(msg).await {
+            Ok(permit) => {
+                tokio::spawn(async move {
+                    let _permit = permit;
+
+                    match rx.await {
+                        Ok(value) => {
+                            if let Err(e) = reply_to.send(Ok(value)) {
+                                error!("{name}: connector failed to send reply: {e}");
+                            }
+                        }
+                        Err(_) => error!("{name}: application dropped the reply channel"),
+                    }
+                });
+
+                Ok(())
+            }
+
+            Err(e @ BackpressureError::Full(_)) => {
+                warn!("{name}: {e}, rejecting it");
+
+                if let Err(e) = reply_to.send(Err(HostError::Backpressure)) {
+                    error!("{name}: connector failed to send rejection: {e}");
+                }
+
+                Ok(())
+            }
+
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Forward the reply of the application to consensus once it is available,
+/// holding on to the permit of the message until then.
+///
+/// This does not block the processing of the next messages while the application
+/// handles this one, the capacity of each message class bounding the number of
+/// messages pending in the application instead.
+fn forward_reply<T>(
+    name: &'static str,
+    permit: Permit,
+    rx: oneshot::Receiver<T>,
+    reply_to: RpcReplyPort<T>,
+) where
+    T: Send + 'static,
+{
+    tokio::spawn(async move {
+        let _permit = permit;
+
+        match rx.await {
+            Ok(value) => {
+                if let Err(e) = reply_to.send(value) {
+                    error!("{name}: connector failed to send reply: {e}");
+                }
+            }
+            Err(_) => error!("{name}: application dropped the reply channel"),
+        }
+    });
 }
 
 #[async_trait]
@@ -16,12 +16,23 @@ mod builder;
 mod connector;
 pub mod spawn;
 
+mod channel;
+pub use channel::{BackpressureError, ChannelConfig, MessageClass};
+
 mod msgs;
 pub use msgs::{
     AppMsg, Channels, ConsensusMsg, ConsensusRequest, ConsensusRequestError, NetworkMsg,
     NetworkRequest, Reply,
 };
 
+mod notifications;
+pub use notifications::{
+    Notification, RxNotification, TxNotification, DEFAULT_NOTIFICATIONS_CAPACITY,
+};
+
+mod peers;
+pub use peers::{PeerEvent, RxPeerEvent};
+
 mod run;
 pub use run::*;
 
@@ -1,4 +1,6 @@
+use std::io;
 use std::ops::RangeInclusive;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use bytes::Bytes;
@@ -14,17 +16,22 @@ use malachitebft_app::types::core::ValueOrigin;
 use malachitebft_app::types::MisbehaviorEvidence;
 use malachitebft_engine::consensus::state_dump::StateDump;
 use malachitebft_engine::consensus::Msg as ConsensusActorMsg;
-use malachitebft_engine::host::{HeightParams, Next};
+use malachitebft_engine::host::{HeightParams, Next, SyncedValueOutcome};
 use malachitebft_engine::network::Msg as NetworkActorMsg;
 use malachitebft_engine::network::{
-    Multiaddr, NetworkStateDump, PersistentPeerError, PersistentPeersOp,
+    DiscoveryStats, Multiaddr, NetworkStateDump, PersistentPeerError, PersistentPeersOp,
 };
 use malachitebft_engine::util::events::TxEvent;
 
-use crate::app::types::core::{CommitCertificate, Context, Round, ValueId, VoteExtensions};
+use crate::app::types::core::{
+    CommitCertificate, Context, HeightParamsOverride, Round, RoundTimeoutsOverride, ValueId,
+    VoteExtensions,
+};
 use crate::app::types::streaming::StreamMessage;
 use crate::app::types::sync::RawDecidedValue;
 use crate::app::types::{LocallyProposedValue, PeerId, ProposedValue};
+use crate::channel::MessageClass;
+use crate::notifications::TxNotification;
 
 pub type Reply<T> = oneshot::Sender<T>;
 
@@ -102,6 +109,9 @@ impl From<oneshot::error::RecvError> for ConsensusRequestError {
 pub enum ConsensusRequest<Ctx: Context> {
     /// Request a state dump from consensus
     DumpState(Reply<Option<StateDump<Ctx>>>),
+
+    /// Request consensus to dump the trace of its flight recorder to the given file
+    DumpTrace(PathBuf, Reply<io::Result<()>>),
 }
 
 impl<Ctx: Context> ConsensusRequest<Ctx> {
@@ -123,12 +133,35 @@ impl<Ctx: Context> ConsensusRequest<Ctx> {
 
         Ok(dump)
     }
+
+    /// Request consensus to dump the trace of its flight recorder to the given file.
+    ///
+    /// The inner result is an error if the flight recorder is disabled
+    /// or if the file could not be written.
+    pub async fn dump_trace(
+        tx_request: &mpsc::Sender<ConsensusRequest<Ctx>>,
+        path: PathBuf,
+    ) -> Result<io::Result<()>, ConsensusRequestError> {
+        let (tx, rx) = oneshot::channel();
+
+        tx_request
+            .try_send(Self::DumpTrace(path, tx))
+            .inspect_err(|e| error!("Failed to send DumpTrace request to consensus: {e}"))?;
+
+        let result = rx
+            .await
+            .inspect_err(|e| error!("Failed to receive DumpTrace response from consensus: {e}"))?;
+
+        Ok(result)
+    }
 }
 
 /// Represents requests that can be sent to the network layer by the application.
 pub enum NetworkRequest {
     /// Request a state dump from the network
     DumpState(Reply<Option<NetworkStateDump>>),
+    /// Request a snapshot of the discovery state
+    GetDiscoveryState(Reply<Option<DiscoveryStats>>),
     /// Add or remove a persistent peer at runtime
     UpdatePersistentPeers(PersistentPeersOp, Reply<Result<(), PersistentPeerError>>),
 }
@@ -151,6 +184,25 @@ impl NetworkRequest {
         Ok(dump)
     }
 
+    /// Request a snapshot of the discovery state.
+    pub async fn discovery_state(
+        tx_request: &mpsc::Sender<NetworkRequest>,
+    ) -> Result<Option<DiscoveryStats>, ConsensusRequestError> {
+        let (tx, rx) = oneshot::channel();
+
+        tx_request
+            .try_send(Self::GetDiscoveryState(tx))
+            .inspect_err(
+                |error| error!(%error, "Failed to send GetDiscoveryState request to network"),
+            )?;
+
+        let stats = rx.await.inspect_err(
+            |error| error!(%error, "Failed to receive GetDiscoveryState response from network"),
+        )?;
+
+        Ok(stats)
+    }
+
     /// Add a persistent peer at runtime.
     pub async fn add_persistent_peer(
         tx_request: &mpsc::Sender<NetworkRequest>,
@@ -204,8 +256,12 @@ pub struct Channels<Ctx: Context> {
     pub consensus: mpsc::Receiver<AppMsg<Ctx>>,
     /// Channel for sending messages to the networking layer
     pub network: mpsc::Sender<NetworkMsg<Ctx>>,
-    /// Receiver of events, call `subscribe` to receive them
+    /// Receiver of events, call `subscribe` to receive them,
+    /// or [`RxPeerEvent::subscribe`](crate::RxPeerEvent::subscribe) for the peer events only
     pub events: TxEvent<Ctx>,
+    /// Sender of notifications of the decisions and validator set changes,
+    /// call `subscribe` to receive them
+    pub notifications: TxNotification<Ctx>,
     /// Channel for sending requests to consensus
     pub requests: mpsc::Sender<ConsensusRequest<Ctx>>,
     /// Channel for sending requests to the network
@@ -256,6 +312,19 @@ pub enum AppMsg<Ctx: Context> {
         reply: Reply<LocallyProposedValue<Ctx>>,
     },
 
+    /// Notifies the application that the propose timeout elapsed before it replied
+    /// to the [`AppMsg::GetValue`] request for the given height and round.
+    ///
+    /// Only sent when `cancel_get_value` is enabled in the consensus configuration.
+    /// The application SHOULD abort building the value, eg. stop executing transactions,
+    /// as consensus has moved on without it. Replying to the request afterwards is harmless.
+    CancelGetValue {
+        /// Height of the cancelled request
+        height: Ctx::Height,
+        /// Round of the cancelled request
+        round: Round,
+    },
+
     /// ExtendVote allows the application to extend the pre-commit vote with arbitrary data.
     ///
     /// When consensus is preparing to send a pre-commit vote, it first calls `ExtendVote`.
@@ -304,11 +373,75 @@ pub enum AppMsg<Ctx: Context> {
         value_id: ValueId<Ctx>,
     },
 
+    /// Asks the application whether it wants to override the durations of the timeouts of a round.
+    ///
+    /// Only sent when `timeout_overrides` is enabled in the consensus configuration,
+    /// once at the start of every round, right after [`AppMsg::StartedRound`].
+    /// This allows the application to give more time to a proposer it knows to be slow,
+    /// for instance because it is doing heavy block building.
+    ///
+    /// The application MUST reply immediately with the durations to use for the timeouts
+    /// it overrides, leaving the others to `None` to use the durations computed from the timeouts
+    /// of the current height. If it does not reply in time, no timeout is overridden for the round.
+    GetTimeoutOverrides {
+        /// Height of the round
+        height: Ctx::Height,
+        /// Round whose timeouts may be overridden
+        round: Round,
+        /// Channel for sending back the durations to use for the overridden timeouts
+        reply: Reply<RoundTimeoutsOverride>,
+    },
+
+    /// Asks the application whether it wants to override the consensus parameters of a height.
+    ///
+    /// Only sent when `consensus_params_overrides` is enabled in the consensus configuration,
+    /// once right before consensus starts or restarts a height. This allows the application
+    /// to change the timeouts, the voting thresholds or the value payload mode at a given height,
+    /// for instance as part of a coordinated network upgrade.
+    ///
+    /// The application MUST reply immediately, and MUST reply with the same overrides
+    /// on every node and every time it is asked about a given height, including after a restart,
+    /// since the overrides are not persisted in the WAL. Parameters left unset in the reply
+    /// keep the values from the consensus configuration.
+    GetConsensusParams {
+        /// Height about to be started
+        height: Ctx::Height,
+        /// Channel for sending back the parameters to override for that height, if any
+        reply: Reply<HeightParamsOverride<Ctx>>,
+    },
+
+    /// Notifies the application that the current height went through too many rounds without deciding.
+    ///
+    /// Only sent when `notify_round_alerts` is enabled in the consensus configuration,
+    /// once when the height reaches the round set by `max_rounds_alert`, and once when it
+    /// reaches the round set by `max_rounds_halt`, after which consensus stops proposing
+    /// and voting until it moves on to the next height. The application may then restart
+    /// the height with [`ConsensusMsg::RestartHeight`] once the operator has intervened.
+    RoundAlert {
+        /// Height which is not deciding
+        height: Ctx::Height,
+        /// Round reached by the height
+        round: Round,
+        /// Whether participation in consensus is halted
+        halted: bool,
+    },
+
     /// Requests the earliest height available in the history maintained by the application.
     ///
     /// The application MUST respond with its earliest available height.
     GetHistoryMinHeight { reply: Reply<Ctx::Height> },
 
+    /// Notifies the application that it can prune the decided values below the given height.
+    ///
+    /// Only sent when retention is enabled in the sync configuration, whenever the height moves up.
+    /// The height accounts for the values still needed by peers which are behind, so that they can
+    /// catch up via sync, up to the maximum number of values to retain for them.
+    /// The application MAY keep more values, eg. to serve peers which are further behind.
+    Prune {
+        /// Decided values at this height and above must be retained
+        retain_height: Ctx::Height,
+    },
+
     /// Notifies the application that consensus has received a proposal part over the network.
     ///
     /// If this part completes the full proposal, the application MUST respond
@@ -322,12 +455,39 @@ pub enum AppMsg<Ctx: Context> {
         reply: Reply<Option<ProposedValue<Ctx>>>,
     },
 
+    /// Notifies the application that consensus has received a proposal carrying a value it has not seen yet.
+    ///
+    /// Only sent when consensus runs in `ProposalOnly` mode, where the proposal
+    /// carries the full value and no proposal parts are streamed.
+    ///
+    /// The application MUST validate the value and respond with the proposed value and its validity.
+    ReceivedProposal {
+        /// Height of the proposal
+        height: Ctx::Height,
+        /// Round of the proposal
+        round: Round,
+        /// Round at which the proposal was locked on
+        valid_round: Round,
+        /// Address of the proposer
+        proposer: Ctx::Address,
+        /// The proposed value
+        value: Ctx::Value,
+        /// Channel for returning the proposed value together with its validity
+        reply: Reply<ProposedValue<Ctx>>,
+    },
+
     /// Notifies the application that consensus has decided on a value.
     ///
     /// This message includes a commit certificate containing the ID of
     /// the value that was decided on, the height and round at which it was decided,
     /// and the aggregated signatures of the validators that committed to it.
-    /// It also includes to the vote extensions received for that height.
+    /// It also includes the vote extensions received for that height, together with
+    /// the address of the validator which sent them and their signature.
+    ///
+    /// When `require_vote_extensions` is enabled in the consensus configuration, validators
+    /// holding more than 2/3 of the voting power are guaranteed to have provided a vote extension,
+    /// unless the value was decided through sync, in which case no vote extensions are included.
+    /// See [`VoteExtensions::voting_power`].
     ///
     /// The application MUST commit the decision and then reply to
     /// acknowledge that the commit is complete. The sync actor will only be notified
@@ -387,8 +547,12 @@ pub enum AppMsg<Ctx: Context> {
     /// Notifies the application that a value has been synced from the network.
     /// This may happen when the node is catching up with the network.
     ///
-    /// If a value can be decoded from the bytes provided, then the application MUST reply
-    /// to this message with the decoded value. Otherwise, it MUST reply with `None`.
+    /// If a value can be decoded from the bytes provided, then the application MUST validate it
+    /// as it would validate a value proposed during consensus, and reply to this message with
+    /// the outcome of that validation. Otherwise, it MUST reply with `None`.
+    ///
+    /// Values reported as [`SyncedValueOutcome::Invalid`] are rejected, and the peer
+    /// which sent them is penalized.
     ProcessSyncedValue {
         /// Height of the synced value
         height: Ctx::Height,
@@ -398,10 +562,76 @@ pub enum AppMsg<Ctx: Context> {
         proposer: Ctx::Address,
         /// Raw encoded value data
         value_bytes: Bytes,
-        /// Channel for sending back the proposed value, if successfully decoded
+        /// Channel for sending back the outcome of the validation of the value, if successfully decoded
         /// or `None` if the value could not be decoded
-        reply: Reply<Option<ProposedValue<Ctx>>>,
+        reply: Reply<Option<SyncedValueOutcome<Ctx>>>,
+    },
+
+    /// Notifies the application that a batch of values has been synced from the network,
+    /// when `batch_synced_values` is enabled in the value sync configuration.
+    ///
+    /// The values are ordered by ascending height, starting at or above the current height.
+    /// The application MUST process each value as it would for [`AppMsg::ProcessSyncedValue`],
+    /// and MAY persist the whole batch at once, eg. in a single transaction. It MUST reply with
+    /// one outcome per value, in the same order, or `None` for the values which could not be decoded.
+    ///
+    /// The proposers of the values are not provided, as they are not known ahead of consensus,
+    /// and MUST be determined by the application from the validator set of each height.
+    /// Outcomes with another proposer than the one of the round are discarded, and the value is
+    /// then processed again with [`AppMsg::ProcessSyncedValue`].
+    ProcessSyncedValues {
+        /// Synced values, with their commit certificates
+        values: Vec<RawDecidedValue<Ctx>>,
+        /// Channel for sending back the outcome of processing each value
+        reply: Reply<Vec<Option<SyncedValueOutcome<Ctx>>>>,
     },
+
+    /// Notifies the application that historical values have been backfilled from the network,
+    /// for heights below its earliest available height.
+    ///
+    /// The values are ordered by ascending height, and the last one is at the height right below
+    /// the earliest height available in the application's storage.
+    /// The application MUST verify the commit certificate of each value, and that the value
+    /// matches the certificate, before storing them. It MUST reply with `true` if all values
+    /// were verified and stored, or `false` otherwise, in which case the peer which sent them
+    /// is penalized.
+    ProcessBackfilledValues {
+        /// Backfilled values, with their commit certificates
+        values: Vec<RawDecidedValue<Ctx>>,
+        /// Channel for sending back whether the values were verified and stored
+        reply: Reply<bool>,
+    },
+}
+
+impl<Ctx: Context> AppMsg<Ctx> {
+    /// The class of this message, which determines the capacity available to it.
+    pub fn class(&self) -> MessageClass {
+        match self {
+            AppMsg::ReceivedProposalPart { .. } | AppMsg::ReceivedProposal { .. } => {
+                MessageClass::Proposals
+            }
+
+            AppMsg::GetHistoryMinHeight { .. }
+            | AppMsg::Prune { .. }
+            | AppMsg::GetDecidedValues { .. }
+            | AppMsg::ProcessSyncedValue { .. }
+            | AppMsg::ProcessSyncedValues { .. }
+            | AppMsg::ProcessBackfilledValues { .. } => MessageClass::Sync,
+
+            AppMsg::ConsensusReady { .. }
+            | AppMsg::StartedRound { .. }
+            | AppMsg::GetValue { .. }
+            | AppMsg::CancelGetValue { .. }
+            | AppMsg::ExtendVote { .. }
+            | AppMsg::VerifyVoteExtension { .. }
+            | AppMsg::RestreamProposal { .. }
+            | AppMsg::GetTimeoutOverrides { .. }
+            | AppMsg::GetConsensusParams { .. }
+            | AppMsg::RoundAlert { .. }
+            | AppMsg::Decided { .. }
+            | AppMsg::Finalized { .. } => MessageClass::Consensus,
+        }
+    }
 }
 
 /// Messages sent from the application to consensus.
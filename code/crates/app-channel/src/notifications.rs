@@ -0,0 +1,136 @@
+//! Notifications of the decisions of consensus, for components running alongside the application.
+//!
+//! Unlike [`AppMsg`](crate::AppMsg), which the application must handle and reply to,
+//! notifications are broadcast once the application has processed the corresponding message,
+//! and are never waited upon. Subscribers which fall behind miss the oldest notifications,
+//! as reported by [`broadcast::error::RecvError::Lagged`], instead of slowing down consensus.
+
+use std::sync::{Arc, Mutex};
+
+use derive_where::derive_where;
+use tokio::sync::broadcast;
+
+use crate::app::types::core::{CommitCertificate, Context};
+
+/// Default number of notifications retained for the subscribers which fall behind.
+pub const DEFAULT_NOTIFICATIONS_CAPACITY: usize = 128;
+
+pub type RxNotification<Ctx> = broadcast::Receiver<Notification<Ctx>>;
+
+/// Notifications broadcast to the subscribers of [`TxNotification`].
+#[derive_where(Clone, Debug)]
+pub enum Notification<Ctx: Context> {
+    /// A value was decided, and the application acknowledged the decision.
+    Decided(CommitCertificate<Ctx>),
+
+    /// Consensus is starting a height with a different validator set than the previous height.
+    ///
+    /// This is also sent for the first height started by consensus.
+    ValidatorSetChanged {
+        height: Ctx::Height,
+        validator_set: Ctx::ValidatorSet,
+    },
+}
+
+/// Sender of the notifications, call `subscribe` to receive them.
+#[derive_where(Clone)]
+pub struct TxNotification<Ctx: Context> {
+    tx: broadcast::Sender<Notification<Ctx>>,
+
+    /// Validator set of the last height started by consensus
+    validator_set: Arc<Mutex<Option<Ctx::ValidatorSet>>>,
+}
+
+impl<Ctx: Context> TxNotification<Ctx> {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity.max(1));
+
+        Self {
+            tx,
+            validator_set: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn subscribe(&self) -> RxNotification<Ctx> {
+        self.tx.subscribe()
+    }
+
+    /// Notify the subscribers that the value certified by the given certificate was decided.
+    pub(crate) fn decided(&self, certificate: &CommitCertificate<Ctx>) {
+        if self.tx.receiver_count() > 0 {
+            let _ = self.tx.send(Notification::Decided(certificate.clone()));
+        }
+    }
+
+    /// Notify the subscribers if the validator set of the height being started
+    /// differs from the one of the previous height.
+    pub(crate) fn started_height(&self, height: Ctx::Height, validator_set: &Ctx::ValidatorSet) {
+        let mut last = self
+            .validator_set
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if last.as_ref() == Some(validator_set) {
+            return;
+        }
+
+        *last = Some(validator_set.clone());
+
+        if self.tx.receiver_count() > 0 {
+            let _ = self.tx.send(Notification::ValidatorSetChanged {
+                height,
+                validator_set: validator_set.clone(),
+            });
+        }
+    }
+}
+
+impl<Ctx: Context> Default for TxNotification<Ctx> {
+    fn default() -> Self {
+        Self::new(DEFAULT_NOTIFICATIONS_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use malachitebft_test::{Height, PrivateKey, TestContext, Validator, ValidatorSet};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    fn validator_set(rng: &mut StdRng, size: usize) -> ValidatorSet {
+        ValidatorSet::new(
+            (0..size).map(|_| Validator::new(PrivateKey::generate(&mut *rng).public_key(), 1)),
+        )
+    }
+
+    #[test]
+    fn only_validator_set_changes_are_notified() {
+        let mut rng = StdRng::seed_from_u64(0x42);
+        let (vs1, vs2) = (validator_set(&mut rng, 3), validator_set(&mut rng, 4));
+
+        let tx = TxNotification::<TestContext>::default();
+        let mut rx = tx.subscribe();
+
+        tx.started_height(Height::new(1), &vs1);
+        tx.started_height(Height::new(2), &vs1);
+        tx.started_height(Height::new(3), &vs2);
+
+        for (height, vs) in [(1, &vs1), (3, &vs2)] {
+            match rx.try_recv().unwrap() {
+                Notification::ValidatorSetChanged {
+                    height: h,
+                    validator_set,
+                } => {
+                    assert_eq!(h, Height::new(height));
+                    assert_eq!(&validator_set, vs);
+                }
+                other => panic!("unexpected notification: {other:?}"),
+            }
+        }
+
+        assert_eq!(rx.try_recv().unwrap_err(), TryRecvError::Empty);
+    }
+}
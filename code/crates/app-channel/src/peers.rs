@@ -0,0 +1,105 @@
+//! Connection lifecycle of the peers of the node, for applications which want to know
+//! which validators are currently reachable.
+//!
+//! Like [`Notification`](crate::Notification)s, peer events are never waited upon.
+//! Subscribers which fall behind miss the oldest events, as reported by
+//! [`broadcast::error::RecvError::Lagged`], instead of slowing down the node.
+
+use derive_where::derive_where;
+use tokio::sync::broadcast;
+
+use malachitebft_engine::network::Multiaddr;
+use malachitebft_engine::util::events::{Event, RxEvent, TxEvent};
+
+use crate::app::types::core::Context;
+use crate::app::types::PeerId;
+
+/// Event in the connection lifecycle of a peer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PeerEvent {
+    /// A peer connected, with the address of the connection to it.
+    Connected { peer_id: PeerId, address: Multiaddr },
+
+    /// A peer disconnected.
+    Disconnected(PeerId),
+
+    /// A peer proved that it holds the consensus key with the given public key,
+    /// which the application can match against the validator set.
+    ValidatorProofVerified {
+        peer_id: PeerId,
+        public_key: Vec<u8>,
+    },
+}
+
+impl PeerEvent {
+    fn from_event<Ctx: Context>(event: Event<Ctx>) -> Option<Self> {
+        match event {
+            Event::PeerConnected { peer_id, address } => Some(Self::Connected { peer_id, address }),
+            Event::PeerDisconnected(peer_id) => Some(Self::Disconnected(peer_id)),
+            Event::ValidatorProofVerified {
+                peer_id,
+                public_key,
+            } => Some(Self::ValidatorProofVerified {
+                peer_id,
+                public_key,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Receiver of the [`PeerEvent`]s, among the events of the engine.
+#[derive_where(Debug)]
+pub struct RxPeerEvent<Ctx: Context> {
+    rx: RxEvent<Ctx>,
+}
+
+impl<Ctx: Context> RxPeerEvent<Ctx> {
+    /// Subscribe to the peer events among the events sent by the given sender.
+    pub fn subscribe(events: &TxEvent<Ctx>) -> Self {
+        Self {
+            rx: events.subscribe(),
+        }
+    }
+
+    /// Receive the next peer event, skipping the other events of the engine.
+    pub async fn recv(&mut self) -> Result<PeerEvent, broadcast::error::RecvError> {
+        loop {
+            if let Some(event) = PeerEvent::from_event(self.rx.recv().await?) {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use malachitebft_test::{Height, TestContext};
+
+    #[tokio::test]
+    async fn only_peer_events_are_received() {
+        let tx = TxEvent::<TestContext>::new();
+        let mut rx = RxPeerEvent::subscribe(&tx);
+
+        let peer_id: PeerId = "12D3KooWHRyfTBKcjkqjNk5UZarJhzT7rXZYfr4DmaCWJgen62Xk"
+            .parse()
+            .unwrap();
+        let address: Multiaddr = "/ip4/127.0.0.1/tcp/27000".parse().unwrap();
+
+        tx.send(|| Event::StartedHeight(Height::new(1), false));
+        tx.send(|| Event::PeerConnected {
+            peer_id,
+            address: address.clone(),
+        });
+        tx.send(|| Event::WalReplayDone(Height::new(1)));
+        tx.send(|| Event::PeerDisconnected(peer_id));
+
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            PeerEvent::Connected { peer_id, address }
+        );
+        assert_eq!(rx.recv().await.unwrap(), PeerEvent::Disconnected(peer_id));
+    }
+}
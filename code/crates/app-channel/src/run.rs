@@ -2,15 +2,18 @@
 //! Provides the application with a channel for receiving messages from consensus.
 
 use tokio::sync::mpsc::Receiver;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
 
-use eyre::Result;
+use eyre::{eyre, Result};
 
 use malachitebft_engine::consensus::{ConsensusMsg, ConsensusRef};
 use malachitebft_engine::network::{NetworkMsg, NetworkRef};
-use malachitebft_engine::node::NodeRef;
+use malachitebft_engine::node::{self, NodeRef};
+use malachitebft_engine::sync::{PauseReason, SyncMsg, SyncRef};
 
 pub use malachitebft_engine::network::NetworkIdentity;
+pub use malachitebft_engine::sync::Reconfiguration as SyncReconfiguration;
 pub use malachitebft_signing::{Signer, Verifier, VerifierExt};
 
 // Re-export context structs from builder module
@@ -33,6 +36,44 @@ impl EngineHandle {
     pub fn new(actor: NodeRef, handle: JoinHandle<()>) -> Self {
         Self { actor, handle }
     }
+
+    /// Shut down the engine gracefully.
+    ///
+    /// The network is drained first, then the state of sync is checkpointed,
+    /// and the WAL is flushed before the remaining actors are stopped.
+    /// Actors which do not stop within the `shutdown_drain_timeout` of the
+    /// consensus configuration are killed.
+    pub async fn stop(&self) -> Result<()> {
+        node::stop(&self.actor)
+            .await
+            .map_err(|e| eyre!("Failed to stop the engine: {e}"))?;
+
+        self.actor.wait(None).await?;
+
+        Ok(())
+    }
+
+    /// Change the settings of sync which can be changed while the engine is running,
+    /// eg. after the configuration of the node has been reloaded.
+    pub fn reconfigure_sync(&self, reconfiguration: SyncReconfiguration) -> Result<()> {
+        node::reconfigure_sync(&self.actor, reconfiguration)
+            .map_err(|e| eyre!("Failed to reconfigure sync: {e}"))
+    }
+
+    /// Stop requesting values from peers, eg. while the application catches up on the values
+    /// already synced, until [`resume_sync`](Self::resume_sync) is called.
+    /// Requests from peers are still served while paused.
+    pub fn pause_sync(&self) -> Result<()> {
+        node::pause_sync(&self.actor).map_err(|e| eyre!("Failed to pause sync: {e}"))
+    }
+
+    /// Resume the requests for values paused with [`pause_sync`](Self::pause_sync).
+    ///
+    /// Sync stays paused while the application has too many pending sync messages,
+    /// as set by [`ChannelConfig::sync_pause_threshold`](crate::ChannelConfig::sync_pause_threshold).
+    pub fn resume_sync(&self) -> Result<()> {
+        node::resume_sync(&self.actor).map_err(|e| eyre!("Failed to resume sync: {e}"))
+    }
 }
 
 /// Start the consensus engine with default actors.
@@ -111,6 +152,34 @@ pub(crate) fn spawn_consensus_request_task<Ctx>(
                         tracing::error!("Failed to send state dump request: {e}");
                     }
                 }
+                ConsensusRequest::DumpTrace(path, reply) => {
+                    if let Err(e) = consensus.cast(ConsensusMsg::DumpTrace(path, reply.into())) {
+                        tracing::error!("Failed to send trace dump request: {e}");
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Pause sync while the application has too many pending sync messages, and resume it after.
+pub(crate) fn spawn_sync_backpressure_task<Ctx>(
+    mut sync_paused: watch::Receiver<bool>,
+    sync: SyncRef<Ctx>,
+) where
+    Ctx: Context,
+{
+    tokio::spawn(async move {
+        while sync_paused.changed().await.is_ok() {
+            let msg = if *sync_paused.borrow_and_update() {
+                SyncMsg::Pause(PauseReason::Backpressure)
+            } else {
+                SyncMsg::Resume(PauseReason::Backpressure)
+            };
+
+            if let Err(e) = sync.cast(msg) {
+                tracing::error!("Failed to send backpressure to sync: {e}");
+                break;
             }
         }
     });
@@ -130,6 +199,11 @@ pub(crate) fn spawn_network_request_task<Ctx>(
                         tracing::error!(%error, "Failed to send network state dump request");
                     }
                 }
+                NetworkRequest::GetDiscoveryState(reply) => {
+                    if let Err(error) = network.cast(NetworkMsg::GetDiscoveryState(reply.into())) {
+                        tracing::error!(%error, "Failed to send discovery state request");
+                    }
+                }
                 NetworkRequest::UpdatePersistentPeers(op, reply) => {
                     if let Err(error) =
                         network.cast(NetworkMsg::UpdatePersistentPeers(op, reply.into()))
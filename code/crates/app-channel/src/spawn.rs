@@ -1,12 +1,14 @@
 //! Utility functions for spawning the actor system and connecting it to the application.
 
+use std::sync::Arc;
+
 use eyre::Result;
 use malachitebft_config::ValueSyncConfig;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 
 use malachitebft_engine::consensus::ConsensusCodec;
 use malachitebft_engine::host::HostRef;
-use malachitebft_engine::network::{NetworkIdentity, NetworkRef};
+use malachitebft_engine::network::{NetworkIdentity, NetworkRef, PeerFilter};
 use malachitebft_engine::sync::SyncCodec;
 
 use crate::app;
@@ -14,22 +16,38 @@ use crate::app::config::ConsensusConfig;
 use crate::app::metrics::Metrics;
 use crate::app::metrics::SharedRegistry;
 use crate::app::types::core::Context;
+use crate::channel::{app_channel, ChannelConfig, ChannelMetrics};
 use crate::connector::Connector;
+use crate::notifications::TxNotification;
 use crate::{AppMsg, NetworkMsg};
 
+/// Spawn the host actor forwarding the messages of consensus to the application.
+///
+/// Also returns whether sync should be paused, as the application has too many pending
+/// sync messages, see [`ChannelConfig::sync_pause_threshold`].
 pub async fn spawn_host_actor<Ctx>(
     metrics: Metrics,
-) -> Result<(HostRef<Ctx>, mpsc::Receiver<AppMsg<Ctx>>)>
+    config: ChannelConfig,
+    notifications: TxNotification<Ctx>,
+    registry: &SharedRegistry,
+) -> Result<(
+    HostRef<Ctx>,
+    mpsc::Receiver<AppMsg<Ctx>>,
+    watch::Receiver<bool>,
+)>
 where
     Ctx: Context,
 {
-    let (tx, rx) = mpsc::channel(128);
-    let actor_ref = Connector::spawn(tx, metrics).await?;
-    Ok((actor_ref, rx))
+    let (tx, rx) = app_channel(config, ChannelMetrics::register(registry));
+    let sync_paused = tx.sync_paused();
+    let actor_ref = Connector::spawn(tx, notifications, metrics).await?;
+    Ok((actor_ref, rx, sync_paused))
 }
 
 pub async fn spawn_network_actor<Ctx, Codec>(
+    ctx: &Ctx,
     identity: NetworkIdentity,
+    peer_filter: Option<Arc<dyn PeerFilter>>,
     cfg: &ConsensusConfig,
     value_sync_cfg: &ValueSyncConfig,
     registry: &SharedRegistry,
@@ -42,8 +60,16 @@ where
 {
     let (tx, mut rx) = mpsc::channel::<NetworkMsg<Ctx>>(1);
 
-    let actor_ref =
-        app::spawn::spawn_network_actor(cfg, value_sync_cfg, identity, registry, codec).await?;
+    let actor_ref = app::spawn::spawn_network_actor(
+        ctx,
+        cfg,
+        value_sync_cfg,
+        identity,
+        peer_filter,
+        registry,
+        codec,
+    )
+    .await?;
 
     tokio::spawn({
         let actor_ref = actor_ref.clone();
@@ -0,0 +1,481 @@
+//! Archival export and import of decided values, for migrating a chain between storage backends.
+//!
+//! An archive holds the decided values of a contiguous range of heights, together with
+//! their commit certificates, ordered by increasing height. Decided values are read through
+//! the host interface when exporting, and their commit certificates are verified when importing.
+//!
+//! ## Format
+//!
+//! All integers are big-endian.
+//!
+//! ```text
+//! header:  magic (8 bytes, "MALARCHV") | version (u32)
+//! entry:   length (u32, non-zero) | CRC32 of the payload (u32) | payload (`length` bytes)
+//! trailer: 0 (u32) | number of entries (u64)
+//! ```
+//!
+//! Each payload is a [`RawDecidedValue`] encoded with the codec of the application,
+//! eg. a Protobuf message. The archive does not contain any timestamp or other
+//! non-deterministic data, so exporting the same values always yields the same archive.
+
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use malachitebft_codec::Codec;
+use malachitebft_core_types::{CommitCertificate, Context, Height, ThresholdParams, Value};
+use malachitebft_engine::host::{HostMsg, HostRef};
+use malachitebft_signing::{Verifier, VerifierExt};
+use malachitebft_sync::RawDecidedValue;
+
+/// Magic bytes at the start of every archive.
+pub const MAGIC: [u8; 8] = *b"MALARCHV";
+
+/// Version of the archive format.
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not an archive: invalid magic bytes")]
+    InvalidMagic,
+
+    #[error("Unsupported archive version {0}, expected version {VERSION}")]
+    UnsupportedVersion(u32),
+
+    #[error("Archive is truncated")]
+    Truncated,
+
+    #[error("Checksum mismatch for entry #{index}")]
+    ChecksumMismatch { index: u64 },
+
+    #[error("Entry #{index} is too large to be archived: {size} bytes")]
+    EntryTooLarge { index: u64, size: usize },
+
+    #[error("Archive trailer records {expected} entries, but found {actual} entries")]
+    CountMismatch { expected: u64, actual: u64 },
+
+    #[error("Failed to encode/decode entry #{index}: {error}")]
+    Codec { index: u64, error: String },
+
+    #[error("Expected a decided value at height {expected}, but found height {actual}")]
+    UnexpectedHeight { expected: String, actual: String },
+
+    #[error("Missing decided value at height {0}")]
+    MissingValue(String),
+
+    #[error("Value at height {height} does not match the value id of its commit certificate")]
+    ValueIdMismatch { height: String },
+
+    #[error("No validator set for height {0}")]
+    MissingValidatorSet(String),
+
+    #[error("Invalid commit certificate at height {height}: {error}")]
+    InvalidCertificate { height: String, error: String },
+
+    #[error("Application error: {0}")]
+    App(eyre::Report),
+}
+
+/// Writes decided values to an archive.
+pub struct ArchiveWriter<Ctx, W, C> {
+    writer: W,
+    codec: C,
+    count: u64,
+    marker: PhantomData<Ctx>,
+}
+
+impl<Ctx, W, C> ArchiveWriter<Ctx, W, C>
+where
+    Ctx: Context,
+    W: Write,
+    C: Codec<RawDecidedValue<Ctx>>,
+{
+    /// Create a new archive, writing its header to the given writer.
+    pub fn new(mut writer: W, codec: C) -> Result<Self, ArchiveError> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_be_bytes())?;
+
+        Ok(Self {
+            writer,
+            codec,
+            count: 0,
+            marker: PhantomData,
+        })
+    }
+
+    /// Number of entries written so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Append a decided value to the archive.
+    pub fn append(&mut self, value: &RawDecidedValue<Ctx>) -> Result<(), ArchiveError> {
+        let index = self.count;
+
+        let payload = self.codec.encode(value).map_err(|e| ArchiveError::Codec {
+            index,
+            error: e.to_string(),
+        })?;
+
+        // A zero length marks the trailer of the archive
+        if payload.is_empty() {
+            return Err(ArchiveError::Codec {
+                index,
+                error: "empty encoding".to_string(),
+            });
+        }
+
+        let length = u32::try_from(payload.len()).map_err(|_| ArchiveError::EntryTooLarge {
+            index,
+            size: payload.len(),
+        })?;
+
+        self.writer.write_all(&length.to_be_bytes())?;
+        self.writer
+            .write_all(&crc32fast::hash(&payload).to_be_bytes())?;
+        self.writer.write_all(&payload)?;
+
+        self.count += 1;
+
+        Ok(())
+    }
+
+    /// Write the trailer of the archive and flush it, returning the underlying writer.
+    pub fn finish(mut self) -> Result<W, ArchiveError> {
+        self.writer.write_all(&0_u32.to_be_bytes())?;
+        self.writer.write_all(&self.count.to_be_bytes())?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+/// Reads decided values from an archive, checking the checksum of every entry.
+pub struct ArchiveReader<Ctx, R, C> {
+    reader: R,
+    codec: C,
+    count: u64,
+    done: bool,
+    marker: PhantomData<Ctx>,
+}
+
+impl<Ctx, R, C> ArchiveReader<Ctx, R, C>
+where
+    Ctx: Context,
+    R: Read,
+    C: Codec<RawDecidedValue<Ctx>>,
+{
+    /// Open an archive, reading and checking its header from the given reader.
+    pub fn new(mut reader: R, codec: C) -> Result<Self, ArchiveError> {
+        let mut magic = [0; 8];
+        read_exact(&mut reader, &mut magic)?;
+
+        if magic != MAGIC {
+            return Err(ArchiveError::InvalidMagic);
+        }
+
+        let version = read_u32(&mut reader)?;
+        if version != VERSION {
+            return Err(ArchiveError::UnsupportedVersion(version));
+        }
+
+        Ok(Self {
+            reader,
+            codec,
+            count: 0,
+            done: false,
+            marker: PhantomData,
+        })
+    }
+
+    /// The codec used to decode the entries of the archive.
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    /// Read the next decided value from the archive,
+    /// or `None` once the trailer has been reached and checked.
+    pub fn next_value(&mut self) -> Result<Option<RawDecidedValue<Ctx>>, ArchiveError> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let index = self.count;
+        let length = read_u32(&mut self.reader)?;
+
+        if length == 0 {
+            self.done = true;
+
+            let expected = read_u64(&mut self.reader)?;
+            if expected != self.count {
+                return Err(ArchiveError::CountMismatch {
+                    expected,
+                    actual: self.count,
+                });
+            }
+
+            return Ok(None);
+        }
+
+        let checksum = read_u32(&mut self.reader)?;
+
+        // Do not trust the length to pre-allocate the buffer, in case the archive is corrupted
+        let mut payload = Vec::new();
+        (&mut self.reader)
+            .take(u64::from(length))
+            .read_to_end(&mut payload)?;
+
+        if payload.len() != length as usize {
+            return Err(ArchiveError::Truncated);
+        }
+
+        if crc32fast::hash(&payload) != checksum {
+            return Err(ArchiveError::ChecksumMismatch { index });
+        }
+
+        let value = self
+            .codec
+            .decode(Bytes::from(payload))
+            .map_err(|e| ArchiveError::Codec {
+                index,
+                error: e.to_string(),
+            })?;
+
+        self.count += 1;
+
+        Ok(Some(value))
+    }
+}
+
+impl<Ctx, R, C> Iterator for ArchiveReader<Ctx, R, C>
+where
+    Ctx: Context,
+    R: Read,
+    C: Codec<RawDecidedValue<Ctx>>,
+{
+    type Item = Result<RawDecidedValue<Ctx>, ArchiveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.next_value();
+
+        if result.is_err() {
+            self.done = true;
+        }
+
+        result.transpose()
+    }
+}
+
+fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<(), ArchiveError> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => ArchiveError::Truncated,
+        _ => ArchiveError::Io(e),
+    })
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, ArchiveError> {
+    let mut buf = [0; 4];
+    read_exact(reader, &mut buf)?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, ArchiveError> {
+    let mut buf = [0; 8];
+    read_exact(reader, &mut buf)?;
+    Ok(u64::from_be_bytes(buf))
+}
+
+/// Where decided values are exported from.
+#[async_trait]
+pub trait DecidedValueSource<Ctx: Context>: Send + Sync {
+    /// The earliest height available in the history of the application.
+    async fn history_min_height(&self) -> eyre::Result<Ctx::Height>;
+
+    /// The value decided at the given height, if available.
+    async fn get_decided_value(
+        &self,
+        height: Ctx::Height,
+    ) -> eyre::Result<Option<RawDecidedValue<Ctx>>>;
+}
+
+/// Decided values are exported through the host actor,
+/// in the same way as they are provided to peers which are syncing.
+#[async_trait]
+impl<Ctx: Context> DecidedValueSource<Ctx> for HostRef<Ctx> {
+    async fn history_min_height(&self) -> eyre::Result<Ctx::Height> {
+        ractor::call!(self, |reply_to| HostMsg::GetHistoryMinHeight { reply_to })
+            .map_err(|e| eyre::eyre!("Failed to get history min height from host: {e}"))?
+            .map_err(|e| eyre::eyre!("Failed to get history min height from host: {e}"))
+    }
+
+    async fn get_decided_value(
+        &self,
+        height: Ctx::Height,
+    ) -> eyre::Result<Option<RawDecidedValue<Ctx>>> {
+        let values = ractor::call!(self, |reply_to| HostMsg::GetDecidedValues {
+            range: height..=height,
+            reply_to
+        })
+        .map_err(|e| eyre::eyre!("Failed to get decided value from host: {e}"))?
+        .map_err(|e| eyre::eyre!("Failed to get decided value from host: {e}"))?;
+
+        Ok(values
+            .into_iter()
+            .find(|value| value.certificate.height == height))
+    }
+}
+
+/// Where decided values are imported to.
+#[async_trait]
+pub trait DecidedValueSink<Ctx: Context>: Send {
+    /// The validator set which decided on the value at the given height,
+    /// used to verify the commit certificate of that value.
+    async fn validator_set(&self, height: Ctx::Height) -> eyre::Result<Option<Ctx::ValidatorSet>>;
+
+    /// Store a decided value, whose commit certificate has been verified.
+    async fn store_decided_value(
+        &mut self,
+        certificate: CommitCertificate<Ctx>,
+        value: Ctx::Value,
+    ) -> eyre::Result<()>;
+}
+
+/// Export the decided values from height `from` (default: the earliest available height)
+/// to height `to` (inclusive) into the given archive.
+///
+/// If `to` is `None`, the export stops at the first height for which no value was decided.
+/// Otherwise, a missing value is an error.
+///
+/// Returns the number of exported values.
+pub async fn export<Ctx, S, W, C>(
+    source: &S,
+    from: Option<Ctx::Height>,
+    to: Option<Ctx::Height>,
+    archive: &mut ArchiveWriter<Ctx, W, C>,
+) -> Result<u64, ArchiveError>
+where
+    Ctx: Context,
+    S: DecidedValueSource<Ctx>,
+    W: Write,
+    C: Codec<RawDecidedValue<Ctx>>,
+{
+    let mut height = match from {
+        Some(from) => from,
+        None => source
+            .history_min_height()
+            .await
+            .map_err(ArchiveError::App)?,
+    };
+
+    let start = archive.count();
+
+    while to.is_none_or(|to| height <= to) {
+        let value = source
+            .get_decided_value(height)
+            .await
+            .map_err(ArchiveError::App)?;
+
+        let Some(value) = value else {
+            if to.is_none() {
+                break;
+            }
+
+            return Err(ArchiveError::MissingValue(height.to_string()));
+        };
+
+        if value.certificate.height != height {
+            return Err(ArchiveError::UnexpectedHeight {
+                expected: height.to_string(),
+                actual: value.certificate.height.to_string(),
+            });
+        }
+
+        archive.append(&value)?;
+        height = height.increment();
+    }
+
+    Ok(archive.count() - start)
+}
+
+/// Import the decided values of an archive into the given sink.
+///
+/// Heights must be contiguous. The value of every entry must match the value id of its
+/// commit certificate, and the certificate must be valid for the validator set of its height.
+/// Import stops at the first invalid entry; values imported before it are kept.
+///
+/// Returns the number of imported values.
+pub async fn import<Ctx, S, R, C, V>(
+    ctx: &Ctx,
+    archive: &mut ArchiveReader<Ctx, R, C>,
+    verifier: &V,
+    thresholds: ThresholdParams,
+    sink: &mut S,
+) -> Result<u64, ArchiveError>
+where
+    Ctx: Context,
+    S: DecidedValueSink<Ctx>,
+    R: Read,
+    C: Codec<RawDecidedValue<Ctx>> + Codec<Ctx::Value>,
+    V: Verifier<Ctx>,
+{
+    let mut count = 0;
+    let mut expected_height: Option<Ctx::Height> = None;
+
+    while let Some(raw) = archive.next_value()? {
+        let RawDecidedValue {
+            certificate,
+            value_bytes,
+        } = raw;
+
+        let height = certificate.height;
+
+        if let Some(expected) = expected_height.filter(|expected| *expected != height) {
+            return Err(ArchiveError::UnexpectedHeight {
+                expected: expected.to_string(),
+                actual: height.to_string(),
+            });
+        }
+
+        let value =
+            <C as Codec<Ctx::Value>>::decode(archive.codec(), value_bytes).map_err(|e| {
+                ArchiveError::Codec {
+                    index: archive.count - 1,
+                    error: e.to_string(),
+                }
+            })?;
+
+        if value.id() != certificate.value_id {
+            return Err(ArchiveError::ValueIdMismatch {
+                height: height.to_string(),
+            });
+        }
+
+        let validator_set = sink
+            .validator_set(height)
+            .await
+            .map_err(ArchiveError::App)?
+            .ok_or_else(|| ArchiveError::MissingValidatorSet(height.to_string()))?;
+
+        verifier
+            .verify_commit_certificate(ctx, &certificate, &validator_set, thresholds)
+            .await
+            .map_err(|e| ArchiveError::InvalidCertificate {
+                height: height.to_string(),
+                error: e.to_string(),
+            })?;
+
+        sink.store_decided_value(certificate, value)
+            .await
+            .map_err(ArchiveError::App)?;
+
+        count += 1;
+        expected_height = Some(height.increment());
+    }
+
+    Ok(count)
+}
@@ -0,0 +1,270 @@
+//! Builder for spawning the actors of the engine around the host actor of the application.
+//!
+//! This is the actor-level counterpart of the channel-based `EngineBuilder` of
+//! `malachitebft-app-channel`, for applications which implement their own host actor.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use eyre::Result;
+use tokio::task::JoinHandle;
+
+use malachitebft_engine::consensus::ConsensusRef;
+use malachitebft_engine::host::HostRef;
+use malachitebft_engine::network::NetworkRef;
+use malachitebft_engine::node::NodeRef;
+use malachitebft_engine::sync::SyncRef;
+use malachitebft_engine::util::clock::{Clock, TokioClock};
+use malachitebft_engine::util::events::TxEvent;
+use malachitebft_engine::util::output_port::{OutputPort, OutputPortSubscriberTrait};
+use malachitebft_engine::wal::WalRef;
+use malachitebft_network::peer_filter::PeerFilter;
+use malachitebft_network::NetworkIdentity;
+use malachitebft_signing::{Signer, Verifier};
+
+use crate::config::NodeConfig;
+use crate::metrics::{Metrics, SharedRegistry};
+use crate::spawn::{
+    spawn_consensus_actor, spawn_network_actor, spawn_node_actor, spawn_sync_actor, spawn_wal_actor,
+};
+use crate::types::codec::{ConsensusCodec, SyncCodec, WalCodec};
+use crate::types::core::Context;
+
+/// Context for spawning the Consensus actor.
+pub struct ConsensusContext<Ctx: Context> {
+    pub address: Ctx::Address,
+    pub verifier: Box<dyn Verifier<Ctx>>,
+    pub signer: Option<Box<dyn Signer<Ctx>>>,
+    /// Clock driving the timers of the Consensus and Sync actors.
+    pub clock: Arc<dyn Clock>,
+}
+
+impl<Ctx: Context> ConsensusContext<Ctx> {
+    /// Create a consensus context for a validator node (has both verifier and signer).
+    pub fn new_validator(
+        address: Ctx::Address,
+        verifier: Box<dyn Verifier<Ctx>>,
+        signer: Box<dyn Signer<Ctx>>,
+    ) -> Self {
+        Self {
+            address,
+            verifier,
+            signer: Some(signer),
+            clock: Arc::new(TokioClock),
+        }
+    }
+
+    /// Create a consensus context for a full (non-validator) node (verifier only).
+    pub fn new_full_node(address: Ctx::Address, verifier: Box<dyn Verifier<Ctx>>) -> Self {
+        Self {
+            address,
+            verifier,
+            signer: None,
+            clock: Arc::new(TokioClock),
+        }
+    }
+
+    /// Drive the timers of the Consensus and Sync actors with the given clock instead of
+    /// the Tokio timer, eg. a [`SimulatedClock`] to control the passage of time in tests.
+    ///
+    /// [`SimulatedClock`]: malachitebft_engine::util::clock::SimulatedClock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+/// Handles to the actors of a running engine, as returned by [`EngineBuilder::build`].
+pub struct Engine<Ctx: Context> {
+    pub node: NodeRef,
+    pub network: NetworkRef<Ctx>,
+    pub consensus: ConsensusRef<Ctx>,
+    pub wal: WalRef<Ctx>,
+    /// `None` if sync is disabled
+    pub sync: Option<SyncRef<Ctx>>,
+    /// Events emitted by consensus, to which the application may subscribe
+    pub events: TxEvent<Ctx>,
+    /// Handle of the node actor, which completes when the node stops
+    pub handle: JoinHandle<()>,
+}
+
+/// Builder for spawning the network, WAL, consensus, sync and node actors
+/// around the host actor of the application.
+///
+/// The same codec is used for the WAL and for the messages sent over the network.
+/// Sync is enabled according to the `value_sync` section of the configuration,
+/// unless disabled with [`without_sync`](Self::without_sync).
+///
+/// # Example
+/// ```rust,ignore
+/// let host = MyHost::spawn(...).await?;
+///
+/// let engine = EngineBuilder::new(
+///     ctx,
+///     config,
+///     ProtobufCodec,
+///     identity,
+///     home_dir.join("wal").join("consensus.wal"),
+///     ConsensusContext::new_validator(address, verifier, signer),
+/// )
+/// .build(host)
+/// .await?;
+///
+/// engine.handle.await?;
+/// ```
+pub struct EngineBuilder<Ctx: Context, Config, Codec> {
+    ctx: Ctx,
+    config: Config,
+    codec: Codec,
+    identity: NetworkIdentity,
+    wal_path: PathBuf,
+    consensus: ConsensusContext<Ctx>,
+    peer_filter: Option<Arc<dyn PeerFilter>>,
+    registry: Option<SharedRegistry>,
+    sync: bool,
+}
+
+impl<Ctx, Config, Codec> EngineBuilder<Ctx, Config, Codec>
+where
+    Ctx: Context,
+    Config: NodeConfig,
+    Codec: WalCodec<Ctx> + ConsensusCodec<Ctx> + SyncCodec<Ctx> + Clone,
+{
+    /// Create a new engine builder, with the components which have no sensible default.
+    pub fn new(
+        ctx: Ctx,
+        config: Config,
+        codec: Codec,
+        identity: NetworkIdentity,
+        wal_path: impl Into<PathBuf>,
+        consensus: ConsensusContext<Ctx>,
+    ) -> Self {
+        Self {
+            ctx,
+            config,
+            codec,
+            identity,
+            wal_path: wal_path.into(),
+            consensus,
+            peer_filter: None,
+            registry: None,
+            sync: true,
+        }
+    }
+
+    /// Only let the peers allowed by the given filter connect to the node,
+    /// instead of the allow-list file of the P2P configuration.
+    #[must_use]
+    pub fn with_peer_filter(mut self, peer_filter: Arc<dyn PeerFilter>) -> Self {
+        self.peer_filter = Some(peer_filter);
+        self
+    }
+
+    /// Register the metrics in the given registry,
+    /// instead of the global registry labelled with the moniker of the node.
+    #[must_use]
+    pub fn with_registry(mut self, registry: SharedRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Do not spawn the Sync actor, even if sync is enabled in the configuration.
+    #[must_use]
+    pub fn without_sync(mut self) -> Self {
+        self.sync = false;
+        self
+    }
+
+    /// Spawn the actors of the engine around the given host actor.
+    ///
+    /// The actors are spawned in dependency order: network → WAL → consensus → sync → node.
+    pub async fn build(self, host: HostRef<Ctx>) -> Result<Engine<Ctx>> {
+        let registry = self
+            .registry
+            .unwrap_or_else(|| SharedRegistry::global().with_moniker(self.config.moniker()));
+        let metrics = Metrics::register(&registry);
+
+        let network = spawn_network_actor(
+            &self.ctx,
+            self.config.consensus(),
+            self.config.value_sync(),
+            self.identity,
+            self.peer_filter,
+            &registry,
+            self.codec.clone(),
+        )
+        .await?;
+
+        let wal = spawn_wal_actor(
+            &self.ctx,
+            self.codec.clone(),
+            &self.wal_path,
+            self.config.consensus().wal_storage,
+            self.config.consensus().wal_encryption_key_file.as_deref(),
+            &registry,
+        )
+        .await?;
+
+        let tx_event = TxEvent::new();
+        let sync_port = Arc::new(OutputPort::new());
+
+        let consensus = spawn_consensus_actor(
+            self.ctx.clone(),
+            self.consensus.address,
+            self.config.consensus().clone(),
+            self.consensus.verifier,
+            self.consensus.signer,
+            network.clone(),
+            host.clone(),
+            wal.clone(),
+            sync_port.clone(),
+            metrics,
+            tx_event.clone(),
+            Arc::clone(&self.consensus.clock),
+        )
+        .await?;
+
+        let sync = if self.sync {
+            spawn_sync_actor(
+                self.ctx.clone(),
+                network.clone(),
+                host.clone(),
+                consensus.clone(),
+                self.codec,
+                self.config.value_sync(),
+                &registry,
+                tx_event.clone(),
+                self.consensus.clock,
+            )
+            .await?
+        } else {
+            None
+        };
+
+        if let Some(sync) = &sync {
+            sync.subscribe_to_port(&sync_port);
+        }
+
+        let (node, handle) = spawn_node_actor(
+            self.ctx,
+            network.clone(),
+            consensus.clone(),
+            wal.clone(),
+            sync.clone(),
+            host,
+            tx_event.clone(),
+            self.config.consensus(),
+        )
+        .await?;
+
+        Ok(Engine {
+            node,
+            network,
+            consensus,
+            wal,
+            sync,
+            events: tx_event,
+            handle,
+        })
+    }
+}
@@ -0,0 +1,406 @@
+//! Bundles of the state of a node, for migrating a validator to another machine.
+//!
+//! A bundle holds the files of a node which must follow it to its new machine, ie. its
+//! configuration and its WAL, together with a manifest recording the address of the validator
+//! and the last height, round and step at which it signed a consensus message. The validator
+//! key is not part of the bundle, and must be moved separately.
+//!
+//! ## Format
+//!
+//! All integers are big-endian.
+//!
+//! ```text
+//! header:   magic (8 bytes, "MALBUNDL") | version (u32)
+//! manifest: length of the address (u16) | address (UTF-8)
+//!           | 0 (u8) if the validator never signed a message, otherwise
+//!           | 1 (u8) | height (u64) | round (i64) | step (u8: 0 = propose, 1 = prevote, 2 = precommit)
+//! file:     length of the path (u16, non-zero) | path (UTF-8)
+//!           | length of the contents (u64) | CRC32 of the contents (u32) | contents
+//! trailer:  0 (u16) | number of files (u64)
+//! ```
+//!
+//! Paths are relative to the home directory of the node, with `/` as separator,
+//! and may not contain `.` or `..` components.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use malachitebft_core_consensus::{SignedConsensusMsg, WalEntry};
+use malachitebft_core_types::{Context, Height, Proposal, Vote, VoteType};
+
+/// Magic bytes at the start of every bundle.
+pub const MAGIC: [u8; 8] = *b"MALBUNDL";
+
+/// Version of the bundle format.
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not a bundle: invalid magic bytes")]
+    InvalidMagic,
+
+    #[error("Unsupported bundle version {0}, expected version {VERSION}")]
+    UnsupportedVersion(u32),
+
+    #[error("Bundle is truncated")]
+    Truncated,
+
+    #[error("Invalid manifest: {0}")]
+    InvalidManifest(String),
+
+    #[error("Invalid path {0:?}: paths must be relative and may not contain `.` or `..`")]
+    InvalidPath(String),
+
+    #[error("Path {0:?} is too long")]
+    PathTooLong(String),
+
+    #[error("Checksum mismatch for file {0:?}")]
+    ChecksumMismatch(String),
+
+    #[error("Bundle trailer records {expected} files, but found {actual} files")]
+    CountMismatch { expected: u64, actual: u64 },
+}
+
+/// Step at which a validator signed a consensus message, in the order they happen within a round.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Step {
+    Propose,
+    Prevote,
+    Precommit,
+}
+
+/// The last height, round and step at which a validator signed a consensus message.
+///
+/// Ordered by height, then round, then step, so that the most recent one compares greatest.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LastSigned {
+    pub height: u64,
+    pub round: i64,
+    pub step: Step,
+}
+
+impl LastSigned {
+    /// The height, round and step at which the given vote is signed.
+    pub fn of_vote<Ctx: Context>(vote: &Ctx::Vote) -> Self {
+        Self {
+            height: vote.height().as_u64(),
+            round: vote.round().as_i64(),
+            step: match vote.vote_type() {
+                VoteType::Prevote => Step::Prevote,
+                VoteType::Precommit => Step::Precommit,
+            },
+        }
+    }
+
+    /// The height, round and step at which the given proposal is signed.
+    pub fn of_proposal<Ctx: Context>(proposal: &Ctx::Proposal) -> Self {
+        Self {
+            height: proposal.height().as_u64(),
+            round: proposal.round().as_i64(),
+            step: Step::Propose,
+        }
+    }
+}
+
+impl fmt::Display for LastSigned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "height {}, round {}, step {:?}",
+            self.height, self.round, self.step
+        )
+    }
+}
+
+/// Find the last consensus message signed by the given validator in the entries of a WAL.
+pub fn last_signed<Ctx, I>(entries: I, address: &Ctx::Address) -> Option<LastSigned>
+where
+    Ctx: Context,
+    I: IntoIterator<Item = WalEntry<Ctx>>,
+{
+    entries
+        .into_iter()
+        .filter_map(|entry| match entry {
+            WalEntry::ConsensusMsg(SignedConsensusMsg::Vote(vote))
+                if vote.validator_address() == address =>
+            {
+                Some(LastSigned::of_vote::<Ctx>(&vote.message))
+            }
+            WalEntry::ConsensusMsg(SignedConsensusMsg::Proposal(proposal))
+                if proposal.validator_address() == address =>
+            {
+                Some(LastSigned::of_proposal::<Ctx>(&proposal.message))
+            }
+            _ => None,
+        })
+        .max()
+}
+
+/// Describes the node a bundle was exported from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Manifest {
+    /// Address of the validator
+    pub address: String,
+
+    /// The last consensus message signed by the validator, if any
+    pub last_signed: Option<LastSigned>,
+}
+
+/// A file of the node held in a bundle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleFile {
+    /// Path of the file, relative to the home directory of the node
+    pub path: String,
+
+    /// Contents of the file
+    pub contents: Vec<u8>,
+}
+
+/// Writes the manifest and the files of a node to a bundle.
+pub struct BundleWriter<W> {
+    writer: W,
+    count: u64,
+}
+
+impl<W: Write> BundleWriter<W> {
+    /// Create a new bundle, writing its header and manifest to the given writer.
+    pub fn new(mut writer: W, manifest: &Manifest) -> Result<Self, BundleError> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_be_bytes())?;
+
+        let address = manifest.address.as_bytes();
+        let length = u16::try_from(address.len())
+            .map_err(|_| BundleError::InvalidManifest("address is too long".to_string()))?;
+
+        writer.write_all(&length.to_be_bytes())?;
+        writer.write_all(address)?;
+
+        match manifest.last_signed {
+            None => writer.write_all(&[0])?,
+            Some(last_signed) => {
+                writer.write_all(&[1])?;
+                writer.write_all(&last_signed.height.to_be_bytes())?;
+                writer.write_all(&last_signed.round.to_be_bytes())?;
+                writer.write_all(&[last_signed.step as u8])?;
+            }
+        }
+
+        Ok(Self { writer, count: 0 })
+    }
+
+    /// Append a file to the bundle.
+    pub fn add(&mut self, path: &str, contents: &[u8]) -> Result<(), BundleError> {
+        check_path(path)?;
+
+        let length =
+            u16::try_from(path.len()).map_err(|_| BundleError::PathTooLong(path.to_string()))?;
+
+        self.writer.write_all(&length.to_be_bytes())?;
+        self.writer.write_all(path.as_bytes())?;
+        self.writer
+            .write_all(&(contents.len() as u64).to_be_bytes())?;
+        self.writer
+            .write_all(&crc32fast::hash(contents).to_be_bytes())?;
+        self.writer.write_all(contents)?;
+
+        self.count += 1;
+
+        Ok(())
+    }
+
+    /// Write the trailer of the bundle and flush it, returning the underlying writer.
+    pub fn finish(mut self) -> Result<W, BundleError> {
+        self.writer.write_all(&0_u16.to_be_bytes())?;
+        self.writer.write_all(&self.count.to_be_bytes())?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+/// Reads the manifest and the files of a bundle, checking the checksum of every file.
+pub struct BundleReader<R> {
+    reader: R,
+    manifest: Manifest,
+    count: u64,
+    done: bool,
+}
+
+impl<R: Read> BundleReader<R> {
+    /// Open a bundle, reading and checking its header and manifest from the given reader.
+    pub fn new(mut reader: R) -> Result<Self, BundleError> {
+        let mut magic = [0; 8];
+        read_exact(&mut reader, &mut magic)?;
+
+        if magic != MAGIC {
+            return Err(BundleError::InvalidMagic);
+        }
+
+        let version = read_u32(&mut reader)?;
+        if version != VERSION {
+            return Err(BundleError::UnsupportedVersion(version));
+        }
+
+        let length = read_u16(&mut reader)?;
+        let address = String::from_utf8(read_bytes(&mut reader, length)?)
+            .map_err(|_| BundleError::InvalidManifest("address is not UTF-8".to_string()))?;
+
+        let last_signed = match read_u8(&mut reader)? {
+            0 => None,
+            1 => {
+                let height = read_u64(&mut reader)?;
+                let round = read_u64(&mut reader)? as i64;
+                let step = match read_u8(&mut reader)? {
+                    0 => Step::Propose,
+                    1 => Step::Prevote,
+                    2 => Step::Precommit,
+                    step => {
+                        return Err(BundleError::InvalidManifest(format!("unknown step {step}")))
+                    }
+                };
+
+                Some(LastSigned {
+                    height,
+                    round,
+                    step,
+                })
+            }
+            tag => {
+                return Err(BundleError::InvalidManifest(format!(
+                    "unknown last signed tag {tag}"
+                )))
+            }
+        };
+
+        Ok(Self {
+            reader,
+            manifest: Manifest {
+                address,
+                last_signed,
+            },
+            count: 0,
+            done: false,
+        })
+    }
+
+    /// The manifest of the bundle.
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// Read the next file from the bundle,
+    /// or `None` once the trailer has been reached and checked.
+    pub fn next_file(&mut self) -> Result<Option<BundleFile>, BundleError> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let length = read_u16(&mut self.reader)?;
+
+        if length == 0 {
+            self.done = true;
+
+            let expected = read_u64(&mut self.reader)?;
+            if expected != self.count {
+                return Err(BundleError::CountMismatch {
+                    expected,
+                    actual: self.count,
+                });
+            }
+
+            return Ok(None);
+        }
+
+        let path = String::from_utf8(read_bytes(&mut self.reader, length)?)
+            .map_err(|e| BundleError::InvalidPath(String::from_utf8_lossy(e.as_bytes()).into()))?;
+
+        check_path(&path)?;
+
+        let length = read_u64(&mut self.reader)?;
+        let checksum = read_u32(&mut self.reader)?;
+
+        // Do not trust the length to pre-allocate the buffer, in case the bundle is corrupted
+        let mut contents = Vec::new();
+        (&mut self.reader).take(length).read_to_end(&mut contents)?;
+
+        if contents.len() as u64 != length {
+            return Err(BundleError::Truncated);
+        }
+
+        if crc32fast::hash(&contents) != checksum {
+            return Err(BundleError::ChecksumMismatch(path));
+        }
+
+        self.count += 1;
+
+        Ok(Some(BundleFile { path, contents }))
+    }
+}
+
+impl<R: Read> Iterator for BundleReader<R> {
+    type Item = Result<BundleFile, BundleError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.next_file();
+
+        if result.is_err() {
+            self.done = true;
+        }
+
+        result.transpose()
+    }
+}
+
+/// Check that a path stays within the home directory of the node it is written to.
+fn check_path(path: &str) -> Result<(), BundleError> {
+    let valid = !path.is_empty()
+        && path
+            .split('/')
+            .all(|c| !c.is_empty() && c != "." && c != ".." && !c.contains('\\'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(BundleError::InvalidPath(path.to_string()))
+    }
+}
+
+fn read_exact(reader: &mut impl Read, buf: &mut [u8]) -> Result<(), BundleError> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => BundleError::Truncated,
+        _ => BundleError::Io(e),
+    })
+}
+
+fn read_bytes(reader: &mut impl Read, length: u16) -> Result<Vec<u8>, BundleError> {
+    let mut buf = vec![0; usize::from(length)];
+    read_exact(reader, &mut buf)?;
+    Ok(buf)
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8, BundleError> {
+    let mut buf = [0; 1];
+    read_exact(reader, &mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16(reader: &mut impl Read) -> Result<u16, BundleError> {
+    let mut buf = [0; 2];
+    read_exact(reader, &mut buf)?;
+    Ok(u16::from_be_bytes(buf))
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, BundleError> {
+    let mut buf = [0; 4];
+    read_exact(reader, &mut buf)?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, BundleError> {
+    let mut buf = [0; 8];
+    read_exact(reader, &mut buf)?;
+    Ok(u64::from_be_bytes(buf))
+}
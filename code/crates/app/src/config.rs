@@ -8,4 +8,12 @@ pub trait NodeConfig {
 
     fn value_sync(&self) -> &ValueSyncConfig;
     fn value_sync_mut(&mut self) -> &mut ValueSyncConfig;
+
+    fn metrics(&self) -> &MetricsConfig;
+
+    /// Check the invariants which span several fields of the configuration,
+    /// see [`validate`](malachitebft_config::validate).
+    fn validate(&self) -> ConfigReport {
+        malachitebft_config::validate(self.consensus(), self.value_sync())
+    }
 }
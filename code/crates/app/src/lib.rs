@@ -10,8 +10,13 @@
 //     rustdoc::missing_doc_code_examples
 // )]
 
+pub mod archive;
+pub mod builder;
+pub mod bundle;
 pub mod config;
 pub mod part_store;
+pub mod partitioning;
+pub mod sign_guard;
 pub mod spawn;
 pub mod types;
 
@@ -28,3 +33,6 @@ pub use malachitebft_engine as engine;
 pub use malachitebft_engine::util::streaming;
 pub use malachitebft_metrics as metrics;
 pub use malachitebft_wal as wal;
+
+#[cfg(feature = "erasure-coding")]
+pub use malachitebft_erasure as erasure;
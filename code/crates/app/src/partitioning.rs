@@ -0,0 +1,232 @@
+//! Splitting of a locally proposed value into the parts streamed to peers,
+//! according to the [`PartitioningPolicy`] of the application.
+//!
+//! The value is first prefixed with a flag telling whether it is compressed, so that peers can
+//! reassemble it regardless of their own policy, and is then split into parts of the size given
+//! by the policy:
+//!
+//! ```text
+//! flag (u8) | value                                  uncompressed
+//! flag (u8) | uncompressed length (u32) | lz4 block  compressed
+//! ```
+//!
+//! Compression is skipped for values which do not get smaller when compressed.
+//!
+//! The policy is an application hook: it is read from the `[consensus.partitioning]` section
+//! of the configuration, and applications call [`partition`] when streaming a value they
+//! propose, then [`reassemble`] once they received all the parts of a value from a peer.
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+pub use malachitebft_config::PartitioningPolicy;
+
+const UNCOMPRESSED: u8 = 0;
+const LZ4: u8 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum PartitioningError {
+    #[error("Missing compression flag")]
+    MissingFlag,
+
+    #[error("Unknown compression flag {0}")]
+    UnknownFlag(u8),
+
+    #[error("Missing uncompressed length")]
+    MissingLength,
+
+    #[error("Value of {len} bytes exceeds the maximum of {max_len} bytes")]
+    TooLarge { len: usize, max_len: usize },
+
+    #[error("Invalid compressed value: {0}")]
+    Decompression(String),
+}
+
+/// Split the given value into parts according to the given policy.
+///
+/// Always returns at least one part.
+pub fn partition(policy: &PartitioningPolicy, value: &[u8]) -> Vec<Bytes> {
+    let compressed = policy
+        .compression
+        .then(|| lz4_flex::block::compress(value))
+        // Only keep the compressed value if it is actually smaller
+        .filter(|compressed| compressed.len() + 4 < value.len());
+
+    let payload = match compressed {
+        Some(compressed) => {
+            let mut buf = BytesMut::with_capacity(1 + 4 + compressed.len());
+            buf.put_u8(LZ4);
+            buf.put_u32(value.len() as u32);
+            buf.put_slice(&compressed);
+            buf.freeze()
+        }
+        None => {
+            let mut buf = BytesMut::with_capacity(1 + value.len());
+            buf.put_u8(UNCOMPRESSED);
+            buf.put_slice(value);
+            buf.freeze()
+        }
+    };
+
+    let part_len = policy.part_len(payload.len());
+
+    (0..payload.len())
+        .step_by(part_len)
+        .map(|start| payload.slice(start..payload.len().min(start + part_len)))
+        .collect()
+}
+
+/// Reassemble a value from its parts, in the order they were produced by [`partition`],
+/// rejecting values which would decompress to more than `max_len` bytes.
+pub fn reassemble<I>(parts: I, max_len: usize) -> Result<Bytes, PartitioningError>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut payload = BytesMut::new();
+    for part in parts {
+        payload.put_slice(part.as_ref());
+    }
+
+    let mut payload = payload.freeze();
+
+    if payload.is_empty() {
+        return Err(PartitioningError::MissingFlag);
+    }
+
+    match payload.get_u8() {
+        UNCOMPRESSED => {
+            if payload.len() > max_len {
+                return Err(PartitioningError::TooLarge {
+                    len: payload.len(),
+                    max_len,
+                });
+            }
+
+            Ok(payload)
+        }
+
+        LZ4 => {
+            if payload.len() < 4 {
+                return Err(PartitioningError::MissingLength);
+            }
+
+            let len = payload.get_u32() as usize;
+            if len > max_len {
+                return Err(PartitioningError::TooLarge { len, max_len });
+            }
+
+            let value = lz4_flex::block::decompress(&payload, len)
+                .map_err(|e| PartitioningError::Decompression(e.to_string()))?;
+
+            if value.len() != len {
+                return Err(PartitioningError::Decompression(format!(
+                    "expected {len} bytes, got {}",
+                    value.len()
+                )));
+            }
+
+            Ok(Bytes::from(value))
+        }
+
+        flag => Err(PartitioningError::UnknownFlag(flag)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytesize::ByteSize;
+
+    use super::*;
+
+    const MAX_LEN: usize = 1024 * 1024;
+
+    fn policy(
+        max_part_bytes: u64,
+        compression: bool,
+        target_parts: Option<usize>,
+    ) -> PartitioningPolicy {
+        PartitioningPolicy {
+            max_part_bytes: ByteSize::b(max_part_bytes),
+            compression,
+            target_parts,
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let value = b"proposed value ".repeat(1000);
+
+        for policy in [
+            PartitioningPolicy::default(),
+            policy(100, false, None),
+            policy(100, true, None),
+            policy(1024, false, Some(3)),
+            policy(1024, true, Some(3)),
+            policy(1, true, Some(1)),
+        ] {
+            let parts = partition(&policy, &value);
+            assert!(parts
+                .iter()
+                .all(|part| part.len() as u64 <= policy.max_part_bytes.as_u64()));
+            assert_eq!(reassemble(&parts, MAX_LEN).unwrap(), value.as_slice());
+        }
+    }
+
+    #[test]
+    fn parts_follow_policy() {
+        let value = vec![7; 999];
+
+        // Flag byte included
+        let parts = partition(&policy(100, false, None), &value);
+        assert_eq!(parts.len(), 10);
+        assert!(parts.iter().all(|part| part.len() <= 100));
+
+        let parts = partition(&policy(1024, false, Some(4)), &value);
+        assert_eq!(parts.len(), 4);
+        assert!(parts.iter().all(|part| part.len() == 250));
+
+        let compressed = partition(&policy(1024, true, None), &value);
+        assert_eq!(compressed.len(), 1);
+        assert!(compressed[0].len() < value.len());
+    }
+
+    #[test]
+    fn empty_value() {
+        let parts = partition(&PartitioningPolicy::default(), &[]);
+        assert_eq!(parts.len(), 1);
+        assert!(reassemble(&parts, MAX_LEN).unwrap().is_empty());
+    }
+
+    #[test]
+    fn incompressible_value_is_not_compressed() {
+        let value: Vec<u8> = (0..=255).collect();
+        let parts = partition(&policy(1024, true, None), &value);
+        assert_eq!(parts[0][0], UNCOMPRESSED);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        let no_parts: [&[u8]; 0] = [];
+        assert_eq!(
+            reassemble(no_parts, MAX_LEN),
+            Err(PartitioningError::MissingFlag)
+        );
+        assert_eq!(
+            reassemble([&[42u8][..]], MAX_LEN),
+            Err(PartitioningError::UnknownFlag(42))
+        );
+        assert_eq!(
+            reassemble([&[LZ4, 0, 0][..]], MAX_LEN),
+            Err(PartitioningError::MissingLength)
+        );
+
+        let parts = partition(&policy(1024, true, None), &[0; 4096]);
+        assert_eq!(
+            reassemble(&parts, 1024),
+            Err(PartitioningError::TooLarge {
+                len: 4096,
+                max_len: 1024
+            })
+        );
+    }
+}
@@ -0,0 +1,383 @@
+//! Protection against double-signing after a crash or a restart of a validator.
+//!
+//! [`SignGuard`] wraps the signer of a validator, and persists the height, round and step
+//! of every vote and proposal it signs to a file, before releasing the signature. It then
+//! refuses to sign a message for an earlier height, round or step than the last one signed,
+//! or a different message for the same ones, even after the node restarts, so that the
+//! validator cannot equivocate by signing again in a round it already signed in.
+//!
+//! The messages signed at the height of the last one may be signed again, eg. if the node crashed
+//! after signing a vote but before writing it to its WAL, or when the node replays its WAL after
+//! a restart. Two messages are deemed identical when their signatures are, which requires a
+//! deterministic signing scheme such as Ed25519. With other schemes, the guard refuses to sign
+//! the message again.
+//!
+//! ## Format
+//!
+//! All integers are big-endian. The file holds one entry per message signed at the height
+//! of the last one, in the order they were signed.
+//!
+//! ```text
+//! magic (8 bytes, "MALSIGNS") | number of entries (u32) | entries | CRC32 of all the preceding bytes (u32)
+//!
+//! entry = height (u64) | round (i64) | step (u8: 0 = propose, 1 = prevote, 2 = precommit)
+//!       | length of the signature (u32) | signature
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::future::Future;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+use malachitebft_core_types::{
+    Context, Signature, SignedMessage, SigningScheme, ValidatorProof, ValidatorSetUpdate,
+};
+use malachitebft_signing::{Error, Signer};
+
+use crate::bundle::{LastSigned, Step};
+
+/// Magic bytes at the start of every sign state file.
+pub const MAGIC: [u8; 8] = *b"MALSIGNS";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SignGuardError {
+    #[error("Refusing to sign at {attempted}, after having signed at {last}")]
+    Regression {
+        attempted: LastSigned,
+        last: LastSigned,
+    },
+
+    #[error("Refusing to sign a different message at {0}, where a message was already signed")]
+    Conflict(LastSigned),
+
+    #[error("Failed to persist the sign state to {path}: {source}")]
+    Persist { path: PathBuf, source: io::Error },
+
+    #[error("Failed to read the sign state from {path}: {source}")]
+    Load { path: PathBuf, source: io::Error },
+
+    #[error("Invalid sign state file {path}: {reason}")]
+    Invalid { path: PathBuf, reason: String },
+}
+
+/// The consensus messages signed by a validator at the height of the last one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignState {
+    /// The height, round and step at which the last message was signed
+    pub last_signed: LastSigned,
+
+    /// The encoded signatures of the messages signed at the height of the last one,
+    /// by the round and step at which they were signed, including the last one
+    pub signatures: BTreeMap<LastSigned, Vec<u8>>,
+}
+
+impl SignState {
+    /// Load the sign state from the given file, or `None` if it does not exist.
+    pub fn load(path: &Path) -> Result<Option<Self>, SignGuardError> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => {
+                return Err(SignGuardError::Load {
+                    path: path.to_path_buf(),
+                    source,
+                })
+            }
+        };
+
+        Self::decode(&bytes)
+            .map(Some)
+            .map_err(|reason| SignGuardError::Invalid {
+                path: path.to_path_buf(),
+                reason: reason.to_string(),
+            })
+    }
+
+    /// Persist the sign state to the given file, replacing it atomically.
+    pub fn save(&self, path: &Path) -> Result<(), SignGuardError> {
+        self.encode()
+            .and_then(|bytes| write_atomically(path, &bytes))
+            .map_err(|source| SignGuardError::Persist {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    fn encode(&self) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(
+            &encode_len(self.signatures.len(), "number of entries")?.to_be_bytes(),
+        );
+
+        for (signed, signature) in &self.signatures {
+            bytes.extend_from_slice(&signed.height.to_be_bytes());
+            bytes.extend_from_slice(&signed.round.to_be_bytes());
+            bytes.push(signed.step as u8);
+            bytes
+                .extend_from_slice(&encode_len(signature.len(), "signature length")?.to_be_bytes());
+            bytes.extend_from_slice(signature);
+        }
+
+        let checksum = crc32fast::hash(&bytes);
+        bytes.extend_from_slice(&checksum.to_be_bytes());
+
+        Ok(bytes)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, &'static str> {
+        let (contents, checksum) = bytes.split_last_chunk::<4>().ok_or("file is truncated")?;
+
+        if crc32fast::hash(contents) != u32::from_be_bytes(*checksum) {
+            return Err("checksum mismatch");
+        }
+
+        let (magic, rest) = contents
+            .split_first_chunk::<8>()
+            .ok_or("file is truncated")?;
+        if magic != &MAGIC {
+            return Err("invalid magic bytes");
+        }
+
+        let (count, mut rest) = rest.split_first_chunk::<4>().ok_or("file is truncated")?;
+        let mut signatures = BTreeMap::new();
+
+        for _ in 0..u32::from_be_bytes(*count) {
+            let (height, tail) = rest.split_first_chunk::<8>().ok_or("file is truncated")?;
+            let (round, tail) = tail.split_first_chunk::<8>().ok_or("file is truncated")?;
+            let (step, tail) = tail.split_first().ok_or("file is truncated")?;
+            let (length, tail) = tail.split_first_chunk::<4>().ok_or("file is truncated")?;
+
+            let length =
+                usize::try_from(u32::from_be_bytes(*length)).map_err(|_| "file is truncated")?;
+            if tail.len() < length {
+                return Err("file is truncated");
+            }
+            let (signature, tail) = tail.split_at(length);
+
+            let step = match step {
+                0 => Step::Propose,
+                1 => Step::Prevote,
+                2 => Step::Precommit,
+                _ => return Err("unknown step"),
+            };
+
+            let signed = LastSigned {
+                height: u64::from_be_bytes(*height),
+                round: i64::from_be_bytes(*round),
+                step,
+            };
+
+            signatures.insert(signed, signature.to_vec());
+            rest = tail;
+        }
+
+        if !rest.is_empty() {
+            return Err("trailing bytes after the entries");
+        }
+
+        let last_signed = *signatures.keys().next_back().ok_or("no entries")?;
+
+        if signatures
+            .keys()
+            .any(|signed| signed.height != last_signed.height)
+        {
+            return Err("entries at different heights");
+        }
+
+        Ok(Self {
+            last_signed,
+            signatures,
+        })
+    }
+}
+
+/// Encode a length as a `u32`, refusing to truncate it.
+fn encode_len(len: usize, what: &str) -> io::Result<u32> {
+    u32::try_from(len).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{what} {len} does not fit in 32 bits"),
+        )
+    })
+}
+
+/// Write the file to a temporary file next to it, then rename it over the file,
+/// so that a crash leaves either the previous or the new contents.
+fn write_atomically(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let mut file = File::create(&tmp)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp, path)?;
+
+    // Persist the rename itself
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        File::open(parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+/// A signer refusing to sign votes and proposals which could make the validator double-sign,
+/// based on the last message it signed, persisted to a file.
+///
+/// Vote extensions, validator proofs and validator set updates are signed by the inner signer
+/// without any check.
+pub struct SignGuard<Ctx, S> {
+    signer: S,
+    path: PathBuf,
+    state: Mutex<Option<SignState>>,
+    _marker: PhantomData<fn() -> Ctx>,
+}
+
+impl<Ctx, S> SignGuard<Ctx, S>
+where
+    Ctx: Context,
+    S: Signer<Ctx>,
+{
+    /// Wrap the given signer, loading the last signed message from the given file if it exists.
+    ///
+    /// The parent directory of the file must exist.
+    pub fn open(signer: S, path: impl Into<PathBuf>) -> Result<Self, SignGuardError> {
+        let path = path.into();
+        let state = SignState::load(&path)?;
+
+        if let Some(state) = &state {
+            info!(last_signed = %state.last_signed, "Loaded the sign state from {}", path.display());
+        }
+
+        Ok(Self {
+            signer,
+            path,
+            state: Mutex::new(state),
+            _marker: PhantomData,
+        })
+    }
+
+    /// The height, round and step at which the last vote or proposal was signed, if any.
+    pub async fn last_signed(&self) -> Option<LastSigned> {
+        self.state
+            .lock()
+            .await
+            .as_ref()
+            .map(|state| state.last_signed)
+    }
+
+    /// Sign a message at the given height, round and step with the given function,
+    /// only releasing the signature once it has been persisted.
+    async fn sign_guarded<M, F>(
+        &self,
+        at: LastSigned,
+        sign: F,
+    ) -> Result<SignedMessage<Ctx, M>, Error>
+    where
+        F: Future<Output = Result<SignedMessage<Ctx, M>, Error>>,
+    {
+        // Hold the lock until the state is persisted, so that messages are signed one at a time
+        let mut state = self.state.lock().await;
+
+        if let Some(last) = state.as_ref() {
+            if at < last.last_signed && !last.signatures.contains_key(&at) {
+                return Err(refuse(SignGuardError::Regression {
+                    attempted: at,
+                    last: last.last_signed,
+                }));
+            }
+        }
+
+        let signed = sign.await?;
+        let signature = encode_signature::<Ctx>(&signed.signature);
+
+        if let Some(previous) = state.as_ref().and_then(|last| last.signatures.get(&at)) {
+            return if *previous == signature {
+                Ok(signed)
+            } else {
+                Err(refuse(SignGuardError::Conflict(at)))
+            };
+        }
+
+        // Only the messages signed at the height of the last one are kept
+        let mut signatures = match state.as_ref() {
+            Some(last) if last.last_signed.height == at.height => last.signatures.clone(),
+            _ => BTreeMap::new(),
+        };
+
+        signatures.insert(at, signature);
+
+        let new_state = SignState {
+            last_signed: at,
+            signatures,
+        };
+
+        new_state.save(&self.path).map_err(refuse)?;
+        *state = Some(new_state);
+
+        Ok(signed)
+    }
+}
+
+fn encode_signature<Ctx: Context>(signature: &Signature<Ctx>) -> Vec<u8> {
+    <Ctx::SigningScheme as SigningScheme>::encode_signature(signature)
+}
+
+fn refuse(e: SignGuardError) -> Error {
+    error!("{e}");
+    Error::from_source(e)
+}
+
+#[async_trait]
+impl<Ctx, S> Signer<Ctx> for SignGuard<Ctx, S>
+where
+    Ctx: Context,
+    S: Signer<Ctx>,
+{
+    async fn sign_vote(&self, vote: Ctx::Vote) -> Result<SignedMessage<Ctx, Ctx::Vote>, Error> {
+        let at = LastSigned::of_vote::<Ctx>(&vote);
+        self.sign_guarded(at, self.signer.sign_vote(vote)).await
+    }
+
+    async fn sign_proposal(
+        &self,
+        proposal: Ctx::Proposal,
+    ) -> Result<SignedMessage<Ctx, Ctx::Proposal>, Error> {
+        let at = LastSigned::of_proposal::<Ctx>(&proposal);
+        self.sign_guarded(at, self.signer.sign_proposal(proposal))
+            .await
+    }
+
+    async fn sign_vote_extension(
+        &self,
+        extension: Ctx::Extension,
+    ) -> Result<SignedMessage<Ctx, Ctx::Extension>, Error> {
+        self.signer.sign_vote_extension(extension).await
+    }
+
+    async fn sign_validator_proof(
+        &self,
+        public_key: Vec<u8>,
+        peer_id: Vec<u8>,
+    ) -> Result<ValidatorProof<Ctx>, Error> {
+        self.signer.sign_validator_proof(public_key, peer_id).await
+    }
+
+    async fn sign_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<Ctx>,
+    ) -> Result<Signature<Ctx>, Error> {
+        self.signer.sign_validator_set_update(update).await
+    }
+}
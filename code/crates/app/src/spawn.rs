@@ -6,27 +6,33 @@ use std::time::Duration;
 
 use eyre::{eyre, Result};
 use tokio::task::JoinHandle;
-use tracing::Span;
+use tracing::{info, warn, Span};
 
+use malachitebft_core_consensus::FullProposalLimits;
 use malachitebft_engine::consensus::{Consensus, ConsensusCodec, ConsensusParams, ConsensusRef};
 use malachitebft_engine::host::HostRef;
-use malachitebft_engine::network::{Network, NetworkRef};
+use malachitebft_engine::network::{Mux, Network, NetworkRef, ShardId};
 use malachitebft_engine::node::{Node, NodeRef};
 use malachitebft_engine::sync::{Params as SyncParams, Sync, SyncCodec, SyncMsg, SyncRef};
+use malachitebft_engine::util::clock::Clock;
 use malachitebft_engine::util::events::TxEvent;
 use malachitebft_engine::util::output_port::OutputPort;
-use malachitebft_engine::wal::{Wal, WalCodec, WalRef};
+use malachitebft_engine::wal::{EncryptionKey as WalEncryptionKey, Wal, WalCodec, WalRef};
+use malachitebft_network::peer_filter::{AllowList, PeerFilter};
 use malachitebft_network::{
-    ChannelNames, Config as NetworkConfig, DiscoveryConfig, GossipSubConfig, NetworkIdentity,
+    ChannelNames, Config as NetworkConfig, DiscoveryConfig, GossipSubConfig,
+    GossipSubScoringConfig, NetworkIdentity,
 };
+use malachitebft_retry::Backoff;
 use malachitebft_signing::{Signer, Verifier};
 use malachitebft_sync as sync;
 
-use crate::config::{ConsensusConfig, ValueSyncConfig};
+use crate::config::{BackoffConfig, ConsensusConfig, ValueSyncConfig, WalStorageConfig};
 use crate::metrics::{Metrics, SharedRegistry};
 use crate::types::core::Context;
 use crate::types::ValuePayload;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn spawn_node_actor<Ctx>(
     ctx: Ctx,
     network: NetworkRef<Ctx>,
@@ -34,6 +40,8 @@ pub async fn spawn_node_actor<Ctx>(
     wal: WalRef<Ctx>,
     sync: Option<SyncRef<Ctx>>,
     host: HostRef<Ctx>,
+    tx_event: TxEvent<Ctx>,
+    cfg: &ConsensusConfig,
 ) -> Result<(NodeRef, JoinHandle<()>)>
 where
     Ctx: Context,
@@ -46,6 +54,8 @@ where
         wal,
         sync,
         host,
+        tx_event,
+        cfg.shutdown_drain_timeout,
         tracing::Span::current(),
     );
 
@@ -53,10 +63,74 @@ where
     Ok((actor_ref, handle))
 }
 
+/// Spawn the network actor.
+///
+/// Peers are filtered with the given peer filter if any, or else with the allow-list
+/// loaded from the `allow_list_file` of the P2P configuration, if set.
 pub async fn spawn_network_actor<Ctx, Codec>(
+    ctx: &Ctx,
     consensus_cfg: &ConsensusConfig,
     value_sync_cfg: &ValueSyncConfig,
     identity: NetworkIdentity,
+    peer_filter: Option<Arc<dyn PeerFilter>>,
+    registry: &SharedRegistry,
+    codec: Codec,
+) -> Result<NetworkRef<Ctx>>
+where
+    Ctx: Context,
+    Codec: ConsensusCodec<Ctx>,
+    Codec: SyncCodec<Ctx>,
+{
+    consensus_cfg
+        .p2p
+        .validate()
+        .map_err(|e| eyre!("Invalid P2P configuration: {e}"))?;
+
+    let mut config = make_network_config(consensus_cfg, value_sync_cfg);
+    config.chain_id = ctx.chain_id().map(|chain_id| chain_id.as_str().to_string());
+
+    config.peer_filter = match (peer_filter, &consensus_cfg.p2p.allow_list_file) {
+        (Some(peer_filter), Some(path)) => {
+            warn!(
+                path = %path.display(),
+                "Ignoring the allow-list file, as the application provides its own peer filter"
+            );
+            Some(peer_filter)
+        }
+        (Some(peer_filter), None) => Some(peer_filter),
+        (None, Some(path)) => {
+            let allow_list = AllowList::from_file(path)
+                .map_err(|e| eyre!("Failed to load allow-list from {}: {e}", path.display()))?;
+
+            info!(path = %path.display(), peers = allow_list.len(), "Loaded allow-list");
+            Some(Arc::new(allow_list))
+        }
+        (None, None) => None,
+    };
+
+    Network::spawn(
+        identity,
+        config,
+        consensus_cfg.p2p.priority_lanes,
+        consensus_cfg.p2p.reputation,
+        registry.clone(),
+        codec,
+        Span::current(),
+    )
+    .await
+    .map_err(Into::into)
+}
+
+/// Spawn a network actor for one of several consensus instances sharing the same network.
+///
+/// The metrics of the network actor are registered with a `shard` label set to the shard id,
+/// and the other actors of the instance should be given the same registry, see [`shard_registry`].
+/// Each instance must be given its own WAL path: the WAL is locked while it is open,
+/// so an instance using the WAL of another instance fails to start.
+pub async fn spawn_shard_network_actor<Ctx, Codec>(
+    mux: &Mux,
+    shard: ShardId,
+    consensus_cfg: &ConsensusConfig,
     registry: &SharedRegistry,
     codec: Codec,
 ) -> Result<NetworkRef<Ctx>>
@@ -65,11 +139,25 @@ where
     Codec: ConsensusCodec<Ctx>,
     Codec: SyncCodec<Ctx>,
 {
-    let config = make_network_config(consensus_cfg, value_sync_cfg);
+    let handle = mux.shard(shard.clone())?;
+    let span = tracing::error_span!(parent: Span::current(), "shard", %shard);
 
-    Network::spawn(identity, config, registry.clone(), codec, Span::current())
-        .await
-        .map_err(Into::into)
+    Network::spawn_with_handle(
+        handle,
+        consensus_cfg.p2p.priority_lanes,
+        consensus_cfg.p2p.reputation,
+        shard_registry(registry, &shard),
+        codec,
+        span,
+    )
+    .await
+    .map_err(Into::into)
+}
+
+/// Registry for the metrics of one of several consensus instances sharing the same network,
+/// whose metrics are all labelled with the given shard id.
+pub fn shard_registry(registry: &SharedRegistry, shard: &ShardId) -> SharedRegistry {
+    registry.with_label("shard", shard.as_str())
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -85,6 +173,7 @@ pub async fn spawn_consensus_actor<Ctx>(
     sync: Arc<OutputPort<SyncMsg<Ctx>>>,
     metrics: Metrics,
     tx_event: TxEvent<Ctx>,
+    clock: Arc<dyn Clock>,
 ) -> Result<ConsensusRef<Ctx>>
 where
     Ctx: Context,
@@ -102,6 +191,11 @@ where
         threshold_params: Default::default(),
         value_payload,
         enabled: cfg.enabled,
+        require_vote_extensions: cfg.require_vote_extensions,
+        full_proposal_limits: FullProposalLimits {
+            round_margin: cfg.full_proposals.round_margin,
+            max_entries_per_round: cfg.full_proposals.max_entries_per_round,
+        },
     };
 
     Consensus::spawn(
@@ -116,6 +210,7 @@ where
         sync,
         metrics,
         tx_event,
+        clock,
         Span::current(),
     )
     .await
@@ -126,6 +221,8 @@ pub async fn spawn_wal_actor<Ctx, Codec>(
     ctx: &Ctx,
     codec: Codec,
     path: &Path,
+    storage: WalStorageConfig,
+    encryption_key_file: Option<&Path>,
     registry: &SharedRegistry,
 ) -> Result<WalRef<Ctx>>
 where
@@ -138,10 +235,23 @@ where
         }
     }
 
+    let encryption_key = encryption_key_file
+        .map(|key_file| {
+            WalEncryptionKey::from_file(key_file).map_err(|e| {
+                eyre!(
+                    "Failed to load WAL encryption key from {}: {e}",
+                    key_file.display()
+                )
+            })
+        })
+        .transpose()?;
+
     Wal::spawn(
         ctx,
         codec,
         path.to_owned(),
+        storage,
+        encryption_key,
         registry.clone(),
         Span::current(),
     )
@@ -149,6 +259,7 @@ where
     .map_err(Into::into)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn spawn_sync_actor<Ctx, Codec>(
     ctx: Ctx,
     network: NetworkRef<Ctx>,
@@ -157,6 +268,8 @@ pub async fn spawn_sync_actor<Ctx, Codec>(
     sync_codec: Codec,
     config: &ValueSyncConfig,
     registry: &SharedRegistry,
+    tx_event: TxEvent<Ctx>,
+    clock: Arc<dyn Clock>,
 ) -> Result<Option<SyncRef<Ctx>>>
 where
     Ctx: Context,
@@ -173,12 +286,21 @@ where
     let params = SyncParams {
         status_update_interval: config.status_update_interval,
         request_timeout: config.request_timeout,
+        batch_synced_values: config.batch_synced_values,
+        decided_values_cache_size: config.decided_values_cache_size,
+        announce_decisions: config.announce_decisions,
     };
 
     let scoring_strategy = match config.scoring_strategy {
         malachitebft_config::ScoringStrategy::Ema => sync::scoring::Strategy::Ema,
     };
 
+    let peer_selection = match config.peer_selection {
+        malachitebft_config::PeerSelection::Uniform => sync::Selection::Uniform,
+        malachitebft_config::PeerSelection::ScoreWeighted => sync::Selection::ScoreWeighted,
+        malachitebft_config::PeerSelection::StakeWeighted => sync::Selection::StakeWeighted,
+    };
+
     let sync_config = sync::Config {
         enabled: config.enabled,
         max_request_size: config.max_request_size.as_u64() as usize,
@@ -186,9 +308,35 @@ where
         request_timeout: config.request_timeout,
         parallel_requests: config.parallel_requests,
         scoring_strategy,
+        peer_selection,
         inactive_threshold: (!config.inactive_threshold.is_zero())
             .then_some(config.inactive_threshold),
         batch_size: config.batch_size,
+        request_retry: Backoff::default().with_max_retries(config.request_max_retries),
+        backfill: config.backfill.enabled.then_some(sync::BackfillConfig {
+            target_min_height: config.backfill.target_min_height,
+            request_interval: config.backfill.request_interval,
+        }),
+        compression: config
+            .compression
+            .enabled
+            .then_some(sync::CompressionConfig {
+                threshold: config.compression.threshold.as_u64() as usize,
+            }),
+        request_limits: sync::RequestLimits {
+            max_range_size: config
+                .request_limits
+                .max_range_size
+                .unwrap_or(config.batch_size),
+            max_concurrent_requests: config.request_limits.max_concurrent_requests,
+            bandwidth_budget: (config.request_limits.bandwidth_budget.as_u64() > 0)
+                .then_some(config.request_limits.bandwidth_budget.as_u64() as usize),
+        },
+        retention: config.retention.enabled.then_some(sync::RetentionConfig {
+            retain_values: config.retention.retain_values,
+            max_retain_values: config.retention.max_retain_values,
+            interval: config.retention.interval,
+        }),
     };
 
     let metrics = sync::Metrics::register(registry, params.status_update_interval);
@@ -202,6 +350,8 @@ where
         sync_codec,
         sync_config,
         metrics,
+        tx_event,
+        clock,
         Span::current(),
     )
     .await?;
@@ -215,7 +365,11 @@ fn make_network_config(cfg: &ConsensusConfig, value_sync_cfg: &ValueSyncConfig)
 
     NetworkConfig {
         listen_addr: cfg.p2p.listen_addr.clone(),
+        additional_listen_addrs: cfg.p2p.additional_listen_addrs.clone(),
+        advertise_addrs: cfg.p2p.advertise_addrs.clone(),
         persistent_peers: cfg.p2p.persistent_peers.clone(),
+        dns_seeds: cfg.p2p.dns_seeds.clone(),
+        preferred_peers_file: cfg.p2p.preferred_peers_file.clone(),
         persistent_peers_only: cfg.p2p.persistent_peers_only,
         discovery: DiscoveryConfig {
             enabled: cfg.p2p.discovery.enabled,
@@ -233,12 +387,23 @@ fn make_network_config(cfg: &ConsensusConfig, value_sync_cfg: &ValueSyncConfig)
             max_connections_per_ip: cfg.p2p.discovery.max_connections_per_ip,
             max_connections_per_peer: cfg.p2p.discovery.max_connections_per_peer,
             ephemeral_connection_timeout: cfg.p2p.discovery.ephemeral_connection_timeout,
+            max_concurrent_dials: cfg.p2p.discovery.max_concurrent_dials,
+            max_concurrent_dials_per_ip: cfg.p2p.discovery.max_concurrent_dials_per_ip,
             dial_max_retries: cfg.p2p.discovery.dial_max_retries,
             request_max_retries: cfg.p2p.discovery.request_max_retries,
             connect_request_max_retries: cfg.p2p.discovery.connect_request_max_retries,
+            retry_backoff: make_backoff(&cfg.p2p.discovery.retry_backoff),
             max_peers_per_response: cfg.p2p.discovery.max_peers_per_response,
+            min_peers_to_idle: cfg.p2p.discovery.min_peers_to_idle,
+            rebootstrap_backoff: make_backoff(&cfg.p2p.discovery.rebootstrap_backoff),
+            mdns: cfg.p2p.discovery.mdns,
+            max_preferred_peers: cfg.p2p.discovery.max_preferred_peers,
+            preferred_peers_max_age: cfg.p2p.discovery.preferred_peers_max_age,
         },
         idle_connection_timeout: Duration::from_secs(15 * 60),
+        dial_timeout: cfg.p2p.dial_timeout,
+        upgrade_timeout: cfg.p2p.upgrade_timeout,
+        handshake_timeout: cfg.p2p.handshake_timeout,
         transport: network::TransportProtocol::from_multiaddr(&cfg.p2p.listen_addr).unwrap_or_else(
             || {
                 panic!(
@@ -251,6 +416,7 @@ fn make_network_config(cfg: &ConsensusConfig, value_sync_cfg: &ValueSyncConfig)
             config::PubSubProtocol::GossipSub(_) => network::PubSubProtocol::GossipSub,
             config::PubSubProtocol::Broadcast => network::PubSubProtocol::Broadcast,
         },
+        selective_gossip: cfg.p2p.selective_gossip,
         gossipsub: match cfg.p2p.protocol {
             config::PubSubProtocol::GossipSub(config) => GossipSubConfig {
                 mesh_n: config.mesh_n(),
@@ -260,6 +426,20 @@ fn make_network_config(cfg: &ConsensusConfig, value_sync_cfg: &ValueSyncConfig)
                 enable_peer_scoring: config.enable_peer_scoring(),
                 enable_explicit_peering: config.enable_explicit_peering(),
                 enable_flood_publish: config.enable_flood_publish(),
+                scoring: GossipSubScoringConfig {
+                    consensus_topic_weight: config.scoring().consensus_topic_weight,
+                    proposal_parts_topic_weight: config.scoring().proposal_parts_topic_weight,
+                    liveness_topic_weight: config.scoring().liveness_topic_weight,
+                    invalid_message_penalty: config.scoring().invalid_message_penalty,
+                    invalid_message_decay: config.scoring().invalid_message_decay,
+                    behaviour_penalty: config.scoring().behaviour_penalty,
+                    behaviour_penalty_decay: config.scoring().behaviour_penalty_decay,
+                    prune_backoff: config.scoring().prune_backoff,
+                    graft_flood_threshold: config.scoring().graft_flood_threshold,
+                    gossip_threshold: config.scoring().gossip_threshold,
+                    publish_threshold: config.scoring().publish_threshold,
+                    graylist_threshold: config.scoring().graylist_threshold,
+                },
             },
             config::PubSubProtocol::Broadcast => GossipSubConfig::default(),
         },
@@ -268,12 +448,50 @@ fn make_network_config(cfg: &ConsensusConfig, value_sync_cfg: &ValueSyncConfig)
         pubsub_max_size: cfg.p2p.pubsub_max_size.as_u64() as usize,
         enable_consensus: cfg.enabled,
         enable_sync: value_sync_cfg.enabled,
+        enable_announcements: value_sync_cfg.announce_decisions,
+        sync_compression: value_sync_cfg.compression.enabled.then_some(
+            network::SyncCompressionConfig {
+                threshold: value_sync_cfg.compression.threshold.as_u64() as usize,
+            },
+        ),
         protocol_names: network::ProtocolNames {
             consensus: cfg.p2p.protocol_names.consensus.clone(),
             discovery_kad: cfg.p2p.protocol_names.discovery_kad.clone(),
             discovery_regres: cfg.p2p.protocol_names.discovery_regres.clone(),
             sync: cfg.p2p.protocol_names.sync.clone(),
             validator_proof: cfg.p2p.protocol_names.validator_proof.clone(),
+            proposal_parts: cfg.p2p.protocol_names.proposal_parts.clone(),
+            node_info: cfg.p2p.protocol_names.node_info.clone(),
+        },
+        chain_id: None,
+        protocol_version: network_protocol_version(cfg.p2p.protocol_version),
+        min_protocol_version: cfg.p2p.min_protocol_version.map(network_protocol_version),
+        nat: network::NatConfig {
+            autonat: cfg.p2p.nat.autonat,
+            relay: cfg.p2p.nat.relay.clone(),
         },
+        rpc_signing: network::RpcSigningConfig {
+            sync: cfg.p2p.rpc_signing.sync,
+            validator_proof: cfg.p2p.rpc_signing.validator_proof,
+            max_age: cfg.p2p.rpc_signing.max_age,
+        },
+        peer_filter: None,
+    }
+}
+
+fn make_backoff(cfg: &BackoffConfig) -> Backoff {
+    Backoff {
+        initial_delay: cfg.initial_delay,
+        max_delay: cfg.max_delay,
+        multiplier: cfg.multiplier,
+        jitter: cfg.jitter,
+        max_retries: None,
+        max_elapsed_time: cfg.max_elapsed_time,
     }
 }
+
+fn network_protocol_version(
+    version: crate::config::ProtocolVersion,
+) -> malachitebft_network::ProtocolVersion {
+    malachitebft_network::ProtocolVersion::new(version.major, version.minor, version.patch)
+}
@@ -0,0 +1,262 @@
+//! Codec which delegates to an inner codec, while teeing the messages it decodes
+//! as pretty JSON to the logs or to a file, for debugging.
+
+use core::any::type_name;
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use tracing::{debug, warn};
+
+use crate::{Codec, HasEncodedLen};
+
+/// Where the decoded messages are teed by a [`DebuggingCodec`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DebugTarget {
+    /// Log the messages at the `DEBUG` level, with the `malachitebft_codec::debug` target
+    Log,
+    /// Append the messages to the given file, creating it if needed
+    File(PathBuf),
+}
+
+/// Codec which encodes and decodes messages with an `inner` codec, eg. the protobuf codec
+/// of the application, and tees the messages it decodes, rendered with a `debug` codec,
+/// eg. a JSON codec, to a [`DebugTarget`].
+///
+/// Only one in every `sample_every` decoded messages is teed, to keep the output manageable
+/// on a busy network. The output of the `debug` codec is pretty-printed if it is valid JSON.
+///
+/// Since it implements [`Codec`] for every message type supported by both codecs, it can be
+/// given to the engine in place of the inner codec, without changing the codecs of the application.
+///
+/// # Example
+/// ```rust,ignore
+/// let codec = DebuggingCodec::new(ProtobufCodec, JsonCodec, DebugTarget::Log, 10)?;
+/// ```
+#[derive(Clone)]
+pub struct DebuggingCodec<C, D> {
+    inner: C,
+    debug: D,
+    tee: Option<Arc<Tee>>,
+}
+
+impl<C, D> DebuggingCodec<C, D> {
+    /// Create a codec which tees one in every `sample_every` decoded messages to the given target.
+    ///
+    /// Fails if the target is a file which cannot be opened for appending.
+    pub fn new(inner: C, debug: D, target: DebugTarget, sample_every: u64) -> io::Result<Self> {
+        let sink = match target {
+            DebugTarget::Log => Sink::Log,
+            DebugTarget::File(path) => {
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                Sink::File(Mutex::new(file))
+            }
+        };
+
+        Ok(Self {
+            inner,
+            debug,
+            tee: Some(Arc::new(Tee {
+                sink,
+                sample_every: sample_every.max(1),
+                decoded: AtomicU64::new(0),
+            })),
+        })
+    }
+
+    /// Create a codec which only delegates to the inner codec, without teeing any message.
+    pub fn disabled(inner: C, debug: D) -> Self {
+        Self {
+            inner,
+            debug,
+            tee: None,
+        }
+    }
+
+    /// The inner codec, used to encode and decode the messages.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+}
+
+impl<T, C, D> Codec<T> for DebuggingCodec<C, D>
+where
+    C: Codec<T>,
+    D: Codec<T>,
+{
+    type Error = C::Error;
+
+    fn decode(&self, bytes: Bytes) -> Result<T, Self::Error> {
+        let msg = self.inner.decode(bytes)?;
+
+        if let Some(tee) = &self.tee {
+            tee.write(&self.debug, &msg);
+        }
+
+        Ok(msg)
+    }
+
+    fn encode(&self, msg: &T) -> Result<Bytes, Self::Error> {
+        self.inner.encode(msg)
+    }
+}
+
+impl<T, C, D> HasEncodedLen<T> for DebuggingCodec<C, D>
+where
+    C: HasEncodedLen<T>,
+    D: Codec<T>,
+{
+    fn encoded_len(&self, msg: &T) -> Result<usize, <Self as Codec<T>>::Error> {
+        self.inner.encoded_len(msg)
+    }
+}
+
+enum Sink {
+    Log,
+    File(Mutex<File>),
+}
+
+struct Tee {
+    sink: Sink,
+    sample_every: u64,
+    decoded: AtomicU64,
+}
+
+impl Tee {
+    fn write<T, D: Codec<T>>(&self, debug: &D, msg: &T) {
+        if !self
+            .decoded
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(self.sample_every)
+        {
+            return;
+        }
+
+        let message_type = type_name::<T>();
+
+        let rendered = match debug.encode(msg) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(%message_type, "Failed to render decoded message for debugging: {e}");
+                return;
+            }
+        };
+
+        let message = serde_json::from_slice::<serde_json::Value>(&rendered)
+            .unwrap_or_else(|_| String::from_utf8_lossy(&rendered).into_owned().into());
+
+        match &self.sink {
+            Sink::Log => {
+                let json = serde_json::to_string_pretty(&message).unwrap_or_default();
+                debug!(target: "malachitebft_codec::debug", %message_type, "Decoded message:\n{json}");
+            }
+            Sink::File(file) => {
+                let entry = serde_json::json!({ "type": message_type, "message": message });
+                let json = serde_json::to_string_pretty(&entry).unwrap_or_default();
+
+                let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                if let Err(e) = writeln!(file, "{json}") {
+                    warn!(%message_type, "Failed to write decoded message for debugging: {e}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Invalid;
+
+    impl core::fmt::Display for Invalid {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str("invalid message")
+        }
+    }
+
+    impl core::error::Error for Invalid {}
+
+    /// Wire codec of numbers as 8 big-endian bytes
+    #[derive(Clone)]
+    struct WireCodec;
+
+    impl Codec<u64> for WireCodec {
+        type Error = Invalid;
+
+        fn decode(&self, bytes: Bytes) -> Result<u64, Self::Error> {
+            let bytes: [u8; 8] = bytes.as_ref().try_into().map_err(|_| Invalid)?;
+            Ok(u64::from_be_bytes(bytes))
+        }
+
+        fn encode(&self, msg: &u64) -> Result<Bytes, Self::Error> {
+            Ok(Bytes::copy_from_slice(&msg.to_be_bytes()))
+        }
+    }
+
+    /// JSON codec of numbers as `{"value": <number>}`
+    #[derive(Clone)]
+    struct JsonCodec;
+
+    impl Codec<u64> for JsonCodec {
+        type Error = Invalid;
+
+        fn decode(&self, _bytes: Bytes) -> Result<u64, Self::Error> {
+            Err(Invalid)
+        }
+
+        fn encode(&self, msg: &u64) -> Result<Bytes, Self::Error> {
+            Ok(Bytes::from(format!(r#"{{"value":{msg}}}"#)))
+        }
+    }
+
+    fn teed(path: &std::path::Path) -> Vec<serde_json::Value> {
+        let contents = std::fs::read_to_string(path).unwrap();
+        serde_json::Deserializer::from_str(&contents)
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn delegates_to_inner_codec() {
+        let codec = DebuggingCodec::new(WireCodec, JsonCodec, DebugTarget::Log, 1).unwrap();
+
+        let bytes = codec.encode(&42).unwrap();
+        assert_eq!(bytes, WireCodec.encode(&42).unwrap());
+        assert_eq!(codec.decode(bytes).unwrap(), 42);
+        assert!(codec.decode(Bytes::from_static(b"{}")).is_err());
+    }
+
+    #[test]
+    fn tees_sampled_decoded_messages_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("messages.json");
+
+        let target = DebugTarget::File(path.clone());
+        let codec = DebuggingCodec::new(WireCodec, JsonCodec, target, 2).unwrap();
+
+        for n in 0..5_u64 {
+            codec.decode(WireCodec.encode(&n).unwrap()).unwrap();
+        }
+
+        // Encoded messages are not teed
+        codec.encode(&100).unwrap();
+
+        let teed = teed(&path);
+        let values: Vec<_> = teed.iter().map(|e| e["message"]["value"].clone()).collect();
+        assert_eq!(values, [0, 2, 4]);
+        assert_eq!(teed[0]["type"], "u64");
+    }
+
+    #[test]
+    fn disabled_does_not_tee() {
+        let codec = DebuggingCodec::disabled(WireCodec, JsonCodec);
+        assert!(codec.tee.is_none());
+        assert_eq!(codec.decode(WireCodec.encode(&7).unwrap()).unwrap(), 7);
+    }
+}
@@ -2,6 +2,9 @@ use core::error::Error;
 
 use bytes::Bytes;
 
+mod debug;
+pub use debug::{DebugTarget, DebuggingCodec};
+
 pub trait Codec<T>: Send + Sync + 'static {
     type Error: Error + Send;
 
@@ -1,14 +1,21 @@
 use core::fmt;
 use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 
 use bytesize::ByteSize;
-use multiaddr::Multiaddr;
+use multiaddr::{Multiaddr, Protocol};
 use serde::{Deserialize, Serialize};
 
 mod utils;
 
+mod validation;
+pub use validation::{validate, ConfigError, ConfigReport, ConfigWarning};
+
+mod reload;
+pub use reload::{reload, ConfigReload, ConfigSections, ReloadError};
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ProtocolNames {
     pub consensus: String,
@@ -20,6 +27,20 @@ pub struct ProtocolNames {
     pub sync: String,
 
     pub validator_proof: String,
+
+    #[serde(default = "default_proposal_parts_protocol")]
+    pub proposal_parts: String,
+
+    #[serde(default = "default_node_info_protocol")]
+    pub node_info: String,
+}
+
+fn default_proposal_parts_protocol() -> String {
+    "/malachitebft-proposal-parts/v1beta1".to_string()
+}
+
+fn default_node_info_protocol() -> String {
+    "/malachitebft-node-info/v1".to_string()
 }
 
 impl Default for ProtocolNames {
@@ -30,23 +51,120 @@ impl Default for ProtocolNames {
             discovery_regres: "/malachitebft-discovery/reqres/v1beta1".to_string(),
             sync: "/malachitebft-sync/v1beta1".to_string(),
             validator_proof: "/malachitebft-validator-proof/v1".to_string(),
+            proposal_parts: default_proposal_parts_protocol(),
+            node_info: default_node_info_protocol(),
+        }
+    }
+}
+
+/// Semantic version of the protocol spoken by a node, exchanged with peers when connecting.
+///
+/// Peers are compatible if they share the same major version, and if their version is not below
+/// the minimum version configured, which allows operators to coordinate network upgrades.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ProtocolVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl ProtocolVersion {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl Default for ProtocolVersion {
+    fn default() -> Self {
+        Self::new(1, 0, 0)
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for ProtocolVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid protocol version: {s}, expected <major>.<minor>.<patch>");
+
+        let mut parts = s
+            .split('.')
+            .map(|part| part.parse::<u64>().map_err(|_| invalid()));
+
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(major), Some(minor), Some(patch), None) => Ok(Self::new(major?, minor?, patch?)),
+            _ => Err(invalid()),
         }
     }
 }
 
+impl TryFrom<String> for ProtocolVersion {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<ProtocolVersion> for String {
+    fn from(version: ProtocolVersion) -> Self {
+        version.to_string()
+    }
+}
+
 /// P2P configuration options
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct P2pConfig {
     /// Address to listen for incoming connections
     pub listen_addr: Multiaddr,
 
-    /// List of nodes to keep persistent connections to
+    /// Additional addresses to listen on, eg. a loopback address for local tooling
+    /// alongside an external interface. They must use the same transport as `listen_addr`.
+    #[serde(default)]
+    pub additional_listen_addrs: Vec<Multiaddr>,
+
+    /// Addresses advertised to peers through identify and discovery, eg. the public address of
+    /// the node. When set, the listen addresses are no longer advertised, so that local-only
+    /// addresses are not shared with the network. When empty, the listen addresses are advertised.
+    #[serde(default)]
+    pub advertise_addrs: Vec<Multiaddr>,
+
+    /// List of nodes to keep persistent connections to.
+    ///
+    /// An address may end with `/p2p/<peer_id>` to pin the identity of the peer,
+    /// in which case connections to a peer presenting another identity are refused.
     pub persistent_peers: Vec<Multiaddr>,
 
+    /// DNS seeds to dial when discovery cannot find enough peers through the persistent peers,
+    /// eg. `/dnsaddr/seed.example.com` or `/dns4/seed.example.com/tcp/27000`.
+    /// The DNS names are resolved every time the seeds are dialed.
+    #[serde(default)]
+    pub dns_seeds: Vec<Multiaddr>,
+
     /// Only allow connections to/from persistent peers
     #[serde(default)]
     pub persistent_peers_only: bool,
 
+    /// Path to a file listing the peer ids of the only peers allowed to connect, one per line.
+    /// Connections with any other peer are refused. All peers are allowed when not set.
+    #[serde(default)]
+    pub allow_list_file: Option<PathBuf>,
+
+    /// Path to a file where the peers which served the node well are persisted, to be
+    /// preferred when selecting the outbound peers after a restart. Disabled when not set.
+    #[serde(default)]
+    pub preferred_peers_file: Option<PathBuf>,
+
     /// Peer discovery
     #[serde(default)]
     pub discovery: DiscoveryConfig,
@@ -54,6 +172,12 @@ pub struct P2pConfig {
     /// The type of pub-sub protocol to use for consensus
     pub protocol: PubSubProtocol,
 
+    /// Publish the votes on a topic reserved to the validators, whose membership is derived from
+    /// their verified validator proofs, while the proposals and certificates stay on the public
+    /// topics. Full nodes then no longer receive the votes, and follow the chain with value sync.
+    #[serde(default)]
+    pub selective_gossip: bool,
+
     /// The maximum size of messages to send over pub-sub
     pub pubsub_max_size: ByteSize,
 
@@ -63,23 +187,477 @@ pub struct P2pConfig {
     /// Protocol name configuration
     #[serde(default)]
     pub protocol_names: ProtocolNames,
+
+    /// Version of the protocol spoken by this node, advertised to peers when connecting.
+    /// Peers with a different major version are ignored, and their consensus messages refused.
+    #[serde(default)]
+    pub protocol_version: ProtocolVersion,
+
+    /// Minimum version of the protocol that peers must speak. Peers with an older version,
+    /// or which do not advertise their version, are ignored and their consensus messages refused.
+    /// All versions with the same major version are accepted when not set.
+    #[serde(default)]
+    pub min_protocol_version: Option<ProtocolVersion>,
+
+    /// Weights of the outbound priority lanes
+    #[serde(default)]
+    pub priority_lanes: PriorityLanesConfig,
+
+    /// Reputation-based peer disconnection policy
+    #[serde(default)]
+    pub reputation: ReputationConfig,
+
+    /// NAT traversal options
+    #[serde(default)]
+    pub nat: NatConfig,
+
+    /// Signing of the messages of the request-response protocols with the node key
+    #[serde(default)]
+    pub rpc_signing: RpcSigningConfig,
+
+    /// Time allowed to establish the connection to a peer when dialing it, eg. to complete
+    /// the TCP handshake, before negotiating the protocols spoken over the connection
+    #[serde(default = "p2p::default_dial_timeout", with = "humantime_serde")]
+    pub dial_timeout: Duration,
+
+    /// Time allowed to negotiate the security and multiplexing protocols of a connection,
+    /// once established, for both inbound and outbound connections
+    #[serde(default = "p2p::default_upgrade_timeout", with = "humantime_serde")]
+    pub upgrade_timeout: Duration,
+
+    /// Time allowed to a peer to complete the identify handshake once connected,
+    /// after which the connection is closed
+    #[serde(default = "p2p::default_handshake_timeout", with = "humantime_serde")]
+    pub handshake_timeout: Duration,
+}
+
+impl P2pConfig {
+    /// Check that the listen and advertised addresses and the addresses of the persistent peers,
+    /// DNS seeds and relays are supported, and that the GossipSub scoring parameters are consistent.
+    ///
+    /// The listen addresses must be made of an IPv4 or IPv6 host followed by a TCP or QUIC transport,
+    /// or be the path of a Unix domain socket (`/unix/<path>`) for nodes running on the same host,
+    /// all using the same transport, while the addresses of persistent peers may also use a DNS name
+    /// (`/dns`, `/dns4` or `/dns6`) as their host and may end with the peer id (`/p2p/<peer_id>`),
+    /// which is mandatory for relays. Advertised addresses may use a DNS name but neither a peer id
+    /// nor an unspecified IP address (`0.0.0.0` or `::`), which peers cannot dial.
+    /// DNS seeds must use a DNS name, and may also be a `/dnsaddr` name resolving to such addresses.
+    pub fn validate(&self) -> Result<(), String> {
+        validate_multiaddr(&self.listen_addr, false)
+            .map_err(|e| format!("invalid listen address '{}': {e}", self.listen_addr))?;
+
+        for addr in &self.additional_listen_addrs {
+            validate_multiaddr(addr, false)
+                .and_then(|()| {
+                    if addr == &self.listen_addr {
+                        Err("duplicate of the listen address".into())
+                    } else if !same_transport(addr, &self.listen_addr) {
+                        Err("expected the same transport as the listen address".into())
+                    } else {
+                        Ok(())
+                    }
+                })
+                .map_err(|e| format!("invalid additional listen address '{addr}': {e}"))?;
+        }
+
+        for addr in &self.advertise_addrs {
+            validate_advertise_addr(addr)
+                .map_err(|e| format!("invalid advertised address '{addr}': {e}"))?;
+        }
+
+        for addr in &self.persistent_peers {
+            validate_multiaddr(addr, true)
+                .map_err(|e| format!("invalid persistent peer address '{addr}': {e}"))?;
+        }
+
+        for addr in &self.dns_seeds {
+            validate_dns_seed(addr).map_err(|e| format!("invalid DNS seed '{addr}': {e}"))?;
+        }
+
+        for addr in &self.nat.relay {
+            validate_multiaddr(addr, true)
+                .and_then(|()| match addr.iter().last() {
+                    Some(Protocol::P2p(_)) => Ok(()),
+                    _ => Err("expected the peer id of the relay".into()),
+                })
+                .map_err(|e| format!("invalid relay address '{addr}': {e}"))?;
+        }
+
+        if let Some(min) = self.min_protocol_version {
+            if min > self.protocol_version {
+                return Err(format!(
+                    "the minimum protocol version {min} is above the protocol version {} of this node",
+                    self.protocol_version
+                ));
+            }
+        }
+
+        if self.rpc_signing.is_enabled() && self.rpc_signing.max_age.is_zero() {
+            return Err("invalid RPC signing configuration: max_age must be positive".into());
+        }
+
+        if let PubSubProtocol::GossipSub(gossipsub) = &self.protocol {
+            gossipsub
+                .scoring()
+                .validate()
+                .map_err(|e| format!("invalid gossipsub scoring configuration: {e}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_multiaddr(addr: &Multiaddr, allow_dns: bool) -> Result<(), String> {
+    let mut protocols = addr.iter();
+
+    match protocols.next() {
+        Some(Protocol::Ip4(_) | Protocol::Ip6(_)) => {}
+        Some(Protocol::Dns(_) | Protocol::Dns4(_) | Protocol::Dns6(_)) if allow_dns => {}
+        Some(Protocol::Dns(_) | Protocol::Dns4(_) | Protocol::Dns6(_)) => {
+            return Err("a DNS name cannot be used here, expected an IPv4 or IPv6 address".into())
+        }
+        // A Unix domain socket is its own transport
+        Some(Protocol::Unix(_)) => return validate_peer_id_suffix(protocols, allow_dns),
+        _ => {
+            return Err(
+                "expected an IPv4 address, an IPv6 address, a DNS name or a Unix socket path"
+                    .into(),
+            )
+        }
+    }
+
+    match protocols.next() {
+        Some(Protocol::Tcp(_)) => {}
+        Some(Protocol::Udp(_)) => match protocols.next() {
+            Some(Protocol::QuicV1 | Protocol::Quic) => {}
+            _ => return Err("expected a QUIC transport after the UDP port".into()),
+        },
+        _ => return Err("expected a TCP or QUIC transport".into()),
+    }
+
+    validate_peer_id_suffix(protocols, allow_dns)
+}
+
+/// Check that the remaining protocols of an address are at most a peer id, if allowed.
+fn validate_peer_id_suffix<'a>(
+    mut protocols: impl Iterator<Item = Protocol<'a>>,
+    allow_peer_id: bool,
+) -> Result<(), String> {
+    match protocols.next() {
+        None => Ok(()),
+        Some(Protocol::P2p(_)) if allow_peer_id && protocols.next().is_none() => Ok(()),
+        Some(protocol) => Err(format!("unexpected protocol '{protocol}'")),
+    }
+}
+
+fn validate_advertise_addr(addr: &Multiaddr) -> Result<(), String> {
+    validate_multiaddr(addr, true)?;
+
+    for protocol in addr.iter() {
+        match protocol {
+            Protocol::Ip4(ip) if ip.is_unspecified() => {
+                return Err("the unspecified address cannot be advertised".into())
+            }
+            Protocol::Ip6(ip) if ip.is_unspecified() => {
+                return Err("the unspecified address cannot be advertised".into())
+            }
+            Protocol::P2p(_) => return Err("unexpected peer id".into()),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether both addresses use the same transport, ie. all TCP, all QUIC or all Unix domain sockets.
+fn same_transport(a: &Multiaddr, b: &Multiaddr) -> bool {
+    let is_tcp = |addr: &Multiaddr| addr.iter().any(|p| matches!(p, Protocol::Tcp(_)));
+    let is_unix = |addr: &Multiaddr| addr.iter().any(|p| matches!(p, Protocol::Unix(_)));
+    is_tcp(a) == is_tcp(b) && is_unix(a) == is_unix(b)
+}
+
+fn validate_dns_seed(addr: &Multiaddr) -> Result<(), String> {
+    let mut protocols = addr.iter();
+
+    match protocols.next() {
+        Some(Protocol::Dnsaddr(_)) => match protocols.next() {
+            None => Ok(()),
+            Some(Protocol::P2p(_)) if protocols.next().is_none() => Ok(()),
+            Some(protocol) => Err(format!("unexpected protocol '{protocol}'")),
+        },
+        Some(Protocol::Dns(_) | Protocol::Dns4(_) | Protocol::Dns6(_)) => {
+            validate_multiaddr(addr, true)
+        }
+        _ => Err("expected a DNS name".into()),
+    }
 }
 
 impl Default for P2pConfig {
     fn default() -> Self {
         P2pConfig {
             listen_addr: Multiaddr::empty(),
+            additional_listen_addrs: vec![],
+            advertise_addrs: vec![],
             persistent_peers: vec![],
+            dns_seeds: vec![],
             persistent_peers_only: false,
+            allow_list_file: None,
+            preferred_peers_file: None,
             discovery: Default::default(),
             protocol: Default::default(),
+            selective_gossip: false,
             rpc_max_size: ByteSize::mib(10),
             pubsub_max_size: ByteSize::mib(4),
             protocol_names: Default::default(),
+            protocol_version: Default::default(),
+            min_protocol_version: None,
+            priority_lanes: Default::default(),
+            reputation: Default::default(),
+            nat: Default::default(),
+            rpc_signing: Default::default(),
+            dial_timeout: p2p::default_dial_timeout(),
+            upgrade_timeout: p2p::default_upgrade_timeout(),
+            handshake_timeout: p2p::default_handshake_timeout(),
+        }
+    }
+}
+
+mod p2p {
+    use std::time::Duration;
+
+    pub fn default_dial_timeout() -> Duration {
+        Duration::from_secs(5)
+    }
+
+    pub fn default_upgrade_timeout() -> Duration {
+        Duration::from_secs(10)
+    }
+
+    pub fn default_handshake_timeout() -> Duration {
+        Duration::from_secs(10)
+    }
+}
+
+/// Weights of the priority lanes used by the network actor for outbound messages.
+///
+/// Lanes are served in order of priority (votes, proposals, sync responses, status).
+/// When several lanes have pending messages, each lane can send up to its weight
+/// in messages before lower priority lanes get their turn, so that votes are never
+/// starved behind large proposal parts while lower priority lanes still make progress.
+///
+/// A weight of zero is treated as one.
+///
+/// Each lane holds at most `capacity` messages. When a lane is full, the other lanes than
+/// the vote lane drop their oldest message which is neither a proposal part nor a reply to
+/// a request. If there is none, the new message waits for room to be made.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriorityLanesConfig {
+    /// Weight of the lane for votes and liveness messages
+    #[serde(default = "priority_lanes::default_votes")]
+    pub votes: u32,
+
+    /// Weight of the lane for proposals and proposal parts
+    #[serde(default = "priority_lanes::default_proposals")]
+    pub proposals: u32,
+
+    /// Weight of the lane for sync responses
+    #[serde(default = "priority_lanes::default_sync_responses")]
+    pub sync_responses: u32,
+
+    /// Weight of the lane for status updates
+    #[serde(default = "priority_lanes::default_status")]
+    pub status: u32,
+
+    /// Maximum number of messages queued in each lane.
+    /// A capacity of zero is treated as one.
+    #[serde(default = "priority_lanes::default_capacity")]
+    pub capacity: usize,
+}
+
+impl Default for PriorityLanesConfig {
+    fn default() -> Self {
+        Self {
+            votes: priority_lanes::default_votes(),
+            proposals: priority_lanes::default_proposals(),
+            sync_responses: priority_lanes::default_sync_responses(),
+            status: priority_lanes::default_status(),
+            capacity: priority_lanes::default_capacity(),
+        }
+    }
+}
+
+mod priority_lanes {
+    pub fn default_votes() -> u32 {
+        8
+    }
+
+    pub fn default_proposals() -> u32 {
+        4
+    }
+
+    pub fn default_sync_responses() -> u32 {
+        2
+    }
+
+    pub fn default_status() -> u32 {
+        1
+    }
+
+    pub fn default_capacity() -> usize {
+        1024
+    }
+}
+
+/// Reputation-based peer disconnection policy.
+///
+/// The reputation of a peer combines its sync score with penalties for
+/// invalid messages and rate limit violations:
+///
+/// `reputation = sync_score_weight * (sync_score - 0.5) - penalties`
+///
+/// Penalties decay exponentially with the configured half-life, so that peers
+/// recover from occasional misbehavior. A peer whose reputation falls below
+/// the ban threshold is disconnected and banned for the configured duration.
+/// Persistent peers are never banned.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReputationConfig {
+    /// Enable the reputation-based disconnection policy
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Peers with a reputation below this threshold are banned
+    #[serde(default = "reputation::default_ban_threshold")]
+    pub ban_threshold: f64,
+
+    /// How long a peer stays banned
+    #[serde(default = "reputation::default_ban_duration", with = "humantime_serde")]
+    pub ban_duration: Duration,
+
+    /// Weight of the sync score, which lies in the `0.0..=1.0` range
+    #[serde(default = "reputation::default_sync_score_weight")]
+    pub sync_score_weight: f64,
+
+    /// Penalty for a message which cannot be decoded
+    #[serde(default = "reputation::default_invalid_message_penalty")]
+    pub invalid_message_penalty: f64,
+
+    /// Penalty for a rate limit violation
+    #[serde(default = "reputation::default_rate_limit_penalty")]
+    pub rate_limit_penalty: f64,
+
+    /// Time after which accumulated penalties are halved
+    #[serde(
+        default = "reputation::default_penalty_half_life",
+        with = "humantime_serde"
+    )]
+    pub penalty_half_life: Duration,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ban_threshold: reputation::default_ban_threshold(),
+            ban_duration: reputation::default_ban_duration(),
+            sync_score_weight: reputation::default_sync_score_weight(),
+            invalid_message_penalty: reputation::default_invalid_message_penalty(),
+            rate_limit_penalty: reputation::default_rate_limit_penalty(),
+            penalty_half_life: reputation::default_penalty_half_life(),
+        }
+    }
+}
+
+mod reputation {
+    use std::time::Duration;
+
+    pub fn default_ban_threshold() -> f64 {
+        -100.0
+    }
+
+    pub fn default_ban_duration() -> Duration {
+        Duration::from_secs(10 * 60)
+    }
+
+    pub fn default_sync_score_weight() -> f64 {
+        20.0
+    }
+
+    pub fn default_invalid_message_penalty() -> f64 {
+        10.0
+    }
+
+    pub fn default_rate_limit_penalty() -> f64 {
+        20.0
+    }
+
+    pub fn default_penalty_half_life() -> Duration {
+        Duration::from_secs(5 * 60)
+    }
+}
+
+/// NAT traversal options, for nodes which are not directly reachable from the Internet.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NatConfig {
+    /// Probe whether the node is publicly reachable with AutoNAT, using the connected peers,
+    /// and advertise its external addresses to its peers once they are confirmed
+    #[serde(default)]
+    pub autonat: bool,
+
+    /// Relays through which the node can be reached when it is behind a NAT,
+    /// each including the peer id of the relay (`/p2p/<peer_id>`).
+    /// When non-empty, the node listens for relayed connections through these relays
+    /// and attempts to upgrade them to direct connections through hole punching (DCUtR)
+    #[serde(default)]
+    pub relay: Vec<Multiaddr>,
+}
+
+/// Signing of the messages of the request-response protocols with the node key.
+///
+/// These messages are otherwise only authenticated by the transport, which is not enough
+/// when TLS or QUIC is terminated at a proxy in front of the node. Signed messages carry
+/// a nonce and an expiry time, which protect against replays. A protocol must be signed
+/// by either all the nodes of the network or none of them, as unsigned messages are
+/// rejected by nodes which expect them to be signed, and conversely.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RpcSigningConfig {
+    /// Sign the sync requests and responses
+    #[serde(default)]
+    pub sync: bool,
+
+    /// Sign the validator proofs sent to peers
+    #[serde(default)]
+    pub validator_proof: bool,
+
+    /// Time after which a signed message expires, and is rejected by the receiver
+    #[serde(default = "rpc_signing::default_max_age", with = "humantime_serde")]
+    pub max_age: Duration,
+}
+
+impl RpcSigningConfig {
+    /// Whether messages are signed for at least one protocol
+    pub fn is_enabled(&self) -> bool {
+        self.sync || self.validator_proof
+    }
+}
+
+impl Default for RpcSigningConfig {
+    fn default() -> Self {
+        Self {
+            sync: false,
+            validator_proof: false,
+            max_age: rpc_signing::default_max_age(),
         }
     }
 }
 
+mod rpc_signing {
+    use std::time::Duration;
+
+    pub fn default_max_age() -> Duration {
+        Duration::from_secs(30)
+    }
+}
+
 /// Peer Discovery configuration options
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DiscoveryConfig {
@@ -118,6 +696,14 @@ pub struct DiscoveryConfig {
     #[serde(with = "humantime_serde")]
     pub ephemeral_connection_timeout: Duration,
 
+    /// Maximum number of dials in progress at once, the others wait in the dial queue
+    #[serde(default = "discovery::default_max_concurrent_dials")]
+    pub max_concurrent_dials: usize,
+
+    /// Maximum number of dials in progress at once to the same IP address
+    #[serde(default = "discovery::default_max_concurrent_dials_per_ip")]
+    pub max_concurrent_dials_per_ip: usize,
+
     #[serde(default = "discovery::default_dial_max_retries")]
     pub dial_max_retries: usize,
 
@@ -127,9 +713,38 @@ pub struct DiscoveryConfig {
     #[serde(default = "discovery::default_connect_request_max_retries")]
     pub connect_request_max_retries: usize,
 
+    /// Backoff between retries of dials and requests
+    #[serde(default)]
+    pub retry_backoff: BackoffConfig,
+
     /// Maximum number of peer records to process or send per peers request/response.
     #[serde(default = "discovery::default_max_peers_per_response")]
     pub max_peers_per_response: usize,
+
+    /// Minimum number of connected peers for discovery to stop once done.
+    /// With fewer peers, discovery bootstraps again, dialing the DNS seeds.
+    #[serde(default = "discovery::default_min_peers_to_idle")]
+    pub min_peers_to_idle: usize,
+
+    /// Backoff between bootstrap attempts while fewer than `min_peers_to_idle` peers are connected
+    #[serde(default = "discovery::default_rebootstrap_backoff")]
+    pub rebootstrap_backoff: BackoffConfig,
+
+    /// Discover the peers on the local network with mDNS, in addition to the bootstrap nodes,
+    /// for zero-config local setups and devnets. Only used when discovery is enabled.
+    #[serde(default)]
+    pub mdns: bool,
+
+    /// Maximum number of peers to persist to `consensus.p2p.preferred_peers_file`
+    #[serde(default = "discovery::default_max_preferred_peers")]
+    pub max_preferred_peers: usize,
+
+    /// Persisted peers which the node has not been connected to for longer are forgotten
+    #[serde(
+        default = "discovery::default_preferred_peers_max_age",
+        with = "humantime_serde"
+    )]
+    pub preferred_peers_max_age: Duration,
 }
 
 impl Default for DiscoveryConfig {
@@ -143,15 +758,27 @@ impl Default for DiscoveryConfig {
             max_connections_per_ip: discovery::default_num_inbound_peers(),
             max_connections_per_peer: discovery::default_max_connections_per_peer(),
             ephemeral_connection_timeout: Duration::from_secs(60),
+            max_concurrent_dials: discovery::default_max_concurrent_dials(),
+            max_concurrent_dials_per_ip: discovery::default_max_concurrent_dials_per_ip(),
             dial_max_retries: discovery::default_dial_max_retries(),
             request_max_retries: discovery::default_request_max_retries(),
             connect_request_max_retries: discovery::default_connect_request_max_retries(),
+            retry_backoff: BackoffConfig::default(),
             max_peers_per_response: discovery::default_max_peers_per_response(),
+            min_peers_to_idle: discovery::default_min_peers_to_idle(),
+            rebootstrap_backoff: discovery::default_rebootstrap_backoff(),
+            mdns: false,
+            max_preferred_peers: discovery::default_max_preferred_peers(),
+            preferred_peers_max_age: discovery::default_preferred_peers_max_age(),
         }
     }
 }
 
 mod discovery {
+    use std::time::Duration;
+
+    use super::BackoffConfig;
+
     pub fn default_num_outbound_peers() -> usize {
         50
     }
@@ -164,6 +791,14 @@ mod discovery {
         5
     }
 
+    pub fn default_max_concurrent_dials() -> usize {
+        20
+    }
+
+    pub fn default_max_concurrent_dials_per_ip() -> usize {
+        4
+    }
+
     pub fn default_dial_max_retries() -> usize {
         5
     }
@@ -179,6 +814,61 @@ mod discovery {
     pub fn default_max_peers_per_response() -> usize {
         100
     }
+
+    pub fn default_min_peers_to_idle() -> usize {
+        1
+    }
+
+    pub fn default_max_preferred_peers() -> usize {
+        32
+    }
+
+    pub fn default_preferred_peers_max_age() -> Duration {
+        Duration::from_secs(7 * 24 * 60 * 60)
+    }
+
+    pub fn default_rebootstrap_backoff() -> BackoffConfig {
+        BackoffConfig {
+            initial_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(300),
+            ..BackoffConfig::default()
+        }
+    }
+}
+
+/// Exponential backoff with jitter between retries
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackoffConfig {
+    /// Delay before the first retry
+    #[serde(with = "humantime_serde")]
+    pub initial_delay: Duration,
+
+    /// Maximum delay between two retries
+    #[serde(with = "humantime_serde")]
+    pub max_delay: Duration,
+
+    /// Factor by which the delay grows after each retry
+    pub multiplier: f64,
+
+    /// Fraction of the delay to randomize, between 0 and 1
+    pub jitter: f64,
+
+    /// Maximum total time spent waiting between retries (unbounded if not set)
+    #[serde(with = "humantime_serde")]
+    pub max_elapsed_time: Option<Duration>,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_elapsed_time: None,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -251,10 +941,28 @@ pub enum TransportProtocol {
 }
 
 impl TransportProtocol {
+    /// Build the address at which a node listening on the given host and port
+    /// can be reached with this transport protocol.
+    ///
+    /// The host can be an IPv4 address, an IPv6 address, optionally enclosed in brackets,
+    /// or a DNS name, which will be resolved to both IPv4 and IPv6 addresses when dialing.
+    ///
+    /// # Panics
+    /// If the port does not fit in 16 bits.
     pub fn multiaddr(&self, host: &str, port: usize) -> Multiaddr {
+        let port = u16::try_from(port).expect("port must fit in 16 bits");
+
+        let host = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+            Ok(IpAddr::V4(ip)) => Protocol::Ip4(ip),
+            Ok(IpAddr::V6(ip)) => Protocol::Ip6(ip),
+            Err(_) => Protocol::Dns(host.to_string().into()),
+        };
+
+        let addr = Multiaddr::empty().with(host);
+
         match self {
-            Self::Tcp => format!("/ip4/{host}/tcp/{port}").parse().unwrap(),
-            Self::Quic => format!("/ip4/{host}/udp/{port}/quic-v1").parse().unwrap(),
+            Self::Tcp => addr.with(Protocol::Tcp(port)),
+            Self::Quic => addr.with(Protocol::Udp(port)).with(Protocol::QuicV1),
         }
     }
 }
@@ -320,6 +1028,9 @@ pub struct GossipSubConfig {
     /// Enable flood publishing.
     /// When enabled the publisher sends the messages to all known peers, not just mesh peers.
     enable_flood_publish: bool,
+
+    /// Behavioural scoring parameters, only used when peer scoring is enabled
+    scoring: GossipSubScoringConfig,
 }
 
 impl Default for GossipSubConfig {
@@ -348,6 +1059,7 @@ impl GossipSubConfig {
             enable_peer_scoring,
             enable_explicit_peering,
             enable_flood_publish,
+            scoring: GossipSubScoringConfig::default(),
         };
 
         result.adjust();
@@ -410,22 +1122,206 @@ impl GossipSubConfig {
     pub fn enable_flood_publish(&self) -> bool {
         self.enable_flood_publish
     }
-}
-
-mod gossipsub {
-    use super::utils::bool_from_anything;
 
-    fn default_enable_peer_scoring() -> bool {
-        false
+    /// Set the behavioural scoring parameters.
+    pub fn with_scoring(mut self, scoring: GossipSubScoringConfig) -> Self {
+        self.scoring = scoring;
+        self
     }
 
-    fn default_enable_explicit_peering() -> bool {
-        false
+    pub fn scoring(&self) -> &GossipSubScoringConfig {
+        &self.scoring
     }
+}
 
-    fn default_enable_flood_publish() -> bool {
-        true
-    }
+/// Behavioural peer scoring parameters for GossipSub.
+///
+/// On top of the score derived from the type of a peer, peers are penalized for delivering
+/// invalid messages and for misbehaving in mesh maintenance, eg. by grafting back too early
+/// after having been pruned. Peers whose score falls below the thresholds are cut off
+/// from gossip, publishing and eventually all communication.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GossipSubScoringConfig {
+    /// Weight of the consensus topic in the peer score,
+    /// also used for the votes topic when selective gossip is enabled
+    #[serde(default = "gossipsub::default_consensus_topic_weight")]
+    pub consensus_topic_weight: f64,
+
+    /// Weight of the proposal parts topic in the peer score
+    #[serde(default = "gossipsub::default_proposal_parts_topic_weight")]
+    pub proposal_parts_topic_weight: f64,
+
+    /// Weight of the liveness topic in the peer score
+    #[serde(default = "gossipsub::default_liveness_topic_weight")]
+    pub liveness_topic_weight: f64,
+
+    /// Penalty for invalid messages, applied to the square of the number of
+    /// invalid messages delivered by a peer on a topic, times the topic weight
+    #[serde(default = "gossipsub::default_invalid_message_penalty")]
+    pub invalid_message_penalty: f64,
+
+    /// Time after which the invalid message penalties of a peer have decayed away
+    #[serde(
+        default = "gossipsub::default_invalid_message_decay",
+        with = "humantime_serde"
+    )]
+    pub invalid_message_decay: Duration,
+
+    /// Penalty for mesh maintenance misbehaviour, eg. grafting during the prune backoff,
+    /// applied to the square of the number of offences
+    #[serde(default = "gossipsub::default_behaviour_penalty")]
+    pub behaviour_penalty: f64,
+
+    /// Time after which the behaviour penalties of a peer have decayed away
+    #[serde(
+        default = "gossipsub::default_behaviour_penalty_decay",
+        with = "humantime_serde"
+    )]
+    pub behaviour_penalty_decay: Duration,
+
+    /// How long a pruned peer must wait before grafting again
+    #[serde(default = "gossipsub::default_prune_backoff", with = "humantime_serde")]
+    pub prune_backoff: Duration,
+
+    /// Grafts received within this time after a prune are penalized as floods
+    #[serde(
+        default = "gossipsub::default_graft_flood_threshold",
+        with = "humantime_serde"
+    )]
+    pub graft_flood_threshold: Duration,
+
+    /// Peers with a score below this threshold do not receive gossip
+    #[serde(default = "gossipsub::default_gossip_threshold")]
+    pub gossip_threshold: f64,
+
+    /// Messages published by peers with a score below this threshold are not forwarded
+    #[serde(default = "gossipsub::default_publish_threshold")]
+    pub publish_threshold: f64,
+
+    /// Peers with a score below this threshold are ignored entirely
+    #[serde(default = "gossipsub::default_graylist_threshold")]
+    pub graylist_threshold: f64,
+}
+
+impl Default for GossipSubScoringConfig {
+    fn default() -> Self {
+        Self {
+            consensus_topic_weight: gossipsub::default_consensus_topic_weight(),
+            proposal_parts_topic_weight: gossipsub::default_proposal_parts_topic_weight(),
+            liveness_topic_weight: gossipsub::default_liveness_topic_weight(),
+            invalid_message_penalty: gossipsub::default_invalid_message_penalty(),
+            invalid_message_decay: gossipsub::default_invalid_message_decay(),
+            behaviour_penalty: gossipsub::default_behaviour_penalty(),
+            behaviour_penalty_decay: gossipsub::default_behaviour_penalty_decay(),
+            prune_backoff: gossipsub::default_prune_backoff(),
+            graft_flood_threshold: gossipsub::default_graft_flood_threshold(),
+            gossip_threshold: gossipsub::default_gossip_threshold(),
+            publish_threshold: gossipsub::default_publish_threshold(),
+            graylist_threshold: gossipsub::default_graylist_threshold(),
+        }
+    }
+}
+
+impl GossipSubScoringConfig {
+    /// Check that the scoring parameters are consistent.
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [
+            self.consensus_topic_weight,
+            self.proposal_parts_topic_weight,
+            self.liveness_topic_weight,
+        ];
+
+        if weights.iter().any(|weight| *weight < 0.0) {
+            return Err("topic weights must be non-negative".to_string());
+        }
+
+        if self.invalid_message_penalty < 0.0 || self.behaviour_penalty < 0.0 {
+            return Err("penalties must be non-negative".to_string());
+        }
+
+        if self.invalid_message_decay.is_zero() || self.behaviour_penalty_decay.is_zero() {
+            return Err("penalty decay times must be non-zero".to_string());
+        }
+
+        if self.gossip_threshold > 0.0
+            || self.publish_threshold > self.gossip_threshold
+            || self.graylist_threshold > self.publish_threshold
+        {
+            return Err(
+                "thresholds must satisfy graylist_threshold <= publish_threshold <= gossip_threshold <= 0"
+                    .to_string(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+mod gossipsub {
+    use std::time::Duration;
+
+    use super::utils::bool_from_anything;
+    use super::GossipSubScoringConfig;
+
+    pub fn default_consensus_topic_weight() -> f64 {
+        1.0
+    }
+
+    pub fn default_proposal_parts_topic_weight() -> f64 {
+        1.0
+    }
+
+    pub fn default_liveness_topic_weight() -> f64 {
+        0.5
+    }
+
+    pub fn default_invalid_message_penalty() -> f64 {
+        100.0
+    }
+
+    pub fn default_invalid_message_decay() -> Duration {
+        Duration::from_secs(10 * 60)
+    }
+
+    pub fn default_behaviour_penalty() -> f64 {
+        10.0
+    }
+
+    pub fn default_behaviour_penalty_decay() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    pub fn default_prune_backoff() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    pub fn default_graft_flood_threshold() -> Duration {
+        Duration::from_secs(10)
+    }
+
+    pub fn default_gossip_threshold() -> f64 {
+        -500.0
+    }
+
+    pub fn default_publish_threshold() -> f64 {
+        -1000.0
+    }
+
+    pub fn default_graylist_threshold() -> f64 {
+        -2000.0
+    }
+
+    fn default_enable_peer_scoring() -> bool {
+        false
+    }
+
+    fn default_enable_explicit_peering() -> bool {
+        false
+    }
+
+    fn default_enable_flood_publish() -> bool {
+        true
+    }
 
     #[derive(serde::Deserialize)]
     pub struct RawConfig {
@@ -452,6 +1348,8 @@ mod gossipsub {
             deserialize_with = "bool_from_anything"
         )]
         enable_flood_publish: bool,
+        #[serde(default)]
+        scoring: GossipSubScoringConfig,
     }
 
     impl From<RawConfig> for super::GossipSubConfig {
@@ -465,6 +1363,7 @@ mod gossipsub {
                 raw.enable_explicit_peering,
                 raw.enable_flood_publish,
             )
+            .with_scoring(raw.scoring)
         }
     }
 }
@@ -596,12 +1495,52 @@ pub struct ValueSyncConfig {
     #[serde(default)]
     pub scoring_strategy: ScoringStrategy,
 
+    /// Strategy for selecting the peer to request values from
+    #[serde(default)]
+    pub peer_selection: PeerSelection,
+
     /// Threshold for considering a peer inactive
     #[serde(with = "humantime_serde")]
     pub inactive_threshold: Duration,
 
     /// Maximum number of decided values to request in a single batch
     pub batch_size: usize,
+
+    /// Maximum number of times a range of values is re-requested from another peer
+    /// after a failed request (unbounded if not set)
+    #[serde(default)]
+    pub request_max_retries: Option<usize>,
+
+    /// Backfill of decided values below the earliest height retained by this node
+    #[serde(default)]
+    pub backfill: BackfillConfig,
+
+    /// Compression of the responses sent to peers which support it
+    #[serde(default)]
+    pub compression: SyncCompressionConfig,
+
+    /// Limits on the requests served to each peer
+    #[serde(default)]
+    pub request_limits: SyncRequestLimitsConfig,
+
+    /// Notifications of the height below which the application can prune its decided values
+    #[serde(default)]
+    pub retention: SyncRetentionConfig,
+
+    /// Process the values of each response as a batch, with a single request to the application,
+    /// and verify their commit certificates at once
+    #[serde(default)]
+    pub batch_synced_values: bool,
+
+    /// Number of recently decided values to keep in memory to serve the requests of peers
+    /// without asking the application (0 to disable)
+    #[serde(default = "default_decided_values_cache_size")]
+    pub decided_values_cache_size: usize,
+
+    /// Announce the values decided by this node to its peers as soon as they are committed,
+    /// and request the values announced by the peers, without waiting for their status updates
+    #[serde(default)]
+    pub announce_decisions: bool,
 }
 
 impl Default for ValueSyncConfig {
@@ -614,8 +1553,128 @@ impl Default for ValueSyncConfig {
             max_response_size: ByteSize::mib(10),
             parallel_requests: 5,
             scoring_strategy: ScoringStrategy::default(),
+            peer_selection: PeerSelection::default(),
             inactive_threshold: Duration::from_secs(60),
             batch_size: 5,
+            request_max_retries: None,
+            backfill: BackfillConfig::default(),
+            compression: SyncCompressionConfig::default(),
+            request_limits: SyncRequestLimitsConfig::default(),
+            retention: SyncRetentionConfig::default(),
+            batch_synced_values: false,
+            decided_values_cache_size: default_decided_values_cache_size(),
+            announce_decisions: false,
+        }
+    }
+}
+
+/// Backfill configuration options
+///
+/// When enabled, a node started from a snapshot fetches the values decided below
+/// the earliest height in its store from its peers, down to `target_min_height`.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackfillConfig {
+    /// Enable backfill
+    pub enabled: bool,
+
+    /// Lowest height to backfill
+    pub target_min_height: u64,
+
+    /// Interval between two backfill requests
+    #[serde(with = "humantime_serde")]
+    pub request_interval: Duration,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_min_height: 1,
+            request_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Compression of the value sync responses, negotiated with each peer.
+///
+/// Peers which do not support compression are sent uncompressed responses.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncCompressionConfig {
+    /// Enable compression of the responses
+    pub enabled: bool,
+
+    /// Responses up to this size are sent uncompressed
+    pub threshold: ByteSize,
+}
+
+impl Default for SyncCompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: ByteSize::kib(16),
+        }
+    }
+}
+
+/// Limits on the value sync requests served to each peer, so that a peer cannot
+/// make this node send it large amounts of data by requesting values repeatedly.
+///
+/// Requests for more than `max_range_size` values are served only up to that many values,
+/// other requests exceeding these limits are answered with an empty response.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncRequestLimitsConfig {
+    /// Maximum number of values served for a single request (defaults to `batch_size` if not set)
+    pub max_range_size: Option<usize>,
+
+    /// Maximum number of requests from the same peer being served at once
+    pub max_concurrent_requests: usize,
+
+    /// Maximum amount of values sent to each peer per second (0 for unlimited)
+    pub bandwidth_budget: ByteSize,
+}
+
+impl Default for SyncRequestLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_range_size: None,
+            max_concurrent_requests: 10,
+            bandwidth_budget: ByteSize::mib(100),
+        }
+    }
+}
+
+/// Retention policy of the decided values, from which the application is periodically
+/// notified of the height below which it can prune them.
+///
+/// Values still needed by peers which are behind are retained so that they can catch up
+/// via sync, up to `max_retain_values`, so that storage is reclaimed safely network-wide.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncRetentionConfig {
+    /// Enable the notifications
+    pub enabled: bool,
+
+    /// Minimum number of latest decided values to retain
+    pub retain_values: u64,
+
+    /// Maximum number of latest decided values to retain for peers which are behind
+    pub max_retain_values: u64,
+
+    /// Interval between two computations of the height below which values can be pruned
+    #[serde(with = "humantime_serde")]
+    pub interval: Duration,
+}
+
+impl Default for SyncRetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retain_values: 1000,
+            max_retain_values: 100_000,
+            interval: Duration::from_secs(60),
         }
     }
 }
@@ -646,10 +1705,52 @@ impl FromStr for ScoringStrategy {
     }
 }
 
+/// Strategy for selecting the peer to request values from, among the peers which have them
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerSelection {
+    /// Every peer is selected with the same probability
+    Uniform,
+    /// Peers are selected proportionally to their score
+    #[default]
+    ScoreWeighted,
+    /// Validators are selected proportionally to their voting power
+    StakeWeighted,
+}
+
+impl PeerSelection {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Uniform => "uniform",
+            Self::ScoreWeighted => "score_weighted",
+            Self::StakeWeighted => "stake_weighted",
+        }
+    }
+}
+
+impl FromStr for PeerSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(Self::Uniform),
+            "score_weighted" => Ok(Self::ScoreWeighted),
+            "stake_weighted" => Ok(Self::StakeWeighted),
+            e => Err(format!(
+                "unknown peer selection: {e}, available: uniform, score_weighted, stake_weighted"
+            )),
+        }
+    }
+}
+
 fn default_consensus_enabled() -> bool {
     true
 }
 
+fn default_decided_values_cache_size() -> usize {
+    100
+}
+
 fn default_queue_capacity() -> usize {
     10
 }
@@ -658,6 +1759,10 @@ fn default_queue_per_height_capacity() -> usize {
     500
 }
 
+fn default_shutdown_drain_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
 fn default_wal_replay_delay() -> Duration {
     Duration::from_secs(5)
 }
@@ -706,6 +1811,115 @@ pub struct ConsensusConfig {
     /// Default: 5s
     #[serde(default = "default_wal_replay_delay", with = "humantime_serde")]
     pub wal_replay_delay: Duration,
+
+    /// Path to a file holding the hex-encoded 256-bit key used to encrypt the WAL entries.
+    ///
+    /// When set, new entries are encrypted with ChaCha20-Poly1305 and encrypted entries are
+    /// decrypted transparently on replay. Existing unencrypted entries remain readable,
+    /// so encryption can be enabled on a node which already has a WAL.
+    /// Default: none
+    #[serde(default)]
+    pub wal_encryption_key_file: Option<PathBuf>,
+
+    /// Storage backing the WAL.
+    /// Default: a single file
+    #[serde(default)]
+    pub wal_storage: WalStorageConfig,
+
+    /// Time given to the actors to drain their pending messages when the node shuts down.
+    ///
+    /// The network is drained first, then the state of sync is checkpointed and
+    /// the WAL is flushed. Actors which have not stopped once this timeout elapses are killed.
+    /// Default: 10s
+    #[serde(default = "default_shutdown_drain_timeout", with = "humantime_serde")]
+    pub shutdown_drain_timeout: Duration,
+
+    /// Ask the application for overrides of the timeouts of each round.
+    ///
+    /// When enabled, consensus asks the application at the start of every round
+    /// whether it wants to override the propose, prevote, precommit and rebroadcast
+    /// timeouts of that round, eg. to give more time to a proposer known to be slow.
+    /// Default: false
+    #[serde(default)]
+    pub timeout_overrides: bool,
+
+    /// Ask the application for overrides of the consensus parameters of each height.
+    ///
+    /// When enabled, consensus asks the application when starting every height whether
+    /// it wants to override the timeouts, the voting power thresholds or the value payload
+    /// of that height, eg. to apply parameter changes scheduled at a given height.
+    /// Default: false
+    #[serde(default)]
+    pub consensus_params_overrides: bool,
+
+    /// Require precommits for a value to carry a vote extension.
+    ///
+    /// When enabled, such precommits without a vote extension are rejected, so that
+    /// every decision is delivered to the application together with the vote extensions
+    /// of validators holding more than 2/3 of the voting power.
+    /// Default: false
+    #[serde(default)]
+    pub require_vote_extensions: bool,
+
+    /// Raise an alert when a height reaches this round without deciding.
+    ///
+    /// The alert is logged, counted in the `round_alerts` metric and emitted as an event,
+    /// so that operators notice a height spinning through rounds without making progress.
+    /// Default: none
+    #[serde(default)]
+    pub max_rounds_alert: Option<u32>,
+
+    /// Halt participation in consensus when a height reaches this round without deciding.
+    ///
+    /// While halted, the node stops proposing, voting and scheduling timeouts for the
+    /// current height, but it keeps receiving messages and syncing, so that it resumes
+    /// participation as soon as it moves on to the next height, either because the
+    /// other validators decided or because the operator restarted the height.
+    /// Default: none
+    #[serde(default)]
+    pub max_rounds_halt: Option<u32>,
+
+    /// Notify the application when a round alert is raised or participation is halted.
+    /// Default: false
+    #[serde(default)]
+    pub notify_round_alerts: bool,
+
+    /// Notify the application when the propose timeout elapses before it provided
+    /// the value it was asked to propose, so that it can stop building the value.
+    ///
+    /// Rounds in which the value is missed are counted in the `missed_value_rounds` metric
+    /// whether or not this is enabled.
+    /// Default: false
+    #[serde(default)]
+    pub cancel_get_value: bool,
+
+    /// Refuse to start a height at which more than a third of the voting power
+    /// changes from the validator set of the previous height.
+    ///
+    /// Such changes are always reported in the `large_validator_set_changes` metric and
+    /// with a warning. When enabled, the node stops instead, so that they require the
+    /// operator to explicitly disable this setting before the node can move on.
+    /// Default: false
+    #[serde(default)]
+    pub reject_large_validator_set_changes: bool,
+
+    /// Flight recorder retaining the inputs and effects of the last heights, for postmortems.
+    /// Default: disabled
+    #[serde(default)]
+    pub flight_recorder: FlightRecorderConfig,
+
+    /// Bounds on the proposals and values kept for the current height.
+    #[serde(default)]
+    pub full_proposals: FullProposalsConfig,
+
+    /// Compute the propose, prevote and precommit timeouts from the observed durations of these steps.
+    /// Default: disabled
+    #[serde(default)]
+    pub adaptive_timeouts: AdaptiveTimeoutsConfig,
+
+    /// Policy for splitting the values proposed by this node into parts, for the application.
+    #[serde(default)]
+    pub partitioning: PartitioningPolicy,
 }
 
 impl Default for ConsensusConfig {
@@ -717,6 +1931,207 @@ impl Default for ConsensusConfig {
             queue_capacity: default_queue_capacity(),
             queue_per_height_capacity: default_queue_per_height_capacity(),
             wal_replay_delay: default_wal_replay_delay(),
+            wal_encryption_key_file: None,
+            wal_storage: WalStorageConfig::default(),
+            shutdown_drain_timeout: default_shutdown_drain_timeout(),
+            timeout_overrides: false,
+            consensus_params_overrides: false,
+            require_vote_extensions: false,
+            max_rounds_alert: None,
+            max_rounds_halt: None,
+            notify_round_alerts: false,
+            cancel_get_value: false,
+            reject_large_validator_set_changes: false,
+            flight_recorder: FlightRecorderConfig::default(),
+            full_proposals: FullProposalsConfig::default(),
+            adaptive_timeouts: AdaptiveTimeoutsConfig::default(),
+            partitioning: PartitioningPolicy::default(),
+        }
+    }
+}
+
+/// Flight recorder of consensus, which retains the inputs processed by consensus and
+/// the effects they produced during the last few heights, so that the trace of a slow
+/// or stuck height can be dumped to a file and analyzed after the fact.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlightRecorderConfig {
+    /// Enable the flight recorder
+    pub enabled: bool,
+
+    /// Number of heights for which the trace is retained, the current one included
+    pub max_heights: usize,
+
+    /// Maximum number of entries retained per height, the oldest ones being dropped first
+    pub max_entries_per_height: usize,
+
+    /// Dump the trace automatically when a height reaches this round without deciding.
+    /// Disabled when not set.
+    pub dump_after_rounds: Option<u32>,
+
+    /// Directory in which traces are dumped automatically
+    pub dump_dir: PathBuf,
+}
+
+impl Default for FlightRecorderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_heights: 3,
+            max_entries_per_height: 10_000,
+            dump_after_rounds: None,
+            dump_dir: PathBuf::from("traces"),
+        }
+    }
+}
+
+/// Bounds on the memory used by the proposals and values kept by consensus for the current height,
+/// which would otherwise grow with the number of rounds and of values sent by proposers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FullProposalsConfig {
+    /// When entering a new round, evict the proposals and values of the rounds lower than
+    /// the new round minus this margin, except those for the locked or valid value
+    pub round_margin: u32,
+
+    /// Maximum number of distinct proposals and values kept for a round,
+    /// the ones received beyond that being dropped
+    pub max_entries_per_round: usize,
+}
+
+impl Default for FullProposalsConfig {
+    fn default() -> Self {
+        Self {
+            round_margin: 2,
+            max_entries_per_round: 16,
+        }
+    }
+}
+
+/// Policy for splitting a locally proposed value into the parts streamed to peers.
+///
+/// This is a hook for the application, which consults it when streaming the values it proposes,
+/// eg. with `malachitebft_app::partitioning::partition`, so that it can tune their dissemination
+/// to the shape of its values. Consensus itself does not look at it.
+///
+/// A value is optionally compressed, then split into `target_parts` parts of equal size if set,
+/// or else into as few parts as possible, without any part exceeding `max_part_bytes`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PartitioningPolicy {
+    /// Maximum size of a part
+    pub max_part_bytes: ByteSize,
+
+    /// Compress the value before splitting it
+    pub compression: bool,
+
+    /// Number of parts to split a value into, as long as they do not exceed `max_part_bytes`
+    pub target_parts: Option<usize>,
+}
+
+impl PartitioningPolicy {
+    /// Size of the parts a payload of the given size is split into
+    pub fn part_len(&self, payload_len: usize) -> usize {
+        let max_part_len = (self.max_part_bytes.as_u64() as usize).max(1);
+
+        match self.target_parts {
+            Some(target_parts) if target_parts > 0 => {
+                payload_len.div_ceil(target_parts).clamp(1, max_part_len)
+            }
+            _ => max_part_len,
+        }
+    }
+
+    /// Number of parts a payload of the given size is split into
+    pub fn parts_count(&self, payload_len: usize) -> usize {
+        payload_len.div_ceil(self.part_len(payload_len)).max(1)
+    }
+}
+
+impl Default for PartitioningPolicy {
+    fn default() -> Self {
+        Self {
+            max_part_bytes: ByteSize::kib(64),
+            compression: false,
+            target_parts: None,
+        }
+    }
+}
+
+/// Timeouts adapted to the latency of the network and of the validators, instead of static ones
+/// which either waste time when the validators are fast or cause needless round changes when
+/// they are slow.
+///
+/// The timeout of each of the propose, prevote and precommit steps is computed from a percentile
+/// of the durations of that step during the last rounds, scaled by a multiplier and bounded by
+/// `min` and `max`. Steps which time out last as long as their timeout, so that the multiplier
+/// lets the timeouts grow back when the steps get slower. The increase of the timeouts with
+/// the round, as set by the application, is added on top.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveTimeoutsConfig {
+    /// Enable adaptive timeouts
+    pub enabled: bool,
+
+    /// Number of recent durations of each step from which its timeout is computed
+    pub window: usize,
+
+    /// Percentile of the recent durations of a step, between 0 (excluded) and 100
+    pub percentile: f64,
+
+    /// Factor applied to the percentile to get the timeout, at least 1
+    pub multiplier: f64,
+
+    /// Minimum timeout of a step
+    #[serde(with = "humantime_serde")]
+    pub min: Duration,
+
+    /// Maximum timeout of a step
+    #[serde(with = "humantime_serde")]
+    pub max: Duration,
+}
+
+impl AdaptiveTimeoutsConfig {
+    /// Check that the parameters are consistent.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window == 0 {
+            return Err("`window` must be positive".to_string());
+        }
+
+        if self.percentile.is_nan() || self.percentile <= 0.0 || self.percentile > 100.0 {
+            return Err(format!(
+                "`percentile` must be in (0, 100], got {}",
+                self.percentile
+            ));
+        }
+
+        if self.multiplier.is_nan() || self.multiplier < 1.0 {
+            return Err(format!(
+                "`multiplier` must be at least 1, got {}",
+                self.multiplier
+            ));
+        }
+
+        if self.min.is_zero() || self.min > self.max {
+            return Err(format!(
+                "`min` must be positive and at most `max`, got {:?} and {:?}",
+                self.min, self.max
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for AdaptiveTimeoutsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: 100,
+            percentile: 90.0,
+            multiplier: 1.5,
+            min: Duration::from_millis(500),
+            max: Duration::from_secs(30),
         }
     }
 }
@@ -727,7 +2142,7 @@ impl Default for ConsensusConfig {
 pub enum ValuePayload {
     #[default]
     PartsOnly,
-    ProposalOnly, // TODO - add small block app to test this option
+    ProposalOnly,
     ProposalAndParts,
 }
 
@@ -754,17 +2169,112 @@ pub struct MetricsConfig {
 
     /// Address at which to serve the metrics at
     pub listen_addr: SocketAddr,
-}
+
+    /// Serve the metrics over HTTPS, over plain HTTP if not set
+    #[serde(default)]
+    pub tls: Option<MetricsTlsConfig>,
+
+    /// Require HTTP basic authentication to access the metrics
+    #[serde(default)]
+    pub basic_auth: Option<BasicAuthConfig>,
+
+    /// Allow serving the metrics without authentication on an address other than loopback
+    #[serde(default)]
+    pub allow_unauthenticated: bool,
+}
 
 impl Default for MetricsConfig {
     fn default() -> Self {
         MetricsConfig {
             enabled: false,
             listen_addr: SocketAddr::new(IpAddr::from([127, 0, 0, 1]), 9000),
+            tls: None,
+            basic_auth: None,
+            allow_unauthenticated: false,
         }
     }
 }
 
+impl MetricsConfig {
+    /// Check that the metrics are not exposed beyond this host without authentication
+    /// over HTTPS, unless explicitly allowed.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(basic_auth) = &self.basic_auth {
+            if basic_auth.username.is_empty() || basic_auth.password.is_empty() {
+                return Err("basic auth username and password must not be empty".to_string());
+            }
+        }
+
+        if !self.enabled || self.listen_addr.ip().is_loopback() || self.allow_unauthenticated {
+            return Ok(());
+        }
+
+        if self.basic_auth.is_none() {
+            return Err(format!(
+                "refusing to serve metrics on non-loopback address {} without authentication, \
+                 configure `basic_auth` or set `allow_unauthenticated`",
+                self.listen_addr
+            ));
+        }
+
+        // Basic auth credentials would otherwise be sent in cleartext on every scrape
+        if self.tls.is_none() {
+            return Err(format!(
+                "refusing to serve metrics with basic auth on non-loopback address {} over plain HTTP, \
+                 configure `tls` or set `allow_unauthenticated`",
+                self.listen_addr
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Certificate and private key with which to serve the metrics over HTTPS.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricsTlsConfig {
+    /// Path to the PEM-encoded certificate chain
+    pub cert_path: PathBuf,
+
+    /// Path to the PEM-encoded private key
+    pub key_path: PathBuf,
+}
+
+/// Credentials for HTTP basic authentication.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BasicAuthConfig {
+    pub username: String,
+    pub password: String,
+}
+
+impl fmt::Debug for BasicAuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicAuthConfig")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Storage backing the Write-Ahead Log (WAL)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WalStorageConfig {
+    /// All entries are stored in a single file
+    #[default]
+    File,
+
+    /// Entries are kept in memory and lost when the node stops, for tests only
+    Memory,
+
+    /// Entries are stored in segment files, which are never modified once sealed
+    /// and can therefore be shipped as-is to an object store
+    Segmented {
+        /// Size above which a segment is sealed, and entries are appended to a new one
+        max_segment_size: ByteSize,
+    },
+}
+
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "flavor", rename_all = "snake_case")]
 pub enum RuntimeConfig {
@@ -903,6 +2413,289 @@ impl fmt::Display for LogFormat {
 mod tests {
     use super::*;
 
+    #[test]
+    fn transport_protocol_multiaddr() {
+        let cases = [
+            (
+                TransportProtocol::Tcp,
+                "127.0.0.1",
+                "/ip4/127.0.0.1/tcp/27000",
+            ),
+            (
+                TransportProtocol::Quic,
+                "127.0.0.1",
+                "/ip4/127.0.0.1/udp/27000/quic-v1",
+            ),
+            (TransportProtocol::Tcp, "::1", "/ip6/::1/tcp/27000"),
+            (
+                TransportProtocol::Quic,
+                "[::1]",
+                "/ip6/::1/udp/27000/quic-v1",
+            ),
+            (
+                TransportProtocol::Tcp,
+                "node0.example.com",
+                "/dns/node0.example.com/tcp/27000",
+            ),
+            (
+                TransportProtocol::Quic,
+                "localhost",
+                "/dns/localhost/udp/27000/quic-v1",
+            ),
+        ];
+
+        for (transport, host, expected) in cases {
+            assert_eq!(
+                transport.multiaddr(host, 27000),
+                expected.parse::<Multiaddr>().unwrap(),
+                "host: {host}"
+            );
+        }
+    }
+
+    #[test]
+    fn p2p_config_nat_toml() {
+        let toml = r#"
+        listen_addr = "/ip4/0.0.0.0/tcp/0"
+        persistent_peers = []
+        protocol = { type = "broadcast" }
+        pubsub_max_size = "4 MiB"
+        rpc_max_size = "10 MiB"
+        "#;
+
+        let config: P2pConfig = toml::from_str(toml).unwrap();
+        assert_eq!(config.nat, NatConfig::default());
+        assert!(!config.nat.autonat);
+        assert!(config.nat.relay.is_empty());
+
+        let relay =
+            "/ip4/1.2.3.4/tcp/27000/p2p/12D3KooWAvnWpDHjd3U2p2CovrP3DuaeMSjtuLbmmSeh1hxNk5sC";
+
+        let toml = format!(
+            r#"
+        listen_addr = "/ip4/0.0.0.0/tcp/0"
+        persistent_peers = []
+        protocol = {{ type = "broadcast" }}
+        pubsub_max_size = "4 MiB"
+        rpc_max_size = "10 MiB"
+
+        [nat]
+        autonat = true
+        relay = ["{relay}"]
+        "#
+        );
+
+        let mut config: P2pConfig = toml::from_str(&toml).unwrap();
+        assert!(config.nat.autonat);
+        assert_eq!(config.nat.relay, vec![relay.parse::<Multiaddr>().unwrap()]);
+        assert_eq!(config.validate(), Ok(()));
+
+        // Relay addresses must include the peer id of the relay
+        config.nat.relay = vec!["/ip4/1.2.3.4/tcp/27000".parse().unwrap()];
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn p2p_config_rpc_signing_toml() {
+        let toml = r#"
+        listen_addr = "/ip4/0.0.0.0/tcp/0"
+        persistent_peers = []
+        protocol = { type = "broadcast" }
+        pubsub_max_size = "4 MiB"
+        rpc_max_size = "10 MiB"
+        "#;
+
+        let config: P2pConfig = toml::from_str(toml).unwrap();
+        assert_eq!(config.rpc_signing, RpcSigningConfig::default());
+        assert!(!config.rpc_signing.is_enabled());
+        assert_eq!(config.rpc_signing.max_age, Duration::from_secs(30));
+
+        let toml = r#"
+        listen_addr = "/ip4/0.0.0.0/tcp/0"
+        persistent_peers = []
+        protocol = { type = "broadcast" }
+        pubsub_max_size = "4 MiB"
+        rpc_max_size = "10 MiB"
+
+        [rpc_signing]
+        sync = true
+        max_age = "1m"
+        "#;
+
+        let mut config: P2pConfig = toml::from_str(toml).unwrap();
+        assert!(config.rpc_signing.sync);
+        assert!(!config.rpc_signing.validator_proof);
+        assert!(config.rpc_signing.is_enabled());
+        assert_eq!(config.rpc_signing.max_age, Duration::from_secs(60));
+        assert_eq!(config.validate(), Ok(()));
+
+        // Signed messages must be valid for some time
+        config.rpc_signing.max_age = Duration::ZERO;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn p2p_config_connection_timeouts_toml() {
+        let toml = r#"
+        listen_addr = "/ip4/0.0.0.0/tcp/0"
+        persistent_peers = []
+        protocol = { type = "broadcast" }
+        pubsub_max_size = "4 MiB"
+        rpc_max_size = "10 MiB"
+        "#;
+
+        let config: P2pConfig = toml::from_str(toml).unwrap();
+        assert_eq!(config.dial_timeout, Duration::from_secs(5));
+        assert_eq!(config.upgrade_timeout, Duration::from_secs(10));
+        assert_eq!(config.handshake_timeout, Duration::from_secs(10));
+
+        let toml = r#"
+        listen_addr = "/ip4/0.0.0.0/tcp/0"
+        persistent_peers = []
+        protocol = { type = "broadcast" }
+        pubsub_max_size = "4 MiB"
+        rpc_max_size = "10 MiB"
+        dial_timeout = "2s"
+        upgrade_timeout = "3s"
+        handshake_timeout = "500ms"
+        "#;
+
+        let config: P2pConfig = toml::from_str(toml).unwrap();
+        assert_eq!(config.dial_timeout, Duration::from_secs(2));
+        assert_eq!(config.upgrade_timeout, Duration::from_secs(3));
+        assert_eq!(config.handshake_timeout, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn p2p_config_validate_addresses() {
+        let config = |listen_addr: &str, persistent_peers: &[&str]| P2pConfig {
+            listen_addr: listen_addr.parse().unwrap(),
+            persistent_peers: persistent_peers
+                .iter()
+                .map(|a| a.parse().unwrap())
+                .collect(),
+            ..P2pConfig::default()
+        };
+
+        let peer = "/p2p/12D3KooWAvnWpDHjd3U2p2CovrP3DuaeMSjtuLbmmSeh1hxNk5sC";
+
+        let valid = [
+            config("/ip4/0.0.0.0/tcp/27000", &[]),
+            config("/ip6/::/udp/27000/quic-v1", &[]),
+            config(
+                "/ip4/0.0.0.0/tcp/27000",
+                &[
+                    "/ip4/10.0.0.1/tcp/27000",
+                    "/ip6/fe80::1/udp/27000/quic-v1",
+                    "/dns/node1.example.com/tcp/27000",
+                    "/dns4/node2.example.com/tcp/27000",
+                    &format!("/dns6/node3.example.com/udp/27000/quic-v1{peer}"),
+                ],
+            ),
+            config(
+                "/unix/%2Ftmp%2Fnode0.sock",
+                &[
+                    "/unix/%2Ftmp%2Fnode1.sock",
+                    &format!("/unix/%2Ftmp%2Fnode2.sock{peer}"),
+                ],
+            ),
+        ];
+
+        for config in valid {
+            assert_eq!(config.validate(), Ok(()), "{config:?}");
+        }
+
+        let invalid = [
+            config("/dns4/node0.example.com/tcp/27000", &[]),
+            config("/ip4/0.0.0.0/udp/27000", &[]),
+            config("/ip4/0.0.0.0", &[]),
+            config(&format!("/ip4/0.0.0.0/tcp/27000{peer}"), &[]),
+            config("/ip4/0.0.0.0/tcp/27000", &["/tcp/27000"]),
+            config("/ip4/0.0.0.0/tcp/27000", &["/dns/node1.example.com"]),
+            config("/ip4/0.0.0.0/tcp/27000", &["/dnsaddr/seed.example.com"]),
+            config(&format!("/unix/%2Ftmp%2Fnode0.sock{peer}"), &[]),
+            config("/unix/%2Ftmp%2Fnode0.sock/tcp/27000", &[]),
+        ];
+
+        for config in invalid {
+            assert!(config.validate().is_err(), "{config:?}");
+        }
+    }
+
+    #[test]
+    fn p2p_config_validate_additional_and_advertised_addresses() {
+        let config = |additional_listen_addrs: &[&str], advertise_addrs: &[&str]| P2pConfig {
+            listen_addr: "/ip4/0.0.0.0/tcp/27000".parse().unwrap(),
+            additional_listen_addrs: additional_listen_addrs
+                .iter()
+                .map(|a| a.parse().unwrap())
+                .collect(),
+            advertise_addrs: advertise_addrs.iter().map(|a| a.parse().unwrap()).collect(),
+            ..P2pConfig::default()
+        };
+
+        let peer = "/p2p/12D3KooWAvnWpDHjd3U2p2CovrP3DuaeMSjtuLbmmSeh1hxNk5sC";
+
+        let valid = [
+            config(&["/ip4/127.0.0.1/tcp/27001"], &[]),
+            config(&["/ip6/::1/tcp/27001"], &["/ip4/203.0.113.7/tcp/27000"]),
+            config(
+                &[],
+                &[
+                    "/dns4/node0.example.com/tcp/27000",
+                    "/ip6/2001:db8::7/tcp/27000",
+                ],
+            ),
+        ];
+
+        for config in valid {
+            assert_eq!(config.validate(), Ok(()), "{config:?}");
+        }
+
+        let invalid = [
+            config(&["/ip4/0.0.0.0/tcp/27000"], &[]),
+            config(&["/ip4/127.0.0.1/udp/27001/quic-v1"], &[]),
+            config(&["/unix/%2Ftmp%2Fnode.sock"], &[]),
+            config(&["/dns4/localhost/tcp/27001"], &[]),
+            config(&[], &["/ip4/0.0.0.0/tcp/27000"]),
+            config(&[], &["/ip6/::/tcp/27000"]),
+            config(&[], &[&format!("/ip4/203.0.113.7/tcp/27000{peer}")]),
+            config(&[], &["/ip4/203.0.113.7"]),
+        ];
+
+        for config in invalid {
+            assert!(config.validate().is_err(), "{config:?}");
+        }
+    }
+
+    #[test]
+    fn p2p_config_validate_dns_seeds() {
+        let config = |dns_seed: &str| P2pConfig {
+            listen_addr: "/ip4/0.0.0.0/tcp/27000".parse().unwrap(),
+            dns_seeds: vec![dns_seed.parse().unwrap()],
+            ..P2pConfig::default()
+        };
+
+        let peer = "/p2p/12D3KooWAvnWpDHjd3U2p2CovrP3DuaeMSjtuLbmmSeh1hxNk5sC";
+
+        for dns_seed in [
+            "/dnsaddr/seed.example.com".to_string(),
+            format!("/dnsaddr/seed.example.com{peer}"),
+            "/dns4/seed.example.com/tcp/27000".to_string(),
+            format!("/dns/seed.example.com/udp/27000/quic-v1{peer}"),
+        ] {
+            assert_eq!(config(&dns_seed).validate(), Ok(()), "{dns_seed}");
+        }
+
+        for dns_seed in [
+            "/ip4/10.0.0.1/tcp/27000",
+            "/dnsaddr/seed.example.com/tcp/27000",
+            "/dns4/seed.example.com",
+        ] {
+            assert!(config(dns_seed).validate().is_err(), "{dns_seed}");
+        }
+    }
+
     #[test]
     fn discovery_config_deserializes_without_max_peers_per_response() {
         // Configs written before this field was added should still deserialize,
@@ -914,6 +2707,226 @@ mod tests {
         "#;
         let config: DiscoveryConfig = toml::from_str(toml).unwrap();
         assert_eq!(config.max_peers_per_response, 100);
+        assert_eq!(config.min_peers_to_idle, 1);
+        assert_eq!(
+            config.rebootstrap_backoff,
+            discovery::default_rebootstrap_backoff()
+        );
+    }
+
+    #[test]
+    fn value_sync_compression_config() {
+        let config: SyncCompressionConfig = toml::from_str("").unwrap();
+        assert_eq!(config, SyncCompressionConfig::default());
+        assert!(!config.enabled);
+
+        let toml = r#"
+            enabled = true
+            threshold = "64 KiB"
+        "#;
+        let config: SyncCompressionConfig = toml::from_str(toml).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.threshold, ByteSize::kib(64));
+    }
+
+    #[test]
+    fn partitioning_policy() {
+        let policy: PartitioningPolicy = toml::from_str("").unwrap();
+        assert_eq!(policy, PartitioningPolicy::default());
+        assert_eq!(policy.part_len(1000), 64 * 1024);
+        assert_eq!(policy.parts_count(0), 1);
+        assert_eq!(policy.parts_count(200 * 1024), 4);
+
+        let toml = r#"
+            max_part_bytes = "1 KiB"
+            compression = true
+            target_parts = 4
+        "#;
+        let policy: PartitioningPolicy = toml::from_str(toml).unwrap();
+        assert!(policy.compression);
+        assert_eq!(policy.part_len(1000), 250);
+        assert_eq!(policy.parts_count(1000), 4);
+        assert_eq!(policy.part_len(3), 1);
+        assert_eq!(policy.parts_count(3), 3);
+
+        // Parts never exceed the maximum size, even if it means more parts than targeted
+        assert_eq!(policy.part_len(10 * 1024), 1024);
+        assert_eq!(policy.parts_count(10 * 1024), 10);
+    }
+
+    #[test]
+    fn value_sync_request_limits_config() {
+        let config: SyncRequestLimitsConfig = toml::from_str("").unwrap();
+        assert_eq!(config, SyncRequestLimitsConfig::default());
+        assert_eq!(config.max_range_size, None);
+
+        let toml = r#"
+            max_range_size = 20
+            max_concurrent_requests = 2
+            bandwidth_budget = "0 B"
+        "#;
+        let config: SyncRequestLimitsConfig = toml::from_str(toml).unwrap();
+        assert_eq!(config.max_range_size, Some(20));
+        assert_eq!(config.max_concurrent_requests, 2);
+        assert_eq!(config.bandwidth_budget, ByteSize::b(0));
+    }
+
+    #[test]
+    fn value_sync_retention_config() {
+        let config: SyncRetentionConfig = toml::from_str("").unwrap();
+        assert_eq!(config, SyncRetentionConfig::default());
+        assert!(!config.enabled);
+
+        let toml = r#"
+            enabled = true
+            retain_values = 10
+            max_retain_values = 100
+            interval = "5s"
+        "#;
+        let config: SyncRetentionConfig = toml::from_str(toml).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.retain_values, 10);
+        assert_eq!(config.max_retain_values, 100);
+        assert_eq!(config.interval, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn metrics_config_requires_auth_beyond_loopback() {
+        let mut config = MetricsConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.listen_addr = "0.0.0.0:9000".parse().unwrap();
+        assert!(config.validate().is_err());
+
+        config.allow_unauthenticated = true;
+        assert!(config.validate().is_ok());
+
+        config.allow_unauthenticated = false;
+        config.basic_auth = Some(BasicAuthConfig {
+            username: "prometheus".to_string(),
+            password: "secret".to_string(),
+        });
+        assert!(!format!("{config:?}").contains("secret"));
+
+        // Basic auth over plain HTTP would leak the credentials
+        assert!(config.validate().is_err());
+
+        config.tls = Some(MetricsTlsConfig {
+            cert_path: "/etc/metrics/cert.pem".into(),
+            key_path: "/etc/metrics/key.pem".into(),
+        });
+        assert!(config.validate().is_ok());
+
+        config.basic_auth = Some(BasicAuthConfig {
+            username: "prometheus".to_string(),
+            password: String::new(),
+        });
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn metrics_config_tls_and_auth() {
+        let toml = r#"
+            enabled = true
+            listen_addr = "0.0.0.0:9000"
+
+            [tls]
+            cert_path = "/etc/metrics/cert.pem"
+            key_path = "/etc/metrics/key.pem"
+
+            [basic_auth]
+            username = "prometheus"
+            password = "secret"
+        "#;
+        let config: MetricsConfig = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.tls,
+            Some(MetricsTlsConfig {
+                cert_path: "/etc/metrics/cert.pem".into(),
+                key_path: "/etc/metrics/key.pem".into(),
+            })
+        );
+        assert_eq!(config.basic_auth.unwrap().username, "prometheus");
+        assert!(!config.allow_unauthenticated);
+    }
+
+    #[test]
+    fn wal_storage_config() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            #[serde(default)]
+            wal_storage: WalStorageConfig,
+        }
+
+        let config: Wrapper = toml::from_str("").unwrap();
+        assert_eq!(config.wal_storage, WalStorageConfig::File);
+
+        let toml = r#"
+            [wal_storage]
+            type = "segmented"
+            max_segment_size = "16 MiB"
+        "#;
+        let config: Wrapper = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.wal_storage,
+            WalStorageConfig::Segmented {
+                max_segment_size: ByteSize::mib(16)
+            }
+        );
+
+        let config: Wrapper = toml::from_str("wal_storage = { type = \"memory\" }").unwrap();
+        assert_eq!(config.wal_storage, WalStorageConfig::Memory);
+    }
+
+    #[test]
+    fn flight_recorder_config() {
+        let config: FlightRecorderConfig = toml::from_str("").unwrap();
+        assert_eq!(config, FlightRecorderConfig::default());
+        assert!(!config.enabled);
+
+        let toml = r#"
+            enabled = true
+            max_heights = 5
+            dump_after_rounds = 10
+            dump_dir = "/var/log/malachite"
+        "#;
+        let config: FlightRecorderConfig = toml::from_str(toml).unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.max_heights, 5);
+        assert_eq!(config.max_entries_per_height, 10_000);
+        assert_eq!(config.dump_after_rounds, Some(10));
+        assert_eq!(config.dump_dir, PathBuf::from("/var/log/malachite"));
+    }
+
+    #[test]
+    fn full_proposals_config() {
+        let config: FullProposalsConfig = toml::from_str("").unwrap();
+        assert_eq!(config, FullProposalsConfig::default());
+
+        let config: FullProposalsConfig = toml::from_str("round_margin = 5").unwrap();
+        assert_eq!(config.round_margin, 5);
+        assert_eq!(config.max_entries_per_round, 16);
+    }
+
+    #[test]
+    fn adaptive_timeouts_config() {
+        let config: AdaptiveTimeoutsConfig = toml::from_str("").unwrap();
+        assert_eq!(config, AdaptiveTimeoutsConfig::default());
+        assert!(config.validate().is_ok());
+
+        let config: AdaptiveTimeoutsConfig =
+            toml::from_str("enabled = true\nmin = \"2s\"\nmax = \"1s\"").unwrap();
+        assert!(config.enabled);
+        assert!(config.validate().is_err());
+
+        let config = AdaptiveTimeoutsConfig {
+            percentile: 0.0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
     }
 
     #[test]
@@ -982,6 +2995,11 @@ mod tests {
             protocol_names.validator_proof,
             "/malachitebft-validator-proof/v1"
         );
+        assert_eq!(
+            protocol_names.proposal_parts,
+            "/malachitebft-proposal-parts/v1beta1"
+        );
+        assert_eq!(protocol_names.node_info, "/malachitebft-node-info/v1");
     }
 
     #[test]
@@ -995,6 +3013,8 @@ mod tests {
             discovery_regres: "/custom-discovery/reqres/v1".to_string(),
             sync: "/custom-sync/v1".to_string(),
             validator_proof: "/custom-validator-proof/v1".to_string(),
+            proposal_parts: "/custom-proposal-parts/v1".to_string(),
+            node_info: "/custom-node-info/v1".to_string(),
         };
 
         let json = serde_json::to_string(&protocol_names).unwrap();
@@ -1018,6 +3038,8 @@ mod tests {
             discovery_regres: "/test-network/discovery/reqres/v1".to_string(),
             sync: "/test-network/sync/v1".to_string(),
             validator_proof: "/test-network/validator-proof/v1".to_string(),
+            proposal_parts: "/test-network/proposal-parts/v1".to_string(),
+            node_info: "/test-network/node-info/v1".to_string(),
         };
 
         let config_with_custom = P2pConfig {
@@ -1028,6 +3050,56 @@ mod tests {
         assert_eq!(config_with_custom.protocol_names, custom_protocol_names);
     }
 
+    #[test]
+    fn protocol_version() {
+        let version: ProtocolVersion = "1.2.3".parse().unwrap();
+        assert_eq!(version, ProtocolVersion::new(1, 2, 3));
+        assert_eq!(version.to_string(), "1.2.3");
+
+        assert!(ProtocolVersion::new(1, 10, 0) > ProtocolVersion::new(1, 9, 5));
+        assert!(ProtocolVersion::new(2, 0, 0) > ProtocolVersion::new(1, 10, 0));
+
+        for invalid in ["", "1", "1.2", "1.2.3.4", "1.2.x", "v1.2.3", "1.-2.3"] {
+            assert!(invalid.parse::<ProtocolVersion>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn p2p_config_protocol_version_toml() {
+        let toml_content = r#"
+        listen_addr = "/ip4/0.0.0.0/tcp/0"
+        persistent_peers = []
+        protocol_version = "1.4.0"
+        min_protocol_version = "1.3.0"
+        pubsub_max_size = "4 MiB"
+        rpc_max_size = "10 MiB"
+
+        [protocol]
+        type = "gossipsub"
+        "#;
+
+        let config: P2pConfig = toml::from_str(toml_content).unwrap();
+        assert_eq!(config.protocol_version, ProtocolVersion::new(1, 4, 0));
+        assert_eq!(
+            config.min_protocol_version,
+            Some(ProtocolVersion::new(1, 3, 0))
+        );
+        assert!(config.validate().is_ok());
+
+        let config = P2pConfig {
+            min_protocol_version: Some(ProtocolVersion::new(1, 5, 0)),
+            ..config
+        };
+        assert!(config.validate().is_err());
+
+        let defaults = P2pConfig::default();
+        assert_eq!(defaults.protocol_version, ProtocolVersion::new(1, 0, 0));
+        assert_eq!(defaults.min_protocol_version, None);
+
+        let invalid = toml_content.replace("1.3.0", "1.3");
+        assert!(toml::from_str::<P2pConfig>(&invalid).is_err());
+    }
+
     #[test]
     fn protocol_names_toml_deserialization() {
         let toml_content = r#"
@@ -1076,6 +3148,12 @@ mod tests {
             config.p2p.protocol_names.validator_proof,
             "/custom-network/validator-proof/v2"
         );
+
+        // Defaults to the standard protocol name when missing from the section
+        assert_eq!(
+            config.p2p.protocol_names.proposal_parts,
+            "/malachitebft-proposal-parts/v1beta1"
+        );
     }
 
     #[test]
@@ -1145,12 +3223,177 @@ mod tests {
         );
     }
 
+    #[test]
+    fn p2p_config_allow_list_file_toml() {
+        let toml_content = r#"
+        value_payload = "parts-only"
+
+        [p2p]
+        listen_addr = "/ip4/0.0.0.0/tcp/0"
+        persistent_peers = []
+        allow_list_file = "config/allow_list.txt"
+        pubsub_max_size = "4 MiB"
+        rpc_max_size = "10 MiB"
+
+        [p2p.protocol]
+        type = "gossipsub"
+        "#;
+
+        let config: ConsensusConfig = toml::from_str(toml_content).unwrap();
+        assert_eq!(
+            config.p2p.allow_list_file,
+            Some(PathBuf::from("config/allow_list.txt"))
+        );
+        assert_eq!(P2pConfig::default().allow_list_file, None);
+    }
+
+    #[test]
+    fn p2p_config_priority_lanes_toml() {
+        let toml_content = r#"
+        timeout_propose = "3s"
+        timeout_propose_delta = "500ms"
+        timeout_prevote = "1s"
+        timeout_prevote_delta = "500ms"
+        timeout_precommit = "1s"
+        timeout_precommit_delta = "500ms"
+        timeout_rebroadcast = "5s"
+        value_payload = "parts-only"
+
+        [p2p]
+        listen_addr = "/ip4/0.0.0.0/tcp/0"
+        persistent_peers = []
+        pubsub_max_size = "4 MiB"
+        rpc_max_size = "10 MiB"
+
+        [p2p.priority_lanes]
+        votes = 16
+        status = 3
+        capacity = 64
+
+        [p2p.protocol]
+        type = "gossipsub"
+        "#;
+
+        let config: ConsensusConfig = toml::from_str(toml_content).unwrap();
+
+        // Missing weights should use their defaults
+        assert_eq!(
+            config.p2p.priority_lanes,
+            PriorityLanesConfig {
+                votes: 16,
+                status: 3,
+                capacity: 64,
+                ..PriorityLanesConfig::default()
+            }
+        );
+    }
+
+    #[test]
+    fn p2p_config_reputation_toml() {
+        let toml_content = r#"
+        timeout_propose = "3s"
+        timeout_propose_delta = "500ms"
+        timeout_prevote = "1s"
+        timeout_prevote_delta = "500ms"
+        timeout_precommit = "1s"
+        timeout_precommit_delta = "500ms"
+        timeout_rebroadcast = "5s"
+        value_payload = "parts-only"
+
+        [p2p]
+        listen_addr = "/ip4/0.0.0.0/tcp/0"
+        persistent_peers = []
+        pubsub_max_size = "4 MiB"
+        rpc_max_size = "10 MiB"
+
+        [p2p.reputation]
+        enabled = true
+        ban_threshold = -50.0
+        ban_duration = "1m"
+
+        [p2p.protocol]
+        type = "gossipsub"
+        "#;
+
+        let config: ConsensusConfig = toml::from_str(toml_content).unwrap();
+
+        assert_eq!(
+            config.p2p.reputation,
+            ReputationConfig {
+                enabled: true,
+                ban_threshold: -50.0,
+                ban_duration: Duration::from_secs(60),
+                ..ReputationConfig::default()
+            }
+        );
+    }
+
     #[test]
     fn gossipsub_config_default_disables_peer_scoring() {
         let config = GossipSubConfig::default();
         assert!(!config.enable_peer_scoring());
     }
 
+    #[test]
+    fn gossipsub_scoring_deserialization() {
+        let toml_content = r#"
+            type = "gossipsub"
+            enable_peer_scoring = true
+
+            [scoring]
+            invalid_message_penalty = 50.0
+            prune_backoff = "30s"
+            graylist_threshold = -5000.0
+        "#;
+
+        let PubSubProtocol::GossipSub(config) = toml::from_str(toml_content).unwrap() else {
+            panic!("expected GossipSub protocol");
+        };
+
+        assert_eq!(
+            config.scoring(),
+            &GossipSubScoringConfig {
+                invalid_message_penalty: 50.0,
+                prune_backoff: Duration::from_secs(30),
+                graylist_threshold: -5000.0,
+                ..GossipSubScoringConfig::default()
+            }
+        );
+
+        assert_eq!(
+            GossipSubConfig::default().scoring(),
+            &GossipSubScoringConfig::default()
+        );
+    }
+
+    #[test]
+    fn gossipsub_scoring_validate() {
+        assert_eq!(GossipSubScoringConfig::default().validate(), Ok(()));
+
+        let invalid = [
+            GossipSubScoringConfig {
+                liveness_topic_weight: -1.0,
+                ..Default::default()
+            },
+            GossipSubScoringConfig {
+                invalid_message_penalty: -100.0,
+                ..Default::default()
+            },
+            GossipSubScoringConfig {
+                behaviour_penalty_decay: Duration::ZERO,
+                ..Default::default()
+            },
+            GossipSubScoringConfig {
+                publish_threshold: -100.0,
+                ..Default::default()
+            },
+        ];
+
+        for config in invalid {
+            assert!(config.validate().is_err(), "{config:?}");
+        }
+    }
+
     #[test]
     fn gossipsub_enable_peer_scoring_deserialization() {
         struct TestCase {
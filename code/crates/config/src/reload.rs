@@ -0,0 +1,318 @@
+//! Reloading of the configuration of a running node.
+//!
+//! Only a few settings can be changed without restarting the node: the log level,
+//! the intervals at which sync sends status updates and backfill requests, and the
+//! metrics server. [`reload`] compares the running configuration with the reloaded one,
+//! and returns the settings to apply, or the fields which changed but can only be
+//! changed by restarting the node, in which case nothing must be applied.
+//!
+//! Note that the timeouts of consensus are not part of the configuration, as they are
+//! provided by the application when starting each height.
+
+use core::fmt;
+use std::time::Duration;
+
+use crate::{
+    BackfillConfig, ConsensusConfig, LogLevel, LoggingConfig, MetricsConfig, ValueSyncConfig,
+};
+
+/// The sections of the configuration which are shared by all applications.
+#[derive(Copy, Clone, Debug)]
+pub struct ConfigSections<'a> {
+    pub logging: &'a LoggingConfig,
+    pub consensus: &'a ConsensusConfig,
+    pub value_sync: &'a ValueSyncConfig,
+    pub metrics: &'a MetricsConfig,
+}
+
+/// The settings which changed in the reloaded configuration, and which can be applied
+/// to the running node. Settings which did not change are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConfigReload {
+    /// New log level
+    pub log_level: Option<LogLevel>,
+
+    /// New interval at which sync sends status updates to peers, `0s` to send them after each decision
+    pub status_update_interval: Option<Duration>,
+
+    /// New interval at which sync sends backfill requests, if backfill is enabled
+    pub backfill_request_interval: Option<Duration>,
+
+    /// New configuration of the metrics server, to be started, stopped or moved to another address
+    pub metrics: Option<MetricsConfig>,
+}
+
+impl ConfigReload {
+    /// Whether there is nothing to apply.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// The reloaded configuration changes fields which cannot be changed while the node is running.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReloadError {
+    /// The fields which changed, eg. `consensus.p2p`
+    pub fields: Vec<&'static str>,
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot change ")?;
+
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "`{field}`")?;
+        }
+
+        write!(
+            f,
+            " while the node is running: revert the changes, or restart the node to apply them"
+        )
+    }
+}
+
+impl std::error::Error for ReloadError {}
+
+/// Push the name of each of the listed fields of `$new` which differs from the one of `$current`.
+///
+/// The fields are destructured from `$new`, so that adding a field to the section
+/// without deciding whether it can be reloaded fails to compile.
+macro_rules! immutable_fields {
+    ($changed:ident, $ty:ident, $section:literal, $current:expr, $new:expr, [$($field:ident),* $(,)?], [$($reloadable:ident),* $(,)?]) => {{
+        let current: &$ty = $current;
+        let $ty { $($field,)* $($reloadable: _,)* } = $new;
+
+        $(
+            if *$field != current.$field {
+                $changed.push(concat!($section, ".", stringify!($field)));
+            }
+        )*
+    }};
+}
+
+/// Compare the running configuration with the reloaded one, and return the settings to apply.
+///
+/// Fails if any field which cannot be changed while the node is running has changed,
+/// listing all of them.
+pub fn reload(
+    current: ConfigSections<'_>,
+    new: ConfigSections<'_>,
+) -> Result<ConfigReload, ReloadError> {
+    let mut changed = Vec::new();
+
+    immutable_fields!(
+        changed,
+        LoggingConfig,
+        "logging",
+        current.logging,
+        new.logging,
+        [log_format],
+        [log_level]
+    );
+
+    immutable_fields!(
+        changed,
+        ConsensusConfig,
+        "consensus",
+        current.consensus,
+        new.consensus,
+        [
+            enabled,
+            p2p,
+            value_payload,
+            queue_capacity,
+            queue_per_height_capacity,
+            wal_replay_delay,
+            wal_encryption_key_file,
+            wal_storage,
+            shutdown_drain_timeout,
+            timeout_overrides,
+            consensus_params_overrides,
+            require_vote_extensions,
+            max_rounds_alert,
+            max_rounds_halt,
+            notify_round_alerts,
+            cancel_get_value,
+            reject_large_validator_set_changes,
+            flight_recorder,
+            full_proposals,
+            adaptive_timeouts,
+            partitioning,
+        ],
+        []
+    );
+
+    immutable_fields!(
+        changed,
+        ValueSyncConfig,
+        "value_sync",
+        current.value_sync,
+        new.value_sync,
+        [
+            enabled,
+            request_timeout,
+            max_request_size,
+            max_response_size,
+            parallel_requests,
+            scoring_strategy,
+            peer_selection,
+            inactive_threshold,
+            batch_size,
+            request_max_retries,
+            compression,
+            request_limits,
+            retention,
+            batch_synced_values,
+            decided_values_cache_size,
+            announce_decisions,
+        ],
+        [status_update_interval, backfill]
+    );
+
+    immutable_fields!(
+        changed,
+        BackfillConfig,
+        "value_sync.backfill",
+        &current.value_sync.backfill,
+        &new.value_sync.backfill,
+        [enabled, target_min_height],
+        [request_interval]
+    );
+
+    if !changed.is_empty() {
+        return Err(ReloadError { fields: changed });
+    }
+
+    // The backfill ticker only runs when backfill is enabled
+    let backfill_request_interval = if new.value_sync.backfill.enabled {
+        changed_to(
+            &current.value_sync.backfill.request_interval,
+            &new.value_sync.backfill.request_interval,
+        )
+    } else {
+        None
+    };
+
+    Ok(ConfigReload {
+        log_level: changed_to(&current.logging.log_level, &new.logging.log_level),
+        status_update_interval: changed_to(
+            &current.value_sync.status_update_interval,
+            &new.value_sync.status_update_interval,
+        ),
+        backfill_request_interval,
+        metrics: changed_to(current.metrics, new.metrics),
+    })
+}
+
+fn changed_to<T: Clone + PartialEq>(current: &T, new: &T) -> Option<T> {
+    (current != new).then(|| new.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LogFormat;
+
+    struct Config {
+        logging: LoggingConfig,
+        consensus: ConsensusConfig,
+        value_sync: ValueSyncConfig,
+        metrics: MetricsConfig,
+    }
+
+    impl Config {
+        fn sections(&self) -> ConfigSections<'_> {
+            ConfigSections {
+                logging: &self.logging,
+                consensus: &self.consensus,
+                value_sync: &self.value_sync,
+                metrics: &self.metrics,
+            }
+        }
+    }
+
+    fn config() -> Config {
+        Config {
+            logging: LoggingConfig::default(),
+            consensus: ConsensusConfig::default(),
+            value_sync: ValueSyncConfig::default(),
+            metrics: MetricsConfig::default(),
+        }
+    }
+
+    #[test]
+    fn unchanged_config_reloads_nothing() {
+        let current = config();
+        let changes = reload(current.sections(), config().sections()).unwrap();
+
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn reloadable_settings_are_applied() {
+        let current = config();
+
+        let mut new = config();
+        new.logging.log_level = LogLevel::Trace;
+        new.value_sync.status_update_interval = Duration::ZERO;
+        new.metrics.enabled = !current.metrics.enabled;
+
+        let changes = reload(current.sections(), new.sections()).unwrap();
+
+        assert_eq!(
+            changes,
+            ConfigReload {
+                log_level: Some(LogLevel::Trace),
+                status_update_interval: Some(Duration::ZERO),
+                backfill_request_interval: None,
+                metrics: Some(new.metrics),
+            }
+        );
+    }
+
+    #[test]
+    fn backfill_interval_is_only_applied_when_backfill_is_enabled() {
+        let mut current = config();
+
+        let mut new = config();
+        new.value_sync.backfill.request_interval *= 2;
+
+        let changes = reload(current.sections(), new.sections()).unwrap();
+        assert_eq!(changes.backfill_request_interval, None);
+
+        current.value_sync.backfill.enabled = true;
+        new.value_sync.backfill.enabled = true;
+
+        let changes = reload(current.sections(), new.sections()).unwrap();
+        assert_eq!(
+            changes.backfill_request_interval,
+            Some(new.value_sync.backfill.request_interval)
+        );
+    }
+
+    #[test]
+    fn immutable_fields_are_rejected() {
+        let current = config();
+
+        let mut new = config();
+        new.logging.log_level = LogLevel::Trace;
+        new.logging.log_format = LogFormat::Json;
+        new.consensus.queue_capacity += 1;
+        new.value_sync.backfill.enabled = true;
+
+        let error = reload(current.sections(), new.sections()).unwrap_err();
+
+        assert_eq!(
+            error.fields,
+            [
+                "logging.log_format",
+                "consensus.queue_capacity",
+                "value_sync.backfill.enabled"
+            ]
+        );
+        assert!(error.to_string().contains("`consensus.queue_capacity`"));
+    }
+}
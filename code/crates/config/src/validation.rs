@@ -0,0 +1,558 @@
+//! Validation of the invariants which span several fields of the configuration.
+//!
+//! Deserializing a configuration only checks that each field is well-formed on its own.
+//! [`validate`] additionally checks that the fields are consistent with each other,
+//! reporting the configurations which cannot work as errors, and the ones which work
+//! but are likely not what was intended, eg. a node which has no peer to connect to,
+//! as warnings. Every issue names the offending fields and how to fix them.
+
+use core::fmt;
+use std::time::Duration;
+
+use bytesize::ByteSize;
+
+use crate::{ConsensusConfig, PubSubProtocol, ValueSyncConfig};
+
+/// A configuration with which the node cannot work.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The P2P configuration is invalid, see [`P2pConfig::validate`](crate::P2pConfig::validate)
+    InvalidP2p(String),
+
+    /// The adaptive timeouts configuration is invalid, see [`AdaptiveTimeoutsConfig::validate`](crate::AdaptiveTimeoutsConfig::validate)
+    InvalidAdaptiveTimeouts(String),
+
+    /// A duration which must be positive is zero
+    ZeroDuration { field: &'static str },
+
+    /// A size or count which must be positive is zero
+    ZeroValue { field: &'static str },
+
+    /// Only persistent peers are allowed, but there are none
+    PersistentPeersOnlyWithoutPeers,
+
+    /// The GossipSub mesh must hold more peers than discovery connects to
+    MeshExceedsPeerLimit { mesh_n_low: usize, max_peers: usize },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidP2p(e) => write!(f, "invalid P2P configuration: {e}"),
+            Self::InvalidAdaptiveTimeouts(e) => {
+                write!(f, "invalid `consensus.adaptive_timeouts` configuration: {e}")
+            }
+            Self::ZeroDuration { field } => {
+                write!(f, "`{field}` must be positive, eg. \"10s\"")
+            }
+            Self::ZeroValue { field } => write!(f, "`{field}` must be positive"),
+            Self::PersistentPeersOnlyWithoutPeers => write!(
+                f,
+                "`consensus.p2p.persistent_peers_only` is set but `consensus.p2p.persistent_peers` is empty, \
+                 so the node cannot connect to any peer: add persistent peers or unset `persistent_peers_only`"
+            ),
+            Self::MeshExceedsPeerLimit {
+                mesh_n_low,
+                max_peers,
+            } => write!(
+                f,
+                "the GossipSub mesh needs at least {mesh_n_low} peers (`consensus.p2p.protocol.mesh_n_low`) \
+                 but discovery connects to at most {max_peers} peers \
+                 (`consensus.p2p.discovery.num_outbound_peers` + `num_inbound_peers`): \
+                 lower the mesh size or raise the number of peers"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// A configuration with which the node works, but which is likely not what was intended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigWarning {
+    /// Discovery is enabled but there are neither persistent peers nor DNS seeds to bootstrap from
+    DiscoveryWithoutBootstrapPeers,
+
+    /// Discovery is disabled and there are no persistent peers, so the node only gets inbound connections
+    NoPeers,
+
+    /// The GossipSub mesh wants more outbound peers than discovery connects to
+    MeshOutboundExceedsOutboundPeers {
+        mesh_outbound_min: usize,
+        num_outbound_peers: usize,
+    },
+
+    /// Sync responses may be larger than the RPC messages allowed by the network
+    SyncResponseExceedsRpcMaxSize {
+        max_response_size: ByteSize,
+        rpc_max_size: ByteSize,
+    },
+
+    /// Consensus halts at a round before it raises an alert at that round
+    RoundHaltBeforeAlert { alert: u32, halt: u32 },
+
+    /// The votes are only gossiped to the validators, but the other nodes cannot sync the decided values
+    SelectiveGossipWithoutSync,
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DiscoveryWithoutBootstrapPeers => write!(
+                f,
+                "discovery is enabled but neither `consensus.p2p.persistent_peers` nor `consensus.p2p.dns_seeds` \
+                 are set, so discovery can only learn about peers which connect to this node first"
+            ),
+            Self::NoPeers => write!(
+                f,
+                "discovery is disabled and `consensus.p2p.persistent_peers` is empty, \
+                 so this node only connects to the peers which dial it: add persistent peers or enable discovery"
+            ),
+            Self::MeshOutboundExceedsOutboundPeers {
+                mesh_outbound_min,
+                num_outbound_peers,
+            } => write!(
+                f,
+                "the GossipSub mesh wants at least {mesh_outbound_min} outbound peers \
+                 (`consensus.p2p.protocol.mesh_outbound_min`) but discovery dials at most {num_outbound_peers} peers \
+                 (`consensus.p2p.discovery.num_outbound_peers`)"
+            ),
+            Self::SyncResponseExceedsRpcMaxSize {
+                max_response_size,
+                rpc_max_size,
+            } => write!(
+                f,
+                "`value_sync.max_response_size` ({max_response_size}) exceeds `consensus.p2p.rpc_max_size` \
+                 ({rpc_max_size}), so the largest sync responses are dropped by the network: \
+                 raise `rpc_max_size` or lower `max_response_size`"
+            ),
+            Self::RoundHaltBeforeAlert { alert, halt } => write!(
+                f,
+                "`consensus.max_rounds_halt` ({halt}) is not above `consensus.max_rounds_alert` ({alert}), \
+                 so consensus halts before the alert is raised"
+            ),
+            Self::SelectiveGossipWithoutSync => write!(
+                f,
+                "`consensus.p2p.selective_gossip` is set but `value_sync.enabled` is not, \
+                 so the nodes which are not validators never receive the votes needed to decide: \
+                 enable value sync or unset `selective_gossip`"
+            ),
+        }
+    }
+}
+
+/// The issues found when validating a configuration.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConfigReport {
+    pub errors: Vec<ConfigError>,
+    pub warnings: Vec<ConfigWarning>,
+}
+
+impl ConfigReport {
+    /// Whether the node can work with the configuration, ie. there are no errors
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Whether there are neither errors nor warnings
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.warnings.is_empty()
+    }
+
+    fn zero_duration(&mut self, field: &'static str, duration: Duration) {
+        if duration.is_zero() {
+            self.errors.push(ConfigError::ZeroDuration { field });
+        }
+    }
+
+    fn zero_value(&mut self, field: &'static str, value: usize) {
+        if value == 0 {
+            self.errors.push(ConfigError::ZeroValue { field });
+        }
+    }
+}
+
+/// Check the invariants which span several fields of the consensus and sync configuration.
+pub fn validate(consensus: &ConsensusConfig, value_sync: &ValueSyncConfig) -> ConfigReport {
+    let mut report = ConfigReport::default();
+    let p2p = &consensus.p2p;
+
+    if let Err(e) = p2p.validate() {
+        report.errors.push(ConfigError::InvalidP2p(e));
+    }
+
+    report.zero_value("consensus.queue_capacity", consensus.queue_capacity);
+    report.zero_value(
+        "consensus.queue_per_height_capacity",
+        consensus.queue_per_height_capacity,
+    );
+
+    report.zero_duration("consensus.p2p.dial_timeout", p2p.dial_timeout);
+    report.zero_duration("consensus.p2p.upgrade_timeout", p2p.upgrade_timeout);
+    report.zero_duration("consensus.p2p.handshake_timeout", p2p.handshake_timeout);
+
+    if p2p.persistent_peers_only && p2p.persistent_peers.is_empty() {
+        report
+            .errors
+            .push(ConfigError::PersistentPeersOnlyWithoutPeers);
+    }
+
+    let discovery = &p2p.discovery;
+
+    if discovery.enabled {
+        report.zero_value(
+            "consensus.p2p.discovery.num_outbound_peers",
+            discovery.num_outbound_peers,
+        );
+        report.zero_value(
+            "consensus.p2p.discovery.max_connections_per_peer",
+            discovery.max_connections_per_peer,
+        );
+        report.zero_value(
+            "consensus.p2p.discovery.max_connections_per_ip",
+            discovery.max_connections_per_ip,
+        );
+        report.zero_duration(
+            "consensus.p2p.discovery.ephemeral_connection_timeout",
+            discovery.ephemeral_connection_timeout,
+        );
+        report.zero_value(
+            "consensus.p2p.discovery.max_concurrent_dials",
+            discovery.max_concurrent_dials,
+        );
+        report.zero_value(
+            "consensus.p2p.discovery.max_concurrent_dials_per_ip",
+            discovery.max_concurrent_dials_per_ip,
+        );
+
+        if p2p.preferred_peers_file.is_some() {
+            report.zero_value(
+                "consensus.p2p.discovery.max_preferred_peers",
+                discovery.max_preferred_peers,
+            );
+            report.zero_duration(
+                "consensus.p2p.discovery.preferred_peers_max_age",
+                discovery.preferred_peers_max_age,
+            );
+        }
+
+        if p2p.persistent_peers.is_empty() && p2p.dns_seeds.is_empty() && !discovery.mdns {
+            report
+                .warnings
+                .push(ConfigWarning::DiscoveryWithoutBootstrapPeers);
+        }
+    } else if p2p.persistent_peers.is_empty() {
+        report.warnings.push(ConfigWarning::NoPeers);
+    }
+
+    if let PubSubProtocol::GossipSub(gossipsub) = &p2p.protocol {
+        if consensus.enabled && discovery.enabled {
+            let max_peers = discovery.num_outbound_peers + discovery.num_inbound_peers;
+
+            if gossipsub.mesh_n_low() > max_peers {
+                report.errors.push(ConfigError::MeshExceedsPeerLimit {
+                    mesh_n_low: gossipsub.mesh_n_low(),
+                    max_peers,
+                });
+            }
+
+            if gossipsub.mesh_outbound_min() > discovery.num_outbound_peers {
+                report
+                    .warnings
+                    .push(ConfigWarning::MeshOutboundExceedsOutboundPeers {
+                        mesh_outbound_min: gossipsub.mesh_outbound_min(),
+                        num_outbound_peers: discovery.num_outbound_peers,
+                    });
+            }
+        }
+    }
+
+    if let (Some(alert), Some(halt)) = (consensus.max_rounds_alert, consensus.max_rounds_halt) {
+        if halt <= alert {
+            report
+                .warnings
+                .push(ConfigWarning::RoundHaltBeforeAlert { alert, halt });
+        }
+    }
+
+    let flight_recorder = &consensus.flight_recorder;
+
+    if flight_recorder.enabled {
+        report.zero_value(
+            "consensus.flight_recorder.max_heights",
+            flight_recorder.max_heights,
+        );
+        report.zero_value(
+            "consensus.flight_recorder.max_entries_per_height",
+            flight_recorder.max_entries_per_height,
+        );
+    }
+
+    report.zero_value(
+        "consensus.full_proposals.max_entries_per_round",
+        consensus.full_proposals.max_entries_per_round,
+    );
+
+    if consensus.adaptive_timeouts.enabled {
+        if let Err(e) = consensus.adaptive_timeouts.validate() {
+            report.errors.push(ConfigError::InvalidAdaptiveTimeouts(e));
+        }
+    }
+
+    if p2p.selective_gossip && !value_sync.enabled {
+        report
+            .warnings
+            .push(ConfigWarning::SelectiveGossipWithoutSync);
+    }
+
+    if value_sync.enabled {
+        report.zero_duration(
+            "value_sync.status_update_interval",
+            value_sync.status_update_interval,
+        );
+        report.zero_duration("value_sync.request_timeout", value_sync.request_timeout);
+        report.zero_duration(
+            "value_sync.inactive_threshold",
+            value_sync.inactive_threshold,
+        );
+        report.zero_value("value_sync.parallel_requests", value_sync.parallel_requests);
+        report.zero_value("value_sync.batch_size", value_sync.batch_size);
+
+        if value_sync.max_response_size > p2p.rpc_max_size {
+            report
+                .warnings
+                .push(ConfigWarning::SyncResponseExceedsRpcMaxSize {
+                    max_response_size: value_sync.max_response_size,
+                    rpc_max_size: p2p.rpc_max_size,
+                });
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DiscoveryConfig, GossipSubConfig, P2pConfig};
+
+    /// A configuration without any issue: discovery bootstraps from a persistent peer.
+    fn valid() -> (ConsensusConfig, ValueSyncConfig) {
+        let consensus = ConsensusConfig {
+            p2p: P2pConfig {
+                listen_addr: "/ip4/0.0.0.0/tcp/27000".parse().unwrap(),
+                persistent_peers: vec!["/ip4/10.0.0.1/tcp/27000".parse().unwrap()],
+                discovery: DiscoveryConfig {
+                    enabled: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        (consensus, ValueSyncConfig::default())
+    }
+
+    #[test]
+    fn valid_config_has_no_issues() {
+        let (consensus, value_sync) = valid();
+        let report = validate(&consensus, &value_sync);
+
+        assert!(report.is_empty(), "{report:?}");
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn zero_durations_and_values_are_errors() {
+        let (mut consensus, mut value_sync) = valid();
+        consensus.queue_capacity = 0;
+        consensus.p2p.upgrade_timeout = Duration::ZERO;
+        value_sync.request_timeout = Duration::ZERO;
+        value_sync.batch_size = 0;
+
+        let report = validate(&consensus, &value_sync);
+        assert_eq!(
+            report.errors,
+            vec![
+                ConfigError::ZeroValue {
+                    field: "consensus.queue_capacity"
+                },
+                ConfigError::ZeroDuration {
+                    field: "consensus.p2p.upgrade_timeout"
+                },
+                ConfigError::ZeroDuration {
+                    field: "value_sync.request_timeout"
+                },
+                ConfigError::ZeroValue {
+                    field: "value_sync.batch_size"
+                },
+            ]
+        );
+
+        // Sync parameters are not checked when sync is disabled
+        value_sync.enabled = false;
+        let report = validate(&consensus, &value_sync);
+        assert_eq!(
+            report.errors,
+            vec![
+                ConfigError::ZeroValue {
+                    field: "consensus.queue_capacity"
+                },
+                ConfigError::ZeroDuration {
+                    field: "consensus.p2p.upgrade_timeout"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn preferred_peers_bounds_are_only_checked_when_persisted() {
+        let (mut consensus, value_sync) = valid();
+        consensus.p2p.discovery.max_preferred_peers = 0;
+        assert!(validate(&consensus, &value_sync).is_empty());
+
+        consensus.p2p.preferred_peers_file = Some("data/preferred_peers.txt".into());
+        let report = validate(&consensus, &value_sync);
+        assert_eq!(
+            report.errors,
+            vec![ConfigError::ZeroValue {
+                field: "consensus.p2p.discovery.max_preferred_peers"
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_p2p_config_is_an_error() {
+        let (mut consensus, value_sync) = valid();
+        consensus.p2p.persistent_peers = vec!["/ip4/10.0.0.1/udp/27000".parse().unwrap()];
+
+        let report = validate(&consensus, &value_sync);
+        assert!(matches!(
+            report.errors.as_slice(),
+            [ConfigError::InvalidP2p(_)]
+        ));
+    }
+
+    #[test]
+    fn invalid_adaptive_timeouts_are_an_error() {
+        let (mut consensus, value_sync) = valid();
+        consensus.adaptive_timeouts.multiplier = 0.5;
+
+        // Only checked when enabled
+        assert!(validate(&consensus, &value_sync).is_empty());
+
+        consensus.adaptive_timeouts.enabled = true;
+        let report = validate(&consensus, &value_sync);
+        assert!(matches!(
+            report.errors.as_slice(),
+            [ConfigError::InvalidAdaptiveTimeouts(_)]
+        ));
+    }
+
+    #[test]
+    fn missing_peers() {
+        let (mut consensus, value_sync) = valid();
+        consensus.p2p.persistent_peers.clear();
+
+        let report = validate(&consensus, &value_sync);
+        assert!(report.is_valid());
+        assert_eq!(
+            report.warnings,
+            vec![ConfigWarning::DiscoveryWithoutBootstrapPeers]
+        );
+
+        // DNS seeds are enough to bootstrap discovery
+        consensus.p2p.dns_seeds = vec!["/dnsaddr/seed.example.com".parse().unwrap()];
+        assert!(validate(&consensus, &value_sync).is_empty());
+
+        // So is mDNS, on the local network
+        consensus.p2p.dns_seeds.clear();
+        consensus.p2p.discovery.mdns = true;
+        assert!(validate(&consensus, &value_sync).is_empty());
+
+        // But not when discovery is disabled
+        consensus.p2p.discovery.enabled = false;
+        let report = validate(&consensus, &value_sync);
+        assert_eq!(report.warnings, vec![ConfigWarning::NoPeers]);
+
+        consensus.p2p.discovery.enabled = true;
+        consensus.p2p.discovery.mdns = false;
+        consensus.p2p.dns_seeds = vec!["/dnsaddr/seed.example.com".parse().unwrap()];
+
+        consensus.p2p.discovery.enabled = false;
+        let report = validate(&consensus, &value_sync);
+        assert_eq!(report.warnings, vec![ConfigWarning::NoPeers]);
+
+        consensus.p2p.persistent_peers_only = true;
+        let report = validate(&consensus, &value_sync);
+        assert_eq!(
+            report.errors,
+            vec![ConfigError::PersistentPeersOnlyWithoutPeers]
+        );
+    }
+
+    #[test]
+    fn mesh_params_vs_peer_limits() {
+        let (mut consensus, value_sync) = valid();
+        consensus.p2p.protocol =
+            PubSubProtocol::GossipSub(GossipSubConfig::new(8, 12, 6, 4, false, false, true));
+        consensus.p2p.discovery.num_outbound_peers = 3;
+        consensus.p2p.discovery.num_inbound_peers = 2;
+
+        let report = validate(&consensus, &value_sync);
+        assert_eq!(
+            report.errors,
+            vec![ConfigError::MeshExceedsPeerLimit {
+                mesh_n_low: 6,
+                max_peers: 5
+            }]
+        );
+        assert_eq!(
+            report.warnings,
+            vec![ConfigWarning::MeshOutboundExceedsOutboundPeers {
+                mesh_outbound_min: 4,
+                num_outbound_peers: 3
+            }]
+        );
+
+        // The mesh is not constrained by discovery when it is disabled
+        consensus.p2p.discovery.enabled = false;
+        assert!(validate(&consensus, &value_sync).is_empty());
+    }
+
+    #[test]
+    fn inconsistent_limits_are_warnings() {
+        let (mut consensus, mut value_sync) = valid();
+        consensus.max_rounds_alert = Some(5);
+        consensus.max_rounds_halt = Some(5);
+        value_sync.max_response_size = ByteSize::mib(20);
+
+        let report = validate(&consensus, &value_sync);
+        assert!(report.is_valid());
+        assert_eq!(
+            report.warnings,
+            vec![
+                ConfigWarning::RoundHaltBeforeAlert { alert: 5, halt: 5 },
+                ConfigWarning::SyncResponseExceedsRpcMaxSize {
+                    max_response_size: ByteSize::mib(20),
+                    rpc_max_size: ByteSize::mib(10),
+                },
+            ]
+        );
+    }
+    #[test]
+    fn selective_gossip_without_sync_is_a_warning() {
+        let (mut consensus, mut value_sync) = valid();
+        consensus.p2p.selective_gossip = true;
+        assert!(validate(&consensus, &value_sync).is_empty());
+
+        value_sync.enabled = false;
+        let report = validate(&consensus, &value_sync);
+        assert!(report.is_valid());
+        assert_eq!(
+            report.warnings,
+            vec![ConfigWarning::SelectiveGossipWithoutSync]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use std::hint::black_box;
+
+use criterion::measurement::WallTime;
+use criterion::{
+    criterion_group, criterion_main, BatchSize, BenchmarkGroup, Criterion, Throughput,
+};
+
+use arc_malachitebft_core_consensus::{
+    process, Effect, Error, Input, Params, ProposedValue, Resumable, Resume, State,
+};
+use malachitebft_core_types::{
+    NilOrVal, Round, SignedProposal, SignedVote, Validity, ValueOrigin, ValuePayload,
+};
+use malachitebft_metrics::Metrics;
+use malachitebft_test::utils::validators::make_validators;
+use malachitebft_test::{
+    Address, Height, Proposal, Signature, TestContext, Validator, ValidatorSet, Value, Vote,
+};
+
+fn handle_effect(effect: Effect<TestContext>) -> Result<Resume<TestContext>, ()> {
+    use Effect::*;
+    Ok(match effect {
+        VerifySignature(_, _, r) => r.resume_with(true),
+        SignVote(vote, r) => r.resume_with(SignedVote::new(vote, Signature::test())),
+        SignProposal(proposal, r) => {
+            r.resume_with(SignedProposal::new(proposal, Signature::test()))
+        }
+        _ => Resume::Continue,
+    })
+}
+
+fn apply(state: &mut State<TestContext>, metrics: &Metrics, input: Input<TestContext>) {
+    let result: Result<(), Error<TestContext>> = process!(
+        input: input,
+        state: state,
+        metrics: metrics,
+        with: effect => handle_effect(effect)
+    );
+
+    // Errors are not relevant here, only the time spent processing the input
+    let _ = black_box(result);
+}
+
+/// A node with a validator set of `N` validators of equal voting power,
+/// which is one of the validators but not the proposer of the first round.
+struct Setup {
+    validators: Vec<Validator>,
+    address: Address,
+    proposer: Address,
+    metrics: Metrics,
+}
+
+impl Setup {
+    fn new<const N: usize>() -> Self {
+        let validators: Vec<_> = make_validators([1; N])
+            .into_iter()
+            .map(|(v, _)| v)
+            .collect();
+
+        let validator_set = ValidatorSet::new(validators.clone());
+        let proposer = TestContext::new()
+            .select_proposer(&validator_set, Height::new(1), Round::new(0))
+            .address;
+
+        let address = validators
+            .iter()
+            .map(|v| v.address)
+            .find(|address| *address != proposer)
+            .unwrap();
+
+        Self {
+            validators,
+            address,
+            proposer,
+            metrics: Metrics::new(),
+        }
+    }
+
+    /// A fresh consensus state, which did not start the first height yet.
+    fn state(&self) -> State<TestContext> {
+        State::new(
+            TestContext::new(),
+            Height::new(1),
+            ValidatorSet::new(self.validators.clone()),
+            Params {
+                address: self.address,
+                threshold_params: Default::default(),
+                value_payload: ValuePayload::ProposalOnly,
+                enabled: true,
+                require_vote_extensions: false,
+                full_proposal_limits: Default::default(),
+            },
+            1000,
+            1000,
+        )
+    }
+
+    /// The inputs of the happy path of the first height, from its start to the decision:
+    /// the proposal, the proposed value, and the votes of all the other validators in the first round.
+    fn inputs(&self) -> Vec<Input<TestContext>> {
+        let height = Height::new(1);
+        let round = Round::new(0);
+        let value = Value::new(42);
+        let value_id = NilOrVal::Val(value.id());
+
+        let mut inputs = vec![
+            Input::StartHeight(
+                height,
+                ValidatorSet::new(self.validators.clone()),
+                false,
+                None,
+            ),
+            Input::Proposal(SignedProposal::new(
+                Proposal::new(height, round, value.clone(), Round::Nil, self.proposer),
+                Signature::test(),
+            )),
+            Input::ProposedValue(
+                ProposedValue {
+                    height,
+                    round,
+                    valid_round: Round::Nil,
+                    proposer: self.proposer,
+                    value,
+                    validity: Validity::Valid,
+                },
+                ValueOrigin::Consensus,
+            ),
+        ];
+
+        let others = || self.validators.iter().filter(|v| v.address != self.address);
+
+        inputs.extend(others().map(|v| {
+            Input::Vote(SignedVote::new(
+                Vote::new_prevote(height, round, value_id, v.address),
+                Signature::test(),
+            ))
+        }));
+
+        inputs.extend(others().map(|v| {
+            Input::Vote(SignedVote::new(
+                Vote::new_precommit(height, round, value_id, v.address),
+                Signature::test(),
+            ))
+        }));
+
+        inputs
+    }
+}
+
+/// The whole consensus loop of a height on the happy path, from its start to the decision.
+fn bench_height<const N: usize>(group: &mut BenchmarkGroup<'_, WallTime>) {
+    let setup = Setup::new::<N>();
+    let inputs = setup.inputs();
+
+    group.bench_function(format!("{N}_validators"), |b| {
+        b.iter_batched(
+            || (setup.state(), inputs.clone()),
+            |(mut state, inputs)| {
+                for input in inputs {
+                    apply(&mut state, &setup.metrics, input);
+                }
+                assert!(state.driver.step_is_commit());
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn height_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("height");
+
+    // One element per height, so that the throughput is reported in heights per second
+    group.throughput(Throughput::Elements(1));
+
+    bench_height::<4>(&mut group);
+    bench_height::<10>(&mut group);
+    bench_height::<100>(&mut group);
+
+    group.finish();
+}
+
+criterion_group!(benches, height_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,179 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+
+use arc_malachitebft_core_consensus::{
+    process, Effect, Error, Input, Params, Resumable, Resume, State,
+};
+use malachitebft_core_types::{NilOrVal, Round, SignedProposal, SignedVote, ValuePayload};
+use malachitebft_metrics::Metrics;
+use malachitebft_test::utils::validators::make_validators;
+use malachitebft_test::{
+    Address, Height, Signature, TestContext, Validator, ValidatorSet, ValueId, Vote,
+};
+
+const NUM_VALIDATORS: usize = 100;
+
+fn handle_effect(effect: Effect<TestContext>) -> Result<Resume<TestContext>, ()> {
+    use Effect::*;
+    Ok(match effect {
+        VerifySignature(_, _, r) => r.resume_with(true),
+        SignVote(vote, r) => r.resume_with(SignedVote::new(vote, Signature::test())),
+        SignProposal(proposal, r) => {
+            r.resume_with(SignedProposal::new(proposal, Signature::test()))
+        }
+        _ => Resume::Continue,
+    })
+}
+
+fn apply(state: &mut State<TestContext>, metrics: &Metrics, input: Input<TestContext>) {
+    let result: Result<(), Error<TestContext>> = process!(
+        input: input,
+        state: state,
+        metrics: metrics,
+        with: effect => handle_effect(effect)
+    );
+
+    // Errors are not relevant here, only the time spent processing the input
+    let _ = black_box(result);
+}
+
+/// A node with a validator set of 100 validators of equal voting power,
+/// which is one of the validators but not the proposer of the first round.
+struct Setup {
+    validators: Vec<Validator>,
+    address: Address,
+    metrics: Metrics,
+}
+
+impl Setup {
+    fn new() -> Self {
+        let validators: Vec<_> = make_validators([1; NUM_VALIDATORS])
+            .into_iter()
+            .map(|(v, _)| v)
+            .collect();
+
+        let validator_set = ValidatorSet::new(validators.clone());
+        let proposer = TestContext::new()
+            .select_proposer(&validator_set, Height::new(1), Round::new(0))
+            .address;
+
+        let address = validators
+            .iter()
+            .map(|v| v.address)
+            .find(|address| *address != proposer)
+            .unwrap();
+
+        Self {
+            validators,
+            address,
+            metrics: Metrics::new(),
+        }
+    }
+
+    /// A fresh consensus state, which started the first height.
+    fn state(&self) -> State<TestContext> {
+        let validator_set = ValidatorSet::new(self.validators.clone());
+
+        let mut state = State::new(
+            TestContext::new(),
+            Height::new(1),
+            validator_set.clone(),
+            Params {
+                address: self.address,
+                threshold_params: Default::default(),
+                value_payload: ValuePayload::ProposalOnly,
+                enabled: true,
+                require_vote_extensions: false,
+                full_proposal_limits: Default::default(),
+            },
+            1000,
+            1000,
+        );
+
+        apply(
+            &mut state,
+            &self.metrics,
+            Input::StartHeight(Height::new(1), validator_set, false, None),
+        );
+
+        state
+    }
+
+    /// The votes of all the other validators in the first round.
+    fn votes(&self, vote: impl Fn(Address) -> Vote) -> Vec<Input<TestContext>> {
+        self.validators
+            .iter()
+            .filter(|v| v.address != self.address)
+            .map(|v| Input::Vote(SignedVote::new(vote(v.address), Signature::test())))
+            .collect()
+    }
+}
+
+/// Every new vote has its signature verified and is appended to the WAL, even when it does not
+/// reach any threshold, after which the driver returns without any output. Votes which were already
+/// applied, including known equivocating votes, are dropped before any of this.
+fn vote_storm_benchmarks(c: &mut Criterion) {
+    let setup = Setup::new();
+    let height = Height::new(1);
+    let round = Round::new(0);
+    let value = NilOrVal::Val(ValueId::new(1));
+
+    let prevotes = setup.votes(|address| Vote::new_prevote(height, round, value, address));
+    let precommits = setup.votes(|address| Vote::new_precommit(height, round, value, address));
+
+    let mut group = c.benchmark_group("vote_storm");
+    group.throughput(Throughput::Elements(prevotes.len() as u64));
+
+    // Prevotes for a value which was not proposed yet, only the one reaching
+    // the quorum changes the state of the round
+    group.bench_function("prevotes", |b| {
+        b.iter_batched(
+            || (setup.state(), prevotes.clone()),
+            |(mut state, votes)| {
+                for vote in votes {
+                    apply(&mut state, &setup.metrics, vote);
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    // Precommits for a value which was not proposed yet, only the one reaching
+    // the quorum changes the state of the round
+    group.bench_function("precommits", |b| {
+        b.iter_batched(
+            || (setup.state(), precommits.clone()),
+            |(mut state, votes)| {
+                for vote in votes {
+                    apply(&mut state, &setup.metrics, vote);
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    // Gossip re-delivering the prevotes which were already received
+    group.bench_function("duplicate_prevotes", |b| {
+        b.iter_batched(
+            || {
+                let mut state = setup.state();
+                for vote in prevotes.clone() {
+                    apply(&mut state, &setup.metrics, vote);
+                }
+                (state, prevotes.clone())
+            },
+            |(mut state, votes)| {
+                for vote in votes {
+                    apply(&mut state, &setup.metrics, vote);
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, vote_storm_benchmarks);
+criterion_main!(benches);
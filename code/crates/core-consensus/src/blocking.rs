@@ -0,0 +1,45 @@
+use core::fmt::Debug;
+
+use crate::prelude::*;
+
+/// Process an [`Input`] without an async runtime, handling the emitted [`Effect`]s
+/// with a blocking effect handler.
+///
+/// This is the synchronous counterpart of the [`process!`][crate::process] macro,
+/// for embedders which cannot run an async executor, eg. over FFI or in WASM.
+///
+/// The effect handler is called for every effect emitted while processing the input,
+/// and must return the value to resume consensus with, as documented on each [`Effect`].
+/// If the handler fails, the error is logged and consensus is resumed with [`Resume::Continue`].
+///
+/// As with [`process!`][crate::process], effects that take a while to complete, such as
+/// [`Effect::GetValue`], should resume immediately, and their result be fed back to
+/// consensus later on as a new input.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let result = malachitebft_core_consensus::run_sync(
+///     &mut state,
+///     &metrics,
+///     Input::StartHeight(height, validator_set, false, None),
+///     |effect| handle_effect(effect),
+/// );
+/// ```
+pub fn run_sync<Ctx, E>(
+    state: &mut State<Ctx>,
+    metrics: &Metrics,
+    input: Input<Ctx>,
+    mut on_effect: impl FnMut(Effect<Ctx>) -> Result<Resume<Ctx>, E>,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+    E: Debug,
+{
+    crate::process!(
+        input: input,
+        state: state,
+        metrics: metrics,
+        with: effect => on_effect(effect)
+    )
+}
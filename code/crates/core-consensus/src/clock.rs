@@ -0,0 +1,29 @@
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// A source of monotonic time for consensus.
+///
+/// Consensus only reads the clock to measure how long the current height has been running,
+/// in order to observe the target time of a height, if any.
+///
+/// Embedders which cannot use `std::time`, eg. on `wasm32-unknown-unknown`,
+/// must provide their own clock via [`State::with_clock`][crate::State::with_clock].
+pub trait Clock: Send + Sync {
+    /// Time elapsed since an arbitrary but fixed point in the past.
+    ///
+    /// Successive calls must never return a smaller duration.
+    fn now(&self) -> Duration;
+}
+
+/// A [`Clock`] backed by [`std::time::Instant`].
+///
+/// This is the default clock, which is NOT available on `wasm32-unknown-unknown`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed()
+    }
+}
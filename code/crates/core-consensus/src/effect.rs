@@ -121,6 +121,30 @@ where
         resume::Continue,
     ),
 
+    /// Requests the application to validate the value carried by a proposal received from the network.
+    ///
+    /// Only performed when consensus runs in [`ValuePayload::ProposalOnly`] mode,
+    /// where the `Proposal` message carries the full value and no proposal parts are streamed.
+    ///
+    /// The application MUST eventually feed a [`ProposedValue`][crate::input::Input::ProposedValue]
+    /// input to consensus for that value, with its validity and [`ValueOrigin::Consensus`] as origin.
+    ///
+    /// Resume with: [`resume::Continue`]
+    ValidateValue(
+        /// Height of the value
+        Ctx::Height,
+        /// Round of the proposal
+        Round,
+        /// Valid round of the proposal
+        Round,
+        /// Address of the proposer
+        Ctx::Address,
+        /// The proposed value
+        Ctx::Value,
+        /// For resumption
+        resume::Continue,
+    ),
+
     /// Notifies the application that consensus has received a valid sync value response.
     ///
     /// Resume with: [`resume::Continue`]
@@ -147,6 +171,20 @@ where
         resume::Continue,
     ),
 
+    /// Notifies the engine that validators with at least f+1 voting power, ie. at least
+    /// one correct validator, were observed voting at a height higher than the current one.
+    /// All heights below it have therefore been decided, and the node should sync up to it.
+    ///
+    /// Resume with: [`resume::Continue`]
+    FutureHeightObserved(
+        /// The highest height at which the validators were observed voting
+        Ctx::Height,
+        /// The votes of these validators, at that height or above
+        Vec<SignedVote<Ctx>>,
+        /// How to resume
+        resume::Continue,
+    ),
+
     /// Notifies the application that consensus has decided on a value.
     ///
     /// This message includes a commit certificate containing the ID of
@@ -205,6 +243,18 @@ where
         resume::CertificateValidity,
     ),
 
+    /// Verify a batch of commit certificates against the same validator set,
+    /// eg. the certificates of a run of values received via the sync protocol.
+    ///
+    /// Resume with: [`resume::CertificatesValidity`], with the result of the verification
+    /// of each certificate, in the same order as the certificates.
+    VerifyCommitCertificates(
+        Vec<CommitCertificate<Ctx>>,
+        Ctx::ValidatorSet,
+        ThresholdParams,
+        resume::CertificatesValidity,
+    ),
+
     /// Verify a polka certificate
     ///
     /// Resume with: [`resume::CertificateValidity`]
@@ -260,6 +310,40 @@ where
     ),
 }
 
+impl<Ctx: Context> Effect<Ctx> {
+    /// Name of the kind of this effect, in snake case, eg. for labelling metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::CancelAllTimeouts(..) => "cancel_all_timeouts",
+            Self::CancelTimeout(..) => "cancel_timeout",
+            Self::ScheduleTimeout(..) => "schedule_timeout",
+            Self::StartRound(..) => "start_round",
+            Self::PublishConsensusMsg(..) => "publish_consensus_msg",
+            Self::PublishLivenessMsg(..) => "publish_liveness_msg",
+            Self::RepublishVote(..) => "republish_vote",
+            Self::RepublishRoundCertificate(..) => "republish_round_certificate",
+            Self::GetValue(..) => "get_value",
+            Self::RestreamProposal(..) => "restream_proposal",
+            Self::ValidateValue(..) => "validate_value",
+            Self::ValidSyncValue(..) => "valid_sync_value",
+            Self::InvalidSyncValue(..) => "invalid_sync_value",
+            Self::FutureHeightObserved(..) => "future_height_observed",
+            Self::Decide(..) => "decide",
+            Self::Finalize(..) => "finalize",
+            Self::SignVote(..) => "sign_vote",
+            Self::SignProposal(..) => "sign_proposal",
+            Self::VerifySignature(..) => "verify_signature",
+            Self::VerifyCommitCertificate(..) => "verify_commit_certificate",
+            Self::VerifyCommitCertificates(..) => "verify_commit_certificates",
+            Self::VerifyPolkaCertificate(..) => "verify_polka_certificate",
+            Self::VerifyRoundCertificate(..) => "verify_round_certificate",
+            Self::WalAppend(..) => "wal_append",
+            Self::ExtendVote(..) => "extend_vote",
+            Self::VerifyVoteExtension(..) => "verify_vote_extension",
+        }
+    }
+}
+
 /// A value with which the consensus process can be resumed after yielding an [`Effect`].
 #[must_use]
 #[allow(clippy::manual_non_exhaustive)]
@@ -297,6 +381,9 @@ where
 
     /// Resume execution with the result of the verification of the [`CommitCertificate`]
     CertificateValidity(Result<(), CertificateError<Ctx>>),
+
+    /// Resume execution with the result of the verification of each [`CommitCertificate`] of a batch
+    CertificatesValidity(Vec<Result<(), CertificateError<Ctx>>>),
 }
 
 pub mod resume {
@@ -370,6 +457,17 @@ pub mod resume {
         }
     }
 
+    #[derive(Debug, Default)]
+    pub struct CertificatesValidity;
+
+    impl<Ctx: Context> Resumable<Ctx> for CertificatesValidity {
+        type Value = Vec<Result<(), CertificateError<Ctx>>>;
+
+        fn resume_with(self, value: Self::Value) -> Resume<Ctx> {
+            Resume::CertificatesValidity(value)
+        }
+    }
+
     #[derive(Debug, Default)]
     pub struct VoteExtensionValidity;
 
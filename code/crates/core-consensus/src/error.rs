@@ -4,7 +4,9 @@ use std::sync::Arc;
 use derive_where::derive_where;
 
 use malachitebft_core_driver::Error as DriverError;
-use malachitebft_core_types::{CertificateError, CommitCertificate, Context, Round, ValueId};
+use malachitebft_core_types::{
+    CertificateError, CommitCertificate, Context, ErrorKind, Round, ValueId,
+};
 
 use crate::effect::Resume;
 
@@ -58,3 +60,46 @@ where
     #[error("Write-ahead log is corrupted: {0}")]
     WalCorrupted(Arc<io::Error>),
 }
+
+impl<Ctx> Error<Ctx>
+where
+    Ctx: Context,
+{
+    /// The name of this error in snake case, which does not change across releases.
+    ///
+    /// Errors raised by the driver are reported with the code of the underlying driver error.
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::UnexpectedResume(..) => "unexpected_resume",
+            Self::ProposerNotFound(..) => "proposer_not_found",
+            Self::DecisionNotFound(..) => "decision_not_found",
+            Self::DriverProposalNotFound(..) => "driver_proposal_not_found",
+            Self::FullProposalNotFound(..) => "full_proposal_not_found",
+            Self::DriverProcess(e) => e.code(),
+            Self::ValidatorSetNotFound(..) => "validator_set_not_found",
+            Self::InvalidCommitCertificate(..) => "invalid_commit_certificate",
+            Self::MissingPolkaCertificate(..) => "missing_polka_certificate",
+            Self::WalCorrupted(..) => "wal_corrupted",
+        }
+    }
+
+    /// The class of this error, to decide how to recover from it.
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            // The application may not know about the validator set yet, eg. while syncing
+            Self::ValidatorSetNotFound(..) => ErrorKind::Recoverable,
+
+            Self::InvalidCommitCertificate(..) => ErrorKind::ProtocolViolation,
+
+            Self::DriverProcess(e) => e.kind(),
+
+            Self::UnexpectedResume(..)
+            | Self::ProposerNotFound(..)
+            | Self::DecisionNotFound(..)
+            | Self::DriverProposalNotFound(..)
+            | Self::FullProposalNotFound(..)
+            | Self::MissingPolkaCertificate(..)
+            | Self::WalCorrupted(..) => ErrorKind::Internal,
+        }
+    }
+}
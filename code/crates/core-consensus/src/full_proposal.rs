@@ -3,8 +3,11 @@ use tracing::{error, warn};
 
 use derive_where::derive_where;
 
-use malachitebft_core_types::{Context, Proposal, Round, SignedProposal, Validity, Value, ValueId};
+use malachitebft_core_types::{
+    Context, Proposal, Round, RoundMap, SignedProposal, Validity, Value, ValueId,
+};
 
+use crate::params::FullProposalLimits;
 use crate::ProposedValue;
 
 /// A full proposal, ie. a proposal together with its value and validity.
@@ -55,6 +58,15 @@ impl<Ctx: Context> Entry<Ctx> {
     fn full(value: Ctx::Value, validity: Validity, proposal: SignedProposal<Ctx>) -> Self {
         Entry::Full(FullProposal::new(value, validity, proposal))
     }
+
+    fn value_id(&self) -> Option<ValueId<Ctx>> {
+        match self {
+            Entry::Full(p) => Some(p.proposal.value().id()),
+            Entry::ProposalOnly(proposal) => Some(proposal.value().id()),
+            Entry::ValueOnly(value, _) => Some(value.id()),
+            Entry::Empty => None,
+        }
+    }
 }
 
 #[allow(clippy::derivable_impls)]
@@ -90,9 +102,15 @@ impl<Ctx: Context> Default for Entry<Ctx> {
 ///
 /// Note: For `parts_only` mode there is no explicit proposal wire message, instead
 /// one is synthesized by the caller (`on_proposed_value` handler) before it invokes the `store_proposal` method.
+///
+/// The memory used by the keeper is bounded by its [`FullProposalLimits`]: proposals and values
+/// beyond `max_entries_per_round` for a round are dropped, and the rounds far below the current
+/// one are evicted with [`FullProposalKeeper::evict_rounds_below`]. The proposer of a round is
+/// given one more slot, so that its proposal is kept even if others filled the round before it.
 #[derive_where(Clone, Debug, Default)]
 pub struct FullProposalKeeper<Ctx: Context> {
-    keeper: BTreeMap<(Ctx::Height, Round), Vec<Entry<Ctx>>>,
+    keeper: BTreeMap<Ctx::Height, RoundMap<Vec<Entry<Ctx>>>>,
+    limits: FullProposalLimits,
 }
 
 /// Replace a value in a mutable reference with a
@@ -114,6 +132,13 @@ impl<Ctx: Context> FullProposalKeeper<Ctx> {
         Self::default()
     }
 
+    pub fn with_limits(limits: FullProposalLimits) -> Self {
+        Self {
+            keeper: BTreeMap::new(),
+            limits,
+        }
+    }
+
     pub fn proposals_for_value(
         &self,
         proposed_value: &ProposedValue<Ctx>,
@@ -140,8 +165,7 @@ impl<Ctx: Context> FullProposalKeeper<Ctx> {
         value_id: &<Ctx::Value as Value>::Id,
     ) -> Option<&FullProposal<Ctx>> {
         let entries = self
-            .keeper
-            .get(&(*height, round))
+            .entries_at_round(height, round)
             .filter(|entries| !entries.is_empty())?;
 
         for entry in entries {
@@ -162,8 +186,7 @@ impl<Ctx: Context> FullProposalKeeper<Ctx> {
         proposer: &Ctx::Address,
     ) -> Option<&FullProposal<Ctx>> {
         let entries = self
-            .keeper
-            .get(&(*height, round))
+            .entries_at_round(height, round)
             .filter(|entries| !entries.is_empty())?;
 
         for entry in entries {
@@ -212,17 +235,37 @@ impl<Ctx: Context> FullProposalKeeper<Ctx> {
         Entry::ProposalOnly(new_proposal)
     }
 
-    pub fn store_proposal(&mut self, new_proposal: SignedProposal<Ctx>) {
-        let key = (new_proposal.height(), new_proposal.round());
+    /// Whether a new entry can be added to a round holding the given entries,
+    /// the round's proposer being given one slot beyond `max_entries_per_round`.
+    fn has_room_for(
+        limits: &FullProposalLimits,
+        entries: &[Entry<Ctx>],
+        from_proposer: bool,
+    ) -> bool {
+        let max = limits.max_entries_per_round;
+        entries.len() < max || (from_proposer && entries.len() == max)
+    }
+
+    /// Store a proposal, `proposer` being the expected proposer of its round.
+    pub fn store_proposal(&mut self, new_proposal: SignedProposal<Ctx>, proposer: &Ctx::Address) {
+        let (height, round) = (new_proposal.height(), new_proposal.round());
 
-        match self.keeper.get_mut(&key) {
+        let entries = self
+            .keeper
+            .get_mut(&height)
+            .and_then(|rounds| rounds.get_mut(round));
+
+        match entries {
             None => {
                 // First time we see something (a proposal) for this height and round:
                 // - if pol_round is Nil then create a partial proposal with just the proposal.
                 // - if pol_round is defined and if a value at pol_round is present, add full entry,
                 // - else just add the proposal.
                 let new_entry = self.new_entry(new_proposal);
-                self.keeper.insert(key, vec![new_entry]);
+                self.keeper
+                    .entry(height)
+                    .or_default()
+                    .insert(round, vec![new_entry]);
             }
             Some(entries) => {
                 // We have seen values and/ or proposals for this height and round.
@@ -259,15 +302,34 @@ impl<Ctx: Context> FullProposalKeeper<Ctx> {
                     }
                 }
 
+                let from_proposer = new_proposal.validator_address() == proposer;
+                let has_room = Self::has_room_for(&self.limits, entries, from_proposer);
+
+                if !has_room {
+                    warn!(
+                        height = %height,
+                        round = %round,
+                        value.id = ?new_proposal.value().id(),
+                        "Too many proposals and values for this round, dropping proposal"
+                    );
+
+                    return;
+                }
+
                 // Append new partial proposal
                 let new_entry = self.new_entry(new_proposal);
-                self.keeper.entry(key).or_default().push(new_entry);
+                self.keeper
+                    .entry(height)
+                    .or_default()
+                    .get_or_default(round)
+                    .push(new_entry);
             }
         }
     }
 
-    pub fn store_value(&mut self, new_value: &ProposedValue<Ctx>) {
-        self.store_value_at_value_round(new_value);
+    /// Store a proposed value, `proposer` being the expected proposer of its round.
+    pub fn store_value(&mut self, new_value: &ProposedValue<Ctx>, proposer: &Ctx::Address) {
+        self.store_value_at_value_round(new_value, proposer);
         self.upgrade_matching_proposals_at_height(new_value);
     }
 
@@ -313,16 +375,25 @@ impl<Ctx: Context> FullProposalKeeper<Ctx> {
         }
     }
 
-    fn store_value_at_value_round(&mut self, new_value: &ProposedValue<Ctx>) {
-        let key = (new_value.height, new_value.round);
-        let entries = self.keeper.get_mut(&key);
+    fn store_value_at_value_round(
+        &mut self,
+        new_value: &ProposedValue<Ctx>,
+        proposer: &Ctx::Address,
+    ) {
+        let entries = self
+            .keeper
+            .get_mut(&new_value.height)
+            .and_then(|rounds| rounds.get_mut(new_value.round));
 
         match entries {
             None => {
                 // First time we see something (a proposed value) for this height and round
                 // Create a full proposal with just the proposal
                 let entry = Entry::ValueOnly(new_value.value.clone(), new_value.validity);
-                self.keeper.insert(key, vec![entry]);
+                self.keeper
+                    .entry(new_value.height)
+                    .or_default()
+                    .insert(new_value.round, vec![entry]);
             }
             Some(entries) => {
                 // We have seen proposals and/ or values for this height and round.
@@ -375,6 +446,20 @@ impl<Ctx: Context> FullProposalKeeper<Ctx> {
                     }
                 }
 
+                let from_proposer = new_value.proposer == *proposer;
+                let has_room = Self::has_room_for(&self.limits, entries, from_proposer);
+
+                if !has_room {
+                    warn!(
+                        height = %new_value.height,
+                        round = %new_value.round,
+                        value.id = ?new_value.value.id(),
+                        "Too many proposals and values for this round, dropping value"
+                    );
+
+                    return;
+                }
+
                 // Append new value
                 entries.push(Entry::ValueOnly(
                     new_value.value.clone(),
@@ -411,24 +496,76 @@ impl<Ctx: Context> FullProposalKeeper<Ctx> {
         self.keeper.clear();
     }
 
-    /// Returns an iterator over all entries at a given height, across all rounds.
-    fn entries_at(
-        &self,
+    /// Evict the proposals and values of the rounds lower than `min_round` at the given height,
+    /// except the ones for which `keep` returns true given their round and value id,
+    /// eg. the locked and valid values.
+    ///
+    /// Returns the number of entries evicted.
+    pub fn evict_rounds_below(
+        &mut self,
         height: Ctx::Height,
-    ) -> impl Iterator<Item = (&(Ctx::Height, Round), &Vec<Entry<Ctx>>)> {
+        min_round: Round,
+        keep: impl Fn(Round, &ValueId<Ctx>) -> bool,
+    ) -> usize {
+        let Some(rounds) = self.keeper.get_mut(&height) else {
+            return 0;
+        };
+
+        let mut evicted = 0;
+
+        for (round, entries) in rounds.range_mut(..min_round) {
+            entries.retain(|entry| {
+                let retain = entry.value_id().is_some_and(|id| keep(round, &id));
+                evicted += usize::from(!retain);
+                retain
+            });
+        }
+
+        rounds.retain(|_, entries| !entries.is_empty());
+
+        if rounds.is_empty() {
+            self.keeper.remove(&height);
+        }
+
+        evicted
+    }
+
+    /// Total number of entries kept, across all heights and rounds.
+    pub fn len(&self) -> usize {
+        self.keeper
+            .values()
+            .flat_map(RoundMap::values)
+            .map(Vec::len)
+            .sum()
+    }
+
+    /// Whether no entry is kept.
+    pub fn is_empty(&self) -> bool {
+        self.keeper.is_empty()
+    }
+
+    /// Returns the entries at a given height and round, if any.
+    fn entries_at_round(&self, height: &Ctx::Height, round: Round) -> Option<&Vec<Entry<Ctx>>> {
+        self.keeper.get(height)?.get(round)
+    }
+
+    /// Returns an iterator over all entries at a given height, across all rounds.
+    fn entries_at(&self, height: Ctx::Height) -> impl Iterator<Item = (Round, &Vec<Entry<Ctx>>)> {
         self.keeper
-            .range((height, Round::Nil)..)
-            .take_while(move |((h, _), _)| h == &height)
+            .get(&height)
+            .into_iter()
+            .flat_map(RoundMap::iter)
     }
 
     /// Returns a mutable iterator over all entries at a given height, across all rounds.
     fn entries_at_mut(
         &mut self,
         height: Ctx::Height,
-    ) -> impl Iterator<Item = (&(Ctx::Height, Round), &mut Vec<Entry<Ctx>>)> {
+    ) -> impl Iterator<Item = (Round, &mut Vec<Entry<Ctx>>)> {
         self.keeper
-            .range_mut((height, Round::Nil)..)
-            .take_while(move |((h, _), _)| h == &height)
+            .get_mut(&height)
+            .into_iter()
+            .flat_map(RoundMap::iter_mut)
     }
 }
 
@@ -436,12 +573,16 @@ impl<Ctx: Context> FullProposalKeeper<Ctx> {
 mod tests {
     use super::*;
 
-    use malachitebft_test::{Address, Height, TestContext, Value};
+    use malachitebft_test::{Address, Height, Proposal, Signature, TestContext, Value};
 
     fn addr() -> Address {
         Address::new([0; 20])
     }
 
+    fn other_addr() -> Address {
+        Address::new([1; 20])
+    }
+
     fn pv(height: u64, round: u32, value: u64) -> ProposedValue<TestContext> {
         ProposedValue {
             height: Height::new(height),
@@ -454,14 +595,136 @@ mod tests {
     }
 
     fn keys(keeper: &FullProposalKeeper<TestContext>, height: Height) -> Vec<(Height, Round)> {
-        keeper.entries_at(height).map(|(k, _)| *k).collect()
+        keeper
+            .entries_at(height)
+            .map(|(round, _)| (height, round))
+            .collect()
     }
 
     fn keys_mut(
         keeper: &mut FullProposalKeeper<TestContext>,
         height: Height,
     ) -> Vec<(Height, Round)> {
-        keeper.entries_at_mut(height).map(|(k, _)| *k).collect()
+        keeper
+            .entries_at_mut(height)
+            .map(|(round, _)| (height, round))
+            .collect()
+    }
+
+    // --- bounds ---
+
+    fn limited_keeper(max_entries_per_round: usize) -> FullProposalKeeper<TestContext> {
+        FullProposalKeeper::with_limits(FullProposalLimits {
+            round_margin: 2,
+            max_entries_per_round,
+        })
+    }
+
+    #[test]
+    fn entries_per_round_are_bounded() {
+        let mut keeper = limited_keeper(4);
+
+        for value in 0..10 {
+            keeper.store_value(&pv(1, 0, value), &other_addr());
+        }
+
+        assert_eq!(keeper.len(), 4);
+
+        // Values already stored are still updated
+        keeper.store_value(&pv(1, 0, 3), &other_addr());
+        assert_eq!(keeper.len(), 4);
+        assert!(keeper
+            .get_value_by_id(&Height::new(1), &Value::new(3).id())
+            .is_some());
+        assert!(keeper
+            .get_value_by_id(&Height::new(1), &Value::new(4).id())
+            .is_none());
+    }
+
+    #[test]
+    fn proposer_entry_is_kept_when_round_is_full() {
+        let mut keeper = limited_keeper(4);
+        let height = Height::new(1);
+        let (proposer, other) = (addr(), other_addr());
+
+        for value in 0..10 {
+            keeper.store_value(
+                &ProposedValue {
+                    proposer: other,
+                    ..pv(1, 0, value)
+                },
+                &proposer,
+            );
+        }
+
+        assert_eq!(keeper.len(), 4);
+
+        // The proposal of the round's proposer is kept, and its value completes it
+        let proposal = Proposal::new(height, Round::new(0), Value::new(42), Round::Nil, proposer);
+        keeper.store_proposal(SignedProposal::new(proposal, Signature::test()), &proposer);
+        keeper.store_value(&pv(1, 0, 42), &proposer);
+
+        assert_eq!(keeper.len(), 5);
+        assert!(keeper
+            .full_proposal_at_round_and_value(&height, Round::new(0), &Value::new(42).id())
+            .is_some());
+
+        // The proposer is only given one more slot
+        keeper.store_value(&pv(1, 0, 43), &proposer);
+        assert_eq!(keeper.len(), 5);
+    }
+
+    #[test]
+    fn evict_rounds_below_keeps_given_values() {
+        let mut keeper = limited_keeper(16);
+        let height = Height::new(1);
+
+        for round in 0..5 {
+            keeper.store_value(&pv(1, round, 10 + u64::from(round)), &addr());
+        }
+        keeper.store_value(&pv(2, 0, 20), &addr());
+
+        let evicted =
+            keeper.evict_rounds_below(height, Round::new(3), |_, id| *id == Value::new(11).id());
+
+        assert_eq!(evicted, 2);
+        assert_eq!(
+            keys(&keeper, height),
+            vec![
+                (height, Round::new(1)),
+                (height, Round::new(3)),
+                (height, Round::new(4)),
+            ]
+        );
+
+        // Other heights are not affected
+        assert_eq!(
+            keys(&keeper, Height::new(2)),
+            vec![(Height::new(2), Round::new(0))]
+        );
+    }
+
+    #[test]
+    fn memory_does_not_grow_with_rounds() {
+        let mut keeper = limited_keeper(8);
+        let height = Height::new(1);
+
+        // Proposers flooding each round with values, the stale rounds being evicted
+        // when entering a new round, with a margin of 2 rounds
+        for round in 0..1000u32 {
+            if let Some(min_round) = round.checked_sub(2) {
+                keeper.evict_rounds_below(height, Round::new(min_round), |_, _| false);
+            }
+
+            for value in 0..100 {
+                keeper.store_value(
+                    &pv(1, round, u64::from(round) * 1000 + value),
+                    &other_addr(),
+                );
+            }
+
+            assert!(keeper.len() <= 3 * 8);
+        }
     }
 
     // --- entries_at ---
@@ -475,8 +738,8 @@ mod tests {
     #[test]
     fn entries_at_nonexistent_height_returns_empty() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(3, 0, 30));
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(3, 0, 30), &addr());
 
         assert!(keeper.entries_at(Height::new(2)).next().is_none());
     }
@@ -484,7 +747,7 @@ mod tests {
     #[test]
     fn entries_at_single_height_single_round() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
+        keeper.store_value(&pv(1, 0, 10), &addr());
 
         let height = Height::new(1);
         assert_eq!(keys(&keeper, height), vec![(height, Round::new(0))]);
@@ -494,9 +757,9 @@ mod tests {
     fn entries_at_multiple_rounds_are_ordered_by_round() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
         // Insert out of order to verify BTreeMap ordering.
-        keeper.store_value(&pv(1, 2, 12));
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(1, 1, 11));
+        keeper.store_value(&pv(1, 2, 12), &addr());
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(1, 1, 11), &addr());
 
         let height = Height::new(1);
         assert_eq!(
@@ -512,10 +775,10 @@ mod tests {
     #[test]
     fn entries_at_skips_lower_heights() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(1, 5, 15));
-        keeper.store_value(&pv(2, 0, 20));
-        keeper.store_value(&pv(2, 1, 21));
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(1, 5, 15), &addr());
+        keeper.store_value(&pv(2, 0, 20), &addr());
+        keeper.store_value(&pv(2, 1, 21), &addr());
 
         let height = Height::new(2);
         assert_eq!(
@@ -527,10 +790,10 @@ mod tests {
     #[test]
     fn entries_at_stops_before_higher_heights() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(1, 1, 11));
-        keeper.store_value(&pv(2, 0, 20));
-        keeper.store_value(&pv(3, 0, 30));
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(1, 1, 11), &addr());
+        keeper.store_value(&pv(2, 0, 20), &addr());
+        keeper.store_value(&pv(3, 0, 30), &addr());
 
         let height = Height::new(1);
         assert_eq!(
@@ -542,11 +805,11 @@ mod tests {
     #[test]
     fn entries_at_isolates_target_height_between_others() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(2, 0, 20));
-        keeper.store_value(&pv(2, 3, 23));
-        keeper.store_value(&pv(3, 0, 30));
-        keeper.store_value(&pv(4, 0, 40));
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(2, 0, 20), &addr());
+        keeper.store_value(&pv(2, 3, 23), &addr());
+        keeper.store_value(&pv(3, 0, 30), &addr());
+        keeper.store_value(&pv(4, 0, 40), &addr());
 
         let height = Height::new(2);
         assert_eq!(
@@ -558,8 +821,8 @@ mod tests {
     #[test]
     fn entries_at_exposes_stored_entries() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(1, 0, 20)); // second value at same round
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(1, 0, 20), &addr()); // second value at same round
 
         let entries: Vec<_> = keeper.entries_at(Height::new(1)).collect();
         assert_eq!(entries.len(), 1);
@@ -588,8 +851,8 @@ mod tests {
     #[test]
     fn entries_at_mut_nonexistent_height_returns_empty() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(3, 0, 30));
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(3, 0, 30), &addr());
 
         assert!(keeper.entries_at_mut(Height::new(2)).next().is_none());
     }
@@ -597,7 +860,7 @@ mod tests {
     #[test]
     fn entries_at_mut_single_height_single_round() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
+        keeper.store_value(&pv(1, 0, 10), &addr());
 
         let height = Height::new(1);
         assert_eq!(keys_mut(&mut keeper, height), vec![(height, Round::new(0))]);
@@ -606,9 +869,9 @@ mod tests {
     #[test]
     fn entries_at_mut_multiple_rounds_are_ordered_by_round() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 2, 12));
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(1, 1, 11));
+        keeper.store_value(&pv(1, 2, 12), &addr());
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(1, 1, 11), &addr());
 
         let height = Height::new(1);
         assert_eq!(
@@ -624,10 +887,10 @@ mod tests {
     #[test]
     fn entries_at_mut_skips_lower_heights() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(1, 5, 15));
-        keeper.store_value(&pv(2, 0, 20));
-        keeper.store_value(&pv(2, 1, 21));
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(1, 5, 15), &addr());
+        keeper.store_value(&pv(2, 0, 20), &addr());
+        keeper.store_value(&pv(2, 1, 21), &addr());
 
         let height = Height::new(2);
         assert_eq!(
@@ -639,10 +902,10 @@ mod tests {
     #[test]
     fn entries_at_mut_stops_before_higher_heights() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(1, 1, 11));
-        keeper.store_value(&pv(2, 0, 20));
-        keeper.store_value(&pv(3, 0, 30));
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(1, 1, 11), &addr());
+        keeper.store_value(&pv(2, 0, 20), &addr());
+        keeper.store_value(&pv(3, 0, 30), &addr());
 
         let height = Height::new(1);
         assert_eq!(
@@ -654,11 +917,11 @@ mod tests {
     #[test]
     fn entries_at_mut_isolates_target_height_between_others() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(2, 0, 20));
-        keeper.store_value(&pv(2, 3, 23));
-        keeper.store_value(&pv(3, 0, 30));
-        keeper.store_value(&pv(4, 0, 40));
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(2, 0, 20), &addr());
+        keeper.store_value(&pv(2, 3, 23), &addr());
+        keeper.store_value(&pv(3, 0, 30), &addr());
+        keeper.store_value(&pv(4, 0, 40), &addr());
 
         let height = Height::new(2);
         assert_eq!(
@@ -670,10 +933,10 @@ mod tests {
     #[test]
     fn entries_at_mut_allows_in_place_mutation() {
         let mut keeper = FullProposalKeeper::<TestContext>::new();
-        keeper.store_value(&pv(1, 0, 10));
-        keeper.store_value(&pv(1, 1, 11));
+        keeper.store_value(&pv(1, 0, 10), &addr());
+        keeper.store_value(&pv(1, 1, 11), &addr());
         // Noise at other heights to ensure we don't touch them.
-        keeper.store_value(&pv(2, 0, 20));
+        keeper.store_value(&pv(2, 0, 20), &addr());
 
         // Mutate every bucket at height 1: replace the stored value's validity with Invalid.
         for (_, bucket) in keeper.entries_at_mut(Height::new(1)) {
@@ -17,7 +17,7 @@ use proposal::on_proposal;
 use propose::on_propose;
 use proposed_value::on_proposed_value;
 use start_height::reset_and_start_height;
-use sync::on_value_response;
+use sync::{on_value_response, on_value_response_batch};
 use timeout::on_timeout_elapsed;
 use vote::on_vote;
 
@@ -67,6 +67,9 @@ where
             on_proposed_value(co, state, metrics, value, origin).await
         }
         Input::SyncValueResponse(value) => on_value_response(co, state, metrics, value).await,
+        Input::ProcessCommitCertificateBatch(values) => {
+            on_value_response_batch(co, state, metrics, values).await
+        }
         Input::PolkaCertificate(certificate) => {
             on_polka_certificate(co, state, metrics, certificate).await
         }
@@ -65,6 +65,7 @@ where
         }
 
         metrics.block_end();
+        metrics.height_decided_in_round(consensus_round.as_i64());
         metrics
             .consensus_round
             .observe(consensus_round.as_i64() as f64);
@@ -90,10 +91,11 @@ where
         return log_and_finalize(co, state, certificate, extensions).await;
     }
 
-    let elapsed = state
+    let height_start_time = state
         .height_start_time
-        .expect("height_start_time must be set when target_time is set")
-        .elapsed();
+        .expect("height_start_time must be set when target_time is set");
+
+    let elapsed = state.clock.now().saturating_sub(height_start_time);
 
     if elapsed >= target_time {
         debug!(%height, ?elapsed, ?target_time, "Target time exceeded, finalizing immediately");
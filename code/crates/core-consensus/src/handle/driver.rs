@@ -71,6 +71,14 @@ where
             state.last_signed_prevote = None;
             state.last_signed_precommit = None;
 
+            let evicted = state.evict_stale_proposals(*round);
+            if evicted > 0 {
+                debug!(%height, %round, evicted, "Evicted proposals and values of stale rounds");
+
+                #[cfg(feature = "metrics")]
+                metrics.full_proposals_evicted.inc_by(evicted as u64);
+            }
+
             perform!(co, Effect::CancelAllTimeouts(Default::default()));
             perform!(
                 co,
@@ -190,7 +198,7 @@ where
         #[cfg(feature = "metrics")]
         {
             metrics.step_end(prev_step);
-            metrics.step_start(new_step);
+            metrics.step_start(new_step, state.driver.round().as_i64());
         }
     }
 
@@ -256,7 +264,11 @@ where
             if state.is_active_validator() {
                 let signed_proposal = sign_proposal(co, proposal.clone()).await?;
 
-                if signed_proposal.pol_round().is_defined() {
+                // There are no proposal parts to restream in proposal-only mode,
+                // the proposal message itself carries the value.
+                if signed_proposal.pol_round().is_defined()
+                    && state.params.value_payload.include_parts()
+                {
                     perform!(
                         co,
                         Effect::RestreamProposal(
@@ -339,17 +351,19 @@ where
                         .driver
                         .proposal_and_validity_for_round_and_value(vote.round(), value_id.clone())
                     {
-                        perform!(
-                            co,
-                            Effect::RestreamProposal(
-                                signed_proposal.height(),
-                                signed_proposal.round(),
-                                signed_proposal.pol_round(),
-                                signed_proposal.validator_address().clone(),
-                                signed_proposal.value().id(),
-                                Default::default()
-                            )
-                        );
+                        if state.params.value_payload.include_parts() {
+                            perform!(
+                                co,
+                                Effect::RestreamProposal(
+                                    signed_proposal.height(),
+                                    signed_proposal.round(),
+                                    signed_proposal.pol_round(),
+                                    signed_proposal.validator_address().clone(),
+                                    signed_proposal.value().id(),
+                                    Default::default()
+                                )
+                            );
+                        }
 
                         if state.params.value_payload.include_proposal() {
                             perform!(
@@ -2,7 +2,7 @@ use crate::handle::driver::apply_driver_input;
 use crate::handle::signature::verify_signature;
 use crate::input::Input;
 use crate::prelude::*;
-use crate::types::{ConsensusMsg, ProposedValue, SignedConsensusMsg, WalEntry};
+use crate::types::{ConsensusMsg, SignedConsensusMsg, WalEntry};
 use crate::util::pretty::PrettyProposal;
 
 /// Handles an incoming consensus proposal message.
@@ -114,18 +114,29 @@ where
         );
     }
 
-    if state.params.value_payload.proposal_only() {
-        // TODO - pass the received value up to the host that will verify and give back validity and extension.
-        let new_value = ProposedValue {
-            height: signed_proposal.height(),
-            round: signed_proposal.round(),
-            valid_round: signed_proposal.pol_round(),
-            proposer: signed_proposal.validator_address().clone(),
-            value: signed_proposal.value().clone(),
-            validity: Validity::Valid,
-        };
-
-        state.store_value(&new_value);
+    // In proposal-only mode, the proposal carries the full value, which we have not seen before
+    // unless we proposed it ourselves or already validated it. Ask the application to validate it,
+    // it will then feed the value back to us with its validity as a `ProposedValue` input.
+    if state.params.value_payload.proposal_only()
+        && state
+            .full_proposal_at_round_and_value(
+                &proposal_height,
+                proposal_round,
+                signed_proposal.value(),
+            )
+            .is_none()
+    {
+        perform!(
+            co,
+            Effect::ValidateValue(
+                proposal_height,
+                proposal_round,
+                signed_proposal.pol_round(),
+                proposer_address.clone(),
+                signed_proposal.value().clone(),
+                Default::default()
+            )
+        );
     }
 
     if let Some(full_proposal) = state.full_proposal_at_round_and_value(
@@ -62,6 +62,23 @@ where
     Ok(result)
 }
 
+pub async fn verify_commit_certificates<Ctx>(
+    co: &Co<Ctx>,
+    certificates: Vec<CommitCertificate<Ctx>>,
+    validator_set: Ctx::ValidatorSet,
+    threshold_params: ThresholdParams,
+) -> Result<Vec<Result<(), CertificateError<Ctx>>>, Error<Ctx>>
+where
+    Ctx: Context,
+{
+    let results = perform!(co,
+        Effect::VerifyCommitCertificates(certificates, validator_set, threshold_params, Default::default()),
+        Resume::CertificatesValidity(results) => results
+    );
+
+    Ok(results)
+}
+
 pub async fn verify_polka_certificate<Ctx>(
     co: &Co<Ctx>,
     certificate: PolkaCertificate<Ctx>,
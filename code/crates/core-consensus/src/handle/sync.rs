@@ -1,5 +1,5 @@
 use crate::handle::driver::apply_driver_input;
-use crate::handle::signature::verify_commit_certificate;
+use crate::handle::signature::{verify_commit_certificate, verify_commit_certificates};
 use crate::prelude::*;
 use crate::types::ProposedValue;
 
@@ -117,6 +117,87 @@ where
     Ok(())
 }
 
+/// Fast path for catching up through sync: verifies the commit certificates of a contiguous
+/// run of values starting at the current height at once, then processes the value for
+/// the current height as a single value response.
+///
+/// The certificates of the later heights which are valid are kept, and are not verified
+/// again when the values for these heights are processed, unless the validator set
+/// changed in the meantime. The ones which are not valid against the current validator set
+/// are verified again at their height, as the validator set may have changed by then.
+pub async fn on_value_response_batch<Ctx>(
+    co: &Co<Ctx>,
+    state: &mut State<Ctx>,
+    metrics: &Metrics,
+    values: Vec<ValueResponse<Ctx>>,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+{
+    let consensus_height = state.height();
+
+    // Only keep the run of values for consecutive heights starting at the current height
+    let mut next_height = consensus_height;
+    let mut values = values
+        .into_iter()
+        .skip_while(|value| value.certificate.height < consensus_height)
+        .take_while(|value| {
+            let is_next = value.certificate.height == next_height;
+            next_height = next_height.increment();
+            is_next
+        })
+        .collect::<Vec<_>>();
+
+    if values.is_empty() {
+        debug!(
+            consensus.height = %consensus_height,
+            "Received batch of value responses without a value for the current height, ignoring"
+        );
+        return Ok(());
+    }
+
+    if values.len() == 1 {
+        return on_value_response(co, state, metrics, values.remove(0)).await;
+    }
+
+    info!(
+        certificate.height = %consensus_height,
+        count = values.len(),
+        "Processing batch of value responses"
+    );
+
+    let validator_set = state.validator_set().clone();
+    let certificates = values
+        .iter()
+        .map(|value| value.certificate.clone())
+        .collect::<Vec<_>>();
+
+    let results = verify_commit_certificates(
+        co,
+        certificates.clone(),
+        validator_set.clone(),
+        state.params.threshold_params,
+    )
+    .await?;
+
+    if results.len() != certificates.len() {
+        warn!(
+            expected = certificates.len(),
+            actual = results.len(),
+            "Unexpected number of certificate verification results, verifying them one by one"
+        );
+    } else {
+        let verified = certificates
+            .into_iter()
+            .zip(results)
+            .filter_map(|(certificate, result)| result.is_ok().then_some(certificate));
+
+        state.store_verified_certificates(validator_set, state.params.threshold_params, verified);
+    }
+
+    on_value_response(co, state, metrics, values.remove(0)).await
+}
+
 async fn process_commit_certificate<Ctx>(
     co: &Co<Ctx>,
     state: &mut State<Ctx>,
@@ -134,12 +215,15 @@ where
 
     assert_eq!(certificate.height, state.height());
 
-    let validator_set = state.validator_set();
-
-    if let Err(e) = verify_commit_certificate(
+    if state.take_verified_certificate(&certificate) {
+        debug!(
+            certificate.height = %certificate.height,
+            "Certificate was already verified as part of a batch"
+        );
+    } else if let Err(e) = verify_commit_certificate(
         co,
         certificate.clone(),
-        validator_set.clone(),
+        state.validator_set().clone(),
         state.params.threshold_params,
     )
     .await?
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use crate::handle::driver::apply_driver_input;
 use crate::handle::signature::verify_signature;
 use crate::input::Input;
@@ -5,6 +7,10 @@ use crate::prelude::*;
 use crate::types::{ConsensusMsg, SignedConsensusMsg, WalEntry};
 use crate::util::pretty::PrettyVote;
 
+/// Maximum number of heights above the current one for which votes are tracked
+/// to detect that the node fell behind, the lowest heights being kept.
+const MAX_FUTURE_HEIGHTS: usize = 16;
+
 pub async fn on_vote<Ctx>(
     co: &Co<Ctx>,
     state: &mut State<Ctx>,
@@ -45,7 +51,9 @@ where
             "Received vote for higher height, queuing for later"
         );
 
-        state.buffer_input(vote_height, Input::Vote(signed_vote), metrics);
+        state.buffer_input(vote_height, Input::Vote(signed_vote.clone()), metrics);
+
+        observe_future_height(co, state, signed_vote).await?;
 
         return Ok(());
     }
@@ -70,8 +78,17 @@ where
 
     debug_assert_eq!(consensus_height, vote_height);
 
-    // Only process this vote if we have not yet seen it.
-    if state.driver.votes().has_vote(&signed_vote) {
+    // Fast path for the votes which were already applied, eg. gossiped again by other peers,
+    // including the equivocating votes already held as evidence: applying them again would not
+    // change the state of the vote keeper, so neither verify them, nor append them to the WAL,
+    // nor feed them to the driver.
+    if state.driver.votes().is_known_vote(&signed_vote) {
+        debug!(
+            vote.round = %vote_round,
+            validator = %validator_address,
+            "Received known vote, dropping"
+        );
+
         return Ok(());
     }
 
@@ -103,6 +120,132 @@ where
     Ok(())
 }
 
+/// Track the validators voting at a height higher than the current one, and notify the engine
+/// once validators with at least f+1 voting power were seen voting at that height or above.
+///
+/// Only validators of the current validator set are counted, since the validator set
+/// of the higher heights is not known yet.
+async fn observe_future_height<Ctx>(
+    co: &Co<Ctx>,
+    state: &mut State<Ctx>,
+    signed_vote: SignedVote<Ctx>,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+{
+    let vote_height = signed_vote.height();
+
+    // We already know that the network is at that height or above
+    if state.observed_height.is_some_and(|h| h >= vote_height) {
+        return Ok(());
+    }
+
+    let tracked = state.future_height_votes.contains_key(&vote_height);
+    let full = !tracked && state.future_height_votes.len() >= MAX_FUTURE_HEIGHTS;
+
+    // Only a height lower than the highest one tracked can take its place
+    if full
+        && state
+            .future_height_votes
+            .last_key_value()
+            .is_none_or(|(highest, _)| *highest <= vote_height)
+    {
+        return Ok(());
+    }
+
+    let validator_address = signed_vote.validator_address();
+
+    if state
+        .future_height_votes
+        .get(&vote_height)
+        .is_some_and(|votes| votes.contains_key(validator_address))
+    {
+        return Ok(());
+    }
+
+    let Some(validator) = state.validator_set().get_by_address(validator_address) else {
+        return Ok(());
+    };
+
+    let signed_msg = signed_vote.clone().map(ConsensusMsg::Vote);
+    if !verify_signature(co, signed_msg, validator).await? {
+        warn!(
+            vote.height = %vote_height,
+            vote.round = %signed_vote.round(),
+            validator = %validator_address,
+            "Received vote for higher height with invalid signature"
+        );
+
+        return Ok(());
+    }
+
+    // Evict the highest height tracked only for an authenticated vote
+    if full {
+        state.future_height_votes.pop_last();
+    }
+
+    state
+        .future_height_votes
+        .entry(vote_height)
+        .or_default()
+        .insert(validator_address.clone(), signed_vote);
+
+    let Some((observed_height, evidence)) = future_height_quorum(state) else {
+        return Ok(());
+    };
+
+    info!(
+        consensus.height = %state.height(),
+        observed.height = %observed_height,
+        "Validators with f+1 voting power are voting at a higher height"
+    );
+
+    state.observed_height = Some(observed_height);
+    state
+        .future_height_votes
+        .retain(|h, _| *h > observed_height);
+
+    perform!(
+        co,
+        Effect::FutureHeightObserved(observed_height, evidence, Default::default())
+    );
+
+    Ok(())
+}
+
+/// Find the highest height such that validators with at least f+1 voting power
+/// voted at that height or above, along with one vote of each of these validators.
+fn future_height_quorum<Ctx>(state: &State<Ctx>) -> Option<(Ctx::Height, Vec<SignedVote<Ctx>>)>
+where
+    Ctx: Context,
+{
+    let validator_set = state.validator_set();
+    let total_voting_power = validator_set.total_voting_power();
+    let threshold = state.params.threshold_params.honest;
+
+    let mut evidence = BTreeMap::new();
+    let mut voting_power = 0;
+
+    for (height, votes) in state.future_height_votes.iter().rev() {
+        for (address, vote) in votes {
+            if evidence.contains_key(address) {
+                continue;
+            }
+
+            if let Some(validator) = validator_set.get_by_address(address) {
+                voting_power += validator.voting_power();
+                evidence.insert(address, vote);
+            }
+        }
+
+        if threshold.is_met(voting_power, total_voting_power) {
+            return Some((*height, evidence.into_values().cloned().collect()));
+        }
+    }
+
+    None
+}
+
 pub async fn verify_signed_vote<Ctx>(
     co: &Co<Ctx>,
     state: &State<Ctx>,
@@ -166,6 +309,19 @@ where
     };
 
     let Some(extension) = vote.extension() else {
+        if state.params.require_vote_extensions {
+            warn!(
+                consensus.height = %state.height(),
+                vote.height = %vote.height(),
+                vote.round = %vote.round(),
+                validator = %validator.address(),
+                "Received precommit without a required vote extension: {}",
+                PrettyVote::<Ctx>(&vote.message)
+            );
+
+            return Ok(false);
+        }
+
         return Ok(true);
     };
 
@@ -48,4 +48,14 @@ where
 
     /// We have received a synced value via the sync protocol.
     SyncValueResponse(ValueResponse<Ctx>),
+
+    /// We have received a contiguous run of synced values via the sync protocol,
+    /// in increasing order of height, starting at the current height.
+    ///
+    /// Fast path for catching up: the commit certificates of the whole run are verified at once,
+    /// with a single [`Effect::VerifyCommitCertificates`](crate::Effect::VerifyCommitCertificates),
+    /// and the value for the current height is then processed as a [`Input::SyncValueResponse`].
+    /// The certificates for the later heights are kept, so that they are not verified again
+    /// when the values for these heights are processed, as long as the validator set is unchanged.
+    ProcessCommitCertificateBatch(Vec<ValueResponse<Ctx>>),
 }
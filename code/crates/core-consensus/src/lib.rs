@@ -7,13 +7,19 @@ mod input;
 pub use input::Input;
 
 mod state;
-pub use state::State;
+pub use state::{State, VerifiedCertificates};
 
 mod error;
 pub use error::Error;
 
+mod clock;
+pub use clock::{Clock, SystemClock};
+
+mod blocking;
+pub use blocking::run_sync;
+
 mod params;
-pub use params::{Params, ThresholdParams};
+pub use params::{FullProposalLimits, Params, ThresholdParams};
 
 #[doc(hidden)]
 pub use params::HIDDEN_LOCK_ROUND;
@@ -25,6 +31,7 @@ mod types;
 pub use types::*;
 
 pub mod full_proposal;
+pub mod proposer;
 pub mod util;
 
 mod macros;
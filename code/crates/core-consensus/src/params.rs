@@ -22,4 +22,36 @@ pub struct Params<Ctx: Context> {
 
     /// Whether consensus is enabled for this node
     pub enabled: bool,
+
+    /// Whether precommits for a value must carry a vote extension.
+    ///
+    /// When enabled, such precommits without a vote extension are rejected,
+    /// so that a decision is always delivered together with the vote extensions
+    /// of validators holding more than 2/3 of the voting power.
+    pub require_vote_extensions: bool,
+
+    /// Bounds on the proposals and values kept for the current height
+    pub full_proposal_limits: FullProposalLimits,
+}
+
+/// Bounds on the memory used by the proposals and values kept for the current height,
+/// which would otherwise grow with the number of rounds and of values sent by proposers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FullProposalLimits {
+    /// When entering a new round, the proposals and values of the rounds lower than
+    /// the new round minus this margin are evicted, except those for the locked or valid value.
+    pub round_margin: u32,
+
+    /// Maximum number of distinct proposals and values kept for a round,
+    /// the ones received beyond that being dropped.
+    pub max_entries_per_round: usize,
+}
+
+impl Default for FullProposalLimits {
+    fn default() -> Self {
+        Self {
+            round_margin: 2,
+            max_entries_per_round: 16,
+        }
+    }
 }
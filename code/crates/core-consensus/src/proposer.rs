@@ -0,0 +1,647 @@
+//! Proposer selection strategies, which a [`Context`] can delegate
+//! [`Context::select_proposer`] to.
+//!
+//! - [`RoundRobin`] rotates through the validators in the order of the validator set,
+//!   regardless of their voting power.
+//! - [`WeightedRoundRobin`] selects proposers in proportion to their voting power, with the
+//!   proposer priority algorithm of CometBFT, so that a chain migrating from CometBFT keeps
+//!   the exact same proposer rotation.
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+
+use malachitebft_core_types::{Context, Height, Round, Validator, ValidatorSet, VotingPower};
+
+/// Maximum total voting power of a validator set supported by [`WeightedRoundRobin`],
+/// as in CometBFT, so that the proposer priorities cannot overflow.
+pub const MAX_TOTAL_VOTING_POWER: i64 = i64::MAX / 8;
+
+/// Maximum distance between the lowest and highest priorities, relative to the total voting
+/// power, above which the priorities are scaled down.
+const PRIORITY_WINDOW_SIZE_FACTOR: i64 = 2;
+
+/// Defines how to select the proposer amongst a validator set for a given height and round.
+pub trait ProposerSelector<Ctx>
+where
+    Self: Send + Sync,
+    Ctx: Context,
+{
+    /// Select the proposer of the given round of the given height in the validator set.
+    ///
+    /// # Important
+    /// This function must be deterministic, and return the same proposer on every node
+    /// for a given height, round and validator set.
+    fn select_proposer<'a>(
+        &self,
+        validator_set: &'a Ctx::ValidatorSet,
+        height: Ctx::Height,
+        round: Round,
+    ) -> &'a Ctx::Validator;
+}
+
+/// Rotates through the validators in the order of the validator set,
+/// starting with the first validator at the first round of height 1.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RoundRobin;
+
+impl<Ctx> ProposerSelector<Ctx> for RoundRobin
+where
+    Ctx: Context,
+{
+    fn select_proposer<'a>(
+        &self,
+        validator_set: &'a Ctx::ValidatorSet,
+        height: Ctx::Height,
+        round: Round,
+    ) -> &'a Ctx::Validator {
+        assert!(validator_set.count() > 0);
+        assert!(round.is_defined());
+
+        let height = height.as_u64() as usize;
+        let round = round.as_i64() as usize;
+        let index = (height.wrapping_sub(1) + round) % validator_set.count();
+
+        validator_set
+            .get_by_index(index)
+            .expect("proposer index is valid")
+    }
+}
+
+/// A validator along with its proposer priority.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProposerPriority<A> {
+    pub address: A,
+    pub voting_power: i64,
+    pub priority: i64,
+}
+
+/// The proposer priorities of a validator set, as maintained by CometBFT.
+///
+/// At every step, the priority of each validator grows by its voting power, and the validator
+/// with the highest priority, or the lowest address amongst those with the highest priority,
+/// becomes the proposer and sees its priority decrease by the total voting power.
+/// Priorities are then kept centered around zero, within a window of twice the total voting power.
+///
+/// Addresses are compared with their [`Ord`] implementation, which must order them as their
+/// bytes for the rotation to match the one of CometBFT.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProposerPriorities<A> {
+    validators: Vec<ProposerPriority<A>>,
+    proposer: usize,
+}
+
+impl<A> ProposerPriorities<A>
+where
+    A: Clone + Ord,
+{
+    /// The priorities of a new validator set, eg. the one of the genesis,
+    /// starting at zero and incremented once, as in CometBFT.
+    ///
+    /// # Panics
+    /// If the validator set is empty or its total voting power exceeds [`MAX_TOTAL_VOTING_POWER`].
+    pub fn new(validators: impl IntoIterator<Item = (A, VotingPower)>) -> Self {
+        let mut priorities = Self::with_priorities(
+            validators
+                .into_iter()
+                .map(|(address, voting_power)| (address, voting_power, 0)),
+        );
+
+        priorities.increment(1);
+        priorities
+    }
+
+    /// The given priorities, eg. as found in the state of a CometBFT chain at the height at
+    /// which it is migrated. The proposer is the validator with the highest priority.
+    ///
+    /// # Panics
+    /// If the validator set is empty or its total voting power exceeds [`MAX_TOTAL_VOTING_POWER`].
+    pub fn with_priorities(validators: impl IntoIterator<Item = (A, VotingPower, i64)>) -> Self {
+        let validators = validators
+            .into_iter()
+            .map(|(address, voting_power, priority)| ProposerPriority {
+                address,
+                voting_power: to_i64(voting_power),
+                priority,
+            })
+            .collect::<Vec<_>>();
+
+        assert!(!validators.is_empty(), "empty validator set");
+
+        let mut priorities = Self {
+            validators,
+            proposer: 0,
+        };
+
+        // Check the total voting power
+        priorities.total_voting_power();
+
+        priorities.proposer = priorities.highest_priority();
+        priorities
+    }
+
+    /// The current proposer.
+    pub fn proposer(&self) -> &A {
+        &self.validators[self.proposer].address
+    }
+
+    /// The validators along with their priorities.
+    pub fn validators(&self) -> &[ProposerPriority<A>] {
+        &self.validators
+    }
+
+    /// The total voting power of the validators.
+    pub fn total_voting_power(&self) -> i64 {
+        let total = self
+            .validators
+            .iter()
+            .try_fold(0_i64, |total, v| total.checked_add(v.voting_power))
+            .filter(|total| *total <= MAX_TOTAL_VOTING_POWER);
+
+        total.expect("total voting power exceeds the maximum")
+    }
+
+    /// Move the priorities the given number of steps forward, which is done once at every height,
+    /// and as many times as the round number to find the proposer of a round.
+    pub fn increment(&mut self, times: u32) {
+        assert!(times > 0, "cannot increment the priorities zero times");
+
+        let total = self.total_voting_power();
+
+        self.rescale(PRIORITY_WINDOW_SIZE_FACTOR * total);
+        self.shift_by_average();
+
+        for _ in 0..times {
+            for v in &mut self.validators {
+                v.priority = v.priority.saturating_add(v.voting_power);
+            }
+
+            self.proposer = self.highest_priority();
+
+            let proposer = &mut self.validators[self.proposer];
+            proposer.priority = proposer.priority.saturating_sub(total);
+        }
+    }
+
+    /// Apply a change of validator set, keeping the priorities of the validators
+    /// remaining in the set, and giving the new validators a priority of -1.125 times the
+    /// total voting power, as CometBFT does, so that they cannot propose right after joining.
+    ///
+    /// The validator set is left unchanged if it contains the same validators with the same
+    /// voting power, in any order. Returns whether the validator set changed.
+    pub fn update(&mut self, validators: impl IntoIterator<Item = (A, VotingPower)>) -> bool {
+        let validators = validators
+            .into_iter()
+            .map(|(address, voting_power)| (address, to_i64(voting_power)))
+            .collect::<Vec<_>>();
+
+        assert!(!validators.is_empty(), "empty validator set");
+
+        if self.same_validators(&validators) {
+            return false;
+        }
+
+        // Total voting power after the updates, but before the removals
+        let removed_power = self
+            .validators
+            .iter()
+            .filter(|v| !validators.iter().any(|(address, _)| *address == v.address))
+            .map(|v| v.voting_power)
+            .sum::<i64>();
+
+        let total = validators.iter().map(|(_, vp)| *vp).sum::<i64>() + removed_power;
+        let new_priority = -(total + (total >> 3));
+
+        let validators = validators
+            .into_iter()
+            .map(|(address, voting_power)| {
+                let priority = self
+                    .validators
+                    .iter()
+                    .find(|v| v.address == address)
+                    .map_or(new_priority, |v| v.priority);
+
+                ProposerPriority {
+                    address,
+                    voting_power,
+                    priority,
+                }
+            })
+            .collect();
+
+        self.validators = validators;
+
+        let total = self.total_voting_power();
+        self.rescale(PRIORITY_WINDOW_SIZE_FACTOR * total);
+        self.shift_by_average();
+
+        self.proposer = self.highest_priority();
+        true
+    }
+
+    fn same_validators(&self, validators: &[(A, i64)]) -> bool {
+        validators.len() == self.validators.len()
+            && validators.iter().all(|(address, voting_power)| {
+                self.validators
+                    .iter()
+                    .any(|v| v.address == *address && v.voting_power == *voting_power)
+            })
+    }
+
+    /// Index of the validator with the highest priority, or with the lowest address
+    /// amongst those with the highest priority.
+    fn highest_priority(&self) -> usize {
+        let mut highest = 0;
+
+        for (i, v) in self.validators.iter().enumerate().skip(1) {
+            let best = &self.validators[highest];
+
+            if v.priority > best.priority
+                || (v.priority == best.priority && v.address < best.address)
+            {
+                highest = i;
+            }
+        }
+
+        highest
+    }
+
+    /// Scale the priorities down if the distance between the lowest and highest ones
+    /// exceeds the given window.
+    fn rescale(&mut self, window: i64) {
+        if window <= 0 {
+            return;
+        }
+
+        let max = self
+            .validators
+            .iter()
+            .map(|v| v.priority)
+            .max()
+            .unwrap_or(0);
+        let min = self
+            .validators
+            .iter()
+            .map(|v| v.priority)
+            .min()
+            .unwrap_or(0);
+        let diff = max.saturating_sub(min);
+
+        if diff > window {
+            let ratio = (diff + window - 1) / window;
+
+            for v in &mut self.validators {
+                v.priority /= ratio;
+            }
+        }
+    }
+
+    /// Center the priorities around zero.
+    fn shift_by_average(&mut self) {
+        let sum = self
+            .validators
+            .iter()
+            .map(|v| i128::from(v.priority))
+            .sum::<i128>();
+
+        // Rounded towards negative infinity, as CometBFT does with `big.Int.Div`
+        let average = sum.div_euclid(self.validators.len() as i128);
+        let average = i64::try_from(average).expect("average priority fits in an i64");
+
+        for v in &mut self.validators {
+            v.priority = v.priority.saturating_sub(average);
+        }
+    }
+}
+
+fn to_i64(voting_power: VotingPower) -> i64 {
+    i64::try_from(voting_power)
+        .ok()
+        .filter(|vp| *vp <= MAX_TOTAL_VOTING_POWER)
+        .expect("voting power exceeds the maximum")
+}
+
+/// Selects proposers in proportion to their voting power, with the proposer priority
+/// algorithm of CometBFT.
+///
+/// The priorities are computed from those at an anchor height, either the initial height of
+/// the chain or a later height at which they were imported, eg. from CometBFT, by moving them
+/// forward once per height, applying the changes of the validator set at the height at which
+/// they occur, and once more per round within a height. The priorities at the latest height
+/// are cached, so that selecting the proposer of the next height only requires a single step,
+/// and the selector anchors itself again at every height at which the validator set changes,
+/// so that the priorities of a lower height are computed again with the validator sets which
+/// were in effect up to that height.
+///
+/// # Important
+/// Every node must compute the priorities from the same anchor, and see every change of the
+/// validator set, for the proposers to be the same on every node. As the changes of the validator
+/// set are only seen at the heights for which a proposer is selected, a selector which skips
+/// heights, eg. a node which restarts or catches up at a height above its anchor, assumes that
+/// the validator set did not change in between. Nothing in this type enforces it, so applications
+/// whose validator set changes must persist the [`priorities`](Self::priorities) at the heights
+/// they commit, and resume from the ones of the height they restart at with
+/// [`WeightedRoundRobin::resume`], rather than from the initial height.
+pub struct WeightedRoundRobin<Ctx>
+where
+    Ctx: Context,
+{
+    anchors: Mutex<Anchors<Ctx>>,
+}
+
+/// Priorities from which [`WeightedRoundRobin`] computes the ones of a height.
+struct Anchors<Ctx>
+where
+    Ctx: Context,
+{
+    /// Priorities at the initial or resumed height,
+    /// and at every later height at which the validator set changed
+    changes: BTreeMap<Ctx::Height, ProposerPriorities<Ctx::Address>>,
+
+    /// Priorities at the latest height
+    latest: (Ctx::Height, ProposerPriorities<Ctx::Address>),
+}
+
+impl<Ctx> WeightedRoundRobin<Ctx>
+where
+    Ctx: Context,
+{
+    /// Start from the validator set of the chain at its initial height.
+    pub fn new(initial_height: Ctx::Height, validator_set: &Ctx::ValidatorSet) -> Self {
+        Self::resume(
+            initial_height,
+            ProposerPriorities::new(validators_of::<Ctx>(validator_set)),
+        )
+    }
+
+    /// Resume from the priorities of the validators at the given height.
+    pub fn resume(height: Ctx::Height, priorities: ProposerPriorities<Ctx::Address>) -> Self {
+        Self {
+            anchors: Mutex::new(Anchors {
+                changes: BTreeMap::from([(height, priorities.clone())]),
+                latest: (height, priorities),
+            }),
+        }
+    }
+
+    /// The priorities of the validators at the first round of the given height,
+    /// whose validator set is the given one.
+    ///
+    /// Heights below the latest one are computed from the closest anchor below them,
+    /// ie. the height of the last change of the validator set before them.
+    ///
+    /// # Panics
+    /// If the height is below the initial or resumed height.
+    pub fn priorities(
+        &self,
+        validator_set: &Ctx::ValidatorSet,
+        height: Ctx::Height,
+    ) -> ProposerPriorities<Ctx::Address> {
+        let mut anchors = self.anchors.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if anchors.latest.0 == height {
+            return anchors.latest.1.clone();
+        }
+
+        let is_latest = anchors.latest.0 < height;
+
+        let (mut current, mut priorities) = if is_latest {
+            anchors.latest.clone()
+        } else {
+            let (first, _) = anchors
+                .changes
+                .first_key_value()
+                .expect("there is at least one anchor");
+
+            let (current, priorities) = anchors
+                .changes
+                .range(..=height)
+                .next_back()
+                .unwrap_or_else(|| {
+                    panic!(
+                        "cannot select the proposer at height {height}, below the anchor height {first}"
+                    )
+                });
+
+            (*current, priorities.clone())
+        };
+
+        while current < height {
+            let changed = priorities.update(validators_of::<Ctx>(validator_set));
+            priorities.increment(1);
+            current = current.increment();
+
+            if is_latest && changed {
+                anchors.changes.insert(current, priorities.clone());
+            }
+        }
+
+        if is_latest {
+            anchors.latest = (height, priorities.clone());
+        }
+
+        priorities
+    }
+}
+
+impl<Ctx> ProposerSelector<Ctx> for WeightedRoundRobin<Ctx>
+where
+    Ctx: Context,
+{
+    fn select_proposer<'a>(
+        &self,
+        validator_set: &'a Ctx::ValidatorSet,
+        height: Ctx::Height,
+        round: Round,
+    ) -> &'a Ctx::Validator {
+        let round = round.as_u32().expect("round is defined");
+
+        let mut priorities = self.priorities(validator_set, height);
+        if round > 0 {
+            priorities.increment(round);
+        }
+
+        validator_set
+            .get_by_address(priorities.proposer())
+            .expect("proposer is in the validator set")
+    }
+}
+
+fn validators_of<Ctx: Context>(
+    validator_set: &Ctx::ValidatorSet,
+) -> impl Iterator<Item = (Ctx::Address, VotingPower)> + '_ {
+    validator_set
+        .iter()
+        .map(|v| (v.address().clone(), v.voting_power()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proposers(priorities: &mut ProposerPriorities<&'static str>, count: usize) -> String {
+        let mut proposers = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            proposers.push(*priorities.proposer());
+            priorities.increment(1);
+        }
+
+        proposers.join(" ")
+    }
+
+    fn priorities_of<A: Clone + Ord>(priorities: &ProposerPriorities<A>) -> Vec<i64> {
+        priorities.validators().iter().map(|v| v.priority).collect()
+    }
+
+    /// `TestProposerSelection1` in CometBFT
+    #[test]
+    fn cometbft_proposer_selection_1() {
+        let mut priorities = ProposerPriorities::new([("foo", 1000), ("bar", 300), ("baz", 330)]);
+
+        let expected = "foo baz foo bar foo foo baz foo bar foo foo baz foo foo bar foo baz foo foo bar \
+                        foo foo baz foo bar foo foo baz foo bar foo foo baz foo foo bar foo baz foo foo bar \
+                        foo baz foo foo bar foo baz foo foo bar foo baz foo foo foo baz bar foo foo foo baz \
+                        foo bar foo foo baz foo bar foo foo baz foo bar foo foo baz foo bar foo foo baz foo \
+                        foo bar foo baz foo foo bar foo baz foo foo bar foo baz foo foo";
+
+        assert_eq!(proposers(&mut priorities, 99), expected);
+    }
+
+    /// `TestProposerSelection2` in CometBFT
+    #[test]
+    fn cometbft_proposer_selection_2() {
+        // With the same voting power, validators propose in the order of their addresses
+        let mut priorities = ProposerPriorities::new([("\x00", 100), ("\x01", 100), ("\x02", 100)]);
+        assert_eq!(
+            proposers(&mut priorities, 15),
+            ["\x00 \x01 \x02"; 5].join(" ")
+        );
+
+        // One validator has more voting power than the others, but not enough to propose twice in a row
+        let mut priorities = ProposerPriorities::new([("\x00", 100), ("\x01", 100), ("\x02", 400)]);
+        assert_eq!(proposers(&mut priorities, 2), "\x02 \x00");
+
+        // One validator has enough voting power to propose twice in a row
+        let mut priorities = ProposerPriorities::new([("\x00", 100), ("\x01", 100), ("\x02", 401)]);
+        assert_eq!(proposers(&mut priorities, 3), "\x02 \x02 \x00");
+
+        // Each validator proposes in proportion to its voting power
+        let mut priorities = ProposerPriorities::new([("\x00", 4), ("\x01", 5), ("\x02", 3)]);
+        let proposers = proposers(&mut priorities, 120);
+
+        for (address, count) in [("\x00", 40), ("\x01", 50), ("\x02", 30)] {
+            assert_eq!(
+                proposers.split(' ').filter(|p| *p == address).count(),
+                count
+            );
+        }
+    }
+
+    /// `TestAveragingInIncrementProposerPriorityWithVotingPower` in CometBFT
+    #[test]
+    fn cometbft_averaging_with_voting_power() {
+        let (vp0, vp1, vp2) = (10, 1, 1);
+        let total = vp0 + vp1 + vp2;
+
+        let initial = ProposerPriorities::with_priorities([
+            ("\x00", vp0 as u64, 0),
+            ("\x01", vp1 as u64, 0),
+            ("\x02", vp2 as u64, 0),
+        ]);
+
+        let cases = [
+            (1, [vp0 - total, vp1, vp2], "\x00"),
+            (2, [2 * (vp0 - total), 2 * vp1, 2 * vp2], "\x00"),
+            (3, [3 * (vp0 - total), 3 * vp1, 3 * vp2], "\x00"),
+            (4, [4 * (vp0 - total), 4 * vp1, 4 * vp2], "\x00"),
+            (
+                5,
+                [4 * (vp0 - total) + vp0, 5 * vp1 - total, 5 * vp2],
+                "\x01",
+            ),
+            (6, [6 * vp0 - 5 * total, 6 * vp1 - total, 6 * vp2], "\x00"),
+            (7, [7 * vp0 - 6 * total, 7 * vp1 - total, 7 * vp2], "\x00"),
+            (8, [8 * vp0 - 7 * total, 8 * vp1 - total, 8 * vp2], "\x00"),
+            (
+                9,
+                [9 * vp0 - 7 * total, 9 * vp1 - total, 9 * vp2 - total],
+                "\x02",
+            ),
+            (
+                10,
+                [10 * vp0 - 8 * total, 10 * vp1 - total, 10 * vp2 - total],
+                "\x00",
+            ),
+            (
+                11,
+                [11 * vp0 - 9 * total, 11 * vp1 - total, 11 * vp2 - total],
+                "\x00",
+            ),
+        ];
+
+        for (times, expected, proposer) in cases {
+            let mut priorities = initial.clone();
+            priorities.increment(times);
+
+            assert_eq!(priorities_of(&priorities), expected, "after {times} steps");
+            assert_eq!(*priorities.proposer(), proposer, "after {times} steps");
+        }
+    }
+
+    #[test]
+    fn average_is_rounded_down() {
+        let mut priorities =
+            ProposerPriorities::with_priorities([("\x00", 1, -3), ("\x01", 1, 0), ("\x02", 1, 1)]);
+
+        // The sum is -2, whose average is rounded down to -1
+        priorities.shift_by_average();
+        assert_eq!(priorities_of(&priorities), [-2, 1, 2]);
+    }
+
+    #[test]
+    fn priorities_are_rescaled() {
+        let mut priorities =
+            ProposerPriorities::with_priorities([("\x00", 1, 100), ("\x01", 1, -100)]);
+
+        // The window is 2 * 2 = 4, the distance 200, hence a ratio of 50
+        priorities.increment(1);
+        assert_eq!(priorities_of(&priorities), [1, -1]);
+        assert_eq!(*priorities.proposer(), "\x00");
+    }
+
+    #[test]
+    fn new_validators_start_with_a_low_priority() {
+        let mut priorities = ProposerPriorities::new([("\x00", 10), ("\x01", 10)]);
+        priorities.update([("\x00", 10), ("\x01", 10), ("\x02", 10)]);
+
+        // -1.125 * 30, centered around zero along with the other priorities
+        assert_eq!(priorities_of(&priorities), [1, 21, -22]);
+
+        // The new validator only proposes after the others
+        priorities.increment(1);
+        assert_ne!(*priorities.proposer(), "\x02");
+
+        // Updating with the same validators does nothing
+        let before = priorities.clone();
+        priorities.update([("\x02", 10), ("\x01", 10), ("\x00", 10)]);
+        assert_eq!(priorities, before);
+    }
+
+    #[test]
+    fn removed_validators_are_forgotten() {
+        let mut priorities = ProposerPriorities::new([("\x00", 10), ("\x01", 20), ("\x02", 30)]);
+        priorities.update([("\x00", 10), ("\x02", 30)]);
+
+        assert_eq!(priorities.validators().len(), 2);
+        assert_eq!(priorities.total_voting_power(), 40);
+
+        // The remaining validators keep their priorities, centered around zero
+        assert_eq!(priorities_of(&priorities), [20, -20]);
+
+        priorities.increment(1);
+        assert_eq!(
+            proposers(&mut priorities, 8),
+            "\x00 \x02 \x02 \x00 \x02 \x02 \x02 \x00"
+        );
+    }
+}
@@ -5,7 +5,8 @@ use {
         BorshDeserialize, BorshSerialize,
     },
     malachitebft_core_types::{
-        Context, PolkaCertificate, Round, RoundCertificate, SignedProposal, SignedVote, Validity,
+        Context, PolkaCertificate, Round, RoundCertificate, SignedProposal, SignedVote,
+        ValidatorSetUpdateCertificate, Validity,
     },
 };
 
@@ -55,6 +56,7 @@ where
     SignedVote<Ctx>: BorshSerialize,
     PolkaCertificate<Ctx>: BorshSerialize,
     RoundCertificate<Ctx>: BorshSerialize,
+    ValidatorSetUpdateCertificate<Ctx>: BorshSerialize,
 {
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
         match self {
@@ -70,6 +72,10 @@ where
                 2u8.serialize(writer)?;
                 round_certificate.serialize(writer)
             }
+            LivenessMsg::ValidatorSetUpdate(certificate) => {
+                3u8.serialize(writer)?;
+                certificate.serialize(writer)
+            }
         }
     }
 }
@@ -79,6 +85,7 @@ where
     SignedVote<Ctx>: BorshDeserialize,
     PolkaCertificate<Ctx>: BorshDeserialize,
     RoundCertificate<Ctx>: BorshDeserialize,
+    ValidatorSetUpdateCertificate<Ctx>: BorshDeserialize,
 {
     fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
         let discriminant = u8::deserialize_reader(reader)?;
@@ -90,6 +97,9 @@ where
             2 => Ok(LivenessMsg::SkipRoundCertificate(
                 RoundCertificate::deserialize_reader(reader)?,
             )),
+            3 => Ok(LivenessMsg::ValidatorSetUpdate(
+                ValidatorSetUpdateCertificate::deserialize_reader(reader)?,
+            )),
             _ => Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
                 "Invalid discriminant",
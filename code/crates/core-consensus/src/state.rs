@@ -1,14 +1,19 @@
-use std::time::{Duration, Instant};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use derive_where::derive_where;
 use tracing::info;
 
 use malachitebft_core_driver::Driver;
 use malachitebft_core_types::*;
 
+use crate::clock::{Clock, SystemClock};
 use crate::full_proposal::{FullProposal, FullProposalKeeper};
 use crate::input::Input;
 use crate::params::Params;
 use crate::prelude::*;
-use crate::types::ProposedValue;
+use crate::types::{Participation, ProposedValue, VoteTally};
 use crate::util::bounded_queue::BoundedQueue;
 
 /// The state maintained by consensus for processing a [`Input`].
@@ -40,8 +45,11 @@ where
     /// Target time for the current height
     pub target_time: Option<Duration>,
 
-    /// Start time of the current height
-    pub height_start_time: Option<Instant>,
+    /// Start time of the current height, as read from the clock
+    pub height_start_time: Option<Duration>,
+
+    /// The clock used to measure the time spent in the current height
+    pub clock: Arc<dyn Clock>,
 
     /// Whether we are in the finalization period.
     ///
@@ -51,6 +59,33 @@ where
     /// It allows collecting additional precommits for the decided value after
     /// the decision is made in decide, which can be included in the commit certificate.
     pub finalization_period: bool,
+
+    /// Votes received from validators of the current validator set for heights
+    /// higher than the current one, at most one per validator and height.
+    pub future_height_votes: BTreeMap<Ctx::Height, BTreeMap<Ctx::Address, SignedVote<Ctx>>>,
+
+    /// Highest height at which validators with at least f+1 voting power were observed voting.
+    pub observed_height: Option<Ctx::Height>,
+
+    /// Commit certificates verified ahead of their height, as part of a batch of synced values.
+    pub verified_certificates: Option<VerifiedCertificates<Ctx>>,
+}
+
+/// Commit certificates verified against a validator set ahead of their height,
+/// which only hold at their height if the validator set and thresholds are unchanged by then.
+#[derive_where(Clone, Debug)]
+pub struct VerifiedCertificates<Ctx>
+where
+    Ctx: Context,
+{
+    /// The validator set the certificates were verified against
+    pub validator_set: Ctx::ValidatorSet,
+
+    /// The thresholds the certificates were verified with
+    pub threshold_params: ThresholdParams,
+
+    /// The verified certificates, by height
+    pub certificates: BTreeMap<Ctx::Height, CommitCertificate<Ctx>>,
 }
 
 impl<Ctx> State<Ctx>
@@ -73,20 +108,32 @@ where
             params.threshold_params,
         );
 
+        let full_proposal_keeper = FullProposalKeeper::with_limits(params.full_proposal_limits);
+
         Self {
             ctx,
             driver,
             params,
             input_queue: BoundedQueue::new(queue_capacity, queue_per_height_capacity),
-            full_proposal_keeper: Default::default(),
+            full_proposal_keeper,
             last_signed_prevote: None,
             last_signed_precommit: None,
             target_time: None,
             height_start_time: None,
+            clock: Arc::new(SystemClock),
             finalization_period: false,
+            future_height_votes: BTreeMap::new(),
+            observed_height: None,
+            verified_certificates: None,
         }
     }
 
+    /// Use the given clock instead of the system clock, eg. on platforms without `std::time`.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
     pub fn height(&self) -> Ctx::Height {
         self.driver.height()
     }
@@ -135,6 +182,23 @@ where
         self.driver.polka_certificate(round, value_id)
     }
 
+    /// Get the voting power received so far at the current height for the specified round
+    pub fn vote_tally(&self, round: Round) -> VoteTally {
+        let Some(per_round) = self.driver.votes().per_round(round) else {
+            return VoteTally::default();
+        };
+
+        VoteTally {
+            prevote_power: per_round.votes().weight_sum(VoteType::Prevote),
+            precommit_power: per_round.votes().weight_sum(VoteType::Precommit),
+        }
+    }
+
+    /// The number of votes received from each validator of the current height, across all rounds.
+    pub fn participation(&self) -> BTreeMap<Ctx::Address, Participation> {
+        self.driver.votes().participation()
+    }
+
     pub fn full_proposal_at_round_and_value(
         &self,
         height: &Ctx::Height,
@@ -186,7 +250,12 @@ where
     }
 
     pub fn store_proposal(&mut self, new_proposal: SignedProposal<Ctx>) {
-        self.full_proposal_keeper.store_proposal(new_proposal)
+        let proposer = self
+            .get_proposer(new_proposal.height(), new_proposal.round())
+            .clone();
+
+        self.full_proposal_keeper
+            .store_proposal(new_proposal, &proposer)
     }
 
     /// Store the proposed value and return its validity,
@@ -209,15 +278,49 @@ where
             );
         }
         // Store the value at both round and valid_round
-        self.full_proposal_keeper.store_value(new_value);
+        let proposer = self.get_proposer(new_value.height, new_value.round).clone();
+        self.full_proposal_keeper.store_value(new_value, &proposer);
 
-        // Retrieve the validity after storing, as it may have changed (e.g., from Invalid to Valid)
-        let (_value, validity) = self
-            .full_proposal_keeper
+        // Retrieve the validity after storing, as it may have changed (e.g., from Invalid to Valid).
+        // The value may not have been stored if there were already too many values for its round.
+        self.full_proposal_keeper
             .get_value_by_id(&new_value.height, &new_value.value.id())
-            .expect("We just stored the entry, so it should be there");
+            .map_or(new_value.validity, |(_value, validity)| validity)
+    }
 
-        validity
+    /// Evict the proposals and values of the rounds lower than the given round minus
+    /// the configured margin, except the ones for the locked and valid values,
+    /// and the ones which received precommits in their round, as they may still be decided.
+    ///
+    /// Returns the number of proposals and values evicted.
+    pub fn evict_stale_proposals(&mut self, round: Round) -> usize {
+        let margin = self.params.full_proposal_limits.round_margin;
+
+        let Some(min_round) = round.as_u32().and_then(|r| r.checked_sub(margin)) else {
+            return 0;
+        };
+
+        let height = self.height();
+        let round_state = self.driver.round_state();
+        let locked_or_valid = [&round_state.locked, &round_state.valid]
+            .into_iter()
+            .flatten()
+            .map(|locked_or_valid| locked_or_valid.value.id())
+            .collect::<Vec<_>>();
+
+        let votes = self.driver.votes();
+        let has_precommits = |round: Round, value_id: &ValueId<Ctx>| {
+            votes.per_round(round).is_some_and(|per_round| {
+                let value = NilOrVal::Val(value_id.clone());
+                per_round.votes().get_weight(VoteType::Precommit, &value) > 0
+            })
+        };
+
+        self.full_proposal_keeper.evict_rounds_below(
+            height,
+            Round::new(min_round),
+            |round, value_id| locked_or_valid.contains(value_id) || has_precommits(round, value_id),
+        )
     }
 
     pub fn reset_and_start_height(
@@ -230,12 +333,67 @@ where
         self.last_signed_prevote = None;
         self.last_signed_precommit = None;
         self.target_time = target_time;
-        self.height_start_time = Some(Instant::now());
+        self.height_start_time = Some(self.clock.now());
         self.finalization_period = false;
+        self.future_height_votes.retain(|h, _| *h > height);
 
+        if let Some(verified) = &mut self.verified_certificates {
+            verified.certificates.retain(|h, _| *h >= height);
+        }
+
+        // The thresholds may have been overridden for this height
+        self.driver
+            .set_threshold_params(self.params.threshold_params);
         self.driver.move_to_height(height, validator_set);
     }
 
+    /// Keep the given certificates, verified against the given validator set with the given thresholds.
+    ///
+    /// Certificates verified against another validator set or with other thresholds
+    /// than the ones already kept replace them.
+    pub fn store_verified_certificates(
+        &mut self,
+        validator_set: Ctx::ValidatorSet,
+        threshold_params: ThresholdParams,
+        certificates: impl IntoIterator<Item = CommitCertificate<Ctx>>,
+    ) {
+        let verified = match &mut self.verified_certificates {
+            Some(verified)
+                if verified.validator_set == validator_set
+                    && verified.threshold_params == threshold_params =>
+            {
+                verified
+            }
+            verified => verified.insert(VerifiedCertificates {
+                validator_set,
+                threshold_params,
+                certificates: BTreeMap::new(),
+            }),
+        };
+
+        verified.certificates.extend(
+            certificates
+                .into_iter()
+                .map(|certificate| (certificate.height, certificate)),
+        );
+    }
+
+    /// Remove the verified certificate kept for the height of the given certificate, and return
+    /// whether it is the same certificate and was verified against the current validator set
+    /// with the current thresholds.
+    pub fn take_verified_certificate(&mut self, certificate: &CommitCertificate<Ctx>) -> bool {
+        let Some(verified) = &mut self.verified_certificates else {
+            return false;
+        };
+
+        verified
+            .certificates
+            .remove(&certificate.height)
+            .is_some_and(|verified_certificate| &verified_certificate == certificate)
+            && verified.validator_set == *self.driver.validator_set()
+            && verified.threshold_params == self.params.threshold_params
+    }
+
     /// Return the round and value id of the decided value.
     pub fn decided_value(&self) -> Option<(Round, Ctx::Value)> {
         self.driver.decided_value()
@@ -3,10 +3,11 @@ use thiserror::Error;
 
 use malachitebft_core_types::{
     Context, PolkaCertificate, Proposal, Round, RoundCertificate, Signature, SignedProposal,
-    SignedVote, Timeout, Validity, Vote,
+    SignedVote, Timeout, ValidatorSetUpdateCertificate, Validity, Vote, VotingPower,
 };
 
 pub use malachitebft_core_types::ValuePayload;
+pub use malachitebft_core_votekeeper::keeper::Participation;
 
 pub use malachitebft_peer::PeerId;
 pub use multiaddr::Multiaddr;
@@ -59,6 +60,15 @@ pub enum ConsensusMsg<Ctx: Context> {
     Proposal(Ctx::Proposal),
 }
 
+/// The voting power received so far for each vote type in a given round.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VoteTally {
+    /// Sum of the voting power of the validators who prevoted, for any value or nil
+    pub prevote_power: VotingPower,
+    /// Sum of the voting power of the validators who precommitted, for any value or nil
+    pub precommit_power: VotingPower,
+}
+
 /// A value to propose by the current node.
 /// Used only when the node is the proposer.
 #[derive_where(Clone, Debug, PartialEq, Eq)]
@@ -125,6 +135,26 @@ impl<Ctx: Context> WalEntry<Ctx> {
             _ => None,
         }
     }
+
+    /// Height of the entry, if known.
+    ///
+    /// Timeouts do not carry a height, they always belong to the height being replayed.
+    pub fn height(&self) -> Option<Ctx::Height> {
+        match self {
+            WalEntry::ConsensusMsg(msg) => Some(msg.height()),
+            WalEntry::Timeout(_) => None,
+            WalEntry::ProposedValue(value) => Some(value.height),
+        }
+    }
+
+    /// Round of the entry.
+    pub fn round(&self) -> Round {
+        match self {
+            WalEntry::ConsensusMsg(msg) => msg.round(),
+            WalEntry::Timeout(timeout) => timeout.round,
+            WalEntry::ProposedValue(value) => value.round,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Error)]
@@ -140,6 +170,7 @@ pub enum LivenessMsg<Ctx: Context> {
     Vote(SignedVote<Ctx>),
     PolkaCertificate(PolkaCertificate<Ctx>),
     SkipRoundCertificate(RoundCertificate<Ctx>),
+    ValidatorSetUpdate(ValidatorSetUpdateCertificate<Ctx>),
 }
 
 /// Misbehavior evidence collected during a height.
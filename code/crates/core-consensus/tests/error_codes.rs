@@ -0,0 +1,30 @@
+use arc_malachitebft_core_consensus::Error;
+use malachitebft_core_driver::Error as DriverError;
+use malachitebft_core_types::{ErrorKind, Round};
+use malachitebft_test::{Height, TestContext};
+
+#[test]
+fn error_codes_and_kinds() {
+    let height = Height::new(1);
+
+    let error = Error::<TestContext>::ValidatorSetNotFound(height);
+    assert_eq!(error.code(), "validator_set_not_found");
+    assert_eq!(error.kind(), ErrorKind::Recoverable);
+
+    let error = Error::<TestContext>::DecisionNotFound(height, Round::new(0));
+    assert_eq!(error.code(), "decision_not_found");
+    assert_eq!(error.kind(), ErrorKind::Internal);
+
+    // Driver errors are reported with the code and kind of the underlying error
+    let error = Error::<TestContext>::DriverProcess(DriverError::InvalidVoteHeight {
+        vote_height: Height::new(2),
+        consensus_height: height,
+    });
+    assert_eq!(error.code(), "invalid_vote_height");
+    assert_eq!(error.kind(), ErrorKind::Recoverable);
+
+    assert_eq!(
+        ErrorKind::ProtocolViolation.to_string(),
+        "protocol_violation"
+    );
+}
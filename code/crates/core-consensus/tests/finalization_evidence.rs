@@ -25,6 +25,8 @@ fn make_state(validators: &[Validator], my_addr: Address) -> State<TestContext>
             threshold_params: Default::default(),
             value_payload: ValuePayload::ProposalOnly,
             enabled: true,
+            require_vote_extensions: false,
+            full_proposal_limits: Default::default(),
         },
         1000,
         1000,
@@ -133,21 +135,36 @@ fn drive_to_finalization(
     assert!(state.driver.step_is_commit());
 }
 
-fn equivocating_proposal(addr: Address) -> Input<TestContext> {
-    Input::Proposal(SignedProposal::new(
-        Proposal::new(
-            Height::new(1),
-            Round::new(0),
-            Value::new(100),
-            Round::Nil,
-            addr,
+/// In proposal-only mode, the application has to validate the value
+/// carried by the proposal before the proposal reaches the driver.
+fn equivocating_proposal(addr: Address) -> Vec<Input<TestContext>> {
+    vec![
+        Input::Proposal(SignedProposal::new(
+            Proposal::new(
+                Height::new(1),
+                Round::new(0),
+                Value::new(100),
+                Round::Nil,
+                addr,
+            ),
+            Signature::test(),
+        )),
+        Input::ProposedValue(
+            ProposedValue {
+                height: Height::new(1),
+                round: Round::new(0),
+                valid_round: Round::Nil,
+                proposer: addr,
+                value: Value::new(100),
+                validity: Validity::Valid,
+            },
+            ValueOrigin::Consensus,
         ),
-        Signature::test(),
-    ))
+    ]
 }
 
-fn equivocating_prevote(addr: Address) -> Input<TestContext> {
-    Input::Vote(SignedVote::new(
+fn equivocating_prevote(addr: Address) -> Vec<Input<TestContext>> {
+    vec![Input::Vote(SignedVote::new(
         Vote::new_prevote(
             Height::new(1),
             Round::new(0),
@@ -155,11 +172,11 @@ fn equivocating_prevote(addr: Address) -> Input<TestContext> {
             addr,
         ),
         Signature::test(),
-    ))
+    ))]
 }
 
-fn equivocating_precommit(addr: Address) -> Input<TestContext> {
-    Input::Vote(SignedVote::new(
+fn equivocating_precommit(addr: Address) -> Vec<Input<TestContext>> {
+    vec![Input::Vote(SignedVote::new(
         Vote::new_precommit(
             Height::new(1),
             Round::new(0),
@@ -167,7 +184,7 @@ fn equivocating_precommit(addr: Address) -> Input<TestContext> {
             addr,
         ),
         Signature::test(),
-    ))
+    ))]
 }
 
 fn vote_evidence_count(state: &State<TestContext>, addr: Address) -> usize {
@@ -192,7 +209,7 @@ fn proposal_evidence_count(state: &State<TestContext>, addr: Address) -> usize {
 
 struct TestCase {
     name: &'static str,
-    make_input: fn(Address) -> Input<TestContext>,
+    make_inputs: fn(Address) -> Vec<Input<TestContext>>,
     get_evidence_count: fn(&State<TestContext>, Address) -> usize,
     expected: usize,
 }
@@ -202,19 +219,19 @@ fn equivocation_detection_in_finalization_period() {
     let tests = vec![
         TestCase {
             name: "prevote",
-            make_input: equivocating_prevote,
+            make_inputs: equivocating_prevote,
             get_evidence_count: vote_evidence_count,
             expected: 1,
         },
         TestCase {
             name: "precommit",
-            make_input: equivocating_precommit,
+            make_inputs: equivocating_precommit,
             get_evidence_count: vote_evidence_count,
             expected: 1,
         },
         TestCase {
             name: "proposal",
-            make_input: equivocating_proposal,
+            make_inputs: equivocating_proposal,
             get_evidence_count: proposal_evidence_count,
             expected: 1,
         },
@@ -235,14 +252,14 @@ fn equivocation_detection_in_finalization_period() {
         drive_to_finalization(&mut state, &metrics, &validators, proposer, value);
 
         // All equivocations come from the proposer
-        let input = (test.make_input)(proposer);
-
-        run(process!(
-            input: input,
-            state: &mut state,
-            metrics: &metrics,
-            with: effect => handle_effect(effect)
-        ));
+        for input in (test.make_inputs)(proposer) {
+            run(process!(
+                input: input,
+                state: &mut state,
+                metrics: &metrics,
+                with: effect => handle_effect(effect)
+            ));
+        }
 
         let count = (test.get_evidence_count)(&state, proposer);
 
@@ -342,8 +342,11 @@ fn full_proposal_keeper_tests() {
 
         for msg in case.input {
             match msg {
-                Input::Proposal(p) => keeper.store_proposal(p),
-                Input::ProposedValue(v, _) => keeper.store_value(&v),
+                Input::Proposal(p) => {
+                    let proposer = p.validator_address;
+                    keeper.store_proposal(p, &proposer)
+                }
+                Input::ProposedValue(v, _) => keeper.store_value(&v, &v.proposer),
                 _ => {}
             }
         }
@@ -0,0 +1,157 @@
+use arc_malachitebft_core_consensus::{
+    process, Effect, Error, Input, Params, Resumable, Resume, State,
+};
+use malachitebft_core_types::{NilOrVal, Round, SignedProposal, SignedVote, ValuePayload};
+use malachitebft_metrics::Metrics;
+use malachitebft_test::utils::validators::make_validators;
+use malachitebft_test::{
+    Address, Height, Signature, TestContext, Validator, ValidatorSet, ValueId, Vote,
+};
+
+fn run(r: Result<(), Error<TestContext>>) {
+    drop(r);
+}
+
+fn make_state(validators: &[Validator], my_addr: Address) -> State<TestContext> {
+    let vs = ValidatorSet::new(validators.to_vec());
+    State::new(
+        TestContext::new(),
+        Height::new(1),
+        vs,
+        Params {
+            address: my_addr,
+            threshold_params: Default::default(),
+            value_payload: ValuePayload::ProposalOnly,
+            enabled: true,
+            require_vote_extensions: false,
+            full_proposal_limits: Default::default(),
+        },
+        1000,
+        1000,
+    )
+}
+
+/// Handle the effects, recording the heights observed by consensus
+/// and the number of votes given as evidence for each of them.
+fn handle_effect(
+    effect: Effect<TestContext>,
+    observed: &mut Vec<(Height, usize)>,
+) -> Result<Resume<TestContext>, ()> {
+    use Effect::*;
+    Ok(match effect {
+        VerifySignature(_, _, r) => r.resume_with(true),
+        SignVote(vote, r) => r.resume_with(SignedVote::new(vote, Signature::test())),
+        SignProposal(proposal, r) => {
+            r.resume_with(SignedProposal::new(proposal, Signature::test()))
+        }
+        FutureHeightObserved(height, evidence, r) => {
+            observed.push((height, evidence.len()));
+            r.resume_with(())
+        }
+        _ => Resume::Continue,
+    })
+}
+
+fn prevote(height: u64, address: Address) -> Input<TestContext> {
+    Input::Vote(SignedVote::new(
+        Vote::new_prevote(
+            Height::new(height),
+            Round::new(0),
+            NilOrVal::Val(ValueId::new(height)),
+            address,
+        ),
+        Signature::test(),
+    ))
+}
+
+fn apply(
+    state: &mut State<TestContext>,
+    metrics: &Metrics,
+    input: Input<TestContext>,
+) -> Vec<(Height, usize)> {
+    let mut observed = Vec::new();
+
+    run(process!(
+        input: input,
+        state: state,
+        metrics: metrics,
+        with: effect => handle_effect(effect, &mut observed)
+    ));
+
+    observed
+}
+
+#[test]
+fn future_height_observed_once_f_plus_one_validators_are_ahead() {
+    let validators: Vec<_> = make_validators([1, 1, 1, 1])
+        .into_iter()
+        .map(|(v, _)| v)
+        .collect();
+    let metrics = Metrics::new();
+
+    let mut state = make_state(&validators, validators[0].address);
+    let vs = ValidatorSet::new(validators.clone());
+
+    apply(
+        &mut state,
+        &metrics,
+        Input::StartHeight(Height::new(1), vs, false, None),
+    );
+
+    // A single validator voting at a higher height is not enough
+    assert!(apply(&mut state, &metrics, prevote(3, validators[1].address)).is_empty());
+
+    // Nor is the same validator voting again at another higher height
+    assert!(apply(&mut state, &metrics, prevote(4, validators[1].address)).is_empty());
+
+    // Two validators out of four have more than f+1 voting power,
+    // the highest height at which both are voting is the one observed
+    assert_eq!(
+        apply(&mut state, &metrics, prevote(3, validators[2].address)),
+        vec![(Height::new(3), 2)]
+    );
+
+    // Heights up to the observed one are only reported once
+    assert!(apply(&mut state, &metrics, prevote(2, validators[3].address)).is_empty());
+    assert!(apply(&mut state, &metrics, prevote(3, validators[3].address)).is_empty());
+    assert_eq!(state.observed_height, Some(Height::new(3)));
+}
+
+#[test]
+fn future_height_tracked_is_only_evicted_for_a_vote_of_a_validator() {
+    let validators: Vec<_> = make_validators([1, 1, 1, 1])
+        .into_iter()
+        .map(|(v, _)| v)
+        .collect();
+    let metrics = Metrics::new();
+
+    let mut state = make_state(&validators, validators[0].address);
+    let vs = ValidatorSet::new(validators.clone());
+
+    apply(
+        &mut state,
+        &metrics,
+        Input::StartHeight(Height::new(1), vs, false, None),
+    );
+
+    // Track as many heights as possible
+    for height in 3..19 {
+        assert!(apply(&mut state, &metrics, prevote(height, validators[1].address)).is_empty());
+    }
+
+    let highest = state.future_height_votes.last_key_value().map(|(h, _)| *h);
+    assert_eq!(highest, Some(Height::new(18)));
+
+    // A vote from a non-validator does not evict the highest height tracked
+    let outsider = Address::new([42; 20]);
+    assert!(apply(&mut state, &metrics, prevote(2, outsider)).is_empty());
+
+    assert!(!state.future_height_votes.contains_key(&Height::new(2)));
+    assert!(state.future_height_votes.contains_key(&Height::new(18)));
+
+    // A vote from a validator for a lower height does
+    assert!(apply(&mut state, &metrics, prevote(2, validators[1].address)).is_empty());
+
+    assert!(state.future_height_votes.contains_key(&Height::new(2)));
+    assert!(!state.future_height_votes.contains_key(&Height::new(18)));
+}
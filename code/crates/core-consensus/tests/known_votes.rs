@@ -0,0 +1,125 @@
+use arc_malachitebft_core_consensus::{
+    process, Effect, Error, Input, Params, Resumable, Resume, State,
+};
+use malachitebft_core_types::{NilOrVal, Round, SignedProposal, SignedVote, ValuePayload};
+use malachitebft_metrics::Metrics;
+use malachitebft_test::utils::validators::make_validators;
+use malachitebft_test::{
+    Address, Height, Signature, TestContext, Validator, ValidatorSet, ValueId, Vote,
+};
+
+fn run(r: Result<(), Error<TestContext>>) {
+    drop(r);
+}
+
+fn make_state(validators: &[Validator], my_addr: Address) -> State<TestContext> {
+    let vs = ValidatorSet::new(validators.to_vec());
+    State::new(
+        TestContext::new(),
+        Height::new(1),
+        vs,
+        Params {
+            address: my_addr,
+            threshold_params: Default::default(),
+            value_payload: ValuePayload::ProposalOnly,
+            enabled: true,
+            require_vote_extensions: false,
+            full_proposal_limits: Default::default(),
+        },
+        1000,
+        1000,
+    )
+}
+
+/// Handle the effects, recording their kind.
+fn handle_effect(
+    effect: Effect<TestContext>,
+    kinds: &mut Vec<&'static str>,
+) -> Result<Resume<TestContext>, ()> {
+    use Effect::*;
+
+    kinds.push(effect.kind());
+
+    Ok(match effect {
+        VerifySignature(_, _, r) => r.resume_with(true),
+        SignVote(vote, r) => r.resume_with(SignedVote::new(vote, Signature::test())),
+        SignProposal(proposal, r) => {
+            r.resume_with(SignedProposal::new(proposal, Signature::test()))
+        }
+        _ => Resume::Continue,
+    })
+}
+
+fn prevote(value: NilOrVal<ValueId>, address: Address) -> SignedVote<TestContext> {
+    SignedVote::new(
+        Vote::new_prevote(Height::new(1), Round::new(0), value, address),
+        Signature::test(),
+    )
+}
+
+fn apply(
+    state: &mut State<TestContext>,
+    metrics: &Metrics,
+    input: Input<TestContext>,
+) -> Vec<&'static str> {
+    let mut kinds = Vec::new();
+
+    run(process!(
+        input: input,
+        state: state,
+        metrics: metrics,
+        with: effect => handle_effect(effect, &mut kinds)
+    ));
+
+    kinds
+}
+
+#[test]
+fn known_votes_emit_no_effects() {
+    let validators: Vec<_> = make_validators([1, 1, 1, 1])
+        .into_iter()
+        .map(|(v, _)| v)
+        .collect();
+    let metrics = Metrics::new();
+
+    let mut state = make_state(&validators, validators[0].address);
+    let vs = ValidatorSet::new(validators.clone());
+
+    apply(
+        &mut state,
+        &metrics,
+        Input::StartHeight(Height::new(1), vs, false, None),
+    );
+
+    let vote = prevote(NilOrVal::Val(ValueId::new(1)), validators[1].address);
+    let conflicting = prevote(NilOrVal::Nil, validators[1].address);
+
+    // New votes are verified and appended to the WAL, including the equivocating ones
+    let kinds = apply(&mut state, &metrics, Input::Vote(vote.clone()));
+    assert!(kinds.contains(&"verify_signature"));
+    assert!(kinds.contains(&"wal_append"));
+
+    let kinds = apply(&mut state, &metrics, Input::Vote(conflicting.clone()));
+    assert!(kinds.contains(&"verify_signature"));
+    assert!(kinds.contains(&"wal_append"));
+
+    let votes = state.driver.votes();
+    assert!(votes.is_known_vote(&vote));
+    assert!(votes.is_known_vote(&conflicting));
+    let evidence = votes.evidence().clone();
+
+    // Receiving them again does not emit any effect, nor change the vote keeper
+    for vote in [vote, conflicting] {
+        assert_eq!(
+            apply(&mut state, &metrics, Input::Vote(vote)),
+            Vec::<&str>::new()
+        );
+    }
+
+    let votes = state.driver.votes();
+    assert_eq!(
+        votes.evidence().get(&validators[1].address),
+        evidence.get(&validators[1].address)
+    );
+    assert_eq!(votes.participation()[&validators[1].address].prevotes, 1);
+}
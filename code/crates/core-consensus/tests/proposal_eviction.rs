@@ -0,0 +1,106 @@
+use arc_malachitebft_core_consensus::{
+    process, Effect, Error, Input, Params, ProposedValue, Resumable, Resume, State,
+};
+use malachitebft_core_types::{NilOrVal, Round, SignedVote, Validity, ValuePayload};
+use malachitebft_metrics::Metrics;
+use malachitebft_test::utils::validators::make_validators;
+use malachitebft_test::{
+    Address, Height, Signature, TestContext, Validator, ValidatorSet, Value, Vote,
+};
+
+fn make_state(validators: &[Validator], my_addr: Address) -> State<TestContext> {
+    let vs = ValidatorSet::new(validators.to_vec());
+    State::new(
+        TestContext::new(),
+        Height::new(1),
+        vs,
+        Params {
+            address: my_addr,
+            threshold_params: Default::default(),
+            value_payload: ValuePayload::ProposalOnly,
+            enabled: true,
+            require_vote_extensions: false,
+            full_proposal_limits: Default::default(),
+        },
+        1000,
+        1000,
+    )
+}
+
+fn apply(state: &mut State<TestContext>, metrics: &Metrics, input: Input<TestContext>) {
+    let r: Result<(), Error<TestContext>> = process!(
+        input: input,
+        state: state,
+        metrics: metrics,
+        with: effect => Ok::<_, ()>(match effect {
+            Effect::VerifySignature(_, _, r) => r.resume_with(true),
+            _ => Resume::Continue,
+        })
+    );
+
+    drop(r);
+}
+
+fn store_value(state: &mut State<TestContext>, round: u32, value: u64) {
+    let round = Round::new(round);
+
+    state.store_value(&ProposedValue {
+        height: Height::new(1),
+        round,
+        valid_round: Round::Nil,
+        proposer: *state.get_proposer(Height::new(1), round),
+        value: Value::new(value),
+        validity: Validity::Valid,
+    });
+}
+
+fn is_kept(state: &State<TestContext>, value: u64) -> bool {
+    state
+        .full_proposal_keeper
+        .get_value_by_id(&Height::new(1), &Value::new(value).id())
+        .is_some()
+}
+
+#[test]
+fn values_with_precommits_in_their_round_are_not_evicted() {
+    let validators: Vec<_> = make_validators([1, 1, 1, 1])
+        .into_iter()
+        .map(|(v, _)| v)
+        .collect();
+    let metrics = Metrics::new();
+
+    let mut state = make_state(&validators, validators[0].address);
+    let vs = ValidatorSet::new(validators.clone());
+
+    apply(
+        &mut state,
+        &metrics,
+        Input::StartHeight(Height::new(1), vs, false, None),
+    );
+
+    store_value(&mut state, 0, 10);
+    store_value(&mut state, 1, 11);
+    store_value(&mut state, 1, 12);
+
+    // A precommit for the value of round 0, and one for a value of round 1 cast in round 0
+    for (value, voter) in [(10, &validators[1]), (12, &validators[2])] {
+        let vote = Vote::new_precommit(
+            Height::new(1),
+            Round::new(0),
+            NilOrVal::Val(Value::new(value).id()),
+            voter.address,
+        );
+        apply(
+            &mut state,
+            &metrics,
+            Input::Vote(SignedVote::new(vote, Signature::test())),
+        );
+    }
+
+    // Rounds 0 and 1 are below the margin of 2 rounds
+    assert_eq!(state.evict_stale_proposals(Round::new(4)), 2);
+
+    assert!(is_kept(&state, 10));
+    assert!(!is_kept(&state, 11));
+    assert!(!is_kept(&state, 12));
+}
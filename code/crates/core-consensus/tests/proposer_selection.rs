@@ -0,0 +1,212 @@
+use std::sync::Arc;
+
+use arc_malachitebft_core_consensus::proposer::{
+    ProposerPriorities, ProposerSelector, RoundRobin, WeightedRoundRobin,
+};
+use malachitebft_core_types::Round;
+use malachitebft_test::utils::validators::make_validators;
+use malachitebft_test::{Address, Height, TestContext, ValidatorSet};
+
+fn validator_set(voting_powers: [u64; 4]) -> ValidatorSet {
+    ValidatorSet::new(make_validators(voting_powers).map(|(v, _)| v))
+}
+
+fn priorities_of(vs: &ValidatorSet) -> ProposerPriorities<Address> {
+    ProposerPriorities::new(vs.iter().map(|v| (v.address, v.voting_power)))
+}
+
+fn proposer(
+    selector: &dyn ProposerSelector<TestContext>,
+    vs: &ValidatorSet,
+    height: u64,
+    round: u32,
+) -> Address {
+    selector
+        .select_proposer(vs, Height::new(height), Round::new(round))
+        .address
+}
+
+#[test]
+fn weighted_round_robin_follows_the_priorities() {
+    let vs = validator_set([40, 30, 20, 10]);
+    let selector = WeightedRoundRobin::<TestContext>::new(Height::new(1), &vs);
+
+    // Priorities move forward once per height...
+    let mut priorities = priorities_of(&vs);
+    for height in 1..=50 {
+        assert_eq!(proposer(&selector, &vs, height, 0), *priorities.proposer());
+
+        // ...and once more per round within a height
+        for round in 1..4 {
+            let mut round_priorities = priorities.clone();
+            round_priorities.increment(round);
+            assert_eq!(
+                proposer(&selector, &vs, height, round),
+                *round_priorities.proposer()
+            );
+        }
+
+        priorities.increment(1);
+    }
+
+    // Heights below the latest one are computed again from the initial height
+    let mut priorities = priorities_of(&vs);
+    priorities.increment(9);
+    assert_eq!(proposer(&selector, &vs, 10, 0), *priorities.proposer());
+}
+
+#[test]
+fn weighted_round_robin_proposes_in_proportion_to_voting_power() {
+    let vs = validator_set([40, 30, 20, 10]);
+    let selector = WeightedRoundRobin::<TestContext>::new(Height::new(1), &vs);
+
+    let proposers = (1..=100)
+        .map(|height| proposer(&selector, &vs, height, 0))
+        .collect::<Vec<_>>();
+
+    for validator in vs.iter() {
+        let count = proposers
+            .iter()
+            .filter(|p| **p == validator.address)
+            .count();
+        assert_eq!(count as u64, validator.voting_power);
+    }
+}
+
+#[test]
+fn weighted_round_robin_resumes_from_priorities() {
+    let vs = validator_set([5, 5, 3, 1]);
+    let selector = WeightedRoundRobin::<TestContext>::new(Height::new(1), &vs);
+
+    let priorities = selector.priorities(&vs, Height::new(20));
+    let resumed = WeightedRoundRobin::<TestContext>::resume(Height::new(20), priorities);
+
+    for height in 20..=40 {
+        for round in 0..3 {
+            assert_eq!(
+                proposer(&selector, &vs, height, round),
+                proposer(&resumed, &vs, height, round)
+            );
+        }
+    }
+}
+
+#[test]
+fn weighted_round_robin_applies_validator_set_changes() {
+    let vs = validator_set([10, 10, 10, 10]);
+    let selector = WeightedRoundRobin::<TestContext>::new(Height::new(1), &vs);
+
+    let mut priorities = priorities_of(&vs);
+    for height in 1..=5 {
+        assert_eq!(proposer(&selector, &vs, height, 0), *priorities.proposer());
+        priorities.increment(1);
+    }
+
+    // The last validator leaves the set at height 6
+    let smaller = ValidatorSet::new(vs.iter().take(3).cloned());
+    let leaving = vs.validators[3].address;
+
+    let mut priorities = selector.priorities(&vs, Height::new(5));
+    priorities.update(smaller.iter().map(|v| (v.address, v.voting_power)));
+    priorities.increment(1);
+
+    for height in 6..=20 {
+        let proposer = proposer(&selector, &smaller, height, 0);
+        assert_ne!(proposer, leaving);
+        assert_eq!(proposer, *priorities.proposer());
+        priorities.increment(1);
+    }
+}
+
+#[test]
+fn weighted_round_robin_recomputes_lower_heights_across_validator_set_changes() {
+    let vs = validator_set([10, 10, 10, 10]);
+
+    // The last validator leaves the set at height 6, and joins it again with more voting power at height 11
+    let smaller = ValidatorSet::new(vs.iter().take(3).cloned());
+    let larger = validator_set([10, 10, 10, 40]);
+
+    let validator_set_at = |height: u64| match height {
+        ..=5 => &vs,
+        6..=10 => &smaller,
+        _ => &larger,
+    };
+
+    let mut priorities = priorities_of(&vs);
+    let mut expected = vec![priorities.clone()];
+    for height in 2..=15 {
+        priorities.update(
+            validator_set_at(height)
+                .iter()
+                .map(|v| (v.address, v.voting_power)),
+        );
+        priorities.increment(1);
+        expected.push(priorities.clone());
+    }
+
+    let selector = WeightedRoundRobin::<TestContext>::new(Height::new(1), &vs);
+
+    for height in 1..=15 {
+        let priorities = selector.priorities(validator_set_at(height), Height::new(height));
+        assert_eq!(
+            priorities,
+            expected[height as usize - 1],
+            "at height {height}"
+        );
+    }
+
+    // Heights below the latest one are computed again with the validator sets of the heights in between
+    for height in (1..=15).rev() {
+        let vs = validator_set_at(height);
+        let priorities = selector.priorities(vs, Height::new(height));
+        assert_eq!(
+            priorities,
+            expected[height as usize - 1],
+            "at height {height}"
+        );
+
+        for round in 0..3 {
+            let mut round_priorities = priorities.clone();
+            if round > 0 {
+                round_priorities.increment(round);
+            }
+
+            assert_eq!(
+                proposer(&selector, vs, height, round),
+                *round_priorities.proposer()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_context_uses_the_selected_proposer_selector() {
+    let vs = validator_set([40, 30, 20, 10]);
+
+    let rotating = TestContext::new();
+    let weighted =
+        TestContext::new().with_proposer_selector(Arc::new(
+            WeightedRoundRobin::<TestContext>::new(Height::new(1), &vs),
+        ));
+
+    for height in 1..=10 {
+        for round in 0..3 {
+            let (h, r) = (Height::new(height), Round::new(round));
+
+            assert_eq!(
+                rotating.select_proposer(&vs, h, r).address,
+                proposer(&RoundRobin, &vs, height, round)
+            );
+
+            assert_eq!(
+                weighted.select_proposer(&vs, h, r).address,
+                proposer(
+                    &WeightedRoundRobin::<TestContext>::new(Height::new(1), &vs),
+                    &vs,
+                    height,
+                    round
+                )
+            );
+        }
+    }
+}
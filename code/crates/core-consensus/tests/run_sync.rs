@@ -0,0 +1,88 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use arc_malachitebft_core_consensus::{
+    run_sync, Clock, Effect, Input, Params, Resumable, Resume, State,
+};
+use malachitebft_core_types::{Round, SignedProposal, SignedVote, ValuePayload};
+use malachitebft_metrics::Metrics;
+use malachitebft_test::utils::validators::make_validators;
+use malachitebft_test::{Height, Signature, TestContext, ValidatorSet};
+
+/// A clock which only moves forward when told to.
+#[derive(Clone, Default)]
+struct ManualClock(Arc<AtomicU64>);
+
+impl ManualClock {
+    fn advance(&self, by: Duration) {
+        self.0.fetch_add(by.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_millis(self.0.load(Ordering::SeqCst))
+    }
+}
+
+fn handle_effect(effect: Effect<TestContext>) -> Result<Resume<TestContext>, ()> {
+    Ok(match effect {
+        Effect::VerifySignature(_, _, r) => r.resume_with(true),
+        Effect::SignVote(vote, r) => r.resume_with(SignedVote::new(vote, Signature::test())),
+        Effect::SignProposal(proposal, r) => {
+            r.resume_with(SignedProposal::new(proposal, Signature::test()))
+        }
+        _ => Resume::Continue,
+    })
+}
+
+#[test]
+fn run_sync_with_injected_clock() {
+    let [(v1, _), (v2, _), (v3, _)] = make_validators([1, 1, 1]);
+    let validator_set = ValidatorSet::new(vec![v1.clone(), v2, v3]);
+
+    let clock = ManualClock::default();
+    clock.advance(Duration::from_secs(42));
+
+    let mut state = State::new(
+        TestContext::new(),
+        Height::new(1),
+        validator_set.clone(),
+        Params {
+            address: v1.address,
+            threshold_params: Default::default(),
+            value_payload: ValuePayload::PartsOnly,
+            enabled: true,
+            require_vote_extensions: false,
+            full_proposal_limits: Default::default(),
+        },
+        1000,
+        1000,
+    )
+    .with_clock(clock.clone());
+
+    let metrics = Metrics::new();
+    let mut started_rounds = Vec::new();
+
+    run_sync(
+        &mut state,
+        &metrics,
+        Input::StartHeight(
+            Height::new(1),
+            validator_set,
+            false,
+            Some(Duration::from_secs(1)),
+        ),
+        |effect| {
+            if let Effect::StartRound(height, round, ..) = &effect {
+                started_rounds.push((*height, *round));
+            }
+            handle_effect(effect)
+        },
+    )
+    .unwrap();
+
+    assert_eq!(started_rounds, vec![(Height::new(1), Round::new(0))]);
+    assert_eq!(state.height_start_time, Some(Duration::from_secs(42)));
+}
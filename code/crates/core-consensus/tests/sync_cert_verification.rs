@@ -9,8 +9,8 @@ use arc_malachitebft_core_consensus::{
     process, Effect, Error, Input, Params, ProposedValue, Resumable, Resume, State,
 };
 use malachitebft_core_types::{
-    CommitCertificate, CommitSignature, Context, NilOrVal, Round, Validity, ValueOrigin,
-    ValuePayload, ValueResponse,
+    CommitCertificate, CommitSignature, Context, NilOrVal, Round, ThresholdParam, ThresholdParams,
+    Validity, ValueOrigin, ValuePayload, ValueResponse,
 };
 use malachitebft_metrics::Metrics;
 use malachitebft_peer::PeerId;
@@ -38,6 +38,8 @@ fn make_state(validators: &[Validator], my_addr: Address) -> State<TestContext>
             threshold_params: Default::default(),
             value_payload: ValuePayload::ProposalOnly,
             enabled: true,
+            require_vote_extensions: false,
+            full_proposal_limits: Default::default(),
         },
         1000,
         500,
@@ -179,3 +181,151 @@ fn sync_decision_path_verifies_commit_certificate_once() {
         "Certificate should be verified only once on sync decision path"
     );
 }
+
+/// Process a batch of synced values for heights 1 to 3 at height 1, then the value for height 2
+/// once at that height, with the given thresholds at height 2.
+///
+/// Return the number of certificates verified on their own.
+fn sync_batch_then_height_2(threshold_params_at_height_2: ThresholdParams) -> u32 {
+    let entries: Vec<(Validator, _)> = make_validators([25, 25, 25, 25]).into();
+    let validators: Vec<Validator> = entries.iter().map(|(v, _)| v.clone()).collect();
+    let signers: Vec<Ed25519Signer> = entries
+        .into_iter()
+        .map(|(_, pk)| Ed25519Signer::new(pk))
+        .collect();
+
+    let my_addr = validators[0].address;
+    let mut state = make_state(&validators, my_addr);
+    let metrics = Metrics::new();
+    let vs = ValidatorSet::new(validators.clone());
+
+    let round = Round::new(0);
+    let value = Value::new(42);
+
+    // Counters for VerifyCommitCertificate effects, and for the certificates
+    // verified through VerifyCommitCertificates effects
+    let verify_count = Cell::new(0u32);
+    let batch_verify_count = Cell::new(0usize);
+
+    let handle_effect = |effect: Effect<TestContext>| -> Result<Resume<TestContext>, ()> {
+        use Effect::*;
+        Ok(match effect {
+            VerifySignature(_, _, r) => r.resume_with(true),
+            SignVote(vote, r) => {
+                let signed = block_on(signers[0].sign_vote(vote)).unwrap();
+                r.resume_with(signed)
+            }
+            SignProposal(proposal, r) => {
+                let signed = block_on(signers[0].sign_proposal(proposal)).unwrap();
+                r.resume_with(signed)
+            }
+            VerifyCommitCertificate(cert, validator_set, tp, r) => {
+                verify_count.set(verify_count.get() + 1);
+                let result = block_on(signers[0].verify_commit_certificate(
+                    &TestContext::new(),
+                    &cert,
+                    &validator_set,
+                    tp,
+                ));
+                r.resume_with(result)
+            }
+            VerifyCommitCertificates(certs, validator_set, tp, r) => {
+                batch_verify_count.set(batch_verify_count.get() + certs.len());
+                let results = certs
+                    .iter()
+                    .map(|cert| {
+                        block_on(signers[0].verify_commit_certificate(
+                            &TestContext::new(),
+                            cert,
+                            &validator_set,
+                            tp,
+                        ))
+                    })
+                    .collect();
+                r.resume_with(results)
+            }
+            _ => Resume::Continue,
+        })
+    };
+
+    let value_response = |height: u64| {
+        let certificate =
+            build_commit_certificate(&validators, &signers, Height::new(height), round, &value);
+        ValueResponse::new(PeerId::random(), Bytes::from("value-bytes"), certificate)
+    };
+
+    run(process!(
+        input: Input::StartHeight(Height::new(1), vs.clone(), false, None),
+        state: &mut state,
+        metrics: &metrics,
+        with: effect => handle_effect(effect)
+    ));
+
+    // Heights 1 to 3 form a contiguous run, the value for height 5 is dropped
+    run(process!(
+        input: Input::ProcessCommitCertificateBatch(vec![
+            value_response(1),
+            value_response(2),
+            value_response(3),
+            value_response(5),
+        ]),
+        state: &mut state,
+        metrics: &metrics,
+        with: effect => handle_effect(effect)
+    ));
+
+    assert_eq!(batch_verify_count.get(), 3, "Run verified at once");
+    assert_eq!(verify_count.get(), 0, "No certificate verified on its own");
+
+    let verified = state.verified_certificates.as_ref().unwrap();
+    assert_eq!(
+        verified.certificates.keys().copied().collect::<Vec<_>>(),
+        vec![Height::new(2), Height::new(3)],
+        "Certificates of the later heights are kept"
+    );
+
+    state.params.threshold_params = threshold_params_at_height_2;
+
+    run(process!(
+        input: Input::StartHeight(Height::new(2), vs, false, None),
+        state: &mut state,
+        metrics: &metrics,
+        with: effect => handle_effect(effect)
+    ));
+
+    run(process!(
+        input: Input::SyncValueResponse(value_response(2)),
+        state: &mut state,
+        metrics: &metrics,
+        with: effect => handle_effect(effect)
+    ));
+
+    verify_count.get()
+}
+
+/// Test that the commit certificates of a batch of synced values are verified at once,
+/// and that the certificate of a later height is not verified again at that height.
+#[test]
+fn sync_batch_verifies_commit_certificates_at_once() {
+    assert_eq!(
+        sync_batch_then_height_2(ThresholdParams::default()),
+        0,
+        "Certificate verified as part of the batch should not be verified again"
+    );
+}
+
+/// Test that the certificate of a later height verified as part of a batch is verified again
+/// at that height if the thresholds were overridden for that height.
+#[test]
+fn sync_batch_certificate_is_verified_again_with_other_thresholds() {
+    let threshold_params = ThresholdParams {
+        quorum: ThresholdParam::new(3, 4),
+        ..ThresholdParams::default()
+    };
+
+    assert_eq!(
+        sync_batch_then_height_2(threshold_params),
+        1,
+        "Certificate verified with other thresholds should be verified again"
+    );
+}
@@ -0,0 +1,135 @@
+use bytes::Bytes;
+
+use arc_malachitebft_core_consensus::{
+    process, Effect, Error, Input, Params, Resumable, Resume, State,
+};
+use malachitebft_core_types::{
+    NilOrVal, Round, SignedExtension, SignedProposal, SignedVote, ValuePayload, Vote as _,
+};
+use malachitebft_metrics::Metrics;
+use malachitebft_test::utils::validators::make_validators;
+use malachitebft_test::{Height, Signature, TestContext, Validator, ValidatorSet, Value, Vote};
+
+fn run(r: Result<(), Error<TestContext>>) {
+    drop(r);
+}
+
+fn handle_effect(effect: Effect<TestContext>) -> Result<Resume<TestContext>, ()> {
+    use Effect::*;
+    Ok(match effect {
+        VerifySignature(_, _, r) => r.resume_with(true),
+        VerifyVoteExtension(.., r) => r.resume_with(Ok(())),
+        SignVote(vote, r) => r.resume_with(SignedVote::new(vote, Signature::test())),
+        SignProposal(proposal, r) => {
+            r.resume_with(SignedProposal::new(proposal, Signature::test()))
+        }
+        _ => Resume::Continue,
+    })
+}
+
+fn start(validators: &[Validator], require_vote_extensions: bool) -> (State<TestContext>, Metrics) {
+    let vs = ValidatorSet::new(validators.to_vec());
+
+    let mut state = State::new(
+        TestContext::new(),
+        Height::new(1),
+        vs.clone(),
+        Params {
+            address: validators[0].address,
+            threshold_params: Default::default(),
+            value_payload: ValuePayload::PartsOnly,
+            enabled: true,
+            require_vote_extensions,
+            full_proposal_limits: Default::default(),
+        },
+        1000,
+        1000,
+    );
+
+    let metrics = Metrics::new();
+
+    run(process!(
+        input: Input::StartHeight(Height::new(1), vs, false, None),
+        state: &mut state,
+        metrics: &metrics,
+        with: effect => handle_effect(effect)
+    ));
+
+    (state, metrics)
+}
+
+fn precommit(validator: &Validator, extension: Option<&[u8]>) -> SignedVote<TestContext> {
+    let mut vote = Vote::new_precommit(
+        Height::new(1),
+        Round::new(0),
+        NilOrVal::Val(Value::new(42).id()),
+        validator.address,
+    );
+
+    if let Some(extension) = extension {
+        vote = vote.extend(SignedExtension::new(
+            Bytes::copy_from_slice(extension),
+            Signature::test(),
+        ));
+    }
+
+    SignedVote::new(vote, Signature::test())
+}
+
+fn receive(state: &mut State<TestContext>, metrics: &Metrics, vote: SignedVote<TestContext>) {
+    run(process!(
+        input: Input::Vote(vote),
+        state: state,
+        metrics: metrics,
+        with: effect => handle_effect(effect)
+    ));
+}
+
+#[test]
+fn precommits_without_extension_are_rejected_when_required() {
+    let [(v1, _), (v2, _), (v3, _)] = make_validators([1, 1, 1]);
+    let validators = [v1, v2, v3];
+
+    let (mut state, metrics) = start(&validators, true);
+
+    let vote = precommit(&validators[1], None);
+    receive(&mut state, &metrics, vote.clone());
+    assert!(!state.driver.votes().has_vote(&vote));
+
+    let vote = precommit(&validators[1], Some(b"extension"));
+    receive(&mut state, &metrics, vote.clone());
+    assert!(state.driver.votes().has_vote(&vote));
+}
+
+#[test]
+fn precommits_without_extension_are_accepted_by_default() {
+    let [(v1, _), (v2, _), (v3, _)] = make_validators([1, 1, 1]);
+    let validators = [v1, v2, v3];
+
+    let (mut state, metrics) = start(&validators, false);
+
+    let vote = precommit(&validators[1], None);
+    receive(&mut state, &metrics, vote.clone());
+    assert!(state.driver.votes().has_vote(&vote));
+}
+
+#[test]
+fn nil_precommits_do_not_require_extension() {
+    let [(v1, _), (v2, _), (v3, _)] = make_validators([1, 1, 1]);
+    let validators = [v1, v2, v3];
+
+    let (mut state, metrics) = start(&validators, true);
+
+    let vote = SignedVote::new(
+        Vote::new_precommit(
+            Height::new(1),
+            Round::new(0),
+            NilOrVal::Nil,
+            validators[1].address,
+        ),
+        Signature::test(),
+    );
+
+    receive(&mut state, &metrics, vote.clone());
+    assert!(state.driver.votes().has_vote(&vote));
+}
@@ -107,6 +107,11 @@ where
         }
     }
 
+    /// Set the voting power thresholds, used from the next call to [`Driver::move_to_height`] on.
+    pub fn set_threshold_params(&mut self, threshold_params: ThresholdParams) {
+        self.threshold_params = threshold_params;
+    }
+
     /// Reset votes, round state, pending input and move to new height with the given validator set.
     pub fn move_to_height(&mut self, height: Ctx::Height, validator_set: Ctx::ValidatorSet) {
         // Update the validator set
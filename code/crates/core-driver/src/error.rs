@@ -1,6 +1,6 @@
 use derive_where::derive_where;
 
-use malachitebft_core_types::{Context, Round, Value};
+use malachitebft_core_types::{Context, ErrorKind, Round, Value};
 
 /// The type of errors that can be yielded by the `Driver`.
 #[derive_where(Clone, Debug, PartialEq, Eq)]
@@ -59,3 +59,37 @@ where
         value_id: <Ctx::Value as Value>::Id,
     },
 }
+
+impl<Ctx> Error<Ctx>
+where
+    Ctx: Context,
+{
+    /// Short name of the error, e.g. for labelling metrics
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::NoProposer(..) => "no_proposer",
+            Self::ProposerNotFound(..) => "proposer_not_found",
+            Self::ValidatorNotFound(..) => "validator_not_found",
+            Self::InvalidProposalHeight { .. } => "invalid_proposal_height",
+            Self::InvalidVoteHeight { .. } => "invalid_vote_height",
+            Self::InvalidCertificateHeight { .. } => "invalid_certificate_height",
+            Self::CertificateNotFound { .. } => "certificate_not_found",
+        }
+    }
+
+    /// The class of this error.
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            // Messages for other heights are expected around height changes
+            Self::InvalidProposalHeight { .. }
+            | Self::InvalidVoteHeight { .. }
+            | Self::InvalidCertificateHeight { .. } => ErrorKind::Recoverable,
+
+            Self::ValidatorNotFound(..) => ErrorKind::ProtocolViolation,
+
+            Self::NoProposer(..)
+            | Self::ProposerNotFound(..)
+            | Self::CertificateNotFound { .. } => ErrorKind::Internal,
+        }
+    }
+}
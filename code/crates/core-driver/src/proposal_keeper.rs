@@ -8,7 +8,7 @@ use derive_where::derive_where;
 use thiserror::Error;
 
 use malachitebft_core_types::{
-    Context, DoubleProposal, Proposal, Round, SignedProposal, Validity, Value, ValueId,
+    Context, DoubleProposal, Proposal, Round, RoundMap, SignedProposal, Validity, Value, ValueId,
 };
 use tracing::{error, warn};
 
@@ -180,7 +180,7 @@ where
     Ctx: Context,
 {
     /// The proposal for each round.
-    per_round: BTreeMap<Round, PerRound<Ctx>>,
+    per_round: RoundMap<PerRound<Ctx>>,
 
     /// Evidence of equivocation.
     evidence: EvidenceMap<Ctx>,
@@ -202,7 +202,7 @@ where
         value_id: ValueId<Ctx>,
     ) -> Option<&(SignedProposal<Ctx>, Validity)> {
         self.per_round
-            .get(&round)
+            .get(round)
             .and_then(|round_info| round_info.get_first_proposal_and_validity(value_id))
     }
 
@@ -212,13 +212,13 @@ where
         round: Round,
     ) -> &[(SignedProposal<Ctx>, Validity)] {
         self.per_round
-            .get(&round)
+            .get(round)
             .map(PerRound::get_proposals_and_validities)
             .unwrap_or(&[])
     }
 
     /// Returns all proposals and their validities for all rounds.
-    pub fn all_rounds(&self) -> &BTreeMap<Round, PerRound<Ctx>> {
+    pub fn all_rounds(&self) -> &RoundMap<PerRound<Ctx>> {
         &self.per_round
     }
 
@@ -234,7 +234,7 @@ where
 
     /// Store a proposal, checking for conflicts and storing evidence of equivocation if necessary.
     pub fn store_proposal(&mut self, proposal: SignedProposal<Ctx>, validity: Validity) {
-        let per_round = self.per_round.entry(proposal.round()).or_default();
+        let per_round = self.per_round.get_or_default(proposal.round());
 
         match per_round.add(proposal, validity) {
             Ok(()) => (),
@@ -6,7 +6,8 @@ use malachitebft_peer::PeerId;
 use thiserror::Error;
 
 use crate::{
-    BoxError, Context, NilOrVal, Round, Signature, SignedVote, ValueId, Vote, VoteType, VotingPower,
+    BoxError, ChainId, Context, NilOrVal, Round, Signature, SignedVote,
+    ValidatorSetUpdateSignature, ValueId, Vote, VoteType, VotingPower,
 };
 
 /// Represents a signature for a commit certificate, with the address of the validator that produced it.
@@ -151,6 +152,19 @@ pub enum CertificateError<Ctx: Context> {
     #[error("Invalid round signature: {0:?}")]
     InvalidRoundSignature(RoundSignature<Ctx>),
 
+    /// One of the signatures of a validator set update certificate is invalid.
+    #[error("Invalid validator set update signature: {0:?}")]
+    InvalidValidatorSetUpdateSignature(ValidatorSetUpdateSignature<Ctx>),
+
+    /// The chain id of a validator set update certificate is not the one of the context.
+    #[error("Chain id mismatch: expected {expected:?}, got {actual:?}")]
+    ChainIdMismatch {
+        /// Chain id of the context
+        expected: Option<ChainId>,
+        /// Chain id of the certificate
+        actual: Option<ChainId>,
+    },
+
     /// A validator in the certificate is not in the validator set.
     #[error("A validator in the certificate is not in the validator set: {0:?}")]
     UnknownValidator(Ctx::Address),
@@ -0,0 +1,114 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
+
+use thiserror::Error;
+
+/// Identifier of the chain (or network) a consensus message belongs to.
+///
+/// Contexts which return a chain id from [`Context::chain_id`](crate::Context::chain_id)
+/// include it in the votes, proposals and validator set updates they build, so that it is
+/// covered by their signatures and a message signed for one chain cannot be replayed on
+/// another chain where the same keys are in use.
+///
+/// A chain id is a non-empty string of at most [`ChainId::MAX_LEN`] bytes.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(::borsh::BorshSerialize, ::borsh::BorshDeserialize)
+)]
+pub struct ChainId(String);
+
+/// Error returned when building a [`ChainId`] from an invalid string.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum InvalidChainId {
+    /// The chain id is empty
+    #[error("Chain id cannot be empty")]
+    Empty,
+
+    /// The chain id is longer than [`ChainId::MAX_LEN`] bytes
+    #[error("Chain id is too long: {0} bytes, maximum is {max}", max = ChainId::MAX_LEN)]
+    TooLong(usize),
+}
+
+impl ChainId {
+    /// Maximum length of a chain id, in bytes.
+    pub const MAX_LEN: usize = 50;
+
+    /// Create a new chain id, checking that it is non-empty and not too long.
+    pub fn new(chain_id: impl Into<String>) -> Result<Self, InvalidChainId> {
+        let chain_id = chain_id.into();
+
+        if chain_id.is_empty() {
+            return Err(InvalidChainId::Empty);
+        }
+
+        if chain_id.len() > Self::MAX_LEN {
+            return Err(InvalidChainId::TooLong(chain_id.len()));
+        }
+
+        Ok(Self(chain_id))
+    }
+
+    /// The chain id as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The chain id as bytes, eg. to include it in the bytes to sign.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl fmt::Display for ChainId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for ChainId {
+    type Err = InvalidChainId;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s.to_string())
+    }
+}
+
+impl TryFrom<String> for ChainId {
+    type Error = InvalidChainId;
+
+    fn try_from(chain_id: String) -> Result<Self, Self::Error> {
+        Self::new(chain_id)
+    }
+}
+
+impl From<ChainId> for String {
+    fn from(chain_id: ChainId) -> Self {
+        chain_id.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_id_validation() {
+        let chain_id = ChainId::new("malachite-1").unwrap();
+        assert_eq!(chain_id.as_str(), "malachite-1");
+        assert_eq!("malachite-1".parse::<ChainId>(), Ok(chain_id));
+
+        assert_eq!(ChainId::new(""), Err(InvalidChainId::Empty));
+        assert!(ChainId::new("a".repeat(ChainId::MAX_LEN)).is_ok());
+        assert_eq!(
+            ChainId::new("a".repeat(ChainId::MAX_LEN + 1)),
+            Err(InvalidChainId::TooLong(ChainId::MAX_LEN + 1))
+        );
+    }
+}
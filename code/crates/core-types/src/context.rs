@@ -1,6 +1,6 @@
 use crate::{
-    Address, Extension, Height, NilOrVal, Proposal, ProposalPart, Round, SigningScheme, Timeouts,
-    Validator, ValidatorSet, Value, ValueId, Vote,
+    Address, ChainId, Extension, Height, NilOrVal, Proposal, ProposalPart, Round, SigningScheme,
+    Timeouts, Validator, ValidatorSet, ValidatorSetUpdate, Value, ValueId, Vote,
 };
 
 /// This trait allows to abstract over the various datatypes
@@ -43,6 +43,14 @@ where
     /// The signing scheme used to sign consensus messages.
     type SigningScheme: SigningScheme;
 
+    /// The chain id included in the votes and proposals built by this context,
+    /// and checked against the chain id of the votes and proposals received from peers.
+    ///
+    /// Returns `None` by default, in which case messages are not domain-separated by chain.
+    fn chain_id(&self) -> Option<&ChainId> {
+        None
+    }
+
     /// Select a proposer in the validator set for the given height and round.
     fn select_proposer<'a>(
         &self,
@@ -80,4 +88,18 @@ where
         value_id: NilOrVal<ValueId<Self>>,
         address: Self::Address,
     ) -> Self::Vote;
+
+    /// Apply the given update to the given validator set, returning the updated validator set.
+    ///
+    /// Returns `None` if validator set updates are not supported by this context,
+    /// or if the update cannot be applied to the given validator set, in which case
+    /// the validator set provided by the application is used as is.
+    fn apply_validator_set_update(
+        &self,
+        validator_set: &Self::ValidatorSet,
+        update: &ValidatorSetUpdate<Self>,
+    ) -> Option<Self::ValidatorSet> {
+        let _ = (validator_set, update);
+        None
+    }
 }
@@ -42,3 +42,35 @@ impl From<Box<dyn core::error::Error + Send + Sync>> for BoxError {
         BoxError::new(error)
     }
 }
+
+/// The class of an error, to drive programmatic recovery and metrics.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ErrorKind {
+    /// The error is transient or caused by stale input,
+    /// and the process can safely carry on.
+    Recoverable,
+
+    /// The error was caused by a peer or an input violating the protocol,
+    /// eg. an invalid certificate or a vote from a non-validator.
+    ProtocolViolation,
+
+    /// The error denotes a bug or an inconsistent internal state.
+    Internal,
+}
+
+impl ErrorKind {
+    /// A stable, lowercase name for this kind of error, suitable for use as a metric label.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Recoverable => "recoverable",
+            Self::ProtocolViolation => "protocol_violation",
+            Self::Internal => "internal",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,101 @@
+//! Hash functions used to derive identifiers, eg. the id of a value.
+//!
+//! Applications pick a hash function by implementing [`Hasher`], or by using one of
+//! the implementations provided behind the `sha2` and `blake3` feature flags.
+
+use core::fmt::Debug;
+
+/// An incremental hash function.
+pub trait Hasher: Default + Debug + Send + Sync + 'static {
+    /// The digest produced by the hash function.
+    type Output: AsRef<[u8]> + Copy + Debug + Eq;
+
+    /// Feed the given bytes to the hash function.
+    fn update(&mut self, data: &[u8]);
+
+    /// Consume the hasher and return the digest of the bytes fed so far.
+    fn finalize(self) -> Self::Output;
+
+    /// Compute the digest of the given bytes.
+    fn digest(data: &[u8]) -> Self::Output {
+        let mut hasher = Self::default();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+/// The SHA-256 hash function.
+#[cfg(feature = "sha2")]
+#[derive(Clone, Debug, Default)]
+pub struct Sha256(sha2::Sha256);
+
+#[cfg(feature = "sha2")]
+impl Hasher for Sha256 {
+    type Output = [u8; 32];
+
+    fn update(&mut self, data: &[u8]) {
+        sha2::Digest::update(&mut self.0, data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        sha2::Digest::finalize(self.0).into()
+    }
+}
+
+/// The BLAKE3 hash function.
+#[cfg(feature = "blake3")]
+#[derive(Clone, Debug, Default)]
+pub struct Blake3(blake3::Hasher);
+
+#[cfg(feature = "blake3")]
+impl Hasher for Blake3 {
+    type Output = [u8; 32];
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        self.0.finalize().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn sha256_digest() {
+        let digest = Sha256::digest(b"abc");
+
+        assert_eq!(
+            digest[..4],
+            [0xba, 0x78, 0x16, 0xbf],
+            "SHA-256 test vector for \"abc\""
+        );
+
+        let mut hasher = Sha256::default();
+        hasher.update(b"a");
+        hasher.update(b"bc");
+        assert_eq!(hasher.finalize(), digest);
+    }
+
+    #[test]
+    #[cfg(feature = "blake3")]
+    fn blake3_digest() {
+        let digest = Blake3::digest(b"abc");
+
+        assert_eq!(
+            digest[..4],
+            [0x64, 0x37, 0xb3, 0xac],
+            "BLAKE3 test vector for \"abc\""
+        );
+
+        let mut hasher = Blake3::default();
+        hasher.update(b"a");
+        hasher.update(b"bc");
+        assert_eq!(hasher.finalize(), digest);
+    }
+}
@@ -1,7 +1,7 @@
 use core::time::Duration;
 use derive_where::derive_where;
 
-use crate::Context;
+use crate::{Context, ThresholdParams, ValuePayload};
 
 /// Consensus parameters to use when starting or restarting a height.
 #[derive_where(Debug, Clone, PartialEq, Eq)]
@@ -30,3 +30,26 @@ impl<Ctx: Context> HeightParams<Ctx> {
         }
     }
 }
+
+/// Consensus parameters overridden by the application for a single height.
+///
+/// The parameters left to `None` keep the value given when starting the height,
+/// for the timeouts, or the configured value, for the other parameters.
+#[derive_where(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HeightParamsOverride<Ctx: Context> {
+    /// Timeouts for the height, instead of the ones given when starting it
+    pub timeouts: Option<Ctx::Timeouts>,
+
+    /// Voting power thresholds for the height
+    pub threshold_params: Option<ThresholdParams>,
+
+    /// How proposed values are disseminated at the height
+    pub value_payload: Option<ValuePayload>,
+}
+
+impl<Ctx: Context> HeightParamsOverride<Ctx> {
+    /// Whether no parameter is overridden.
+    pub fn is_empty(&self) -> bool {
+        self.timeouts.is_none() && self.threshold_params.is_none() && self.value_payload.is_none()
+    }
+}
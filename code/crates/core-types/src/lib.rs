@@ -14,6 +14,7 @@
 extern crate alloc;
 
 mod certificate;
+mod chain_id;
 mod context;
 mod error;
 mod height;
@@ -21,6 +22,7 @@ mod height_params;
 mod proposal;
 mod proposal_part;
 mod round;
+mod round_map;
 mod ser;
 mod signed_message;
 mod signing;
@@ -29,6 +31,7 @@ mod timeout;
 mod timeouts;
 mod validator_proof;
 mod validator_set;
+mod validator_set_update;
 mod value;
 mod vote;
 mod vote_extension;
@@ -36,6 +39,8 @@ mod vote_extension;
 /// Utility functions and types.
 pub mod utils;
 
+pub mod hash;
+
 /// Type alias to make it easier to refer the `ValueId` type.
 pub type ValueId<Ctx> = <<Ctx as Context>::Value as Value>::Id;
 
@@ -67,20 +72,27 @@ pub use certificate::{
     CertificateError, CommitCertificate, CommitSignature, EnterRoundCertificate, PolkaCertificate,
     PolkaSignature, RoundCertificate, RoundCertificateType, RoundSignature, ValueResponse,
 };
+pub use chain_id::{ChainId, InvalidChainId};
 pub use context::Context;
-pub use error::BoxError;
+pub use error::{BoxError, ErrorKind};
 pub use height::Height;
-pub use height_params::HeightParams;
+pub use height_params::{HeightParams, HeightParamsOverride};
+#[cfg(feature = "derive")]
+pub use malachitebft_derive::Context;
 pub use proposal::{Proposal, Validity};
 pub use proposal_part::ProposalPart;
 pub use round::Round;
+pub use round_map::RoundMap;
 pub use signed_message::SignedMessage;
 pub use signing::SigningScheme;
 pub use threshold::{Threshold, ThresholdParam, ThresholdParams};
-pub use timeout::{Timeout, TimeoutKind};
+pub use timeout::{RoundTimeoutsOverride, Timeout, TimeoutKind};
 pub use timeouts::{LinearTimeouts, Timeouts};
 pub use validator_proof::ValidatorProof;
-pub use validator_set::{Address, Validator, ValidatorSet, VotingPower};
+pub use validator_set::{Address, Validator, ValidatorSet, VotingPower, VotingPowerChange};
+pub use validator_set_update::{
+    ValidatorChange, ValidatorSetUpdate, ValidatorSetUpdateCertificate, ValidatorSetUpdateSignature,
+};
 pub use value::{NilOrVal, Value, ValueOrigin, ValuePayload};
 pub use vote::{Vote, VoteType};
 pub use vote_extension::{Extension, VoteExtensions};
@@ -1,6 +1,6 @@
 use core::fmt::Debug;
 
-use crate::{Context, Round};
+use crate::{ChainId, Context, Round};
 
 /// Defines the requirements for a proposal type.
 pub trait Proposal<Ctx>
@@ -25,6 +25,13 @@ where
 
     /// Address of the validator who issued this proposal
     fn validator_address(&self) -> &Ctx::Address;
+
+    /// The chain id this proposal was made for, if any.
+    ///
+    /// Proposals whose chain id differs from the one of the context are rejected.
+    fn chain_id(&self) -> Option<&ChainId> {
+        None
+    }
 }
 
 /// Whether or not a proposal is valid.
@@ -0,0 +1,244 @@
+use alloc::collections::btree_map::{self, BTreeMap};
+use core::ops::RangeBounds;
+
+use crate::Round;
+
+/// A map from rounds to values, ordered by round.
+///
+/// The nil round is ordered before all defined rounds, as `-1` would be,
+/// and is returned as the highest or lowest round of an empty map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoundMap<T> {
+    rounds: BTreeMap<Round, T>,
+}
+
+impl<T> Default for RoundMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> RoundMap<T> {
+    /// Create an empty map.
+    pub const fn new() -> Self {
+        Self {
+            rounds: BTreeMap::new(),
+        }
+    }
+
+    /// Number of rounds in the map.
+    pub fn len(&self) -> usize {
+        self.rounds.len()
+    }
+
+    /// Whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.rounds.is_empty()
+    }
+
+    /// Whether the map has a value for the given round.
+    pub fn contains(&self, round: Round) -> bool {
+        self.rounds.contains_key(&round)
+    }
+
+    /// The value for the given round, if any.
+    pub fn get(&self, round: Round) -> Option<&T> {
+        self.rounds.get(&round)
+    }
+
+    /// The value for the given round, if any.
+    pub fn get_mut(&mut self, round: Round) -> Option<&mut T> {
+        self.rounds.get_mut(&round)
+    }
+
+    /// The value for the given round, inserting the default value if there is none.
+    pub fn get_or_default(&mut self, round: Round) -> &mut T
+    where
+        T: Default,
+    {
+        self.rounds.entry(round).or_default()
+    }
+
+    /// The value for the given round, inserting the value computed by `f` if there is none.
+    pub fn get_or_insert_with(&mut self, round: Round, f: impl FnOnce() -> T) -> &mut T {
+        self.rounds.entry(round).or_insert_with(f)
+    }
+
+    /// Insert the value for the given round, returning the previous value for that round, if any.
+    pub fn insert(&mut self, round: Round, value: T) -> Option<T> {
+        self.rounds.insert(round, value)
+    }
+
+    /// Remove the value for the given round, if any.
+    pub fn remove(&mut self, round: Round) -> Option<T> {
+        self.rounds.remove(&round)
+    }
+
+    /// Remove all rounds.
+    pub fn clear(&mut self) {
+        self.rounds.clear();
+    }
+
+    /// The highest round in the map, or [`Round::Nil`] if the map is empty.
+    pub fn max_round(&self) -> Round {
+        self.rounds
+            .last_key_value()
+            .map_or(Round::Nil, |(round, _)| *round)
+    }
+
+    /// The lowest round in the map, or [`Round::Nil`] if the map is empty.
+    pub fn min_round(&self) -> Round {
+        self.rounds
+            .first_key_value()
+            .map_or(Round::Nil, |(round, _)| *round)
+    }
+
+    /// Iterate over the rounds and their values, in increasing order of rounds.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Round, &T)> {
+        self.rounds.iter().map(|(round, value)| (*round, value))
+    }
+
+    /// Iterate over the rounds and their values, in increasing order of rounds.
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = (Round, &mut T)> {
+        self.rounds.iter_mut().map(|(round, value)| (*round, value))
+    }
+
+    /// Iterate over the rounds, in increasing order.
+    pub fn rounds(&self) -> impl DoubleEndedIterator<Item = Round> + '_ {
+        self.rounds.keys().copied()
+    }
+
+    /// Iterate over the values, in increasing order of rounds.
+    pub fn values(&self) -> btree_map::Values<'_, Round, T> {
+        self.rounds.values()
+    }
+
+    /// Iterate over the rounds within the given range and their values, in increasing order of rounds.
+    pub fn range(
+        &self,
+        range: impl RangeBounds<Round>,
+    ) -> impl DoubleEndedIterator<Item = (Round, &T)> {
+        self.rounds
+            .range(range)
+            .map(|(round, value)| (*round, value))
+    }
+
+    /// Iterate over the rounds within the given range and their values, in increasing order of rounds.
+    pub fn range_mut(
+        &mut self,
+        range: impl RangeBounds<Round>,
+    ) -> impl DoubleEndedIterator<Item = (Round, &mut T)> {
+        self.rounds
+            .range_mut(range)
+            .map(|(round, value)| (*round, value))
+    }
+
+    /// Keep only the rounds for which the predicate holds.
+    pub fn retain(&mut self, mut f: impl FnMut(Round, &mut T) -> bool) {
+        self.rounds.retain(|round, value| f(*round, value));
+    }
+
+    /// Remove the rounds lower than `min_round`, returning the number of rounds removed.
+    ///
+    /// Pruning below [`Round::Nil`] removes nothing.
+    pub fn prune_below(&mut self, min_round: Round) -> usize {
+        let kept = self.rounds.split_off(&min_round);
+        let pruned = self.rounds.len();
+        self.rounds = kept;
+        pruned
+    }
+}
+
+impl<T> FromIterator<(Round, T)> for RoundMap<T> {
+    fn from_iter<I: IntoIterator<Item = (Round, T)>>(iter: I) -> Self {
+        Self {
+            rounds: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> IntoIterator for RoundMap<T> {
+    type Item = (Round, T);
+    type IntoIter = btree_map::IntoIter<Round, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rounds.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn map(rounds: &[Round]) -> RoundMap<i64> {
+        rounds.iter().map(|r| (*r, r.as_i64())).collect()
+    }
+
+    #[test]
+    fn nil_round_is_lowest() {
+        let map = map(&[Round::new(1), Round::Nil, Round::new(0)]);
+
+        assert_eq!(
+            map.rounds().collect::<Vec<_>>(),
+            vec![Round::Nil, Round::new(0), Round::new(1)]
+        );
+        assert_eq!(map.min_round(), Round::Nil);
+        assert_eq!(map.max_round(), Round::new(1));
+    }
+
+    #[test]
+    fn empty_map_rounds_are_nil() {
+        let map = RoundMap::<()>::new();
+
+        assert_eq!(map.min_round(), Round::Nil);
+        assert_eq!(map.max_round(), Round::Nil);
+        assert_eq!(map.get(Round::Nil), None);
+    }
+
+    #[test]
+    fn range_queries() {
+        let map = map(&[Round::Nil, Round::new(0), Round::new(2), Round::new(5)]);
+
+        let below_two = map
+            .range(..Round::new(2))
+            .map(|(r, _)| r)
+            .collect::<Vec<_>>();
+        assert_eq!(below_two, vec![Round::Nil, Round::new(0)]);
+
+        let from_one = map
+            .range(Round::new(1)..)
+            .map(|(r, _)| r)
+            .collect::<Vec<_>>();
+        assert_eq!(from_one, vec![Round::new(2), Round::new(5)]);
+    }
+
+    #[test]
+    fn prune_below() {
+        let mut map = map(&[Round::Nil, Round::new(0), Round::new(2), Round::new(5)]);
+
+        assert_eq!(map.prune_below(Round::Nil), 0);
+        assert_eq!(map.len(), 4);
+
+        assert_eq!(map.prune_below(Round::new(2)), 2);
+        assert_eq!(
+            map.rounds().collect::<Vec<_>>(),
+            vec![Round::new(2), Round::new(5)]
+        );
+
+        assert_eq!(map.prune_below(Round::new(10)), 2);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn get_or_default() {
+        let mut map = RoundMap::<u32>::new();
+
+        *map.get_or_default(Round::new(3)) += 1;
+        *map.get_or_default(Round::new(3)) += 1;
+
+        assert_eq!(map.get(Round::new(3)), Some(&2));
+        assert_eq!(map.len(), 1);
+    }
+}
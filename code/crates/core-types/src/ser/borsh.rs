@@ -1,8 +1,9 @@
 use {
     crate::{
-        CommitCertificate, CommitSignature, Context, NilOrVal, PolkaCertificate, PolkaSignature,
-        Round, RoundCertificate, RoundCertificateType, RoundSignature, Signature, SignedMessage,
-        ValueId, VoteType,
+        ChainId, CommitCertificate, CommitSignature, Context, NilOrVal, PolkaCertificate,
+        PolkaSignature, Round, RoundCertificate, RoundCertificateType, RoundSignature, Signature,
+        SignedMessage, ValidatorChange, ValidatorSetUpdate, ValidatorSetUpdateCertificate,
+        ValidatorSetUpdateSignature, ValueId, VoteType,
     },
     ::borsh::BorshSerialize,
     alloc::vec::Vec,
@@ -200,6 +201,87 @@ where
     }
 }
 
+impl<Ctx: Context> ::borsh::BorshSerialize for ValidatorSetUpdate<Ctx>
+where
+    Ctx::Height: borsh::BorshSerialize,
+{
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        self.chain_id.serialize(writer)?;
+        self.epoch.serialize(writer)?;
+        self.effective_height.serialize(writer)?;
+        self.diff.serialize(writer)?;
+        Ok(())
+    }
+}
+
+impl<Ctx: Context> ::borsh::BorshDeserialize for ValidatorSetUpdate<Ctx>
+where
+    Ctx::Height: borsh::BorshDeserialize,
+{
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let chain_id = Option::<ChainId>::deserialize_reader(reader)?;
+        let epoch = u64::deserialize_reader(reader)?;
+        let effective_height = Ctx::Height::deserialize_reader(reader)?;
+        let diff = Vec::<ValidatorChange>::deserialize_reader(reader)?;
+        Ok(ValidatorSetUpdate {
+            chain_id,
+            epoch,
+            effective_height,
+            diff,
+        })
+    }
+}
+
+impl<Ctx: Context> ::borsh::BorshSerialize for ValidatorSetUpdateSignature<Ctx>
+where
+    Ctx::Address: borsh::BorshSerialize,
+    Signature<Ctx>: borsh::BorshSerialize,
+{
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        self.address.serialize(writer)?;
+        self.signature.serialize(writer)?;
+        Ok(())
+    }
+}
+
+impl<Ctx: Context> ::borsh::BorshDeserialize for ValidatorSetUpdateSignature<Ctx>
+where
+    Ctx::Address: borsh::BorshDeserialize,
+    Signature<Ctx>: borsh::BorshDeserialize,
+{
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let address = Ctx::Address::deserialize_reader(reader)?;
+        let signature = Signature::<Ctx>::deserialize_reader(reader)?;
+        Ok(ValidatorSetUpdateSignature { address, signature })
+    }
+}
+
+impl<Ctx: Context> ::borsh::BorshSerialize for ValidatorSetUpdateCertificate<Ctx>
+where
+    Ctx::Height: borsh::BorshSerialize,
+    Ctx::Address: borsh::BorshSerialize,
+    Signature<Ctx>: borsh::BorshSerialize,
+{
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        self.update.serialize(writer)?;
+        self.signatures.serialize(writer)?;
+        Ok(())
+    }
+}
+
+impl<Ctx: Context> ::borsh::BorshDeserialize for ValidatorSetUpdateCertificate<Ctx>
+where
+    Ctx::Height: borsh::BorshDeserialize,
+    Ctx::Address: borsh::BorshDeserialize,
+    Signature<Ctx>: borsh::BorshDeserialize,
+{
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let update = ValidatorSetUpdate::<Ctx>::deserialize_reader(reader)?;
+        let signatures = Vec::<ValidatorSetUpdateSignature<Ctx>>::deserialize_reader(reader)?;
+        Ok(ValidatorSetUpdateCertificate { update, signatures })
+    }
+}
+
 impl<Ctx, Msg> borsh::BorshSerialize for SignedMessage<Ctx, Msg>
 where
     Ctx: Context,
@@ -65,6 +65,43 @@ impl Timeout {
     }
 }
 
+/// Durations of the timeouts of a round, as overridden by the application.
+///
+/// The timeouts left to `None` keep the duration computed from the timeouts of the height.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoundTimeoutsOverride {
+    /// Duration of the propose timeout
+    pub propose: Option<Duration>,
+
+    /// Duration of the prevote timeout
+    pub prevote: Option<Duration>,
+
+    /// Duration of the precommit timeout
+    pub precommit: Option<Duration>,
+
+    /// Duration of the rebroadcast timeout
+    pub rebroadcast: Option<Duration>,
+}
+
+impl RoundTimeoutsOverride {
+    /// The overridden duration of the given kind of timeout, if any.
+    pub fn get(&self, kind: TimeoutKind) -> Option<Duration> {
+        match kind {
+            TimeoutKind::Propose => self.propose,
+            TimeoutKind::Prevote => self.prevote,
+            TimeoutKind::Precommit => self.precommit,
+            TimeoutKind::Rebroadcast => self.rebroadcast,
+            TimeoutKind::FinalizeHeight(_) => None,
+        }
+    }
+
+    /// Whether no timeout is overridden.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 impl fmt::Display for Timeout {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}Timeout({})", self.kind, self.round)
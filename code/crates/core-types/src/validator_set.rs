@@ -1,6 +1,6 @@
 use core::fmt::{Debug, Display};
 
-use crate::{Context, PublicKey};
+use crate::{Context, PublicKey, ThresholdParam};
 
 /// Voting power held by a validator.
 ///
@@ -62,3 +62,43 @@ where
     /// Get the validator at the given index.
     fn get_by_index(&self, index: usize) -> Option<&Ctx::Validator>;
 }
+
+/// How much voting power changes from one validator set to the next.
+///
+/// The voting power of a validator present in both sets is only counted as unchanged
+/// up to the smaller of its two voting powers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VotingPowerChange {
+    /// Voting power held by the same validators in both validator sets
+    pub unchanged: VotingPower,
+    /// Total voting power of the validator set with the most voting power
+    pub total: VotingPower,
+}
+
+impl VotingPowerChange {
+    /// Compute how much voting power changes from the `previous` validator set to the `next` one.
+    pub fn between<Ctx: Context>(previous: &Ctx::ValidatorSet, next: &Ctx::ValidatorSet) -> Self {
+        let unchanged = previous
+            .iter()
+            .filter_map(|validator| {
+                next.get_by_address(validator.address())
+                    .map(|other| validator.voting_power().min(other.voting_power()))
+            })
+            .sum();
+
+        let total = previous.total_voting_power().max(next.total_voting_power());
+
+        Self { unchanged, total }
+    }
+
+    /// The voting power which changes.
+    pub fn changed(&self) -> VotingPower {
+        self.total.saturating_sub(self.unchanged)
+    }
+
+    /// Whether the voting power which changes meets the given threshold of the total voting power,
+    /// eg. [`ThresholdParam::F_PLUS_ONE`] for more than a third of it.
+    pub fn exceeds(&self, threshold: ThresholdParam) -> bool {
+        threshold.is_met(self.changed(), self.total)
+    }
+}
@@ -0,0 +1,143 @@
+//! Validator set updates, gossiped between nodes to propagate validator set changes.
+
+use alloc::vec::Vec;
+use derive_where::derive_where;
+
+use crate::{ChainId, Context, Height, Signature, VotingPower};
+
+/// Separator bytes for validator set update signatures.
+/// The 3-byte ASCII string "VSU" (0x56 0x53 0x55).
+const VSU_SEPARATOR: &[u8] = b"VSU";
+
+/// A change to a single validator of the validator set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "borsh",
+    derive(::borsh::BorshSerialize, ::borsh::BorshDeserialize)
+)]
+pub struct ValidatorChange {
+    /// The validator's consensus public key (raw bytes)
+    pub public_key: Vec<u8>,
+    /// The new voting power of the validator, the validator is removed if zero
+    pub voting_power: VotingPower,
+}
+
+impl ValidatorChange {
+    /// Creates a new `ValidatorChange`.
+    pub fn new(public_key: Vec<u8>, voting_power: VotingPower) -> Self {
+        Self {
+            public_key,
+            voting_power,
+        }
+    }
+
+    /// Whether this change removes the validator from the validator set.
+    pub fn is_removal(&self) -> bool {
+        self.voting_power == 0
+    }
+}
+
+/// A set of changes to the validator set, taking effect at a given height.
+///
+/// Changes set the voting power of a validator rather than adjusting it,
+/// so applying the same update twice yields the same validator set.
+#[derive_where(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorSetUpdate<Ctx: Context> {
+    /// The chain the update belongs to, see [`Context::chain_id`]
+    pub chain_id: Option<ChainId>,
+    /// The epoch of the update, strictly increasing from one update to the next
+    pub epoch: u64,
+    /// The height from which the updated validator set is in effect
+    pub effective_height: Ctx::Height,
+    /// The changes to the validator set
+    pub diff: Vec<ValidatorChange>,
+}
+
+impl<Ctx: Context> ValidatorSetUpdate<Ctx> {
+    /// Creates a new `ValidatorSetUpdate`, without a chain id.
+    pub fn new(epoch: u64, effective_height: Ctx::Height, diff: Vec<ValidatorChange>) -> Self {
+        Self {
+            chain_id: None,
+            epoch,
+            effective_height,
+            diff,
+        }
+    }
+
+    /// Sets the chain the update belongs to, which must be the one of the context
+    /// for the update to be accepted.
+    pub fn with_chain_id(self, chain_id: Option<ChainId>) -> Self {
+        Self { chain_id, ..self }
+    }
+
+    /// Returns the bytes to be signed by the validators approving this update.
+    ///
+    /// Format: SEPARATOR || len(chain_id) || chain_id || epoch || effective_height || len(diff) || (len(public_key) || public_key || voting_power)*
+    ///
+    /// Where:
+    /// - SEPARATOR is "VSU" (0x56 0x53 0x55)
+    /// - chain_id is empty if the update has no chain id
+    /// - epoch, effective_height and voting_power are encoded as 8 bytes (u64 big-endian)
+    /// - len() is encoded as 4 bytes (u32 big-endian)
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let chain_id = self.chain_id.as_ref().map_or(&[][..], ChainId::as_bytes);
+
+        let changes_len = self
+            .diff
+            .iter()
+            .map(|change| 4 + change.public_key.len() + 8)
+            .sum::<usize>();
+
+        let mut bytes =
+            Vec::with_capacity(VSU_SEPARATOR.len() + 4 + chain_id.len() + 8 + 8 + 4 + changes_len);
+        bytes.extend_from_slice(VSU_SEPARATOR);
+        bytes.extend_from_slice(&(chain_id.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(chain_id);
+        bytes.extend_from_slice(&self.epoch.to_be_bytes());
+        bytes.extend_from_slice(&self.effective_height.as_u64().to_be_bytes());
+        bytes.extend_from_slice(&(self.diff.len() as u32).to_be_bytes());
+
+        for change in &self.diff {
+            bytes.extend_from_slice(&(change.public_key.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(&change.public_key);
+            bytes.extend_from_slice(&change.voting_power.to_be_bytes());
+        }
+
+        bytes
+    }
+}
+
+/// Signature of a validator approving a validator set update.
+#[derive_where(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorSetUpdateSignature<Ctx: Context> {
+    /// The address of the validator
+    pub address: Ctx::Address,
+    /// The signature of the validator over the update
+    pub signature: Signature<Ctx>,
+}
+
+impl<Ctx: Context> ValidatorSetUpdateSignature<Ctx> {
+    /// Creates a new `ValidatorSetUpdateSignature`.
+    pub fn new(address: Ctx::Address, signature: Signature<Ctx>) -> Self {
+        Self { address, signature }
+    }
+}
+
+/// A validator set update, approved by 2/3+ of the voting power of the current validator set.
+#[derive_where(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorSetUpdateCertificate<Ctx: Context> {
+    /// The validator set update
+    pub update: ValidatorSetUpdate<Ctx>,
+    /// The signatures of the validators approving the update
+    pub signatures: Vec<ValidatorSetUpdateSignature<Ctx>>,
+}
+
+impl<Ctx: Context> ValidatorSetUpdateCertificate<Ctx> {
+    /// Creates a new `ValidatorSetUpdateCertificate`.
+    pub fn new(
+        update: ValidatorSetUpdate<Ctx>,
+        signatures: Vec<ValidatorSetUpdateSignature<Ctx>>,
+    ) -> Self {
+        Self { update, signatures }
+    }
+}
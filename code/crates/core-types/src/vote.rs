@@ -1,6 +1,6 @@
 use core::fmt::Debug;
 
-use crate::{Context, NilOrVal, Round, SignedExtension, Value};
+use crate::{ChainId, Context, NilOrVal, Round, SignedExtension, Value};
 
 /// A type of vote.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -53,4 +53,11 @@ where
 
     /// Extend this vote with an extension, overriding any existing extension.
     fn extend(self, extension: SignedExtension<Ctx>) -> Self;
+
+    /// The chain id this vote was cast for, if any.
+    ///
+    /// Votes whose chain id differs from the one of the context are rejected.
+    fn chain_id(&self) -> Option<&ChainId> {
+        None
+    }
 }
@@ -4,7 +4,7 @@ use alloc::vec::Vec;
 use bytes::Bytes;
 use derive_where::derive_where;
 
-use crate::{Context, SignedExtension};
+use crate::{Context, SignedExtension, Validator, ValidatorSet, VotingPower};
 
 /// A set of vote extensions.
 #[derive_where(Clone, Debug, Default, PartialEq, Eq)]
@@ -26,6 +26,36 @@ impl<Ctx: Context> VoteExtensions<Ctx> {
     pub fn size_bytes(&self) -> usize {
         self.extensions.iter().map(|(_, e)| e.size_bytes()).sum()
     }
+
+    /// Returns the number of vote extensions.
+    pub fn len(&self) -> usize {
+        self.extensions.len()
+    }
+
+    /// Returns whether there are no vote extensions.
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty()
+    }
+
+    /// Returns the vote extension of the given validator, if any.
+    pub fn get(&self, address: &Ctx::Address) -> Option<&SignedExtension<Ctx>> {
+        self.extensions
+            .iter()
+            .find(|(a, _)| a == address)
+            .map(|(_, extension)| extension)
+    }
+
+    /// Returns the total voting power of the validators which provided a vote extension,
+    /// according to the given validator set.
+    ///
+    /// Validators which are not part of the validator set are ignored.
+    pub fn voting_power(&self, validator_set: &Ctx::ValidatorSet) -> VotingPower {
+        self.extensions
+            .iter()
+            .filter_map(|(address, _)| validator_set.get_by_address(address))
+            .map(|validator| validator.voting_power())
+            .sum()
+    }
 }
 
 /// Vote extensions allows applications to extend the pre-commit vote with arbitrary data.
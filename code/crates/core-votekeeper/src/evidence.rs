@@ -63,6 +63,13 @@ where
         }
     }
 
+    /// Check whether the given vote is already part of the recorded evidence of equivocation.
+    pub fn contains(&self, vote: &SignedVote<Ctx>) -> bool {
+        self.map
+            .get(vote.validator_address())
+            .is_some_and(|evidence| evidence.iter().any(|(e, c)| e == vote || c == vote))
+    }
+
     /// Iterate over all addresses with recorded vote equivocations.
     pub fn iter(
         &self,
@@ -7,7 +7,8 @@ use tracing::warn;
 use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::vec::Vec;
 use malachitebft_core_types::{
-    Context, NilOrVal, Round, SignedVote, Validator, ValidatorSet, ValueId, Vote, VoteType,
+    Context, NilOrVal, Round, RoundMap, SignedVote, Validator, ValidatorSet, ValueId, Vote,
+    VoteType,
 };
 
 use crate::evidence::EvidenceMap;
@@ -56,6 +57,23 @@ where
     emitted_outputs: BTreeSet<Output<ValueId<Ctx>>>,
 }
 
+/// Number of votes received from a validator at the current height, across all rounds.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Participation {
+    /// Number of prevotes received from the validator.
+    pub prevotes: usize,
+
+    /// Number of precommits received from the validator.
+    pub precommits: usize,
+}
+
+impl Participation {
+    /// Whether no vote was received from the validator.
+    pub fn is_absent(&self) -> bool {
+        self.prevotes == 0 && self.precommits == 0
+    }
+}
+
 /// Errors can that be yielded when recording a vote.
 #[derive(Error)]
 pub enum RecordVoteError<Ctx>
@@ -178,10 +196,13 @@ where
     threshold_params: ThresholdParams,
 
     /// The votes and emitted outputs for each round.
-    per_round: BTreeMap<Round, PerRound<Ctx>>,
+    per_round: RoundMap<PerRound<Ctx>>,
 
     /// Evidence of equivocation.
     evidence: EvidenceMap<Ctx>,
+
+    /// Number of votes received from each validator, across all rounds.
+    participation: BTreeMap<Ctx::Address, Participation>,
 }
 
 impl<Ctx> VoteKeeper<Ctx>
@@ -194,8 +215,9 @@ where
         Self {
             validator_set,
             threshold_params,
-            per_round: BTreeMap::new(),
+            per_round: RoundMap::new(),
             evidence: EvidenceMap::new(),
+            participation: BTreeMap::new(),
         }
     }
 
@@ -211,11 +233,11 @@ where
 
     /// Return the votes for the given round.
     pub fn per_round(&self, round: Round) -> Option<&PerRound<Ctx>> {
-        self.per_round.get(&round)
+        self.per_round.get(round)
     }
 
     /// Return votes for all rounds we have seen so far.
-    pub fn all_rounds(&self) -> &BTreeMap<Round, PerRound<Ctx>> {
+    pub fn all_rounds(&self) -> &RoundMap<PerRound<Ctx>> {
         &self.per_round
     }
 
@@ -226,7 +248,7 @@ where
 
     /// Return the highest round we have seen votes for so far.
     pub fn max_round(&self) -> Round {
-        self.per_round.keys().max().copied().unwrap_or(Round::Nil)
+        self.per_round.max_round()
     }
 
     /// Return the evidence of equivocation.
@@ -239,13 +261,35 @@ where
         core::mem::take(&mut self.evidence)
     }
 
+    /// Return the number of votes received from each validator of the validator set
+    /// at the current height, across all rounds, including the rounds which were pruned.
+    ///
+    /// Validators from which no vote was received are included, with no votes.
+    pub fn participation(&self) -> BTreeMap<Ctx::Address, Participation> {
+        (0..self.validator_set.count())
+            .filter_map(|index| self.validator_set.get_by_index(index))
+            .map(|validator| {
+                let address = validator.address();
+                let participation = self.participation.get(address).copied().unwrap_or_default();
+
+                (address.clone(), participation)
+            })
+            .collect()
+    }
+
     /// Check if we have already seen a vote.
     pub fn has_vote(&self, vote: &SignedVote<Ctx>) -> bool {
         self.per_round
-            .get(&vote.round())
+            .get(vote.round())
             .is_some_and(|per_round| per_round.received_votes().contains(vote))
     }
 
+    /// Check if applying the vote cannot change the state of the vote keeper, nor trigger any output,
+    /// because the vote was already recorded or is already held as evidence of equivocation.
+    pub fn is_known_vote(&self, vote: &SignedVote<Ctx>) -> bool {
+        self.has_vote(vote) || self.evidence.contains(vote)
+    }
+
     /// Apply a vote with a given weight, potentially triggering an output.
     pub fn apply_vote(
         &mut self,
@@ -253,20 +297,37 @@ where
         round: Round,
     ) -> Option<Output<ValueId<Ctx>>> {
         let total_weight = self.total_weight();
-        let per_round =
-            self.per_round
-                .entry(vote.round())
-                .or_insert(PerRound::with_expected_number_of_votes(
-                    self.validator_set.count(),
-                ));
+        let expected_votes = self.validator_set.count();
+        let per_round = self.per_round.get_or_insert_with(vote.round(), || {
+            PerRound::with_expected_number_of_votes(expected_votes)
+        });
 
         let Some(validator) = self.validator_set.get_by_address(vote.validator_address()) else {
             // Vote from unknown validator, let's discard it.
             return None;
         };
 
-        match per_round.add(vote.clone(), validator.voting_power()) {
-            Ok(()) => (),
+        let vote_type = vote.vote_type();
+        let vote_round = vote.round();
+        let value = vote.value().clone();
+
+        // Only the address of new voters is needed afterwards, to record their participation
+        let new_voter = per_round
+            .get_vote(vote_type, vote.validator_address())
+            .is_none()
+            .then(|| vote.validator_address().clone());
+
+        match per_round.add(vote, validator.voting_power()) {
+            Ok(()) => {
+                if let Some(address) = new_voter {
+                    let participation = self.participation.entry(address).or_default();
+
+                    match vote_type {
+                        VoteType::Prevote => participation.prevotes += 1,
+                        VoteType::Precommit => participation.precommits += 1,
+                    }
+                }
+            }
             Err(RecordVoteError::ConflictingVote {
                 existing,
                 conflicting,
@@ -282,25 +343,25 @@ where
         }
 
         let threshold = compute_threshold(
-            vote.vote_type(),
+            vote_type,
             per_round,
-            vote.value(),
+            &value,
             self.threshold_params,
             total_weight,
         );
 
-        let skip_round = if vote.round() > round
+        let skip_round = if vote_round > round
             && self
                 .threshold_params
                 .honest
                 .is_met(per_round.addresses_weights.sum(), total_weight)
         {
-            Some(vote.round())
+            Some(vote_round)
         } else {
             None
         };
 
-        let output = threshold_to_output(vote.vote_type(), threshold, skip_round);
+        let output = threshold_to_output(vote_type, threshold, skip_round);
 
         match output {
             // Ensure we do not output the same message twice
@@ -319,7 +380,7 @@ where
         vote_type: VoteType,
         threshold: Threshold<ValueId<Ctx>>,
     ) -> bool {
-        self.per_round.get(round).is_some_and(|per_round| {
+        self.per_round.get(*round).is_some_and(|per_round| {
             per_round.votes.is_threshold_met(
                 vote_type,
                 threshold,
@@ -331,7 +392,7 @@ where
 
     /// Prunes all stored votes from rounds less than `min_round`.
     pub fn prune_votes(&mut self, min_round: Round) {
-        self.per_round.retain(|round, _| *round >= min_round);
+        self.per_round.prune_below(min_round);
     }
 }
 
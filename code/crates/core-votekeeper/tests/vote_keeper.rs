@@ -1,6 +1,6 @@
 use malachitebft_core_types::{NilOrVal, Round, SignedVote};
 
-use arc_malachitebft_core_votekeeper::keeper::{Output, VoteKeeper};
+use arc_malachitebft_core_votekeeper::keeper::{Output, Participation, VoteKeeper};
 
 use malachitebft_test::{
     Address, Height, PrivateKey, Signature, TestContext, Validator, ValidatorSet, ValueId, Vote,
@@ -345,3 +345,96 @@ fn equivocation() {
 
     assert_eq!(keeper.evidence().get(&addr2), Some(&vec![(vote21, vote22)]));
 }
+
+#[test]
+fn equivocating_vote_is_not_tallied() {
+    let ([addr1, addr2, addr3, _], mut keeper) = setup([1, 1, 1, 1]);
+
+    let height = Height::new(1);
+    let round = Round::new(0);
+
+    let val = NilOrVal::Val(ValueId::new(1));
+
+    let vote = new_signed_prevote(height, round, val, addr1);
+    let msg = keeper.apply_vote(vote.clone(), round);
+    assert_eq!(msg, None);
+
+    let conflicting = new_signed_prevote(height, round, NilOrVal::Nil, addr1);
+    let msg = keeper.apply_vote(conflicting.clone(), round);
+    assert_eq!(msg, None);
+
+    // Only the first vote is recorded, the conflicting one is kept as evidence
+    assert!(keeper.has_vote(&vote));
+    assert!(!keeper.has_vote(&conflicting));
+    assert_eq!(
+        keeper.evidence().get(&addr1),
+        Some(&vec![(vote.clone(), conflicting.clone())])
+    );
+
+    // Both are known, so applying them again can be skipped altogether
+    assert!(keeper.is_known_vote(&vote));
+    assert!(keeper.is_known_vote(&conflicting));
+
+    // Receiving the conflicting vote again does not tally it either
+    let msg = keeper.apply_vote(conflicting, round);
+    assert_eq!(msg, None);
+
+    let vote = new_signed_prevote(height, round, NilOrVal::Nil, addr2);
+    let msg = keeper.apply_vote(vote, round);
+    assert_eq!(msg, None);
+
+    // Had the conflicting vote been tallied, there would be a quorum of prevotes for nil
+    let vote = new_signed_prevote(height, round, NilOrVal::Nil, addr3);
+    let msg = keeper.apply_vote(vote, round);
+    assert_eq!(msg, Some(Output::PolkaAny));
+
+    // The equivocating validator is counted as having prevoted once
+    assert_eq!(
+        keeper.participation()[&addr1],
+        Participation {
+            prevotes: 1,
+            precommits: 0
+        }
+    );
+}
+
+#[test]
+fn participation_across_rounds() {
+    let ([addr1, addr2, addr3], mut keeper) = setup([1, 1, 1]);
+
+    let height = Height::new(1);
+    let (round0, round1) = (Round::new(0), Round::new(1));
+
+    let vote = new_signed_prevote(height, round0, NilOrVal::Nil, addr1);
+    keeper.apply_vote(vote.clone(), round0);
+    // Duplicate votes are only counted once
+    keeper.apply_vote(vote, round0);
+
+    let vote = new_signed_precommit(height, round0, NilOrVal::Nil, addr1);
+    keeper.apply_vote(vote, round0);
+
+    let vote = new_signed_prevote(height, round1, NilOrVal::Nil, addr2);
+    keeper.apply_vote(vote, round1);
+
+    // Pruning the votes of previous rounds does not affect participation
+    keeper.prune_votes(round1);
+
+    let participation = keeper.participation();
+    assert_eq!(participation.len(), 3);
+
+    assert_eq!(
+        participation[&addr1],
+        Participation {
+            prevotes: 1,
+            precommits: 1
+        }
+    );
+    assert_eq!(
+        participation[&addr2],
+        Participation {
+            prevotes: 1,
+            precommits: 0
+        }
+    );
+    assert!(participation[&addr3].is_absent());
+}
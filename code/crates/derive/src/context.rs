@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, DeriveInput, LitStr, Path, Type};
+
+/// Associated types of the `Context` trait which must be given in the `#[context(...)]` attribute,
+/// along with the name of the attribute setting each of them.
+const REQUIRED_TYPES: [(&str, &str); 9] = [
+    ("address", "Address"),
+    ("height", "Height"),
+    ("proposal_part", "ProposalPart"),
+    ("proposal", "Proposal"),
+    ("validator", "Validator"),
+    ("validator_set", "ValidatorSet"),
+    ("value", "Value"),
+    ("vote", "Vote"),
+    ("signing_scheme", "SigningScheme"),
+];
+
+/// Associated types of the `Context` trait which have a default.
+const OPTIONAL_TYPES: [(&str, &str); 2] = [("timeouts", "Timeouts"), ("extension", "Extension")];
+
+#[derive(Default)]
+struct ContextAttrs {
+    krate: Option<Path>,
+    select_proposer: Option<Path>,
+    types: BTreeMap<String, Type>,
+}
+
+impl ContextAttrs {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+
+        for attr in input.attrs.iter().filter(|a| a.path().is_ident("context")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    let path: LitStr = meta.value()?.parse()?;
+                    attrs.krate = Some(path.parse()?);
+                } else if meta.path.is_ident("select_proposer") {
+                    attrs.select_proposer = Some(meta.value()?.parse()?);
+                } else {
+                    let name = meta
+                        .path
+                        .get_ident()
+                        .map(|ident| ident.to_string())
+                        .filter(|name| is_type_attr(name))
+                        .ok_or_else(|| meta.error("unknown context attribute"))?;
+
+                    let ty: Type = meta.value()?.parse()?;
+
+                    if attrs.types.insert(name, ty).is_some() {
+                        return Err(meta.error("duplicate context attribute"));
+                    }
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(attrs)
+    }
+}
+
+fn is_type_attr(name: &str) -> bool {
+    REQUIRED_TYPES
+        .iter()
+        .chain(&OPTIONAL_TYPES)
+        .any(|(attr, _)| *attr == name)
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let mut attrs = ContextAttrs::parse(&input)?;
+
+    let krate = attrs
+        .krate
+        .take()
+        .unwrap_or_else(|| parse_quote!(::malachitebft_core_types));
+
+    let missing = REQUIRED_TYPES
+        .iter()
+        .filter(|(attr, _)| !attrs.types.contains_key(*attr))
+        .map(|(attr, _)| format!("`{attr}`"))
+        .collect::<Vec<_>>();
+
+    if !missing.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            format!(
+                "missing context attributes: {}, eg. #[context(address = MyAddress, ...)]",
+                missing.join(", ")
+            ),
+        ));
+    }
+
+    attrs
+        .types
+        .entry("timeouts".to_string())
+        .or_insert_with(|| parse_quote!(#krate::LinearTimeouts));
+
+    attrs
+        .types
+        .entry("extension".to_string())
+        .or_insert_with(|| parse_quote!(()));
+
+    let associated_types = REQUIRED_TYPES
+        .iter()
+        .chain(&OPTIONAL_TYPES)
+        .map(|(attr, name)| {
+            let name = format_ident!("{name}");
+            let ty = &attrs.types[*attr];
+            quote! { type #name = #ty; }
+        });
+
+    let proposal = &attrs.types["proposal"];
+    let vote = &attrs.types["vote"];
+
+    let select_proposer = match &attrs.select_proposer {
+        Some(select_proposer) => quote! {
+            #select_proposer(self, validator_set, height, round)
+        },
+        None => quote! {
+            use #krate::{Height as _, ValidatorSet as _};
+
+            assert!(round.is_defined(), "cannot select a proposer for a nil round");
+
+            let count = validator_set.count() as u64;
+            assert!(count > 0, "cannot select a proposer in an empty validator set");
+
+            let index = (height.as_u64().saturating_sub(1) + round.as_i64() as u64) % count;
+
+            validator_set
+                .get_by_index(index as usize)
+                .expect("proposer index is within the validator set")
+        },
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #krate::Context for #name #ty_generics #where_clause {
+            #(#associated_types)*
+
+            fn select_proposer<'a>(
+                &self,
+                validator_set: &'a Self::ValidatorSet,
+                height: Self::Height,
+                round: #krate::Round,
+            ) -> &'a Self::Validator {
+                #select_proposer
+            }
+
+            fn new_proposal(
+                &self,
+                height: Self::Height,
+                round: #krate::Round,
+                value: Self::Value,
+                pol_round: #krate::Round,
+                address: Self::Address,
+            ) -> Self::Proposal {
+                <#proposal>::new(height, round, value, pol_round, address)
+            }
+
+            fn new_prevote(
+                &self,
+                height: Self::Height,
+                round: #krate::Round,
+                value_id: #krate::NilOrVal<#krate::ValueId<Self>>,
+                address: Self::Address,
+            ) -> Self::Vote {
+                <#vote>::new_prevote(height, round, value_id, address)
+            }
+
+            fn new_precommit(
+                &self,
+                height: Self::Height,
+                round: #krate::Round,
+                value_id: #krate::NilOrVal<#krate::ValueId<Self>>,
+                address: Self::Address,
+            ) -> Self::Vote {
+                <#vote>::new_precommit(height, round, value_id, address)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_to_impl(input: DeriveInput) -> syn::ItemImpl {
+        syn::parse2(expand(input).unwrap()).unwrap()
+    }
+
+    fn associated_type(item: &syn::ItemImpl, name: &str) -> String {
+        item.items
+            .iter()
+            .find_map(|item| match item {
+                syn::ImplItem::Type(ty) if ty.ident == name => Some(ty.ty.clone()),
+                _ => None,
+            })
+            .map(|ty| quote!(#ty).to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn derive_with_defaults() {
+        let item = expand_to_impl(parse_quote! {
+            #[derive(Clone, Debug, Context)]
+            #[context(
+                address = Address,
+                height = Height,
+                proposal_part = ProposalPart,
+                proposal = Proposal,
+                validator = Validator,
+                validator_set = ValidatorSet,
+                value = Value,
+                vote = Vote,
+                signing_scheme = Ed25519,
+            )]
+            struct MyContext;
+        });
+
+        assert_eq!(item.items.len(), 15);
+        assert_eq!(associated_type(&item, "Vote"), "Vote");
+        assert_eq!(associated_type(&item, "Extension"), "()");
+        assert_eq!(
+            associated_type(&item, "Timeouts"),
+            ":: malachitebft_core_types :: LinearTimeouts"
+        );
+    }
+
+    #[test]
+    fn derive_with_custom_crate_and_proposer() {
+        let item = expand_to_impl(parse_quote! {
+            #[context(crate = "app::types::core", select_proposer = my_proposer)]
+            #[context(
+                address = Address,
+                height = Height,
+                proposal_part = ProposalPart,
+                proposal = Proposal,
+                validator = Validator,
+                validator_set = ValidatorSet,
+                value = Value,
+                vote = Vote,
+                extension = Bytes,
+                timeouts = MyTimeouts,
+                signing_scheme = Ed25519,
+            )]
+            struct MyContext<T: Send>(T);
+        });
+
+        let tokens = quote!(#item).to_string();
+        assert!(tokens
+            .starts_with("impl < T : Send > app :: types :: core :: Context for MyContext < T >"));
+        assert!(tokens.contains("my_proposer (self , validator_set , height , round)"));
+        assert_eq!(associated_type(&item, "Extension"), "Bytes");
+        assert_eq!(associated_type(&item, "Timeouts"), "MyTimeouts");
+    }
+
+    #[test]
+    fn reject_incomplete_or_unknown_attributes() {
+        let error = expand(parse_quote! {
+            #[context(address = Address, height = Height)]
+            struct MyContext;
+        })
+        .unwrap_err();
+
+        assert!(error.to_string().contains("`proposal_part`"));
+        assert!(!error.to_string().contains("`height`"));
+
+        let error = expand(parse_quote! {
+            #[context(hasher = Sha256)]
+            struct MyContext;
+        })
+        .unwrap_err();
+
+        assert_eq!(error.to_string(), "unknown context attribute");
+
+        let error = expand(parse_quote! {
+            #[context(address = Address, address = Address)]
+            struct MyContext;
+        })
+        .unwrap_err();
+
+        assert_eq!(error.to_string(), "duplicate context attribute");
+    }
+}
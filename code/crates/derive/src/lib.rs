@@ -0,0 +1,64 @@
+//! Derive macros for the Malachite BFT consensus engine.
+//!
+//! These macros are re-exported by `malachitebft-core-types` when its `derive` feature is enabled.
+
+#![forbid(unsafe_code)]
+#![deny(trivial_casts, trivial_numeric_casts)]
+#![warn(missing_docs, rustdoc::broken_intra_doc_links)]
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod context;
+
+/// Derive an implementation of the `Context` trait from the types it is made of.
+///
+/// The associated types of the context are given in the `#[context(...)]` attribute:
+/// `address`, `height`, `proposal_part`, `proposal`, `validator`, `validator_set`, `value`,
+/// `vote` and `signing_scheme` are mandatory, while `timeouts` defaults to `LinearTimeouts`
+/// and `extension` defaults to `()`.
+///
+/// The constructors of the context follow the most common pattern, and expect the proposal
+/// and vote types to provide the following constructors:
+///
+/// - `Proposal::new(height, round, value, pol_round, address)`
+/// - `Vote::new_prevote(height, round, value_id, address)`
+/// - `Vote::new_precommit(height, round, value_id, address)`
+///
+/// The proposer is selected in a round-robin fashion over the validator set, starting with the
+/// first validator at height 1 and round 0, unless the `select_proposer` attribute gives the path
+/// of a function with the same signature as `Context::select_proposer` to use instead.
+///
+/// The `crate` attribute gives the path to the `malachitebft-core-types` crate, for applications
+/// which access it through another crate, eg. `malachitebft_app_channel::app::types::core`.
+///
+/// Validator set updates are not supported by the derived context.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use malachitebft_core_types::Context;
+///
+/// #[derive(Clone, Debug, Context)]
+/// #[context(
+///     address = Address,
+///     height = Height,
+///     proposal_part = ProposalPart,
+///     proposal = Proposal,
+///     validator = Validator,
+///     validator_set = ValidatorSet,
+///     value = Value,
+///     vote = Vote,
+///     extension = Bytes,
+///     signing_scheme = Ed25519,
+/// )]
+/// pub struct MyContext;
+/// ```
+#[proc_macro_derive(Context, attributes(context))]
+pub fn derive_context(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    context::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
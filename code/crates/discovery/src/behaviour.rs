@@ -157,6 +157,8 @@ pub trait DiscoveryClient: NetworkBehaviour {
 
     fn kbuckets(&mut self) -> impl Iterator<Item = KBucketRef<'_, KBucketKey<PeerId>, Addresses>>;
 
+    fn bootstrap(&mut self) -> Result<kad::QueryId, kad::NoKnownPeers>;
+
     fn send_request(&mut self, peer_id: &PeerId, req: Request) -> OutboundRequestId;
 
     fn send_response(
@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use malachitebft_retry::Backoff;
+
 const DEFAULT_NUM_OUTBOUND_PEERS: usize = 50;
 const DEFAULT_NUM_INBOUND_PEERS: usize = 50;
 
@@ -7,12 +9,22 @@ const DEFAULT_MAX_CONNECTIONS_PER_PEER: usize = 5;
 
 const DEFAULT_EPHEMERAL_CONNECTION_TIMEOUT: Duration = Duration::from_secs(15);
 
+const DEFAULT_MAX_CONCURRENT_DIALS: usize = 20;
+const DEFAULT_MAX_CONCURRENT_DIALS_PER_IP: usize = 4;
+
 const DEFAULT_DIAL_MAX_RETRIES: usize = 5;
 const DEFAULT_PEERS_REQUEST_MAX_RETRIES: usize = 5;
 const DEFAULT_CONNECT_REQUEST_MAX_RETRIES: usize = 0;
 
 const DEFAULT_MAX_PEERS_PER_RESPONSE: usize = 100;
 
+const DEFAULT_MIN_PEERS_TO_IDLE: usize = 1;
+const DEFAULT_REBOOTSTRAP_INITIAL_DELAY: Duration = Duration::from_secs(5);
+const DEFAULT_REBOOTSTRAP_MAX_DELAY: Duration = Duration::from_secs(300);
+
+const DEFAULT_MAX_PREFERRED_PEERS: usize = 32;
+const DEFAULT_PREFERRED_PEERS_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
 pub enum BootstrapProtocol {
     #[default]
@@ -45,13 +57,38 @@ pub struct Config {
 
     pub ephemeral_connection_timeout: Duration,
 
+    /// Maximum number of dials in progress at once, the others wait in the dial queue.
+    pub max_concurrent_dials: usize,
+    /// Maximum number of dials in progress at once to the same IP address,
+    /// the others are deferred until one of them completes.
+    pub max_concurrent_dials_per_ip: usize,
+
     pub dial_max_retries: usize,
     pub request_max_retries: usize,
     pub connect_request_max_retries: usize,
 
+    /// Backoff between retries of dials, peers requests and connect requests.
+    /// The maximum number of retries is taken from the settings above.
+    pub retry_backoff: Backoff,
+
     /// Maximum number of peer records to process or send per peers request/response.
     /// Limits the impact of a single response containing many records.
     pub max_peers_per_response: usize,
+
+    /// Minimum number of connected peers for discovery to stop once done.
+    /// With fewer peers, discovery bootstraps again after a delay given by `rebootstrap_backoff`.
+    pub min_peers_to_idle: usize,
+
+    /// Backoff between bootstrap attempts while fewer than `min_peers_to_idle` peers are connected.
+    pub rebootstrap_backoff: Backoff,
+    /// Dial the peers discovered on the local network with mDNS.
+    pub mdns: bool,
+
+    /// Maximum number of peers which served the node well to persist,
+    /// to be preferred when selecting the outbound peers after a restart.
+    pub max_preferred_peers: usize,
+    /// Preferred peers which the node has not been connected to for longer are forgotten.
+    pub preferred_peers_max_age: Duration,
 }
 
 impl Default for Config {
@@ -76,11 +113,26 @@ impl Default for Config {
 
             ephemeral_connection_timeout: DEFAULT_EPHEMERAL_CONNECTION_TIMEOUT,
 
+            max_concurrent_dials: DEFAULT_MAX_CONCURRENT_DIALS,
+            max_concurrent_dials_per_ip: DEFAULT_MAX_CONCURRENT_DIALS_PER_IP,
+
             dial_max_retries: DEFAULT_DIAL_MAX_RETRIES,
             request_max_retries: DEFAULT_PEERS_REQUEST_MAX_RETRIES,
             connect_request_max_retries: DEFAULT_CONNECT_REQUEST_MAX_RETRIES,
 
+            retry_backoff: Backoff::default(),
+
             max_peers_per_response: DEFAULT_MAX_PEERS_PER_RESPONSE,
+
+            min_peers_to_idle: DEFAULT_MIN_PEERS_TO_IDLE,
+            rebootstrap_backoff: Backoff::default()
+                .with_initial_delay(DEFAULT_REBOOTSTRAP_INITIAL_DELAY)
+                .with_max_delay(DEFAULT_REBOOTSTRAP_MAX_DELAY),
+
+            mdns: false,
+
+            max_preferred_peers: DEFAULT_MAX_PREFERRED_PEERS,
+            preferred_peers_max_age: DEFAULT_PREFERRED_PEERS_MAX_AGE,
         }
     }
 }
@@ -126,6 +178,37 @@ impl Config {
     pub fn set_ephemeral_connection_timeout(&mut self, timeout: Duration) {
         self.ephemeral_connection_timeout = timeout;
     }
+
+    pub fn set_dial_concurrency(&mut self, max_concurrent_dials: usize, max_per_ip: usize) {
+        self.max_concurrent_dials = max_concurrent_dials;
+        self.max_concurrent_dials_per_ip = max_per_ip;
+    }
+
+    pub fn set_retry_backoff(&mut self, backoff: Backoff) {
+        self.retry_backoff = backoff;
+    }
+
+    pub fn set_min_peers_to_idle(&mut self, min_peers_to_idle: usize) {
+        self.min_peers_to_idle = min_peers_to_idle;
+    }
+
+    pub fn set_rebootstrap_backoff(&mut self, backoff: Backoff) {
+        self.rebootstrap_backoff = backoff;
+    }
+
+    pub fn set_mdns(&mut self, mdns: bool) {
+        self.mdns = mdns;
+    }
+
+    pub fn set_preferred_peers_bounds(&mut self, max_preferred_peers: usize, max_age: Duration) {
+        self.max_preferred_peers = max_preferred_peers;
+        self.preferred_peers_max_age = max_age;
+    }
+
+    /// The retry backoff, limited to the given number of retries.
+    pub(crate) fn backoff(&self, max_retries: usize) -> Backoff {
+        self.retry_backoff.with_max_retries(Some(max_retries))
+    }
 }
 
 #[cfg(test)]
@@ -152,4 +235,15 @@ mod tests {
         };
         assert_eq!(config.max_peers_per_response, 50);
     }
+
+    #[test]
+    fn default_config_rebootstraps_without_peers() {
+        let config = Config::default();
+        assert_eq!(config.min_peers_to_idle, 1);
+        assert_eq!(
+            config.rebootstrap_backoff.initial_delay,
+            Duration::from_secs(5)
+        );
+        assert_eq!(config.rebootstrap_backoff.max_retries, None);
+    }
 }
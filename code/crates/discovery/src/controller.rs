@@ -10,7 +10,6 @@ use tracing::error;
 
 use crate::{request::RequestData, DialData};
 
-const DEFAULT_DIAL_CONCURRENT_FACTOR: usize = 20;
 const DEFAULT_PEERS_REQUEST_CONCURRENT_FACTOR: usize = 20;
 const DEFAULT_CONNECT_REQUEST_CONCURRENT_FACTOR: usize = 100;
 const DEFAULT_CLOSE_CONCURRENT_FACTOR: usize = usize::MAX;
@@ -126,9 +125,9 @@ pub struct Controller {
 }
 
 impl Controller {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(max_concurrent_dials: usize) -> Self {
         Controller {
-            dial: Action::new(DEFAULT_DIAL_CONCURRENT_FACTOR),
+            dial: Action::new(max_concurrent_dials),
             peers_request: Action::new(DEFAULT_PEERS_REQUEST_CONCURRENT_FACTOR),
             connect_request: Action::new(DEFAULT_CONNECT_REQUEST_CONCURRENT_FACTOR),
             close: Action::new(DEFAULT_CLOSE_CONCURRENT_FACTOR),
@@ -256,7 +255,7 @@ mod tests {
     fn test_address_poisoning_prevented() {
         use crate::dial::DialData;
 
-        let mut controller = Controller::new();
+        let mut controller = Controller::new(20);
 
         // Attacker claims victim's address
         let attacker_peer_id = PeerId::random();
@@ -297,7 +296,7 @@ mod tests {
     fn test_bootstrap_addresses_registered() {
         use crate::dial::DialData;
 
-        let mut controller = Controller::new();
+        let mut controller = Controller::new(20);
 
         let bootstrap_peer_id = PeerId::random();
         let bootstrap_addr = Multiaddr::from_str("/ip4/10.0.0.1/tcp/8000").unwrap();
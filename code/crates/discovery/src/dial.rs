@@ -1,6 +1,12 @@
-use libp2p::{swarm::dial_opts::DialOpts, Multiaddr, PeerId};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
 
-use crate::util::Retry;
+use libp2p::swarm::dial_opts::{DialOpts, PeerCondition};
+use libp2p::{Multiaddr, PeerId};
+
+use malachitebft_retry::Retry;
+
+use crate::util::{ip_of_multiaddr, peer_id_from_multiaddr};
 
 #[derive(Debug, Clone)]
 pub struct DialData {
@@ -10,6 +16,8 @@ pub struct DialData {
     /// Whether this dial originated from bootstrap/persistent peer configuration.
     /// Used to determine retry behavior, only bootstrap dials get unlimited retries.
     is_bootstrap: bool,
+    /// Time at which the dial in progress started
+    dialed_at: Option<Instant>,
 }
 
 impl DialData {
@@ -19,6 +27,7 @@ impl DialData {
             listen_addrs,
             retry: Retry::new(),
             is_bootstrap: false,
+            dialed_at: None,
         }
     }
 
@@ -29,6 +38,7 @@ impl DialData {
             listen_addrs,
             retry: Retry::new(),
             is_bootstrap: true,
+            dialed_at: None,
         }
     }
 
@@ -49,6 +59,31 @@ impl DialData {
         self.listen_addrs.clone()
     }
 
+    /// IP addresses of the listen addresses of the peer.
+    pub(crate) fn ips(&self) -> impl Iterator<Item = IpAddr> + '_ {
+        self.listen_addrs.iter().filter_map(ip_of_multiaddr)
+    }
+
+    /// Record that the peer is being dialed.
+    pub(crate) fn mark_dialed(&mut self) {
+        self.dialed_at = Some(Instant::now());
+    }
+
+    /// Time elapsed since the peer was dialed, once per dial.
+    pub(crate) fn take_dial_latency(&mut self) -> Option<Duration> {
+        self.dialed_at.take().map(|dialed_at| dialed_at.elapsed())
+    }
+
+    /// Peer ID found in the /p2p/<peer_id> component of every listen address, if they all agree.
+    pub(crate) fn peer_id_from_listen_addrs(&self) -> Option<PeerId> {
+        let (first, rest) = self.listen_addrs.split_first()?;
+        let peer_id = peer_id_from_multiaddr(first)?;
+
+        rest.iter()
+            .all(|addr| peer_id_from_multiaddr(addr) == Some(peer_id))
+            .then_some(peer_id)
+    }
+
     pub fn build_dial_opts(&self) -> Option<DialOpts> {
         if let Some(addr) = self.listen_addrs.first() {
             if let Some(peer_id) = self.peer_id {
@@ -58,6 +93,17 @@ impl DialData {
                         .allocate_new_port()
                         .build(),
                 )
+            } else if let Some(peer_id) = self.peer_id_from_listen_addrs() {
+                // The peer is not identified yet, but all its addresses carry the same peer ID,
+                // so let the swarm try each of them in turn rather than only the first one.
+                // Always dial, as a dial without a peer ID would.
+                Some(
+                    DialOpts::peer_id(peer_id)
+                        .addresses(self.listen_addrs.clone())
+                        .condition(PeerCondition::Always)
+                        .allocate_new_port()
+                        .build(),
+                )
             } else {
                 Some(
                     DialOpts::unknown_peer_id()
@@ -71,3 +117,72 @@ impl DialData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> Multiaddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn dial_opts_use_all_addresses_of_peer() {
+        let peer_id = PeerId::random();
+
+        let dial_data = DialData::new_bootstrap(
+            None,
+            vec![
+                addr(&format!("/ip4/10.0.0.1/tcp/27000/p2p/{peer_id}")),
+                addr(&format!("/ip6/fe80::1/tcp/27000/p2p/{peer_id}")),
+                addr(&format!("/dns4/node.example.com/tcp/27000/p2p/{peer_id}")),
+            ],
+        );
+
+        let opts = dial_data.build_dial_opts().unwrap();
+        assert_eq!(opts.get_peer_id(), Some(peer_id));
+    }
+
+    #[test]
+    fn dial_opts_without_common_peer_id() {
+        let dial_data = DialData::new_bootstrap(
+            None,
+            vec![
+                addr("/ip4/10.0.0.1/tcp/27000"),
+                addr(&format!("/ip4/10.0.0.2/tcp/27000/p2p/{}", PeerId::random())),
+            ],
+        );
+
+        let opts = dial_data.build_dial_opts().unwrap();
+        assert_eq!(opts.get_peer_id(), None);
+
+        assert!(DialData::new(None, vec![]).build_dial_opts().is_none());
+    }
+
+    #[test]
+    fn dial_data_ips_and_latency() {
+        let mut dial_data = DialData::new(
+            None,
+            vec![
+                addr("/ip4/10.0.0.1/tcp/27000"),
+                addr("/dns4/node.example.com/tcp/27000"),
+                addr("/ip6/fe80::1/udp/27000/quic-v1"),
+            ],
+        );
+
+        let ips: Vec<IpAddr> = dial_data.ips().collect();
+        assert_eq!(
+            ips,
+            [
+                "10.0.0.1".parse::<IpAddr>().unwrap(),
+                "fe80::1".parse().unwrap()
+            ]
+        );
+
+        assert_eq!(dial_data.take_dial_latency(), None);
+
+        dial_data.mark_dialed();
+        assert!(dial_data.take_dial_latency().is_some());
+        assert_eq!(dial_data.take_dial_latency(), None);
+    }
+}
@@ -1,6 +1,12 @@
+use std::time::Instant;
+
 use libp2p::swarm;
-use tracing::info;
+use tracing::{debug, info, warn};
+
+use malachitebft_retry::Retry;
 
+use crate::config::BootstrapProtocol;
+use crate::dial::DialData;
 use crate::{Discovery, DiscoveryClient, State};
 
 impl<C> Discovery<C>
@@ -39,14 +45,112 @@ where
 
                 self.adjust_peers(swarm);
 
-                self.state = State::Idle;
+                self.go_idle();
             }
         }
     }
 
     pub(crate) fn handle_failed_bootstrap(&mut self) {
         if self.state == State::Bootstrapping {
-            self.state = State::Idle;
+            self.go_idle();
+        }
+    }
+
+    /// Stop discovery, unless fewer than `min_peers_to_idle` peers are connected,
+    /// in which case another bootstrap attempt is scheduled.
+    pub(crate) fn go_idle(&mut self) {
+        self.state = State::Idle;
+
+        if self.active_connections.len() >= self.config.min_peers_to_idle {
+            self.rebootstrap = Retry::new();
+            self.next_rebootstrap = None;
+        } else {
+            info!(
+                "Discovery stopped with {} peers (expected at least {})",
+                self.active_connections.len(),
+                self.config.min_peers_to_idle
+            );
+
+            self.schedule_rebootstrap();
+        }
+    }
+
+    pub(crate) fn schedule_rebootstrap(&mut self) {
+        match self
+            .rebootstrap
+            .next_delay(&self.config.rebootstrap_backoff)
+        {
+            Some(delay) => {
+                debug!(
+                    "Next discovery bootstrap attempt in {}ms",
+                    delay.as_millis()
+                );
+
+                self.next_rebootstrap = Some(Instant::now() + delay);
+            }
+            None => {
+                warn!(
+                    "Giving up on discovery bootstrap after {} attempts",
+                    self.rebootstrap.count()
+                );
+
+                self.next_rebootstrap = None;
+            }
+        }
+    }
+
+    /// Bootstrap discovery again, dialing the DNS seeds, if the previous attempt did not find
+    /// enough peers, or if no peer could be reached yet, and the backoff delay has elapsed.
+    ///
+    /// Meant to be called periodically.
+    pub fn rebootstrap_if_due(&mut self, swarm: &mut swarm::Swarm<C>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let stalled = self.state == State::Bootstrapping && self.active_connections.is_empty();
+
+        if self.state != State::Idle && !stalled {
+            return;
+        }
+
+        if self
+            .next_rebootstrap
+            .is_none_or(|next_rebootstrap| Instant::now() < next_rebootstrap)
+        {
+            return;
+        }
+
+        self.next_rebootstrap = None;
+
+        info!(
+            "Bootstrapping discovery again (attempt #{}), {} peers connected",
+            self.rebootstrap.count(),
+            self.active_connections.len()
+        );
+
+        // The bootstrap nodes are already dialed periodically,
+        // the DNS seeds are only dialed as a fallback.
+        for seed in self.dns_seeds.clone() {
+            self.add_to_dial_queue(swarm, DialData::new(None, vec![seed]));
+        }
+
+        match self.config.bootstrap_protocol {
+            BootstrapProtocol::Kademlia => {
+                self.state = State::Bootstrapping;
+
+                // Without any peer in the routing table, the bootstrap query starts
+                // once a peer is added to it, while the next attempt is already scheduled.
+                if let Err(e) = swarm.behaviour_mut().bootstrap() {
+                    debug!("Cannot start discovery bootstrap yet: {e}");
+
+                    self.schedule_rebootstrap();
+                }
+            }
+
+            BootstrapProtocol::Full => {
+                self.initiate_extension_with_target(swarm, self.config.num_outbound_peers);
+            }
         }
     }
 }
@@ -1,4 +1,5 @@
 use libp2p::{swarm::ConnectionId, PeerId, Swarm};
+use malachitebft_peer::{PeerExit, PeerState};
 use tracing::{debug, warn};
 
 use crate::{Discovery, DiscoveryClient, State};
@@ -73,6 +74,7 @@ where
                 warn!("Last connection to peer {peer_id} closed, removing from outbound peers");
 
                 self.outbound_peers.remove(&peer_id);
+                self.preferred_peers.session_ended(&peer_id);
             }
 
             if self.is_enabled() {
@@ -100,14 +102,7 @@ where
     fn cleanup_peer_on_disconnect(&mut self, peer_id: PeerId) {
         let peer_info = self.discovered_peers.remove(&peer_id);
 
-        // Remove signed peer record (no longer connected, record may be stale)
-        self.signed_peer_records.remove(&peer_id);
-
-        // Clear rate limiter state for this peer
-        self.rate_limiter.remove_peer(&peer_id);
-
-        // Clear connect_request done_on to allow re-upgrading the peer on reconnection
-        self.controller.connect_request.remove_done_on(&peer_id);
+        self.forget_peer(&peer_id, PeerExit::Disconnected);
 
         // Find and reset the bootstrap node peer_id to allow re-identification
         // This handles the case where a bootstrap node restarts with a different peer_id
@@ -131,3 +126,34 @@ where
         }
     }
 }
+
+impl<C> PeerState<PeerId> for Discovery<C>
+where
+    C: DiscoveryClient,
+{
+    fn forget_peer(&mut self, peer_id: &PeerId, _exit: PeerExit) {
+        self.discovered_peers.remove(peer_id);
+
+        // Remove signed peer record (no longer connected, record may be stale)
+        self.signed_peer_records.remove(peer_id);
+
+        // Clear rate limiter state for this peer
+        self.rate_limiter.remove_peer(peer_id);
+
+        // Clear connect_request done_on to allow re-upgrading the peer on reconnection
+        self.controller.connect_request.remove_done_on(peer_id);
+
+        // The session may still be ongoing if the peer was removed from the outbound peers
+        // without its last connection being closed first
+        self.preferred_peers.session_ended(peer_id);
+    }
+
+    fn peer_entries(&self) -> usize {
+        self.discovered_peers.len()
+            + self.signed_peer_records.len()
+            + self.active_connections.len()
+            + self.outbound_peers.len()
+            + self.inbound_peers.len()
+            + self.rate_limiter.peer_entries()
+    }
+}
@@ -112,6 +112,7 @@ where
 
             if let Some(state) = self.outbound_peers.get_mut(&peer) {
                 *state = OutboundState::Confirmed;
+                self.start_preferred_session(peer);
             }
 
             // if all outbound peers are persistent, discovery is done
@@ -152,13 +153,13 @@ where
             .connect_request
             .remove_in_progress(&request_id)
         {
-            if request_data.retry.count() < self.config.connect_request_max_retries {
-                // Retry request after a delay
-                request_data.retry.inc_count();
+            let backoff = self.config.backoff(self.config.connect_request_max_retries);
 
+            if let Some(next_delay) = request_data.retry.next_delay(&backoff) {
+                // Retry request after a delay
                 self.controller
                     .connect_request
-                    .add_to_queue(request_data.clone(), Some(request_data.retry.next_delay()));
+                    .add_to_queue(request_data.clone(), Some(next_delay));
             } else {
                 // No more trials left
                 error!(
@@ -1,3 +1,6 @@
+use std::net::IpAddr;
+use std::time::Duration;
+
 use libp2p::{
     core::ConnectedPoint,
     swarm::{ConnectionId, DialError},
@@ -10,6 +13,10 @@ use crate::{
     DiscoveryClient,
 };
 
+/// Delay before dialing again a peer whose dial was deferred
+/// because too many dials to the same IP were in progress.
+const DEFERRED_DIAL_DELAY: Duration = Duration::from_millis(500);
+
 impl<C> Discovery<C>
 where
     C: DiscoveryClient,
@@ -18,6 +25,20 @@ where
         self.controller.dial.can_perform()
     }
 
+    /// An IP address of the peer to which `max_concurrent_dials_per_ip` dials are already in progress.
+    fn saturated_dial_ip(&self, dial_data: &DialData) -> Option<IpAddr> {
+        dial_data.ips().find(|ip| {
+            let in_progress = self
+                .controller
+                .dial
+                .get_in_progress_iter()
+                .filter(|(_, other)| other.ips().any(|other_ip| other_ip == *ip))
+                .count();
+
+            in_progress >= self.config.max_concurrent_dials_per_ip
+        })
+    }
+
     fn should_dial(
         &self,
         swarm: &Swarm<C>,
@@ -32,17 +53,37 @@ where
         })
             // Has not already dialed, or has dialed but retries are allowed
             && (!check_already_dialed || !self.controller.dial_is_done_on(dial_data) || dial_data.retry.count() != 0)
-            // Is not itself (listen addresses)
-            && !swarm.listeners().any(|addr| dial_data.listen_addrs().contains(addr))
+            // Is not itself (listen or advertised addresses)
+            && !swarm
+                .listeners()
+                .chain(swarm.external_addresses())
+                .any(|addr| dial_data.listen_addrs().contains(addr))
     }
 
-    pub fn dial_peer(&mut self, swarm: &mut Swarm<C>, dial_data: DialData) {
+    pub fn dial_peer(&mut self, swarm: &mut Swarm<C>, mut dial_data: DialData) {
+        self.metrics
+            .set_dial_queue_depth(self.controller.dial.queue_len());
+
         // Not checking if the peer was already dialed because it is done when
         // adding to the dial queue
         if !self.should_dial(swarm, &dial_data, false) {
             return;
         }
 
+        if let Some(ip) = self.saturated_dial_ip(&dial_data) {
+            debug!(
+                %ip,
+                "Deferring dial of peer {:?}, too many dials in progress to the same IP",
+                dial_data.peer_id()
+            );
+
+            self.metrics.increment_total_deferred_dials();
+            self.controller
+                .dial
+                .add_to_queue(dial_data, Some(DEFERRED_DIAL_DELAY));
+            return;
+        }
+
         let Some(dial_opts) = dial_data.build_dial_opts() else {
             warn!(
                 "No addresses to dial for peer {:?}, skipping dial attempt",
@@ -55,6 +96,7 @@ where
         // Register peer_id only, not addresses as they are untrusted
         self.controller.dial_register_done_on(&dial_data, false);
 
+        dial_data.mark_dialed();
         self.controller
             .dial
             .register_in_progress(connection_id, dial_data.clone());
@@ -114,6 +156,15 @@ where
                 self.controller
                     .dial
                     .register_done_on(PeerData::PeerId(peer_id));
+
+                if let Some(latency) = self
+                    .controller
+                    .dial
+                    .get_in_progress_mut(&connection_id)
+                    .and_then(|dial_data| dial_data.take_dial_latency())
+                {
+                    self.metrics.observe_dial_latency(latency);
+                }
             }
             l @ ConnectedPoint::Listener { .. } => {
                 let remote_addr = l.get_remote_address().clone();
@@ -158,6 +209,28 @@ where
         error: DialError,
     ) {
         if let Some(mut dial_data) = self.controller.dial.remove_in_progress(&connection_id) {
+            if let Some(latency) = dial_data.take_dial_latency() {
+                self.metrics.observe_dial_latency(latency);
+            }
+
+            // The address presented another identity than the one pinned for the peer,
+            // eg. in the `/p2p/<peer_id>` suffix of a persistent peer address.
+            // The connection was refused, and the dial is not retried.
+            if let DialError::WrongPeerId { obtained, address } = &error {
+                let expected = dial_data
+                    .peer_id()
+                    .or_else(|| dial_data.peer_id_from_listen_addrs());
+
+                error!(
+                    ?expected,
+                    %obtained,
+                    %address,
+                    "Peer presented an unexpected identity, refusing the connection"
+                );
+
+                self.metrics.increment_total_identity_mismatches();
+            }
+
             // Skip retrying for errors that will occur again
             if matches!(
                 error,
@@ -169,12 +242,10 @@ where
                 return;
             }
 
-            if dial_data.retry.count() < self.config.dial_max_retries {
-                // Retry dialing after a delay
-                dial_data.retry.inc_count();
-
-                let next_delay = dial_data.retry.next_delay();
+            let backoff = self.config.backoff(self.config.dial_max_retries);
 
+            if let Some(next_delay) = dial_data.retry.next_delay(&backoff) {
+                // Retry dialing after a delay
                 self.controller
                     .dial
                     .add_to_queue(dial_data.clone(), Some(next_delay));
@@ -218,6 +289,8 @@ where
             self.controller.dial_register_done_on(&dial_data, false);
 
             self.controller.dial.add_to_queue(dial_data, None);
+            self.metrics
+                .set_dial_queue_depth(self.controller.dial.queue_len());
         }
     }
 
@@ -89,7 +89,7 @@ where
 
             self.adjust_peers(swarm);
 
-            self.state = State::Idle;
+            self.go_idle();
         } else {
             debug!("Discovery extension in progress ({}ms), {} pending connections ({} in queue), {} pending requests ({} in queue)",
                 self.metrics.elapsed().as_millis(),
@@ -2,8 +2,10 @@ use libp2p::{identify, swarm::ConnectionId, PeerId, Swarm};
 use tracing::{debug, info, warn};
 
 use crate::{
-    config::BootstrapProtocol, request::RequestData, util::strip_peer_id_from_multiaddr, Discovery,
-    DiscoveryClient, OutboundState, State,
+    config::BootstrapProtocol,
+    request::RequestData,
+    util::{sort_addrs_by_reachability, strip_peer_id_from_multiaddr},
+    Discovery, DiscoveryClient, OutboundState, State,
 };
 
 impl<C> Discovery<C>
@@ -262,10 +264,13 @@ where
                     self.make_extension_step(swarm);
                 }
             }
-            // Add the address to the Kademlia routing table
+            // Add the addresses to the Kademlia routing table
             if self.config.bootstrap_protocol == BootstrapProtocol::Kademlia {
-                if let Some(addr) = info.listen_addrs.first() {
-                    swarm.behaviour_mut().add_address(&peer_id, addr.clone());
+                let mut listen_addrs = info.listen_addrs.clone();
+                sort_addrs_by_reachability(&mut listen_addrs);
+
+                for addr in listen_addrs {
+                    swarm.behaviour_mut().add_address(&peer_id, addr);
                 }
             }
         } else {
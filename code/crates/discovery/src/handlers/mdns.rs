@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use libp2p::{Multiaddr, PeerId, Swarm};
+use tracing::debug;
+
+use crate::{dial::DialData, util::sort_addrs_by_reachability, Discovery, DiscoveryClient};
+
+impl<C> Discovery<C>
+where
+    C: DiscoveryClient,
+{
+    /// Dial the peers discovered on the local network with mDNS.
+    ///
+    /// The peers are added to the dial queue like the peers learned from a peers response,
+    /// so that they are subject to the same limits, and are then handled as any other peer
+    /// once connected. In `persistent_peers_only` mode, only the persistent peers are dialed.
+    pub fn handle_mdns_discovered(
+        &mut self,
+        swarm: &Swarm<C>,
+        discovered: impl IntoIterator<Item = (PeerId, Multiaddr)>,
+    ) {
+        // mDNS reports each address of a peer separately
+        let mut peers = BTreeMap::<PeerId, Vec<Multiaddr>>::new();
+        for (peer_id, addr) in discovered {
+            peers.entry(peer_id).or_default().push(addr);
+        }
+
+        for (peer_id, mut addrs) in peers {
+            if self.config.persistent_peers_only && !self.is_persistent_peer(&peer_id) {
+                debug!(peer = %peer_id, "Ignoring non-persistent peer discovered with mDNS");
+                continue;
+            }
+
+            debug!(
+                peer = %peer_id, addr_count = addrs.len(),
+                "Discovered peer on the local network with mDNS"
+            );
+
+            sort_addrs_by_reachability(&mut addrs);
+            self.add_to_dial_queue(swarm, DialData::new(Some(peer_id), addrs));
+        }
+    }
+}
@@ -7,5 +7,7 @@ pub mod dial;
 pub mod extension;
 pub mod helpers;
 pub mod identify;
+pub mod mdns;
 pub mod peers_management;
 pub mod peers_request;
+pub mod preferred;
@@ -15,12 +15,7 @@ where
             .num_outbound_peers
             .saturating_sub(self.outbound_peers.len());
 
-        let peers = match self.selector.try_select_n_outbound_candidates(
-            swarm,
-            &self.discovered_peers,
-            self.get_excluded_peers(),
-            n,
-        ) {
+        let peers = match self.select_outbound_candidates(swarm, n) {
             Selection::Exactly(peers) => {
                 debug!("Selected exactly {} outbound candidates", peers.len());
                 peers
@@ -106,18 +101,15 @@ where
             // Consider the connect request as done
             self.controller.connect_request.register_done_on(peer_id);
 
+            self.start_preferred_session(peer_id);
+
             self.update_discovery_metrics();
 
             return;
         }
 
         // If no inbound peers is available, then select a candidate
-        match self.selector.try_select_n_outbound_candidates(
-            swarm,
-            &self.discovered_peers,
-            self.get_excluded_peers(),
-            1,
-        ) {
+        match self.select_outbound_candidates(swarm, 1) {
             Selection::Exactly(peers) => {
                 if let Some(peer_id) = peers.first() {
                     debug!("Trying to connect to peer {peer_id} to repair outbound peers");
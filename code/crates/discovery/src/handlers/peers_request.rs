@@ -9,6 +9,7 @@ use crate::{
     behaviour::{self, Response, SignedPeerRecordBytes},
     dial::DialData,
     request::RequestData,
+    util::sort_addrs_by_reachability,
     Discovery, DiscoveryClient,
 };
 
@@ -53,10 +54,9 @@ where
             self.rate_limiter.rate_window()
         );
 
-        // TODO: When reputation system is implemented:
-        // - Report ReputationPenalty::ExcessivePeersRequests (-4096) to reputation system
-        // - Reputation system will handle backoff tiers and eventual banning
-        // - Maybe keep the immediate disconnect for max violations as fast path
+        // Report the violation to the reputation system, which may eventually ban the peer.
+        // The immediate disconnect for max violations below is kept as a fast path.
+        self.rate_limit_violations.push(*peer);
 
         if should_disconnect {
             warn!(
@@ -190,13 +190,13 @@ where
             .peers_request
             .remove_in_progress(&request_id)
         {
-            if request_data.retry.count() < self.config.request_max_retries {
-                // Retry request after a delay
-                request_data.retry.inc_count();
+            let backoff = self.config.backoff(self.config.request_max_retries);
 
+            if let Some(next_delay) = request_data.retry.next_delay(&backoff) {
+                // Retry request after a delay
                 self.controller
                     .peers_request
-                    .add_to_queue(request_data.clone(), Some(request_data.retry.next_delay()));
+                    .add_to_queue(request_data.clone(), Some(next_delay));
             } else {
                 // No more trials left
                 error!(
@@ -243,7 +243,8 @@ where
             match PeerRecord::from_signed_envelope(envelope) {
                 Ok(peer_record) => {
                     let peer_id = peer_record.peer_id();
-                    let addresses = peer_record.addresses().to_vec();
+                    let mut addresses = peer_record.addresses().to_vec();
+                    sort_addrs_by_reachability(&mut addresses);
 
                     if addresses.is_empty() {
                         continue;
@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use libp2p::{PeerId, Swarm};
+use tracing::{debug, warn};
+
+use crate::{dial::DialData, util::sort_addrs_by_reachability, Discovery, DiscoveryClient};
+
+use super::selection::selector::Selection;
+
+impl<C> Discovery<C>
+where
+    C: DiscoveryClient,
+{
+    /// Dial the best peers which served the node well in previous sessions,
+    /// so that they are identified and can be selected as outbound peers.
+    pub fn dial_preferred_peers(&mut self, swarm: &Swarm<C>) {
+        if !self.is_enabled() || self.config.persistent_peers_only {
+            return;
+        }
+
+        let peers: Vec<_> = self
+            .preferred_peers
+            .ranked()
+            .into_iter()
+            .take(self.config.num_outbound_peers)
+            .map(|(peer_id, peer)| (*peer_id, peer.addrs.clone()))
+            .collect();
+
+        if !peers.is_empty() {
+            debug!(count = peers.len(), "Dialing preferred peers");
+        }
+
+        for (peer_id, mut addrs) in peers {
+            sort_addrs_by_reachability(&mut addrs);
+            self.add_to_dial_queue(swarm, DialData::new(Some(peer_id), addrs));
+        }
+    }
+
+    /// Select `n` outbound candidates, preferring the discovered peers which served the node
+    /// well in previous sessions, and falling back to the selector for the others.
+    pub(crate) fn select_outbound_candidates(
+        &mut self,
+        swarm: &mut Swarm<C>,
+        n: usize,
+    ) -> Selection<PeerId> {
+        let mut excluded = self.get_excluded_peers();
+
+        let preferred: Vec<PeerId> = self
+            .preferred_peers
+            .ranked()
+            .into_iter()
+            .map(|(peer_id, _)| *peer_id)
+            .filter(|peer_id| {
+                self.discovered_peers.contains_key(peer_id) && !excluded.contains(peer_id)
+            })
+            .take(n)
+            .collect();
+
+        if !preferred.is_empty() {
+            debug!(
+                count = preferred.len(),
+                "Selected preferred outbound candidates"
+            );
+        }
+
+        if !preferred.is_empty() && preferred.len() == n {
+            return Selection::Exactly(preferred);
+        }
+
+        let remaining = n - preferred.len();
+        excluded.extend(preferred.iter().copied());
+
+        match self.selector.try_select_n_outbound_candidates(
+            swarm,
+            &self.discovered_peers,
+            excluded,
+            remaining,
+        ) {
+            Selection::Exactly(peers) => Selection::Exactly([preferred, peers].concat()),
+            Selection::Only(peers) => Selection::Only([preferred, peers].concat()),
+            Selection::None if preferred.is_empty() => Selection::None,
+            Selection::None => Selection::Only(preferred),
+        }
+    }
+
+    /// Start tracking the session with a peer which became an outbound peer.
+    pub(crate) fn start_preferred_session(&mut self, peer_id: PeerId) {
+        let addrs = self
+            .discovered_peers
+            .get(&peer_id)
+            .map(|info| info.listen_addrs.clone())
+            .unwrap_or_default();
+
+        self.preferred_peers.session_started(peer_id, addrs);
+    }
+
+    /// Record a round-trip time to a peer, used to score the outbound peers.
+    pub fn record_latency(&mut self, peer_id: &PeerId, rtt: Duration) {
+        self.preferred_peers.record_latency(peer_id, rtt);
+    }
+
+    /// Persist the peers which served the node well, if a file was given
+    /// with [`with_preferred_peers_file`](Self::with_preferred_peers_file).
+    pub fn save_preferred_peers(&mut self) {
+        if let Err(e) = self.preferred_peers.save() {
+            warn!("Failed to save preferred peers: {e}");
+        }
+    }
+}
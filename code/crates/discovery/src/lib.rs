@@ -1,8 +1,11 @@
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Instant;
 
 use tracing::{debug, error, info, warn};
 
 use malachitebft_metrics::Registry;
+use malachitebft_retry::Retry;
 
 use libp2p::core::SignedEnvelope;
 use libp2p::{identify, kad, request_response, swarm::ConnectionId, Multiaddr, PeerId, Swarm};
@@ -25,11 +28,17 @@ use handlers::selection::selector::Selector;
 mod metrics;
 use metrics::Metrics;
 
+mod preferred;
+pub use preferred::{PreferredPeer, PreferredPeers};
+
 mod rate_limiter;
 use rate_limiter::DiscoveryRateLimiter;
 
 mod request;
 
+mod stats;
+pub use stats::{BootstrapStatus, ConnectionStats, DiscoveryStats, KBucketStats};
+
 pub mod util;
 
 #[derive(Debug, PartialEq)]
@@ -80,6 +89,12 @@ where
     selector: Box<dyn Selector<C>>,
 
     bootstrap_nodes: Vec<(Option<PeerId>, Vec<Multiaddr>)>,
+    /// DNS seeds dialed when bootstrapping again, resolved at dial time
+    dns_seeds: Vec<Multiaddr>,
+    /// Bootstrap attempts since discovery last found enough peers
+    rebootstrap: Retry,
+    /// Time of the next bootstrap attempt, if discovery has not found enough peers
+    next_rebootstrap: Option<Instant>,
     discovered_peers: HashMap<PeerId, identify::Info>,
     /// Signed peer records received from peers (cryptographically verified)
     signed_peer_records: HashMap<PeerId, SignedEnvelope>,
@@ -88,9 +103,13 @@ where
     pub connections: HashMap<ConnectionId, ConnectionInfo>,
     outbound_peers: HashMap<PeerId, OutboundState>,
     inbound_peers: HashSet<PeerId>,
+    /// Peers which served the node well, preferred when selecting the outbound peers
+    preferred_peers: PreferredPeers,
 
     /// Rate limiter for peers requests
     rate_limiter: DiscoveryRateLimiter,
+    /// Peers which violated the rate limit since the last call to [`Discovery::take_rate_limit_violations`]
+    rate_limit_violations: Vec<PeerId>,
 
     pub controller: Controller,
     metrics: Metrics,
@@ -100,7 +119,12 @@ impl<C> Discovery<C>
 where
     C: DiscoveryClient,
 {
-    pub fn new(config: Config, bootstrap_nodes: Vec<Multiaddr>, registry: &mut Registry) -> Self {
+    pub fn new(
+        config: Config,
+        bootstrap_nodes: Vec<Multiaddr>,
+        dns_seeds: Vec<Multiaddr>,
+        registry: &mut Registry,
+    ) -> Self {
         info!(
             "Discovery is {}",
             if config.enabled {
@@ -119,8 +143,10 @@ where
             );
         }
 
-        let state = if config.enabled && bootstrap_nodes.is_empty() {
-            warn!("No bootstrap nodes provided");
+        let no_bootstrap_nodes = bootstrap_nodes.is_empty() && dns_seeds.is_empty();
+
+        let state = if config.enabled && no_bootstrap_nodes {
+            warn!("No bootstrap nodes nor DNS seeds provided");
             info!("Discovery found 0 peers in 0ms");
             State::Idle
         } else if config.enabled {
@@ -141,7 +167,7 @@ where
             State::Idle
         };
 
-        Self {
+        let mut discovery = Self {
             config,
             state,
 
@@ -151,29 +177,57 @@ where
                 config.selector,
             ),
 
-            bootstrap_nodes: bootstrap_nodes
-                .clone()
-                .into_iter()
-                .map(|addr| (None, vec![addr]))
-                .collect(),
+            bootstrap_nodes: group_bootstrap_addrs(bootstrap_nodes),
+            dns_seeds,
+            rebootstrap: Retry::new(),
+            next_rebootstrap: None,
             discovered_peers: HashMap::new(),
             signed_peer_records: HashMap::new(),
             active_connections: HashMap::new(),
             connections: HashMap::new(),
             outbound_peers: HashMap::new(),
             inbound_peers: HashSet::new(),
+            preferred_peers: PreferredPeers::disabled(),
 
             rate_limiter: DiscoveryRateLimiter::default(),
+            rate_limit_violations: Vec::new(),
+
+            controller: Controller::new(config.max_concurrent_dials),
+            metrics: Metrics::new(registry, !config.enabled || no_bootstrap_nodes),
+        };
 
-            controller: Controller::new(),
-            metrics: Metrics::new(registry, !config.enabled || bootstrap_nodes.is_empty()),
+        // Bootstrap again if the bootstrap nodes cannot be reached
+        if config.enabled && !no_bootstrap_nodes {
+            discovery.schedule_rebootstrap();
         }
+
+        discovery
+    }
+
+    /// Persist the peers which served the node well to the given file, and prefer
+    /// the peers already persisted there when selecting the outbound peers.
+    pub fn with_preferred_peers_file(mut self, path: Option<PathBuf>) -> Self {
+        if let Some(path) = path {
+            self.preferred_peers = PreferredPeers::load(
+                path,
+                self.config.max_preferred_peers,
+                self.config.preferred_peers_max_age,
+            );
+        }
+
+        self
     }
 
     pub fn is_enabled(&self) -> bool {
         self.config.enabled
     }
 
+    /// Take the peers which violated the rate limit since the last call,
+    /// one entry per violation, so that they can be reported to the reputation system.
+    pub fn take_rate_limit_violations(&mut self) -> Vec<PeerId> {
+        std::mem::take(&mut self.rate_limit_violations)
+    }
+
     /// Check if a peer connection is outbound
     pub fn is_outbound_peer(&self, peer_id: &PeerId) -> bool {
         self.outbound_peers.contains_key(peer_id)
@@ -357,6 +411,9 @@ where
     }
 
     /// Add a bootstrap node for persistent peer management
+    ///
+    /// If the address carries the peer ID of a known bootstrap node,
+    /// it is added to the addresses of that node instead.
     pub fn add_bootstrap_node(&mut self, addr: Multiaddr) {
         // Check if this address already exists in bootstrap nodes
         if self
@@ -369,13 +426,19 @@ where
         }
 
         // Extract peer_id from multiaddr if present
-        let peer_id = addr.iter().find_map(|protocol| {
-            if let libp2p::multiaddr::Protocol::P2p(peer_id) = protocol {
-                Some(peer_id)
-            } else {
-                None
+        let peer_id = util::peer_id_from_multiaddr(&addr);
+
+        if let Some(peer_id) = peer_id {
+            if let Some((_, addrs)) = self
+                .bootstrap_nodes
+                .iter_mut()
+                .find(|(id, addrs)| is_same_bootstrap_node(*id, addrs, peer_id))
+            {
+                addrs.push(addr);
+                info!(%peer_id, "Added address to existing bootstrap node");
+                return;
             }
-        });
+        }
 
         // Add to bootstrap_nodes list
         self.bootstrap_nodes.push((peer_id, vec![addr]));
@@ -395,9 +458,16 @@ where
             .position(|(_, addrs)| addrs.iter().any(|a| a == addr));
 
         if let Some(index) = pos {
-            self.bootstrap_nodes.remove(index);
+            // Only remove the node once it has no addresses left
+            let addrs = &mut self.bootstrap_nodes[index].1;
+            addrs.retain(|a| a != addr);
+
+            if addrs.is_empty() {
+                self.bootstrap_nodes.remove(index);
+            }
+
             info!(
-                "Removed bootstrap node, remaining: {}",
+                "Removed bootstrap node address, remaining nodes: {}",
                 self.bootstrap_nodes.len()
             );
             true
@@ -453,3 +523,66 @@ where
         self.inbound_peers.insert(peer_id);
     }
 }
+
+/// Group the addresses of bootstrap nodes which carry the same peer ID,
+/// so that a node listening on several addresses is treated as a single node.
+fn group_bootstrap_addrs(addrs: Vec<Multiaddr>) -> Vec<(Option<PeerId>, Vec<Multiaddr>)> {
+    let mut nodes: Vec<(Option<PeerId>, Vec<Multiaddr>)> = Vec::new();
+
+    for addr in addrs {
+        let existing = util::peer_id_from_multiaddr(&addr).and_then(|peer_id| {
+            nodes
+                .iter_mut()
+                .find(|(id, addrs)| is_same_bootstrap_node(*id, addrs, peer_id))
+        });
+
+        match existing {
+            Some((_, addrs)) => addrs.push(addr),
+            None => nodes.push((None, vec![addr])),
+        }
+    }
+
+    nodes
+}
+
+/// Whether the bootstrap node with the given (possibly unknown) peer ID and addresses
+/// is the node with the given peer ID.
+fn is_same_bootstrap_node(id: Option<PeerId>, addrs: &[Multiaddr], peer_id: PeerId) -> bool {
+    id == Some(peer_id)
+        || addrs
+            .iter()
+            .any(|addr| util::peer_id_from_multiaddr(addr) == Some(peer_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_bootstrap_addrs_by_peer_id() {
+        let (peer1, peer2) = (PeerId::random(), PeerId::random());
+
+        let addrs: Vec<Multiaddr> = [
+            format!("/ip4/10.0.0.1/tcp/27000/p2p/{peer1}"),
+            format!("/ip4/10.0.0.2/tcp/27000/p2p/{peer2}"),
+            format!("/ip6/fe80::1/tcp/27000/p2p/{peer1}"),
+            "/dns4/node3.example.com/tcp/27000".to_string(),
+            "/dns6/node3.example.com/tcp/27000".to_string(),
+        ]
+        .iter()
+        .map(|addr| addr.parse().unwrap())
+        .collect();
+
+        let nodes = group_bootstrap_addrs(addrs.clone());
+
+        assert_eq!(
+            nodes,
+            vec![
+                (None, vec![addrs[0].clone(), addrs[2].clone()]),
+                (None, vec![addrs[1].clone()]),
+                (None, vec![addrs[3].clone()]),
+                (None, vec![addrs[4].clone()]),
+            ]
+        );
+    }
+}
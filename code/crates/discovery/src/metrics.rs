@@ -3,6 +3,7 @@ use std::time::{Duration, Instant};
 use malachitebft_metrics::prometheus::metrics::counter::Counter;
 
 use malachitebft_metrics::prometheus::metrics::gauge::Gauge;
+use malachitebft_metrics::prometheus::metrics::histogram::{exponential_buckets, Histogram};
 use malachitebft_metrics::Registry;
 
 #[derive(Debug)]
@@ -38,6 +39,14 @@ pub(crate) struct Metrics {
     total_dials: Counter,
     /// Total number of failed dial attempts
     total_failed_dials: Counter,
+    /// Total number of dials refused because the peer presented another identity than the expected one
+    total_identity_mismatches: Counter,
+    /// Number of dials waiting in the dial queue
+    dial_queue_depth: Gauge,
+    /// Total number of dials deferred because too many dials to the same IP were in progress
+    total_deferred_dials: Counter,
+    /// Time between dialing a peer and the connection being established or failing
+    dial_latency: Histogram,
     /// Total number of peers request attempts
     total_peer_requests: Counter,
     /// Total number of failed peer request attempts
@@ -72,6 +81,10 @@ impl Metrics {
 
             total_dials: Counter::default(),
             total_failed_dials: Counter::default(),
+            total_identity_mismatches: Counter::default(),
+            dial_queue_depth: Gauge::default(),
+            total_deferred_dials: Counter::default(),
+            dial_latency: Histogram::new(exponential_buckets(0.01, 2.0, 12)),
             total_peer_requests: Counter::default(),
             total_failed_peer_requests: Counter::default(),
             total_connect_requests: Counter::default(),
@@ -145,6 +158,30 @@ impl Metrics {
             this.total_failed_dials.clone(),
         );
 
+        registry.register(
+            "total_identity_mismatches",
+            "Total number of dials refused because the peer presented another identity than the expected one",
+            this.total_identity_mismatches.clone(),
+        );
+
+        registry.register(
+            "dial_queue_depth",
+            "Number of dials waiting in the dial queue",
+            this.dial_queue_depth.clone(),
+        );
+
+        registry.register(
+            "total_deferred_dials",
+            "Total number of dials deferred because too many dials to the same IP were in progress",
+            this.total_deferred_dials.clone(),
+        );
+
+        registry.register(
+            "dial_latency",
+            "Time between dialing a peer and the connection being established or failing, in seconds",
+            this.dial_latency.clone(),
+        );
+
         registry.register(
             "total_peer_requests",
             "Total number of peer request attempts",
@@ -242,6 +279,22 @@ impl Metrics {
         self.total_failed_dials.inc();
     }
 
+    pub(crate) fn increment_total_identity_mismatches(&self) {
+        self.total_identity_mismatches.inc();
+    }
+
+    pub(crate) fn set_dial_queue_depth(&self, depth: usize) {
+        self.dial_queue_depth.set(depth as i64);
+    }
+
+    pub(crate) fn increment_total_deferred_dials(&self) {
+        self.total_deferred_dials.inc();
+    }
+
+    pub(crate) fn observe_dial_latency(&self, latency: Duration) {
+        self.dial_latency.observe(latency.as_secs_f64());
+    }
+
     pub(crate) fn increment_total_peer_requests(&self) {
         self.total_peer_requests.inc();
     }
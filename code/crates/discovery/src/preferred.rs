@@ -0,0 +1,409 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use libp2p::{Multiaddr, PeerId};
+use tracing::{debug, warn};
+
+/// Weight of the latest round-trip time in the average latency of a peer
+const LATENCY_WEIGHT: f64 = 0.2;
+
+/// Latency at which the score of a peer is halved
+const REFERENCE_LATENCY: Duration = Duration::from_millis(100);
+
+/// A peer which served the node well in previous sessions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreferredPeer {
+    /// Addresses the peer listens on
+    pub addrs: Vec<Multiaddr>,
+    /// Total duration of the sessions with the peer
+    pub uptime: Duration,
+    /// Average round-trip time to the peer, if it was measured
+    pub latency: Option<Duration>,
+    /// Last time the node was connected to the peer
+    pub last_seen: SystemTime,
+}
+
+impl PreferredPeer {
+    fn new(addrs: Vec<Multiaddr>) -> Self {
+        Self {
+            addrs,
+            uptime: Duration::ZERO,
+            latency: None,
+            last_seen: SystemTime::now(),
+        }
+    }
+
+    /// Score of the peer, the higher the better: its uptime in seconds,
+    /// divided by a factor growing with its latency.
+    pub fn score(&self) -> f64 {
+        let latency = self.latency.unwrap_or_default();
+        self.uptime.as_secs_f64() / (1.0 + latency.as_secs_f64() / REFERENCE_LATENCY.as_secs_f64())
+    }
+
+    fn is_stale(&self, now: SystemTime, max_age: Duration) -> bool {
+        now.duration_since(self.last_seen)
+            .is_ok_and(|age| age > max_age)
+    }
+
+    /// Format the peer as a line of the preferred peers file:
+    /// `<peer id> <uptime (s)> <latency (ms) or -> <last seen (s since epoch)> <addresses...>`
+    fn to_line(&self, peer_id: &PeerId) -> String {
+        let latency = self
+            .latency
+            .map_or_else(|| "-".to_string(), |l| l.as_millis().to_string());
+
+        let last_seen = self
+            .last_seen
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut line = format!("{peer_id} {} {latency} {last_seen}", self.uptime.as_secs());
+
+        for addr in &self.addrs {
+            line.push(' ');
+            line.push_str(&addr.to_string());
+        }
+
+        line
+    }
+
+    fn from_line(line: &str) -> Option<(PeerId, Self)> {
+        let mut parts = line.split_whitespace();
+
+        let peer_id = parts.next()?.parse().ok()?;
+        let uptime = Duration::from_secs(parts.next()?.parse().ok()?);
+        let latency = match parts.next()? {
+            "-" => None,
+            ms => Some(Duration::from_millis(ms.parse().ok()?)),
+        };
+        let last_seen = UNIX_EPOCH + Duration::from_secs(parts.next()?.parse().ok()?);
+        let addrs = parts
+            .map(|addr| addr.parse().ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        Some((
+            peer_id,
+            Self {
+                addrs,
+                uptime,
+                latency,
+                last_seen,
+            },
+        ))
+    }
+}
+
+/// The peers which served the node well in previous sessions, persisted to a file so that
+/// they are preferred when selecting the outbound peers after a restart.
+///
+/// The outbound peers are scored by the duration of their sessions with the node and by
+/// their latency. Only the best `max_entries` peers are persisted, and the peers which the
+/// node has not been connected to for more than `max_age` are forgotten.
+#[derive(Debug)]
+pub struct PreferredPeers {
+    /// File the peers are persisted to, if any
+    path: Option<PathBuf>,
+    max_entries: usize,
+    max_age: Duration,
+    peers: HashMap<PeerId, PreferredPeer>,
+    /// Start of the ongoing sessions with outbound peers, or of their part
+    /// which has not yet been added to the uptime of the peers
+    sessions: HashMap<PeerId, Instant>,
+}
+
+impl PreferredPeers {
+    /// Do not track nor persist any peer.
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            max_entries: 0,
+            max_age: Duration::ZERO,
+            peers: HashMap::new(),
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// Load the peers persisted to the given file, if it exists.
+    ///
+    /// Invalid lines are skipped, as well as the peers which are stale already.
+    pub fn load(path: PathBuf, max_entries: usize, max_age: Duration) -> Self {
+        let mut peers = HashMap::new();
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let now = SystemTime::now();
+
+                for line in contents.lines().map(str::trim) {
+                    if line.is_empty() || line.starts_with('#') {
+                        continue;
+                    }
+
+                    match PreferredPeer::from_line(line) {
+                        Some((peer_id, peer)) if !peer.is_stale(now, max_age) => {
+                            peers.insert(peer_id, peer);
+                        }
+                        Some(_) => {}
+                        None => {
+                            warn!(path = %path.display(), "Skipping invalid preferred peer: {line}")
+                        }
+                    }
+                }
+
+                debug!(path = %path.display(), count = peers.len(), "Loaded preferred peers");
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => {
+                warn!(path = %path.display(), "Failed to read preferred peers: {e}");
+            }
+        }
+
+        Self {
+            path: Some(path),
+            max_entries,
+            max_age,
+            peers,
+            sessions: HashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    pub fn get(&self, peer_id: &PeerId) -> Option<&PreferredPeer> {
+        self.peers.get(peer_id)
+    }
+
+    /// The peers, from the best to the worst score.
+    pub fn ranked(&self) -> Vec<(&PeerId, &PreferredPeer)> {
+        let mut peers: Vec<_> = self.peers.iter().collect();
+        peers.sort_by(|(a_id, a), (b_id, b)| {
+            b.score().total_cmp(&a.score()).then_with(|| a_id.cmp(b_id))
+        });
+        peers
+    }
+
+    /// Start a session with an outbound peer, listening on the given addresses.
+    pub fn session_started(&mut self, peer_id: PeerId, addrs: Vec<Multiaddr>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let peer = self
+            .peers
+            .entry(peer_id)
+            .or_insert_with(|| PreferredPeer::new(vec![]));
+
+        if !addrs.is_empty() {
+            peer.addrs = addrs;
+        }
+        peer.last_seen = SystemTime::now();
+
+        self.sessions.entry(peer_id).or_insert_with(Instant::now);
+    }
+
+    /// End the session with an outbound peer, adding its duration to the uptime of the peer.
+    pub fn session_ended(&mut self, peer_id: &PeerId) {
+        let Some(start) = self.sessions.remove(peer_id) else {
+            return;
+        };
+
+        if let Some(peer) = self.peers.get_mut(peer_id) {
+            peer.uptime += start.elapsed();
+            peer.last_seen = SystemTime::now();
+        }
+    }
+
+    /// Record a round-trip time to an outbound peer.
+    pub fn record_latency(&mut self, peer_id: &PeerId, rtt: Duration) {
+        if !self.sessions.contains_key(peer_id) {
+            return;
+        }
+
+        if let Some(peer) = self.peers.get_mut(peer_id) {
+            peer.latency = Some(match peer.latency {
+                Some(latency) => {
+                    latency.mul_f64(1.0 - LATENCY_WEIGHT) + rtt.mul_f64(LATENCY_WEIGHT)
+                }
+                None => rtt,
+            });
+        }
+    }
+
+    /// Add the duration of the ongoing sessions to the uptime of the peers,
+    /// then forget the stale peers and all but the best `max_entries` other peers,
+    /// except for the peers with an ongoing session.
+    fn prune(&mut self) {
+        let now = SystemTime::now();
+
+        for (peer_id, start) in self.sessions.iter_mut() {
+            if let Some(peer) = self.peers.get_mut(peer_id) {
+                peer.uptime += start.elapsed();
+                peer.last_seen = now;
+            }
+
+            *start = Instant::now();
+        }
+
+        let best: Vec<PeerId> = self
+            .ranked()
+            .into_iter()
+            .filter(|(_, peer)| !peer.is_stale(now, self.max_age))
+            .take(self.max_entries)
+            .map(|(peer_id, _)| *peer_id)
+            .collect();
+
+        self.peers
+            .retain(|peer_id, _| best.contains(peer_id) || self.sessions.contains_key(peer_id));
+    }
+
+    /// Persist the best `max_entries` peers to the file, if any.
+    pub fn save(&mut self) -> io::Result<()> {
+        let Some(path) = self.path.clone() else {
+            return Ok(());
+        };
+
+        self.prune();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Write to a temporary file first, so that a crash never leaves a truncated file
+        let tmp_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+
+        writeln!(
+            file,
+            "# <peer id> <uptime (s)> <latency (ms)> <last seen (s since epoch)> <addresses...>"
+        )?;
+
+        for (peer_id, peer) in self.ranked().into_iter().take(self.max_entries) {
+            writeln!(file, "{}", peer.to_line(peer_id))?;
+        }
+
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(port: u16) -> Multiaddr {
+        format!("/ip4/10.0.0.1/udp/{port}/quic-v1").parse().unwrap()
+    }
+
+    fn peer(uptime: u64, latency: Option<u64>) -> PreferredPeer {
+        PreferredPeer {
+            addrs: vec![addr(27000)],
+            uptime: Duration::from_secs(uptime),
+            latency: latency.map(Duration::from_millis),
+            last_seen: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn score_grows_with_uptime_and_shrinks_with_latency() {
+        assert!(peer(100, None).score() > peer(50, None).score());
+        assert!(peer(100, Some(10)).score() > peer(100, Some(200)).score());
+        assert_eq!(peer(100, Some(100)).score(), 50.0);
+    }
+
+    #[test]
+    fn line_roundtrip() {
+        let peer_id = PeerId::random();
+        let mut peer = peer(3600, Some(42));
+        peer.addrs.push(addr(27001));
+        peer.last_seen = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let line = peer.to_line(&peer_id);
+        assert_eq!(PreferredPeer::from_line(&line), Some((peer_id, peer)));
+
+        assert_eq!(PreferredPeer::from_line("not-a-peer-id 1 - 2"), None);
+        assert_eq!(PreferredPeer::from_line(&format!("{peer_id} 1 2")), None);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("preferred_peers.txt");
+        let max_age = Duration::from_secs(3600);
+
+        let mut peers = PreferredPeers::load(path.clone(), 2, max_age);
+        assert!(peers.is_empty());
+
+        let (best, second, worst, stale) = (
+            PeerId::random(),
+            PeerId::random(),
+            PeerId::random(),
+            PeerId::random(),
+        );
+
+        peers.peers.insert(best, peer(300, Some(10)));
+        peers.peers.insert(second, peer(200, Some(10)));
+        peers.peers.insert(worst, peer(100, Some(10)));
+
+        let mut stale_peer = peer(1000, None);
+        stale_peer.last_seen = SystemTime::now() - 2 * max_age;
+        peers.peers.insert(stale, stale_peer);
+
+        peers.save().unwrap();
+
+        let loaded = PreferredPeers::load(path, 2, max_age);
+        let ranked: Vec<PeerId> = loaded.ranked().into_iter().map(|(id, _)| *id).collect();
+        assert_eq!(ranked, vec![best, second]);
+    }
+
+    #[test]
+    fn sessions_add_to_uptime() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut peers =
+            PreferredPeers::load(dir.path().join("peers.txt"), 10, Duration::from_secs(60));
+
+        let peer_id = PeerId::random();
+        peers.session_started(peer_id, vec![addr(27000)]);
+        peers.record_latency(&peer_id, Duration::from_millis(50));
+        peers.record_latency(&peer_id, Duration::from_millis(100));
+
+        std::thread::sleep(Duration::from_millis(10));
+        peers.session_ended(&peer_id);
+
+        let peer = peers.get(&peer_id).unwrap();
+        assert!(peer.uptime >= Duration::from_millis(10));
+        assert_eq!(peer.latency, Some(Duration::from_millis(60)));
+        assert_eq!(peer.addrs, vec![addr(27000)]);
+
+        // Latency is only recorded during a session
+        peers.record_latency(&peer_id, Duration::from_secs(1));
+        assert_eq!(
+            peers.get(&peer_id).unwrap().latency,
+            Some(Duration::from_millis(60))
+        );
+    }
+
+    #[test]
+    fn disabled_tracks_nothing() {
+        let mut peers = PreferredPeers::disabled();
+        let peer_id = PeerId::random();
+
+        peers.session_started(peer_id, vec![addr(27000)]);
+        peers.session_ended(&peer_id);
+
+        assert!(peers.is_empty());
+        assert!(peers.save().is_ok());
+    }
+}
@@ -189,10 +189,25 @@ impl DiscoveryRateLimiter {
 
     /// Remove rate limiting state for a peer (e.g., on disconnect).
     /// Note: This does NOT clear violation count, which persists across sessions
-    /// to support the backoff/banning system.
+    /// to support the backoff/banning system, until it expires.
     pub fn remove_peer(&mut self, peer_id: &PeerId) {
         self.requests.remove(peer_id);
         // Violations are intentionally NOT cleared - they persist for backoff/ban decisions
+        self.prune_expired_violations();
+    }
+
+    /// Forget the violations which have expired, of all peers.
+    fn prune_expired_violations(&mut self) {
+        let now = Instant::now();
+        let expiry = self.violation_expiry;
+
+        self.violations
+            .retain(|_, (_, last_violation)| now.duration_since(*last_violation) < expiry);
+    }
+
+    /// Number of entries kept for peers, whether for their requests or their violations.
+    pub fn peer_entries(&self) -> usize {
+        self.requests.len() + self.violations.len()
     }
 
     /// Clear all state for a peer, including violations.
@@ -341,6 +356,22 @@ mod tests {
         assert_eq!(limiter.violation_count(&peer), 2);
     }
 
+    #[test]
+    fn test_churned_peers_are_forgotten() {
+        let mut limiter = DiscoveryRateLimiter::new(Duration::from_secs(60), 1, 3, Duration::ZERO);
+
+        for _ in 0..5000 {
+            let peer = PeerId::random();
+
+            limiter.check_request(&peer);
+            limiter.check_request(&peer); // violation, which expires right away
+
+            limiter.remove_peer(&peer);
+        }
+
+        assert_eq!(limiter.peer_entries(), 0);
+    }
+
     #[test]
     fn test_clear_peer_removes_violations() {
         let mut limiter =
@@ -1,6 +1,5 @@
 use libp2p::PeerId;
-
-use crate::util::Retry;
+use malachitebft_retry::Retry;
 
 #[derive(Debug, Clone)]
 pub struct RequestData {
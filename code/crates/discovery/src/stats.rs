@@ -0,0 +1,166 @@
+use libp2p::{PeerId, Swarm};
+use serde::{Deserialize, Serialize};
+
+use crate::config::BootstrapProtocol;
+use crate::{ConnectionDirection, Discovery, DiscoveryClient, State};
+
+/// Status of the discovery bootstrap process
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BootstrapStatus {
+    /// Discovery is disabled
+    Disabled,
+    /// The Kademlia bootstrap query is in progress
+    Bootstrapping,
+    /// Discovery is extending the set of known peers
+    Extending {
+        /// Target number of outbound peers
+        target: usize,
+    },
+    /// Discovery is not actively searching for peers
+    Idle,
+}
+
+/// Peers contained in a single Kademlia bucket
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KBucketStats {
+    /// Index of the bucket, ie. the log2 of the distance range it covers
+    pub index: u32,
+    /// Peers in the bucket
+    pub peers: Vec<PeerId>,
+}
+
+/// Number of peers and connections in a given category
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionStats {
+    pub num_peers: usize,
+    pub num_connections: usize,
+}
+
+/// Snapshot of the discovery state, see [`Discovery::snapshot`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveryStats {
+    /// Whether discovery is enabled
+    pub enabled: bool,
+    /// Status of the bootstrap process
+    pub bootstrap_status: BootstrapStatus,
+    /// Time elapsed since discovery started, in milliseconds
+    pub elapsed_ms: u128,
+    /// Number of peers discovered so far
+    pub num_discovered_peers: usize,
+    /// Kademlia buckets (empty unless the Kademlia bootstrap protocol is used)
+    pub kbuckets: Vec<KBucketStats>,
+    /// All peers with at least one active connection
+    pub active: ConnectionStats,
+    /// Peers we dialed and maintain a connection to
+    pub outbound: ConnectionStats,
+    /// Peers that dialed us and that we accepted
+    pub inbound: ConnectionStats,
+    /// Temporary connections, neither outbound nor inbound
+    pub ephemeral: ConnectionStats,
+    /// Managed outbound peers
+    pub outbound_peers: Vec<PeerId>,
+    /// Accepted inbound peers (excluding those which are also outbound peers)
+    pub inbound_peers: Vec<PeerId>,
+    /// Ephemeral peers
+    pub ephemeral_peers: Vec<PeerId>,
+}
+
+impl<C> Discovery<C>
+where
+    C: DiscoveryClient,
+{
+    /// Take a snapshot of the current discovery state.
+    pub fn snapshot(&self, swarm: &mut Swarm<C>) -> DiscoveryStats {
+        let bootstrap_status = if !self.config.enabled {
+            BootstrapStatus::Disabled
+        } else {
+            match self.state {
+                State::Bootstrapping => BootstrapStatus::Bootstrapping,
+                State::Extending(target) => BootstrapStatus::Extending { target },
+                State::Idle => BootstrapStatus::Idle,
+            }
+        };
+
+        let kbuckets = if self.config.enabled
+            && self.config.bootstrap_protocol == BootstrapProtocol::Kademlia
+        {
+            swarm
+                .behaviour_mut()
+                .kbuckets()
+                .map(|kbucket| KBucketStats {
+                    index: kbucket.range().0.ilog2().unwrap_or(0),
+                    peers: kbucket
+                        .iter()
+                        .map(|entry| *entry.node.key.preimage())
+                        .collect(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut outbound_peers: Vec<PeerId> = self.outbound_peers.keys().copied().collect();
+        outbound_peers.sort_unstable();
+
+        let mut inbound_peers: Vec<PeerId> = self
+            .inbound_peers
+            .iter()
+            .filter(|peer_id| !self.outbound_peers.contains_key(peer_id))
+            .copied()
+            .collect();
+        inbound_peers.sort_unstable();
+
+        let mut ephemeral_peers: Vec<PeerId> = self
+            .active_connections
+            .keys()
+            .filter(|peer_id| self.is_ephemeral_peer(peer_id))
+            .copied()
+            .collect();
+        ephemeral_peers.sort_unstable();
+
+        let num_connections = |direction| {
+            self.active_connections
+                .values()
+                .flatten()
+                .filter(|connection_id| {
+                    self.connections
+                        .get(connection_id)
+                        .is_some_and(|info| info.direction == direction)
+                })
+                .count()
+        };
+
+        let num_active_connections = self.active_connections.values().map(Vec::len).sum();
+        let num_outbound_connections = num_connections(ConnectionDirection::Outbound);
+        let num_inbound_connections = num_connections(ConnectionDirection::Inbound);
+
+        DiscoveryStats {
+            enabled: self.config.enabled,
+            bootstrap_status,
+            elapsed_ms: self.metrics.elapsed().as_millis(),
+            num_discovered_peers: self.discovered_peers.len(),
+            kbuckets,
+            active: ConnectionStats {
+                num_peers: self.active_connections.len(),
+                num_connections: num_active_connections,
+            },
+            outbound: ConnectionStats {
+                num_peers: outbound_peers.len(),
+                num_connections: num_outbound_connections,
+            },
+            inbound: ConnectionStats {
+                num_peers: inbound_peers.len(),
+                num_connections: num_inbound_connections,
+            },
+            ephemeral: ConnectionStats {
+                num_peers: ephemeral_peers.len(),
+                num_connections: num_active_connections
+                    .saturating_sub(num_outbound_connections + num_inbound_connections),
+            },
+            outbound_peers,
+            inbound_peers,
+            ephemeral_peers,
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use std::time::Duration;
+use std::net::IpAddr;
 
-use libp2p::Multiaddr;
+use libp2p::{Multiaddr, PeerId};
 
 /// Strip /p2p/<peer_id> component from a Multiaddr for address comparison.
 /// This allows comparing addresses regardless of whether they include a peer ID.
@@ -16,60 +16,86 @@ pub fn strip_peer_id_from_multiaddr(addr: &Multiaddr) -> Multiaddr {
     result
 }
 
-#[derive(Debug, Clone)]
-struct FibonacciBackoff {
-    current: u64,
-    next: u64,
+/// Extract the peer ID from the /p2p/<peer_id> component of a Multiaddr, if present.
+pub fn peer_id_from_multiaddr(addr: &Multiaddr) -> Option<PeerId> {
+    use libp2p::multiaddr::Protocol;
+
+    addr.iter().find_map(|protocol| match protocol {
+        Protocol::P2p(peer_id) => Some(peer_id),
+        _ => None,
+    })
 }
 
-impl FibonacciBackoff {
-    pub fn new() -> Self {
-        // Start from 1 second
-        Self {
-            current: 1000,
-            next: 1000,
-        }
+/// The IP address of a Multiaddr, if it starts with one.
+pub(crate) fn ip_of_multiaddr(addr: &Multiaddr) -> Option<IpAddr> {
+    use libp2p::multiaddr::Protocol;
+
+    match addr.iter().next() {
+        Some(Protocol::Ip4(ip)) => Some(IpAddr::V4(ip)),
+        Some(Protocol::Ip6(ip)) => Some(IpAddr::V6(ip)),
+        _ => None,
     }
 }
 
-impl Iterator for FibonacciBackoff {
-    type Item = Duration;
+/// Sort addresses so that the ones most likely to be reachable come first.
+///
+/// Publicly routable addresses, such as the external addresses of a peer confirmed by AutoNAT,
+/// come first, followed by relayed addresses, then private and link-local addresses,
+/// and finally loopback and unspecified addresses. The order is otherwise preserved.
+pub fn sort_addrs_by_reachability(addrs: &mut [Multiaddr]) {
+    addrs.sort_by_key(reachability_rank);
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let new_next = self.current + self.next;
-        self.current = self.next;
-        self.next = new_next;
+fn reachability_rank(addr: &Multiaddr) -> u8 {
+    use libp2p::multiaddr::Protocol;
 
-        Some(Duration::from_millis(self.current))
+    if addr.iter().any(|p| matches!(p, Protocol::P2pCircuit)) {
+        return 1;
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct Retry {
-    count: usize,
-    backoff: FibonacciBackoff,
-}
+    match addr.iter().next() {
+        Some(Protocol::Ip4(ip)) if ip.is_loopback() || ip.is_unspecified() => 3,
+        Some(Protocol::Ip4(ip)) if ip.is_private() || ip.is_link_local() => 2,
+        Some(Protocol::Ip6(ip)) if ip.is_loopback() || ip.is_unspecified() => 3,
+        Some(Protocol::Ip6(ip)) => {
+            let unique_local = (ip.segments()[0] & 0xfe00) == 0xfc00;
+            let link_local = (ip.segments()[0] & 0xffc0) == 0xfe80;
 
-impl Retry {
-    #[allow(clippy::new_without_default)]
-    pub fn new() -> Self {
-        Self {
-            count: 0,
-            backoff: FibonacciBackoff::new(),
+            if unique_local || link_local {
+                2
+            } else {
+                0
+            }
         }
+        _ => 0,
     }
+}
 
-    pub fn count(&self) -> usize {
-        self.count
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    pub fn inc_count(&mut self) {
-        self.count += 1;
-    }
+    #[test]
+    fn sort_addrs_public_first() {
+        let relay = PeerId::random();
+
+        let mut addrs: Vec<Multiaddr> = [
+            "/ip4/127.0.0.1/tcp/27000".to_string(),
+            "/ip4/192.168.1.10/tcp/27000".to_string(),
+            format!("/ip4/1.2.3.4/tcp/27000/p2p/{relay}/p2p-circuit"),
+            "/ip6/fe80::1/tcp/27000".to_string(),
+            "/ip4/8.8.4.4/tcp/27000".to_string(),
+            "/dns4/node.example.com/tcp/27000".to_string(),
+            "/ip6/::1/tcp/27000".to_string(),
+            "/ip6/2001:db8::1/tcp/27000".to_string(),
+        ]
+        .iter()
+        .map(|addr| addr.parse().unwrap())
+        .collect();
+
+        let expected = [4, 5, 7, 2, 1, 3, 0, 6].map(|i| addrs[i].clone());
 
-    pub fn next_delay(&mut self) -> Duration {
-        self.backoff
-            .next()
-            .expect("FibonacciBackoff is an infinite iterator")
+        sort_addrs_by_reachability(&mut addrs);
+        assert_eq!(addrs, expected);
     }
 }
@@ -26,6 +26,8 @@ use malachitebft_core_types::{Height, Round};
 /// equivocate_votes = { mode = "random", probability = 0.3 }
 /// drop_proposals = { mode = "at_heights", heights = [10, 20, 30] }
 /// ignore_locks = { mode = "always" }
+/// withhold_proposal_parts = { mode = "at_rounds", rounds = [0] }
+/// corrupt_sync_responses = { mode = "always" }
 /// seed = 42
 /// ```
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
@@ -69,6 +71,25 @@ pub struct ByzantineConfig {
     /// as if it lost the proposal on the wire while everyone else received it.
     pub drop_inbound_proposals: Trigger,
 
+    /// When to withhold outgoing proposal parts.
+    ///
+    /// When triggered for the `(height, round)` of a stream of proposal parts,
+    /// only the first part of the stream is published and the remaining parts
+    /// are withheld, so that peers learn that a value is being proposed but
+    /// can never assemble it. Requires a [`ProposalPartPositionFn`] to find the
+    /// height and round of a stream; without one, no part is withheld.
+    ///
+    /// [`ProposalPartPositionFn`]: crate::ProposalPartPositionFn
+    pub withhold_proposal_parts: Trigger,
+
+    /// When to corrupt outgoing sync responses.
+    ///
+    /// When triggered for the height of a value in a sync response, the last
+    /// byte of the encoded value is flipped, so that the value no longer
+    /// matches its commit certificate. The certificate itself is left intact.
+    /// The trigger is evaluated with round 0.
+    pub corrupt_sync_responses: Trigger,
+
     /// Random seed for reproducible random attacks.
     ///
     /// If set, the random number generator is seeded with this value,
@@ -120,6 +141,16 @@ impl ByzantineConfig {
         self
     }
 
+    pub fn with_withhold_proposal_parts(mut self, trigger: Trigger) -> Self {
+        self.withhold_proposal_parts = trigger;
+        self
+    }
+
+    pub fn with_corrupt_sync_responses(mut self, trigger: Trigger) -> Self {
+        self.corrupt_sync_responses = trigger;
+        self
+    }
+
     /// Returns `true` if any Byzantine behavior is configured.
     pub fn is_active(&self) -> bool {
         self.equivocate_votes.is_set()
@@ -129,6 +160,8 @@ impl ByzantineConfig {
             || self.ignore_locks.is_set()
             || self.force_precommit_nil.is_set()
             || self.drop_inbound_proposals.is_set()
+            || self.withhold_proposal_parts.is_set()
+            || self.corrupt_sync_responses.is_set()
     }
 
     /// Validate trigger parameters and reject invalid configuration.
@@ -141,6 +174,10 @@ impl ByzantineConfig {
         self.force_precommit_nil.validate("force_precommit_nil")?;
         self.drop_inbound_proposals
             .validate("drop_inbound_proposals")?;
+        self.withhold_proposal_parts
+            .validate("withhold_proposal_parts")?;
+        self.corrupt_sync_responses
+            .validate("corrupt_sync_responses")?;
 
         if self.drop_votes.is_set() && self.equivocate_votes.is_set() {
             bail!("drop_votes and equivocate_votes cannot both be set");
@@ -357,6 +394,20 @@ mod tests {
         assert_eq!(parsed.seed, Some(42));
     }
 
+    #[test]
+    fn test_withhold_parts_and_corrupt_sync_responses_are_active() {
+        let config = ByzantineConfig::new(Some(42))
+            .with_withhold_proposal_parts(Trigger::AtRounds { rounds: vec![0] });
+        assert!(config.is_active());
+
+        let config = ByzantineConfig::new(Some(42)).with_corrupt_sync_responses(Trigger::Always);
+        assert!(config.is_active());
+
+        let toml_str = toml::to_string_pretty(&config).unwrap();
+        let parsed: ByzantineConfig = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed, config);
+    }
+
     #[test]
     fn test_empty_config_is_inactive() {
         let config = ByzantineConfig::default();
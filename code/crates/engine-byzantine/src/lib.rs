@@ -39,4 +39,6 @@ pub mod proxy;
 pub use amnesia::Amnesia;
 pub use config::{ByzantineConfig, Trigger};
 pub use inbound::{InboundFilter, InboundFilterMsg};
-pub use proxy::{ByzantineNetworkProxy, ConflictingValueFn, ConflictingVoteValueFn};
+pub use proxy::{
+    ByzantineNetworkProxy, ConflictingValueFn, ConflictingVoteValueFn, ProposalPartPositionFn,
+};
@@ -8,8 +8,13 @@
 //! - **Drop** vote/proposal messages (simulating silence / censorship)
 //! - **Duplicate** vote/proposal messages with conflicting content on consensus
 //!   and liveness vote paths (simulating equivocation)
+//! - **Withhold** the proposal parts following the first part of a stream
+//!   (simulating a proposer which never delivers its value)
 //! - **Forward** non-targeted messages unchanged (honest behavior)
 //!
+//! It also intercepts outgoing [`NetworkMsg::OutgoingResponse`] messages and
+//! can **corrupt** the values they carry (simulating a malicious sync peer).
+//!
 //! Subscribe messages receive special handling: when `drop_inbound_proposals`
 //! is configured, an [`InboundFilter`] is spliced between the real network's
 //! output port and the consensus subscriber so selected inbound proposals can
@@ -20,6 +25,8 @@
 
 use std::collections::HashMap;
 
+use bytes::{Bytes, BytesMut};
+
 use async_trait::async_trait;
 use eyre::{eyre, Result};
 use ractor::{Actor, ActorProcessingErr, ActorRef};
@@ -31,7 +38,9 @@ use malachitebft_core_types::{
     Context, Height, NilOrVal, Proposal, Round, ValueId, Vote, VoteType,
 };
 use malachitebft_engine::network::{Msg as NetworkMsg, NetworkRef};
+use malachitebft_engine::util::streaming::{StreamId, StreamMessage};
 use malachitebft_signing::Signer;
+use malachitebft_sync::Response;
 
 use crate::config::{make_rng, ByzantineConfig};
 use crate::inbound::InboundFilter;
@@ -50,6 +59,17 @@ pub type ConflictingValueFn<Ctx> =
 pub type ConflictingVoteValueFn<Ctx> =
     Box<dyn Fn(Option<&ValueId<Ctx>>) -> ValueId<Ctx> + Send + Sync>;
 
+/// A function that finds the height and round of the proposal which a
+/// proposal part belongs to.
+///
+/// Used for withholding proposal parts: the first part of a stream is expected
+/// to carry this information, and the function may return `None` for the others.
+pub type ProposalPartPositionFn<Ctx> = Box<
+    dyn Fn(&<Ctx as Context>::ProposalPart) -> Option<(<Ctx as Context>::Height, Round)>
+        + Send
+        + Sync,
+>;
+
 /// A ractor actor that proxies [`NetworkMsg`] between consensus and the real
 /// network, applying Byzantine behavior according to a [`ByzantineConfig`].
 ///
@@ -70,6 +90,9 @@ pub struct ByzantineNetworkProxy<Ctx: Context> {
     /// Receives `Some(&id)` for non-nil votes, `None` for nil votes.
     /// If absent, vote equivocation falls back to flipping `Val -> Nil`.
     conflicting_vote_value_fn: Option<ConflictingVoteValueFn<Ctx>>,
+    /// Finds the height and round of a stream of proposal parts from its first part.
+    /// Required for withholding proposal parts; if `None`, no part is withheld.
+    proposal_part_position_fn: Option<ProposalPartPositionFn<Ctx>>,
 }
 
 /// The action decided for a vote by the consensus path, to be replayed
@@ -89,6 +112,9 @@ pub struct ProxyState {
     /// decision instead of re-evaluating the trigger (which would consume a
     /// fresh RNG sample and produce an independent, inconsistent result).
     vote_actions: HashMap<(u64, i64, VoteType), VoteAction>,
+    /// Streams of proposal parts whose remaining parts are withheld,
+    /// with the height of the proposal they belong to.
+    withheld_streams: HashMap<StreamId, u64>,
 }
 
 impl<Ctx: Context> ByzantineNetworkProxy<Ctx> {
@@ -98,6 +124,7 @@ impl<Ctx: Context> ByzantineNetworkProxy<Ctx> {
     /// - Without `conflicting_value_fn`, proposal equivocation is skipped.
     /// - Without `conflicting_vote_value_fn`, non-nil votes equivocate to nil;
     ///   nil votes cannot be equivocated.
+    /// - Without `proposal_part_position_fn`, proposal parts are never withheld.
     #[allow(clippy::too_many_arguments)]
     pub async fn spawn(
         config: ByzantineConfig,
@@ -108,6 +135,7 @@ impl<Ctx: Context> ByzantineNetworkProxy<Ctx> {
         span: tracing::Span,
         conflicting_value_fn: Option<ConflictingValueFn<Ctx>>,
         conflicting_vote_value_fn: Option<ConflictingVoteValueFn<Ctx>>,
+        proposal_part_position_fn: Option<ProposalPartPositionFn<Ctx>>,
     ) -> Result<NetworkRef<Ctx>> {
         config
             .validate()
@@ -123,6 +151,7 @@ impl<Ctx: Context> ByzantineNetworkProxy<Ctx> {
             span,
             conflicting_value_fn,
             conflicting_vote_value_fn,
+            proposal_part_position_fn,
         };
 
         let (actor_ref, _) = Actor::spawn(None, proxy, seed)
@@ -147,6 +176,7 @@ impl<Ctx: Context> Actor for ByzantineNetworkProxy<Ctx> {
         Ok(ProxyState {
             rng: make_rng(seed),
             vote_actions: HashMap::new(),
+            withheld_streams: HashMap::new(),
         })
     }
 
@@ -167,6 +197,17 @@ impl<Ctx: Context> Actor for ByzantineNetworkProxy<Ctx> {
                     .instrument(self.span.clone())
                     .await?;
             }
+            NetworkMsg::PublishProposalPart(part) => {
+                let _enter = self.span.enter();
+                self.handle_proposal_part(part, state)?;
+            }
+            NetworkMsg::OutgoingResponse(request_id, response) => {
+                let _enter = self.span.enter();
+                let response = self.corrupt_sync_response(response, state);
+                self.real_network
+                    .cast(NetworkMsg::OutgoingResponse(request_id, response))
+                    .map_err(|e| format!("Failed to forward sync response: {e:?}"))?;
+            }
             // Receiver-side interception. When `drop_inbound_proposals` is set,
             // splice an `InboundFilter` forwarder between the real network's
             // output port and the consensus subscriber so that specific
@@ -370,6 +411,84 @@ impl<Ctx: Context> ByzantineNetworkProxy<Ctx> {
         Ok(())
     }
 
+    /// Handle a proposal part, withholding it if it belongs to a stream whose
+    /// first part was published when the `withhold_proposal_parts` trigger fired.
+    fn handle_proposal_part(
+        &self,
+        part: StreamMessage<Ctx::ProposalPart>,
+        state: &mut ProxyState,
+    ) -> Result<(), ActorProcessingErr> {
+        if state.withheld_streams.contains_key(&part.stream_id) {
+            debug!(stream_id = %part.stream_id, sequence = %part.sequence, "BYZANTINE: Withholding proposal part");
+            return Ok(());
+        }
+
+        let position = self
+            .proposal_part_position_fn
+            .as_ref()
+            .zip(part.content.as_data())
+            .and_then(|(position_of, data)| position_of(data));
+
+        if let Some((height, round)) = position {
+            if self
+                .config
+                .withhold_proposal_parts
+                .fires(height, round, &mut state.rng)
+            {
+                warn!(%height, %round, stream_id = %part.stream_id, "BYZANTINE: Withholding the remaining proposal parts");
+
+                // Forget about the streams of previous heights
+                let height = height.as_u64();
+                state
+                    .withheld_streams
+                    .retain(|_, withheld| *withheld >= height);
+                state
+                    .withheld_streams
+                    .insert(part.stream_id.clone(), height);
+            }
+        }
+
+        self.real_network
+            .cast(NetworkMsg::PublishProposalPart(part))
+            .map_err(|e| {
+                ActorProcessingErr::from(format!(
+                    "Failed to forward proposal part to network: {e:?}"
+                ))
+            })
+    }
+
+    /// Corrupt the values of a sync response for which the
+    /// `corrupt_sync_responses` trigger fires, by flipping the last byte of their encoding.
+    fn corrupt_sync_response(
+        &self,
+        response: Response<Ctx>,
+        state: &mut ProxyState,
+    ) -> Response<Ctx> {
+        let Response::ValueResponse(mut value_response) = response;
+
+        for value in &mut value_response.values {
+            let height = value.certificate.height;
+
+            if value.value_bytes.is_empty()
+                || !self
+                    .config
+                    .corrupt_sync_responses
+                    .fires(height, Round::new(0), &mut state.rng)
+            {
+                continue;
+            }
+
+            warn!(%height, "BYZANTINE: Corrupting value in sync response");
+
+            let mut bytes = BytesMut::from(value.value_bytes.as_ref());
+            let last = bytes.len() - 1;
+            bytes[last] ^= 0x01;
+            value.value_bytes = Bytes::from(bytes);
+        }
+
+        Response::ValueResponse(value_response)
+    }
+
     /// Evaluate the drop and equivocation triggers for a vote, returning
     /// the decided action. Used as a fallback when no cached action exists.
     fn decide_vote_action(
@@ -1,12 +1,14 @@
 use core::fmt;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::future::{pending, Future};
 use std::io;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
 use async_recursion::async_recursion;
 use async_trait::async_trait;
+use bytes::Bytes;
 use derive_where::derive_where;
 use eyre::eyre;
 use itertools::Itertools;
@@ -19,22 +21,29 @@ use malachitebft_codec as codec;
 use malachitebft_config::ConsensusConfig;
 use malachitebft_core_consensus::{
     Effect, LivenessMsg, PeerId, Resumable, Resume, SignedConsensusMsg, VoteExtensionError,
+    VoteTally,
 };
 use malachitebft_core_types::{
-    CommitCertificate, Context, Proposal, Round, Timeout, TimeoutKind, Timeouts, ValidatorProof,
-    ValidatorSet, Validity, Value, ValueId, ValueOrigin, ValueResponse as CoreValueResponse, Vote,
+    ChainId, CommitCertificate, Context, Proposal, Round, SigningScheme, ThresholdParam, Timeout,
+    TimeoutKind, Timeouts, Validator, ValidatorProof, ValidatorSet, ValidatorSetUpdateCertificate,
+    Validity, Value, ValueId, ValueOrigin, ValuePayload, ValueResponse as CoreValueResponse, Vote,
+    VotingPowerChange,
 };
 use malachitebft_metrics::Metrics;
 use malachitebft_signing::{Signer, Verifier, VerifierExt};
-use malachitebft_sync::HeightStartType;
+use malachitebft_sync::{HeightStartType, NodeMode, PeerStakes};
 
-use crate::host::{HeightParams, HostMsg, HostRef, LocallyProposedValue, Next, ProposedValue};
-use crate::network::{NetworkEvent, NetworkMsg, NetworkRef};
+use crate::host::{
+    HeightParams, HeightParamsOverride, HostMsg, HostRef, LocallyProposedValue, Next,
+    ProposedValue, RoundTimeoutsOverride, SyncedValueOutcome,
+};
+use crate::network::{NetworkEvent, NetworkMsg, NetworkRef, PeerExit, PeerState};
 use crate::sync::Msg as SyncMsg;
+use crate::util::clock::Clock;
 use crate::util::events::{Event, TxEvent};
 use crate::util::msg_buffer::MessageBuffer;
 use crate::util::output_port::OutputPort;
-use crate::util::ractor::cast_option_and_handle;
+use crate::util::ractor::cast_result_option_and_handle;
 use crate::util::streaming::StreamMessage;
 use crate::util::timers::{TimeoutElapsed, TimerScheduler};
 use crate::wal::{Msg as WalMsg, WalEntry, WalRef};
@@ -46,6 +55,18 @@ pub use malachitebft_core_consensus::State as ConsensusState;
 pub mod state_dump;
 use state_dump::StateDump;
 
+mod flight_recorder;
+use flight_recorder::{FlightRecorder, TraceKind};
+
+mod adaptive_timeouts;
+use adaptive_timeouts::AdaptiveTimeouts;
+
+mod decisions;
+use decisions::{DecidedValues, Decision};
+
+mod seen;
+use seen::{MessageDigest, SeenMessages};
+
 /// Codec for consensus messages.
 ///
 /// This trait is automatically implemented for any type that implements:
@@ -93,6 +114,7 @@ where
     sync: Arc<OutputPort<SyncMsg<Ctx>>>,
     metrics: Metrics,
     tx_event: TxEvent<Ctx>,
+    clock: Arc<dyn Clock>,
     span: tracing::Span,
 }
 
@@ -118,6 +140,10 @@ pub enum Msg<Ctx: Context> {
     /// Process a sync response
     ProcessSyncResponse(CoreValueResponse<Ctx>),
 
+    /// Process a contiguous run of sync responses, starting at the current height,
+    /// whose certificates are verified at once, see [`ConsensusInput::ProcessCommitCertificateBatch`].
+    ProcessSyncResponses(Vec<CoreValueResponse<Ctx>>),
+
     /// Instructs consensus to restart at a given height with the provided parameters.
     ///
     /// On this input consensus resets the Write-Ahead Log.
@@ -129,15 +155,42 @@ pub enum Msg<Ctx: Context> {
     ///    for the restarted height, potentially violating protocol safety
     RestartHeight(Ctx::Height, HeightParams<Ctx>),
 
-    /// The application has confirmed that the decision has been committed.
+    /// The application has confirmed that the decision certified by the given
+    /// commit certificate has been committed.
     /// This triggers notifying the sync actor about the decided height.
-    DecisionCommitted(Ctx::Height),
+    DecisionCommitted(CommitCertificate<Ctx>),
 
     /// The WAL replay delay has elapsed; replay WAL entries or skip if sync succeeded.
     WalReplayDelayElapsed,
 
     /// Request to dump the current consensus state
     DumpState(RpcReplyPort<Option<StateDump<Ctx>>>),
+
+    /// Request to dump the trace retained by the flight recorder to the given file,
+    /// replying with an error if the flight recorder is disabled or the file cannot be written.
+    DumpTrace(PathBuf, RpcReplyPort<io::Result<()>>),
+
+    /// Publish a validator set update approved by the current validator set,
+    /// to be applied by every node when starting its effective height.
+    PublishValidatorSetUpdate(ValidatorSetUpdateCertificate<Ctx>),
+
+    /// The application has processed a batch of synced values, whose outcomes
+    /// are used once the corresponding sync responses are processed.
+    SyncedValuesProcessed(Vec<ProcessedSyncedValue<Ctx>>),
+}
+
+/// A synced value processed by the application as part of a batch,
+/// see [`HostMsg::ProcessSyncedValues`].
+#[derive_where(Debug)]
+pub struct ProcessedSyncedValue<Ctx: Context> {
+    /// Height of the synced value
+    pub height: Ctx::Height,
+    /// Round of the synced value
+    pub round: Round,
+    /// Raw encoded value data
+    pub value_bytes: Bytes,
+    /// Outcome of the validation of the value, or `None` if it could not be decoded
+    pub outcome: Option<SyncedValueOutcome<Ctx>>,
 }
 
 impl<Ctx: Context> fmt::Display for Msg<Ctx> {
@@ -182,12 +235,31 @@ impl<Ctx: Context> fmt::Display for Msg<Ctx> {
                     response.peer, response.certificate.height, response.certificate.value_id
                 )
             }
+            Msg::ProcessSyncResponses(responses) => match (responses.first(), responses.last()) {
+                (Some(first), Some(last)) => write!(
+                    f,
+                    "ProcessSyncResponses(peer={} heights={}..={})",
+                    first.peer, first.certificate.height, last.certificate.height
+                ),
+                _ => write!(f, "ProcessSyncResponses(empty)"),
+            },
             Msg::RestartHeight(height, params) => {
                 write!(f, "RestartHeight(height={height} params={params:?})")
             }
-            Msg::DecisionCommitted(height) => write!(f, "DecisionCommitted(height={height})"),
+            Msg::DecisionCommitted(certificate) => {
+                write!(f, "DecisionCommitted(height={})", certificate.height)
+            }
             Msg::WalReplayDelayElapsed => write!(f, "WalReplayDelayElapsed"),
             Msg::DumpState(_) => write!(f, "DumpState"),
+            Msg::DumpTrace(path, _) => write!(f, "DumpTrace(path={})", path.display()),
+            Msg::PublishValidatorSetUpdate(certificate) => write!(
+                f,
+                "PublishValidatorSetUpdate(epoch={} effective_height={})",
+                certificate.update.epoch, certificate.update.effective_height
+            ),
+            Msg::SyncedValuesProcessed(values) => {
+                write!(f, "SyncedValuesProcessed(count={})", values.len())
+            }
         }
     }
 }
@@ -208,6 +280,44 @@ impl<Ctx: Context> From<TimeoutElapsed<Timeout>> for Msg<Ctx> {
 
 type Timers = TimerScheduler<Timeout>;
 
+/// Durations of the timeouts of a round, as overridden by the application.
+struct TimeoutOverrides {
+    round: Round,
+    durations: RoundTimeoutsOverride,
+}
+
+impl TimeoutOverrides {
+    fn new() -> Self {
+        Self {
+            round: Round::Nil,
+            durations: RoundTimeoutsOverride::default(),
+        }
+    }
+
+    fn reset(&mut self, round: Round) {
+        self.round = round;
+        self.durations = RoundTimeoutsOverride::default();
+    }
+
+    fn get(&self, timeout: Timeout) -> Option<Duration> {
+        if timeout.round != self.round {
+            return None;
+        }
+
+        self.durations.get(timeout.kind)
+    }
+}
+
+/// Alerts raised for the current height when it goes through too many rounds.
+#[derive(Debug, Default)]
+struct RoundAlerts {
+    /// Whether the round alert was raised, see `max_rounds_alert`.
+    raised: bool,
+
+    /// Whether participation is halted until the next height, see `max_rounds_halt`.
+    halted: bool,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum Phase {
     Unstarted,
@@ -223,6 +333,17 @@ enum Phase {
 /// not in the `Running` phase
 const MAX_BUFFER_SIZE: usize = 1024;
 
+/// Minimum interval between two progress events while replaying the WAL
+const WAL_REPLAY_PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum time to wait for the application to reply with the timeout overrides of a round,
+/// after which the timeouts of the round are not overridden
+const TIMEOUT_OVERRIDES_DEADLINE: Duration = Duration::from_millis(200);
+
+/// Maximum number of heights above the current one for which the peers
+/// relaying votes are tracked, the lowest heights being kept.
+const MAX_FUTURE_VOTE_HEIGHTS: usize = 16;
+
 pub struct State<Ctx: Context> {
     /// Scheduler for timers
     timers: Timers,
@@ -237,6 +358,9 @@ pub struct State<Ctx: Context> {
     /// The set of peers we are connected to.
     connected_peers: BTreeSet<PeerId>,
 
+    /// Consensus public keys of the peers which sent us a valid validator proof.
+    validator_peers: BTreeMap<PeerId, Vec<u8>>,
+
     /// The current phase
     phase: Phase,
 
@@ -253,6 +377,46 @@ pub struct State<Ctx: Context> {
 
     /// Handle for the WAL replay delay timer, used for cancellation.
     wal_replay_timer: Option<JoinHandle<()>>,
+
+    /// Last vote tally emitted for each round of the current height.
+    vote_tallies: BTreeMap<Round, VoteTally>,
+
+    /// Timeouts of the current round overridden by the application.
+    timeout_overrides: TimeoutOverrides,
+
+    /// Alerts raised for the current height.
+    round_alerts: RoundAlerts,
+
+    /// Height and round of the value requested from the application, until it is received.
+    pending_value: Option<(Ctx::Height, Round)>,
+
+    /// Synced values processed by the application in batches, until their height is processed.
+    synced_values: BTreeMap<Ctx::Height, ProcessedSyncedValue<Ctx>>,
+
+    /// Peers from which votes for heights higher than the current one were received.
+    future_vote_peers: BTreeMap<Ctx::Height, BTreeSet<PeerId>>,
+
+    /// Verified validator set updates, indexed by the height at which they take effect.
+    validator_set_updates: BTreeMap<Ctx::Height, ValidatorSetUpdateCertificate<Ctx>>,
+
+    /// Epoch of the latest validator set update accepted.
+    validator_set_epoch: Option<u64>,
+
+    /// Root span of the trace of the current height, under which its messages are handled.
+    height_span: Option<(Ctx::Height, tracing::Span)>,
+
+    /// Trace of the inputs and effects of the last heights, if the flight recorder is enabled.
+    flight_recorder: Option<FlightRecorder<Ctx::Height>>,
+
+    /// Timeouts computed from the durations of the last steps, if adaptive timeouts are enabled.
+    adaptive_timeouts: Option<AdaptiveTimeouts>,
+
+    /// Values decided at the last heights, to notify the application only once about each decision.
+    decided_values: DecidedValues<Ctx::Height, ValueId<Ctx>>,
+
+    /// Digests of the votes and proposals appended to the WAL at the current height, including
+    /// the ones replayed from the WAL, to drop the duplicates of these messages received from the network.
+    seen_messages: SeenMessages,
 }
 
 impl<Ctx> State<Ctx>
@@ -273,6 +437,25 @@ where
             .unwrap_or(Round::Nil)
     }
 
+    /// Voting power of the peers which proved to be validators of the current height.
+    fn peer_stakes(&self) -> PeerStakes {
+        let Some(consensus) = &self.consensus else {
+            return PeerStakes::new();
+        };
+
+        let validator_set = consensus.validator_set();
+
+        self.validator_peers
+            .iter()
+            .filter_map(|(peer_id, public_key)| {
+                validator_set
+                    .iter()
+                    .find(|v| Ctx::SigningScheme::encode_public_key(v.public_key()) == *public_key)
+                    .map(|v| (*peer_id, v.voting_power()))
+            })
+            .collect()
+    }
+
     fn set_phase(&mut self, phase: Phase) {
         if self.phase != phase {
             info!(prev = ?self.phase, new = ?phase, "Phase transition");
@@ -281,11 +464,72 @@ where
     }
 }
 
+impl<Ctx> PeerState for State<Ctx>
+where
+    Ctx: Context,
+{
+    fn forget_peer(&mut self, peer_id: &PeerId, _exit: PeerExit) {
+        self.connected_peers.remove(peer_id);
+        self.validator_peers.remove(peer_id);
+
+        self.future_vote_peers.retain(|_, peers| {
+            peers.remove(peer_id);
+            !peers.is_empty()
+        });
+    }
+
+    fn peer_entries(&self) -> usize {
+        self.connected_peers.len()
+            + self.validator_peers.len()
+            + self
+                .future_vote_peers
+                .values()
+                .map(BTreeSet::len)
+                .sum::<usize>()
+    }
+}
+
 struct HandlerState<'a, Ctx: Context> {
     phase: Phase,
     is_validator: bool,
     timers: &'a mut Timers,
     timeouts: Ctx::Timeouts,
+    timeout_overrides: &'a mut TimeoutOverrides,
+    adaptive_timeouts: Option<&'a AdaptiveTimeouts>,
+    round_alerts: &'a mut RoundAlerts,
+    pending_value: &'a mut Option<(Ctx::Height, Round)>,
+    synced_values: &'a mut BTreeMap<Ctx::Height, ProcessedSyncedValue<Ctx>>,
+    future_vote_peers: &'a mut BTreeMap<Ctx::Height, BTreeSet<PeerId>>,
+    decided_values: &'a mut DecidedValues<Ctx::Height, ValueId<Ctx>>,
+    seen_messages: &'a mut SeenMessages,
+}
+
+impl<Ctx: Context> HandlerState<'_, Ctx> {
+    /// Duration of the given timeout, as overridden by the application if it did.
+    ///
+    /// Otherwise, if adaptive timeouts are enabled, the adaptive timeout of the step replaces
+    /// the duration of the timeout at round 0, its increase with the round being kept.
+    fn timeout_duration(&self, timeout: Timeout) -> Duration {
+        if let Some(duration) = self.timeout_overrides.get(timeout) {
+            return duration;
+        }
+
+        let duration = self.timeouts.duration_for(timeout);
+
+        match self
+            .adaptive_timeouts
+            .and_then(|adaptive| adaptive.timeout(timeout.kind))
+        {
+            Some(adaptive) => {
+                let initial = self
+                    .timeouts
+                    .duration_for(Timeout::new(Round::ZERO, timeout.kind));
+
+                adaptive + duration.saturating_sub(initial)
+            }
+            None => duration,
+        }
+    }
 }
 
 impl<Ctx> Consensus<Ctx>
@@ -305,6 +549,7 @@ where
         sync: Arc<OutputPort<SyncMsg<Ctx>>>,
         metrics: Metrics,
         tx_event: TxEvent<Ctx>,
+        clock: Arc<dyn Clock>,
         span: tracing::Span,
     ) -> Result<ActorRef<Msg<Ctx>>, ractor::SpawnErr> {
         let node = Self {
@@ -319,6 +564,7 @@ where
             sync,
             metrics,
             tx_event,
+            clock,
             span,
         };
 
@@ -339,21 +585,151 @@ where
         state: &mut State<Ctx>,
         input: ConsensusInput<Ctx>,
     ) -> Result<(), ConsensusError<Ctx>> {
-        malachitebft_core_consensus::process!(
+        let vote_round = match &input {
+            ConsensusInput::Vote(vote) => Some(vote.round()),
+            _ => None,
+        };
+
+        if let Some(recorder) = &mut state.flight_recorder {
+            recorder.record(self.clock.now(), TraceKind::Input, || format!("{input:?}"));
+        }
+
+        let result = malachitebft_core_consensus::process!(
             input: input,
             state: state.consensus.as_mut().expect("Consensus not started"),
             metrics: &self.metrics,
             with: effect => {
+                if let Some(recorder) = &mut state.flight_recorder {
+                    recorder.record(self.clock.now(), TraceKind::Effect, || format!("{effect:?}"));
+                }
+
                 let handler_state = HandlerState {
                     phase: state.phase,
                     is_validator: state.is_validator,
                     timers: &mut state.timers,
                     timeouts: state.timeouts,
+                    timeout_overrides: &mut state.timeout_overrides,
+                    adaptive_timeouts: state.adaptive_timeouts.as_ref(),
+                    round_alerts: &mut state.round_alerts,
+                    pending_value: &mut state.pending_value,
+                    synced_values: &mut state.synced_values,
+                    future_vote_peers: &mut state.future_vote_peers,
+                    decided_values: &mut state.decided_values,
+                    seen_messages: &mut state.seen_messages,
                 };
 
-                self.handle_effect(myself, handler_state, effect).await
+                let kind = effect.kind();
+                let start = Instant::now();
+
+                let result = self.handle_effect(myself, handler_state, effect).await;
+                self.metrics.effect_handled(kind, start.elapsed());
+
+                result
             }
-        )
+        );
+
+        self.emit_vote_tallies(state, vote_round);
+        self.observe_step(state);
+        self.auto_dump_trace(state).await;
+
+        result
+    }
+
+    /// Record the duration of the step which just ended, if any, to adapt its timeout.
+    ///
+    /// Steps are only observed while running, since replaying the WAL goes through them
+    /// much faster than the network does.
+    fn observe_step(&self, state: &mut State<Ctx>) {
+        if state.phase != Phase::Running {
+            return;
+        }
+
+        let (Some(adaptive), Some(consensus)) = (&mut state.adaptive_timeouts, &state.consensus)
+        else {
+            return;
+        };
+
+        let (round, step) = (consensus.round(), consensus.driver.step());
+
+        if let Some((ended, timeout)) = adaptive.observe(round, step, self.clock.now()) {
+            debug!(%round, step = ?ended, ?timeout, "Adapted the timeout of the step");
+            self.metrics.set_effective_timeout(ended, timeout);
+        }
+    }
+
+    /// Dump the trace of the flight recorder once the current height reaches the round
+    /// configured by `dump_after_rounds` without deciding.
+    async fn auto_dump_trace(&self, state: &mut State<Ctx>) {
+        let config = &self.consensus_config.flight_recorder;
+
+        let Some(dump_after_rounds) = config.dump_after_rounds else {
+            return;
+        };
+
+        let (height, round) = (state.height(), state.round());
+
+        if round.as_u32().is_none_or(|round| round < dump_after_rounds) {
+            return;
+        }
+
+        let Some(recorder) = &mut state.flight_recorder else {
+            return;
+        };
+
+        if !recorder.should_auto_dump() {
+            return;
+        }
+
+        let path = config.dump_dir.join(format!("trace-{height}-{round}.log"));
+
+        warn!(
+            %height, %round, path = %path.display(),
+            "Height reached the trace dump threshold without deciding, dumping the trace"
+        );
+
+        let result = match tokio::fs::create_dir_all(&config.dump_dir).await {
+            Ok(()) => write_trace(recorder, &path).await,
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = result {
+            error!(path = %path.display(), "Failed to dump the consensus trace: {e}");
+        }
+    }
+
+    /// Emit a [`Event::VoteTally`] event for each round whose tally changed since the last one
+    /// was emitted, ie. the round of the vote that was just processed, if any, and the current
+    /// round, in which our own votes are applied.
+    fn emit_vote_tallies(&self, state: &mut State<Ctx>, vote_round: Option<Round>) {
+        let Some(consensus) = state.consensus.as_ref() else {
+            return;
+        };
+
+        let height = consensus.height();
+        let current_round = consensus.round();
+
+        let rounds = vote_round
+            .into_iter()
+            .chain(Some(current_round))
+            .filter(|round| round.is_defined())
+            .unique();
+
+        for round in rounds {
+            let tally = consensus.vote_tally(round);
+
+            if tally == VoteTally::default() || state.vote_tallies.get(&round) == Some(&tally) {
+                continue;
+            }
+
+            state.vote_tallies.insert(round, tally);
+
+            self.tx_event.send(|| Event::VoteTally {
+                height,
+                round,
+                prevote_power: tally.prevote_power,
+                precommit_power: tally.precommit_power,
+            });
+        }
     }
 
     #[async_recursion]
@@ -374,6 +750,11 @@ where
         info!(count = %state.msg_buffer.len(), "Replaying buffered messages");
 
         while let Some(msg) = state.msg_buffer.pop() {
+            // The message may have been replayed from the WAL in the meantime
+            if self.is_seen_duplicate(state, &msg) {
+                continue;
+            }
+
             debug!("Replaying buffered message: {msg}");
 
             if let Err(e) = self.handle_msg(myself.clone(), state, msg).await {
@@ -391,18 +772,84 @@ where
         let is_restart = matches!(msg, Msg::RestartHeight(_, _));
 
         match msg {
-            Msg::StartHeight(height, params) | Msg::RestartHeight(height, params) => {
+            Msg::StartHeight(height, mut params) | Msg::RestartHeight(height, mut params) => {
+                // Apply the validator set update taking effect at this height, if any
+                state.validator_set_updates.retain(|h, _| *h >= height);
+
+                if let Some(certificate) = state.validator_set_updates.remove(&height) {
+                    let epoch = certificate.update.epoch;
+
+                    match self
+                        .ctx
+                        .apply_validator_set_update(&params.validator_set, &certificate.update)
+                    {
+                        Some(validator_set) => {
+                            info!(%height, %epoch, "Applied validator set update");
+
+                            params.validator_set = validator_set;
+                            self.tx_event
+                                .send(|| Event::ValidatorSetUpdateApplied(height, epoch));
+                        }
+                        None => {
+                            warn!(
+                                %height, %epoch,
+                                "Could not apply validator set update, using the validator set provided by the application"
+                            );
+                        }
+                    }
+                }
+
                 // Check that the validator set is provided and that it is not empty
                 if params.validator_set.count() == 0 {
                     return Err(eyre!("Validator set for height {height} is empty").into());
                 }
 
+                // Apply the consensus parameters overridden by the application for this height only
+                let overrides = self.get_consensus_params(height).await?;
+
+                if let Some(timeouts) = overrides.timeouts {
+                    params.timeouts = timeouts;
+                }
+
+                if !is_restart {
+                    if let Some(consensus) = &state.consensus {
+                        self.check_validator_set_change(
+                            height,
+                            consensus.validator_set(),
+                            &params.validator_set,
+                        )?;
+                    }
+
+                    self.emit_height_completed(state, height);
+                }
+
                 // Reset per-height state
                 state.pending_wal_entries.clear();
+                state.vote_tallies.clear();
+                state.round_alerts = RoundAlerts::default();
+                state.pending_value = None;
+                state.seen_messages.clear();
+                if let Some(recorder) = &mut state.flight_recorder {
+                    recorder.start_height(height, self.clock.now());
+                }
+                self.metrics.halted.set(0);
                 if let Some(handle) = state.wal_replay_timer.take() {
                     handle.abort();
                 }
 
+                // Drop the synced values processed for lower heights, and for a restarted height
+                // since the application has cleaned its state for that height
+                state
+                    .synced_values
+                    .retain(|h, _| *h > height || (*h == height && !is_restart));
+                state.future_vote_peers.retain(|h, _| *h > height);
+
+                // The application cleaned its state for a restarted height,
+                // and must be notified again about its decision
+                if is_restart {
+                    state.decided_values.forget_from(height);
+                }
+
                 // Initialize consensus state if this is the first height we start
                 if state.consensus.is_none() {
                     state.consensus = Some(ConsensusState::new(
@@ -415,6 +862,14 @@ where
                     ));
                 }
 
+                if let Some(consensus) = state.consensus.as_mut() {
+                    consensus.params.threshold_params = overrides
+                        .threshold_params
+                        .unwrap_or(self.params.threshold_params);
+                    consensus.params.value_payload =
+                        overrides.value_payload.unwrap_or(self.params.value_payload);
+                }
+
                 self.tx_event
                     .send(|| Event::StartedHeight(height, is_restart));
 
@@ -453,16 +908,22 @@ where
 
                     vec![]
                 } else {
-                    hang_on_failure(self.wal_fetch(height), |e| {
+                    let wal_entries = hang_on_failure(self.wal_fetch(height), |e| {
                         error!(%height, "Error when fetching WAL entries: {e}");
                         error!(%height, "Consensus may be in an inconsistent state after WAL fetch failure");
                     })
-                    .await
+                    .await;
+
+                    wal_entries
                 };
 
                 // Update the timeouts
                 state.timeouts = params.timeouts;
 
+                if let Some(adaptive) = &mut state.adaptive_timeouts {
+                    adaptive.reset();
+                }
+
                 let wal_replay_delay = self.consensus_config.wal_replay_delay;
                 // Note: both `is_restart` and non-validator paths yield empty
                 // `wal_entries`, so the delay is inherently skipped in those cases.
@@ -500,12 +961,16 @@ where
 
                     // Notify sync so it can start fetching certificates during the delay
                     let start_type = HeightStartType::from_is_restart(is_restart);
-                    self.sync.send(SyncMsg::StartedHeight(height, start_type));
+                    let mode = NodeMode::from_is_validator(state.is_validator);
+                    self.sync
+                        .send(SyncMsg::StartedHeight(height, start_type, mode));
+                    self.sync.send(SyncMsg::PeerStakes(state.peer_stakes()));
 
                     // Schedule the WAL replay delay timer
                     let actor = myself.clone();
+                    let sleep = self.clock.sleep(wal_replay_delay);
                     state.wal_replay_timer = Some(tokio::spawn(async move {
-                        tokio::time::sleep(wal_replay_delay).await;
+                        sleep.await;
                         let _ = actor.cast(Msg::WalReplayDelayElapsed);
                     }));
 
@@ -530,10 +995,13 @@ where
                 // NOTE: SyncMsg::Decided is sent separately via Msg::DecisionCommitted,
                 // which fires when the app confirms the decision commit (after Effect::Decide).
                 let start_type = HeightStartType::from_is_restart(is_restart);
+                let mode = NodeMode::from_is_validator(state.is_validator);
 
                 // If the WAL replay is not delayed, notify sync here.
                 // (The delay path at L472 already sends StartedHeight earlier.)
-                self.sync.send(SyncMsg::StartedHeight(height, start_type));
+                self.sync
+                    .send(SyncMsg::StartedHeight(height, start_type, mode));
+                self.sync.send(SyncMsg::PeerStakes(state.peer_stakes()));
 
                 // Process any buffered messages, now that we are in the `Running` phase
                 self.process_buffered_msgs(&myself, state, is_restart).await;
@@ -542,6 +1010,10 @@ where
             }
 
             Msg::ProposeValue(value) => {
+                if state.pending_value == Some((value.height, value.round)) {
+                    state.pending_value = None;
+                }
+
                 let result = self
                     .process_input(&myself, state, ConsensusInput::Propose(value.clone()))
                     .await;
@@ -559,6 +1031,19 @@ where
             }
 
             Msg::NetworkEvent(event) => {
+                // Drop the state kept for peers which disconnected or were banned
+                if let Some((peer_id, exit)) = event.peer_exit() {
+                    info!(%peer_id, ?exit, "Disconnected from peer");
+
+                    if state.connected_peers.contains(&peer_id) {
+                        self.metrics.connected_peers.dec();
+                        self.tx_event.send(|| Event::PeerDisconnected(peer_id));
+                    }
+
+                    state.forget_peer(&peer_id, exit);
+                    return Ok(());
+                }
+
                 match event {
                     NetworkEvent::Listening(address) => {
                         info!(%address, "Listening");
@@ -575,29 +1060,40 @@ where
                         }
                     }
 
-                    NetworkEvent::PeerConnected(peer_id) => {
+                    NetworkEvent::PeerConnected(peer_id, address) => {
                         if !state.connected_peers.insert(peer_id) {
                             // We already saw that peer, ignoring...
                             return Ok(());
                         }
 
-                        info!(%peer_id, total = %state.connected_peers.len(), "Connected to peer");
+                        info!(%peer_id, %address, total = %state.connected_peers.len(), "Connected to peer");
 
                         self.metrics.connected_peers.inc();
-                    }
-
-                    NetworkEvent::PeerDisconnected(peer_id) => {
-                        info!(%peer_id, "Disconnected from peer");
 
-                        if state.connected_peers.remove(&peer_id) {
-                            self.metrics.connected_peers.dec();
-                        }
+                        self.tx_event
+                            .send(|| Event::PeerConnected { peer_id, address });
                     }
 
                     NetworkEvent::Vote(from, vote) => {
+                        if !self.matches_chain_id(vote.chain_id()) {
+                            warn!(
+                                %from, chain_id = ?vote.chain_id(),
+                                "Rejecting vote for another chain"
+                            );
+                            return Ok(());
+                        }
+
                         self.tx_event
                             .send(|| Event::Received(SignedConsensusMsg::Vote(vote.clone())));
 
+                        if vote.height() > state.height() {
+                            record_future_vote_peer(
+                                &mut state.future_vote_peers,
+                                vote.height(),
+                                from,
+                            );
+                        }
+
                         if let Err(e) = self
                             .process_input(&myself, state, ConsensusInput::Vote(vote))
                             .await
@@ -607,11 +1103,19 @@ where
                     }
 
                     NetworkEvent::Proposal(from, proposal) => {
+                        if !self.matches_chain_id(proposal.chain_id()) {
+                            warn!(
+                                %from, chain_id = ?proposal.chain_id(),
+                                "Rejecting proposal for another chain"
+                            );
+                            return Ok(());
+                        }
+
                         self.tx_event.send(|| {
                             Event::Received(SignedConsensusMsg::Proposal(proposal.clone()))
                         });
 
-                        if self.params.value_payload.parts_only() {
+                        if self.value_payload(state).parts_only() {
                             error!(%from, "Properly configured peer should never send proposal messages in BlockPart mode");
                             return Ok(());
                         }
@@ -651,7 +1155,7 @@ where
                     }
 
                     NetworkEvent::ProposalPart(from, part) => {
-                        if self.params.value_payload.proposal_only() {
+                        if self.value_payload(state).proposal_only() {
                             error!(%from, "Properly configured peer should never send proposal part messages in Proposal mode");
                             return Ok(());
                         }
@@ -689,6 +1193,17 @@ where
                                     public_key = %hex::encode(&proof.public_key),
                                     "Valid validator proof received"
                                 );
+                                self.tx_event.send(|| Event::ValidatorProofVerified {
+                                    peer_id,
+                                    public_key: proof.public_key.clone(),
+                                });
+
+                                // Let sync weigh the peer by its voting power, if it is a validator
+                                state
+                                    .validator_peers
+                                    .insert(peer_id, proof.public_key.clone());
+                                self.sync.send(SyncMsg::PeerStakes(state.peer_stakes()));
+
                                 (
                                     ProofVerificationResult::Valid,
                                     Some(proof.public_key.clone()),
@@ -714,6 +1229,15 @@ where
                         }
                     }
 
+                    NetworkEvent::ValidatorSetUpdate(from, certificate) => {
+                        // Accepted updates are relayed by the gossip layer, no need to republish them
+                        let accepted = self.accept_validator_set_update(state, &certificate).await;
+
+                        if !accepted {
+                            debug!(%from, epoch = %certificate.update.epoch, "Ignoring validator set update");
+                        }
+                    }
+
                     _ => {}
                 }
 
@@ -772,10 +1296,41 @@ where
                 Ok(())
             }
 
-            Msg::DecisionCommitted(height) => {
+            Msg::ProcessSyncResponses(responses) => {
+                let count = responses.len();
+
+                debug!(%count, "Processing batch of sync responses");
+
+                if let Err(e) = self
+                    .process_input(
+                        &myself,
+                        state,
+                        ConsensusInput::ProcessCommitCertificateBatch(responses),
+                    )
+                    .await
+                {
+                    error!(%count, "Failed to process batch of sync responses: {e:?}");
+                }
+
+                Ok(())
+            }
+
+            Msg::SyncedValuesProcessed(values) => {
+                let height = state.height();
+
+                for value in values {
+                    if value.height >= height {
+                        state.synced_values.insert(value.height, value);
+                    }
+                }
+
+                Ok(())
+            }
+
+            Msg::DecisionCommitted(certificate) => {
                 // The application has confirmed that the decision has been committed.
                 // Notify the sync actor so it can advertise this height to peers.
-                self.sync.send(SyncMsg::Decided(height));
+                self.sync.send(SyncMsg::Decided(certificate));
                 Ok(())
             }
 
@@ -810,6 +1365,132 @@ where
 
                 Ok(())
             }
+
+            Msg::DumpTrace(path, reply_to) => {
+                let result = match &state.flight_recorder {
+                    Some(recorder) => {
+                        info!(
+                            path = %path.display(),
+                            entries = %recorder.num_entries(),
+                            "Dumping consensus trace"
+                        );
+                        write_trace(recorder, &path).await
+                    }
+                    None => Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "the flight recorder is disabled",
+                    )),
+                };
+
+                if let Err(e) = reply_to.send(result) {
+                    error!("Failed to reply with trace dump result: {e}");
+                }
+
+                Ok(())
+            }
+
+            Msg::PublishValidatorSetUpdate(certificate) => {
+                if !self.accept_validator_set_update(state, &certificate).await {
+                    warn!(
+                        epoch = %certificate.update.epoch,
+                        "Not publishing rejected validator set update"
+                    );
+
+                    return Ok(());
+                }
+
+                self.network
+                    .cast(NetworkMsg::PublishLivenessMsg(
+                        LivenessMsg::ValidatorSetUpdate(certificate),
+                    ))
+                    .map_err(|e| eyre!("Error when publishing validator set update: {e:?}"))?;
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Verify the given validator set update against the current validator set and,
+    /// if it is valid and newer than the updates seen so far, keep it until its
+    /// effective height is started.
+    ///
+    /// Returns whether the update was accepted.
+    async fn accept_validator_set_update(
+        &self,
+        state: &mut State<Ctx>,
+        certificate: &ValidatorSetUpdateCertificate<Ctx>,
+    ) -> bool {
+        let update = &certificate.update;
+        let (epoch, effective_height) = (update.epoch, update.effective_height);
+
+        let Some(consensus) = &state.consensus else {
+            return false;
+        };
+
+        if effective_height <= consensus.height() {
+            debug!(%epoch, %effective_height, "Validator set update is already in effect");
+            return false;
+        }
+
+        if state
+            .validator_set_epoch
+            .is_some_and(|latest| epoch <= latest)
+        {
+            debug!(%epoch, "Validator set update is not newer than the latest one");
+            return false;
+        }
+
+        if let Err(e) = self
+            .verifier
+            .verify_validator_set_update_certificate(
+                &self.ctx,
+                certificate,
+                consensus.validator_set(),
+                consensus.params.threshold_params,
+            )
+            .await
+        {
+            warn!(%epoch, %effective_height, "Invalid validator set update: {e}");
+            return false;
+        }
+
+        info!(%epoch, %effective_height, "Accepted validator set update");
+
+        state.validator_set_epoch = Some(epoch);
+        state
+            .validator_set_updates
+            .insert(effective_height, certificate.clone());
+
+        true
+    }
+
+    /// Span under which a message is handled: the root span of the trace of the height
+    /// the message belongs to, starting a new trace when moving to a new height,
+    /// or the span of the actor until consensus has started.
+    fn height_span(&self, state: &mut State<Ctx>, msg: &Msg<Ctx>) -> tracing::Span {
+        if state.consensus.is_none() && !matches!(msg, Msg::StartHeight(..)) {
+            return self.span.clone();
+        }
+
+        let height = span_height(state.height(), msg);
+
+        match &state.height_span {
+            Some((h, span)) if *h == height => span.clone(),
+            _ => {
+                let span = error_span!(parent: None, "height", %height);
+                span.follows_from(&self.span);
+                state.height_span = Some((height, span.clone()));
+                span
+            }
+        }
+    }
+
+    /// Whether the chain id of a message received from a peer matches the one of the context.
+    /// Messages are accepted regardless of their chain id if the context has none.
+    fn matches_chain_id(&self, chain_id: Option<&ChainId>) -> bool {
+        match self.ctx.chain_id() {
+            Some(expected) => chain_id == Some(expected),
+            None => true,
         }
     }
 
@@ -822,6 +1503,10 @@ where
         // Make sure the associated timer is cancelled
         state.timers.cancel(&timeout);
 
+        if timeout.kind == TimeoutKind::Propose {
+            self.check_missed_value(state, timeout.round);
+        }
+
         // Print debug information if the timeout is for a prevote or precommit
         if matches!(
             timeout.kind,
@@ -890,11 +1575,45 @@ where
             Err(e) => {
                 error!(%height, "Error when notifying WAL of started height: {e}");
 
-                self.tx_event.send(|| Event::WalResetError(Arc::new(e)));
+                self.tx_event.send(|| Event::WalResetError(Arc::new(e)));
+
+                Err(eyre!("Failed to fetch WAL entries for height {height}").into())
+            }
+        }
+    }
+
+    /// Whether the message is a vote or proposal of the current height received from the network,
+    /// which was already appended to the WAL. Such messages are dropped, as processing them again
+    /// would only produce redundant effects.
+    fn is_seen_duplicate(&self, state: &State<Ctx>, msg: &Msg<Ctx>) -> bool {
+        if state.seen_messages.is_empty() {
+            return false;
+        }
 
-                Err(eyre!("Failed to fetch WAL entries for height {height}").into())
-            }
+        let (kind, height, round, digest) = match msg {
+            Msg::NetworkEvent(NetworkEvent::Vote(_, vote)) => (
+                "vote",
+                vote.height(),
+                vote.round(),
+                MessageDigest::of_vote(vote),
+            ),
+            Msg::NetworkEvent(NetworkEvent::Proposal(_, proposal)) => (
+                "proposal",
+                proposal.height(),
+                proposal.round(),
+                MessageDigest::of_proposal(proposal),
+            ),
+            _ => return false,
+        };
+
+        if height != state.height() || !state.seen_messages.contains(round, digest) {
+            return false;
         }
+
+        debug!(%height, %round, "Dropping duplicate {kind} already seen at this height");
+
+        self.metrics.duplicate_suppressed(kind);
+        true
     }
 
     async fn wal_replay(
@@ -914,11 +1633,14 @@ where
 
         info!("Replaying {} WAL entries", entries.len());
 
-        self.tx_event
-            .send(|| Event::WalReplayBegin(height, entries.len()));
+        let total = entries.len();
+
+        self.tx_event.send(|| Event::WalReplayBegin(height, total));
+
+        let mut last_progress = None;
 
         // Replay WAL entries, stopping at the first corrupted entry
-        for entry in entries {
+        for (index, entry) in entries.into_iter().enumerate() {
             let entry = match entry {
                 Ok(entry) => entry,
                 Err(e) => {
@@ -936,6 +1658,40 @@ where
 
             self.tx_event.send(|| Event::WalReplayEntry(entry.clone()));
 
+            // Report progress on the first and last entries, and periodically in between
+            let now = self.clock.now();
+            let is_last = index + 1 == total;
+
+            if is_last
+                || last_progress.is_none_or(|last: Duration| {
+                    now.saturating_sub(last) >= WAL_REPLAY_PROGRESS_INTERVAL
+                })
+            {
+                last_progress = Some(now);
+
+                let entry_height = entry.height().unwrap_or(height);
+                let entry_round = entry.round();
+
+                debug!(
+                    %entry_height, %entry_round,
+                    "Replaying WAL entry {}/{total}", index + 1
+                );
+
+                self.tx_event.send(|| Event::WalReplayProgress {
+                    height,
+                    index,
+                    total,
+                    entry_height,
+                    entry_round,
+                });
+            }
+
+            // Digest of the replayed vote or proposal, recorded in the window once its effects are applied
+            let seen = match &entry {
+                WalEntry::ConsensusMsg(msg) => Some((msg.round(), MessageDigest::of(msg))),
+                _ => None,
+            };
+
             match entry {
                 WalEntry::ConsensusMsg(Vote(vote)) => {
                     info!("Replaying vote: {vote:?}");
@@ -1014,6 +1770,10 @@ where
                     }
                 }
             }
+
+            if let Some((round, digest)) = seen {
+                state.seen_messages.insert(round, digest);
+            }
         }
 
         self.tx_event.send(|| Event::WalReplayDone(state.height()));
@@ -1045,6 +1805,30 @@ where
         Ok(())
     }
 
+    /// If the propose timeout elapsed before the application replied with the value
+    /// requested for this round, count the round as missed and, if `cancel_get_value`
+    /// is enabled, notify the application so that it can stop building the value.
+    fn check_missed_value(&self, state: &mut State<Ctx>, round: Round) {
+        let height = state.height();
+
+        if state.pending_value != Some((height, round)) {
+            return;
+        }
+
+        state.pending_value = None;
+
+        warn!(%height, %round, "Application did not provide a value to propose in time");
+        self.metrics.missed_value_rounds.inc();
+
+        if !self.consensus_config.cancel_get_value {
+            return;
+        }
+
+        if let Err(e) = self.host.cast(HostMsg::CancelGetValue { height, round }) {
+            error!(%height, %round, "Error when cancelling the request for a value: {e:?}");
+        }
+    }
+
     async fn extend_vote(
         &self,
         height: Ctx::Height,
@@ -1110,8 +1894,12 @@ where
             return Ok(());
         }
 
+        let start = Instant::now();
         let result = ractor::call!(self.wal, WalMsg::Flush);
 
+        // Flushes happen while handling other effects, so this kind is nested in their duration
+        self.metrics.effect_handled("wal_flush", start.elapsed());
+
         match result {
             Ok(Ok(())) => {
                 // Success
@@ -1202,12 +1990,247 @@ where
                 &self.ctx,
                 certificate,
                 validator_set,
-                self.params.threshold_params,
+                consensus.params.threshold_params,
             )
             .await
             .is_ok()
     }
 
+    /// The value payload mode of the current height, which the application may have overridden.
+    fn value_payload(&self, state: &State<Ctx>) -> ValuePayload {
+        state
+            .consensus
+            .as_ref()
+            .map_or(self.params.value_payload, |consensus| {
+                consensus.params.value_payload
+            })
+    }
+
+    /// Ask the application whether it wants to override the consensus parameters of the height
+    /// about to be started, if enabled in the configuration.
+    async fn get_consensus_params(
+        &self,
+        height: Ctx::Height,
+    ) -> Result<HeightParamsOverride<Ctx>, ActorProcessingErr> {
+        if !self.consensus_config.consensus_params_overrides {
+            return Ok(HeightParamsOverride::default());
+        }
+
+        let overrides = ractor::call!(self.host, |reply_to| HostMsg::GetConsensusParams {
+            height,
+            reply_to
+        })?;
+
+        if !overrides.is_empty() {
+            info!(
+                %height,
+                threshold_params = ?overrides.threshold_params,
+                value_payload = ?overrides.value_payload,
+                timeouts = ?overrides.timeouts,
+                "Application overrode consensus parameters for this height"
+            );
+        }
+
+        Ok(overrides)
+    }
+
+    /// Ask the application whether it wants to override the timeouts of the round which just started,
+    /// if enabled in the configuration.
+    ///
+    /// The application is given [`TIMEOUT_OVERRIDES_DEADLINE`] to reply, so that a slow application
+    /// cannot stall consensus, after which the configured timeouts are used for the round.
+    async fn get_timeout_overrides(
+        &self,
+        height: Ctx::Height,
+        round: Round,
+        overrides: &mut TimeoutOverrides,
+    ) -> Result<(), ActorProcessingErr> {
+        overrides.reset(round);
+
+        if !self.consensus_config.timeout_overrides {
+            return Ok(());
+        }
+
+        let result = ractor::call_t!(
+            self.host,
+            |reply_to| HostMsg::GetTimeoutOverrides {
+                height,
+                round,
+                reply_to,
+            },
+            TIMEOUT_OVERRIDES_DEADLINE.as_millis() as u64
+        );
+
+        let durations = match result {
+            Ok(durations) => durations,
+            Err(ractor::RactorErr::Timeout) => {
+                warn!(
+                    %height, %round, deadline = ?TIMEOUT_OVERRIDES_DEADLINE,
+                    "Application did not reply with the timeout overrides in time, using the configured timeouts"
+                );
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if !durations.is_empty() {
+            debug!(%height, %round, ?durations, "Application overrode timeouts");
+        }
+
+        overrides.durations = durations;
+
+        Ok(())
+    }
+
+    /// Check how much voting power changes from the validator set of the previous height
+    /// to the validator set of the given height.
+    ///
+    /// If more than a third of the voting power changes, the validators of the previous height
+    /// can no longer vouch for the new validator set, so this is reported with a
+    /// [`Event::LargeValidatorSetChange`] event and in the metrics, and refused altogether
+    /// if `reject_large_validator_set_changes` is enabled.
+    fn check_validator_set_change(
+        &self,
+        height: Ctx::Height,
+        previous: &Ctx::ValidatorSet,
+        next: &Ctx::ValidatorSet,
+    ) -> Result<(), ActorProcessingErr> {
+        if previous == next {
+            return Ok(());
+        }
+
+        let change = VotingPowerChange::between::<Ctx>(previous, next);
+
+        if !change.exceeds(ThresholdParam::F_PLUS_ONE) {
+            return Ok(());
+        }
+
+        let (changed, total) = (change.changed(), change.total);
+
+        self.metrics.large_validator_set_changes.inc();
+        self.tx_event.send(|| Event::LargeValidatorSetChange {
+            height,
+            changed,
+            total,
+        });
+
+        if self.consensus_config.reject_large_validator_set_changes {
+            return Err(eyre!(
+                "More than a third of the voting power changes at height {height} \
+                 ({changed} out of {total}), refusing to start the height"
+            )
+            .into());
+        }
+
+        warn!(%height, %changed, %total, "More than a third of the voting power changes at this height");
+
+        Ok(())
+    }
+
+    /// Emit a [`Event::HeightCompleted`] event with the participation of the validators in the
+    /// previous height, and record it in the metrics, if it was decided before starting the given height.
+    fn emit_height_completed(&self, state: &State<Ctx>, next_height: Ctx::Height) {
+        let Some(consensus) = state.consensus.as_ref() else {
+            return;
+        };
+
+        let height = consensus.height();
+        if height >= next_height {
+            return;
+        }
+
+        let Some((round, _)) = consensus.decided_value() else {
+            return;
+        };
+
+        let participation = consensus.participation();
+
+        let absent_validators = participation
+            .iter()
+            .filter(|(_, participation)| participation.is_absent())
+            .map(|(address, _)| address.clone())
+            .collect::<Vec<_>>();
+
+        for (address, participation) in &participation {
+            self.metrics
+                .record_participation(address, participation.is_absent());
+        }
+
+        if !absent_validators.is_empty() {
+            debug!(%height, ?absent_validators, "No vote received from some validators");
+        }
+
+        self.tx_event.send(|| Event::HeightCompleted {
+            height,
+            round,
+            participation,
+            absent_validators,
+        });
+    }
+
+    /// Raise the round alert and halt participation once the current height reaches the rounds
+    /// configured by `max_rounds_alert` and `max_rounds_halt` without deciding.
+    fn check_round_alerts(
+        &self,
+        height: Ctx::Height,
+        round: Round,
+        alerts: &mut RoundAlerts,
+    ) -> Result<(), ActorProcessingErr> {
+        let Some(round_number) = round.as_u32() else {
+            return Ok(());
+        };
+
+        let reached = |max_rounds: Option<u32>| max_rounds.is_some_and(|max| round_number >= max);
+
+        if !alerts.raised && reached(self.consensus_config.max_rounds_alert) {
+            alerts.raised = true;
+
+            warn!(%height, %round, "Height reached the round alert threshold without deciding");
+
+            self.metrics.round_alerts.inc();
+            self.tx_event.send(|| Event::RoundAlert(height, round));
+            self.notify_round_alert(height, round, false)?;
+        }
+
+        if !alerts.halted && reached(self.consensus_config.max_rounds_halt) {
+            alerts.halted = true;
+
+            error!(
+                %height, %round,
+                "Height reached the round halt threshold without deciding, \
+                 halting participation until the next height"
+            );
+
+            self.metrics.halted.set(1);
+            self.tx_event
+                .send(|| Event::ParticipationHalted(height, round));
+            self.notify_round_alert(height, round, true)?;
+        }
+
+        Ok(())
+    }
+
+    fn notify_round_alert(
+        &self,
+        height: Ctx::Height,
+        round: Round,
+        halted: bool,
+    ) -> Result<(), ActorProcessingErr> {
+        if !self.consensus_config.notify_round_alerts {
+            return Ok(());
+        }
+
+        self.host
+            .cast(HostMsg::RoundAlert {
+                height,
+                round,
+                halted,
+            })
+            .map_err(|e| eyre!("Error when sending round alert to host: {e:?}"))?;
+
+        Ok(())
+    }
+
     async fn handle_effect(
         &self,
         myself: &ActorRef<Msg<Ctx>>,
@@ -1226,7 +2249,14 @@ where
             }
 
             Effect::ScheduleTimeout(timeout, r) => {
-                let duration = state.timeouts.duration_for(timeout);
+                if state.round_alerts.halted
+                    && !matches!(timeout.kind, TimeoutKind::FinalizeHeight(_))
+                {
+                    debug!(?timeout, "Participation halted, not scheduling timeout");
+                    return Ok(r.resume_with(()));
+                }
+
+                let duration = state.timeout_duration(timeout);
                 state.timers.start_timer(timeout, duration);
 
                 Ok(r.resume_with(()))
@@ -1248,9 +2278,18 @@ where
                     let _ = myself.cast(Msg::ReceivedProposedValue(value, ValueOrigin::Consensus));
                 }
 
+                self.get_timeout_overrides(height, round, state.timeout_overrides)
+                    .await?;
+
                 self.tx_event
                     .send(|| Event::StartedRound(height, round, proposer, role));
 
+                self.check_round_alerts(height, round, state.round_alerts)?;
+
+                if state.round_alerts.halted {
+                    state.timers.cancel_all();
+                }
+
                 Ok(r.resume_with(()))
             }
 
@@ -1312,6 +2351,20 @@ where
                 Ok(r.resume_with(result))
             }
 
+            Effect::VerifyCommitCertificates(certificates, validator_set, thresholds, r) => {
+                let results = self
+                    .verifier
+                    .verify_commit_certificates(
+                        &self.ctx,
+                        &certificates,
+                        &validator_set,
+                        thresholds,
+                    )
+                    .await;
+
+                Ok(r.resume_with(results))
+            }
+
             Effect::VerifyPolkaCertificate(certificate, validator_set, thresholds, r) => {
                 let result = self
                     .verifier
@@ -1369,6 +2422,11 @@ where
             }
 
             Effect::PublishConsensusMsg(msg, r) => {
+                if state.round_alerts.halted {
+                    debug!("Participation halted, not publishing consensus message: {msg:?}");
+                    return Ok(r.resume_with(()));
+                }
+
                 // Sync the WAL to disk before we broadcast the message
                 // NOTE: The message has already been append to the WAL by the `WalAppend` effect.
                 self.wal_flush(state.phase, state.is_validator).await?;
@@ -1384,6 +2442,11 @@ where
             }
 
             Effect::PublishLivenessMsg(msg, r) => {
+                if state.round_alerts.halted {
+                    debug!("Participation halted, not publishing liveness message: {msg:?}");
+                    return Ok(r.resume_with(()));
+                }
+
                 match msg {
                     LivenessMsg::Vote(ref msg) => {
                         self.tx_event.send(|| Event::RepublishVote(msg.clone()));
@@ -1396,6 +2459,8 @@ where
                         self.tx_event
                             .send(|| Event::SkipRoundCertificate(certificate.clone()));
                     }
+                    // Validator set updates are not surfaced as events when published
+                    LivenessMsg::ValidatorSetUpdate(_) => {}
                 }
 
                 self.network
@@ -1406,6 +2471,10 @@ where
             }
 
             Effect::RepublishVote(msg, r) => {
+                if state.round_alerts.halted {
+                    return Ok(r.resume_with(()));
+                }
+
                 // Notify any subscribers that we are about to rebroadcast a vote
                 self.tx_event.send(|| Event::RepublishVote(msg.clone()));
 
@@ -1417,6 +2486,10 @@ where
             }
 
             Effect::RepublishRoundCertificate(certificate, r) => {
+                if state.round_alerts.halted {
+                    return Ok(r.resume_with(()));
+                }
+
                 // Notify any subscribers that we are about to rebroadcast a round certificate
                 self.tx_event
                     .send(|| Event::RebroadcastRoundCertificate(certificate.clone()));
@@ -1433,7 +2506,13 @@ where
             }
 
             Effect::GetValue(height, round, timeout, r) => {
-                let timeout_duration = state.timeouts.duration_for(timeout);
+                if state.round_alerts.halted {
+                    debug!(%height, %round, "Participation halted, not proposing a value");
+                    return Ok(r.resume_with(()));
+                }
+
+                let timeout_duration = state.timeout_duration(timeout);
+                *state.pending_value = Some((height, round));
 
                 self.get_value(myself, height, round, timeout_duration)
                     .map_err(|e| {
@@ -1457,9 +2536,77 @@ where
                 Ok(r.resume_with(()))
             }
 
+            Effect::ValidateValue(height, round, valid_round, proposer, value, r) => {
+                self.host
+                    .call_and_forward(
+                        |reply_to| HostMsg::ReceivedProposal {
+                            height,
+                            round,
+                            valid_round,
+                            proposer,
+                            value,
+                            reply_to,
+                        },
+                        myself,
+                        |value| Msg::ReceivedProposedValue(value, ValueOrigin::Consensus),
+                        None,
+                    )
+                    .map_err(|e| {
+                        eyre!("Error when asking host to validate proposed value: {e:?}")
+                    })?;
+
+                Ok(r.resume_with(()))
+            }
+
             Effect::Decide(certificate, extensions, r) => {
                 assert!(!certificate.commit_signatures.is_empty());
 
+                // Both consensus and sync may decide the same height,
+                // in which case the application must only be notified once
+                match state
+                    .decided_values
+                    .record(certificate.height, certificate.value_id.clone())
+                {
+                    Decision::New => {}
+                    Decision::Duplicate => {
+                        warn!(
+                            height = %certificate.height,
+                            round = %certificate.round,
+                            value_id = %certificate.value_id,
+                            "Height was already decided, not notifying the application again"
+                        );
+
+                        self.tx_event.send(|| Event::DuplicateDecisionSuppressed {
+                            height: certificate.height,
+                            round: certificate.round,
+                            value_id: certificate.value_id.clone(),
+                        });
+
+                        return Ok(r.resume_with(()));
+                    }
+                    // Deciding two values at the same height violates the safety of consensus,
+                    // which cannot continue without risking to make things worse
+                    Decision::Conflicting(decided) => {
+                        error!(
+                            height = %certificate.height,
+                            round = %certificate.round,
+                            value_id = %certificate.value_id,
+                            %decided,
+                            "Height was already decided with another value, stopping consensus"
+                        );
+
+                        self.tx_event.send(|| Event::ConflictingDecision {
+                            height: certificate.height,
+                            round: certificate.round,
+                            value_id: certificate.value_id.clone(),
+                            decided: decided.clone(),
+                        });
+
+                        error!("Critical consensus failure, hanging to prevent safety violations. Manual intervention required!");
+                        hang().await
+                    }
+                }
+
                 // Sync the WAL to disk before we decide the value
                 self.wal_flush(state.phase, state.is_validator).await?;
 
@@ -1468,7 +2615,7 @@ where
                     commit_certificate: certificate.clone(),
                 });
 
-                let height = certificate.height;
+                let committed = certificate.clone();
 
                 // Notify the host about the decided value and wait for commit confirmation.
                 // When the app replies, the forwarded DecisionCommitted message will notify
@@ -1481,7 +2628,7 @@ where
                             reply_to,
                         },
                         myself,
-                        move |()| Msg::<Ctx>::DecisionCommitted(height),
+                        move |()| Msg::<Ctx>::DecisionCommitted(committed),
                         None,
                     )
                     .map_err(|e| eyre!("Error when sending decided value to host: {e:?}"))?;
@@ -1579,6 +2726,8 @@ where
 
                     self.sync
                         .send(SyncMsg::InvalidValue(peer, certificate.height));
+                    self.tx_event
+                        .send(|| Event::InvalidSyncedValue(certificate.height));
                 } else {
                     self.sync.send(SyncMsg::ValueProcessingError(peer, height));
                 }
@@ -1586,47 +2735,128 @@ where
                 Ok(r.resume_with(()))
             }
 
+            Effect::FutureHeightObserved(height, evidence, r) => {
+                // The peers which relayed votes for that height or above are most likely
+                // at that height, and have therefore decided the heights below it.
+                let peers = state
+                    .future_vote_peers
+                    .range(height..)
+                    .flat_map(|(_, peers)| peers.iter().copied())
+                    .collect::<BTreeSet<_>>();
+
+                state.future_vote_peers.retain(|h, _| *h > height);
+
+                warn!(
+                    %height,
+                    validators = evidence.len(),
+                    peers = peers.len(),
+                    "Validators are voting at a higher height, falling behind"
+                );
+
+                self.sync.send(SyncMsg::FutureHeightObserved(
+                    height,
+                    peers.into_iter().collect(),
+                ));
+
+                self.tx_event
+                    .send(|| Event::FutureHeightObserved(height, evidence));
+
+                Ok(r.resume_with(()))
+            }
+
             Effect::ValidSyncValue(value, proposer, r) => {
                 let certificate_height = value.certificate.height;
                 let certificate_round = value.certificate.round;
 
-                let sync = Arc::clone(&self.sync);
-                let sync_on_none = Arc::clone(&self.sync);
-                let myself = myself.clone();
-
-                cast_option_and_handle(
-                    &self.host,
-                    |reply_to| HostMsg::ProcessSyncedValue {
-                        height: certificate_height,
-                        round: certificate_round,
-                        proposer,
-                        value_bytes: value.value_bytes,
-                        reply_to,
-                    },
-                    move |proposed| {
-                        if proposed.validity == Validity::Invalid
-                            || proposed.value.id() != value.certificate.value_id
+                // Use the outcome of the value if it was already processed as part of a batch,
+                // unless the application assumed another proposer than the one of the round.
+                let processed = state
+                    .synced_values
+                    .remove(&certificate_height)
+                    .filter(|processed| {
+                        processed.round == certificate_round
+                            && processed.value_bytes == value.value_bytes
+                    })
+                    .and_then(|processed| match processed.outcome {
+                        Some(SyncedValueOutcome::Valid(proposed))
+                            if proposed.proposer != proposer =>
                         {
-                            sync.send(SyncMsg::InvalidValue(value.peer, certificate_height));
+                            debug!(
+                                height = %certificate_height,
+                                round = %certificate_round,
+                                "Synced value was processed with another proposer"
+                            );
+                            None
                         }
+                        outcome => Some(outcome),
+                    });
+
+                match processed {
+                    Some(Some(outcome)) => {
+                        on_synced_value_outcome(
+                            &self.sync,
+                            &self.tx_event,
+                            myself,
+                            value.peer,
+                            &value.certificate,
+                            outcome,
+                        );
+                    }
 
-                        let _ = myself.cast(Msg::<Ctx>::ReceivedProposedValue(
-                            proposed,
-                            ValueOrigin::Sync,
-                        ));
-                    },
-                    move || {
-                        sync_on_none.send(SyncMsg::ValueProcessingError(
+                    Some(None) => {
+                        self.sync.send(SyncMsg::ValueProcessingError(
                             value.peer,
                             certificate_height,
                         ));
-                    },
-                )?;
+                    }
+
+                    None => {
+                        let sync = Arc::clone(&self.sync);
+                        let sync_on_none = Arc::clone(&self.sync);
+                        let tx_event = self.tx_event.clone();
+                        let myself = myself.clone();
+
+                        cast_result_option_and_handle(
+                            &self.host,
+                            |reply_to| HostMsg::ProcessSyncedValue {
+                                height: certificate_height,
+                                round: certificate_round,
+                                proposer,
+                                value_bytes: value.value_bytes,
+                                reply_to,
+                            },
+                            move |outcome| {
+                                on_synced_value_outcome(
+                                    &sync,
+                                    &tx_event,
+                                    &myself,
+                                    value.peer,
+                                    &value.certificate,
+                                    outcome,
+                                );
+                            },
+                            move || {
+                                sync_on_none.send(SyncMsg::ValueProcessingError(
+                                    value.peer,
+                                    certificate_height,
+                                ));
+                            },
+                        )?;
+                    }
+                }
 
                 Ok(r.resume_with(()))
             }
 
             Effect::WalAppend(height, entry, r) => {
+                // Consensus only appends the messages of the current height,
+                // while the ones replayed from the WAL are recorded by the replay
+                if let WalEntry::ConsensusMsg(msg) = &entry {
+                    state
+                        .seen_messages
+                        .insert(msg.round(), MessageDigest::of(msg));
+                }
+
                 self.wal_append(height, entry, state.phase, state.is_validator)
                     .await?;
                 Ok(r.resume_with(()))
@@ -1660,15 +2890,38 @@ where
             .cast(NetworkMsg::Subscribe(Box::new(myself.clone())))?;
 
         Ok(State {
-            timers: Timers::new(Box::new(myself)),
+            timers: Timers::with_clock(Box::new(myself), Arc::clone(&self.clock)),
             timeouts: Ctx::Timeouts::default(),
             consensus: None,
             connected_peers: BTreeSet::new(),
+            validator_peers: BTreeMap::new(),
             phase: Phase::Unstarted,
             is_validator: false,
             msg_buffer: MessageBuffer::new(MAX_BUFFER_SIZE),
             pending_wal_entries: Vec::new(),
             wal_replay_timer: None,
+            vote_tallies: BTreeMap::new(),
+            timeout_overrides: TimeoutOverrides::new(),
+            round_alerts: RoundAlerts::default(),
+            pending_value: None,
+            synced_values: BTreeMap::new(),
+            future_vote_peers: BTreeMap::new(),
+            validator_set_updates: BTreeMap::new(),
+            validator_set_epoch: None,
+            height_span: None,
+            flight_recorder: self.consensus_config.flight_recorder.enabled.then(|| {
+                FlightRecorder::new(
+                    self.consensus_config.flight_recorder.max_heights,
+                    self.consensus_config.flight_recorder.max_entries_per_height,
+                )
+            }),
+            adaptive_timeouts: self
+                .consensus_config
+                .adaptive_timeouts
+                .enabled
+                .then(|| AdaptiveTimeouts::new(self.consensus_config.adaptive_timeouts.clone())),
+            decided_values: DecidedValues::default(),
+            seen_messages: SeenMessages::default(),
         })
     }
 
@@ -1691,7 +2944,8 @@ where
 
     #[tracing::instrument(
         name = "consensus",
-        parent = &self.span,
+        parent = self.height_span(state, &msg),
+        follows_from = [tracing::Span::current()],
         skip_all,
         fields(
             height = %span_height(state.height(), &msg),
@@ -1704,13 +2958,16 @@ where
         msg: Msg<Ctx>,
         state: &mut State<Ctx>,
     ) -> Result<(), ActorProcessingErr> {
+        if self.is_seen_duplicate(state, &msg) {
+            return Ok(());
+        }
+
         if state.phase != Phase::Running && should_buffer(&msg) {
             // If sync delivers a certificate while we wait, verify it.
             // If valid, skip WAL replay entirely. If invalid, let the timer expire normally.
-            if state.phase == Phase::WaitingForSync && matches!(&msg, Msg::ProcessSyncResponse(_)) {
-                let is_valid_certificate = if let Msg::ProcessSyncResponse(ref response) = msg {
-                    self.verify_sync_certificate(state, &response.certificate)
-                        .await
+            if state.phase == Phase::WaitingForSync && sync_certificate(&msg).is_some() {
+                let is_valid_certificate = if let Some(certificate) = sync_certificate(&msg) {
+                    self.verify_sync_certificate(state, certificate).await
                 } else {
                     false
                 };
@@ -1759,18 +3016,97 @@ where
     }
 }
 
+/// Handle the outcome of the processing of a synced value by the application.
+fn on_synced_value_outcome<Ctx: Context>(
+    sync: &OutputPort<SyncMsg<Ctx>>,
+    tx_event: &TxEvent<Ctx>,
+    myself: &ActorRef<Msg<Ctx>>,
+    peer: PeerId,
+    certificate: &CommitCertificate<Ctx>,
+    outcome: SyncedValueOutcome<Ctx>,
+) {
+    // A value reported as valid may still be marked as invalid,
+    // or not be the value decided by the commit certificate
+    let outcome = match outcome {
+        SyncedValueOutcome::Valid(proposed) if proposed.validity == Validity::Invalid => {
+            SyncedValueOutcome::Invalid {
+                reason: "value is marked as invalid".to_string(),
+            }
+        }
+        SyncedValueOutcome::Valid(proposed) if proposed.value.id() != certificate.value_id => {
+            SyncedValueOutcome::Invalid {
+                reason: format!(
+                    "value {} does not match the value {} of the commit certificate",
+                    proposed.value.id(),
+                    certificate.value_id
+                ),
+            }
+        }
+        outcome => outcome,
+    };
+
+    match outcome {
+        SyncedValueOutcome::Valid(proposed) => {
+            let _ = myself.cast(Msg::ReceivedProposedValue(proposed, ValueOrigin::Sync));
+        }
+        SyncedValueOutcome::Invalid { reason } => {
+            warn!(
+                %peer,
+                height = %certificate.height,
+                "Application rejected synced value: {reason}"
+            );
+
+            sync.send(SyncMsg::InvalidValue(peer, certificate.height));
+            tx_event.send(|| Event::InvalidSyncedValue(certificate.height));
+        }
+    }
+}
+
+/// Record the peer from which a vote for a height higher than the current one was received.
+fn record_future_vote_peer<Height: Ord>(
+    future_vote_peers: &mut BTreeMap<Height, BTreeSet<PeerId>>,
+    height: Height,
+    peer: PeerId,
+) {
+    if !future_vote_peers.contains_key(&height)
+        && future_vote_peers.len() >= MAX_FUTURE_VOTE_HEIGHTS
+    {
+        match future_vote_peers.last_key_value() {
+            Some((highest, _)) if *highest > height => {
+                future_vote_peers.pop_last();
+            }
+            _ => return,
+        }
+    }
+
+    future_vote_peers.entry(height).or_default().insert(peer);
+}
+
 fn should_buffer<Ctx: Context>(msg: &Msg<Ctx>) -> bool {
     !matches!(
         msg,
         Msg::StartHeight(..)
             | Msg::DecisionCommitted(..)
             | Msg::WalReplayDelayElapsed
+            | Msg::DumpTrace(..)
             | Msg::NetworkEvent(NetworkEvent::Listening(..))
             | Msg::NetworkEvent(NetworkEvent::PeerConnected(..))
             | Msg::NetworkEvent(NetworkEvent::PeerDisconnected(..))
+            | Msg::NetworkEvent(NetworkEvent::PeerBanned(..))
     )
 }
 
+/// The certificate of the first value carried by a sync response, if any
+fn sync_certificate<Ctx: Context>(msg: &Msg<Ctx>) -> Option<&CommitCertificate<Ctx>> {
+    match msg {
+        Msg::ProcessSyncResponse(response) => Some(&response.certificate),
+        Msg::ProcessSyncResponses(responses) => {
+            responses.first().map(|response| &response.certificate)
+        }
+        _ => None,
+    }
+}
+
 /// Use the height we are about to start instead of the consensus state height
 /// for the tracing span of the Consensus actor when starting a new height.
 fn span_height<Ctx: Context>(height: Ctx::Height, msg: &Msg<Ctx>) -> Ctx::Height {
@@ -1791,6 +3127,10 @@ fn span_round<Ctx: Context>(round: Round, msg: &Msg<Ctx>) -> Round {
     }
 }
 
+async fn write_trace<H: fmt::Display>(recorder: &FlightRecorder<H>, path: &Path) -> io::Result<()> {
+    tokio::fs::write(path, recorder.to_string()).await
+}
+
 async fn hang_on_failure<A, E>(
     f: impl Future<Output = Result<A, E>>,
     on_error: impl FnOnce(E),
@@ -1807,7 +3147,8 @@ async fn hang_on_failure<A, E>(
 
 /// Hangs the consensus actor indefinitely to prevent safety violations.
 ///
-/// This is called when WAL operations fail and consensus cannot safely continue.
+/// This is called when WAL operations fail or a height is decided with two different values,
+/// and consensus cannot safely continue.
 /// The node operator should investigate the WAL issue and restart the node
 /// only after ensuring data integrity.
 async fn hang() -> ! {
@@ -0,0 +1,195 @@
+//! Timeouts of the propose, prevote and precommit steps adapted to the observed durations of these steps.
+//!
+//! The consensus actor reports the round and step consensus is in after each input it processed.
+//! Whenever a step ends, its duration is recorded, and the timeout of that step is recomputed
+//! as a percentile of its last durations, scaled by the multiplier and bounded by the minimum
+//! and maximum set in the [configuration](AdaptiveTimeoutsConfig).
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use malachitebft_config::AdaptiveTimeoutsConfig;
+use malachitebft_core_driver::Step;
+use malachitebft_core_types::{Round, TimeoutKind};
+
+/// Last durations of a step, and the timeout computed from them.
+#[derive(Debug, Default)]
+struct StepDurations {
+    durations: VecDeque<Duration>,
+    timeout: Option<Duration>,
+}
+
+impl StepDurations {
+    fn record(&mut self, duration: Duration, config: &AdaptiveTimeoutsConfig) -> Duration {
+        while self.durations.len() >= config.window.max(1) {
+            self.durations.pop_front();
+        }
+
+        self.durations.push_back(duration);
+
+        let mut sorted = self.durations.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+
+        // Nearest-rank percentile
+        let rank = (config.percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+        let percentile = sorted[rank.clamp(1, sorted.len()) - 1];
+
+        let timeout = percentile
+            .mul_f64(config.multiplier)
+            .clamp(config.min, config.max);
+
+        self.timeout = Some(timeout);
+        timeout
+    }
+}
+
+/// Timeouts computed from the durations of the steps of the last rounds.
+#[derive(Debug)]
+pub struct AdaptiveTimeouts {
+    config: AdaptiveTimeoutsConfig,
+    propose: StepDurations,
+    prevote: StepDurations,
+    precommit: StepDurations,
+
+    /// Round and step consensus is in, and when it entered them
+    current: Option<(Round, Step, Duration)>,
+}
+
+impl AdaptiveTimeouts {
+    pub fn new(config: AdaptiveTimeoutsConfig) -> Self {
+        Self {
+            config,
+            propose: StepDurations::default(),
+            prevote: StepDurations::default(),
+            precommit: StepDurations::default(),
+            current: None,
+        }
+    }
+
+    /// Timeout at round 0 of the given kind, or `None` if it is not adaptive
+    /// or if the corresponding step never ended yet.
+    pub fn timeout(&self, kind: TimeoutKind) -> Option<Duration> {
+        match kind {
+            TimeoutKind::Propose => self.propose.timeout,
+            TimeoutKind::Prevote => self.prevote.timeout,
+            TimeoutKind::Precommit => self.precommit.timeout,
+            TimeoutKind::Rebroadcast | TimeoutKind::FinalizeHeight(_) => None,
+        }
+    }
+
+    /// Observe the round and step consensus is in.
+    ///
+    /// If this ends the propose, prevote or precommit step, record its duration
+    /// and return the step along with its new timeout.
+    pub fn observe(&mut self, round: Round, step: Step, now: Duration) -> Option<(Step, Duration)> {
+        if let Some((current_round, current_step, _)) = self.current {
+            if current_round == round && current_step == step {
+                return None;
+            }
+        }
+
+        let (_, ended, entered_at) = self.current.replace((round, step, now))?;
+        let duration = now.saturating_sub(entered_at);
+
+        let durations = match ended {
+            Step::Propose => &mut self.propose,
+            Step::Prevote => &mut self.prevote,
+            Step::Precommit => &mut self.precommit,
+            Step::Unstarted | Step::Commit => return None,
+        };
+
+        Some((ended, durations.record(duration, &self.config)))
+    }
+
+    /// Forget the step consensus is in, eg. when starting a new height,
+    /// so that the time spent between two heights is not counted in any step.
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AdaptiveTimeoutsConfig {
+        AdaptiveTimeoutsConfig {
+            enabled: true,
+            window: 10,
+            percentile: 90.0,
+            multiplier: 2.0,
+            min: Duration::from_millis(100),
+            max: Duration::from_secs(5),
+        }
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    /// Go through the propose step of the given rounds, taking the given time each.
+    fn propose_steps(timeouts: &mut AdaptiveTimeouts, rounds: u32, duration: Duration) {
+        let mut now = ms(0);
+
+        for round in 0..rounds {
+            timeouts.observe(Round::new(round), Step::Propose, now);
+            now += duration;
+            timeouts.observe(Round::new(round), Step::Prevote, now);
+            now += ms(1);
+        }
+    }
+
+    #[test]
+    fn timeouts_follow_the_step_durations() {
+        let mut timeouts = AdaptiveTimeouts::new(config());
+        assert_eq!(timeouts.timeout(TimeoutKind::Propose), None);
+
+        propose_steps(&mut timeouts, 10, ms(200));
+        assert_eq!(timeouts.timeout(TimeoutKind::Propose), Some(ms(400)));
+        assert_eq!(timeouts.timeout(TimeoutKind::Precommit), None);
+        assert_eq!(timeouts.timeout(TimeoutKind::Rebroadcast), None);
+
+        // Only the last `window` durations are taken into account
+        propose_steps(&mut timeouts, 10, ms(300));
+        assert_eq!(timeouts.timeout(TimeoutKind::Propose), Some(ms(600)));
+    }
+
+    #[test]
+    fn timeouts_are_bounded() {
+        let mut timeouts = AdaptiveTimeouts::new(config());
+
+        propose_steps(&mut timeouts, 10, ms(10));
+        assert_eq!(timeouts.timeout(TimeoutKind::Propose), Some(ms(100)));
+
+        propose_steps(&mut timeouts, 10, Duration::from_secs(10));
+        assert_eq!(
+            timeouts.timeout(TimeoutKind::Propose),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn only_ended_steps_are_recorded() {
+        let mut timeouts = AdaptiveTimeouts::new(config());
+
+        // Staying in the same step records nothing
+        timeouts.observe(Round::new(0), Step::Prevote, ms(0));
+        assert_eq!(timeouts.observe(Round::new(0), Step::Prevote, ms(50)), None);
+
+        assert_eq!(
+            timeouts.observe(Round::new(0), Step::Precommit, ms(300)),
+            Some((Step::Prevote, ms(600)))
+        );
+
+        // Neither the commit step nor the time between heights are recorded
+        assert_eq!(
+            timeouts.observe(Round::new(0), Step::Commit, ms(400)),
+            Some((Step::Precommit, ms(200)))
+        );
+        timeouts.reset();
+        assert_eq!(
+            timeouts.observe(Round::new(0), Step::Propose, ms(900)),
+            None
+        );
+    }
+}
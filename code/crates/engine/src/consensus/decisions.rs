@@ -0,0 +1,145 @@
+//! Guard against notifying the application twice about the decision of a height.
+//!
+//! A height can be decided both by consensus and from a value received via sync,
+//! for instance when the commit certificate of a synced value arrives while consensus
+//! is about to decide the same height. The consensus actor records every decision
+//! before notifying the application about it, so that it is notified at most once per height.
+
+use std::collections::BTreeMap;
+
+/// Number of decided heights to remember.
+const HISTORY: usize = 16;
+
+/// Outcome of recording a decision.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Decision<V> {
+    /// The height was not decided yet.
+    New,
+    /// The height was already decided with the same value.
+    Duplicate,
+    /// The height was already decided with another value, which is returned.
+    Conflicting(V),
+}
+
+/// Values decided at the last heights.
+#[derive(Debug)]
+pub struct DecidedValues<H, V> {
+    decided: BTreeMap<H, V>,
+}
+
+impl<H, V> Default for DecidedValues<H, V> {
+    fn default() -> Self {
+        Self {
+            decided: BTreeMap::new(),
+        }
+    }
+}
+
+impl<H, V> DecidedValues<H, V>
+where
+    H: Ord + Copy,
+    V: Clone + PartialEq,
+{
+    /// Record that the given value was decided at the given height,
+    /// unless a value was already decided at that height.
+    pub fn record(&mut self, height: H, value_id: V) -> Decision<V> {
+        if let Some(decided) = self.decided.get(&height) {
+            return if *decided == value_id {
+                Decision::Duplicate
+            } else {
+                Decision::Conflicting(decided.clone())
+            };
+        }
+
+        self.decided.insert(height, value_id);
+
+        while self.decided.len() > HISTORY {
+            self.decided.pop_first();
+        }
+
+        Decision::New
+    }
+
+    /// Forget the decisions of the given height and higher ones,
+    /// eg. when the application restarts a height after having cleaned its state for it.
+    pub fn forget_from(&mut self, height: H) {
+        self.decided.retain(|h, _| *h < height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_decision_is_new() {
+        let mut decided = DecidedValues::default();
+
+        assert_eq!(decided.record(1, "a"), Decision::New);
+        assert_eq!(decided.record(2, "b"), Decision::New);
+    }
+
+    #[test]
+    fn decision_by_sync_after_consensus_is_suppressed() {
+        let mut decided = DecidedValues::default();
+
+        // Consensus decides the height
+        assert_eq!(decided.record(5, "a"), Decision::New);
+
+        // The same value is then received via sync for that height
+        assert_eq!(decided.record(5, "a"), Decision::Duplicate);
+    }
+
+    #[test]
+    fn decision_by_consensus_after_sync_is_suppressed() {
+        let mut decided = DecidedValues::default();
+
+        // A synced value is decided first
+        assert_eq!(decided.record(5, "a"), Decision::New);
+
+        // Consensus then decides the same value, and keeps suppressing it
+        assert_eq!(decided.record(5, "a"), Decision::Duplicate);
+        assert_eq!(decided.record(5, "a"), Decision::Duplicate);
+    }
+
+    #[test]
+    fn conflicting_decision_is_reported() {
+        let mut decided = DecidedValues::default();
+
+        assert_eq!(decided.record(5, "a"), Decision::New);
+        assert_eq!(decided.record(5, "b"), Decision::Conflicting("a"));
+
+        // The value decided first is kept
+        assert_eq!(decided.record(5, "a"), Decision::Duplicate);
+        assert_eq!(decided.record(5, "c"), Decision::Conflicting("a"));
+    }
+
+    #[test]
+    fn restarted_height_can_be_decided_again() {
+        let mut decided = DecidedValues::default();
+
+        assert_eq!(decided.record(4, "a"), Decision::New);
+        assert_eq!(decided.record(5, "b"), Decision::New);
+
+        decided.forget_from(5);
+
+        assert_eq!(decided.record(4, "a"), Decision::Duplicate);
+        assert_eq!(decided.record(5, "c"), Decision::New);
+    }
+
+    #[test]
+    fn only_last_heights_are_remembered() {
+        let mut decided = DecidedValues::default();
+
+        for height in 0..(HISTORY as u64 + 1) {
+            assert_eq!(decided.record(height, height), Decision::New);
+        }
+
+        // The oldest height was forgotten
+        assert_eq!(decided.record(0, 0), Decision::New);
+        assert_eq!(
+            decided.record(HISTORY as u64, HISTORY as u64),
+            Decision::Duplicate
+        );
+    }
+}
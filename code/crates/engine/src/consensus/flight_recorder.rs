@@ -0,0 +1,238 @@
+//! Flight recorder of consensus, for postmortems of slow or stuck heights.
+//!
+//! The recorder retains the inputs processed by consensus and the effects they produced
+//! during the last few heights, in memory bounded by the number of heights and the number
+//! of entries per height. The trace can then be dumped to a file, either on demand with
+//! [`Msg::DumpTrace`](super::Msg::DumpTrace) or automatically once a height reaches
+//! the round set by `dump_after_rounds` in the configuration.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+/// Maximum length in bytes of the description of an entry, longer ones being truncated.
+const MAX_DESCRIPTION_LEN: usize = 4096;
+
+/// Whether an entry of the trace is an input of consensus or one of its effects.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TraceKind {
+    Input,
+    Effect,
+}
+
+impl fmt::Display for TraceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Input => f.pad("input"),
+            Self::Effect => f.pad("effect"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TraceEntry {
+    /// Time elapsed since the start of the height
+    elapsed: Duration,
+    kind: TraceKind,
+    description: String,
+}
+
+#[derive(Debug)]
+struct HeightTrace<H> {
+    height: H,
+    started_at: Duration,
+    entries: VecDeque<TraceEntry>,
+    /// Number of entries dropped because the trace of the height was full
+    dropped: usize,
+    /// Whether the trace was dumped automatically during this height
+    dumped: bool,
+}
+
+/// Bounded in-memory trace of the inputs and effects of consensus during the last heights.
+#[derive(Debug)]
+pub struct FlightRecorder<H> {
+    max_heights: usize,
+    max_entries_per_height: usize,
+    heights: VecDeque<HeightTrace<H>>,
+}
+
+impl<H> FlightRecorder<H>
+where
+    H: Copy + fmt::Display,
+{
+    pub fn new(max_heights: usize, max_entries_per_height: usize) -> Self {
+        Self {
+            max_heights: max_heights.max(1),
+            max_entries_per_height: max_entries_per_height.max(1),
+            heights: VecDeque::new(),
+        }
+    }
+
+    /// Start recording a new height, dropping the trace of the oldest height if needed.
+    ///
+    /// Restarting a height records it anew, keeping the trace of the previous attempt.
+    pub fn start_height(&mut self, height: H, now: Duration) {
+        while self.heights.len() >= self.max_heights {
+            self.heights.pop_front();
+        }
+
+        self.heights.push_back(HeightTrace {
+            height,
+            started_at: now,
+            entries: VecDeque::new(),
+            dropped: 0,
+            dumped: false,
+        });
+    }
+
+    /// Record an entry in the trace of the current height, dropping its oldest entry if it is full.
+    ///
+    /// The description is only computed if a height was started.
+    pub fn record(&mut self, now: Duration, kind: TraceKind, describe: impl FnOnce() -> String) {
+        let Some(trace) = self.heights.back_mut() else {
+            return;
+        };
+
+        if trace.entries.len() >= self.max_entries_per_height {
+            trace.entries.pop_front();
+            trace.dropped += 1;
+        }
+
+        trace.entries.push_back(TraceEntry {
+            elapsed: now.saturating_sub(trace.started_at),
+            kind,
+            description: truncate(describe()),
+        });
+    }
+
+    /// Whether the trace of the current height should be dumped automatically, which is
+    /// done at most once per height. Returns `false` if no height was started.
+    pub fn should_auto_dump(&mut self) -> bool {
+        match self.heights.back_mut() {
+            Some(trace) if !trace.dumped => {
+                trace.dumped = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Total number of entries retained, across all heights.
+    pub fn num_entries(&self) -> usize {
+        self.heights.iter().map(|trace| trace.entries.len()).sum()
+    }
+}
+
+/// Human-readable rendering of the trace, written to the dump files.
+impl<H> fmt::Display for FlightRecorder<H>
+where
+    H: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "# Consensus trace of the last {} heights",
+            self.heights.len()
+        )?;
+
+        for trace in &self.heights {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "## Height {} (started at {:.3}s, {} entries, {} dropped)",
+                trace.height,
+                trace.started_at.as_secs_f64(),
+                trace.entries.len(),
+                trace.dropped
+            )?;
+
+            for entry in &trace.entries {
+                writeln!(
+                    f,
+                    "+{:>10.3}s {:<6} {}",
+                    entry.elapsed.as_secs_f64(),
+                    entry.kind,
+                    entry.description
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn truncate(mut description: String) -> String {
+    if description.len() <= MAX_DESCRIPTION_LEN {
+        return description;
+    }
+
+    let mut len = MAX_DESCRIPTION_LEN;
+    while !description.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    description.truncate(len);
+    description.push_str("...");
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn retains_the_last_heights_and_entries() {
+        let mut recorder = FlightRecorder::new(2, 3);
+
+        // Entries recorded before the first height are ignored
+        recorder.record(secs(0), TraceKind::Input, || unreachable!());
+        assert_eq!(recorder.num_entries(), 0);
+
+        for height in 1..=3u64 {
+            recorder.start_height(height, secs(height * 10));
+
+            for i in 0..4 {
+                recorder.record(secs(height * 10 + i), TraceKind::Input, || {
+                    format!("input {height}.{i}")
+                });
+            }
+        }
+
+        assert_eq!(recorder.num_entries(), 6);
+
+        let dump = recorder.to_string();
+        assert!(dump.starts_with("# Consensus trace of the last 2 heights"));
+        assert!(!dump.contains("Height 1 "));
+        assert!(dump.contains("## Height 2 (started at 20.000s, 3 entries, 1 dropped)"));
+        assert!(!dump.contains("input 2.0"));
+        assert!(dump.contains("+     3.000s input  input 3.3"));
+    }
+
+    #[test]
+    fn auto_dump_once_per_height() {
+        let mut recorder = FlightRecorder::new(2, 10);
+        assert!(!recorder.should_auto_dump());
+
+        recorder.start_height(1, secs(0));
+        assert!(recorder.should_auto_dump());
+        assert!(!recorder.should_auto_dump());
+
+        // Restarting the height allows dumping it again
+        recorder.start_height(1, secs(5));
+        assert!(recorder.should_auto_dump());
+    }
+
+    #[test]
+    fn truncate_long_descriptions() {
+        let description = "é".repeat(MAX_DESCRIPTION_LEN);
+        let truncated = truncate(description);
+
+        assert!(truncated.len() <= MAX_DESCRIPTION_LEN + 3);
+        assert!(truncated.ends_with("..."));
+        assert_eq!(truncate("short".to_string()), "short");
+    }
+}
@@ -0,0 +1,159 @@
+//! Window of the votes and proposals seen at the current height.
+//!
+//! After a restart within a height, peers keep gossiping the votes and proposals of that height,
+//! which the node already processed before restarting and recovers from its WAL. The consensus
+//! actor thus keeps a compact digest of every vote and proposal it appends to the WAL at the
+//! current height, and drops the messages received from the network whose digest it already
+//! knows instead of verifying and processing them once more.
+//!
+//! The window is not persisted on its own, but rebuilt from the WAL: the replay of the WAL records
+//! the digest of every vote and proposal it recovers, and consensus then records the ones it appends.
+//! A message is thus only dropped once its effects were applied, and messages whose WAL entries are
+//! missing or corrupt, and thus were not replayed, are processed when received again. As a result,
+//! the window is empty after a restart which does not replay the WAL, eg. when a sync certificate
+//! for the height is received during the WAL replay delay, in which case the height is decided
+//! through sync anyway. The window only covers the current height, and holds at most
+//! [`MAX_SEEN_MESSAGES`] digests, above which the digests of the lowest rounds are dropped.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use malachitebft_core_consensus::SignedConsensusMsg;
+use malachitebft_core_types::{
+    Context, Height, Proposal, Round, Signature, SignedProposal, SignedVote, SigningScheme, Vote,
+};
+
+/// Maximum number of digests kept in the window
+pub const MAX_SEEN_MESSAGES: usize = 4096;
+
+/// Compact digest of a signed vote or proposal.
+///
+/// The signature of a message commits to the whole message, so that the digest is computed
+/// over the signature, along with the kind, height and round of the message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MessageDigest(u64);
+
+impl MessageDigest {
+    /// Digest of the given vote or proposal
+    pub fn of<Ctx: Context>(msg: &SignedConsensusMsg<Ctx>) -> Self {
+        match msg {
+            SignedConsensusMsg::Vote(vote) => Self::of_vote(vote),
+            SignedConsensusMsg::Proposal(proposal) => Self::of_proposal(proposal),
+        }
+    }
+
+    /// Digest of the given vote
+    pub fn of_vote<Ctx: Context>(vote: &SignedVote<Ctx>) -> Self {
+        Self::compute::<Ctx>(0, vote.height(), vote.round(), &vote.signature)
+    }
+
+    /// Digest of the given proposal
+    pub fn of_proposal<Ctx: Context>(proposal: &SignedProposal<Ctx>) -> Self {
+        Self::compute::<Ctx>(1, proposal.height(), proposal.round(), &proposal.signature)
+    }
+
+    fn compute<Ctx: Context>(
+        kind: u8,
+        height: Ctx::Height,
+        round: Round,
+        signature: &Signature<Ctx>,
+    ) -> Self {
+        let mut hasher = Fnv1a::default();
+        hasher.write(&[kind]);
+        hasher.write(&height.as_u64().to_be_bytes());
+        hasher.write(&round.as_i64().to_be_bytes());
+        hasher.write(&Ctx::SigningScheme::encode_signature(signature));
+
+        Self(hasher.0)
+    }
+}
+
+/// 64-bit FNV-1a, which is stable across platforms and releases, unlike the hasher of the standard library
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Digests of the votes and proposals seen at a height, per round.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SeenMessages {
+    rounds: BTreeMap<Round, BTreeSet<MessageDigest>>,
+    len: usize,
+}
+
+impl SeenMessages {
+    /// Whether the message with the given digest was seen in the given round
+    pub fn contains(&self, round: Round, digest: MessageDigest) -> bool {
+        self.rounds
+            .get(&round)
+            .is_some_and(|digests| digests.contains(&digest))
+    }
+
+    /// Record the digest of a message seen in the given round,
+    /// returning whether it was not seen before.
+    pub fn insert(&mut self, round: Round, digest: MessageDigest) -> bool {
+        if !self.rounds.entry(round).or_default().insert(digest) {
+            return false;
+        }
+
+        self.len += 1;
+
+        while self.len > MAX_SEEN_MESSAGES {
+            let Some((_, digests)) = self.rounds.pop_first() else {
+                break;
+            };
+
+            self.len -= digests.len();
+        }
+
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.rounds.clear();
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(n: u64) -> MessageDigest {
+        MessageDigest(n)
+    }
+
+    #[test]
+    fn window_drops_lowest_rounds() {
+        let mut seen = SeenMessages::default();
+
+        assert!(seen.insert(Round::new(1), digest(1)));
+        assert!(!seen.insert(Round::new(1), digest(1)));
+        assert!(seen.contains(Round::new(1), digest(1)));
+        assert!(!seen.contains(Round::new(0), digest(1)));
+
+        for n in 0..MAX_SEEN_MESSAGES as u64 {
+            seen.insert(Round::new(2), digest(n));
+        }
+
+        // The digests of round 1 were dropped to make room for the ones of round 2
+        assert_eq!(seen.len, MAX_SEEN_MESSAGES);
+        assert!(!seen.contains(Round::new(1), digest(1)));
+        assert!(seen.contains(Round::new(2), digest(1)));
+    }
+}
@@ -1,5 +1,3 @@
-use std::collections::BTreeMap;
-
 use derive_where::derive_where;
 use malachitebft_core_types::Context;
 
@@ -17,7 +15,7 @@ pub mod types {
     pub use malachitebft_core_state_machine::state::Step;
     pub use malachitebft_core_types::EnterRoundCertificate;
     pub use malachitebft_core_types::ValuePayload;
-    pub use malachitebft_core_types::{Round, SignedVote, ThresholdParams};
+    pub use malachitebft_core_types::{Round, RoundMap, SignedVote, ThresholdParams};
     pub use malachitebft_core_votekeeper::evidence::EvidenceMap as VoteEvidenceMap;
     pub use malachitebft_core_votekeeper::keeper::PerRound as VotePerRound;
 }
@@ -28,7 +26,7 @@ use self::types::*;
 #[derive_where(Debug, Clone)]
 pub struct VoteKeeperState<Ctx: Context> {
     /// The votes that were received in each round so far
-    pub votes: BTreeMap<Round, VotePerRound<Ctx>>,
+    pub votes: RoundMap<VotePerRound<Ctx>>,
 
     /// Misbehavior evidence for voting
     pub evidence: VoteEvidenceMap<Ctx>,
@@ -38,7 +36,7 @@ pub struct VoteKeeperState<Ctx: Context> {
 #[derive_where(Debug, Clone)]
 pub struct ProposalKeeperState<Ctx: Context> {
     /// The proposals that were received in each round so far
-    pub proposals: BTreeMap<Round, ProposalPerRound<Ctx>>,
+    pub proposals: RoundMap<ProposalPerRound<Ctx>>,
 
     /// Misbehavior evidence for proposals
     pub evidence: ProposalEvidenceMap<Ctx>,
@@ -1,4 +1,5 @@
 use bytes::Bytes;
+use std::fmt;
 use std::ops::RangeInclusive;
 use std::time::Duration;
 
@@ -12,7 +13,7 @@ use malachitebft_sync::{PeerId, RawDecidedValue};
 use crate::util::streaming::StreamMessage;
 
 pub use malachitebft_core_consensus::{LocallyProposedValue, ProposedValue};
-pub use malachitebft_core_types::HeightParams;
+pub use malachitebft_core_types::{HeightParams, HeightParamsOverride, RoundTimeoutsOverride};
 
 /// A reference to the host actor.
 pub type HostRef<Ctx> = ActorRef<HostMsg<Ctx>>;
@@ -27,6 +28,43 @@ pub enum Next<Ctx: Context> {
     Restart(Ctx::Height, HeightParams<Ctx>),
 }
 
+/// The outcome of processing a value synced from the network, see [`HostMsg::ProcessSyncedValue`].
+#[derive_where(Clone, Debug)]
+pub enum SyncedValueOutcome<Ctx: Context> {
+    /// The value was decoded and passed the application's validity checks.
+    ///
+    /// A value marked with `Validity::Invalid` or which does not match the commit certificate
+    /// is handled as [`SyncedValueOutcome::Invalid`].
+    Valid(ProposedValue<Ctx>),
+
+    /// The value was decoded but failed the application's validity checks.
+    ///
+    /// The peer which sent the value is penalized, and the value is requested from another peer.
+    Invalid {
+        /// Why the value is invalid
+        reason: String,
+    },
+}
+
+/// Error replied by the host when it rejects a request instead of handling it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HostError {
+    /// The application has too many pending requests of this kind.
+    ///
+    /// The request was not handled, and may be sent again once the application has caught up.
+    Backpressure,
+}
+
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostError::Backpressure => write!(f, "The application has too many pending requests"),
+        }
+    }
+}
+
+impl std::error::Error for HostError {}
+
 /// Messages that need to be handled by the host actor.
 #[derive_where(Debug)]
 pub enum HostMsg<Ctx: Context> {
@@ -71,6 +109,19 @@ pub enum HostMsg<Ctx: Context> {
         reply_to: RpcReplyPort<LocallyProposedValue<Ctx>>,
     },
 
+    /// Notifies the application that the propose timeout elapsed before it replied to
+    /// the `GetValue` request for the given height and round.
+    ///
+    /// Only sent when `cancel_get_value` is enabled in the consensus configuration.
+    /// The application SHOULD stop building the value, as consensus has moved on
+    /// without it and will ignore it, unless it is needed again in a later round.
+    CancelGetValue {
+        /// The height of the cancelled request.
+        height: Ctx::Height,
+        /// The round of the cancelled request.
+        round: Round,
+    },
+
     /// ExtendVote allows the application to extend the pre-commit vote with arbitrary data.
     ///
     /// When consensus is preparing to send a pre-commit vote, it first calls `ExtendVote`.
@@ -123,10 +174,79 @@ pub enum HostMsg<Ctx: Context> {
         value_id: ValueId<Ctx>,
     },
 
+    /// Asks the application whether it wants to override the durations of the timeouts of a round.
+    ///
+    /// Only sent when `timeout_overrides` is enabled in the consensus configuration,
+    /// once at the start of every round, right after [`HostMsg::StartedRound`].
+    /// This allows the application to give more time to a proposer it knows to be slow,
+    /// for instance because it is doing heavy block building.
+    ///
+    /// The application MUST reply immediately with the durations to use for the timeouts
+    /// it overrides, leaving the others to `None` to use the durations computed from the timeouts
+    /// of the current height. If it does not reply in time, no timeout is overridden for the round.
+    GetTimeoutOverrides {
+        /// The height of the round.
+        height: Ctx::Height,
+        /// The round whose timeouts may be overridden.
+        round: Round,
+        /// Use this reply port to send the durations to use for the overridden timeouts.
+        reply_to: RpcReplyPort<RoundTimeoutsOverride>,
+    },
+
+    /// Asks the application whether it wants to override the consensus parameters of a height.
+    ///
+    /// Only sent when `consensus_params_overrides` is enabled in the consensus configuration,
+    /// every time a height is started or restarted, before consensus starts it.
+    /// The overrides only apply to that height, the next height using the configured parameters
+    /// unless they are overridden again. This allows the application to apply parameter changes
+    /// scheduled at a given height, eg. new timeouts, voting power thresholds or value payload.
+    ///
+    /// The overrides are not persisted in the WAL, so the application is asked again when the
+    /// height is restarted or replayed from the WAL after a crash. It MUST therefore reply
+    /// deterministically, with the same overrides every time it is asked for the same height,
+    /// eg. by deriving them from the decided values of the previous heights.
+    GetConsensusParams {
+        /// The height about to be started.
+        height: Ctx::Height,
+        /// Use this reply port to send the parameters to override for that height, if any.
+        reply_to: RpcReplyPort<HeightParamsOverride<Ctx>>,
+    },
+
+    /// Notifies the application that the current height went through too many rounds without deciding.
+    ///
+    /// Only sent when `notify_round_alerts` is enabled in the consensus configuration,
+    /// once when the height reaches the round set by `max_rounds_alert`, and once when it
+    /// reaches the round set by `max_rounds_halt`, after which consensus stops proposing
+    /// and voting until it moves on to the next height.
+    RoundAlert {
+        /// The height which is not deciding.
+        height: Ctx::Height,
+        /// The round reached by the height.
+        round: Round,
+        /// Whether participation in consensus is halted.
+        halted: bool,
+    },
+
+    /// Notifies the application that it can prune the decided values below the given height.
+    ///
+    /// Only sent when retention is enabled in the sync configuration, whenever the height
+    /// moves up. The height accounts for the values still needed by peers which are behind,
+    /// up to the maximum number of values to retain for them.
+    ///
+    /// The host MAY drop this message when the application is overloaded,
+    /// the values being pruned the next time the height moves up.
+    Prune {
+        /// Decided values at this height and above must be retained.
+        retain_height: Ctx::Height,
+    },
+
     /// Requests the earliest height available in the history maintained by the application.
     ///
     /// The application MUST respond with its earliest available height.
-    GetHistoryMinHeight { reply_to: RpcReplyPort<Ctx::Height> },
+    /// The host MAY reply with [`HostError::Backpressure`] instead when the application is overloaded.
+    GetHistoryMinHeight {
+        reply_to: RpcReplyPort<Result<Ctx::Height, HostError>>,
+    },
 
     /// Notifies the application that consensus has received a proposal part over the network.
     ///
@@ -138,12 +258,35 @@ pub enum HostMsg<Ctx: Context> {
         reply_to: RpcReplyPort<ProposedValue<Ctx>>,
     },
 
+    /// Notifies the application that consensus has received a proposal carrying a value it has not seen yet.
+    ///
+    /// Only sent when consensus runs in `ProposalOnly` mode, where the proposal
+    /// carries the full value and no proposal parts are streamed.
+    ///
+    /// The application MUST validate the value and respond with the proposed value and its validity.
+    ReceivedProposal {
+        /// The height at which the value was proposed.
+        height: Ctx::Height,
+        /// The round in which the value was proposed.
+        round: Round,
+        /// The round in which the value was valid.
+        valid_round: Round,
+        /// The address of the proposer of the value.
+        proposer: Ctx::Address,
+        /// The proposed value.
+        value: Ctx::Value,
+        /// Use this reply port to send the proposed value and its validity.
+        reply_to: RpcReplyPort<ProposedValue<Ctx>>,
+    },
+
     /// Notifies the application that consensus has decided on a value.
     ///
     /// This message includes a commit certificate containing the ID of
     /// the value that was decided on, the height and round at which it was decided,
     /// and the aggregated signatures of the validators that committed to it.
-    /// It also includes to the vote extensions received for that height.
+    /// It also includes the vote extensions received for that height, which come from
+    /// validators holding more than 2/3 of the voting power when `require_vote_extensions`
+    /// is enabled in the consensus configuration, unless the value was decided through sync.
     ///
     /// The application MUST commit the decision and then reply to
     /// acknowledge that the commit is complete. The sync actor will only be notified
@@ -198,18 +341,24 @@ pub enum HostMsg<Ctx: Context> {
     /// ## Important
     /// The range is NOT checked for validity by consensus. It is the application's responsibility
     /// to ensure that the the range is within valid bounds.
+    ///
+    /// The host MAY reply with [`HostError::Backpressure`] instead when the application is overloaded.
     GetDecidedValues {
         /// Range of decided values to retrieve
         range: RangeInclusive<Ctx::Height>,
         /// Channel for sending back the decided value
-        reply_to: RpcReplyPort<Vec<RawDecidedValue<Ctx>>>,
+        reply_to: RpcReplyPort<Result<Vec<RawDecidedValue<Ctx>>, HostError>>,
     },
 
     /// Notifies the application that a value has been synced from the network.
     /// This may happen when the node is catching up with the network.
     ///
-    /// If a value can be decoded from the bytes provided, then the application MUST reply
-    /// to this message with the decoded value. Otherwise, it MUST reply with `None`.
+    /// If a value can be decoded from the bytes provided, then the application MUST validate it
+    /// as it would validate a value proposed during consensus, and reply to this message with
+    /// the outcome of that validation. Otherwise, it MUST reply with `None`.
+    ///
+    /// The host MAY reply with [`HostError::Backpressure`] instead when the application is overloaded,
+    /// in which case the value is requested again.
     ProcessSyncedValue {
         /// Height of the synced value
         height: Ctx::Height,
@@ -219,8 +368,48 @@ pub enum HostMsg<Ctx: Context> {
         proposer: Ctx::Address,
         /// Raw encoded value data
         value_bytes: Bytes,
-        /// Channel for sending back the proposed value, if successfully decoded
+        /// Channel for sending back the outcome of the validation of the value, if successfully decoded
         /// or `None` if the value could not be decoded
-        reply_to: RpcReplyPort<Option<ProposedValue<Ctx>>>,
+        reply_to: RpcReplyPort<Result<Option<SyncedValueOutcome<Ctx>>, HostError>>,
+    },
+
+    /// Notifies the application that a batch of values has been synced from the network,
+    /// when value sync is configured to process the values of each response as a batch.
+    ///
+    /// The values are ordered by ascending height, starting at or above the current height.
+    /// The application MUST process each value as it would for [`HostMsg::ProcessSyncedValue`],
+    /// and MAY persist the whole batch at once, eg. in a single transaction. It MUST reply with
+    /// one outcome per value, in the same order, or `None` for the values which could not be decoded.
+    ///
+    /// The proposers of the values are not provided, as they are not known ahead of consensus,
+    /// and MUST be determined by the application from the validator set of each height.
+    /// The commit certificates are verified by consensus once it reaches their heights,
+    /// after which the outcomes are used in place of a [`HostMsg::ProcessSyncedValue`] request.
+    ///
+    /// The host MAY reply with [`HostError::Backpressure`] instead when the application is overloaded,
+    /// in which case the values are processed one by one once consensus reaches their heights.
+    ProcessSyncedValues {
+        /// Synced values, with their commit certificates
+        values: Vec<RawDecidedValue<Ctx>>,
+        /// Channel for sending back the outcome of processing each value
+        reply_to: RpcReplyPort<Result<Vec<Option<SyncedValueOutcome<Ctx>>>, HostError>>,
+    },
+
+    /// Notifies the application that historical values have been backfilled from the network,
+    /// for heights below its earliest available height.
+    ///
+    /// The values are ordered by ascending height, and the last one is at the height right below
+    /// the earliest height available in the application's storage.
+    /// The application MUST verify the commit certificate of each value, and that the value
+    /// matches the certificate, before storing them. It MUST reply with `true` if all values
+    /// were verified and stored, or `false` otherwise.
+    ///
+    /// The host MAY reply with [`HostError::Backpressure`] instead when the application is overloaded,
+    /// in which case the values are requested again.
+    ProcessBackfilledValues {
+        /// Backfilled values, with their commit certificates
+        values: Vec<RawDecidedValue<Ctx>>,
+        /// Channel for sending back whether the values were verified and stored
+        reply_to: RpcReplyPort<Result<bool, HostError>>,
     },
 }
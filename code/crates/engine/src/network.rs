@@ -1,5 +1,7 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use derive_where::derive_where;
@@ -10,28 +12,52 @@ use tokio::task::JoinHandle;
 use tracing::{debug, error, info, trace, warn};
 
 use malachitebft_codec as codec;
+use malachitebft_config::{PriorityLanesConfig, ReputationConfig};
 use malachitebft_core_consensus::{LivenessMsg, SignedConsensusMsg};
 use malachitebft_core_types::{
-    Context, PolkaCertificate, RoundCertificate, SignedProposal, SignedVote, SigningScheme,
-    Validator, ValidatorProof, ValidatorSet,
+    Context, Height, PolkaCertificate, Round, RoundCertificate, SignedProposal, SignedVote,
+    SigningScheme, Validator, ValidatorProof, ValidatorSet, ValidatorSetUpdateCertificate, ValueId,
+    Vote as _,
 };
 use malachitebft_metrics::SharedRegistry;
-use malachitebft_network::handle::CtrlHandle;
+use malachitebft_network::handle::{CtrlHandle, Handle};
 use malachitebft_network::validator_proof::ProofVerificationResult;
 use malachitebft_network::{Channel, Config, Event, PeerId};
 
+pub use malachitebft_network::mux::{Mux, MuxError, ShardId};
+pub use malachitebft_network::peer_filter::{self, PeerFilter};
 pub use malachitebft_network::{
-    Multiaddr, NetworkIdentity, NetworkStateDump, PersistentPeerError, PersistentPeersOp,
+    DiscoveryStats, Multiaddr, NetworkIdentity, NetworkStateDump, PeerExit, PeerState,
+    PersistentPeerError, PersistentPeersOp,
 };
 
 use malachitebft_sync::{
-    self as sync, InboundRequestId, OutboundRequestId, RawMessage, Request, Response,
+    self as sync, CertificateHash, InboundRequestId, NodeMode, OutboundRequestId, RawMessage,
+    Request, Response,
 };
 
 use crate::consensus::ConsensusCodec;
 use crate::sync::SyncCodec;
 use crate::util::output_port::{OutputPort, OutputPortSubscriberTrait};
-use crate::util::streaming::StreamMessage;
+use crate::util::span::{parent_span, record_height_and_round};
+use crate::util::streaming::{StreamId, StreamMessage};
+
+mod lanes;
+pub use lanes::Lane;
+use lanes::{Outbound, PriorityLanes};
+
+mod parts;
+use parts::{PartCache, PartRequest, StreamTracker};
+
+mod reputation;
+use reputation::{Reputation, Update};
+
+/// Time to wait after the end of a stream of proposal parts was received before
+/// requesting its missing parts, leaving time for parts received out of order to arrive.
+const PART_REPAIR_DELAY: Duration = Duration::from_millis(200);
+
+/// Number of peers besides the proposer from which to request a missing proposal part.
+const PART_REPAIR_FANOUT: usize = 2;
 
 pub type NetworkRef<Ctx> = ActorRef<Msg<Ctx>>;
 pub type NetworkMsg<Ctx> = Msg<Ctx>;
@@ -81,13 +107,38 @@ where
     pub async fn spawn(
         identity: NetworkIdentity,
         config: Config,
+        lanes: PriorityLanesConfig,
+        reputation: ReputationConfig,
         metrics: SharedRegistry,
         codec: Codec,
         span: tracing::Span,
     ) -> Result<ActorRef<Msg<Ctx>>, ractor::SpawnErr> {
-        let args = Args {
+        let args = Args::Spawn {
             identity,
-            config: config.clone(),
+            config: Box::new(config),
+            lanes,
+            reputation,
+            metrics,
+        };
+
+        let (actor_ref, _) = Actor::spawn(None, Self::new(codec, span), args).await?;
+        Ok(actor_ref)
+    }
+
+    /// Spawn a network actor on top of an existing network handle,
+    /// eg. the handle of a shard obtained from a [`Mux`].
+    pub async fn spawn_with_handle(
+        handle: Handle,
+        lanes: PriorityLanesConfig,
+        reputation: ReputationConfig,
+        metrics: SharedRegistry,
+        codec: Codec,
+        span: tracing::Span,
+    ) -> Result<ActorRef<Msg<Ctx>>, ractor::SpawnErr> {
+        let args = Args::Handle {
+            handle,
+            lanes,
+            reputation,
             metrics,
         };
 
@@ -96,17 +147,31 @@ where
     }
 }
 
-pub struct Args {
-    pub identity: NetworkIdentity,
-    pub config: Config,
-    pub metrics: SharedRegistry,
+pub enum Args {
+    /// Spawn a dedicated network with its own libp2p swarm
+    Spawn {
+        identity: NetworkIdentity,
+        config: Box<Config>,
+        lanes: PriorityLanesConfig,
+        reputation: ReputationConfig,
+        metrics: SharedRegistry,
+    },
+
+    /// Use an already running network, eg. a shard of a network shared by several consensus instances
+    Handle {
+        handle: Handle,
+        lanes: PriorityLanesConfig,
+        reputation: ReputationConfig,
+        metrics: SharedRegistry,
+    },
 }
 
 #[derive_where(Clone, Debug, PartialEq, Eq)]
 pub enum NetworkEvent<Ctx: Context> {
     Listening(Multiaddr),
 
-    PeerConnected(PeerId),
+    /// A peer connected, with the address of the connection to it
+    PeerConnected(PeerId, Multiaddr),
     PeerDisconnected(PeerId),
 
     Vote(PeerId, SignedVote<Ctx>),
@@ -118,6 +183,9 @@ pub enum NetworkEvent<Ctx: Context> {
 
     RoundCertificate(PeerId, RoundCertificate<Ctx>),
 
+    /// A validator set update gossiped by a peer
+    ValidatorSetUpdate(PeerId, ValidatorSetUpdateCertificate<Ctx>),
+
     /// A validator proof received from a peer (one-way, no response expected).
     ValidatorProofReceived {
         peer_id: PeerId,
@@ -126,19 +194,43 @@ pub enum NetworkEvent<Ctx: Context> {
 
     Status(PeerId, Status<Ctx>),
 
+    /// A peer announced a value it decided
+    Announcement(PeerId, Announcement<Ctx>),
+
     SyncRequest(InboundRequestId, PeerId, Request<Ctx>),
     SyncResponse(OutboundRequestId, PeerId, Option<Response<Ctx>>),
+
+    /// A peer was disconnected and temporarily banned because of its low reputation
+    PeerBanned(PeerId),
+}
+
+impl<Ctx: Context> NetworkEvent<Ctx> {
+    /// The peer which is gone and why, if this event means that
+    /// the state kept for that peer must be dropped, see [`PeerState`].
+    pub fn peer_exit(&self) -> Option<(PeerId, PeerExit)> {
+        match self {
+            Self::PeerDisconnected(peer_id) => Some((*peer_id, PeerExit::Disconnected)),
+            Self::PeerBanned(peer_id) => Some((*peer_id, PeerExit::Banned)),
+            _ => None,
+        }
+    }
 }
 
 pub enum State<Ctx: Context> {
     Stopped,
     Running {
         listen_addrs: Vec<Multiaddr>,
-        peers: BTreeSet<PeerId>,
+        peers: BTreeMap<PeerId, Multiaddr>,
         output_port: OutputPort<NetworkEvent<Ctx>>,
-        ctrl_handle: Box<CtrlHandle>,
+        ctrl_handle: Arc<CtrlHandle>,
+        lanes: PriorityLanes,
+        reputation: Box<Reputation>,
         recv_task: JoinHandle<()>,
-        inbound_requests: HashMap<InboundRequestId, request_response::InboundRequestId>,
+        inbound_requests: Box<HashMap<InboundRequestId, request_response::InboundRequestId>>,
+        parts: Box<PartCache>,
+        streams: Box<StreamTracker>,
+        /// Earliest height for which decided values are retained, last advertised in the node info
+        history_min_height: Option<u64>,
     },
 }
 
@@ -146,13 +238,44 @@ pub enum State<Ctx: Context> {
 pub struct Status<Ctx: Context> {
     pub tip_height: Ctx::Height,
     pub history_min_height: Ctx::Height,
+    pub sync_height: Ctx::Height,
+    pub mode: NodeMode,
 }
 
 impl<Ctx: Context> Status<Ctx> {
-    pub fn new(tip_height: Ctx::Height, history_min_height: Ctx::Height) -> Self {
+    pub fn new(
+        tip_height: Ctx::Height,
+        history_min_height: Ctx::Height,
+        sync_height: Ctx::Height,
+        mode: NodeMode,
+    ) -> Self {
         Self {
             tip_height,
             history_min_height,
+            sync_height,
+            mode,
+        }
+    }
+}
+
+/// Announcement of a value decided by the local node, see [`sync::Announcement`].
+#[derive_where(Clone, Debug, PartialEq, Eq)]
+pub struct Announcement<Ctx: Context> {
+    pub height: Ctx::Height,
+    pub value_id: ValueId<Ctx>,
+    pub certificate_hash: CertificateHash,
+}
+
+impl<Ctx: Context> Announcement<Ctx> {
+    pub fn new(
+        height: Ctx::Height,
+        value_id: ValueId<Ctx>,
+        certificate_hash: CertificateHash,
+    ) -> Self {
+        Self {
+            height,
+            value_id,
+            certificate_hash,
         }
     }
 }
@@ -173,6 +296,9 @@ pub enum Msg<Ctx: Context> {
     /// Broadcast status to all direct peers
     BroadcastStatus(Status<Ctx>),
 
+    /// Broadcast the announcement of a decided value to all direct peers
+    BroadcastAnnouncement(Announcement<Ctx>),
+
     /// Send a request to a peer, returning the outbound request ID
     OutgoingRequest(PeerId, Request<Ctx>, RpcReplyPort<OutboundRequestId>),
 
@@ -182,6 +308,9 @@ pub enum Msg<Ctx: Context> {
     /// Request to dump the current network state
     DumpState(RpcReplyPort<Option<NetworkStateDump>>),
 
+    /// Request a snapshot of the discovery state
+    GetDiscoveryState(RpcReplyPort<Option<DiscoveryStats>>),
+
     /// Add or remove a persistent peer at runtime
     UpdatePersistentPeers(
         PersistentPeersOp,
@@ -191,6 +320,9 @@ pub enum Msg<Ctx: Context> {
     /// Update the validator set for the current height
     UpdateValidatorSet(Ctx::ValidatorSet),
 
+    /// Update the sync scores of peers, which contribute to their reputation
+    UpdateSyncScores(Vec<(PeerId, f64)>),
+
     /// Send a validator proof verification result.
     /// If result is Valid and public_key is Some, stores the proof for this peer.
     ValidatorProofVerified {
@@ -200,6 +332,10 @@ pub enum Msg<Ctx: Context> {
         public_key: Option<Vec<u8>>,
     },
 
+    /// Request the parts missing from the stream of proposal parts sent by the given peer
+    #[doc(hidden)]
+    RepairStream(PeerId, StreamId),
+
     // Event emitted by the gossip layer
     #[doc(hidden)]
     NewEvent(Event),
@@ -226,9 +362,39 @@ where
         myself: ActorRef<Msg<Ctx>>,
         args: Args,
     ) -> Result<Self::State, ActorProcessingErr> {
-        let handle = malachitebft_network::spawn(args.identity, args.config, args.metrics).await?;
+        let (handle, lanes_config, reputation_config, metrics) = match args {
+            Args::Spawn {
+                identity,
+                config,
+                lanes,
+                reputation,
+                metrics,
+            } => {
+                let handle =
+                    malachitebft_network::spawn(identity, *config, metrics.clone()).await?;
+                (handle, lanes, reputation, metrics)
+            }
+            Args::Handle {
+                handle,
+                lanes,
+                reputation,
+                metrics,
+            } => (handle, lanes, reputation, metrics),
+        };
 
         let (mut recv_handle, ctrl_handle) = handle.split();
+        let ctrl_handle = Arc::new(ctrl_handle);
+
+        let lanes = PriorityLanes::spawn(
+            Arc::clone(&ctrl_handle),
+            &lanes_config,
+            lanes::Metrics::register(&metrics),
+        );
+
+        let reputation = Box::new(Reputation::new(
+            reputation_config,
+            reputation::Metrics::register(&metrics),
+        ));
 
         let recv_task = tokio::spawn(async move {
             while let Some(event) = recv_handle.recv().await {
@@ -241,11 +407,16 @@ where
 
         Ok(State::Running {
             listen_addrs: Vec::new(),
-            peers: BTreeSet::new(),
+            peers: BTreeMap::new(),
             output_port: OutputPort::with_capacity(128),
-            ctrl_handle: Box::new(ctrl_handle),
+            ctrl_handle,
+            lanes,
+            reputation,
             recv_task,
-            inbound_requests: HashMap::new(),
+            inbound_requests: Box::default(),
+            parts: Box::default(),
+            streams: Box::default(),
+            history_min_height: None,
         })
     }
 
@@ -257,19 +428,33 @@ where
         Ok(())
     }
 
-    #[tracing::instrument(name = "network", parent = &self.span, skip_all)]
+    #[tracing::instrument(
+        name = "network",
+        parent = parent_span(&self.span),
+        skip_all,
+        fields(height = tracing::field::Empty, round = tracing::field::Empty)
+    )]
     async fn handle(
         &self,
-        _myself: ActorRef<Msg<Ctx>>,
+        myself: ActorRef<Msg<Ctx>>,
         msg: Msg<Ctx>,
         state: &mut State<Ctx>,
     ) -> Result<(), ActorProcessingErr> {
+        if let Some((height, round)) = span_height_and_round(&msg) {
+            record_height_and_round(&tracing::Span::current(), height, round);
+        }
+
         // We need to handle before deconstructing `state` to always reply.
         if let Msg::DumpState(reply_to) = msg {
             handle_dump_state(state, reply_to).await;
             return Ok(());
         }
 
+        if let Msg::GetDiscoveryState(reply_to) = msg {
+            handle_get_discovery_state(state, reply_to).await;
+            return Ok(());
+        }
+
         if let Msg::UpdatePersistentPeers(op, reply_to) = msg {
             handle_update_persistent_peers(state, op, reply_to).await;
             return Ok(());
@@ -280,7 +465,12 @@ where
             peers,
             output_port,
             ctrl_handle,
+            lanes,
+            reputation,
             inbound_requests,
+            parts,
+            streams,
+            history_min_height,
             ..
         } = state
         else {
@@ -293,20 +483,33 @@ where
                     subscriber.send(NetworkEvent::Listening(addr.clone()));
                 }
 
-                for peer in peers.iter() {
-                    subscriber.send(NetworkEvent::PeerConnected(*peer));
+                for (peer, address) in peers.iter() {
+                    subscriber.send(NetworkEvent::PeerConnected(*peer, address.clone()));
                 }
 
                 subscriber.subscribe_to_port(output_port);
             }
 
-            Msg::PublishConsensusMsg(msg) => match self.codec.encode(&msg) {
-                Ok(data) => ctrl_handle.publish(Channel::Consensus, data).await?,
-                Err(e) => error!("Failed to encode consensus message: {e:?}"),
-            },
+            Msg::PublishConsensusMsg(msg) => {
+                // Votes are published on the channel reserved to the validators,
+                // when selective gossip is enabled in the network
+                let (lane, channel) = match &msg {
+                    SignedConsensusMsg::Vote(_) => (Lane::Votes, Channel::Votes),
+                    SignedConsensusMsg::Proposal(_) => (Lane::Proposals, Channel::Consensus),
+                };
+
+                match self.codec.encode(&msg) {
+                    Ok(data) => lanes.push(lane, Outbound::Publish(channel, data)).await,
+                    Err(e) => error!("Failed to encode consensus message: {e:?}"),
+                }
+            }
 
             Msg::PublishLivenessMsg(msg) => match self.codec.encode(&msg) {
-                Ok(data) => ctrl_handle.publish(Channel::Liveness, data).await?,
+                Ok(data) => {
+                    lanes
+                        .push(Lane::Votes, Outbound::Publish(Channel::Liveness, data))
+                        .await
+                }
                 Err(e) => error!("Failed to encode liveness message: {e:?}"),
             },
 
@@ -319,7 +522,16 @@ where
 
                 let data = self.codec.encode(&msg);
                 match data {
-                    Ok(data) => ctrl_handle.publish(Channel::ProposalParts, data).await?,
+                    Ok(data) => {
+                        parts.insert(msg.stream_id, msg.sequence, data.clone());
+
+                        lanes
+                            .push(
+                                Lane::Proposals,
+                                Outbound::Publish(Channel::ProposalParts, data),
+                            )
+                            .await
+                    }
                     Err(e) => error!("Failed to encode proposal part: {e:?}"),
                 }
             }
@@ -329,13 +541,47 @@ where
                     peer_id: ctrl_handle.peer_id(),
                     tip_height: status.tip_height,
                     history_min_height: status.history_min_height,
+                    sync_height: status.sync_height,
+                    mode: status.mode,
                 };
 
                 let data = self.codec.encode(&status);
                 match data {
-                    Ok(data) => ctrl_handle.broadcast(Channel::Sync, data).await?,
+                    Ok(data) => {
+                        lanes
+                            .push(Lane::Status, Outbound::Broadcast(Channel::Sync, data))
+                            .await
+                    }
                     Err(e) => error!("Failed to encode status message: {e:?}"),
                 }
+
+                // Advertise the earliest retained height in the node info sent to new peers
+                let min_height = status.history_min_height.as_u64();
+                if *history_min_height != Some(min_height) {
+                    *history_min_height = Some(min_height);
+                    ctrl_handle.update_history_min_height(min_height).await?;
+                }
+            }
+
+            Msg::BroadcastAnnouncement(announcement) => {
+                let announcement = sync::Announcement::<Ctx> {
+                    peer_id: ctrl_handle.peer_id(),
+                    height: announcement.height,
+                    value_id: announcement.value_id,
+                    certificate_hash: announcement.certificate_hash,
+                };
+
+                match self.codec.encode(&announcement) {
+                    Ok(data) => {
+                        lanes
+                            .push(
+                                Lane::Status,
+                                Outbound::Broadcast(Channel::Announcements, data),
+                            )
+                            .await
+                    }
+                    Err(e) => error!("Failed to encode announcement: {e:?}"),
+                }
             }
 
             Msg::OutgoingRequest(peer_id, request, reply_to) => {
@@ -359,7 +605,9 @@ where
                             .remove(&request_id)
                             .ok_or_else(|| eyre!("Unknown inbound request ID: {request_id}"))?;
 
-                        ctrl_handle.sync_reply(request_id, data).await?
+                        lanes
+                            .push(Lane::SyncResponses, Outbound::SyncReply(request_id, data))
+                            .await
                     }
                     Err(e) => {
                         error!(%request_id, "Failed to encode response message: {e:?}");
@@ -373,13 +621,15 @@ where
                 output_port.send(NetworkEvent::Listening(addr));
             }
 
-            Msg::NewEvent(Event::PeerConnected(peer_id)) => {
-                peers.insert(peer_id);
-                output_port.send(NetworkEvent::PeerConnected(peer_id));
+            Msg::NewEvent(Event::PeerConnected(peer_id, address)) => {
+                peers.insert(peer_id, address.clone());
+                output_port.send(NetworkEvent::PeerConnected(peer_id, address));
             }
 
             Msg::NewEvent(Event::PeerDisconnected(peer_id)) => {
                 peers.remove(&peer_id);
+                reputation.forget_peer(&peer_id, PeerExit::Disconnected);
+                streams.forget_peer(&peer_id, PeerExit::Disconnected);
                 output_port.send(NetworkEvent::PeerDisconnected(peer_id));
             }
 
@@ -388,6 +638,14 @@ where
                     Ok(msg) => msg,
                     Err(e) => {
                         error!(%from, "Failed to decode liveness message: {e:?}");
+                        update_reputation(
+                            reputation,
+                            ctrl_handle,
+                            output_port,
+                            from,
+                            Update::InvalidMessage,
+                        )
+                        .await?;
                         return Ok(());
                     }
                 };
@@ -400,6 +658,9 @@ where
                         NetworkEvent::RoundCertificate(from, round_cert)
                     }
                     LivenessMsg::Vote(vote) => NetworkEvent::Vote(from, vote),
+                    LivenessMsg::ValidatorSetUpdate(certificate) => {
+                        NetworkEvent::ValidatorSetUpdate(from, certificate)
+                    }
                 };
 
                 output_port.send(event);
@@ -410,11 +671,23 @@ where
                 return Ok(());
             }
 
-            Msg::NewEvent(Event::ConsensusMessage(Channel::Consensus, from, data)) => {
+            Msg::NewEvent(Event::ConsensusMessage(
+                Channel::Consensus | Channel::Votes,
+                from,
+                data,
+            )) => {
                 let msg = match self.codec.decode(data) {
                     Ok(msg) => msg,
                     Err(e) => {
                         error!(%from, "Failed to decode consensus message: {e:?}");
+                        update_reputation(
+                            reputation,
+                            ctrl_handle,
+                            output_port,
+                            from,
+                            Update::InvalidMessage,
+                        )
+                        .await?;
                         return Ok(());
                     }
                 };
@@ -430,10 +703,18 @@ where
             }
 
             Msg::NewEvent(Event::ConsensusMessage(Channel::ProposalParts, from, data)) => {
-                let msg: StreamMessage<Ctx::ProposalPart> = match self.codec.decode(data) {
+                let msg: StreamMessage<Ctx::ProposalPart> = match self.codec.decode(data.clone()) {
                     Ok(stream_msg) => stream_msg,
                     Err(e) => {
                         error!(%from, "Failed to decode stream message: {e:?}");
+                        update_reputation(
+                            reputation,
+                            ctrl_handle,
+                            output_port,
+                            from,
+                            Update::InvalidMessage,
+                        )
+                        .await?;
                         return Ok(());
                     }
                 };
@@ -445,14 +726,134 @@ where
                     "Received proposal part"
                 );
 
+                parts.insert(msg.stream_id.clone(), msg.sequence, data);
+                streams.record(from, msg.stream_id.clone(), msg.sequence, msg.is_fin());
+
+                if msg.is_fin() && !streams.missing(from, &msg.stream_id).is_empty() {
+                    let stream_id = msg.stream_id.clone();
+                    myself.send_after(PART_REPAIR_DELAY, move || {
+                        Msg::RepairStream(from, stream_id)
+                    });
+                }
+
                 output_port.send(NetworkEvent::ProposalPart(from, msg));
             }
 
+            Msg::RepairStream(proposer, stream_id) => {
+                let missing = streams.missing(proposer, &stream_id);
+                if missing.is_empty() {
+                    return Ok(());
+                }
+
+                debug!(%proposer, %stream_id, ?missing, "Requesting missing proposal parts");
+
+                let others = peers
+                    .keys()
+                    .filter(|peer| **peer != proposer)
+                    .take(PART_REPAIR_FANOUT);
+
+                for peer in std::iter::once(&proposer).chain(others) {
+                    for &index in &missing {
+                        let request = PartRequest::new(stream_id.clone(), index);
+                        ctrl_handle.part_request(*peer, request.encode()).await?;
+                    }
+                }
+            }
+
+            Msg::NewEvent(Event::ProposalParts(RawMessage::Request {
+                request_id,
+                peer,
+                body,
+            })) => {
+                let Some(request) = PartRequest::decode(body) else {
+                    error!(%peer, "Failed to decode proposal part request");
+                    update_reputation(
+                        reputation,
+                        ctrl_handle,
+                        output_port,
+                        peer,
+                        Update::InvalidMessage,
+                    )
+                    .await?;
+                    return Ok(());
+                };
+
+                // An empty response signals that we do not have the requested part
+                let data = parts.get(&request).cloned().unwrap_or_default();
+
+                trace!(
+                    %peer,
+                    stream_id = %request.stream_id,
+                    index = %request.index,
+                    found = !data.is_empty(),
+                    "Replying to proposal part request"
+                );
+
+                lanes
+                    .push(Lane::Proposals, Outbound::PartReply(request_id, data))
+                    .await;
+            }
+
+            Msg::NewEvent(Event::ProposalParts(RawMessage::Response { peer, body, .. })) => {
+                if body.is_empty() {
+                    trace!(%peer, "Peer does not have the requested proposal part");
+                    return Ok(());
+                }
+
+                let msg: StreamMessage<Ctx::ProposalPart> = match self.codec.decode(body.clone()) {
+                    Ok(stream_msg) => stream_msg,
+                    Err(e) => {
+                        error!(%peer, "Failed to decode requested proposal part: {e:?}");
+                        update_reputation(
+                            reputation,
+                            ctrl_handle,
+                            output_port,
+                            peer,
+                            Update::InvalidMessage,
+                        )
+                        .await?;
+                        return Ok(());
+                    }
+                };
+
+                // Parts are attributed to the proposer of the stream, whichever peer sent them,
+                // and only forwarded if still missing since the same part is requested from several peers
+                let Some(proposer) = streams.incomplete_stream_sender(&msg.stream_id) else {
+                    trace!(%peer, stream_id = %msg.stream_id, "Ignoring proposal part for complete or unknown stream");
+                    return Ok(());
+                };
+
+                if !streams.is_missing(proposer, &msg.stream_id, msg.sequence) {
+                    return Ok(());
+                }
+
+                debug!(
+                    %peer,
+                    %proposer,
+                    stream_id = %msg.stream_id,
+                    sequence = %msg.sequence,
+                    "Received missing proposal part"
+                );
+
+                parts.insert(msg.stream_id.clone(), msg.sequence, body);
+                streams.record(proposer, msg.stream_id.clone(), msg.sequence, msg.is_fin());
+
+                output_port.send(NetworkEvent::ProposalPart(proposer, msg));
+            }
+
             Msg::NewEvent(Event::ConsensusMessage(Channel::Sync, from, data)) => {
                 let status: sync::Status<Ctx> = match self.codec.decode(data) {
                     Ok(status) => status,
                     Err(e) => {
                         error!(%from, "Failed to decode status message: {e:?}");
+                        update_reputation(
+                            reputation,
+                            ctrl_handle,
+                            output_port,
+                            from,
+                            Update::InvalidMessage,
+                        )
+                        .await?;
                         return Ok(());
                     }
                 };
@@ -466,7 +867,46 @@ where
 
                 output_port.send(NetworkEvent::Status(
                     status.peer_id,
-                    Status::new(status.tip_height, status.history_min_height),
+                    Status::new(
+                        status.tip_height,
+                        status.history_min_height,
+                        status.sync_height,
+                        status.mode,
+                    ),
+                ));
+            }
+
+            Msg::NewEvent(Event::ConsensusMessage(Channel::Announcements, from, data)) => {
+                let announcement: sync::Announcement<Ctx> = match self.codec.decode(data) {
+                    Ok(announcement) => announcement,
+                    Err(e) => {
+                        error!(%from, "Failed to decode announcement: {e:?}");
+                        update_reputation(
+                            reputation,
+                            ctrl_handle,
+                            output_port,
+                            from,
+                            Update::InvalidMessage,
+                        )
+                        .await?;
+                        return Ok(());
+                    }
+                };
+
+                if from != announcement.peer_id {
+                    error!(%from, %announcement.peer_id, "Mismatched peer ID in announcement");
+                    return Ok(());
+                }
+
+                trace!(%from, height = %announcement.height, "Received announcement");
+
+                output_port.send(NetworkEvent::Announcement(
+                    announcement.peer_id,
+                    Announcement::new(
+                        announcement.height,
+                        announcement.value_id,
+                        announcement.certificate_hash,
+                    ),
                 ));
             }
 
@@ -483,6 +923,14 @@ where
                     Ok(p) => p,
                     Err(e) => {
                         warn!(%peer_id, "Failed to decode validator proof: {e:?}, ignoring");
+                        update_reputation(
+                            reputation,
+                            ctrl_handle,
+                            output_port,
+                            peer_id,
+                            Update::InvalidMessage,
+                        )
+                        .await?;
                         return Ok(());
                     }
                 };
@@ -505,6 +953,17 @@ where
                 output_port.send(NetworkEvent::ValidatorProofReceived { peer_id, proof });
             }
 
+            Msg::NewEvent(Event::RateLimitViolation(peer_id)) => {
+                update_reputation(
+                    reputation,
+                    ctrl_handle,
+                    output_port,
+                    peer_id,
+                    Update::RateLimitViolation,
+                )
+                .await?;
+            }
+
             Msg::NewEvent(Event::Sync(raw_msg)) => match raw_msg {
                 RawMessage::Request {
                     request_id,
@@ -515,6 +974,14 @@ where
                         Ok(request) => request,
                         Err(e) => {
                             error!(%peer, "Failed to decode sync request: {e:?}");
+                            update_reputation(
+                                reputation,
+                                ctrl_handle,
+                                output_port,
+                                peer,
+                                Update::InvalidMessage,
+                            )
+                            .await?;
                             return Ok(());
                         }
                     };
@@ -537,6 +1004,14 @@ where
                         Ok(response) => Some(response),
                         Err(e) => {
                             error!(%peer, "Failed to decode sync response: {e:?}");
+                            update_reputation(
+                                reputation,
+                                ctrl_handle,
+                                output_port,
+                                peer,
+                                Update::InvalidMessage,
+                            )
+                            .await?;
                             None
                         }
                     };
@@ -568,6 +1043,19 @@ where
                 ctrl_handle.update_validator_set(validators).await?;
             }
 
+            Msg::UpdateSyncScores(scores) => {
+                for (peer_id, score) in scores {
+                    update_reputation(
+                        reputation,
+                        ctrl_handle,
+                        output_port,
+                        peer_id,
+                        Update::SyncScore(score),
+                    )
+                    .await?;
+                }
+            }
+
             Msg::ValidatorProofVerified {
                 peer_id,
                 result,
@@ -580,6 +1068,9 @@ where
             }
 
             Msg::DumpState(_) => unreachable!("DumpState handled above to ensure a reply"),
+            Msg::GetDiscoveryState(_) => {
+                unreachable!("GetDiscoveryState handled above to ensure a reply")
+            }
             Msg::UpdatePersistentPeers(_, _) => {
                 unreachable!("UpdatePersistentPeers handled above to ensure a reply")
             }
@@ -597,11 +1088,18 @@ where
 
         if let State::Running {
             ctrl_handle,
+            lanes,
             recv_task,
             ..
         } = state
         {
-            ctrl_handle.wait_shutdown().await?;
+            // Flush the outbound lanes, which also releases their reference to the handle
+            lanes.close().await?;
+
+            if let Some(ctrl_handle) = Arc::into_inner(ctrl_handle) {
+                ctrl_handle.wait_shutdown().await?;
+            }
+
             recv_task.await?;
         }
 
@@ -609,6 +1107,50 @@ where
     }
 }
 
+/// Apply an update to the reputation of a peer,
+/// and ban the peer if its reputation fell below the threshold.
+/// Height and round of the consensus messages to publish, for the tracing span of the Network actor.
+fn span_height_and_round<Ctx: Context>(msg: &Msg<Ctx>) -> Option<(Ctx::Height, Round)> {
+    match msg {
+        Msg::PublishConsensusMsg(msg) => Some((msg.height(), msg.round())),
+        Msg::PublishLivenessMsg(LivenessMsg::Vote(vote)) => Some((vote.height(), vote.round())),
+        Msg::PublishLivenessMsg(LivenessMsg::PolkaCertificate(certificate)) => {
+            Some((certificate.height, certificate.round))
+        }
+        Msg::PublishLivenessMsg(LivenessMsg::SkipRoundCertificate(certificate)) => {
+            Some((certificate.height, certificate.round))
+        }
+        _ => None,
+    }
+}
+
+async fn update_reputation<Ctx>(
+    reputation: &mut Reputation,
+    ctrl_handle: &CtrlHandle,
+    output_port: &OutputPort<NetworkEvent<Ctx>>,
+    peer_id: PeerId,
+    update: Update,
+) -> Result<(), eyre::Report>
+where
+    Ctx: Context,
+{
+    let Some(value) = reputation.update(peer_id, update, Instant::now()) else {
+        trace!(%peer_id, ?update, reputation = %reputation.get(&peer_id), "Updated peer reputation");
+        return Ok(());
+    };
+
+    let ban_duration = reputation.config().ban_duration;
+
+    if ctrl_handle.ban_peer(peer_id, ban_duration).await? {
+        warn!(%peer_id, reputation = %value, ?ban_duration, "Banned peer because of its low reputation");
+
+        reputation.record_ban();
+        output_port.send(NetworkEvent::PeerBanned(peer_id));
+    }
+
+    Ok(())
+}
+
 async fn handle_dump_state<Ctx>(
     state: &mut State<Ctx>,
     reply_to: RpcReplyPort<Option<NetworkStateDump>>,
@@ -634,6 +1176,31 @@ async fn handle_dump_state<Ctx>(
     }
 }
 
+async fn handle_get_discovery_state<Ctx>(
+    state: &mut State<Ctx>,
+    reply_to: RpcReplyPort<Option<DiscoveryStats>>,
+) where
+    Ctx: Context,
+{
+    let stats = match state {
+        State::Stopped => {
+            info!("Getting discovery state: network not started");
+            None
+        }
+        State::Running { ctrl_handle, .. } => match ctrl_handle.discovery_state().await {
+            Ok(stats) => Some(stats),
+            Err(error) => {
+                error!(%error, "Failed to obtain discovery state");
+                None
+            }
+        },
+    };
+
+    if let Err(error) = reply_to.send(stats) {
+        error!(%error, "Failed to reply with discovery state");
+    }
+}
+
 async fn handle_update_persistent_peers<Ctx>(
     state: &mut State<Ctx>,
     op: PersistentPeersOp,
@@ -0,0 +1,553 @@
+//! Priority lanes for the outbound messages of the network actor.
+//!
+//! Outbound messages are queued in one of several lanes, and a dedicated task
+//! sends them to the network in order of priority, using weighted round-robin
+//! so that lower priority lanes are never starved completely.
+//!
+//! Each lane is bounded. When any other lane than the vote lane is full, its oldest
+//! droppable message is dropped to make room for the new one, see [`Outbound::is_droppable`].
+//! Otherwise, pushing a message waits until the sending task has made room for it,
+//! so that votes, proposal parts and replies are never lost and the network actor
+//! is slowed down to the pace of the network.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use libp2p::request_response;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::error;
+
+use malachitebft_config::PriorityLanesConfig;
+use malachitebft_metrics::prometheus::encoding::{EncodeLabelSet, EncodeLabelValue};
+use malachitebft_metrics::prometheus::metrics::counter::Counter;
+use malachitebft_metrics::prometheus::metrics::family::Family;
+use malachitebft_metrics::prometheus::metrics::gauge::Gauge;
+use malachitebft_metrics::SharedRegistry;
+use malachitebft_network::handle::CtrlHandle;
+use malachitebft_network::Channel;
+
+// Make prometheus_client available for the derive macros
+use malachitebft_metrics::prometheus as prometheus_client;
+
+/// A priority lane, from highest to lowest priority.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, EncodeLabelValue)]
+pub enum Lane {
+    /// Votes and liveness messages
+    Votes,
+    /// Proposals and proposal parts
+    Proposals,
+    /// Responses to sync requests
+    SyncResponses,
+    /// Status updates
+    Status,
+}
+
+impl Lane {
+    /// All lanes, in order of priority.
+    pub const ALL: [Lane; 4] = [
+        Lane::Votes,
+        Lane::Proposals,
+        Lane::SyncResponses,
+        Lane::Status,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn weight(self, config: &PriorityLanesConfig) -> u32 {
+        let weight = match self {
+            Lane::Votes => config.votes,
+            Lane::Proposals => config.proposals,
+            Lane::SyncResponses => config.sync_responses,
+            Lane::Status => config.status,
+        };
+
+        weight.max(1)
+    }
+}
+
+/// An outbound message, already encoded.
+#[derive(Clone, Debug)]
+pub enum Outbound {
+    Publish(Channel, Bytes),
+    Broadcast(Channel, Bytes),
+    SyncReply(request_response::InboundRequestId, Bytes),
+    PartReply(request_response::InboundRequestId, Bytes),
+}
+
+impl Outbound {
+    /// Whether the message can be dropped when its lane is full.
+    ///
+    /// Proposal parts are needed to reassemble their stream, and replies to answer their request,
+    /// so only the other gossip messages, which are superseded by the next ones, can be dropped.
+    pub fn is_droppable(&self) -> bool {
+        match self {
+            Outbound::Publish(channel, _) | Outbound::Broadcast(channel, _) => {
+                *channel != Channel::ProposalParts
+            }
+            Outbound::SyncReply(..) | Outbound::PartReply(..) => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct LaneLabels {
+    lane: Lane,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    /// Number of messages waiting to be sent, per lane
+    queue_depth: Family<LaneLabels, Gauge>,
+    /// Number of messages sent, per lane
+    sent: Family<LaneLabels, Counter>,
+    /// Number of messages dropped because their lane was full, per lane
+    dropped: Family<LaneLabels, Counter>,
+}
+
+impl Metrics {
+    pub fn register(registry: &SharedRegistry) -> Self {
+        let metrics = Self::default();
+
+        registry.with_prefix("malachitebft_network_lanes", |registry| {
+            registry.register(
+                "queue_depth",
+                "Number of outbound messages waiting to be sent, per priority lane",
+                metrics.queue_depth.clone(),
+            );
+
+            registry.register(
+                "sent",
+                "Number of outbound messages sent, per priority lane",
+                metrics.sent.clone(),
+            );
+
+            registry.register(
+                "dropped",
+                "Number of outbound messages dropped because their priority lane was full, per priority lane",
+                metrics.dropped.clone(),
+            );
+        });
+
+        metrics
+    }
+
+    fn set_depth(&self, lane: Lane, depth: usize) {
+        self.queue_depth
+            .get_or_create(&LaneLabels { lane })
+            .set(depth as i64);
+    }
+
+    fn inc_sent(&self, lane: Lane) {
+        self.sent.get_or_create(&LaneLabels { lane }).inc();
+    }
+
+    fn inc_dropped(&self, lane: Lane) {
+        self.dropped.get_or_create(&LaneLabels { lane }).inc();
+    }
+}
+
+/// The queues of all lanes, together with the state of the weighted round-robin scheduler.
+#[derive(Debug)]
+pub struct Queues {
+    weights: [u32; 4],
+    credits: [u32; 4],
+    capacity: usize,
+    queues: [VecDeque<Outbound>; 4],
+    closed: bool,
+}
+
+impl Queues {
+    pub fn new(config: &PriorityLanesConfig) -> Self {
+        let weights = Lane::ALL.map(|lane| lane.weight(config));
+
+        Self {
+            weights,
+            credits: weights,
+            capacity: config.capacity.max(1),
+            queues: Default::default(),
+            closed: false,
+        }
+    }
+
+    pub fn len(&self, lane: Lane) -> usize {
+        self.queues[lane.index()].len()
+    }
+
+    /// Queue a message in the given lane.
+    ///
+    /// If the lane is full, its oldest droppable message is dropped to make room and returned.
+    /// If the lane is the vote lane or holds no droppable message, the message is given back instead.
+    pub fn push(&mut self, lane: Lane, msg: Outbound) -> Result<Option<Outbound>, Outbound> {
+        let queue = &mut self.queues[lane.index()];
+
+        let dropped = if queue.len() >= self.capacity {
+            let oldest = queue
+                .iter()
+                .position(Outbound::is_droppable)
+                .filter(|_| lane != Lane::Votes);
+
+            match oldest {
+                Some(index) => queue.remove(index),
+                None => return Err(msg),
+            }
+        } else {
+            None
+        };
+
+        queue.push_back(msg);
+        Ok(dropped)
+    }
+
+    /// Pop the next message to send.
+    ///
+    /// Picks the highest priority non-empty lane which has credits left,
+    /// refilling the credits of all lanes once every non-empty lane has used up its credits.
+    pub fn pop(&mut self) -> Option<(Lane, Outbound)> {
+        for _ in 0..2 {
+            for lane in Lane::ALL {
+                let i = lane.index();
+
+                if self.credits[i] > 0 {
+                    if let Some(msg) = self.queues[i].pop_front() {
+                        self.credits[i] -= 1;
+                        return Some((lane, msg));
+                    }
+                }
+            }
+
+            if self.queues.iter().all(VecDeque::is_empty) {
+                break;
+            }
+
+            self.credits = self.weights;
+        }
+
+        None
+    }
+}
+
+struct Shared {
+    queues: Mutex<Queues>,
+    /// Signalled when a message is queued or the lanes are closed
+    notify: Notify,
+    /// Signalled when a message is taken out of a lane, per lane
+    room: [Notify; 4],
+    metrics: Metrics,
+}
+
+impl Shared {
+    async fn next(&self) -> Option<(Lane, Outbound)> {
+        loop {
+            {
+                let mut queues = self.queues.lock().expect("lanes lock poisoned");
+
+                if let Some((lane, msg)) = queues.pop() {
+                    self.metrics.set_depth(lane, queues.len(lane));
+
+                    self.room[lane.index()].notify_one();
+                    return Some((lane, msg));
+                }
+
+                if queues.closed {
+                    return None;
+                }
+            }
+
+            self.notify.notified().await;
+        }
+    }
+}
+
+/// Outbound priority lanes of the network actor.
+pub struct PriorityLanes {
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+impl PriorityLanes {
+    /// Spawn the task sending the queued messages to the network via the given handle.
+    pub fn spawn(ctrl: Arc<CtrlHandle>, config: &PriorityLanesConfig, metrics: Metrics) -> Self {
+        let shared = Arc::new(Shared {
+            queues: Mutex::new(Queues::new(config)),
+            notify: Notify::new(),
+            room: Default::default(),
+            metrics,
+        });
+
+        let task = tokio::spawn(send_loop(ctrl, Arc::clone(&shared)));
+
+        Self { shared, task }
+    }
+
+    /// Queue a message in the given lane.
+    ///
+    /// If the lane is full, its oldest droppable message is dropped to make room for the message,
+    /// or if there is none, waits until the sending task has made room for it.
+    pub async fn push(&self, lane: Lane, mut msg: Outbound) {
+        loop {
+            {
+                let mut queues = self.shared.queues.lock().expect("lanes lock poisoned");
+
+                match queues.push(lane, msg) {
+                    Ok(dropped) => {
+                        if dropped.is_some() {
+                            self.shared.metrics.inc_dropped(lane);
+                        }
+
+                        self.shared.metrics.set_depth(lane, queues.len(lane));
+                        break;
+                    }
+                    Err(rejected) => msg = rejected,
+                }
+            }
+
+            self.shared.room[lane.index()].notified().await;
+        }
+
+        self.shared.notify.notify_one();
+    }
+
+    /// Send all the messages still queued and wait for the sending task to finish.
+    pub async fn close(self) -> Result<(), tokio::task::JoinError> {
+        self.shared
+            .queues
+            .lock()
+            .expect("lanes lock poisoned")
+            .closed = true;
+
+        self.shared.notify.notify_one();
+        self.task.await
+    }
+}
+
+async fn send_loop(ctrl: Arc<CtrlHandle>, shared: Arc<Shared>) {
+    while let Some((lane, msg)) = shared.next().await {
+        let result = match msg {
+            Outbound::Publish(channel, data) => ctrl.publish(channel, data).await,
+            Outbound::Broadcast(channel, data) => ctrl.broadcast(channel, data).await,
+            Outbound::SyncReply(request_id, data) => ctrl.sync_reply(request_id, data).await,
+            Outbound::PartReply(request_id, data) => ctrl.part_reply(request_id, data).await,
+        };
+
+        match result {
+            Ok(()) => shared.metrics.inc_sent(lane),
+            Err(e) => error!(?lane, "Failed to send outbound message: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn msg(n: u8) -> Outbound {
+        Outbound::Publish(Channel::Consensus, Bytes::from(vec![n]))
+    }
+
+    fn payload(msg: Outbound) -> u8 {
+        match msg {
+            Outbound::Publish(_, data) => data[0],
+            _ => unreachable!(),
+        }
+    }
+
+    fn drain(queues: &mut Queues) -> Vec<(Lane, u8)> {
+        std::iter::from_fn(|| queues.pop())
+            .map(|(lane, msg)| (lane, payload(msg)))
+            .collect()
+    }
+
+    #[test]
+    fn higher_priority_first() {
+        let mut queues = Queues::new(&PriorityLanesConfig::default());
+
+        queues.push(Lane::Status, msg(0)).unwrap();
+        queues.push(Lane::SyncResponses, msg(1)).unwrap();
+        queues.push(Lane::Proposals, msg(2)).unwrap();
+        queues.push(Lane::Votes, msg(3)).unwrap();
+
+        assert_eq!(
+            drain(&mut queues),
+            vec![
+                (Lane::Votes, 3),
+                (Lane::Proposals, 2),
+                (Lane::SyncResponses, 1),
+                (Lane::Status, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn weighted_round_robin() {
+        let config = PriorityLanesConfig {
+            votes: 3,
+            proposals: 1,
+            sync_responses: 0,
+            status: 1,
+            ..PriorityLanesConfig::default()
+        };
+
+        let mut queues = Queues::new(&config);
+
+        for i in 0..5 {
+            queues.push(Lane::Votes, msg(i)).unwrap();
+            queues.push(Lane::Proposals, msg(i)).unwrap();
+        }
+        queues.push(Lane::SyncResponses, msg(0)).unwrap();
+
+        let lanes = drain(&mut queues)
+            .into_iter()
+            .map(|(lane, _)| lane)
+            .collect::<Vec<_>>();
+
+        use Lane::*;
+        assert_eq!(
+            lanes,
+            vec![
+                Votes,
+                Votes,
+                Votes,
+                Proposals,
+                SyncResponses, // weight of zero is treated as one
+                Votes,
+                Votes,
+                Proposals,
+                Proposals,
+                Proposals,
+                Proposals,
+            ]
+        );
+    }
+
+    #[test]
+    fn preserves_order_within_lane() {
+        let mut queues = Queues::new(&PriorityLanesConfig::default());
+
+        for i in 0..20 {
+            queues.push(Lane::Proposals, msg(i)).unwrap();
+        }
+
+        let payloads = drain(&mut queues)
+            .into_iter()
+            .map(|(_, n)| n)
+            .collect::<Vec<_>>();
+
+        assert_eq!(payloads, (0..20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn full_lane_drops_oldest() {
+        let config = PriorityLanesConfig {
+            capacity: 3,
+            ..PriorityLanesConfig::default()
+        };
+
+        let mut queues = Queues::new(&config);
+
+        for i in 0..3 {
+            assert!(queues.push(Lane::Proposals, msg(i)).unwrap().is_none());
+        }
+
+        assert_eq!(queues.len(Lane::Proposals), 3);
+        assert_eq!(queues.len(Lane::Votes), 0);
+
+        let dropped = queues.push(Lane::Proposals, msg(3)).unwrap().map(payload);
+        assert_eq!(dropped, Some(0));
+        assert_eq!(queues.len(Lane::Proposals), 3);
+
+        let payloads = drain(&mut queues)
+            .into_iter()
+            .map(|(_, n)| n)
+            .collect::<Vec<_>>();
+
+        assert_eq!(payloads, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn full_lane_keeps_proposal_parts() {
+        let config = PriorityLanesConfig {
+            capacity: 3,
+            ..PriorityLanesConfig::default()
+        };
+
+        let mut queues = Queues::new(&config);
+
+        let part = |n| Outbound::Publish(Channel::ProposalParts, Bytes::from(vec![n]));
+
+        queues.push(Lane::Proposals, part(0)).unwrap();
+        queues.push(Lane::Proposals, msg(1)).unwrap();
+        queues.push(Lane::Proposals, part(2)).unwrap();
+
+        // The proposal is the only message which can be dropped
+        let dropped = queues.push(Lane::Proposals, part(3)).unwrap().map(payload);
+        assert_eq!(dropped, Some(1));
+
+        // The lane now only holds proposal parts
+        assert!(queues.push(Lane::Proposals, msg(4)).is_err());
+        assert!(queues.push(Lane::Proposals, part(4)).is_err());
+        assert_eq!(queues.len(Lane::Proposals), 3);
+    }
+
+    #[tokio::test]
+    async fn full_vote_lane_waits_for_room() {
+        let config = PriorityLanesConfig {
+            capacity: 2,
+            ..PriorityLanesConfig::default()
+        };
+
+        let shared = Arc::new(Shared {
+            queues: Mutex::new(Queues::new(&config)),
+            notify: Notify::new(),
+            room: Default::default(),
+            metrics: Metrics::default(),
+        });
+
+        let lanes = Arc::new(PriorityLanes {
+            shared: Arc::clone(&shared),
+            task: tokio::spawn(async {}),
+        });
+
+        lanes.push(Lane::Votes, msg(0)).await;
+        lanes.push(Lane::Votes, msg(1)).await;
+
+        let push = tokio::spawn({
+            let lanes = Arc::clone(&lanes);
+            async move { lanes.push(Lane::Votes, msg(2)).await }
+        });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!push.is_finished());
+
+        let (lane, first) = shared.next().await.unwrap();
+        assert_eq!((lane, payload(first)), (Lane::Votes, 0));
+
+        push.await.unwrap();
+
+        {
+            let mut queues = shared.queues.lock().unwrap();
+            assert_eq!(drain(&mut queues), vec![(Lane::Votes, 1), (Lane::Votes, 2)]);
+        }
+
+        // Other lanes drop their oldest message instead of waiting
+        for i in 0..3 {
+            lanes.push(Lane::Proposals, msg(i)).await;
+        }
+
+        let dropped = |lane| {
+            shared
+                .metrics
+                .dropped
+                .get_or_create(&LaneLabels { lane })
+                .get()
+        };
+
+        assert_eq!(dropped(Lane::Votes), 0);
+        assert_eq!(dropped(Lane::Proposals), 1);
+    }
+}
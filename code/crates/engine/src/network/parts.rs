@@ -0,0 +1,251 @@
+//! Repair of incomplete streams of proposal parts.
+//!
+//! Proposal parts are gossiped, so a single lost part would otherwise cause the whole
+//! proposal to time out. The network actor keeps track of the sequences received on each
+//! stream and, once the end of a stream has been received with parts still missing,
+//! requests these parts directly from the proposer and from a few other peers.
+//!
+//! Every node caches the encoded parts it has recently published or received,
+//! so that it can serve the requests of its peers.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use malachitebft_network::{PeerExit, PeerId, PeerState};
+
+use crate::util::streaming::{Sequence, StreamId};
+
+/// Maximum number of parts kept in the cache.
+const MAX_CACHED_PARTS: usize = 4096;
+
+/// Maximum number of streams tracked at once.
+const MAX_TRACKED_STREAMS: usize = 256;
+
+/// Highest sequence accepted on a stream, bounding the number of parts requested for a single stream.
+const MAX_SEQUENCE: Sequence = 4096;
+
+/// Request for the part at the given index of a stream of proposal parts.
+///
+/// Wire format: `index (u64 big-endian) || stream_id`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartRequest {
+    pub stream_id: StreamId,
+    pub index: Sequence,
+}
+
+impl PartRequest {
+    pub fn new(stream_id: StreamId, index: Sequence) -> Self {
+        Self { stream_id, index }
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(8 + self.stream_id.0.len());
+        buf.put_u64(self.index);
+        buf.put_slice(&self.stream_id.0);
+        buf.freeze()
+    }
+
+    pub fn decode(mut bytes: Bytes) -> Option<Self> {
+        if bytes.len() < 8 {
+            return None;
+        }
+
+        let index = bytes.get_u64();
+        Some(Self::new(StreamId::new(bytes), index))
+    }
+}
+
+/// Cache of the encoded proposal parts recently published or received by this node,
+/// evicting the oldest parts first.
+#[derive(Debug, Default)]
+pub struct PartCache {
+    parts: HashMap<(StreamId, Sequence), Bytes>,
+    order: VecDeque<(StreamId, Sequence)>,
+}
+
+impl PartCache {
+    pub fn insert(&mut self, stream_id: StreamId, sequence: Sequence, data: Bytes) {
+        let key = (stream_id, sequence);
+
+        if self.parts.insert(key.clone(), data).is_some() {
+            return;
+        }
+
+        self.order.push_back(key);
+
+        while self.order.len() > MAX_CACHED_PARTS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.parts.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, request: &PartRequest) -> Option<&Bytes> {
+        self.parts.get(&(request.stream_id.clone(), request.index))
+    }
+}
+
+#[derive(Debug, Default)]
+struct StreamState {
+    received: BTreeSet<Sequence>,
+    fin: Option<Sequence>,
+}
+
+impl StreamState {
+    fn missing(&self) -> Vec<Sequence> {
+        let Some(fin) = self.fin else {
+            return Vec::new();
+        };
+
+        (0..fin)
+            .filter(|seq| !self.received.contains(seq))
+            .collect()
+    }
+}
+
+/// Sequences received on the streams of proposal parts, per sender and stream.
+#[derive(Debug, Default)]
+pub struct StreamTracker {
+    streams: HashMap<(PeerId, StreamId), StreamState>,
+    order: VecDeque<(PeerId, StreamId)>,
+}
+
+impl StreamTracker {
+    /// Record that the part with the given sequence was received on a stream.
+    ///
+    /// Returns `true` if the part was not received before.
+    pub fn record(
+        &mut self,
+        peer: PeerId,
+        stream_id: StreamId,
+        sequence: Sequence,
+        is_fin: bool,
+    ) -> bool {
+        if sequence > MAX_SEQUENCE {
+            return false;
+        }
+
+        let key = (peer, stream_id);
+
+        if !self.streams.contains_key(&key) {
+            self.order.push_back(key.clone());
+
+            while self.order.len() > MAX_TRACKED_STREAMS {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.streams.remove(&oldest);
+                }
+            }
+        }
+
+        let stream = self.streams.entry(key).or_default();
+
+        if is_fin {
+            stream.fin = Some(sequence);
+        }
+
+        stream.received.insert(sequence)
+    }
+
+    /// Sequences missing from a stream, empty until the end of the stream has been received.
+    pub fn missing(&self, peer: PeerId, stream_id: &StreamId) -> Vec<Sequence> {
+        self.streams
+            .get(&(peer, stream_id.clone()))
+            .map(StreamState::missing)
+            .unwrap_or_default()
+    }
+
+    /// Whether the part with the given sequence is missing from a stream.
+    pub fn is_missing(&self, peer: PeerId, stream_id: &StreamId, sequence: Sequence) -> bool {
+        self.streams
+            .get(&(peer, stream_id.clone()))
+            .is_some_and(|stream| {
+                stream.fin.is_some_and(|fin| sequence < fin) && !stream.received.contains(&sequence)
+            })
+    }
+
+    /// Proposer of the incomplete stream with the given id, if any.
+    pub fn incomplete_stream_sender(&self, stream_id: &StreamId) -> Option<PeerId> {
+        self.streams
+            .iter()
+            .find(|((_, id), stream)| id == stream_id && !stream.missing().is_empty())
+            .map(|((peer, _), _)| *peer)
+    }
+}
+
+impl PeerState for StreamTracker {
+    fn forget_peer(&mut self, peer_id: &PeerId, _exit: PeerExit) {
+        self.streams.retain(|(peer, _), _| peer != peer_id);
+        self.order.retain(|(peer, _)| peer != peer_id);
+    }
+
+    fn peer_entries(&self) -> usize {
+        self.streams.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream_id(n: u8) -> StreamId {
+        StreamId::new(Bytes::from(vec![n; 4]))
+    }
+
+    #[test]
+    fn part_request_roundtrip() {
+        let request = PartRequest::new(stream_id(1), 42);
+        assert_eq!(PartRequest::decode(request.encode()), Some(request));
+        assert_eq!(PartRequest::decode(Bytes::from_static(&[0; 4])), None);
+    }
+
+    #[test]
+    fn missing_parts_once_fin_received() {
+        let peer = PeerId::random();
+        let mut tracker = StreamTracker::default();
+
+        assert!(tracker.record(peer, stream_id(1), 0, false));
+        assert!(tracker.record(peer, stream_id(1), 2, false));
+        assert!(!tracker.record(peer, stream_id(1), 2, false));
+
+        // The end of the stream is unknown, so nothing is missing yet
+        assert!(tracker.missing(peer, &stream_id(1)).is_empty());
+
+        tracker.record(peer, stream_id(1), 4, true);
+        assert_eq!(tracker.missing(peer, &stream_id(1)), vec![1, 3]);
+        assert!(tracker.is_missing(peer, &stream_id(1), 3));
+        assert!(!tracker.is_missing(peer, &stream_id(1), 4));
+        assert_eq!(tracker.incomplete_stream_sender(&stream_id(1)), Some(peer));
+
+        tracker.record(peer, stream_id(1), 1, false);
+        tracker.record(peer, stream_id(1), 3, false);
+        assert!(tracker.missing(peer, &stream_id(1)).is_empty());
+        assert_eq!(tracker.incomplete_stream_sender(&stream_id(1)), None);
+    }
+
+    #[test]
+    fn streams_of_churned_peers_are_forgotten() {
+        let mut tracker = StreamTracker::default();
+        let peer = PeerId::random();
+
+        tracker.record(peer, stream_id(1), 0, false);
+        tracker.record(peer, stream_id(2), 0, false);
+        tracker.record(PeerId::random(), stream_id(1), 0, false);
+
+        tracker.forget_peer(&peer, PeerExit::Disconnected);
+        assert_eq!(tracker.peer_entries(), 1);
+        assert!(!tracker.order.iter().any(|(p, _)| *p == peer));
+    }
+
+    #[test]
+    fn cache_evicts_oldest_parts() {
+        let mut cache = PartCache::default();
+
+        for seq in 0..=MAX_CACHED_PARTS as Sequence {
+            cache.insert(stream_id(1), seq, Bytes::from_static(b"part"));
+        }
+
+        assert!(cache.get(&PartRequest::new(stream_id(1), 0)).is_none());
+        assert!(cache.get(&PartRequest::new(stream_id(1), 1)).is_some());
+    }
+}
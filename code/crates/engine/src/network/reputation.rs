@@ -0,0 +1,367 @@
+//! Reputation of peers, used to disconnect and ban misbehaving peers.
+//!
+//! The reputation of a peer combines its sync score, as computed by the sync actor,
+//! with penalties for invalid messages and rate limit violations.
+//! Penalties decay exponentially over time so that peers can recover from occasional misbehavior.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use malachitebft_config::ReputationConfig;
+use malachitebft_metrics::prometheus::encoding::{EncodeLabelSet, EncodeLabelValue};
+use malachitebft_metrics::prometheus::metrics::counter::Counter;
+use malachitebft_metrics::prometheus::metrics::family::Family;
+use malachitebft_metrics::SharedRegistry;
+use malachitebft_network::{PeerExit, PeerId, PeerState};
+
+// Make prometheus_client available for the derive macros
+use malachitebft_metrics::prometheus as prometheus_client;
+
+/// Sync score assumed for peers we have no sync score for yet.
+const NEUTRAL_SYNC_SCORE: f64 = 0.5;
+
+/// Penalties below this value are considered fully decayed.
+const NEGLIGIBLE_PENALTY: f64 = 1.0;
+
+/// An update to the reputation of a peer.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Update {
+    /// New sync score of the peer, in the `0.0..=1.0` range
+    SyncScore(f64),
+    /// The peer sent a message which could not be decoded
+    InvalidMessage,
+    /// The peer violated a rate limit
+    RateLimitViolation,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, EncodeLabelValue)]
+enum Penalty {
+    InvalidMessage,
+    RateLimitViolation,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct PenaltyLabels {
+    kind: Penalty,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    /// Number of penalties applied, per kind
+    penalties: Family<PenaltyLabels, Counter>,
+    /// Number of peers banned because of a low reputation
+    banned_peers: Counter,
+}
+
+impl Metrics {
+    pub fn register(registry: &SharedRegistry) -> Self {
+        let metrics = Self::default();
+
+        registry.with_prefix("malachitebft_network_reputation", |registry| {
+            registry.register(
+                "penalties",
+                "Number of reputation penalties applied to peers, per kind",
+                metrics.penalties.clone(),
+            );
+
+            registry.register(
+                "banned_peers",
+                "Number of peers banned because of a low reputation",
+                metrics.banned_peers.clone(),
+            );
+        });
+
+        metrics
+    }
+
+    fn inc_penalty(&self, kind: Penalty) {
+        self.penalties.get_or_create(&PenaltyLabels { kind }).inc();
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct PeerReputation {
+    sync_score: Option<f64>,
+    penalty: f64,
+    last_decay: Instant,
+}
+
+impl PeerReputation {
+    fn new(now: Instant) -> Self {
+        Self {
+            sync_score: None,
+            penalty: 0.0,
+            last_decay: now,
+        }
+    }
+
+    fn decay(&mut self, config: &ReputationConfig, now: Instant) {
+        let half_life = config.penalty_half_life.as_secs_f64();
+        let elapsed = now.saturating_duration_since(self.last_decay).as_secs_f64();
+
+        if half_life > 0.0 {
+            self.penalty *= 0.5_f64.powf(elapsed / half_life);
+        }
+
+        self.last_decay = now;
+    }
+
+    fn value(&self, config: &ReputationConfig) -> f64 {
+        let sync_score = self.sync_score.unwrap_or(NEUTRAL_SYNC_SCORE);
+        config.sync_score_weight * (sync_score - NEUTRAL_SYNC_SCORE) - self.penalty
+    }
+}
+
+/// Per-peer reputation, together with the policy deciding when to ban a peer.
+pub struct Reputation {
+    config: ReputationConfig,
+    peers: HashMap<PeerId, PeerReputation>,
+    metrics: Metrics,
+}
+
+impl Reputation {
+    pub fn new(config: ReputationConfig, metrics: Metrics) -> Self {
+        Self {
+            config,
+            peers: HashMap::new(),
+            metrics,
+        }
+    }
+
+    pub fn config(&self) -> &ReputationConfig {
+        &self.config
+    }
+
+    /// Current reputation of the given peer.
+    pub fn get(&self, peer_id: &PeerId) -> f64 {
+        self.peers
+            .get(peer_id)
+            .map_or(0.0, |peer| peer.value(&self.config))
+    }
+
+    /// Apply an update to the reputation of a peer.
+    ///
+    /// Returns the new reputation of the peer if it fell below the ban threshold,
+    /// in which case the peer is forgotten and should be banned.
+    /// Always returns `None` if the policy is disabled.
+    pub fn update(&mut self, peer_id: PeerId, update: Update, now: Instant) -> Option<f64> {
+        if !self.config.enabled {
+            return None;
+        }
+
+        let peer = self
+            .peers
+            .entry(peer_id)
+            .or_insert_with(|| PeerReputation::new(now));
+
+        peer.decay(&self.config, now);
+
+        match update {
+            Update::SyncScore(score) => {
+                peer.sync_score = Some(score);
+            }
+            Update::InvalidMessage => {
+                peer.penalty += self.config.invalid_message_penalty;
+                self.metrics.inc_penalty(Penalty::InvalidMessage);
+            }
+            Update::RateLimitViolation => {
+                peer.penalty += self.config.rate_limit_penalty;
+                self.metrics.inc_penalty(Penalty::RateLimitViolation);
+            }
+        }
+
+        let reputation = peer.value(&self.config);
+
+        if reputation < self.config.ban_threshold {
+            self.peers.remove(&peer_id);
+            return Some(reputation);
+        }
+
+        None
+    }
+
+    /// Record that a peer was banned because of its low reputation.
+    pub fn record_ban(&self) {
+        self.metrics.banned_peers.inc();
+    }
+
+    /// Forget the sync score of a disconnected peer, and the peer itself
+    /// if its penalties have decayed, while remembering recent misbehavior
+    /// across reconnections.
+    ///
+    /// Other peers whose penalties have decayed since they disconnected are forgotten too,
+    /// so that peers which never reconnect are eventually forgotten.
+    pub fn peer_disconnected(&mut self, peer_id: &PeerId, now: Instant) {
+        if let Some(peer) = self.peers.get_mut(peer_id) {
+            peer.sync_score = None;
+        }
+
+        let config = &self.config;
+
+        // Keep the peers with a sync score, which is only forgotten on disconnection,
+        // and the peers whose penalties have not fully decayed
+        self.peers.retain(|_, peer| {
+            if peer.sync_score.is_some() {
+                return true;
+            }
+
+            peer.decay(config, now);
+            peer.penalty >= NEGLIGIBLE_PENALTY
+        });
+    }
+}
+
+impl PeerState for Reputation {
+    fn forget_peer(&mut self, peer_id: &PeerId, exit: PeerExit) {
+        match exit {
+            PeerExit::Disconnected => self.peer_disconnected(peer_id, Instant::now()),
+            PeerExit::Banned => {
+                self.peers.remove(peer_id);
+            }
+        }
+    }
+
+    fn peer_entries(&self) -> usize {
+        self.peers.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn reputation(config: ReputationConfig) -> Reputation {
+        Reputation::new(config, Metrics::default())
+    }
+
+    fn enabled() -> ReputationConfig {
+        ReputationConfig {
+            enabled: true,
+            ..ReputationConfig::default()
+        }
+    }
+
+    #[test]
+    fn disabled_never_bans() {
+        let mut rep = reputation(ReputationConfig::default());
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        for _ in 0..100 {
+            assert_eq!(rep.update(peer, Update::InvalidMessage, now), None);
+        }
+
+        assert_eq!(rep.get(&peer), 0.0);
+    }
+
+    #[test]
+    fn bans_below_threshold() {
+        let config = ReputationConfig {
+            ban_threshold: -25.0,
+            invalid_message_penalty: 10.0,
+            rate_limit_penalty: 5.0,
+            ..enabled()
+        };
+
+        let mut rep = reputation(config);
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        assert_eq!(rep.update(peer, Update::InvalidMessage, now), None);
+        assert_eq!(rep.update(peer, Update::RateLimitViolation, now), None);
+        assert_eq!(rep.update(peer, Update::InvalidMessage, now), None);
+        assert_eq!(rep.get(&peer), -25.0);
+
+        assert_eq!(
+            rep.update(peer, Update::RateLimitViolation, now),
+            Some(-30.0)
+        );
+
+        // The banned peer is forgotten
+        assert_eq!(rep.get(&peer), 0.0);
+    }
+
+    #[test]
+    fn sync_score_contributes() {
+        let config = ReputationConfig {
+            ban_threshold: -15.0,
+            sync_score_weight: 20.0,
+            invalid_message_penalty: 10.0,
+            ..enabled()
+        };
+
+        let mut rep = reputation(config);
+        let good = PeerId::random();
+        let bad = PeerId::random();
+        let now = Instant::now();
+
+        assert_eq!(rep.update(good, Update::SyncScore(1.0), now), None);
+        assert_eq!(rep.update(bad, Update::SyncScore(0.0), now), None);
+        assert_eq!(rep.get(&good), 10.0);
+        assert_eq!(rep.get(&bad), -10.0);
+
+        assert_eq!(rep.update(good, Update::InvalidMessage, now), None);
+        assert_eq!(rep.update(bad, Update::InvalidMessage, now), Some(-20.0));
+    }
+
+    #[test]
+    fn penalties_decay() {
+        let config = ReputationConfig {
+            invalid_message_penalty: 40.0,
+            penalty_half_life: Duration::from_secs(60),
+            ..enabled()
+        };
+
+        let mut rep = reputation(config);
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        assert_eq!(rep.update(peer, Update::InvalidMessage, now), None);
+        assert_eq!(rep.get(&peer), -40.0);
+
+        let later = now + Duration::from_secs(120);
+        assert_eq!(rep.update(peer, Update::SyncScore(0.5), later), None);
+        assert!((rep.get(&peer) + 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn disconnect_keeps_recent_penalties() {
+        let mut rep = reputation(enabled());
+        let clean = PeerId::random();
+        let penalized = PeerId::random();
+        let now = Instant::now();
+
+        rep.update(clean, Update::SyncScore(1.0), now);
+        rep.update(penalized, Update::InvalidMessage, now);
+
+        rep.peer_disconnected(&clean, now);
+        rep.peer_disconnected(&penalized, now);
+
+        assert!(!rep.peers.contains_key(&clean));
+        assert_eq!(rep.get(&penalized), -rep.config().invalid_message_penalty);
+    }
+
+    #[test]
+    fn churned_peers_are_forgotten_once_penalties_decay() {
+        let mut rep = reputation(enabled());
+        let start = Instant::now();
+
+        for _ in 0..2000 {
+            let peer = PeerId::random();
+            rep.update(peer, Update::SyncScore(0.8), start);
+            rep.update(peer, Update::InvalidMessage, start);
+            rep.forget_peer(&peer, PeerExit::Disconnected);
+        }
+
+        // Recent misbehavior is remembered
+        assert_eq!(rep.peer_entries(), 2000);
+
+        // Until the penalties have decayed, at the next disconnection
+        let later = start + rep.config().penalty_half_life * 64;
+        rep.peer_disconnected(&PeerId::random(), later);
+
+        assert_eq!(rep.peer_entries(), 0);
+    }
+}
@@ -1,17 +1,48 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
-use ractor::{Actor, ActorProcessingErr, ActorRef, SupervisionEvent};
+use ractor::rpc::CallResult;
+use ractor::{
+    Actor, ActorCell, ActorProcessingErr, ActorRef, RactorErr, RpcReplyPort, SupervisionEvent,
+};
 use tokio::task::JoinHandle;
-use tracing::{error, info, warn};
+use tokio::time::Instant;
+use tracing::{debug, error, info, warn};
 
 use malachitebft_core_types::Context;
 
 use crate::consensus::ConsensusRef;
 use crate::host::HostRef;
 use crate::network::NetworkRef;
-use crate::sync::SyncRef;
+use crate::sync::{self, SyncCheckpoint, SyncRef};
+use crate::util::events::{Event, TxEvent};
 use crate::wal::WalRef;
 
-pub type NodeRef = ActorRef<()>;
+pub type NodeRef = ActorRef<Msg>;
+pub type NodeMsg = Msg;
+
+pub enum Msg {
+    /// Shut down the node gracefully, replying once all the actors have stopped.
+    Stop(RpcReplyPort<()>),
+
+    /// Change the settings of sync which can be changed while the node is running.
+    /// Ignored if sync is disabled.
+    ReconfigureSync(sync::Reconfiguration),
+
+    /// Stop requesting values from peers until sync is resumed with [`Msg::ResumeSync`].
+    /// Ignored if sync is disabled.
+    PauseSync,
+
+    /// Resume the requests for values paused with [`Msg::PauseSync`].
+    /// Ignored if sync is disabled.
+    ResumeSync,
+}
+
+#[derive(Default)]
+pub struct State {
+    /// Whether the node is shutting down
+    stopping: bool,
+}
 
 #[allow(dead_code)]
 pub struct Node<Ctx: Context> {
@@ -21,6 +52,8 @@ pub struct Node<Ctx: Context> {
     wal: WalRef<Ctx>,
     sync: Option<SyncRef<Ctx>>,
     host: HostRef<Ctx>,
+    tx_event: TxEvent<Ctx>,
+    drain_timeout: Duration,
     span: tracing::Span,
 }
 
@@ -36,6 +69,8 @@ where
         wal: WalRef<Ctx>,
         sync: Option<SyncRef<Ctx>>,
         host: HostRef<Ctx>,
+        tx_event: TxEvent<Ctx>,
+        drain_timeout: Duration,
         span: tracing::Span,
     ) -> Self {
         Self {
@@ -45,13 +80,125 @@ where
             wal,
             sync,
             host,
+            tx_event,
+            drain_timeout,
             span,
         }
     }
 
-    pub async fn spawn(self) -> Result<(ActorRef<()>, JoinHandle<()>), ractor::SpawnErr> {
+    pub async fn spawn(self) -> Result<(NodeRef, JoinHandle<()>), ractor::SpawnErr> {
         Actor::spawn(None, self, ()).await
     }
+
+    /// Shut down the actors in an order which avoids losing state:
+    ///
+    /// 1. Drain the network, so that no more messages are received from peers,
+    ///    and the messages already queued are sent out.
+    /// 2. Abandon the in-flight sync requests and checkpoint the state of sync.
+    /// 3. Drain consensus, so that the inputs it already received are appended to the WAL.
+    /// 4. Drain the WAL, which flushes it to disk.
+    /// 5. Stop sync and the host.
+    ///
+    /// Each actor which does not stop before the drain timeout elapses is killed.
+    /// Returns the sync checkpoint, if any, and whether some actor had to be killed.
+    async fn shutdown(&self) -> (Option<SyncCheckpoint<Ctx>>, bool) {
+        let deadline = Instant::now() + self.drain_timeout;
+        let mut timed_out = false;
+
+        timed_out |= !drain_actor("network", &self.network, deadline).await;
+
+        let checkpoint = match &self.sync {
+            Some(actor) => checkpoint_sync(actor, deadline).await,
+            None => None,
+        };
+
+        timed_out |= !drain_actor("consensus", &self.consensus, deadline).await;
+        timed_out |= !drain_actor("wal", &self.wal, deadline).await;
+
+        if let Some(actor) = &self.sync {
+            timed_out |= !drain_actor("sync", actor, deadline).await;
+        }
+
+        timed_out |= !drain_actor("host", &self.host, deadline).await;
+
+        (checkpoint, timed_out)
+    }
+}
+
+/// Shut down the node gracefully, and wait until all its actors have stopped.
+pub async fn stop(node: &NodeRef) -> Result<(), ActorProcessingErr> {
+    match node.call(Msg::Stop, None).await? {
+        CallResult::Success(()) => Ok(()),
+        CallResult::Timeout => Err("timed out waiting for the node to stop".into()),
+        CallResult::SenderError => Err("node stopped without replying".into()),
+    }
+}
+
+/// Change the settings of sync which can be changed while the node is running.
+pub fn reconfigure_sync(
+    node: &NodeRef,
+    reconfiguration: sync::Reconfiguration,
+) -> Result<(), ActorProcessingErr> {
+    node.cast(Msg::ReconfigureSync(reconfiguration))
+        .map_err(Into::into)
+}
+
+/// Stop requesting values from peers until sync is resumed with [`resume_sync`],
+/// while still serving the requests of peers.
+pub fn pause_sync(node: &NodeRef) -> Result<(), ActorProcessingErr> {
+    node.cast(Msg::PauseSync).map_err(Into::into)
+}
+
+/// Resume the requests for values paused with [`pause_sync`].
+pub fn resume_sync(node: &NodeRef) -> Result<(), ActorProcessingErr> {
+    node.cast(Msg::ResumeSync).map_err(Into::into)
+}
+
+/// Drain the mailbox of the given actor and wait for it to stop, until the deadline.
+/// Kill the actor if it has not stopped by then, and return whether it stopped in time.
+async fn drain_actor(name: &str, actor: &ActorCell, deadline: Instant) -> bool {
+    let timeout = deadline.saturating_duration_since(Instant::now());
+
+    match actor.drain_and_wait(Some(timeout)).await {
+        Ok(()) => {
+            debug!("Actor {name} has stopped");
+            true
+        }
+        Err(RactorErr::Timeout) => {
+            warn!("Actor {name} did not stop within the drain timeout, killing it");
+            actor.kill();
+            false
+        }
+        Err(e) => {
+            debug!("Actor {name} was already stopped: {e}");
+            true
+        }
+    }
+}
+
+async fn checkpoint_sync<Ctx: Context>(
+    actor: &SyncRef<Ctx>,
+    deadline: Instant,
+) -> Option<SyncCheckpoint<Ctx>> {
+    let timeout = deadline.saturating_duration_since(Instant::now());
+
+    match actor
+        .call(
+            |reply_to| sync::Msg::Checkpoint(reply_to.into()),
+            Some(timeout),
+        )
+        .await
+    {
+        Ok(CallResult::Success(checkpoint)) => Some(checkpoint),
+        Ok(CallResult::Timeout) => {
+            warn!("Timed out waiting for the sync state to be checkpointed");
+            None
+        }
+        Ok(CallResult::SenderError) | Err(_) => {
+            warn!("Sync actor stopped before checkpointing its state");
+            None
+        }
+    }
 }
 
 #[async_trait]
@@ -59,15 +206,15 @@ impl<Ctx> Actor for Node<Ctx>
 where
     Ctx: Context,
 {
-    type Msg = ();
-    type State = ();
+    type Msg = Msg;
+    type State = State;
     type Arguments = ();
 
     async fn pre_start(
         &self,
         myself: ActorRef<Self::Msg>,
         _args: (),
-    ) -> Result<(), ActorProcessingErr> {
+    ) -> Result<State, ActorProcessingErr> {
         // Set ourselves as the supervisor of the other actors
         self.network.link(myself.get_cell());
         self.consensus.link(myself.get_cell());
@@ -78,16 +225,52 @@ where
             actor.link(myself.get_cell());
         }
 
-        Ok(())
+        Ok(State::default())
     }
 
     #[tracing::instrument(name = "node", parent = &self.span, skip_all)]
     async fn handle(
         &self,
-        _myself: ActorRef<Self::Msg>,
-        _msg: Self::Msg,
-        _state: &mut (),
+        myself: ActorRef<Self::Msg>,
+        msg: Self::Msg,
+        state: &mut State,
     ) -> Result<(), ActorProcessingErr> {
+        match msg {
+            Msg::Stop(reply_to) => {
+                state.stopping = true;
+
+                info!(drain_timeout = ?self.drain_timeout, "Shutting down the node");
+                self.tx_event.send(|| Event::ShutdownStarted);
+
+                let (sync, timed_out) = self.shutdown().await;
+
+                info!(timed_out, "Node has shut down");
+                self.tx_event
+                    .send(|| Event::ShutdownCompleted { sync, timed_out });
+
+                if let Err(e) = reply_to.send(()) {
+                    error!("Failed to reply to the stop request: {e:?}");
+                }
+
+                myself.stop(Some("Node has shut down".to_string()));
+            }
+
+            Msg::ReconfigureSync(reconfiguration) => match &self.sync {
+                Some(actor) => actor.cast(sync::Msg::Reconfigure(reconfiguration))?,
+                None => debug!("Ignoring the sync reconfiguration as sync is disabled"),
+            },
+
+            Msg::PauseSync => match &self.sync {
+                Some(actor) => actor.cast(sync::Msg::Pause(sync::PauseReason::Application))?,
+                None => debug!("Ignoring the request to pause sync as sync is disabled"),
+            },
+
+            Msg::ResumeSync => match &self.sync {
+                Some(actor) => actor.cast(sync::Msg::Resume(sync::PauseReason::Application))?,
+                None => debug!("Ignoring the request to resume sync as sync is disabled"),
+            },
+        }
+
         Ok(())
     }
 
@@ -96,12 +279,19 @@ where
         &self,
         _myself: ActorRef<Self::Msg>,
         evt: SupervisionEvent,
-        _state: &mut (),
+        state: &mut State,
     ) -> Result<(), ActorProcessingErr> {
         match evt {
             SupervisionEvent::ActorStarted(cell) => {
                 info!(actor = %cell.get_id(), "Actor has started");
             }
+            SupervisionEvent::ActorTerminated(cell, _state, reason) if state.stopping => {
+                debug!(
+                    "Actor {} has stopped: {}",
+                    cell.get_id(),
+                    reason.unwrap_or_default()
+                );
+            }
             SupervisionEvent::ActorTerminated(cell, _state, reason) => {
                 warn!(
                     "Actor {} has terminated: {}",
@@ -1,6 +1,8 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt;
 use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -8,7 +10,7 @@ use bytes::Bytes;
 use bytesize::ByteSize;
 use derive_where::derive_where;
 use eyre::eyre;
-use ractor::{Actor, ActorProcessingErr, ActorRef};
+use ractor::{Actor, ActorProcessingErr, ActorRef, RpcReplyPort};
 use rand::SeedableRng;
 use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn, Instrument};
@@ -20,26 +22,34 @@ use malachitebft_core_types::utils::height::DisplayRange;
 use malachitebft_core_types::ValueResponse as CoreValueResponse;
 use malachitebft_core_types::{CommitCertificate, Context};
 use malachitebft_sync::{
-    self as sync, HeightStartType, InboundRequestId, OutboundRequestId, RawDecidedValue, Request,
-    Response, Resumable,
+    self as sync, DecidedValuesCache, HeightStartType, InboundRequestId, NodeMode,
+    OutboundRequestId, PeerExit, PeerStakes, PeerState, RawDecidedValue, Request, Response,
+    Resumable,
 };
 
-use crate::consensus::{ConsensusMsg, ConsensusRef};
+use crate::consensus::{ConsensusMsg, ConsensusRef, ProcessedSyncedValue};
 use crate::host::{HostMsg, HostRef};
-use crate::network::{NetworkEvent, NetworkMsg, NetworkRef, Status};
+use crate::network::{Announcement, NetworkEvent, NetworkMsg, NetworkRef, Status};
+use crate::util::clock::Clock;
+use crate::util::events::{Event, TxEvent};
+use crate::util::span::parent_span;
 use crate::util::ticker::ticker;
 use crate::util::timers::{TimeoutElapsed, TimerScheduler};
 
+pub use malachitebft_sync::PauseReason;
+
 /// Codec for sync protocol messages
 ///
 /// This trait is automatically implemented for any type that implements:
 /// - [`codec::Codec<sync::Status<Ctx>>`]
+/// - [`codec::Codec<sync::Announcement<Ctx>>`]
 /// - [`codec::Codec<sync::Request<Ctx>>`]
 /// - [`codec::Codec<sync::Response<Ctx>>`]
 pub trait SyncCodec<Ctx>
 where
     Ctx: Context,
     Self: codec::Codec<sync::Status<Ctx>>,
+    Self: codec::Codec<sync::Announcement<Ctx>>,
     Self: codec::Codec<sync::Request<Ctx>>,
     Self: codec::Codec<sync::Response<Ctx>>,
     Self: codec::HasEncodedLen<sync::Response<Ctx>>,
@@ -50,6 +60,7 @@ impl<Ctx, Codec> SyncCodec<Ctx> for Codec
 where
     Ctx: Context,
     Codec: codec::Codec<sync::Status<Ctx>>,
+    Codec: codec::Codec<sync::Announcement<Ctx>>,
     Codec: codec::Codec<sync::Request<Ctx>>,
     Codec: codec::Codec<sync::Response<Ctx>>,
     Codec: codec::HasEncodedLen<sync::Response<Ctx>>,
@@ -90,13 +101,18 @@ pub enum Msg<Ctx: Context> {
     /// Receive an even from gossip layer
     NetworkEvent(NetworkEvent<Ctx>),
 
-    /// Consensus has decided on a value at the given height
-    Decided(Ctx::Height),
+    /// Consensus has decided on the value certified by the given commit certificate
+    Decided(CommitCertificate<Ctx>),
 
     /// Consensus has (re)started a new height.
     ///
-    /// The second argument indicates whether this is a restart or not.
-    StartedHeight(Ctx::Height, HeightStartType),
+    /// The second argument indicates whether this is a restart or not,
+    /// and the third one whether the node is a validator at that height.
+    StartedHeight(Ctx::Height, HeightStartType, NodeMode),
+
+    /// Voting power of the peers which proved to be validators of the current height,
+    /// used to select peers when the stake-weighted selection strategy is configured.
+    PeerStakes(PeerStakes),
 
     /// Host has a response for the blocks request
     GotDecidedValues(
@@ -113,6 +129,94 @@ pub enum Msg<Ctx: Context> {
 
     /// An error occurred while processing a value
     ValueProcessingError(PeerId, Ctx::Height),
+
+    /// Consensus observed validators with at least f+1 voting power voting at the given height,
+    /// higher than the current one, in votes relayed by the given peers
+    FutureHeightObserved(Ctx::Height, Vec<PeerId>),
+
+    /// Internal tick triggering the next backfill request
+    BackfillTick,
+
+    /// Internal tick triggering the computation of the height below which
+    /// the host can prune its decided values
+    PruneTick,
+
+    /// Stop sending requests to peers, abandon the requests in flight,
+    /// and reply with a checkpoint of the state of sync.
+    /// Sent when the node shuts down, before the sync actor is stopped.
+    Checkpoint(CheckpointReply<Ctx>),
+
+    /// Change the settings of sync which can be changed while it is running,
+    /// eg. after the configuration of the node has been reloaded.
+    Reconfigure(Reconfiguration),
+
+    /// Stop requesting values from peers for the given reason, eg. because the application
+    /// cannot keep up with the synced values. Requests from peers are still served.
+    Pause(PauseReason),
+
+    /// Lift the given reason for pausing sync, resuming the requests for values
+    /// once sync is not paused for any other reason.
+    Resume(PauseReason),
+}
+
+/// Settings of sync which can be changed while it is running, see [`Msg::Reconfigure`].
+/// Settings which are `None` are left unchanged.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reconfiguration {
+    /// New interval at which to update other peers of our status.
+    /// If set to 0s, status updates are sent eagerly right after each decision.
+    pub status_update_interval: Option<Duration>,
+
+    /// New interval between backfill requests, ignored if backfill is disabled
+    pub backfill_request_interval: Option<Duration>,
+}
+
+/// Reply port for a [`Msg::Checkpoint`] request.
+///
+/// The port is shared, so that messages of the sync actor can be cloned
+/// when they are broadcast through an output port. Only the first reply is sent.
+#[derive_where(Clone)]
+pub struct CheckpointReply<Ctx: Context>(Arc<Mutex<Option<RpcReplyPort<SyncCheckpoint<Ctx>>>>>);
+
+impl<Ctx: Context> CheckpointReply<Ctx> {
+    /// Send the checkpoint, unless a reply was already sent through another clone of the port.
+    pub fn send(&self, checkpoint: SyncCheckpoint<Ctx>) -> Result<(), eyre::Report> {
+        let reply_to = self
+            .0
+            .lock()
+            .map_err(|_| eyre!("Checkpoint reply lock poisoned"))?
+            .take()
+            .ok_or_else(|| eyre!("Checkpoint was already replied to"))?;
+
+        reply_to
+            .send(checkpoint)
+            .map_err(|e| eyre!("Failed to send checkpoint: {e:?}"))
+    }
+}
+
+impl<Ctx: Context> From<RpcReplyPort<SyncCheckpoint<Ctx>>> for CheckpointReply<Ctx> {
+    fn from(reply_to: RpcReplyPort<SyncCheckpoint<Ctx>>) -> Self {
+        Self(Arc::new(Mutex::new(Some(reply_to))))
+    }
+}
+
+impl<Ctx: Context> fmt::Debug for CheckpointReply<Ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CheckpointReply").finish_non_exhaustive()
+    }
+}
+
+/// State of sync at the time the node was shut down.
+#[derive_where(Clone, Debug)]
+pub struct SyncCheckpoint<Ctx: Context> {
+    /// Height of the last decided value
+    pub tip_height: Ctx::Height,
+    /// Next height to request from peers
+    pub sync_height: Ctx::Height,
+    /// Earliest height for which decided values are retained, as last reported by the application
+    pub history_min_height: Ctx::Height,
+    /// Number of requests which were in flight and have been abandoned
+    pub abandoned_requests: usize,
 }
 
 impl<Ctx: Context> From<NetworkEvent<Ctx>> for Msg<Ctx> {
@@ -137,6 +241,21 @@ pub struct Params {
     /// Timeout duration for sync requests
     /// Default: 10s
     pub request_timeout: Duration,
+
+    /// Whether to process the values of each sync response as a batch,
+    /// with a single `ProcessSyncedValues` request to the application,
+    /// and a single verification of their commit certificates by consensus.
+    /// Default: false
+    pub batch_synced_values: bool,
+
+    /// Number of recently decided values to keep in memory to serve the requests
+    /// of peers without asking the application, 0 to disable the cache.
+    /// Default: 100
+    pub decided_values_cache_size: usize,
+
+    /// Whether to announce the decided values to peers as soon as they are committed.
+    /// Default: false
+    pub announce_decisions: bool,
 }
 
 impl Default for Params {
@@ -144,6 +263,9 @@ impl Default for Params {
         Self {
             status_update_interval: Duration::from_secs(5),
             request_timeout: Duration::from_secs(10),
+            batch_synced_values: false,
+            decided_values_cache_size: 100,
+            announce_decisions: false,
         }
     }
 }
@@ -186,8 +308,21 @@ pub struct State<Ctx: Context> {
     /// Queue of sync value responses for heights ahead of consensus
     sync_queue: SyncQueue<Ctx>,
 
+    /// Recently decided values, served to peers without asking the host
+    decided_values: DecidedValuesCache<Ctx>,
+
     /// Status update mode
     status_update_mode: StatusUpdateMode,
+
+    /// Handle of the backfill ticker task, if backfill is enabled
+    backfill_ticker: Option<JoinHandle<()>>,
+
+    /// Handle of the prune ticker task, if retention is enabled
+    prune_ticker: Option<JoinHandle<()>>,
+
+    /// Whether the state has been checkpointed ahead of shutting down,
+    /// after which messages are ignored.
+    checkpointed: bool,
 }
 
 struct HandlerState<'a, Ctx: Context> {
@@ -198,8 +333,12 @@ struct HandlerState<'a, Ctx: Context> {
     inflight: &'a mut InflightRequests<Ctx>,
     /// Buffer for sync responses for heights ahead of consensus, keyed by height.
     sync_queue: &'a mut SyncQueue<Ctx>,
+    /// Recently decided values, used to serve value requests without asking the host.
+    decided_values: &'a mut DecidedValuesCache<Ctx>,
     /// The current consensus height according to the last processed input.
     consensus_height: Ctx::Height,
+    /// The earliest height available in the history of the application, as last reported by it.
+    history_min_height: Ctx::Height,
 }
 
 #[allow(dead_code)]
@@ -216,6 +355,8 @@ where
     sync_codec: Codec,
     sync_config: sync::Config,
     metrics: sync::Metrics,
+    tx_event: TxEvent<Ctx>,
+    clock: Arc<dyn Clock>,
     span: tracing::Span,
 }
 
@@ -234,6 +375,8 @@ where
         sync_codec: Codec,
         sync_config: sync::Config,
         metrics: sync::Metrics,
+        tx_event: TxEvent<Ctx>,
+        clock: Arc<dyn Clock>,
         span: tracing::Span,
     ) -> Self {
         Self {
@@ -245,6 +388,8 @@ where
             sync_codec,
             sync_config,
             metrics,
+            tx_event,
+            clock,
             span,
         }
     }
@@ -259,6 +404,8 @@ where
         sync_codec: Codec,
         sync_config: sync::Config,
         metrics: sync::Metrics,
+        tx_event: TxEvent<Ctx>,
+        clock: Arc<dyn Clock>,
         span: tracing::Span,
     ) -> Result<SyncRef<Ctx>, ractor::SpawnErr> {
         let actor = Self::new(
@@ -270,6 +417,8 @@ where
             sync_codec,
             sync_config,
             metrics,
+            tx_event,
+            clock,
             span,
         );
         let (actor_ref, _) = Actor::spawn(None, actor, ()).await?;
@@ -286,7 +435,9 @@ where
             timers: &mut state.timers,
             inflight: &mut state.inflight,
             sync_queue: &mut state.sync_queue,
+            decided_values: &mut state.decided_values,
             consensus_height: state.sync.consensus_height,
+            history_min_height: state.sync.history_min_height,
         };
 
         malachitebft_sync::process!(
@@ -303,11 +454,24 @@ where
         )
     }
 
-    async fn get_history_min_height(&self) -> Result<Ctx::Height, ActorProcessingErr> {
-        ractor::call!(self.host, |reply_to| HostMsg::GetHistoryMinHeight {
+    /// Get the earliest height available in the history of the application,
+    /// or the last one it reported if it is too busy to reply.
+    async fn get_history_min_height(
+        &self,
+        state: &HandlerState<'_, Ctx>,
+    ) -> Result<Ctx::Height, ActorProcessingErr> {
+        let result = ractor::call!(self.host, |reply_to| HostMsg::GetHistoryMinHeight {
             reply_to
         })
-        .map_err(|e| eyre!("Failed to get earliest history height: {e:?}").into())
+        .map_err(|e| eyre!("Failed to get earliest history height: {e:?}"))?;
+
+        match result {
+            Ok(history_min_height) => Ok(history_min_height),
+            Err(e) => {
+                warn!("Failed to get earliest history height, keeping the last one: {e}");
+                Ok(state.history_min_height)
+            }
+        }
     }
 
     async fn handle_effect(
@@ -319,12 +483,17 @@ where
         use sync::Effect;
 
         match effect {
-            Effect::BroadcastStatus(height, r) => {
-                let history_min_height = self.get_history_min_height().await?;
+            Effect::GetHistoryMinHeight(r) => {
+                let history_min_height = self.get_history_min_height(state).await?;
+                Ok(r.resume_with(history_min_height))
+            }
 
+            Effect::BroadcastStatus(tip_height, history_min_height, sync_height, mode, r) => {
                 self.network.cast(NetworkMsg::BroadcastStatus(Status::new(
-                    height,
+                    tip_height,
                     history_min_height,
+                    sync_height,
+                    mode,
                 )))?;
 
                 Ok(r.resume_with(()))
@@ -374,13 +543,38 @@ where
             }
 
             Effect::GetDecidedValues(request_id, range, r) => {
+                if state.decided_values.is_enabled() {
+                    if let Some(values) = state.decided_values.get_range(&range) {
+                        self.metrics.decided_values_cache_hits.inc();
+
+                        debug!(
+                            %request_id,
+                            range = %DisplayRange(&range),
+                            "Serving decided values from cache"
+                        );
+
+                        myself.cast(Msg::GotDecidedValues(request_id, range, values))?;
+                        return Ok(r.resume_with(()));
+                    }
+
+                    self.metrics.decided_values_cache_misses.inc();
+                }
+
                 self.host.call_and_forward(
                     {
                         let range = range.clone();
                         |reply_to| HostMsg::GetDecidedValues { range, reply_to }
                     },
                     myself,
-                    |values| Msg::<Ctx>::GotDecidedValues(request_id, range, values),
+                    // Peers treat an empty response as if we did not have the values,
+                    // and request them from another peer
+                    |result| match result {
+                        Ok(values) => Msg::<Ctx>::GotDecidedValues(request_id, range, values),
+                        Err(e) => {
+                            warn!(%request_id, "Failed to get decided values from host: {e}");
+                            Msg::<Ctx>::GotDecidedValues(request_id, range, Vec::new())
+                        }
+                    },
                     None,
                 )?;
 
@@ -388,13 +582,47 @@ where
             }
 
             Effect::ProcessValueResponse(peer_id, request_id, response, r) => {
-                self.process_value_response(state, peer_id, request_id, response);
+                self.process_value_response(state, peer_id, request_id, response)
+                    .await;
+                Ok(r.resume_with(()))
+            }
+
+            Effect::StoreBackfilledValues(peer_id, values, r) => {
+                let stored = match ractor::call!(self.host, |reply_to| {
+                    HostMsg::ProcessBackfilledValues { values, reply_to }
+                }) {
+                    Ok(Ok(stored)) => Some(stored),
+                    Ok(Err(e)) => {
+                        warn!(%peer_id, "Host did not process backfilled values: {e}");
+                        None
+                    }
+                    Err(e) => {
+                        error!(%peer_id, "Failed to send backfilled values to host: {e:?}");
+                        Some(false)
+                    }
+                };
+
+                Ok(r.resume_with(stored))
+            }
+
+            Effect::ReportBackfillProgress(lowest_height, target_height, r) => {
+                self.tx_event.send(|| Event::BackfillProgress {
+                    lowest_height,
+                    target_height,
+                });
+
+                Ok(r.resume_with(()))
+            }
+
+            Effect::Prune(retain_height, r) => {
+                self.host.cast(HostMsg::Prune { retain_height })?;
+
                 Ok(r.resume_with(()))
             }
         }
     }
 
-    fn process_value_response(
+    async fn process_value_response(
         &self,
         state: &mut HandlerState<'_, Ctx>,
         peer_id: PeerId,
@@ -405,6 +633,25 @@ where
         let mut ignored = Vec::new();
         let mut buffered = Vec::new();
 
+        // In batch mode, the values from the current height onwards are handed to consensus at once,
+        // for their certificates to be verified together. The values for the later heights are still
+        // buffered, and processed when consensus reaches their height, without verifying them again.
+        let mut batch = Vec::new();
+
+        // Have the application process the values not yet decided before they are handed to consensus
+        if self.params.batch_synced_values {
+            let values = response
+                .values
+                .iter()
+                .filter(|value| value.height() >= consensus_height)
+                .cloned()
+                .collect::<Vec<_>>();
+
+            if !values.is_empty() {
+                self.process_synced_values(peer_id, values).await;
+            }
+        }
+
         for raw_value in response.values {
             let height = raw_value.height();
             let value = raw_value.to_core(peer_id);
@@ -417,6 +664,10 @@ where
 
                 // The value is for a height ahead of consensus, buffer it for later processing when we reach that height.
                 Ordering::Greater => {
+                    if !batch.is_empty() {
+                        batch.push(value.clone());
+                    }
+
                     let buffered_value = BufferedValue::new(request_id.clone(), value);
                     if state.sync_queue.push(height, buffered_value) {
                         buffered.push(height);
@@ -429,7 +680,9 @@ where
                 Ordering::Equal => {
                     debug!(%peer_id, %request_id, %height, "Processing value for current consensus height");
 
-                    if let Err(e) = self
+                    if self.params.batch_synced_values {
+                        batch.push(value);
+                    } else if let Err(e) = self
                         .consensus
                         .cast(ConsensusMsg::ProcessSyncResponse(value))
                     {
@@ -439,6 +692,15 @@ where
             }
         }
 
+        if !batch.is_empty() {
+            if let Err(e) = self
+                .consensus
+                .cast(ConsensusMsg::ProcessSyncResponses(batch))
+            {
+                error!("Failed to forward value responses to consensus: {e}");
+            }
+        }
+
         self.metrics
             .sync_queue_updated(state.sync_queue.len(), state.sync_queue.size());
 
@@ -457,6 +719,128 @@ where
         }
     }
 
+    /// Send a batch of synced values to the host to be processed at once,
+    /// and forward the outcomes to consensus ahead of the values themselves.
+    async fn process_synced_values(&self, peer_id: PeerId, values: Vec<RawDecidedValue<Ctx>>) {
+        let count = values.len();
+
+        let result = ractor::call!(self.host, |reply_to| {
+            HostMsg::ProcessSyncedValues {
+                values: values.clone(),
+                reply_to,
+            }
+        });
+
+        let outcomes = match result {
+            Ok(Ok(outcomes)) if outcomes.len() == count => outcomes,
+            Ok(Ok(outcomes)) => {
+                warn!(
+                    %peer_id,
+                    "Host processed {} synced values instead of {count}, processing them one by one",
+                    outcomes.len()
+                );
+                return;
+            }
+            Ok(Err(e)) => {
+                warn!(%peer_id, "Host did not process synced values, processing them one by one: {e}");
+                return;
+            }
+            Err(e) => {
+                error!(%peer_id, "Failed to send synced values to host: {e:?}");
+                return;
+            }
+        };
+
+        let processed = values
+            .into_iter()
+            .zip(outcomes)
+            .map(|(value, outcome)| ProcessedSyncedValue {
+                height: value.certificate.height,
+                round: value.certificate.round,
+                value_bytes: value.value_bytes,
+                outcome,
+            })
+            .collect();
+
+        debug!(%peer_id, "Processed a batch of {count} synced values");
+
+        if let Err(e) = self
+            .consensus
+            .cast(ConsensusMsg::SyncedValuesProcessed(processed))
+        {
+            error!("Failed to forward processed synced values to consensus: {e}");
+        }
+    }
+
+    /// Drop the state and the metrics kept for a peer which disconnected or was banned.
+    fn forget_peer(&self, state: &mut State<Ctx>, peer_id: PeerId, exit: PeerExit) {
+        state.sync.forget_peer(&peer_id, exit);
+        self.metrics.scoring.forget_peer(peer_id);
+    }
+
+    /// Stop the tickers and timers, abandon the requests in flight and the buffered values,
+    /// and return a checkpoint of the state of sync.
+    fn checkpoint(&self, state: &mut State<Ctx>) -> SyncCheckpoint<Ctx> {
+        state.checkpointed = true;
+
+        if let StatusUpdateMode::Interval(ticker) = &state.status_update_mode {
+            ticker.abort();
+        }
+
+        if let Some(ticker) = &state.backfill_ticker {
+            ticker.abort();
+        }
+
+        if let Some(ticker) = &state.prune_ticker {
+            ticker.abort();
+        }
+
+        state.timers.cancel_all();
+
+        let abandoned_requests = state.inflight.len();
+        state.inflight.clear();
+        state.sync_queue.clear();
+        self.metrics.sync_queue_updated(0, 0);
+
+        SyncCheckpoint {
+            tip_height: state.sync.tip_height,
+            sync_height: state.sync.sync_height,
+            history_min_height: state.sync.history_min_height,
+            abandoned_requests,
+        }
+    }
+
+    /// Restart the tickers whose interval has changed.
+    fn reconfigure(
+        &self,
+        myself: &ActorRef<Msg<Ctx>>,
+        state: &mut State<Ctx>,
+        reconfiguration: Reconfiguration,
+    ) {
+        if let Some(interval) = reconfiguration.status_update_interval {
+            info!(?interval, "Changing the status update interval");
+
+            if let StatusUpdateMode::Interval(ticker) = &state.status_update_mode {
+                ticker.abort();
+            }
+
+            let mut rng = rand::rngs::StdRng::from_entropy();
+            state.status_update_mode = status_update_mode(interval, myself, &mut rng);
+        }
+
+        if let Some(interval) = reconfiguration.backfill_request_interval {
+            match state.backfill_ticker.take() {
+                Some(ticker) => {
+                    info!(?interval, "Changing the backfill request interval");
+
+                    ticker.abort();
+                    state.backfill_ticker = Some(backfill_ticker(interval, myself));
+                }
+                None => debug!("Ignoring the backfill request interval as backfill is disabled"),
+            }
+        }
+    }
+
     async fn handle_msg(
         &self,
         myself: ActorRef<Msg<Ctx>>,
@@ -467,14 +851,28 @@ where
             Msg::Tick => {
                 self.process_input(&myself, state, sync::Input::SendStatusUpdate)
                     .await?;
+
+                // Report the sync scores of peers, which contribute to their reputation
+                let scorer = &state.sync.peer_scorer;
+                let scores = scorer
+                    .get_scores()
+                    .keys()
+                    .map(|peer_id| (*peer_id, scorer.get_score(peer_id)))
+                    .collect::<Vec<_>>();
+
+                if !scores.is_empty() {
+                    self.network.cast(NetworkMsg::UpdateSyncScores(scores))?;
+                }
             }
 
             Msg::NetworkEvent(NetworkEvent::PeerDisconnected(peer_id)) => {
                 info!(%peer_id, "Disconnected from peer");
+                self.forget_peer(state, peer_id, PeerExit::Disconnected);
+            }
 
-                if state.sync.peers.remove(&peer_id).is_some() {
-                    debug!(%peer_id, "Removed disconnected peer");
-                }
+            Msg::NetworkEvent(NetworkEvent::PeerBanned(peer_id)) => {
+                info!(%peer_id, "Peer was banned");
+                self.forget_peer(state, peer_id, PeerExit::Banned);
             }
 
             Msg::NetworkEvent(NetworkEvent::Status(peer_id, status)) => {
@@ -482,12 +880,26 @@ where
                     peer_id,
                     tip_height: status.tip_height,
                     history_min_height: status.history_min_height,
+                    sync_height: status.sync_height,
+                    mode: status.mode,
                 };
 
                 self.process_input(&myself, state, sync::Input::Status(status))
                     .await?;
             }
 
+            Msg::NetworkEvent(NetworkEvent::Announcement(peer_id, announcement)) => {
+                let announcement = sync::Announcement {
+                    peer_id,
+                    height: announcement.height,
+                    value_id: announcement.value_id,
+                    certificate_hash: announcement.certificate_hash,
+                };
+
+                self.process_input(&myself, state, sync::Input::Announcement(announcement))
+                    .await?;
+            }
+
             Msg::NetworkEvent(NetworkEvent::SyncRequest(request_id, from, request)) => {
                 match request {
                     Request::ValueRequest(value_request) => {
@@ -527,7 +939,7 @@ where
                 .await?;
             }
 
-            Msg::NetworkEvent(NetworkEvent::PeerConnected(peer_id)) => {
+            Msg::NetworkEvent(NetworkEvent::PeerConnected(peer_id, _)) => {
                 info!(%peer_id, "Peer connected, broadcasting status");
 
                 self.process_input(&myself, state, sync::Input::SendStatusUpdate)
@@ -538,8 +950,14 @@ where
                 // Ignore other gossip events
             }
 
+            Msg::PeerStakes(stakes) => {
+                state.sync.peer_stakes = stakes;
+            }
+
             // (Re)Started a new height
-            Msg::StartedHeight(height, restart) => {
+            Msg::StartedHeight(height, restart, mode) => {
+                state.sync.mode = mode;
+
                 if restart.is_restart() {
                     // Clear the sync queue
                     state.sync_queue.clear();
@@ -570,10 +988,20 @@ where
             }
 
             // Decided on a value
-            Msg::Decided(height) => {
-                self.process_input(&myself, state, sync::Input::Decided(height))
+            Msg::Decided(certificate) => {
+                self.process_input(&myself, state, sync::Input::Decided(certificate.height))
                     .await?;
 
+                // Let peers following the chain request the value right away
+                if self.params.announce_decisions {
+                    self.network
+                        .cast(NetworkMsg::BroadcastAnnouncement(Announcement::new(
+                            certificate.height,
+                            certificate.value_id.clone(),
+                            sync::certificate_hash(&certificate),
+                        )))?;
+                }
+
                 // In Eager mode, broadcast our status immediately after deciding
                 // rather than waiting for the next height to start, so that peers
                 // who need to sync from us learn about our latest height sooner.
@@ -596,6 +1024,10 @@ where
                     "Processing decided values from host"
                 );
 
+                for value in &values {
+                    state.decided_values.insert(value.clone());
+                }
+
                 // Filter values to respect maximum response size
                 let max_response_size = ByteSize::b(self.sync_config.max_response_size as u64);
                 truncate_values_to_size_limit(&mut values, max_response_size, &self.sync_codec);
@@ -638,6 +1070,52 @@ where
                 .await?
             }
 
+            Msg::FutureHeightObserved(height, peers) => {
+                self.process_input(
+                    &myself,
+                    state,
+                    sync::Input::FutureHeightObserved(height, peers),
+                )
+                .await?
+            }
+
+            Msg::BackfillTick => {
+                self.process_input(&myself, state, sync::Input::BackfillTick)
+                    .await?
+            }
+
+            Msg::PruneTick => {
+                self.process_input(&myself, state, sync::Input::PruneTick)
+                    .await?
+            }
+
+            Msg::Checkpoint(reply_to) => {
+                let checkpoint = self.checkpoint(state);
+
+                info!(
+                    tip_height = %checkpoint.tip_height,
+                    sync_height = %checkpoint.sync_height,
+                    abandoned_requests = %checkpoint.abandoned_requests,
+                    "Checkpointed sync state"
+                );
+
+                if let Err(e) = reply_to.send(checkpoint) {
+                    error!("Failed to reply with sync checkpoint: {e}");
+                }
+            }
+
+            Msg::Reconfigure(reconfiguration) => self.reconfigure(&myself, state, reconfiguration),
+
+            Msg::Pause(reason) => {
+                self.process_input(&myself, state, sync::Input::Pause(reason))
+                    .await?
+            }
+
+            Msg::Resume(reason) => {
+                self.process_input(&myself, state, sync::Input::Resume(reason))
+                    .await?
+            }
+
             Msg::TimeoutElapsed(elapsed) => {
                 let Some(timeout) = state.timers.intercept_timer_msg(elapsed) else {
                     // Timer was cancelled or already processed, ignore
@@ -698,6 +1176,14 @@ where
     }
 }
 
+fn backfill_ticker<Ctx: Context>(interval: Duration, sync: &ActorRef<Msg<Ctx>>) -> JoinHandle<()> {
+    tokio::spawn(ticker(interval, sync.clone(), 0.0, || Msg::BackfillTick).in_current_span())
+}
+
+fn prune_ticker<Ctx: Context>(interval: Duration, sync: &ActorRef<Msg<Ctx>>) -> JoinHandle<()> {
+    tokio::spawn(ticker(interval, sync.clone(), 0.0, || Msg::PruneTick).in_current_span())
+}
+
 fn truncate_values_to_size_limit<Ctx, Codec>(
     values: &mut Vec<RawDecidedValue<Ctx>>,
     max_response_size: ByteSize,
@@ -766,20 +1252,47 @@ where
         // maximum number of parallel requests and batch size, with some additional buffer.
         let queue_capacity = 2 * self.sync_config.parallel_requests * self.sync_config.batch_size;
 
+        let backfill_ticker = self.sync_config.backfill.map(|backfill| {
+            info!(
+                target_min_height = backfill.target_min_height,
+                request_interval = ?backfill.request_interval,
+                "Backfill enabled"
+            );
+
+            backfill_ticker(backfill.request_interval, &myself)
+        });
+
+        let prune_ticker = self.sync_config.retention.map(|retention| {
+            info!(
+                retain_values = retention.retain_values,
+                max_retain_values = retention.max_retain_values,
+                interval = ?retention.interval,
+                "Retention enabled"
+            );
+
+            prune_ticker(retention.interval, &myself)
+        });
+
         Ok(State {
             sync: sync::State::new(rng, self.sync_config),
-            timers: Timers::new(Box::new(myself.clone())),
+            timers: Timers::with_clock(Box::new(myself.clone()), Arc::clone(&self.clock)),
             inflight: HashMap::new(),
             sync_queue: SyncQueue::new(queue_capacity, queue_capacity),
+            decided_values: DecidedValuesCache::new(self.params.decided_values_cache_size),
             status_update_mode,
+            backfill_ticker,
+            prune_ticker,
+            checkpointed: false,
         })
     }
 
     #[tracing::instrument(
         name = "sync",
-        parent = &self.span,
+        parent = parent_span(&self.span),
         skip_all,
         fields(
+            height = %state.sync.sync_height,
+            round = tracing::field::Empty,
             tip_height = %state.sync.tip_height,
             sync_height = %state.sync.sync_height,
         ),
@@ -790,6 +1303,11 @@ where
         msg: Self::Msg,
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
+        if state.checkpointed {
+            debug!("Ignoring message received after the sync state was checkpointed");
+            return Ok(());
+        }
+
         if let Err(e) = self.handle_msg(myself, msg, state).await {
             error!("Error handling message: {e:?}");
         }
@@ -806,6 +1324,14 @@ where
             ticker.abort();
         }
 
+        if let Some(ticker) = &state.backfill_ticker {
+            ticker.abort();
+        }
+
+        if let Some(ticker) = &state.prune_ticker {
+            ticker.abort();
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,200 @@
+//! Source of time for the timers of the engine.
+//!
+//! Actors schedule their timers through a [`Clock`], which is backed by
+//! the Tokio timer in production ([`TokioClock`]). Tests can instead use a
+//! [`SimulatedClock`], which only moves forward when told to, so that timeouts
+//! fire instantly and in a deterministic order.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+/// A future which completes once a [`Clock::sleep`] has elapsed.
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A source of time for scheduling timers.
+pub trait Clock: Send + Sync + 'static {
+    /// Time elapsed since an arbitrary but fixed point in the past.
+    fn now(&self) -> Duration;
+
+    /// Returns a future which completes once the given duration has elapsed.
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+/// A [`Clock`] backed by the Tokio timer.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TokioClock;
+
+impl Clock for TokioClock {
+    fn now(&self) -> Duration {
+        use std::sync::OnceLock;
+        use tokio::time::Instant;
+
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A [`Clock`] whose time only moves forward through [`SimulatedClock::advance`].
+///
+/// Cloning the clock yields a handle to the same underlying time.
+#[derive(Clone, Default)]
+pub struct SimulatedClock {
+    inner: Arc<Mutex<Simulated>>,
+}
+
+#[derive(Default)]
+struct Simulated {
+    now: Duration,
+    next_id: u64,
+    sleepers: BTreeMap<(Duration, u64), oneshot::Sender<()>>,
+}
+
+impl SimulatedClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move time forward by the given duration, waking up every sleeper
+    /// whose deadline has been reached, in order of their deadlines.
+    pub fn advance(&self, duration: Duration) {
+        let mut inner = self.inner.lock().expect("clock lock poisoned");
+        inner.now += duration;
+        let now = inner.now;
+
+        while let Some(entry) = inner.sleepers.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+
+            // The sleeper may have been dropped already, eg. if its timer was canceled
+            let _ = entry.remove().send(());
+        }
+    }
+
+    /// Deadline of the next pending sleeper, if any.
+    pub fn next_deadline(&self) -> Option<Duration> {
+        let mut inner = self.inner.lock().expect("clock lock poisoned");
+        inner.sleepers.retain(|_, tx| !tx.is_closed());
+        inner.sleepers.keys().next().map(|(deadline, _)| *deadline)
+    }
+
+    /// Advance time up to the deadline of the next pending sleeper, if any,
+    /// and return that deadline.
+    pub fn advance_to_next_deadline(&self) -> Option<Duration> {
+        let deadline = self.next_deadline()?;
+        self.advance(deadline.saturating_sub(self.now()));
+        Some(deadline)
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Duration {
+        self.inner.lock().expect("clock lock poisoned").now
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        let (tx, rx) = oneshot::channel();
+
+        if duration.is_zero() {
+            let _ = tx.send(());
+        } else {
+            let mut inner = self.inner.lock().expect("clock lock poisoned");
+            let deadline = inner.now + duration;
+            let id = inner.next_id;
+            inner.next_id += 1;
+            inner.sleepers.insert((deadline, id), tx);
+        }
+
+        Box::pin(async move {
+            // The sender is only dropped without sending if the clock itself is dropped,
+            // in which case the sleep never completes.
+            if rx.await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        })
+    }
+}
+
+impl fmt::Debug for SimulatedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.lock().expect("clock lock poisoned");
+
+        f.debug_struct("SimulatedClock")
+            .field("now", &inner.now)
+            .field("sleepers", &inner.sleepers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn simulated_sleepers_wake_up_in_order() {
+        let clock = SimulatedClock::new();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let woken = Arc::new(AtomicUsize::new(0));
+
+        let mut tasks = Vec::new();
+
+        for secs in [30, 10, 20] {
+            let sleep = clock.sleep(Duration::from_secs(secs));
+            let order = Arc::clone(&order);
+            let woken = Arc::clone(&woken);
+
+            tasks.push(tokio::spawn(async move {
+                sleep.await;
+                order.lock().unwrap().push(secs);
+                woken.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        clock.advance(Duration::from_secs(5));
+        tokio::task::yield_now().await;
+        assert_eq!(woken.load(Ordering::SeqCst), 0);
+
+        assert_eq!(
+            clock.advance_to_next_deadline(),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(clock.now(), Duration::from_secs(10));
+        tokio::task::yield_now().await;
+        assert_eq!(woken.load(Ordering::SeqCst), 1);
+
+        assert_eq!(
+            clock.advance_to_next_deadline(),
+            Some(Duration::from_secs(20))
+        );
+        tokio::task::yield_now().await;
+        assert_eq!(woken.load(Ordering::SeqCst), 2);
+
+        clock.advance(Duration::from_secs(10));
+
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(*order.lock().unwrap(), vec![10, 20, 30]);
+        assert_eq!(clock.next_deadline(), None);
+    }
+
+    #[tokio::test]
+    async fn simulated_zero_sleep_completes_immediately() {
+        let clock = SimulatedClock::new();
+        clock.sleep(Duration::ZERO).await;
+        assert_eq!(clock.now(), Duration::ZERO);
+    }
+}
@@ -1,4 +1,5 @@
 use core::fmt;
+use std::collections::BTreeMap;
 use std::io;
 use std::sync::Arc;
 
@@ -6,13 +7,17 @@ use derive_where::derive_where;
 use tokio::sync::broadcast;
 
 use malachitebft_core_consensus::{
-    Error as ConsensusError, LocallyProposedValue, MisbehaviorEvidence, ProposedValue, Role,
-    SignedConsensusMsg, WalEntry,
+    Error as ConsensusError, LocallyProposedValue, MisbehaviorEvidence, Participation, PeerId,
+    ProposedValue, Role, SignedConsensusMsg, WalEntry,
 };
 use malachitebft_core_types::{
-    CommitCertificate, Context, PolkaCertificate, Round, RoundCertificate, SignedVote, ValueOrigin,
+    CommitCertificate, Context, PolkaCertificate, Round, RoundCertificate, SignedVote, ValueId,
+    ValueOrigin, VotingPower,
 };
 
+use crate::network::Multiaddr;
+use crate::sync::SyncCheckpoint;
+
 pub type RxEvent<Ctx> = broadcast::Receiver<Event<Ctx>>;
 
 #[derive_where(Clone)]
@@ -58,16 +63,114 @@ pub enum Event<Ctx: Context> {
         commit_certificate: CommitCertificate<Ctx>,
         evidence: MisbehaviorEvidence<Ctx>,
     },
+    /// The application was not notified again about the decision of a height it was already
+    /// notified about, eg. when both consensus and sync decided that height.
+    DuplicateDecisionSuppressed {
+        height: Ctx::Height,
+        round: Round,
+        value_id: ValueId<Ctx>,
+    },
+    /// A height was decided with a value other than the one it was already decided with,
+    /// which means that the safety of consensus was violated. Consensus stops after emitting it.
+    ConflictingDecision {
+        height: Ctx::Height,
+        round: Round,
+        value_id: ValueId<Ctx>,
+        decided: ValueId<Ctx>,
+    },
     RepublishVote(SignedVote<Ctx>),
     RebroadcastRoundCertificate(RoundCertificate<Ctx>),
     SkipRoundCertificate(RoundCertificate<Ctx>),
     PolkaCertificate(PolkaCertificate<Ctx>),
+    VoteTally {
+        height: Ctx::Height,
+        round: Round,
+        prevote_power: VotingPower,
+        precommit_power: VotingPower,
+    },
+    /// The height reached the round alert threshold (`max_rounds_alert`) without deciding.
+    RoundAlert(Ctx::Height, Round),
+    /// The height reached the round halt threshold (`max_rounds_halt`) without deciding,
+    /// and participation in consensus is halted until the next height.
+    ParticipationHalted(Ctx::Height, Round),
+    /// A decided height was completed, and consensus moved on to the next height.
+    HeightCompleted {
+        /// Height which was completed
+        height: Ctx::Height,
+        /// Round in which the height was decided
+        round: Round,
+        /// Number of votes received from each validator of the validator set, across all rounds
+        participation: BTreeMap<Ctx::Address, Participation>,
+        /// Validators of the validator set from which no vote was received
+        absent_validators: Vec<Ctx::Address>,
+    },
+    /// Validators with at least f+1 voting power were observed voting at the given height,
+    /// higher than the current one, with one of their votes each as evidence.
+    FutureHeightObserved(Ctx::Height, Vec<SignedVote<Ctx>>),
+    /// A validator set update was applied when starting the given height,
+    /// with the epoch of the update.
+    ValidatorSetUpdateApplied(Ctx::Height, u64),
+    /// More than a third of the voting power changed from the validator set of the previous height
+    /// to the validator set of the given height.
+    LargeValidatorSetChange {
+        /// Height at which the new validator set takes effect
+        height: Ctx::Height,
+        /// Voting power which changed
+        changed: VotingPower,
+        /// Total voting power of the validator set with the most voting power
+        total: VotingPower,
+    },
+    /// A value received from a peer via sync was invalid, eg. it did not match its commit
+    /// certificate or was rejected by the application, and is requested again from another peer.
+    InvalidSyncedValue(Ctx::Height),
+    /// A peer connected, with the address of the connection to it.
+    PeerConnected {
+        peer_id: PeerId,
+        address: Multiaddr,
+    },
+    /// A peer disconnected.
+    PeerDisconnected(PeerId),
+    /// A peer proved that it holds the consensus key with the given public key,
+    /// which the application can match against the validator set.
+    ValidatorProofVerified {
+        peer_id: PeerId,
+        public_key: Vec<u8>,
+    },
+    /// Progress of the backfill of historical values, sent after each batch of backfilled values.
+    BackfillProgress {
+        /// Lowest height backfilled so far
+        lowest_height: Ctx::Height,
+        /// Lowest height to backfill
+        target_height: Ctx::Height,
+    },
     WalReplayBegin(Ctx::Height, usize),
     WalReplayEntry(WalEntry<Ctx>),
+    /// Progress of the WAL replay, sent periodically while replaying.
+    WalReplayProgress {
+        /// Height at which the WAL is being replayed
+        height: Ctx::Height,
+        /// Index of the entry being replayed, starting from 0
+        index: usize,
+        /// Total number of entries to replay
+        total: usize,
+        /// Height of the entry being replayed
+        entry_height: Ctx::Height,
+        /// Round of the entry being replayed
+        entry_round: Round,
+    },
     WalReplayDone(Ctx::Height),
     WalReplayError(Arc<ConsensusError<Ctx>>),
     WalResetError(Arc<eyre::Report>),
     WalCorrupted(Arc<io::Error>),
+    /// The node started shutting down gracefully.
+    ShutdownStarted,
+    /// The node has shut down, and no more events will be emitted.
+    ShutdownCompleted {
+        /// State of sync when it was stopped, if sync is enabled
+        sync: Option<SyncCheckpoint<Ctx>>,
+        /// Whether some actors had to be stopped forcefully because they did not drain in time
+        timed_out: bool,
+    },
 }
 
 impl<Ctx: Context> fmt::Display for Event<Ctx> {
@@ -117,19 +220,120 @@ impl<Ctx: Context> fmt::Display for Event<Ctx> {
                     )
                 }
             }
+            Event::DuplicateDecisionSuppressed {
+                height,
+                round,
+                value_id,
+            } => write!(
+                f,
+                "DuplicateDecisionSuppressed(height: {height}, round: {round}, value_id: {value_id})"
+            ),
+            Event::ConflictingDecision {
+                height,
+                round,
+                value_id,
+                decided,
+            } => write!(
+                f,
+                "ConflictingDecision(height: {height}, round: {round}, value_id: {value_id}, decided: {decided})"
+            ),
             Event::RepublishVote(vote) => write!(f, "RepublishVote(vote: {vote:?})"),
             Event::RebroadcastRoundCertificate(certificate) => write!(
                 f,
                 "RebroadcastRoundCertificate(certificate: {certificate:?})"
             ),
+            Event::VoteTally {
+                height,
+                round,
+                prevote_power,
+                precommit_power,
+            } => write!(
+                f,
+                "VoteTally(height: {height}, round: {round}, prevote_power: {prevote_power}, precommit_power: {precommit_power})"
+            ),
+            Event::RoundAlert(height, round) => {
+                write!(f, "RoundAlert(height: {height}, round: {round})")
+            }
+            Event::ParticipationHalted(height, round) => {
+                write!(f, "ParticipationHalted(height: {height}, round: {round})")
+            }
+            Event::HeightCompleted {
+                height,
+                round,
+                absent_validators,
+                ..
+            } => {
+                write!(
+                    f,
+                    "HeightCompleted(height: {height}, round: {round}, absent_validators: {absent_validators:?})"
+                )
+            }
+            Event::FutureHeightObserved(height, evidence) => {
+                write!(
+                    f,
+                    "FutureHeightObserved(height: {height}, validators: {})",
+                    evidence.len()
+                )
+            }
+            Event::ValidatorSetUpdateApplied(height, epoch) => {
+                write!(
+                    f,
+                    "ValidatorSetUpdateApplied(height: {height}, epoch: {epoch})"
+                )
+            }
+            Event::LargeValidatorSetChange {
+                height,
+                changed,
+                total,
+            } => write!(
+                f,
+                "LargeValidatorSetChange(height: {height}, changed: {changed}, total: {total})"
+            ),
+            Event::InvalidSyncedValue(height) => {
+                write!(f, "InvalidSyncedValue(height: {height})")
+            }
+            Event::PeerConnected { peer_id, address } => {
+                write!(f, "PeerConnected(peer_id: {peer_id}, address: {address})")
+            }
+            Event::PeerDisconnected(peer_id) => write!(f, "PeerDisconnected(peer_id: {peer_id})"),
+            Event::ValidatorProofVerified {
+                peer_id,
+                public_key,
+            } => write!(
+                f,
+                "ValidatorProofVerified(peer_id: {peer_id}, public_key: {})",
+                hex::encode(public_key)
+            ),
+            Event::BackfillProgress {
+                lowest_height,
+                target_height,
+            } => write!(
+                f,
+                "BackfillProgress(lowest_height: {lowest_height}, target_height: {target_height})"
+            ),
             Event::WalReplayBegin(height, count) => {
                 write!(f, "WalReplayBegin(height: {height}, count: {count})")
             }
             Event::WalReplayEntry(entry) => write!(f, "WalReplayEntry(entry: {entry:?})"),
+            Event::WalReplayProgress {
+                height,
+                index,
+                total,
+                entry_height,
+                entry_round,
+            } => write!(
+                f,
+                "WalReplayProgress(height: {height}, entry: {}/{total}, entry_height: {entry_height}, entry_round: {entry_round})",
+                index + 1
+            ),
             Event::WalReplayDone(height) => write!(f, "WalReplayDone(height: {height})"),
             Event::WalReplayError(error) => write!(f, "WalReplayError({error})"),
             Event::WalResetError(error) => write!(f, "WalResetError({error})"),
             Event::WalCorrupted(error) => write!(f, "WalCorrupted(error: {error:?})"),
+            Event::ShutdownStarted => write!(f, "ShutdownStarted"),
+            Event::ShutdownCompleted { sync, timed_out } => {
+                write!(f, "ShutdownCompleted(sync: {sync:?}, timed_out: {timed_out})")
+            }
 
             Event::PolkaCertificate(certificate) => {
                 write!(f, "PolkaCertificate: {certificate:?})")
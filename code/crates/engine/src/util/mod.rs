@@ -1,7 +1,9 @@
+pub mod clock;
 pub mod events;
 pub mod msg_buffer;
 pub mod output_port;
 pub mod ractor;
+pub mod span;
 pub mod streaming;
 pub mod ticker;
 pub mod timers;
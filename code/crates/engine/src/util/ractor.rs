@@ -1,3 +1,5 @@
+use core::fmt;
+
 use ractor::{ActorRef, Message, MessagingErr, RpcReplyPort};
 
 /// Send a message with an `RpcReplyPort<Option<TReply>>` to `target` and spawn a task
@@ -28,3 +30,38 @@ where
 
     Ok(())
 }
+
+/// Send a message with an `RpcReplyPort<Result<Option<TReply>, TErr>>` to `target` and spawn a task
+/// that handles the response: `on_some` when the reply is `Ok(Some)`, `on_none` when `Ok(None)`
+/// or when `target` replied with an error, which is logged.
+/// Channel errors (target actor died) are logged.
+pub fn cast_result_option_and_handle<TMsg, TReply, TErr>(
+    target: &ActorRef<TMsg>,
+    msg_factory: impl FnOnce(RpcReplyPort<Result<Option<TReply>, TErr>>) -> TMsg,
+    on_some: impl FnOnce(TReply) + Send + 'static,
+    on_none: impl FnOnce() + Send + 'static,
+) -> Result<(), MessagingErr<TMsg>>
+where
+    TMsg: Message,
+    TReply: Send + 'static,
+    TErr: fmt::Display + Send + 'static,
+{
+    let (tx, rx) = ractor::concurrency::oneshot();
+    target.cast(msg_factory(tx.into()))?;
+
+    ractor::concurrency::spawn(async move {
+        match rx.await {
+            Ok(Ok(Some(value))) => on_some(value),
+            Ok(Ok(None)) => on_none(),
+            Ok(Err(e)) => {
+                tracing::warn!("Actor did not handle the message: {e}");
+                on_none();
+            }
+            Err(_) => {
+                tracing::error!("Actor dropped reply channel");
+            }
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! Propagation of tracing spans across actors.
+//!
+//! The span a message is sent from is propagated along with the message, and the actor
+//! handling the message nests its own span under it. Since the Consensus actor handles each
+//! height under a root span of its own, everything done on behalf of a height, from the
+//! messages it broadcasts to the values it asks the application for, ends up in the same trace.
+//!
+//! The spans in which the Consensus, Network, Sync and WAL actors handle their messages
+//! all carry the `height` and `round` fields, so that their logs can be correlated and
+//! indexed by height and round. These fields are left empty when a message does not relate
+//! to a specific height or round.
+
+use std::fmt;
+
+use tracing::field::display;
+use tracing::Span;
+
+use malachitebft_core_types::Round;
+
+/// Parent of the span in which an actor handles a message: the span the message was sent from,
+/// if it was propagated along with the message, or the span of the actor itself otherwise,
+/// eg. for messages sent from outside of any span.
+///
+/// Must be called from within the handler of the message, before entering any other span.
+pub fn parent_span(actor_span: &Span) -> Span {
+    let current = Span::current();
+
+    if current.is_none() {
+        actor_span.clone()
+    } else {
+        current
+    }
+}
+
+/// Record the height and round a message relates to in the given span,
+/// which must declare the `height` and `round` fields, eg. as [`tracing::field::Empty`].
+///
+/// The round is not recorded if it is nil.
+pub fn record_height_and_round(span: &Span, height: impl fmt::Display, round: Round) {
+    span.record("height", display(height));
+
+    if round.is_defined() {
+        span.record("round", display(round));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Empty, Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{error_span, Subscriber};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::Layer;
+
+    use super::*;
+
+    type Fields = Arc<Mutex<BTreeMap<&'static str, String>>>;
+
+    /// Layer capturing the fields recorded in spans.
+    struct CaptureFields(Fields);
+
+    impl Visit for CaptureFields {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .insert(field.name(), format!("{value:?}"));
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for CaptureFields {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            attrs.record(&mut CaptureFields(self.0.clone()));
+        }
+
+        fn on_record(&self, _id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            values.record(&mut CaptureFields(self.0.clone()));
+        }
+    }
+
+    fn capture(f: impl FnOnce()) -> BTreeMap<&'static str, String> {
+        let fields = Fields::default();
+        let subscriber = tracing_subscriber::registry().with(CaptureFields(fields.clone()));
+
+        tracing::subscriber::with_default(subscriber, f);
+
+        let fields = fields.lock().unwrap().clone();
+        fields
+    }
+
+    #[test]
+    fn records_height_and_round() {
+        let fields = capture(|| {
+            let span = error_span!("network", height = Empty, round = Empty);
+            record_height_and_round(&span, 42, Round::new(3));
+        });
+
+        assert_eq!(fields.get("height").map(String::as_str), Some("42"));
+        assert_eq!(fields.get("round").map(String::as_str), Some("3"));
+    }
+
+    #[test]
+    fn does_not_record_nil_round() {
+        let fields = capture(|| {
+            let span = error_span!("wal", height = Empty, round = Empty);
+            record_height_and_round(&span, 42, Round::Nil);
+        });
+
+        assert_eq!(fields.get("height").map(String::as_str), Some("42"));
+        assert_eq!(fields.get("round"), None);
+    }
+}
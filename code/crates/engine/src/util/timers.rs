@@ -10,6 +10,7 @@ use std::time::Duration;
 use tokio::task::JoinHandle;
 use tracing::trace;
 
+use super::clock::{Clock, TokioClock};
 use super::output_port::{OutputPort, OutputPortSubscriber};
 
 #[derive(Debug)]
@@ -47,6 +48,7 @@ where
     output_port: Arc<OutputPort<TimeoutElapsed<Key>>>,
     timers: HashMap<Key, Timer<Key>>,
     generations: RangeFrom<u64>,
+    clock: Arc<dyn Clock>,
 }
 
 impl<Key> TimerScheduler<Key>
@@ -54,6 +56,14 @@ where
     Key: Clone + Eq + Hash + Send + 'static,
 {
     pub fn new(subscriber: OutputPortSubscriber<TimeoutElapsed<Key>>) -> Self {
+        Self::with_clock(subscriber, Arc::new(TokioClock))
+    }
+
+    /// Create a scheduler whose timers elapse according to the given [`Clock`].
+    pub fn with_clock(
+        subscriber: OutputPortSubscriber<TimeoutElapsed<Key>>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
         let output_port = OutputPort::with_capacity(32);
         subscriber.subscribe_to_port(&output_port);
 
@@ -61,6 +71,7 @@ where
             output_port: Arc::new(output_port),
             timers: HashMap::new(),
             generations: 1..,
+            clock,
         }
     }
 
@@ -90,9 +101,10 @@ where
         let task = {
             let key = key.clone();
             let output_port = Arc::clone(&self.output_port);
+            let sleep = self.clock.sleep(timeout);
 
             tokio::spawn(async move {
-                tokio::time::sleep(timeout).await;
+                sleep.await;
                 output_port.send(TimeoutElapsed { key, generation })
             })
         };
@@ -190,6 +202,7 @@ where
 mod tests {
     use super::*;
 
+    use crate::util::clock::SimulatedClock;
     use ractor::{Actor, ActorRef};
     use std::time::Duration;
     use tokio::time::sleep;
@@ -254,6 +267,30 @@ mod tests {
         assert!(!scheduler.is_timer_active(&key));
     }
 
+    #[tokio::test]
+    async fn test_start_timer_with_simulated_clock() {
+        let actor_ref = TestActor::spawn(None, TestActor, ()).await.unwrap().0;
+        let clock = SimulatedClock::new();
+        let mut scheduler =
+            TimerScheduler::with_clock(Box::new(actor_ref), Arc::new(clock.clone()));
+
+        let key = TestKey("timer1");
+        scheduler.start_timer(key, Duration::from_secs(30));
+        scheduler.start_timer(TestKey("timer2"), Duration::from_secs(60));
+
+        assert_eq!(clock.next_deadline(), Some(Duration::from_secs(30)));
+        assert_eq!(
+            clock.advance_to_next_deadline(),
+            Some(Duration::from_secs(30))
+        );
+
+        let elapsed_key = scheduler.intercept_timer_msg(TimeoutElapsed { key, generation: 1 });
+        assert_eq!(elapsed_key, Some(key));
+
+        assert!(!scheduler.is_timer_active(&key));
+        assert!(scheduler.is_timer_active(&TestKey("timer2")));
+    }
+
     #[tokio::test]
     async fn test_cancel_timer() {
         let mut scheduler = spawn().await;
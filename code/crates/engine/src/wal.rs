@@ -1,23 +1,33 @@
 use std::io;
 use std::marker::PhantomData;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use eyre::eyre;
 use ractor::{async_trait, Actor, ActorProcessingErr, ActorRef, RpcReplyPort, SpawnErr};
 use tokio::sync::{mpsc, oneshot};
 use tracing::{debug, error, info, warn};
 
+use malachitebft_config::WalStorageConfig;
 use malachitebft_core_types::{Context, Height};
 use malachitebft_metrics::SharedRegistry;
 use malachitebft_wal as wal;
 
+use crate::util::span::{parent_span, record_height_and_round};
+
+use self::stability::Stability;
+
 mod entry;
 mod iter;
+mod migrate;
+mod stability;
 mod thread;
 
+pub use entry::FormatVersion;
 pub use entry::WalCodec;
 pub use entry::WalEntry;
-pub use iter::log_entries;
+pub use iter::{log_entries, WalIter};
+pub use migrate::migrate_log;
+pub use wal::EncryptionKey;
 
 pub type WalRef<Ctx> = ActorRef<Msg<Ctx>>;
 
@@ -42,10 +52,20 @@ where
         _ctx: &Ctx,
         codec: Codec,
         path: PathBuf,
-        _metrics: SharedRegistry,
+        storage: WalStorageConfig,
+        encryption_key: Option<EncryptionKey>,
+        metrics: SharedRegistry,
         span: tracing::Span,
     ) -> Result<WalRef<Ctx>, SpawnErr> {
-        let (actor_ref, _) = Actor::spawn(None, Self::new(span), Args { path, codec }).await?;
+        let args = Args {
+            path,
+            codec,
+            storage,
+            encryption_key,
+            metrics: stability::Metrics::register(&metrics),
+        };
+
+        let (actor_ref, _) = Actor::spawn(None, Self::new(span), args).await?;
         Ok(actor_ref)
     }
 }
@@ -63,12 +83,20 @@ pub enum Msg<Ctx: Context> {
 pub struct Args<Codec> {
     pub path: PathBuf,
     pub codec: Codec,
+    /// Storage backing the WAL, at `path`
+    pub storage: WalStorageConfig,
+    /// Key used to encrypt new entries and decrypt existing ones, if any
+    pub encryption_key: Option<EncryptionKey>,
+    /// Metrics of the restarts of the node and of the WAL replays
+    pub metrics: stability::Metrics,
 }
 
 pub struct State<Ctx: Context> {
     height: Ctx::Height,
     wal_sender: mpsc::Sender<self::thread::WalMsg<Ctx>>,
-    _handle: std::thread::JoinHandle<()>,
+    handle: Option<std::thread::JoinHandle<()>>,
+    /// Restarts of the node and WAL replays, unless the WAL is kept in memory
+    stability: Option<Stability>,
 }
 
 impl<Ctx, Codec> Wal<Ctx, Codec>
@@ -167,6 +195,12 @@ where
 
         let to_replay = rx.await?;
 
+        if let (Ok(entries), Some(stability)) = (&to_replay, &mut state.stability) {
+            if !entries.is_empty() {
+                stability.replayed();
+            }
+        }
+
         reply_to
             .send(to_replay)
             .map_err(|e| eyre!("Failed to send reply: {e}"))?;
@@ -239,8 +273,13 @@ where
         _myself: WalRef<Ctx>,
         args: Self::Arguments,
     ) -> Result<Self::State, ActorProcessingErr> {
-        let log = wal::Log::open(&args.path)?;
-        info!("Opened WAL at {}", args.path.display());
+        // Record the start of the node before opening the WAL, which creates it if needed
+        let stability = match args.storage {
+            WalStorageConfig::Memory => None,
+            _ => Some(Stability::start(&args.path, args.metrics)),
+        };
+
+        let log = open_log(&args.path, args.storage, args.encryption_key)?;
 
         let (tx, rx) = mpsc::channel(100);
 
@@ -250,15 +289,16 @@ where
         Ok(State {
             height: Ctx::Height::ZERO,
             wal_sender: tx,
-            _handle: handle,
+            handle: Some(handle),
+            stability,
         })
     }
 
     #[tracing::instrument(
         name = "wal",
-        parent = &self.span,
+        parent = parent_span(&self.span),
         skip_all,
-        fields(height = %span_height(state.height, &msg)),
+        fields(height = %span_height(state.height, &msg), round = tracing::field::Empty),
     )]
     async fn handle(
         &self,
@@ -266,6 +306,10 @@ where
         msg: Self::Msg,
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
+        if let Msg::Append(height, entry, _) = &msg {
+            record_height_and_round(&tracing::Span::current(), height, entry.round());
+        }
+
         if let Err(e) = self.handle_msg(myself, msg, state).await {
             error!("Failed to handle WAL message: {e}");
         }
@@ -288,6 +332,20 @@ where
 
         let _ = state.wal_sender.send(self::thread::WalMsg::Shutdown).await;
 
+        // Wait for the WAL thread to process the pending operations and flush the log,
+        // so that the node does not exit in the middle of a write.
+        if let Some(handle) = state.handle.take() {
+            match tokio::task::spawn_blocking(move || handle.join()).await {
+                Ok(Ok(())) => debug!("WAL thread has exited"),
+                Ok(Err(_)) => error!("WAL thread panicked"),
+                Err(e) => error!("Failed to wait for the WAL thread to exit: {e}"),
+            }
+        }
+
+        if let Some(stability) = &mut state.stability {
+            stability.stop();
+        }
+
         Ok(())
     }
 }
@@ -301,3 +359,49 @@ fn span_height<Ctx: Context>(height: Ctx::Height, msg: &Msg<Ctx>) -> Ctx::Height
         height
     }
 }
+
+/// Open the WAL at the given path, with the configured storage.
+///
+/// A WAL stored in a single file is locked until it is dropped,
+/// so that it cannot be opened while a node is running on it.
+pub fn open_log(
+    path: &Path,
+    storage: WalStorageConfig,
+    encryption_key: Option<EncryptionKey>,
+) -> io::Result<Box<dyn wal::WalStorage>> {
+    match storage {
+        WalStorageConfig::File => {
+            let mut log = wal::Log::open(path)?;
+            info!("Opened WAL at {}", path.display());
+
+            if let Some(key) = encryption_key {
+                log.enable_encryption(key)?;
+                info!("Enabled encryption of WAL entries");
+            }
+
+            Ok(Box::new(log))
+        }
+
+        WalStorageConfig::Memory => {
+            warn!("WAL entries are kept in memory, they will be lost when the node stops");
+
+            Ok(Box::new(wal::MemoryLog::new()))
+        }
+
+        WalStorageConfig::Segmented { max_segment_size } => {
+            let mut log = wal::SegmentedLog::open(path, max_segment_size.as_u64())?;
+            info!(
+                segments = log.segments(),
+                "Opened segmented WAL at {}",
+                path.display()
+            );
+
+            if let Some(key) = encryption_key {
+                log.enable_encryption(key)?;
+                info!("Enabled encryption of WAL entries");
+            }
+
+            Ok(Box::new(log))
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::io::{self, Read, Write};
 
 use byteorder::{ReadBytesExt, WriteBytesExt, BE};
@@ -28,6 +29,54 @@ where
 
 pub use malachitebft_core_consensus::WalEntry;
 
+/// Version of the encoding of the WAL entries.
+///
+/// The version is recorded in a header, written as the first entry of the WAL.
+/// WALs written before the header was introduced have no such entry, and are at version 1.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u32)]
+pub enum FormatVersion {
+    /// Entries written without a header
+    V1 = 1,
+    /// Entries following a header
+    V2 = 2,
+}
+
+impl FormatVersion {
+    /// Version of the entries written by this release.
+    pub const CURRENT: Self = Self::V2;
+
+    /// Whether a WAL at this version starts with a header entry.
+    pub fn has_header(&self) -> bool {
+        *self > Self::V1
+    }
+}
+
+impl TryFrom<u32> for FormatVersion {
+    type Error = io::Error;
+
+    fn try_from(version: u32) -> Result<Self, Self::Error> {
+        match version {
+            1 => Ok(Self::V1),
+            2 => Ok(Self::V2),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "unsupported WAL format version {version}, the latest supported version is {}",
+                    Self::CURRENT
+                ),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FormatVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", *self as u32)
+    }
+}
+
+const TAG_HEADER: u8 = 0x00;
 const TAG_CONSENSUS: u8 = 0x01;
 const TAG_TIMEOUT: u8 = 0x02;
 const TAG_PROPOSED_VALUE: u8 = 0x04;
@@ -47,7 +96,49 @@ where
     }
 }
 
-pub fn decode_entry<Ctx, C, R>(codec: &C, mut buf: R) -> io::Result<WalEntry<Ctx>>
+/// Decode an entry written at the given format version.
+///
+/// This is the registry of the decoders of every format version ever released.
+/// When the encoding of the entries changes, bump [`FormatVersion::CURRENT`] and keep
+/// the decoder of the previous version around, so that the WALs written by previous
+/// releases can still be replayed, and migrated to the current version.
+pub fn decode_versioned_entry<Ctx, C, R>(
+    version: FormatVersion,
+    codec: &C,
+    buf: R,
+) -> io::Result<WalEntry<Ctx>>
+where
+    Ctx: Context,
+    C: WalCodec<Ctx>,
+    R: Read,
+{
+    match version {
+        // Both versions only differ by the header
+        FormatVersion::V1 | FormatVersion::V2 => decode_entry(codec, buf),
+    }
+}
+
+/// Encode the header recording the format version of the entries following it.
+pub fn encode_header<W: Write>(version: FormatVersion, mut buf: W) -> io::Result<()> {
+    buf.write_u8(TAG_HEADER)?;
+    buf.write_u32::<BE>(version as u32)?;
+
+    Ok(())
+}
+
+/// Decode the format version recorded in the given header entry.
+///
+/// Returns `None` if the entry is not a header, as is the first entry of a WAL at version 1.
+pub fn decode_header(bytes: &[u8]) -> Option<io::Result<FormatVersion>> {
+    match bytes.split_first() {
+        Some((&TAG_HEADER, mut buf)) => {
+            Some(buf.read_u32::<BE>().and_then(FormatVersion::try_from))
+        }
+        _ => None,
+    }
+}
+
+fn decode_entry<Ctx, C, R>(codec: &C, mut buf: R) -> io::Result<WalEntry<Ctx>>
 where
     Ctx: Context,
     C: WalCodec<Ctx>,
@@ -4,32 +4,60 @@ use std::marker::PhantomData;
 use malachitebft_core_types::Context;
 use malachitebft_wal as wal;
 
-use eyre::Result;
+use eyre::{eyre, Result};
 
-use super::entry::decode_entry;
+use super::entry::{decode_header, decode_versioned_entry, FormatVersion};
 use super::{WalCodec, WalEntry};
 
+/// Iterate over the entries of the WAL, decoded according to the format version in its header.
+///
+/// Fails if the WAL was written by a newer release, in a format version this release cannot decode.
 pub fn log_entries<'a, Ctx, Codec>(
-    log: &'a mut wal::Log,
+    log: &'a mut dyn wal::WalStorage,
     codec: &'a Codec,
 ) -> Result<WalIter<'a, Ctx, Codec>>
 where
     Ctx: Context,
     Codec: WalCodec<Ctx>,
 {
+    let mut iter = log.entries()?;
+
+    // WALs at version 1 have no header, their first entry is a regular entry
+    let (version, first) = match iter.next() {
+        Some(Ok(bytes)) => match decode_header(&bytes) {
+            Some(version) => {
+                let version = version.map_err(|e| eyre!("Failed to read WAL header: {e}"))?;
+                (version, None)
+            }
+            None => (FormatVersion::V1, Some(Ok(bytes))),
+        },
+        first => (FormatVersion::V1, first),
+    };
+
     Ok(WalIter {
-        iter: log.iter()?,
+        iter,
+        first,
+        version,
         codec,
         _marker: PhantomData,
     })
 }
 
 pub struct WalIter<'a, Ctx, Codec> {
-    iter: wal::LogIter<'a>,
+    iter: wal::WalEntries<'a>,
+    first: Option<io::Result<Vec<u8>>>,
+    version: FormatVersion,
     codec: &'a Codec,
     _marker: PhantomData<Ctx>,
 }
 
+impl<Ctx, Codec> WalIter<'_, Ctx, Codec> {
+    /// Format version of the entries of the WAL
+    pub fn version(&self) -> FormatVersion {
+        self.version
+    }
+}
+
 impl<Ctx, Codec> Iterator for WalIter<'_, Ctx, Codec>
 where
     Ctx: Context,
@@ -38,11 +66,11 @@ where
     type Item = io::Result<WalEntry<Ctx>>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let entry = self.iter.next()?;
+        let entry = self.first.take().or_else(|| self.iter.next())?;
         match entry {
             Ok(bytes) => {
                 let buf = io::Cursor::new(bytes);
-                Some(decode_entry(self.codec, buf))
+                Some(decode_versioned_entry(self.version, self.codec, buf))
             }
             Err(e) => Some(Err(e)),
         }
@@ -0,0 +1,70 @@
+use std::io;
+
+use eyre::{eyre, Result};
+
+use malachitebft_core_types::Context;
+use malachitebft_wal as wal;
+
+use super::entry::{encode_entry, encode_header, FormatVersion};
+use super::iter::log_entries;
+use super::{WalCodec, WalEntry};
+
+/// Migrate the entries of the WAL to the current format version.
+///
+/// Returns the format version the WAL was at before the migration,
+/// in which case the WAL is left untouched if it is already at the current version.
+///
+/// Fails without modifying the WAL if any of its entries cannot be decoded.
+pub fn migrate_log<Ctx, Codec>(
+    log: &mut dyn wal::WalStorage,
+    codec: &Codec,
+) -> Result<FormatVersion>
+where
+    Ctx: Context,
+    Codec: WalCodec<Ctx>,
+{
+    let iter = log_entries(log, codec)?;
+    let version = iter.version();
+
+    if version == FormatVersion::CURRENT {
+        return Ok(version);
+    }
+
+    let entries = iter
+        .enumerate()
+        .map(|(idx, entry)| entry.map_err(|e| eyre!("Failed to decode WAL entry {idx}: {e}")))
+        .collect::<Result<Vec<_>>>()?;
+
+    rewrite_log(log, codec, &entries)?;
+
+    Ok(version)
+}
+
+/// Rewrite the WAL with the given entries, at the current format version.
+pub(super) fn rewrite_log<'a, Ctx, Codec>(
+    log: &mut dyn wal::WalStorage,
+    codec: &Codec,
+    entries: impl IntoIterator<Item = &'a WalEntry<Ctx>>,
+) -> io::Result<()>
+where
+    Ctx: Context,
+    Codec: WalCodec<Ctx>,
+{
+    log.reset(log.sequence())?;
+    append_header(log)?;
+
+    for entry in entries {
+        let mut buf = Vec::new();
+        encode_entry(entry, codec, &mut buf)?;
+        log.append(&buf)?;
+    }
+
+    log.flush()
+}
+
+/// Append the header recording the current format version, as the first entry of the WAL.
+pub(super) fn append_header(log: &mut dyn wal::WalStorage) -> io::Result<()> {
+    let mut buf = Vec::new();
+    encode_header(FormatVersion::CURRENT, &mut buf)?;
+    log.append(&buf)
+}
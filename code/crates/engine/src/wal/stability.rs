@@ -0,0 +1,332 @@
+//! Record of the restarts of the node, persisted in a small file next to the WAL.
+//!
+//! The WAL actor marks the node as running in that file when it starts, and clears the mark
+//! when it stops. Finding the mark at startup thus means that the node did not shut down cleanly.
+//! The number of restarts and of WAL replays, per reason of the restart, are kept across restarts
+//! and exposed as metrics, so that operators can track the stability of their nodes over time.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
+
+use malachitebft_metrics::prometheus::encoding::{EncodeLabelSet, EncodeLabelValue};
+use malachitebft_metrics::prometheus::metrics::counter::Counter;
+use malachitebft_metrics::prometheus::metrics::family::Family;
+use malachitebft_metrics::prometheus::metrics::gauge::Gauge;
+use malachitebft_metrics::SharedRegistry;
+
+// Make prometheus_client available for the derive macros
+use malachitebft_metrics::prometheus as prometheus_client;
+
+/// Why the node (re)started.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, EncodeLabelValue)]
+pub enum RestartReason {
+    /// The node never ran with this WAL before
+    FirstStart,
+    /// The node was shut down cleanly
+    CleanShutdown,
+    /// The node crashed or was killed
+    UncleanShutdown,
+}
+
+impl RestartReason {
+    const ALL: [Self; 3] = [Self::FirstStart, Self::CleanShutdown, Self::UncleanShutdown];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::FirstStart => "first_start",
+            Self::CleanShutdown => "clean_shutdown",
+            Self::UncleanShutdown => "unclean_shutdown",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|reason| reason.as_str() == s)
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct ReasonLabels {
+    reason: RestartReason,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    /// Number of starts of the node, per reason
+    restarts: Family<ReasonLabels, Counter>,
+    /// Number of WAL replays, per reason of the restart which led to them
+    replays: Family<ReasonLabels, Counter>,
+    /// Whether the node did not shut down cleanly before its last start
+    unclean_shutdown: Gauge,
+}
+
+impl Metrics {
+    pub fn register(registry: &SharedRegistry) -> Self {
+        let metrics = Self::default();
+
+        registry.with_prefix("malachitebft_wal", |registry| {
+            registry.register(
+                "restarts",
+                "Number of starts of the node, per reason, persisted across restarts",
+                metrics.restarts.clone(),
+            );
+
+            registry.register(
+                "replays",
+                "Number of WAL replays, per reason of the restart, persisted across restarts",
+                metrics.replays.clone(),
+            );
+
+            registry.register(
+                "unclean_shutdown",
+                "Whether the node did not shut down cleanly before its last start (1) or not (0)",
+                metrics.unclean_shutdown.clone(),
+            );
+        });
+
+        metrics
+    }
+}
+
+/// Restarts and WAL replays of the node, as persisted in the stability file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Record {
+    /// Whether the node is running
+    running: bool,
+    /// Number of starts, per reason
+    restarts: BTreeMap<RestartReason, u64>,
+    /// Number of WAL replays, per reason of the restart which led to them
+    replays: BTreeMap<RestartReason, u64>,
+}
+
+impl Record {
+    /// One `key=value` pair per line, eg. `restarts.unclean_shutdown=3`.
+    fn encode(&self) -> String {
+        let mut out = format!("running={}\n", self.running);
+
+        for (kind, counts) in [("restarts", &self.restarts), ("replays", &self.replays)] {
+            for (reason, count) in counts {
+                out.push_str(&format!("{kind}.{}={count}\n", reason.as_str()));
+            }
+        }
+
+        out
+    }
+
+    fn decode(s: &str) -> Option<Self> {
+        let mut record = Self::default();
+
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let (key, value) = line.split_once('=')?;
+
+            if key == "running" {
+                record.running = value.parse().ok()?;
+                continue;
+            }
+
+            let (kind, reason) = key.split_once('.')?;
+            let reason = RestartReason::from_str(reason)?;
+            let count = value.parse().ok()?;
+
+            match kind {
+                "restarts" => record.restarts.insert(reason, count),
+                "replays" => record.replays.insert(reason, count),
+                _ => return None,
+            };
+        }
+
+        Some(record)
+    }
+}
+
+/// Tracks the restarts of the node and the WAL replays they lead to.
+pub struct Stability {
+    path: PathBuf,
+    record: Record,
+    reason: RestartReason,
+    metrics: Metrics,
+}
+
+impl Stability {
+    /// Load the stability file of the WAL at the given path, and record the start of the node.
+    pub fn start(wal_path: &Path, metrics: Metrics) -> Self {
+        let path = stability_path(wal_path);
+
+        let record = match fs::read_to_string(&path) {
+            Ok(s) => Record::decode(&s),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => {
+                warn!("Failed to read stability file {}: {e}", path.display());
+                None
+            }
+        };
+
+        let (mut record, reason) = match record {
+            Some(record) if record.running => (record, RestartReason::UncleanShutdown),
+            Some(record) => (record, RestartReason::CleanShutdown),
+            None if wal_path.exists() => {
+                warn!(
+                    "Stability file {} is missing or invalid, starting over",
+                    path.display()
+                );
+                (Record::default(), RestartReason::CleanShutdown)
+            }
+            None => (Record::default(), RestartReason::FirstStart),
+        };
+
+        if reason == RestartReason::UncleanShutdown {
+            warn!("Node did not shut down cleanly, it will recover from the WAL");
+        }
+
+        record.running = true;
+        *record.restarts.entry(reason).or_default() += 1;
+
+        for (reason, count) in &record.restarts {
+            metrics
+                .restarts
+                .get_or_create(&ReasonLabels { reason: *reason })
+                .inc_by(*count);
+        }
+
+        for (reason, count) in &record.replays {
+            metrics
+                .replays
+                .get_or_create(&ReasonLabels { reason: *reason })
+                .inc_by(*count);
+        }
+
+        metrics
+            .unclean_shutdown
+            .set((reason == RestartReason::UncleanShutdown) as i64);
+
+        let stability = Self {
+            path,
+            record,
+            reason,
+            metrics,
+        };
+
+        stability.save();
+        stability
+    }
+
+    /// Record that entries of the WAL were replayed.
+    pub fn replayed(&mut self) {
+        *self.record.replays.entry(self.reason).or_default() += 1;
+
+        self.metrics
+            .replays
+            .get_or_create(&ReasonLabels {
+                reason: self.reason,
+            })
+            .inc();
+
+        self.save();
+    }
+
+    /// Record that the node shut down cleanly.
+    pub fn stop(&mut self) {
+        self.record.running = false;
+        self.save();
+    }
+
+    fn save(&self) {
+        // Write to a temporary file first, so that the file is never left half-written
+        let tmp = with_suffix(&self.path, ".tmp");
+
+        let result =
+            fs::write(&tmp, self.record.encode()).and_then(|()| fs::rename(&tmp, &self.path));
+
+        if let Err(e) = result {
+            warn!(
+                "Failed to write stability file {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// Path of the stability file of the WAL at the given path, next to it.
+fn stability_path(wal_path: &Path) -> PathBuf {
+    with_suffix(wal_path, ".stability")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_wal_path() -> PathBuf {
+        std::env::temp_dir().join(format!("malachite-stability-{}.wal", rand::random::<u64>()))
+    }
+
+    fn restarts(stability: &Stability, reason: RestartReason) -> u64 {
+        stability.record.restarts.get(&reason).copied().unwrap_or(0)
+    }
+
+    #[test]
+    fn record_roundtrip() {
+        let record = Record {
+            running: true,
+            restarts: BTreeMap::from([
+                (RestartReason::FirstStart, 1),
+                (RestartReason::UncleanShutdown, 3),
+            ]),
+            replays: BTreeMap::from([(RestartReason::UncleanShutdown, 2)]),
+        };
+
+        assert_eq!(Record::decode(&record.encode()), Some(record));
+    }
+
+    #[test]
+    fn invalid_record_is_rejected() {
+        assert_eq!(Record::decode("running=maybe"), None);
+        assert_eq!(Record::decode("restarts.reboot=1"), None);
+        assert_eq!(Record::decode("crashes.unclean_shutdown=1"), None);
+    }
+
+    #[test]
+    fn tracks_restart_reasons() {
+        let wal_path = temp_wal_path();
+
+        let mut stability = Stability::start(&wal_path, Metrics::default());
+        assert_eq!(stability.reason, RestartReason::FirstStart);
+        stability.stop();
+
+        // The WAL now exists, and the node was shut down cleanly
+        fs::write(&wal_path, b"").unwrap();
+
+        let stability = Stability::start(&wal_path, Metrics::default());
+        assert_eq!(stability.reason, RestartReason::CleanShutdown);
+
+        // The node crashes without stopping
+        drop(stability);
+
+        let mut stability = Stability::start(&wal_path, Metrics::default());
+        assert_eq!(stability.reason, RestartReason::UncleanShutdown);
+        assert_eq!(stability.metrics.unclean_shutdown.get(), 1);
+
+        stability.replayed();
+        stability.stop();
+
+        let stability = Stability::start(&wal_path, Metrics::default());
+        assert_eq!(restarts(&stability, RestartReason::FirstStart), 1);
+        assert_eq!(restarts(&stability, RestartReason::CleanShutdown), 2);
+        assert_eq!(restarts(&stability, RestartReason::UncleanShutdown), 1);
+        assert_eq!(
+            stability.record.replays,
+            BTreeMap::from([(RestartReason::UncleanShutdown, 1)])
+        );
+
+        let _ = fs::remove_file(&wal_path);
+        let _ = fs::remove_file(stability_path(&wal_path));
+    }
+}
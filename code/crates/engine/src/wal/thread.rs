@@ -5,13 +5,17 @@ use std::{io, thread};
 
 use eyre::{eyre, Result};
 use tokio::sync::{mpsc, oneshot};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use malachitebft_core_types::{Context, Height};
 use malachitebft_wal as wal;
 
-use super::entry::{decode_entry, encode_entry, WalCodec, WalEntry};
+use super::entry::{
+    decode_header, decode_versioned_entry, encode_entry, FormatVersion, WalCodec, WalEntry,
+};
 use super::iter::log_entries;
+use super::migrate::{append_header, rewrite_log};
+use crate::util::span::record_height_and_round;
 
 pub type ReplyTo<T> = oneshot::Sender<Result<T>>;
 
@@ -26,7 +30,7 @@ pub enum WalMsg<Ctx: Context> {
 
 pub fn spawn<Ctx, Codec>(
     span: tracing::Span,
-    mut log: wal::Log,
+    mut log: Box<dyn wal::WalStorage>,
     codec: Codec,
     mut rx: mpsc::Receiver<WalMsg<Ctx>>,
 ) -> JoinHandle<()>
@@ -37,7 +41,7 @@ where
     thread::spawn(move || {
         let result = catch_unwind(AssertUnwindSafe(|| {
             while let Some(msg) = rx.blocking_recv() {
-                match process_msg(msg, &span, &mut log, &codec) {
+                match process_msg(msg, &span, log.as_mut(), &codec) {
                     Ok(ControlFlow::Continue(())) => continue,
                     Ok(ControlFlow::Break(())) => break,
                     Err(e) => error!("WAL task failed: {e}"),
@@ -60,12 +64,12 @@ where
     name = "wal",
     parent = span,
     skip_all,
-    fields(height = span_sequence(log.sequence(), &msg))
+    fields(height = span_sequence(log.sequence(), &msg), round = tracing::field::Empty)
 )]
 fn process_msg<Ctx, Codec>(
     msg: WalMsg<Ctx>,
     span: &tracing::Span,
-    log: &mut wal::Log,
+    log: &mut dyn wal::WalStorage,
     codec: &Codec,
 ) -> Result<ControlFlow<()>>
 where
@@ -110,6 +114,8 @@ where
         }
 
         WalMsg::Append(entry, reply) => {
+            record_height_and_round(&tracing::Span::current(), log.sequence(), entry.round());
+
             let entry_type = wal_entry_type(&entry);
 
             let mut buf = Vec::new();
@@ -117,11 +123,16 @@ where
             // Capture encoding result and always send a reply to prevent deadlock
             let result = encode_entry(&entry, codec, &mut buf)
                 .and_then(|_| {
-                    if !buf.is_empty() {
-                        log.append(&buf)
-                    } else {
-                        Ok(())
+                    if buf.is_empty() {
+                        return Ok(());
+                    }
+
+                    // Record the format version of the entries before the first one
+                    if log.is_empty() {
+                        append_header(log)?;
                     }
+
+                    log.append(&buf)
                 })
                 .map_err(Into::into);
 
@@ -165,6 +176,12 @@ where
 
         WalMsg::Shutdown => {
             info!("Shutting down WAL thread");
+
+            // Make sure every entry appended so far is on disk before exiting
+            if let Err(e) = log.flush() {
+                error!("Failed to flush WAL on shutdown: {e}");
+            }
+
             return Ok(ControlFlow::Break(()));
         }
     }
@@ -173,7 +190,7 @@ where
 }
 
 fn fetch_entries<Ctx, Codec>(
-    log: &mut wal::Log,
+    log: &mut dyn wal::WalStorage,
     codec: &Codec,
 ) -> Result<Vec<io::Result<WalEntry<Ctx>>>>
 where
@@ -185,35 +202,93 @@ where
     }
 
     let iter = log
-        .iter()
+        .entries()
         .map_err(|e| eyre!("Failed to open WAL for reading entries: {e}"))?;
 
     let mut entries = Vec::new();
+    let mut failed_at = None;
+
+    // WALs at version 1 have no header, their first entry is a regular entry
+    let mut version = FormatVersion::V1;
 
     for (idx, result) in iter.enumerate() {
         match result {
             Ok(bytes) => {
-                let decoded = decode_result(idx, Ok(bytes), codec);
+                if idx == 0 {
+                    if let Some(header) = decode_header(&bytes) {
+                        version = header.map_err(|e| eyre!("Failed to read WAL header: {e}"))?;
+                        continue;
+                    }
+                }
+
+                let decoded = decode_result(idx, version, Ok(bytes), codec);
                 entries.push(decoded);
             }
             Err(e) => {
                 error!("Failed to read WAL entry {idx}: {e}");
                 entries.push(Err(e));
-
-                log.truncate(idx as u64).map_err(|e| {
-                    eyre!("Failed to truncate WAL after read error at entry {idx}: {e}")
-                })?;
+                failed_at = Some(idx);
 
                 break;
             }
         }
     }
 
+    // Drop the entry which could not be read and all entries after it
+    if let Some(idx) = failed_at {
+        log.truncate(idx as u64)
+            .map_err(|e| eyre!("Failed to truncate WAL after read error at entry {idx}: {e}"))?;
+    }
+
+    if version < FormatVersion::CURRENT {
+        migrate_entries(log, codec, version, &entries)?;
+    }
+
     Ok(entries)
 }
 
+/// Rewrite the entries of a WAL written by a previous release at the current format version,
+/// so that they can still be read once the decoders of that version are dropped.
+///
+/// WALs with entries which cannot be decoded are left untouched, so that nothing is lost,
+/// and are only migrated once reset at the next height.
+fn migrate_entries<Ctx, Codec>(
+    log: &mut dyn wal::WalStorage,
+    codec: &Codec,
+    version: FormatVersion,
+    entries: &[io::Result<WalEntry<Ctx>>],
+) -> Result<()>
+where
+    Ctx: Context,
+    Codec: WalCodec<Ctx>,
+{
+    let Some(entries) = entries
+        .iter()
+        .map(|entry| entry.as_ref().ok())
+        .collect::<Option<Vec<_>>>()
+    else {
+        warn!(
+            from = %version, to = %FormatVersion::CURRENT,
+            "Not migrating the WAL as some of its entries cannot be decoded"
+        );
+
+        return Ok(());
+    };
+
+    rewrite_log(log, codec, entries.iter().copied())
+        .map_err(|e| eyre!("Failed to migrate WAL from {version}: {e}"))?;
+
+    info!(
+        from = %version, to = %FormatVersion::CURRENT, entries = entries.len(),
+        "Migrated WAL to the current format version"
+    );
+
+    Ok(())
+}
+
 fn decode_result<Ctx, Codec>(
     idx: usize,
+    version: FormatVersion,
     result: io::Result<Vec<u8>>,
     codec: &Codec,
 ) -> io::Result<WalEntry<Ctx>>
@@ -224,7 +299,7 @@ where
     result
         .inspect_err(|e| error!("Failed to retrieve WAL entry {idx}: {e}"))
         .and_then(|bytes| {
-            decode_entry(codec, io::Cursor::new(&bytes)).inspect_err(|e| {
+            decode_versioned_entry(version, codec, io::Cursor::new(&bytes)).inspect_err(|e| {
                 error!(
                     "Failed to decode WAL entry {idx}: {e} (0x{})",
                     hex::encode(&bytes)
@@ -233,20 +308,28 @@ where
         })
 }
 
-fn dump_entries<'a, Ctx, Codec>(log: &'a mut wal::Log, codec: &'a Codec) -> Result<()>
+fn dump_entries<'a, Ctx, Codec>(log: &'a mut dyn wal::WalStorage, codec: &'a Codec) -> Result<()>
 where
     Ctx: Context,
     Codec: WalCodec<Ctx>,
 {
     let len = log.len();
+    let size = log.size_bytes().unwrap_or(0);
     let mut count = 0;
 
+    let entries = log_entries(log, codec)?;
+    let version = entries.version();
+
+    // The header is not an entry
+    let len = len - usize::from(version.has_header());
+
     info!("WAL Dump");
+    info!("- Format:  {version}");
     info!("- Entries: {len}");
-    info!("- Size:    {} bytes", log.size_bytes().unwrap_or(0));
+    info!("- Size:    {size} bytes");
     info!("Entries:");
 
-    for (idx, entry) in log_entries(log, codec)?.enumerate() {
+    for (idx, entry) in entries.enumerate() {
         count += 1;
 
         match entry {
@@ -0,0 +1,89 @@
+use std::hint::black_box;
+
+use bytes::{Bytes, BytesMut};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+use arc_malachitebft_erasure::{ErasureCoder, Shard};
+
+const DATA_SHARDS: usize = 16;
+const PARITY_SHARDS: usize = 8;
+
+/// Split the value into parts of the same size as the data shards, as when streaming it in plain parts.
+fn split(value: &Bytes, part_len: usize) -> Vec<Bytes> {
+    value
+        .chunks(part_len)
+        .map(|chunk| value.slice_ref(chunk))
+        .collect()
+}
+
+/// Reassemble the value from all its plain parts.
+fn reassemble(parts: &[Bytes]) -> Bytes {
+    let mut value = BytesMut::with_capacity(parts.iter().map(Bytes::len).sum());
+    for part in parts {
+        value.extend_from_slice(part);
+    }
+    value.freeze()
+}
+
+fn erasure_benchmarks(c: &mut Criterion) {
+    let coder = ErasureCoder::new(DATA_SHARDS, PARITY_SHARDS).unwrap();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let value_sizes = vec![
+        64 * 1024,       // 64 KB
+        1024 * 1024,     // 1 MB
+        4 * 1024 * 1024, // 4 MB
+    ];
+
+    let mut group = c.benchmark_group("proposal_dissemination");
+
+    for size in value_sizes {
+        let mut value = vec![0; size];
+        rng.fill_bytes(&mut value);
+        let value = Bytes::from(value);
+
+        let part_len = coder.shard_len(size);
+        let parts = split(&value, part_len);
+        let shards = coder.encode(&value);
+
+        // Reconstruct the value after losing as many data shards as there are parity shards
+        let worst_case = shards[PARITY_SHARDS..].iter().collect::<Vec<&Shard>>();
+
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("plain_split", size), &value, |b, value| {
+            b.iter(|| split(black_box(value), part_len))
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("plain_reassemble", size),
+            &parts,
+            |b, parts| b.iter(|| reassemble(black_box(parts))),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("erasure_encode", size),
+            &value,
+            |b, value| b.iter(|| coder.encode(black_box(value))),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("erasure_decode_data_shards", size),
+            &shards[..DATA_SHARDS],
+            |b, shards| b.iter(|| coder.decode(black_box(shards)).unwrap()),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("erasure_decode_parity_shards", size),
+            &worst_case,
+            |b, shards| b.iter(|| coder.decode(black_box(shards.iter().copied())).unwrap()),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, erasure_benchmarks);
+criterion_main!(benches);
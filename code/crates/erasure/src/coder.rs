@@ -0,0 +1,333 @@
+use std::collections::BTreeMap;
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{gf256, ErasureError, Shard, MAX_SHARDS};
+
+/// A systematic Reed-Solomon code, splitting a value into data shards and computing
+/// parity shards from them, such that the value can be reconstructed from any
+/// `data_shards` of the shards.
+///
+/// The data shards are slices of the value, padded with zeroes to the same length.
+/// The coefficients of the parity shards form a Cauchy matrix, every square submatrix
+/// of which is invertible, which is what makes any `data_shards` shards sufficient.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErasureCoder {
+    data_shards: usize,
+    parity_shards: usize,
+
+    /// Coefficients of the data shards in each parity shard
+    parity_matrix: Vec<Vec<u8>>,
+}
+
+impl ErasureCoder {
+    /// Create a code with the given number of data and parity shards.
+    ///
+    /// There must be at least one data shard, and at most [`MAX_SHARDS`] shards in total.
+    pub fn new(data_shards: usize, parity_shards: usize) -> Result<Self, ErasureError> {
+        if data_shards == 0 || data_shards + parity_shards > MAX_SHARDS {
+            return Err(ErasureError::InvalidShardCount {
+                data_shards,
+                parity_shards,
+            });
+        }
+
+        // The element of row i and column j is 1 / (x_i + y_j), with x_i = data_shards + i
+        // and y_j = j, which are all distinct so that the sum is never zero.
+        let parity_matrix = (0..parity_shards)
+            .map(|i| {
+                (0..data_shards)
+                    .map(|j| gf256::inv((data_shards + i) as u8 ^ j as u8))
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self {
+            data_shards,
+            parity_shards,
+            parity_matrix,
+        })
+    }
+
+    /// Number of data shards, ie. the number of shards needed to reconstruct a value.
+    pub fn data_shards(&self) -> usize {
+        self.data_shards
+    }
+
+    pub fn parity_shards(&self) -> usize {
+        self.parity_shards
+    }
+
+    pub fn total_shards(&self) -> usize {
+        self.data_shards + self.parity_shards
+    }
+
+    /// Length of each of the shards of a value of the given length.
+    pub fn shard_len(&self, value_len: usize) -> usize {
+        value_len.div_ceil(self.data_shards)
+    }
+
+    /// Split the given value into shards, the data shards first followed by the parity shards.
+    pub fn encode(&self, value: &[u8]) -> Vec<Shard> {
+        let shard_len = self.shard_len(value.len());
+
+        let mut padded = BytesMut::zeroed(shard_len * self.data_shards);
+        padded[..value.len()].copy_from_slice(value);
+        let padded = padded.freeze();
+
+        let data = (0..self.data_shards)
+            .map(|j| padded.slice(j * shard_len..(j + 1) * shard_len))
+            .collect::<Vec<_>>();
+
+        let parity = self.parity_matrix.iter().map(|coefficients| {
+            let mut shard = vec![0; shard_len];
+            for (coefficient, data) in coefficients.iter().zip(&data) {
+                gf256::mul_add(*coefficient, data, &mut shard);
+            }
+            Bytes::from(shard)
+        });
+
+        data.iter()
+            .cloned()
+            .chain(parity)
+            .enumerate()
+            .map(|(index, data)| Shard {
+                index: index as u16,
+                data_shards: self.data_shards as u16,
+                parity_shards: self.parity_shards as u16,
+                value_len: value.len() as u64,
+                data,
+            })
+            .collect()
+    }
+
+    /// Reconstruct a value from at least `data_shards` of its shards.
+    ///
+    /// Duplicate shards are ignored. When all data shards are present,
+    /// the value is reassembled from them without any computation.
+    pub fn decode<'a>(
+        &self,
+        shards: impl IntoIterator<Item = &'a Shard>,
+    ) -> Result<Bytes, ErasureError> {
+        let mut received = BTreeMap::new();
+        let mut value_len = None;
+
+        for shard in shards {
+            let shard_value_len = self.check(shard, value_len)?;
+            value_len = Some(shard_value_len);
+            received.entry(shard.index as usize).or_insert(&shard.data);
+        }
+
+        if received.len() < self.data_shards {
+            return Err(ErasureError::NotEnoughShards {
+                received: received.len(),
+                required: self.data_shards,
+            });
+        }
+
+        let value_len = value_len.unwrap_or_default();
+        let shard_len = self.shard_len(value_len);
+
+        // Use the data shards first, as they need no computation, then the parity shards
+        let selected = received
+            .into_iter()
+            .take(self.data_shards)
+            .collect::<Vec<_>>();
+
+        let mut value = BytesMut::with_capacity(shard_len * self.data_shards);
+
+        if selected.iter().all(|(index, _)| *index < self.data_shards) {
+            for (_, data) in &selected {
+                value.extend_from_slice(data);
+            }
+        } else {
+            let matrix = selected
+                .iter()
+                .map(|(index, _)| self.coefficients(*index))
+                .collect();
+
+            // The selected rows of the code are invertible, see `ErasureCoder::new`
+            let inverse = gf256::invert(matrix).expect("submatrix of the code is invertible");
+
+            for (j, row) in inverse.iter().enumerate() {
+                match selected.iter().find(|(index, _)| *index == j) {
+                    Some((_, data)) => value.extend_from_slice(data),
+                    None => {
+                        let mut shard = vec![0; shard_len];
+                        for (coefficient, (_, data)) in row.iter().zip(&selected) {
+                            gf256::mul_add(*coefficient, data, &mut shard);
+                        }
+                        value.extend_from_slice(&shard);
+                    }
+                }
+            }
+        }
+
+        value.truncate(value_len);
+        Ok(value.freeze())
+    }
+
+    /// Check that the given shard was produced by this code, and is consistent with the value
+    /// length of the other shards, if any. Returns the length of the value.
+    pub(crate) fn check(
+        &self,
+        shard: &Shard,
+        value_len: Option<usize>,
+    ) -> Result<usize, ErasureError> {
+        let index = shard.index as usize;
+        let inconsistent = |reason| ErasureError::InconsistentShard { index, reason };
+
+        if shard.data_shards as usize != self.data_shards
+            || shard.parity_shards as usize != self.parity_shards
+        {
+            return Err(inconsistent("different number of shards"));
+        }
+
+        if index >= self.total_shards() {
+            return Err(ErasureError::InvalidShardIndex {
+                index,
+                total_shards: self.total_shards(),
+            });
+        }
+
+        let shard_value_len = usize::try_from(shard.value_len)
+            .map_err(|_| inconsistent("value length is too large"))?;
+
+        if value_len.is_some_and(|len| len != shard_value_len) {
+            return Err(inconsistent("different value length"));
+        }
+
+        if shard.data.len() != self.shard_len(shard_value_len) {
+            return Err(inconsistent("shard length does not match the value length"));
+        }
+
+        Ok(shard_value_len)
+    }
+
+    /// Coefficients of the data shards in the shard with the given index.
+    fn coefficients(&self, index: usize) -> Vec<u8> {
+        if index < self.data_shards {
+            (0..self.data_shards)
+                .map(|j| u8::from(j == index))
+                .collect()
+        } else {
+            self.parity_matrix[index - self.data_shards].clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{RngCore, SeedableRng};
+
+    use super::*;
+
+    fn random_value(rng: &mut StdRng, len: usize) -> Vec<u8> {
+        let mut value = vec![0; len];
+        rng.fill_bytes(&mut value);
+        value
+    }
+
+    #[test]
+    fn invalid_shard_count() {
+        assert!(ErasureCoder::new(0, 2).is_err());
+        assert!(ErasureCoder::new(200, 57).is_err());
+        assert!(ErasureCoder::new(200, 56).is_ok());
+        assert!(ErasureCoder::new(1, 0).is_ok());
+    }
+
+    #[test]
+    fn data_shards_are_slices_of_the_value() {
+        let coder = ErasureCoder::new(3, 2).unwrap();
+        let shards = coder.encode(b"hello world");
+
+        assert_eq!(shards.len(), 5);
+        assert_eq!(shards[0].data, &b"hell"[..]);
+        assert_eq!(shards[1].data, &b"o wo"[..]);
+        assert_eq!(shards[2].data, &b"rld\0"[..]);
+        assert!(shards.iter().all(|shard| shard.value_len == 11));
+        assert!(shards[3].is_parity() && shards[4].is_parity());
+    }
+
+    #[test]
+    fn reconstruct_from_any_subset() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for (data_shards, parity_shards) in [(1, 0), (1, 3), (4, 2), (5, 5), (16, 8)] {
+            let coder = ErasureCoder::new(data_shards, parity_shards).unwrap();
+
+            for len in [0, 1, data_shards, 1000, 4099] {
+                let value = random_value(&mut rng, len);
+                let mut shards = coder.encode(&value);
+
+                for _ in 0..10 {
+                    shards.shuffle(&mut rng);
+                    let decoded = coder.decode(&shards[..data_shards]).unwrap();
+                    assert_eq!(
+                        decoded, value,
+                        "{data_shards}+{parity_shards} shards, {len} bytes"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reconstruct_from_parity_shards_only() {
+        let coder = ErasureCoder::new(4, 4).unwrap();
+        let value = random_value(&mut StdRng::seed_from_u64(7), 333);
+        let shards = coder.encode(&value);
+
+        assert_eq!(coder.decode(&shards[4..]).unwrap(), value);
+    }
+
+    #[test]
+    fn not_enough_shards() {
+        let coder = ErasureCoder::new(4, 2).unwrap();
+        let shards = coder.encode(&[1; 100]);
+
+        // Duplicates do not count
+        let duplicates = [&shards[0], &shards[1], &shards[2], &shards[2]];
+
+        assert_eq!(
+            coder.decode(duplicates),
+            Err(ErasureError::NotEnoughShards {
+                received: 3,
+                required: 4
+            })
+        );
+    }
+
+    #[test]
+    fn inconsistent_shards() {
+        let coder = ErasureCoder::new(2, 1).unwrap();
+        let mut shards = coder.encode(&[1; 10]);
+
+        shards[1].value_len = 9;
+        assert!(matches!(
+            coder.decode(&shards),
+            Err(ErasureError::InconsistentShard { index: 1, .. })
+        ));
+
+        shards[1].value_len = 10;
+        shards[1].data = Bytes::from_static(&[1; 4]);
+        assert!(matches!(
+            coder.decode(&shards),
+            Err(ErasureError::InconsistentShard { index: 1, .. })
+        ));
+
+        shards[1].index = 3;
+        assert!(matches!(
+            coder.decode(&shards),
+            Err(ErasureError::InvalidShardIndex { index: 3, .. })
+        ));
+
+        let other = ErasureCoder::new(3, 1).unwrap();
+        assert!(matches!(
+            other.decode(&shards),
+            Err(ErasureError::InconsistentShard { index: 0, .. })
+        ));
+    }
+}
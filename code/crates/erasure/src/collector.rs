@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use bytes::Bytes;
+
+use crate::{ErasureCoder, ErasureError, Shard};
+
+/// Collects the shards of a value as they are received,
+/// and reconstructs the value as soon as enough of them have been received.
+///
+/// The parameters of the code are taken from the first shard,
+/// and every following shard must be consistent with it.
+#[derive(Clone, Debug, Default)]
+pub struct ShardCollector {
+    coder: Option<ErasureCoder>,
+    value_len: Option<usize>,
+    shards: BTreeMap<u16, Shard>,
+    reconstructed: bool,
+}
+
+impl ShardCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a shard of the value, returning the value if it can be reconstructed
+    /// with this shard. The value is returned once, further shards are ignored.
+    pub fn insert(&mut self, shard: Shard) -> Result<Option<Bytes>, ErasureError> {
+        if self.reconstructed {
+            return Ok(None);
+        }
+
+        let coder = match &mut self.coder {
+            Some(coder) => coder,
+            coder @ None => coder.insert(ErasureCoder::new(
+                shard.data_shards as usize,
+                shard.parity_shards as usize,
+            )?),
+        };
+
+        self.value_len = Some(coder.check(&shard, self.value_len)?);
+        self.shards.entry(shard.index).or_insert(shard);
+
+        if self.shards.len() < coder.data_shards() {
+            return Ok(None);
+        }
+
+        let value = coder.decode(self.shards.values())?;
+
+        self.reconstructed = true;
+        self.shards.clear();
+
+        Ok(Some(value))
+    }
+
+    /// Number of distinct shards received, until the value is reconstructed.
+    pub fn received(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Whether the value was reconstructed.
+    pub fn is_reconstructed(&self) -> bool {
+        self.reconstructed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconstruct_once_enough_shards_are_received() {
+        let coder = ErasureCoder::new(3, 3).unwrap();
+        let value = (0..=255).cycle().take(1000).collect::<Vec<u8>>();
+        let shards = coder.encode(&value);
+
+        let mut collector = ShardCollector::new();
+
+        // Receive the shards out of order, with a duplicate
+        assert_eq!(collector.insert(shards[4].clone()), Ok(None));
+        assert_eq!(collector.insert(shards[1].clone()), Ok(None));
+        assert_eq!(collector.insert(shards[4].clone()), Ok(None));
+        assert_eq!(collector.received(), 2);
+
+        assert_eq!(collector.insert(shards[5].clone()), Ok(Some(value.into())));
+        assert!(collector.is_reconstructed());
+
+        // Late shards are ignored
+        assert_eq!(collector.insert(shards[0].clone()), Ok(None));
+    }
+
+    #[test]
+    fn reject_inconsistent_shards() {
+        let shards = ErasureCoder::new(2, 2).unwrap().encode(&[1; 10]);
+        let other = ErasureCoder::new(2, 2).unwrap().encode(&[1; 12]);
+
+        let mut collector = ShardCollector::new();
+        assert_eq!(collector.insert(shards[0].clone()), Ok(None));
+
+        assert!(matches!(
+            collector.insert(other[1].clone()),
+            Err(ErasureError::InconsistentShard { index: 1, .. })
+        ));
+
+        assert_eq!(collector.received(), 1);
+        assert!(collector.insert(shards[3].clone()).unwrap().is_some());
+    }
+}
@@ -0,0 +1,26 @@
+use crate::MAX_SHARDS;
+
+/// Error returned when encoding, decoding or reconstructing shards.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ErasureError {
+    #[error(
+        "invalid number of shards: {data_shards} data shards and {parity_shards} parity shards, \
+         expected at least one data shard and at most {MAX_SHARDS} shards in total"
+    )]
+    InvalidShardCount {
+        data_shards: usize,
+        parity_shards: usize,
+    },
+
+    #[error("shard index {index} is out of range for {total_shards} shards")]
+    InvalidShardIndex { index: usize, total_shards: usize },
+
+    #[error("shard {index} does not match the other shards of the value: {reason}")]
+    InconsistentShard { index: usize, reason: &'static str },
+
+    #[error("not enough shards to reconstruct the value: got {received}, need {required}")]
+    NotEnoughShards { received: usize, required: usize },
+
+    #[error("shard is truncated: got {len} bytes, expected at least {min_len}")]
+    Truncated { len: usize, min_len: usize },
+}
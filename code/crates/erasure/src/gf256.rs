@@ -0,0 +1,167 @@
+//! Arithmetic in GF(2^8), the field over which the shards are computed.
+//!
+//! Elements are bytes, addition is XOR, and multiplication is carried out
+//! modulo the polynomial x^8 + x^4 + x^3 + x^2 + 1, with logarithm tables.
+
+/// The reduction polynomial, x^8 + x^4 + x^3 + x^2 + 1.
+const POLYNOMIAL: u16 = 0x11d;
+
+/// Powers of the generator 2, repeated twice so that the sum of two logarithms
+/// can index the table without being reduced modulo 255.
+static EXP: [u8; 510] = exp_table();
+
+/// Logarithms in base 2 of the non-zero elements. The logarithm of 0 is undefined.
+static LOG: [u8; 256] = log_table();
+
+const fn exp_table() -> [u8; 510] {
+    let mut table = [0; 510];
+    let mut x: u16 = 1;
+    let mut i = 0;
+
+    while i < 255 {
+        table[i] = x as u8;
+        table[i + 255] = x as u8;
+
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= POLYNOMIAL;
+        }
+
+        i += 1;
+    }
+
+    table
+}
+
+const fn log_table() -> [u8; 256] {
+    let exp = exp_table();
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 255 {
+        table[exp[i] as usize] = i as u8;
+        i += 1;
+    }
+
+    table
+}
+
+pub fn mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+
+    EXP[LOG[a as usize] as usize + LOG[b as usize] as usize]
+}
+
+/// The multiplicative inverse of the given element.
+///
+/// # Panics
+/// If the element is 0, which has no inverse.
+pub fn inv(a: u8) -> u8 {
+    assert_ne!(a, 0, "0 has no inverse in GF(256)");
+    EXP[255 - LOG[a as usize] as usize]
+}
+
+/// Add `c * input` to `output`, byte by byte.
+pub fn mul_add(c: u8, input: &[u8], output: &mut [u8]) {
+    debug_assert_eq!(input.len(), output.len());
+
+    match c {
+        0 => {}
+        1 => {
+            for (out, x) in output.iter_mut().zip(input) {
+                *out ^= x;
+            }
+        }
+        _ => {
+            let mut table = [0; 256];
+            for (x, product) in table.iter_mut().enumerate() {
+                *product = mul(c, x as u8);
+            }
+
+            for (out, x) in output.iter_mut().zip(input) {
+                *out ^= table[*x as usize];
+            }
+        }
+    }
+}
+
+/// Invert the given square matrix, or return `None` if it is singular.
+pub fn invert(mut matrix: Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
+    let n = matrix.len();
+
+    let mut inverse = (0..n)
+        .map(|i| (0..n).map(|j| u8::from(i == j)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    // Gauss-Jordan elimination, applying the same row operations to the identity matrix
+    for col in 0..n {
+        let pivot = (col..n).find(|&row| matrix[row][col] != 0)?;
+        matrix.swap(col, pivot);
+        inverse.swap(col, pivot);
+
+        let scale = inv(matrix[col][col]);
+        for j in 0..n {
+            matrix[col][j] = mul(matrix[col][j], scale);
+            inverse[col][j] = mul(inverse[col][j], scale);
+        }
+
+        for row in 0..n {
+            let factor = matrix[row][col];
+            if row == col || factor == 0 {
+                continue;
+            }
+
+            for j in 0..n {
+                matrix[row][j] ^= mul(factor, matrix[col][j]);
+                inverse[row][j] ^= mul(factor, inverse[col][j]);
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_axioms() {
+        for a in 1..=255u8 {
+            assert_eq!(mul(a, inv(a)), 1);
+            assert_eq!(mul(a, 1), a);
+            assert_eq!(mul(a, 0), 0);
+
+            for b in [2, 3, 0x53, 0xca, 0xff] {
+                assert_eq!(mul(a, b), mul(b, a));
+            }
+        }
+    }
+
+    #[test]
+    fn known_products() {
+        assert_eq!(mul(2, 0x80), 0x1d);
+        assert_eq!(mul(0x53, 0xca), 0x8f);
+    }
+
+    #[test]
+    fn invert_matrix() {
+        let matrix = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 10]];
+        let inverse = invert(matrix.clone()).unwrap();
+
+        for (i, row) in matrix.iter().enumerate() {
+            for j in 0..3 {
+                let product = row
+                    .iter()
+                    .zip(&inverse)
+                    .fold(0, |acc, (x, inverse_row)| acc ^ mul(*x, inverse_row[j]));
+
+                assert_eq!(product, u8::from(i == j));
+            }
+        }
+
+        assert_eq!(invert(vec![vec![1, 2], vec![1, 2]]), None);
+    }
+}
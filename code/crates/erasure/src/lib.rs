@@ -0,0 +1,35 @@
+//! Reed-Solomon erasure coding of values, for disseminating large proposals.
+//!
+//! When a value is streamed as a sequence of proposal parts, a peer must receive every
+//! part before it can reassemble the value, and a part missing from the mesh of a peer
+//! delays the whole proposal. With erasure coding, the proposer instead splits the value
+//! into `data_shards` shards and computes `parity_shards` additional shards from them,
+//! sending every shard as a proposal part. Peers reconstruct the value from the first
+//! `data_shards` shards they receive, whichever they are.
+//!
+//! - The proposer encodes the value with [`ErasureCoder::encode`], and embeds each [`Shard`]
+//!   in a proposal part, eg. as encoded by [`ShardCodec`].
+//! - Peers feed the shards they receive into a [`ShardCollector`], which returns the value
+//!   as soon as enough shards were received.
+//!
+//! The shards are not authenticated by this crate: applications must check the reconstructed
+//! value against the signature of the proposer, as they do for values streamed in plain parts.
+//!
+//! The engine does not erasure-code values by itself, as the proposal parts are defined by
+//! the application. An application opting into erasure coding streams the shards in its own
+//! parts, and considers a value received once its [`ShardCollector`] returned it, without
+//! waiting for the rest of the stream.
+
+mod coder;
+mod collector;
+mod error;
+mod gf256;
+mod shard;
+
+pub use coder::ErasureCoder;
+pub use collector::ShardCollector;
+pub use error::ErasureError;
+pub use shard::{Shard, ShardCodec};
+
+/// Maximum number of shards, data and parity shards included, of a value.
+pub const MAX_SHARDS: usize = 256;
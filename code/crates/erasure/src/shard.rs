@@ -0,0 +1,129 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use malachitebft_codec::{Codec, HasEncodedLen};
+
+use crate::ErasureError;
+
+/// One of the shards of an erasure-coded value.
+///
+/// Each shard carries the parameters of the code and the length of the value,
+/// so that any `data_shards` of the shards of a value are enough to reconstruct it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shard {
+    /// Index of the shard, the first `data_shards` indices being those of the data shards
+    pub index: u16,
+
+    /// Number of data shards of the value, ie. the number of shards needed to reconstruct it
+    pub data_shards: u16,
+
+    /// Number of parity shards of the value
+    pub parity_shards: u16,
+
+    /// Length of the value in bytes, which is padded to a multiple of `data_shards`
+    pub value_len: u64,
+
+    /// Content of the shard
+    pub data: Bytes,
+}
+
+impl Shard {
+    /// Length of the header preceding the content of the shard in its encoding.
+    pub const HEADER_LEN: usize = 14;
+
+    /// Total number of shards of the value.
+    pub fn total_shards(&self) -> usize {
+        self.data_shards as usize + self.parity_shards as usize
+    }
+
+    /// Whether this is a parity shard, rather than a slice of the value.
+    pub fn is_parity(&self) -> bool {
+        self.index >= self.data_shards
+    }
+}
+
+/// Binary encoding of shards, to embed them into the proposal parts of an application.
+///
+/// A shard is encoded as its index, number of data shards and number of parity shards
+/// as big-endian `u16`, followed by the length of the value as a big-endian `u64`,
+/// followed by the content of the shard.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ShardCodec;
+
+impl Codec<Shard> for ShardCodec {
+    type Error = ErasureError;
+
+    fn decode(&self, mut bytes: Bytes) -> Result<Shard, Self::Error> {
+        if bytes.len() < Shard::HEADER_LEN {
+            return Err(ErasureError::Truncated {
+                len: bytes.len(),
+                min_len: Shard::HEADER_LEN,
+            });
+        }
+
+        let index = bytes.get_u16();
+        let data_shards = bytes.get_u16();
+        let parity_shards = bytes.get_u16();
+        let value_len = bytes.get_u64();
+
+        Ok(Shard {
+            index,
+            data_shards,
+            parity_shards,
+            value_len,
+            data: bytes,
+        })
+    }
+
+    fn encode(&self, shard: &Shard) -> Result<Bytes, Self::Error> {
+        let mut bytes = BytesMut::with_capacity(Shard::HEADER_LEN + shard.data.len());
+
+        bytes.put_u16(shard.index);
+        bytes.put_u16(shard.data_shards);
+        bytes.put_u16(shard.parity_shards);
+        bytes.put_u64(shard.value_len);
+        bytes.put_slice(&shard.data);
+
+        Ok(bytes.freeze())
+    }
+}
+
+impl HasEncodedLen<Shard> for ShardCodec {
+    fn encoded_len(&self, shard: &Shard) -> Result<usize, Self::Error> {
+        Ok(Shard::HEADER_LEN + shard.data.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let shard = Shard {
+            index: 5,
+            data_shards: 4,
+            parity_shards: 2,
+            value_len: 1000,
+            data: Bytes::from(vec![42; 250]),
+        };
+
+        let bytes = ShardCodec.encode(&shard).unwrap();
+        assert_eq!(bytes.len(), ShardCodec.encoded_len(&shard).unwrap());
+        assert_eq!(
+            &bytes[..Shard::HEADER_LEN],
+            &[0, 5, 0, 4, 0, 2, 0, 0, 0, 0, 0, 0, 3, 232]
+        );
+        assert_eq!(ShardCodec.decode(bytes).unwrap(), shard);
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(
+            ShardCodec.decode(Bytes::from_static(&[0; 13])),
+            Err(ErasureError::Truncated {
+                len: 13,
+                min_len: Shard::HEADER_LEN
+            })
+        );
+    }
+}
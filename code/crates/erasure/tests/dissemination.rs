@@ -0,0 +1,94 @@
+//! Dissemination of a value as erasure-coded proposal parts, as an application would do it:
+//! the proposer encodes the value and embeds every shard in a part with [`ShardCodec`],
+//! and peers reconstruct the value from any `data_shards` of the parts they receive.
+
+use bytes::Bytes;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{RngCore, SeedableRng};
+
+use arc_malachitebft_erasure::{ErasureCoder, ShardCodec, ShardCollector};
+use malachitebft_codec::Codec;
+
+/// Encode the value into the payloads of the parts streamed by the proposer.
+fn parts(coder: &ErasureCoder, value: &[u8]) -> Vec<Bytes> {
+    coder
+        .encode(value)
+        .iter()
+        .map(|shard| ShardCodec.encode(shard).unwrap())
+        .collect()
+}
+
+/// Receive the given parts in order, returning the value along with the number
+/// of parts received when it was reconstructed.
+fn receive(parts: &[Bytes]) -> Option<(Bytes, usize)> {
+    let mut collector = ShardCollector::new();
+
+    for (received, part) in parts.iter().enumerate() {
+        let shard = ShardCodec.decode(part.clone()).unwrap();
+
+        if let Some(value) = collector.insert(shard).unwrap() {
+            return Some((value, received + 1));
+        }
+    }
+
+    None
+}
+
+/// All the subsets of the given size of the indices below `n`.
+fn subsets(n: usize, k: usize) -> Vec<Vec<usize>> {
+    (0..1u32 << n)
+        .filter(|mask| mask.count_ones() as usize == k)
+        .map(|mask| (0..n).filter(|i| mask & (1 << i) != 0).collect())
+        .collect()
+}
+
+#[test]
+fn any_k_of_n_parts_reconstruct_the_value() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    for (data_shards, parity_shards) in [(1, 2), (2, 2), (3, 3), (4, 4), (6, 3)] {
+        let coder = ErasureCoder::new(data_shards, parity_shards).unwrap();
+
+        let mut value = vec![0; 1000];
+        rng.fill_bytes(&mut value);
+
+        let parts = parts(&coder, &value);
+        assert_eq!(parts.len(), data_shards + parity_shards);
+
+        for subset in subsets(parts.len(), data_shards) {
+            let mut received = subset.iter().map(|&i| parts[i].clone()).collect::<Vec<_>>();
+            received.shuffle(&mut rng);
+
+            let (reconstructed, count) = receive(&received).unwrap();
+            assert_eq!(reconstructed, value, "shards {subset:?}");
+            assert_eq!(count, data_shards);
+
+            // One part fewer is not enough
+            assert_eq!(receive(&received[1..]), None);
+        }
+    }
+}
+
+#[test]
+fn lost_and_duplicated_parts() {
+    let mut rng = StdRng::seed_from_u64(7);
+
+    let coder = ErasureCoder::new(16, 8).unwrap();
+
+    let mut value = vec![0; 64 * 1024];
+    rng.fill_bytes(&mut value);
+
+    let mut parts = parts(&coder, &value);
+
+    for _ in 0..20 {
+        // Lose as many parts as there are parity shards, and receive some of the others twice
+        parts.shuffle(&mut rng);
+        let mut received = parts[8..].to_vec();
+        received.extend_from_slice(&parts[8..12]);
+        received.shuffle(&mut rng);
+
+        let (reconstructed, _) = receive(&received).unwrap();
+        assert_eq!(reconstructed, value);
+    }
+}
@@ -0,0 +1,207 @@
+/*
+ * C bindings for the Malachite BFT core consensus library.
+ *
+ * Must be kept in sync with the `arc-malachitebft-ffi` crate.
+ * See the crate documentation for the semantics of each function and effect.
+ */
+
+#ifndef MALACHITE_H
+#define MALACHITE_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define MAL_ADDRESS_LEN 20
+#define MAL_VALUE_ID_LEN 32
+#define MAL_PUBLIC_KEY_LEN 32
+#define MAL_SIGNATURE_LEN 64
+
+typedef int32_t MalStatus;
+
+#define MAL_OK 0
+#define MAL_ERR_NULL_POINTER -1
+#define MAL_ERR_INVALID_ARGUMENT -2
+#define MAL_ERR_CONSENSUS -3
+#define MAL_ERR_PANIC -4
+
+#define MAL_VOTE_PREVOTE 0
+#define MAL_VOTE_PRECOMMIT 1
+
+#define MAL_TIMEOUT_PROPOSE 0
+#define MAL_TIMEOUT_PREVOTE 1
+#define MAL_TIMEOUT_PRECOMMIT 2
+#define MAL_TIMEOUT_REBROADCAST 3
+#define MAL_TIMEOUT_FINALIZE_HEIGHT 4
+
+#define MAL_VALUE_PAYLOAD_PARTS_ONLY 0
+#define MAL_VALUE_PAYLOAD_PROPOSAL_ONLY 1
+#define MAL_VALUE_PAYLOAD_PROPOSAL_AND_PARTS 2
+
+#define MAL_ROLE_PROPOSER 0
+#define MAL_ROLE_VALIDATOR 1
+#define MAL_ROLE_NONE 2
+
+#define MAL_WAL_VOTE 0
+#define MAL_WAL_PROPOSAL 1
+#define MAL_WAL_TIMEOUT 2
+#define MAL_WAL_PROPOSED_VALUE 3
+
+#define MAL_STEP_UNSTARTED 0
+#define MAL_STEP_PROPOSE 1
+#define MAL_STEP_PREVOTE 2
+#define MAL_STEP_PRECOMMIT 3
+#define MAL_STEP_COMMIT 4
+
+typedef enum MalEffectKind {
+    MAL_EFFECT_CANCEL_ALL_TIMEOUTS = 0,
+    MAL_EFFECT_CANCEL_TIMEOUT = 1,
+    MAL_EFFECT_SCHEDULE_TIMEOUT = 2,
+    MAL_EFFECT_START_ROUND = 3,
+    MAL_EFFECT_PUBLISH_VOTE = 4,
+    MAL_EFFECT_PUBLISH_PROPOSAL = 5,
+    MAL_EFFECT_REPUBLISH_VOTE = 6,
+    MAL_EFFECT_PUBLISH_CERTIFICATE = 7,
+    MAL_EFFECT_GET_VALUE = 8,
+    MAL_EFFECT_RESTREAM_PROPOSAL = 9,
+    MAL_EFFECT_VALIDATE_VALUE = 10,
+    MAL_EFFECT_DECIDE = 11,
+    MAL_EFFECT_FINALIZE = 12,
+    MAL_EFFECT_SIGN_VOTE = 13,
+    MAL_EFFECT_SIGN_PROPOSAL = 14,
+    MAL_EFFECT_VERIFY_VOTE_SIGNATURE = 15,
+    MAL_EFFECT_VERIFY_PROPOSAL_SIGNATURE = 16,
+    MAL_EFFECT_WAL_APPEND = 17,
+} MalEffectKind;
+
+typedef struct MalValidator {
+    uint8_t address[MAL_ADDRESS_LEN];
+    uint8_t public_key[MAL_PUBLIC_KEY_LEN];
+    uint64_t voting_power;
+} MalValidator;
+
+typedef struct MalVote {
+    uint8_t vote_type;
+    uint64_t height;
+    int64_t round;
+    bool is_nil;
+    uint8_t value_id[MAL_VALUE_ID_LEN];
+    uint8_t address[MAL_ADDRESS_LEN];
+    uint8_t signature[MAL_SIGNATURE_LEN];
+} MalVote;
+
+typedef struct MalProposal {
+    uint64_t height;
+    int64_t round;
+    int64_t pol_round;
+    uint8_t value_id[MAL_VALUE_ID_LEN];
+    uint8_t address[MAL_ADDRESS_LEN];
+    uint8_t signature[MAL_SIGNATURE_LEN];
+} MalProposal;
+
+typedef struct MalTimeout {
+    uint8_t kind;
+    int64_t round;
+    uint64_t duration_ms;
+} MalTimeout;
+
+typedef struct MalConfig {
+    uint8_t address[MAL_ADDRESS_LEN];
+    uint8_t value_payload;
+    uint64_t timeout_propose_ms;
+    uint64_t timeout_propose_delta_ms;
+    uint64_t timeout_prevote_ms;
+    uint64_t timeout_prevote_delta_ms;
+    uint64_t timeout_precommit_ms;
+    uint64_t timeout_precommit_delta_ms;
+    uint64_t timeout_rebroadcast_ms;
+} MalConfig;
+
+typedef struct MalEffect {
+    MalEffectKind kind;
+    uint64_t height;
+    int64_t round;
+    int64_t valid_round;
+    uint8_t role;
+    uint8_t address[MAL_ADDRESS_LEN];
+    uint8_t value_id[MAL_VALUE_ID_LEN];
+    bool valid;
+    MalTimeout timeout;
+    uint8_t public_key[MAL_PUBLIC_KEY_LEN];
+    MalVote vote;
+    MalProposal proposal;
+    const MalVote *votes;
+    size_t votes_len;
+    uint8_t wal_entry;
+} MalEffect;
+
+typedef struct MalResume {
+    uint8_t signature[MAL_SIGNATURE_LEN];
+    bool valid;
+} MalResume;
+
+typedef struct MalState {
+    uint64_t height;
+    int64_t round;
+    uint8_t step;
+    int64_t locked_round;
+    uint8_t locked_value_id[MAL_VALUE_ID_LEN];
+    int64_t valid_round;
+    uint8_t valid_value_id[MAL_VALUE_ID_LEN];
+    bool decided;
+    int64_t decided_round;
+    uint8_t decided_value_id[MAL_VALUE_ID_LEN];
+} MalState;
+
+typedef int32_t (*MalEffectCallback)(void *user_data, const MalEffect *effect, MalResume *resume);
+
+typedef struct MalHost {
+    void *user_data;
+    MalEffectCallback on_effect;
+} MalHost;
+
+typedef struct MalConsensus MalConsensus;
+
+MalConsensus *malachite_new(const MalConfig *config,
+                            uint64_t height,
+                            const MalValidator *validators,
+                            size_t validators_len,
+                            MalHost host);
+
+void malachite_free(MalConsensus *consensus);
+
+MalStatus malachite_start_height(MalConsensus *consensus,
+                                 uint64_t height,
+                                 const MalValidator *validators,
+                                 size_t validators_len);
+
+MalStatus malachite_propose(MalConsensus *consensus,
+                            uint64_t height,
+                            int64_t round,
+                            const uint8_t *value_id);
+
+MalStatus malachite_receive_vote(MalConsensus *consensus, const MalVote *vote);
+
+MalStatus malachite_receive_proposal(MalConsensus *consensus, const MalProposal *proposal);
+
+MalStatus malachite_proposed_value(MalConsensus *consensus,
+                                   uint64_t height,
+                                   int64_t round,
+                                   int64_t valid_round,
+                                   const uint8_t *proposer,
+                                   const uint8_t *value_id,
+                                   bool valid);
+
+MalStatus malachite_timeout_elapsed(MalConsensus *consensus, const MalTimeout *timeout);
+
+MalStatus malachite_state(const MalConsensus *consensus, MalState *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MALACHITE_H */
@@ -0,0 +1,366 @@
+//! The concrete [`Context`] used by the FFI bindings.
+//!
+//! Heights are plain integers, addresses, value ids, public keys and signatures are
+//! fixed-size byte arrays, and values are identified by their 32-byte id only.
+//! The host application is responsible for disseminating the actual values and for
+//! computing their ids, eg. by hashing them.
+
+use core::fmt;
+
+use malachitebft_core_types::{
+    self as types, LinearTimeouts, NilOrVal, Round, SignedExtension, VoteType, VotingPower,
+};
+
+/// Size in bytes of an [`Address`].
+pub const ADDRESS_LEN: usize = 20;
+
+/// Size in bytes of a [`ValueId`].
+pub const VALUE_ID_LEN: usize = 32;
+
+/// Size in bytes of a [`PublicKey`].
+pub const PUBLIC_KEY_LEN: usize = 32;
+
+/// Size in bytes of a [`Signature`].
+pub const SIGNATURE_LEN: usize = 64;
+
+fn fmt_hex(bytes: &[u8], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    bytes.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FfiContext;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Height(pub u64);
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl types::Height for Height {
+    const ZERO: Self = Self(0);
+    const INITIAL: Self = Self(1);
+
+    fn increment_by(&self, n: u64) -> Self {
+        Self(self.0 + n)
+    }
+
+    fn decrement_by(&self, n: u64) -> Option<Self> {
+        self.0.checked_sub(n).map(Self)
+    }
+
+    fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Address(pub [u8; ADDRESS_LEN]);
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_hex(&self.0, f)
+    }
+}
+
+impl types::Address for Address {}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ValueId(pub [u8; VALUE_ID_LEN]);
+
+impl fmt::Display for ValueId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_hex(&self.0, f)
+    }
+}
+
+/// A value, identified by its id.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Value(pub ValueId);
+
+impl types::Value for Value {
+    type Id = ValueId;
+
+    fn id(&self) -> ValueId {
+        self.0
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Signature(pub [u8; SIGNATURE_LEN]);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PublicKey(pub [u8; PUBLIC_KEY_LEN]);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InvalidLength {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for InvalidLength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid length: expected {} bytes, got {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+/// Signing is performed by the host application, hence there is no private key on the Rust side.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HostSigningScheme;
+
+impl types::SigningScheme for HostSigningScheme {
+    type DecodingError = InvalidLength;
+    type Signature = Signature;
+    type PublicKey = PublicKey;
+    type PrivateKey = ();
+
+    fn decode_signature(bytes: &[u8]) -> Result<Signature, InvalidLength> {
+        bytes.try_into().map(Signature).map_err(|_| InvalidLength {
+            expected: SIGNATURE_LEN,
+            actual: bytes.len(),
+        })
+    }
+
+    fn encode_signature(signature: &Signature) -> Vec<u8> {
+        signature.0.to_vec()
+    }
+
+    fn decode_public_key(bytes: &[u8]) -> Result<PublicKey, InvalidLength> {
+        bytes.try_into().map(PublicKey).map_err(|_| InvalidLength {
+            expected: PUBLIC_KEY_LEN,
+            actual: bytes.len(),
+        })
+    }
+
+    fn encode_public_key(public_key: &PublicKey) -> Vec<u8> {
+        public_key.0.to_vec()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Validator {
+    pub address: Address,
+    pub public_key: PublicKey,
+    pub voting_power: VotingPower,
+}
+
+impl types::Validator<FfiContext> for Validator {
+    fn address(&self) -> &Address {
+        &self.address
+    }
+
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    fn voting_power(&self) -> VotingPower {
+        self.voting_power
+    }
+}
+
+/// A validator set, in the order given by the host application.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorSet {
+    pub validators: Vec<Validator>,
+}
+
+impl types::ValidatorSet<FfiContext> for ValidatorSet {
+    fn count(&self) -> usize {
+        self.validators.len()
+    }
+
+    fn total_voting_power(&self) -> VotingPower {
+        self.validators.iter().map(|v| v.voting_power).sum()
+    }
+
+    fn get_by_address(&self, address: &Address) -> Option<&Validator> {
+        self.validators.iter().find(|v| &v.address == address)
+    }
+
+    fn get_by_index(&self, index: usize) -> Option<&Validator> {
+        self.validators.get(index)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vote {
+    pub vote_type: VoteType,
+    pub height: Height,
+    pub round: Round,
+    pub value: NilOrVal<ValueId>,
+    pub validator_address: Address,
+}
+
+impl types::Vote<FfiContext> for Vote {
+    fn height(&self) -> Height {
+        self.height
+    }
+
+    fn round(&self) -> Round {
+        self.round
+    }
+
+    fn value(&self) -> &NilOrVal<ValueId> {
+        &self.value
+    }
+
+    fn take_value(self) -> NilOrVal<ValueId> {
+        self.value
+    }
+
+    fn vote_type(&self) -> VoteType {
+        self.vote_type
+    }
+
+    fn validator_address(&self) -> &Address {
+        &self.validator_address
+    }
+
+    // Vote extensions are not supported over FFI
+
+    fn extension(&self) -> Option<&SignedExtension<FfiContext>> {
+        None
+    }
+
+    fn take_extension(&mut self) -> Option<SignedExtension<FfiContext>> {
+        None
+    }
+
+    fn extend(self, _extension: SignedExtension<FfiContext>) -> Self {
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proposal {
+    pub height: Height,
+    pub round: Round,
+    pub value: Value,
+    pub pol_round: Round,
+    pub validator_address: Address,
+}
+
+impl types::Proposal<FfiContext> for Proposal {
+    fn height(&self) -> Height {
+        self.height
+    }
+
+    fn round(&self) -> Round {
+        self.round
+    }
+
+    fn value(&self) -> &Value {
+        &self.value
+    }
+
+    fn take_value(self) -> Value {
+        self.value
+    }
+
+    fn pol_round(&self) -> Round {
+        self.pol_round
+    }
+
+    fn validator_address(&self) -> &Address {
+        &self.validator_address
+    }
+}
+
+/// Proposal parts are streamed by the host application, and never seen by consensus.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProposalPart;
+
+impl types::ProposalPart<FfiContext> for ProposalPart {
+    fn is_first(&self) -> bool {
+        true
+    }
+
+    fn is_last(&self) -> bool {
+        true
+    }
+}
+
+impl types::Context for FfiContext {
+    type Address = Address;
+    type Height = Height;
+    type ProposalPart = ProposalPart;
+    type Proposal = Proposal;
+    type Validator = Validator;
+    type ValidatorSet = ValidatorSet;
+    type Timeouts = LinearTimeouts;
+    type Value = Value;
+    type Vote = Vote;
+    type Extension = ();
+    type SigningScheme = HostSigningScheme;
+
+    /// Round-robin over the validators, in the order given by the host application.
+    fn select_proposer<'a>(
+        &self,
+        validator_set: &'a ValidatorSet,
+        height: Height,
+        round: Round,
+    ) -> &'a Validator {
+        assert!(!validator_set.validators.is_empty());
+        assert!(round.is_defined());
+
+        let index = (height.0.saturating_sub(1) + round.as_i64() as u64)
+            % validator_set.validators.len() as u64;
+
+        &validator_set.validators[index as usize]
+    }
+
+    fn new_proposal(
+        &self,
+        height: Height,
+        round: Round,
+        value: Value,
+        pol_round: Round,
+        address: Address,
+    ) -> Proposal {
+        Proposal {
+            height,
+            round,
+            value,
+            pol_round,
+            validator_address: address,
+        }
+    }
+
+    fn new_prevote(
+        &self,
+        height: Height,
+        round: Round,
+        value_id: NilOrVal<ValueId>,
+        address: Address,
+    ) -> Vote {
+        Vote {
+            vote_type: VoteType::Prevote,
+            height,
+            round,
+            value: value_id,
+            validator_address: address,
+        }
+    }
+
+    fn new_precommit(
+        &self,
+        height: Height,
+        round: Round,
+        value_id: NilOrVal<ValueId>,
+        address: Address,
+    ) -> Vote {
+        Vote {
+            vote_type: VoteType::Precommit,
+            height,
+            round,
+            value: value_id,
+            validator_address: address,
+        }
+    }
+}
@@ -0,0 +1,397 @@
+//! Dispatch of consensus effects to the host application.
+
+use core::ffi::c_void;
+use core::fmt;
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+
+use malachitebft_core_consensus::{
+    ConsensusMsg, Effect, LivenessMsg, Resumable, Resume, SignedConsensusMsg, WalEntry,
+};
+use malachitebft_core_types::{
+    LinearTimeouts, SignedMessage, SignedProposal, SignedVote, Timeout, TimeoutKind,
+    ValidatorProof, ValidatorSetUpdate,
+};
+use malachitebft_signing::{Error as SigningError, VerificationResult, Verifier, VerifierExt};
+
+use crate::context::*;
+use crate::types::*;
+
+/// The callback through which the host application handles effects.
+///
+/// Must return `0` on success. Any other value is logged, and consensus resumes
+/// as if the effect had not been handled, treating signatures as invalid.
+pub type MalEffectCallback = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    effect: *const MalEffect,
+    resume: *mut MalResume,
+) -> i32;
+
+/// The host application, as seen by consensus.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct MalHost {
+    /// Opaque pointer given back to the host on every callback
+    pub user_data: *mut c_void,
+    pub on_effect: Option<MalEffectCallback>,
+}
+
+// SAFETY: The host is only ever called synchronously, on the thread which called into the library.
+unsafe impl Send for MalHost {}
+unsafe impl Sync for MalHost {}
+
+/// The host failed to handle an effect, with the given status.
+pub(crate) struct HostError(i32);
+
+impl fmt::Debug for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "host failed to handle effect with status {}", self.0)
+    }
+}
+
+impl MalHost {
+    pub(crate) fn call(&self, effect: &MalEffect) -> Result<MalResume, HostError> {
+        let Some(on_effect) = self.on_effect else {
+            return Err(HostError(-1));
+        };
+
+        let mut resume = MalResume::default();
+
+        // SAFETY: Both pointers are valid for the duration of the call.
+        let status = unsafe { on_effect(self.user_data, effect, &mut resume) };
+
+        if status == 0 {
+            Ok(resume)
+        } else {
+            Err(HostError(status))
+        }
+    }
+
+    fn notify(&self, effect: MalEffect) -> Result<(), HostError> {
+        self.call(&effect).map(|_| ())
+    }
+
+    fn verify_vote(&self, vote: &SignedVote<FfiContext>, public_key: &PublicKey) -> bool {
+        let mut effect =
+            MalEffect::new(MalEffectKind::VerifyVoteSignature).with_round(vote.height, vote.round);
+        effect.vote = MalVote::new(vote, Some(&vote.signature));
+        effect.public_key = public_key.0;
+
+        self.call(&effect).is_ok_and(|resume| resume.valid)
+    }
+
+    fn verify_proposal(
+        &self,
+        proposal: &SignedProposal<FfiContext>,
+        public_key: &PublicKey,
+    ) -> bool {
+        let mut effect = MalEffect::new(MalEffectKind::VerifyProposalSignature)
+            .with_round(proposal.height, proposal.round);
+        effect.proposal = MalProposal::new(proposal, Some(&proposal.signature));
+        effect.public_key = public_key.0;
+
+        self.call(&effect).is_ok_and(|resume| resume.valid)
+    }
+}
+
+/// Verifies signatures through the host, so that certificates can be checked
+/// against the validator set with the shared [`VerifierExt`] logic.
+struct HostVerifier<'a>(&'a MalHost);
+
+#[async_trait]
+impl Verifier<FfiContext> for HostVerifier<'_> {
+    async fn verify_signed_vote(
+        &self,
+        vote: &Vote,
+        signature: &Signature,
+        public_key: &PublicKey,
+    ) -> Result<VerificationResult, SigningError> {
+        let vote = SignedVote::new(vote.clone(), *signature);
+        Ok(VerificationResult::from_bool(
+            self.0.verify_vote(&vote, public_key),
+        ))
+    }
+
+    async fn verify_signed_proposal(
+        &self,
+        proposal: &Proposal,
+        signature: &Signature,
+        public_key: &PublicKey,
+    ) -> Result<VerificationResult, SigningError> {
+        let proposal = SignedProposal::new(proposal.clone(), *signature);
+        Ok(VerificationResult::from_bool(
+            self.0.verify_proposal(&proposal, public_key),
+        ))
+    }
+
+    async fn verify_signed_vote_extension(
+        &self,
+        _extension: &(),
+        _signature: &Signature,
+        _public_key: &PublicKey,
+    ) -> Result<VerificationResult, SigningError> {
+        // Vote extensions are not supported over FFI
+        Ok(VerificationResult::Invalid)
+    }
+
+    async fn verify_validator_proof(
+        &self,
+        _proof: &ValidatorProof<FfiContext>,
+    ) -> Result<VerificationResult, SigningError> {
+        // Validator proofs are only used by the networking layer
+        Ok(VerificationResult::Invalid)
+    }
+
+    async fn verify_validator_set_update(
+        &self,
+        _update: &ValidatorSetUpdate<FfiContext>,
+        _signature: &Signature,
+        _public_key: &PublicKey,
+    ) -> Result<VerificationResult, SigningError> {
+        // Validator set updates are only gossiped by the engine
+        Ok(VerificationResult::Invalid)
+    }
+}
+
+fn timeout_ms(timeouts: &LinearTimeouts, timeout: Timeout) -> u64 {
+    let duration = match timeout.kind {
+        TimeoutKind::FinalizeHeight(duration) => duration,
+        _ => timeouts.duration_for(timeout),
+    };
+
+    duration.as_millis() as u64
+}
+
+/// Handle an effect emitted by consensus, by forwarding it to the host
+/// or by handling it directly when it does not require any input from the host.
+pub(crate) fn handle_effect(
+    host: &MalHost,
+    ctx: &FfiContext,
+    timeouts: &LinearTimeouts,
+    effect: Effect<FfiContext>,
+) -> Result<Resume<FfiContext>, HostError> {
+    match effect {
+        Effect::CancelAllTimeouts(r) => {
+            host.notify(MalEffect::new(MalEffectKind::CancelAllTimeouts))?;
+            Ok(r.resume_with(()))
+        }
+
+        Effect::CancelTimeout(timeout, r) => {
+            let mut effect = MalEffect::new(MalEffectKind::CancelTimeout);
+            effect.round = timeout.round.as_i64();
+            effect.timeout = MalTimeout::new(&timeout, timeout_ms(timeouts, timeout));
+            host.notify(effect)?;
+            Ok(r.resume_with(()))
+        }
+
+        Effect::ScheduleTimeout(timeout, r) => {
+            let mut effect = MalEffect::new(MalEffectKind::ScheduleTimeout);
+            effect.round = timeout.round.as_i64();
+            effect.timeout = MalTimeout::new(&timeout, timeout_ms(timeouts, timeout));
+            host.notify(effect)?;
+            Ok(r.resume_with(()))
+        }
+
+        Effect::StartRound(height, round, proposer, role, r) => {
+            let mut effect = MalEffect::new(MalEffectKind::StartRound).with_round(height, round);
+            effect.address = proposer.0;
+            effect.role = to_role(role);
+            host.notify(effect)?;
+            Ok(r.resume_with(()))
+        }
+
+        Effect::PublishConsensusMsg(msg, r) => {
+            let kind = match &msg {
+                SignedConsensusMsg::Vote(_) => MalEffectKind::PublishVote,
+                SignedConsensusMsg::Proposal(_) => MalEffectKind::PublishProposal,
+            };
+            host.notify(MalEffect::new(kind).with_signed_msg(&msg))?;
+            Ok(r.resume_with(()))
+        }
+
+        Effect::PublishLivenessMsg(msg, r) => {
+            match msg {
+                LivenessMsg::Vote(vote) => {
+                    let mut effect = MalEffect::new(MalEffectKind::RepublishVote)
+                        .with_round(vote.height, vote.round);
+                    effect.vote = MalVote::new(&vote, Some(&vote.signature));
+                    host.notify(effect)?;
+                }
+                LivenessMsg::PolkaCertificate(certificate) => {
+                    let votes = polka_votes(&certificate);
+                    let mut effect = MalEffect::new(MalEffectKind::PublishCertificate)
+                        .with_round(certificate.height, certificate.round)
+                        .with_votes(&votes);
+                    effect.value_id = certificate.value_id.0;
+                    host.notify(effect)?;
+                }
+                LivenessMsg::SkipRoundCertificate(certificate) => {
+                    let votes = round_votes(&certificate);
+                    host.notify(
+                        MalEffect::new(MalEffectKind::PublishCertificate)
+                            .with_round(certificate.height, certificate.round)
+                            .with_votes(&votes),
+                    )?;
+                }
+                // Only published by the engine, never by the core consensus
+                LivenessMsg::ValidatorSetUpdate(_) => {}
+            }
+            Ok(r.resume_with(()))
+        }
+
+        Effect::RepublishVote(vote, r) => {
+            let mut effect =
+                MalEffect::new(MalEffectKind::RepublishVote).with_round(vote.height, vote.round);
+            effect.vote = MalVote::new(&vote, Some(&vote.signature));
+            host.notify(effect)?;
+            Ok(r.resume_with(()))
+        }
+
+        Effect::RepublishRoundCertificate(certificate, r) => {
+            let votes = round_votes(&certificate);
+            host.notify(
+                MalEffect::new(MalEffectKind::PublishCertificate)
+                    .with_round(certificate.height, certificate.round)
+                    .with_votes(&votes),
+            )?;
+            Ok(r.resume_with(()))
+        }
+
+        Effect::GetValue(height, round, timeout, r) => {
+            let mut effect = MalEffect::new(MalEffectKind::GetValue).with_round(height, round);
+            effect.timeout = MalTimeout::new(&timeout, timeout_ms(timeouts, timeout));
+            host.notify(effect)?;
+            Ok(r.resume_with(()))
+        }
+
+        Effect::RestreamProposal(height, round, valid_round, proposer, value_id, r) => {
+            let mut effect =
+                MalEffect::new(MalEffectKind::RestreamProposal).with_round(height, round);
+            effect.valid_round = valid_round.as_i64();
+            effect.address = proposer.0;
+            effect.value_id = value_id.0;
+            host.notify(effect)?;
+            Ok(r.resume_with(()))
+        }
+
+        Effect::ValidateValue(height, round, valid_round, proposer, value, r) => {
+            let mut effect = MalEffect::new(MalEffectKind::ValidateValue).with_round(height, round);
+            effect.valid_round = valid_round.as_i64();
+            effect.address = proposer.0;
+            effect.value_id = value.0 .0;
+            host.notify(effect)?;
+            Ok(r.resume_with(()))
+        }
+
+        // Values are never synced through the FFI bindings
+        Effect::ValidSyncValue(.., r) | Effect::InvalidSyncValue(.., r) => Ok(r.resume_with(())),
+
+        // There is no sync subsystem to notify through the FFI bindings
+        Effect::FutureHeightObserved(.., r) => Ok(r.resume_with(())),
+
+        Effect::Decide(certificate, _, r) => {
+            let votes = commit_votes(&certificate);
+            let mut effect = MalEffect::new(MalEffectKind::Decide)
+                .with_round(certificate.height, certificate.round)
+                .with_votes(&votes);
+            effect.value_id = certificate.value_id.0;
+            host.notify(effect)?;
+            Ok(r.resume_with(()))
+        }
+
+        Effect::Finalize(certificate, _, _, r) => {
+            let votes = commit_votes(&certificate);
+            let mut effect = MalEffect::new(MalEffectKind::Finalize)
+                .with_round(certificate.height, certificate.round)
+                .with_votes(&votes);
+            effect.value_id = certificate.value_id.0;
+            host.notify(effect)?;
+            Ok(r.resume_with(()))
+        }
+
+        Effect::SignVote(vote, r) => {
+            let mut effect =
+                MalEffect::new(MalEffectKind::SignVote).with_round(vote.height, vote.round);
+            effect.vote = MalVote::new(&vote, None);
+            let resume = host.call(&effect)?;
+            Ok(r.resume_with(SignedMessage::new(vote, Signature(resume.signature))))
+        }
+
+        Effect::SignProposal(proposal, r) => {
+            let mut effect = MalEffect::new(MalEffectKind::SignProposal)
+                .with_round(proposal.height, proposal.round);
+            effect.proposal = MalProposal::new(&proposal, None);
+            let resume = host.call(&effect)?;
+            Ok(r.resume_with(SignedMessage::new(proposal, Signature(resume.signature))))
+        }
+
+        Effect::VerifySignature(msg, public_key, r) => {
+            let valid = match msg.message {
+                ConsensusMsg::Vote(vote) => {
+                    host.verify_vote(&SignedVote::new(vote, msg.signature), &public_key)
+                }
+                ConsensusMsg::Proposal(proposal) => {
+                    host.verify_proposal(&SignedProposal::new(proposal, msg.signature), &public_key)
+                }
+            };
+            Ok(r.resume_with(valid))
+        }
+
+        Effect::VerifyCommitCertificate(certificate, validator_set, thresholds, r) => {
+            let result = block_on(HostVerifier(host).verify_commit_certificate(
+                ctx,
+                &certificate,
+                &validator_set,
+                thresholds,
+            ));
+            Ok(r.resume_with(result))
+        }
+
+        Effect::VerifyCommitCertificates(certificates, validator_set, thresholds, r) => {
+            let results = block_on(HostVerifier(host).verify_commit_certificates(
+                ctx,
+                &certificates,
+                &validator_set,
+                thresholds,
+            ));
+            Ok(r.resume_with(results))
+        }
+
+        Effect::VerifyPolkaCertificate(certificate, validator_set, thresholds, r) => {
+            let result = block_on(HostVerifier(host).verify_polka_certificate(
+                ctx,
+                &certificate,
+                &validator_set,
+                thresholds,
+            ));
+            Ok(r.resume_with(result))
+        }
+
+        Effect::VerifyRoundCertificate(certificate, validator_set, thresholds, r) => {
+            let result = block_on(HostVerifier(host).verify_round_certificate(
+                ctx,
+                &certificate,
+                &validator_set,
+                thresholds,
+            ));
+            Ok(r.resume_with(result))
+        }
+
+        Effect::WalAppend(height, entry, r) => {
+            let duration_ms = match &entry {
+                WalEntry::Timeout(timeout) => timeout_ms(timeouts, *timeout),
+                _ => 0,
+            };
+            let mut effect =
+                MalEffect::new(MalEffectKind::WalAppend).with_wal_entry(&entry, duration_ms);
+            effect.height = height.0;
+            host.notify(effect)?;
+            Ok(r.resume_with(()))
+        }
+
+        // Vote extensions are not supported over FFI
+        Effect::ExtendVote(_, _, _, r) => Ok(r.resume_with(None)),
+        Effect::VerifyVoteExtension(.., r) => Ok(r.resume_with(Ok(()))),
+    }
+}
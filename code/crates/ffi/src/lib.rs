@@ -0,0 +1,407 @@
+//! C ABI for embedding the Malachite core consensus library in non-Rust applications,
+//! eg. written in Go or C++, without the actor-based engine.
+//!
+//! The host application creates a consensus instance with [`malachite_new`], feeds it
+//! inputs (votes, proposals, values, elapsed timeouts) through the `malachite_*` functions,
+//! and handles the resulting effects in its [`MalHost::on_effect`] callback, which is called
+//! synchronously, on the calling thread, before the input function returns.
+//!
+//! The host is responsible for networking, signing, timers and for disseminating values,
+//! which consensus only ever sees through their 32-byte id. See `include/malachite.h`
+//! for the corresponding C declarations.
+//!
+//! All functions are safe to call with null pointers, which are rejected with
+//! [`MalStatus::NullPointer`], and never unwind into the host: panics are caught
+//! and reported as [`MalStatus::Panic`].
+
+use core::panic::AssertUnwindSafe;
+use core::time::Duration;
+use std::panic::catch_unwind;
+
+use tracing::error;
+
+use malachitebft_core_consensus::{
+    run_sync, Input, LocallyProposedValue, Params, ProposedValue, State,
+};
+use malachitebft_core_driver::Step;
+use malachitebft_core_types::{LinearTimeouts, Validity, ValueOrigin};
+use malachitebft_metrics::Metrics;
+
+mod context;
+pub use context::*;
+
+mod host;
+pub use host::{MalEffectCallback, MalHost};
+
+mod types;
+pub use types::*;
+
+/// Capacity of the queue of inputs for future heights and rounds.
+const QUEUE_CAPACITY: usize = 1000;
+
+/// Status codes returned by the `malachite_*` functions.
+#[repr(i32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MalStatus {
+    /// The call succeeded
+    Ok = 0,
+    /// A required pointer was null
+    NullPointer = -1,
+    /// An argument was invalid, eg. an unknown vote type or an empty validator set
+    InvalidArgument = -2,
+    /// Consensus failed to process the input, see the logs for details
+    Consensus = -3,
+    /// The library panicked
+    Panic = -4,
+}
+
+/// A consensus instance, opaque to the host application.
+pub struct MalConsensus {
+    state: State<FfiContext>,
+    metrics: Metrics,
+    timeouts: LinearTimeouts,
+    host: MalHost,
+}
+
+impl MalConsensus {
+    fn process(&mut self, input: Input<FfiContext>) -> MalStatus {
+        let Self {
+            state,
+            metrics,
+            timeouts,
+            host,
+        } = self;
+
+        let ctx = state.ctx;
+
+        let result = run_sync(state, metrics, input, |effect| {
+            host::handle_effect(host, &ctx, timeouts, effect)
+        });
+
+        match result {
+            Ok(()) => MalStatus::Ok,
+            Err(e) => {
+                error!("Error when processing input: {e}");
+                MalStatus::Consensus
+            }
+        }
+    }
+}
+
+fn guard(f: impl FnOnce() -> Result<MalStatus, MalStatus>) -> MalStatus {
+    catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or(Err(MalStatus::Panic))
+        .unwrap_or_else(|status| status)
+}
+
+unsafe fn deref<'a, T>(ptr: *const T) -> Result<&'a T, MalStatus> {
+    ptr.as_ref().ok_or(MalStatus::NullPointer)
+}
+
+unsafe fn deref_mut<'a, T>(ptr: *mut T) -> Result<&'a mut T, MalStatus> {
+    ptr.as_mut().ok_or(MalStatus::NullPointer)
+}
+
+unsafe fn read_bytes<const N: usize>(ptr: *const u8) -> Result<[u8; N], MalStatus> {
+    if ptr.is_null() {
+        return Err(MalStatus::NullPointer);
+    }
+
+    Ok(*ptr.cast::<[u8; N]>())
+}
+
+unsafe fn validator_set(
+    validators: *const MalValidator,
+    validators_len: usize,
+) -> Result<ValidatorSet, MalStatus> {
+    if validators.is_null() {
+        return Err(MalStatus::NullPointer);
+    }
+
+    if validators_len == 0 {
+        return Err(MalStatus::InvalidArgument);
+    }
+
+    let validators = core::slice::from_raw_parts(validators, validators_len);
+
+    Ok(ValidatorSet {
+        validators: validators.iter().map(Validator::from).collect(),
+    })
+}
+
+/// Create a consensus instance for the given initial height and validator set.
+///
+/// Returns null if any of the arguments is invalid.
+/// The instance must be released with [`malachite_free`].
+///
+/// # Safety
+///
+/// `config` must point to a valid [`MalConfig`], `validators` to an array of `validators_len`
+/// [`MalValidator`], and `host.user_data` must remain valid until the instance is released.
+#[no_mangle]
+pub unsafe extern "C" fn malachite_new(
+    config: *const MalConfig,
+    height: u64,
+    validators: *const MalValidator,
+    validators_len: usize,
+    host: MalHost,
+) -> *mut MalConsensus {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let config = deref(config)?;
+        let validator_set = validator_set(validators, validators_len)?;
+
+        if host.on_effect.is_none() {
+            return Err(MalStatus::NullPointer);
+        }
+
+        let params = Params {
+            address: Address(config.address),
+            threshold_params: Default::default(),
+            value_payload: config.value_payload()?,
+            enabled: true,
+            // Vote extensions are not supported over FFI
+            require_vote_extensions: false,
+            full_proposal_limits: Default::default(),
+        };
+
+        let timeouts = LinearTimeouts {
+            propose: Duration::from_millis(config.timeout_propose_ms),
+            propose_delta: Duration::from_millis(config.timeout_propose_delta_ms),
+            prevote: Duration::from_millis(config.timeout_prevote_ms),
+            prevote_delta: Duration::from_millis(config.timeout_prevote_delta_ms),
+            precommit: Duration::from_millis(config.timeout_precommit_ms),
+            precommit_delta: Duration::from_millis(config.timeout_precommit_delta_ms),
+            rebroadcast: Duration::from_millis(config.timeout_rebroadcast_ms),
+        };
+
+        let state = State::new(
+            FfiContext,
+            Height(height),
+            validator_set,
+            params,
+            QUEUE_CAPACITY,
+            QUEUE_CAPACITY,
+        );
+
+        Ok(Box::new(MalConsensus {
+            state,
+            metrics: Metrics::new(),
+            timeouts,
+            host,
+        }))
+    }));
+
+    match result {
+        Ok(Ok(consensus)) => Box::into_raw(consensus),
+        _ => core::ptr::null_mut(),
+    }
+}
+
+/// Release a consensus instance.
+///
+/// # Safety
+///
+/// `consensus` must have been returned by [`malachite_new`], and not been released already.
+#[no_mangle]
+pub unsafe extern "C" fn malachite_free(consensus: *mut MalConsensus) {
+    if !consensus.is_null() {
+        drop(Box::from_raw(consensus));
+    }
+}
+
+/// Start consensus at the given height, with the given validator set.
+///
+/// # Safety
+///
+/// `consensus` must be a valid instance, and `validators` must point to an array
+/// of `validators_len` [`MalValidator`].
+#[no_mangle]
+pub unsafe extern "C" fn malachite_start_height(
+    consensus: *mut MalConsensus,
+    height: u64,
+    validators: *const MalValidator,
+    validators_len: usize,
+) -> MalStatus {
+    guard(|| {
+        let consensus = deref_mut(consensus)?;
+        let validator_set = validator_set(validators, validators_len)?;
+
+        Ok(consensus.process(Input::StartHeight(
+            Height(height),
+            validator_set,
+            false,
+            None,
+        )))
+    })
+}
+
+/// Propose the value with the given id, in response to a [`MalEffectKind::GetValue`] effect.
+///
+/// # Safety
+///
+/// `consensus` must be a valid instance, and `value_id` must point to 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn malachite_propose(
+    consensus: *mut MalConsensus,
+    height: u64,
+    round: i64,
+    value_id: *const u8,
+) -> MalStatus {
+    guard(|| {
+        let consensus = deref_mut(consensus)?;
+        let value = Value(ValueId(read_bytes(value_id)?));
+
+        Ok(consensus.process(Input::Propose(LocallyProposedValue::new(
+            Height(height),
+            to_round(round)?,
+            value,
+        ))))
+    })
+}
+
+/// Process a vote received from a peer.
+///
+/// # Safety
+///
+/// `consensus` must be a valid instance, and `vote` must point to a valid [`MalVote`].
+#[no_mangle]
+pub unsafe extern "C" fn malachite_receive_vote(
+    consensus: *mut MalConsensus,
+    vote: *const MalVote,
+) -> MalStatus {
+    guard(|| {
+        let consensus = deref_mut(consensus)?;
+        let vote = deref(vote)?.to_signed_vote()?;
+
+        Ok(consensus.process(Input::Vote(vote)))
+    })
+}
+
+/// Process a proposal received from a peer.
+///
+/// # Safety
+///
+/// `consensus` must be a valid instance, and `proposal` must point to a valid [`MalProposal`].
+#[no_mangle]
+pub unsafe extern "C" fn malachite_receive_proposal(
+    consensus: *mut MalConsensus,
+    proposal: *const MalProposal,
+) -> MalStatus {
+    guard(|| {
+        let consensus = deref_mut(consensus)?;
+        let proposal = deref(proposal)?.to_signed_proposal()?;
+
+        Ok(consensus.process(Input::Proposal(proposal)))
+    })
+}
+
+/// Process a value proposed by a peer, once it has been fully received and validated by the host,
+/// or in response to a [`MalEffectKind::ValidateValue`] effect.
+///
+/// # Safety
+///
+/// `consensus` must be a valid instance, `proposer` must point to 20 bytes,
+/// and `value_id` to 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn malachite_proposed_value(
+    consensus: *mut MalConsensus,
+    height: u64,
+    round: i64,
+    valid_round: i64,
+    proposer: *const u8,
+    value_id: *const u8,
+    valid: bool,
+) -> MalStatus {
+    guard(|| {
+        let consensus = deref_mut(consensus)?;
+
+        let value = ProposedValue {
+            height: Height(height),
+            round: to_round(round)?,
+            valid_round: to_round(valid_round)?,
+            proposer: Address(read_bytes(proposer)?),
+            value: Value(ValueId(read_bytes(value_id)?)),
+            validity: Validity::from_bool(valid),
+        };
+
+        Ok(consensus.process(Input::ProposedValue(value, ValueOrigin::Consensus)))
+    })
+}
+
+/// Notify consensus that a timeout scheduled by a [`MalEffectKind::ScheduleTimeout`] effect has elapsed.
+///
+/// # Safety
+///
+/// `consensus` must be a valid instance, and `timeout` must point to a valid [`MalTimeout`].
+#[no_mangle]
+pub unsafe extern "C" fn malachite_timeout_elapsed(
+    consensus: *mut MalConsensus,
+    timeout: *const MalTimeout,
+) -> MalStatus {
+    guard(|| {
+        let consensus = deref_mut(consensus)?;
+        let timeout = deref(timeout)?.to_timeout()?;
+
+        Ok(consensus.process(Input::TimeoutElapsed(timeout)))
+    })
+}
+
+/// Write a snapshot of the consensus state to `out`.
+///
+/// # Safety
+///
+/// `consensus` must be a valid instance, and `out` must point to writable memory for a [`MalState`].
+#[no_mangle]
+pub unsafe extern "C" fn malachite_state(
+    consensus: *const MalConsensus,
+    out: *mut MalState,
+) -> MalStatus {
+    guard(|| {
+        let consensus = deref(consensus)?;
+        let out = deref_mut(out)?;
+
+        let state = &consensus.state;
+        let round_state = state.driver.round_state();
+        let decided = state.decided_value();
+
+        let (locked_round, locked_value_id) = round_state
+            .locked
+            .as_ref()
+            .map_or((-1, [0; VALUE_ID_LEN]), |locked| {
+                (locked.round.as_i64(), locked.value.0 .0)
+            });
+
+        let (valid_round, valid_value_id) = round_state
+            .valid
+            .as_ref()
+            .map_or((-1, [0; VALUE_ID_LEN]), |valid| {
+                (valid.round.as_i64(), valid.value.0 .0)
+            });
+        let (decided_round, decided_value_id) = decided
+            .as_ref()
+            .map_or((-1, [0; VALUE_ID_LEN]), |(round, value)| {
+                (round.as_i64(), value.0 .0)
+            });
+
+        *out = MalState {
+            height: state.height().0,
+            round: state.round().as_i64(),
+            step: match state.driver.step() {
+                Step::Unstarted => MAL_STEP_UNSTARTED,
+                Step::Propose => MAL_STEP_PROPOSE,
+                Step::Prevote => MAL_STEP_PREVOTE,
+                Step::Precommit => MAL_STEP_PRECOMMIT,
+                Step::Commit => MAL_STEP_COMMIT,
+            },
+            locked_round,
+            locked_value_id,
+            valid_round,
+            valid_value_id,
+            decided: decided.is_some(),
+            decided_round,
+            decided_value_id,
+        };
+
+        Ok(MalStatus::Ok)
+    })
+}
@@ -0,0 +1,517 @@
+//! The C representation of the types exchanged with the host application.
+//!
+//! Rounds are represented as signed integers, where `-1` denotes the nil round,
+//! and enumerations coming from the host are plain integers, validated on conversion.
+
+use core::ptr;
+
+use malachitebft_core_consensus::{Role, SignedConsensusMsg, WalEntry};
+use malachitebft_core_types::{
+    CommitCertificate, NilOrVal, PolkaCertificate, Round, RoundCertificate, SignedProposal,
+    SignedVote, Timeout, TimeoutKind, ValuePayload, VoteType,
+};
+
+use crate::context::*;
+use crate::MalStatus as Status;
+
+/// A validator, as given by the host application.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct MalValidator {
+    pub address: [u8; ADDRESS_LEN],
+    pub public_key: [u8; PUBLIC_KEY_LEN],
+    pub voting_power: u64,
+}
+
+impl From<&MalValidator> for Validator {
+    fn from(validator: &MalValidator) -> Self {
+        Self {
+            address: Address(validator.address),
+            public_key: PublicKey(validator.public_key),
+            voting_power: validator.voting_power,
+        }
+    }
+}
+
+/// Vote types
+pub const MAL_VOTE_PREVOTE: u8 = 0;
+pub const MAL_VOTE_PRECOMMIT: u8 = 1;
+
+/// A vote, together with its signature.
+///
+/// The signature is ignored when the vote is given to the host to be signed.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct MalVote {
+    pub vote_type: u8,
+    pub height: u64,
+    pub round: i64,
+    /// Whether this is a vote for nil, in which case `value_id` is ignored
+    pub is_nil: bool,
+    pub value_id: [u8; VALUE_ID_LEN],
+    pub address: [u8; ADDRESS_LEN],
+    pub signature: [u8; SIGNATURE_LEN],
+}
+
+impl MalVote {
+    const ZERO: Self = Self {
+        vote_type: 0,
+        height: 0,
+        round: -1,
+        is_nil: true,
+        value_id: [0; VALUE_ID_LEN],
+        address: [0; ADDRESS_LEN],
+        signature: [0; SIGNATURE_LEN],
+    };
+
+    pub(crate) fn new(vote: &Vote, signature: Option<&Signature>) -> Self {
+        let (is_nil, value_id) = match &vote.value {
+            NilOrVal::Nil => (true, [0; VALUE_ID_LEN]),
+            NilOrVal::Val(value_id) => (false, value_id.0),
+        };
+
+        Self {
+            vote_type: match vote.vote_type {
+                VoteType::Prevote => MAL_VOTE_PREVOTE,
+                VoteType::Precommit => MAL_VOTE_PRECOMMIT,
+            },
+            height: vote.height.0,
+            round: vote.round.as_i64(),
+            is_nil,
+            value_id,
+            address: vote.validator_address.0,
+            signature: signature.map_or([0; SIGNATURE_LEN], |s| s.0),
+        }
+    }
+
+    pub(crate) fn to_signed_vote(self) -> Result<SignedVote<FfiContext>, Status> {
+        let vote_type = match self.vote_type {
+            MAL_VOTE_PREVOTE => VoteType::Prevote,
+            MAL_VOTE_PRECOMMIT => VoteType::Precommit,
+            _ => return Err(Status::InvalidArgument),
+        };
+
+        let vote = Vote {
+            vote_type,
+            height: Height(self.height),
+            round: to_round(self.round)?,
+            value: if self.is_nil {
+                NilOrVal::Nil
+            } else {
+                NilOrVal::Val(ValueId(self.value_id))
+            },
+            validator_address: Address(self.address),
+        };
+
+        Ok(SignedVote::new(vote, Signature(self.signature)))
+    }
+}
+
+/// A proposal, together with its signature.
+///
+/// The signature is ignored when the proposal is given to the host to be signed.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct MalProposal {
+    pub height: u64,
+    pub round: i64,
+    pub pol_round: i64,
+    pub value_id: [u8; VALUE_ID_LEN],
+    pub address: [u8; ADDRESS_LEN],
+    pub signature: [u8; SIGNATURE_LEN],
+}
+
+impl MalProposal {
+    const ZERO: Self = Self {
+        height: 0,
+        round: -1,
+        pol_round: -1,
+        value_id: [0; VALUE_ID_LEN],
+        address: [0; ADDRESS_LEN],
+        signature: [0; SIGNATURE_LEN],
+    };
+
+    pub(crate) fn new(proposal: &Proposal, signature: Option<&Signature>) -> Self {
+        Self {
+            height: proposal.height.0,
+            round: proposal.round.as_i64(),
+            pol_round: proposal.pol_round.as_i64(),
+            value_id: proposal.value.0 .0,
+            address: proposal.validator_address.0,
+            signature: signature.map_or([0; SIGNATURE_LEN], |s| s.0),
+        }
+    }
+
+    pub(crate) fn to_signed_proposal(self) -> Result<SignedProposal<FfiContext>, Status> {
+        let proposal = Proposal {
+            height: Height(self.height),
+            round: to_round(self.round)?,
+            value: Value(ValueId(self.value_id)),
+            pol_round: to_round(self.pol_round)?,
+            validator_address: Address(self.address),
+        };
+
+        Ok(SignedProposal::new(proposal, Signature(self.signature)))
+    }
+}
+
+/// Timeout kinds
+pub const MAL_TIMEOUT_PROPOSE: u8 = 0;
+pub const MAL_TIMEOUT_PREVOTE: u8 = 1;
+pub const MAL_TIMEOUT_PRECOMMIT: u8 = 2;
+pub const MAL_TIMEOUT_REBROADCAST: u8 = 3;
+pub const MAL_TIMEOUT_FINALIZE_HEIGHT: u8 = 4;
+
+/// A timeout, as scheduled by consensus.
+///
+/// The host must give the timeout back as is once it has elapsed.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct MalTimeout {
+    pub kind: u8,
+    pub round: i64,
+    /// How long to wait for, in milliseconds
+    pub duration_ms: u64,
+}
+
+impl MalTimeout {
+    const ZERO: Self = Self {
+        kind: 0,
+        round: -1,
+        duration_ms: 0,
+    };
+
+    pub(crate) fn new(timeout: &Timeout, duration_ms: u64) -> Self {
+        let kind = match timeout.kind {
+            TimeoutKind::Propose => MAL_TIMEOUT_PROPOSE,
+            TimeoutKind::Prevote => MAL_TIMEOUT_PREVOTE,
+            TimeoutKind::Precommit => MAL_TIMEOUT_PRECOMMIT,
+            TimeoutKind::Rebroadcast => MAL_TIMEOUT_REBROADCAST,
+            TimeoutKind::FinalizeHeight(_) => MAL_TIMEOUT_FINALIZE_HEIGHT,
+        };
+
+        Self {
+            kind,
+            round: timeout.round.as_i64(),
+            duration_ms,
+        }
+    }
+
+    pub(crate) fn to_timeout(self) -> Result<Timeout, Status> {
+        let kind = match self.kind {
+            MAL_TIMEOUT_PROPOSE => TimeoutKind::Propose,
+            MAL_TIMEOUT_PREVOTE => TimeoutKind::Prevote,
+            MAL_TIMEOUT_PRECOMMIT => TimeoutKind::Precommit,
+            MAL_TIMEOUT_REBROADCAST => TimeoutKind::Rebroadcast,
+            MAL_TIMEOUT_FINALIZE_HEIGHT => {
+                TimeoutKind::FinalizeHeight(core::time::Duration::from_millis(self.duration_ms))
+            }
+            _ => return Err(Status::InvalidArgument),
+        };
+
+        Ok(Timeout::new(to_round(self.round)?, kind))
+    }
+}
+
+/// Value payload modes
+pub const MAL_VALUE_PAYLOAD_PARTS_ONLY: u8 = 0;
+pub const MAL_VALUE_PAYLOAD_PROPOSAL_ONLY: u8 = 1;
+pub const MAL_VALUE_PAYLOAD_PROPOSAL_AND_PARTS: u8 = 2;
+
+/// The configuration of a consensus instance.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct MalConfig {
+    /// The address of this node
+    pub address: [u8; ADDRESS_LEN],
+    /// How proposed values are disseminated, see the `MAL_VALUE_PAYLOAD_*` constants
+    pub value_payload: u8,
+    pub timeout_propose_ms: u64,
+    pub timeout_propose_delta_ms: u64,
+    pub timeout_prevote_ms: u64,
+    pub timeout_prevote_delta_ms: u64,
+    pub timeout_precommit_ms: u64,
+    pub timeout_precommit_delta_ms: u64,
+    pub timeout_rebroadcast_ms: u64,
+}
+
+impl MalConfig {
+    pub(crate) fn value_payload(&self) -> Result<ValuePayload, Status> {
+        match self.value_payload {
+            MAL_VALUE_PAYLOAD_PARTS_ONLY => Ok(ValuePayload::PartsOnly),
+            MAL_VALUE_PAYLOAD_PROPOSAL_ONLY => Ok(ValuePayload::ProposalOnly),
+            MAL_VALUE_PAYLOAD_PROPOSAL_AND_PARTS => Ok(ValuePayload::ProposalAndParts),
+            _ => Err(Status::InvalidArgument),
+        }
+    }
+}
+
+/// The kinds of effects emitted by consensus.
+///
+/// Each kind documents the fields of [`MalEffect`] it sets,
+/// and the fields of [`MalResume`] the host must set, if any.
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MalEffectKind {
+    /// Cancel all scheduled timeouts.
+    CancelAllTimeouts = 0,
+    /// Cancel the given `timeout`.
+    CancelTimeout = 1,
+    /// Schedule the given `timeout`, and call `malachite_timeout_elapsed` when it elapses.
+    ScheduleTimeout = 2,
+    /// A round is starting at `height` and `round`, with `address` as the proposer,
+    /// and this node having the given `role`.
+    StartRound = 3,
+    /// Publish the signed `vote` to peers.
+    PublishVote = 4,
+    /// Publish the signed `proposal` to peers.
+    PublishProposal = 5,
+    /// Re-publish the signed `vote` to peers.
+    RepublishVote = 6,
+    /// Publish the certificate made of the `votes` for `height` and `round` to peers.
+    PublishCertificate = 7,
+    /// Build a value to propose at `height` and `round` within `timeout.duration_ms`,
+    /// and give it to `malachite_propose`.
+    GetValue = 8,
+    /// Re-stream the value `value_id` proposed by `address` at `height`, `round` and `valid_round`.
+    RestreamProposal = 9,
+    /// Validate the value `value_id` proposed by `address` at `height`, `round` and `valid_round`,
+    /// and give the result to `malachite_proposed_value`.
+    ValidateValue = 10,
+    /// Consensus has decided on `value_id` at `height` and `round`,
+    /// with the precommits `votes` as commit certificate.
+    Decide = 11,
+    /// The height has been finalized with value `value_id` at `height` and `round`,
+    /// with all the precommits `votes` received until then. The host must then start the next height.
+    Finalize = 12,
+    /// Sign the `vote` and set `resume.signature`.
+    SignVote = 13,
+    /// Sign the `proposal` and set `resume.signature`.
+    SignProposal = 14,
+    /// Verify the signature of the `vote` with `public_key` and set `resume.valid`.
+    VerifyVoteSignature = 15,
+    /// Verify the signature of the `proposal` with `public_key` and set `resume.valid`.
+    VerifyProposalSignature = 16,
+    /// Append an entry to the write-ahead log of `height`, see `wal_entry`.
+    WalAppend = 17,
+}
+
+/// Roles of this node in a round
+pub const MAL_ROLE_PROPOSER: u8 = 0;
+pub const MAL_ROLE_VALIDATOR: u8 = 1;
+pub const MAL_ROLE_NONE: u8 = 2;
+
+/// Kinds of write-ahead log entries
+pub const MAL_WAL_VOTE: u8 = 0;
+pub const MAL_WAL_PROPOSAL: u8 = 1;
+pub const MAL_WAL_TIMEOUT: u8 = 2;
+pub const MAL_WAL_PROPOSED_VALUE: u8 = 3;
+
+/// An effect emitted by consensus, to be handled by the host.
+///
+/// Only the fields relevant to the `kind` of effect are set, the others are zeroed.
+/// The effect, including the `votes` array, is only valid for the duration of the callback.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MalEffect {
+    pub kind: MalEffectKind,
+    pub height: u64,
+    pub round: i64,
+    pub valid_round: i64,
+    /// The role of this node in the round, see the `MAL_ROLE_*` constants
+    pub role: u8,
+    pub address: [u8; ADDRESS_LEN],
+    pub value_id: [u8; VALUE_ID_LEN],
+    /// The validity of a proposed value written to the WAL
+    pub valid: bool,
+    pub timeout: MalTimeout,
+    pub public_key: [u8; PUBLIC_KEY_LEN],
+    pub vote: MalVote,
+    pub proposal: MalProposal,
+    pub votes: *const MalVote,
+    pub votes_len: usize,
+    /// The kind of write-ahead log entry, see the `MAL_WAL_*` constants
+    pub wal_entry: u8,
+}
+
+impl MalEffect {
+    pub(crate) fn new(kind: MalEffectKind) -> Self {
+        Self {
+            kind,
+            height: 0,
+            round: -1,
+            valid_round: -1,
+            role: MAL_ROLE_NONE,
+            address: [0; ADDRESS_LEN],
+            value_id: [0; VALUE_ID_LEN],
+            valid: false,
+            timeout: MalTimeout::ZERO,
+            public_key: [0; PUBLIC_KEY_LEN],
+            vote: MalVote::ZERO,
+            proposal: MalProposal::ZERO,
+            votes: ptr::null(),
+            votes_len: 0,
+            wal_entry: 0,
+        }
+    }
+
+    pub(crate) fn with_round(mut self, height: Height, round: Round) -> Self {
+        self.height = height.0;
+        self.round = round.as_i64();
+        self
+    }
+
+    pub(crate) fn with_votes(mut self, votes: &[MalVote]) -> Self {
+        self.votes = votes.as_ptr();
+        self.votes_len = votes.len();
+        self
+    }
+
+    pub(crate) fn with_signed_msg(mut self, msg: &SignedConsensusMsg<FfiContext>) -> Self {
+        match msg {
+            SignedConsensusMsg::Vote(vote) => {
+                self.height = vote.height.0;
+                self.round = vote.round.as_i64();
+                self.vote = MalVote::new(vote, Some(&vote.signature));
+                self.wal_entry = MAL_WAL_VOTE;
+            }
+            SignedConsensusMsg::Proposal(proposal) => {
+                self.height = proposal.height.0;
+                self.round = proposal.round.as_i64();
+                self.proposal = MalProposal::new(proposal, Some(&proposal.signature));
+                self.wal_entry = MAL_WAL_PROPOSAL;
+            }
+        }
+        self
+    }
+
+    pub(crate) fn with_wal_entry(mut self, entry: &WalEntry<FfiContext>, duration_ms: u64) -> Self {
+        match entry {
+            WalEntry::ConsensusMsg(msg) => return self.with_signed_msg(msg),
+            WalEntry::Timeout(timeout) => {
+                self.round = timeout.round.as_i64();
+                self.timeout = MalTimeout::new(timeout, duration_ms);
+                self.wal_entry = MAL_WAL_TIMEOUT;
+            }
+            WalEntry::ProposedValue(value) => {
+                self.round = value.round.as_i64();
+                self.valid_round = value.valid_round.as_i64();
+                self.address = value.proposer.0;
+                self.value_id = value.value.0 .0;
+                self.valid = value.validity.is_valid();
+                self.wal_entry = MAL_WAL_PROPOSED_VALUE;
+            }
+        }
+        self
+    }
+}
+
+/// The values to resume consensus with, set by the host for some kinds of effects.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct MalResume {
+    pub signature: [u8; SIGNATURE_LEN],
+    pub valid: bool,
+}
+
+impl Default for MalResume {
+    fn default() -> Self {
+        Self {
+            signature: [0; SIGNATURE_LEN],
+            valid: false,
+        }
+    }
+}
+
+/// Consensus steps
+pub const MAL_STEP_UNSTARTED: u8 = 0;
+pub const MAL_STEP_PROPOSE: u8 = 1;
+pub const MAL_STEP_PREVOTE: u8 = 2;
+pub const MAL_STEP_PRECOMMIT: u8 = 3;
+pub const MAL_STEP_COMMIT: u8 = 4;
+
+/// A snapshot of the consensus state.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct MalState {
+    pub height: u64,
+    pub round: i64,
+    /// See the `MAL_STEP_*` constants
+    pub step: u8,
+    pub locked_round: i64,
+    pub locked_value_id: [u8; VALUE_ID_LEN],
+    pub valid_round: i64,
+    pub valid_value_id: [u8; VALUE_ID_LEN],
+    pub decided: bool,
+    pub decided_round: i64,
+    pub decided_value_id: [u8; VALUE_ID_LEN],
+}
+
+pub(crate) fn to_round(round: i64) -> Result<Round, Status> {
+    if round < 0 {
+        return Ok(Round::Nil);
+    }
+
+    u32::try_from(round)
+        .map(Round::new)
+        .map_err(|_| Status::InvalidArgument)
+}
+
+pub(crate) fn to_role(role: Role) -> u8 {
+    match role {
+        Role::Proposer => MAL_ROLE_PROPOSER,
+        Role::Validator => MAL_ROLE_VALIDATOR,
+        Role::None => MAL_ROLE_NONE,
+    }
+}
+
+pub(crate) fn commit_votes(certificate: &CommitCertificate<FfiContext>) -> Vec<MalVote> {
+    certificate
+        .commit_signatures
+        .iter()
+        .map(|commit| {
+            let vote = Vote {
+                vote_type: VoteType::Precommit,
+                height: certificate.height,
+                round: certificate.round,
+                value: NilOrVal::Val(certificate.value_id),
+                validator_address: commit.address,
+            };
+            MalVote::new(&vote, Some(&commit.signature))
+        })
+        .collect()
+}
+
+pub(crate) fn polka_votes(certificate: &PolkaCertificate<FfiContext>) -> Vec<MalVote> {
+    certificate
+        .polka_signatures
+        .iter()
+        .map(|polka| {
+            let vote = Vote {
+                vote_type: VoteType::Prevote,
+                height: certificate.height,
+                round: certificate.round,
+                value: NilOrVal::Val(certificate.value_id),
+                validator_address: polka.address,
+            };
+            MalVote::new(&vote, Some(&polka.signature))
+        })
+        .collect()
+}
+
+pub(crate) fn round_votes(certificate: &RoundCertificate<FfiContext>) -> Vec<MalVote> {
+    certificate
+        .round_signatures
+        .iter()
+        .map(|signature| {
+            let vote = Vote {
+                vote_type: signature.vote_type,
+                height: certificate.height,
+                round: certificate.round,
+                value: signature.value_id,
+                validator_address: signature.address,
+            };
+            MalVote::new(&vote, Some(&signature.signature))
+        })
+        .collect()
+}
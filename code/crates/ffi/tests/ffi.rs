@@ -0,0 +1,246 @@
+use core::ffi::c_void;
+use core::ptr;
+
+use arc_malachitebft_ffi::*;
+
+const VALUE_ID: [u8; VALUE_ID_LEN] = [42; VALUE_ID_LEN];
+const SIGNATURE: [u8; SIGNATURE_LEN] = [7; SIGNATURE_LEN];
+
+#[derive(Default)]
+struct Host {
+    effects: Vec<MalEffectKind>,
+    get_value: Option<(u64, i64)>,
+    scheduled: Vec<MalTimeout>,
+    decided: Option<([u8; VALUE_ID_LEN], usize)>,
+}
+
+unsafe extern "C" fn on_effect(
+    user_data: *mut c_void,
+    effect: *const MalEffect,
+    resume: *mut MalResume,
+) -> i32 {
+    let host = &mut *user_data.cast::<Host>();
+    let effect = &*effect;
+    let resume = &mut *resume;
+
+    host.effects.push(effect.kind);
+
+    match effect.kind {
+        MalEffectKind::GetValue => host.get_value = Some((effect.height, effect.round)),
+        MalEffectKind::ScheduleTimeout => host.scheduled.push(effect.timeout),
+        MalEffectKind::SignVote | MalEffectKind::SignProposal => resume.signature = SIGNATURE,
+        MalEffectKind::VerifyVoteSignature | MalEffectKind::VerifyProposalSignature => {
+            resume.valid =
+                effect.vote.signature == SIGNATURE || effect.proposal.signature == SIGNATURE
+        }
+        MalEffectKind::Decide => host.decided = Some((effect.value_id, effect.votes_len)),
+        _ => (),
+    }
+
+    0
+}
+
+fn config(address: [u8; ADDRESS_LEN]) -> MalConfig {
+    MalConfig {
+        address,
+        value_payload: MAL_VALUE_PAYLOAD_PARTS_ONLY,
+        timeout_propose_ms: 3000,
+        timeout_propose_delta_ms: 500,
+        timeout_prevote_ms: 1000,
+        timeout_prevote_delta_ms: 500,
+        timeout_precommit_ms: 1000,
+        timeout_precommit_delta_ms: 500,
+        timeout_rebroadcast_ms: 4000,
+    }
+}
+
+fn validator(byte: u8) -> MalValidator {
+    MalValidator {
+        address: [byte; ADDRESS_LEN],
+        public_key: [byte; PUBLIC_KEY_LEN],
+        voting_power: 1,
+    }
+}
+
+fn state(consensus: *const MalConsensus) -> MalState {
+    let mut state = MalState {
+        height: 0,
+        round: 0,
+        step: 0,
+        locked_round: 0,
+        locked_value_id: [0; VALUE_ID_LEN],
+        valid_round: 0,
+        valid_value_id: [0; VALUE_ID_LEN],
+        decided: false,
+        decided_round: 0,
+        decided_value_id: [0; VALUE_ID_LEN],
+    };
+
+    assert_eq!(
+        unsafe { malachite_state(consensus, &mut state) },
+        MalStatus::Ok
+    );
+
+    state
+}
+
+#[test]
+fn single_validator_decides() {
+    let mut host = Host::default();
+    let validators = [validator(1)];
+
+    let consensus = unsafe {
+        malachite_new(
+            &config([1; ADDRESS_LEN]),
+            1,
+            validators.as_ptr(),
+            validators.len(),
+            MalHost {
+                user_data: ptr::addr_of_mut!(host).cast(),
+                on_effect: Some(on_effect),
+            },
+        )
+    };
+
+    assert!(!consensus.is_null());
+
+    let status =
+        unsafe { malachite_start_height(consensus, 1, validators.as_ptr(), validators.len()) };
+    assert_eq!(status, MalStatus::Ok);
+
+    assert!(host.effects.contains(&MalEffectKind::StartRound));
+    assert_eq!(host.get_value, Some((1, 0)));
+    assert!(host
+        .scheduled
+        .iter()
+        .any(|t| t.kind == MAL_TIMEOUT_PROPOSE && t.round == 0 && t.duration_ms == 3000));
+
+    let status = unsafe { malachite_propose(consensus, 1, 0, VALUE_ID.as_ptr()) };
+    assert_eq!(status, MalStatus::Ok);
+
+    assert!(host.effects.contains(&MalEffectKind::SignProposal));
+    assert!(host.effects.contains(&MalEffectKind::SignVote));
+    assert_eq!(host.decided, Some((VALUE_ID, 1)));
+
+    let state = state(consensus);
+    assert_eq!(state.height, 1);
+    assert_eq!(state.step, MAL_STEP_COMMIT);
+    assert!(state.decided);
+    assert_eq!(state.decided_round, 0);
+    assert_eq!(state.decided_value_id, VALUE_ID);
+
+    unsafe { malachite_free(consensus) };
+}
+
+#[test]
+fn receives_votes_from_peers() {
+    let mut host = Host::default();
+    let validators = [validator(1), validator(2), validator(3), validator(4)];
+
+    // Validator 4 is not the proposer of round 0 at height 1
+    let consensus = unsafe {
+        malachite_new(
+            &config([4; ADDRESS_LEN]),
+            1,
+            validators.as_ptr(),
+            validators.len(),
+            MalHost {
+                user_data: ptr::addr_of_mut!(host).cast(),
+                on_effect: Some(on_effect),
+            },
+        )
+    };
+
+    let status =
+        unsafe { malachite_start_height(consensus, 1, validators.as_ptr(), validators.len()) };
+    assert_eq!(status, MalStatus::Ok);
+    assert_eq!(host.get_value, None);
+
+    for byte in 1..=3 {
+        let vote = MalVote {
+            vote_type: MAL_VOTE_PRECOMMIT,
+            height: 1,
+            round: 0,
+            is_nil: false,
+            value_id: VALUE_ID,
+            address: [byte; ADDRESS_LEN],
+            signature: SIGNATURE,
+        };
+
+        let status = unsafe { malachite_receive_vote(consensus, &vote) };
+        assert_eq!(status, MalStatus::Ok);
+    }
+
+    assert!(host.effects.contains(&MalEffectKind::VerifyVoteSignature));
+
+    // We have a commit certificate, but not the value itself yet
+    assert_eq!(host.decided, None);
+
+    let status = unsafe {
+        malachite_proposed_value(
+            consensus,
+            1,
+            0,
+            -1,
+            [1; ADDRESS_LEN].as_ptr(),
+            VALUE_ID.as_ptr(),
+            true,
+        )
+    };
+    assert_eq!(status, MalStatus::Ok);
+
+    assert_eq!(host.decided, Some((VALUE_ID, 3)));
+    assert!(state(consensus).decided);
+
+    unsafe { malachite_free(consensus) };
+}
+
+#[test]
+fn rejects_invalid_arguments() {
+    let validators = [validator(1)];
+    let host = MalHost {
+        user_data: ptr::null_mut(),
+        on_effect: Some(on_effect),
+    };
+
+    unsafe {
+        assert!(malachite_new(ptr::null(), 1, validators.as_ptr(), 1, host).is_null());
+        assert!(
+            malachite_new(&config([1; ADDRESS_LEN]), 1, validators.as_ptr(), 0, host).is_null()
+        );
+
+        assert_eq!(
+            malachite_start_height(ptr::null_mut(), 1, validators.as_ptr(), 1),
+            MalStatus::NullPointer
+        );
+
+        let mut host = Host::default();
+        let consensus = malachite_new(
+            &config([1; ADDRESS_LEN]),
+            1,
+            validators.as_ptr(),
+            1,
+            MalHost {
+                user_data: ptr::addr_of_mut!(host).cast(),
+                on_effect: Some(on_effect),
+            },
+        );
+
+        let vote = MalVote {
+            vote_type: 42,
+            height: 1,
+            round: 0,
+            is_nil: true,
+            value_id: [0; VALUE_ID_LEN],
+            address: [1; ADDRESS_LEN],
+            signature: SIGNATURE,
+        };
+
+        assert_eq!(
+            malachite_receive_vote(consensus, &vote),
+            MalStatus::InvalidArgument
+        );
+
+        malachite_free(consensus);
+    }
+}
@@ -35,6 +35,102 @@ impl TimePerStep {
     }
 }
 
+/// Label set for the `phase_duration` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct PhaseDuration {
+    phase: AsLabelValue<Step>,
+    round: RoundBucket,
+}
+
+impl PhaseDuration {
+    pub fn new(phase: Step, round: i64) -> Self {
+        Self {
+            phase: AsLabelValue(phase),
+            round: RoundBucket::new(round),
+        }
+    }
+}
+
+/// Label set for the `effective_timeout` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct EffectiveTimeout {
+    step: AsLabelValue<Step>,
+}
+
+impl EffectiveTimeout {
+    pub fn new(step: Step) -> Self {
+        Self {
+            step: AsLabelValue(step),
+        }
+    }
+}
+
+/// Label set for the `effect_duration` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct EffectLabel {
+    effect: &'static str,
+}
+
+impl EffectLabel {
+    pub fn new(effect: &'static str) -> Self {
+        Self { effect }
+    }
+}
+
+/// Label set for the `suppressed_duplicates` metric.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MessageLabel {
+    message: &'static str,
+}
+
+impl MessageLabel {
+    pub fn new(message: &'static str) -> Self {
+        Self { message }
+    }
+}
+
+/// Label set for the per-validator participation metrics.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct ValidatorLabel {
+    validator: String,
+}
+
+impl ValidatorLabel {
+    pub fn new(validator: impl ToString) -> Self {
+        Self {
+            validator: validator.to_string(),
+        }
+    }
+}
+
+/// Bucket of the round in which a phase took place, bounding the cardinality of the round label.
+///
+/// Rounds 0, 1 and 2 get a bucket of their own, later rounds all fall in the `3+` bucket.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct RoundBucket(i64);
+
+impl RoundBucket {
+    /// Rounds at or above this one share the same bucket
+    const LAST: i64 = 3;
+
+    pub fn new(round: i64) -> Self {
+        Self(round.clamp(0, Self::LAST))
+    }
+}
+
+impl EncodeLabelValue for RoundBucket {
+    fn encode(
+        &self,
+        encoder: &mut prometheus_client::encoding::LabelValueEncoder,
+    ) -> Result<(), std::fmt::Error> {
+        if self.0 == Self::LAST {
+            encoder.write_fmt(format_args!("{}+", self.0))
+        } else {
+            encoder.write_fmt(format_args!("{}", self.0))
+        }
+    }
+}
+
 /// This wrapper allows us to derive `AsLabelValue` for `Step` without
 /// running into Rust orphan rules, cf. <https://rust-lang.github.io/chalk/book/clauses/coherence.html>
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -60,9 +156,16 @@ pub struct Inner {
     /// Time taken for a step within a round, in secodns
     pub time_per_step: Family<TimePerStep, Histogram>,
 
+    /// Time taken for a phase of consensus (propose, prevote, precommit, commit), in seconds,
+    /// per phase and round bucket
+    pub phase_duration: Family<PhaseDuration, Histogram>,
+
     /// The consensus round in which the node was when it finalized a block
     pub consensus_round: Histogram,
 
+    /// Number of rounds it took to decide on a value at a height
+    pub rounds_per_height: Histogram,
+
     /// The round of the proposal that was decided on
     pub proposal_round: Histogram,
 
@@ -99,6 +202,40 @@ pub struct Inner {
     /// Number of additional precommits received during finalization period
     pub additional_precommits: Counter,
 
+    /// Number of heights which reached the round alert threshold without deciding
+    pub round_alerts: Counter,
+
+    /// Whether participation in consensus is halted for the current height (0 or 1)
+    pub halted: Gauge,
+
+    /// Number of rounds in which the application did not provide a value to propose in time
+    pub missed_value_rounds: Counter,
+
+    /// Number of proposals and values evicted from rounds far below the current round
+    pub full_proposals_evicted: Counter,
+
+    /// Number of heights at which more than a third of the voting power changed from the previous height
+    pub large_validator_set_changes: Counter,
+
+    /// Timeout of the propose, prevote and precommit steps at round 0 computed by the adaptive timeouts, in seconds
+    pub effective_timeout: Family<EffectiveTimeout, Gauge<f64, AtomicU64>>,
+
+    /// Number of decided heights in which a vote was received from the validator, per validator
+    pub validator_participated_heights: Family<ValidatorLabel, Counter>,
+
+    /// Number of decided heights in which no vote was received from the validator, per validator
+    pub validator_absent_heights: Family<ValidatorLabel, Counter>,
+
+    /// Time taken to handle an effect of consensus, per kind of effect, in seconds.
+    ///
+    /// The `wal_flush` kind times the WAL flushes performed while handling other effects,
+    /// and is thus nested in their duration rather than being disjoint from it.
+    pub effect_duration: Family<EffectLabel, Histogram>,
+
+    /// Number of votes and proposals received from the network and dropped as duplicates
+    /// of messages already seen at the current height, per kind of message
+    pub suppressed_duplicates: Family<MessageLabel, Counter>,
+
     /// Internal state for measuring time taken for consensus
     instant_consensus_started: Arc<AtomicInstant>,
 
@@ -106,7 +243,7 @@ pub struct Inner {
     instant_block_started: Arc<AtomicInstant>,
 
     /// Internal state for measuring time taken for a step within a round
-    instant_step_started: Arc<Mutex<(Step, Instant)>>,
+    instant_step_started: Arc<Mutex<(Step, i64, Instant)>>,
 }
 
 impl Metrics {
@@ -117,7 +254,11 @@ impl Metrics {
             time_per_step: Family::new_with_constructor(|| {
                 Histogram::new(linear_buckets(0.0, 0.1, 20))
             }),
+            phase_duration: Family::new_with_constructor(|| {
+                Histogram::new(exponential_buckets(0.01, 2.0, 12))
+            }),
             consensus_round: Histogram::new(linear_buckets(0.0, 1.0, 20)),
+            rounds_per_height: Histogram::new(linear_buckets(1.0, 1.0, 10)),
             proposal_round: Histogram::new(linear_buckets(0.0, 1.0, 20)),
             rebroadcast_timeouts: Counter::default(),
             connected_peers: Gauge::default(),
@@ -130,9 +271,21 @@ impl Metrics {
             equivocation_votes: Counter::default(),
             equivocation_proposals: Counter::default(),
             additional_precommits: Counter::default(),
+            round_alerts: Counter::default(),
+            halted: Gauge::default(),
+            missed_value_rounds: Counter::default(),
+            full_proposals_evicted: Counter::default(),
+            large_validator_set_changes: Counter::default(),
+            effective_timeout: Family::default(),
+            validator_participated_heights: Family::default(),
+            validator_absent_heights: Family::default(),
+            effect_duration: Family::new_with_constructor(|| {
+                Histogram::new(exponential_buckets(0.0001, 2.0, 16))
+            }),
+            suppressed_duplicates: Family::default(),
             instant_consensus_started: Arc::new(AtomicInstant::empty()),
             instant_block_started: Arc::new(AtomicInstant::empty()),
-            instant_step_started: Arc::new(Mutex::new((Step::Unstarted, Instant::now()))),
+            instant_step_started: Arc::new(Mutex::new((Step::Unstarted, 0, Instant::now()))),
         }))
     }
 
@@ -158,6 +311,12 @@ impl Metrics {
                 metrics.time_per_step.clone(),
             );
 
+            registry.register(
+                "phase_duration",
+                "Time taken for a phase of consensus, per phase and round, in seconds",
+                metrics.phase_duration.clone(),
+            );
+
             registry.register(
                 "consensus_round",
                 "The consensus round in which the node was when it finalized a block",
@@ -170,6 +329,12 @@ impl Metrics {
                 metrics.proposal_round.clone(),
             );
 
+            registry.register(
+                "rounds_per_height",
+                "Number of rounds it took to decide on a value at a height",
+                metrics.rounds_per_height.clone(),
+            );
+
             registry.register(
                 "rebroadcast_timeouts",
                 "Number of times consensus rebroadcasted its vote due to no round progress",
@@ -235,11 +400,89 @@ impl Metrics {
                 "Number of additional precommits received during finalization period",
                 metrics.additional_precommits.clone(),
             );
+
+            registry.register(
+                "round_alerts",
+                "Number of heights which reached the round alert threshold without deciding",
+                metrics.round_alerts.clone(),
+            );
+
+            registry.register(
+                "halted",
+                "Whether participation in consensus is halted for the current height (0 or 1)",
+                metrics.halted.clone(),
+            );
+
+            registry.register(
+                "missed_value_rounds",
+                "Number of rounds in which the application did not provide a value to propose in time",
+                metrics.missed_value_rounds.clone(),
+            );
+
+            registry.register(
+                "full_proposals_evicted",
+                "Number of proposals and values evicted from rounds far below the current round",
+                metrics.full_proposals_evicted.clone(),
+            );
+
+            registry.register(
+                "large_validator_set_changes",
+                "Number of heights at which more than a third of the voting power changed from the previous height",
+                metrics.large_validator_set_changes.clone(),
+            );
+
+            registry.register(
+                "effective_timeout",
+                "Timeout of a step at round 0 computed by the adaptive timeouts, in seconds",
+                metrics.effective_timeout.clone(),
+            );
+
+            registry.register(
+                "validator_participated_heights",
+                "Number of decided heights in which a vote was received from the validator, per validator",
+                metrics.validator_participated_heights.clone(),
+            );
+
+            registry.register(
+                "validator_absent_heights",
+                "Number of decided heights in which no vote was received from the validator, per validator",
+                metrics.validator_absent_heights.clone(),
+            );
+
+            registry.register(
+                "suppressed_duplicates",
+                "Number of votes and proposals received from the network and dropped as duplicates of messages already seen at the current height, per kind of message",
+                metrics.suppressed_duplicates.clone(),
+            );
+        });
+
+        registry.with_prefix("malachitebft_consensus_effects", |registry| {
+            registry.register(
+                "duration",
+                "Time taken to handle an effect of consensus, per kind of effect, in seconds. The `wal_flush` kind is nested in the duration of the effects during which the WAL is flushed",
+                metrics.effect_duration.clone(),
+            );
         });
 
         metrics
     }
 
+    /// Record that a message of the given kind was dropped as a duplicate.
+    pub fn duplicate_suppressed(&self, message: &'static str) {
+        self.suppressed_duplicates
+            .get_or_create(&MessageLabel::new(message))
+            .inc();
+    }
+
+    /// Record the time taken to handle an effect of the given kind.
+    ///
+    /// Kinds may nest, eg. `wal_flush` is recorded while handling another effect.
+    pub fn effect_handled(&self, effect: &'static str, elapsed: Duration) {
+        self.effect_duration
+            .get_or_create(&EffectLabel::new(effect))
+            .observe(elapsed.as_secs_f64());
+    }
+
     pub fn consensus_start(&self) {
         self.instant_consensus_started.set_now();
     }
@@ -266,15 +509,15 @@ impl Metrics {
         }
     }
 
-    pub fn step_start(&self, step: Step) {
+    pub fn step_start(&self, step: Step, round: i64) {
         let mut guard = self.instant_step_started.lock().expect("poisoned mutex");
-        *guard = (step, Instant::now());
+        *guard = (step, round, Instant::now());
     }
 
     pub fn step_end(&self, step: Step) {
         let mut guard = self.instant_step_started.lock().expect("poisoned mutex");
 
-        let (current_step, started) = *guard;
+        let (current_step, round, started) = *guard;
         debug_assert_eq!(current_step, step, "step_end called for wrong step");
 
         // If the step was never started, ignore
@@ -282,11 +525,42 @@ impl Metrics {
             return;
         }
 
+        let elapsed = started.elapsed().as_secs_f64();
+
         self.time_per_step
             .get_or_create(&TimePerStep::new(step))
-            .observe(started.elapsed().as_secs_f64());
+            .observe(elapsed);
+
+        self.phase_duration
+            .get_or_create(&PhaseDuration::new(step, round))
+            .observe(elapsed);
+
+        *guard = (Step::Unstarted, 0, Instant::now());
+    }
 
-        *guard = (Step::Unstarted, Instant::now());
+    /// Record the timeout of the given step computed by the adaptive timeouts.
+    pub fn set_effective_timeout(&self, step: Step, timeout: Duration) {
+        self.effective_timeout
+            .get_or_create(&EffectiveTimeout::new(step))
+            .set(timeout.as_secs_f64());
+    }
+
+    /// Record whether a vote was received from the given validator at a decided height.
+    pub fn record_participation(&self, validator: impl ToString, absent: bool) {
+        let label = ValidatorLabel::new(validator);
+
+        if absent {
+            self.validator_absent_heights.get_or_create(&label).inc();
+        } else {
+            self.validator_participated_heights
+                .get_or_create(&label)
+                .inc();
+        }
+    }
+
+    /// Record the number of rounds it took to decide at a height, given the round of the decision.
+    pub fn height_decided_in_round(&self, round: i64) {
+        self.rounds_per_height.observe((round + 1) as f64);
     }
 }
 
@@ -347,3 +621,32 @@ impl AtomicInstant {
             .as_millis() as u64
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use prometheus_client::registry::Registry;
+
+    use super::*;
+
+    #[test]
+    fn effect_durations_are_recorded_per_kind() {
+        let registry = SharedRegistry::new(Registry::default(), None);
+        let metrics = Metrics::register(&registry);
+
+        metrics.effect_handled("publish_consensus_msg", Duration::from_millis(2));
+        metrics.effect_handled("publish_consensus_msg", Duration::from_millis(3));
+        metrics.effect_handled("wal_flush", Duration::from_millis(1));
+
+        let mut encoded = String::new();
+        registry.read(|reg| prometheus_client::encoding::text::encode(&mut encoded, reg).unwrap());
+
+        assert!(encoded.contains("# TYPE malachitebft_consensus_effects_duration histogram"));
+        assert!(encoded.contains(
+            r#"malachitebft_consensus_effects_duration_count{effect="publish_consensus_msg"} 2"#
+        ));
+        assert!(encoded
+            .contains(r#"malachitebft_consensus_effects_duration_count{effect="wal_flush"} 1"#));
+    }
+}
@@ -6,6 +6,7 @@ pub use prometheus_client::registry::Registry;
 #[derive(Clone)]
 pub struct SharedRegistry {
     moniker: Option<String>,
+    labels: Vec<(String, String)>,
     registry: Arc<RwLock<Registry>>,
 }
 
@@ -13,6 +14,7 @@ impl SharedRegistry {
     pub fn new(registry: Registry, moniker: Option<String>) -> Self {
         Self {
             moniker,
+            labels: Vec::new(),
             registry: Arc::new(RwLock::new(registry)),
         }
     }
@@ -24,26 +26,49 @@ impl SharedRegistry {
     pub fn with_moniker(&self, moniker: impl Into<String>) -> Self {
         Self {
             moniker: Some(moniker.into()),
+            labels: self.labels.clone(),
             registry: Arc::clone(&self.registry),
         }
     }
 
-    pub fn with_prefix<A>(&self, prefix: impl AsRef<str>, f: impl FnOnce(&mut Registry) -> A) -> A {
-        if let Some(moniker) = &self.moniker {
-            self.write(|reg| {
-                f(reg
-                    .sub_registry_with_prefix(prefix)
-                    .sub_registry_with_label((
-                        Cow::Borrowed("moniker"),
-                        Cow::Owned(moniker.to_string()),
-                    )))
-            })
-        } else {
-            self.write(|reg| f(reg.sub_registry_with_prefix(prefix)))
+    /// Returns a registry sharing the same metrics storage, whose metrics
+    /// are all registered with the additional label `name="value"`.
+    ///
+    /// Useful to isolate the metrics of several consensus instances running in the same process.
+    pub fn with_label(&self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        let mut labels = self.labels.clone();
+        labels.push((name.into(), value.into()));
+
+        Self {
+            moniker: self.moniker.clone(),
+            labels,
+            registry: Arc::clone(&self.registry),
         }
     }
 
-    fn read<A>(&self, f: impl FnOnce(&Registry) -> A) -> A {
+    pub fn with_prefix<A>(&self, prefix: impl AsRef<str>, f: impl FnOnce(&mut Registry) -> A) -> A {
+        self.write(|reg| {
+            let mut reg = reg.sub_registry_with_prefix(prefix);
+
+            if let Some(moniker) = &self.moniker {
+                reg = reg.sub_registry_with_label((
+                    Cow::Borrowed("moniker"),
+                    Cow::Owned(moniker.to_string()),
+                ));
+            }
+
+            for (name, value) in &self.labels {
+                reg = reg.sub_registry_with_label((
+                    Cow::Owned(name.to_string()),
+                    Cow::Owned(value.to_string()),
+                ));
+            }
+
+            f(reg)
+        })
+    }
+
+    pub(crate) fn read<A>(&self, f: impl FnOnce(&Registry) -> A) -> A {
         f(&self.registry.read().expect("poisoned lock"))
     }
 
@@ -62,3 +87,34 @@ pub fn export<W: core::fmt::Write>(writer: &mut W) {
 
     SharedRegistry::global().read(|registry| encode(writer, registry).unwrap())
 }
+
+#[cfg(test)]
+mod tests {
+    use prometheus_client::metrics::counter::Counter;
+
+    use super::*;
+
+    #[test]
+    fn labels_isolate_metrics() {
+        let registry = SharedRegistry::new(Registry::default(), Some("node".to_string()));
+
+        let register = |shard: &str| {
+            let counter = Counter::<u64>::default();
+            registry
+                .with_label("shard", shard)
+                .with_prefix("test", |reg| {
+                    reg.register("decisions", "Number of decisions", counter.clone())
+                });
+            counter
+        };
+
+        register("a").inc();
+        register("b").inc_by(2);
+
+        let mut encoded = String::new();
+        registry.read(|reg| prometheus_client::encoding::text::encode(&mut encoded, reg).unwrap());
+
+        assert!(encoded.contains(r#"test_decisions_total{moniker="node",shard="a"} 1"#));
+        assert!(encoded.contains(r#"test_decisions_total{moniker="node",shard="b"} 2"#));
+    }
+}
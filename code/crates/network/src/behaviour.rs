@@ -4,11 +4,11 @@ use std::time::Duration;
 use eyre::Result;
 use libp2p::connection_limits;
 pub use libp2p::identity::Keypair;
-use libp2p::kad::{Addresses, KBucketKey, KBucketRef};
+use libp2p::kad::{Addresses, KBucketKey, KBucketRef, NoKnownPeers, QueryId};
 use libp2p::request_response::{OutboundRequestId, ResponseChannel};
 use libp2p::swarm::behaviour::toggle::Toggle;
 use libp2p::swarm::NetworkBehaviour;
-use libp2p::{gossipsub, identify, ping};
+use libp2p::{autonat, dcutr, gossipsub, identify, mdns, ping, relay};
 pub use libp2p::{Multiaddr, PeerId};
 use libp2p_broadcast as broadcast;
 
@@ -17,8 +17,9 @@ use malachitebft_metrics::Registry;
 use malachitebft_sync as sync;
 use tracing::info;
 
-use crate::validator_proof;
-use crate::{ip_limits, peer_scoring, Config, GossipSubConfig};
+use crate::envelope::Authenticator;
+use crate::{ip_limits, peer_filter, peer_scoring, Config, GossipSubConfig};
+use crate::{node_info, proposal_parts, validator_proof};
 
 /// Multiplier for connection limits.
 /// Connection limits are higher than discovery limits to allow headroom for ephemeral
@@ -50,8 +51,14 @@ pub enum NetworkEvent {
     GossipSub(gossipsub::Event),
     Broadcast(broadcast::Event),
     Sync(sync::Event),
+    ProposalParts(proposal_parts::Event),
     Discovery(Box<discovery::NetworkEvent>),
+    Mdns(mdns::Event),
     ValidatorProof(validator_proof::Event),
+    NodeInfo(node_info::Event),
+    Autonat(autonat::Event),
+    RelayClient(relay::client::Event),
+    Dcutr(dcutr::Event),
 }
 
 impl From<identify::Event> for NetworkEvent {
@@ -84,18 +91,54 @@ impl From<sync::Event> for NetworkEvent {
     }
 }
 
+impl From<proposal_parts::Event> for NetworkEvent {
+    fn from(event: proposal_parts::Event) -> Self {
+        Self::ProposalParts(event)
+    }
+}
+
 impl From<discovery::NetworkEvent> for NetworkEvent {
     fn from(network_event: discovery::NetworkEvent) -> Self {
         Self::Discovery(Box::new(network_event))
     }
 }
 
+impl From<mdns::Event> for NetworkEvent {
+    fn from(event: mdns::Event) -> Self {
+        Self::Mdns(event)
+    }
+}
+
 impl From<validator_proof::Event> for NetworkEvent {
     fn from(event: validator_proof::Event) -> Self {
         Self::ValidatorProof(event)
     }
 }
 
+impl From<node_info::Event> for NetworkEvent {
+    fn from(event: node_info::Event) -> Self {
+        Self::NodeInfo(event)
+    }
+}
+
+impl From<autonat::Event> for NetworkEvent {
+    fn from(event: autonat::Event) -> Self {
+        Self::Autonat(event)
+    }
+}
+
+impl From<relay::client::Event> for NetworkEvent {
+    fn from(event: relay::client::Event) -> Self {
+        Self::RelayClient(event)
+    }
+}
+
+impl From<dcutr::Event> for NetworkEvent {
+    fn from(event: dcutr::Event) -> Self {
+        Self::Dcutr(event)
+    }
+}
+
 // connection_limits::Behaviour never emits events (uses Infallible),
 // but the NetworkBehaviour derive macro requires this implementation.
 impl From<Infallible> for NetworkEvent {
@@ -109,13 +152,20 @@ impl From<Infallible> for NetworkEvent {
 pub struct Behaviour {
     pub connection_limits: connection_limits::Behaviour,
     pub ip_limits: ip_limits::Behaviour,
+    pub peer_filter: Toggle<peer_filter::Behaviour>,
     pub identify: identify::Behaviour,
     pub ping: ping::Behaviour,
     pub gossipsub: Toggle<gossipsub::Behaviour>,
     pub broadcast: Toggle<broadcast::Behaviour>,
     pub sync: Toggle<sync::Behaviour>,
+    pub proposal_parts: Toggle<proposal_parts::Behaviour>,
     pub discovery: Toggle<discovery::Behaviour>,
+    pub mdns: Toggle<mdns::tokio::Behaviour>,
     pub validator_proof: Toggle<validator_proof::Behaviour>,
+    pub node_info: Toggle<node_info::Behaviour>,
+    pub autonat: Toggle<autonat::Behaviour>,
+    pub relay_client: Toggle<relay::client::Behaviour>,
+    pub dcutr: Toggle<dcutr::Behaviour>,
 }
 
 /// Dummy implementation of Debug for Behaviour.
@@ -146,6 +196,16 @@ impl discovery::DiscoveryClient for Behaviour {
             .kbuckets()
     }
 
+    fn bootstrap(&mut self) -> Result<QueryId, NoKnownPeers> {
+        self.discovery
+            .as_mut()
+            .expect("Discovery behaviour should be available")
+            .kademlia
+            .as_mut()
+            .expect("Kademlia behaviour should be available")
+            .bootstrap()
+    }
+
     fn send_request(&mut self, peer_id: &PeerId, req: discovery::Request) -> OutboundRequestId {
         self.discovery
             .as_mut()
@@ -190,19 +250,26 @@ fn gossipsub_config(config: GossipSubConfig, max_transmit_size: usize) -> gossip
         .mesh_outbound_min(config.mesh_outbound_min)
         .mesh_n(config.mesh_n)
         .flood_publish(config.enable_flood_publish)
+        .prune_backoff(config.scoring.prune_backoff)
+        .graft_flood_threshold(config.scoring.graft_flood_threshold)
         .message_id_fn(message_id)
         .build()
         .unwrap()
 }
 
 impl Behaviour {
+    /// Create the behaviour of the swarm.
+    ///
+    /// The relay client behaviour is provided by the swarm builder together with the relay transport,
+    /// and is only enabled, together with hole punching, if relays are configured.
     pub fn new_with_metrics(
         config: &Config,
         identity: &crate::NetworkIdentity,
+        relay_client: relay::client::Behaviour,
         registry: &mut Registry,
     ) -> Result<Self> {
-        // Build agent_version for peer identification (moniker only)
-        let agent_version = format!("moniker={}", identity.moniker);
+        // Build agent_version for peer identification (moniker and protocol version)
+        let agent_version = crate::utils::agent_version(&identity.moniker, config.protocol_version);
 
         // Validate consensus protocol name and use it for identify (and compatibility check in event loop)
         let consensus_protocol =
@@ -210,12 +277,15 @@ impl Behaviour {
 
         // Use signed peer records to prevent peer ID spoofing.
         // Peers will sign their addresses with their private key, allowing verification.
+        // If addresses to advertise are configured, only share those (added as external
+        // addresses of the swarm) and not the listen addresses, which may be local-only.
         let identify = identify::Behaviour::new(
             identify::Config::new_with_signed_peer_record(
                 consensus_protocol.to_string(),
                 &identity.keypair,
             )
-            .with_agent_version(agent_version),
+            .with_agent_version(agent_version)
+            .with_hide_listen_addrs(!config.advertise_addrs.is_empty()),
         );
 
         let ping = ping::Behaviour::new(ping::Config::new().with_interval(Duration::from_secs(5)));
@@ -233,8 +303,11 @@ impl Behaviour {
                 info!("Enabling peer scoring for GossipSub");
                 behaviour
                     .with_peer_score(
-                        peer_scoring::peer_score_params(),
-                        peer_scoring::peer_score_thresholds(),
+                        peer_scoring::peer_score_params(
+                            &config.gossipsub.scoring,
+                            config.channel_names,
+                        ),
+                        peer_scoring::peer_score_thresholds(&config.gossipsub.scoring),
                     )
                     .expect("Failed to enable peer scoring");
             } else {
@@ -259,9 +332,22 @@ impl Behaviour {
         });
 
         let sync = if config.enable_sync {
-            Some(sync::Behaviour::new(
-                sync::Config::default().with_max_response_size(config.rpc_max_size),
+            Some(sync::Behaviour::new_with_metrics(
+                sync::Config::default()
+                    .with_max_response_size(config.rpc_max_size)
+                    .with_compression(config.sync_compression),
                 config.protocol_names.sync.clone(),
+                registry.sub_registry_with_prefix("sync"),
+            )?)
+        } else {
+            None
+        };
+
+        // Enable fetching missing proposal parts from peers if consensus is enabled
+        let proposal_parts = if config.enable_consensus {
+            Some(proposal_parts::Behaviour::new(
+                config.rpc_max_size,
+                config.protocol_names.proposal_parts.clone(),
             )?)
         } else {
             None
@@ -278,32 +364,89 @@ impl Behaviour {
             None
         };
 
+        // Discover the peers on the local network, which are then dialed by discovery
+        let mdns = if config.discovery.enabled && config.discovery.mdns {
+            info!("Enabling mDNS discovery of the peers on the local network");
+
+            Some(mdns::tokio::Behaviour::new(
+                mdns::Config::default(),
+                identity.keypair.public().to_peer_id(),
+            )?)
+        } else {
+            None
+        };
+
         // Enable validator proof verification if consensus is enabled
         let validator_proof = if config.enable_consensus {
             let protocol = libp2p::StreamProtocol::try_from_owned(
                 config.protocol_names.validator_proof.clone(),
             )?;
-            Some(validator_proof::Behaviour::new(protocol))
+            let behaviour = validator_proof::Behaviour::new(protocol);
+
+            // Sign the proofs with the node key, if configured
+            if config.rpc_signing.validator_proof {
+                Some(behaviour.with_envelopes(Authenticator::new(
+                    identity.keypair.clone(),
+                    config.protocol_names.validator_proof.clone(),
+                    config.rpc_signing.max_age,
+                )))
+            } else {
+                Some(behaviour)
+            }
         } else {
             None
         };
 
+        // Exchange node info with the peers if consensus is enabled
+        let node_info = if config.enable_consensus {
+            let protocol =
+                libp2p::StreamProtocol::try_from_owned(config.protocol_names.node_info.clone())?;
+            Some(node_info::Behaviour::new(protocol))
+        } else {
+            None
+        };
+
+        let local_peer_id = identity.keypair.public().to_peer_id();
+
+        // Probe our public reachability through the connected peers, confirming
+        // the external addresses they observe so that identify advertises them
+        let autonat = config
+            .nat
+            .autonat
+            .then(|| autonat::Behaviour::new(local_peer_id, autonat::Config::default()));
+
+        // Listen for relayed connections through the configured relays,
+        // and try to upgrade them to direct connections via hole punching
+        let enable_relay = !config.nat.relay.is_empty();
+        let relay_client = enable_relay.then_some(relay_client);
+        let dcutr = enable_relay.then(|| dcutr::Behaviour::new(local_peer_id));
+
         // Limits for transport layer defense against connection attacks
         let connection_limits = connection_limits::Behaviour::new(connection_limits(config));
 
         // Per-IP connection limits to prevent DoS from multiple PeerIds on same IP
         let ip_limits = ip_limits::Behaviour::new(config.discovery.max_connections_per_ip);
 
+        // Refuse inbound connections from the peers rejected by the peer filter, if any
+        let peer_filter = config.peer_filter.clone().map(peer_filter::Behaviour::new);
+
         Ok(Self {
             connection_limits,
             ip_limits,
+            peer_filter: Toggle::from(peer_filter),
             identify,
             ping,
             sync: Toggle::from(sync),
+            proposal_parts: Toggle::from(proposal_parts),
             gossipsub: Toggle::from(gossipsub),
             broadcast: Toggle::from(broadcast),
             discovery: Toggle::from(discovery),
+            mdns: Toggle::from(mdns),
             validator_proof: Toggle::from(validator_proof),
+            node_info: Toggle::from(node_info),
+            autonat: Toggle::from(autonat),
+            relay_client: Toggle::from(relay_client),
+            dcutr: Toggle::from(dcutr),
         })
     }
 }
@@ -7,18 +7,22 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Copy)]
 pub struct ChannelNames {
     pub consensus: &'static str,
+    pub votes: &'static str,
     pub proposal_parts: &'static str,
     pub sync: &'static str,
     pub liveness: &'static str,
+    pub announcements: &'static str,
 }
 
 impl Default for ChannelNames {
     fn default() -> Self {
         Self {
             consensus: "/consensus",
+            votes: "/votes",
             proposal_parts: "/proposal_parts",
             sync: "/sync",
             liveness: "/liveness",
+            announcements: "/announcements",
         }
     }
 }
@@ -26,18 +30,26 @@ impl Default for ChannelNames {
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Channel {
     Consensus,
+    /// Votes, published on a topic reserved to the validators when selective gossip is enabled,
+    /// and on the topic of the [`Channel::Consensus`] channel otherwise
+    Votes,
     Liveness,
     ProposalParts,
     Sync,
+    /// Announcements of the values decided by the peers, broadcast to the direct peers
+    /// alongside their status when enabled, see [`crate::Config::enable_announcements`]
+    Announcements,
 }
 
 impl Channel {
     pub fn all() -> &'static [Channel] {
         &[
             Channel::Consensus,
+            Channel::Votes,
             Channel::ProposalParts,
             Channel::Sync,
             Channel::Liveness,
+            Channel::Announcements,
         ]
     }
 
@@ -60,9 +72,11 @@ impl Channel {
     pub fn as_str(&self, channel_names: ChannelNames) -> &'static str {
         match self {
             Channel::Consensus => channel_names.consensus,
+            Channel::Votes => channel_names.votes,
             Channel::ProposalParts => channel_names.proposal_parts,
             Channel::Sync => channel_names.sync,
             Channel::Liveness => channel_names.liveness,
+            Channel::Announcements => channel_names.announcements,
         }
     }
 
@@ -87,12 +101,16 @@ impl Channel {
     ) -> Option<Self> {
         if topic == &Self::Consensus.to_gossipsub_topic(channel_names).hash() {
             Some(Self::Consensus)
+        } else if topic == &Self::Votes.to_gossipsub_topic(channel_names).hash() {
+            Some(Self::Votes)
         } else if topic == &Self::ProposalParts.to_gossipsub_topic(channel_names).hash() {
             Some(Self::ProposalParts)
         } else if topic == &Self::Sync.to_gossipsub_topic(channel_names).hash() {
             Some(Self::Sync)
         } else if topic == &Self::Liveness.to_gossipsub_topic(channel_names).hash() {
             Some(Self::Liveness)
+        } else if topic == &Self::Announcements.to_gossipsub_topic(channel_names).hash() {
+            Some(Self::Announcements)
         } else {
             None
         }
@@ -104,12 +122,16 @@ impl Channel {
     ) -> Option<Self> {
         if topic == &Self::Consensus.to_broadcast_topic(channel_names) {
             Some(Self::Consensus)
+        } else if topic == &Self::Votes.to_broadcast_topic(channel_names) {
+            Some(Self::Votes)
         } else if topic == &Self::ProposalParts.to_broadcast_topic(channel_names) {
             Some(Self::ProposalParts)
         } else if topic == &Self::Sync.to_broadcast_topic(channel_names) {
             Some(Self::Sync)
         } else if topic == &Self::Liveness.to_broadcast_topic(channel_names) {
             Some(Self::Liveness)
+        } else if topic == &Self::Announcements.to_broadcast_topic(channel_names) {
+            Some(Self::Announcements)
         } else {
             None
         }
@@ -0,0 +1,512 @@
+//! Signed envelopes for the messages of the request-response protocols.
+//!
+//! The messages of the sync and validator proof protocols are otherwise only authenticated by
+//! the transport, which is not enough when TLS or QUIC is terminated at a proxy in front of the
+//! node. When enabled for a protocol, every message is wrapped in an envelope signed with the
+//! node key, which binds it to the protocol, to its sender and to its recipient, together with
+//! a nonce and an expiry time protecting against replays.
+//!
+//! ## Wire Format
+//!
+//! ```text
+//! [version: u8][nonce: u64][expiry: u64][key length: u16][public key][signature length: u16][signature][payload]
+//! ```
+//!
+//! Integers are big-endian, the expiry is in milliseconds since the UNIX epoch and the public key
+//! is protobuf-encoded. The signature covers the protocol name, the peer id of the recipient,
+//! the nonce, the expiry and the payload.
+//!
+//! ## Verification
+//!
+//! An envelope is accepted if the public key is the one of the peer it was received from, if the
+//! signature is valid for the local peer id, and if it has neither expired nor been seen before.
+//! The nonces of accepted envelopes are remembered until the envelopes expire, up to a bound.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use libp2p::identity::{Keypair, PublicKey};
+use libp2p::PeerId;
+use thiserror::Error;
+
+/// Version of the envelope format.
+const VERSION: u8 = 1;
+
+/// Domain separator prepended to the signed bytes.
+const DOMAIN: &[u8] = b"malachitebft-rpc-envelope";
+
+/// Tolerated difference between the clocks of the sender and the receiver.
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(10);
+
+/// Maximum number of nonces remembered for replay protection.
+/// When full, the oldest nonces are forgotten first.
+const MAX_SEEN_NONCES: usize = 100_000;
+
+/// Errors that can occur when sealing or opening an envelope.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Failed to sign envelope: {0}")]
+    Signing(String),
+    #[error("Malformed envelope")]
+    Malformed,
+    #[error("Unsupported envelope version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("Invalid public key in envelope")]
+    InvalidPublicKey,
+    #[error("Envelope signed by {signer} instead of the sending peer")]
+    WrongSigner { signer: PeerId },
+    #[error("Invalid envelope signature")]
+    InvalidSignature,
+    #[error("Envelope has expired")]
+    Expired,
+    #[error("Envelope expires too far in the future")]
+    ExpiryTooFar,
+    #[error("Envelope has already been received")]
+    Replayed,
+}
+
+/// Seals the messages sent over a protocol in signed envelopes,
+/// and opens the envelopes received over that protocol.
+pub struct Authenticator {
+    keypair: Keypair,
+    local_peer_id: PeerId,
+    protocol: String,
+    max_age: Duration,
+    next_nonce: u64,
+    seen: SeenNonces,
+}
+
+impl fmt::Debug for Authenticator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Authenticator")
+            .field("local_peer_id", &self.local_peer_id)
+            .field("protocol", &self.protocol)
+            .field("max_age", &self.max_age)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Authenticator {
+    /// Create an authenticator for the given protocol, signing with the node key.
+    ///
+    /// Sealed envelopes expire `max_age` after being sealed.
+    pub fn new(keypair: Keypair, protocol: impl Into<String>, max_age: Duration) -> Self {
+        let local_peer_id = keypair.public().to_peer_id();
+
+        // Start from the current time so that the nonces are not reused after a restart
+        let next_nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+
+        Self {
+            keypair,
+            local_peer_id,
+            protocol: protocol.into(),
+            max_age,
+            next_nonce,
+            seen: SeenNonces::default(),
+        }
+    }
+
+    /// Seal a message to be sent to the given peer.
+    pub fn seal(&mut self, recipient: &PeerId, payload: Bytes) -> Result<Bytes, Error> {
+        self.seal_at(recipient, payload, SystemTime::now())
+    }
+
+    /// Open an envelope received from the given peer, returning the message it contains.
+    pub fn open(&mut self, sender: &PeerId, envelope: Bytes) -> Result<Bytes, Error> {
+        self.open_at(sender, envelope, SystemTime::now())
+    }
+
+    fn seal_at(
+        &mut self,
+        recipient: &PeerId,
+        payload: Bytes,
+        now: SystemTime,
+    ) -> Result<Bytes, Error> {
+        let nonce = self.next_nonce;
+        self.next_nonce = self.next_nonce.wrapping_add(1);
+
+        let expiry = unix_millis(now).saturating_add(millis(self.max_age));
+
+        let message = signed_bytes(&self.protocol, recipient, nonce, expiry, &payload);
+        let signature = self
+            .keypair
+            .sign(&message)
+            .map_err(|e| Error::Signing(e.to_string()))?;
+
+        let public_key = self.keypair.public().encode_protobuf();
+
+        let key_len = u16::try_from(public_key.len())
+            .map_err(|_| Error::Signing("public key too large".into()))?;
+        let signature_len = u16::try_from(signature.len())
+            .map_err(|_| Error::Signing("signature too large".into()))?;
+
+        let mut envelope =
+            BytesMut::with_capacity(21 + public_key.len() + signature.len() + payload.len());
+
+        envelope.put_u8(VERSION);
+        envelope.put_u64(nonce);
+        envelope.put_u64(expiry);
+        envelope.put_u16(key_len);
+        envelope.put_slice(&public_key);
+        envelope.put_u16(signature_len);
+        envelope.put_slice(&signature);
+        envelope.put_slice(&payload);
+
+        Ok(envelope.freeze())
+    }
+
+    fn open_at(
+        &mut self,
+        sender: &PeerId,
+        mut envelope: Bytes,
+        now: SystemTime,
+    ) -> Result<Bytes, Error> {
+        let version = take(&mut envelope, 1)?.get_u8();
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let nonce = take(&mut envelope, 8)?.get_u64();
+        let expiry = take(&mut envelope, 8)?.get_u64();
+        let key_len = take(&mut envelope, 2)?.get_u16();
+        let public_key = take(&mut envelope, key_len as usize)?;
+        let signature_len = take(&mut envelope, 2)?.get_u16();
+        let signature = take(&mut envelope, signature_len as usize)?;
+        let payload = envelope;
+
+        let public_key =
+            PublicKey::try_decode_protobuf(&public_key).map_err(|_| Error::InvalidPublicKey)?;
+
+        let signer = public_key.to_peer_id();
+        if signer != *sender {
+            return Err(Error::WrongSigner { signer });
+        }
+
+        let now = unix_millis(now);
+        let skew = millis(MAX_CLOCK_SKEW);
+
+        if expiry.saturating_add(skew) < now {
+            return Err(Error::Expired);
+        }
+
+        // Bounds the time for which the nonce must be remembered
+        if expiry
+            > now
+                .saturating_add(millis(self.max_age))
+                .saturating_add(skew)
+        {
+            return Err(Error::ExpiryTooFar);
+        }
+
+        let message = signed_bytes(&self.protocol, &self.local_peer_id, nonce, expiry, &payload);
+        if !public_key.verify(&message, &signature) {
+            return Err(Error::InvalidSignature);
+        }
+
+        if !self
+            .seen
+            .insert(signer, nonce, expiry.saturating_add(skew), now)
+        {
+            return Err(Error::Replayed);
+        }
+
+        Ok(payload)
+    }
+}
+
+/// Seal a message with the given authenticator, if any, otherwise return it as is.
+pub fn seal(
+    authenticator: Option<&mut Authenticator>,
+    recipient: &PeerId,
+    payload: Bytes,
+) -> Result<Bytes, Error> {
+    match authenticator {
+        Some(authenticator) => authenticator.seal(recipient, payload),
+        None => Ok(payload),
+    }
+}
+
+/// Open an envelope with the given authenticator, if any, otherwise return the message as is.
+pub fn open(
+    authenticator: Option<&mut Authenticator>,
+    sender: &PeerId,
+    envelope: Bytes,
+) -> Result<Bytes, Error> {
+    match authenticator {
+        Some(authenticator) => authenticator.open(sender, envelope),
+        None => Ok(envelope),
+    }
+}
+
+/// Nonces of the accepted envelopes, remembered until the envelopes expire.
+#[derive(Default)]
+struct SeenNonces {
+    expiries: HashMap<(PeerId, u64), u64>,
+    order: VecDeque<(PeerId, u64)>,
+}
+
+impl SeenNonces {
+    /// Remember the nonce of the given sender until the given time.
+    /// Returns `false` if the nonce was already seen.
+    fn insert(&mut self, sender: PeerId, nonce: u64, until: u64, now: u64) -> bool {
+        self.prune(now);
+
+        let key = (sender, nonce);
+        if self.expiries.contains_key(&key) {
+            return false;
+        }
+
+        if self.order.len() >= MAX_SEEN_NONCES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.expiries.remove(&oldest);
+            }
+        }
+
+        self.expiries.insert(key, until);
+        self.order.push_back(key);
+        true
+    }
+
+    /// Forget the oldest nonces, as long as their envelopes have expired.
+    fn prune(&mut self, now: u64) {
+        while let Some(key) = self.order.front() {
+            if self.expiries.get(key).is_some_and(|until| *until >= now) {
+                break;
+            }
+
+            if let Some(key) = self.order.pop_front() {
+                self.expiries.remove(&key);
+            }
+        }
+    }
+}
+
+/// The bytes covered by the signature of an envelope.
+fn signed_bytes(
+    protocol: &str,
+    recipient: &PeerId,
+    nonce: u64,
+    expiry: u64,
+    payload: &[u8],
+) -> Vec<u8> {
+    let recipient = recipient.to_bytes();
+
+    let mut bytes =
+        Vec::with_capacity(DOMAIN.len() + protocol.len() + recipient.len() + 24 + payload.len());
+
+    bytes.extend_from_slice(DOMAIN);
+    bytes.put_u32(protocol.len() as u32);
+    bytes.extend_from_slice(protocol.as_bytes());
+    bytes.put_u32(recipient.len() as u32);
+    bytes.extend_from_slice(&recipient);
+    bytes.put_u64(nonce);
+    bytes.put_u64(expiry);
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// Split the first `len` bytes off the given buffer.
+fn take(bytes: &mut Bytes, len: usize) -> Result<Bytes, Error> {
+    if bytes.len() < len {
+        return Err(Error::Malformed);
+    }
+
+    Ok(bytes.split_to(len))
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    millis(time.duration_since(UNIX_EPOCH).unwrap_or_default())
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROTOCOL: &str = "/malachitebft-sync/v1beta1";
+    const MAX_AGE: Duration = Duration::from_secs(30);
+
+    fn authenticator() -> Authenticator {
+        Authenticator::new(Keypair::generate_ed25519(), PROTOCOL, MAX_AGE)
+    }
+
+    #[test]
+    fn open_returns_sealed_payload() {
+        let mut alice = authenticator();
+        let mut bob = authenticator();
+
+        let envelope = alice
+            .seal(&bob.local_peer_id, Bytes::from_static(b"request"))
+            .unwrap();
+
+        let payload = bob.open(&alice.local_peer_id, envelope).unwrap();
+        assert_eq!(payload.as_ref(), b"request");
+    }
+
+    #[test]
+    fn replayed_envelope_is_rejected() {
+        let mut alice = authenticator();
+        let mut bob = authenticator();
+
+        let envelope = alice
+            .seal(&bob.local_peer_id, Bytes::from_static(b"request"))
+            .unwrap();
+
+        assert!(bob.open(&alice.local_peer_id, envelope.clone()).is_ok());
+        assert_eq!(
+            bob.open(&alice.local_peer_id, envelope),
+            Err(Error::Replayed)
+        );
+
+        // A new envelope for the same payload uses a new nonce
+        let envelope = alice
+            .seal(&bob.local_peer_id, Bytes::from_static(b"request"))
+            .unwrap();
+        assert!(bob.open(&alice.local_peer_id, envelope).is_ok());
+    }
+
+    #[test]
+    fn envelope_relayed_by_another_peer_is_rejected() {
+        let mut alice = authenticator();
+        let mut bob = authenticator();
+        let mallory = authenticator();
+
+        let envelope = alice
+            .seal(&bob.local_peer_id, Bytes::from_static(b"request"))
+            .unwrap();
+
+        assert_eq!(
+            bob.open(&mallory.local_peer_id, envelope),
+            Err(Error::WrongSigner {
+                signer: alice.local_peer_id
+            })
+        );
+    }
+
+    #[test]
+    fn envelope_for_another_recipient_is_rejected() {
+        let mut alice = authenticator();
+        let mut bob = authenticator();
+        let carol = authenticator();
+
+        let envelope = alice
+            .seal(&carol.local_peer_id, Bytes::from_static(b"request"))
+            .unwrap();
+
+        assert_eq!(
+            bob.open(&alice.local_peer_id, envelope),
+            Err(Error::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn envelope_for_another_protocol_is_rejected() {
+        let keypair = Keypair::generate_ed25519();
+        let mut alice = Authenticator::new(keypair, "/malachitebft-validator-proof/v1", MAX_AGE);
+        let mut bob = authenticator();
+
+        let envelope = alice
+            .seal(&bob.local_peer_id, Bytes::from_static(b"proof"))
+            .unwrap();
+
+        assert_eq!(
+            bob.open(&alice.local_peer_id, envelope),
+            Err(Error::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn tampered_payload_is_rejected() {
+        let mut alice = authenticator();
+        let mut bob = authenticator();
+
+        let envelope = alice
+            .seal(&bob.local_peer_id, Bytes::from_static(b"request"))
+            .unwrap();
+
+        let mut tampered = envelope.to_vec();
+        *tampered.last_mut().unwrap() ^= 1;
+
+        assert_eq!(
+            bob.open(&alice.local_peer_id, Bytes::from(tampered)),
+            Err(Error::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn expired_envelope_is_rejected() {
+        let mut alice = authenticator();
+        let mut bob = authenticator();
+
+        let sealed_at = SystemTime::now() - MAX_AGE - MAX_CLOCK_SKEW - Duration::from_secs(1);
+        let envelope = alice
+            .seal_at(
+                &bob.local_peer_id,
+                Bytes::from_static(b"request"),
+                sealed_at,
+            )
+            .unwrap();
+
+        assert_eq!(
+            bob.open(&alice.local_peer_id, envelope),
+            Err(Error::Expired)
+        );
+    }
+
+    #[test]
+    fn envelope_expiring_too_late_is_rejected() {
+        let mut alice = authenticator();
+        let mut bob = authenticator();
+
+        let sealed_at = SystemTime::now() + MAX_CLOCK_SKEW + Duration::from_secs(1);
+        let envelope = alice
+            .seal_at(
+                &bob.local_peer_id,
+                Bytes::from_static(b"request"),
+                sealed_at,
+            )
+            .unwrap();
+
+        assert_eq!(
+            bob.open(&alice.local_peer_id, envelope),
+            Err(Error::ExpiryTooFar)
+        );
+    }
+
+    #[test]
+    fn unsigned_message_is_rejected() {
+        let mut bob = authenticator();
+        let alice = PeerId::random();
+
+        assert_eq!(
+            bob.open(&alice, Bytes::from_static(b"request")),
+            Err(Error::UnsupportedVersion(b'r'))
+        );
+        assert_eq!(
+            bob.open(&alice, Bytes::from_static(&[VERSION, 0, 0])),
+            Err(Error::Malformed)
+        );
+    }
+
+    #[test]
+    fn seen_nonces_are_forgotten_once_expired() {
+        let mut seen = SeenNonces::default();
+        let peer = PeerId::random();
+
+        assert!(seen.insert(peer, 1, 100, 0));
+        assert!(!seen.insert(peer, 1, 100, 50));
+        assert!(seen.insert(PeerId::random(), 1, 100, 50));
+
+        assert!(seen.insert(peer, 2, 200, 150));
+        assert!(!seen.expiries.contains_key(&(peer, 1)));
+        assert_eq!(seen.order.len(), 1);
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bytes::Bytes;
 use libp2p::request_response::{InboundRequestId, OutboundRequestId};
 use tokio::sync::{mpsc, oneshot};
@@ -35,6 +37,10 @@ impl CtrlHandle {
         self.peer_id
     }
 
+    pub(crate) fn tx_ctrl(&self) -> &mpsc::Sender<CtrlMsg> {
+        &self.tx_ctrl
+    }
+
     pub async fn publish(&self, channel: Channel, data: Bytes) -> Result<(), eyre::Report> {
         self.tx_ctrl.send(CtrlMsg::Publish(channel, data)).await?;
         Ok(())
@@ -70,6 +76,24 @@ impl CtrlHandle {
         Ok(())
     }
 
+    pub async fn part_request(&self, peer_id: PeerId, data: Bytes) -> Result<(), eyre::Report> {
+        self.tx_ctrl
+            .send(CtrlMsg::PartRequest(peer_id, data))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn part_reply(
+        &self,
+        request_id: InboundRequestId,
+        data: Bytes,
+    ) -> Result<(), eyre::Report> {
+        self.tx_ctrl
+            .send(CtrlMsg::PartReply(request_id, data))
+            .await?;
+        Ok(())
+    }
+
     pub async fn update_validator_set(
         &self,
         validators: Vec<crate::ValidatorInfo>,
@@ -80,6 +104,15 @@ impl CtrlHandle {
         Ok(())
     }
 
+    /// Update the earliest height for which this node retains decided values,
+    /// advertised to peers in the node info.
+    pub async fn update_history_min_height(&self, height: u64) -> Result<(), eyre::Report> {
+        self.tx_ctrl
+            .send(CtrlMsg::UpdateHistoryMinHeight(height))
+            .await?;
+        Ok(())
+    }
+
     /// Send a validator proof verification result.
     /// If result is Valid, provide the public_key to store the proof.
     pub async fn validator_proof_verified(
@@ -106,6 +139,14 @@ impl CtrlHandle {
         Ok(rx.await?)
     }
 
+    pub async fn discovery_state(&self) -> Result<crate::DiscoveryStats, eyre::Report> {
+        let (tx, rx) = oneshot::channel();
+
+        self.tx_ctrl.send(CtrlMsg::GetDiscoveryState(tx)).await?;
+
+        Ok(rx.await?)
+    }
+
     pub async fn add_persistent_peer(
         &self,
         addr: Multiaddr,
@@ -122,6 +163,23 @@ impl CtrlHandle {
         Ok(rx.await?)
     }
 
+    /// Disconnect a peer and refuse connections from it for the given duration.
+    ///
+    /// Returns whether the peer was banned, persistent peers are never banned.
+    pub async fn ban_peer(
+        &self,
+        peer_id: PeerId,
+        duration: Duration,
+    ) -> Result<bool, eyre::Report> {
+        let (tx, rx) = oneshot::channel();
+
+        self.tx_ctrl
+            .send(CtrlMsg::BanPeer(peer_id, duration, tx))
+            .await?;
+
+        Ok(rx.await?)
+    }
+
     pub async fn remove_persistent_peer(
         &self,
         addr: Multiaddr,
@@ -205,6 +263,10 @@ impl Handle {
         self.ctrl.remove_persistent_peer(addr).await
     }
 
+    pub async fn discovery_state(&self) -> Result<crate::DiscoveryStats, eyre::Report> {
+        self.ctrl.discovery_state().await
+    }
+
     pub async fn wait_shutdown(self) -> Result<(), eyre::Report> {
         self.ctrl.wait_shutdown().await
     }
@@ -1,22 +1,27 @@
 use std::error::Error;
 use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
 use futures::StreamExt;
 use itertools::Itertools;
+use libp2p::core::upgrade;
 use libp2p::metrics::{Metrics, Recorder};
+use libp2p::multiaddr::Protocol;
 use libp2p::request_response::{InboundRequestId, OutboundRequestId};
 use libp2p::swarm::{self, SwarmEvent};
-use libp2p::{gossipsub, identify, quic, SwarmBuilder};
+use libp2p::{autonat, gossipsub, identify, mdns, quic, relay, SwarmBuilder, Transport};
 use libp2p_broadcast as broadcast;
 use tokio::sync::{mpsc, oneshot};
+use tokio_util::compat::TokioAsyncReadCompatExt;
 use tracing::{debug, error, error_span, info, trace, warn, Instrument};
 
 use malachitebft_discovery::{self as discovery};
 use malachitebft_metrics::SharedRegistry;
 use malachitebft_sync::{self as sync};
 
-pub use malachitebft_peer::PeerId;
+pub use malachitebft_peer::{PeerExit, PeerId, PeerState};
 
 pub use bytes::Bytes;
 pub use libp2p::gossipsub::MessageId;
@@ -24,7 +29,9 @@ pub use libp2p::identity::Keypair;
 pub use libp2p::Multiaddr;
 
 pub mod behaviour;
+pub mod envelope;
 pub mod handle;
+pub mod mux;
 pub mod pubsub;
 
 mod channel;
@@ -38,11 +45,25 @@ pub use peer_type::PeerType;
 
 pub mod peer_scoring;
 
+pub mod proposal_parts;
+
 mod utils;
 
 mod ip_limits;
 pub mod validator_proof;
 
+pub mod node_info;
+use node_info::NodeInfo;
+
+pub mod peer_filter;
+use peer_filter::PeerFilter;
+
+pub mod protocol_version;
+pub use protocol_version::ProtocolVersion;
+
+mod transport;
+pub use transport::ConnectionTimeout;
+
 // Re-export state types for external use (e.g., RPC)
 pub use state::{LocalNodeInfo, PeerInfo, ValidatorInfo};
 
@@ -63,6 +84,8 @@ pub struct ProtocolNames {
     pub discovery_regres: String,
     pub sync: String,
     pub validator_proof: String,
+    pub proposal_parts: String,
+    pub node_info: String,
 }
 
 impl Default for ProtocolNames {
@@ -73,6 +96,8 @@ impl Default for ProtocolNames {
             discovery_regres: "/malachitebft-discovery/reqres/v1beta1".to_string(),
             sync: "/malachitebft-sync/v1beta1".to_string(),
             validator_proof: "/malachitebft-validator-proof/v1".to_string(),
+            proposal_parts: "/malachitebft-proposal-parts/v1beta1".to_string(),
+            node_info: "/malachitebft-node-info/v1".to_string(),
         }
     }
 }
@@ -106,6 +131,7 @@ pub struct GossipSubConfig {
     pub enable_peer_scoring: bool,
     pub enable_explicit_peering: bool,
     pub enable_flood_publish: bool,
+    pub scoring: GossipSubScoringConfig,
 }
 
 impl Default for GossipSubConfig {
@@ -119,6 +145,55 @@ impl Default for GossipSubConfig {
             enable_peer_scoring: false,
             enable_explicit_peering: false,
             enable_flood_publish: true,
+            scoring: GossipSubScoringConfig::default(),
+        }
+    }
+}
+
+/// Behavioural peer scoring parameters for GossipSub, used when peer scoring is enabled
+#[derive(Copy, Clone, Debug)]
+pub struct GossipSubScoringConfig {
+    /// Weight of the consensus topic in the peer score
+    pub consensus_topic_weight: f64,
+    /// Weight of the proposal parts topic in the peer score
+    pub proposal_parts_topic_weight: f64,
+    /// Weight of the liveness topic in the peer score
+    pub liveness_topic_weight: f64,
+    /// Penalty for invalid messages, applied to the square of the number of invalid messages
+    pub invalid_message_penalty: f64,
+    /// Time after which the invalid message penalties have decayed away
+    pub invalid_message_decay: Duration,
+    /// Penalty for mesh maintenance misbehaviour, applied to the square of the number of offences
+    pub behaviour_penalty: f64,
+    /// Time after which the behaviour penalties have decayed away
+    pub behaviour_penalty_decay: Duration,
+    /// How long a pruned peer must wait before grafting again
+    pub prune_backoff: Duration,
+    /// Grafts received within this time after a prune are penalized as floods
+    pub graft_flood_threshold: Duration,
+    /// Peers below this score do not receive gossip
+    pub gossip_threshold: f64,
+    /// Messages published by peers below this score are not forwarded
+    pub publish_threshold: f64,
+    /// Peers below this score are ignored entirely
+    pub graylist_threshold: f64,
+}
+
+impl Default for GossipSubScoringConfig {
+    fn default() -> Self {
+        Self {
+            consensus_topic_weight: 1.0,
+            proposal_parts_topic_weight: 1.0,
+            liveness_topic_weight: 0.5,
+            invalid_message_penalty: 100.0,
+            invalid_message_decay: Duration::from_secs(10 * 60),
+            behaviour_penalty: 10.0,
+            behaviour_penalty_decay: Duration::from_secs(60),
+            prune_backoff: Duration::from_secs(60),
+            graft_flood_threshold: Duration::from_secs(10),
+            gossip_threshold: -500.0,
+            publish_threshold: -1000.0,
+            graylist_threshold: -2000.0,
         }
     }
 }
@@ -126,9 +201,12 @@ impl Default for GossipSubConfig {
 pub type BoxError = Box<dyn Error + Send + Sync + 'static>;
 
 pub type DiscoveryConfig = discovery::Config;
+pub type SyncCompressionConfig = sync::CompressionConfig;
 pub type BootstrapProtocol = discovery::config::BootstrapProtocol;
 pub type Selector = discovery::config::Selector;
 
+pub use discovery::{BootstrapStatus, ConnectionStats, DiscoveryStats, KBucketStats};
+
 /// Node identity bundling all node-specific information.
 ///
 /// The consensus address is derived from the keypair in the current implementation
@@ -209,19 +287,90 @@ impl NetworkIdentity {
 #[derive(Clone, Debug)]
 pub struct Config {
     pub listen_addr: Multiaddr,
+    /// Addresses listened on in addition to `listen_addr`, with the same transport
+    pub additional_listen_addrs: Vec<Multiaddr>,
+    /// Addresses advertised to peers instead of the listen addresses, unless empty
+    pub advertise_addrs: Vec<Multiaddr>,
     pub persistent_peers: Vec<Multiaddr>,
+    /// DNS seeds dialed when discovery cannot reach the persistent peers
+    pub dns_seeds: Vec<Multiaddr>,
+    /// File the peers which served the node well are persisted to, to be preferred
+    /// when selecting the outbound peers after a restart
+    pub preferred_peers_file: Option<PathBuf>,
     pub persistent_peers_only: bool,
     pub discovery: DiscoveryConfig,
     pub idle_connection_timeout: Duration,
+    /// Time allowed to establish the transport connection to a peer, eg. the TCP handshake
+    pub dial_timeout: Duration,
+    /// Time allowed to negotiate the security and multiplexing protocols on a new connection.
+    /// Outbound connections must be dialed and upgraded within the sum of both timeouts.
+    pub upgrade_timeout: Duration,
+    /// Time allowed to peers to complete the identify handshake once connected,
+    /// after which their connection is closed
+    pub handshake_timeout: Duration,
     pub transport: TransportProtocol,
     pub gossipsub: GossipSubConfig,
     pub pubsub_protocol: PubSubProtocol,
+    /// Publish the votes on a topic reserved to the validators, see [`Channel::Votes`].
+    /// Only the nodes which are validators subscribe to it, and the votes received on it
+    /// are only accepted from the peers whose validator proof was verified.
+    pub selective_gossip: bool,
     pub channel_names: ChannelNames,
     pub rpc_max_size: usize,
     pub pubsub_max_size: usize,
     pub enable_consensus: bool,
     pub enable_sync: bool,
+    /// Broadcast and receive the announcements of the decided values, see [`Channel::Announcements`].
+    /// Only effective when sync is enabled.
+    pub enable_announcements: bool,
+    /// Compression of the sync responses sent to peers which support it, disabled if `None`
+    pub sync_compression: Option<SyncCompressionConfig>,
     pub protocol_names: ProtocolNames,
+    /// Version of the protocol spoken by this node, advertised to peers through identify
+    pub protocol_version: ProtocolVersion,
+    /// Minimum version of the protocol that peers must speak, see the [`protocol_version`] module
+    pub min_protocol_version: Option<ProtocolVersion>,
+    /// Chain the node takes part in, exchanged with peers in the [`node_info`] handshake
+    /// to disconnect from the peers on another chain
+    pub chain_id: Option<String>,
+    pub nat: NatConfig,
+    pub rpc_signing: RpcSigningConfig,
+    /// Filter deciding which peers may connect to the node, all peers are allowed if `None`,
+    /// see the [`peer_filter`] module
+    pub peer_filter: Option<Arc<dyn PeerFilter>>,
+}
+
+/// NAT traversal options
+#[derive(Clone, Debug, Default)]
+pub struct NatConfig {
+    /// Probe the public reachability of the node with AutoNAT
+    /// and advertise its external addresses once confirmed
+    pub autonat: bool,
+    /// Relays to listen on for relayed connections, which are then
+    /// upgraded to direct connections through hole punching (DCUtR)
+    pub relay: Vec<Multiaddr>,
+}
+
+/// Signing of the messages of the request-response protocols with the node key,
+/// see the [`envelope`] module
+#[derive(Clone, Debug)]
+pub struct RpcSigningConfig {
+    /// Sign the sync requests and responses, and reject unsigned ones
+    pub sync: bool,
+    /// Sign the validator proofs, and reject unsigned ones
+    pub validator_proof: bool,
+    /// Time after which a signed message expires
+    pub max_age: Duration,
+}
+
+impl Default for RpcSigningConfig {
+    fn default() -> Self {
+        Self {
+            sync: false,
+            validator_proof: false,
+            max_age: Duration::from_secs(30),
+        }
+    }
 }
 
 impl Config {
@@ -229,6 +378,11 @@ impl Config {
         cfg.with_idle_connection_timeout(self.idle_connection_timeout)
     }
 
+    /// Time allowed to dial and upgrade outbound connections, for all the transports
+    fn connection_timeout(&self) -> Duration {
+        self.dial_timeout + self.upgrade_timeout
+    }
+
     fn apply_to_quic(&self, mut cfg: quic::Config) -> quic::Config {
         // NOTE: This is set low due to quic transport not properly resetting
         // connection state when reconnecting before connection timeout.
@@ -243,6 +397,8 @@ impl Config {
 pub enum TransportProtocol {
     Tcp,
     Quic,
+    /// Unix domain sockets, for nodes running on the same host
+    Unix,
 }
 
 impl TransportProtocol {
@@ -251,6 +407,7 @@ impl TransportProtocol {
             match protocol {
                 "tcp" => return Some(TransportProtocol::Tcp),
                 "quic" | "quic-v1" => return Some(TransportProtocol::Quic),
+                "unix" => return Some(TransportProtocol::Unix),
                 _ => {}
             }
         }
@@ -296,16 +453,21 @@ pub enum PersistentPeerError {
 #[derive(Clone, Debug)]
 pub enum Event {
     Listening(Multiaddr),
-    PeerConnected(PeerId),
+    /// A peer using a compatible protocol connected, with the address of the connection to it
+    PeerConnected(PeerId, Multiaddr),
     PeerDisconnected(PeerId),
     ConsensusMessage(Channel, PeerId, Bytes),
     LivenessMessage(Channel, PeerId, Bytes),
     Sync(sync::RawMessage),
+    /// A request for a missing proposal part, or the response to such a request
+    ProposalParts(sync::RawMessage),
     /// A validator proof received from a peer (one-way, no response expected).
     ValidatorProofReceived {
         peer_id: PeerId,
         proof_bytes: Bytes,
     },
+    /// A peer exceeded the rate limit for discovery requests.
+    RateLimitViolation(PeerId),
 }
 
 #[derive(Debug)]
@@ -314,7 +476,16 @@ pub enum CtrlMsg {
     Broadcast(Channel, Bytes),
     SyncRequest(PeerId, Bytes, oneshot::Sender<OutboundRequestId>),
     SyncReply(InboundRequestId, Bytes),
+    /// Reject a Sync request without replying to it, making it fail on the requester side
+    RejectSyncRequest(InboundRequestId),
+    /// Request a missing proposal part from a peer
+    PartRequest(PeerId, Bytes),
+    /// Reply to a request for a proposal part
+    PartReply(InboundRequestId, Bytes),
     UpdateValidatorSet(Vec<ValidatorInfo>),
+    /// Update the earliest height for which this node retains decided values,
+    /// advertised to peers in the node info
+    UpdateHistoryMinHeight(u64),
     /// Validator proof verification result. If Valid, public_key should be Some.
     /// The public_key is stored and used to check validator set membership.
     ValidatorProofVerified {
@@ -323,10 +494,14 @@ pub enum CtrlMsg {
         public_key: Option<Vec<u8>>,
     },
     DumpState(oneshot::Sender<NetworkStateDump>),
+    GetDiscoveryState(oneshot::Sender<DiscoveryStats>),
     UpdatePersistentPeers(
         PersistentPeersOp,
         oneshot::Sender<Result<(), PersistentPeerError>>,
     ),
+    /// Disconnect a peer and refuse connections from it for the given duration.
+    /// Replies with whether the peer was banned, persistent peers are never banned.
+    BanPeer(PeerId, Duration, oneshot::Sender<bool>),
     Shutdown,
 }
 
@@ -335,37 +510,73 @@ pub async fn spawn(
     config: Config,
     registry: SharedRegistry,
 ) -> Result<Handle, eyre::Report> {
+    let network_metrics = registry.with_prefix(METRICS_PREFIX, NetworkMetrics::new);
+    let connection_timeouts = network_metrics.connection_timeouts();
+
     let mut swarm =
         registry.with_prefix(METRICS_PREFIX, |registry| -> Result<_, eyre::Report> {
             // Pass the libp2p keypair to the behaviour, it is included in the Identify protocol
             // Required for ALL nodes
             let builder =
                 SwarmBuilder::with_existing_identity(identity.keypair.clone()).with_tokio();
+            // The relay client transport is always set up, but its behaviour is only
+            // enabled if relays are configured, see `Behaviour::new_with_metrics`
             match config.transport {
-                TransportProtocol::Tcp => {
-                    let behaviour = Behaviour::new_with_metrics(&config, &identity, registry)?;
-                    Ok(builder
-                        .with_tcp(
-                            libp2p::tcp::Config::new().nodelay(true), // Disable Nagle's algorithm
-                            libp2p::noise::Config::new,
-                            libp2p::yamux::Config::default,
-                        )?
-                        .with_dns()?
-                        .with_bandwidth_metrics(registry)
-                        .with_behaviour(|_| behaviour)?
-                        .with_swarm_config(|cfg| config.apply_to_swarm(cfg))
-                        .build())
-                }
-                TransportProtocol::Quic => {
-                    let behaviour = Behaviour::new_with_metrics(&config, &identity, registry)?;
-                    Ok(builder
-                        .with_quic_config(|cfg| config.apply_to_quic(cfg))
-                        .with_dns()?
-                        .with_bandwidth_metrics(registry)
-                        .with_behaviour(|_| behaviour)?
-                        .with_swarm_config(|cfg| config.apply_to_swarm(cfg))
-                        .build())
-                }
+                TransportProtocol::Tcp => Ok(builder
+                    .with_other_transport(|keypair| -> Result<_, BoxError> {
+                        Ok(transport::upgrade(
+                            libp2p::tcp::tokio::Transport::new(
+                                libp2p::tcp::Config::new().nodelay(true), // Disable Nagle's algorithm
+                            ),
+                            keypair,
+                            upgrade::Version::V1Lazy,
+                            config.dial_timeout,
+                            config.upgrade_timeout,
+                            connection_timeouts.clone(),
+                        )?)
+                    })?
+                    .with_dns()?
+                    .with_relay_client(libp2p::noise::Config::new, libp2p::yamux::Config::default)?
+                    .with_bandwidth_metrics(registry)
+                    .with_behaviour(|_, relay_client| {
+                        Behaviour::new_with_metrics(&config, &identity, relay_client, registry)
+                            .map_err(BoxError::from)
+                    })?
+                    .with_swarm_config(|cfg| config.apply_to_swarm(cfg))
+                    .with_connection_timeout(config.connection_timeout())
+                    .build()),
+                TransportProtocol::Quic => Ok(builder
+                    .with_quic_config(|cfg| config.apply_to_quic(cfg))
+                    .with_dns()?
+                    .with_relay_client(libp2p::noise::Config::new, libp2p::yamux::Config::default)?
+                    .with_bandwidth_metrics(registry)
+                    .with_behaviour(|_, relay_client| {
+                        Behaviour::new_with_metrics(&config, &identity, relay_client, registry)
+                            .map_err(BoxError::from)
+                    })?
+                    .with_swarm_config(|cfg| config.apply_to_swarm(cfg))
+                    .with_connection_timeout(config.connection_timeout())
+                    .build()),
+                TransportProtocol::Unix => Ok(builder
+                    .with_other_transport(|keypair| -> Result<_, BoxError> {
+                        Ok(transport::upgrade(
+                            transport::UnixTransport::default().map(|stream, _| stream.compat()),
+                            keypair,
+                            upgrade::Version::V1,
+                            config.dial_timeout,
+                            config.upgrade_timeout,
+                            connection_timeouts.clone(),
+                        )?)
+                    })?
+                    .with_relay_client(libp2p::noise::Config::new, libp2p::yamux::Config::default)?
+                    .with_bandwidth_metrics(registry)
+                    .with_behaviour(|_, relay_client| {
+                        Behaviour::new_with_metrics(&config, &identity, relay_client, registry)
+                            .map_err(BoxError::from)
+                    })?
+                    .with_swarm_config(|cfg| config.apply_to_swarm(cfg))
+                    .with_connection_timeout(config.connection_timeout())
+                    .build()),
             }
         })?;
 
@@ -375,11 +586,15 @@ pub async fn spawn(
     let (tx_ctrl, rx_ctrl) = mpsc::channel(32);
 
     let discovery = registry.with_prefix(DISCOVERY_METRICS_PREFIX, |reg| {
-        discovery::Discovery::new(config.discovery, config.persistent_peers.clone(), reg)
+        discovery::Discovery::new(
+            config.discovery,
+            config.persistent_peers.clone(),
+            config.dns_seeds.clone(),
+            reg,
+        )
+        .with_preferred_peers_file(config.preferred_peers_file.clone())
     });
 
-    let network_metrics = registry.with_prefix(METRICS_PREFIX, NetworkMetrics::new);
-
     let peer_id = PeerId::from_libp2p(swarm.local_peer_id());
 
     // Create local node info with subscribed consensus topics
@@ -392,7 +607,7 @@ pub async fn spawn(
 
     let NetworkIdentity {
         moniker,
-        keypair: _,
+        keypair,
         validator,
     } = identity;
 
@@ -406,6 +621,27 @@ pub async fn spawn(
         }
     }
 
+    // Set the info exchanged with peers on connection, kept up to date as the node runs
+    if let Some(ni) = swarm.behaviour_mut().node_info.as_mut() {
+        let mut protocols = vec![
+            config.protocol_names.consensus.clone(),
+            config.protocol_names.proposal_parts.clone(),
+            config.protocol_names.validator_proof.clone(),
+        ];
+        if config.enable_sync {
+            protocols.push(config.protocol_names.sync.clone());
+        }
+
+        ni.set_info(NodeInfo {
+            moniker: moniker.clone(),
+            chain_id: config.chain_id.clone(),
+            protocol_version: config.protocol_version,
+            protocols,
+            is_validator: false, // Will be updated when validator set is received
+            history_min_height: None,
+        });
+    }
+
     // Create local node info
     let local_node_info = LocalNodeInfo {
         moniker,
@@ -421,13 +657,22 @@ pub async fn spawn(
     // Set local node info in metrics
     network_metrics.set_local_node_info(&local_node_info);
 
-    let state = State::new(
+    let mut state = State::new(
         discovery,
         config.persistent_peers.clone(),
         local_node_info,
         network_metrics,
     );
 
+    // Sign the sync requests and responses with the node key, if configured
+    if config.rpc_signing.sync {
+        state.sync_envelopes = Some(envelope::Authenticator::new(
+            keypair,
+            config.protocol_names.sync.clone(),
+            config.rpc_signing.max_age,
+        ));
+    }
+
     let span = error_span!("network");
 
     info!(parent: span.clone(), %peer_id, "Starting network service");
@@ -454,6 +699,27 @@ async fn run(
         return;
     }
 
+    for listen_addr in &config.additional_listen_addrs {
+        if let Err(e) = swarm.listen_on(listen_addr.clone()) {
+            error!("Error listening on {listen_addr}: {e}");
+            return;
+        }
+    }
+
+    // Advertise the configured addresses through identify, in place of the listen addresses
+    for advertise_addr in &config.advertise_addrs {
+        swarm.add_external_address(advertise_addr.clone());
+    }
+
+    // Reserve a slot on each relay, to be reachable through it from behind a NAT
+    for relay_addr in &config.nat.relay {
+        let circuit_addr = relay_addr.clone().with(Protocol::P2pCircuit);
+
+        if let Err(e) = swarm.listen_on(circuit_addr.clone()) {
+            error!("Error listening on relay circuit {circuit_addr}: {e}");
+        }
+    }
+
     if config.enable_consensus {
         if let Err(e) = pubsub::subscribe(
             &mut swarm,
@@ -478,6 +744,21 @@ async fn run(
         };
     }
 
+    if config.enable_sync && config.enable_announcements {
+        if let Err(e) = pubsub::subscribe(
+            &mut swarm,
+            PubSubProtocol::Broadcast,
+            &[Channel::Announcements],
+            config.channel_names,
+        ) {
+            error!("Error subscribing to Announcements channel: {e}");
+            return;
+        };
+    }
+
+    // Reconnect to the peers which served the node well before it restarted
+    state.discovery.dial_preferred_peers(&swarm);
+
     // Timer to perform periodic network operations (peer reconnection, metrics updates, etc.)
     // TODO: Using 1 second for now, for faster reconnection during testing
     // Maybe adjust via config in the future
@@ -515,9 +796,18 @@ async fn run(
             }
 
             _ = periodic_timer.tick() => {
+                // Lift expired peer bans
+                state.prune_expired_bans();
+
+                // Close the connections of the peers which did not complete the handshake in time
+                close_expired_handshakes(&mut swarm, &mut state, config.handshake_timeout);
+
                 // Attempt to dial bootstrap nodes
                 state.discovery.dial_bootstrap_nodes(&swarm);
 
+                // Bootstrap discovery again if it did not find enough peers
+                state.discovery.rebootstrap_if_due(&mut swarm);
+
                 // Update peer info in State and metrics (includes gossipsub scores and mesh membership)
                 if let Some(gossipsub) = swarm.behaviour_mut().gossipsub.as_mut() {
                     state.update_peer_info(
@@ -532,6 +822,10 @@ async fn run(
                     info!("Network peer state\n{}", state.format_peer_info());
                 }
 
+                if periodic_tick_count.is_multiple_of(60) {
+                    state.discovery.save_preferred_peers();
+                }
+
                 ControlFlow::Continue(())
             }
         };
@@ -541,6 +835,8 @@ async fn run(
             ControlFlow::Break(()) => break,
         }
     }
+
+    state.discovery.save_preferred_peers();
 }
 
 async fn handle_ctrl_msg(
@@ -551,6 +847,7 @@ async fn handle_ctrl_msg(
 ) -> ControlFlow<()> {
     match msg {
         CtrlMsg::Publish(channel, data) => {
+            let channel = topic_channel(config, channel);
             let msg_size = data.len();
             let result = pubsub::publish(
                 swarm,
@@ -574,6 +871,13 @@ async fn handle_ctrl_msg(
                 return ControlFlow::Continue(());
             }
 
+            if channel == Channel::Announcements
+                && !(config.enable_sync && config.enable_announcements)
+            {
+                trace!("Ignoring broadcast message to Announcements channel: Announcements not enabled");
+                return ControlFlow::Continue(());
+            }
+
             let msg_size = data.len();
             let result = pubsub::publish(
                 swarm,
@@ -597,6 +901,18 @@ async fn handle_ctrl_msg(
                 return ControlFlow::Continue(());
             };
 
+            let request = match envelope::seal(
+                state.sync_envelopes.as_mut(),
+                &peer_id.to_libp2p(),
+                request,
+            ) {
+                Ok(request) => request,
+                Err(e) => {
+                    error!(%peer_id, "Error signing Sync request: {e}");
+                    return ControlFlow::Continue(());
+                }
+            };
+
             let request_id = sync.send_request(peer_id.to_libp2p(), request);
 
             if let Err(e) = reply_to.send(request_id) {
@@ -612,11 +928,19 @@ async fn handle_ctrl_msg(
                 return ControlFlow::Continue(());
             };
 
-            let Some(channel) = state.sync_channels.remove(&request_id) else {
+            let Some((peer, channel)) = state.sync_channels.remove(&request_id) else {
                 debug!(%request_id, "Received Sync reply for unknown request ID");
                 return ControlFlow::Continue(());
             };
 
+            let data = match envelope::seal(state.sync_envelopes.as_mut(), &peer, data) {
+                Ok(data) => data,
+                Err(e) => {
+                    error!(%request_id, "Error signing Sync response: {e}");
+                    return ControlFlow::Continue(());
+                }
+            };
+
             let result = sync.send_response(channel, data);
 
             match result {
@@ -627,6 +951,47 @@ async fn handle_ctrl_msg(
             ControlFlow::Continue(())
         }
 
+        CtrlMsg::RejectSyncRequest(request_id) => {
+            // Dropping the channel of a rejected request makes it fail on the requester side
+            match state.sync_channels.remove(&request_id) {
+                Some((peer, _)) => debug!(%request_id, %peer, "Rejected Sync request"),
+                None => debug!(%request_id, "Cannot reject Sync request with unknown request ID"),
+            }
+
+            ControlFlow::Continue(())
+        }
+
+        CtrlMsg::PartRequest(peer_id, request) => {
+            let Some(proposal_parts) = swarm.behaviour_mut().proposal_parts.as_mut() else {
+                error!("Cannot request proposal part from peer: Consensus not enabled");
+                return ControlFlow::Continue(());
+            };
+
+            let request_id = proposal_parts.send_request(peer_id.to_libp2p(), request);
+            trace!(%peer_id, %request_id, "Requested proposal part");
+
+            ControlFlow::Continue(())
+        }
+
+        CtrlMsg::PartReply(request_id, data) => {
+            let Some(proposal_parts) = swarm.behaviour_mut().proposal_parts.as_mut() else {
+                error!("Cannot send proposal part to peer: Consensus not enabled");
+                return ControlFlow::Continue(());
+            };
+
+            let Some(channel) = state.part_channels.remove(&request_id) else {
+                debug!(%request_id, "Received proposal part reply for unknown request ID");
+                return ControlFlow::Continue(());
+            };
+
+            match proposal_parts.send_response(channel, data) {
+                Ok(()) => trace!(%request_id, "Replied to proposal part request"),
+                Err(e) => error!(%request_id, "Error replying to proposal part request: {e}"),
+            }
+
+            ControlFlow::Continue(())
+        }
+
         CtrlMsg::UpdateValidatorSet(validators) => {
             // Process the validator set update and get peers that need score updates
             let validator_set = validators.into_iter().collect();
@@ -637,11 +1002,37 @@ async fn handle_ctrl_msg(
                 set_peer_score(swarm, *peer_id, *new_score);
             }
 
+            if config.selective_gossip && config.enable_consensus {
+                update_votes_subscription(swarm, state, config);
+            }
+
             // Promote newly promoted validators from ephemeral to inbound
             for (peer_id, _) in &changed_peers {
                 state.try_prioritize_peer(*peer_id);
             }
 
+            // Ask the peers which did not prove their identity yet for their proof, so that
+            // the validators joining the set are recognized without waiting for a reconnect.
+            // The proofs already received were re-evaluated against the new set above.
+            if let Some(vp) = swarm.behaviour_mut().validator_proof.as_mut() {
+                for peer_id in state.unproven_peers() {
+                    vp.request_proof(peer_id);
+                }
+            }
+
+            let is_validator = state.local_node.is_validator;
+            if let Some(ni) = swarm.behaviour_mut().node_info.as_mut() {
+                ni.update_info(|info| info.is_validator = is_validator);
+            }
+
+            ControlFlow::Continue(())
+        }
+
+        CtrlMsg::UpdateHistoryMinHeight(height) => {
+            if let Some(ni) = swarm.behaviour_mut().node_info.as_mut() {
+                ni.update_info(|info| info.history_min_height = Some(height));
+            }
+
             ControlFlow::Continue(())
         }
 
@@ -690,6 +1081,7 @@ async fn handle_ctrl_msg(
                     .sorted_unstable()
                     .collect(),
                 persistent_peer_addrs: state.persistent_peer_addrs.clone(),
+                peer_node_info: state.peer_node_info.clone(),
             };
 
             if let Err(_s) = reply_to.send(snapshot) {
@@ -699,6 +1091,16 @@ async fn handle_ctrl_msg(
             ControlFlow::Continue(())
         }
 
+        CtrlMsg::GetDiscoveryState(reply_to) => {
+            let snapshot = state.discovery.snapshot(swarm);
+
+            if let Err(_s) = reply_to.send(snapshot) {
+                error!("Error replying to GetDiscoveryState");
+            }
+
+            ControlFlow::Continue(())
+        }
+
         CtrlMsg::UpdatePersistentPeers(op, reply_to) => {
             let result = match op {
                 PersistentPeersOp::Add(addr) => state.add_persistent_peer(addr, swarm),
@@ -710,10 +1112,46 @@ async fn handle_ctrl_msg(
             ControlFlow::Continue(())
         }
 
+        CtrlMsg::BanPeer(peer_id, duration, reply_to) => {
+            let libp2p_peer_id = peer_id.to_libp2p();
+            let banned = state.ban_peer(libp2p_peer_id, duration);
+
+            if banned {
+                warn!(%peer_id, ?duration, "Banning peer");
+                let _ = swarm.disconnect_peer_id(libp2p_peer_id);
+            } else {
+                debug!(%peer_id, "Not banning persistent peer");
+            }
+
+            if reply_to.send(banned).is_err() {
+                error!("Error replying to BanPeer");
+            }
+
+            ControlFlow::Continue(())
+        }
+
         CtrlMsg::Shutdown => ControlFlow::Break(()),
     }
 }
 
+/// Close the connections on which the peer did not complete the identify handshake
+/// within the given timeout, so that they do not hold a connection slot.
+fn close_expired_handshakes(
+    swarm: &mut swarm::Swarm<Behaviour>,
+    state: &mut State,
+    timeout: Duration,
+) {
+    for (peer_id, connection_id) in state.take_expired_handshakes(timeout) {
+        warn!(%peer_id, %connection_id, "Closing connection of peer which did not complete the handshake in time");
+
+        state
+            .metrics
+            .record_connection_timeout(ConnectionTimeout::Handshake);
+
+        let _ = swarm.close_connection(connection_id);
+    }
+}
+
 /// Set a default low score for a peer immediately upon connection
 /// This allows gossipsub to form an initial mesh before Identify completes
 fn set_default_peer_score(swarm: &mut swarm::Swarm<Behaviour>, peer_id: libp2p::PeerId) {
@@ -724,6 +1162,22 @@ fn set_default_peer_score(swarm: &mut swarm::Swarm<Behaviour>, peer_id: libp2p::
     }
 }
 
+/// Refuse the messages of a peer speaking an incompatible version of the protocol,
+/// until it disconnects. The peer is not reported as connected to consensus.
+fn refuse_incompatible_peer(
+    swarm: &mut swarm::Swarm<Behaviour>,
+    state: &mut State,
+    peer_id: libp2p::PeerId,
+) {
+    state.incompatible_peers.insert(peer_id);
+
+    // GossipSub rejects the messages sent or authored by blacklisted peers,
+    // and does not graft them into the mesh
+    if let Some(gossipsub) = swarm.behaviour_mut().gossipsub.as_mut() {
+        gossipsub.blacklist_peer(&peer_id);
+    }
+}
+
 fn set_peer_score(swarm: &mut swarm::Swarm<Behaviour>, peer_id: libp2p::PeerId, score: f64) {
     // Set application-specific score in gossipsub if enabled
     if let Some(gossipsub) = swarm.behaviour_mut().gossipsub.as_mut() {
@@ -733,6 +1187,65 @@ fn set_peer_score(swarm: &mut swarm::Swarm<Behaviour>, peer_id: libp2p::PeerId,
     }
 }
 
+/// Channel on whose topic the messages of the given channel are published,
+/// the votes being published on the consensus topic unless selective gossip is enabled.
+fn topic_channel(config: &Config, channel: Channel) -> Channel {
+    if channel == Channel::Votes && !config.selective_gossip {
+        Channel::Consensus
+    } else {
+        channel
+    }
+}
+
+/// Subscribe to the votes topic when the local node becomes a validator,
+/// and unsubscribe from it when the node is no longer a validator.
+fn update_votes_subscription(
+    swarm: &mut swarm::Swarm<Behaviour>,
+    state: &mut State,
+    config: &Config,
+) {
+    let topic = Channel::Votes.as_str(config.channel_names).to_string();
+    let is_validator = state.local_node.is_validator;
+
+    if state.local_node.subscribed_topics.contains(&topic) == is_validator {
+        return;
+    }
+
+    let result = if is_validator {
+        pubsub::subscribe(
+            swarm,
+            config.pubsub_protocol,
+            &[Channel::Votes],
+            config.channel_names,
+        )
+    } else {
+        pubsub::unsubscribe(
+            swarm,
+            config.pubsub_protocol,
+            &[Channel::Votes],
+            config.channel_names,
+        )
+    };
+
+    if let Err(e) = result {
+        error!("Error updating the subscription to the votes channel: {e}");
+        return;
+    }
+
+    info!(
+        is_validator,
+        "Updated the subscription to the votes channel"
+    );
+
+    if is_validator {
+        state.local_node.subscribed_topics.insert(topic);
+    } else {
+        state.local_node.subscribed_topics.remove(&topic);
+    }
+
+    state.metrics.set_local_node_info(&state.local_node);
+}
+
 /// Add a persistent peer as an explicit peer in gossipsub (if explicit peering is enabled).
 /// A node always sends and forwards messages to its explicit peers, regardless of mesh membership.
 fn add_explicit_peer_to_gossipsub(
@@ -811,6 +1324,12 @@ async fn handle_swarm_event(
         } => {
             trace!("Connected to {peer_id} with connection id {connection_id}");
 
+            if state.is_banned(&peer_id) {
+                debug!(%peer_id, "Refusing connection from banned peer");
+                let _ = swarm.close_connection(connection_id);
+                return ControlFlow::Continue(());
+            }
+
             // Set a low default score immediately for gossipsub mesh formation
             // This will be upgraded later when Identify completes
             if num_established.get() == 1 {
@@ -818,6 +1337,8 @@ async fn handle_swarm_event(
                 set_default_peer_score(swarm, peer_id);
             }
 
+            state.start_handshake(connection_id, peer_id);
+
             state
                 .discovery
                 .handle_connection(swarm, peer_id, connection_id, endpoint);
@@ -852,6 +1373,8 @@ async fn handle_swarm_event(
                 warn!("Connection closed with {peer_id}, reason: unknown");
             }
 
+            state.end_handshake(&connection_id);
+
             state
                 .discovery
                 .handle_closed_connection(swarm, peer_id, connection_id);
@@ -861,11 +1384,21 @@ async fn handle_swarm_event(
                 if config.gossipsub.enable_explicit_peering {
                     remove_explicit_peer_from_gossipsub(swarm, state, &peer_id);
                 }
-                if let Some(peer_info) = state.peer_info.remove(&peer_id) {
-                    state.metrics.free_slot(&peer_id, &peer_info);
+
+                // The peer may come back with an upgraded version of the protocol
+                if state.incompatible_peers.contains(&peer_id) {
+                    if let Some(gossipsub) = swarm.behaviour_mut().gossipsub.as_mut() {
+                        gossipsub.remove_blacklisted_peer(&peer_id);
+                    }
                 }
-                // Also clean up any pending proof (proof verified before Identify completed)
-                state.pending_verified_proofs.remove(&peer_id);
+
+                let exit = if state.is_banned(&peer_id) {
+                    PeerExit::Banned
+                } else {
+                    PeerExit::Disconnected
+                };
+
+                state.forget_peer(&peer_id, exit);
 
                 if let Err(e) = tx_event
                     .send(Event::PeerDisconnected(PeerId::from_libp2p(&peer_id)))
@@ -892,12 +1425,40 @@ async fn handle_swarm_event(
                     info.protocol_version, info.agent_version
                 );
 
+                state.end_handshake(&connection_id);
+
+                if let Some(filter) = &config.peer_filter {
+                    if !filter.is_allowed(&PeerId::from_libp2p(&peer_id), &info.public_key) {
+                        warn!(%peer_id, "Disconnecting from peer which is not allowed to connect");
+                        let _ = swarm.disconnect_peer_id(peer_id);
+                        return ControlFlow::Continue(());
+                    }
+                }
+
                 if info.protocol_version == config.protocol_names.consensus {
                     trace!(
                         "Peer {peer_id} is using compatible protocol version: {:?}",
                         info.protocol_version
                     );
 
+                    let agent_info = utils::parse_agent_version(&info.agent_version);
+
+                    if !config
+                        .protocol_version
+                        .is_compatible(agent_info.protocol_version, config.min_protocol_version)
+                    {
+                        warn!(
+                            %peer_id,
+                            peer_version = ?agent_info.protocol_version,
+                            version = %config.protocol_version,
+                            min_version = ?config.min_protocol_version,
+                            "Ignoring peer with incompatible protocol version, refusing its consensus messages"
+                        );
+
+                        refuse_incompatible_peer(swarm, state, peer_id);
+                        return ControlFlow::Continue(());
+                    }
+
                     let is_already_connected = state.discovery.handle_new_peer(
                         swarm,
                         connection_id,
@@ -918,8 +1479,14 @@ async fn handle_swarm_event(
                     }
 
                     if !is_already_connected {
+                        let address = state
+                            .peer_info
+                            .get(&peer_id)
+                            .map(|peer_info| peer_info.address.clone())
+                            .expect("peer info was just updated");
+
                         if let Err(e) = tx_event
-                            .send(Event::PeerConnected(PeerId::from_libp2p(&peer_id)))
+                            .send(Event::PeerConnected(PeerId::from_libp2p(&peer_id), address))
                             .await
                         {
                             error!("Error sending peer connected event to handle: {e}");
@@ -942,6 +1509,8 @@ async fn handle_swarm_event(
             match &event.result {
                 Ok(rtt) => {
                     trace!("Received pong from {} in {rtt:?}", event.peer);
+
+                    state.discovery.record_latency(&event.peer, *rtt);
                 }
                 Err(e) => {
                     trace!("Received pong from {} with error: {e}", event.peer);
@@ -964,12 +1533,71 @@ async fn handle_swarm_event(
             return handle_sync_event(event, metrics, swarm, state, tx_event).await;
         }
 
+        SwarmEvent::Behaviour(NetworkEvent::ProposalParts(event)) => {
+            return handle_proposal_parts_event(event, state, tx_event).await;
+        }
+
         SwarmEvent::Behaviour(NetworkEvent::ValidatorProof(event)) => {
             return handle_validator_proof_event(event, tx_event).await;
         }
 
+        SwarmEvent::Behaviour(NetworkEvent::NodeInfo(event)) => {
+            return handle_node_info_event(event, swarm, state);
+        }
+
+        SwarmEvent::Behaviour(NetworkEvent::Autonat(event)) => {
+            if let autonat::Event::StatusChanged { old, new } = event {
+                info!(?old, ?new, "NAT status changed");
+            }
+        }
+
+        SwarmEvent::Behaviour(NetworkEvent::RelayClient(event)) => match event {
+            relay::client::Event::ReservationReqAccepted { relay_peer_id, .. } => {
+                info!(%relay_peer_id, "Reservation accepted by relay");
+            }
+            relay::client::Event::OutboundCircuitEstablished { relay_peer_id, .. } => {
+                debug!(%relay_peer_id, "Established outbound circuit through relay");
+            }
+            relay::client::Event::InboundCircuitEstablished { src_peer_id, .. } => {
+                debug!(%src_peer_id, "Established inbound circuit through relay");
+            }
+        },
+
+        SwarmEvent::Behaviour(NetworkEvent::Dcutr(event)) => {
+            match &event.result {
+                Ok(connection_id) => {
+                    info!(peer = %event.remote_peer_id, %connection_id, "Upgraded relayed connection to a direct connection");
+                }
+                Err(e) => {
+                    debug!(peer = %event.remote_peer_id, "Failed to upgrade relayed connection to a direct connection: {e}");
+                }
+            }
+
+            metrics.record(&event);
+        }
+
+        SwarmEvent::Behaviour(NetworkEvent::Mdns(event)) => match event {
+            mdns::Event::Discovered(peers) => {
+                state.discovery.handle_mdns_discovered(swarm, peers);
+            }
+            mdns::Event::Expired(peers) => {
+                for (peer_id, addr) in peers {
+                    debug!(peer = %peer_id, %addr, "mDNS record expired");
+                }
+            }
+        },
+
         SwarmEvent::Behaviour(NetworkEvent::Discovery(network_event)) => {
             state.discovery.on_network_event(swarm, *network_event);
+
+            for peer_id in state.discovery.take_rate_limit_violations() {
+                let peer_id = PeerId::from_libp2p(&peer_id);
+
+                if let Err(e) = tx_event.send(Event::RateLimitViolation(peer_id)).await {
+                    error!("Error sending rate limit violation event to handle: {e}");
+                    return ControlFlow::Break(());
+                }
+            }
         }
 
         swarm_event => {
@@ -985,7 +1613,7 @@ async fn handle_gossipsub_event(
     config: &Config,
     _metrics: &Metrics,
     _swarm: &mut swarm::Swarm<Behaviour>,
-    _state: &mut State,
+    state: &mut State,
     tx_event: &mpsc::Sender<Event>,
 ) -> ControlFlow<()> {
     match event {
@@ -1008,9 +1636,9 @@ async fn handle_gossipsub_event(
         }
 
         gossipsub::Event::Message {
+            propagation_source,
             message_id,
             message,
-            ..
         } => {
             let Some(peer_id) = message.source else {
                 return ControlFlow::Continue(());
@@ -1027,6 +1655,13 @@ async fn handle_gossipsub_event(
                 return ControlFlow::Continue(());
             };
 
+            if channel == Channel::Votes && !state.is_validator_peer(&propagation_source) {
+                debug!(
+                    "Dropping vote {message_id} forwarded by {propagation_source}, which is not a verified validator"
+                );
+                return ControlFlow::Continue(());
+            }
+
             trace!(
                 "Received message {message_id} from {peer_id} on channel {channel} of {} bytes",
                 message.data.len()
@@ -1069,7 +1704,7 @@ async fn handle_broadcast_event(
     config: &Config,
     _metrics: &Metrics,
     _swarm: &mut swarm::Swarm<Behaviour>,
-    _state: &mut State,
+    state: &mut State,
     tx_event: &mpsc::Sender<Event>,
 ) -> ControlFlow<()> {
     match event {
@@ -1092,11 +1727,21 @@ async fn handle_broadcast_event(
         }
 
         broadcast::Event::Received(peer_id, topic, message) => {
+            if state.incompatible_peers.contains(&peer_id) {
+                trace!("Refusing message from {peer_id} with incompatible protocol version");
+                return ControlFlow::Continue(());
+            }
+
             let Some(channel) = Channel::from_broadcast_topic(&topic, config.channel_names) else {
                 trace!("Received message from {peer_id} on different channel: {topic:?}");
                 return ControlFlow::Continue(());
             };
 
+            if channel == Channel::Votes && !state.is_validator_peer(&peer_id) {
+                debug!("Dropping vote from {peer_id}, which is not a verified validator");
+                return ControlFlow::Continue(());
+            }
+
             trace!(
                 "Received message from {peer_id} on channel {channel} of {} bytes",
                 message.len()
@@ -1135,13 +1780,23 @@ async fn handle_sync_event(
                     request,
                     channel,
                 } => {
-                    state.sync_channels.insert(request_id, channel);
+                    // Dropping the channel of a rejected request makes it fail on the requester side
+                    let body = match envelope::open(state.sync_envelopes.as_mut(), &peer, request.0)
+                    {
+                        Ok(body) => body,
+                        Err(e) => {
+                            warn!(%request_id, %peer, "Rejecting Sync request: {e}");
+                            return ControlFlow::Continue(());
+                        }
+                    };
+
+                    state.sync_channels.insert(request_id, (peer, channel));
 
                     let _ = tx_event
                         .send(Event::Sync(sync::RawMessage::Request {
                             request_id,
                             peer: PeerId::from_libp2p(&peer),
-                            body: request.0,
+                            body,
                         }))
                         .await
                         .map_err(|e| {
@@ -1153,11 +1808,21 @@ async fn handle_sync_event(
                     request_id,
                     response,
                 } => {
+                    // The request then times out on our side, as for an unresponsive peer
+                    let body =
+                        match envelope::open(state.sync_envelopes.as_mut(), &peer, response.0) {
+                            Ok(body) => body,
+                            Err(e) => {
+                                warn!(%request_id, %peer, "Rejecting Sync response: {e}");
+                                return ControlFlow::Continue(());
+                            }
+                        };
+
                     let _ = tx_event
                         .send(Event::Sync(sync::RawMessage::Response {
                             request_id,
                             peer: PeerId::from_libp2p(&peer),
-                            body: response.0,
+                            body,
                         }))
                         .await
                         .map_err(|e| {
@@ -1186,6 +1851,61 @@ async fn handle_sync_event(
     }
 }
 
+async fn handle_proposal_parts_event(
+    event: proposal_parts::Event,
+    state: &mut State,
+    tx_event: &mpsc::Sender<Event>,
+) -> ControlFlow<()> {
+    let raw_msg = match event.0 {
+        sync::Event::Message { peer, message, .. } => match message {
+            libp2p::request_response::Message::Request {
+                request_id,
+                request,
+                channel,
+            } => {
+                state.part_channels.insert(request_id, channel);
+
+                sync::RawMessage::Request {
+                    request_id,
+                    peer: PeerId::from_libp2p(&peer),
+                    body: request.0,
+                }
+            }
+
+            libp2p::request_response::Message::Response {
+                request_id,
+                response,
+            } => sync::RawMessage::Response {
+                request_id,
+                peer: PeerId::from_libp2p(&peer),
+                body: response.0,
+            },
+        },
+
+        sync::Event::InboundFailure {
+            request_id,
+            peer,
+            error,
+            ..
+        } => {
+            debug!(%request_id, %peer, ?error, "Inbound proposal part request failed");
+            state.part_channels.remove(&request_id);
+            return ControlFlow::Continue(());
+        }
+
+        sync::Event::ResponseSent { .. } | sync::Event::OutboundFailure { .. } => {
+            return ControlFlow::Continue(());
+        }
+    };
+
+    if let Err(e) = tx_event.send(Event::ProposalParts(raw_msg)).await {
+        error!("Error sending proposal part message to handle: {e}");
+        return ControlFlow::Break(());
+    }
+
+    ControlFlow::Continue(())
+}
+
 async fn handle_validator_proof_event(
     event: validator_proof::Event,
     tx_event: &mpsc::Sender<Event>,
@@ -1216,6 +1936,16 @@ async fn handle_validator_proof_event(
             ControlFlow::Continue(())
         }
 
+        validator_proof::Event::ProofRequested { peer } => {
+            debug!(%peer, "Validator proof requested by peer");
+            ControlFlow::Continue(())
+        }
+
+        validator_proof::Event::ProofRequestFailed { peer, error } => {
+            debug!(%peer, %error, "Failed to request validator proof");
+            ControlFlow::Continue(())
+        }
+
         validator_proof::Event::ProofReceiveFailed { .. } => {
             // This is handled directly by behaviour (closes connection via ToSwarm::CloseConnection)
             // and should never be emitted as an event to the swarm
@@ -1224,6 +1954,61 @@ async fn handle_validator_proof_event(
     }
 }
 
+fn handle_node_info_event(
+    event: node_info::Event,
+    swarm: &mut swarm::Swarm<Behaviour>,
+    state: &mut State,
+) -> ControlFlow<()> {
+    match event {
+        node_info::Event::InfoReceived { peer, info } => {
+            let other_chain = swarm
+                .behaviour()
+                .node_info
+                .as_ref()
+                .and_then(|ni| ni.info())
+                .is_some_and(|local| local.is_other_chain(&info));
+
+            if other_chain {
+                warn!(
+                    %peer,
+                    chain_id = ?info.chain_id,
+                    "Peer takes part in another chain, disconnecting"
+                );
+                let _ = swarm.disconnect_peer_id(peer);
+                return ControlFlow::Continue(());
+            }
+
+            debug!(
+                %peer,
+                moniker = %info.moniker,
+                protocol_version = %info.protocol_version,
+                is_validator = info.is_validator,
+                history_min_height = ?info.history_min_height,
+                "Received node info"
+            );
+
+            state.metrics.record_peer_node_info(&peer, &info);
+            state.peer_node_info.insert(peer, info);
+        }
+
+        node_info::Event::InfoSent { peer } => {
+            debug!(%peer, "Node info sent successfully");
+        }
+
+        node_info::Event::InfoSendFailed { peer, error } => {
+            debug!(%peer, %error, "Failed to send node info");
+        }
+
+        node_info::Event::InfoReceiveFailed { .. } => {
+            // This is handled directly by behaviour (closes connection via ToSwarm::CloseConnection)
+            // and should never be emitted as an event to the swarm
+            unreachable!("InfoReceiveFailed is handled by behaviour, not emitted")
+        }
+    }
+
+    ControlFlow::Continue(())
+}
+
 pub trait PeerIdExt {
     fn to_libp2p(&self) -> libp2p::PeerId;
     fn from_libp2p(peer_id: &libp2p::PeerId) -> Self;
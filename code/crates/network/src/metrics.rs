@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 
 use malachitebft_metrics::prometheus::encoding::EncodeLabelSet;
+use malachitebft_metrics::prometheus::metrics::counter::Counter;
 use malachitebft_metrics::prometheus::metrics::family::Family;
 use malachitebft_metrics::prometheus::metrics::gauge::Gauge;
 use malachitebft_metrics::Registry;
@@ -9,7 +10,9 @@ use tracing::{debug, warn};
 // Make prometheus_client available for the derive macro
 use malachitebft_metrics::prometheus as prometheus_client;
 
+use crate::node_info::NodeInfo;
 use crate::state::{LocalNodeInfo, PeerInfo};
+use crate::transport::ConnectionTimeout;
 use crate::utils::Slots;
 use crate::PeerType;
 use libp2p::PeerId;
@@ -45,6 +48,56 @@ pub(crate) struct ExplicitPeerLabels {
     peer_moniker: String,
 }
 
+/// Labels for the node info advertised by peers
+/// Note: gauge value = earliest height for which the peer retains decided values (0 if unknown)
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub(crate) struct NodeInfoLabels {
+    peer_id: String,
+    peer_moniker: String,
+    chain_id: String, // "none" if the peer does not advertise a chain id
+    protocol_version: String,
+    role: String, // "validator" or "full_node"
+}
+
+impl NodeInfoLabels {
+    fn new(peer_id: &PeerId, info: &NodeInfo) -> Self {
+        Self {
+            peer_id: peer_id.to_string(),
+            peer_moniker: info.moniker.clone(),
+            chain_id: info.chain_id.clone().unwrap_or_else(|| "none".to_string()),
+            protocol_version: info.protocol_version.to_string(),
+            role: if info.is_validator {
+                "validator"
+            } else {
+                "full_node"
+            }
+            .to_string(),
+        }
+    }
+}
+
+/// Labels for connection timeout metric
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub(crate) struct ConnectionTimeoutLabels {
+    stage: String, // "dial", "upgrade" or "handshake"
+}
+
+/// Number of connections which timed out, by stage of their establishment.
+///
+/// Cheap to clone, so that the transport can record the timeouts of the connections it establishes.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ConnectionTimeouts(Family<ConnectionTimeoutLabels, Counter>);
+
+impl ConnectionTimeouts {
+    /// Record a connection which timed out at the given stage
+    pub(crate) fn record(&self, timeout: ConnectionTimeout) {
+        let labels = ConnectionTimeoutLabels {
+            stage: timeout.as_str().to_string(),
+        };
+        self.0.get_or_create(&labels).inc();
+    }
+}
+
 impl PeerInfo {
     /// Convert to Prometheus metric labels (with slot number)
     pub(crate) fn to_labels(&self, peer_id: &PeerId, slot: usize) -> PeerInfoLabels {
@@ -92,6 +145,12 @@ pub(crate) struct Metrics {
     peer_mesh_membership: Family<MeshMembershipLabels, Gauge>,
     /// Explicit peers in gossipsub (1 = active, i64::MIN = disconnected/stale)
     explicit_peers: Family<ExplicitPeerLabels, Gauge>,
+    /// Number of connected peers with a negative gossipsub score
+    gossipsub_penalized_peers: Gauge,
+    /// Node info advertised by the connected peers (gauge value = earliest retained height)
+    peer_node_info: Family<NodeInfoLabels, Gauge>,
+    /// Connections which timed out, by stage (dial, upgrade, handshake)
+    connection_timeouts: ConnectionTimeouts,
     /// PeerId to slot number mapping
     peer_slots: Slots<PeerId>,
 }
@@ -111,6 +170,9 @@ impl Metrics {
         let peer_info = Family::<PeerInfoLabels, Gauge>::default();
         let mesh_membership = Family::<MeshMembershipLabels, Gauge>::default();
         let explicit_peers = Family::<ExplicitPeerLabels, Gauge>::default();
+        let gossipsub_penalized_peers = Gauge::default();
+        let peer_node_info = Family::<NodeInfoLabels, Gauge>::default();
+        let connection_timeouts = ConnectionTimeouts::default();
 
         registry.register(
             "local_node_info",
@@ -136,11 +198,32 @@ impl Metrics {
             explicit_peers.clone(),
         );
 
+        registry.register(
+            "gossipsub_penalized_peers",
+            "Number of connected peers with a negative gossipsub score, eg. for delivering invalid messages",
+            gossipsub_penalized_peers.clone(),
+        );
+
+        registry.register(
+            "peer_node_info",
+            "Node info advertised by the connected peers (gauge value = earliest retained height, 0 if unknown)",
+            peer_node_info.clone(),
+        );
+
+        registry.register(
+            "connection_timeouts",
+            "Connections which timed out before being established, by stage (dial, upgrade, handshake)",
+            connection_timeouts.0.clone(),
+        );
+
         Self {
             local_node_info,
             discovered_peers: peer_info,
             peer_mesh_membership: mesh_membership,
             explicit_peers,
+            gossipsub_penalized_peers,
+            peer_node_info,
+            connection_timeouts,
             peer_slots: Slots::new(MAX_PEER_SLOTS),
         }
     }
@@ -212,6 +295,35 @@ impl Metrics {
         Ok(())
     }
 
+    /// Set the number of connected peers with a negative gossipsub score
+    pub(crate) fn set_gossipsub_penalized_peers(&self, count: usize) {
+        self.gossipsub_penalized_peers.set(count as i64);
+    }
+
+    /// Record the node info advertised by a peer
+    pub(crate) fn record_peer_node_info(&self, peer_id: &PeerId, info: &NodeInfo) {
+        let height = info.history_min_height.unwrap_or(0);
+        self.peer_node_info
+            .get_or_create(&NodeInfoLabels::new(peer_id, info))
+            .set(height.try_into().unwrap_or(i64::MAX));
+    }
+
+    /// Remove the node info of a peer which disconnected
+    pub(crate) fn remove_peer_node_info(&self, peer_id: &PeerId, info: &NodeInfo) {
+        self.peer_node_info
+            .remove(&NodeInfoLabels::new(peer_id, info));
+    }
+
+    /// Handle on the connection timeouts, to be passed to the transport
+    pub(crate) fn connection_timeouts(&self) -> ConnectionTimeouts {
+        self.connection_timeouts.clone()
+    }
+
+    /// Record a connection which timed out at the given stage
+    pub(crate) fn record_connection_timeout(&self, timeout: ConnectionTimeout) {
+        self.connection_timeouts.record(timeout);
+    }
+
     /// Free a slot when a peer disconnects
     /// Note: Caller should also remove peer from State.peer_info
     pub(crate) fn free_slot(&mut self, peer_id: &PeerId, peer_info: &PeerInfo) {
@@ -0,0 +1,794 @@
+//! Multiplexing of several consensus instances (shards) over a single network.
+//!
+//! A [`Mux`] takes ownership of the [`Handle`] of a running network and hands out
+//! one [`Handle`] per shard, which can be used exactly as if it was the handle
+//! of a dedicated network. Every payload published, broadcast or sent by a shard
+//! is framed with its [`ShardId`], and incoming messages are routed back to the
+//! shard they are addressed to.
+//!
+//! Incoming messages are never waited on: a message for a shard whose event queue is full
+//! is dropped and counted in the `malachitebft_network_mux_dropped_messages` metric, so that
+//! a slow shard cannot hold up the others. Messages for unknown shards are dropped as well,
+//! except for requests, which are rejected so that the requester does not wait for a reply.
+//!
+//! The network knows a single validator set and a single earliest retained height, which are
+//! respectively the union of the validator sets and the minimum of the earliest heights of all shards.
+//!
+//! Frame format: `[shard id length: u8][shard id: utf-8][payload]`
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tokio::sync::mpsc;
+use tokio::task;
+use tracing::{debug, error, trace, warn};
+
+use malachitebft_metrics::prometheus::encoding::EncodeLabelSet;
+use malachitebft_metrics::prometheus::metrics::counter::Counter;
+use malachitebft_metrics::prometheus::metrics::family::Family;
+use malachitebft_metrics::SharedRegistry;
+use malachitebft_peer::PeerId;
+use malachitebft_sync::RawMessage;
+
+// Make prometheus_client available for the derive macro
+use malachitebft_metrics::prometheus as prometheus_client;
+
+use crate::handle::{CtrlHandle, Handle};
+use crate::{CtrlMsg, Event, ValidatorInfo};
+
+/// Maximum length in bytes of a shard identifier
+pub const MAX_SHARD_ID_LEN: usize = u8::MAX as usize;
+
+/// Identifier of a consensus instance multiplexed over a shared network,
+/// typically the chain identifier of that instance.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShardId(Arc<str>);
+
+impl ShardId {
+    /// Create a new shard identifier.
+    ///
+    /// Fails if the identifier is empty or longer than [`MAX_SHARD_ID_LEN`] bytes.
+    pub fn new(id: impl AsRef<str>) -> Result<Self, MuxError> {
+        let id = id.as_ref();
+
+        if id.is_empty() || id.len() > MAX_SHARD_ID_LEN {
+            return Err(MuxError::InvalidShardId(id.to_string()));
+        }
+
+        Ok(Self(Arc::from(id)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ShardId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Errors that can occur when multiplexing shards over a network
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MuxError {
+    /// The shard identifier is empty or too long
+    #[error("Invalid shard id: {0:?}")]
+    InvalidShardId(String),
+    /// A shard with the same identifier is already registered
+    #[error("Shard already registered: {0}")]
+    AlreadyRegistered(ShardId),
+    /// The frame is too short or the shard identifier is not valid utf-8
+    #[error("Malformed frame")]
+    MalformedFrame,
+}
+
+/// Prefix the given payload with the shard identifier.
+pub fn encode_frame(shard: &ShardId, payload: &[u8]) -> Bytes {
+    let id = shard.as_str().as_bytes();
+
+    let mut buf = BytesMut::with_capacity(1 + id.len() + payload.len());
+    buf.put_u8(id.len() as u8);
+    buf.put_slice(id);
+    buf.put_slice(payload);
+    buf.freeze()
+}
+
+/// Split a frame into the shard identifier and the payload.
+pub fn decode_frame(mut frame: Bytes) -> Result<(ShardId, Bytes), MuxError> {
+    if frame.is_empty() {
+        return Err(MuxError::MalformedFrame);
+    }
+
+    let len = frame.get_u8() as usize;
+    if len == 0 || frame.len() < len {
+        return Err(MuxError::MalformedFrame);
+    }
+
+    let id = frame.split_to(len);
+    let id = std::str::from_utf8(&id).map_err(|_| MuxError::MalformedFrame)?;
+
+    Ok((ShardId(Arc::from(id)), frame))
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct ShardLabels {
+    shard: String,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Metrics {
+    /// Number of incoming messages dropped because the queue of their shard was full, per shard
+    dropped_messages: Family<ShardLabels, Counter>,
+}
+
+impl Metrics {
+    fn register(registry: &SharedRegistry) -> Self {
+        let metrics = Self::default();
+
+        registry.with_prefix("malachitebft_network_mux", |registry| {
+            registry.register(
+                "dropped_messages",
+                "Number of incoming messages dropped because the queue of their shard was full, per shard",
+                metrics.dropped_messages.clone(),
+            );
+        });
+
+        metrics
+    }
+
+    fn inc_dropped(&self, shard: &ShardId) {
+        self.dropped_messages
+            .get_or_create(&ShardLabels {
+                shard: shard.to_string(),
+            })
+            .inc();
+    }
+}
+
+type Routes = Arc<Mutex<HashMap<ShardId, mpsc::Sender<Event>>>>;
+type ValidatorSets = Arc<Mutex<BTreeMap<ShardId, Vec<ValidatorInfo>>>>;
+type HistoryMinHeights = Arc<Mutex<BTreeMap<ShardId, u64>>>;
+
+/// Multiplexes several shards over a single network.
+pub struct Mux {
+    ctrl: CtrlHandle,
+    routes: Routes,
+    validator_sets: ValidatorSets,
+    history_min_heights: HistoryMinHeights,
+    demux_task: task::JoinHandle<()>,
+}
+
+impl Mux {
+    /// Take ownership of the handle of a running network
+    /// and start routing its events to the registered shards.
+    pub fn new(handle: Handle, registry: &SharedRegistry) -> Self {
+        Self::with_metrics(handle, Metrics::register(registry))
+    }
+
+    fn with_metrics(handle: Handle, metrics: Metrics) -> Self {
+        let (recv, ctrl) = handle.split();
+        let routes = Routes::default();
+
+        let demux_task = tokio::spawn(demux(
+            recv,
+            ctrl.tx_ctrl().clone(),
+            Arc::clone(&routes),
+            metrics,
+        ));
+
+        Self {
+            ctrl,
+            routes,
+            validator_sets: ValidatorSets::default(),
+            history_min_heights: HistoryMinHeights::default(),
+            demux_task,
+        }
+    }
+
+    pub fn peer_id(&self) -> PeerId {
+        self.ctrl.peer_id()
+    }
+
+    /// Register a new shard and return its handle.
+    ///
+    /// Shutting down the returned handle only detaches the shard,
+    /// the underlying network keeps running until [`Mux::wait_shutdown`] is called.
+    pub fn shard(&self, shard: ShardId) -> Result<Handle, MuxError> {
+        let (tx_event, rx_event) = mpsc::channel(32);
+        let (tx_ctrl, rx_ctrl) = mpsc::channel(32);
+
+        {
+            let mut routes = self.routes.lock().expect("poisoned lock");
+            if routes.contains_key(&shard) {
+                return Err(MuxError::AlreadyRegistered(shard));
+            }
+            routes.insert(shard.clone(), tx_event);
+        }
+
+        let task_handle = tokio::spawn(forward(
+            shard,
+            rx_ctrl,
+            self.ctrl.tx_ctrl().clone(),
+            Arc::clone(&self.routes),
+            Arc::clone(&self.validator_sets),
+            Arc::clone(&self.history_min_heights),
+        ));
+
+        Ok(Handle::new(self.peer_id(), tx_ctrl, rx_event, task_handle))
+    }
+
+    /// Shut down the underlying network and stop routing events.
+    pub async fn wait_shutdown(self) -> Result<(), eyre::Report> {
+        self.ctrl.wait_shutdown().await?;
+        self.demux_task.await?;
+        Ok(())
+    }
+}
+
+/// Route the events emitted by the network to the shards they are addressed to.
+async fn demux(
+    mut recv: crate::handle::RecvHandle,
+    tx_ctrl: mpsc::Sender<CtrlMsg>,
+    routes: Routes,
+    metrics: Metrics,
+) {
+    while let Some(event) = recv.recv().await {
+        match event {
+            Event::ConsensusMessage(channel, from, data) => {
+                route(&routes, &metrics, data, |shard, data| {
+                    trace!(%shard, %channel, %from, "Routing consensus message");
+                    Event::ConsensusMessage(channel, from, data)
+                });
+            }
+
+            Event::LivenessMessage(channel, from, data) => {
+                route(&routes, &metrics, data, |shard, data| {
+                    trace!(%shard, %channel, %from, "Routing liveness message");
+                    Event::LivenessMessage(channel, from, data)
+                });
+            }
+
+            Event::Sync(RawMessage::Request {
+                request_id,
+                peer,
+                body,
+            }) => {
+                let unknown = route(&routes, &metrics, body, |_, body| {
+                    Event::Sync(RawMessage::Request {
+                        request_id,
+                        peer,
+                        body,
+                    })
+                });
+
+                if let Some(shard) = unknown {
+                    debug!(%shard, %request_id, %peer, "Rejecting Sync request for unknown shard");
+                    let _ = tx_ctrl.send(CtrlMsg::RejectSyncRequest(request_id)).await;
+                }
+            }
+
+            Event::Sync(RawMessage::Response {
+                request_id,
+                peer,
+                body,
+            }) => {
+                route(&routes, &metrics, body, |_, body| {
+                    Event::Sync(RawMessage::Response {
+                        request_id,
+                        peer,
+                        body,
+                    })
+                });
+            }
+
+            Event::ProposalParts(RawMessage::Request {
+                request_id,
+                peer,
+                body,
+            }) => {
+                let unknown = route(&routes, &metrics, body, |_, body| {
+                    Event::ProposalParts(RawMessage::Request {
+                        request_id,
+                        peer,
+                        body,
+                    })
+                });
+
+                // An empty response signals that we do not have the requested part
+                if let Some(shard) = unknown {
+                    debug!(%shard, %request_id, %peer, "Rejecting proposal part request for unknown shard");
+                    let reply = CtrlMsg::PartReply(request_id, encode_frame(&shard, &[]));
+                    let _ = tx_ctrl.send(reply).await;
+                }
+            }
+
+            Event::ProposalParts(RawMessage::Response {
+                request_id,
+                peer,
+                body,
+            }) => {
+                route(&routes, &metrics, body, |_, body| {
+                    Event::ProposalParts(RawMessage::Response {
+                        request_id,
+                        peer,
+                        body,
+                    })
+                });
+            }
+
+            // Events which are not specific to a shard are sent to all of them.
+            // They are rare and must not be lost, so we wait for room in the queue of each shard.
+            event @ (Event::Listening(_)
+            | Event::PeerConnected(..)
+            | Event::PeerDisconnected(_)
+            | Event::ValidatorProofReceived { .. }
+            | Event::RateLimitViolation(_)) => {
+                let senders = routes
+                    .lock()
+                    .expect("poisoned lock")
+                    .values()
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                for tx in senders {
+                    let _ = tx.send(event.clone()).await;
+                }
+            }
+        }
+    }
+
+    debug!("Network has stopped, no more events to route");
+}
+
+/// Route a framed message to the shard it is addressed to, without waiting for room in its queue.
+///
+/// Returns the shard the message is addressed to if no such shard is registered.
+fn route(
+    routes: &Routes,
+    metrics: &Metrics,
+    frame: Bytes,
+    to_event: impl FnOnce(&ShardId, Bytes) -> Event,
+) -> Option<ShardId> {
+    let (shard, payload) = match decode_frame(frame) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            warn!("Dropping message: {e}");
+            return None;
+        }
+    };
+
+    let tx = routes.lock().expect("poisoned lock").get(&shard).cloned();
+
+    let Some(tx) = tx else {
+        trace!(%shard, "Dropping message for unknown shard");
+        return Some(shard);
+    };
+
+    match tx.try_send(to_event(&shard, payload)) {
+        Ok(()) => {}
+        Err(mpsc::error::TrySendError::Full(_)) => {
+            debug!(%shard, "Shard is not keeping up, dropping message");
+            metrics.inc_dropped(&shard);
+        }
+        Err(mpsc::error::TrySendError::Closed(_)) => {
+            debug!(%shard, "Shard has stopped, dropping message");
+        }
+    }
+
+    None
+}
+
+/// Forward the control messages of a shard to the network, framing their payload.
+async fn forward(
+    shard: ShardId,
+    mut rx_ctrl: mpsc::Receiver<CtrlMsg>,
+    tx_ctrl: mpsc::Sender<CtrlMsg>,
+    routes: Routes,
+    validator_sets: ValidatorSets,
+    history_min_heights: HistoryMinHeights,
+) {
+    while let Some(msg) = rx_ctrl.recv().await {
+        let msg = match msg {
+            CtrlMsg::Publish(channel, data) => {
+                CtrlMsg::Publish(channel, encode_frame(&shard, &data))
+            }
+            CtrlMsg::Broadcast(channel, data) => {
+                CtrlMsg::Broadcast(channel, encode_frame(&shard, &data))
+            }
+            CtrlMsg::SyncRequest(peer_id, data, reply_to) => {
+                CtrlMsg::SyncRequest(peer_id, encode_frame(&shard, &data), reply_to)
+            }
+            CtrlMsg::SyncReply(request_id, data) => {
+                CtrlMsg::SyncReply(request_id, encode_frame(&shard, &data))
+            }
+            CtrlMsg::PartRequest(peer_id, data) => {
+                CtrlMsg::PartRequest(peer_id, encode_frame(&shard, &data))
+            }
+            CtrlMsg::PartReply(request_id, data) => {
+                CtrlMsg::PartReply(request_id, encode_frame(&shard, &data))
+            }
+
+            // The network knows a single validator set, which is the union of the validator sets of all shards
+            CtrlMsg::UpdateValidatorSet(validators) => {
+                let mut validator_sets = validator_sets.lock().expect("poisoned lock");
+                validator_sets.insert(shard.clone(), validators);
+                CtrlMsg::UpdateValidatorSet(union(&validator_sets))
+            }
+
+            // The network advertises a single earliest retained height, which is the minimum across all shards
+            CtrlMsg::UpdateHistoryMinHeight(height) => {
+                let mut history_min_heights = history_min_heights.lock().expect("poisoned lock");
+                history_min_heights.insert(shard.clone(), height);
+                CtrlMsg::UpdateHistoryMinHeight(lowest(&history_min_heights).unwrap_or(height))
+            }
+
+            // Shutting down a shard only detaches it from the network
+            CtrlMsg::Shutdown => break,
+
+            // Messages about peers, request ids or the state of the network as a whole,
+            // which are the same for all shards
+            msg @ (CtrlMsg::RejectSyncRequest(_)
+            | CtrlMsg::ValidatorProofVerified { .. }
+            | CtrlMsg::DumpState(_)
+            | CtrlMsg::GetDiscoveryState(_)
+            | CtrlMsg::UpdatePersistentPeers(..)
+            | CtrlMsg::BanPeer(..)) => msg,
+        };
+
+        if tx_ctrl.send(msg).await.is_err() {
+            error!(%shard, "Network has stopped, cannot forward message");
+            break;
+        }
+    }
+
+    routes.lock().expect("poisoned lock").remove(&shard);
+
+    let validators = {
+        let mut validator_sets = validator_sets.lock().expect("poisoned lock");
+        validator_sets
+            .remove(&shard)
+            .map(|_| union(&validator_sets))
+    };
+
+    if let Some(validators) = validators {
+        let _ = tx_ctrl.send(CtrlMsg::UpdateValidatorSet(validators)).await;
+    }
+
+    let min_height = {
+        let mut history_min_heights = history_min_heights.lock().expect("poisoned lock");
+        history_min_heights
+            .remove(&shard)
+            .and_then(|_| lowest(&history_min_heights))
+    };
+
+    if let Some(min_height) = min_height {
+        let _ = tx_ctrl
+            .send(CtrlMsg::UpdateHistoryMinHeight(min_height))
+            .await;
+    }
+
+    debug!(%shard, "Shard detached from network");
+}
+
+fn union(validator_sets: &BTreeMap<ShardId, Vec<ValidatorInfo>>) -> Vec<ValidatorInfo> {
+    let mut validators: Vec<ValidatorInfo> = Vec::new();
+
+    for validator in validator_sets.values().flatten() {
+        if !validators.contains(validator) {
+            validators.push(validator.clone());
+        }
+    }
+
+    validators
+}
+
+fn lowest(history_min_heights: &BTreeMap<ShardId, u64>) -> Option<u64> {
+    history_min_heights.values().copied().min()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::state::tests::test_inbound_request_id;
+    use crate::PeerIdExt;
+
+    struct TestNetwork {
+        mux: Mux,
+        metrics: Metrics,
+        tx_event: mpsc::Sender<Event>,
+        rx_ctrl: mpsc::Receiver<CtrlMsg>,
+    }
+
+    fn test_network() -> TestNetwork {
+        let peer_id = PeerId::from_libp2p(&libp2p::PeerId::random());
+        let (tx_ctrl, rx_ctrl) = mpsc::channel(32);
+        let (tx_event, rx_event) = mpsc::channel(32);
+        let task = tokio::spawn(async {});
+
+        let metrics = Metrics::default();
+        let mux = Mux::with_metrics(
+            Handle::new(peer_id, tx_ctrl, rx_event, task),
+            metrics.clone(),
+        );
+
+        TestNetwork {
+            mux,
+            metrics,
+            tx_event,
+            rx_ctrl,
+        }
+    }
+
+    #[test]
+    fn frame_roundtrip() {
+        let shard = ShardId::new("shard-1").unwrap();
+        let frame = encode_frame(&shard, b"hello");
+
+        let (decoded, payload) = decode_frame(frame).unwrap();
+        assert_eq!(decoded, shard);
+        assert_eq!(payload, Bytes::from_static(b"hello"));
+    }
+
+    #[test]
+    fn frame_empty_payload() {
+        let shard = ShardId::new("a").unwrap();
+        let frame = encode_frame(&shard, b"");
+
+        let (decoded, payload) = decode_frame(frame).unwrap();
+        assert_eq!(decoded, shard);
+        assert!(payload.is_empty());
+    }
+
+    #[test]
+    fn malformed_frames() {
+        assert_eq!(decode_frame(Bytes::new()), Err(MuxError::MalformedFrame));
+        assert_eq!(
+            decode_frame(Bytes::from_static(&[0, 1, 2])),
+            Err(MuxError::MalformedFrame)
+        );
+        assert_eq!(
+            decode_frame(Bytes::from_static(&[5, b'a', b'b'])),
+            Err(MuxError::MalformedFrame)
+        );
+        assert_eq!(
+            decode_frame(Bytes::from_static(&[2, 0xff, 0xfe])),
+            Err(MuxError::MalformedFrame)
+        );
+    }
+
+    #[test]
+    fn invalid_shard_ids() {
+        assert!(ShardId::new("").is_err());
+        assert!(ShardId::new("x".repeat(MAX_SHARD_ID_LEN)).is_ok());
+        assert!(ShardId::new("x".repeat(MAX_SHARD_ID_LEN + 1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn frames_and_routes_shard_messages() {
+        let TestNetwork {
+            mux,
+            tx_event,
+            mut rx_ctrl,
+            ..
+        } = test_network();
+
+        let shard_a = ShardId::new("a").unwrap();
+        let shard_b = ShardId::new("b").unwrap();
+
+        let (mut recv_a, ctrl_a) = mux.shard(shard_a.clone()).unwrap().split();
+        let (mut recv_b, ctrl_b) = mux.shard(shard_b.clone()).unwrap().split();
+
+        assert_eq!(
+            mux.shard(shard_a.clone()).err(),
+            Some(MuxError::AlreadyRegistered(shard_a.clone()))
+        );
+
+        // Outbound payloads are framed with the shard id
+        ctrl_a
+            .publish(crate::Channel::Consensus, Bytes::from_static(b"vote"))
+            .await
+            .unwrap();
+
+        match rx_ctrl.recv().await.unwrap() {
+            CtrlMsg::Publish(crate::Channel::Consensus, data) => {
+                assert_eq!(data, encode_frame(&shard_a, b"vote"));
+            }
+            _ => panic!("expected a publish message"),
+        }
+
+        // Inbound payloads are routed to the right shard only
+        let from = PeerId::from_libp2p(&libp2p::PeerId::random());
+        tx_event
+            .send(Event::ConsensusMessage(
+                crate::Channel::Consensus,
+                from,
+                encode_frame(&shard_b, b"proposal"),
+            ))
+            .await
+            .unwrap();
+
+        match recv_b.recv().await.unwrap() {
+            Event::ConsensusMessage(_, peer, data) => {
+                assert_eq!(peer, from);
+                assert_eq!(data, Bytes::from_static(b"proposal"));
+            }
+            _ => panic!("expected a consensus message"),
+        }
+
+        // Shard-agnostic events are sent to all shards
+        let addr: crate::Multiaddr = "/ip4/127.0.0.1/tcp/27000".parse().unwrap();
+        tx_event
+            .send(Event::PeerConnected(from, addr.clone()))
+            .await
+            .unwrap();
+        assert!(
+            matches!(recv_a.recv().await, Some(Event::PeerConnected(p, a)) if p == from && a == addr)
+        );
+        assert!(
+            matches!(recv_b.recv().await, Some(Event::PeerConnected(p, a)) if p == from && a == addr)
+        );
+
+        // The network is given the union of the validator sets of all shards
+        let validator = |address: &str| ValidatorInfo {
+            address: address.to_string(),
+            public_key: address.as_bytes().to_vec(),
+            voting_power: 1,
+        };
+
+        ctrl_a
+            .update_validator_set(vec![validator("v1")])
+            .await
+            .unwrap();
+        assert!(matches!(
+            rx_ctrl.recv().await,
+            Some(CtrlMsg::UpdateValidatorSet(vs)) if vs == vec![validator("v1")]
+        ));
+
+        ctrl_b
+            .update_validator_set(vec![validator("v2"), validator("v1")])
+            .await
+            .unwrap();
+        assert!(matches!(
+            rx_ctrl.recv().await,
+            Some(CtrlMsg::UpdateValidatorSet(vs)) if vs == vec![validator("v1"), validator("v2")]
+        ));
+
+        // Detaching a shard removes its validators from the union
+        ctrl_b.shutdown().await.unwrap();
+        assert!(matches!(
+            rx_ctrl.recv().await,
+            Some(CtrlMsg::UpdateValidatorSet(vs)) if vs == vec![validator("v1")]
+        ));
+    }
+
+    #[tokio::test]
+    async fn slow_shard_does_not_hold_up_others() {
+        let TestNetwork {
+            mux,
+            metrics,
+            tx_event,
+            ..
+        } = test_network();
+
+        let slow = ShardId::new("slow").unwrap();
+        let fast = ShardId::new("fast").unwrap();
+
+        // The events of the slow shard are never received
+        let _slow = mux.shard(slow.clone()).unwrap();
+        let (mut recv_fast, _ctrl_fast) = mux.shard(fast.clone()).unwrap().split();
+
+        let from = PeerId::from_libp2p(&libp2p::PeerId::random());
+        let message = |shard: &ShardId| {
+            Event::ConsensusMessage(
+                crate::Channel::Consensus,
+                from,
+                encode_frame(shard, b"vote"),
+            )
+        };
+
+        // One more message than the queue of the slow shard can hold
+        for _ in 0..33 {
+            tx_event.send(message(&slow)).await.unwrap();
+        }
+
+        tx_event.send(message(&fast)).await.unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(1), recv_fast.recv()).await;
+        assert!(matches!(received, Ok(Some(Event::ConsensusMessage(..)))));
+
+        let dropped = |shard: &str| {
+            metrics
+                .dropped_messages
+                .get_or_create(&ShardLabels {
+                    shard: shard.to_string(),
+                })
+                .get()
+        };
+
+        assert_eq!(dropped("slow"), 1);
+        assert_eq!(dropped("fast"), 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_requests_for_unknown_shards() {
+        let TestNetwork {
+            mux,
+            tx_event,
+            mut rx_ctrl,
+            ..
+        } = test_network();
+
+        let _known = mux.shard(ShardId::new("known").unwrap()).unwrap();
+        let unknown = ShardId::new("unknown").unwrap();
+
+        let peer = PeerId::from_libp2p(&libp2p::PeerId::random());
+        let request_id = test_inbound_request_id(1);
+
+        tx_event
+            .send(Event::Sync(RawMessage::Request {
+                request_id,
+                peer,
+                body: encode_frame(&unknown, b"request"),
+            }))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            rx_ctrl.recv().await,
+            Some(CtrlMsg::RejectSyncRequest(id)) if id == request_id
+        ));
+
+        tx_event
+            .send(Event::ProposalParts(RawMessage::Request {
+                request_id,
+                peer,
+                body: encode_frame(&unknown, b"request"),
+            }))
+            .await
+            .unwrap();
+
+        // Replying with an empty part signals that we do not have it
+        assert!(matches!(
+            rx_ctrl.recv().await,
+            Some(CtrlMsg::PartReply(id, data)) if id == request_id && data == encode_frame(&unknown, b"")
+        ));
+    }
+
+    #[tokio::test]
+    async fn advertises_lowest_history_min_height() {
+        let TestNetwork {
+            mux, mut rx_ctrl, ..
+        } = test_network();
+
+        let (_, ctrl_a) = mux.shard(ShardId::new("a").unwrap()).unwrap().split();
+        let (_, ctrl_b) = mux.shard(ShardId::new("b").unwrap()).unwrap().split();
+
+        async fn update(
+            ctrl: &CtrlHandle,
+            rx_ctrl: &mut mpsc::Receiver<CtrlMsg>,
+            height: u64,
+        ) -> u64 {
+            ctrl.update_history_min_height(height).await.unwrap();
+            match rx_ctrl.recv().await {
+                Some(CtrlMsg::UpdateHistoryMinHeight(height)) => height,
+                _ => panic!("expected a history min height update"),
+            }
+        }
+
+        assert_eq!(update(&ctrl_a, &mut rx_ctrl, 10).await, 10);
+        assert_eq!(update(&ctrl_b, &mut rx_ctrl, 5).await, 5);
+        assert_eq!(update(&ctrl_a, &mut rx_ctrl, 20).await, 5);
+
+        // Detaching a shard removes its height from the minimum
+        ctrl_b.shutdown().await.unwrap();
+        assert!(matches!(
+            rx_ctrl.recv().await,
+            Some(CtrlMsg::UpdateHistoryMinHeight(20))
+        ));
+    }
+}
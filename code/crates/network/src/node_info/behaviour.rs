@@ -0,0 +1,440 @@
+//! Behaviour for the Node Info protocol using libp2p_stream.
+//!
+//! Each node sends its info to a peer on the first connection to it.
+//! No response is expected - the receiver just stores the info.
+
+use std::collections::HashSet;
+use std::task::{self, Poll};
+
+use libp2p::swarm::behaviour::ConnectionEstablished;
+use libp2p::swarm::{
+    CloseConnection, ConnectionClosed, ConnectionId, FromSwarm, NetworkBehaviour, ToSwarm,
+};
+use libp2p::{Multiaddr, PeerId, StreamProtocol};
+use libp2p_stream as stream;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tracing::{debug, trace, warn};
+
+use super::{protocol, NodeInfo};
+
+/// Events emitted by the Node Info behaviour.
+#[derive(Debug)]
+pub enum Event {
+    /// Successfully sent our info to a peer.
+    InfoSent { peer: PeerId },
+    /// Received the info of a peer.
+    InfoReceived { peer: PeerId, info: NodeInfo },
+    /// Failed to send our info to a peer.
+    InfoSendFailed { peer: PeerId, error: Error },
+    /// Failed to receive valid info from a peer (should disconnect).
+    InfoReceiveFailed { peer: PeerId, error: Error },
+}
+
+/// Errors that can occur in the Node Info protocol.
+#[derive(Clone, Debug, Error)]
+pub enum Error {
+    #[error("IO error: {0}")]
+    Io(String),
+    #[error("Stream closed unexpectedly")]
+    UnexpectedEof,
+    #[error("Invalid node info: {0}")]
+    Decode(String),
+}
+
+/// Node Info behaviour using libp2p_stream for one-way info sending.
+pub struct Behaviour {
+    /// Inner stream behaviour.
+    inner: stream::Behaviour,
+
+    /// Protocol name for node info (e.g. `/malachitebft-node-info/v1`).
+    protocol: StreamProtocol,
+
+    /// Info to send to peers, set once the swarm is created.
+    info: Option<NodeInfo>,
+
+    /// Channel for receiving events from protocol tasks.
+    events_rx: mpsc::UnboundedReceiver<Event>,
+    events_tx: mpsc::UnboundedSender<Event>,
+
+    /// Track peers we've received info from (anti-spam: one info per peer per session).
+    /// Cleared when the last connection to a peer closes.
+    infos_received: HashSet<PeerId>,
+
+    /// Whether we're listening for incoming streams.
+    listening: bool,
+}
+
+impl Behaviour {
+    /// Create a new behaviour with the given protocol name.
+    pub fn new(protocol: StreamProtocol) -> Self {
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+
+        Self {
+            inner: stream::Behaviour::new(),
+            protocol,
+            info: None,
+            events_rx,
+            events_tx,
+            infos_received: HashSet::new(),
+            listening: false,
+        }
+    }
+
+    /// Create a behaviour with the default protocol name (for tests or when not using config).
+    /// Prefer [`new`](Self::new) with the protocol from config.
+    pub fn with_default_protocol() -> Self {
+        Self::new(StreamProtocol::new("/malachitebft-node-info/v1"))
+    }
+
+    /// Set the info to send when connecting to peers.
+    pub fn set_info(&mut self, info: NodeInfo) {
+        self.info = Some(info);
+    }
+
+    /// Update the info to send on the next connections to peers, if it is set.
+    pub fn update_info(&mut self, f: impl FnOnce(&mut NodeInfo)) {
+        if let Some(info) = &mut self.info {
+            f(info);
+        }
+    }
+
+    /// The info sent when connecting to peers, if it is set.
+    pub fn info(&self) -> Option<&NodeInfo> {
+        self.info.as_ref()
+    }
+
+    /// Send our info to a specific peer.
+    /// Returns true if the send was initiated, false if no info is set.
+    fn send_info(&mut self, peer_id: PeerId) -> bool {
+        let Some(info) = &self.info else {
+            return false;
+        };
+
+        let info = info.encode();
+        let control = self.inner.new_control();
+        let events_tx = self.events_tx.clone();
+        let protocol = self.protocol.clone();
+
+        tokio::spawn(async move {
+            let event = protocol::send_info(peer_id, info, control, protocol).await;
+            let _ = events_tx.send(event);
+        });
+
+        true
+    }
+
+    fn start_listening(&mut self) {
+        if self.listening {
+            // If there are multiple listen addresses, we may get multiple NewListenAddr events - only start once
+            return;
+        }
+
+        self.listening = true;
+
+        let control = self.inner.new_control();
+        let events_tx = self.events_tx.clone();
+        let protocol = self.protocol.clone();
+
+        tokio::spawn(async move {
+            protocol::accept_incoming_streams(control, events_tx, protocol).await;
+        });
+
+        debug!(protocol = %self.protocol, "Listening for incoming node info");
+    }
+
+    fn on_connection_established(&mut self, conn: &ConnectionEstablished<'_>) {
+        let peer_id = conn.peer_id;
+
+        if conn.other_established > 0 {
+            trace!(
+                %peer_id,
+                other_established = conn.other_established,
+                "Additional connection to peer, skipping node info send"
+            );
+            return;
+        }
+
+        if self.send_info(peer_id) {
+            debug!(%peer_id, "Sending node info on first connection");
+        }
+    }
+
+    fn on_connection_closed(&mut self, conn: &ConnectionClosed<'_>) {
+        if conn.remaining_established > 0 {
+            return;
+        }
+
+        let peer_id = conn.peer_id;
+        trace!(%peer_id, "Last connection closed, cleaning up node info state");
+        self.infos_received.remove(&peer_id);
+    }
+}
+
+impl Default for Behaviour {
+    fn default() -> Self {
+        Self::with_default_protocol()
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = <stream::Behaviour as NetworkBehaviour>::ConnectionHandler;
+    type ToSwarm = Event;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<libp2p::swarm::THandler<Self>, libp2p::swarm::ConnectionDenied> {
+        self.inner.handle_established_inbound_connection(
+            connection_id,
+            peer,
+            local_addr,
+            remote_addr,
+        )
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &Multiaddr,
+        role_override: libp2p::core::Endpoint,
+        port_use: libp2p::core::transport::PortUse,
+    ) -> Result<libp2p::swarm::THandler<Self>, libp2p::swarm::ConnectionDenied> {
+        self.inner.handle_established_outbound_connection(
+            connection_id,
+            peer,
+            addr,
+            role_override,
+            port_use,
+        )
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        match &event {
+            FromSwarm::NewListenAddr(_) => {
+                self.start_listening();
+            }
+            FromSwarm::ConnectionEstablished(conn) => {
+                self.on_connection_established(conn);
+            }
+            FromSwarm::ConnectionClosed(conn) => {
+                self.on_connection_closed(conn);
+            }
+            _ => {}
+        }
+
+        self.inner.on_swarm_event(event);
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        event: libp2p::swarm::THandlerOutEvent<Self>,
+    ) {
+        self.inner
+            .on_connection_handler_event(peer_id, connection_id, event);
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut task::Context<'_>,
+    ) -> Poll<ToSwarm<Self::ToSwarm, libp2p::swarm::THandlerInEvent<Self>>> {
+        // Check for events from protocol tasks
+        if let Poll::Ready(Some(event)) = self.events_rx.poll_recv(cx) {
+            match event {
+                // On receive failure, disconnect peer directly
+                Event::InfoReceiveFailed { peer, error } => {
+                    warn!(%peer, %error, "Failed to receive node info, closing connection");
+                    return Poll::Ready(ToSwarm::CloseConnection {
+                        peer_id: peer,
+                        connection: CloseConnection::All,
+                    });
+                }
+                // On info received, check for duplicate (anti-spam)
+                Event::InfoReceived { peer, info } => {
+                    if !self.infos_received.insert(peer) {
+                        warn!(%peer, "Duplicate node info received, closing connection (anti-spam)");
+                        return Poll::Ready(ToSwarm::CloseConnection {
+                            peer_id: peer,
+                            connection: CloseConnection::All,
+                        });
+                    }
+
+                    return Poll::Ready(ToSwarm::GenerateEvent(Event::InfoReceived { peer, info }));
+                }
+                // Forward other events to swarm
+                _ => return Poll::Ready(ToSwarm::GenerateEvent(event)),
+            }
+        }
+
+        // Poll the inner behaviour, see `validator_proof::Behaviour::poll`
+        let _ = self.inner.poll(cx);
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Poll;
+
+    use futures::task::noop_waker_ref;
+    use libp2p::core::transport::PortUse;
+    use libp2p::core::Endpoint;
+
+    use crate::ProtocolVersion;
+
+    fn node_info(moniker: &str) -> NodeInfo {
+        NodeInfo {
+            moniker: moniker.to_string(),
+            chain_id: Some("test-chain".to_string()),
+            protocol_version: ProtocolVersion::new(1, 0, 0),
+            protocols: vec![],
+            is_validator: false,
+            history_min_height: None,
+        }
+    }
+
+    /// Returns a `Dialer` connected point for tests.
+    fn dialer_endpoint() -> libp2p::core::ConnectedPoint {
+        libp2p::core::ConnectedPoint::Dialer {
+            address: "/ip4/127.0.0.1/tcp/9000".parse().unwrap(),
+            role_override: Endpoint::Dialer,
+            port_use: PortUse::Reuse,
+        }
+    }
+
+    /// Poll the behaviour once with a noop waker and return the result.
+    fn poll_behaviour(
+        b: &mut Behaviour,
+    ) -> Poll<ToSwarm<Event, libp2p::swarm::THandlerInEvent<Behaviour>>> {
+        let waker = noop_waker_ref();
+        let mut cx = std::task::Context::from_waker(waker);
+        b.poll(&mut cx)
+    }
+
+    /// Simulate a connection closed event.
+    fn close_connection(
+        b: &mut Behaviour,
+        peer: PeerId,
+        conn_id: ConnectionId,
+        remaining_established: usize,
+    ) {
+        let endpoint = dialer_endpoint();
+        let event = FromSwarm::ConnectionClosed(ConnectionClosed {
+            peer_id: peer,
+            connection_id: conn_id,
+            endpoint: &endpoint,
+            cause: None,
+            remaining_established,
+        });
+        b.on_swarm_event(event);
+    }
+
+    fn assert_disconnects(b: &mut Behaviour, peer: PeerId) {
+        match poll_behaviour(b) {
+            Poll::Ready(ToSwarm::CloseConnection {
+                peer_id,
+                connection,
+            }) => {
+                assert_eq!(peer_id, peer);
+                assert!(matches!(connection, CloseConnection::All));
+            }
+            other => panic!("expected CloseConnection, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn poll_info_received_emits_event() {
+        let mut b = Behaviour::with_default_protocol();
+        let peer = PeerId::random();
+
+        b.events_tx
+            .send(Event::InfoReceived {
+                peer,
+                info: node_info("node-1"),
+            })
+            .unwrap();
+
+        match poll_behaviour(&mut b) {
+            Poll::Ready(ToSwarm::GenerateEvent(Event::InfoReceived { peer: p, info })) => {
+                assert_eq!(p, peer);
+                assert_eq!(info.moniker, "node-1");
+            }
+            other => panic!("expected GenerateEvent(InfoReceived), got {other:?}"),
+        }
+        assert!(b.infos_received.contains(&peer));
+        assert!(poll_behaviour(&mut b).is_pending());
+    }
+
+    #[test]
+    fn poll_duplicate_info_triggers_disconnect() {
+        let mut b = Behaviour::with_default_protocol();
+        let peer = PeerId::random();
+
+        for moniker in ["node-1", "node-1-again"] {
+            b.events_tx
+                .send(Event::InfoReceived {
+                    peer,
+                    info: node_info(moniker),
+                })
+                .unwrap();
+        }
+
+        let _ = poll_behaviour(&mut b);
+        assert_disconnects(&mut b, peer);
+    }
+
+    #[test]
+    fn poll_receive_failure_triggers_disconnect() {
+        let mut b = Behaviour::with_default_protocol();
+        let peer = PeerId::random();
+
+        b.events_tx
+            .send(Event::InfoReceiveFailed {
+                peer,
+                error: Error::Decode("invalid".into()),
+            })
+            .unwrap();
+
+        assert_disconnects(&mut b, peer);
+    }
+
+    #[test]
+    fn anti_spam_reset_after_full_disconnect() {
+        let mut b = Behaviour::with_default_protocol();
+        let peer = PeerId::random();
+        let (conn1, conn2) = (
+            ConnectionId::new_unchecked(1),
+            ConnectionId::new_unchecked(2),
+        );
+
+        b.infos_received.insert(peer);
+
+        // Close one, one remains
+        close_connection(&mut b, peer, conn1, 1);
+        assert!(b.infos_received.contains(&peer));
+
+        close_connection(&mut b, peer, conn2, 0);
+        assert!(!b.infos_received.contains(&peer));
+    }
+
+    #[test]
+    fn update_info_only_when_set() {
+        let mut b = Behaviour::with_default_protocol();
+
+        b.update_info(|info| info.is_validator = true);
+        assert!(b.info().is_none());
+        assert!(!b.send_info(PeerId::random()));
+
+        b.set_info(node_info("node-0"));
+        b.update_info(|info| info.history_min_height = Some(10));
+        assert_eq!(b.info().unwrap().history_min_height, Some(10));
+    }
+}
@@ -0,0 +1,146 @@
+//! Node Info Protocol
+//!
+//! A lightweight handshake in which nodes exchange node-level information on connection,
+//! beyond what identify provides: moniker, chain id, protocol version, supported protocols,
+//! whether the node is a validator, and the earliest height for which it retains decided values.
+//!
+//! ## Wire Format
+//!
+//! ```text
+//! [length: unsigned-varint][JSON-encoded NodeInfo]
+//! ```
+//!
+//! Unknown fields are ignored when decoding, so that fields can be added to [`NodeInfo`]
+//! without breaking the handshake with nodes running an older release.
+//!
+//! ## Handshake
+//!
+//! Like the [validator proof protocol](crate::validator_proof), each node sends its info
+//! on a one-way stream on the first connection to a peer, so that both sides learn about
+//! each other without a request-response round trip:
+//!
+//! ```text
+//! ConnectionEstablished event:
+//!   └─► behaviour.send_info(peer_id)  — first connection only (other_established == 0)
+//!       └─► protocol::send_info() spawned as task
+//!
+//! Stream received
+//!   └─► protocol::recv_info() — decodes the info
+//!       └─► Event::InfoReceived ──► network/lib.rs
+//!           └─► stored in `State::peer_node_info`, exported in the `peer_node_info` metric
+//! ```
+//!
+//! The info sent is a snapshot of the node when connecting: the local info is kept up to date
+//! as the validator set and the earliest retained height change, and the updated info is sent
+//! on the next connections.
+//!
+//! The info of the connected peers is available in [`NetworkStateDump::peer_node_info`](crate::NetworkStateDump).
+//!
+//! ## Failure Handling
+//!
+//! - Info which cannot be read or decoded (framing error, oversized or malformed message)
+//!   → behaviour emits `CloseConnection` → DISCONNECT
+//! - Duplicate info from the same peer in the same session → DISCONNECT (anti-spam)
+//! - Info from a peer on another chain → DISCONNECT (see `network/lib.rs`)
+//! - Send failures are only logged, the info is sent again on the next connection
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::ProtocolVersion;
+
+mod behaviour;
+mod protocol;
+
+pub use behaviour::{Behaviour, Error, Event};
+
+/// Information about a node, exchanged with its peers on connection.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeInfo {
+    /// Human-readable name of the node
+    pub moniker: String,
+    /// Identifier of the chain the node takes part in, if any
+    pub chain_id: Option<String>,
+    /// Version of the protocol spoken by the node
+    pub protocol_version: ProtocolVersion,
+    /// Names of the protocols supported by the node, eg. consensus and sync
+    pub protocols: Vec<String>,
+    /// Whether the node is in the validator set when sending its info
+    pub is_validator: bool,
+    /// Earliest height for which the node retains decided values, if known
+    pub history_min_height: Option<u64>,
+}
+
+impl NodeInfo {
+    /// Whether the peer with the given info takes part in another chain than this node.
+    ///
+    /// Nodes which do not advertise a chain id are assumed to take part in the same chain.
+    pub fn is_other_chain(&self, peer: &NodeInfo) -> bool {
+        match (&self.chain_id, &peer.chain_id) {
+            (Some(ours), Some(theirs)) => ours != theirs,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn encode(&self) -> Bytes {
+        serde_json::to_vec(self)
+            .expect("node info is always serializable")
+            .into()
+    }
+
+    pub(crate) fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        serde_json::from_slice(bytes).map_err(|e| Error::Decode(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_info(chain_id: Option<&str>) -> NodeInfo {
+        NodeInfo {
+            moniker: "node-0".to_string(),
+            chain_id: chain_id.map(str::to_string),
+            protocol_version: ProtocolVersion::new(1, 2, 3),
+            protocols: vec!["/malachitebft-sync/v1beta1".to_string()],
+            is_validator: true,
+            history_min_height: Some(42),
+        }
+    }
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        let info = node_info(Some("test-chain"));
+        assert_eq!(NodeInfo::decode(&info.encode()).unwrap(), info);
+    }
+
+    #[test]
+    fn decode_ignores_unknown_fields() {
+        let json = r#"{
+            "moniker": "node-1",
+            "chain_id": null,
+            "protocol_version": "1.0.0",
+            "protocols": [],
+            "is_validator": false,
+            "history_min_height": null,
+            "added_in_a_later_release": 7
+        }"#;
+
+        let info = NodeInfo::decode(json.as_bytes()).unwrap();
+        assert_eq!(info.moniker, "node-1");
+        assert_eq!(info.protocol_version, ProtocolVersion::new(1, 0, 0));
+
+        assert!(NodeInfo::decode(b"not json").is_err());
+        assert!(NodeInfo::decode(br#"{"moniker": "node-1"}"#).is_err());
+    }
+
+    #[test]
+    fn other_chain_requires_both_chain_ids() {
+        let ours = node_info(Some("chain-a"));
+
+        assert!(!ours.is_other_chain(&node_info(Some("chain-a"))));
+        assert!(ours.is_other_chain(&node_info(Some("chain-b"))));
+        assert!(!ours.is_other_chain(&node_info(None)));
+        assert!(!node_info(None).is_other_chain(&ours));
+    }
+}
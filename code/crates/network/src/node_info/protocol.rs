@@ -0,0 +1,111 @@
+//! Protocol handlers for sending and receiving node info.
+//!
+//! Uses unsigned-varint length-delimited framing, like the validator proof protocol.
+
+use std::time::Duration;
+
+use asynchronous_codec::{FramedRead, FramedWrite};
+use bytes::Bytes;
+use libp2p::futures::{SinkExt, StreamExt};
+use libp2p::{PeerId, Stream, StreamProtocol};
+use libp2p_stream as stream;
+use tokio::sync::mpsc;
+use tracing::{debug, error};
+use unsigned_varint::codec::UviBytes;
+
+use super::behaviour::{Error, Event};
+use super::NodeInfo;
+
+/// Maximum size of an encoded node info.
+/// The info is a few hundred bytes, so 4KB leaves room for many protocols.
+const MAX_MESSAGE_SIZE: usize = 4 * 1024;
+
+/// Timeout for reading the node info from a stream.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn codec() -> UviBytes {
+    let mut codec = UviBytes::default();
+    codec.set_max_len(MAX_MESSAGE_SIZE);
+    codec
+}
+
+/// Accept and handle incoming node info streams.
+pub async fn accept_incoming_streams(
+    mut control: stream::Control,
+    events_tx: mpsc::UnboundedSender<Event>,
+    protocol: StreamProtocol,
+) {
+    let mut incoming = match control.accept(protocol) {
+        Ok(incoming) => incoming,
+        Err(error) => {
+            error!(%error, "Failed to accept incoming node info streams");
+            return;
+        }
+    };
+
+    while let Some((peer, stream)) = incoming.next().await {
+        let events_tx = events_tx.clone();
+
+        tokio::spawn(async move {
+            let event = match recv_info(stream).await {
+                Ok(info) => Event::InfoReceived { peer, info },
+                Err(error) => Event::InfoReceiveFailed { peer, error },
+            };
+
+            let _ = events_tx.send(event);
+        });
+    }
+}
+
+/// Read and decode the node info of a peer from a stream.
+///
+/// Applies a timeout to prevent a peer from holding the stream open indefinitely.
+async fn recv_info(stream: Stream) -> Result<NodeInfo, Error> {
+    let mut reader = FramedRead::new(stream, codec());
+
+    let bytes = match tokio::time::timeout(READ_TIMEOUT, reader.next()).await {
+        Ok(Some(Ok(bytes))) => bytes,
+        Ok(Some(Err(e))) => return Err(Error::Io(e.to_string())),
+        Ok(None) => return Err(Error::UnexpectedEof),
+        Err(_) => return Err(Error::Io("read timed out".into())),
+    };
+
+    NodeInfo::decode(&bytes)
+}
+
+/// Send our node info to a peer.
+pub async fn send_info(
+    peer: PeerId,
+    info: Bytes,
+    mut control: stream::Control,
+    protocol: StreamProtocol,
+) -> Event {
+    let stream = match control.open_stream(peer, protocol).await {
+        Ok(stream) => stream,
+        Err(error) => {
+            return Event::InfoSendFailed {
+                peer,
+                error: Error::Io(error.to_string()),
+            };
+        }
+    };
+
+    let mut writer = FramedWrite::new(stream, codec());
+
+    let result = async {
+        writer.send(info).await?;
+        writer.close().await
+    }
+    .await;
+
+    match result {
+        Ok(()) => {
+            debug!(%peer, "Sent node info");
+            Event::InfoSent { peer }
+        }
+        Err(e) => Event::InfoSendFailed {
+            peer,
+            error: Error::Io(e.to_string()),
+        },
+    }
+}
@@ -0,0 +1,279 @@
+//! Filtering of the peers allowed to connect to the node, eg. in a permissioned network.
+//!
+//! The [`PeerFilter`] provided by the application is consulted with the peer id and
+//! public key of the peer on every inbound connection, as soon as the peer is authenticated,
+//! and again whenever a peer identifies itself, which also covers outbound connections.
+//! Connections with peers which are not allowed are closed.
+//!
+//! [`AllowList`] is a static list of allowed peers, eg. loaded from a file.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::{fmt, io};
+
+use libp2p::core::Endpoint;
+use libp2p::swarm::{
+    ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, THandler, THandlerInEvent,
+    THandlerOutEvent, ToSwarm,
+};
+use libp2p::Multiaddr;
+use tracing::debug;
+
+pub use libp2p::identity::PublicKey;
+
+use crate::{PeerId, PeerIdExt};
+
+/// Code of the identity multihash, in which public keys of up to 42 bytes are inlined.
+const IDENTITY_MULTIHASH_CODE: u64 = 0x00;
+
+/// Decides which peers may connect to the node.
+pub trait PeerFilter: fmt::Debug + Send + Sync + 'static {
+    /// Whether the peer with the given peer id and public key may connect to the node.
+    fn is_allowed(&self, peer_id: &PeerId, public_key: &PublicKey) -> bool;
+}
+
+/// A static list of the peers allowed to connect to the node.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllowList {
+    peers: HashSet<PeerId>,
+}
+
+impl AllowList {
+    pub fn new(peers: impl IntoIterator<Item = PeerId>) -> Self {
+        Self {
+            peers: peers.into_iter().collect(),
+        }
+    }
+
+    /// Load an allow-list from the given file, holding one peer id per line.
+    ///
+    /// Empty lines and comments, starting with `#`, are ignored.
+    pub fn from_file(path: &Path) -> Result<Self, AllowListError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Whether the given peer is in the list.
+    pub fn contains(&self, peer_id: &PeerId) -> bool {
+        self.peers.contains(peer_id)
+    }
+
+    /// The number of peers in the list.
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+}
+
+impl FromStr for AllowList {
+    type Err = AllowListError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut peers = HashSet::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let peer_id = line.parse().map_err(|_| AllowListError::InvalidPeerId {
+                line: index + 1,
+                peer_id: line.to_string(),
+            })?;
+
+            peers.insert(peer_id);
+        }
+
+        Ok(Self { peers })
+    }
+}
+
+impl PeerFilter for AllowList {
+    fn is_allowed(&self, peer_id: &PeerId, _public_key: &PublicKey) -> bool {
+        self.contains(peer_id)
+    }
+}
+
+/// Error returned when an allow-list cannot be loaded.
+#[derive(Debug, thiserror::Error)]
+pub enum AllowListError {
+    #[error("failed to read allow-list: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("invalid peer id '{peer_id}' at line {line}")]
+    InvalidPeerId { line: usize, peer_id: String },
+}
+
+/// The public key of the peer with the given peer id, if it is inlined in the peer id,
+/// as are Ed25519 keys. Larger keys, eg. ECDSA ones, are hashed into the peer id instead.
+pub fn public_key(peer_id: &libp2p::PeerId) -> Option<PublicKey> {
+    let multihash = peer_id.as_ref();
+
+    if multihash.code() != IDENTITY_MULTIHASH_CODE {
+        return None;
+    }
+
+    PublicKey::try_decode_protobuf(multihash.digest()).ok()
+}
+
+/// Behaviour that refuses inbound connections from the peers rejected by a [`PeerFilter`].
+///
+/// Peers whose public key cannot be recovered from their peer id are let through,
+/// to be filtered once they identify themselves.
+pub struct Behaviour {
+    filter: Arc<dyn PeerFilter>,
+}
+
+impl Behaviour {
+    pub fn new(filter: Arc<dyn PeerFilter>) -> Self {
+        Self { filter }
+    }
+
+    fn is_allowed(&self, peer: &libp2p::PeerId) -> bool {
+        match public_key(peer) {
+            Some(public_key) => self
+                .filter
+                .is_allowed(&PeerId::from_libp2p(peer), &public_key),
+            None => true,
+        }
+    }
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = libp2p::swarm::dummy::ConnectionHandler;
+    type ToSwarm = std::convert::Infallible;
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        peer: libp2p::PeerId,
+        _local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        if !self.is_allowed(&peer) {
+            debug!(%peer, %remote_addr, "Rejecting inbound connection: peer is not allowed");
+            return Err(ConnectionDenied::new(PeerNotAllowed(peer)));
+        }
+
+        Ok(libp2p::swarm::dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _connection_id: ConnectionId,
+        _peer: libp2p::PeerId,
+        _addr: &Multiaddr,
+        _role_override: Endpoint,
+        _port_use: libp2p::core::transport::PortUse,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(libp2p::swarm::dummy::ConnectionHandler)
+    }
+
+    fn on_swarm_event(&mut self, _event: FromSwarm<'_>) {}
+
+    fn on_connection_handler_event(
+        &mut self,
+        _peer_id: libp2p::PeerId,
+        _connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        // dummy::ConnectionHandler produces no events
+        match event {}
+    }
+
+    fn poll(
+        &mut self,
+        _cx: &mut Context<'_>,
+    ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+}
+
+/// Error returned when a peer is rejected by the peer filter.
+#[derive(Debug)]
+struct PeerNotAllowed(libp2p::PeerId);
+
+impl fmt::Display for PeerNotAllowed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "peer {} is not allowed to connect", self.0)
+    }
+}
+
+impl std::error::Error for PeerNotAllowed {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use libp2p::identity::Keypair;
+
+    fn peer() -> PeerId {
+        PeerId::from_libp2p(&Keypair::generate_ed25519().public().to_peer_id())
+    }
+
+    fn listen_addr() -> Multiaddr {
+        "/ip4/127.0.0.1/tcp/27000".parse().unwrap()
+    }
+
+    #[test]
+    fn parse_allow_list() {
+        let peer1 = peer();
+        let peer2 = peer();
+        let peer3 = peer();
+
+        let allow_list: AllowList = format!("# Validators\n{peer1}\n\n  {peer2}  # sentry\n")
+            .parse()
+            .unwrap();
+
+        assert_eq!(allow_list.len(), 2);
+        assert!(allow_list.contains(&peer1));
+        assert!(allow_list.contains(&peer2));
+        assert!(!allow_list.contains(&peer3));
+    }
+
+    #[test]
+    fn parse_allow_list_with_invalid_peer_id() {
+        let peer1 = peer();
+
+        let error = format!("{peer1}\nnot-a-peer-id\n")
+            .parse::<AllowList>()
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            AllowListError::InvalidPeerId { line: 2, ref peer_id } if peer_id == "not-a-peer-id"
+        ));
+    }
+
+    #[test]
+    fn public_key_is_recovered_from_peer_id() {
+        let key = Keypair::generate_ed25519().public();
+        assert_eq!(public_key(&key.to_peer_id()), Some(key));
+    }
+
+    #[test]
+    fn inbound_connections_are_filtered() {
+        let allowed = peer();
+        let rejected = peer();
+
+        let mut behaviour = Behaviour::new(Arc::new(AllowList::new([allowed])));
+
+        let mut connect = |peer: PeerId| {
+            behaviour.handle_established_inbound_connection(
+                ConnectionId::new_unchecked(1),
+                peer.to_libp2p(),
+                &listen_addr(),
+                &listen_addr(),
+            )
+        };
+
+        assert!(connect(allowed).is_ok());
+        assert!(connect(rejected).is_err());
+    }
+}
@@ -20,11 +20,21 @@
 //!
 //! The final peer score is: `app_specific_score × app_specific_weight`
 //!
-//! Note: We currently only use application-specific scoring based on peer type.
-//! Topic-specific behavioral scoring (penalties for invalid messages, mesh misbehavior, etc.)
-//! is not enabled.
-//! Enabling topic-specific scoring will be done in a future release. This requires passing message
-//! validation results from the application (malachite engine) layer to the network layer.
+//! On top of the application-specific score, peers are penalized for misbehaviour, as configured
+//! by [`GossipSubScoringConfig`]:
+//!
+//! - Invalid messages: each consensus topic has a weight, and a peer delivering invalid messages
+//!   (eg. with an invalid signature) on a topic receives
+//!   `topic_weight × -invalid_message_penalty × invalid_messages²`
+//! - Mesh misbehaviour: a peer grafting during its prune backoff, or within `graft_flood_threshold`
+//!   of a prune, receives `-behaviour_penalty × offences²`
+//!
+//! Both penalties decay over time, and have fully decayed after the configured decay times.
+//! Mesh delivery and time-in-mesh scoring are not enabled, as consensus topics can be quiet
+//! for long periods of time, which would otherwise penalize honest peers.
+//!
+//! Peers with a negative score are pruned from the mesh at every heartbeat, and are counted
+//! in the `gossipsub_penalized_peers` metric.
 //!
 //! ## Score Updates
 //!
@@ -46,16 +56,16 @@
 //! - opportunistic_graft_threshold (100,000): Set very high to maximize high value node density. Triggers
 //!   grafting whenever full nodes are present in the mesh, continuously replacing them with higher scored peers
 //!   until the mesh is entirely made up of higher scored peers (or no more are available).
-//! - gossip_threshold (-500): All nodes are well above this threshold and receive gossip messages.
-//! - publish_threshold (-1,000): All nodes are well above this threshold and can publish messages.
-//! - graylist_threshold (-2,000): All nodes are well above this threshold.
+//! - gossip_threshold (default: -500): Well-behaved nodes are well above this threshold and receive gossip messages.
+//! - publish_threshold (default: -1,000): Well-behaved nodes are well above this threshold and can publish messages.
+//! - graylist_threshold (default: -2,000): Peers below this threshold are ignored entirely.
 //!
 //! Full nodes remain functional (can publish and receive gossip) but are aggressively replaced in
 //! the mesh by higher scored peers through continuous opportunistic grafting.
 
 use libp2p::gossipsub;
 
-use crate::PeerType;
+use crate::{Channel, ChannelNames, GossipSubScoringConfig, PeerType};
 
 /// Application-Specific Scores
 ///
@@ -132,10 +142,57 @@ pub fn get_peer_score(peer_type: PeerType) -> f64 {
 
 /// Constructs the peer score parameters for GossipSub.
 ///
-/// Configures application-specific scoring with a weight multiplier to amplify score differences.
-pub fn peer_score_params() -> gossipsub::PeerScoreParams {
+/// Configures application-specific scoring with a weight multiplier to amplify score differences,
+/// together with the invalid message and mesh misbehaviour penalties of the scoring config.
+pub fn peer_score_params(
+    config: &GossipSubScoringConfig,
+    channel_names: ChannelNames,
+) -> gossipsub::PeerScoreParams {
+    let topics = Channel::consensus()
+        .iter()
+        .chain([&Channel::Votes])
+        .map(|channel| {
+            let topic_weight = match channel {
+                Channel::Consensus | Channel::Votes => config.consensus_topic_weight,
+                Channel::ProposalParts => config.proposal_parts_topic_weight,
+                Channel::Liveness => config.liveness_topic_weight,
+                Channel::Sync | Channel::Announcements => 0.0,
+            };
+
+            let params = topic_score_params(config, topic_weight);
+            (channel.to_gossipsub_topic(channel_names).hash(), params)
+        })
+        .collect();
+
     gossipsub::PeerScoreParams {
+        topics,
         app_specific_weight: APP_SPECIFIC_WEIGHT,
+        behaviour_penalty_weight: -config.behaviour_penalty,
+        behaviour_penalty_decay: gossipsub::score_parameter_decay(config.behaviour_penalty_decay),
+        ..Default::default()
+    }
+}
+
+/// Constructs the score parameters of a single topic, which only penalize invalid messages.
+fn topic_score_params(
+    config: &GossipSubScoringConfig,
+    topic_weight: f64,
+) -> gossipsub::TopicScoreParams {
+    gossipsub::TopicScoreParams {
+        topic_weight,
+        // P1: Time in mesh
+        time_in_mesh_weight: 0.0,
+        // P2: First message deliveries
+        first_message_deliveries_weight: 0.0,
+        // P3: Mesh message deliveries
+        mesh_message_deliveries_weight: 0.0,
+        // P3b: Mesh failure penalty
+        mesh_failure_penalty_weight: 0.0,
+        // P4: Invalid messages
+        invalid_message_deliveries_weight: -config.invalid_message_penalty,
+        invalid_message_deliveries_decay: gossipsub::score_parameter_decay(
+            config.invalid_message_decay,
+        ),
         ..Default::default()
     }
 }
@@ -147,12 +204,12 @@ pub fn peer_score_params() -> gossipsub::PeerScoreParams {
 /// - `gossip_threshold`: Peers below this don't receive gossip
 /// - `publish_threshold`: Peers below this can't publish messages
 /// - `graylist_threshold`: Peers below this are completely ignored
-pub fn peer_score_thresholds() -> gossipsub::PeerScoreThresholds {
+pub fn peer_score_thresholds(config: &GossipSubScoringConfig) -> gossipsub::PeerScoreThresholds {
     gossipsub::PeerScoreThresholds {
         opportunistic_graft_threshold: OPPORTUNISTIC_GRAFT_THRESHOLD,
-        gossip_threshold: -500.0,
-        publish_threshold: -1000.0,
-        graylist_threshold: -2000.0,
+        gossip_threshold: config.gossip_threshold,
+        publish_threshold: config.publish_threshold,
+        graylist_threshold: config.graylist_threshold,
         ..Default::default()
     }
 }
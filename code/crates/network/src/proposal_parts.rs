@@ -0,0 +1,54 @@
+//! Request-response protocol used to fetch missing proposal parts from peers.
+//!
+//! Proposal parts are gossiped, so a part lost along the way would otherwise cause the
+//! whole proposal to time out. The receiver of an incomplete stream of parts instead asks
+//! the proposer, or any other peer which has the part, to send it directly.
+//!
+//! The payloads are opaque to the network, the engine is responsible for their encoding.
+
+use bytes::Bytes;
+use eyre::Result;
+use libp2p::request_response::OutboundRequestId;
+use libp2p::swarm::NetworkBehaviour;
+use libp2p::PeerId;
+
+use malachitebft_sync as sync;
+
+/// Maximum size of a request for a proposal part.
+const MAX_REQUEST_SIZE: usize = 1024;
+
+#[derive(Debug)]
+pub struct Event(pub sync::Event);
+
+impl From<sync::Event> for Event {
+    fn from(event: sync::Event) -> Self {
+        Self(event)
+    }
+}
+
+#[derive(NetworkBehaviour)]
+#[behaviour(to_swarm = "Event")]
+pub struct Behaviour {
+    rpc: sync::Behaviour,
+}
+
+impl Behaviour {
+    pub fn new(max_response_size: usize, protocol: String) -> Result<Self> {
+        let config = sync::Config::default()
+            .with_max_request_size(MAX_REQUEST_SIZE)
+            .with_max_response_size(max_response_size);
+
+        Ok(Self {
+            rpc: sync::Behaviour::new(config, protocol)?,
+        })
+    }
+
+    pub fn send_request(&mut self, peer: PeerId, data: Bytes) -> OutboundRequestId {
+        self.rpc.send_request(peer, data)
+    }
+
+    pub fn send_response(&mut self, channel: sync::ResponseChannel, data: Bytes) -> Result<()> {
+        self.rpc.send_response(channel, data)?;
+        Ok(())
+    }
+}
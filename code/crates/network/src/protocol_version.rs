@@ -0,0 +1,129 @@
+//! Semantic versioning of the protocol spoken by the nodes, for coordinating network upgrades.
+//!
+//! Each node advertises the version of the protocol it speaks in the agent version it
+//! exchanges with its peers through identify. Peers speaking an incompatible version are
+//! ignored: they are not reported as connected, and the consensus messages they authored
+//! are refused. Peers are compatible if they share the same major version, and if their
+//! version is not below the minimum version configured on the node, if any.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Semantic version of the protocol spoken by a node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProtocolVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl ProtocolVersion {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Whether a peer speaking the given version is compatible with a node speaking this version
+    /// and requiring at least the given minimum version.
+    ///
+    /// Peers which did not advertise their version, eg. because they run an older release,
+    /// are only compatible if no minimum version is required.
+    pub fn is_compatible(&self, peer: Option<Self>, min: Option<Self>) -> bool {
+        match peer {
+            Some(peer) => peer.major == self.major && min.is_none_or(|min| peer >= min),
+            None => min.is_none(),
+        }
+    }
+}
+
+impl Default for ProtocolVersion {
+    fn default() -> Self {
+        Self::new(1, 0, 0)
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("invalid protocol version '{0}', expected <major>.<minor>.<patch>")]
+pub struct InvalidProtocolVersion(String);
+
+impl FromStr for ProtocolVersion {
+    type Err = InvalidProtocolVersion;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidProtocolVersion(s.to_string());
+
+        let mut parts = s
+            .split('.')
+            .map(|part| part.parse::<u64>().map_err(|_| invalid()));
+
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(major), Some(minor), Some(patch), None) => Ok(Self::new(major?, minor?, patch?)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Serialize for ProtocolVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ProtocolVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        let version: ProtocolVersion = "1.2.3".parse().unwrap();
+        assert_eq!(version, ProtocolVersion::new(1, 2, 3));
+        assert_eq!(version.to_string(), "1.2.3");
+
+        for invalid in ["", "1.2", "1.2.3.4", "1.x.3"] {
+            assert_eq!(
+                invalid.parse::<ProtocolVersion>(),
+                Err(InvalidProtocolVersion(invalid.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn compatibility() {
+        let ours = ProtocolVersion::new(1, 4, 0);
+        let v = ProtocolVersion::new;
+
+        // Same major version, no minimum
+        assert!(ours.is_compatible(Some(v(1, 0, 0)), None));
+        assert!(ours.is_compatible(Some(v(1, 9, 2)), None));
+        assert!(!ours.is_compatible(Some(v(2, 0, 0)), None));
+        assert!(!ours.is_compatible(Some(v(0, 9, 0)), None));
+
+        // With a minimum version
+        let min = Some(v(1, 3, 0));
+        assert!(ours.is_compatible(Some(v(1, 3, 0)), min));
+        assert!(ours.is_compatible(Some(v(1, 5, 1)), min));
+        assert!(!ours.is_compatible(Some(v(1, 2, 9)), min));
+
+        // Peers which do not advertise their version
+        assert!(ours.is_compatible(None, None));
+        assert!(!ours.is_compatible(None, min));
+    }
+}
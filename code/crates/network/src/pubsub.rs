@@ -34,6 +34,36 @@ pub fn subscribe(
     Ok(())
 }
 
+pub fn unsubscribe(
+    swarm: &mut swarm::Swarm<Behaviour>,
+    protocol: PubSubProtocol,
+    channels: &[Channel],
+    channel_names: ChannelNames,
+) -> Result<(), eyre::Report> {
+    match protocol {
+        PubSubProtocol::GossipSub => {
+            if let Some(gossipsub) = swarm.behaviour_mut().gossipsub.as_mut() {
+                for channel in channels {
+                    gossipsub.unsubscribe(&channel.to_gossipsub_topic(channel_names));
+                }
+            } else {
+                return Err(eyre::eyre!("GossipSub not enabled"));
+            }
+        }
+        PubSubProtocol::Broadcast => {
+            if let Some(broadcast) = swarm.behaviour_mut().broadcast.as_mut() {
+                for channel in channels {
+                    broadcast.unsubscribe(&channel.to_broadcast_topic(channel_names));
+                }
+            } else {
+                return Err(eyre::eyre!("Broadcast not enabled"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub fn publish(
     swarm: &mut swarm::Swarm<Behaviour>,
     protocol: PubSubProtocol,
@@ -2,18 +2,23 @@
 
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::time::{Duration, Instant};
 
 use libp2p::identify;
 use libp2p::request_response::InboundRequestId;
+use libp2p::swarm::ConnectionId;
 use libp2p::Multiaddr;
 use malachitebft_discovery as discovery;
 use malachitebft_discovery::util::strip_peer_id_from_multiaddr;
 use malachitebft_sync as sync;
 
 use crate::behaviour::Behaviour;
+use crate::envelope::Authenticator;
 use crate::metrics::Metrics as NetworkMetrics;
+use crate::node_info::NodeInfo;
 use crate::{Channel, ChannelNames, PeerType, PersistentPeerError};
 use malachitebft_discovery::ConnectionDirection;
+use malachitebft_peer::{PeerExit, PeerState};
 
 /// Public network state dump for external consumers
 #[derive(Clone, Debug)]
@@ -23,6 +28,8 @@ pub struct NetworkStateDump {
     pub validator_set: Vec<ValidatorInfo>,
     pub persistent_peer_ids: Vec<libp2p::PeerId>,
     pub persistent_peer_addrs: Vec<Multiaddr>,
+    /// Node info advertised by the connected peers, see the [`node_info`](crate::node_info) module
+    pub peer_node_info: std::collections::HashMap<libp2p::PeerId, NodeInfo>,
 }
 
 /// Validator information passed from consensus to network layer
@@ -150,7 +157,13 @@ impl PeerInfo {
 
 #[derive(Debug)]
 pub struct State {
-    pub sync_channels: HashMap<InboundRequestId, sync::ResponseChannel>,
+    /// Channels on which to reply to sync requests, together with the peers which sent them
+    pub sync_channels: HashMap<InboundRequestId, (libp2p::PeerId, sync::ResponseChannel)>,
+    /// Signs the sync requests and responses sent to peers and verifies the ones received,
+    /// if enabled
+    pub(crate) sync_envelopes: Option<Authenticator>,
+    /// Channels on which to reply to the requests for proposal parts
+    pub part_channels: HashMap<InboundRequestId, sync::ResponseChannel>,
     pub discovery: discovery::Discovery<Behaviour>,
     pub persistent_peer_ids: HashSet<libp2p::PeerId>,
     pub persistent_peer_addrs: Vec<Multiaddr>,
@@ -161,6 +174,8 @@ pub struct State {
     pub local_node: LocalNodeInfo,
     /// Detailed peer information indexed by PeerId
     pub peer_info: HashMap<libp2p::PeerId, PeerInfo>,
+    /// Node info advertised by the connected peers
+    pub peer_node_info: HashMap<libp2p::PeerId, NodeInfo>,
     /// Pending verified proofs for peers not yet in peer_info (Identify not received yet).
     ///
     /// rust-libp2p does not guarantee Identify runs before other protocols:
@@ -169,6 +184,13 @@ pub struct State {
     /// If proof verification completes before Identify, we buffer the public_key here
     /// and apply it when Identify completes and creates the PeerInfo.
     pub(crate) pending_verified_proofs: HashMap<libp2p::PeerId, Vec<u8>>,
+    /// Peers which are temporarily banned, together with the time at which the ban expires
+    pub(crate) banned_peers: HashMap<libp2p::PeerId, Instant>,
+    /// Connections on which the peer has not completed the identify handshake yet,
+    /// together with the time at which the connection was established
+    pub(crate) pending_handshakes: HashMap<ConnectionId, (libp2p::PeerId, Instant)>,
+    /// Connected peers speaking an incompatible version of the protocol, whose messages are refused
+    pub(crate) incompatible_peers: HashSet<libp2p::PeerId>,
 }
 
 impl State {
@@ -321,6 +343,22 @@ impl State {
         )
     }
 
+    /// Identified peers from which no valid proof was received yet.
+    pub(crate) fn unproven_peers(&self) -> Vec<libp2p::PeerId> {
+        self.peer_info
+            .iter()
+            .filter(|(_, peer_info)| peer_info.consensus_public_key.is_none())
+            .map(|(peer_id, _)| *peer_id)
+            .collect()
+    }
+
+    /// Whether the peer proved that it is a validator of the current validator set.
+    pub(crate) fn is_validator_peer(&self, peer_id: &libp2p::PeerId) -> bool {
+        self.peer_info
+            .get(peer_id)
+            .is_some_and(|info| info.peer_type.is_validator())
+    }
+
     pub(crate) fn new(
         discovery: discovery::Discovery<Behaviour>,
         persistent_peer_addrs: Vec<Multiaddr>,
@@ -335,6 +373,8 @@ impl State {
 
         Self {
             sync_channels: Default::default(),
+            part_channels: Default::default(),
+            sync_envelopes: None,
             discovery,
             persistent_peer_ids,
             persistent_peer_addrs,
@@ -342,10 +382,71 @@ impl State {
             metrics,
             local_node,
             peer_info: HashMap::new(),
+            peer_node_info: HashMap::new(),
             pending_verified_proofs: HashMap::new(),
+            banned_peers: HashMap::new(),
+            pending_handshakes: HashMap::new(),
+            incompatible_peers: HashSet::new(),
         }
     }
 
+    /// Ban a peer for the given duration.
+    ///
+    /// Persistent peers cannot be banned. Returns whether the peer was banned.
+    pub(crate) fn ban_peer(&mut self, peer_id: libp2p::PeerId, duration: Duration) -> bool {
+        if self.persistent_peer_ids.contains(&peer_id) {
+            return false;
+        }
+
+        self.banned_peers.insert(peer_id, Instant::now() + duration);
+        true
+    }
+
+    /// Check whether a peer is currently banned.
+    pub(crate) fn is_banned(&self, peer_id: &libp2p::PeerId) -> bool {
+        self.banned_peers
+            .get(peer_id)
+            .is_some_and(|until| *until > Instant::now())
+    }
+
+    /// Lift the bans which have expired.
+    pub(crate) fn prune_expired_bans(&mut self) {
+        let now = Instant::now();
+        self.banned_peers.retain(|_, until| *until > now);
+    }
+
+    /// Start tracking the identify handshake of a newly established connection.
+    pub(crate) fn start_handshake(&mut self, connection_id: ConnectionId, peer_id: libp2p::PeerId) {
+        self.pending_handshakes
+            .insert(connection_id, (peer_id, Instant::now()));
+    }
+
+    /// Stop tracking the handshake of a connection, once completed or when the connection is closed.
+    pub(crate) fn end_handshake(&mut self, connection_id: &ConnectionId) {
+        self.pending_handshakes.remove(connection_id);
+    }
+
+    /// Remove and return the connections which have not completed the handshake within the given timeout.
+    pub(crate) fn take_expired_handshakes(
+        &mut self,
+        timeout: Duration,
+    ) -> Vec<(libp2p::PeerId, ConnectionId)> {
+        let now = Instant::now();
+        let mut expired = Vec::new();
+
+        self.pending_handshakes
+            .retain(|connection_id, (peer_id, established)| {
+                if now.duration_since(*established) < timeout {
+                    return true;
+                }
+
+                expired.push((*peer_id, *connection_id));
+                false
+            });
+
+        expired
+    }
+
     /// Check if a peer is persistent, by PeerId or by connection address.
     fn is_persistent_peer(
         &self,
@@ -353,7 +454,7 @@ impl State {
         connection_id: libp2p::swarm::ConnectionId,
     ) -> bool {
         self.persistent_peer_ids.contains(peer_id)
-            || self.is_persistent_peer_by_address(connection_id)
+            || self.is_persistent_peer_by_address(peer_id, connection_id)
     }
 
     /// Check if a peer is a persistent peer by matching its addresses against persistent peer addresses
@@ -361,7 +462,14 @@ impl State {
     /// For inbound connections, we use the actual remote address from the connection endpoint
     /// to prevent address spoofing attacks where a malicious peer could claim to be a
     /// persistent peer by faking its `listen_addrs` in the Identify message.
-    fn is_persistent_peer_by_address(&self, connection_id: libp2p::swarm::ConnectionId) -> bool {
+    ///
+    /// If the persistent peer address pins a peer ID with a `/p2p/<peer_id>` suffix,
+    /// a peer found at that address with another identity is not considered persistent.
+    fn is_persistent_peer_by_address(
+        &self,
+        peer_id: &libp2p::PeerId,
+        connection_id: libp2p::swarm::ConnectionId,
+    ) -> bool {
         // Use actual remote address for both inbound and outbound connections
         // This prevents address spoofing for inbound, and for outbound it's the address we dialed
         let Some(conn_info) = self.discovery.connections.get(&connection_id) else {
@@ -373,8 +481,20 @@ impl State {
         for persistent_addr in &self.persistent_peer_addrs {
             let persistent_addr_without_p2p = strip_peer_id_from_multiaddr(persistent_addr);
 
-            if remote_addr_without_p2p == persistent_addr_without_p2p {
-                return true;
+            if remote_addr_without_p2p != persistent_addr_without_p2p {
+                continue;
+            }
+
+            match extract_peer_id_from_multiaddr(persistent_addr) {
+                Some(pinned) if pinned != *peer_id => {
+                    tracing::warn!(
+                        %peer_id,
+                        expected = %pinned,
+                        address = %conn_info.remote_addr,
+                        "Peer at the address of a persistent peer presented an unexpected identity"
+                    );
+                }
+                _ => return true,
             }
         }
 
@@ -405,12 +525,29 @@ impl State {
             }
         }
 
+        let mut penalized_peers = 0;
+
         // Update score and topics for all peers in State
         for (peer_id, peer_info) in self.peer_info.iter_mut() {
             // Use GossipSub score if available, otherwise use internal score based on peer type
             let new_score = gossipsub.peer_score(peer_id).unwrap_or(peer_info.score);
             let new_topics = peer_topics.get(peer_id).cloned().unwrap_or_default();
 
+            // Application-specific scores are never negative, so a negative score
+            // means that the peer has been penalized for misbehaving
+            if new_score < 0.0 {
+                penalized_peers += 1;
+
+                if peer_info.score >= 0.0 {
+                    tracing::warn!(
+                        %peer_id,
+                        moniker = %peer_info.moniker,
+                        score = new_score,
+                        "Peer has been penalized by GossipSub"
+                    );
+                }
+            }
+
             // Update metrics before updating peer_info.topics
             // (metrics needs to compare old vs new topics)
             let _ = self.metrics.update_peer_metrics(
@@ -424,6 +561,8 @@ impl State {
             peer_info.score = new_score;
             peer_info.topics = new_topics;
         }
+
+        self.metrics.set_gossipsub_penalized_peers(penalized_peers);
     }
 
     /// Update the peer information after Identify completes and compute peer score.
@@ -642,6 +781,15 @@ impl State {
         // when we successfully connected to this address
         let peer_id = self.discovery.get_peer_id_for_addr(&addr);
 
+        // Update discovery layer
+        self.discovery.remove_bootstrap_node(&addr);
+
+        // The peer remains persistent if it is still reachable at another of its addresses
+        if peer_id.is_some_and(|peer_id| self.discovery.is_persistent_peer(&peer_id)) {
+            self.discovery.cancel_dial_attempts(&addr, None);
+            return Ok(());
+        }
+
         if let Some(peer_id) = peer_id {
             self.persistent_peer_ids.remove(&peer_id);
 
@@ -669,9 +817,6 @@ impl State {
         // Cancel any in-progress dial attempts for this address and peer
         self.discovery.cancel_dial_attempts(&addr, peer_id);
 
-        // Update discovery layer
-        self.discovery.remove_bootstrap_node(&addr);
-
         Ok(())
     }
 
@@ -753,6 +898,38 @@ impl State {
     }
 }
 
+impl PeerState<libp2p::PeerId> for State {
+    fn forget_peer(&mut self, peer_id: &libp2p::PeerId, exit: PeerExit) {
+        if let Some(peer_info) = self.peer_info.remove(peer_id) {
+            self.metrics.free_slot(peer_id, &peer_info);
+        }
+
+        if let Some(node_info) = self.peer_node_info.remove(peer_id) {
+            self.metrics.remove_peer_node_info(peer_id, &node_info);
+        }
+
+        // Also clean up any pending proof (proof verified before Identify completed)
+        self.pending_verified_proofs.remove(peer_id);
+
+        // The peer may come back with an upgraded version of the protocol
+        self.incompatible_peers.remove(peer_id);
+
+        // Responses can no longer be sent to the peer
+        self.sync_channels.retain(|_, (peer, _)| peer != peer_id);
+
+        self.discovery.forget_peer(peer_id, exit);
+    }
+
+    fn peer_entries(&self) -> usize {
+        self.peer_info.len()
+            + self.peer_node_info.len()
+            + self.pending_verified_proofs.len()
+            + self.incompatible_peers.len()
+            + self.sync_channels.len()
+            + self.discovery.peer_entries()
+    }
+}
+
 /// Extract PeerId from a Multiaddr if it contains a /p2p/<peer_id> component
 fn extract_peer_id_from_multiaddr(addr: &Multiaddr) -> Option<libp2p::PeerId> {
     use libp2p::multiaddr::Protocol;
@@ -789,7 +966,7 @@ fn apply_peer_type_change(
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::peer_scoring::{FULL_NODE_SCORE, VALIDATOR_SCORE};
     use malachitebft_discovery::Config;
@@ -802,8 +979,12 @@ mod tests {
     /// Create a minimal `State` with disabled discovery and an optional local consensus address.
     fn test_state_with_local_addr(consensus_address: Option<&str>) -> State {
         let mut registry = malachitebft_metrics::Registry::default();
-        let discovery =
-            discovery::Discovery::<Behaviour>::new(Config::new(false), vec![], &mut registry);
+        let discovery = discovery::Discovery::<Behaviour>::new(
+            Config::new(false),
+            vec![],
+            vec![],
+            &mut registry,
+        );
         let metrics = NetworkMetrics::new(&mut registry);
 
         let local_node = LocalNodeInfo {
@@ -908,6 +1089,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn only_peers_with_a_verified_proof_are_validator_peers() {
+        let mut state = test_state();
+        let validator = libp2p::PeerId::random();
+        let full_node = libp2p::PeerId::random();
+        let public_key = vec![7, 8, 9];
+
+        insert_peer(&mut state, validator, test_peer_info());
+        insert_peer(&mut state, full_node, test_peer_info());
+        state.validator_set.insert(ValidatorInfo {
+            address: "val_addr_1".to_string(),
+            public_key: public_key.clone(),
+            voting_power: 100,
+        });
+
+        assert!(!state.is_validator_peer(&validator));
+
+        state.record_verified_proof(&validator, public_key);
+
+        assert!(state.is_validator_peer(&validator));
+        assert!(!state.is_validator_peer(&full_node));
+        assert!(!state.is_validator_peer(&libp2p::PeerId::random()));
+    }
+
     // ── reclassify_peers (via process_validator_set_update) ──────────
 
     #[test]
@@ -1053,6 +1258,22 @@ mod tests {
         assert_eq!(score, VALIDATOR_SCORE);
     }
 
+    #[test]
+    fn unproven_peers_are_peers_without_verified_proof() {
+        let mut state = test_state();
+        let proven = libp2p::PeerId::random();
+        let unproven = libp2p::PeerId::random();
+
+        insert_peer(&mut state, proven, test_peer_info());
+        insert_peer(&mut state, unproven, test_peer_info());
+        state.record_verified_proof(&proven, vec![1, 2, 3]);
+
+        // Proofs buffered until identify completes are not requested again
+        state.record_verified_proof(&libp2p::PeerId::random(), vec![4, 5, 6]);
+
+        assert_eq!(state.unproven_peers(), vec![unproven]);
+    }
+
     // ── Persistent peer + proof ──────────────────────────────────────
 
     #[test]
@@ -1087,7 +1308,7 @@ mod tests {
     ///
     /// `InboundRequestId` has no public constructor; we transmute from `u64`.
     /// This is sound because `InboundRequestId` is a newtype wrapping `u64` with no invariants.
-    fn test_inbound_request_id(id: u64) -> InboundRequestId {
+    pub(crate) fn test_inbound_request_id(id: u64) -> InboundRequestId {
         // SAFETY: InboundRequestId is a #[repr(Rust)] newtype over u64.
         unsafe { std::mem::transmute(id) }
     }
@@ -1109,7 +1330,9 @@ mod tests {
         let channel = test_response_channel();
 
         // Simulate Message::Request inserting the channel
-        state.sync_channels.insert(request_id, channel);
+        state
+            .sync_channels
+            .insert(request_id, (libp2p::PeerId::random(), channel));
         assert_eq!(state.sync_channels.len(), 1);
 
         // Simulate InboundFailure cleanup
@@ -1124,7 +1347,9 @@ mod tests {
         let request_id = test_inbound_request_id(2);
         let channel = test_response_channel();
 
-        state.sync_channels.insert(request_id, channel);
+        state
+            .sync_channels
+            .insert(request_id, (libp2p::PeerId::random(), channel));
 
         // InboundFailure cleans up first
         state.sync_channels.remove(&request_id);
@@ -1140,7 +1365,9 @@ mod tests {
         let request_id = test_inbound_request_id(3);
         let channel = test_response_channel();
 
-        state.sync_channels.insert(request_id, channel);
+        state
+            .sync_channels
+            .insert(request_id, (libp2p::PeerId::random(), channel));
 
         // SyncReply arrives first and consumes the channel
         let reply_remove = state.sync_channels.remove(&request_id);
@@ -1158,7 +1385,8 @@ mod tests {
         let mut registry = malachitebft_metrics::Registry::default();
         let mut config = malachitebft_discovery::Config::new(false);
         config.set_peers_bounds(capacity, capacity);
-        let discovery = discovery::Discovery::<Behaviour>::new(config, vec![], &mut registry);
+        let discovery =
+            discovery::Discovery::<Behaviour>::new(config, vec![], vec![], &mut registry);
         let metrics = NetworkMetrics::new(&mut registry);
 
         let local_node = LocalNodeInfo {
@@ -1339,4 +1567,125 @@ mod tests {
             Some(malachitebft_discovery::ConnectionDirection::Inbound)
         );
     }
+
+    // ── Peer bans ────────────────────────────────────────────────────
+
+    #[test]
+    fn ban_peer_expires() {
+        let mut state = test_state();
+        let peer_id = libp2p::PeerId::random();
+
+        assert!(!state.is_banned(&peer_id));
+
+        assert!(state.ban_peer(peer_id, Duration::from_secs(60)));
+        assert!(state.is_banned(&peer_id));
+
+        assert!(state.ban_peer(peer_id, Duration::ZERO));
+        assert!(!state.is_banned(&peer_id));
+
+        state.prune_expired_bans();
+        assert!(state.banned_peers.is_empty());
+    }
+
+    #[test]
+    fn ban_peer_ignores_persistent_peers() {
+        let mut state = test_state();
+        let peer_id = libp2p::PeerId::random();
+        state.persistent_peer_ids.insert(peer_id);
+
+        assert!(!state.ban_peer(peer_id, Duration::from_secs(60)));
+        assert!(!state.is_banned(&peer_id));
+    }
+
+    // ── Handshake deadline ───────────────────────────────────────────
+
+    #[test]
+    fn pending_handshakes_expire() {
+        let mut state = test_state();
+        let peer_id = libp2p::PeerId::random();
+        let completed = ConnectionId::new_unchecked(1);
+        let pending = ConnectionId::new_unchecked(2);
+
+        state.start_handshake(completed, peer_id);
+        state.start_handshake(pending, peer_id);
+        state.end_handshake(&completed);
+
+        assert!(state
+            .take_expired_handshakes(Duration::from_secs(60))
+            .is_empty());
+
+        assert_eq!(
+            state.take_expired_handshakes(Duration::ZERO),
+            vec![(peer_id, pending)]
+        );
+        assert!(state.pending_handshakes.is_empty());
+    }
+
+    #[test]
+    fn pinned_persistent_peer_address_requires_matching_identity() {
+        let pinned = libp2p::PeerId::random();
+        let other = libp2p::PeerId::random();
+
+        let mut state = test_state();
+        state.persistent_peer_addrs = vec![
+            format!("/ip4/10.0.0.1/tcp/26656/p2p/{pinned}")
+                .parse()
+                .unwrap(),
+            "/ip4/10.0.0.2/tcp/26656".parse().unwrap(),
+        ];
+
+        let mut connect = |id, addr: &str| {
+            let connection_id = libp2p::swarm::ConnectionId::new_unchecked(id);
+            state.discovery.connections.insert(
+                connection_id,
+                discovery::ConnectionInfo {
+                    direction: discovery::ConnectionDirection::Outbound,
+                    remote_addr: addr.parse().unwrap(),
+                },
+            );
+            connection_id
+        };
+
+        let pinned_conn = connect(1, "/ip4/10.0.0.1/tcp/26656");
+        let unpinned_conn = connect(2, "/ip4/10.0.0.2/tcp/26656");
+
+        // The pinned address is only persistent with the pinned identity
+        assert!(state.is_persistent_peer_by_address(&pinned, pinned_conn));
+        assert!(!state.is_persistent_peer_by_address(&other, pinned_conn));
+
+        // Any identity is accepted at an address without a pinned peer id
+        assert!(state.is_persistent_peer_by_address(&other, unpinned_conn));
+    }
+
+    #[test]
+    fn churned_peers_are_forgotten() {
+        let mut state = test_state();
+
+        for i in 0..2000 {
+            let peer_id = libp2p::PeerId::random();
+
+            insert_peer(&mut state, peer_id, test_peer_info());
+            state.pending_verified_proofs.insert(peer_id, vec![1, 2, 3]);
+            state.incompatible_peers.insert(peer_id);
+            state.peer_node_info.insert(
+                peer_id,
+                NodeInfo {
+                    moniker: "peer".to_string(),
+                    chain_id: None,
+                    protocol_version: crate::ProtocolVersion::new(1, 0, 0),
+                    protocols: vec![],
+                    is_validator: false,
+                    history_min_height: None,
+                },
+            );
+            state.sync_channels.insert(
+                test_inbound_request_id(i),
+                (peer_id, test_response_channel()),
+            );
+
+            state.forget_peer(&peer_id, PeerExit::Disconnected);
+        }
+
+        assert_eq!(state.peer_entries(), 0);
+    }
 }
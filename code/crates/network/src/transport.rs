@@ -0,0 +1,174 @@
+//! Upgrade of the TCP and Unix domain socket transports with the security and multiplexing
+//! protocols, bounding the time spent establishing connections.
+//!
+//! Dials which are stuck, eg. towards an unreachable address, and peers which are slow to
+//! negotiate the protocols hold a connection slot until they give up. Each stage of the
+//! establishment of a connection is thus bounded by its own timeout, distinct from the
+//! idle timeout which closes established connections once they are no longer in use:
+//!
+//! - the dial timeout bounds the establishment of the transport connection to a peer,
+//!   eg. the TCP handshake,
+//! - the upgrade timeout bounds the negotiation of the security (Noise) and multiplexing
+//!   (Yamux) protocols. Outbound connections must be dialed and upgraded within the sum
+//!   of both timeouts, so that the upgrade is always allowed at least the upgrade timeout.
+//!
+//! Connections which time out fail with an [`io::ErrorKind::TimedOut`] error
+//! carrying the [`ConnectionTimeout`], and are counted in the `connection_timeouts` metric.
+//!
+//! The identify handshake which follows, bounded by the handshake timeout, is enforced
+//! by the network actor, which closes the connections of peers which do not complete it in time.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use libp2p::core::muxing::StreamMuxerBox;
+use libp2p::core::transport::timeout::{TransportTimeout, TransportTimeoutError};
+use libp2p::core::transport::{Boxed, DialOpts, ListenerId, TransportError, TransportEvent};
+use libp2p::core::upgrade;
+use libp2p::futures::{AsyncRead, AsyncWrite};
+use libp2p::identity::Keypair;
+use libp2p::multiaddr::Protocol;
+use libp2p::{noise, yamux, Multiaddr, PeerId, Transport};
+use libp2p_uds::TokioUdsConfig;
+
+use crate::metrics::ConnectionTimeouts;
+
+/// Stage of the establishment of a connection which timed out
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ConnectionTimeout {
+    /// The transport connection could not be established in time
+    Dial,
+    /// The security and multiplexing protocols could not be negotiated in time
+    Upgrade,
+    /// The peer did not complete the identify handshake in time
+    Handshake,
+}
+
+impl ConnectionTimeout {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dial => "dial",
+            Self::Upgrade => "upgrade",
+            Self::Handshake => "handshake",
+        }
+    }
+}
+
+impl fmt::Display for ConnectionTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} timed out", self.as_str())
+    }
+}
+
+impl std::error::Error for ConnectionTimeout {}
+
+/// Unix domain socket transport, accepting socket paths which are percent-encoded,
+/// eg. `/unix/%2Ftmp%2Fnode.sock`, as the multiaddr parser does not decode them.
+#[derive(Default)]
+pub(crate) struct UnixTransport(TokioUdsConfig);
+
+impl UnixTransport {
+    fn decode_path(addr: Multiaddr) -> Multiaddr {
+        addr.into_iter()
+            .map(|protocol| match protocol {
+                Protocol::Unix(path) => {
+                    let decoded = percent_encoding::percent_decode_str(&path).decode_utf8_lossy();
+                    Protocol::Unix(Cow::Owned(decoded.into_owned()))
+                }
+                protocol => protocol,
+            })
+            .collect()
+    }
+}
+
+impl Transport for UnixTransport {
+    type Output = <TokioUdsConfig as Transport>::Output;
+    type Error = <TokioUdsConfig as Transport>::Error;
+    type ListenerUpgrade = <TokioUdsConfig as Transport>::ListenerUpgrade;
+    type Dial = <TokioUdsConfig as Transport>::Dial;
+
+    fn listen_on(
+        &mut self,
+        id: ListenerId,
+        addr: Multiaddr,
+    ) -> Result<(), TransportError<Self::Error>> {
+        self.0.listen_on(id, Self::decode_path(addr))
+    }
+
+    fn remove_listener(&mut self, id: ListenerId) -> bool {
+        self.0.remove_listener(id)
+    }
+
+    fn dial(
+        &mut self,
+        addr: Multiaddr,
+        opts: DialOpts,
+    ) -> Result<Self::Dial, TransportError<Self::Error>> {
+        self.0.dial(Self::decode_path(addr), opts)
+    }
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+/// Upgrade the given transport with Noise and Yamux, bounding the time
+/// to dial and upgrade connections with the given timeouts.
+pub(crate) fn upgrade<T>(
+    transport: T,
+    keypair: &Keypair,
+    version: upgrade::Version,
+    dial_timeout: Duration,
+    upgrade_timeout: Duration,
+    timeouts: ConnectionTimeouts,
+) -> Result<Boxed<(PeerId, StreamMuxerBox)>, noise::Error>
+where
+    T: Transport + Send + Unpin + 'static,
+    T::Output: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T::Dial: Send + 'static,
+    T::ListenerUpgrade: Send + 'static,
+    T::Error: std::error::Error + Send + Sync + 'static,
+{
+    let transport = TransportTimeout::with_outgoing_timeout(transport, dial_timeout)
+        .map_err(on_timeout(ConnectionTimeout::Dial, timeouts.clone()))
+        .upgrade(version)
+        .authenticate(noise::Config::new(keypair)?)
+        .multiplex(yamux::Config::default());
+
+    // The timer of outbound connections starts with the dial, which is bounded by the dial timeout
+    let transport =
+        TransportTimeout::with_outgoing_timeout(transport, dial_timeout + upgrade_timeout)
+            .map_err(on_timeout(ConnectionTimeout::Upgrade, timeouts.clone()));
+
+    let transport = TransportTimeout::with_ingoing_timeout(transport, upgrade_timeout)
+        .map_err(on_timeout(ConnectionTimeout::Upgrade, timeouts));
+
+    Ok(transport
+        .map(|(peer_id, muxer), _| (peer_id, StreamMuxerBox::new(muxer)))
+        .boxed())
+}
+
+/// Record the connections which timed out at the given stage, and report them as such
+fn on_timeout<E>(
+    timeout: ConnectionTimeout,
+    timeouts: ConnectionTimeouts,
+) -> impl FnOnce(TransportTimeoutError<E>) -> io::Error + Clone + Send + 'static
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    move |error| match error {
+        TransportTimeoutError::Timeout => {
+            timeouts.record(timeout);
+            io::Error::new(io::ErrorKind::TimedOut, timeout)
+        }
+        TransportTimeoutError::TimerError(e) => e,
+        TransportTimeoutError::Other(e) => io::Error::other(e),
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::hash::Hash;
 
+use crate::ProtocolVersion;
+
 pub(crate) type Slot = usize;
 
 /// Manages the assignment of stable slots (0..N) to entries.
@@ -77,30 +79,66 @@ where
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AgentInfo {
     pub moniker: String,
+    /// Version of the protocol spoken by the peer, if advertised and valid
+    pub protocol_version: Option<ProtocolVersion>,
+}
+
+/// Build the agent_version string advertised to peers.
+///
+/// Format: "moniker=<name>,protocol_version=<major>.<minor>.<patch>"
+pub fn agent_version(moniker: &str, protocol_version: ProtocolVersion) -> String {
+    format!("moniker={moniker},protocol_version={protocol_version}")
 }
 
-/// Parse agent_version string to extract moniker.
+/// Parse agent_version string to extract moniker and protocol version.
 ///
-/// Expected format: "moniker=<name>"
+/// Expected format: "moniker=<name>,protocol_version=<major>.<minor>.<patch>"
 ///
 /// Returns `AgentInfo` with parsed moniker. Defaults to "unknown" if not found.
+/// Peers running older releases do not advertise their protocol version.
 pub fn parse_agent_version(agent_version: &str) -> AgentInfo {
     let mut moniker = String::from("unknown");
+    let mut protocol_version = None;
 
     for part in agent_version.split(',') {
         let part = part.trim();
         if let Some(mon) = part.strip_prefix("moniker=") {
             moniker = mon.to_string();
+        } else if let Some(version) = part.strip_prefix("protocol_version=") {
+            protocol_version = version.parse().ok();
         }
     }
 
-    AgentInfo { moniker }
+    AgentInfo {
+        moniker,
+        protocol_version,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_agent_version_roundtrip() {
+        let agent_info =
+            parse_agent_version(&agent_version("node-1", ProtocolVersion::new(1, 2, 0)));
+        assert_eq!(agent_info.moniker, "node-1");
+        assert_eq!(
+            agent_info.protocol_version,
+            Some(ProtocolVersion::new(1, 2, 0))
+        );
+
+        // Peers running older releases only advertise their moniker
+        let agent_info = parse_agent_version("moniker=node-2");
+        assert_eq!(agent_info.moniker, "node-2");
+        assert_eq!(agent_info.protocol_version, None);
+
+        let agent_info = parse_agent_version("protocol_version=latest");
+        assert_eq!(agent_info.moniker, "unknown");
+        assert_eq!(agent_info.protocol_version, None);
+    }
+
     #[test]
     fn test_initial_state() {
         let slots: Slots<i32> = Slots::new(5);
@@ -2,9 +2,13 @@
 //!
 //! This is a one-way protocol where validators send their proof to peers.
 //! No response is expected - the receiver just stores the proof.
+//!
+//! Peers may also ask for the proof again on a companion request protocol,
+//! eg. when the validator set changes, see [`Behaviour::request_proof`].
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::task::{self, Poll};
+use std::time::{Duration, Instant};
 
 use bytes::Bytes;
 use libp2p::swarm::behaviour::ConnectionEstablished;
@@ -18,6 +22,7 @@ use tokio::sync::mpsc;
 use tracing::{debug, trace, warn};
 
 use super::protocol;
+use crate::envelope::{self, Authenticator};
 
 /// Events emitted by the Validator Proof behaviour.
 #[derive(Debug)]
@@ -30,8 +35,15 @@ pub enum Event {
     ProofSendFailed { peer: PeerId, error: Error },
     /// Failed to receive a valid proof from peer (should disconnect).
     ProofReceiveFailed { peer: PeerId, error: Error },
+    /// A peer asked for our proof, which is sent again unless it asked too recently.
+    ProofRequested { peer: PeerId },
+    /// Failed to ask a peer for its proof, eg. because it runs an older release.
+    ProofRequestFailed { peer: PeerId, error: Error },
 }
 
+/// Minimum interval between two proof requests of the same peer which are served.
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(10);
+
 /// Errors that can occur in the Validator Proof protocol.
 #[derive(Clone, Debug, Error)]
 pub enum Error {
@@ -49,6 +61,9 @@ pub struct Behaviour {
     /// Protocol name for validator proof (e.g. `/malachitebft-validator-proof/v1`).
     protocol: StreamProtocol,
 
+    /// Protocol name for proof requests, the proof protocol name suffixed with `/request`.
+    request_protocol: StreamProtocol,
+
     /// Proof bytes to send (if we're a validator).
     proof_bytes: Option<Bytes>,
 
@@ -60,8 +75,15 @@ pub struct Behaviour {
     /// Cleared when the last connection to a peer closes.
     proofs_received: HashSet<PeerId>,
 
+    /// When we last sent our proof to each peer which requested it (anti-spam).
+    /// Cleared when the last connection to a peer closes.
+    requests_served: HashMap<PeerId, Instant>,
+
     /// Whether we're listening for incoming streams.
     listening: bool,
+
+    /// Signs the proofs sent to peers and verifies the ones received, if enabled.
+    envelopes: Option<Authenticator>,
 }
 
 impl Behaviour {
@@ -69,17 +91,30 @@ impl Behaviour {
     pub fn new(protocol: StreamProtocol) -> Self {
         let (events_tx, events_rx) = mpsc::unbounded_channel();
 
+        let request_protocol = StreamProtocol::try_from_owned(format!("{protocol}/request"))
+            .expect("protocol names start with a slash");
+
         Self {
             inner: stream::Behaviour::new(),
             protocol,
+            request_protocol,
             proof_bytes: None,
             events_rx,
             events_tx,
             proofs_received: HashSet::new(),
+            requests_served: HashMap::new(),
             listening: false,
+            envelopes: None,
         }
     }
 
+    /// Sign the proofs sent to peers in envelopes, and reject the proofs received
+    /// from peers which are not in a valid envelope.
+    pub fn with_envelopes(mut self, envelopes: Authenticator) -> Self {
+        self.envelopes = Some(envelopes);
+        self
+    }
+
     /// Create a behaviour with the default protocol name (for tests or when not using config).
     /// Prefer [`new`](Self::new) with the protocol from config to match sync/identify.
     pub fn with_default_protocol() -> Self {
@@ -105,10 +140,19 @@ impl Behaviour {
             return false;
         };
 
+        // Seal the proof for this peer, as envelopes are bound to their recipient
+        let proof_bytes =
+            match envelope::seal(self.envelopes.as_mut(), &peer_id, proof_bytes.clone()) {
+                Ok(proof_bytes) => proof_bytes,
+                Err(error) => {
+                    warn!(%peer_id, %error, "Failed to sign validator proof");
+                    return false;
+                }
+            };
+
         let control = self.inner.new_control();
         let events_tx = self.events_tx.clone();
         let protocol = self.protocol.clone();
-        let proof_bytes = proof_bytes.clone();
 
         tokio::spawn(async move {
             let event = protocol::send_proof(peer_id, proof_bytes, control, protocol).await;
@@ -118,6 +162,41 @@ impl Behaviour {
         true
     }
 
+    /// Ask a connected peer to send its proof again, eg. because the validator set changed
+    /// and we have not received a valid proof from it.
+    ///
+    /// The proof received in response is not treated as a duplicate.
+    pub fn request_proof(&mut self, peer_id: PeerId) {
+        self.proofs_received.remove(&peer_id);
+
+        let control = self.inner.new_control();
+        let events_tx = self.events_tx.clone();
+        let protocol = self.request_protocol.clone();
+
+        tokio::spawn(async move {
+            if let Some(event) = protocol::request_proof(peer_id, control, protocol).await {
+                let _ = events_tx.send(event);
+            }
+        });
+    }
+
+    /// Send our proof again to a peer which requested it, unless it did so too recently.
+    /// Returns true if the send was initiated.
+    fn serve_proof_request(&mut self, peer_id: PeerId, now: Instant) -> bool {
+        if let Some(served_at) = self.requests_served.get(&peer_id) {
+            if now.saturating_duration_since(*served_at) < MIN_REQUEST_INTERVAL {
+                return false;
+            }
+        }
+
+        if !self.send_proof(peer_id) {
+            return false;
+        }
+
+        self.requests_served.insert(peer_id, now);
+        true
+    }
+
     fn start_listening(&mut self) {
         if self.listening {
             // If there are multiple listen addresses, we may get multiple NewListenAddr events - only start once
@@ -134,6 +213,14 @@ impl Behaviour {
             protocol::accept_incoming_streams(control, events_tx, protocol).await;
         });
 
+        let control = self.inner.new_control();
+        let events_tx = self.events_tx.clone();
+        let protocol = self.request_protocol.clone();
+
+        tokio::spawn(async move {
+            protocol::accept_proof_requests(control, events_tx, protocol).await;
+        });
+
         debug!(protocol = %self.protocol, "Listening for incoming validator proof");
     }
 
@@ -162,6 +249,7 @@ impl Behaviour {
         let peer_id = conn.peer_id;
         trace!(%peer_id, "Last connection closed, cleaning up proof state");
         self.proofs_received.remove(&peer_id);
+        self.requests_served.remove(&peer_id);
     }
 }
 
@@ -240,7 +328,7 @@ impl NetworkBehaviour for Behaviour {
     ) -> Poll<ToSwarm<Self::ToSwarm, libp2p::swarm::THandlerInEvent<Self>>> {
         // Check for events from protocol tasks
         if let Poll::Ready(Some(event)) = self.events_rx.poll_recv(cx) {
-            match &event {
+            match event {
                 Event::ProofSendFailed { .. } => {
                     return Poll::Ready(ToSwarm::GenerateEvent(event));
                 }
@@ -248,20 +336,47 @@ impl NetworkBehaviour for Behaviour {
                 Event::ProofReceiveFailed { peer, error } => {
                     warn!(%peer, %error, "Failed to receive validator proof, closing connection");
                     return Poll::Ready(ToSwarm::CloseConnection {
-                        peer_id: *peer,
+                        peer_id: peer,
                         connection: CloseConnection::All,
                     });
                 }
                 // On proof received, check for duplicate (anti-spam)
-                Event::ProofReceived { peer, .. } => {
-                    if self.proofs_received.contains(peer) {
+                Event::ProofReceived { peer, proof_bytes } => {
+                    if self.proofs_received.contains(&peer) {
                         warn!(%peer, "Duplicate validator proof received, closing connection (anti-spam)");
                         return Poll::Ready(ToSwarm::CloseConnection {
-                            peer_id: *peer,
+                            peer_id: peer,
                             connection: CloseConnection::All,
                         });
                     }
-                    self.proofs_received.insert(*peer);
+
+                    // If enabled, the proof must come in a valid envelope
+                    let proof_bytes = match envelope::open(
+                        self.envelopes.as_mut(),
+                        &peer,
+                        proof_bytes,
+                    ) {
+                        Ok(proof_bytes) => proof_bytes,
+                        Err(error) => {
+                            warn!(%peer, %error, "Invalid validator proof envelope, closing connection");
+                            return Poll::Ready(ToSwarm::CloseConnection {
+                                peer_id: peer,
+                                connection: CloseConnection::All,
+                            });
+                        }
+                    };
+
+                    self.proofs_received.insert(peer);
+                    return Poll::Ready(ToSwarm::GenerateEvent(Event::ProofReceived {
+                        peer,
+                        proof_bytes,
+                    }));
+                }
+                // Send our proof again, unless the peer asked too recently
+                Event::ProofRequested { peer } => {
+                    if !self.serve_proof_request(peer, Instant::now()) {
+                        debug!(%peer, "Not serving validator proof request");
+                    }
                     return Poll::Ready(ToSwarm::GenerateEvent(event));
                 }
                 // Forward other events to swarm
@@ -284,6 +399,7 @@ impl NetworkBehaviour for Behaviour {
 mod tests {
     use super::*;
     use std::task::Poll;
+    use std::time::Duration;
 
     use futures::task::noop_waker_ref;
     use libp2p::core::transport::PortUse;
@@ -469,6 +585,81 @@ mod tests {
         }
     }
 
+    /// Create a behaviour which verifies the envelopes of the proofs it receives,
+    /// together with the peer id of the local node.
+    fn behaviour_with_envelopes() -> (Behaviour, PeerId) {
+        let keypair = libp2p::identity::Keypair::generate_ed25519();
+        let local_peer_id = keypair.public().to_peer_id();
+        let envelopes = Authenticator::new(
+            keypair,
+            "/malachitebft-validator-proof/v1",
+            Duration::from_secs(30),
+        );
+
+        (
+            Behaviour::with_default_protocol().with_envelopes(envelopes),
+            local_peer_id,
+        )
+    }
+
+    #[test]
+    fn poll_proof_in_valid_envelope_emits_event() {
+        let (mut b, local_peer_id) = behaviour_with_envelopes();
+
+        let keypair = libp2p::identity::Keypair::generate_ed25519();
+        let peer = keypair.public().to_peer_id();
+        let mut sender = Authenticator::new(
+            keypair,
+            "/malachitebft-validator-proof/v1",
+            Duration::from_secs(30),
+        );
+
+        let proof_bytes = sender
+            .seal(&local_peer_id, Bytes::from_static(b"proof"))
+            .unwrap();
+
+        b.events_tx
+            .send(Event::ProofReceived { peer, proof_bytes })
+            .unwrap();
+
+        match poll_behaviour(&mut b) {
+            Poll::Ready(ToSwarm::GenerateEvent(Event::ProofReceived {
+                peer: p,
+                proof_bytes,
+            })) => {
+                assert_eq!(p, peer);
+                assert_eq!(proof_bytes.as_ref(), b"proof");
+            }
+            other => panic!("expected GenerateEvent(ProofReceived), got {other:?}"),
+        }
+        assert!(b.proofs_received.contains(&peer));
+    }
+
+    #[test]
+    fn poll_proof_without_envelope_triggers_disconnect() {
+        let (mut b, _) = behaviour_with_envelopes();
+        let peer = PeerId::random();
+
+        b.events_tx
+            .send(Event::ProofReceived {
+                peer,
+                proof_bytes: Bytes::from_static(b"proof"),
+            })
+            .unwrap();
+
+        match poll_behaviour(&mut b) {
+            Poll::Ready(ToSwarm::CloseConnection {
+                peer_id,
+                connection,
+            }) => {
+                assert_eq!(peer_id, peer);
+                assert!(matches!(connection, CloseConnection::All));
+            }
+            other => panic!("expected CloseConnection, got {other:?}"),
+        }
+        assert!(!b.proofs_received.contains(&peer));
+    }
+
     #[test]
     fn poll_send_failure_emits_event() {
         let mut b = Behaviour::with_default_protocol();
@@ -580,6 +771,72 @@ mod tests {
         ));
     }
 
+    // ── Proof request tests ──────────────────────────────────────────
+
+    #[test]
+    fn proof_request_protocol_is_derived_from_proof_protocol() {
+        let b = Behaviour::with_default_protocol();
+        assert_eq!(
+            b.request_protocol.as_ref(),
+            "/malachitebft-validator-proof/v1/request"
+        );
+    }
+
+    #[test]
+    fn poll_proof_request_without_proof_is_not_served() {
+        let mut b = Behaviour::with_default_protocol();
+        let peer = PeerId::random();
+
+        b.events_tx.send(Event::ProofRequested { peer }).unwrap();
+
+        assert!(matches!(
+            poll_behaviour(&mut b),
+            Poll::Ready(ToSwarm::GenerateEvent(Event::ProofRequested { .. }))
+        ));
+        assert!(!b.requests_served.contains_key(&peer));
+    }
+
+    #[tokio::test]
+    async fn proof_requests_are_rate_limited() {
+        let mut b = Behaviour::with_default_protocol();
+        b.set_proof(Bytes::from_static(b"proof"));
+        let peer = PeerId::random();
+        let conn = ConnectionId::new_unchecked(1);
+        let now = Instant::now();
+
+        assert!(b.serve_proof_request(peer, now));
+        assert!(!b.serve_proof_request(peer, now + MIN_REQUEST_INTERVAL / 2));
+        assert!(b.serve_proof_request(peer, now + MIN_REQUEST_INTERVAL));
+
+        // Other peers are not affected
+        assert!(b.serve_proof_request(PeerId::random(), now));
+
+        // The rate limit is reset after a full disconnect
+        close_connection(&mut b, peer, conn, 0);
+        assert!(b.serve_proof_request(peer, now + MIN_REQUEST_INTERVAL));
+    }
+
+    #[tokio::test]
+    async fn requested_proof_is_not_a_duplicate() {
+        let mut b = Behaviour::with_default_protocol();
+        let peer = PeerId::random();
+        b.proofs_received.insert(peer);
+
+        b.request_proof(peer);
+
+        b.events_tx
+            .send(Event::ProofReceived {
+                peer,
+                proof_bytes: Bytes::from_static(b"proof"),
+            })
+            .unwrap();
+
+        assert!(matches!(
+            poll_behaviour(&mut b),
+            Poll::Ready(ToSwarm::GenerateEvent(Event::ProofReceived { .. }))
+        ));
+    }
+
     // ── Connection established + send_proof integration (requires tokio) ─
 
     #[tokio::test]
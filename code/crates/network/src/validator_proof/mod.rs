@@ -12,6 +12,9 @@
 //! Uses unsigned-varint length prefix, consistent with libp2p request-response
 //! and identify protocols. This is a one-way message with no response.
 //!
+//! When the validator proofs are configured to be signed, `proof_bytes` is a signed envelope
+//! bound to the receiving peer, see the [`envelope`](crate::envelope) module.
+//!
 //! ## Sending Proof
 //!
 //! The proof is set once at startup and sent automatically on every new connection:
@@ -31,6 +34,25 @@
 //! with validator set membership. Whether the receiver classifies us as a
 //! validator depends on their own validator set.
 //!
+//! ### Proof Requests
+//!
+//! When the validator set changes, the proofs already received are re-evaluated against
+//! the new set, and the identified peers which did not send a valid proof yet are asked for it
+//! on a companion protocol, the proof protocol name suffixed with `/request`:
+//!
+//! ```text
+//! CtrlMsg::UpdateValidatorSet:
+//!   └─► state.unproven_peers()
+//!       └─► behaviour.request_proof(peer_id)  — opens and closes a stream, no data
+//!
+//! Request received:
+//!   └─► Event::ProofRequested
+//!       └─► behaviour.send_proof(peer_id)  — at most once every 10s per peer
+//! ```
+//!
+//! This lets the validators joining the set be recognized without waiting for a reconnect.
+//! Peers running an older release do not support requests, which fail with `ProofRequestFailed`.
+//!
 //! ### Sending Guards (in `validator_proof/behaviour.rs`)
 //! - `proof_bytes` must be set (set once at startup)
 //! - `other_established == 0` gates sending to first connection only (via libp2p)
@@ -72,7 +94,7 @@
 //! ## Failure Handling
 //!
 //! **Send failures** (`ProofSendFailed`):
-//! - Forwarded to swarm; retry allowed on next connection or trigger
+//! - Forwarded to swarm; retry allowed on next connection or proof request
 //!
 //! **Receive failures** (`ProofReceiveFailed`):
 //! - Cannot read stream (framing error, oversized message, connection drop)
@@ -1,7 +1,7 @@
 //! Protocol handlers for sending and receiving validator proofs.
 
 use bytes::Bytes;
-use libp2p::futures::StreamExt;
+use libp2p::futures::{AsyncWriteExt, StreamExt};
 use libp2p::{PeerId, Stream};
 use libp2p_stream as stream;
 use tokio::sync::mpsc;
@@ -84,3 +84,53 @@ pub async fn send_proof(
     debug!(%peer, "Successfully sent validator proof");
     Event::ProofSent { peer }
 }
+
+/// Accept incoming proof requests, on which peers ask for our proof.
+///
+/// A request carries no data: the peer opens a stream and closes it.
+pub async fn accept_proof_requests(
+    mut control: stream::Control,
+    events_tx: mpsc::UnboundedSender<Event>,
+    protocol: StreamProtocol,
+) {
+    let mut requests = match control.accept(protocol) {
+        Ok(requests) => requests,
+        Err(error) => {
+            error!(%error, "Failed to accept incoming validator proof requests");
+            return;
+        }
+    };
+
+    while let Some((peer, _stream)) = requests.next().await {
+        debug!(%peer, "Received validator proof request");
+        let _ = events_tx.send(Event::ProofRequested { peer });
+    }
+}
+
+/// Ask a peer to send us its proof.
+pub async fn request_proof(
+    peer: PeerId,
+    mut control: stream::Control,
+    protocol: StreamProtocol,
+) -> Option<Event> {
+    let mut stream = match control.open_stream(peer, protocol).await {
+        Ok(stream) => stream,
+        Err(error) => {
+            // Peers running an older release do not support proof requests
+            return Some(Event::ProofRequestFailed {
+                peer,
+                error: Error::Io(error.to_string()),
+            });
+        }
+    };
+
+    if let Err(error) = stream.close().await {
+        return Some(Event::ProofRequestFailed {
+            peer,
+            error: Error::Io(error.to_string()),
+        });
+    }
+
+    debug!(%peer, "Requested validator proof");
+    None
+}
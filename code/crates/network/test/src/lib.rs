@@ -149,6 +149,8 @@ impl<const N: usize> Test<N> {
             let mut config = Config {
                 listen_addr: TransportProtocol::Quic
                     .multiaddr("127.0.0.1", self.consensus_base_port + i),
+                additional_listen_addrs: vec![],
+                advertise_addrs: vec![],
                 persistent_peers: self.nodes[i]
                     .bootstrap_nodes
                     .iter()
@@ -157,18 +159,32 @@ impl<const N: usize> Test<N> {
                             .multiaddr("127.0.0.1", self.consensus_base_port + *j)
                     })
                     .collect(),
+                dns_seeds: vec![],
+                preferred_peers_file: None,
                 persistent_peers_only: false,
                 discovery: discovery_config,
                 idle_connection_timeout: Duration::from_secs(60),
+                dial_timeout: Duration::from_secs(5),
+                upgrade_timeout: Duration::from_secs(10),
+                handshake_timeout: Duration::from_secs(10),
                 transport: malachitebft_network::TransportProtocol::Quic,
                 gossipsub: malachitebft_network::GossipSubConfig::default(),
                 pubsub_protocol: malachitebft_network::PubSubProtocol::default(),
+                selective_gossip: false,
                 channel_names: malachitebft_network::ChannelNames::default(),
                 rpc_max_size: 10 * 1024 * 1024,   // 10 MiB
                 pubsub_max_size: 4 * 1024 * 1024, // 4 MiB
                 enable_consensus: true,
                 enable_sync: false,
+                enable_announcements: false,
+                sync_compression: None,
                 protocol_names: ProtocolNames::default(),
+                protocol_version: Default::default(),
+                min_protocol_version: None,
+                nat: Default::default(),
+                chain_id: None,
+                rpc_signing: Default::default(),
+                peer_filter: None,
             };
 
             // Apply custom configuration if provided
@@ -227,7 +243,7 @@ impl<const N: usize> Test<N> {
                     tokio::select! {
                         event = handle.recv() => {
                             match event {
-                                Some(malachitebft_network::Event::PeerConnected(peer_id)) => {
+                                Some(malachitebft_network::Event::PeerConnected(peer_id, _)) => {
                                     if !peers.contains(&peer_id.to_libp2p()) {
                                         peers.push(peer_id.to_libp2p());
                                     }
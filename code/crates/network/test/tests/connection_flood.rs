@@ -63,6 +63,8 @@ fn init_logging() {
 fn make_config(port: u16, persistent_peers: Vec<u16>) -> Config {
     Config {
         listen_addr: TransportProtocol::Tcp.multiaddr("127.0.0.1", port as usize),
+        additional_listen_addrs: vec![],
+        advertise_addrs: vec![],
         persistent_peers: persistent_peers
             .iter()
             .map(|p| TransportProtocol::Tcp.multiaddr("127.0.0.1", *p as usize))
@@ -76,15 +78,29 @@ fn make_config(port: u16, persistent_peers: Vec<u16>) -> Config {
             ..Default::default()
         },
         idle_connection_timeout: Duration::from_secs(60),
+        dial_timeout: Duration::from_secs(5),
+        upgrade_timeout: Duration::from_secs(10),
+        handshake_timeout: Duration::from_secs(10),
         transport: malachitebft_network::TransportProtocol::Tcp,
         gossipsub: GossipSubConfig::default(),
         pubsub_protocol: PubSubProtocol::default(),
+        selective_gossip: false,
         channel_names: ChannelNames::default(),
         rpc_max_size: 10 * 1024 * 1024,
         pubsub_max_size: 4 * 1024 * 1024,
         enable_consensus: true,
         enable_sync: false,
+        enable_announcements: false,
+        sync_compression: None,
         protocol_names: ProtocolNames::default(),
+        protocol_version: Default::default(),
+        min_protocol_version: None,
+        nat: Default::default(),
+        chain_id: None,
+        rpc_signing: Default::default(),
+        peer_filter: None,
+        dns_seeds: vec![],
+        preferred_peers_file: None,
         persistent_peers_only: false,
     }
 }
@@ -241,3 +241,32 @@ pub async fn persistent_peers_only_with_discovery_enabled() {
 
     test.run().await
 }
+
+// Testing that nodes without any bootstrap nodes find each other with mDNS
+#[tokio::test]
+#[ignore] // Requires multicast on the local network
+pub async fn mdns_without_bootstrap_nodes() {
+    let test = Test::new(
+        [
+            TestNode::correct(0, vec![]),
+            TestNode::correct(1, vec![]),
+            TestNode::correct(2, vec![]),
+        ],
+        [
+            Expected::Exactly(vec![1, 2]),
+            Expected::Exactly(vec![0, 2]),
+            Expected::Exactly(vec![0, 1]),
+        ],
+        Duration::from_secs(0),
+        Duration::from_secs(20),
+        DiscoveryConfig {
+            enabled: true,
+            bootstrap_protocol: BootstrapProtocol::Full,
+            selector: Selector::Random,
+            mdns: true,
+            ..Default::default()
+        },
+    );
+
+    test.run().await
+}
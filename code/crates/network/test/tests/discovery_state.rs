@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+use malachitebft_config::TransportProtocol;
+use malachitebft_network::handle::Handle;
+use malachitebft_network::{
+    spawn, BootstrapProtocol, BootstrapStatus, Config, ConnectionStats, DiscoveryConfig,
+    DiscoveryStats, Keypair, Multiaddr, NetworkIdentity, ProtocolNames, Selector,
+};
+use tokio::time::sleep;
+
+fn make_config(port: u16, persistent_peers: Vec<Multiaddr>, discovery: bool) -> Config {
+    Config {
+        listen_addr: TransportProtocol::Quic.multiaddr("127.0.0.1", port as usize),
+        additional_listen_addrs: vec![],
+        advertise_addrs: vec![],
+        persistent_peers,
+        dns_seeds: vec![],
+        preferred_peers_file: None,
+        persistent_peers_only: false,
+        discovery: DiscoveryConfig {
+            enabled: discovery,
+            bootstrap_protocol: BootstrapProtocol::Full,
+            selector: Selector::Random,
+            ..Default::default()
+        },
+        idle_connection_timeout: Duration::from_secs(60),
+        dial_timeout: Duration::from_secs(5),
+        upgrade_timeout: Duration::from_secs(10),
+        handshake_timeout: Duration::from_secs(10),
+        transport: malachitebft_network::TransportProtocol::Quic,
+        gossipsub: malachitebft_network::GossipSubConfig::default(),
+        pubsub_protocol: malachitebft_network::PubSubProtocol::default(),
+        selective_gossip: false,
+        channel_names: malachitebft_network::ChannelNames::default(),
+        rpc_max_size: 10 * 1024 * 1024,
+        pubsub_max_size: 4 * 1024 * 1024,
+        enable_consensus: true,
+        enable_sync: false,
+        enable_announcements: false,
+        sync_compression: None,
+        protocol_names: ProtocolNames::default(),
+        protocol_version: Default::default(),
+        min_protocol_version: None,
+        nat: Default::default(),
+        chain_id: None,
+        rpc_signing: Default::default(),
+        peer_filter: None,
+    }
+}
+
+async fn spawn_node(moniker: &str, keypair: Keypair, config: Config) -> Handle {
+    spawn(
+        NetworkIdentity::new(moniker.to_string(), keypair, None),
+        config,
+        malachitebft_metrics::SharedRegistry::global().with_moniker(moniker.to_string()),
+    )
+    .await
+    .unwrap()
+}
+
+/// Polls the discovery state of the node until it satisfies the given predicate
+async fn wait_for_state(
+    handle: &Handle,
+    predicate: impl Fn(&DiscoveryStats) -> bool,
+) -> DiscoveryStats {
+    for _ in 0..100 {
+        let state = handle.discovery_state().await.unwrap();
+
+        if predicate(&state) {
+            return state;
+        }
+
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    panic!("Discovery state did not reach the expected state in time");
+}
+
+/// A node dialing another one through its persistent peers reports it as an outbound peer.
+/// As its initial discovery found no peer, the node being dialed also makes the dialing node
+/// one of its outbound peers, over the inbound connection of the dialing node.
+#[tokio::test]
+async fn discovery_state_of_two_connected_nodes() {
+    let keypair1 = Keypair::generate_ed25519();
+    let keypair2 = Keypair::generate_ed25519();
+    let (peer1, peer2) = (
+        keypair1.public().to_peer_id(),
+        keypair2.public().to_peer_id(),
+    );
+
+    let base_port: u16 = rand::random::<u16>() % 10000 + 30000;
+    let node1_addr = TransportProtocol::Quic.multiaddr("127.0.0.1", base_port as usize);
+
+    let handle1 = spawn_node("node-1", keypair1, make_config(base_port, vec![], true)).await;
+
+    // Without any bootstrap node, discovery has nothing to search for
+    let state1 = handle1.discovery_state().await.unwrap();
+    assert!(state1.enabled);
+    assert_eq!(state1.bootstrap_status, BootstrapStatus::Idle);
+    assert_eq!(state1.active, ConnectionStats::default());
+
+    let handle2 = spawn_node(
+        "node-2",
+        keypair2,
+        make_config(base_port + 1, vec![node1_addr], true),
+    )
+    .await;
+
+    let state2 = wait_for_state(&handle2, |state| state.outbound.num_peers == 1).await;
+    let state1 = wait_for_state(&handle1, |state| state.outbound.num_peers == 1).await;
+
+    assert!(state2.enabled);
+    assert!(matches!(
+        state2.bootstrap_status,
+        BootstrapStatus::Extending { .. } | BootstrapStatus::Idle
+    ));
+    assert_eq!(state2.outbound_peers, vec![peer1]);
+    assert!(state2.inbound_peers.is_empty());
+    assert_eq!(state2.active.num_peers, 1);
+    assert!(state2.outbound.num_connections >= 1);
+    assert_eq!(state2.inbound, ConnectionStats::default());
+
+    assert_eq!(state1.bootstrap_status, BootstrapStatus::Idle);
+    assert_eq!(state1.outbound_peers, vec![peer2]);
+    assert!(state1.inbound_peers.is_empty());
+    assert_eq!(state1.active.num_peers, 1);
+    assert!(state1.inbound.num_connections >= 1);
+    assert_eq!(state1.outbound.num_connections, 0);
+
+    handle1.shutdown().await.unwrap();
+    handle2.shutdown().await.unwrap();
+}
+
+/// A node with discovery disabled reports it as such, without any peer.
+#[tokio::test]
+async fn discovery_state_when_disabled() {
+    let base_port: u16 = rand::random::<u16>() % 10000 + 40000;
+
+    let handle = spawn_node(
+        "node-disabled",
+        Keypair::generate_ed25519(),
+        make_config(base_port, vec![], false),
+    )
+    .await;
+
+    let state = handle.discovery_state().await.unwrap();
+
+    assert!(!state.enabled);
+    assert_eq!(state.bootstrap_status, BootstrapStatus::Disabled);
+    assert_eq!(state.num_discovered_peers, 0);
+    assert!(state.kbuckets.is_empty());
+    assert_eq!(state.active, ConnectionStats::default());
+
+    handle.shutdown().await.unwrap();
+}
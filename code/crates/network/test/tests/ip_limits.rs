@@ -21,6 +21,8 @@ fn init_logging() {
 fn make_config(port: u16, persistent_peers: Vec<u16>, max_connections_per_ip: usize) -> Config {
     Config {
         listen_addr: TransportProtocol::Quic.multiaddr("127.0.0.1", port as usize),
+        additional_listen_addrs: vec![],
+        advertise_addrs: vec![],
         persistent_peers: persistent_peers
             .iter()
             .map(|p| TransportProtocol::Quic.multiaddr("127.0.0.1", *p as usize))
@@ -33,15 +35,29 @@ fn make_config(port: u16, persistent_peers: Vec<u16>, max_connections_per_ip: us
             ..Default::default()
         },
         idle_connection_timeout: Duration::from_secs(60),
+        dial_timeout: Duration::from_secs(5),
+        upgrade_timeout: Duration::from_secs(10),
+        handshake_timeout: Duration::from_secs(10),
         transport: malachitebft_network::TransportProtocol::Quic,
         gossipsub: GossipSubConfig::default(),
         pubsub_protocol: PubSubProtocol::default(),
+        selective_gossip: false,
         channel_names: ChannelNames::default(),
         rpc_max_size: 10 * 1024 * 1024,
         pubsub_max_size: 4 * 1024 * 1024,
         enable_consensus: true,
         enable_sync: false,
+        enable_announcements: false,
+        sync_compression: None,
         protocol_names: ProtocolNames::default(),
+        protocol_version: Default::default(),
+        min_protocol_version: None,
+        nat: Default::default(),
+        chain_id: None,
+        rpc_signing: Default::default(),
+        peer_filter: None,
+        dns_seeds: vec![],
+        preferred_peers_file: None,
         persistent_peers_only: false,
     }
 }
@@ -94,7 +110,7 @@ async fn same_ip_connection_attack() {
         tokio::select! {
             event = target_handle.recv() => {
                 match event {
-                    Some(malachitebft_network::Event::PeerConnected(_)) => {
+                    Some(malachitebft_network::Event::PeerConnected(..)) => {
                         connected_peers += 1;
                     }
                     Some(_) => {}
@@ -0,0 +1,143 @@
+//! Peer filter tests.
+//!
+//! Tests that only the peers allowed by the peer filter can connect to a node.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use malachitebft_config::TransportProtocol;
+use malachitebft_metrics::SharedRegistry;
+use malachitebft_network::peer_filter::{AllowList, PeerFilter};
+use malachitebft_network::{
+    spawn, ChannelNames, Config, DiscoveryConfig, GossipSubConfig, Keypair, NetworkIdentity,
+    PeerId, PeerIdExt, ProtocolNames, PubSubProtocol,
+};
+
+fn init_logging() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter("debug")
+        .try_init();
+}
+
+fn make_config(
+    port: u16,
+    persistent_peers: Vec<u16>,
+    peer_filter: Option<Arc<dyn PeerFilter>>,
+) -> Config {
+    Config {
+        listen_addr: TransportProtocol::Quic.multiaddr("127.0.0.1", port as usize),
+        additional_listen_addrs: vec![],
+        advertise_addrs: vec![],
+        persistent_peers: persistent_peers
+            .iter()
+            .map(|p| TransportProtocol::Quic.multiaddr("127.0.0.1", *p as usize))
+            .collect(),
+        discovery: DiscoveryConfig {
+            enabled: false,
+            num_inbound_peers: 10,
+            num_outbound_peers: 10,
+            ..Default::default()
+        },
+        idle_connection_timeout: Duration::from_secs(60),
+        dial_timeout: Duration::from_secs(5),
+        upgrade_timeout: Duration::from_secs(10),
+        handshake_timeout: Duration::from_secs(10),
+        transport: malachitebft_network::TransportProtocol::Quic,
+        gossipsub: GossipSubConfig::default(),
+        pubsub_protocol: PubSubProtocol::default(),
+        selective_gossip: false,
+        channel_names: ChannelNames::default(),
+        rpc_max_size: 10 * 1024 * 1024,
+        pubsub_max_size: 4 * 1024 * 1024,
+        enable_consensus: true,
+        enable_sync: false,
+        enable_announcements: false,
+        sync_compression: None,
+        protocol_names: ProtocolNames::default(),
+        protocol_version: Default::default(),
+        min_protocol_version: None,
+        nat: Default::default(),
+        chain_id: None,
+        rpc_signing: Default::default(),
+        peer_filter,
+        dns_seeds: vec![],
+        preferred_peers_file: None,
+        persistent_peers_only: false,
+    }
+}
+
+/// Tests that a node only accepts connections from the peers in its allow-list.
+#[tokio::test]
+async fn only_allowed_peers_connect() {
+    init_logging();
+
+    let base_port: u16 = rand::random::<u16>() % 10000 + 30000;
+    let target_port = base_port;
+
+    let allowed_keypair = Keypair::generate_ed25519();
+    let allowed_peer_id = PeerId::from_libp2p(&allowed_keypair.public().to_peer_id());
+    let rejected_keypair = Keypair::generate_ed25519();
+
+    // Target node only allowing the first peer to connect
+    let allow_list = AllowList::new([allowed_peer_id]);
+    let target_config = make_config(target_port, vec![], Some(Arc::new(allow_list)));
+    let target_identity =
+        NetworkIdentity::new("target".to_string(), Keypair::generate_ed25519(), None);
+    let target_registry = SharedRegistry::global().with_moniker("peer-filter-target");
+
+    let mut target_handle = spawn(target_identity, target_config, target_registry)
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // Both peers dial the target
+    let mut peer_handles = Vec::new();
+    for (i, keypair) in [allowed_keypair, rejected_keypair].into_iter().enumerate() {
+        let peer_port = base_port + 1 + i as u16;
+        let peer_config = make_config(peer_port, vec![target_port], None);
+        let peer_identity = NetworkIdentity::new(format!("peer-{i}"), keypair, None);
+        let peer_registry = SharedRegistry::global().with_moniker(format!("peer-filter-peer-{i}"));
+
+        let handle = spawn(peer_identity, peer_config, peer_registry)
+            .await
+            .unwrap();
+        peer_handles.push(handle);
+    }
+
+    // Wait for connection attempts and stabilization
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    // Collect the peers connected to the target
+    let mut connected_peers = Vec::new();
+    loop {
+        tokio::select! {
+            event = target_handle.recv() => {
+                match event {
+                    Some(malachitebft_network::Event::PeerConnected(peer_id, _)) => {
+                        connected_peers.push(peer_id);
+                    }
+                    Some(_) => {}
+                    None => break,
+                }
+            }
+            _ = tokio::time::sleep(Duration::from_millis(100)) => {
+                break;
+            }
+        }
+    }
+
+    tracing::info!("Connected peers: {connected_peers:?}");
+
+    assert_eq!(
+        connected_peers,
+        vec![allowed_peer_id],
+        "Only the allowed peer should be connected"
+    );
+
+    // Clean up
+    for handle in peer_handles {
+        drop(handle);
+    }
+    drop(target_handle);
+}
@@ -10,22 +10,38 @@ use tokio::time::sleep;
 fn make_config(port: usize) -> Config {
     Config {
         listen_addr: TransportProtocol::Quic.multiaddr("127.0.0.1", port),
+        additional_listen_addrs: vec![],
+        advertise_addrs: vec![],
         persistent_peers: vec![],
+        dns_seeds: vec![],
+        preferred_peers_file: None,
         persistent_peers_only: false,
         discovery: DiscoveryConfig {
             enabled: false,
             ..Default::default()
         },
         idle_connection_timeout: Duration::from_secs(60),
+        dial_timeout: Duration::from_secs(5),
+        upgrade_timeout: Duration::from_secs(10),
+        handshake_timeout: Duration::from_secs(10),
         transport: malachitebft_network::TransportProtocol::Quic,
         gossipsub: malachitebft_network::GossipSubConfig::default(),
         pubsub_protocol: malachitebft_network::PubSubProtocol::default(),
+        selective_gossip: false,
         channel_names: malachitebft_network::ChannelNames::default(),
         rpc_max_size: 10 * 1024 * 1024,
         pubsub_max_size: 4 * 1024 * 1024,
         enable_consensus: true,
         enable_sync: false,
+        enable_announcements: false,
+        sync_compression: None,
         protocol_names: ProtocolNames::default(),
+        protocol_version: Default::default(),
+        min_protocol_version: None,
+        nat: Default::default(),
+        chain_id: None,
+        rpc_signing: Default::default(),
+        peer_filter: None,
     }
 }
 
@@ -148,7 +164,7 @@ async fn test_persistent_peer_establishes_connection() {
     for _ in 0..50 {
         tokio::select! {
             event = handle1.recv() => {
-                if let Some(Event::PeerConnected(_)) = event {
+                if let Some(Event::PeerConnected(..)) = event {
                     connected = true;
                     break;
                 }
@@ -264,7 +280,7 @@ async fn test_remove_connected_peer_in_persistent_only_mode() {
     for _ in 0..50 {
         tokio::select! {
             event = handle1.recv() => {
-                if let Some(Event::PeerConnected(_)) = event {
+                if let Some(Event::PeerConnected(..)) = event {
                     connected = true;
                     break;
                 }
@@ -0,0 +1,156 @@
+//! Protocol version tests.
+//!
+//! Tests that a node ignores the peers speaking an incompatible version of the protocol.
+
+use std::time::Duration;
+
+use malachitebft_config::TransportProtocol;
+use malachitebft_metrics::SharedRegistry;
+use malachitebft_network::{
+    spawn, ChannelNames, Config, DiscoveryConfig, GossipSubConfig, Keypair, NetworkIdentity,
+    PeerId, PeerIdExt, ProtocolNames, ProtocolVersion, PubSubProtocol,
+};
+
+fn init_logging() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter("debug")
+        .try_init();
+}
+
+fn make_config(
+    port: u16,
+    persistent_peers: Vec<u16>,
+    protocol_version: ProtocolVersion,
+    min_protocol_version: Option<ProtocolVersion>,
+) -> Config {
+    Config {
+        listen_addr: TransportProtocol::Quic.multiaddr("127.0.0.1", port as usize),
+        additional_listen_addrs: vec![],
+        advertise_addrs: vec![],
+        persistent_peers: persistent_peers
+            .iter()
+            .map(|p| TransportProtocol::Quic.multiaddr("127.0.0.1", *p as usize))
+            .collect(),
+        discovery: DiscoveryConfig {
+            enabled: false,
+            num_inbound_peers: 10,
+            num_outbound_peers: 10,
+            ..Default::default()
+        },
+        idle_connection_timeout: Duration::from_secs(60),
+        dial_timeout: Duration::from_secs(5),
+        upgrade_timeout: Duration::from_secs(10),
+        handshake_timeout: Duration::from_secs(10),
+        transport: malachitebft_network::TransportProtocol::Quic,
+        gossipsub: GossipSubConfig::default(),
+        pubsub_protocol: PubSubProtocol::default(),
+        selective_gossip: false,
+        channel_names: ChannelNames::default(),
+        rpc_max_size: 10 * 1024 * 1024,
+        pubsub_max_size: 4 * 1024 * 1024,
+        enable_consensus: true,
+        enable_sync: false,
+        enable_announcements: false,
+        sync_compression: None,
+        protocol_names: ProtocolNames::default(),
+        protocol_version,
+        min_protocol_version,
+        nat: Default::default(),
+        chain_id: None,
+        rpc_signing: Default::default(),
+        peer_filter: None,
+        dns_seeds: vec![],
+        preferred_peers_file: None,
+        persistent_peers_only: false,
+    }
+}
+
+/// Tests that a node only reports as connected the peers speaking a compatible protocol version.
+#[tokio::test]
+async fn only_compatible_peers_connect() {
+    init_logging();
+
+    let base_port: u16 = rand::random::<u16>() % 10000 + 30000;
+    let target_port = base_port;
+
+    // Target node requiring at least version 1.3.0
+    let target_config = make_config(
+        target_port,
+        vec![],
+        ProtocolVersion::new(1, 3, 0),
+        Some(ProtocolVersion::new(1, 3, 0)),
+    );
+    let target_identity =
+        NetworkIdentity::new("target".to_string(), Keypair::generate_ed25519(), None);
+    let target_registry = SharedRegistry::global().with_moniker("protocol-version-target");
+
+    let mut target_handle = spawn(target_identity, target_config, target_registry)
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // Peers with a compatible version, an older minor version and a newer major version dial the target
+    let versions = [
+        ProtocolVersion::new(1, 4, 2),
+        ProtocolVersion::new(1, 2, 0),
+        ProtocolVersion::new(2, 0, 0),
+    ];
+
+    let mut compatible_peer_id = None;
+    let mut peer_handles = Vec::new();
+
+    for (i, version) in versions.into_iter().enumerate() {
+        let keypair = Keypair::generate_ed25519();
+        if i == 0 {
+            compatible_peer_id = Some(PeerId::from_libp2p(&keypair.public().to_peer_id()));
+        }
+
+        let peer_port = base_port + 1 + i as u16;
+        let peer_config = make_config(peer_port, vec![target_port], version, None);
+        let peer_identity = NetworkIdentity::new(format!("peer-{i}"), keypair, None);
+        let peer_registry =
+            SharedRegistry::global().with_moniker(format!("protocol-version-peer-{i}"));
+
+        let handle = spawn(peer_identity, peer_config, peer_registry)
+            .await
+            .unwrap();
+        peer_handles.push(handle);
+    }
+
+    // Wait for connection attempts and stabilization
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    // Collect the peers connected to the target
+    let mut connected_peers = Vec::new();
+    loop {
+        tokio::select! {
+            event = target_handle.recv() => {
+                match event {
+                    Some(malachitebft_network::Event::PeerConnected(peer_id, _)) => {
+                        connected_peers.push(peer_id);
+                    }
+                    Some(_) => {}
+                    None => break,
+                }
+            }
+            _ = tokio::time::sleep(Duration::from_millis(100)) => {
+                break;
+            }
+        }
+    }
+
+    tracing::info!("Connected peers: {connected_peers:?}");
+
+    assert_eq!(
+        connected_peers,
+        Vec::from_iter(compatible_peer_id),
+        "Only the peer with a compatible protocol version should be connected"
+    );
+
+    // Clean up
+    for handle in peer_handles {
+        drop(handle);
+    }
+    drop(target_handle);
+}
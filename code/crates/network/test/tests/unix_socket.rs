@@ -0,0 +1,143 @@
+//! Unix domain socket transport tests.
+//!
+//! Tests that nodes running on the same host connect to each other over Unix domain sockets.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use malachitebft_metrics::SharedRegistry;
+use malachitebft_network::{
+    spawn, ChannelNames, Config, DiscoveryConfig, GossipSubConfig, Keypair, Multiaddr,
+    NetworkIdentity, PeerId, PeerIdExt, ProtocolNames, ProtocolVersion, PubSubProtocol,
+    TransportProtocol,
+};
+
+fn init_logging() {
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter("debug")
+        .try_init();
+}
+
+fn socket_path(name: &str) -> PathBuf {
+    let suffix = rand::random::<u32>();
+    std::env::temp_dir().join(format!("malachite-{name}-{suffix}.sock"))
+}
+
+fn unix_multiaddr(path: &Path) -> Multiaddr {
+    let path = path.to_str().unwrap().replace('/', "%2F");
+    format!("/unix/{path}").parse().unwrap()
+}
+
+fn make_config(listen_addr: Multiaddr, persistent_peers: Vec<Multiaddr>) -> Config {
+    Config {
+        listen_addr,
+        additional_listen_addrs: vec![],
+        advertise_addrs: vec![],
+        persistent_peers,
+        discovery: DiscoveryConfig {
+            enabled: false,
+            num_inbound_peers: 10,
+            num_outbound_peers: 10,
+            ..Default::default()
+        },
+        idle_connection_timeout: Duration::from_secs(60),
+        dial_timeout: Duration::from_secs(5),
+        upgrade_timeout: Duration::from_secs(10),
+        handshake_timeout: Duration::from_secs(10),
+        transport: TransportProtocol::Unix,
+        gossipsub: GossipSubConfig::default(),
+        pubsub_protocol: PubSubProtocol::default(),
+        selective_gossip: false,
+        channel_names: ChannelNames::default(),
+        rpc_max_size: 10 * 1024 * 1024,
+        pubsub_max_size: 4 * 1024 * 1024,
+        enable_consensus: true,
+        enable_sync: false,
+        enable_announcements: false,
+        sync_compression: None,
+        protocol_names: ProtocolNames::default(),
+        protocol_version: ProtocolVersion::default(),
+        min_protocol_version: None,
+        nat: Default::default(),
+        chain_id: None,
+        rpc_signing: Default::default(),
+        peer_filter: None,
+        dns_seeds: vec![],
+        preferred_peers_file: None,
+        persistent_peers_only: false,
+    }
+}
+
+#[test]
+fn unix_transport_is_selected_from_the_listen_address() {
+    let addr = unix_multiaddr(Path::new("/tmp/node.sock"));
+
+    assert_eq!(
+        TransportProtocol::from_multiaddr(&addr),
+        Some(TransportProtocol::Unix)
+    );
+}
+
+/// Tests that a node dialing a persistent peer listening on a Unix domain socket connects to it.
+#[tokio::test]
+async fn peers_connect_over_unix_sockets() {
+    init_logging();
+
+    let target_path = socket_path("target");
+    let target_addr = unix_multiaddr(&target_path);
+
+    let target_identity =
+        NetworkIdentity::new("target".to_string(), Keypair::generate_ed25519(), None);
+    let target_registry = SharedRegistry::global().with_moniker("unix-socket-target");
+
+    let mut target_handle = spawn(
+        target_identity,
+        make_config(target_addr.clone(), vec![]),
+        target_registry,
+    )
+    .await
+    .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let peer_keypair = Keypair::generate_ed25519();
+    let peer_id = PeerId::from_libp2p(&peer_keypair.public().to_peer_id());
+    let peer_path = socket_path("peer");
+
+    let peer_identity = NetworkIdentity::new("peer".to_string(), peer_keypair, None);
+    let peer_registry = SharedRegistry::global().with_moniker("unix-socket-peer");
+
+    let peer_handle = spawn(
+        peer_identity,
+        make_config(unix_multiaddr(&peer_path), vec![target_addr]),
+        peer_registry,
+    )
+    .await
+    .unwrap();
+
+    let connected = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match target_handle.recv().await {
+                Some(malachitebft_network::Event::PeerConnected(connected, _)) => {
+                    break Some(connected);
+                }
+                Some(_) => {}
+                None => break None,
+            }
+        }
+    })
+    .await;
+
+    assert_eq!(
+        connected.ok().flatten(),
+        Some(peer_id),
+        "The peer should connect to the target over a Unix domain socket"
+    );
+
+    // Clean up
+    drop(peer_handle);
+    drop(target_handle);
+
+    let _ = std::fs::remove_file(target_path);
+    let _ = std::fs::remove_file(peer_path);
+}
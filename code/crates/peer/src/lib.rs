@@ -33,6 +33,9 @@ use rand::Rng;
 
 mod ser;
 
+mod lifecycle;
+pub use lifecycle::{PeerExit, PeerState};
+
 /// Local type-alias for multihash.
 ///
 /// Must be big enough to accommodate for `MAX_INLINE_KEY_LENGTH`.
@@ -0,0 +1,30 @@
+//! Cleanup of the state kept per peer once the peer is gone.
+//!
+//! Every subsystem keeping state per peer (discovery, network, sync, consensus) implements
+//! [`PeerState`], and drops that state when it is told that a peer disconnected or was banned,
+//! so that its state does not grow as peers come and go.
+
+use crate::PeerId;
+
+/// Why a peer is gone.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PeerExit {
+    /// The last connection to the peer was closed
+    Disconnected,
+    /// The peer was banned, eg. because of its low reputation
+    Banned,
+}
+
+/// State kept per peer, which must be dropped once the peer is gone.
+///
+/// The peer is identified by a value of type `P`, which defaults to [`PeerId`].
+pub trait PeerState<P = PeerId> {
+    /// Drop the state kept for the given peer.
+    ///
+    /// Some state may purposely outlive the connection to the peer, eg. a record of its
+    /// recent misbehavior, as long as it is bounded or eventually dropped.
+    fn forget_peer(&mut self, peer_id: &P, exit: PeerExit);
+
+    /// Number of entries kept for peers, which must not grow as peers come and go.
+    fn peer_entries(&self) -> usize;
+}
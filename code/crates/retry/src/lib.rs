@@ -0,0 +1,225 @@
+//! Retry and backoff utilities shared by the networking crates.
+//!
+//! A [`Backoff`] describes a retry policy: an exponential backoff with jitter,
+//! optionally bounded by a maximum number of retries and a maximum total delay.
+//! A [`Retry`] tracks the progress of a single operation against such a policy.
+
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Exponential backoff policy with jitter.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Backoff {
+    /// Delay before the first retry.
+    pub initial_delay: Duration,
+
+    /// Upper bound on the delay between two retries, before jitter is applied.
+    pub max_delay: Duration,
+
+    /// Factor by which the delay grows after each retry.
+    pub multiplier: f64,
+
+    /// Fraction of the delay to randomize, between 0 and 1.
+    ///
+    /// A jitter of 0.2 spreads each delay uniformly within ±20% of its nominal value.
+    pub jitter: f64,
+
+    /// Maximum number of retries, unbounded if `None`.
+    pub max_retries: Option<usize>,
+
+    /// Maximum total time spent waiting between retries, unbounded if `None`.
+    pub max_elapsed_time: Option<Duration>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_retries: None,
+            max_elapsed_time: None,
+        }
+    }
+}
+
+impl Backoff {
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_max_retries(mut self, max_retries: Option<usize>) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn with_max_elapsed_time(mut self, max_elapsed_time: Option<Duration>) -> Self {
+        self.max_elapsed_time = max_elapsed_time;
+        self
+    }
+
+    /// Nominal delay before the retry following `attempt` previous retries, without jitter.
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let exponent = i32::try_from(attempt).unwrap_or(i32::MAX);
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+
+        if delay.is_finite() && delay < self.max_delay.as_secs_f64() {
+            Duration::from_secs_f64(delay)
+        } else {
+            self.max_delay
+        }
+    }
+
+    /// Apply jitter to the given delay.
+    pub fn jittered(&self, delay: Duration, rng: &mut impl Rng) -> Duration {
+        let jitter = self.jitter.clamp(0.0, 1.0);
+
+        if jitter == 0.0 {
+            return delay;
+        }
+
+        let factor = rng.gen_range(1.0 - jitter..=1.0 + jitter);
+        delay.mul_f64(factor)
+    }
+}
+
+/// Progress of a single operation being retried according to a [`Backoff`] policy.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Retry {
+    count: usize,
+    elapsed: Duration,
+}
+
+impl Retry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of retries performed so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Total delay handed out so far.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Record a new retry and return the delay to wait before performing it,
+    /// or `None` if the retry budget of the policy is exhausted.
+    pub fn next_delay(&mut self, backoff: &Backoff) -> Option<Duration> {
+        self.next_delay_with(backoff, &mut rand::thread_rng())
+    }
+
+    /// Same as [`Retry::next_delay`], using the given source of randomness for the jitter.
+    pub fn next_delay_with(&mut self, backoff: &Backoff, rng: &mut impl Rng) -> Option<Duration> {
+        if backoff
+            .max_retries
+            .is_some_and(|max_retries| self.count >= max_retries)
+        {
+            return None;
+        }
+
+        let delay = backoff.jittered(backoff.delay(self.count), rng);
+        let elapsed = self.elapsed.saturating_add(delay);
+
+        if backoff
+            .max_elapsed_time
+            .is_some_and(|max_elapsed_time| elapsed > max_elapsed_time)
+        {
+            return None;
+        }
+
+        self.count += 1;
+        self.elapsed = elapsed;
+
+        Some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn no_jitter() -> Backoff {
+        Backoff::default().with_jitter(0.0)
+    }
+
+    #[test]
+    fn delay_grows_exponentially_up_to_max() {
+        let backoff = no_jitter()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(1));
+
+        let delays = (0..6)
+            .map(|attempt| backoff.delay(attempt))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            delays,
+            [100, 200, 400, 800, 1000, 1000].map(Duration::from_millis)
+        );
+
+        assert_eq!(backoff.delay(usize::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let backoff = Backoff::default().with_jitter(0.5);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..1000 {
+            let delay = backoff.jittered(Duration::from_secs(10), &mut rng);
+            assert!(delay >= Duration::from_secs(5) && delay <= Duration::from_secs(15));
+        }
+    }
+
+    #[test]
+    fn retry_stops_after_max_retries() {
+        let backoff = no_jitter().with_max_retries(Some(3));
+        let mut retry = Retry::new();
+
+        assert_eq!(retry.next_delay(&backoff), Some(Duration::from_secs(1)));
+        assert_eq!(retry.next_delay(&backoff), Some(Duration::from_secs(2)));
+        assert_eq!(retry.next_delay(&backoff), Some(Duration::from_secs(4)));
+        assert_eq!(retry.next_delay(&backoff), None);
+
+        assert_eq!(retry.count(), 3);
+        assert_eq!(retry.elapsed(), Duration::from_secs(7));
+    }
+
+    #[test]
+    fn retry_stops_after_max_elapsed_time() {
+        let backoff = no_jitter().with_max_elapsed_time(Some(Duration::from_secs(5)));
+        let mut retry = Retry::new();
+
+        assert_eq!(retry.next_delay(&backoff), Some(Duration::from_secs(1)));
+        assert_eq!(retry.next_delay(&backoff), Some(Duration::from_secs(2)));
+        assert_eq!(retry.next_delay(&backoff), None);
+
+        // An exhausted retry does not count towards the budget
+        assert_eq!(retry.count(), 2);
+        assert_eq!(retry.elapsed(), Duration::from_secs(3));
+    }
+}
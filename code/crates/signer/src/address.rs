@@ -0,0 +1,77 @@
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::SignerError;
+
+/// Address at which a remote signer listens for connections from the node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerAddress {
+    /// TCP socket, eg. `tcp://127.0.0.1:26659`
+    Tcp(SocketAddr),
+
+    /// Unix domain socket, eg. `unix:///var/run/signer.sock`
+    Unix(PathBuf),
+}
+
+impl FromStr for SignerAddress {
+    type Err = SignerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(addr) = s.strip_prefix("tcp://") {
+            return addr
+                .parse()
+                .map(Self::Tcp)
+                .map_err(|_| SignerError::InvalidAddress(s.to_string()));
+        }
+
+        match s.strip_prefix("unix://") {
+            Some(path) if !path.is_empty() => Ok(Self::Unix(PathBuf::from(path))),
+            _ => Err(SignerError::InvalidAddress(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for SignerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "tcp://{addr}"),
+            Self::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        for s in [
+            "tcp://127.0.0.1:26659",
+            "tcp://[::1]:26659",
+            "unix:///tmp/signer.sock",
+        ] {
+            let address = s.parse::<SignerAddress>().unwrap();
+            assert_eq!(address.to_string(), s);
+        }
+
+        assert_eq!(
+            "unix://signer.sock".parse::<SignerAddress>().unwrap(),
+            SignerAddress::Unix(PathBuf::from("signer.sock"))
+        );
+
+        for s in [
+            "127.0.0.1:26659",
+            "tcp://localhost",
+            "unix://",
+            "http://127.0.0.1:80",
+        ] {
+            assert!(
+                s.parse::<SignerAddress>().is_err(),
+                "{s} should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::io;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tokio::time::{sleep, timeout};
+use tracing::{debug, warn};
+
+use malachitebft_codec::Codec;
+use malachitebft_core_types::{
+    Context, Signature, SignedMessage, SigningScheme, ValidatorProof, ValidatorSetUpdate,
+};
+use malachitebft_retry::{Backoff, Retry};
+use malachitebft_signing::{Error, Signer};
+
+use crate::protocol::{Request, Response};
+use crate::transport::{Connection, Io};
+use crate::{AuthKey, SignerAddress, SignerCodec, SignerError};
+
+/// Configuration of a [`RemoteSigner`].
+#[derive(Clone, Debug)]
+pub struct RemoteSignerConfig {
+    /// Address at which the signer listens
+    pub address: SignerAddress,
+
+    /// Key shared with the signer
+    pub key: AuthKey,
+
+    /// Maximum time to connect to the signer and authenticate
+    pub connect_timeout: Duration,
+
+    /// Maximum time to wait for the signer to respond to a request
+    pub request_timeout: Duration,
+
+    /// Policy for reconnecting to the signer when the connection is lost
+    pub backoff: Backoff,
+}
+
+impl RemoteSignerConfig {
+    /// Default configuration for connecting to the signer at the given address.
+    pub fn new(address: SignerAddress, key: AuthKey) -> Self {
+        Self {
+            address,
+            key,
+            connect_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(5),
+            backoff: Backoff::default()
+                .with_initial_delay(Duration::from_millis(100))
+                .with_max_delay(Duration::from_secs(1))
+                .with_max_retries(Some(3)),
+        }
+    }
+
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+}
+
+/// A signer forwarding every signing request to a remote signer.
+///
+/// The connection is established on the first request, and established again whenever it is lost,
+/// according to the backoff policy of the configuration. Requests are sent one at a time.
+pub struct RemoteSigner<Ctx, C> {
+    config: RemoteSignerConfig,
+    codec: C,
+    connection: Mutex<Option<Connection<Box<dyn Io>>>>,
+    _marker: PhantomData<fn() -> Ctx>,
+}
+
+impl<Ctx, C> RemoteSigner<Ctx, C>
+where
+    Ctx: Context,
+    C: SignerCodec<Ctx>,
+{
+    pub fn new(config: RemoteSignerConfig, codec: C) -> Self {
+        Self {
+            config,
+            codec,
+            connection: Mutex::new(None),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn config(&self) -> &RemoteSignerConfig {
+        &self.config
+    }
+
+    /// Check that the signer is reachable and that it knows the key.
+    pub async fn ping(&self) -> Result<(), SignerError> {
+        match self.request(Request::Ping).await? {
+            Response::Pong => Ok(()),
+            response => Err(unexpected(&response)),
+        }
+    }
+
+    async fn sign(&self, request: Request) -> Result<Signature<Ctx>, SignerError> {
+        match self.request(request).await? {
+            Response::Signed(signature) => {
+                <Ctx::SigningScheme as SigningScheme>::decode_signature(&signature)
+                    .map_err(|e| SignerError::Codec(format!("invalid signature: {e}")))
+            }
+            Response::Refused(reason) => Err(SignerError::Refused(reason)),
+            response => Err(unexpected(&response)),
+        }
+    }
+
+    async fn request(&self, request: Request) -> Result<Response, SignerError> {
+        let request = request.encode();
+
+        let mut connection = self.connection.lock().await;
+        let mut retry = Retry::new();
+
+        loop {
+            match self.try_request(&mut connection, &request).await {
+                Err(e) if e.is_connection_error() => {
+                    let Some(delay) = retry.next_delay(&self.config.backoff) else {
+                        return Err(e);
+                    };
+
+                    warn!(
+                        address = %self.config.address,
+                        "Lost connection to the remote signer, retrying in {delay:?}: {e}"
+                    );
+
+                    sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn try_request(
+        &self,
+        connection: &mut Option<Connection<Box<dyn Io>>>,
+        request: &Bytes,
+    ) -> Result<Response, SignerError> {
+        let conn = match connection {
+            Some(conn) => conn,
+            None => connection.insert(self.connect().await?),
+        };
+
+        let exchange = async {
+            conn.send(request).await?;
+            conn.recv().await
+        };
+
+        // The connection cannot be used anymore after a failed exchange,
+        // as the response to the request may still be on its way.
+        let response = match timeout(self.config.request_timeout, exchange).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => {
+                *connection = None;
+                return Err(e);
+            }
+            Err(_) => {
+                *connection = None;
+                return Err(SignerError::Timeout(self.config.request_timeout));
+            }
+        };
+
+        Response::decode(Bytes::from(response))
+    }
+
+    async fn connect(&self) -> Result<Connection<Box<dyn Io>>, SignerError> {
+        let connect = async {
+            let io: Box<dyn Io> = match &self.config.address {
+                SignerAddress::Tcp(addr) => {
+                    let stream = TcpStream::connect(addr).await?;
+                    stream.set_nodelay(true)?;
+                    Box::new(stream)
+                }
+
+                #[cfg(unix)]
+                SignerAddress::Unix(path) => Box::new(tokio::net::UnixStream::connect(path).await?),
+
+                #[cfg(not(unix))]
+                SignerAddress::Unix(_) => {
+                    return Err(SignerError::InvalidAddress(self.config.address.to_string()))
+                }
+            };
+
+            Connection::client(io, &self.config.key).await
+        };
+
+        let connection = timeout(self.config.connect_timeout, connect)
+            .await
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "timed out connecting to the signer",
+                )
+            })??;
+
+        debug!(address = %self.config.address, "Connected to the remote signer");
+
+        Ok(connection)
+    }
+
+    fn encode<T>(&self, msg: &T) -> Result<Bytes, SignerError>
+    where
+        C: Codec<T>,
+    {
+        self.codec
+            .encode(msg)
+            .map_err(|e| SignerError::Codec(e.to_string()))
+    }
+}
+
+fn unexpected(response: &Response) -> SignerError {
+    SignerError::InvalidMessage(format!("unexpected response {response:?}"))
+}
+
+#[async_trait]
+impl<Ctx, C> Signer<Ctx> for RemoteSigner<Ctx, C>
+where
+    Ctx: Context,
+    C: SignerCodec<Ctx>,
+{
+    async fn sign_vote(&self, vote: Ctx::Vote) -> Result<SignedMessage<Ctx, Ctx::Vote>, Error> {
+        let request = Request::SignVote(self.encode(&vote).map_err(Error::from_source)?);
+        let signature = self.sign(request).await.map_err(Error::from_source)?;
+
+        Ok(SignedMessage::new(vote, signature))
+    }
+
+    async fn sign_proposal(
+        &self,
+        proposal: Ctx::Proposal,
+    ) -> Result<SignedMessage<Ctx, Ctx::Proposal>, Error> {
+        let request = Request::SignProposal(self.encode(&proposal).map_err(Error::from_source)?);
+        let signature = self.sign(request).await.map_err(Error::from_source)?;
+
+        Ok(SignedMessage::new(proposal, signature))
+    }
+
+    async fn sign_vote_extension(
+        &self,
+        extension: Ctx::Extension,
+    ) -> Result<SignedMessage<Ctx, Ctx::Extension>, Error> {
+        let request =
+            Request::SignVoteExtension(self.encode(&extension).map_err(Error::from_source)?);
+        let signature = self.sign(request).await.map_err(Error::from_source)?;
+
+        Ok(SignedMessage::new(extension, signature))
+    }
+
+    async fn sign_validator_proof(
+        &self,
+        public_key: Vec<u8>,
+        peer_id: Vec<u8>,
+    ) -> Result<ValidatorProof<Ctx>, Error> {
+        let request = Request::SignValidatorProof {
+            public_key: Bytes::copy_from_slice(&public_key),
+            peer_id: Bytes::copy_from_slice(&peer_id),
+        };
+
+        let signature = self.sign(request).await.map_err(Error::from_source)?;
+
+        Ok(ValidatorProof::new(public_key, peer_id, signature))
+    }
+
+    async fn sign_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<Ctx>,
+    ) -> Result<Signature<Ctx>, Error> {
+        let request =
+            Request::SignValidatorSetUpdate(self.encode(update).map_err(Error::from_source)?);
+
+        self.sign(request).await.map_err(Error::from_source)
+    }
+}
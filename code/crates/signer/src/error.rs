@@ -0,0 +1,43 @@
+use std::io;
+use std::time::Duration;
+
+/// Errors of the remote signing protocol.
+#[derive(Debug, thiserror::Error)]
+pub enum SignerError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Request timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("Authentication failed: {0}")]
+    Authentication(&'static str),
+
+    #[error("Unsupported protocol version {0}, expected version {expected}", expected = crate::PROTOCOL_VERSION)]
+    UnsupportedVersion(u16),
+
+    #[error("Frame of {0} bytes exceeds the maximum frame size")]
+    FrameTooLarge(usize),
+
+    #[error("Invalid message: {0}")]
+    InvalidMessage(String),
+
+    #[error("Failed to encode or decode a message: {0}")]
+    Codec(String),
+
+    #[error("The signer refused the request: {0}")]
+    Refused(String),
+
+    #[error("Invalid address {0:?}, expected `tcp://<host>:<port>` or `unix://<path>`")]
+    InvalidAddress(String),
+
+    #[error("Invalid authentication key: {0}")]
+    InvalidKey(String),
+}
+
+impl SignerError {
+    /// Whether the connection to the signer is broken, and must be established again.
+    pub(crate) fn is_connection_error(&self) -> bool {
+        matches!(self, Self::Io(_))
+    }
+}
@@ -0,0 +1,58 @@
+//! Remote signing protocol, to keep the private key of a validator off the consensus host.
+//!
+//! The node uses a [`RemoteSigner`] as its [`Signer`](malachitebft_signing::Signer), which forwards
+//! every signing request to a signer process listening on a TCP or Unix socket, and waits for the
+//! signature. The signer process runs a [`SignerServer`] in front of the actual signer, eg. one
+//! backed by an HSM or a cloud KMS, or a soft signer holding the key in memory.
+//!
+//! The node and the signer authenticate each other with a pre-shared [`AuthKey`], and every frame
+//! they exchange afterwards is authenticated, see the [`transport`] module for details.
+//!
+//! The signer should protect the validator against double-signing, eg. by wrapping its signer in
+//! a sign guard, especially if several nodes may connect to it, for instance during a failover.
+//! As a request which timed out may have been signed anyway, the node may send the same request
+//! again, and the signer must then sign it again.
+
+mod address;
+pub use address::SignerAddress;
+
+mod error;
+pub use error::SignerError;
+
+mod client;
+pub use client::{RemoteSigner, RemoteSignerConfig};
+
+mod server;
+pub use server::{SignerListener, SignerServer};
+
+pub mod protocol;
+
+pub mod transport;
+pub use transport::AuthKey;
+
+use malachitebft_codec::Codec;
+use malachitebft_core_types::{Context, ValidatorSetUpdate};
+
+/// Version of the remote signing protocol, exchanged when connecting.
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// Codec with which the node and the remote signer encode the messages to sign.
+pub trait SignerCodec<Ctx>
+where
+    Ctx: Context,
+    Self: Codec<Ctx::Vote>,
+    Self: Codec<Ctx::Proposal>,
+    Self: Codec<Ctx::Extension>,
+    Self: Codec<ValidatorSetUpdate<Ctx>>,
+{
+}
+
+impl<Ctx, C> SignerCodec<Ctx> for C
+where
+    Ctx: Context,
+    C: Codec<Ctx::Vote>,
+    C: Codec<Ctx::Proposal>,
+    C: Codec<Ctx::Extension>,
+    C: Codec<ValidatorSetUpdate<Ctx>>,
+{
+}
@@ -0,0 +1,226 @@
+//! Requests sent by the node to the remote signer, and the responses of the signer.
+//!
+//! Messages are encoded with the codec of the application, and signatures with
+//! [`SigningScheme::encode_signature`](malachitebft_core_types::SigningScheme::encode_signature).
+//! The other fields are encoded as follows, with all integers big-endian:
+//!
+//! ```text
+//! request:  kind (u8) | payload
+//!   0 = sign vote:                 encoded vote
+//!   1 = sign proposal:             encoded proposal
+//!   2 = sign vote extension:       encoded extension
+//!   3 = sign validator proof:      length (u32) | public key | length (u32) | peer ID
+//!   4 = sign validator set update: encoded update
+//!   5 = ping:                      empty
+//!
+//! response: status (u8) | payload
+//!   0 = signed:  encoded signature
+//!   1 = refused: reason (UTF-8)
+//!   2 = pong:    empty
+//! ```
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::SignerError;
+
+/// A request sent by the node to the remote signer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Request {
+    SignVote(Bytes),
+    SignProposal(Bytes),
+    SignVoteExtension(Bytes),
+    SignValidatorProof { public_key: Bytes, peer_id: Bytes },
+    SignValidatorSetUpdate(Bytes),
+    Ping,
+}
+
+impl Request {
+    pub fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::new();
+
+        match self {
+            Self::SignVote(vote) => {
+                buf.put_u8(0);
+                buf.put_slice(vote);
+            }
+            Self::SignProposal(proposal) => {
+                buf.put_u8(1);
+                buf.put_slice(proposal);
+            }
+            Self::SignVoteExtension(extension) => {
+                buf.put_u8(2);
+                buf.put_slice(extension);
+            }
+            Self::SignValidatorProof {
+                public_key,
+                peer_id,
+            } => {
+                buf.put_u8(3);
+                put_bytes(&mut buf, public_key);
+                put_bytes(&mut buf, peer_id);
+            }
+            Self::SignValidatorSetUpdate(update) => {
+                buf.put_u8(4);
+                buf.put_slice(update);
+            }
+            Self::Ping => {
+                buf.put_u8(5);
+            }
+        }
+
+        buf.freeze()
+    }
+
+    pub fn decode(mut bytes: Bytes) -> Result<Self, SignerError> {
+        if !bytes.has_remaining() {
+            return Err(SignerError::InvalidMessage("empty request".to_string()));
+        }
+
+        match bytes.get_u8() {
+            0 => Ok(Self::SignVote(bytes)),
+            1 => Ok(Self::SignProposal(bytes)),
+            2 => Ok(Self::SignVoteExtension(bytes)),
+            3 => {
+                let public_key = get_bytes(&mut bytes)?;
+                let peer_id = get_bytes(&mut bytes)?;
+
+                if bytes.has_remaining() {
+                    return Err(SignerError::InvalidMessage(
+                        "trailing bytes after validator proof request".to_string(),
+                    ));
+                }
+
+                Ok(Self::SignValidatorProof {
+                    public_key,
+                    peer_id,
+                })
+            }
+            4 => Ok(Self::SignValidatorSetUpdate(bytes)),
+            5 if !bytes.has_remaining() => Ok(Self::Ping),
+            kind => Err(SignerError::InvalidMessage(format!(
+                "unknown request kind {kind}"
+            ))),
+        }
+    }
+}
+
+/// The response of the remote signer to a [`Request`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Response {
+    /// The encoded signature of the message
+    Signed(Bytes),
+
+    /// The signer refused to sign the message, for the given reason
+    Refused(String),
+
+    /// Response to a ping
+    Pong,
+}
+
+impl Response {
+    pub fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::new();
+
+        match self {
+            Self::Signed(signature) => {
+                buf.put_u8(0);
+                buf.put_slice(signature);
+            }
+            Self::Refused(reason) => {
+                buf.put_u8(1);
+                buf.put_slice(reason.as_bytes());
+            }
+            Self::Pong => {
+                buf.put_u8(2);
+            }
+        }
+
+        buf.freeze()
+    }
+
+    pub fn decode(mut bytes: Bytes) -> Result<Self, SignerError> {
+        if !bytes.has_remaining() {
+            return Err(SignerError::InvalidMessage("empty response".to_string()));
+        }
+
+        match bytes.get_u8() {
+            0 => Ok(Self::Signed(bytes)),
+            1 => Ok(Self::Refused(String::from_utf8_lossy(&bytes).into_owned())),
+            2 if !bytes.has_remaining() => Ok(Self::Pong),
+            status => Err(SignerError::InvalidMessage(format!(
+                "unknown response status {status}"
+            ))),
+        }
+    }
+}
+
+fn put_bytes(buf: &mut BytesMut, bytes: &[u8]) {
+    buf.put_u32(bytes.len() as u32);
+    buf.put_slice(bytes);
+}
+
+fn get_bytes(buf: &mut Bytes) -> Result<Bytes, SignerError> {
+    if buf.remaining() < 4 {
+        return Err(SignerError::InvalidMessage("truncated request".to_string()));
+    }
+
+    let length = buf.get_u32() as usize;
+    if buf.remaining() < length {
+        return Err(SignerError::InvalidMessage("truncated request".to_string()));
+    }
+
+    Ok(buf.split_to(length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_roundtrip() {
+        let requests = [
+            Request::SignVote(Bytes::from_static(b"vote")),
+            Request::SignProposal(Bytes::from_static(b"proposal")),
+            Request::SignVoteExtension(Bytes::new()),
+            Request::SignValidatorProof {
+                public_key: Bytes::from_static(b"public key"),
+                peer_id: Bytes::from_static(b"peer id"),
+            },
+            Request::SignValidatorSetUpdate(Bytes::from_static(b"update")),
+            Request::Ping,
+        ];
+
+        for request in requests {
+            assert_eq!(Request::decode(request.encode()).unwrap(), request);
+        }
+    }
+
+    #[test]
+    fn response_roundtrip() {
+        let responses = [
+            Response::Signed(Bytes::from_static(b"signature")),
+            Response::Refused("double signing".to_string()),
+            Response::Pong,
+        ];
+
+        for response in responses {
+            assert_eq!(Response::decode(response.encode()).unwrap(), response);
+        }
+    }
+
+    #[test]
+    fn malformed_requests_are_rejected() {
+        for bytes in [
+            &b""[..],
+            &[6],
+            &[3, 0, 0, 0, 8, 1],
+            &[3, 0, 0, 0, 0],
+            &[5, 0],
+        ] {
+            assert!(
+                Request::decode(Bytes::copy_from_slice(bytes)).is_err(),
+                "{bytes:?} should be rejected"
+            );
+        }
+    }
+}
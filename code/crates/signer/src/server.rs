@@ -0,0 +1,223 @@
+use std::io;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use tokio::net::TcpListener;
+use tracing::{debug, info, warn};
+
+use malachitebft_codec::Codec;
+use malachitebft_core_types::{Context, SigningScheme};
+use malachitebft_signing::Signer;
+
+use crate::protocol::{Request, Response};
+use crate::transport::{Connection, Io};
+use crate::{AuthKey, SignerAddress, SignerCodec, SignerError};
+
+/// A listener accepting connections from nodes.
+pub enum SignerListener {
+    Tcp(TcpListener),
+
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener),
+}
+
+impl SignerListener {
+    /// Listen at the given address.
+    ///
+    /// A Unix socket left over at the given path, eg. after a crash, is replaced.
+    pub async fn bind(address: &SignerAddress) -> Result<Self, SignerError> {
+        match address {
+            SignerAddress::Tcp(addr) => Ok(Self::Tcp(TcpListener::bind(addr).await?)),
+
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => {
+                use std::os::unix::fs::FileTypeExt;
+
+                match std::fs::symlink_metadata(path) {
+                    Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+                    Ok(_) => {
+                        return Err(SignerError::InvalidAddress(format!(
+                            "{address}: file exists and is not a socket"
+                        )))
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e.into()),
+                }
+
+                Ok(Self::Unix(tokio::net::UnixListener::bind(path)?))
+            }
+
+            #[cfg(not(unix))]
+            SignerAddress::Unix(_) => Err(SignerError::InvalidAddress(address.to_string())),
+        }
+    }
+
+    /// The address at which the listener listens, with the actual port if it was bound to port 0.
+    pub fn local_addr(&self) -> Result<SignerAddress, SignerError> {
+        match self {
+            Self::Tcp(listener) => Ok(SignerAddress::Tcp(listener.local_addr()?)),
+
+            #[cfg(unix)]
+            Self::Unix(listener) => {
+                let addr = listener.local_addr()?;
+                let path = addr.as_pathname().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "unnamed Unix socket")
+                })?;
+
+                Ok(SignerAddress::Unix(path.to_path_buf()))
+            }
+        }
+    }
+
+    async fn accept(&self) -> io::Result<(Box<dyn Io>, String)> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, addr) = listener.accept().await?;
+                stream.set_nodelay(true)?;
+                Ok((Box::new(stream), addr.to_string()))
+            }
+
+            #[cfg(unix)]
+            Self::Unix(listener) => {
+                let (stream, _) = listener.accept().await?;
+                Ok((Box::new(stream), "unix socket".to_string()))
+            }
+        }
+    }
+}
+
+/// Serves the signing requests of nodes with the given signer.
+///
+/// Nodes must authenticate with the given key. Each connection is served by its own task,
+/// handling the requests of the node one at a time.
+pub struct SignerServer<Ctx, S, C> {
+    signer: Arc<S>,
+    codec: Arc<C>,
+    key: AuthKey,
+    _marker: PhantomData<fn() -> Ctx>,
+}
+
+impl<Ctx, S, C> SignerServer<Ctx, S, C>
+where
+    Ctx: Context,
+    S: Signer<Ctx> + 'static,
+    C: SignerCodec<Ctx>,
+{
+    pub fn new(signer: S, codec: C, key: AuthKey) -> Self {
+        Self {
+            signer: Arc::new(signer),
+            codec: Arc::new(codec),
+            key,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Accept connections from nodes until an error occurs while accepting one.
+    pub async fn run(self, listener: SignerListener) -> Result<(), SignerError> {
+        info!(address = %listener.local_addr()?, "Remote signer listening");
+
+        loop {
+            let (io, peer) = listener.accept().await?;
+
+            let server = Self {
+                signer: Arc::clone(&self.signer),
+                codec: Arc::clone(&self.codec),
+                key: self.key.clone(),
+                _marker: PhantomData,
+            };
+
+            tokio::spawn(async move {
+                match server.serve(io).await {
+                    Ok(()) => debug!(%peer, "Node disconnected"),
+                    Err(e) => warn!(%peer, "Closed connection with node: {e}"),
+                }
+            });
+        }
+    }
+
+    /// Authenticate the node at the other end of the given stream,
+    /// then serve its requests until it disconnects.
+    pub async fn serve<T>(&self, io: T) -> Result<(), SignerError>
+    where
+        T: Io,
+    {
+        let mut connection = Connection::server(io, &self.key).await?;
+
+        info!("Node connected");
+
+        loop {
+            let request = match connection.recv().await {
+                Ok(request) => request,
+                Err(SignerError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Ok(())
+                }
+                Err(e) => return Err(e),
+            };
+
+            let response = match Request::decode(Bytes::from(request)) {
+                Ok(request) => self.handle(request).await,
+                Err(e) => Response::Refused(e.to_string()),
+            };
+
+            connection.send(&response.encode()).await?;
+        }
+    }
+
+    async fn handle(&self, request: Request) -> Response {
+        let signature = match request {
+            Request::SignVote(vote) => match self.decode(vote) {
+                Ok(vote) => self.signer.sign_vote(vote).await.map(|s| s.signature),
+                Err(e) => return e,
+            },
+            Request::SignProposal(proposal) => match self.decode(proposal) {
+                Ok(proposal) => self
+                    .signer
+                    .sign_proposal(proposal)
+                    .await
+                    .map(|s| s.signature),
+                Err(e) => return e,
+            },
+            Request::SignVoteExtension(extension) => match self.decode(extension) {
+                Ok(extension) => self
+                    .signer
+                    .sign_vote_extension(extension)
+                    .await
+                    .map(|s| s.signature),
+                Err(e) => return e,
+            },
+            Request::SignValidatorProof {
+                public_key,
+                peer_id,
+            } => self
+                .signer
+                .sign_validator_proof(public_key.to_vec(), peer_id.to_vec())
+                .await
+                .map(|proof| proof.signature),
+            Request::SignValidatorSetUpdate(update) => match self.decode(update) {
+                Ok(update) => self.signer.sign_validator_set_update(&update).await,
+                Err(e) => return e,
+            },
+            Request::Ping => return Response::Pong,
+        };
+
+        match signature {
+            Ok(signature) => Response::Signed(Bytes::from(
+                <Ctx::SigningScheme as SigningScheme>::encode_signature(&signature),
+            )),
+            Err(e) => {
+                warn!("Refused to sign: {e}");
+                Response::Refused(e.to_string())
+            }
+        }
+    }
+
+    fn decode<T>(&self, bytes: Bytes) -> Result<T, Response>
+    where
+        C: Codec<T>,
+    {
+        self.codec
+            .decode(bytes)
+            .map_err(|e| Response::Refused(format!("invalid message: {e}")))
+    }
+}
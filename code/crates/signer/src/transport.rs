@@ -0,0 +1,396 @@
+//! Mutually authenticated transport between the node and the remote signer.
+//!
+//! Both ends share a secret [`AuthKey`]. When connecting, each end proves to the other that
+//! it knows the key, by computing an HMAC-SHA256 of the random nonces chosen by both ends:
+//!
+//! ```text
+//! client -> server: magic (8 bytes, "MALSIGNR") | version (u16) | client nonce (32 bytes)
+//! server -> client: version (u16) | server nonce (32 bytes) | HMAC(key, "server" | client nonce | server nonce)
+//! client -> server: HMAC(key, "client" | client nonce | server nonce)
+//! server -> client: empty frame, once the client is authenticated
+//! ```
+//!
+//! All the frames which follow are authenticated with a session key derived from the nonces,
+//! and bound to their direction and sequence number, so that they cannot be tampered with,
+//! replayed, reordered or reflected. Frames are not encrypted, as votes and proposals are
+//! broadcast to the whole network anyway.
+//!
+//! ```text
+//! frame: length (u32) | body | HMAC(session key, direction | sequence number (u64) | body)
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{SignerError, PROTOCOL_VERSION};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Size of an authentication key, in bytes
+pub const KEY_SIZE: usize = 32;
+
+/// Maximum size of the body of a frame, in bytes
+pub const MAX_FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+const MAGIC: [u8; 8] = *b"MALSIGNR";
+const NONCE_SIZE: usize = 32;
+const TAG_SIZE: usize = 32;
+
+/// A stream over which the node and the remote signer communicate, eg. a TCP or Unix socket.
+pub trait Io: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T> Io for T where T: AsyncRead + AsyncWrite + Unpin + Send {}
+
+/// Secret key shared by the node and the remote signer, with which they authenticate each other.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AuthKey([u8; KEY_SIZE]);
+
+impl AuthKey {
+    /// Creates a key from its raw bytes.
+    pub fn from_bytes(bytes: [u8; KEY_SIZE]) -> Self {
+        Self(bytes)
+    }
+
+    /// Generates a new random key.
+    pub fn generate() -> Self {
+        let mut bytes = [0; KEY_SIZE];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    /// Parses a key from its hex encoding, ignoring surrounding whitespace.
+    pub fn from_hex(hex: &str) -> Result<Self, SignerError> {
+        let mut bytes = [0; KEY_SIZE];
+
+        hex::decode_to_slice(hex.trim(), &mut bytes).map_err(|e| {
+            SignerError::InvalidKey(format!("expected {} hex characters: {e}", KEY_SIZE * 2))
+        })?;
+
+        Ok(Self(bytes))
+    }
+
+    /// Reads a key from a file containing its hex encoding.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SignerError> {
+        Self::from_hex(&fs::read_to_string(path)?)
+    }
+
+    /// The hex encoding of the key.
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    fn mac(&self, label: &[u8], client_nonce: &[u8], server_nonce: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.0).expect("HMAC accepts keys of any size");
+        mac.update(label);
+        mac.update(client_nonce);
+        mac.update(server_nonce);
+        mac
+    }
+}
+
+impl fmt::Debug for AuthKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuthKey(<redacted>)")
+    }
+}
+
+/// Direction of a frame, so that a frame sent by one end cannot be reflected back to it.
+#[derive(Copy, Clone, Debug)]
+enum Direction {
+    ToServer = 0,
+    ToClient = 1,
+}
+
+/// An authenticated connection to the other end.
+pub struct Connection<T> {
+    io: T,
+    session_key: [u8; 32],
+    send: Direction,
+    send_seq: u64,
+    recv_seq: u64,
+}
+
+impl<T> Connection<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Authenticate to the signer listening at the other end of the given stream.
+    pub async fn client(mut io: T, key: &AuthKey) -> Result<Self, SignerError> {
+        let client_nonce = nonce();
+
+        io.write_all(&MAGIC).await?;
+        io.write_all(&PROTOCOL_VERSION.to_be_bytes()).await?;
+        io.write_all(&client_nonce).await?;
+        io.flush().await?;
+
+        let version = io.read_u16().await?;
+        if version != PROTOCOL_VERSION {
+            return Err(SignerError::UnsupportedVersion(version));
+        }
+
+        let mut server_nonce = [0; NONCE_SIZE];
+        io.read_exact(&mut server_nonce).await?;
+
+        let mut server_tag = [0; TAG_SIZE];
+        io.read_exact(&mut server_tag).await?;
+
+        key.mac(b"server", &client_nonce, &server_nonce)
+            .verify_slice(&server_tag)
+            .map_err(|_| SignerError::Authentication("the signer does not know the key"))?;
+
+        let client_tag = key
+            .mac(b"client", &client_nonce, &server_nonce)
+            .finalize()
+            .into_bytes();
+
+        io.write_all(&client_tag).await?;
+        io.flush().await?;
+
+        let mut connection = Self::new(io, key, &client_nonce, &server_nonce, Direction::ToServer);
+
+        // The signer closes the connection instead of acknowledging it if it rejects the key
+        match connection.recv().await {
+            Ok(body) if body.is_empty() => Ok(connection),
+            Ok(_) => Err(SignerError::InvalidMessage(
+                "unexpected handshake acknowledgement".to_string(),
+            )),
+            Err(SignerError::Io(_)) => Err(SignerError::Authentication(
+                "the signer rejected the key of the node",
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Authenticate the node connecting at the other end of the given stream.
+    pub async fn server(mut io: T, key: &AuthKey) -> Result<Self, SignerError> {
+        let mut magic = [0; MAGIC.len()];
+        io.read_exact(&mut magic).await?;
+
+        if magic != MAGIC {
+            return Err(SignerError::InvalidMessage(
+                "not a remote signer client".to_string(),
+            ));
+        }
+
+        let version = io.read_u16().await?;
+        if version != PROTOCOL_VERSION {
+            return Err(SignerError::UnsupportedVersion(version));
+        }
+
+        let mut client_nonce = [0; NONCE_SIZE];
+        io.read_exact(&mut client_nonce).await?;
+
+        let server_nonce = nonce();
+        let server_tag = key
+            .mac(b"server", &client_nonce, &server_nonce)
+            .finalize()
+            .into_bytes();
+
+        io.write_all(&PROTOCOL_VERSION.to_be_bytes()).await?;
+        io.write_all(&server_nonce).await?;
+        io.write_all(&server_tag).await?;
+        io.flush().await?;
+
+        let mut client_tag = [0; TAG_SIZE];
+        io.read_exact(&mut client_tag).await?;
+
+        key.mac(b"client", &client_nonce, &server_nonce)
+            .verify_slice(&client_tag)
+            .map_err(|_| SignerError::Authentication("the node does not know the key"))?;
+
+        let mut connection = Self::new(io, key, &client_nonce, &server_nonce, Direction::ToClient);
+        connection.send(&[]).await?;
+
+        Ok(connection)
+    }
+
+    fn new(
+        io: T,
+        key: &AuthKey,
+        client_nonce: &[u8],
+        server_nonce: &[u8],
+        send: Direction,
+    ) -> Self {
+        let session_key = key
+            .mac(b"session", client_nonce, server_nonce)
+            .finalize()
+            .into_bytes()
+            .into();
+
+        Self {
+            io,
+            session_key,
+            send,
+            send_seq: 0,
+            recv_seq: 0,
+        }
+    }
+
+    /// Send a frame to the other end.
+    pub async fn send(&mut self, body: &[u8]) -> Result<(), SignerError> {
+        if body.len() > MAX_FRAME_SIZE {
+            return Err(SignerError::FrameTooLarge(body.len()));
+        }
+
+        let tag = self.tag(self.send, self.send_seq, body);
+        self.send_seq += 1;
+
+        self.io.write_u32(body.len() as u32).await?;
+        self.io.write_all(body).await?;
+        self.io.write_all(&tag).await?;
+        self.io.flush().await?;
+
+        Ok(())
+    }
+
+    /// Receive the next frame from the other end, checking that it was sent by it.
+    pub async fn recv(&mut self) -> Result<Vec<u8>, SignerError> {
+        let length = self.io.read_u32().await? as usize;
+        if length > MAX_FRAME_SIZE {
+            return Err(SignerError::FrameTooLarge(length));
+        }
+
+        let mut body = vec![0; length];
+        self.io.read_exact(&mut body).await?;
+
+        let mut tag = [0; TAG_SIZE];
+        self.io.read_exact(&mut tag).await?;
+
+        let direction = match self.send {
+            Direction::ToServer => Direction::ToClient,
+            Direction::ToClient => Direction::ToServer,
+        };
+
+        self.mac(direction, self.recv_seq, &body)
+            .verify_slice(&tag)
+            .map_err(|_| SignerError::Authentication("invalid frame tag"))?;
+
+        self.recv_seq += 1;
+
+        Ok(body)
+    }
+
+    fn mac(&self, direction: Direction, seq: u64, body: &[u8]) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.session_key).expect("HMAC accepts keys of any size");
+        mac.update(&[direction as u8]);
+        mac.update(&seq.to_be_bytes());
+        mac.update(body);
+        mac
+    }
+
+    fn tag(&self, direction: Direction, seq: u64, body: &[u8]) -> [u8; TAG_SIZE] {
+        self.mac(direction, seq, body)
+            .finalize()
+            .into_bytes()
+            .into()
+    }
+}
+
+fn nonce() -> [u8; NONCE_SIZE] {
+    let mut nonce = [0; NONCE_SIZE];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    nonce
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::duplex;
+
+    use super::*;
+
+    async fn connect(
+        client_key: &AuthKey,
+        server_key: &AuthKey,
+    ) -> (
+        Result<Connection<tokio::io::DuplexStream>, SignerError>,
+        Result<Connection<tokio::io::DuplexStream>, SignerError>,
+    ) {
+        let (client, server) = duplex(1024);
+
+        tokio::join!(
+            Connection::client(client, client_key),
+            Connection::server(server, server_key)
+        )
+    }
+
+    #[tokio::test]
+    async fn authenticated_exchange() {
+        let key = AuthKey::generate();
+        let (client, server) = connect(&key, &key).await;
+        let (mut client, mut server) = (client.unwrap(), server.unwrap());
+
+        client.send(b"request").await.unwrap();
+        assert_eq!(server.recv().await.unwrap(), b"request");
+
+        server.send(b"response").await.unwrap();
+        assert_eq!(client.recv().await.unwrap(), b"response");
+
+        client.send(&[]).await.unwrap();
+        assert_eq!(server.recv().await.unwrap(), b"");
+    }
+
+    #[tokio::test]
+    async fn different_keys_are_rejected() {
+        let (client, server) = connect(&AuthKey::generate(), &AuthKey::generate()).await;
+
+        assert!(matches!(client, Err(SignerError::Authentication(_))));
+
+        // The client stops before proving that it knows the key
+        assert!(matches!(server, Err(SignerError::Io(_))));
+    }
+
+    #[tokio::test]
+    async fn tampered_frames_are_rejected() {
+        let key = AuthKey::generate();
+        let (client, server) = connect(&key, &key).await;
+        let (mut client, mut server) = (client.unwrap(), server.unwrap());
+
+        // A frame with the tag of another frame
+        let tag = client.tag(Direction::ToServer, 0, b"request");
+        client.io.write_u32(8).await.unwrap();
+        client.io.write_all(b"tampered").await.unwrap();
+        client.io.write_all(&tag).await.unwrap();
+
+        assert!(matches!(
+            server.recv().await,
+            Err(SignerError::Authentication(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn reflected_frames_are_rejected() {
+        let key = AuthKey::generate();
+        let (client, server) = connect(&key, &key).await;
+        let (mut client, mut server) = (client.unwrap(), server.unwrap());
+
+        // A frame tagged as sent by the server, sent by the client
+        let tag = client.tag(Direction::ToClient, 0, b"request");
+        client.io.write_u32(7).await.unwrap();
+        client.io.write_all(b"request").await.unwrap();
+        client.io.write_all(&tag).await.unwrap();
+
+        assert!(matches!(
+            server.recv().await,
+            Err(SignerError::Authentication(_))
+        ));
+    }
+
+    #[test]
+    fn key_hex_roundtrip() {
+        let key = AuthKey::generate();
+        assert_eq!(
+            AuthKey::from_hex(&format!(" {}\n", key.to_hex())).unwrap(),
+            key
+        );
+
+        assert!(AuthKey::from_hex("00").is_err());
+        assert!(AuthKey::from_hex(&"zz".repeat(KEY_SIZE)).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+use arc_malachitebft_signing_ed25519::{Ed25519, PublicKey, Signature};
+
+/// Size of a signed precommit, roughly
+const MSG_LEN: usize = 128;
+
+fn sign_all(rng: &mut StdRng, count: usize) -> Vec<(PublicKey, Vec<u8>, Signature)> {
+    (0..count)
+        .map(|_| {
+            let private_key = Ed25519::generate_keypair(&mut *rng);
+            let mut msg = vec![0; MSG_LEN];
+            rng.fill_bytes(&mut msg);
+            let signature = private_key.sign(&msg);
+            (private_key.public_key(), msg, signature)
+        })
+        .collect()
+}
+
+fn verify_benchmarks(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    // Eg. the commit signatures of a batch of synced values,
+    // for a validator set of 100 validators with 67 signatures per certificate
+    let counts = vec![67, 10 * 67, 100 * 67];
+
+    let mut group = c.benchmark_group("verify_signatures");
+
+    for count in counts {
+        let signatures = sign_all(&mut rng, count);
+
+        group.throughput(Throughput::Elements(count as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("single", count),
+            &signatures,
+            |b, signatures| {
+                b.iter(|| {
+                    for (public_key, msg, signature) in black_box(signatures) {
+                        public_key.verify(msg, signature).unwrap();
+                    }
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("batch", count),
+            &signatures,
+            |b, signatures| {
+                b.iter(|| {
+                    Ed25519::verify_batch(
+                        black_box(signatures)
+                            .iter()
+                            .map(|(public_key, msg, signature)| {
+                                (public_key, msg.as_slice(), signature)
+                            }),
+                        rand::thread_rng(),
+                    )
+                    .unwrap()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, verify_benchmarks);
+criterion_main!(benches);
@@ -30,6 +30,25 @@ impl Ed25519 {
     {
         PrivateKey::generate(rng)
     }
+
+    /// Verify many signatures at once, each over the given message with the given public key.
+    ///
+    /// This is much faster than verifying the signatures one at a time, but only tells
+    /// whether they are all valid, not which ones are invalid.
+    #[cfg(feature = "rand")]
+    pub fn verify_batch<'a, I, R>(signatures: I, rng: R) -> Result<(), signature::Error>
+    where
+        I: IntoIterator<Item = (&'a PublicKey, &'a [u8], &'a Signature)>,
+        R: RngCore + CryptoRng,
+    {
+        let mut verifier = ed25519_consensus::batch::Verifier::new();
+
+        for (public_key, msg, signature) in signatures {
+            verifier.queue((public_key.0.into(), signature.0, msg));
+        }
+
+        verifier.verify(rng).map_err(|_| signature::Error::new())
+    }
 }
 
 impl SigningScheme for Ed25519 {
@@ -266,6 +285,52 @@ mod tests {
     }
 }
 
+#[cfg(all(test, feature = "rand"))]
+mod batch_tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn sign_all(count: u8) -> Vec<(PublicKey, Vec<u8>, Signature)> {
+        (0..count)
+            .map(|i| {
+                let private_key = PrivateKey::from([i; 32]);
+                let msg = vec![i; 64];
+                let signature = private_key.sign(&msg);
+                (private_key.public_key(), msg, signature)
+            })
+            .collect()
+    }
+
+    fn verify_batch(signatures: &[(PublicKey, Vec<u8>, Signature)]) -> bool {
+        Ed25519::verify_batch(
+            signatures
+                .iter()
+                .map(|(public_key, msg, signature)| (public_key, msg.as_slice(), signature)),
+            rand::thread_rng(),
+        )
+        .is_ok()
+    }
+
+    #[test]
+    fn verify_batch_valid() {
+        assert!(verify_batch(&[]));
+        assert!(verify_batch(&sign_all(10)));
+    }
+
+    #[test]
+    fn verify_batch_invalid_signature() {
+        let mut signatures = sign_all(10);
+        signatures[3].2 = Signature::test();
+        assert!(!verify_batch(&signatures));
+
+        // Signature over another message
+        let mut signatures = sign_all(10);
+        signatures[7].1 = vec![42; 64];
+        assert!(!verify_batch(&signatures));
+    }
+}
+
 #[cfg(all(test, feature = "zeroize"))]
 mod zeroize_tests {
     use super::*;
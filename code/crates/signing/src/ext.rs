@@ -4,8 +4,8 @@ use alloc::vec::Vec;
 use async_trait::async_trait;
 use malachitebft_core_types::{
     CertificateError, CommitCertificate, CommitSignature, Context, NilOrVal, PolkaCertificate,
-    PolkaSignature, RoundCertificate, RoundCertificateType, RoundSignature, ThresholdParams,
-    Validator, ValidatorSet, VoteType, VotingPower,
+    PolkaSignature, PublicKey, RoundCertificate, RoundCertificateType, RoundSignature, SignedVote,
+    ThresholdParams, Validator, ValidatorSet, ValidatorSetUpdateCertificate, VoteType, VotingPower,
 };
 
 use crate::Verifier;
@@ -73,6 +73,25 @@ where
         thresholds: ThresholdParams,
     ) -> Result<(), CertificateError<Ctx>>;
 
+    /// Verify the given commit certificates against the same validator set,
+    /// eg. the certificates of a run of values received via sync.
+    ///
+    /// The checks of [`verify_commit_certificate`](Self::verify_commit_certificate) which
+    /// do not involve signatures are performed for each certificate, then the signatures
+    /// of all the certificates which passed them are verified at once, with
+    /// [`Verifier::verify_signed_votes`]. Only if that fails are the certificates
+    /// verified one at a time, to find out which ones are invalid.
+    ///
+    /// ## Return
+    /// Return the result of the verification of each certificate, in the same order.
+    async fn verify_commit_certificates(
+        &self,
+        ctx: &Ctx,
+        certificates: &[CommitCertificate<Ctx>],
+        validator_set: &Ctx::ValidatorSet,
+        thresholds: ThresholdParams,
+    ) -> Vec<Result<(), CertificateError<Ctx>>>;
+
     /// Verify the polka certificate against the given validator set.
     ///
     /// - For each signature in the certificate:
@@ -109,6 +128,23 @@ where
         validator_set: &Ctx::ValidatorSet,
         thresholds: ThresholdParams,
     ) -> Result<(), CertificateError<Ctx>>;
+
+    /// Verify the validator set update certificate against the given validator set,
+    /// ie. the validator set in effect when the update was approved.
+    ///
+    /// - Check that the update belongs to the chain of the context, if it has a chain id.
+    /// - For each signature in the certificate, verify it over the update.
+    ///   If the signature is invalid, the entire certificate is rejected.
+    /// - Check that we have 2/3+ of voting power has signed the certificate.
+    ///
+    /// If any of those steps fail, return a [`CertificateError`].
+    async fn verify_validator_set_update_certificate(
+        &self,
+        ctx: &Ctx,
+        certificate: &ValidatorSetUpdateCertificate<Ctx>,
+        validator_set: &Ctx::ValidatorSet,
+        thresholds: ThresholdParams,
+    ) -> Result<(), CertificateError<Ctx>>;
 }
 
 #[async_trait]
@@ -259,6 +295,56 @@ where
         }
     }
 
+    async fn verify_commit_certificates(
+        &self,
+        ctx: &Ctx,
+        certificates: &[CommitCertificate<Ctx>],
+        validator_set: &Ctx::ValidatorSet,
+        thresholds: ThresholdParams,
+    ) -> Vec<Result<(), CertificateError<Ctx>>> {
+        let mut results = Vec::with_capacity(certificates.len());
+        let mut batched = Vec::new();
+        let mut votes = Vec::new();
+
+        for (index, certificate) in certificates.iter().enumerate() {
+            match signed_precommits(ctx, certificate, validator_set, thresholds) {
+                Some(precommits) => {
+                    votes.extend(precommits);
+                    batched.push(index);
+                    results.push(Ok(()));
+                }
+                // The certificate is invalid regardless of its signatures,
+                // verify it on its own to report the same error as for a single certificate
+                None => {
+                    let result = self
+                        .verify_commit_certificate(ctx, certificate, validator_set, thresholds)
+                        .await;
+
+                    results.push(result);
+                }
+            }
+        }
+
+        if votes.is_empty() {
+            return results;
+        }
+
+        let all_valid = self
+            .verify_signed_votes(&votes)
+            .await
+            .is_ok_and(|result| result.is_valid());
+
+        if !all_valid {
+            for index in batched {
+                results[index] = self
+                    .verify_commit_certificate(ctx, &certificates[index], validator_set, thresholds)
+                    .await;
+            }
+        }
+
+        results
+    }
+
     async fn verify_polka_certificate(
         &self,
         ctx: &Ctx,
@@ -368,4 +454,120 @@ where
             })
         }
     }
+
+    async fn verify_validator_set_update_certificate(
+        &self,
+        ctx: &Ctx,
+        certificate: &ValidatorSetUpdateCertificate<Ctx>,
+        validator_set: &Ctx::ValidatorSet,
+        thresholds: ThresholdParams,
+    ) -> Result<(), CertificateError<Ctx>> {
+        // An update signed for another chain must not be replayed on this one
+        if let Some(expected) = ctx.chain_id() {
+            if certificate.update.chain_id.as_ref() != Some(expected) {
+                return Err(CertificateError::ChainIdMismatch {
+                    expected: Some(expected.clone()),
+                    actual: certificate.update.chain_id.clone(),
+                });
+            }
+        }
+
+        let mut signed_voting_power = 0;
+        let mut seen_validators = Vec::new();
+
+        for signature in &certificate.signatures {
+            let validator_address = &signature.address;
+
+            // Abort if validator already signed
+            if seen_validators.contains(&validator_address) {
+                return Err(CertificateError::DuplicateVote(validator_address.clone()));
+            }
+
+            // Add the validator to the list of seen validators
+            seen_validators.push(validator_address);
+
+            // Abort if validator not in validator set
+            let validator = validator_set
+                .get_by_address(validator_address)
+                .ok_or_else(|| CertificateError::UnknownValidator(validator_address.clone()))?;
+
+            if self
+                .verify_validator_set_update(
+                    &certificate.update,
+                    &signature.signature,
+                    validator.public_key(),
+                )
+                .await
+                .map_err(|e| CertificateError::VerificationError(e.into_source()))?
+                .is_invalid()
+            {
+                return Err(CertificateError::InvalidValidatorSetUpdateSignature(
+                    signature.clone(),
+                ));
+            }
+
+            signed_voting_power += validator.voting_power();
+        }
+
+        let total_voting_power = validator_set.total_voting_power();
+
+        // Check if we have 2/3+ voting power
+        if thresholds
+            .quorum
+            .is_met(signed_voting_power, total_voting_power)
+        {
+            Ok(())
+        } else {
+            Err(CertificateError::NotEnoughVotingPower {
+                signed: signed_voting_power,
+                total: total_voting_power,
+                expected: thresholds.quorum.min_expected(total_voting_power),
+            })
+        }
+    }
+}
+
+/// The precommits signed in the given commit certificate, along with the public key
+/// of their validator, if the certificate has no duplicate or unknown
+/// validator and would have enough voting power if all its signatures were valid.
+fn signed_precommits<Ctx: Context>(
+    ctx: &Ctx,
+    certificate: &CommitCertificate<Ctx>,
+    validator_set: &Ctx::ValidatorSet,
+    thresholds: ThresholdParams,
+) -> Option<Vec<(SignedVote<Ctx>, PublicKey<Ctx>)>> {
+    let mut signed_voting_power = 0;
+    let mut precommits = Vec::with_capacity(certificate.commit_signatures.len());
+
+    for (i, commit_sig) in certificate.commit_signatures.iter().enumerate() {
+        let validator_address = &commit_sig.address;
+
+        if certificate.commit_signatures[..i]
+            .iter()
+            .any(|other| &other.address == validator_address)
+        {
+            return None;
+        }
+
+        let validator = validator_set.get_by_address(validator_address)?;
+
+        let vote = ctx.new_precommit(
+            certificate.height,
+            certificate.round,
+            NilOrVal::Val(certificate.value_id.clone()),
+            validator_address.clone(),
+        );
+
+        precommits.push((
+            SignedVote::new(vote, commit_sig.signature.clone()),
+            validator.public_key().clone(),
+        ));
+
+        signed_voting_power += validator.voting_power();
+    }
+
+    thresholds
+        .quorum
+        .is_met(signed_voting_power, validator_set.total_voting_power())
+        .then_some(precommits)
 }
@@ -8,7 +8,9 @@ use alloc::sync::Arc;
 use alloc::vec::Vec;
 
 use async_trait::async_trait;
-use malachitebft_core_types::{Context, PublicKey, Signature, SignedMessage, ValidatorProof};
+use malachitebft_core_types::{
+    Context, PublicKey, Signature, SignedMessage, SignedVote, ValidatorProof, ValidatorSetUpdate,
+};
 
 mod error;
 pub use error::Error;
@@ -72,6 +74,29 @@ where
         public_key: &PublicKey<Ctx>,
     ) -> Result<VerificationResult, Error>;
 
+    /// Verify the signatures of the given signed votes, each using the public key it is paired with.
+    ///
+    /// The result is only valid if all signatures are valid, without telling which ones are not.
+    /// The default implementation verifies the signatures one at a time. Implementations
+    /// for signing schemes supporting batch verification (e.g. Ed25519) should override it
+    /// to verify them all at once.
+    async fn verify_signed_votes(
+        &self,
+        votes: &[(SignedVote<Ctx>, PublicKey<Ctx>)],
+    ) -> Result<VerificationResult, Error> {
+        for (vote, public_key) in votes {
+            if self
+                .verify_signed_vote(&vote.message, &vote.signature, public_key)
+                .await?
+                .is_invalid()
+            {
+                return Ok(VerificationResult::Invalid);
+            }
+        }
+
+        Ok(VerificationResult::Valid)
+    }
+
     /// Verify the given proposal's signature using the given public key.
     async fn verify_signed_proposal(
         &self,
@@ -98,6 +123,15 @@ where
         &self,
         proof: &ValidatorProof<Ctx>,
     ) -> Result<VerificationResult, Error>;
+
+    /// Verify the signature of a validator approving the given validator set update,
+    /// over the bytes produced by [`ValidatorSetUpdate::signing_bytes`].
+    async fn verify_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<Ctx>,
+        signature: &Signature<Ctx>,
+        public_key: &PublicKey<Ctx>,
+    ) -> Result<VerificationResult, Error>;
 }
 
 /// A provider of message signing functionality for the consensus engine.
@@ -143,6 +177,13 @@ where
         public_key: Vec<u8>,
         peer_id: Vec<u8>,
     ) -> Result<ValidatorProof<Ctx>, Error>;
+
+    /// Sign the given validator set update, approving it, over the bytes produced
+    /// by [`ValidatorSetUpdate::signing_bytes`].
+    async fn sign_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<Ctx>,
+    ) -> Result<Signature<Ctx>, Error>;
 }
 
 // --- Blanket impls for &T ---
@@ -164,6 +205,13 @@ where
             .await
     }
 
+    async fn verify_signed_votes(
+        &self,
+        votes: &[(SignedVote<Ctx>, PublicKey<Ctx>)],
+    ) -> Result<VerificationResult, Error> {
+        (*self).verify_signed_votes(votes).await
+    }
+
     async fn verify_signed_proposal(
         &self,
         proposal: &Ctx::Proposal,
@@ -192,6 +240,17 @@ where
     ) -> Result<VerificationResult, Error> {
         (*self).verify_validator_proof(proof).await
     }
+
+    async fn verify_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<Ctx>,
+        signature: &Signature<Ctx>,
+        public_key: &PublicKey<Ctx>,
+    ) -> Result<VerificationResult, Error> {
+        (*self)
+            .verify_validator_set_update(update, signature, public_key)
+            .await
+    }
 }
 
 #[async_trait]
@@ -225,6 +284,13 @@ where
     ) -> Result<ValidatorProof<Ctx>, Error> {
         (*self).sign_validator_proof(public_key, peer_id).await
     }
+
+    async fn sign_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<Ctx>,
+    ) -> Result<Signature<Ctx>, Error> {
+        (*self).sign_validator_set_update(update).await
+    }
 }
 
 // --- Blanket impls for Box<dyn ...> ---
@@ -245,6 +311,13 @@ where
             .await
     }
 
+    async fn verify_signed_votes(
+        &self,
+        votes: &[(SignedVote<Ctx>, PublicKey<Ctx>)],
+    ) -> Result<VerificationResult, Error> {
+        self.as_ref().verify_signed_votes(votes).await
+    }
+
     async fn verify_signed_proposal(
         &self,
         proposal: &Ctx::Proposal,
@@ -273,6 +346,17 @@ where
     ) -> Result<VerificationResult, Error> {
         self.as_ref().verify_validator_proof(proof).await
     }
+
+    async fn verify_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<Ctx>,
+        signature: &Signature<Ctx>,
+        public_key: &PublicKey<Ctx>,
+    ) -> Result<VerificationResult, Error> {
+        self.as_ref()
+            .verify_validator_set_update(update, signature, public_key)
+            .await
+    }
 }
 
 #[async_trait]
@@ -307,6 +391,13 @@ where
             .sign_validator_proof(public_key, peer_id)
             .await
     }
+
+    async fn sign_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<Ctx>,
+    ) -> Result<Signature<Ctx>, Error> {
+        self.as_ref().sign_validator_set_update(update).await
+    }
 }
 
 // --- Blanket impls for Arc<dyn ...> ---
@@ -327,6 +418,13 @@ where
             .await
     }
 
+    async fn verify_signed_votes(
+        &self,
+        votes: &[(SignedVote<Ctx>, PublicKey<Ctx>)],
+    ) -> Result<VerificationResult, Error> {
+        self.as_ref().verify_signed_votes(votes).await
+    }
+
     async fn verify_signed_proposal(
         &self,
         proposal: &Ctx::Proposal,
@@ -355,6 +453,17 @@ where
     ) -> Result<VerificationResult, Error> {
         self.as_ref().verify_validator_proof(proof).await
     }
+
+    async fn verify_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<Ctx>,
+        signature: &Signature<Ctx>,
+        public_key: &PublicKey<Ctx>,
+    ) -> Result<VerificationResult, Error> {
+        self.as_ref()
+            .verify_validator_set_update(update, signature, public_key)
+            .await
+    }
 }
 
 #[async_trait]
@@ -389,4 +498,11 @@ where
             .sign_validator_proof(public_key, peer_id)
             .await
     }
+
+    async fn sign_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<Ctx>,
+    ) -> Result<Signature<Ctx>, Error> {
+        self.as_ref().sign_validator_set_update(update).await
+    }
 }
@@ -5,6 +5,9 @@ use libp2p::swarm::NetworkBehaviour;
 use libp2p::{PeerId, StreamProtocol};
 use thiserror::Error;
 
+use malachitebft_metrics::Registry;
+
+use crate::compression::{self, CompressionMetrics};
 use crate::rpc::Codec;
 use crate::types::{RawRequest, RawResponse, ResponseChannel};
 use crate::Config;
@@ -27,18 +30,47 @@ pub struct Behaviour {
 
 pub type Event = rpc::Event<RawRequest, RawResponse>;
 
-impl Behaviour {
-    pub fn new(config: Config, sync_protocol: String) -> Result<Self> {
-        let protocol = [(
+/// The compressed variant of the given protocol, preferred during negotiation, followed by the protocol itself.
+fn protocols(sync_protocol: String) -> Result<[(StreamProtocol, ProtocolSupport); 2]> {
+    Ok([
+        (
+            StreamProtocol::try_from_owned(compression::compressed_protocol(&sync_protocol))?,
+            ProtocolSupport::Full,
+        ),
+        (
             StreamProtocol::try_from_owned(sync_protocol)?,
             ProtocolSupport::Full,
-        )];
+        ),
+    ])
+}
+
+impl Behaviour {
+    pub fn new(config: Config, sync_protocol: String) -> Result<Self> {
+        Self::with_compression_metrics(config, sync_protocol, CompressionMetrics::default())
+    }
+
+    /// Create the behaviour, registering the compression metrics in the given registry.
+    pub fn new_with_metrics(
+        config: Config,
+        sync_protocol: String,
+        registry: &mut Registry,
+    ) -> Result<Self> {
+        let metrics = CompressionMetrics::register(registry);
+        Self::with_compression_metrics(config, sync_protocol, metrics)
+    }
+
+    fn with_compression_metrics(
+        config: Config,
+        sync_protocol: String,
+        metrics: CompressionMetrics,
+    ) -> Result<Self> {
+        let protocols = protocols(sync_protocol)?;
         let rpc_config = rpc::Config::default()
             .with_request_timeout(config.request_timeout)
             .with_max_concurrent_streams(max_concurrent_streams(&config));
 
         Ok(Self {
-            rpc: rpc::Behaviour::with_codec(Codec::new(config), protocol, rpc_config),
+            rpc: rpc::Behaviour::with_codec(Codec::new(config, metrics), protocols, rpc_config),
         })
     }
 
@@ -65,16 +97,26 @@ pub enum Error {
 impl Behaviour {
     pub fn with_default_protocol(config: Config) -> Self {
         // Infallible constructor using hardcoded default protocol
-        let protocol = [(
-            StreamProtocol::new("/malachitebft-sync/v1beta1"),
-            ProtocolSupport::Full,
-        )];
+        let protocols = [
+            (
+                StreamProtocol::new("/malachitebft-sync/v1beta1/lz4"),
+                ProtocolSupport::Full,
+            ),
+            (
+                StreamProtocol::new("/malachitebft-sync/v1beta1"),
+                ProtocolSupport::Full,
+            ),
+        ];
         let rpc_config = rpc::Config::default()
             .with_request_timeout(config.request_timeout)
             .with_max_concurrent_streams(max_concurrent_streams(&config));
 
         Self {
-            rpc: rpc::Behaviour::with_codec(Codec::new(config), protocol, rpc_config),
+            rpc: rpc::Behaviour::with_codec(
+                Codec::new(config, CompressionMetrics::default()),
+                protocols,
+                rpc_config,
+            ),
         }
     }
 }
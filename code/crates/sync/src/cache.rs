@@ -0,0 +1,199 @@
+//! Bounded cache of the values decided by this node, to serve the sync requests
+//! of peers catching up with recent heights without asking the application.
+
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+use malachitebft_core_types::{Context, Height};
+
+use crate::RawDecidedValue;
+
+/// Bounded cache of recently decided values, with their encoded bytes and certificate,
+/// evicting the least recently used value when full.
+pub struct DecidedValuesCache<Ctx: Context> {
+    capacity: usize,
+    values: BTreeMap<Ctx::Height, (RawDecidedValue<Ctx>, u64)>,
+    recency: BTreeMap<u64, Ctx::Height>,
+    tick: u64,
+}
+
+impl<Ctx: Context> DecidedValuesCache<Ctx> {
+    /// Create a cache holding at most `capacity` values. A capacity of zero disables the cache.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            values: BTreeMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Whether the cache can hold any value.
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Number of values in the cache.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the cache holds no value.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Get the values for all the heights in the given range, if they are all in the cache.
+    pub fn get_range(
+        &mut self,
+        range: &RangeInclusive<Ctx::Height>,
+    ) -> Option<Vec<RawDecidedValue<Ctx>>> {
+        let (start, end) = (*range.start(), *range.end());
+
+        if start > end {
+            return None;
+        }
+
+        let count = end.as_u64() - start.as_u64() + 1;
+        if count > self.values.len() as u64 {
+            return None;
+        }
+
+        let values = self
+            .values
+            .range(start..=end)
+            .map(|(_, (value, _))| value.clone())
+            .collect::<Vec<_>>();
+
+        if values.len() as u64 != count {
+            return None;
+        }
+
+        for value in &values {
+            self.touch(value.certificate.height);
+        }
+
+        Some(values)
+    }
+
+    /// Insert a decided value, evicting the least recently used value if the cache is full.
+    pub fn insert(&mut self, value: RawDecidedValue<Ctx>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let height = value.certificate.height;
+
+        self.tick += 1;
+
+        if let Some((_, used)) = self.values.insert(height, (value, self.tick)) {
+            self.recency.remove(&used);
+        }
+
+        self.recency.insert(self.tick, height);
+
+        while self.values.len() > self.capacity {
+            let Some((_, evicted)) = self.recency.pop_first() else {
+                break;
+            };
+
+            self.values.remove(&evicted);
+        }
+    }
+
+    /// Mark the value at the given height as the most recently used one.
+    fn touch(&mut self, height: Ctx::Height) {
+        let Some((_, used)) = self.values.get_mut(&height) else {
+            return;
+        };
+
+        self.tick += 1;
+        self.recency.remove(used);
+        self.recency.insert(self.tick, height);
+        *used = self.tick;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use arc_malachitebft_test::{Height, TestContext, ValueId};
+    use malachitebft_core_types::{CommitCertificate, Round};
+
+    use super::*;
+
+    fn value(height: u64) -> RawDecidedValue<TestContext> {
+        let certificate = CommitCertificate::new(
+            Height::new(height),
+            Round::new(0),
+            ValueId::new(height),
+            vec![],
+        );
+
+        RawDecidedValue::new(Bytes::from(height.to_be_bytes().to_vec()), certificate)
+    }
+
+    fn heights(values: &[RawDecidedValue<TestContext>]) -> Vec<u64> {
+        values
+            .iter()
+            .map(|value| value.certificate.height.as_u64())
+            .collect()
+    }
+
+    #[test]
+    fn serves_only_complete_ranges() {
+        let mut cache = DecidedValuesCache::new(10);
+
+        for height in [1, 2, 3, 5] {
+            cache.insert(value(height));
+        }
+
+        let range = Height::new(1)..=Height::new(3);
+        assert_eq!(heights(&cache.get_range(&range).unwrap()), [1, 2, 3]);
+
+        let range = Height::new(3)..=Height::new(5);
+        assert!(cache.get_range(&range).is_none());
+
+        let range = Height::new(5)..=Height::new(6);
+        assert!(cache.get_range(&range).is_none());
+    }
+
+    #[test]
+    fn evicts_least_recently_used_values() {
+        let mut cache = DecidedValuesCache::new(3);
+
+        for height in 1..=3 {
+            cache.insert(value(height));
+        }
+
+        // Height 1 is now more recently used than heights 2 and 3
+        assert!(cache
+            .get_range(&(Height::new(1)..=Height::new(1)))
+            .is_some());
+
+        cache.insert(value(4));
+
+        assert_eq!(cache.len(), 3);
+        assert!(cache
+            .get_range(&(Height::new(2)..=Height::new(2)))
+            .is_none());
+        assert!(cache
+            .get_range(&(Height::new(1)..=Height::new(1)))
+            .is_some());
+        assert!(cache
+            .get_range(&(Height::new(3)..=Height::new(4)))
+            .is_some());
+    }
+
+    #[test]
+    fn disabled_cache_holds_nothing() {
+        let mut cache = DecidedValuesCache::new(0);
+        cache.insert(value(1));
+
+        assert!(cache.is_empty());
+        assert!(cache
+            .get_range(&(Height::new(1)..=Height::new(1)))
+            .is_none());
+    }
+}
@@ -0,0 +1,241 @@
+//! Compression of the responses sent over the sync protocol.
+//!
+//! Responses carrying large values dominate the bandwidth used by sync, so they are compressed
+//! with LZ4 when both peers support it. Support is negotiated through the protocol name: a node
+//! advertises the compressed variant of the protocol (the protocol name followed by
+//! [`COMPRESSED_PROTOCOL_SUFFIX`]) in addition to the plain one, and peers running an older
+//! version fall back to the plain protocol.
+//!
+//! On the compressed protocol, every response starts with a flag telling whether it is compressed,
+//! so that responses below the configured threshold, or which do not compress well,
+//! can still be sent as is.
+//!
+//! Wire format: `flag (u8) || payload` if uncompressed (flag 0),
+//! `flag (u8) || uncompressed length (u32 big-endian) || LZ4 block` if compressed (flag 1).
+
+use std::io;
+use std::time::Instant;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use malachitebft_metrics::prometheus::metrics::histogram::{
+    exponential_buckets, linear_buckets, Histogram,
+};
+use malachitebft_metrics::Registry;
+
+/// Suffix appended to the name of a protocol to get the name of its compressed variant.
+pub const COMPRESSED_PROTOCOL_SUFFIX: &str = "/lz4";
+
+const UNCOMPRESSED: u8 = 0;
+const LZ4: u8 = 1;
+
+/// Compression of the responses sent to peers which support it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CompressionConfig {
+    /// Responses up to this size, in bytes, are sent uncompressed.
+    pub threshold: usize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 16 * 1024, // 16 KiB
+        }
+    }
+}
+
+/// Name of the compressed variant of the given protocol.
+pub fn compressed_protocol(protocol: &str) -> String {
+    format!("{protocol}{COMPRESSED_PROTOCOL_SUFFIX}")
+}
+
+/// Whether the given protocol is the compressed variant of a protocol.
+pub fn is_compressed_protocol(protocol: &str) -> bool {
+    protocol.ends_with(COMPRESSED_PROTOCOL_SUFFIX)
+}
+
+#[derive(Clone, Debug)]
+pub struct CompressionMetrics {
+    /// Size of the compressed responses relative to their uncompressed size
+    ratio: Histogram,
+    /// Time spent compressing responses, in seconds
+    compress_time: Histogram,
+    /// Time spent decompressing responses, in seconds
+    decompress_time: Histogram,
+}
+
+impl Default for CompressionMetrics {
+    fn default() -> Self {
+        Self {
+            ratio: Histogram::new(linear_buckets(0.1, 0.1, 10)),
+            compress_time: Histogram::new(exponential_buckets(0.0001, 2.0, 16)),
+            decompress_time: Histogram::new(exponential_buckets(0.0001, 2.0, 16)),
+        }
+    }
+}
+
+impl CompressionMetrics {
+    pub fn register(registry: &mut Registry) -> Self {
+        let metrics = Self::default();
+
+        registry.register(
+            "response_compression_ratio",
+            "Size of the compressed sync responses relative to their uncompressed size",
+            metrics.ratio.clone(),
+        );
+
+        registry.register(
+            "response_compression_time",
+            "Time spent compressing sync responses, in seconds",
+            metrics.compress_time.clone(),
+        );
+
+        registry.register(
+            "response_decompression_time",
+            "Time spent decompressing sync responses, in seconds",
+            metrics.decompress_time.clone(),
+        );
+
+        metrics
+    }
+}
+
+/// Encode a response for the compressed protocol,
+/// compressing it if it is larger than the threshold and compression is enabled.
+pub fn encode(
+    data: Bytes,
+    config: Option<CompressionConfig>,
+    metrics: &CompressionMetrics,
+) -> Bytes {
+    let compressed = config
+        .filter(|config| data.len() > config.threshold)
+        .and_then(|_| {
+            let start = Instant::now();
+            let compressed = lz4_flex::block::compress(&data);
+            metrics.compress_time.observe(start.elapsed().as_secs_f64());
+
+            metrics
+                .ratio
+                .observe(compressed.len() as f64 / data.len() as f64);
+
+            // Only keep the compressed response if it is actually smaller
+            (compressed.len() + 4 < data.len()).then_some(compressed)
+        });
+
+    match compressed {
+        Some(compressed) => {
+            let mut buf = BytesMut::with_capacity(1 + 4 + compressed.len());
+            buf.put_u8(LZ4);
+            buf.put_u32(data.len() as u32);
+            buf.put_slice(&compressed);
+            buf.freeze()
+        }
+        None => {
+            let mut buf = BytesMut::with_capacity(1 + data.len());
+            buf.put_u8(UNCOMPRESSED);
+            buf.put_slice(&data);
+            buf.freeze()
+        }
+    }
+}
+
+/// Decode a response received over the compressed protocol,
+/// rejecting responses which would decompress to more than `max_len` bytes.
+pub fn decode(mut data: Bytes, max_len: usize, metrics: &CompressionMetrics) -> io::Result<Bytes> {
+    if data.is_empty() {
+        return Err(invalid_data("missing compression flag"));
+    }
+
+    match data.get_u8() {
+        UNCOMPRESSED => Ok(data),
+
+        LZ4 => {
+            if data.len() < 4 {
+                return Err(invalid_data("missing uncompressed length"));
+            }
+
+            let len = data.get_u32() as usize;
+            if len > max_len {
+                return Err(invalid_data("data too large"));
+            }
+
+            let start = Instant::now();
+            let decompressed = lz4_flex::block::decompress(&data, len)
+                .map_err(|e| invalid_data(&format!("invalid compressed data: {e}")))?;
+            metrics
+                .decompress_time
+                .observe(start.elapsed().as_secs_f64());
+
+            if decompressed.len() != len {
+                return Err(invalid_data("unexpected uncompressed length"));
+            }
+
+            Ok(Bytes::from(decompressed))
+        }
+
+        flag => Err(invalid_data(&format!("unknown compression flag {flag}"))),
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    const MAX_LEN: usize = 1024 * 1024;
+
+    fn config(threshold: usize) -> Option<CompressionConfig> {
+        Some(CompressionConfig { threshold })
+    }
+
+    #[test]
+    fn roundtrip() {
+        let metrics = CompressionMetrics::default();
+        let data = Bytes::from(b"decided value ".repeat(1000));
+
+        let encoded = encode(data.clone(), config(1024), &metrics);
+        assert_eq!(encoded[0], LZ4);
+        assert!(encoded.len() < data.len() / 4);
+        assert_eq!(decode(encoded, MAX_LEN, &metrics).unwrap(), data);
+
+        // Below the threshold, or with compression disabled, the response is sent as is
+        for config in [config(data.len()), None] {
+            let encoded = encode(data.clone(), config, &metrics);
+            assert_eq!(encoded[0], UNCOMPRESSED);
+            assert_eq!(decode(encoded, MAX_LEN, &metrics).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn incompressible_data_is_sent_as_is() {
+        let metrics = CompressionMetrics::default();
+        let mut data = vec![0; 4096];
+        rand::thread_rng().fill(&mut data[..]);
+        let data = Bytes::from(data);
+
+        let encoded = encode(data.clone(), config(0), &metrics);
+        assert_eq!(encoded[0], UNCOMPRESSED);
+        assert_eq!(decode(encoded, MAX_LEN, &metrics).unwrap(), data);
+    }
+
+    #[test]
+    fn reject_invalid_responses() {
+        let metrics = CompressionMetrics::default();
+        let data = Bytes::from(vec![0; 4096]);
+        let encoded = encode(data, config(0), &metrics);
+
+        // Decompressing to more than the maximum response size
+        assert!(decode(encoded.clone(), 1024, &metrics).is_err());
+
+        // Truncated or unknown data
+        assert!(decode(encoded.slice(..encoded.len() - 1), MAX_LEN, &metrics).is_err());
+        assert!(decode(Bytes::new(), MAX_LEN, &metrics).is_err());
+        assert!(decode(Bytes::from_static(&[LZ4, 0]), MAX_LEN, &metrics).is_err());
+        assert!(decode(Bytes::from_static(&[2, 0]), MAX_LEN, &metrics).is_err());
+    }
+}
@@ -1,9 +1,61 @@
 use std::time::Duration;
 
+use malachitebft_retry::Backoff;
+
+use crate::compression::CompressionConfig;
 use crate::scoring::Strategy;
+use crate::selection::Selection;
 
 const DEFAULT_PARALLEL_REQUESTS: usize = 5;
 const DEFAULT_BATCH_SIZE: usize = 5;
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 10;
+const DEFAULT_BANDWIDTH_BUDGET: usize = 100 * 1024 * 1024; // 100 MiB per second
+
+/// Configuration of the backfill of decided values below the earliest height
+/// retained by this node, eg. after it was started from a snapshot.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BackfillConfig {
+    /// Lowest height to backfill.
+    pub target_min_height: u64,
+    /// Interval between two backfill requests, bounding the rate at which historical values are fetched.
+    pub request_interval: Duration,
+}
+
+/// Configuration of the notifications telling the application below which height
+/// it can prune its decided values, so that storage is reclaimed without
+/// pruning values that peers still need to catch up.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RetentionConfig {
+    /// Minimum number of latest decided values to retain, regardless of peers.
+    pub retain_values: u64,
+    /// Maximum number of latest decided values to retain for peers which are behind,
+    /// so that a peer far behind cannot prevent pruning forever.
+    pub max_retain_values: u64,
+    /// Interval between two computations of the height below which values can be pruned.
+    pub interval: Duration,
+}
+
+/// Limits on the requests served to each peer, so that a peer cannot make this node
+/// send it large amounts of data by requesting values repeatedly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RequestLimits {
+    /// Maximum number of values a peer may request at once.
+    pub max_range_size: usize,
+    /// Maximum number of requests from the same peer being served at once.
+    pub max_concurrent_requests: usize,
+    /// Maximum number of bytes of values sent to each peer per second, unlimited if `None`.
+    pub bandwidth_budget: Option<usize>,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_range_size: DEFAULT_BATCH_SIZE,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            bandwidth_budget: Some(DEFAULT_BANDWIDTH_BUDGET),
+        }
+    }
+}
 
 #[derive(Copy, Clone, Debug)]
 pub struct Config {
@@ -13,8 +65,21 @@ pub struct Config {
     pub max_response_size: usize,
     pub parallel_requests: usize,
     pub scoring_strategy: Strategy,
+    /// Strategy for selecting the peer to request values from.
+    pub peer_selection: Selection,
     pub inactive_threshold: Option<Duration>,
     pub batch_size: usize,
+    /// Retry policy for re-requesting a range of values after a failed request.
+    /// Values are re-requested right away from another peer, so only the retry limits apply.
+    pub request_retry: Backoff,
+    /// Backfill of historical values, disabled if `None`.
+    pub backfill: Option<BackfillConfig>,
+    /// Compression of the responses sent to peers which support it, disabled if `None`.
+    pub compression: Option<CompressionConfig>,
+    /// Limits on the requests served to each peer.
+    pub request_limits: RequestLimits,
+    /// Notifications of the height below which decided values can be pruned, disabled if `None`.
+    pub retention: Option<RetentionConfig>,
 }
 
 impl Config {
@@ -50,6 +115,11 @@ impl Config {
         self
     }
 
+    pub fn with_peer_selection(mut self, peer_selection: Selection) -> Self {
+        self.peer_selection = peer_selection;
+        self
+    }
+
     pub fn with_inactive_threshold(mut self, inactive_threshold: Option<Duration>) -> Self {
         self.inactive_threshold = inactive_threshold;
         self
@@ -59,6 +129,31 @@ impl Config {
         self.batch_size = batch_size;
         self
     }
+
+    pub fn with_request_retry(mut self, request_retry: Backoff) -> Self {
+        self.request_retry = request_retry;
+        self
+    }
+
+    pub fn with_backfill(mut self, backfill: Option<BackfillConfig>) -> Self {
+        self.backfill = backfill;
+        self
+    }
+
+    pub fn with_compression(mut self, compression: Option<CompressionConfig>) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn with_request_limits(mut self, request_limits: RequestLimits) -> Self {
+        self.request_limits = request_limits;
+        self
+    }
+
+    pub fn with_retention(mut self, retention: Option<RetentionConfig>) -> Self {
+        self.retention = retention;
+        self
+    }
 }
 
 impl Default for Config {
@@ -70,8 +165,14 @@ impl Default for Config {
             max_response_size: 10 * 1024 * 1024, // 10 MiB
             parallel_requests: DEFAULT_PARALLEL_REQUESTS,
             scoring_strategy: Strategy::default(),
+            peer_selection: Selection::default(),
             inactive_threshold: None,
             batch_size: DEFAULT_BATCH_SIZE,
+            request_retry: Backoff::default(),
+            backfill: None,
+            compression: None,
+            request_limits: RequestLimits::default(),
+            retention: None,
         }
     }
 }
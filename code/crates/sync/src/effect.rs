@@ -3,10 +3,12 @@ use std::{marker::PhantomData, ops::RangeInclusive};
 use derive_where::derive_where;
 use thiserror::Error;
 
-use malachitebft_core_types::Context;
+use malachitebft_core_types::{Context, ErrorKind};
 use malachitebft_peer::PeerId;
 
-use crate::{InboundRequestId, OutboundRequestId, ValueRequest, ValueResponse};
+use crate::{
+    InboundRequestId, NodeMode, OutboundRequestId, RawDecidedValue, ValueRequest, ValueResponse,
+};
 
 /// Provides a way to construct the appropriate [`Resume`] value to
 /// resume execution after handling an [`Effect`].
@@ -48,10 +50,28 @@ pub enum Error<Ctx: Context> {
     UnexpectedResume(Resume<Ctx>, &'static str),
 }
 
+impl<Ctx: Context> Error<Ctx> {
+    /// The code of this error, to count the sync errors per type.
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::UnexpectedResume(..) => "unexpected_resume",
+        }
+    }
+
+    /// The class of this error, to decide how to recover from it.
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            Self::UnexpectedResume(..) => ErrorKind::Internal,
+        }
+    }
+}
+
 #[derive_where(Debug)]
 pub enum Resume<Ctx: Context> {
     Continue(PhantomData<Ctx>),
     ValueRequestId(Option<OutboundRequestId>),
+    HistoryMinHeight(Ctx::Height),
+    BackfillStored(Option<bool>),
 }
 
 impl<Ctx: Context> Default for Resume<Ctx> {
@@ -62,8 +82,19 @@ impl<Ctx: Context> Default for Resume<Ctx> {
 
 #[derive_where(Debug)]
 pub enum Effect<Ctx: Context> {
-    /// Broadcast our status to our direct peers
-    BroadcastStatus(Ctx::Height, resume::Continue),
+    /// Get the earliest height for which the application still retains decided values
+    GetHistoryMinHeight(resume::HistoryMinHeight),
+
+    /// Broadcast our status to our direct peers, ie. our tip height,
+    /// the earliest height for which we can serve decided values,
+    /// the next height we will request via sync, and whether we are a validator
+    BroadcastStatus(
+        Ctx::Height,
+        Ctx::Height,
+        Ctx::Height,
+        NodeMode,
+        resume::Continue,
+    ),
 
     /// Send a ValueSync request to a peer
     SendValueRequest(PeerId, ValueRequest<Ctx>, resume::ValueRequestId),
@@ -85,6 +116,20 @@ pub enum Effect<Ctx: Context> {
         ValueResponse<Ctx>,
         resume::Continue,
     ),
+
+    /// Ask the application to verify and store decided values backfilled from a peer,
+    /// below the earliest height it retains.
+    ///
+    /// Resumes with whether the values were stored, or `None` if the application
+    /// was too busy to process them.
+    StoreBackfilledValues(PeerId, Vec<RawDecidedValue<Ctx>>, resume::BackfillStored),
+
+    /// Report the progress of the backfill, ie. the lowest height backfilled so far
+    /// and the lowest height to backfill
+    ReportBackfillProgress(Ctx::Height, Ctx::Height, resume::Continue),
+
+    /// Notify the application that it can prune the decided values below the given height
+    Prune(Ctx::Height, resume::Continue),
 }
 
 pub mod resume {
@@ -112,4 +157,26 @@ pub mod resume {
             Resume::ValueRequestId(value)
         }
     }
+
+    #[derive(Debug, Default)]
+    pub struct HistoryMinHeight;
+
+    impl<Ctx: Context> Resumable<Ctx> for HistoryMinHeight {
+        type Value = Ctx::Height;
+
+        fn resume_with(self, value: Self::Value) -> Resume<Ctx> {
+            Resume::HistoryMinHeight(value)
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub struct BackfillStored;
+
+    impl<Ctx: Context> Resumable<Ctx> for BackfillStored {
+        type Value = Option<bool>;
+
+        fn resume_with(self, value: Self::Value) -> Resume<Ctx> {
+            Resume::BackfillStored(value)
+        }
+    }
 }
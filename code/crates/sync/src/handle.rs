@@ -1,19 +1,21 @@
 use std::cmp::{max, min};
 use std::collections::{BTreeMap, BTreeSet};
 use std::ops::RangeInclusive;
+use std::time::Instant;
 
 use derive_where::derive_where;
 use tracing::{debug, error, info, warn};
 
 use malachitebft_core_types::utils::height::{DisplayRange, HeightRangeExt};
 use malachitebft_core_types::{Context, Height};
+use malachitebft_retry::Retry;
 
 use crate::co::Co;
 use crate::scoring::SyncResult;
 use crate::{
-    perform, Effect, Error, HeightStartType, InboundRequestId, Metrics, OutboundRequestId, PeerId,
-    PendingRequestEntry, RawDecidedValue, Request, Resume, State, Status, ValueRequest,
-    ValueResponse,
+    perform, Announcement, Effect, Error, HeightStartType, InboundRequestId, Metrics,
+    OutboundRequestId, PauseReason, PeerId, PendingRequestEntry, RawDecidedValue, Request, Resume,
+    State, Status, ValueRequest, ValueResponse,
 };
 
 #[derive_where(Debug)]
@@ -31,6 +33,9 @@ pub enum Input<Ctx: Context> {
     /// Consensus just decided on a new value
     Decided(Ctx::Height),
 
+    /// A peer announced that it decided on a new value
+    Announcement(Announcement<Ctx>),
+
     /// A ValueSync request has been received from a peer
     ValueRequest(InboundRequestId, PeerId, ValueRequest<Ctx>),
 
@@ -52,6 +57,25 @@ pub enum Input<Ctx: Context> {
 
     /// An error occurred while processing a value
     ValueProcessingError(PeerId, Ctx::Height),
+
+    /// Consensus observed validators with at least f+1 voting power voting at the given height,
+    /// in votes relayed by the given peers
+    FutureHeightObserved(Ctx::Height, Vec<PeerId>),
+
+    /// Periodical event triggering the next backfill request, if backfill is enabled
+    BackfillTick,
+
+    /// Periodical event triggering the computation of the height below which
+    /// decided values can be pruned, if retention is enabled
+    PruneTick,
+
+    /// Stop requesting values from peers for the given reason, eg. because the application
+    /// cannot keep up with the synced values. Requests from peers are still served.
+    Pause(PauseReason),
+
+    /// Lift the given reason for pausing sync, resuming the requests for values
+    /// once sync is not paused for any other reason.
+    Resume(PauseReason),
 }
 
 pub async fn handle<Ctx>(
@@ -74,10 +98,20 @@ where
 
         Input::Decided(height) => on_decided(state, metrics, height).await,
 
+        Input::Announcement(announcement) => {
+            on_announcement(co, state, metrics, announcement).await
+        }
+
         Input::ValueRequest(request_id, peer_id, request) => {
             on_value_request(co, state, metrics, request_id, peer_id, request).await
         }
 
+        Input::ValueResponse(request_id, peer_id, response)
+            if state.is_backfill_request(&request_id) =>
+        {
+            on_backfill_response(co, state, metrics, request_id, peer_id, response).await
+        }
+
         Input::ValueResponse(request_id, peer_id, Some(response)) => {
             on_value_response(co, state, metrics, request_id, peer_id, response).await
         }
@@ -90,6 +124,12 @@ where
             on_got_decided_values(co, state, metrics, request_id, range, values).await
         }
 
+        Input::SyncRequestTimedOut(request_id, peer_id, _)
+            if state.is_backfill_request(&request_id) =>
+        {
+            on_backfill_request_timed_out(state, request_id, peer_id).await
+        }
+
         Input::SyncRequestTimedOut(request_id, peer_id, request) => {
             on_sync_request_timed_out(co, state, metrics, request_id, peer_id, request).await
         }
@@ -99,6 +139,18 @@ where
         Input::ValueProcessingError(peer, height) => {
             on_value_processing_error(co, state, metrics, peer, height).await
         }
+
+        Input::FutureHeightObserved(height, peers) => {
+            on_future_height_observed(co, state, metrics, height, peers).await
+        }
+
+        Input::BackfillTick => on_backfill_tick(co, state, metrics).await,
+
+        Input::PruneTick => on_prune_tick(co, state, metrics).await,
+
+        Input::Pause(reason) => on_pause(state, metrics, reason).await,
+
+        Input::Resume(reason) => on_resume(co, state, metrics, reason).await,
     }
 }
 
@@ -200,11 +252,31 @@ pub async fn on_send_status_update<Ctx>(
 where
     Ctx: Context,
 {
-    debug!(tip_height = %state.tip_height, "Broadcasting status");
+    let history_min_height = perform!(
+        co,
+        Effect::GetHistoryMinHeight(Default::default()),
+        Resume::HistoryMinHeight(height) => height
+    );
+
+    state.history_min_height = history_min_height;
+
+    debug!(
+        tip_height = %state.tip_height,
+        history_min_height = %state.history_min_height,
+        sync_height = %state.sync_height,
+        mode = ?state.mode,
+        "Broadcasting status"
+    );
 
     perform!(
         co,
-        Effect::BroadcastStatus(state.tip_height, Default::default())
+        Effect::BroadcastStatus(
+            state.tip_height,
+            state.history_min_height,
+            state.sync_height,
+            state.mode,
+            Default::default()
+        )
     );
 
     if let Some(inactive_threshold) = state.config.inactive_threshold {
@@ -315,6 +387,142 @@ where
     Ok(())
 }
 
+/// A peer announced a value it just decided. Rather than waiting for its next status update,
+/// consider that the peer has decided that height, and request the value if we are missing it.
+pub async fn on_announcement<Ctx>(
+    co: Co<Ctx>,
+    state: &mut State<Ctx>,
+    metrics: &Metrics,
+    announcement: Announcement<Ctx>,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+{
+    let peer_id = announcement.peer_id;
+    let height = announcement.height;
+
+    debug!(
+        %peer_id,
+        %height,
+        value_id = %announcement.value_id,
+        "Received decided value announcement"
+    );
+
+    // Only the peers which sent us their status are known to retain the values we request.
+    let Some(status) = state.peers.get_mut(&peer_id) else {
+        return Ok(());
+    };
+
+    status.tip_height = max(status.tip_height, height);
+
+    if !state.started || height < state.sync_height {
+        // Either consensus has not started yet, or the value is already requested.
+        return Ok(());
+    }
+
+    info!(
+        tip_height = %state.tip_height,
+        sync_height = %state.sync_height,
+        %peer_id,
+        announced_height = %height,
+        "SYNC REQUIRED: Peer announced a higher decided value"
+    );
+
+    request_values(co, state, metrics).await
+}
+
+/// At least one correct validator is voting at the observed height, so all heights below it
+/// have been decided. Rather than waiting for the next status update of our peers, consider
+/// that the peers which relayed these votes have decided these heights, and request them.
+pub async fn on_future_height_observed<Ctx>(
+    co: Co<Ctx>,
+    state: &mut State<Ctx>,
+    metrics: &Metrics,
+    height: Ctx::Height,
+    peers: Vec<PeerId>,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+{
+    let Some(decided_height) = height.decrement() else {
+        return Ok(());
+    };
+
+    debug!(%height, peers = peers.len(), "Consensus observed a higher height");
+
+    if !state.started || decided_height < state.sync_height {
+        // Either consensus has not started yet, or the missing values are already requested.
+        return Ok(());
+    }
+
+    // Only the peers which sent us their status are known to retain these values.
+    for peer_id in peers {
+        if let Some(status) = state.peers.get_mut(&peer_id) {
+            status.tip_height = max(status.tip_height, decided_height);
+        }
+    }
+
+    info!(
+        tip_height = %state.tip_height,
+        sync_height = %state.sync_height,
+        observed_height = %height,
+        "SYNC REQUIRED: Validators are voting at a higher height"
+    );
+
+    request_values(co, state, metrics).await
+}
+
+pub async fn on_pause<Ctx>(
+    state: &mut State<Ctx>,
+    metrics: &Metrics,
+    reason: PauseReason,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+{
+    if !state.paused.insert(reason) {
+        return Ok(());
+    }
+
+    info!(?reason, "Pausing sync");
+
+    if state.paused.len() == 1 {
+        metrics.sync_paused();
+    }
+
+    Ok(())
+}
+
+pub async fn on_resume<Ctx>(
+    co: Co<Ctx>,
+    state: &mut State<Ctx>,
+    metrics: &Metrics,
+    reason: PauseReason,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+{
+    if !state.paused.remove(&reason) {
+        return Ok(());
+    }
+
+    if state.is_paused() {
+        debug!(?reason, paused = ?state.paused, "Sync is still paused for other reasons");
+        return Ok(());
+    }
+
+    info!(?reason, "Resuming sync");
+
+    metrics.sync_resumed();
+
+    if state.started {
+        // Request the values we skipped while paused
+        request_values(co, state, metrics).await?;
+    }
+
+    Ok(())
+}
+
 #[tracing::instrument(
     name = "on_value_request",
     skip_all,
@@ -337,7 +545,7 @@ where
 {
     debug!("Received request for values");
 
-    if !validate_request_range::<Ctx>(&request.range, state.tip_height, state.config.batch_size) {
+    if !validate_request_range::<Ctx>(&request.range, state.history_min_height, state.tip_height) {
         debug!("Sending empty response to peer");
 
         perform!(
@@ -352,16 +560,41 @@ where
         return Ok(());
     }
 
+    if let Err(rejection) = state
+        .request_guard
+        .admit(request_id.clone(), peer_id, Instant::now())
+    {
+        warn!(%rejection, "Rejecting request for values, sending empty response to peer");
+        metrics.value_request_rejected(&rejection);
+
+        perform!(
+            co,
+            Effect::SendValueResponse(
+                request_id,
+                ValueResponse::new(*request.range.start(), vec![]),
+                Default::default()
+            )
+        );
+
+        return Ok(());
+    }
+
     metrics.value_request_received(request.range.start().as_u64());
 
-    let range = clamp_request_range::<Ctx>(&request.range, state.tip_height);
+    let max_range_size = state.request_guard.max_range_size();
+    let range = clamp_request_range::<Ctx>(&request.range, state.tip_height, max_range_size);
 
     if range != request.range {
         debug!(
             requested = %DisplayRange(&request.range),
             clamped = %DisplayRange(&range),
-            "Clamped request range to our tip height"
+            %max_range_size,
+            "Clamped request range to our tip height and the max range size"
         );
+
+        if range_len::<Ctx>(&request.range) > max_range_size {
+            metrics.value_request_range_clamped();
+        }
     }
 
     perform!(
@@ -374,8 +607,8 @@ where
 
 fn validate_request_range<Ctx>(
     range: &RangeInclusive<Ctx::Height>,
+    history_min_height: Ctx::Height,
     tip_height: Ctx::Height,
-    batch_size: usize,
 ) -> bool
 where
     Ctx: Context,
@@ -395,18 +628,29 @@ where
         return false;
     }
 
-    let len = (range.end().as_u64() - range.start().as_u64()).saturating_add(1) as usize;
-    if len > batch_size {
-        warn!("Received request for too many values: requested {len}, max is {batch_size}");
+    if range.start() < &history_min_height {
+        debug!("Received request for values below our history min height {history_min_height}");
         return false;
     }
 
     true
 }
 
+/// Number of values in the given range, which must not be empty.
+fn range_len<Ctx>(range: &RangeInclusive<Ctx::Height>) -> usize
+where
+    Ctx: Context,
+{
+    let len = (range.end().as_u64() - range.start().as_u64()).saturating_add(1);
+    usize::try_from(len).unwrap_or(usize::MAX)
+}
+
+/// Clamp the range to our tip height and to at most `max_len` values,
+/// so that the peer is served a prefix of the values it requested.
 fn clamp_request_range<Ctx>(
     range: &RangeInclusive<Ctx::Height>,
     tip_height: Ctx::Height,
+    max_len: usize,
 ) -> RangeInclusive<Ctx::Height>
 where
     Ctx: Context,
@@ -418,7 +662,12 @@ where
     );
 
     let start = *range.start();
-    let end = min(*range.end(), tip_height);
+    let mut end = min(*range.end(), tip_height);
+
+    if range_len::<Ctx>(&(start..=end)) > max_len {
+        end = start.increment_by(max_len.saturating_sub(1) as u64);
+    }
+
     start..=end
 }
 
@@ -496,7 +745,13 @@ where
 
         let entry = state.pending_requests.remove(&request_id).unwrap();
         let updated_range = range_start..=new_start.decrement().unwrap_or_default();
-        state.update_request(request_id, peer_id, updated_range, entry.excluded_peers);
+        state.update_request(
+            request_id,
+            peer_id,
+            updated_range,
+            entry.excluded_peers,
+            entry.retry,
+        );
 
         // Issue a new request for the remaining values to any peer (not necessarily the same one).
         let new_range = new_start..=range_end;
@@ -531,7 +786,7 @@ where
 
 pub async fn on_got_decided_values<Ctx>(
     co: Co<Ctx>,
-    _state: &mut State<Ctx>,
+    state: &mut State<Ctx>,
     metrics: &Metrics,
     request_id: InboundRequestId,
     range: RangeInclusive<Ctx::Height>,
@@ -578,6 +833,11 @@ where
 
     values.truncate(valid_count);
 
+    let bytes = values.iter().map(|value| value.value_bytes.len()).sum();
+    state
+        .request_guard
+        .complete(&request_id, bytes, Instant::now());
+
     debug!(%request_id, range = %DisplayRange(&range), "Sending {} values to peer", values.len());
     perform!(
         co,
@@ -674,114 +934,387 @@ where
     Ok(())
 }
 
-/// Request multiple batches of values in parallel.
-async fn request_values<Ctx>(
+/// Request the batch of values right below the earliest height we retain,
+/// unless sync is paused, a backfill request is already in flight or the target height has been reached.
+///
+/// At most one backfill request is in flight at any time, and one is sent per tick at most,
+/// so that the rate of backfill requests is bounded by the tick interval.
+async fn on_backfill_tick<Ctx>(
     co: Co<Ctx>,
     state: &mut State<Ctx>,
-    metrics: &Metrics,
+    _metrics: &Metrics,
 ) -> Result<(), Error<Ctx>>
 where
     Ctx: Context,
 {
-    let max_parallel_requests = state.max_parallel_requests();
+    if !state.started || state.is_paused() {
+        return Ok(());
+    }
 
-    if state.pending_requests.len() >= max_parallel_requests {
-        info!(
-            max_parallel_requests,
-            pending_requests = state.pending_requests.len(),
-            "Maximum number of parallel requests reached, skipping request for values"
-        );
+    let Some(backfill) = &state.backfill else {
+        return Ok(());
+    };
+
+    if backfill.done || backfill.pending.is_some() {
+        return Ok(());
+    }
+
+    let (target_min_height, batch_size) = (backfill.target_min_height, backfill.batch_size);
+
+    let history_min_height = perform!(
+        co,
+        Effect::GetHistoryMinHeight(Default::default()),
+        Resume::HistoryMinHeight(height) => height
+    );
+
+    state.history_min_height = history_min_height;
+
+    // Nothing has been decided yet, so there is no history to extend downwards
+    if history_min_height == Ctx::Height::ZERO {
+        return Ok(());
+    }
+
+    if history_min_height <= target_min_height {
+        info!(%history_min_height, %target_min_height, "Backfill complete");
+
+        if let Some(backfill) = &mut state.backfill {
+            backfill.done = true;
+        }
 
+        return Ok(());
+    }
+
+    let Some(end) = history_min_height.decrement() else {
         return Ok(());
     };
 
-    while state.pending_requests.len() < max_parallel_requests {
-        // Find the next uncovered range starting from current sync_height
-        let initial_height = state.sync_height;
-        let range = find_next_uncovered_range_from::<Ctx>(
-            initial_height,
-            state.config.batch_size as u64,
-            &state.pending_requests,
-        );
+    let start = end
+        .decrement_by(batch_size as u64 - 1)
+        .map_or(target_min_height, |start| max(start, target_min_height));
 
-        // Get a random peer that can provide the values in the range.
-        let Some((peer, range)) = state.random_peer_with(&range) else {
-            debug!("No peer to request sync from");
-            // No connected peer reached this height yet, we can stop syncing here.
-            break;
+    let Some((peer, range)) = state.random_peer_with(&(start..=end)) else {
+        debug!(range = %DisplayRange(&(start..=end)), "No peer to request backfill from");
+        return Ok(());
+    };
+
+    info!(range = %DisplayRange(&range), %peer, "Requesting backfill from peer");
+
+    let Some(request_id) = perform!(
+        co,
+        Effect::SendValueRequest(peer, ValueRequest::new(range.clone()), Default::default()),
+        Resume::ValueRequestId(id) => id,
+    ) else {
+        warn!(range = %DisplayRange(&range), %peer, "Failed to send backfill request to peer");
+        return Ok(());
+    };
+
+    if let Some(backfill) = &mut state.backfill {
+        let entry = PendingRequestEntry {
+            range,
+            peer,
+            excluded_peers: BTreeSet::new(),
+            retry: Retry::new(),
         };
 
-        send_and_track_request_to_peer(&co, state, metrics, peer, range, BTreeSet::new()).await?;
+        backfill.pending = Some((request_id, entry));
     }
 
     Ok(())
 }
 
-/// Request values for this specific range from a peer.
-/// Should only be used when re-requesting a partial range of values from a peer.
-async fn request_values_range<Ctx>(
+async fn on_backfill_response<Ctx>(
     co: Co<Ctx>,
     state: &mut State<Ctx>,
     metrics: &Metrics,
-    range: RangeInclusive<Ctx::Height>,
+    request_id: OutboundRequestId,
+    peer_id: PeerId,
+    response: Option<ValueResponse<Ctx>>,
 ) -> Result<(), Error<Ctx>>
 where
     Ctx: Context,
 {
-    // NOTE: We do not perform a `max_parallel_requests` check and return here in contrast to what is done, for
-    // example, in `request_values`. This is because `request_values_range` is only called for retrieving
-    // partial responses, which means the original request is not on the wire anymore. Nevertheless,
-    // we log here because seeing this log frequently implies that we keep getting partial responses
-    // from peers and hints to potential reconfiguration.
-    let max_parallel_requests = state.max_parallel_requests();
+    let Some(backfill) = &mut state.backfill else {
+        return Ok(());
+    };
 
-    if state.pending_requests.len() >= max_parallel_requests {
-        info!(
-            %max_parallel_requests,
-            pending_requests = %state.pending_requests.len(),
-            "Maximum number of pending requests reached when re-requesting a partial range of values"
-        );
+    let Some((_, entry)) = backfill.pending.take() else {
+        return Ok(());
     };
 
-    // Capture the range start before `range` is shadowed by the peer-selected sub-range,
-    // so we can roll sync_height back if no peer can serve it.
-    let range_start = *range.start();
+    let target_min_height = backfill.target_min_height;
+    let requested_len = entry.range.len();
 
-    // Get a random peer that can provide the values in the range.
-    let Some((peer, range)) = state.random_peer_with(&range) else {
-        // No connected peer reached this height yet, we can stop syncing here.
-        debug!(range = %DisplayRange(&range), "No peer to request sync from");
-        // Roll sync_height back towards the range start so this range is
-        // picked up again once a peer advances past it. The original request
-        // has already advanced sync_height past the range, so without this
-        // rollback the missing segment would stall until an external event.
-        set_sync_height(state, min(state.sync_height, range_start));
+    if entry.peer != peer_id {
+        warn!(
+            %request_id, actual_peer = %peer_id, expected_peer = %entry.peer,
+            "Received backfill response from different peer than expected"
+        );
+
+        state.peer_scorer.update_score(peer_id, SyncResult::Failure);
+        return Ok(());
+    }
+
+    let Some(response) = response.filter(|response| {
+        response.start_height == *entry.range.start()
+            && !response.values.is_empty()
+            && response.values.len() <= requested_len
+            && validate_value_response_heights(response)
+    }) else {
+        warn!(
+            %request_id, %peer_id, range = %DisplayRange(&entry.range),
+            "Received invalid backfill response"
+        );
+
+        state.peer_scorer.update_score(peer_id, SyncResult::Failure);
         return Ok(());
     };
 
-    send_and_track_request_to_peer(&co, state, metrics, peer, range, BTreeSet::new()).await?;
+    let received_len = response.values.len();
 
-    Ok(())
-}
+    if received_len < requested_len {
+        // Storing only a prefix of the range would leave a gap right below the earliest
+        // height we retain, so instead request smaller batches which fit in a single response.
+        debug!(
+            %request_id, %peer_id,
+            "Received {received_len} out of {requested_len} backfilled values, reducing backfill batch size"
+        );
 
-async fn send_and_track_request_to_peer<Ctx>(
-    co: &Co<Ctx>,
-    state: &mut State<Ctx>,
-    metrics: &Metrics,
-    peer: PeerId,
-    range: RangeInclusive<<Ctx as Context>::Height>,
-    excluded_peers: BTreeSet<PeerId>,
-) -> Result<(), Error<Ctx>>
-where
-    Ctx: Context,
-{
-    // Capture the range start before `range` is moved into `send_request_to_peer`,
-    // so we can roll sync_height back if the send is skipped.
-    let range_start = *range.start();
+        if let Some(backfill) = &mut state.backfill {
+            backfill.batch_size = received_len;
+        }
 
-    // Send the request
-    let Some((request_id, final_range)) =
-        send_request_to_peer(co, state, metrics, range, peer).await?
+        return Ok(());
+    }
+
+    let lowest_height = response.start_height;
+
+    let stored = perform!(
+        co,
+        Effect::StoreBackfilledValues(peer_id, response.values, Default::default()),
+        Resume::BackfillStored(stored) => stored
+    );
+
+    // The values are requested again on the next backfill tick
+    let Some(stored) = stored else {
+        warn!(%request_id, %peer_id, "Application was too busy to store the backfilled values");
+        return Ok(());
+    };
+
+    if !stored {
+        warn!(%request_id, %peer_id, "Backfilled values were rejected by the application");
+
+        state.peer_scorer.update_score(peer_id, SyncResult::Failure);
+        return Ok(());
+    }
+
+    info!(
+        %lowest_height, %target_min_height,
+        "Backfilled {received_len} values from {peer_id}"
+    );
+
+    metrics.values_backfilled(lowest_height.as_u64(), received_len);
+
+    perform!(
+        co,
+        Effect::ReportBackfillProgress(lowest_height, target_min_height, Default::default())
+    );
+
+    Ok(())
+}
+
+async fn on_prune_tick<Ctx>(
+    co: Co<Ctx>,
+    state: &mut State<Ctx>,
+    _metrics: &Metrics,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+{
+    let Some(retention) = state.config.retention else {
+        return Ok(());
+    };
+
+    // Nothing has been decided yet, so there is nothing to prune
+    if state.tip_height == Ctx::Height::ZERO {
+        return Ok(());
+    }
+
+    let retain_height = state.retain_height(&retention);
+
+    // The application may already have pruned the values below the last retain height,
+    // so only notify it when the retain height moves up.
+    if retain_height <= state.retain_height {
+        return Ok(());
+    }
+
+    debug!(%retain_height, "Decided values below the retain height can be pruned");
+
+    state.retain_height = retain_height;
+
+    perform!(co, Effect::Prune(retain_height, Default::default()));
+
+    Ok(())
+}
+
+async fn on_backfill_request_timed_out<Ctx>(
+    state: &mut State<Ctx>,
+    request_id: OutboundRequestId,
+    peer_id: PeerId,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+{
+    info!(%request_id, %peer_id, "Backfill request timed out");
+
+    if let Some(backfill) = &mut state.backfill {
+        backfill.pending = None;
+    }
+
+    // The range will be requested again on the next tick, likely from another peer
+    state.peer_scorer.update_score(peer_id, SyncResult::Timeout);
+
+    Ok(())
+}
+
+/// Request multiple batches of values in parallel.
+async fn request_values<Ctx>(
+    co: Co<Ctx>,
+    state: &mut State<Ctx>,
+    metrics: &Metrics,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+{
+    if state.is_paused() {
+        debug!(paused = ?state.paused, "Sync is paused, skipping request for values");
+        return Ok(());
+    }
+
+    let max_parallel_requests = state.max_parallel_requests();
+
+    if state.pending_requests.len() >= max_parallel_requests {
+        info!(
+            max_parallel_requests,
+            pending_requests = state.pending_requests.len(),
+            "Maximum number of parallel requests reached, skipping request for values"
+        );
+
+        return Ok(());
+    };
+
+    while state.pending_requests.len() < max_parallel_requests {
+        // Find the next uncovered range starting from current sync_height
+        let initial_height = state.sync_height;
+        let range = find_next_uncovered_range_from::<Ctx>(
+            initial_height,
+            state.config.batch_size as u64,
+            &state.pending_requests,
+        );
+
+        // Get a random peer that can provide the values in the range.
+        let Some((peer, range)) = state.random_peer_with(&range) else {
+            debug!("No peer to request sync from");
+            // No connected peer reached this height yet, we can stop syncing here.
+            break;
+        };
+
+        send_and_track_request_to_peer(
+            &co,
+            state,
+            metrics,
+            peer,
+            range,
+            BTreeSet::new(),
+            Retry::new(),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Request values for this specific range from a peer.
+/// Should only be used when re-requesting a partial range of values from a peer.
+async fn request_values_range<Ctx>(
+    co: Co<Ctx>,
+    state: &mut State<Ctx>,
+    metrics: &Metrics,
+    range: RangeInclusive<Ctx::Height>,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+{
+    // NOTE: We do not perform a `max_parallel_requests` check and return here in contrast to what is done, for
+    // example, in `request_values`. This is because `request_values_range` is only called for retrieving
+    // partial responses, which means the original request is not on the wire anymore. Nevertheless,
+    // we log here because seeing this log frequently implies that we keep getting partial responses
+    // from peers and hints to potential reconfiguration.
+    let max_parallel_requests = state.max_parallel_requests();
+
+    if state.pending_requests.len() >= max_parallel_requests {
+        info!(
+            %max_parallel_requests,
+            pending_requests = %state.pending_requests.len(),
+            "Maximum number of pending requests reached when re-requesting a partial range of values"
+        );
+    };
+
+    // Capture the range start before `range` is shadowed by the peer-selected sub-range,
+    // so we can roll sync_height back if no peer can serve it.
+    let range_start = *range.start();
+
+    if state.is_paused() {
+        // Roll sync_height back so that the range is requested again once sync resumes.
+        debug!(range = %DisplayRange(&range), "Sync is paused, skipping request for values");
+        set_sync_height(state, min(state.sync_height, range_start));
+        return Ok(());
+    }
+
+    // Get a random peer that can provide the values in the range.
+    let Some((peer, range)) = state.random_peer_with(&range) else {
+        // No connected peer reached this height yet, we can stop syncing here.
+        debug!(range = %DisplayRange(&range), "No peer to request sync from");
+        // Roll sync_height back towards the range start so this range is
+        // picked up again once a peer advances past it. The original request
+        // has already advanced sync_height past the range, so without this
+        // rollback the missing segment would stall until an external event.
+        set_sync_height(state, min(state.sync_height, range_start));
+        return Ok(());
+    };
+
+    send_and_track_request_to_peer(
+        &co,
+        state,
+        metrics,
+        peer,
+        range,
+        BTreeSet::new(),
+        Retry::new(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn send_and_track_request_to_peer<Ctx>(
+    co: &Co<Ctx>,
+    state: &mut State<Ctx>,
+    metrics: &Metrics,
+    peer: PeerId,
+    range: RangeInclusive<<Ctx as Context>::Height>,
+    excluded_peers: BTreeSet<PeerId>,
+    retry: Retry,
+) -> Result<(), Error<Ctx>>
+where
+    Ctx: Context,
+{
+    // Capture the range start before `range` is moved into `send_request_to_peer`,
+    // so we can roll sync_height back if the send is skipped.
+    let range_start = *range.start();
+
+    // Send the request
+    let Some((request_id, final_range)) =
+        send_request_to_peer(co, state, metrics, range, peer).await?
     else {
         // Request was skipped. `send_request_to_peer` returns `Ok(None)` in
         // three cases:
@@ -814,6 +1347,7 @@ where
             range: final_range.clone(),
             peer,
             excluded_peers,
+            retry,
         },
     );
 
@@ -875,9 +1409,10 @@ where
 ///
 /// If `except_peer_id` is `Some`, the failed peer is added to the set of
 /// excluded peers accumulated across retries. Once every eligible peer has
-/// been tried and failed, no further retry is attempted and sync_height is
-/// reset so a future event (status update, consensus advance) can restart
-/// the request cycle with a clean slate.
+/// been tried and failed, or once the retry budget of `Config::request_retry`
+/// is exhausted, no further retry is attempted and sync_height is reset so a
+/// future event (status update, consensus advance) can restart the request
+/// cycle with a clean slate.
 ///
 /// If `except_peer_id` is `None` (internal processing error), no peer is
 /// added to the exclusion set because the failure was not the peer's fault.
@@ -918,6 +1453,30 @@ where
         }
     };
 
+    if state.is_paused() {
+        // Roll sync_height back so that the range is requested again once sync resumes.
+        debug!(%request_id, range = %DisplayRange(&entry.range), "Sync is paused, not re-requesting values");
+        set_sync_height(state, min(state.sync_height, *entry.range.start()));
+        return Ok(());
+    }
+
+    if entry
+        .retry
+        .next_delay_with(&state.config.request_retry, &mut state.rng)
+        .is_none()
+    {
+        warn!(
+            %request_id,
+            range = %DisplayRange(&entry.range),
+            retries = entry.retry.count(),
+            "Giving up on re-requesting values, maximum number of retries reached"
+        );
+        // Reset sync_height towards the start of the failed range so it can be requested
+        // again, with a fresh retry budget, on the next request cycle.
+        set_sync_height(state, min(state.sync_height, *entry.range.start()));
+        return Ok(());
+    }
+
     let Some((peer, peer_range)) =
         state.random_peer_with_except(&entry.range, &entry.excluded_peers)
     else {
@@ -937,8 +1496,16 @@ where
     // set, and convergence away from unhealthy peers is left to the peer
     // scorer, which biases `random_peer_with_except`'s selection (timeouts
     // and invalid responses from the same peer will score it down).
-    send_and_track_request_to_peer(&co, state, metrics, peer, peer_range, entry.excluded_peers)
-        .await?;
+    send_and_track_request_to_peer(
+        &co,
+        state,
+        metrics,
+        peer,
+        peer_range,
+        entry.excluded_peers,
+        entry.retry,
+    )
+    .await?;
 
     Ok(())
 }
@@ -1051,6 +1618,7 @@ mod tests {
 
     use crate::effect::Resumable;
     use crate::Config;
+    use crate::NodeMode;
 
     type TestPendingRequests = BTreeMap<OutboundRequestId, PendingRequestEntry<Height>>;
 
@@ -1155,6 +1723,7 @@ mod tests {
                         range: Height::new(start)..=Height::new(end),
                         peer,
                         excluded_peers: BTreeSet::new(),
+                        retry: Retry::new(),
                     },
                 );
             }
@@ -1233,6 +1802,7 @@ mod tests {
                         range: Height::new(start)..=Height::new(end),
                         peer,
                         excluded_peers: BTreeSet::new(),
+                        retry: Retry::new(),
                     },
                 );
             }
@@ -1319,6 +1889,7 @@ mod tests {
                         range: Height::new(start)..=Height::new(end),
                         peer,
                         excluded_peers: BTreeSet::new(),
+                        retry: Retry::new(),
                     },
                 );
             }
@@ -1341,28 +1912,40 @@ mod tests {
     fn test_validate_request_range() {
         let validate = validate_request_range::<TestContext>;
 
+        let history_min_height = Height::new(0);
         let tip_height = Height::new(20);
-        let batch_size = 5;
 
         // Valid range
         let range = Height::new(15)..=Height::new(19);
-        assert!(validate(&range, tip_height, batch_size));
+        assert!(validate(&range, history_min_height, tip_height));
 
         // Start greater than end
         let range = Height::new(18)..=Height::new(17);
-        assert!(!validate(&range, tip_height, batch_size));
+        assert!(!validate(&range, history_min_height, tip_height));
 
         // Start greater than tip height
         let range = Height::new(21)..=Height::new(25);
-        assert!(!validate(&range, tip_height, batch_size));
+        assert!(!validate(&range, history_min_height, tip_height));
+
+        // Start below history min height (values have been pruned)
+        let history_min_height = Height::new(16);
+        let range = Height::new(15)..=Height::new(19);
+        assert!(!validate(&range, history_min_height, tip_height));
+
+        // Start at history min height
+        let range = Height::new(16)..=Height::new(19);
+        assert!(validate(&range, history_min_height, tip_height));
+    }
+
+    #[test]
+    fn test_range_len() {
+        let len = range_len::<TestContext>;
 
-        // Exceeds batch size
-        let range = Height::new(10)..=Height::new(16);
-        assert!(!validate(&range, tip_height, batch_size));
+        assert_eq!(len(&(Height::new(10)..=Height::new(16))), 7);
+        assert_eq!(len(&(Height::new(3)..=Height::new(3))), 1);
 
         // No overflow
-        let range = Height::new(0)..=Height::new(u64::MAX);
-        assert!(!validate(&range, tip_height, batch_size));
+        assert_eq!(len(&(Height::new(0)..=Height::new(u64::MAX))), usize::MAX);
     }
 
     #[test]
@@ -1373,18 +1956,28 @@ mod tests {
 
         // Range within tip height
         let range = Height::new(15)..=Height::new(18);
-        let clamped = clamp(&range, tip_height);
+        let clamped = clamp(&range, tip_height, 10);
         assert_eq!(clamped, range);
 
         // Range exceeding tip height
         let range = Height::new(18)..=Height::new(25);
-        let clamped = clamp(&range, tip_height);
+        let clamped = clamp(&range, tip_height, 10);
         assert_eq!(clamped, Height::new(18)..=tip_height);
 
         // Range starting at tip height
         let range = tip_height..=Height::new(25);
-        let clamped = clamp(&range, tip_height);
+        let clamped = clamp(&range, tip_height, 10);
         assert_eq!(clamped, tip_height..=tip_height);
+
+        // Range larger than the max range size
+        let range = Height::new(5)..=Height::new(18);
+        let clamped = clamp(&range, tip_height, 10);
+        assert_eq!(clamped, Height::new(5)..=Height::new(14));
+
+        // Range exceeding both
+        let range = Height::new(1)..=Height::new(u64::MAX);
+        let clamped = clamp(&range, tip_height, 10);
+        assert_eq!(clamped, Height::new(1)..=Height::new(10));
     }
 
     #[test]
@@ -1493,6 +2086,7 @@ mod tests {
                 range: Height::new(1)..=Height::new(10),
                 peer: peer_a,
                 excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
             },
         );
 
@@ -1501,6 +2095,8 @@ mod tests {
             peer_id: peer_b,
             tip_height: Height::new(20),
             history_min_height: Height::new(1),
+            sync_height: Height::new(21),
+            mode: NodeMode::FullNode,
         });
 
         // Build a malformed response: 10 values starting at height 1
@@ -1550,10 +2146,14 @@ mod tests {
                         Effect::SendValueRequest(_, _, r) => {
                             r.resume_with(Some(OutboundRequestId::new("req-2")))
                         }
-                        Effect::BroadcastStatus(_, r) => r.resume_with(()),
+                        Effect::GetHistoryMinHeight(r) => r.resume_with(Height::new(1)),
+                        Effect::BroadcastStatus(_, _, _, _, r) => r.resume_with(()),
                         Effect::SendValueResponse(_, _, r) => r.resume_with(()),
                         Effect::GetDecidedValues(_, _, r) => r.resume_with(()),
                         Effect::ProcessValueResponse(_, _, _, r) => r.resume_with(()),
+                        Effect::StoreBackfilledValues(_, _, r) => r.resume_with(Some(true)),
+                        Effect::ReportBackfillProgress(_, _, r) => r.resume_with(()),
+                        Effect::Prune(_, r) => r.resume_with(()),
                     })
                 }
             )
@@ -1688,6 +2288,7 @@ mod tests {
                 range: Height::new(110)..=Height::new(120),
                 peer: peer_a,
                 excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
             },
         );
 
@@ -1729,6 +2330,7 @@ mod tests {
                 range: Height::new(100)..=Height::new(110),
                 peer: peer_a,
                 excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
             },
         );
         state.pending_requests.insert(
@@ -1737,6 +2339,7 @@ mod tests {
                 range: Height::new(111)..=Height::new(120),
                 peer: peer_b,
                 excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
             },
         );
         state.peers.insert(
@@ -1745,6 +2348,8 @@ mod tests {
                 peer_id: peer_a,
                 tip_height: Height::new(120),
                 history_min_height: Height::new(1),
+                sync_height: Height::new(121),
+                mode: NodeMode::FullNode,
             },
         );
 
@@ -1801,6 +2406,7 @@ mod tests {
                 range: Height::new(10)..=Height::new(14),
                 peer: PeerId::random(),
                 excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
             },
         );
 
@@ -1834,6 +2440,7 @@ mod tests {
                 range: Height::new(11)..=Height::new(15),
                 peer: peer_a,
                 excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
             },
         );
         state.peers.insert(
@@ -1842,6 +2449,8 @@ mod tests {
                 peer_id: peer_a,
                 tip_height: Height::new(15),
                 history_min_height: Height::new(1),
+                sync_height: Height::new(16),
+                mode: NodeMode::FullNode,
             },
         );
 
@@ -1917,6 +2526,8 @@ mod tests {
                 peer_id: peer_a,
                 tip_height: Height::new(20),
                 history_min_height: Height::new(1),
+                sync_height: Height::new(21),
+                mode: NodeMode::FullNode,
             },
         );
         state.peers.insert(
@@ -1925,6 +2536,8 @@ mod tests {
                 peer_id: peer_b,
                 tip_height: Height::new(20),
                 history_min_height: Height::new(1),
+                sync_height: Height::new(21),
+                mode: NodeMode::FullNode,
             },
         );
 
@@ -1935,6 +2548,7 @@ mod tests {
                 range: Height::new(11)..=Height::new(15),
                 peer: peer_a,
                 excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
             },
         );
 
@@ -2004,6 +2618,79 @@ mod tests {
         assert_eq!(state.sync_height, Height::new(11));
     }
 
+    #[test]
+    fn test_re_request_stops_after_max_retries() {
+        let mut state = make_test_state();
+        state.started = true;
+        state.config.request_retry = state.config.request_retry.with_max_retries(Some(1));
+        let metrics = crate::Metrics::new(std::time::Duration::from_secs(10));
+
+        state.tip_height = Height::new(10);
+        state.sync_height = Height::new(16);
+
+        let peers = [PeerId::random(), PeerId::random(), PeerId::random()];
+
+        for peer_id in peers {
+            state.peers.insert(
+                peer_id,
+                crate::Status {
+                    peer_id,
+                    tip_height: Height::new(20),
+                    history_min_height: Height::new(1),
+                    sync_height: Height::new(21),
+                    mode: NodeMode::FullNode,
+                },
+            );
+        }
+
+        state.pending_requests.insert(
+            OutboundRequestId::new("req1"),
+            PendingRequestEntry {
+                range: Height::new(11)..=Height::new(15),
+                peer: peers[0],
+                excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
+            },
+        );
+
+        let time_out = |state: &mut State<TestContext>| {
+            let (request_id, entry) = state
+                .pending_requests
+                .first_key_value()
+                .map(|(request_id, entry)| (request_id.clone(), entry.clone()))
+                .unwrap();
+
+            drive_input_with_retries(
+                state,
+                &metrics,
+                Input::SyncRequestTimedOut(
+                    request_id,
+                    entry.peer,
+                    crate::Request::ValueRequest(crate::ValueRequest::new(entry.range)),
+                ),
+            )
+            .unwrap()
+        };
+
+        // First timeout — the range is re-requested from another peer.
+        let effects = time_out(&mut state);
+        assert!(effects
+            .iter()
+            .any(|e| matches!(e, Effect::SendValueRequest(..))));
+
+        let entry = state.pending_requests.values().next().unwrap();
+        assert_eq!(entry.retry.count(), 1);
+
+        // Second timeout — a peer is still available, but the retry budget is exhausted.
+        let effects = time_out(&mut state);
+        assert!(!effects
+            .iter()
+            .any(|e| matches!(e, Effect::SendValueRequest(..))));
+
+        assert!(state.pending_requests.is_empty());
+        assert_eq!(state.sync_height, Height::new(11));
+    }
+
     // -- on_value_response: certificate height validation --
 
     /// Helper to create a RawDecidedValue with a given certificate height.
@@ -2038,6 +2725,8 @@ mod tests {
                 peer_id: peer,
                 tip_height: Height::new(range_end + 10),
                 history_min_height: Height::new(1),
+                sync_height: Height::new(range_end + 11),
+                mode: NodeMode::FullNode,
             },
         );
         state.pending_requests.insert(
@@ -2046,6 +2735,7 @@ mod tests {
                 range: Height::new(range_start)..=Height::new(range_end),
                 peer,
                 excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
             },
         );
 
@@ -2233,6 +2923,8 @@ mod tests {
                 peer_id: other_peer,
                 tip_height: Height::new(24),
                 history_min_height: Height::new(1),
+                sync_height: Height::new(25),
+                mode: NodeMode::FullNode,
             },
         );
 
@@ -2467,6 +3159,8 @@ mod tests {
                 peer_id: peer_a,
                 tip_height: Height::new(20),
                 history_min_height: Height::new(1),
+                sync_height: Height::new(21),
+                mode: NodeMode::FullNode,
             },
         );
         state.peers.insert(
@@ -2475,6 +3169,8 @@ mod tests {
                 peer_id: peer_b,
                 tip_height: Height::new(20),
                 history_min_height: Height::new(1),
+                sync_height: Height::new(21),
+                mode: NodeMode::FullNode,
             },
         );
 
@@ -2485,6 +3181,7 @@ mod tests {
                 range: Height::new(11)..=Height::new(15),
                 peer: peer_a,
                 excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
             },
         );
 
@@ -2542,6 +3239,8 @@ mod tests {
                 peer_id: peer,
                 tip_height: Height::new(12),
                 history_min_height: Height::new(1),
+                sync_height: Height::new(13),
+                mode: NodeMode::FullNode,
             },
         );
 
@@ -2552,6 +3251,7 @@ mod tests {
                 range: Height::new(11)..=Height::new(15),
                 peer,
                 excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
             },
         );
 
@@ -2599,6 +3299,8 @@ mod tests {
                 peer_id: peer,
                 tip_height: Height::new(20),
                 history_min_height: Height::new(1),
+                sync_height: Height::new(21),
+                mode: NodeMode::FullNode,
             },
         );
 
@@ -2609,6 +3311,7 @@ mod tests {
                 range: Height::new(11)..=Height::new(15),
                 peer,
                 excluded_peers: BTreeSet::new(),
+                retry: Retry::new(),
             },
         );
 
@@ -2638,4 +3341,554 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_backfill_fetches_values_below_history_min_height() {
+        use crate::{BackfillConfig, Resume};
+
+        let config = Config::default()
+            .with_batch_size(5)
+            .with_backfill(Some(BackfillConfig {
+                target_min_height: 3,
+                request_interval: std::time::Duration::from_secs(1),
+            }));
+
+        let mut state =
+            State::<TestContext>::new(Box::new(rand::rngs::StdRng::seed_from_u64(42)), config);
+        state.started = true;
+        let metrics = crate::Metrics::default();
+
+        let peer = PeerId::random();
+        state.update_status(Status {
+            peer_id: peer,
+            tip_height: Height::new(20),
+            history_min_height: Height::new(1),
+            sync_height: Height::new(21),
+            mode: NodeMode::FullNode,
+        });
+
+        // The node only retains values from height 11 onwards
+        let history_min_height = |effect: &Effect<TestContext>| match effect {
+            Effect::GetHistoryMinHeight(_) => Resume::HistoryMinHeight(Height::new(11)),
+            Effect::SendValueRequest(_, _, _) => {
+                Resume::ValueRequestId(Some(OutboundRequestId::new("backfill-1")))
+            }
+            _ => Resume::default(),
+        };
+
+        let effects = drive_input_with(
+            &mut state,
+            &metrics,
+            Input::BackfillTick,
+            history_min_height,
+        )
+        .unwrap();
+
+        // The batch right below the history min height is requested
+        assert!(effects.iter().any(|e| matches!(
+            e,
+            Effect::SendValueRequest(p, request, _)
+                if *p == peer && request.range == (Height::new(6)..=Height::new(10))
+        )));
+        assert!(state.is_backfill_request(&OutboundRequestId::new("backfill-1")));
+
+        // Forward sync is unaffected
+        assert!(state.pending_requests.is_empty());
+
+        // Only one backfill request is in flight at a time
+        let effects = drive_input_with(
+            &mut state,
+            &metrics,
+            Input::BackfillTick,
+            history_min_height,
+        )
+        .unwrap();
+        assert!(effects.is_empty());
+
+        let response = ValueResponse::new(
+            Height::new(6),
+            (6..=10).map(make_raw_decided_value).collect(),
+        );
+
+        let effects = drive_input_with(
+            &mut state,
+            &metrics,
+            Input::ValueResponse(OutboundRequestId::new("backfill-1"), peer, Some(response)),
+            |effect| match effect {
+                Effect::StoreBackfilledValues(_, _, _) => Resume::BackfillStored(Some(true)),
+                _ => Resume::default(),
+            },
+        )
+        .unwrap();
+
+        assert!(effects
+            .iter()
+            .any(|e| matches!(e, Effect::StoreBackfilledValues(p, values, _) if *p == peer && values.len() == 5)));
+        assert!(effects.iter().any(|e| matches!(
+            e,
+            Effect::ReportBackfillProgress(lowest, target, _)
+                if *lowest == Height::new(6) && *target == Height::new(3)
+        )));
+        assert!(!state.is_backfill_request(&OutboundRequestId::new("backfill-1")));
+
+        // The last batch stops at the target height
+        let effects =
+            drive_input_with(
+                &mut state,
+                &metrics,
+                Input::BackfillTick,
+                |effect| match effect {
+                    Effect::GetHistoryMinHeight(_) => Resume::HistoryMinHeight(Height::new(6)),
+                    Effect::SendValueRequest(_, _, _) => {
+                        Resume::ValueRequestId(Some(OutboundRequestId::new("backfill-2")))
+                    }
+                    _ => Resume::default(),
+                },
+            )
+            .unwrap();
+
+        assert!(effects.iter().any(|e| matches!(
+            e,
+            Effect::SendValueRequest(_, request, _)
+                if request.range == (Height::new(3)..=Height::new(5))
+        )));
+
+        // Once the target height is reached, backfill stops
+        state.backfill.as_mut().unwrap().pending = None;
+        let effects =
+            drive_input_with(
+                &mut state,
+                &metrics,
+                Input::BackfillTick,
+                |effect| match effect {
+                    Effect::GetHistoryMinHeight(_) => Resume::HistoryMinHeight(Height::new(3)),
+                    _ => Resume::default(),
+                },
+            )
+            .unwrap();
+
+        assert!(!effects
+            .iter()
+            .any(|e| matches!(e, Effect::SendValueRequest(..))));
+        assert!(state.backfill.as_ref().unwrap().done);
+    }
+
+    #[test]
+    fn test_backfilled_values_are_requested_again_when_the_application_is_busy() {
+        use crate::{BackfillConfig, Resume};
+
+        let config = Config::default()
+            .with_batch_size(5)
+            .with_backfill(Some(BackfillConfig {
+                target_min_height: 3,
+                request_interval: std::time::Duration::from_secs(1),
+            }));
+
+        let mut state =
+            State::<TestContext>::new(Box::new(rand::rngs::StdRng::seed_from_u64(42)), config);
+        state.started = true;
+        let metrics = crate::Metrics::default();
+
+        let peer = PeerId::random();
+        state.update_status(Status {
+            peer_id: peer,
+            tip_height: Height::new(20),
+            history_min_height: Height::new(1),
+            sync_height: Height::new(21),
+            mode: NodeMode::FullNode,
+        });
+
+        let history_min_height = |effect: &Effect<TestContext>| match effect {
+            Effect::GetHistoryMinHeight(_) => Resume::HistoryMinHeight(Height::new(11)),
+            Effect::SendValueRequest(_, _, _) => {
+                Resume::ValueRequestId(Some(OutboundRequestId::new("backfill-1")))
+            }
+            _ => Resume::default(),
+        };
+
+        drive_input_with(
+            &mut state,
+            &metrics,
+            Input::BackfillTick,
+            history_min_height,
+        )
+        .unwrap();
+
+        let score = state.peer_scorer.get_score(&peer);
+
+        let response = ValueResponse::new(
+            Height::new(6),
+            (6..=10).map(make_raw_decided_value).collect(),
+        );
+
+        let effects = drive_input_with(
+            &mut state,
+            &metrics,
+            Input::ValueResponse(OutboundRequestId::new("backfill-1"), peer, Some(response)),
+            |effect| match effect {
+                Effect::StoreBackfilledValues(_, _, _) => Resume::BackfillStored(None),
+                _ => Resume::default(),
+            },
+        )
+        .unwrap();
+
+        // No progress is reported, and the peer is not penalized for it
+        assert!(!effects
+            .iter()
+            .any(|e| matches!(e, Effect::ReportBackfillProgress(..))));
+        assert_eq!(state.peer_scorer.get_score(&peer), score);
+
+        // The same batch is requested again on the next tick
+        let effects = drive_input_with(
+            &mut state,
+            &metrics,
+            Input::BackfillTick,
+            history_min_height,
+        )
+        .unwrap();
+
+        assert!(effects.iter().any(|e| matches!(
+            e,
+            Effect::SendValueRequest(p, request, _)
+                if *p == peer && request.range == (Height::new(6)..=Height::new(10))
+        )));
+    }
+
+    #[test]
+    fn test_future_height_observed_requests_values_from_relaying_peers() {
+        let mut state = make_test_state();
+        state.started = true;
+        let metrics = crate::Metrics::new(std::time::Duration::from_secs(10));
+
+        state.consensus_height = Height::new(11);
+        state.tip_height = Height::new(10);
+        state.sync_height = Height::new(11);
+
+        let peer_a = PeerId::random();
+        let peer_b = PeerId::random();
+
+        // Neither peer has reported a status covering the next height yet.
+        for peer in [peer_a, peer_b] {
+            state.peers.insert(
+                peer,
+                crate::Status {
+                    peer_id: peer,
+                    tip_height: Height::new(10),
+                    history_min_height: Height::new(1),
+                    sync_height: Height::new(11),
+                    mode: NodeMode::FullNode,
+                },
+            );
+        }
+
+        let effects = drive_input_with_retries(
+            &mut state,
+            &metrics,
+            Input::FutureHeightObserved(Height::new(14), vec![peer_a]),
+        )
+        .unwrap();
+
+        assert_eq!(state.peers[&peer_a].tip_height, Height::new(13));
+        assert_eq!(state.peers[&peer_b].tip_height, Height::new(10));
+
+        assert!(effects.iter().any(|e| matches!(
+            e,
+            Effect::SendValueRequest(peer, request, _)
+                if *peer == peer_a && *request.range.start() == Height::new(11)
+        )));
+
+        // The heights below the observed height are already requested
+        let effects = drive_input_with_retries(
+            &mut state,
+            &metrics,
+            Input::FutureHeightObserved(Height::new(12), vec![peer_b]),
+        )
+        .unwrap();
+
+        assert!(effects.is_empty());
+        assert_eq!(state.peers[&peer_b].tip_height, Height::new(10));
+    }
+
+    #[test]
+    fn test_announcement_requests_value_from_announcing_peer() {
+        let mut state = make_test_state();
+        state.started = true;
+        let metrics = crate::Metrics::new(std::time::Duration::from_secs(10));
+
+        state.consensus_height = Height::new(11);
+        state.tip_height = Height::new(10);
+        state.sync_height = Height::new(11);
+
+        let peer = PeerId::random();
+        let unknown_peer = PeerId::random();
+
+        state.peers.insert(
+            peer,
+            crate::Status {
+                peer_id: peer,
+                tip_height: Height::new(10),
+                history_min_height: Height::new(1),
+                sync_height: Height::new(11),
+                mode: NodeMode::FullNode,
+            },
+        );
+
+        let announcement = |peer_id, height| {
+            Input::Announcement(Announcement {
+                peer_id,
+                height: Height::new(height),
+                value_id: ValueId::new(height),
+                certificate_hash: [0; 32],
+            })
+        };
+
+        // The peer did not send its status, so it is not known to retain the value
+        let effects =
+            drive_input_with_retries(&mut state, &metrics, announcement(unknown_peer, 11)).unwrap();
+
+        assert!(effects.is_empty());
+        assert!(!state.peers.contains_key(&unknown_peer));
+
+        let effects =
+            drive_input_with_retries(&mut state, &metrics, announcement(peer, 11)).unwrap();
+
+        assert_eq!(state.peers[&peer].tip_height, Height::new(11));
+
+        assert!(effects.iter().any(|e| matches!(
+            e,
+            Effect::SendValueRequest(p, request, _)
+                if *p == peer && *request.range.start() == Height::new(11)
+        )));
+
+        // The announced value is already requested
+        let effects =
+            drive_input_with_retries(&mut state, &metrics, announcement(peer, 11)).unwrap();
+
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn test_value_requests_beyond_limits_are_clamped_or_rejected() {
+        let mut state = make_test_state();
+        let metrics = crate::Metrics::new(std::time::Duration::from_secs(10));
+
+        state.tip_height = Height::new(10);
+        state.request_guard = crate::RequestGuard::new(
+            crate::RequestLimits {
+                max_range_size: 5,
+                max_concurrent_requests: 1,
+                bandwidth_budget: None,
+            },
+            std::time::Duration::from_secs(10),
+        );
+
+        let peer = PeerId::random();
+        let request = |id: &str, end: u64| {
+            Input::ValueRequest(
+                InboundRequestId::new(id),
+                peer,
+                ValueRequest::new(Height::new(1)..=Height::new(end)),
+            )
+        };
+
+        let is_rejected = |effects: &[Effect<TestContext>]| {
+            effects.iter().any(|e| {
+                matches!(e, Effect::SendValueResponse(_, response, _) if response.values.is_empty())
+            })
+        };
+        let is_served = |effects: &[Effect<TestContext>]| {
+            effects
+                .iter()
+                .any(|e| matches!(e, Effect::GetDecidedValues(..)))
+        };
+
+        // Too many values at once, only the first ones are served
+        let effects = drive_input(&mut state, &metrics, request("a", 8)).unwrap();
+        assert!(effects.iter().any(|e| matches!(
+            e,
+            Effect::GetDecidedValues(_, range, _) if *range == (Height::new(1)..=Height::new(5))
+        )));
+
+        drive_input(
+            &mut state,
+            &metrics,
+            Input::GotDecidedValues(
+                InboundRequestId::new("a"),
+                Height::new(1)..=Height::new(5),
+                vec![],
+            ),
+        )
+        .unwrap();
+
+        let effects = drive_input(&mut state, &metrics, request("b", 5)).unwrap();
+        assert!(is_served(&effects));
+
+        // Another request while the previous one is being served
+        let effects = drive_input(&mut state, &metrics, request("c", 5)).unwrap();
+        assert!(is_rejected(&effects));
+
+        // Once the previous request is served, the peer can send another one
+        drive_input(
+            &mut state,
+            &metrics,
+            Input::GotDecidedValues(
+                InboundRequestId::new("b"),
+                Height::new(1)..=Height::new(5),
+                vec![],
+            ),
+        )
+        .unwrap();
+
+        let effects = drive_input(&mut state, &metrics, request("d", 5)).unwrap();
+        assert!(is_served(&effects));
+    }
+
+    #[test]
+    fn test_pause_stops_requests_until_every_reason_is_lifted() {
+        let mut state = make_test_state();
+        state.started = true;
+        let metrics = crate::Metrics::new(std::time::Duration::from_secs(10));
+
+        state.consensus_height = Height::new(11);
+        state.tip_height = Height::new(10);
+        state.sync_height = Height::new(11);
+
+        let has_value_request = |effects: &[Effect<TestContext>]| {
+            effects
+                .iter()
+                .any(|e| matches!(e, Effect::SendValueRequest(..)))
+        };
+
+        for reason in [PauseReason::Application, PauseReason::Backpressure] {
+            drive_input(&mut state, &metrics, Input::Pause(reason)).unwrap();
+        }
+        assert_eq!(metrics.paused.get(), 1);
+
+        // A peer ahead of us does not trigger any request while paused
+        let peer = PeerId::random();
+        let status = crate::Status {
+            peer_id: peer,
+            tip_height: Height::new(20),
+            history_min_height: Height::new(1),
+            sync_height: Height::new(21),
+            mode: NodeMode::FullNode,
+        };
+        let effects =
+            drive_input_with_retries(&mut state, &metrics, Input::Status(status)).unwrap();
+        assert!(!has_value_request(&effects));
+        assert!(state.pending_requests.is_empty());
+
+        // Requests from peers are still served
+        let request = ValueRequest::new(Height::new(5)..=Height::new(6));
+        let effects = drive_input(
+            &mut state,
+            &metrics,
+            Input::ValueRequest(InboundRequestId::new("req"), peer, request),
+        )
+        .unwrap();
+        assert!(effects
+            .iter()
+            .any(|e| matches!(e, Effect::GetDecidedValues(..))));
+
+        // Sync stays paused until the last reason is lifted
+        let effects = drive_input_with_retries(
+            &mut state,
+            &metrics,
+            Input::Resume(PauseReason::Application),
+        )
+        .unwrap();
+        assert!(!has_value_request(&effects));
+        assert!(state.is_paused());
+
+        let effects = drive_input_with_retries(
+            &mut state,
+            &metrics,
+            Input::Resume(PauseReason::Backpressure),
+        )
+        .unwrap();
+        assert!(has_value_request(&effects));
+        assert!(!state.is_paused());
+        assert_eq!(metrics.paused.get(), 0);
+    }
+
+    // -- on_prune_tick --
+
+    fn has_prune(effects: &[crate::Effect<TestContext>], height: u64) -> bool {
+        effects
+            .iter()
+            .any(|e| matches!(e, crate::Effect::Prune(h, _) if *h == Height::new(height)))
+    }
+
+    #[test]
+    fn test_prune_tick_retains_values_needed_by_peers() {
+        let mut state = make_test_state();
+        let metrics = crate::Metrics::new(std::time::Duration::from_secs(10));
+
+        state.config.retention = Some(crate::RetentionConfig {
+            retain_values: 10,
+            max_retain_values: 50,
+            interval: std::time::Duration::from_secs(60),
+        });
+        state.tip_height = Height::new(100);
+        state.history_min_height = Height::new(1);
+
+        // A peer behind holds back pruning
+        let peer = PeerId::random();
+        let mut status = crate::Status {
+            peer_id: peer,
+            tip_height: Height::new(70),
+            history_min_height: Height::new(1),
+            sync_height: Height::new(71),
+            mode: NodeMode::FullNode,
+        };
+        state.peers.insert(peer, status.clone());
+
+        let effects = drive_input(&mut state, &metrics, Input::PruneTick).unwrap();
+        assert!(has_prune(&effects, 71));
+        assert_eq!(state.retain_height, Height::new(71));
+
+        // The retain height does not move until the tip does
+        let effects = drive_input(&mut state, &metrics, Input::PruneTick).unwrap();
+        assert!(effects.is_empty());
+
+        // No more than the maximum number of values are retained for the peer
+        state.tip_height = Height::new(130);
+        let effects = drive_input(&mut state, &metrics, Input::PruneTick).unwrap();
+        assert!(has_prune(&effects, 81));
+
+        // Once the peer caught up, only the latest values are retained
+        status.tip_height = Height::new(130);
+        state.peers.insert(peer, status);
+
+        let effects = drive_input(&mut state, &metrics, Input::PruneTick).unwrap();
+        assert!(has_prune(&effects, 121));
+
+        // The retain height never moves down, eg. when a peer far behind connects
+        let lagging = PeerId::random();
+        state.peers.insert(
+            lagging,
+            crate::Status {
+                peer_id: lagging,
+                tip_height: Height::new(10),
+                history_min_height: Height::new(1),
+                sync_height: Height::new(11),
+                mode: NodeMode::FullNode,
+            },
+        );
+
+        let effects = drive_input(&mut state, &metrics, Input::PruneTick).unwrap();
+        assert!(effects.is_empty());
+        assert_eq!(state.retain_height, Height::new(121));
+    }
+
+    #[test]
+    fn test_prune_tick_is_ignored_when_retention_is_disabled() {
+        let mut state = make_test_state();
+        let metrics = crate::Metrics::new(std::time::Duration::from_secs(10));
+
+        state.tip_height = Height::new(100);
+
+        let effects = drive_input(&mut state, &metrics, Input::PruneTick).unwrap();
+        assert!(effects.is_empty());
+    }
 }
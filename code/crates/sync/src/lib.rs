@@ -5,19 +5,31 @@ mod metrics;
 pub use metrics::Metrics;
 
 mod state;
-pub use state::{PendingRequestEntry, State};
+pub use state::{Backfill, PendingRequestEntry, State};
+
+pub mod cache;
+pub use cache::DecidedValuesCache;
 
 mod types;
 pub use types::*;
 
 pub mod scoring;
 
+pub mod selection;
+pub use selection::{PeerStakes, Selection};
+
+pub mod compression;
+pub use compression::CompressionConfig;
+
+pub mod limits;
+pub use limits::{Rejection, RequestGuard};
+
 mod macros;
 mod rpc;
 mod ser;
 
 pub mod config;
-pub use config::Config;
+pub use config::{BackfillConfig, Config, RequestLimits, RetentionConfig};
 
 #[doc(hidden)]
 pub mod handle;
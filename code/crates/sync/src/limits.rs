@@ -0,0 +1,283 @@
+//! Guard against peers making this node send them large amounts of data,
+//! eg. by requesting large ranges of values repeatedly.
+//!
+//! Each request from a peer is admitted only if it is within the [`RequestLimits`]:
+//! the number of requests from the same peer being served at once, and the number of bytes
+//! of values sent to the peer per second. Requests for more values than allowed at once
+//! are not rejected but only served up to [`RequestGuard::max_range_size`] values.
+//!
+//! The guard never reads the clock itself, the current time is given by the caller.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use displaydoc::Display;
+use malachitebft_peer::PeerId;
+
+use crate::{InboundRequestId, RequestLimits};
+
+/// Reason for rejecting a request from a peer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Display)]
+pub enum Rejection {
+    /// Already serving {max} requests from this peer
+    TooManyConcurrentRequests { max: usize },
+
+    /// Bandwidth budget of the peer is exhausted
+    BandwidthExceeded,
+}
+
+/// Bytes a peer may still be sent, replenished continuously at the rate of the bandwidth budget,
+/// up to one second worth of budget.
+///
+/// The balance goes negative when a response is larger than what was left,
+/// in which case the requests of the peer are rejected until it is replenished.
+#[derive(Copy, Clone, Debug)]
+struct Allowance {
+    balance: f64,
+    updated_at: Instant,
+}
+
+impl Allowance {
+    fn replenish(&mut self, budget: usize, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.balance = (self.balance + elapsed * budget as f64).min(budget as f64);
+        self.updated_at = now;
+    }
+
+    /// Whether the allowance is back to the full budget at the given time,
+    /// in which case it is no different from the one of a peer never sent any value.
+    fn is_full(&self, budget: usize, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.balance + elapsed * budget as f64 >= budget as f64
+    }
+}
+
+/// Tracks the requests being served to each peer and the bytes sent to them.
+#[derive(Debug)]
+pub struct RequestGuard {
+    limits: RequestLimits,
+
+    /// Requests being served are forgotten after this long,
+    /// eg. if the application never provided the values.
+    request_timeout: Duration,
+
+    /// Requests being served, with the peer which sent them and when they were admitted.
+    in_flight: HashMap<InboundRequestId, (PeerId, Instant)>,
+
+    /// Bandwidth allowance of each peer which was sent values, kept after the peer disconnects
+    /// until it is back to the full budget, so that a peer cannot reset it by reconnecting.
+    allowances: HashMap<PeerId, Allowance>,
+}
+
+impl RequestGuard {
+    pub fn new(limits: RequestLimits, request_timeout: Duration) -> Self {
+        Self {
+            limits,
+            request_timeout,
+            in_flight: HashMap::new(),
+            allowances: HashMap::new(),
+        }
+    }
+
+    /// Maximum number of values served for a single request.
+    pub fn max_range_size(&self) -> usize {
+        self.limits.max_range_size
+    }
+
+    /// Admit the request from the given peer if it is within the limits,
+    /// in which case it counts towards the concurrent requests of the peer until [`Self::complete`].
+    ///
+    /// The allowances which are back to the full budget are dropped.
+    pub fn admit(
+        &mut self,
+        request_id: InboundRequestId,
+        peer_id: PeerId,
+        now: Instant,
+    ) -> Result<(), Rejection> {
+        let request_timeout = self.request_timeout;
+        self.in_flight.retain(|_, (_, admitted_at)| {
+            now.saturating_duration_since(*admitted_at) < request_timeout
+        });
+
+        let concurrent = self
+            .in_flight
+            .values()
+            .filter(|(peer, _)| *peer == peer_id)
+            .count();
+
+        if concurrent >= self.limits.max_concurrent_requests {
+            return Err(Rejection::TooManyConcurrentRequests {
+                max: self.limits.max_concurrent_requests,
+            });
+        }
+
+        if let Some(budget) = self.limits.bandwidth_budget {
+            self.allowances
+                .retain(|_, allowance| !allowance.is_full(budget, now));
+
+            if let Some(allowance) = self.allowances.get_mut(&peer_id) {
+                allowance.replenish(budget, now);
+
+                if allowance.balance <= 0.0 {
+                    return Err(Rejection::BandwidthExceeded);
+                }
+            }
+        }
+
+        self.in_flight.insert(request_id, (peer_id, now));
+
+        Ok(())
+    }
+
+    /// The response to the given request, of `bytes` bytes, is being sent.
+    ///
+    /// Returns the peer which sent the request, if it was admitted and has not timed out.
+    pub fn complete(
+        &mut self,
+        request_id: &InboundRequestId,
+        bytes: usize,
+        now: Instant,
+    ) -> Option<PeerId> {
+        let (peer_id, _) = self.in_flight.remove(request_id)?;
+
+        if let Some(budget) = self.limits.bandwidth_budget {
+            let allowance = self.allowances.entry(peer_id).or_insert(Allowance {
+                balance: budget as f64,
+                updated_at: now,
+            });
+
+            allowance.replenish(budget, now);
+            allowance.balance -= bytes as f64;
+        }
+
+        Some(peer_id)
+    }
+
+    /// Forget the requests being served to the given peer, which disconnected.
+    ///
+    /// Its bandwidth allowance is kept until it is replenished, see [`Self::admit`].
+    pub fn forget_peer(&mut self, peer_id: &PeerId) {
+        self.in_flight.retain(|_, (peer, _)| peer != peer_id);
+    }
+
+    pub fn peer_entries(&self) -> usize {
+        self.in_flight.len() + self.allowances.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn limits(bandwidth_budget: Option<usize>) -> RequestLimits {
+        RequestLimits {
+            max_range_size: 5,
+            max_concurrent_requests: 2,
+            bandwidth_budget,
+        }
+    }
+
+    fn request(id: u64) -> InboundRequestId {
+        InboundRequestId::new(id)
+    }
+
+    #[test]
+    fn limits_concurrent_requests_per_peer() {
+        let mut guard = RequestGuard::new(limits(None), TIMEOUT);
+        let (peer, other, now) = (PeerId::random(), PeerId::random(), Instant::now());
+
+        assert_eq!(guard.admit(request(1), peer, now), Ok(()));
+        assert_eq!(guard.admit(request(2), peer, now), Ok(()));
+        assert_eq!(
+            guard.admit(request(3), peer, now),
+            Err(Rejection::TooManyConcurrentRequests { max: 2 })
+        );
+
+        // Other peers are not affected
+        assert_eq!(guard.admit(request(4), other, now), Ok(()));
+
+        // Completing a request makes room for another one
+        assert_eq!(guard.complete(&request(1), 0, now), Some(peer));
+        assert_eq!(guard.admit(request(5), peer, now), Ok(()));
+
+        // Requests which were never completed eventually expire
+        let later = now + TIMEOUT;
+        assert_eq!(guard.admit(request(6), peer, later), Ok(()));
+        assert_eq!(guard.complete(&request(2), 0, later), None);
+    }
+
+    #[test]
+    fn limits_bandwidth_per_peer() {
+        let mut guard = RequestGuard::new(limits(Some(1000)), TIMEOUT);
+        let (peer, other, now) = (PeerId::random(), PeerId::random(), Instant::now());
+
+        // Sending more than the budget exhausts it
+        assert_eq!(guard.admit(request(1), peer, now), Ok(()));
+        guard.complete(&request(1), 1500, now);
+        assert_eq!(
+            guard.admit(request(2), peer, now),
+            Err(Rejection::BandwidthExceeded)
+        );
+
+        // Other peers are not affected
+        assert_eq!(guard.admit(request(3), other, now), Ok(()));
+
+        // The balance of -500 bytes is back above zero after more than half a second
+        let later = now + Duration::from_millis(400);
+        assert_eq!(
+            guard.admit(request(4), peer, later),
+            Err(Rejection::BandwidthExceeded)
+        );
+
+        let later = now + Duration::from_millis(600);
+        assert_eq!(guard.admit(request(5), peer, later), Ok(()));
+    }
+
+    #[test]
+    fn forgets_peers() {
+        let mut guard = RequestGuard::new(limits(Some(1000)), TIMEOUT);
+        let (peer, now) = (PeerId::random(), Instant::now());
+
+        guard.admit(request(1), peer, now).unwrap();
+        guard.admit(request(2), peer, now).unwrap();
+        guard.complete(&request(1), 1500, now);
+        assert_eq!(guard.peer_entries(), 2);
+
+        // The requests being served are forgotten, but not the allowance
+        guard.forget_peer(&peer);
+        assert_eq!(guard.peer_entries(), 1);
+
+        // Which is dropped once it is replenished
+        let later = now + Duration::from_millis(1500);
+        assert_eq!(guard.admit(request(3), PeerId::random(), later), Ok(()));
+        assert_eq!(guard.peer_entries(), 1);
+    }
+
+    #[test]
+    fn reconnecting_does_not_restore_the_allowance() {
+        let mut guard = RequestGuard::new(limits(Some(1000)), TIMEOUT);
+        let (peer, now) = (PeerId::random(), Instant::now());
+
+        guard.admit(request(1), peer, now).unwrap();
+        guard.complete(&request(1), 1500, now);
+
+        guard.forget_peer(&peer);
+        assert_eq!(
+            guard.admit(request(2), peer, now),
+            Err(Rejection::BandwidthExceeded)
+        );
+
+        // The allowance is replenished over time only
+        let later = now + Duration::from_millis(400);
+        guard.forget_peer(&peer);
+        assert_eq!(
+            guard.admit(request(3), peer, later),
+            Err(Rejection::BandwidthExceeded)
+        );
+
+        let later = now + Duration::from_millis(600);
+        assert_eq!(guard.admit(request(4), peer, later), Ok(()));
+    }
+}
@@ -1,4 +1,5 @@
 use std::ops::Deref;
+use std::sync::atomic::AtomicU64;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -8,6 +9,8 @@ use malachitebft_metrics::prometheus::metrics::gauge::Gauge;
 use malachitebft_metrics::prometheus::metrics::histogram::{exponential_buckets, Histogram};
 use malachitebft_metrics::SharedRegistry;
 
+use crate::limits::Rejection;
+
 #[derive(Clone, Debug)]
 pub struct Metrics(Arc<Inner>);
 
@@ -28,6 +31,9 @@ pub struct Inner {
     value_client_latency: Histogram,
     value_server_latency: Histogram,
     value_request_timeouts: Counter,
+    value_requests_range_clamped: Counter,
+    value_requests_rejected_too_many_concurrent: Counter,
+    value_requests_rejected_bandwidth_exceeded: Counter,
     status_interarrival: Histogram,
     status_interarrival_normalized: Histogram, // Independent of number of peers and status update interval
     status_total: Counter,
@@ -44,6 +50,26 @@ pub struct Inner {
 
     /// Number of inputs in the sync input queue across all heights
     pub sync_queue_size: Gauge,
+
+    /// Lowest height backfilled so far
+    pub backfill_height: Gauge,
+
+    /// Number of decided values backfilled
+    pub backfill_values: Counter,
+
+    /// Whether sync is paused (1) or not (0)
+    pub paused: Gauge,
+
+    /// Total time spent paused, in seconds
+    pub paused_seconds: Counter<f64, AtomicU64>,
+
+    instant_paused: Arc<Mutex<Option<Instant>>>,
+
+    /// Number of value requests served from the decided values cache
+    pub decided_values_cache_hits: Counter,
+
+    /// Number of value requests forwarded to the application on a cache miss
+    pub decided_values_cache_misses: Counter,
 }
 
 impl Inner {
@@ -57,6 +83,9 @@ impl Inner {
             value_client_latency: Histogram::new(exponential_buckets(0.1, 2.0, 20)),
             value_server_latency: Histogram::new(exponential_buckets(0.1, 2.0, 20)),
             value_request_timeouts: Counter::default(),
+            value_requests_range_clamped: Counter::default(),
+            value_requests_rejected_too_many_concurrent: Counter::default(),
+            value_requests_rejected_bandwidth_exceeded: Counter::default(),
             status_interarrival: Histogram::new(exponential_buckets(0.05 * t.max(1e-6), 1.15, 40)),
             status_interarrival_normalized: Histogram::new(exponential_buckets(0.05, 1.15, 40)),
             status_total: Counter::default(),
@@ -67,6 +96,13 @@ impl Inner {
             scoring: crate::scoring::metrics::Metrics::new(),
             sync_queue_heights: Gauge::default(),
             sync_queue_size: Gauge::default(),
+            backfill_height: Gauge::default(),
+            backfill_values: Counter::default(),
+            paused: Gauge::default(),
+            paused_seconds: Counter::default(),
+            instant_paused: Arc::new(Mutex::new(None)),
+            decided_values_cache_hits: Counter::default(),
+            decided_values_cache_misses: Counter::default(),
         }
     }
 }
@@ -123,6 +159,24 @@ impl Metrics {
                 metrics.value_request_timeouts.clone(),
             );
 
+            registry.register(
+                "value_requests_range_clamped",
+                "Number of ValueSync requests for too many values at once, which were only served in part",
+                metrics.value_requests_range_clamped.clone(),
+            );
+
+            registry.register(
+                "value_requests_rejected_too_many_concurrent",
+                "Number of ValueSync requests rejected because too many requests from the same peer were being served",
+                metrics.value_requests_rejected_too_many_concurrent.clone(),
+            );
+
+            registry.register(
+                "value_requests_rejected_bandwidth_exceeded",
+                "Number of ValueSync requests rejected because the peer exhausted its bandwidth budget",
+                metrics.value_requests_rejected_bandwidth_exceeded.clone(),
+            );
+
             metrics.scoring.register(registry);
 
             registry.register(
@@ -137,6 +191,42 @@ impl Metrics {
                 metrics.sync_queue_size.clone(),
             );
 
+            registry.register(
+                "backfill_height",
+                "Lowest height backfilled so far",
+                metrics.backfill_height.clone(),
+            );
+
+            registry.register(
+                "backfill_values",
+                "Number of decided values backfilled",
+                metrics.backfill_values.clone(),
+            );
+
+            registry.register(
+                "paused",
+                "Whether sync is paused (1) or not (0)",
+                metrics.paused.clone(),
+            );
+
+            registry.register(
+                "paused_seconds",
+                "Total time spent with sync paused, in seconds",
+                metrics.paused_seconds.clone(),
+            );
+
+            registry.register(
+                "decided_values_cache_hits",
+                "Number of value requests served from the decided values cache",
+                metrics.decided_values_cache_hits.clone(),
+            );
+
+            registry.register(
+                "decided_values_cache_misses",
+                "Number of value requests forwarded to the application on a cache miss",
+                metrics.decided_values_cache_misses.clone(),
+            );
+
             registry.register(
                 "status_interarrival",
                 "Status updates interarrival histogram (any peer)",
@@ -194,6 +284,19 @@ impl Metrics {
         self.instant_request_sent.remove(&height);
     }
 
+    pub fn value_request_range_clamped(&self) {
+        self.value_requests_range_clamped.inc();
+    }
+
+    pub fn value_request_rejected(&self, rejection: &Rejection) {
+        match rejection {
+            Rejection::TooManyConcurrentRequests { .. } => {
+                self.value_requests_rejected_too_many_concurrent.inc()
+            }
+            Rejection::BandwidthExceeded => self.value_requests_rejected_bandwidth_exceeded.inc(),
+        };
+    }
+
     pub fn status_received(&self, n_peers: u64) {
         self.status_total.inc();
         let now = Instant::now();
@@ -219,6 +322,24 @@ impl Metrics {
         self.sync_queue_heights.set(heights as _);
         self.sync_queue_size.set(size as _);
     }
+
+    pub fn values_backfilled(&self, lowest_height: u64, count: usize) {
+        self.backfill_height.set(lowest_height as _);
+        self.backfill_values.inc_by(count as u64);
+    }
+
+    pub fn sync_paused(&self) {
+        self.paused.set(1);
+        *self.instant_paused.lock().unwrap() = Some(Instant::now());
+    }
+
+    pub fn sync_resumed(&self) {
+        self.paused.set(0);
+
+        if let Some(instant) = self.instant_paused.lock().unwrap().take() {
+            self.paused_seconds.inc_by(instant.elapsed().as_secs_f64());
+        }
+    }
 }
 
 impl Default for Metrics {
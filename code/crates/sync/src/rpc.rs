@@ -3,17 +3,19 @@ use bytes::Bytes;
 use libp2p::futures::{io, AsyncRead, AsyncWrite};
 use libp2p::StreamProtocol;
 
+use crate::compression::{self, CompressionMetrics};
 use crate::types::{RawRequest, RawResponse};
 use crate::Config;
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct Codec {
     config: Config,
+    metrics: CompressionMetrics,
 }
 
 impl Codec {
-    pub fn new(config: Config) -> Self {
-        Self { config }
+    pub fn new(config: Config, metrics: CompressionMetrics) -> Self {
+        Self { config, metrics }
     }
 }
 
@@ -35,15 +37,19 @@ impl libp2p::request_response::Codec for Codec {
 
     async fn read_response<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         io: &mut T,
     ) -> io::Result<Self::Response>
     where
         T: AsyncRead + Unpin + Send,
     {
-        read_length_prefixed(io, self.config.max_response_size)
-            .await
-            .map(RawResponse)
+        let data = read_length_prefixed(io, self.config.max_response_size).await?;
+
+        if compression::is_compressed_protocol(protocol.as_ref()) {
+            compression::decode(data, self.config.max_response_size, &self.metrics).map(RawResponse)
+        } else {
+            Ok(RawResponse(data))
+        }
     }
 
     async fn write_request<T>(
@@ -60,14 +66,20 @@ impl libp2p::request_response::Codec for Codec {
 
     async fn write_response<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         io: &mut T,
         res: Self::Response,
     ) -> io::Result<()>
     where
         T: AsyncWrite + Unpin + Send,
     {
-        write_length_prefixed(io, res.0, self.config.max_response_size).await
+        let data = if compression::is_compressed_protocol(protocol.as_ref()) {
+            compression::encode(res.0, self.config.compression, &self.metrics)
+        } else {
+            res.0
+        };
+
+        write_length_prefixed(io, data, self.config.max_response_size).await
     }
 }
 
@@ -7,7 +7,7 @@ use rand::distributions::Distribution;
 use rand::Rng;
 use tracing::debug;
 
-use malachitebft_peer::PeerId;
+use malachitebft_peer::{PeerExit, PeerId, PeerState};
 
 pub mod ema;
 pub mod metrics;
@@ -186,6 +186,16 @@ impl PeerScorer {
     }
 }
 
+impl PeerState for PeerScorer {
+    fn forget_peer(&mut self, peer_id: &PeerId, _exit: PeerExit) {
+        self.scores.remove(peer_id);
+    }
+
+    fn peer_entries(&self) -> usize {
+        self.scores.len()
+    }
+}
+
 impl Default for PeerScorer {
     fn default() -> Self {
         Self::new(ema::ExponentialMovingAverage::default())
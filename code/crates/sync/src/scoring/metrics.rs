@@ -63,4 +63,9 @@ impl Metrics {
             .get_or_create(&PeerLabel::new(peer_id))
             .observe(score);
     }
+
+    /// Remove the scores of a peer which is gone, so that the metrics do not grow as peers come and go.
+    pub fn forget_peer(&self, peer_id: PeerId) {
+        self.scores.remove(&PeerLabel::new(peer_id));
+    }
 }
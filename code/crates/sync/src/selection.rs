@@ -0,0 +1,248 @@
+//! Strategies for selecting the peer to request values from,
+//! among the peers which can provide the requested range of values.
+
+use std::collections::BTreeMap;
+
+use rand::distributions::weighted::WeightedIndex;
+use rand::distributions::Distribution;
+use rand::Rng;
+
+use malachitebft_core_types::VotingPower;
+use malachitebft_peer::PeerId;
+
+use crate::scoring::PeerScorer;
+
+/// Voting power of the peers which are known validators, ie. which proved their identity
+/// and are in the validator set of the current height.
+pub type PeerStakes = BTreeMap<PeerId, VotingPower>;
+
+/// Strategy for weighting the peers to select from
+pub trait SelectionStrategy: Send + Sync {
+    /// Weight of the given peer, which is selected with a probability proportional to it.
+    ///
+    /// ## Important
+    /// The weight MUST be non-negative.
+    fn weight(&self, peer_id: &PeerId, scorer: &PeerScorer, stakes: &PeerStakes) -> f64;
+}
+
+/// Select every peer with the same probability.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Uniform;
+
+impl SelectionStrategy for Uniform {
+    fn weight(&self, _peer_id: &PeerId, _scorer: &PeerScorer, _stakes: &PeerStakes) -> f64 {
+        1.0
+    }
+}
+
+/// Select peers with a probability proportional to their score, see [`PeerScorer`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ScoreWeighted;
+
+impl SelectionStrategy for ScoreWeighted {
+    fn weight(&self, peer_id: &PeerId, scorer: &PeerScorer, _stakes: &PeerStakes) -> f64 {
+        scorer.get_score(peer_id).max(0.0)
+    }
+}
+
+/// Select peers which are known validators with a probability proportional to their voting power.
+///
+/// Peers which are not known validators are only selected if none of the candidates is.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct StakeWeighted;
+
+impl SelectionStrategy for StakeWeighted {
+    fn weight(&self, peer_id: &PeerId, _scorer: &PeerScorer, stakes: &PeerStakes) -> f64 {
+        stakes.get(peer_id).map_or(0.0, |stake| *stake as f64)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Selection {
+    /// Every peer is selected with the same probability
+    Uniform,
+    /// Peers are selected proportionally to their score
+    #[default]
+    ScoreWeighted,
+    /// Known validators are selected proportionally to their voting power
+    StakeWeighted,
+}
+
+impl Selection {
+    pub fn strategy(&self) -> Box<dyn SelectionStrategy> {
+        match self {
+            Self::Uniform => Box::new(Uniform),
+            Self::ScoreWeighted => Box::new(ScoreWeighted),
+            Self::StakeWeighted => Box::new(StakeWeighted),
+        }
+    }
+}
+
+/// Select a peer at random, with a probability proportional to its weight under the given strategy.
+///
+/// If all peers have a zero weight, eg. when none of them is a known validator
+/// under the stake-weighted strategy, a peer is selected uniformly at random.
+pub fn select_peer<R: Rng + ?Sized>(
+    strategy: &dyn SelectionStrategy,
+    peers: &[PeerId],
+    scorer: &PeerScorer,
+    stakes: &PeerStakes,
+    rng: &mut R,
+) -> Option<PeerId> {
+    if peers.is_empty() {
+        return None;
+    }
+
+    let weights = peers
+        .iter()
+        .map(|peer_id| strategy.weight(peer_id, scorer, stakes));
+
+    let index = match WeightedIndex::new(weights) {
+        Ok(distr) => distr.sample(rng),
+        Err(_) => rng.gen_range(0..peers.len()),
+    };
+
+    Some(peers[index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+
+    use crate::scoring::SyncResult;
+
+    const SAMPLES: usize = 20_000;
+
+    /// Number of times each peer is selected over `SAMPLES` selections.
+    fn selections(
+        selection: Selection,
+        peers: &[PeerId],
+        scorer: &PeerScorer,
+        stakes: &PeerStakes,
+    ) -> HashMap<PeerId, usize> {
+        let strategy = selection.strategy();
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut counts = HashMap::new();
+
+        for _ in 0..SAMPLES {
+            let peer = select_peer(&*strategy, peers, scorer, stakes, &mut rng).unwrap();
+            *counts.entry(peer).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    fn frequency(counts: &HashMap<PeerId, usize>, peer: &PeerId) -> f64 {
+        counts.get(peer).copied().unwrap_or(0) as f64 / SAMPLES as f64
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 0.02,
+            "frequency {actual} is not close to {expected}"
+        );
+    }
+
+    #[test]
+    fn empty_peer_list_returns_none() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for selection in [
+            Selection::Uniform,
+            Selection::ScoreWeighted,
+            Selection::StakeWeighted,
+        ] {
+            let strategy = selection.strategy();
+            let selected = select_peer(
+                &*strategy,
+                &[],
+                &PeerScorer::default(),
+                &PeerStakes::new(),
+                &mut rng,
+            );
+            assert_eq!(selected, None);
+        }
+    }
+
+    #[test]
+    fn uniform_selects_peers_equally() {
+        let peers: Vec<_> = (0..4).map(|_| PeerId::random()).collect();
+
+        // Scores and stakes are ignored
+        let mut scorer = PeerScorer::default();
+        for _ in 0..10 {
+            scorer.update_score(peers[0], SyncResult::Failure);
+        }
+        let stakes = PeerStakes::from([(peers[1], 100)]);
+
+        let counts = selections(Selection::Uniform, &peers, &scorer, &stakes);
+
+        for peer in &peers {
+            assert_close(frequency(&counts, peer), 0.25);
+        }
+    }
+
+    #[test]
+    fn score_weighted_favors_higher_scores() {
+        let peers: Vec<_> = (0..2).map(|_| PeerId::random()).collect();
+
+        let mut scorer = PeerScorer::default();
+        for _ in 0..5 {
+            scorer.update_score(peers[1], SyncResult::Timeout);
+        }
+
+        let counts = selections(
+            Selection::ScoreWeighted,
+            &peers,
+            &scorer,
+            &PeerStakes::new(),
+        );
+
+        let (good, bad) = (scorer.get_score(&peers[0]), scorer.get_score(&peers[1]));
+        assert!(good > bad);
+        assert_close(frequency(&counts, &peers[0]), good / (good + bad));
+        assert_close(frequency(&counts, &peers[1]), bad / (good + bad));
+    }
+
+    #[test]
+    fn stake_weighted_is_proportional_to_voting_power() {
+        let peers: Vec<_> = (0..4).map(|_| PeerId::random()).collect();
+
+        // The last peer is not a known validator
+        let stakes = PeerStakes::from([(peers[0], 10), (peers[1], 30), (peers[2], 60)]);
+
+        let counts = selections(
+            Selection::StakeWeighted,
+            &peers,
+            &PeerScorer::default(),
+            &stakes,
+        );
+
+        assert_close(frequency(&counts, &peers[0]), 0.1);
+        assert_close(frequency(&counts, &peers[1]), 0.3);
+        assert_close(frequency(&counts, &peers[2]), 0.6);
+        assert_eq!(frequency(&counts, &peers[3]), 0.0);
+    }
+
+    #[test]
+    fn stake_weighted_without_known_validators_is_uniform() {
+        let peers: Vec<_> = (0..4).map(|_| PeerId::random()).collect();
+
+        // Only a peer which is not a candidate is a known validator
+        let stakes = PeerStakes::from([(PeerId::random(), 100)]);
+
+        let counts = selections(
+            Selection::StakeWeighted,
+            &peers,
+            &PeerScorer::default(),
+            &stakes,
+        );
+
+        for peer in &peers {
+            assert_close(frequency(&counts, peer), 0.25);
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use {
-    crate::{RawDecidedValue, Request, Response, Status, ValueRequest, ValueResponse},
+    crate::{
+        Announcement, NodeMode, RawDecidedValue, Request, Response, Status, ValueRequest,
+        ValueResponse,
+    },
     borsh::BorshSerialize,
-    malachitebft_core_types::{CommitCertificate, Context},
+    malachitebft_core_types::{CommitCertificate, Context, ValueId},
     malachitebft_peer::PeerId,
     std::ops::RangeInclusive,
 };
@@ -14,6 +17,8 @@ where
         self.peer_id.serialize(writer)?;
         self.tip_height.serialize(writer)?;
         self.history_min_height.serialize(writer)?;
+        self.sync_height.serialize(writer)?;
+        self.mode.is_validator().serialize(writer)?;
         Ok(())
     }
 }
@@ -26,10 +31,47 @@ where
         let peer_id = PeerId::deserialize_reader(reader)?;
         let tip_height = Ctx::Height::deserialize_reader(reader)?;
         let history_min_height = Ctx::Height::deserialize_reader(reader)?;
+        let sync_height = Ctx::Height::deserialize_reader(reader)?;
+        let mode = NodeMode::from_is_validator(bool::deserialize_reader(reader)?);
         Ok(Status {
             peer_id,
             tip_height,
             history_min_height,
+            sync_height,
+            mode,
+        })
+    }
+}
+
+impl<Ctx: Context> borsh::BorshSerialize for Announcement<Ctx>
+where
+    Ctx::Height: borsh::BorshSerialize,
+    ValueId<Ctx>: borsh::BorshSerialize,
+{
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        self.peer_id.serialize(writer)?;
+        self.height.serialize(writer)?;
+        self.value_id.serialize(writer)?;
+        self.certificate_hash.serialize(writer)?;
+        Ok(())
+    }
+}
+
+impl<Ctx: Context> borsh::BorshDeserialize for Announcement<Ctx>
+where
+    Ctx::Height: borsh::BorshDeserialize,
+    ValueId<Ctx>: borsh::BorshDeserialize,
+{
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let peer_id = PeerId::deserialize_reader(reader)?;
+        let height = Ctx::Height::deserialize_reader(reader)?;
+        let value_id = ValueId::<Ctx>::deserialize_reader(reader)?;
+        let certificate_hash = <[u8; 32]>::deserialize_reader(reader)?;
+        Ok(Announcement {
+            peer_id,
+            height,
+            value_id,
+            certificate_hash,
         })
     }
 }
@@ -1,12 +1,15 @@
-use std::cmp::max;
+use std::cmp::{max, min};
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ops::RangeInclusive;
 
 use malachitebft_core_types::{Context, Height};
-use malachitebft_peer::PeerId;
+use malachitebft_peer::{PeerExit, PeerId, PeerState};
+use malachitebft_retry::Retry;
 
+use crate::limits::RequestGuard;
 use crate::scoring::{ema, PeerScorer, Strategy};
-use crate::{Config, OutboundRequestId, Status};
+use crate::selection::{self, PeerStakes, SelectionStrategy};
+use crate::{Config, NodeMode, OutboundRequestId, PauseReason, RetentionConfig, Status};
 
 /// The value stored for each pending request.
 #[derive(Debug, Clone)]
@@ -17,6 +20,33 @@ pub struct PendingRequestEntry<H> {
     pub peer: PeerId,
     /// Peers already tried and failed for this range, accumulated across retries.
     pub excluded_peers: BTreeSet<PeerId>,
+    /// Retries performed so far for this range.
+    pub retry: Retry,
+}
+
+/// State of the backfill of decided values below the earliest height retained by this node.
+#[derive(Debug, Clone)]
+pub struct Backfill<H> {
+    /// Lowest height to backfill.
+    pub target_min_height: H,
+    /// Number of values to request at once, reduced when peers can only
+    /// send part of a batch within the maximum response size.
+    pub batch_size: usize,
+    /// The backfill request in flight, if any.
+    pub pending: Option<(OutboundRequestId, PendingRequestEntry<H>)>,
+    /// Whether all values down to the target height have been backfilled.
+    pub done: bool,
+}
+
+impl<H: Height> Backfill<H> {
+    pub fn new(target_min_height: H, batch_size: usize) -> Self {
+        Self {
+            target_min_height,
+            batch_size: max(1, batch_size),
+            pending: None,
+            done: false,
+        }
+    }
 }
 
 pub struct State<Ctx>
@@ -37,6 +67,10 @@ where
     /// Height of last decided value
     pub tip_height: Ctx::Height,
 
+    /// Earliest height for which we still retain decided values,
+    /// as last reported by the application.
+    pub history_min_height: Ctx::Height,
+
     /// Next height to send a sync request.
     /// Invariant: `sync_height > tip_height`
     pub sync_height: Ctx::Height,
@@ -50,6 +84,31 @@ where
 
     /// Peer scorer for scoring peers based on their performance.
     pub peer_scorer: PeerScorer,
+
+    /// Strategy for selecting the peer to request values from.
+    pub peer_selection: Box<dyn SelectionStrategy>,
+
+    /// Voting power of the peers which are known validators at the current height.
+    pub peer_stakes: PeerStakes,
+
+    /// Backfill of decided values below `history_min_height`, if enabled.
+    ///
+    /// Backfill requests are tracked separately from `pending_requests`,
+    /// so that they never interfere with syncing forward.
+    pub backfill: Option<Backfill<Ctx::Height>>,
+
+    /// Reasons for which sync is paused, in which case no values are requested from peers.
+    /// Requests from peers are still served while paused.
+    pub paused: BTreeSet<PauseReason>,
+
+    /// Whether this node takes part in consensus at its current height, advertised in our status.
+    pub mode: NodeMode,
+
+    /// Limits on the requests from peers being served.
+    pub request_guard: RequestGuard,
+
+    /// Height below which the application was last told it can prune its decided values.
+    pub retain_height: Ctx::Height,
 }
 
 impl<Ctx> State<Ctx>
@@ -66,19 +125,66 @@ where
             Strategy::Ema => PeerScorer::new(ema::ExponentialMovingAverage::default()),
         };
 
+        let backfill = config.backfill.map(|backfill| {
+            Backfill::new(
+                Ctx::Height::ZERO.increment_by(backfill.target_min_height),
+                config.batch_size,
+            )
+        });
+
         Self {
             rng,
             config,
             started: false,
             consensus_height: Ctx::Height::ZERO,
             tip_height: Ctx::Height::ZERO,
+            history_min_height: Ctx::Height::ZERO,
             sync_height: Ctx::Height::ZERO,
             pending_requests: BTreeMap::new(),
             peers: BTreeMap::new(),
             peer_scorer,
+            peer_selection: config.peer_selection.strategy(),
+            peer_stakes: PeerStakes::new(),
+            backfill,
+            paused: BTreeSet::new(),
+            mode: NodeMode::default(),
+            request_guard: RequestGuard::new(config.request_limits, config.request_timeout),
+            retain_height: Ctx::Height::ZERO,
         }
     }
 
+    /// Height below which decided values can be pruned under the given retention policy.
+    ///
+    /// The latest `retain_values` values are always retained. Below those, values are retained
+    /// down to the lowest height needed by a peer which is behind and can still be served,
+    /// ie. the height right after its tip, but no more than `max_retain_values` values.
+    pub fn retain_height(&self, retention: &RetentionConfig) -> Ctx::Height {
+        let next_height = self.tip_height.as_u64() + 1;
+
+        let local = next_height.saturating_sub(retention.retain_values);
+        let floor =
+            next_height.saturating_sub(max(retention.retain_values, retention.max_retain_values));
+
+        let needed_by_peers = self
+            .peers
+            .values()
+            .map(|status| status.tip_height.increment())
+            .filter(|height| *height >= self.history_min_height && *height <= self.tip_height)
+            .min();
+
+        let retain_height = match needed_by_peers {
+            Some(height) => min(local, height.as_u64()).max(floor),
+            None => local,
+        };
+
+        Ctx::Height::ZERO.increment_by(retain_height)
+    }
+
+    /// Whether sync is paused, for any reason.
+    pub fn is_paused(&self) -> bool {
+        !self.paused.is_empty()
+    }
+
     /// The maximum number of parallel requests that can be made to peers.
     /// If the configuration is set to 0, it defaults to 1.
     pub fn max_parallel_requests(&self) -> usize {
@@ -95,6 +201,7 @@ where
         peer_id: PeerId,
         range: RangeInclusive<Ctx::Height>,
         excluded_peers: BTreeSet<PeerId>,
+        retry: Retry,
     ) {
         self.pending_requests.insert(
             request_id,
@@ -102,14 +209,18 @@ where
                 range,
                 peer: peer_id,
                 excluded_peers,
+                retry,
             },
         );
     }
 
     /// Filter peers to only include those that can provide the given range of values, or at least a prefix of the range.
     ///
+    /// A peer can only provide the heights between its `history_min_height` and its `tip_height`,
+    /// so peers which have pruned the start of the range are never selected.
     /// If there is no peer with all requested values, select a peer that has a tip at or above the start of the range.
-    /// Prefer peers that support batching (v2 sync protocol).
+    /// Among these peers, prefer the ones which are not syncing themselves, and then full nodes over validators,
+    /// so that requests go to peers which keep up with the network without taking bandwidth from consensus.
     /// Return the peer ID and the range of heights that the peer can provide.
     pub fn filter_peers_by_range(
         peers: &BTreeMap<PeerId, Status<Ctx>>,
@@ -129,7 +240,7 @@ where
             .collect::<HashMap<_, _>>();
 
         // Prefer peers that have the whole range of values in their history.
-        if !peers_with_whole_range.is_empty() {
+        let candidates = if !peers_with_whole_range.is_empty() {
             peers_with_whole_range
         } else {
             // Otherwise, just get the peers that can provide a prefix of the range.
@@ -141,7 +252,18 @@ where
                 .map(|(peer, status)| (*peer, *range.start()..=status.tip_height))
                 .filter(|(_, range)| !range.is_empty())
                 .collect::<HashMap<_, _>>()
-        }
+        };
+
+        // Keep only the most preferred peers, see `peer_preference`.
+        let best = candidates
+            .keys()
+            .map(|peer| peer_preference(&peers[peer]))
+            .min();
+
+        candidates
+            .into_iter()
+            .filter(|(peer, _)| Some(peer_preference(&peers[peer])) == best)
+            .collect()
     }
 
     /// Select at random a peer that can provide the given range of values,
@@ -154,11 +276,16 @@ where
         // Filtered peers together with the range of heights they can provide.
         let peers_range = Self::filter_peers_by_range(&self.peers, range, except);
 
-        // Select a peer at random.
+        // Select a peer at random, according to the selection strategy.
         let peer_ids = peers_range.keys().cloned().collect::<Vec<_>>();
-        self.peer_scorer
-            .select_peer(&peer_ids, &mut self.rng)
-            .map(|peer_id| (peer_id, peers_range.get(&peer_id).unwrap().clone()))
+        selection::select_peer(
+            &*self.peer_selection,
+            &peer_ids,
+            &self.peer_scorer,
+            &self.peer_stakes,
+            &mut self.rng,
+        )
+        .map(|peer_id| (peer_id, peers_range.get(&peer_id).unwrap().clone()))
     }
 
     /// Same as [`Self::random_peer_with_except`] but without excluding any peer.
@@ -172,6 +299,14 @@ where
         self.random_peer_with_except(range, &BTreeSet::new())
     }
 
+    /// Whether the given request is the backfill request in flight.
+    pub fn is_backfill_request(&self, request_id: &OutboundRequestId) -> bool {
+        self.backfill
+            .as_ref()
+            .and_then(|backfill| backfill.pending.as_ref())
+            .is_some_and(|(id, _)| id == request_id)
+    }
+
     /// Get the request that contains the given height.
     ///
     /// Assumes a height cannot be in multiple pending requests.
@@ -198,3 +333,28 @@ where
             .retain(|_, entry| entry.range.end() > &self.tip_height);
     }
 }
+
+impl<Ctx> PeerState for State<Ctx>
+where
+    Ctx: Context,
+{
+    fn forget_peer(&mut self, peer_id: &PeerId, exit: PeerExit) {
+        self.peers.remove(peer_id);
+        self.peer_scorer.forget_peer(peer_id, exit);
+        self.peer_stakes.remove(peer_id);
+        self.request_guard.forget_peer(peer_id);
+    }
+
+    fn peer_entries(&self) -> usize {
+        self.peers.len()
+            + self.peer_scorer.peer_entries()
+            + self.peer_stakes.len()
+            + self.request_guard.peer_entries()
+    }
+}
+
+/// Preference for requesting values from a peer, lower is better:
+/// peers which are not syncing themselves first, and then full nodes over validators.
+fn peer_preference<Ctx: Context>(status: &Status<Ctx>) -> (bool, bool) {
+    (status.is_syncing(), status.mode.is_validator())
+}
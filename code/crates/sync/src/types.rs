@@ -6,10 +6,11 @@ use displaydoc::Display;
 use libp2p::request_response;
 use serde::{Deserialize, Serialize};
 
+use malachitebft_core_types::hash::{Hasher, Sha256};
 use malachitebft_core_types::ValueResponse as CoreValueResponse;
-use malachitebft_core_types::{CommitCertificate, Context, Height};
+use malachitebft_core_types::{CommitCertificate, Context, Height, SigningScheme, ValueId};
 
-pub use malachitebft_peer::PeerId;
+pub use malachitebft_peer::{PeerExit, PeerId, PeerState};
 
 /// Indicates whether the height is the start of a new height or a restart of the latest height
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -39,6 +40,18 @@ impl HeightStartType {
     }
 }
 
+/// Reason for which sync stopped requesting values from peers, see [`Input::Pause`](crate::Input::Pause).
+///
+/// Sync only resumes once every reason it was paused for has been lifted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PauseReason {
+    /// The application asked for sync to be paused
+    Application,
+
+    /// The application has too many messages from sync waiting to be handled
+    Backpressure,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
 #[displaydoc("{0}")]
 pub struct InboundRequestId(Arc<str>);
@@ -61,11 +74,93 @@ impl OutboundRequestId {
 
 pub type ResponseChannel = request_response::ResponseChannel<RawResponse>;
 
+/// Whether a node takes part in consensus at its current height, as advertised in its status
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum NodeMode {
+    /// The node is in the validator set of its current height
+    Validator,
+
+    /// The node is not in the validator set of its current height
+    #[default]
+    FullNode,
+}
+
+impl NodeMode {
+    pub const fn from_is_validator(is_validator: bool) -> Self {
+        if is_validator {
+            Self::Validator
+        } else {
+            Self::FullNode
+        }
+    }
+
+    pub const fn is_validator(&self) -> bool {
+        matches!(self, Self::Validator)
+    }
+}
+
 #[derive_where(Clone, Debug, PartialEq, Eq)]
 pub struct Status<Ctx: Context> {
     pub peer_id: PeerId,
+    /// Height of the latest decided value of the peer
     pub tip_height: Ctx::Height,
+    /// Earliest height for which the peer still retains decided values
     pub history_min_height: Ctx::Height,
+    /// Next height the peer will request via sync, which is past the height right after its tip
+    /// while it is catching up with the network
+    pub sync_height: Ctx::Height,
+    /// Whether the peer takes part in consensus at its current height
+    pub mode: NodeMode,
+}
+
+impl<Ctx: Context> Status<Ctx> {
+    /// Whether the peer is catching up with the network via sync,
+    /// rather than deciding heights as they come.
+    pub fn is_syncing(&self) -> bool {
+        self.sync_height > self.tip_height.increment()
+    }
+}
+
+/// Hash of a commit certificate, see [`certificate_hash`].
+pub type CertificateHash = [u8; 32];
+
+/// Compact announcement of a value decided by a peer, which the peer publishes as soon as
+/// the value is committed, so that the nodes following the chain can request it
+/// without waiting for the next status update of that peer.
+#[derive_where(Clone, Debug, PartialEq, Eq)]
+pub struct Announcement<Ctx: Context> {
+    pub peer_id: PeerId,
+    /// Height of the decided value
+    pub height: Ctx::Height,
+    /// Identifier of the decided value
+    pub value_id: ValueId<Ctx>,
+    /// Hash of the commit certificate of the decided value
+    pub certificate_hash: CertificateHash,
+}
+
+/// SHA-256 hash of the height, round, value id and signatures of the given commit certificate.
+pub fn certificate_hash<Ctx: Context>(certificate: &CommitCertificate<Ctx>) -> CertificateHash {
+    let mut hasher = Sha256::default();
+
+    let mut update = |data: &[u8]| {
+        hasher.update(&(data.len() as u64).to_be_bytes());
+        hasher.update(data);
+    };
+
+    update(&certificate.height.as_u64().to_be_bytes());
+    update(&certificate.round.as_i64().to_be_bytes());
+    update(certificate.value_id.to_string().as_bytes());
+
+    for commit_signature in &certificate.commit_signatures {
+        update(commit_signature.address.to_string().as_bytes());
+        update(&Ctx::SigningScheme::encode_signature(
+            &commit_signature.signature,
+        ));
+    }
+
+    hasher.finalize()
 }
 
 #[derive_where(Clone, Debug, PartialEq, Eq)]
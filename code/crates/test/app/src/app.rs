@@ -2,12 +2,14 @@ use std::time::Duration;
 
 use eyre::eyre;
 use tokio::time::sleep;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use malachitebft_app_channel::app::engine::host::{HeightParams, Next};
 use malachitebft_app_channel::app::streaming::StreamContent;
 use malachitebft_app_channel::app::types::core::utils::height::HeightRangeExt;
-use malachitebft_app_channel::app::types::core::{Round, Validity};
+use malachitebft_app_channel::app::types::core::{
+    Round, RoundTimeoutsOverride, TimeoutKind, Validity,
+};
 use malachitebft_app_channel::app::types::sync::RawDecidedValue;
 use malachitebft_app_channel::app::types::ProposedValue;
 use malachitebft_app_channel::{AppMsg, Channels, NetworkMsg};
@@ -228,6 +230,12 @@ pub async fn run(state: &mut State, channels: &mut Channels<TestContext>) -> eyr
                     error!("Failed to send GetValue reply");
                 }
 
+                // In proposal-only mode, the value is small enough to be carried
+                // by the proposal message itself, there are no parts to stream.
+                if !state.config.consensus.value_payload.include_parts() {
+                    continue;
+                }
+
                 // The POL round is always nil when we propose a newly built value.
                 // See L15/L18 of the Tendermint algorithm.
                 let pol_round = Round::Nil;
@@ -253,6 +261,13 @@ pub async fn run(state: &mut State, channels: &mut Channels<TestContext>) -> eyr
                 }
             }
 
+            // When `cancel_get_value` is enabled, the engine notifies us when the propose timeout
+            // elapses before we replied to `GetValue`. As we build values right away, there is
+            // nothing to abort, but an application building values from a mempool would stop here.
+            AppMsg::CancelGetValue { height, round } => {
+                warn!(%height, %round, "Consensus cancelled the request for a value to propose");
+            }
+
             // On the receiving end of these proposal parts (ie. when we are not the proposer),
             // we need to process these parts and re-assemble the full value.
             // To this end, we store each part that we receive and assemble the full value once we
@@ -273,6 +288,35 @@ pub async fn run(state: &mut State, channels: &mut Channels<TestContext>) -> eyr
                 }
             }
 
+            // When running in proposal-only mode, the proposal message carries the full value,
+            // so there are no parts to re-assemble. We only need to validate the value and
+            // send it back to consensus together with its validity.
+            AppMsg::ReceivedProposal {
+                height,
+                round,
+                valid_round,
+                proposer,
+                value,
+                reply,
+            } => {
+                debug!(%height, %round, %valid_round, %proposer, "Received proposal");
+
+                let proposed_value = ProposedValue {
+                    height,
+                    round,
+                    valid_round,
+                    proposer,
+                    value,
+                    validity: Validity::Valid,
+                };
+
+                let proposed_value = state.received_proposal(proposed_value).await?;
+
+                if reply.send(proposed_value).is_err() {
+                    error!("Failed to send ReceivedProposal reply");
+                }
+            }
+
             // After some time, consensus will finally reach a decision on the value
             // to commit for the current height, and will notify the application,
             // providing it with a commit certificate which contains the ID of the value
@@ -409,10 +453,9 @@ pub async fn run(state: &mut State, channels: &mut Channels<TestContext>) -> eyr
                         validity: Validity::Valid,
                     };
 
-                    // TODO: We plan to add some validation here in the future.
-                    state.store_synced_value(proposal.clone()).await?;
+                    let outcome = state.validate_synced_value(proposal).await?;
 
-                    if reply.send(Some(proposal)).is_err() {
+                    if reply.send(Some(outcome)).is_err() {
                         error!("Failed to send ProcessSyncedValue reply");
                     }
                 } else {
@@ -423,6 +466,18 @@ pub async fn run(state: &mut State, channels: &mut Channels<TestContext>) -> eyr
                 }
             }
 
+            // When batching is enabled in the value sync config, the engine instead forwards all the
+            // values of a sync response at once, so that we can store them in a single transaction.
+            AppMsg::ProcessSyncedValues { values, reply } => {
+                info!(count = values.len(), "Processing a batch of synced values");
+
+                let outcomes = state.process_synced_values(values).await?;
+
+                if reply.send(outcomes).is_err() {
+                    error!("Failed to send ProcessSyncedValues reply");
+                }
+            }
+
             // If, on the other hand, we are not lagging behind but are instead asked by one of
             // our peer to help them catch up because they are the one lagging behind,
             // then the engine might ask the application to provide with the value
@@ -448,6 +503,19 @@ pub async fn run(state: &mut State, channels: &mut Channels<TestContext>) -> eyr
                 }
             }
 
+            // When backfill is enabled, the engine fetches the values decided below the earliest
+            // height in our store from our peers, eg. when we started from a snapshot.
+            // We verify them against their commit certificates before storing them.
+            AppMsg::ProcessBackfilledValues { values, reply } => {
+                info!(count = values.len(), "Processing backfilled values");
+
+                let stored = state.store_backfilled_values(values).await?;
+
+                if reply.send(stored).is_err() {
+                    error!("Failed to send ProcessBackfilledValues reply");
+                }
+            }
+
             // In order to figure out if we can help a peer that is lagging behind,
             // the engine may ask us for the height of the earliest available value in our store.
             AppMsg::GetHistoryMinHeight { reply } => {
@@ -458,6 +526,58 @@ pub async fn run(state: &mut State, channels: &mut Channels<TestContext>) -> eyr
                 }
             }
 
+            // When timeout overrides are enabled, the engine asks us at the start of every round
+            // whether we want to override any of its timeouts, which we delegate to the middleware.
+            AppMsg::GetTimeoutOverrides {
+                height,
+                round,
+                reply,
+            } => {
+                let middleware = state.ctx.middleware();
+                let get = |kind| middleware.get_timeout_override(&state.ctx, height, round, kind);
+
+                let durations = RoundTimeoutsOverride {
+                    propose: get(TimeoutKind::Propose),
+                    prevote: get(TimeoutKind::Prevote),
+                    precommit: get(TimeoutKind::Precommit),
+                    rebroadcast: get(TimeoutKind::Rebroadcast),
+                };
+
+                if reply.send(durations).is_err() {
+                    error!("Failed to send GetTimeoutOverrides reply");
+                }
+            }
+
+            // When consensus parameter overrides are enabled, the engine asks us before starting
+            // every height whether we want to override its parameters, which we delegate to the middleware.
+            AppMsg::GetConsensusParams { height, reply } => {
+                let overrides = state
+                    .ctx
+                    .middleware()
+                    .get_consensus_params(&state.ctx, height);
+
+                if reply.send(overrides).is_err() {
+                    error!("Failed to send GetConsensusParams reply");
+                }
+            }
+
+            // When round alerts are enabled, the engine notifies us when a height goes through
+            // too many rounds, in which case an operator should look into why it is not deciding.
+            AppMsg::RoundAlert {
+                height,
+                round,
+                halted,
+            } => {
+                warn!(%height, %round, %halted, "Height is going through too many rounds");
+            }
+
+            // When retention is enabled, sync tells us below which height our decided values
+            // are no longer needed by peers. We keep pruning the store on commit instead,
+            // retaining a fixed number of values.
+            AppMsg::Prune { retain_height } => {
+                debug!(%retain_height, "Decided values below the retain height can be pruned");
+            }
+
             AppMsg::RestreamProposal {
                 height,
                 round,
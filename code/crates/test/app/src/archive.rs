@@ -0,0 +1,45 @@
+//! Export and import of the decided values of the application, for the `archive` commands.
+
+use async_trait::async_trait;
+
+use malachitebft_app_channel::app::archive::{DecidedValueSink, DecidedValueSource};
+use malachitebft_app_channel::app::types::core::CommitCertificate;
+use malachitebft_app_channel::app::types::sync::RawDecidedValue;
+use malachitebft_test::{Height, TestContext, ValidatorSet, Value};
+
+use crate::state::{encode_value, State};
+
+#[async_trait]
+impl DecidedValueSource<TestContext> for State {
+    async fn history_min_height(&self) -> eyre::Result<Height> {
+        Ok(self.get_earliest_height().await)
+    }
+
+    async fn get_decided_value(
+        &self,
+        height: Height,
+    ) -> eyre::Result<Option<RawDecidedValue<TestContext>>> {
+        let decided_value = State::get_decided_value(self, height).await;
+
+        Ok(decided_value.map(|decided_value| RawDecidedValue {
+            value_bytes: encode_value(&decided_value.value),
+            certificate: decided_value.certificate,
+        }))
+    }
+}
+
+#[async_trait]
+impl DecidedValueSink<TestContext> for State {
+    async fn validator_set(&self, height: Height) -> eyre::Result<Option<ValidatorSet>> {
+        Ok(Some(self.get_validator_set(height)))
+    }
+
+    async fn store_decided_value(
+        &mut self,
+        certificate: CommitCertificate<TestContext>,
+        value: Value,
+    ) -> eyre::Result<()> {
+        self.store.store_decided_value(&certificate, value).await?;
+        Ok(())
+    }
+}
@@ -1,13 +1,24 @@
+use bytesize::ByteSize;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
-use malachitebft_app_channel::app::config::NodeConfig;
+use malachitebft_app_channel::app::config::{ConfigSections, NodeConfig};
 use malachitebft_engine_byzantine::ByzantineConfig;
 
 pub use malachitebft_app_channel::app::config::{
-    ConsensusConfig, LoggingConfig, MetricsConfig, RuntimeConfig, TestConfig, ValueSyncConfig,
+    ConsensusConfig, LoggingConfig, MetricsConfig, PartitioningPolicy, RuntimeConfig, TestConfig,
+    ValueSyncConfig,
 };
 
+/// Partitioning policy streaming each factor of the values proposed by the test application
+/// in its own part.
+pub fn factor_partitioning() -> PartitioningPolicy {
+    PartitioningPolicy {
+        max_part_bytes: ByteSize::b(size_of::<u64>() as u64),
+        ..PartitioningPolicy::default()
+    }
+}
+
 /// Configuration for validator set rotation
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ValidatorRotationConfig {
@@ -98,6 +109,22 @@ impl NodeConfig for Config {
     fn value_sync_mut(&mut self) -> &mut ValueSyncConfig {
         &mut self.value_sync
     }
+
+    fn metrics(&self) -> &MetricsConfig {
+        &self.metrics
+    }
+}
+
+impl Config {
+    /// The sections of the configuration which are shared by all applications.
+    pub fn sections(&self) -> ConfigSections<'_> {
+        ConfigSections {
+            logging: &self.logging,
+            consensus: &self.consensus,
+            value_sync: &self.value_sync,
+            metrics: &self.metrics,
+        }
+    }
 }
 
 /// load_config parses the environment variables and loads the provided config file path
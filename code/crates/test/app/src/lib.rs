@@ -2,6 +2,7 @@ pub mod app;
 pub mod config;
 pub mod metrics;
 pub mod node;
+pub mod reload;
 pub mod state;
 pub mod store;
 pub mod streaming;
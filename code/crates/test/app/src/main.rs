@@ -5,25 +5,41 @@ use eyre::{eyre, Result};
 use malachitebft_test::node::Node;
 use tracing::info;
 
+use malachitebft_app_channel::app::consensus::{FullProposalLimits, Params};
+use malachitebft_app_channel::app::types::ValuePayload;
+use malachitebft_signer::{AuthKey, RemoteSignerConfig};
 use malachitebft_test::codec::proto::ProtobufCodec;
-use malachitebft_test::Height;
+use malachitebft_test::{Height, TestContext};
 use malachitebft_test_cli::args::{Args, Commands};
+use malachitebft_test_cli::cmd::archive::{ArchiveCmd, ArchiveCommands};
+use malachitebft_test_cli::cmd::config::{check_config, ConfigCmd, ConfigCommands};
 use malachitebft_test_cli::cmd::dump_wal::DumpWalCmd;
+use malachitebft_test_cli::cmd::genesis::{GenesisCmd, GenesisCommands};
 use malachitebft_test_cli::cmd::init::InitCmd;
+use malachitebft_test_cli::cmd::keys::{KeyScheme, KeysCmd, KeysCommands};
+use malachitebft_test_cli::cmd::metrics::{MetricsCmd, MetricsCommands};
+use malachitebft_test_cli::cmd::node::{check_not_migrated, NodeCmd, NodeCommands};
+use malachitebft_test_cli::cmd::signer::{SignerCmd, SignerCommands};
 use malachitebft_test_cli::cmd::start::StartCmd;
 use malachitebft_test_cli::cmd::testnet::TestnetCmd;
-use malachitebft_test_cli::config::{LogFormat, LogLevel};
+use malachitebft_test_cli::cmd::wal::{WalCmd, WalCommands};
+use malachitebft_test_cli::config::{LogFormat, LogLevel, ValuePayload as ValuePayloadConfig};
+use malachitebft_test_cli::logging::OtlpConfig;
 use malachitebft_test_cli::{logging, runtime};
 
 mod app;
+mod archive;
 mod config;
 mod metrics;
 mod node;
+mod reload;
 mod state;
 mod store;
 mod streaming;
 
-use node::CliApp;
+use node::{sign_state_path, CliApp};
+use state::State;
+use store::{NoMetrics, Store, StoreMetrics};
 
 fn main() -> Result<()> {
     color_eyre::install()?;
@@ -35,11 +51,32 @@ fn main() -> Result<()> {
         Commands::Init(cmd) => init(&args, cmd),
         Commands::Testnet(cmd) => testnet(&args, cmd),
         Commands::DumpWal(cmd) => dump_wal(&args, cmd),
+        Commands::Wal(cmd) => wal(&args, cmd),
+        Commands::Archive(cmd) => archive(&args, cmd),
+        Commands::Metrics(cmd) => metrics_command(cmd),
+        Commands::Genesis(cmd) => genesis(&args, cmd),
+        Commands::Config(cmd) => config_command(&args, cmd),
+        Commands::Node(cmd) => node_command(&args, cmd),
+        Commands::Signer(cmd) => signer_command(&args, cmd),
+        Commands::Keys(cmd) => keys_command(&args, cmd),
         Commands::DistributedTestnet(_) => unimplemented!(),
     }
 }
 
 fn start(args: &Args, cmd: &StartCmd) -> Result<()> {
+    let remote_signer = match (&cmd.remote_signer, &cmd.remote_signer_auth_key_file) {
+        (Some(address), Some(key_file)) => Some(RemoteSignerConfig::new(
+            address.clone(),
+            AuthKey::from_file(key_file).map_err(|error| {
+                eyre!(
+                    "Failed to read the remote signer key from {}: {error}",
+                    key_file.display()
+                )
+            })?,
+        )),
+        _ => None,
+    };
+
     let app = CliApp {
         home_dir: args.get_home_dir()?,
         config_file: args.get_config_file_path()?,
@@ -47,11 +84,20 @@ fn start(args: &Args, cmd: &StartCmd) -> Result<()> {
         private_key_file: args.get_priv_validator_key_file_path()?,
         start_height: cmd.start_height.map(Height::new),
         validator: cmd.validator,
+        remote_signer,
     };
 
     let config: Config = app.load_config()?;
 
-    let _guard = logging::init(config.logging.log_level, config.logging.log_format);
+    let otlp = cmd.otlp_endpoint.clone().map(|endpoint| OtlpConfig {
+        endpoint,
+        service_name: config.moniker.clone(),
+    });
+
+    let _guard = logging::init_with_otlp(config.logging.log_level, config.logging.log_format, otlp);
+
+    check_config(&config).map_err(|error| eyre!("Invalid configuration: {error}"))?;
+    check_not_migrated(&app.home_dir)?;
 
     let rt = runtime::build_runtime(config.runtime)?;
 
@@ -71,6 +117,7 @@ fn init(args: &Args, cmd: &InitCmd) -> Result<()> {
         private_key_file: args.get_priv_validator_key_file_path()?,
         start_height: None,
         validator: false,
+        remote_signer: None,
     };
 
     cmd.run(
@@ -92,6 +139,7 @@ fn testnet(args: &Args, cmd: &TestnetCmd) -> Result<()> {
         private_key_file: args.get_priv_validator_key_file_path()?,
         start_height: Some(Height::new(1)),
         validator: false,
+        remote_signer: None,
     };
 
     cmd.run(&app, &args.get_home_dir()?)
@@ -104,3 +152,307 @@ fn dump_wal(_args: &Args, cmd: &DumpWalCmd) -> Result<()> {
     cmd.run(ProtobufCodec)
         .map_err(|error| eyre!("Failed to run dump-wal command {:?}", error))
 }
+
+fn wal(args: &Args, cmd: &WalCmd) -> Result<()> {
+    let _guard = logging::init(LogLevel::Info, LogFormat::Plaintext);
+
+    let app = CliApp {
+        home_dir: args.get_home_dir()?,
+        config_file: args.get_config_file_path()?,
+        genesis_file: args.get_genesis_file_path()?,
+        private_key_file: args.get_priv_validator_key_file_path()?,
+        start_height: None,
+        validator: false,
+        remote_signer: None,
+    };
+
+    let config: Config = app.load_config()?;
+    let genesis = app.load_genesis()?;
+    let verifier = app.get_verifier();
+
+    let rt = runtime::build_runtime(config.runtime)?;
+
+    match &cmd.command {
+        WalCommands::Inspect(inspect) => rt
+            .block_on(inspect.run::<TestContext, _, _>(
+                ProtobufCodec,
+                &genesis.validator_set,
+                &verifier,
+            ))
+            .map_err(|error| eyre!("Failed to run wal inspect command {error:?}")),
+
+        WalCommands::Replay(replay) => {
+            let private_key = app.load_private_key(app.load_private_key_file()?);
+            let address = app.get_address(&app.get_public_key(&private_key));
+            let signer = app.get_signer(private_key);
+
+            let value_payload = match config.consensus.value_payload {
+                ValuePayloadConfig::PartsOnly => ValuePayload::PartsOnly,
+                ValuePayloadConfig::ProposalOnly => ValuePayload::ProposalOnly,
+                ValuePayloadConfig::ProposalAndParts => ValuePayload::ProposalAndParts,
+            };
+
+            let params = Params {
+                address,
+                threshold_params: Default::default(),
+                value_payload,
+                enabled: true,
+                require_vote_extensions: config.consensus.require_vote_extensions,
+                full_proposal_limits: FullProposalLimits {
+                    round_margin: config.consensus.full_proposals.round_margin,
+                    max_entries_per_round: config.consensus.full_proposals.max_entries_per_round,
+                },
+            };
+
+            rt.block_on(replay.run(
+                TestContext::new(),
+                ProtobufCodec,
+                params,
+                genesis.validator_set,
+                &verifier,
+                &signer,
+            ))
+            .map_err(|error| eyre!("Failed to run wal replay command {error:?}"))
+        }
+
+        WalCommands::Migrate(migrate) => migrate
+            .run::<TestContext, _>(ProtobufCodec)
+            .map_err(|error| eyre!("Failed to run wal migrate command {error:?}")),
+    }
+}
+
+fn metrics_command(cmd: &MetricsCmd) -> Result<()> {
+    let _guard = logging::init(LogLevel::Info, LogFormat::Plaintext);
+
+    match &cmd.command {
+        MetricsCommands::Dashboard(dashboard) => dashboard
+            .run()
+            .map_err(|error| eyre!("Failed to run metrics dashboard command {error:?}")),
+    }
+}
+
+fn genesis(args: &Args, cmd: &GenesisCmd) -> Result<()> {
+    let _guard = logging::init(LogLevel::Info, LogFormat::Plaintext);
+
+    let app = CliApp {
+        home_dir: args.get_home_dir()?,
+        config_file: args.get_config_file_path()?,
+        genesis_file: args.get_genesis_file_path()?,
+        private_key_file: args.get_priv_validator_key_file_path()?,
+        start_height: None,
+        validator: false,
+        remote_signer: None,
+    };
+
+    let genesis_file = match &cmd.genesis_file {
+        Some(genesis_file) => genesis_file.clone(),
+        None => args.get_genesis_file_path()?,
+    };
+
+    match &cmd.command {
+        GenesisCommands::AddValidator(add) => add
+            .run(&app, &genesis_file)
+            .map_err(|error| eyre!("Failed to run genesis add-validator command {error:?}")),
+
+        GenesisCommands::Validate(validate) => validate
+            .run(&app, &genesis_file)
+            .map_err(|error| eyre!("Failed to run genesis validate command {error:?}")),
+
+        GenesisCommands::Hash(hash) => hash
+            .run(&app, &genesis_file)
+            .map_err(|error| eyre!("Failed to run genesis hash command {error:?}")),
+    }
+}
+
+fn config_command(args: &Args, cmd: &ConfigCmd) -> Result<()> {
+    let _guard = logging::init(LogLevel::Info, LogFormat::Plaintext);
+
+    let app = CliApp {
+        home_dir: args.get_home_dir()?,
+        config_file: args.get_config_file_path()?,
+        genesis_file: args.get_genesis_file_path()?,
+        private_key_file: args.get_priv_validator_key_file_path()?,
+        start_height: None,
+        validator: false,
+        remote_signer: None,
+    };
+
+    match &cmd.command {
+        ConfigCommands::Check(check) => check
+            .run(&app)
+            .map_err(|error| eyre!("Failed to run config check command {error:?}")),
+    }
+}
+
+fn node_command(args: &Args, cmd: &NodeCmd) -> Result<()> {
+    let _guard = logging::init(LogLevel::Info, LogFormat::Plaintext);
+
+    let app = CliApp {
+        home_dir: args.get_home_dir()?,
+        config_file: args.get_config_file_path()?,
+        genesis_file: args.get_genesis_file_path()?,
+        private_key_file: args.get_priv_validator_key_file_path()?,
+        start_height: None,
+        validator: false,
+        remote_signer: None,
+    };
+
+    let private_key = app.load_private_key(app.load_private_key_file()?);
+    let address = app.get_address(&app.get_public_key(&private_key));
+
+    let wal_path = app.get_home_dir().join("wal").join("consensus.wal");
+
+    match &cmd.command {
+        NodeCommands::Export(export) => {
+            let config: Config = app.load_config()?;
+
+            export
+                .run::<TestContext, _>(
+                    &app.home_dir,
+                    &app.config_file,
+                    &wal_path,
+                    &sign_state_path(&app.home_dir),
+                    &config.consensus,
+                    &address,
+                    ProtobufCodec,
+                )
+                .map_err(|error| eyre!("Failed to run node export command {error:?}"))
+        }
+
+        NodeCommands::Import(import) => import
+            .run::<TestContext>(&app.home_dir, &wal_path, &address)
+            .map_err(|error| eyre!("Failed to run node import command {error:?}")),
+    }
+}
+
+fn signer_command(args: &Args, cmd: &SignerCmd) -> Result<()> {
+    let _guard = logging::init(LogLevel::Info, LogFormat::Plaintext);
+
+    match &cmd.command {
+        SignerCommands::Start(start) => {
+            let app = CliApp {
+                home_dir: args.get_home_dir()?,
+                config_file: args.get_config_file_path()?,
+                genesis_file: args.get_genesis_file_path()?,
+                private_key_file: args.get_priv_validator_key_file_path()?,
+                start_height: None,
+                validator: false,
+                remote_signer: None,
+            };
+
+            let private_key = app.load_private_key(app.load_private_key_file()?);
+            let address = app.get_address(&app.get_public_key(&private_key));
+            let signer = app.get_signer(private_key);
+
+            let state_file = match &start.state_file {
+                Some(state_file) => state_file.clone(),
+                None => sign_state_path(&app.home_dir),
+            };
+
+            info!(%address, "Starting the remote signer");
+
+            let rt = runtime::build_runtime(Default::default())?;
+
+            rt.block_on(start.run::<TestContext, _, _>(signer, ProtobufCodec, &state_file))
+                .map_err(|error| eyre!("Failed to run signer start command {error:?}"))
+        }
+
+        SignerCommands::GenerateKey(generate_key) => generate_key
+            .run()
+            .map_err(|error| eyre!("Failed to run signer generate-key command {error:?}")),
+    }
+}
+
+fn keys_command(args: &Args, cmd: &KeysCmd) -> Result<()> {
+    let _guard = logging::init(LogLevel::Info, LogFormat::Plaintext);
+
+    let app = CliApp {
+        home_dir: args.get_home_dir()?,
+        config_file: args.get_config_file_path()?,
+        genesis_file: args.get_genesis_file_path()?,
+        private_key_file: args.get_priv_validator_key_file_path()?,
+        start_height: None,
+        validator: false,
+        remote_signer: None,
+    };
+
+    let key_file = match &cmd.key_file {
+        Some(key_file) => key_file.clone(),
+        None => args.get_priv_validator_key_file_path()?,
+    };
+
+    let passphrase_file = cmd.passphrase_file.as_deref();
+
+    match &cmd.command {
+        KeysCommands::Generate(generate) => match generate.scheme {
+            KeyScheme::Ed25519 => generate
+                .run(&app, &key_file, passphrase_file)
+                .map_err(|error| eyre!("Failed to run keys generate command {error:?}")),
+        },
+
+        KeysCommands::Show(show) => show
+            .run(&app, &key_file, passphrase_file)
+            .map_err(|error| eyre!("Failed to run keys show command {error:?}")),
+
+        KeysCommands::Address(address) => address
+            .run(&app, &key_file, passphrase_file)
+            .map_err(|error| eyre!("Failed to run keys address command {error:?}")),
+    }
+}
+
+fn archive(args: &Args, cmd: &ArchiveCmd) -> Result<()> {
+    let _guard = logging::init(LogLevel::Info, LogFormat::Plaintext);
+
+    let app = CliApp {
+        home_dir: args.get_home_dir()?,
+        config_file: args.get_config_file_path()?,
+        genesis_file: args.get_genesis_file_path()?,
+        private_key_file: args.get_priv_validator_key_file_path()?,
+        start_height: None,
+        validator: false,
+        remote_signer: None,
+    };
+
+    let config: Config = app.load_config()?;
+    let genesis = app.load_genesis()?;
+    let verifier = app.get_verifier();
+
+    let private_key = app.load_private_key(app.load_private_key_file()?);
+    let address = app.get_address(&app.get_public_key(&private_key));
+    let signer = app.get_signer(private_key);
+
+    let rt = runtime::build_runtime(config.runtime)?;
+
+    rt.block_on(async {
+        let db_dir = app.get_home_dir().join("db");
+        std::fs::create_dir_all(&db_dir)?;
+
+        let store = Store::open(
+            db_dir.join("store.db"),
+            Box::new(NoMetrics) as Box<dyn StoreMetrics>,
+        )
+        .await?;
+
+        let ctx = TestContext::new();
+        let mut state = State::new(
+            ctx.clone(),
+            config,
+            genesis,
+            address,
+            Height::default(),
+            store,
+            signer,
+            None,
+        );
+
+        match &cmd.command {
+            ArchiveCommands::Export(export) => {
+                export.run::<TestContext, _, _>(&state, ProtobufCodec).await
+            }
+            ArchiveCommands::Import(import) => {
+                import.run(&ctx, ProtobufCodec, &verifier, &mut state).await
+            }
+        }
+    })
+    .map_err(|error| eyre!("Failed to run archive command {error:?}"))
+}
@@ -1,15 +1,17 @@
 #![allow(clippy::too_many_arguments)]
 
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
-use rand::{CryptoRng, Rng, RngCore};
+use rand::{CryptoRng, RngCore};
 use tokio::task::JoinHandle;
-use tracing::Instrument;
+use tracing::{error, info, warn, Instrument};
 
 use malachitebft_app_channel::app::config::*;
+use malachitebft_app_channel::app::engine::util::clock::Clock;
 use malachitebft_app_channel::app::events::{RxEvent, TxEvent};
+use malachitebft_app_channel::app::sign_guard::SignGuard;
 use malachitebft_app_channel::app::types::codec::Codec;
 use malachitebft_app_channel::app::types::core::VotingPower;
 use malachitebft_app_channel::app::types::Keypair;
@@ -17,23 +19,27 @@ use malachitebft_app_channel::{
     ByzantineContext, ConsensusContext, EngineBuilder, EngineHandle, NetworkContext,
     NetworkIdentity, RequestContext, Signer, SyncContext, WalContext,
 };
+use malachitebft_signer::{RemoteSigner, RemoteSignerConfig};
 use malachitebft_test::byzantine::ByzantineMiddleware;
 use malachitebft_test::codec::proto::ProtobufCodec;
+use malachitebft_test::home_dir::HomeDirLock;
 use malachitebft_test::node::{Node, NodeHandle};
 use malachitebft_test::traits::{
     CanGeneratePrivateKey, CanMakeConfig, CanMakeGenesis, CanMakePrivateKeyFile, MakeConfigSettings,
 };
+use malachitebft_test_cli::cmd::keys::{read_key_file, PASSPHRASE_FILE_ENV};
 
 use malachitebft_test::middleware::{DefaultMiddleware, Middleware};
 
 // Use the same types used for integration tests.
 // A real application would use its own types and context instead.
 use malachitebft_test::{
-    Address, Ed25519Signer, Ed25519Verifier, Genesis, Height, PrivateKey, PublicKey, TestContext,
-    Validator, ValidatorSet, Value, ValueId,
+    Address, Ed25519Signer, Ed25519Verifier, Genesis, Height, PrivateKey, ProposalPart, PublicKey,
+    TestContext, Validator, ValidatorSet, Value, ValueId,
 };
 
-use crate::config::{Config, ValidatorRotationConfig};
+use crate::config::{factor_partitioning, Config, ValidatorRotationConfig};
+use crate::reload::{ConfigReloader, HangupSignal};
 use crate::state::State;
 use crate::store::{NoMetrics, Store, StoreMetrics};
 
@@ -41,6 +47,19 @@ pub struct Handle {
     pub app: JoinHandle<()>,
     pub engine: EngineHandle,
     pub tx_event: TxEvent<TestContext>,
+    /// Reloads the configuration on SIGHUP, if the node was started from a configuration file
+    pub reloader: Option<ConfigReloader>,
+    /// Lock on the home directory, released once the node is killed or stopped
+    pub home_dir_lock: Mutex<Option<HomeDirLock>>,
+}
+
+impl Handle {
+    fn release_home_dir(&self) {
+        self.home_dir_lock
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+    }
 }
 
 #[async_trait]
@@ -53,10 +72,116 @@ impl NodeHandle<TestContext> for Handle {
         self.engine.actor.kill_and_wait(None).await?;
         self.app.abort();
         self.engine.handle.abort();
+        self.release_home_dir();
+        Ok(())
+    }
+    async fn stop(&self) -> eyre::Result<()> {
+        self.engine.stop().await?;
+        self.app.abort();
+        self.release_home_dir();
         Ok(())
     }
 }
 
+/// Run the node until the application exits, or until the process receives
+/// a shutdown signal, in which case the engine is shut down gracefully.
+///
+/// The configuration is reloaded whenever the process receives SIGHUP, if the node has a reloader.
+async fn run_until_shutdown(handle: Handle) -> eyre::Result<()> {
+    let Handle {
+        mut app,
+        engine,
+        mut reloader,
+        ..
+    } = handle;
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    // Keep the default action of SIGHUP if the configuration cannot be reloaded
+    let mut hangup = match reloader {
+        Some(_) => HangupSignal::new(),
+        None => HangupSignal::default(),
+    };
+
+    loop {
+        tokio::select! {
+            result = &mut app => return result.map_err(Into::into),
+            signal = &mut shutdown => {
+                info!("Received {signal}, shutting down");
+                break;
+            }
+            () = hangup.recv() => {
+                info!("Received SIGHUP, reloading the configuration");
+
+                if let Some(reloader) = &mut reloader {
+                    if let Err(e) = reloader.reload(&engine) {
+                        error!("Failed to reload the configuration: {e}");
+                    }
+                }
+            }
+        }
+    }
+
+    engine.stop().await?;
+    app.abort();
+
+    Ok(())
+}
+
+/// Serve the metrics if they are enabled, until the returned task is aborted.
+pub(crate) fn spawn_metrics_server(config: &MetricsConfig) -> Option<JoinHandle<()>> {
+    use malachitebft_test_cli::metrics;
+
+    config
+        .enabled
+        .then(|| tokio::spawn(metrics::serve(config.clone())))
+}
+
+/// Path of the file in which the last vote or proposal signed by the validator is persisted.
+pub fn sign_state_path(home_dir: &Path) -> PathBuf {
+    home_dir.join("sign_state")
+}
+
+/// Wrap the signer of the validator so that it refuses to double-sign, even after a restart.
+fn guarded_signer(
+    home_dir: &Path,
+    signer: Ed25519Signer,
+) -> eyre::Result<Box<dyn Signer<TestContext>>> {
+    std::fs::create_dir_all(home_dir)?;
+
+    let guard = SignGuard::open(signer, sign_state_path(home_dir))?;
+    Ok(Box::new(guard))
+}
+
+/// Wait for a signal asking the process to shut down, and return its name.
+async fn shutdown_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut sigterm = match signal(SignalKind::terminate()) {
+            Ok(sigterm) => sigterm,
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {e}");
+                let _ = tokio::signal::ctrl_c().await;
+                return "SIGINT";
+            }
+        };
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => "SIGINT",
+            _ = sigterm.recv() => "SIGTERM",
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "Ctrl-C"
+    }
+}
+
 /// Application struct used by the integration test framework.
 /// Configuration and keys are provided in-memory.
 #[derive(Clone)]
@@ -71,6 +196,8 @@ pub struct App {
     /// When true, the node signs a validator proof and advertises a validator identity.
     /// When false, the node starts without a validator identity.
     pub validator: bool,
+    /// Clock driving the timers of the node, the Tokio timer if not set.
+    pub clock: Option<Arc<dyn Clock>>,
 }
 
 impl App {
@@ -81,6 +208,16 @@ impl App {
         let net_pk = self.generate_private_key(rng);
         Keypair::ed25519_from_bytes(net_pk.inner().to_bytes()).unwrap()
     }
+
+    fn with_clock(
+        &self,
+        consensus_ctx: ConsensusContext<TestContext>,
+    ) -> ConsensusContext<TestContext> {
+        match &self.clock {
+            Some(clock) => consensus_ctx.with_clock(Arc::clone(clock)),
+            None => consensus_ctx,
+        }
+    }
 }
 
 #[async_trait]
@@ -151,6 +288,8 @@ impl Node for App {
                 .map_err(|e| eyre::eyre!("Invalid byzantine configuration: {e}"))?;
         }
 
+        let home_dir_lock = self.initialize_home_dir()?;
+
         let public_key = self.get_public_key(&self.private_key);
         let address = self.get_address(&public_key);
 
@@ -194,7 +333,7 @@ impl Node for App {
         let ctx = TestContext::with_middleware(middleware.clone());
         let keypair = self.get_network_keypair(); // Separate network identity
         let genesis = self.load_genesis()?;
-        let wal_path = self.get_home_dir().join("wal").join("consensus.wal");
+        let wal_path = self.get_wal_path();
 
         let identity = if self.validator {
             let signer = self.get_signer(self.private_key.clone());
@@ -246,13 +385,17 @@ impl Node for App {
                         Some(id) => ValueId::new(id.as_u64() ^ 0x01),
                         None => ValueId::new(0),
                     })),
+                    proposal_part_position_fn: Some(Box::new(|part: &ProposalPart| {
+                        part.as_init().map(|init| (init.height, init.round))
+                    })),
                 })
                 .await?
-                .with_default_consensus(ConsensusContext::new_validator(
+                // Byzantine nodes sign without the guard, so that they may equivocate
+                .with_default_consensus(self.with_clock(ConsensusContext::new_validator(
                     address,
                     Box::new(self.get_verifier()),
                     Box::new(self.get_signer(self.private_key.clone())),
-                ))
+                )))
                 .with_default_sync(SyncContext::new(ProtobufCodec))
                 .with_default_request(RequestContext::new(100))
                 .build()
@@ -262,12 +405,14 @@ impl Node for App {
                 ConsensusContext::new_validator(
                     address,
                     Box::new(self.get_verifier()),
-                    Box::new(self.get_signer(self.private_key.clone())),
+                    guarded_signer(&self.home_dir, self.get_signer(self.private_key.clone()))?,
                 )
             } else {
                 ConsensusContext::new_full_node(address, Box::new(self.get_verifier()))
             };
 
+            let consensus_ctx = self.with_clock(consensus_ctx);
+
             builder
                 .with_default_network(NetworkContext::new(identity, ProtobufCodec))
                 .with_default_consensus(consensus_ctx)
@@ -279,11 +424,8 @@ impl Node for App {
 
         drop(_guard);
 
-        let db_path = self.get_home_dir().join("db");
-        std::fs::create_dir_all(&db_path)?;
-
         let store = Store::open(
-            db_path.join("store.db"),
+            self.get_db_dir().join("store.db"),
             Box::new(NoMetrics) as Box<dyn StoreMetrics>,
         )
         .await?;
@@ -315,12 +457,14 @@ impl Node for App {
             app: app_handle,
             engine: engine_handle,
             tx_event,
+            reloader: None,
+            home_dir_lock: Mutex::new(Some(home_dir_lock)),
         })
     }
 
     async fn run(self) -> eyre::Result<()> {
-        let handles = self.start().await?;
-        handles.app.await.map_err(Into::into)
+        let handle = self.start().await?;
+        run_until_shutdown(handle).await
     }
 }
 
@@ -334,6 +478,35 @@ pub struct CliApp {
     pub private_key_file: PathBuf,
     pub start_height: Option<Height>,
     pub validator: bool,
+    /// Remote signer to sign with, instead of the validator key.
+    /// The address of the validator is still derived from its key file.
+    pub remote_signer: Option<RemoteSignerConfig>,
+}
+
+impl CliApp {
+    /// The signer of the validator: the remote signer if one is configured, in charge of
+    /// refusing to double-sign, or else the validator key wrapped in a sign guard.
+    async fn validator_signer(
+        &self,
+        private_key: PrivateKey,
+    ) -> eyre::Result<Box<dyn Signer<TestContext>>> {
+        let Some(config) = &self.remote_signer else {
+            return guarded_signer(&self.home_dir, self.get_signer(private_key));
+        };
+
+        let signer = RemoteSigner::new(config.clone(), ProtobufCodec);
+
+        signer.ping().await.map_err(|e| {
+            eyre::eyre!(
+                "Failed to connect to the remote signer at {}: {e}",
+                config.address
+            )
+        })?;
+
+        info!(address = %config.address, "Signing with the remote signer");
+
+        Ok(Box::new(signer))
+    }
 }
 
 #[async_trait]
@@ -379,8 +552,9 @@ impl Node for CliApp {
     }
 
     fn load_private_key_file(&self) -> eyre::Result<Self::PrivateKeyFile> {
-        let private_key = std::fs::read_to_string(&self.private_key_file)?;
-        serde_json::from_str(&private_key).map_err(Into::into)
+        // Encrypted key files are decrypted with the passphrase in the file given by the environment
+        let passphrase_file = std::env::var_os(PASSPHRASE_FILE_ENV).map(PathBuf::from);
+        read_key_file(&self.private_key_file, passphrase_file.as_deref())
     }
 
     fn load_genesis(&self) -> eyre::Result<Self::Genesis> {
@@ -394,11 +568,13 @@ impl Node for CliApp {
         let span = tracing::error_span!("node", moniker = %config.moniker);
         let _enter = span.enter();
 
+        let home_dir_lock = self.initialize_home_dir()?;
+
         let private_key_file = self.load_private_key_file()?;
         let private_key = self.load_private_key(private_key_file);
         let public_key = self.get_public_key(&private_key);
         let address = self.get_address(&public_key);
-        let wal_path = self.get_home_dir().join("wal").join("consensus.wal");
+        let wal_path = self.get_wal_path();
         let ctx = TestContext::new();
         let genesis = self.load_genesis()?;
 
@@ -406,8 +582,13 @@ impl Node for CliApp {
         let net_pk = self.generate_private_key(rand::thread_rng());
         let keypair = Keypair::ed25519_from_bytes(net_pk.inner().to_bytes()).unwrap();
 
-        let identity = if self.validator {
-            let signer = self.get_signer(private_key.clone());
+        let signer = if self.validator {
+            Some(self.validator_signer(private_key.clone()).await?)
+        } else {
+            None
+        };
+
+        let identity = if let Some(signer) = &signer {
             let peer_id_bytes = keypair.public().to_peer_id().to_bytes();
             let proof = signer
                 .sign_validator_proof(public_key.as_bytes().to_vec(), peer_id_bytes)
@@ -426,14 +607,11 @@ impl Node for CliApp {
             NetworkIdentity::new(config.moniker.clone(), keypair, None)
         };
 
-        let consensus_ctx = if self.validator {
-            ConsensusContext::new_validator(
-                address,
-                Box::new(self.get_verifier()),
-                Box::new(self.get_signer(private_key.clone())),
-            )
-        } else {
-            ConsensusContext::new_full_node(address, Box::new(self.get_verifier()))
+        let consensus_ctx = match signer {
+            Some(signer) => {
+                ConsensusContext::new_validator(address, Box::new(self.get_verifier()), signer)
+            }
+            None => ConsensusContext::new_full_node(address, Box::new(self.get_verifier())),
         };
 
         let (mut channels, engine_handle) = EngineBuilder::new(ctx.clone(), config.clone())
@@ -447,22 +625,16 @@ impl Node for CliApp {
 
         let tx_event = channels.events.clone();
 
-        let db_dir = self.get_home_dir().join("db");
-        std::fs::create_dir_all(&db_dir)?;
-
         use crate::metrics::DbMetrics;
         use malachitebft_app_channel::app::metrics::SharedRegistry;
 
         let registry = SharedRegistry::global().with_moniker(&config.moniker);
         let metrics = DbMetrics::register(&registry);
 
-        if config.metrics.enabled {
-            use malachitebft_test_cli::metrics;
-            tokio::spawn(metrics::serve(config.metrics.listen_addr));
-        }
+        let metrics_server = spawn_metrics_server(&config.metrics);
 
         let store = Store::open(
-            db_dir.join("store.db"),
+            self.get_db_dir().join("store.db"),
             Box::new(metrics) as Box<dyn StoreMetrics>,
         )
         .await?;
@@ -493,12 +665,18 @@ impl Node for CliApp {
             app: app_handle,
             engine: engine_handle,
             tx_event,
+            reloader: Some(ConfigReloader::new(
+                self.config_file.clone(),
+                config,
+                metrics_server,
+            )),
+            home_dir_lock: Mutex::new(Some(home_dir_lock)),
         })
     }
 
     async fn run(self) -> eyre::Result<()> {
-        let handles = self.start().await?;
-        handles.app.await.map_err(Into::into)
+        let handle = self.start().await?;
+        run_until_shutdown(handle).await
     }
 }
 
@@ -512,6 +690,14 @@ impl CanMakeGenesis for App {
 
         Genesis { validator_set }
     }
+
+    fn genesis_validators(&self, genesis: &Self::Genesis) -> Vec<(PublicKey, VotingPower)> {
+        genesis
+            .validator_set
+            .iter()
+            .map(|v| (v.public_key, v.voting_power))
+            .collect()
+    }
 }
 
 impl CanGeneratePrivateKey for App {
@@ -545,6 +731,14 @@ impl CanMakeGenesis for CliApp {
 
         Genesis { validator_set }
     }
+
+    fn genesis_validators(&self, genesis: &Self::Genesis) -> Vec<(PublicKey, VotingPower)> {
+        genesis
+            .validator_set
+            .iter()
+            .map(|v| (v.public_key, v.voting_power))
+            .collect()
+    }
 }
 
 impl CanGeneratePrivateKey for CliApp {
@@ -570,9 +764,6 @@ impl CanMakeConfig for CliApp {
 
 /// Generate configuration for node "index" out of "total" number of nodes.
 fn make_config(index: usize, total: usize, settings: MakeConfigSettings) -> Config {
-    use itertools::Itertools;
-    use rand::seq::IteratorRandom;
-
     const CONSENSUS_BASE_PORT: usize = 27000;
     const METRICS_BASE_PORT: usize = 29000;
 
@@ -588,45 +779,26 @@ fn make_config(index: usize, total: usize, settings: MakeConfigSettings) -> Conf
             p2p: P2pConfig {
                 protocol: PubSubProtocol::default(),
                 listen_addr: settings.transport.multiaddr("127.0.0.1", consensus_port),
-                persistent_peers: if settings.discovery.enabled {
-                    let mut rng = rand::thread_rng();
-                    let count = if total > 1 {
-                        rng.gen_range(1..=(total / 2))
-                    } else {
-                        0
-                    };
-                    let peers = (0..total)
-                        .filter(|j| *j != index)
-                        .choose_multiple(&mut rng, count);
-
-                    peers
-                        .iter()
-                        .unique()
-                        .map(|index| {
-                            settings
-                                .transport
-                                .multiaddr("127.0.0.1", CONSENSUS_BASE_PORT + index)
-                        })
-                        .collect()
-                } else {
-                    (0..total)
-                        .filter(|j| *j != index)
-                        .map(|j| {
-                            settings
-                                .transport
-                                .multiaddr("127.0.0.1", CONSENSUS_BASE_PORT + j)
-                        })
-                        .collect()
-                },
+                persistent_peers: settings
+                    .persistent_peers(index, total)
+                    .into_iter()
+                    .map(|j| {
+                        settings
+                            .transport
+                            .multiaddr("127.0.0.1", CONSENSUS_BASE_PORT + j)
+                    })
+                    .collect(),
                 discovery: settings.discovery,
                 persistent_peers_only: settings.persistent_peers_only,
                 ..Default::default()
             },
+            partitioning: factor_partitioning(),
             ..Default::default()
         },
         metrics: MetricsConfig {
             enabled: true,
             listen_addr: format!("127.0.0.1:{metrics_port}").parse().unwrap(),
+            ..Default::default()
         },
         runtime: settings.runtime,
         value_sync: ValueSyncConfig::default(),
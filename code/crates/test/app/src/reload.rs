@@ -0,0 +1,176 @@
+//! Reloading of the configuration of a running node, when the process receives SIGHUP.
+//!
+//! The configuration file is read again and validated, then the log level, the intervals
+//! of sync and the metrics server are updated if they changed. If any other field has
+//! changed, the reloaded configuration is rejected as a whole and nothing is applied.
+
+use std::path::PathBuf;
+
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use malachitebft_app_channel::app::config::{reload, ConfigReload, ReloadError};
+use malachitebft_app_channel::{EngineHandle, SyncReconfiguration};
+use malachitebft_test_cli::cmd::config::check_config;
+use malachitebft_test_cli::logging;
+
+use crate::config::{load_config, Config};
+use crate::node::spawn_metrics_server;
+
+/// Applies the changes made to the configuration file to the running node.
+pub struct ConfigReloader {
+    config_file: PathBuf,
+    config: Config,
+    metrics_server: Option<JoinHandle<()>>,
+}
+
+impl ConfigReloader {
+    pub fn new(
+        config_file: PathBuf,
+        config: Config,
+        metrics_server: Option<JoinHandle<()>>,
+    ) -> Self {
+        Self {
+            config_file,
+            config,
+            metrics_server,
+        }
+    }
+
+    /// Read the configuration file again, and apply the settings which changed to the running node.
+    pub fn reload(&mut self, engine: &EngineHandle) -> eyre::Result<()> {
+        let config = load_config(&self.config_file, Some("MALACHITE"))?;
+
+        check_config(&config)?;
+
+        let changes = changes(&self.config, &config)?;
+
+        if changes.is_empty() {
+            info!("Configuration is unchanged");
+            return Ok(());
+        }
+
+        if let Some(log_level) = changes.log_level {
+            info!(%log_level, "Changing the log level");
+            logging::reload(log_level);
+        }
+
+        if changes.status_update_interval.is_some() || changes.backfill_request_interval.is_some() {
+            engine.reconfigure_sync(SyncReconfiguration {
+                status_update_interval: changes.status_update_interval,
+                backfill_request_interval: changes.backfill_request_interval,
+            })?;
+        }
+
+        if let Some(metrics) = changes.metrics {
+            info!(
+                enabled = %metrics.enabled,
+                listen_addr = %metrics.listen_addr,
+                "Restarting the metrics server"
+            );
+
+            if let Some(server) = self.metrics_server.take() {
+                server.abort();
+            }
+
+            self.metrics_server = spawn_metrics_server(&metrics);
+        }
+
+        self.config = config;
+
+        Ok(())
+    }
+}
+
+impl Drop for ConfigReloader {
+    fn drop(&mut self) {
+        if let Some(server) = self.metrics_server.take() {
+            server.abort();
+        }
+    }
+}
+
+/// The settings to apply, rejecting the changes to the fields specific to this application,
+/// which cannot be changed while the node is running either.
+fn changes(current: &Config, new: &Config) -> Result<ConfigReload, ReloadError> {
+    let Config {
+        moniker,
+        logging: _,
+        consensus: _,
+        value_sync: _,
+        metrics: _,
+        runtime,
+        test,
+        byzantine,
+        validator_rotation,
+    } = new;
+
+    let mut fields = Vec::new();
+
+    if moniker != &current.moniker {
+        fields.push("moniker");
+    }
+    if runtime != &current.runtime {
+        fields.push("runtime");
+    }
+    if test != &current.test {
+        fields.push("test");
+    }
+    if byzantine != &current.byzantine {
+        fields.push("byzantine");
+    }
+    if validator_rotation != &current.validator_rotation {
+        fields.push("validator_rotation");
+    }
+
+    match reload(current.sections(), new.sections()) {
+        Ok(changes) if fields.is_empty() => Ok(changes),
+        Ok(_) => Err(ReloadError { fields }),
+        Err(mut e) => {
+            e.fields.extend(fields);
+            Err(e)
+        }
+    }
+}
+
+/// Listener for SIGHUP, which asks the node to reload its configuration.
+///
+/// The default listener does not listen for the signal, which then terminates the process.
+#[derive(Default)]
+pub struct HangupSignal {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl HangupSignal {
+    /// Listen for SIGHUP, replacing its default action.
+    pub fn new() -> Self {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let signal = signal(SignalKind::hangup())
+                .inspect_err(|e| warn!("Failed to listen for SIGHUP: {e}"))
+                .ok();
+
+            Self { signal }
+        }
+
+        #[cfg(not(unix))]
+        {
+            Self {}
+        }
+    }
+
+    /// Wait for the next SIGHUP, forever if the signal cannot be listened for.
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal {
+            if signal.recv().await.is_some() {
+                return;
+            }
+        }
+
+        std::future::pending().await
+    }
+}
@@ -13,10 +13,15 @@ use sha3::Digest;
 use tracing::{debug, error, info};
 
 use malachitebft_app_channel::app::consensus::{ProposedValue, Role};
+use malachitebft_app_channel::app::engine::host::SyncedValueOutcome;
 use malachitebft_app_channel::app::streaming::{StreamContent, StreamId, StreamMessage};
 use malachitebft_app_channel::app::types::codec::Codec;
-use malachitebft_app_channel::app::types::core::{CommitCertificate, Round, Validity};
+use malachitebft_app_channel::app::types::core::{
+    CommitCertificate, Round, ThresholdParams, Validity,
+};
+use malachitebft_app_channel::app::types::sync::RawDecidedValue;
 use malachitebft_app_channel::app::types::{LocallyProposedValue, PeerId};
+use malachitebft_signing::VerifierExt;
 use malachitebft_test::codec::proto::ProtobufCodec;
 use malachitebft_test::middleware::Middleware;
 use malachitebft_test::{
@@ -346,6 +351,28 @@ impl State {
         }
     }
 
+    /// Validates a value received in a proposal when running in proposal-only mode,
+    /// and stores it as undecided.
+    ///
+    /// The proposer and signature of the proposal have already been verified by consensus,
+    /// so only the value itself needs to be validated here.
+    pub async fn received_proposal(
+        &mut self,
+        mut value: ProposedValue<TestContext>,
+    ) -> eyre::Result<ProposedValue<TestContext>> {
+        value.validity = match &self.middleware {
+            Some(middleware) => {
+                middleware.get_validity(&self.ctx, value.height, value.round, &value.value)
+            }
+            None => Validity::Valid,
+        };
+
+        info!(%value.height, %value.round, %value.proposer, validity = ?value.validity, "Storing received proposal as undecided");
+        self.store.store_undecided_proposal(value.clone()).await?;
+
+        Ok(value)
+    }
+
     /// Retrieves a decided block at the given height
     pub async fn get_decided_value(&self, height: Height) -> Option<DecidedValue> {
         self.store.get_decided_value(height).await.ok().flatten()
@@ -427,12 +454,167 @@ impl State {
         }
     }
 
-    pub async fn store_synced_value(
+    /// Verifies and stores historical values backfilled from a peer.
+    ///
+    /// Returns `false` without storing anything if any value cannot be decoded,
+    /// does not match its certificate, or if its certificate is invalid.
+    pub async fn store_backfilled_values(
+        &mut self,
+        values: Vec<RawDecidedValue<TestContext>>,
+    ) -> eyre::Result<bool> {
+        let mut verified = Vec::with_capacity(values.len());
+
+        for raw in values {
+            let height = raw.certificate.height;
+
+            let Some(value) = decode_value(raw.value_bytes) else {
+                error!(%height, "Failed to decode backfilled value");
+                return Ok(false);
+            };
+
+            if value.id() != raw.certificate.value_id {
+                error!(%height, "Backfilled value does not match its certificate");
+                return Ok(false);
+            }
+
+            let validator_set = self.get_validator_set(height);
+
+            if let Err(e) = self
+                .signer
+                .verify_commit_certificate(
+                    &self.ctx,
+                    &raw.certificate,
+                    &validator_set,
+                    ThresholdParams::default(),
+                )
+                .await
+            {
+                error!(%height, "Invalid commit certificate for backfilled value: {e}");
+                return Ok(false);
+            }
+
+            verified.push((raw.certificate, value));
+        }
+
+        for (certificate, value) in verified {
+            self.store.store_decided_value(&certificate, value).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Validates a value received through sync, as a value received in a proposal would be,
+    /// and stores it as undecided if it is valid.
+    pub async fn validate_synced_value(
         &mut self,
         proposal: ProposedValue<TestContext>,
-    ) -> eyre::Result<()> {
-        self.store.store_undecided_proposal(proposal).await?;
-        Ok(())
+    ) -> eyre::Result<SyncedValueOutcome<TestContext>> {
+        if let Some(outcome) = self.reject_synced_value(&proposal) {
+            return Ok(outcome);
+        }
+
+        self.store
+            .store_undecided_proposal(proposal.clone())
+            .await?;
+
+        Ok(SyncedValueOutcome::Valid(proposal))
+    }
+
+    /// Decodes and validates a batch of values received through sync,
+    /// and stores the valid ones as undecided in a single transaction.
+    ///
+    /// The proposer of each value is selected from the validator set of its height,
+    /// as consensus only knows it once it reaches that height.
+    pub async fn process_synced_values(
+        &mut self,
+        values: Vec<RawDecidedValue<TestContext>>,
+    ) -> eyre::Result<Vec<Option<SyncedValueOutcome<TestContext>>>> {
+        let mut outcomes = Vec::with_capacity(values.len());
+        let mut valid = Vec::new();
+
+        for raw in values {
+            let height = raw.certificate.height;
+            let round = raw.certificate.round;
+
+            let should_fail = self
+                .middleware
+                .as_ref()
+                .is_some_and(|m| m.fail_synced_value_decode(&self.ctx, height, round));
+
+            let decoded = if should_fail {
+                None
+            } else {
+                decode_value(raw.value_bytes)
+            };
+
+            let Some(value) = decoded else {
+                error!(%height, %round, "Failed to decode synced value");
+                outcomes.push(None);
+                continue;
+            };
+
+            let validator_set = self.get_validator_set(height);
+            let proposer = self
+                .ctx
+                .select_proposer(&validator_set, height, round)
+                .address;
+
+            let proposal = ProposedValue {
+                height,
+                round,
+                valid_round: Round::Nil,
+                proposer,
+                value,
+                validity: Validity::Valid,
+            };
+
+            let outcome = match self.reject_synced_value(&proposal) {
+                Some(outcome) => outcome,
+                None => {
+                    valid.push(proposal.clone());
+                    SyncedValueOutcome::Valid(proposal)
+                }
+            };
+
+            outcomes.push(Some(outcome));
+        }
+
+        self.store.store_undecided_proposals(valid).await?;
+
+        Ok(outcomes)
+    }
+
+    /// Checks the validity of a value received through sync with the middleware,
+    /// returning the outcome to report if the value is rejected.
+    fn reject_synced_value(
+        &self,
+        proposal: &ProposedValue<TestContext>,
+    ) -> Option<SyncedValueOutcome<TestContext>> {
+        let validity = match &self.middleware {
+            Some(middleware)
+                if middleware.fail_synced_value_validation(
+                    &self.ctx,
+                    proposal.height,
+                    proposal.round,
+                ) =>
+            {
+                Validity::Invalid
+            }
+            Some(middleware) => {
+                middleware.get_validity(&self.ctx, proposal.height, proposal.round, &proposal.value)
+            }
+            None => Validity::Valid,
+        };
+
+        if validity.is_invalid() {
+            error!(%proposal.height, %proposal.round, "Rejecting invalid synced value");
+
+            return Some(SyncedValueOutcome::Invalid {
+                reason: "value rejected by middleware".to_string(),
+            });
+        }
+
+        None
     }
 
     /// Retrieves a previously built proposal value for the given height and round.
@@ -567,7 +749,16 @@ impl State {
 
         // Data
         {
-            for factor in factor_value(value.value.clone()) {
+            // The policy determines how many factors are grouped in each part, as their product.
+            // Factors are too small to benefit from compression, so it is not applied to them.
+            let factors = factor_value(value.value.clone());
+            let payload_len = factors.len() * size_of::<u64>();
+            let factors_per_part = (self.config.consensus.partitioning.part_len(payload_len)
+                / size_of::<u64>())
+            .max(1);
+
+            for group in factors.chunks(factors_per_part) {
+                let factor = group.iter().product::<u64>();
                 parts.push(ProposalPart::Data(ProposalData::new(factor)));
 
                 hasher.update(factor.to_be_bytes().as_slice());
@@ -11,11 +11,19 @@ use std::path::PathBuf;
 use clap::{Parser, Subcommand};
 use directories::BaseDirs;
 
+use crate::cmd::archive::ArchiveCmd;
+use crate::cmd::config::ConfigCmd;
 use crate::cmd::distributed_testnet::DistributedTestnetCmd;
 use crate::cmd::dump_wal::DumpWalCmd;
+use crate::cmd::genesis::GenesisCmd;
 use crate::cmd::init::InitCmd;
+use crate::cmd::keys::KeysCmd;
+use crate::cmd::metrics::MetricsCmd;
+use crate::cmd::node::NodeCmd;
+use crate::cmd::signer::SignerCmd;
 use crate::cmd::start::StartCmd;
 use crate::cmd::testnet::TestnetCmd;
+use crate::cmd::wal::WalCmd;
 use crate::error::Error;
 
 const APP_FOLDER: &str = ".malachite";
@@ -50,6 +58,30 @@ pub enum Commands {
 
     /// Dump WAL entries
     DumpWal(DumpWalCmd),
+
+    /// Inspect or replay a WAL file
+    Wal(WalCmd),
+
+    /// Export or import the decided values of the node
+    Archive(ArchiveCmd),
+
+    /// Generate monitoring resources for the metrics of the node
+    Metrics(MetricsCmd),
+
+    /// Assemble, validate and hash genesis files
+    Genesis(GenesisCmd),
+
+    /// Check the configuration of the node
+    Config(ConfigCmd),
+
+    /// Export or import the state of the node, to migrate it to another machine
+    Node(NodeCmd),
+
+    /// Run a remote signer holding the validator key, or generate its authentication key
+    Signer(SignerCmd),
+
+    /// Generate or inspect the private key of the validator
+    Keys(KeysCmd),
 }
 
 impl Default for Commands {
@@ -102,7 +134,17 @@ impl Args {
 
 #[cfg(test)]
 mod tests {
+    use malachitebft_test::topology::Topology;
+
     use super::*;
+    use crate::cmd::archive::{ArchiveCommands, ArchiveExportCmd};
+    use crate::cmd::config::{ConfigCheckCmd, ConfigCommands};
+    use crate::cmd::genesis::{GenesisAddValidatorCmd, GenesisCommands};
+    use crate::cmd::keys::{KeyScheme, KeysAddressCmd, KeysCommands, KeysGenerateCmd};
+    use crate::cmd::metrics::{MetricsCommands, MetricsDashboardCmd};
+    use crate::cmd::node::{NodeCommands, NodeExportCmd, NodeImportCmd};
+    use crate::cmd::signer::{SignerCommands, SignerGenerateKeyCmd, SignerStartCmd};
+    use crate::cmd::wal::{WalCommands, WalMigrateCmd, WalReplayCmd};
 
     #[test]
     fn parse_args() {
@@ -111,6 +153,229 @@ mod tests {
 
         let args = Args::parse_from(["test", "start"]);
         assert!(matches!(args.command, Commands::Start(_)));
+
+        let args = Args::parse_from(["test", "wal", "inspect", "wal.db"]);
+        assert!(matches!(
+            args.command,
+            Commands::Wal(WalCmd {
+                command: WalCommands::Inspect(_)
+            })
+        ));
+
+        let args = Args::parse_from(["test", "wal", "replay", "wal.db", "--height", "3"]);
+        assert!(matches!(
+            args.command,
+            Commands::Wal(WalCmd {
+                command: WalCommands::Replay(WalReplayCmd {
+                    height: Some(3),
+                    ..
+                })
+            })
+        ));
+
+        let args = Args::parse_from(["test", "wal", "migrate", "wal.db"]);
+        assert!(matches!(
+            args.command,
+            Commands::Wal(WalCmd {
+                command: WalCommands::Migrate(WalMigrateCmd {
+                    encryption_key_file: None,
+                    ..
+                })
+            })
+        ));
+
+        let args = Args::parse_from(["test", "archive", "export", "chain.arc", "--from", "2"]);
+        assert!(matches!(
+            args.command,
+            Commands::Archive(ArchiveCmd {
+                command: ArchiveCommands::Export(ArchiveExportCmd {
+                    from: Some(2),
+                    to: None,
+                    ..
+                })
+            })
+        ));
+
+        let args = Args::parse_from([
+            "test",
+            "testnet",
+            "--nodes",
+            "4",
+            "--topology",
+            "random:2",
+            "--bootstrap-nodes",
+            "0,2",
+            "--docker-compose",
+        ]);
+        let Commands::Testnet(cmd) = args.command else {
+            panic!("Expected testnet command");
+        };
+        assert_eq!(cmd.topology, Topology::Random { degree: 2 });
+        assert_eq!(cmd.bootstrap_nodes, vec![0, 2]);
+        assert!(cmd.docker_compose);
+
+        let args = Args::parse_from(["test", "archive", "import", "chain.arc"]);
+        assert!(matches!(
+            args.command,
+            Commands::Archive(ArchiveCmd {
+                command: ArchiveCommands::Import(_)
+            })
+        ));
+
+        let args = Args::parse_from(["test", "metrics", "dashboard", "--output", "dash.json"]);
+        let Commands::Metrics(MetricsCmd {
+            command: MetricsCommands::Dashboard(MetricsDashboardCmd { output, datasource }),
+        }) = args.command
+        else {
+            panic!("Expected metrics dashboard command");
+        };
+        assert_eq!(output, Some(PathBuf::from("dash.json")));
+        assert_eq!(datasource, "prometheus");
+
+        let args = Args::parse_from([
+            "test",
+            "genesis",
+            "add-validator",
+            "ab01",
+            "--voting-power",
+            "10",
+            "--genesis-file",
+            "genesis.json",
+        ]);
+        let Commands::Genesis(GenesisCmd {
+            genesis_file,
+            command:
+                GenesisCommands::AddValidator(GenesisAddValidatorCmd {
+                    public_key,
+                    voting_power,
+                }),
+        }) = args.command
+        else {
+            panic!("Expected genesis add-validator command");
+        };
+        assert_eq!(genesis_file, Some(PathBuf::from("genesis.json")));
+        assert_eq!(public_key, "ab01");
+        assert_eq!(voting_power, 10);
+
+        let args = Args::parse_from(["test", "config", "check", "--strict"]);
+        assert!(matches!(
+            args.command,
+            Commands::Config(ConfigCmd {
+                command: ConfigCommands::Check(ConfigCheckCmd { strict: true })
+            })
+        ));
+
+        let args = Args::parse_from(["test", "node", "export", "--output", "bundle.bin"]);
+        let Commands::Node(NodeCmd {
+            command: NodeCommands::Export(NodeExportCmd { output }),
+        }) = args.command
+        else {
+            panic!("Expected node export command");
+        };
+        assert_eq!(output, PathBuf::from("bundle.bin"));
+
+        let args = Args::parse_from(["test", "node", "import", "-i", "bundle.bin", "--force"]);
+        assert!(matches!(
+            args.command,
+            Commands::Node(NodeCmd {
+                command: NodeCommands::Import(NodeImportCmd { force: true, .. })
+            })
+        ));
+
+        let args = Args::parse_from([
+            "test",
+            "signer",
+            "start",
+            "--listen",
+            "tcp://127.0.0.1:26659",
+            "--auth-key-file",
+            "signer.key",
+        ]);
+        let Commands::Signer(SignerCmd {
+            command:
+                SignerCommands::Start(SignerStartCmd {
+                    listen,
+                    auth_key_file,
+                    state_file,
+                }),
+        }) = args.command
+        else {
+            panic!("Expected signer start command");
+        };
+        assert_eq!(listen.to_string(), "tcp://127.0.0.1:26659");
+        assert_eq!(auth_key_file, PathBuf::from("signer.key"));
+        assert_eq!(state_file, None);
+
+        let args = Args::parse_from(["test", "signer", "generate-key", "-o", "signer.key"]);
+        assert!(matches!(
+            args.command,
+            Commands::Signer(SignerCmd {
+                command: SignerCommands::GenerateKey(SignerGenerateKeyCmd { .. })
+            })
+        ));
+
+        let args = Args::parse_from([
+            "test",
+            "start",
+            "--validator",
+            "--remote-signer",
+            "unix:///tmp/signer.sock",
+            "--remote-signer-auth-key-file",
+            "signer.key",
+        ]);
+        let Commands::Start(cmd) = args.command else {
+            panic!("Expected start command");
+        };
+        assert_eq!(
+            cmd.remote_signer.map(|address| address.to_string()),
+            Some("unix:///tmp/signer.sock".to_string())
+        );
+        assert_eq!(
+            cmd.remote_signer_auth_key_file,
+            Some(PathBuf::from("signer.key"))
+        );
+
+        let result =
+            Args::try_parse_from(["test", "start", "--remote-signer", "tcp://127.0.0.1:26659"]);
+        assert!(result.is_err(), "the authentication key is required");
+
+        let args = Args::parse_from([
+            "test",
+            "keys",
+            "generate",
+            "--seed",
+            "42",
+            "--key-file",
+            "key.json",
+            "--passphrase-file",
+            "passphrase",
+        ]);
+        let Commands::Keys(KeysCmd {
+            key_file,
+            passphrase_file,
+            command: KeysCommands::Generate(KeysGenerateCmd { scheme, seed, .. }),
+        }) = args.command
+        else {
+            panic!("Expected keys generate command");
+        };
+        assert_eq!(key_file, Some(PathBuf::from("key.json")));
+        assert_eq!(passphrase_file, Some(PathBuf::from("passphrase")));
+        assert_eq!(scheme, KeyScheme::Ed25519);
+        assert_eq!(seed, Some(42));
+
+        let args = Args::parse_from(["test", "keys", "address", "--public-key", "ab01"]);
+        assert!(matches!(
+            args.command,
+            Commands::Keys(KeysCmd {
+                command: KeysCommands::Address(KeysAddressCmd {
+                    public_key: Some(_)
+                }),
+                ..
+            })
+        ));
+
+        let result = Args::try_parse_from(["test", "keys", "generate", "--scheme", "rsa"]);
+        assert!(result.is_err(), "only supported schemes are accepted");
     }
 
     #[test]
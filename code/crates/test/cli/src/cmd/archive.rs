@@ -0,0 +1,114 @@
+//! Archive commands, for migrating the decided values of a node between storage backends.
+//!
+//! `archive export` writes the decided values of the node, together with their commit
+//! certificates, to an archive file, while `archive import` loads them back into a node,
+//! verifying every commit certificate along the way.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use color_eyre::eyre;
+use tracing::info;
+
+use malachitebft_app::archive::{
+    self, ArchiveReader, ArchiveWriter, DecidedValueSink, DecidedValueSource,
+};
+use malachitebft_app::types::codec::Codec;
+use malachitebft_app::types::sync::RawDecidedValue;
+use malachitebft_core_types::{Context, Height};
+use malachitebft_signing::Verifier;
+
+#[derive(Parser, Debug, Clone, PartialEq)]
+pub struct ArchiveCmd {
+    #[command(subcommand)]
+    pub command: ArchiveCommands,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum ArchiveCommands {
+    /// Export the decided values of the node to an archive file
+    Export(ArchiveExportCmd),
+
+    /// Import the decided values of an archive file into the node
+    Import(ArchiveImportCmd),
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct ArchiveExportCmd {
+    /// Path to the archive file to create
+    pub archive_file: PathBuf,
+
+    /// First height to export (default: the earliest height available)
+    #[clap(long)]
+    pub from: Option<u64>,
+
+    /// Last height to export (default: the latest decided height)
+    #[clap(long)]
+    pub to: Option<u64>,
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct ArchiveImportCmd {
+    /// Path to the archive file to import
+    pub archive_file: PathBuf,
+}
+
+fn height<Ctx: Context>(height: u64) -> Ctx::Height {
+    Ctx::Height::ZERO.increment_by(height)
+}
+
+impl ArchiveExportCmd {
+    pub async fn run<Ctx, C, S>(&self, source: &S, codec: C) -> eyre::Result<()>
+    where
+        Ctx: Context,
+        C: Codec<RawDecidedValue<Ctx>>,
+        S: DecidedValueSource<Ctx>,
+    {
+        // Never overwrite an existing archive
+        let file = File::create_new(&self.archive_file)?;
+        let mut writer = ArchiveWriter::new(BufWriter::new(file), codec)?;
+
+        let from = self.from.map(height::<Ctx>);
+        let to = self.to.map(height::<Ctx>);
+
+        let count = archive::export(source, from, to, &mut writer).await?;
+        writer.finish()?;
+
+        info!(
+            "Exported {count} decided values to {}",
+            self.archive_file.display()
+        );
+
+        Ok(())
+    }
+}
+
+impl ArchiveImportCmd {
+    pub async fn run<Ctx, C, V, S>(
+        &self,
+        ctx: &Ctx,
+        codec: C,
+        verifier: &V,
+        sink: &mut S,
+    ) -> eyre::Result<()>
+    where
+        Ctx: Context,
+        C: Codec<RawDecidedValue<Ctx>> + Codec<Ctx::Value>,
+        V: Verifier<Ctx>,
+        S: DecidedValueSink<Ctx>,
+    {
+        let file = File::open(&self.archive_file)?;
+        let mut reader = ArchiveReader::new(BufReader::new(file), codec)?;
+
+        let count = archive::import(ctx, &mut reader, verifier, Default::default(), sink).await?;
+
+        info!(
+            "Imported {count} decided values from {}",
+            self.archive_file.display()
+        );
+
+        Ok(())
+    }
+}
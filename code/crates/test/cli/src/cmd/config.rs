@@ -0,0 +1,81 @@
+//! Configuration commands.
+//!
+//! `config check` loads the configuration of the node, including the overrides from the
+//! environment, and reports the fields which are inconsistent with each other, without
+//! starting the node. The same checks are run by `start`, which refuses to start the node
+//! if any of them fails.
+
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::{self, bail};
+use tracing::{error, info, warn};
+
+use malachitebft_app::config::{ConfigReport, NodeConfig};
+use malachitebft_test::node::Node;
+
+#[derive(Parser, Debug, Clone, PartialEq)]
+pub struct ConfigCmd {
+    #[command(subcommand)]
+    pub command: ConfigCommands,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum ConfigCommands {
+    /// Check the configuration for inconsistent fields
+    Check(ConfigCheckCmd),
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct ConfigCheckCmd {
+    /// Fail if the configuration has warnings, not only if it has errors
+    #[clap(long)]
+    pub strict: bool,
+}
+
+impl ConfigCheckCmd {
+    pub fn run<N: Node>(&self, node: &N) -> eyre::Result<()> {
+        let config = node.load_config()?;
+        let report = config.validate();
+
+        log_report(&report);
+
+        if self.strict && !report.warnings.is_empty() {
+            bail!(
+                "Configuration has {} warning(s) in strict mode",
+                report.warnings.len()
+            );
+        }
+
+        check_report(&report)?;
+
+        info!("Configuration is valid");
+
+        Ok(())
+    }
+}
+
+/// Validate the configuration of a node about to start,
+/// logging the warnings and failing if there are errors.
+pub fn check_config(config: &impl NodeConfig) -> eyre::Result<()> {
+    let report = config.validate();
+
+    log_report(&report);
+    check_report(&report)
+}
+
+fn log_report(report: &ConfigReport) {
+    for warning in &report.warnings {
+        warn!("Configuration warning: {warning}");
+    }
+
+    for error in &report.errors {
+        error!("Configuration error: {error}");
+    }
+}
+
+fn check_report(report: &ConfigReport) -> eyre::Result<()> {
+    if !report.is_valid() {
+        bail!("Configuration has {} error(s)", report.errors.len());
+    }
+
+    Ok(())
+}
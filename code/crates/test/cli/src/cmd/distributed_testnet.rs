@@ -126,6 +126,8 @@ impl DistributedTestnetCmd {
             },
             value_sync: Default::default(),
             persistent_peers_only: self.persistent_peers_only,
+            topology: Default::default(),
+            bootstrap_nodes: Vec::new(),
         };
 
         distributed_testnet(
@@ -187,7 +189,13 @@ where
 
         save_config::<N>(
             &args.get_config_file_path()?,
-            &N::make_distributed_config(i, nodes, machines.clone(), bootstrap_set_size, settings),
+            &N::make_distributed_config(
+                i,
+                nodes,
+                machines.clone(),
+                bootstrap_set_size,
+                settings.clone(),
+            ),
         )?;
 
         let priv_validator_key = node.make_private_key_file((*private_key).clone());
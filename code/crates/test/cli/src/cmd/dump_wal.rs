@@ -6,11 +6,16 @@ use malachitebft_core_types::Context;
 use tracing::{error, info};
 
 use malachitebft_app::engine::wal::{log_entries, WalCodec};
-use malachitebft_app::wal::Log;
+
+use crate::cmd::wal::open_log;
 
 #[derive(Parser, Debug, Clone, Default, PartialEq)]
 pub struct DumpWalCmd {
     pub wal_file: PathBuf,
+
+    /// Path to the file holding the key the WAL entries are encrypted with
+    #[clap(long)]
+    pub encryption_key_file: Option<PathBuf>,
 }
 
 impl DumpWalCmd {
@@ -19,17 +24,25 @@ impl DumpWalCmd {
         Ctx: Context,
         Codec: WalCodec<Ctx>,
     {
-        let mut log = Log::open(&self.wal_file)?;
+        let mut log = open_log(&self.wal_file, self.encryption_key_file.as_deref())?;
 
         let len = log.len();
+        let size = log.size_bytes().unwrap_or(0);
         let mut count = 0;
 
+        let entries = log_entries(&mut log, &codec)?;
+        let version = entries.version();
+
+        // The header is not an entry
+        let len = len - usize::from(version.has_header());
+
         info!("WAL Dump");
+        info!("- Format:  {version}");
         info!("- Entries: {len}");
-        info!("- Size:    {} bytes", log.size_bytes().unwrap_or(0));
+        info!("- Size:    {size} bytes");
         info!("Entries:");
 
-        for (idx, entry) in log_entries(&mut log, &codec)?.enumerate() {
+        for (idx, entry) in entries.enumerate() {
             count += 1;
 
             match entry {
@@ -0,0 +1,261 @@
+//! Genesis commands, for assembling a genesis file from the public keys of multiple validators.
+//!
+//! `genesis add-validator` adds a validator to a genesis file, creating the file if needed,
+//! `genesis validate` checks the validator set of a genesis file, and `genesis hash` prints
+//! the hash of a genesis file, so that validators can check they all have the same genesis.
+//!
+//! Genesis files are written in a canonical JSON encoding: the keys of every object are sorted,
+//! there is no insignificant whitespace, and the validators are sorted by descending voting power
+//! and then by ascending address. Genesis files assembled independently from the same validators,
+//! in whatever order, are therefore byte-for-byte identical. The hash of a genesis file is the
+//! SHA-256 hash of its canonical encoding, regardless of how the file itself is formatted.
+
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::{self, bail, eyre, WrapErr};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use malachitebft_core_types::{Context, PublicKey, SigningScheme, VotingPower};
+use malachitebft_test::node::Node;
+use malachitebft_test::traits::CanMakeGenesis;
+
+use crate::file::save_text;
+
+#[derive(Parser, Debug, Clone, PartialEq)]
+pub struct GenesisCmd {
+    /// Path to the genesis file (default: the genesis file in the home directory)
+    #[clap(long, global = true)]
+    pub genesis_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: GenesisCommands,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum GenesisCommands {
+    /// Add a validator to the genesis file, creating the file if it does not exist
+    AddValidator(GenesisAddValidatorCmd),
+
+    /// Check the validator set of the genesis file
+    Validate(GenesisValidateCmd),
+
+    /// Print the hash of the canonical encoding of the genesis file
+    Hash(GenesisHashCmd),
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct GenesisAddValidatorCmd {
+    /// Hex-encoded public key of the validator
+    pub public_key: String,
+
+    /// Voting power of the validator
+    #[clap(long, default_value_t = 1)]
+    pub voting_power: VotingPower,
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct GenesisValidateCmd {}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct GenesisHashCmd {}
+
+type Validators<N> = Vec<(PublicKey<<N as Node>::Context>, VotingPower)>;
+
+impl GenesisAddValidatorCmd {
+    pub fn run<N>(&self, node: &N, genesis_file: &Path) -> eyre::Result<()>
+    where
+        N: Node + CanMakeGenesis,
+    {
+        let public_key = decode_public_key::<N::Context>(&self.public_key)?;
+        let address = node.get_address(&public_key);
+
+        if self.voting_power == 0 {
+            bail!("Voting power of validator {address} must be positive");
+        }
+
+        let mut validators = if genesis_file.exists() {
+            node.genesis_validators(&load_genesis::<N>(genesis_file)?)
+        } else {
+            Vec::new()
+        };
+
+        if let Some((_, voting_power)) = validators
+            .iter()
+            .find(|(pk, _)| node.get_address(pk) == address)
+        {
+            if *voting_power != self.voting_power {
+                bail!(
+                    "Validator {address} is already in the genesis with voting power {voting_power}"
+                );
+            }
+
+            info!("Validator {address} is already in the genesis file");
+        } else {
+            validators.push((public_key, self.voting_power));
+        }
+
+        sort_validators(node, &mut validators);
+        check_validators(node, &validators)?;
+
+        let genesis = node.make_genesis(validators);
+        save_text(genesis_file, &canonical_json(&genesis)?)?;
+
+        info!(
+            "Added validator {address} with voting power {} to {}",
+            self.voting_power,
+            genesis_file.display()
+        );
+
+        Ok(())
+    }
+}
+
+impl GenesisValidateCmd {
+    pub fn run<N>(&self, node: &N, genesis_file: &Path) -> eyre::Result<()>
+    where
+        N: Node + CanMakeGenesis,
+    {
+        let genesis = load_genesis::<N>(genesis_file)?;
+        let validators = node.genesis_validators(&genesis);
+
+        check_validators(node, &validators)?;
+
+        let mut sorted = validators.clone();
+        sort_validators(node, &mut sorted);
+
+        if sorted != validators {
+            bail!("Validators are not sorted by descending voting power and ascending address");
+        }
+
+        if fs::read_to_string(genesis_file)? != canonical_json(&genesis)? {
+            warn!("Genesis file is not canonically encoded");
+        }
+
+        info!(
+            "Genesis file {} is valid, with {} validators and hash {}",
+            genesis_file.display(),
+            validators.len(),
+            hex::encode(genesis_hash(&genesis)?)
+        );
+
+        Ok(())
+    }
+}
+
+impl GenesisHashCmd {
+    pub fn run<N>(&self, _node: &N, genesis_file: &Path) -> eyre::Result<()>
+    where
+        N: Node,
+    {
+        let genesis = load_genesis::<N>(genesis_file)?;
+        println!("{}", hex::encode(genesis_hash(&genesis)?));
+
+        Ok(())
+    }
+}
+
+/// Encode a value as canonical JSON: the keys of every object are sorted,
+/// and there is no insignificant whitespace.
+pub fn canonical_json<T: Serialize>(value: &T) -> serde_json::Result<String> {
+    serde_json::to_string(&canonicalize(serde_json::to_value(value)?))
+}
+
+/// SHA-256 hash of the canonical JSON encoding of a genesis.
+pub fn genesis_hash<T: Serialize>(genesis: &T) -> serde_json::Result<[u8; 32]> {
+    Ok(Sha256::digest(canonical_json(genesis)?.as_bytes()).into())
+}
+
+/// Rebuild every object of a JSON value with its keys in sorted order,
+/// whether or not `serde_json` preserves the insertion order of keys.
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, canonicalize(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(canonicalize).collect()),
+        value => value,
+    }
+}
+
+fn load_genesis<N: Node>(genesis_file: &Path) -> eyre::Result<N::Genesis> {
+    let genesis = fs::read_to_string(genesis_file)
+        .wrap_err_with(|| format!("Failed to read {}", genesis_file.display()))?;
+
+    serde_json::from_str(&genesis)
+        .wrap_err_with(|| format!("Failed to parse {}", genesis_file.display()))
+}
+
+fn decode_public_key<Ctx: Context>(public_key: &str) -> eyre::Result<PublicKey<Ctx>> {
+    let bytes = hex::decode(public_key.trim().trim_start_matches("0x"))
+        .wrap_err("Public key is not valid hex")?;
+
+    Ctx::SigningScheme::decode_public_key(&bytes).map_err(|e| eyre!("Invalid public key: {e}"))
+}
+
+/// Sort validators by descending voting power, and then by ascending address.
+fn sort_validators<N: Node>(node: &N, validators: &mut Validators<N>) {
+    validators
+        .sort_by_cached_key(|(pk, voting_power)| (Reverse(*voting_power), node.get_address(pk)));
+}
+
+fn check_validators<N: Node>(node: &N, validators: &Validators<N>) -> eyre::Result<()> {
+    if validators.is_empty() {
+        bail!("Genesis has no validators");
+    }
+
+    let mut addresses = BTreeSet::new();
+    let mut total_voting_power: VotingPower = 0;
+
+    for (public_key, voting_power) in validators {
+        let address = node.get_address(public_key);
+
+        if *voting_power == 0 {
+            bail!("Validator {address} has no voting power");
+        }
+
+        if !addresses.insert(address.clone()) {
+            bail!("Validator {address} appears more than once");
+        }
+
+        total_voting_power = total_voting_power
+            .checked_add(*voting_power)
+            .ok_or_else(|| eyre!("Total voting power overflows"))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn canonical_encoding() {
+        let a = json!({ "b": [{ "d": 1, "c": 2 }], "a": "x" });
+        let b = json!({ "a": "x", "b": [{ "c": 2, "d": 1 }] });
+
+        assert_eq!(
+            canonical_json(&a).unwrap(),
+            r#"{"a":"x","b":[{"c":2,"d":1}]}"#
+        );
+        assert_eq!(canonical_json(&a).unwrap(), canonical_json(&b).unwrap());
+        assert_eq!(genesis_hash(&a).unwrap(), genesis_hash(&b).unwrap());
+    }
+}
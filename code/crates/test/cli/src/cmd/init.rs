@@ -101,6 +101,8 @@ impl InitCmd {
             },
             value_sync: Default::default(),
             persistent_peers_only: self.persistent_peers_only,
+            topology: Default::default(),
+            bootstrap_nodes: Vec::new(),
         };
 
         let config = N::make_config(0, 1, settings);
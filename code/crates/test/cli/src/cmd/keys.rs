@@ -0,0 +1,374 @@
+//! Key commands, for generating and inspecting the private key of a validator.
+//!
+//! `keys generate` generates a private key, at random or deterministically from a seed for tests,
+//! `keys show` prints the public key, address and peer ID derived from a private key file,
+//! and `keys address` prints the address of a private key file or of a hex-encoded public key.
+//!
+//! A private key file can be encrypted at rest with a passphrase, read from the file given
+//! with `--passphrase-file` or the `MALACHITE_KEY_PASSPHRASE_FILE` environment variable.
+//! The encryption key is derived from the passphrase with PBKDF2-HMAC-SHA256 and a random salt,
+//! and the key file is encrypted with XChaCha20-Poly1305. The node reads the passphrase from the
+//! same environment variable when it starts with an encrypted key file.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use clap::{Parser, Subcommand, ValueEnum};
+use color_eyre::eyre::{self, bail, eyre, WrapErr};
+use hmac::{Hmac, Mac};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tracing::info;
+
+use malachitebft_core_types::{Context, SigningScheme};
+use malachitebft_test::node::Node;
+use malachitebft_test::traits::{CanGeneratePrivateKey, CanMakePrivateKeyFile};
+
+use crate::file::save_text;
+
+/// Environment variable holding the path to the file containing the passphrase of the key file
+pub const PASSPHRASE_FILE_ENV: &str = "MALACHITE_KEY_PASSPHRASE_FILE";
+
+/// Key derivation function of encrypted key files
+const KDF: &str = "pbkdf2-hmac-sha256";
+
+/// Cipher of encrypted key files
+const CIPHER: &str = "xchacha20-poly1305";
+
+/// Number of PBKDF2 iterations when encrypting a key file
+const KDF_ITERATIONS: u32 = 600_000;
+
+/// Size of the salt of the key derivation, in bytes
+const SALT_SIZE: usize = 16;
+
+#[derive(Parser, Debug, Clone, PartialEq)]
+pub struct KeysCmd {
+    /// Path to the private key file (default: the private key file in the home directory)
+    #[clap(long, global = true)]
+    pub key_file: Option<PathBuf>,
+
+    /// Path to the file containing the passphrase with which the private key file is encrypted
+    #[clap(long, global = true, env = PASSPHRASE_FILE_ENV)]
+    pub passphrase_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: KeysCommands,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum KeysCommands {
+    /// Generate a private key, encrypted if a passphrase file is given
+    Generate(KeysGenerateCmd),
+
+    /// Print the public key, address and peer ID of the private key
+    Show(KeysShowCmd),
+
+    /// Print the address of the private key, or of the given public key
+    Address(KeysAddressCmd),
+}
+
+/// Signing scheme of a key
+#[derive(ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum KeyScheme {
+    #[default]
+    Ed25519,
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct KeysGenerateCmd {
+    /// Signing scheme of the key
+    #[clap(long, value_enum, default_value_t)]
+    pub scheme: KeyScheme,
+
+    /// Derive the key deterministically from the given seed, for tests only
+    #[clap(long)]
+    pub seed: Option<u64>,
+
+    /// Overwrite the key file if it exists
+    #[clap(long)]
+    pub overwrite: bool,
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct KeysShowCmd {}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct KeysAddressCmd {
+    /// Hex-encoded public key, instead of the private key file
+    #[clap(long)]
+    pub public_key: Option<String>,
+}
+
+impl KeysGenerateCmd {
+    pub fn run<N>(
+        &self,
+        node: &N,
+        key_file: &Path,
+        passphrase_file: Option<&Path>,
+    ) -> eyre::Result<()>
+    where
+        N: Node + CanGeneratePrivateKey + CanMakePrivateKeyFile,
+    {
+        if key_file.exists() && !self.overwrite {
+            bail!(
+                "{} already exists, use --overwrite to replace it",
+                key_file.display()
+            );
+        }
+
+        let private_key = match self.seed {
+            Some(seed) => node.generate_private_key(StdRng::seed_from_u64(seed)),
+            None => node.generate_private_key(OsRng),
+        };
+
+        let public_key = node.get_public_key(&private_key);
+        let contents = serde_json::to_string_pretty(&node.make_private_key_file(private_key))?;
+
+        let contents = match passphrase_file {
+            Some(passphrase_file) => {
+                let passphrase = read_passphrase(passphrase_file)?;
+                serde_json::to_string_pretty(&EncryptedKeyFile::encrypt(
+                    contents.as_bytes(),
+                    &passphrase,
+                    KDF_ITERATIONS,
+                )?)?
+            }
+            None => contents,
+        };
+
+        save_text(key_file, &contents)?;
+
+        info!(
+            address = %node.get_address(&public_key),
+            public_key = %encode_public_key::<N::Context>(&public_key),
+            encrypted = passphrase_file.is_some(),
+            "Wrote {:?} private key to {}",
+            self.scheme,
+            key_file.display()
+        );
+
+        Ok(())
+    }
+}
+
+impl KeysShowCmd {
+    pub fn run<N: Node>(
+        &self,
+        node: &N,
+        key_file: &Path,
+        passphrase_file: Option<&Path>,
+    ) -> eyre::Result<()> {
+        let private_key = node.load_private_key(read_key_file(key_file, passphrase_file)?);
+        let public_key = node.get_public_key(&private_key);
+        let peer_id = node.get_keypair(private_key).public().to_peer_id();
+
+        println!("address: {}", node.get_address(&public_key));
+        println!(
+            "public_key: {}",
+            encode_public_key::<N::Context>(&public_key)
+        );
+        println!("peer_id: {peer_id}");
+
+        Ok(())
+    }
+}
+
+impl KeysAddressCmd {
+    pub fn run<N: Node>(
+        &self,
+        node: &N,
+        key_file: &Path,
+        passphrase_file: Option<&Path>,
+    ) -> eyre::Result<()> {
+        let public_key = match &self.public_key {
+            Some(public_key) => decode_public_key::<N::Context>(public_key)?,
+            None => node
+                .get_public_key(&node.load_private_key(read_key_file(key_file, passphrase_file)?)),
+        };
+
+        println!("{}", node.get_address(&public_key));
+
+        Ok(())
+    }
+}
+
+/// Read a private key file, decrypting it with the passphrase in the given file if it is encrypted.
+pub fn read_key_file<T: DeserializeOwned>(
+    key_file: &Path,
+    passphrase_file: Option<&Path>,
+) -> eyre::Result<T> {
+    let contents = fs::read_to_string(key_file)
+        .wrap_err_with(|| format!("Failed to read {}", key_file.display()))?;
+
+    let parse_err = || format!("Failed to parse {}", key_file.display());
+
+    if let Ok(encrypted) = serde_json::from_str::<EncryptedKeyFile>(&contents) {
+        let Some(passphrase_file) = passphrase_file else {
+            bail!(
+                "{} is encrypted, set {PASSPHRASE_FILE_ENV} to the file containing its passphrase",
+                key_file.display()
+            );
+        };
+
+        let decrypted = encrypted.decrypt(&read_passphrase(passphrase_file)?)?;
+        return serde_json::from_slice(&decrypted).wrap_err_with(parse_err);
+    }
+
+    serde_json::from_str(&contents).wrap_err_with(parse_err)
+}
+
+/// Private key file encrypted with a key derived from a passphrase
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EncryptedKeyFile {
+    kdf: String,
+    iterations: u32,
+    #[serde(with = "hex::serde")]
+    salt: Vec<u8>,
+    cipher: String,
+    #[serde(with = "hex::serde")]
+    nonce: Vec<u8>,
+    #[serde(with = "hex::serde")]
+    ciphertext: Vec<u8>,
+}
+
+impl EncryptedKeyFile {
+    fn encrypt(plaintext: &[u8], passphrase: &str, iterations: u32) -> eyre::Result<Self> {
+        let mut salt = vec![0; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+        let ciphertext = cipher(passphrase, &salt, iterations)
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| eyre!("Failed to encrypt the key file"))?;
+
+        Ok(Self {
+            kdf: KDF.to_string(),
+            iterations,
+            salt,
+            cipher: CIPHER.to_string(),
+            nonce: nonce.to_vec(),
+            ciphertext,
+        })
+    }
+
+    fn decrypt(&self, passphrase: &str) -> eyre::Result<Vec<u8>> {
+        if self.kdf != KDF || self.cipher != CIPHER {
+            bail!(
+                "Unsupported key file encryption: {} with {}",
+                self.kdf,
+                self.cipher
+            );
+        }
+
+        if self.nonce.len() != 24 {
+            bail!("Invalid nonce size: {}", self.nonce.len());
+        }
+
+        cipher(passphrase, &self.salt, self.iterations)
+            .decrypt(XNonce::from_slice(&self.nonce), self.ciphertext.as_slice())
+            .map_err(|_| eyre!("Failed to decrypt the key file, the passphrase may be wrong"))
+    }
+}
+
+fn cipher(passphrase: &str, salt: &[u8], iterations: u32) -> XChaCha20Poly1305 {
+    let key = pbkdf2_sha256(passphrase.as_bytes(), salt, iterations);
+    XChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+/// PBKDF2 with HMAC-SHA256 (RFC 8018), deriving a single block of 32 bytes.
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    let prf =
+        <Hmac<Sha256> as Mac>::new_from_slice(password).expect("HMAC accepts keys of any size");
+
+    let mut mac = prf.clone();
+    mac.update(salt);
+    mac.update(&1u32.to_be_bytes());
+    let mut u: [u8; 32] = mac.finalize().into_bytes().into();
+
+    let mut key = u;
+    for _ in 1..iterations {
+        let mut mac = prf.clone();
+        mac.update(&u);
+        u = mac.finalize().into_bytes().into();
+
+        key.iter_mut().zip(u).for_each(|(k, u)| *k ^= u);
+    }
+
+    key
+}
+
+/// Read the passphrase from the given file, ignoring the trailing newline.
+fn read_passphrase(passphrase_file: &Path) -> eyre::Result<String> {
+    let passphrase = fs::read_to_string(passphrase_file)
+        .wrap_err_with(|| format!("Failed to read {}", passphrase_file.display()))?;
+
+    let passphrase = passphrase.trim_end_matches(['\r', '\n']);
+
+    if passphrase.is_empty() {
+        bail!("Passphrase in {} is empty", passphrase_file.display());
+    }
+
+    Ok(passphrase.to_string())
+}
+
+fn encode_public_key<Ctx: Context>(public_key: &malachitebft_core_types::PublicKey<Ctx>) -> String {
+    hex::encode(Ctx::SigningScheme::encode_public_key(public_key))
+}
+
+fn decode_public_key<Ctx: Context>(
+    public_key: &str,
+) -> eyre::Result<malachitebft_core_types::PublicKey<Ctx>> {
+    let bytes = hex::decode(public_key.trim().trim_start_matches("0x"))
+        .wrap_err("Public key is not valid hex")?;
+
+    Ctx::SigningScheme::decode_public_key(&bytes).map_err(|e| eyre!("Invalid public key: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pbkdf2_test_vectors() {
+        // RFC 7914, section 11
+        let key = pbkdf2_sha256(b"passwd", b"salt", 1);
+        assert_eq!(
+            hex::encode(key),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+
+        let key = pbkdf2_sha256(b"Password", b"NaCl", 80000);
+        assert_eq!(
+            hex::encode(key),
+            "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56"
+        );
+    }
+
+    #[test]
+    fn encrypted_key_file_roundtrip() {
+        let plaintext = br#""private key""#;
+        let encrypted = EncryptedKeyFile::encrypt(plaintext, "correct horse", 10).unwrap();
+
+        assert_ne!(encrypted.ciphertext, plaintext);
+        assert_eq!(encrypted.decrypt("correct horse").unwrap(), plaintext);
+        assert!(encrypted.decrypt("wrong horse").is_err());
+
+        let json = serde_json::to_string(&encrypted).unwrap();
+        assert_eq!(
+            serde_json::from_str::<EncryptedKeyFile>(&json).unwrap(),
+            encrypted
+        );
+    }
+
+    #[test]
+    fn plain_key_file_is_not_encrypted() {
+        assert!(serde_json::from_str::<EncryptedKeyFile>(r#""private key""#).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Metrics commands.
+//!
+//! `metrics dashboard` generates a Grafana dashboard tracking the progress of consensus,
+//! built on the metrics exported by the node, ready to be imported into Grafana.
+
+use std::fs;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use color_eyre::eyre;
+use serde_json::{json, Value};
+use tracing::info;
+
+/// Prefix of the consensus metrics exported by the node.
+const CONSENSUS_PREFIX: &str = "malachitebft_core_consensus";
+
+#[derive(Parser, Debug, Clone, PartialEq)]
+pub struct MetricsCmd {
+    #[command(subcommand)]
+    pub command: MetricsCommands,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum MetricsCommands {
+    /// Generate a Grafana dashboard tracking the progress of consensus
+    Dashboard(MetricsDashboardCmd),
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct MetricsDashboardCmd {
+    /// Path to the dashboard file to create (default: print to stdout)
+    #[clap(long)]
+    pub output: Option<PathBuf>,
+
+    /// UID of the Prometheus data source the dashboard queries
+    #[clap(long, default_value = "prometheus")]
+    pub datasource: String,
+}
+
+impl MetricsDashboardCmd {
+    pub fn run(&self) -> eyre::Result<()> {
+        let dashboard = serde_json::to_string_pretty(&dashboard(&self.datasource))?;
+
+        match &self.output {
+            Some(output) => {
+                fs::write(output, dashboard)?;
+                info!("Wrote consensus dashboard to {}", output.display());
+            }
+            None => println!("{dashboard}"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Grafana dashboard tracking the progress of consensus.
+pub fn dashboard(datasource: &str) -> Value {
+    let datasource = json!({ "type": "prometheus", "uid": datasource });
+
+    let panels = [
+        (
+            "Height",
+            "stat",
+            vec![(format!("max({CONSENSUS_PREFIX}_height)"), "height")],
+        ),
+        (
+            "Round",
+            "stat",
+            vec![(format!("max({CONSENSUS_PREFIX}_round)"), "round")],
+        ),
+        (
+            "Heights per minute",
+            "timeseries",
+            vec![(
+                format!("rate({CONSENSUS_PREFIX}_rounds_per_height_count[1m]) * 60"),
+                "{{job}}",
+            )],
+        ),
+        (
+            "Rounds per height (p50, p99)",
+            "timeseries",
+            vec![
+                (quantile("0.5", "rounds_per_height_bucket", "le"), "p50"),
+                (quantile("0.99", "rounds_per_height_bucket", "le"), "p99"),
+            ],
+        ),
+        (
+            "Phase duration p50, by phase and round",
+            "timeseries",
+            vec![(
+                quantile("0.5", "phase_duration_bucket", "le, phase, round"),
+                "{{phase}} (round {{round}})",
+            )],
+        ),
+        (
+            "Phase duration p99, by phase and round",
+            "timeseries",
+            vec![(
+                quantile("0.99", "phase_duration_bucket", "le, phase, round"),
+                "{{phase}} (round {{round}})",
+            )],
+        ),
+        (
+            "Time per block (p50, p99)",
+            "timeseries",
+            vec![
+                (quantile("0.5", "time_per_block_bucket", "le"), "p50"),
+                (quantile("0.99", "time_per_block_bucket", "le"), "p99"),
+            ],
+        ),
+        (
+            "Connected peers",
+            "timeseries",
+            vec![(format!("{CONSENSUS_PREFIX}_connected_peers"), "{{job}}")],
+        ),
+    ];
+
+    let panels = panels
+        .into_iter()
+        .enumerate()
+        .map(|(id, (title, kind, targets))| {
+            // Two panels per row, each half the width of the dashboard
+            let grid_pos = json!({ "h": 8, "w": 12, "x": (id % 2) * 12, "y": (id / 2) * 8 });
+
+            let targets = targets
+                .into_iter()
+                .zip('A'..)
+                .map(|((expr, legend), ref_id)| {
+                    json!({
+                        "datasource": datasource,
+                        "expr": expr,
+                        "legendFormat": legend,
+                        "refId": ref_id.to_string(),
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            json!({
+                "id": id + 1,
+                "title": title,
+                "type": kind,
+                "datasource": datasource,
+                "gridPos": grid_pos,
+                "targets": targets,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "title": "Malachite consensus progress",
+        "uid": "malachite-consensus",
+        "tags": ["malachite"],
+        "timezone": "browser",
+        "schemaVersion": 39,
+        "refresh": "10s",
+        "time": { "from": "now-30m", "to": "now" },
+        "panels": panels,
+    })
+}
+
+fn quantile(quantile: &str, metric: &str, by: &str) -> String {
+    format!("histogram_quantile({quantile}, sum by ({by}) (rate({CONSENSUS_PREFIX}_{metric}[5m])))")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dashboard_queries_consensus_metrics() {
+        let dashboard = dashboard("my-prometheus");
+
+        let panels = dashboard["panels"].as_array().unwrap();
+        assert!(!panels.is_empty());
+
+        let exprs = panels
+            .iter()
+            .flat_map(|panel| panel["targets"].as_array().unwrap())
+            .map(|target| {
+                assert_eq!(target["datasource"]["uid"], "my-prometheus");
+                target["expr"].as_str().unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        for metric in ["phase_duration_bucket", "rounds_per_height_bucket"] {
+            let metric = format!("{CONSENSUS_PREFIX}_{metric}");
+            assert!(exprs.iter().any(|expr| expr.contains(&metric)));
+        }
+    }
+}
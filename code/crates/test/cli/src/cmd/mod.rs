@@ -1,5 +1,13 @@
+pub mod archive;
+pub mod config;
 pub mod distributed_testnet;
 pub mod dump_wal;
+pub mod genesis;
 pub mod init;
+pub mod keys;
+pub mod metrics;
+pub mod node;
+pub mod signer;
 pub mod start;
 pub mod testnet;
+pub mod wal;
@@ -0,0 +1,338 @@
+//! Node commands, for migrating a validator to another machine.
+//!
+//! `node export` packages the configuration, the WAL and the sign state of a stopped node
+//! into a bundle, together with the last height, round and step at which the validator signed
+//! a message, and marks the node as migrated so that it refuses to start again, which would
+//! let the validator sign conflicting messages from both machines. `node import` installs a bundle
+//! into the home directory of the new machine, after checking that the validator key found
+//! there is the one of the bundle and that no existing state is overwritten.
+//!
+//! The validator key, and the WAL encryption key if any, are not part of the bundle
+//! and must be moved to the new machine separately.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::{self, bail, eyre, WrapErr};
+use tracing::{info, warn};
+
+use malachitebft_app::bundle::{self, BundleReader, BundleWriter, LastSigned, Manifest};
+use malachitebft_app::engine::wal::{log_entries, open_log, EncryptionKey, WalCodec};
+use malachitebft_app::sign_guard::SignState;
+use malachitebft_app::wal::Log;
+use malachitebft_config::{ConsensusConfig, WalStorageConfig};
+use malachitebft_core_types::Context;
+
+/// Name of the file marking a home directory whose node was exported to another machine.
+pub const MIGRATED_FILE: &str = "MIGRATED";
+
+#[derive(Parser, Debug, Clone, PartialEq)]
+pub struct NodeCmd {
+    #[command(subcommand)]
+    pub command: NodeCommands,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum NodeCommands {
+    /// Export the configuration and the WAL of the node to a bundle, and prevent it from starting again
+    Export(NodeExportCmd),
+
+    /// Import a bundle exported from another machine into the node
+    Import(NodeImportCmd),
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct NodeExportCmd {
+    /// Path to the bundle file to create
+    #[clap(long, short)]
+    pub output: PathBuf,
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct NodeImportCmd {
+    /// Path to the bundle file to import
+    #[clap(long, short)]
+    pub input: PathBuf,
+
+    /// Replace the existing configuration and WAL of the node, keeping a backup of them
+    #[clap(long)]
+    pub force: bool,
+}
+
+/// Fail if the node in the given home directory was exported to another machine.
+pub fn check_not_migrated(home_dir: &Path) -> eyre::Result<()> {
+    let marker = home_dir.join(MIGRATED_FILE);
+
+    if marker.exists() {
+        bail!(
+            "The node was exported to another machine, starting it here could make the validator \
+             double-sign. Remove {} only if the exported bundle will never be imported",
+            marker.display()
+        );
+    }
+
+    Ok(())
+}
+
+impl NodeExportCmd {
+    /// Export the node whose WAL is at `wal_path`, configuration at `config_file` and sign state
+    /// at `sign_state_file`, all within `home_dir`, recording the last message signed by the
+    /// validator at `address`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn run<Ctx, Codec>(
+        &self,
+        home_dir: &Path,
+        config_file: &Path,
+        wal_path: &Path,
+        sign_state_file: &Path,
+        consensus: &ConsensusConfig,
+        address: &Ctx::Address,
+        codec: Codec,
+    ) -> eyre::Result<()>
+    where
+        Ctx: Context,
+        Codec: WalCodec<Ctx>,
+    {
+        check_not_migrated(home_dir)?;
+
+        let encryption_key = consensus
+            .wal_encryption_key_file
+            .as_deref()
+            .map(EncryptionKey::from_file)
+            .transpose()?;
+
+        // Keep the WAL open until the node is marked as migrated, so that it cannot be started meanwhile
+        let mut log =
+            open_log(wal_path, consensus.wal_storage, encryption_key).wrap_err_with(|| {
+                format!(
+                    "Failed to open the WAL at {}, make sure the node is stopped",
+                    wal_path.display()
+                )
+            })?;
+
+        let entries = log_entries(log.as_mut(), &codec)?.collect::<Result<Vec<_>, _>>()?;
+
+        let sign_state = SignState::load(sign_state_file)?;
+
+        // The sign state may be ahead of the WAL if the node crashed right after signing
+        let manifest = Manifest {
+            address: address.to_string(),
+            last_signed: Ord::max(
+                bundle::last_signed::<Ctx, _>(entries, address),
+                sign_state.map(|state| state.last_signed),
+            ),
+        };
+
+        // Never overwrite an existing bundle
+        let file = File::create_new(&self.output)?;
+        let mut writer = BundleWriter::new(BufWriter::new(file), &manifest)?;
+
+        writer.add(
+            &relative_path(home_dir, config_file)?,
+            &fs::read(config_file)?,
+        )?;
+
+        if sign_state_file.exists() {
+            writer.add(
+                &relative_path(home_dir, sign_state_file)?,
+                &fs::read(sign_state_file)?,
+            )?;
+        }
+
+        let wal_files = match consensus.wal_storage {
+            WalStorageConfig::Memory => {
+                warn!("The WAL is kept in memory, only the configuration is exported");
+                Vec::new()
+            }
+            WalStorageConfig::File | WalStorageConfig::Segmented { .. } => files_at(wal_path)?,
+        };
+        for path in &wal_files {
+            writer.add(&relative_path(home_dir, path)?, &fs::read(path)?)?;
+        }
+
+        writer.finish()?;
+
+        let marker = home_dir.join(MIGRATED_FILE);
+        fs::write(&marker, format!("Exported to {}\n", self.output.display()))?;
+
+        drop(log);
+
+        info!(
+            last_signed = %describe(manifest.last_signed),
+            "Exported the configuration and {} WAL file(s) to {}",
+            wal_files.len(),
+            self.output.display()
+        );
+
+        warn!(
+            "The node will refuse to start from {} until {} is removed",
+            home_dir.display(),
+            marker.display()
+        );
+
+        Ok(())
+    }
+}
+
+impl NodeImportCmd {
+    /// Import the bundle into `home_dir`, whose validator key belongs to the validator at `address`,
+    /// and whose WAL, if any, is at `wal_path`.
+    pub fn run<Ctx>(
+        &self,
+        home_dir: &Path,
+        wal_path: &Path,
+        address: &Ctx::Address,
+    ) -> eyre::Result<()>
+    where
+        Ctx: Context,
+    {
+        let file = File::open(&self.input)?;
+        let mut reader = BundleReader::new(BufReader::new(file))?;
+        let manifest = reader.manifest().clone();
+
+        let address = address.to_string();
+        if manifest.address != address {
+            bail!(
+                "The bundle was exported by validator {}, but the validator key of this node belongs to {address}",
+                manifest.address
+            );
+        }
+
+        // Read and check the whole bundle before writing anything
+        let files = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+
+        // The WAL is replaced as a whole, so that no segment is left over from the existing one
+        let mut existing = files
+            .iter()
+            .map(|file| home_dir.join(&file.path))
+            .filter(|path| path.exists() && !path.starts_with(wal_path))
+            .collect::<Vec<_>>();
+
+        if wal_path.exists() {
+            existing.push(wal_path.to_path_buf());
+        }
+
+        if !existing.is_empty() && !self.force {
+            bail!(
+                "Importing the bundle would overwrite {}, which may hold more recent state than the bundle. \
+                 Pass --force to replace them, keeping a backup of each with a `.bak` extension",
+                list(&existing)
+            );
+        }
+
+        // Keep the WAL locked while replacing it, so that the node cannot be started meanwhile
+        let _log = if wal_path.is_file() {
+            let log = Log::open(wal_path).wrap_err_with(|| {
+                format!(
+                    "Failed to open the WAL at {}, make sure the node is stopped",
+                    wal_path.display()
+                )
+            })?;
+
+            Some(log)
+        } else {
+            None
+        };
+
+        for path in &existing {
+            let backup = backup_path(path);
+            warn!("Moving {} to {}", path.display(), backup.display());
+
+            fs::rename(path, &backup)
+                .wrap_err_with(|| format!("Failed to back up {}", path.display()))?;
+        }
+
+        for file in &files {
+            let path = home_dir.join(&file.path);
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            fs::write(&path, &file.contents)?;
+        }
+
+        // The node may have been exported from this machine before
+        let marker = home_dir.join(MIGRATED_FILE);
+        if marker.exists() {
+            fs::remove_file(&marker)?;
+        }
+
+        info!(
+            last_signed = %describe(manifest.last_signed),
+            "Imported {} file(s) from {} into {}",
+            files.len(),
+            self.input.display(),
+            home_dir.display()
+        );
+
+        Ok(())
+    }
+}
+
+/// The path of a file within the home directory, as stored in a bundle.
+fn relative_path(home_dir: &Path, path: &Path) -> eyre::Result<String> {
+    let relative = path.strip_prefix(home_dir).map_err(|_| {
+        eyre!(
+            "{} is not within the home directory {}",
+            path.display(),
+            home_dir.display()
+        )
+    })?;
+
+    let components = relative
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| eyre!("{} is not a valid UTF-8 path", path.display()))?;
+
+    Ok(components.join("/"))
+}
+
+/// The files making up the WAL at the given path: the file itself, or the segments in the directory.
+fn files_at(path: &Path) -> eyre::Result<Vec<PathBuf>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+
+        if entry.file_type()?.is_dir() {
+            files.extend(files_at(&entry.path())?);
+        } else {
+            files.push(entry.path());
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+fn list(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn describe(last_signed: Option<LastSigned>) -> String {
+    match last_signed {
+        Some(last_signed) => last_signed.to_string(),
+        None => "none".to_string(),
+    }
+}
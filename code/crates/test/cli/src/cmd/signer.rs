@@ -0,0 +1,113 @@
+//! Remote signer commands.
+//!
+//! `signer start` runs a reference soft signer, holding the validator key in memory and serving
+//! the signing requests of a node started with `--remote-signer`. It refuses to double-sign,
+//! based on the last message it signed, persisted to its state file. `signer generate-key`
+//! generates the key with which the node and the signer authenticate each other, which must
+//! be copied to both machines.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::{self, bail, WrapErr};
+use tracing::info;
+
+use malachitebft_app::sign_guard::SignGuard;
+use malachitebft_core_types::Context;
+use malachitebft_signer::{AuthKey, SignerAddress, SignerCodec, SignerListener, SignerServer};
+use malachitebft_signing::Signer;
+
+#[derive(Parser, Debug, Clone, PartialEq)]
+pub struct SignerCmd {
+    #[command(subcommand)]
+    pub command: SignerCommands,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum SignerCommands {
+    /// Serve the signing requests of a node with the validator key of the home directory
+    Start(SignerStartCmd),
+
+    /// Generate the key with which the node and the signer authenticate each other
+    GenerateKey(SignerGenerateKeyCmd),
+}
+
+#[derive(Parser, Debug, Clone, PartialEq)]
+pub struct SignerStartCmd {
+    /// Address to listen at, eg. `tcp://0.0.0.0:26659` or `unix:///var/run/signer.sock`
+    #[clap(long)]
+    pub listen: SignerAddress,
+
+    /// Path to the file containing the authentication key shared with the node
+    #[clap(long)]
+    pub auth_key_file: PathBuf,
+
+    /// Path to the file in which the last signed message is persisted
+    /// (default: `sign_state` in the home directory)
+    #[clap(long)]
+    pub state_file: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct SignerGenerateKeyCmd {
+    /// Path to the key file to create
+    #[clap(long, short)]
+    pub output: PathBuf,
+}
+
+impl SignerStartCmd {
+    /// Serve the signing requests of nodes with the given signer, until an error occurs.
+    pub async fn run<Ctx, S, C>(&self, signer: S, codec: C, state_file: &Path) -> eyre::Result<()>
+    where
+        Ctx: Context,
+        S: Signer<Ctx> + 'static,
+        C: SignerCodec<Ctx>,
+    {
+        let key = AuthKey::from_file(&self.auth_key_file).wrap_err_with(|| {
+            format!(
+                "Failed to read the authentication key from {}",
+                self.auth_key_file.display()
+            )
+        })?;
+
+        if let Some(parent) = state_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let guard = SignGuard::open(signer, state_file)?;
+        let listener = SignerListener::bind(&self.listen).await?;
+
+        SignerServer::new(guard, codec, key).run(listener).await?;
+
+        Ok(())
+    }
+}
+
+impl SignerGenerateKeyCmd {
+    pub fn run(&self) -> eyre::Result<()> {
+        if self.output.exists() {
+            bail!("{} already exists", self.output.display());
+        }
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options
+            .open(&self.output)
+            .wrap_err_with(|| format!("Failed to create {}", self.output.display()))?;
+
+        writeln!(file, "{}", AuthKey::generate().to_hex())?;
+
+        info!(
+            "Wrote authentication key to {}, copy it to both the node and the signer",
+            self.output.display()
+        );
+
+        Ok(())
+    }
+}
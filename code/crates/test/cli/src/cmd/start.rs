@@ -1,8 +1,11 @@
+use std::path::PathBuf;
+
 use clap::Parser;
 use color_eyre::eyre;
 use tracing::info;
 
 use malachitebft_config::MetricsConfig;
+use malachitebft_signer::SignerAddress;
 use malachitebft_test::node::Node;
 
 use crate::metrics;
@@ -26,6 +29,26 @@ pub struct StartCmd {
     /// a validator proof.
     #[clap(long)]
     pub validator: bool,
+
+    /// Export the tracing spans to an OpenTelemetry collector at the given OTLP/HTTP traces endpoint,
+    /// eg. `http://localhost:4318/v1/traces` for a local Jaeger instance.
+    ///
+    /// Each height is exported as a separate trace, spanning the consensus, network, WAL,
+    /// sync and host actors, with the moniker of the node as the service name.
+    #[clap(long, value_name = "URL")]
+    pub otlp_endpoint: Option<String>,
+
+    /// Sign with the remote signer listening at the given address, eg. `tcp://10.0.0.2:26659`
+    /// or `unix:///var/run/signer.sock`, instead of the validator key of the home directory.
+    ///
+    /// See the `signer start` command for a reference signer.
+    #[clap(long, value_name = "ADDRESS", requires = "remote_signer_auth_key_file")]
+    pub remote_signer: Option<SignerAddress>,
+
+    /// Path to the file containing the key with which the node and the remote signer
+    /// authenticate each other, as generated by `signer generate-key`
+    #[clap(long, value_name = "PATH", requires = "remote_signer")]
+    pub remote_signer_auth_key_file: Option<PathBuf>,
 }
 
 impl StartCmd {
@@ -45,7 +68,7 @@ pub async fn start(node: impl Node, metrics: Option<MetricsConfig>) -> eyre::Res
     // Enable Prometheus
     if let Some(metrics) = metrics {
         if metrics.enabled {
-            tokio::spawn(metrics::serve(metrics.listen_addr));
+            tokio::spawn(metrics::serve(metrics));
         }
     }
 
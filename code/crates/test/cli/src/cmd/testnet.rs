@@ -1,5 +1,7 @@
 //! Testnet command
 
+use std::fmt::Write;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::str::FromStr;
 use std::time::Duration;
@@ -8,15 +10,17 @@ use clap::Parser;
 use color_eyre::eyre::{eyre, Result};
 use tracing::info;
 
+use malachitebft_app::config::NodeConfig;
 use malachitebft_config::*;
 use malachitebft_test::node::Node;
+use malachitebft_test::topology::Topology;
 use malachitebft_test::traits::{
     CanGeneratePrivateKey, CanMakeConfig, CanMakeGenesis, CanMakePrivateKeyFile, MakeConfigSettings,
 };
 
 use crate::args::Args;
 use crate::error::Error;
-use crate::file::{save_config, save_genesis, save_priv_validator_key};
+use crate::file::{save_config, save_genesis, save_priv_validator_key, save_text};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum RuntimeFlavour {
@@ -114,6 +118,30 @@ pub struct TestnetCmd {
     /// - "quic": QUIC
     #[clap(short, long, default_value = "tcp", verbatim_doc_comment)]
     pub transport: TransportProtocol,
+
+    /// Topology formed by the persistent peers of the nodes
+    /// Possible values:
+    /// - "full": Every node is connected to every other node (default)
+    /// - "ring": Every node is connected to its two neighbours
+    /// - "star": Every node is connected to the first node
+    /// - "random:N": Every node is connected to at least N random nodes
+    #[clap(long, default_value = "full", verbatim_doc_comment)]
+    pub topology: Topology,
+
+    /// Comma-separated indices of the nodes used to bootstrap discovery
+    /// If set and discovery is enabled, every node only has the bootstrap nodes as
+    /// persistent peers and finds the other nodes through discovery, ignoring the topology
+    #[clap(long, value_delimiter = ',', verbatim_doc_comment)]
+    pub bootstrap_nodes: Vec<usize>,
+
+    /// Generate a `docker-compose.yaml` running the nodes and a Prometheus instance
+    /// scraping their metrics, along with its `prometheus.yml` configuration
+    #[clap(long, verbatim_doc_comment)]
+    pub docker_compose: bool,
+
+    /// Docker image to run the nodes with, which must provide the `malachitebft-test-app` binary
+    #[clap(long, default_value = "malachitebft-test-app:latest")]
+    pub docker_image: String,
 }
 
 impl TestnetCmd {
@@ -147,10 +175,28 @@ impl TestnetCmd {
             },
             value_sync: Default::default(),
             persistent_peers_only: self.persistent_peers_only,
+            topology: self.topology,
+            bootstrap_nodes: self.bootstrap_nodes.clone(),
         };
 
-        testnet(node, self.nodes, home_dir, self.deterministic, settings)
-            .map_err(|e| eyre!("Failed to generate testnet configuration: {:?}", e))
+        if let Some(index) = self.bootstrap_nodes.iter().find(|&&i| i >= self.nodes) {
+            return Err(eyre!(
+                "Invalid bootstrap node {index}, the testnet only has {} nodes",
+                self.nodes
+            ));
+        }
+
+        let docker_image = self.docker_compose.then_some(self.docker_image.as_str());
+
+        testnet(
+            node,
+            self.nodes,
+            home_dir,
+            self.deterministic,
+            settings,
+            docker_image,
+        )
+        .map_err(|e| eyre!("Failed to generate testnet configuration: {:?}", e))
     }
 }
 
@@ -160,6 +206,7 @@ pub fn testnet<N>(
     home_dir: &Path,
     deterministic: bool,
     settings: MakeConfigSettings,
+    docker_image: Option<&str>,
 ) -> std::result::Result<(), Error>
 where
     N: Node + CanMakeConfig + CanMakePrivateKeyFile + CanGeneratePrivateKey + CanMakeGenesis,
@@ -172,6 +219,8 @@ where
 
     let genesis = crate::new::generate_genesis(node, public_keys, deterministic);
 
+    let mut metrics_addrs = Vec::new();
+
     for (i, private_key) in private_keys.iter().enumerate().take(nodes) {
         // Use home directory `home_dir/<index>`
         let node_home_dir = home_dir.join(i.to_string());
@@ -189,10 +238,12 @@ where
         };
 
         // Save config
-        save_config::<N>(
-            &args.get_config_file_path()?,
-            &N::make_config(i, nodes, settings),
-        )?;
+        let config = N::make_config(i, nodes, settings.clone());
+        save_config::<N>(&args.get_config_file_path()?, &config)?;
+
+        if config.metrics().enabled {
+            metrics_addrs.push(config.metrics().listen_addr);
+        }
 
         // Save private key
         let priv_validator_key = node.make_private_key_file((*private_key).clone());
@@ -206,5 +257,73 @@ where
         save_genesis(node, &args.get_genesis_file_path()?, &genesis)?;
     }
 
+    if let Some(image) = docker_image {
+        info!(home = %home_dir.display(), "Generating docker-compose configuration...");
+
+        save_text(
+            &home_dir.join("docker-compose.yaml"),
+            &docker_compose(image, nodes),
+        )?;
+
+        save_text(
+            &home_dir.join("prometheus.yml"),
+            &prometheus_config(&metrics_addrs),
+        )?;
+    }
+
     Ok(())
 }
+
+/// Generate a docker-compose file running every node of the testnet, along with Prometheus.
+///
+/// Containers share the network of the host, so that the addresses
+/// of the generated configurations can be used as they are.
+fn docker_compose(image: &str, nodes: usize) -> String {
+    let mut compose = String::from("services:\n");
+
+    for i in 0..nodes {
+        let _ = write!(
+            compose,
+            r#"  node{i}:
+    image: {image}
+    container_name: node{i}
+    network_mode: host
+    volumes:
+      - ./{i}:/node
+    command: ["malachitebft-test-app", "start", "--home", "/node"]
+"#
+        );
+    }
+
+    compose.push_str(
+        r#"  prometheus:
+    image: prom/prometheus:latest
+    container_name: prometheus
+    network_mode: host
+    volumes:
+      - ./prometheus.yml:/etc/prometheus/prometheus.yml:ro
+"#,
+    );
+
+    compose
+}
+
+/// Generate a Prometheus configuration scraping the metrics of the given addresses.
+fn prometheus_config(metrics_addrs: &[SocketAddr]) -> String {
+    let targets = metrics_addrs
+        .iter()
+        .map(|addr| format!("'{addr}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        r#"global:
+  scrape_interval: 5s
+
+scrape_configs:
+  - job_name: malachite
+    static_configs:
+      - targets: [{targets}]
+"#
+    )
+}
@@ -0,0 +1,676 @@
+//! WAL commands, for inspecting and replaying a WAL file offline.
+//!
+//! These commands are meant for debugging consensus failures from the field:
+//! `wal inspect` lists the entries of a WAL file and verifies their signatures,
+//! while `wal replay` feeds them through a fresh consensus state to reproduce
+//! the behavior of the node which wrote them.
+//!
+//! `wal migrate` rewrites a WAL file written by a previous release in the current format,
+//! as a node does on its own when it opens the WAL.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::{self, eyre};
+use tracing::{debug, error, info, warn};
+
+use malachitebft_app::consensus::{
+    process, Effect, Error as ConsensusError, Input, Params, Resumable, Resume, SignedConsensusMsg,
+    State, WalEntry,
+};
+use malachitebft_app::engine::wal::{log_entries, migrate_log, FormatVersion, WalCodec};
+use malachitebft_app::wal::{EncryptionKey, Log};
+use malachitebft_core_types::{
+    Context, Height, Proposal, Validator, ValidatorSet, Value, ValueOrigin, Vote,
+};
+use malachitebft_metrics::Metrics;
+use malachitebft_signing::{Signer, Verifier, VerifierExt};
+
+#[derive(Parser, Debug, Clone, PartialEq)]
+pub struct WalCmd {
+    #[command(subcommand)]
+    pub command: WalCommands,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum WalCommands {
+    /// List the entries of a WAL file and verify their signatures
+    Inspect(WalInspectCmd),
+
+    /// Replay the entries of a WAL file through a fresh consensus state
+    Replay(WalReplayCmd),
+
+    /// Rewrite a WAL file written by a previous release in the current format
+    Migrate(WalMigrateCmd),
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct WalInspectCmd {
+    /// Path to the WAL file
+    pub wal_file: PathBuf,
+
+    /// Do not verify the signatures of the entries
+    #[clap(long)]
+    pub no_verify: bool,
+
+    /// Path to the file holding the key the WAL entries are encrypted with
+    #[clap(long)]
+    pub encryption_key_file: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct WalReplayCmd {
+    /// Path to the WAL file
+    pub wal_file: PathBuf,
+
+    /// Height to replay the entries at (default: the height recorded in the WAL)
+    #[clap(long)]
+    pub height: Option<u64>,
+
+    /// Stop at the first entry which fails to replay
+    #[clap(long)]
+    pub stop_on_error: bool,
+
+    /// Path to the file holding the key the WAL entries are encrypted with
+    #[clap(long)]
+    pub encryption_key_file: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug, Clone, Default, PartialEq)]
+pub struct WalMigrateCmd {
+    /// Path to the WAL file, which must not be in use by a running node
+    pub wal_file: PathBuf,
+
+    /// Path to the file holding the key the WAL entries are encrypted with
+    #[clap(long)]
+    pub encryption_key_file: Option<PathBuf>,
+}
+
+/// Outcome of the verification of the signature of a WAL entry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SignatureStatus {
+    /// The entry is not signed (timeouts and proposed values)
+    Unsigned,
+    /// Verification was skipped
+    Skipped,
+    /// The signature is valid
+    Valid,
+    /// The signature is invalid
+    Invalid,
+    /// The signer is not part of the validator set
+    UnknownSigner,
+}
+
+/// Open the WAL at the given path, decrypting its entries with the key in the given file, if any.
+pub fn open_log(wal_file: &Path, encryption_key_file: Option<&Path>) -> eyre::Result<Log> {
+    let mut log = Log::open(wal_file)?;
+
+    if let Some(key_file) = encryption_key_file {
+        log.enable_encryption(EncryptionKey::from_file(key_file)?)?;
+    }
+
+    Ok(log)
+}
+
+/// The height recorded in the WAL, ie. the height the node was at when it wrote the entries.
+fn wal_height<Ctx: Context>(log: &Log) -> Ctx::Height {
+    Ctx::Height::ZERO.increment_by(log.sequence())
+}
+
+/// Short description of a WAL entry: its kind, round and signer.
+fn describe<Ctx: Context>(entry: &WalEntry<Ctx>) -> String {
+    match entry {
+        WalEntry::ConsensusMsg(SignedConsensusMsg::Vote(vote)) => format!(
+            "{:?} height={} round={} value={:?} signer={}",
+            vote.vote_type(),
+            vote.height(),
+            vote.round(),
+            vote.value(),
+            vote.validator_address()
+        ),
+        WalEntry::ConsensusMsg(SignedConsensusMsg::Proposal(proposal)) => format!(
+            "Proposal height={} round={} pol_round={} value={:?} signer={}",
+            proposal.height(),
+            proposal.round(),
+            proposal.pol_round(),
+            proposal.value().id(),
+            proposal.validator_address()
+        ),
+        WalEntry::Timeout(timeout) => {
+            format!("Timeout kind={:?} round={}", timeout.kind, timeout.round)
+        }
+        WalEntry::ProposedValue(value) => format!(
+            "ProposedValue height={} round={} valid_round={} value={:?} proposer={} validity={:?}",
+            value.height,
+            value.round,
+            value.valid_round,
+            value.value.id(),
+            value.proposer,
+            value.validity
+        ),
+    }
+}
+
+/// Verify the signature of a WAL entry against the given validator set.
+async fn verify<Ctx, V>(
+    entry: &WalEntry<Ctx>,
+    validator_set: &Ctx::ValidatorSet,
+    verifier: &V,
+) -> eyre::Result<SignatureStatus>
+where
+    Ctx: Context,
+    V: Verifier<Ctx>,
+{
+    let WalEntry::ConsensusMsg(msg) = entry else {
+        return Ok(SignatureStatus::Unsigned);
+    };
+
+    let signer = match msg {
+        SignedConsensusMsg::Vote(vote) => vote.validator_address(),
+        SignedConsensusMsg::Proposal(proposal) => proposal.validator_address(),
+    };
+
+    let Some(validator) = validator_set.get_by_address(signer) else {
+        return Ok(SignatureStatus::UnknownSigner);
+    };
+
+    let result = match msg {
+        SignedConsensusMsg::Vote(vote) => {
+            verifier
+                .verify_signed_vote(&vote.message, &vote.signature, validator.public_key())
+                .await?
+        }
+        SignedConsensusMsg::Proposal(proposal) => {
+            verifier
+                .verify_signed_proposal(
+                    &proposal.message,
+                    &proposal.signature,
+                    validator.public_key(),
+                )
+                .await?
+        }
+    };
+
+    if result.is_valid() {
+        Ok(SignatureStatus::Valid)
+    } else {
+        Ok(SignatureStatus::Invalid)
+    }
+}
+
+impl WalInspectCmd {
+    pub async fn run<Ctx, Codec, V>(
+        &self,
+        codec: Codec,
+        validator_set: &Ctx::ValidatorSet,
+        verifier: &V,
+    ) -> eyre::Result<()>
+    where
+        Ctx: Context,
+        Codec: WalCodec<Ctx>,
+        V: Verifier<Ctx>,
+    {
+        let mut log = open_log(&self.wal_file, self.encryption_key_file.as_deref())?;
+
+        let height = wal_height::<Ctx>(&log);
+        let size = log.size_bytes().unwrap_or(0);
+        let len = log.len();
+
+        let entries = log_entries(&mut log, &codec)?;
+        let version = entries.version();
+
+        // The header is not an entry
+        let len = len - usize::from(version.has_header());
+
+        info!("WAL");
+        info!("- Height:  {height}");
+        info!("- Format:  {version}");
+        info!("- Entries: {len}");
+        info!("- Size:    {size} bytes");
+        info!("Entries:");
+
+        let mut count = 0;
+        let mut corrupted = 0;
+        let mut statuses = BTreeMap::<SignatureStatus, usize>::new();
+
+        for (idx, entry) in entries.enumerate() {
+            count += 1;
+
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    corrupted += 1;
+                    error!("- #{idx}: Error decoding WAL entry: {e}");
+                    continue;
+                }
+            };
+
+            let status = if self.no_verify {
+                SignatureStatus::Skipped
+            } else {
+                verify(&entry, validator_set, verifier).await?
+            };
+
+            *statuses.entry(status).or_default() += 1;
+
+            match status {
+                SignatureStatus::Invalid | SignatureStatus::UnknownSigner => {
+                    warn!("- #{idx}: {} signature={status:?}", describe(&entry))
+                }
+                _ => info!("- #{idx}: {} signature={status:?}", describe(&entry)),
+            }
+        }
+
+        info!("Summary:");
+        info!("- Decoded:   {}", count - corrupted);
+        info!("- Corrupted: {corrupted}");
+        for (status, count) in &statuses {
+            info!("- {status:?}: {count}");
+        }
+
+        if count != len {
+            error!("Expected {len} entries, but found {count} entries");
+        }
+
+        let failed = statuses
+            .iter()
+            .filter(|(status, _)| {
+                matches!(
+                    status,
+                    SignatureStatus::Invalid | SignatureStatus::UnknownSigner
+                )
+            })
+            .map(|(_, count)| count)
+            .sum::<usize>();
+
+        if failed > 0 || corrupted > 0 {
+            return Err(eyre!(
+                "{failed} entries failed signature verification and {corrupted} entries are corrupted"
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl WalReplayCmd {
+    /// Replay the WAL entries through a fresh consensus state built from the given parameters,
+    /// logging the effects emitted by consensus along the way.
+    ///
+    /// The given signer is used for the votes and proposals of the node itself,
+    /// as identified by the address in `params`.
+    pub async fn run<Ctx, Codec, V, S>(
+        &self,
+        ctx: Ctx,
+        codec: Codec,
+        params: Params<Ctx>,
+        validator_set: Ctx::ValidatorSet,
+        verifier: &V,
+        signer: &S,
+    ) -> eyre::Result<()>
+    where
+        Ctx: Context,
+        Codec: WalCodec<Ctx>,
+        V: Verifier<Ctx>,
+        S: Signer<Ctx>,
+    {
+        let mut log = open_log(&self.wal_file, self.encryption_key_file.as_deref())?;
+
+        let height = match self.height {
+            Some(height) => Ctx::Height::ZERO.increment_by(height),
+            None => wal_height::<Ctx>(&log),
+        };
+
+        let entries = log_entries(&mut log, &codec)?.collect::<Vec<_>>();
+
+        info!(%height, "Replaying {} WAL entries", entries.len());
+
+        let metrics = Metrics::new();
+        let mut state = State::new(
+            ctx.clone(),
+            height,
+            validator_set.clone(),
+            params,
+            entries.len().max(1),
+            entries.len().max(1),
+        );
+
+        let mut handler = Handler {
+            ctx,
+            verifier,
+            signer,
+            effects: BTreeMap::new(),
+        };
+
+        let result: Result<(), ConsensusError<Ctx>> = process!(
+            input: Input::StartHeight(height, validator_set.clone(), false, None),
+            state: &mut state,
+            metrics: &metrics,
+            with: effect => handler.handle(effect).await
+        );
+
+        result.map_err(|e| eyre!("Failed to start height {height}: {e}"))?;
+
+        let mut failed = 0;
+
+        for (idx, entry) in entries.into_iter().enumerate() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    error!("#{idx}: Corrupted WAL entry, stopping replay: {e}");
+                    failed += 1;
+                    break;
+                }
+            };
+
+            info!("#{idx}: Replaying {}", describe(&entry));
+
+            let input = match entry {
+                WalEntry::ConsensusMsg(SignedConsensusMsg::Vote(vote)) => Input::Vote(vote),
+                WalEntry::ConsensusMsg(SignedConsensusMsg::Proposal(proposal)) => {
+                    Input::Proposal(proposal)
+                }
+                WalEntry::Timeout(timeout) => Input::TimeoutElapsed(timeout),
+                WalEntry::ProposedValue(value) => {
+                    Input::ProposedValue(value, ValueOrigin::Consensus)
+                }
+            };
+
+            let result: Result<(), ConsensusError<Ctx>> = process!(
+                input: input,
+                state: &mut state,
+                metrics: &metrics,
+                with: effect => handler.handle(effect).await
+            );
+
+            if let Err(e) = result {
+                error!("#{idx}: Error when replaying entry: {e}");
+                failed += 1;
+
+                if self.stop_on_error {
+                    break;
+                }
+            }
+        }
+
+        info!("Replay done");
+        info!("- Height: {}", state.height());
+        info!("- Round:  {}", state.round());
+
+        match state.decided_value() {
+            Some((round, value)) => info!("- Decided: {:?} at round {round}", value.id()),
+            None => info!("- Decided: none"),
+        }
+
+        info!("Effects:");
+        for (effect, count) in &handler.effects {
+            info!("- {effect}: {count}");
+        }
+
+        state.print_state();
+
+        if failed > 0 {
+            return Err(eyre!("{failed} entries failed to replay"));
+        }
+
+        Ok(())
+    }
+}
+
+impl WalMigrateCmd {
+    /// Rewrite the WAL in the current format, if it was written in the format of a previous release.
+    pub fn run<Ctx, Codec>(&self, codec: Codec) -> eyre::Result<()>
+    where
+        Ctx: Context,
+        Codec: WalCodec<Ctx>,
+    {
+        let mut log = open_log(&self.wal_file, self.encryption_key_file.as_deref())?;
+
+        let version = migrate_log(&mut log, &codec)?;
+
+        if version == FormatVersion::CURRENT {
+            info!("WAL is already in the current format ({version}), nothing to migrate");
+        } else {
+            info!(
+                "Migrated {} WAL entries from {version} to {}",
+                log.len() - 1,
+                FormatVersion::CURRENT
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Handles the effects emitted by consensus during a replay.
+///
+/// Nothing is sent to the network, the application or the WAL,
+/// effects are only logged and counted.
+struct Handler<'a, Ctx, V, S> {
+    ctx: Ctx,
+    verifier: &'a V,
+    signer: &'a S,
+    effects: BTreeMap<&'static str, usize>,
+}
+
+impl<Ctx, V, S> Handler<'_, Ctx, V, S>
+where
+    Ctx: Context,
+    V: Verifier<Ctx>,
+    S: Signer<Ctx>,
+{
+    fn record(&mut self, name: &'static str) {
+        *self.effects.entry(name).or_default() += 1;
+    }
+
+    async fn handle(&mut self, effect: Effect<Ctx>) -> eyre::Result<Resume<Ctx>> {
+        match effect {
+            Effect::CancelAllTimeouts(r) => {
+                self.record("CancelAllTimeouts");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::CancelTimeout(timeout, r) => {
+                self.record("CancelTimeout");
+                debug!(?timeout, "Cancel timeout");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::ScheduleTimeout(timeout, r) => {
+                self.record("ScheduleTimeout");
+                debug!(?timeout, "Schedule timeout");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::StartRound(height, round, proposer, role, r) => {
+                self.record("StartRound");
+                info!(%height, %round, %proposer, ?role, "Started round");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::PublishConsensusMsg(msg, r) => {
+                self.record("PublishConsensusMsg");
+                info!("Publish {}", describe(&WalEntry::ConsensusMsg(msg)));
+                Ok(r.resume_with(()))
+            }
+
+            Effect::PublishLivenessMsg(msg, r) => {
+                self.record("PublishLivenessMsg");
+                debug!(?msg, "Publish liveness message");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::RepublishVote(vote, r) => {
+                self.record("RepublishVote");
+                debug!(?vote, "Republish vote");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::RepublishRoundCertificate(certificate, r) => {
+                self.record("RepublishRoundCertificate");
+                debug!(?certificate, "Republish round certificate");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::GetValue(height, round, _timeout, r) => {
+                self.record("GetValue");
+                info!(%height, %round, "Consensus requested a value to propose");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::RestreamProposal(height, round, valid_round, _, value_id, r) => {
+                self.record("RestreamProposal");
+                info!(%height, %round, %valid_round, ?value_id, "Restream proposal");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::ValidateValue(height, round, _, proposer, value, r) => {
+                self.record("ValidateValue");
+                info!(%height, %round, %proposer, value = ?value.id(), "Consensus requested validation of a value");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::ValidSyncValue(_, _, r) => {
+                self.record("ValidSyncValue");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::InvalidSyncValue(_, _, _, r) => {
+                self.record("InvalidSyncValue");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::FutureHeightObserved(height, _, r) => {
+                self.record("FutureHeightObserved");
+                info!(%height, "Validators observed voting at a higher height");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::Decide(certificate, _, r) => {
+                self.record("Decide");
+                info!(
+                    height = %certificate.height,
+                    round = %certificate.round,
+                    value = ?certificate.value_id,
+                    signatures = certificate.commit_signatures.len(),
+                    "Decided"
+                );
+                Ok(r.resume_with(()))
+            }
+
+            Effect::Finalize(certificate, _, _, r) => {
+                self.record("Finalize");
+                info!(height = %certificate.height, "Finalized");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::SignVote(vote, r) => {
+                self.record("SignVote");
+                Ok(r.resume_with(self.signer.sign_vote(vote).await?))
+            }
+
+            Effect::SignProposal(proposal, r) => {
+                self.record("SignProposal");
+                Ok(r.resume_with(self.signer.sign_proposal(proposal).await?))
+            }
+
+            Effect::VerifySignature(msg, pk, r) => {
+                use malachitebft_app::consensus::ConsensusMsg as Msg;
+
+                self.record("VerifySignature");
+
+                let result = match msg.message {
+                    Msg::Vote(v) => {
+                        self.verifier
+                            .verify_signed_vote(&v, &msg.signature, &pk)
+                            .await?
+                    }
+                    Msg::Proposal(p) => {
+                        self.verifier
+                            .verify_signed_proposal(&p, &msg.signature, &pk)
+                            .await?
+                    }
+                };
+
+                if result.is_invalid() {
+                    warn!("Invalid signature");
+                }
+
+                Ok(r.resume_with(result.is_valid()))
+            }
+
+            Effect::VerifyCommitCertificate(certificate, validator_set, thresholds, r) => {
+                self.record("VerifyCommitCertificate");
+                let result = self
+                    .verifier
+                    .verify_commit_certificate(&self.ctx, &certificate, &validator_set, thresholds)
+                    .await;
+                Ok(r.resume_with(result))
+            }
+
+            Effect::VerifyCommitCertificates(certificates, validator_set, thresholds, r) => {
+                self.record("VerifyCommitCertificates");
+                let results = self
+                    .verifier
+                    .verify_commit_certificates(
+                        &self.ctx,
+                        &certificates,
+                        &validator_set,
+                        thresholds,
+                    )
+                    .await;
+                Ok(r.resume_with(results))
+            }
+
+            Effect::VerifyPolkaCertificate(certificate, validator_set, thresholds, r) => {
+                self.record("VerifyPolkaCertificate");
+                let result = self
+                    .verifier
+                    .verify_polka_certificate(&self.ctx, &certificate, &validator_set, thresholds)
+                    .await;
+                Ok(r.resume_with(result))
+            }
+
+            Effect::VerifyRoundCertificate(certificate, validator_set, thresholds, r) => {
+                self.record("VerifyRoundCertificate");
+                let result = self
+                    .verifier
+                    .verify_round_certificate(&self.ctx, &certificate, &validator_set, thresholds)
+                    .await;
+                Ok(r.resume_with(result))
+            }
+
+            Effect::WalAppend(_, _, r) => {
+                self.record("WalAppend");
+                Ok(r.resume_with(()))
+            }
+
+            Effect::ExtendVote(_, _, _, r) => {
+                // Vote extensions are provided by the application, which is not available here
+                self.record("ExtendVote");
+                Ok(r.resume_with(None))
+            }
+
+            Effect::VerifyVoteExtension(_, _, _, signed_extension, pk, r) => {
+                self.record("VerifyVoteExtension");
+
+                let result = self
+                    .verifier
+                    .verify_signed_vote_extension(
+                        &signed_extension.message,
+                        &signed_extension.signature,
+                        &pk,
+                    )
+                    .await?;
+
+                if result.is_invalid() {
+                    use malachitebft_app::consensus::VoteExtensionError;
+                    return Ok(r.resume_with(Err(VoteExtensionError::InvalidSignature)));
+                }
+
+                Ok(r.resume_with(Ok(())))
+            }
+        }
+    }
+}
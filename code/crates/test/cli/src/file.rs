@@ -40,6 +40,11 @@ pub fn save_priv_validator_key<N: Node>(
     )
 }
 
+/// Save a plain text file, eg. a docker-compose or Prometheus configuration
+pub fn save_text(path: &Path, data: &str) -> Result<(), Error> {
+    save(path, data)
+}
+
 fn save(path: &Path, data: &str) -> Result<(), Error> {
     use std::io::Write;
 
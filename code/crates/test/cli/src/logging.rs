@@ -1,8 +1,14 @@
 use std::sync::OnceLock;
 
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::{SdkTracerProvider, Tracer};
+use opentelemetry_sdk::Resource;
 use tracing::error;
 use tracing_appender::non_blocking::WorkerGuard;
+use tracing_opentelemetry::OpenTelemetryLayer;
 use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{fmt, reload, Registry};
 
@@ -37,11 +43,54 @@ fn reload_env_filter(env_filter: EnvFilter) {
     }
 }
 
+/// Export of the tracing spans to an OpenTelemetry collector, over OTLP/HTTP.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OtlpConfig {
+    /// URL of the OTLP/HTTP traces endpoint of the collector, eg. `http://localhost:4318/v1/traces`
+    pub endpoint: String,
+
+    /// Name of the service the spans are reported under, eg. the moniker of the node
+    pub service_name: String,
+}
+
+/// Drop guard returned by [`init`] and [`init_with_otlp`].
+///
+/// Flushes any remaining logs, and exports any remaining spans, when dropped.
+pub struct LogGuard {
+    _worker: WorkerGuard,
+    tracer_provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for LogGuard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.tracer_provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to export the remaining spans: {e}");
+            }
+        }
+    }
+}
+
 /// Initialize logging.
 ///
 /// Returns a drop guard responsible for flushing any remaining logs when the program terminates.
 /// The guard must be assigned to a binding that is not _, as _ will result in the guard being dropped immediately.
-pub fn init(log_level: LogLevel, log_format: LogFormat) -> WorkerGuard {
+pub fn init(log_level: LogLevel, log_format: LogFormat) -> LogGuard {
+    init_with_otlp(log_level, log_format, None)
+}
+
+/// Initialize logging, and export the tracing spans over OTLP if `otlp` is set.
+///
+/// Spans are subject to the same filter as logs. They are exported in batches from a background
+/// thread, so that the export does not require a Tokio runtime.
+///
+/// Returns a drop guard responsible for flushing any remaining logs and spans when the program terminates.
+/// The guard must be assigned to a binding that is not _, as _ will result in the guard being dropped immediately.
+pub fn init_with_otlp(
+    log_level: LogLevel,
+    log_format: LogFormat,
+    otlp: Option<OtlpConfig>,
+) -> LogGuard {
     let log_level = if let Ok(rust_log) = std::env::var("RUST_LOG") {
         rust_log
     } else {
@@ -68,23 +117,62 @@ pub fn init(log_level: LogLevel, log_format: LogFormat) -> WorkerGuard {
         .with_ansi(enable_ansi())
         .with_thread_ids(false);
 
+    let tracer_provider = otlp.and_then(|otlp| match build_tracer_provider(&otlp) {
+        Ok(provider) => Some(provider),
+        Err(e) => {
+            eprintln!("Failed to set up the OTLP exporter, spans will not be exported: {e}");
+            None
+        }
+    });
+
     // There must be a better way to use conditionals in the builder pattern.
     match log_format {
         LogFormat::Plaintext => {
             tracing_subscriber::registry()
                 .with(reload_filter)
                 .with(fmt_layer)
+                .with(otlp_layer(tracer_provider.as_ref()))
                 .init();
         }
         LogFormat::Json => {
             tracing_subscriber::registry()
                 .with(reload_filter)
                 .with(fmt_layer.json())
+                .with(otlp_layer(tracer_provider.as_ref()))
                 .init();
         }
     };
 
-    guard
+    LogGuard {
+        _worker: guard,
+        tracer_provider,
+    }
+}
+
+fn build_tracer_provider(
+    otlp: &OtlpConfig,
+) -> Result<SdkTracerProvider, opentelemetry_otlp::ExporterBuildError> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(&otlp.endpoint)
+        .build()?;
+
+    let resource = Resource::builder()
+        .with_service_name(otlp.service_name.clone())
+        .build();
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource)
+        .build())
+}
+
+fn otlp_layer<S>(provider: Option<&SdkTracerProvider>) -> Option<OpenTelemetryLayer<S, Tracer>>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    provider
+        .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer("malachitebft")))
 }
 
 /// Checks if output is going to a terminal.
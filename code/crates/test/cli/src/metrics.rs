@@ -1,26 +1,65 @@
 use std::io;
+use std::sync::Arc;
 
+use axum::extract::{Request, State};
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use axum::http::StatusCode;
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
 use axum::routing::get;
 use axum::Router;
-use tokio::net::{TcpListener, ToSocketAddrs};
+use axum_server::tls_rustls::RustlsConfig;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use tokio::net::TcpListener;
 use tracing::{error, info};
 
 use malachitebft_app::metrics::export;
+use malachitebft_config::{BasicAuthConfig, MetricsConfig};
 
+/// Serve the metrics as configured, over HTTPS if TLS is configured and behind
+/// basic authentication if credentials are configured.
+///
+/// Refuses to serve the metrics on an address other than loopback without authentication,
+/// unless `allow_unauthenticated` is set, see [`MetricsConfig::validate`].
 #[tracing::instrument(name = "metrics", skip_all)]
-pub async fn serve(listen_addr: impl ToSocketAddrs) {
-    if let Err(e) = inner(listen_addr).await {
+pub async fn serve(config: MetricsConfig) {
+    if let Err(e) = config.validate() {
+        error!("Not serving metrics: {e}");
+        return;
+    }
+
+    if let Err(e) = inner(config).await {
         error!("Metrics server failed: {e}");
     }
 }
 
-async fn inner(listen_addr: impl ToSocketAddrs) -> io::Result<()> {
-    let app = Router::new().route("/metrics", get(get_metrics));
-    let listener = TcpListener::bind(listen_addr).await?;
-    let local_addr = listener.local_addr()?;
+async fn inner(config: MetricsConfig) -> io::Result<()> {
+    let mut app = Router::new().route("/metrics", get(get_metrics));
+
+    if let Some(basic_auth) = &config.basic_auth {
+        let expected = Arc::new(Credentials::new(basic_auth));
+        app = app.layer(middleware::from_fn_with_state(expected, authenticate));
+    }
+
+    match &config.tls {
+        Some(tls) => {
+            let rustls = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path).await?;
+
+            info!(address = %config.listen_addr, "Serving metrics over HTTPS");
+            axum_server::bind_rustls(config.listen_addr, rustls)
+                .serve(app.into_make_service())
+                .await?;
+        }
+        None => {
+            let listener = TcpListener::bind(config.listen_addr).await?;
+            let local_addr = listener.local_addr()?;
 
-    info!(address = %local_addr, "Serving metrics");
-    axum::serve(listener, app).await?;
+            info!(address = %local_addr, "Serving metrics");
+            axum::serve(listener, app).await?;
+        }
+    }
 
     Ok(())
 }
@@ -30,3 +69,60 @@ async fn get_metrics() -> String {
     export(&mut buf);
     buf
 }
+
+/// Digest of the expected `Authorization` header, compared against the digest of the header
+/// of each request so that the comparison takes the same time whatever the header.
+struct Credentials([u8; 32]);
+
+impl Credentials {
+    fn new(basic_auth: &BasicAuthConfig) -> Self {
+        let encoded = BASE64.encode(format!("{}:{}", basic_auth.username, basic_auth.password));
+        Self(Sha256::digest(format!("Basic {encoded}")).into())
+    }
+
+    fn matches(&self, header: &[u8]) -> bool {
+        let digest: [u8; 32] = Sha256::digest(header).into();
+        digest == self.0
+    }
+}
+
+async fn authenticate(
+    State(expected): State<Arc<Credentials>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let authorized = request
+        .headers()
+        .get(AUTHORIZATION)
+        .is_some_and(|header| expected.matches(header.as_bytes()));
+
+    if authorized {
+        next.run(request).await
+    } else {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(WWW_AUTHENTICATE, r#"Basic realm="metrics""#)],
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_match_basic_auth_header() {
+        let credentials = Credentials::new(&BasicAuthConfig {
+            username: "prometheus".to_string(),
+            password: "secret".to_string(),
+        });
+
+        let header = format!("Basic {}", BASE64.encode("prometheus:secret"));
+        assert!(credentials.matches(header.as_bytes()));
+
+        let header = format!("Basic {}", BASE64.encode("prometheus:wrong"));
+        assert!(!credentials.matches(header.as_bytes()));
+        assert!(!credentials.matches(b""));
+    }
+}
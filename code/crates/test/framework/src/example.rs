@@ -0,0 +1,179 @@
+//! Runner for the integration tests of the example applications built on the test context.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::async_trait;
+use derive_where::derive_where;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tempfile::TempDir;
+
+use malachitebft_config::{ConsensusConfig, P2pConfig, RuntimeConfig, TransportProtocol};
+use malachitebft_test::middleware::Middleware;
+use malachitebft_test::node::NodeHandle;
+use malachitebft_test::{Genesis, Height, PrivateKey, TestContext, Validator, ValidatorSet};
+use malachitebft_test_app::config::Config;
+
+use crate::{NodeId, NodeRunner, TestNode, TestParams};
+
+/// A node of an example application, as spawned by the [`ExampleRunner`].
+pub struct ExampleNode {
+    pub home_dir: PathBuf,
+    pub config: Config,
+    pub genesis: Genesis,
+    pub private_key: PrivateKey,
+    pub start_height: Height,
+    pub middleware: Arc<dyn Middleware>,
+}
+
+/// An example application whose nodes can be spawned by the [`ExampleRunner`].
+#[async_trait]
+pub trait ExampleApp: Send + Sync + 'static {
+    type Handle: NodeHandle<TestContext>;
+
+    /// Name of the application, used as a prefix of the home directories of its nodes
+    const NAME: &'static str;
+
+    /// Start the given node of the application
+    async fn start(node: ExampleNode) -> eyre::Result<Self::Handle>;
+
+    /// Adjust the configuration of a node to the application,
+    /// before the configuration modifier of the test is applied
+    fn configure(_config: &mut Config) {}
+}
+
+#[derive(Clone)]
+struct NodeInfo {
+    start_height: Height,
+    home_dir: PathBuf,
+    middleware: Arc<dyn Middleware>,
+    config_modifier: crate::ConfigModifier<Config>,
+}
+
+/// Runner spawning the nodes of the example application `A`, connected to each other over TCP.
+#[derive_where(Clone)]
+pub struct ExampleRunner<A> {
+    params: TestParams,
+    nodes_info: HashMap<NodeId, NodeInfo>,
+    private_keys: HashMap<NodeId, PrivateKey>,
+    genesis: Genesis,
+    base_port: usize,
+    app: PhantomData<fn() -> A>,
+}
+
+const BASE_PORT: usize = 5000;
+const PORTS_PER_NODE: usize = 10;
+const PORTS_PER_SLOT: usize = 200;
+
+/// Slot of the test when running under `cargo nextest`, used to pick ports
+/// which do not conflict with the ones of the tests running in parallel
+fn global_slot() -> usize {
+    std::env::var("NEXTEST_TEST_GLOBAL_SLOT")
+        .ok()
+        .and_then(|slot| slot.parse().ok())
+        .unwrap_or(0)
+}
+
+#[async_trait]
+impl<A: ExampleApp> NodeRunner<TestContext> for ExampleRunner<A> {
+    type NodeHandle = A::Handle;
+
+    fn new<S>(id: usize, nodes: &[TestNode<TestContext, S>], params: TestParams) -> Self {
+        let base_port = BASE_PORT + global_slot() * PORTS_PER_SLOT + id * PORTS_PER_NODE;
+
+        let mut rng = StdRng::seed_from_u64(0x42);
+
+        let private_keys: HashMap<_, _> = nodes
+            .iter()
+            .map(|node| (node.id, PrivateKey::generate(&mut rng)))
+            .collect();
+
+        let validators = nodes
+            .iter()
+            .map(|node| Validator::new(private_keys[&node.id].public_key(), node.voting_power))
+            .collect::<Vec<_>>();
+
+        let nodes_info = nodes
+            .iter()
+            .map(|node| {
+                let home_dir =
+                    TempDir::with_prefix(format!("malachitebft-example-{}-{id}", A::NAME))
+                        .unwrap()
+                        .keep();
+
+                let info = NodeInfo {
+                    start_height: node.start_height,
+                    home_dir,
+                    middleware: Arc::clone(&node.middleware),
+                    config_modifier: Arc::clone(&node.config_modifier),
+                };
+
+                (node.id, info)
+            })
+            .collect();
+
+        Self {
+            params,
+            nodes_info,
+            private_keys,
+            genesis: Genesis {
+                validator_set: ValidatorSet::new(validators),
+            },
+            base_port,
+            app: PhantomData,
+        }
+    }
+
+    async fn spawn(&self, id: NodeId) -> eyre::Result<A::Handle> {
+        let node_info = &self.nodes_info[&id];
+
+        A::start(ExampleNode {
+            home_dir: node_info.home_dir.clone(),
+            config: self.generate_config(id),
+            genesis: self.genesis.clone(),
+            private_key: self.private_keys[&id].clone(),
+            start_height: node_info.start_height,
+            middleware: Arc::clone(&node_info.middleware),
+        })
+        .await
+    }
+
+    async fn reset_db(&self, _id: NodeId) -> eyre::Result<()> {
+        // The example applications do not persist their values
+        Ok(())
+    }
+}
+
+impl<A: ExampleApp> ExampleRunner<A> {
+    fn generate_config(&self, node: NodeId) -> Config {
+        let transport = TransportProtocol::Tcp;
+        let i = node - 1;
+
+        let mut config = Config {
+            moniker: format!("node-{node}"),
+            consensus: ConsensusConfig {
+                queue_capacity: 100,
+                p2p: P2pConfig {
+                    listen_addr: transport.multiaddr("127.0.0.1", self.base_port + i),
+                    persistent_peers: (0..self.nodes_info.len())
+                        .filter(|j| *j != i)
+                        .map(|j| transport.multiaddr("127.0.0.1", self.base_port + j))
+                        .collect(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            runtime: RuntimeConfig::single_threaded(),
+            ..Default::default()
+        };
+
+        self.params.apply_to_config(&mut config);
+        A::configure(&mut config);
+        (self.nodes_info[&node].config_modifier)(&mut config);
+
+        config
+    }
+}
@@ -11,6 +11,7 @@ use tracing::{debug, error, error_span, info, Instrument};
 
 use malachitebft_core_types::{Context, Height};
 
+pub use malachitebft_engine::util::clock::{Clock, SimulatedClock};
 pub use malachitebft_engine::util::events::{Event, RxEvent, TxEvent};
 pub use malachitebft_test::node::{Node, NodeHandle};
 pub use malachitebft_test::traits::{
@@ -24,11 +25,17 @@ mod node;
 pub use node::{ConfigModifier, HandlerResult, NodeId, TestNode};
 
 mod params;
-pub use params::TestParams;
+pub use params::{TestParams, FAST_FORWARD_INTERVAL};
 
 mod expected;
 pub use expected::Expected;
 
+mod safety;
+pub use safety::DecidedValues;
+
+mod example;
+pub use example::{ExampleApp, ExampleNode, ExampleRunner};
+
 use node::Step;
 
 fn unique_id() -> usize {
@@ -142,8 +149,7 @@ fn check_results(results: Vec<(NodeId, Result<TestResult, Elapsed>)>) {
     }
 
     if errors > 0 {
-        error!("Test failed with {errors} errors");
-        std::process::exit(1);
+        panic!("Test failed with {errors} errors");
     }
 }
 
@@ -165,15 +171,28 @@ where
 
     let mut set = JoinSet::new();
 
+    // Fast-forward the simulated clock, if any, to the next timer deadline
+    // whenever the nodes have had a chance to make progress.
+    let fast_forward = params.clock.clone().map(|clock| {
+        tokio::spawn(async move {
+            loop {
+                sleep(FAST_FORWARD_INTERVAL).await;
+                clock.advance_to_next_deadline();
+            }
+        })
+    });
+
     let runner = R::new(test.id, &test.nodes, params);
+    let decided = Arc::new(DecidedValues::default());
 
     for node in test.nodes {
         let runner = runner.clone();
+        let decided = Arc::clone(&decided);
 
         set.spawn(
             async move {
                 let id = node.id;
-                let result = tokio::time::timeout(timeout, run_node(runner, node)).await;
+                let result = tokio::time::timeout(timeout, run_node(runner, node, decided)).await;
                 (id, result)
             }
             .instrument(span.clone()),
@@ -181,6 +200,11 @@ where
     }
 
     let results = set.join_all().await;
+
+    if let Some(fast_forward) = fast_forward {
+        fast_forward.abort();
+    }
+
     check_results(results);
 }
 
@@ -196,15 +220,31 @@ where
 
     async fn spawn(&self, id: NodeId) -> eyre::Result<Self::NodeHandle>;
     async fn reset_db(&self, id: NodeId) -> eyre::Result<()>;
+
+    /// Cut the last entry of the WAL of the given node short
+    async fn truncate_wal(&self, id: NodeId) -> eyre::Result<()> {
+        eyre::bail!("Node {id}: truncating the WAL is not supported by this runner")
+    }
 }
 
+/// Run the steps of a node, failing if it is honest and decides a value
+/// different from the one decided by another honest node at the same height.
 #[tracing::instrument("node", skip_all, fields(id = %node.id))]
-pub async fn run_node<Ctx, R, S>(runner: R, mut node: TestNode<Ctx, S>) -> TestResult
+pub async fn run_node<Ctx, R, S>(
+    runner: R,
+    mut node: TestNode<Ctx, S>,
+    decided: Arc<DecidedValues<Ctx>>,
+) -> TestResult
 where
     Ctx: Context,
     R: NodeRunner<Ctx>,
     S: Send + Sync + 'static,
 {
+    if node.never_starts {
+        info!("Node never starts");
+        return TestResult::Success("Node never started".to_string());
+    }
+
     sleep(node.start_delay).await;
 
     info!(%node.voting_power, "Spawning node");
@@ -218,13 +258,16 @@ where
     let current_height = Arc::new(AtomicUsize::new(0));
     let failure = Arc::new(Mutex::new(None));
     let is_full_node = node.is_full_node();
+    let is_byzantine = node.byzantine;
     let consensus_enabled = node.consensus_enabled;
+    let id = node.id;
 
     let spawn_event_monitor = |mut rx: RxEvent<Ctx>| {
         tokio::spawn({
             let decisions = Arc::clone(&decisions);
             let current_height = Arc::clone(&current_height);
             let failure = Arc::clone(&failure);
+            let decided = Arc::clone(&decided);
 
             async move {
                 while let Ok(event) = rx.recv().await {
@@ -232,8 +275,15 @@ where
                         Event::StartedHeight(height, _is_restart) => {
                             current_height.store(height.as_u64() as usize, Ordering::SeqCst);
                         }
-                        Event::Decided { .. } => {
+                        Event::Decided { commit_certificate } => {
                             decisions.fetch_add(1, Ordering::SeqCst);
+
+                            if !is_byzantine {
+                                if let Err(e) = decided.record(id, commit_certificate) {
+                                    error!("{e}");
+                                    *failure.lock().await = Some(e);
+                                }
+                            }
                         }
                         Event::Published(msg) if is_full_node => {
                             error!("Full node unexpectedly published a consensus message: {msg:?}");
@@ -331,11 +381,27 @@ where
                     .expect("Node must stop");
             }
 
+            Step::Stop(after) => {
+                let height = current_height.load(Ordering::SeqCst);
+
+                info!("Node will shut down at height {height}");
+                sleep(after).await;
+
+                event_monitor.abort();
+
+                handle.stop().await.expect("Node must shut down");
+            }
+
             Step::ResetDb => {
                 info!("Resetting database");
                 runner.reset_db(node.id).await.unwrap();
             }
 
+            Step::TruncateWal => {
+                info!("Truncating WAL");
+                runner.truncate_wal(node.id).await.unwrap();
+            }
+
             Step::Restart(after) => {
                 info!("Node will restart in {after:?}");
 
@@ -1,5 +1,5 @@
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use eyre::bail;
 use tracing::info;
@@ -10,6 +10,7 @@ use malachitebft_core_types::{
     CommitCertificate, Context, Height, SignedVote, Vote, VoteType, VotingPower,
 };
 use malachitebft_engine::util::events::Event;
+use malachitebft_engine_byzantine::ByzantineConfig;
 use malachitebft_test::middleware::{DefaultMiddleware, Middleware};
 use malachitebft_test_app::config::Config as TestConfig;
 
@@ -23,7 +24,9 @@ where
     Ctx: Context,
 {
     Crash(Duration),
+    Stop(Duration),
     ResetDb,
+    TruncateWal,
     Restart(Duration),
     WaitUntil(u64),
     WaitUntilRound(u32),
@@ -50,11 +53,16 @@ where
     pub voting_power: VotingPower,
     pub start_height: Ctx::Height,
     pub start_delay: Duration,
+    /// Nodes which never start are left out of the network for the whole test,
+    /// while remaining in the validator set
+    pub never_starts: bool,
     pub steps: Vec<Step<Ctx, State>>,
     pub state: State,
     pub middleware: Arc<dyn Middleware>,
     pub config_modifier: ConfigModifier<Cfg>,
     pub consensus_enabled: bool,
+    /// Byzantine nodes are left out of the check that the honest nodes decide the same values
+    pub byzantine: bool,
 }
 
 impl<Ctx, State, Cfg> TestNode<Ctx, State, Cfg>
@@ -75,11 +83,13 @@ where
             voting_power: 1,
             start_height: Ctx::Height::INITIAL,
             start_delay: Duration::from_secs(0),
+            never_starts: false,
             steps: vec![],
             state,
             middleware: Arc::new(DefaultMiddleware),
             config_modifier: Arc::new(|_config| {}),
             consensus_enabled: true,
+            byzantine: false,
         }
     }
 
@@ -103,6 +113,13 @@ where
         self
     }
 
+    /// Keep this node out of the network for the whole test, eg. to test that the other nodes
+    /// handle a validator which is offline. Its steps are not run, and it is deemed successful.
+    pub fn never_start(&mut self) -> &mut Self {
+        self.never_starts = true;
+        self
+    }
+
     pub fn start_at(&mut self, height: u64) -> &mut Self {
         self.start_after(height, Duration::from_secs(0))
     }
@@ -123,11 +140,27 @@ where
         self
     }
 
+    pub fn stop(&mut self) -> &mut Self {
+        self.steps.push(Step::Stop(Duration::from_secs(0)));
+        self
+    }
+
+    pub fn stop_after(&mut self, duration: Duration) -> &mut Self {
+        self.steps.push(Step::Stop(duration));
+        self
+    }
+
     pub fn reset_db(&mut self) -> &mut Self {
         self.steps.push(Step::ResetDb);
         self
     }
 
+    /// Cut the last entry of the WAL short, as if the node crashed while writing it
+    pub fn truncate_wal(&mut self) -> &mut Self {
+        self.steps.push(Step::TruncateWal);
+        self
+    }
+
     pub fn restart_after(&mut self, delay: Duration) -> &mut Self {
         self.steps.push(Step::Restart(delay));
         self
@@ -170,6 +203,48 @@ where
         })
     }
 
+    /// Wait until the WAL replay is done, failing if the replay reports
+    /// no progress for longer than `stall_timeout`.
+    pub fn expect_wal_replay_done(&mut self, stall_timeout: Duration) -> &mut Self {
+        let last_progress = Mutex::new(None::<Instant>);
+
+        self.on_event(move |event, _| {
+            let mut last_progress = last_progress.lock().expect("lock poisoned");
+            let now = Instant::now();
+
+            if let Some(last) = *last_progress {
+                if now.duration_since(last) > stall_timeout {
+                    bail!("WAL replay made no progress for {stall_timeout:?}")
+                }
+            }
+
+            match event {
+                Event::WalReplayBegin(..) => {
+                    *last_progress = Some(now);
+                    Ok(HandlerResult::WaitForNextEvent)
+                }
+                Event::WalReplayProgress {
+                    index,
+                    total,
+                    entry_height,
+                    entry_round,
+                    ..
+                } => {
+                    info!(%entry_height, %entry_round, "Replayed WAL entry {}/{total}", index + 1);
+
+                    *last_progress = Some(now);
+                    Ok(HandlerResult::WaitForNextEvent)
+                }
+                Event::WalReplayDone(height) => {
+                    info!("WAL replay done at height {height}");
+                    Ok(HandlerResult::ContinueTest)
+                }
+                Event::WalReplayError(error) => bail!("WAL replay failed: {error}"),
+                _ => Ok(HandlerResult::WaitForNextEvent),
+            }
+        })
+    }
+
     pub fn expect_vote_rebroadcast(
         &mut self,
         at_height: u64,
@@ -252,6 +327,120 @@ where
         })
     }
 
+    pub fn expect_graceful_shutdown(&mut self) -> &mut Self {
+        self.on_event(move |event, _| {
+            let Event::ShutdownCompleted { sync, timed_out } = event else {
+                return Ok(HandlerResult::WaitForNextEvent);
+            };
+
+            if timed_out {
+                bail!("Some actors did not drain before the shutdown timeout")
+            }
+
+            info!(?sync, "Node has shut down gracefully");
+
+            Ok(HandlerResult::ContinueTest)
+        })
+    }
+
+    pub fn expect_round_alert(&mut self, at_height: u64) -> &mut Self {
+        self.on_event(move |event, _| {
+            let Event::RoundAlert(height, round) = event else {
+                return Ok(HandlerResult::WaitForNextEvent);
+            };
+
+            if height.as_u64() != at_height {
+                bail!("Unexpected round alert for height {height}, expected {at_height}")
+            }
+
+            info!(%height, %round, "Raised round alert");
+
+            Ok(HandlerResult::ContinueTest)
+        })
+    }
+
+    pub fn expect_participation_halted(&mut self, at_height: u64) -> &mut Self {
+        self.on_event(move |event, _| {
+            let Event::ParticipationHalted(height, round) = event else {
+                return Ok(HandlerResult::WaitForNextEvent);
+            };
+
+            if height.as_u64() != at_height {
+                bail!("Unexpected halt for height {height}, expected {at_height}")
+            }
+
+            info!(%height, %round, "Halted participation");
+
+            Ok(HandlerResult::ContinueTest)
+        })
+    }
+
+    /// Expect the node to finalize a height with evidence of misbehavior,
+    /// ie. equivocating votes or conflicting proposals.
+    pub fn expect_misbehavior_evidence(&mut self) -> &mut Self {
+        self.on_event(move |event, _| {
+            let Event::Finalized {
+                commit_certificate,
+                evidence,
+            } = event
+            else {
+                return Ok(HandlerResult::WaitForNextEvent);
+            };
+
+            if evidence.is_empty() {
+                return Ok(HandlerResult::WaitForNextEvent);
+            }
+
+            info!(
+                height = %commit_certificate.height,
+                "Finalized height with evidence of misbehavior"
+            );
+
+            Ok(HandlerResult::ContinueTest)
+        })
+    }
+
+    /// Expect the node to receive an invalid value via sync.
+    pub fn expect_invalid_synced_value(&mut self) -> &mut Self {
+        self.on_event(move |event, _| {
+            let Event::InvalidSyncedValue(height) = event else {
+                return Ok(HandlerResult::WaitForNextEvent);
+            };
+
+            info!(%height, "Received invalid synced value");
+
+            Ok(HandlerResult::ContinueTest)
+        })
+    }
+
+    pub fn expect_absent_validators(&mut self, at_height: u64, expected: usize) -> &mut Self {
+        self.on_event(move |event, _| {
+            let Event::HeightCompleted {
+                height,
+                absent_validators,
+                ..
+            } = event
+            else {
+                return Ok(HandlerResult::WaitForNextEvent);
+            };
+
+            if height.as_u64() != at_height {
+                return Ok(HandlerResult::WaitForNextEvent);
+            }
+
+            if absent_validators.len() != expected {
+                bail!(
+                    "Unexpected number of absent validators at height {height}: {}, expected {expected}",
+                    absent_validators.len()
+                )
+            }
+
+            info!(%height, ?absent_validators, "Completed height");
+
+            Ok(HandlerResult::ContinueTest)
+        })
+    }
+
     pub fn expect_polka_certificate(&mut self, at_height: u64, at_round: u32) -> &mut Self {
         self.on_event(move |event, _| {
             let Event::PolkaCertificate(msg) = event else {
@@ -274,6 +463,36 @@ where
         })
     }
 
+    pub fn expect_vote_tally(
+        &mut self,
+        at_height: u64,
+        min_prevote_power: VotingPower,
+        min_precommit_power: VotingPower,
+    ) -> &mut Self {
+        self.on_event(move |event, _| {
+            let Event::VoteTally {
+                height,
+                round,
+                prevote_power,
+                precommit_power,
+            } = event
+            else {
+                return Ok(HandlerResult::WaitForNextEvent);
+            };
+
+            if height.as_u64() != at_height
+                || prevote_power < min_prevote_power
+                || precommit_power < min_precommit_power
+            {
+                return Ok(HandlerResult::WaitForNextEvent);
+            }
+
+            info!(%height, %round, %prevote_power, %precommit_power, "Received vote tally");
+
+            Ok(HandlerResult::ContinueTest)
+        })
+    }
+
     pub fn on_proposed_value<F>(&mut self, f: F) -> &mut Self
     where
         F: Fn(LocallyProposedValue<Ctx>, &mut State) -> Result<HandlerResult, eyre::Report>
@@ -388,6 +607,22 @@ where
     }
 }
 
+impl<Ctx, State> TestNode<Ctx, State, TestConfig>
+where
+    Ctx: Context,
+{
+    /// Make the node misbehave as described by the given configuration.
+    ///
+    /// The node is left out of the check that the honest nodes decide the same values.
+    pub fn with_byzantine(&mut self, config: ByzantineConfig) -> &mut Self {
+        self.byzantine = true;
+
+        self.add_config_modifier(move |c| {
+            c.byzantine = Some(config.clone());
+        })
+    }
+}
+
 impl<Ctx, State, Cfg> TestNode<Ctx, State, Cfg>
 where
     Ctx: Context,
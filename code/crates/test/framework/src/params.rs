@@ -3,6 +3,7 @@ use std::collections::HashSet;
 use std::time::Duration;
 
 use malachitebft_config::{PubSubProtocol, ValuePayload};
+use malachitebft_engine::util::clock::SimulatedClock;
 use malachitebft_test_app::config::Config;
 
 #[derive(Clone, Debug)]
@@ -12,6 +13,8 @@ pub struct TestParams {
     pub consensus_enabled: bool,
     pub parallel_requests: usize,
     pub batch_size: usize,
+    pub batch_synced_values: bool,
+    pub announce_decisions: bool,
     pub protocol: PubSubProtocol,
     pub rpc_max_size: ByteSize,
     pub block_size: ByteSize,
@@ -31,8 +34,15 @@ pub struct TestParams {
     pub shared_key_group: HashSet<usize>,
     /// Target time for heights. If present Finalized effect will be emitted.
     pub target_time: Option<Duration>,
+    /// Simulated clock driving the timers of all nodes, instead of the Tokio timer.
+    /// When present, the framework fast-forwards the clock to the next timer deadline
+    /// every [`FAST_FORWARD_INTERVAL`] of real time, so that timeouts elapse almost instantly.
+    pub clock: Option<SimulatedClock>,
 }
 
+/// Interval of real time between two fast-forwards of the simulated clock.
+pub const FAST_FORWARD_INTERVAL: Duration = Duration::from_millis(20);
+
 impl Default for TestParams {
     fn default() -> Self {
         Self {
@@ -41,6 +51,8 @@ impl Default for TestParams {
             consensus_enabled: true,
             parallel_requests: 1,
             batch_size: 1,
+            batch_synced_values: false,
+            announce_decisions: false,
             protocol: PubSubProtocol::default(),
             rpc_max_size: ByteSize::mib(2),
             block_size: ByteSize::mib(1),
@@ -55,6 +67,7 @@ impl Default for TestParams {
             exclude_from_persistent_peers: Vec::new(),
             shared_key_group: HashSet::new(),
             target_time: None,
+            clock: None,
         }
     }
 }
@@ -64,6 +77,8 @@ impl TestParams {
         config.value_sync.enabled = self.enable_value_sync;
         config.value_sync.parallel_requests = self.parallel_requests;
         config.value_sync.batch_size = self.batch_size;
+        config.value_sync.batch_synced_values = self.batch_synced_values;
+        config.value_sync.announce_decisions = self.announce_decisions;
         config.value_sync.max_response_size = self.max_response_size;
         config.value_sync.status_update_interval = self.status_update_interval;
 
@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use malachitebft_core_types::{CommitCertificate, Context, Height, ValueId};
+
+use crate::NodeId;
+
+/// Values decided by the honest nodes of a test at each height, shared by all the nodes
+/// to check that no two honest nodes ever decide different values at the same height.
+pub struct DecidedValues<Ctx: Context> {
+    decided: Mutex<BTreeMap<u64, (NodeId, ValueId<Ctx>)>>,
+}
+
+impl<Ctx: Context> Default for DecidedValues<Ctx> {
+    fn default() -> Self {
+        Self {
+            decided: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl<Ctx: Context> DecidedValues<Ctx> {
+    /// Record the value decided by the given node, failing if another
+    /// honest node decided a different value at the same height.
+    pub fn record(&self, node: NodeId, certificate: &CommitCertificate<Ctx>) -> Result<(), String> {
+        let height = certificate.height.as_u64();
+        let mut decided = self.decided.lock().unwrap_or_else(|e| e.into_inner());
+
+        match decided.get(&height) {
+            Some((other, value_id)) if *value_id != certificate.value_id => Err(format!(
+                "Safety violation at height {height}: node {node} decided {:?}, \
+                 but node {other} decided {value_id:?}",
+                certificate.value_id
+            )),
+            Some(_) => Ok(()),
+            None => {
+                decided.insert(height, (node, certificate.value_id.clone()));
+                Ok(())
+            }
+        }
+    }
+}
@@ -29,10 +29,13 @@
 //! (passed in at construction time).
 
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use eyre::Result;
 use malachitebft_core_consensus::{LocallyProposedValue, ProposedValue};
-use malachitebft_core_types::{CommitCertificate, LinearTimeouts, NilOrVal, Round, Validity};
+use malachitebft_core_types::{
+    CommitCertificate, LinearTimeouts, NilOrVal, Round, TimeoutKind, Validity,
+};
 use malachitebft_engine_byzantine::config::make_rng;
 use malachitebft_engine_byzantine::{Amnesia, Trigger};
 use rand::rngs::StdRng;
@@ -124,6 +127,16 @@ impl Middleware for ByzantineMiddleware {
         self.inner.get_timeouts(ctx, current_height, height)
     }
 
+    fn get_timeout_override(
+        &self,
+        ctx: &TestContext,
+        height: Height,
+        round: Round,
+        kind: TimeoutKind,
+    ) -> Option<Duration> {
+        self.inner.get_timeout_override(ctx, height, round, kind)
+    }
+
     fn new_proposal(
         &self,
         ctx: &TestContext,
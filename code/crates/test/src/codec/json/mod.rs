@@ -1,19 +1,20 @@
 pub mod raw;
 
 use bytes::Bytes;
+use serde::de::Error as _;
 use tracing::warn;
 
 use malachitebft_codec::{Codec, HasEncodedLen};
 use malachitebft_core_consensus::{LivenessMsg, SignedConsensusMsg};
 use malachitebft_engine::util::streaming::StreamMessage;
-use malachitebft_sync::{Request, Response, Status};
+use malachitebft_sync::{Announcement, Request, Response, Status};
 
 use crate::{ProposalPart, TestContext, Value};
 
 use malachitebft_core_types::ValidatorProof;
 use raw::{
-    RawLivenessMsg, RawRequest, RawResponse, RawSignedConsensusMsg, RawStatus, RawStreamMessage,
-    RawValidatorProof,
+    RawAnnouncement, RawLivenessMsg, RawRequest, RawResponse, RawSignedConsensusMsg, RawStatus,
+    RawStreamMessage, RawValidatorProof,
 };
 
 #[derive(Copy, Clone, Debug)]
@@ -47,7 +48,9 @@ impl Codec<SignedConsensusMsg<TestContext>> for JsonCodec {
     type Error = serde_json::Error;
 
     fn decode(&self, bytes: Bytes) -> Result<SignedConsensusMsg<TestContext>, Self::Error> {
-        serde_json::from_slice::<RawSignedConsensusMsg>(&bytes).map(Into::into)
+        serde_json::from_slice::<RawSignedConsensusMsg>(&bytes)?
+            .try_into()
+            .map_err(serde_json::Error::custom)
     }
 
     fn encode(&self, msg: &SignedConsensusMsg<TestContext>) -> Result<Bytes, Self::Error> {
@@ -79,6 +82,18 @@ impl Codec<Status<TestContext>> for JsonCodec {
     }
 }
 
+impl Codec<Announcement<TestContext>> for JsonCodec {
+    type Error = serde_json::Error;
+
+    fn decode(&self, bytes: Bytes) -> Result<Announcement<TestContext>, Self::Error> {
+        serde_json::from_slice::<RawAnnouncement>(&bytes).map(Into::into)
+    }
+
+    fn encode(&self, msg: &Announcement<TestContext>) -> Result<Bytes, Self::Error> {
+        serde_json::to_vec(&RawAnnouncement::from(msg.clone())).map(Bytes::from)
+    }
+}
+
 impl Codec<Request<TestContext>> for JsonCodec {
     type Error = serde_json::Error;
 
@@ -118,7 +133,9 @@ impl Codec<LivenessMsg<TestContext>> for JsonCodec {
     type Error = serde_json::Error;
 
     fn decode(&self, bytes: Bytes) -> Result<LivenessMsg<TestContext>, Self::Error> {
-        serde_json::from_slice::<RawLivenessMsg>(&bytes).map(Into::into)
+        serde_json::from_slice::<RawLivenessMsg>(&bytes)?
+            .try_into()
+            .map_err(serde_json::Error::custom)
     }
 
     fn encode(&self, msg: &LivenessMsg<TestContext>) -> Result<Bytes, Self::Error> {
@@ -5,13 +5,16 @@ use serde::{Deserialize, Serialize};
 use malachitebft_app::streaming::StreamId;
 use malachitebft_core_consensus::{LivenessMsg, SignedConsensusMsg};
 use malachitebft_core_types::{
-    CommitCertificate, CommitSignature, NilOrVal, PolkaCertificate, PolkaSignature, Round,
-    RoundCertificate, RoundCertificateType, RoundSignature, SignedProposal, SignedVote, VoteType,
+    ChainId, CommitCertificate, CommitSignature, NilOrVal, PolkaCertificate, PolkaSignature, Round,
+    RoundCertificate, RoundCertificateType, RoundSignature, SignedProposal, SignedVote,
+    ValidatorChange, ValidatorSetUpdate, ValidatorSetUpdateCertificate,
+    ValidatorSetUpdateSignature, VoteType,
 };
 use malachitebft_engine::util::streaming::{StreamContent, StreamMessage};
-use malachitebft_proto::Protobuf;
+use malachitebft_proto::{Error as ProtoError, Protobuf};
 use malachitebft_sync::{
-    PeerId, RawDecidedValue, Request, Response, Status, ValueRequest, ValueResponse,
+    Announcement, CertificateHash, NodeMode, PeerId, RawDecidedValue, Request, Response, Status,
+    ValueRequest, ValueResponse,
 };
 
 use crate::{Address, Height, Proposal, ProposalPart, TestContext, ValueId, Vote};
@@ -43,20 +46,22 @@ impl From<SignedConsensusMsg<TestContext>> for RawSignedConsensusMsg {
     }
 }
 
-impl From<RawSignedConsensusMsg> for SignedConsensusMsg<TestContext> {
-    fn from(value: RawSignedConsensusMsg) -> Self {
-        match value {
+impl TryFrom<RawSignedConsensusMsg> for SignedConsensusMsg<TestContext> {
+    type Error = ProtoError;
+
+    fn try_from(value: RawSignedConsensusMsg) -> Result<Self, Self::Error> {
+        Ok(match value {
             RawSignedConsensusMsg::Vote(vote) => SignedConsensusMsg::Vote(SignedVote {
-                message: Vote::from_sign_bytes(&vote.message).unwrap(),
+                message: Vote::from_sign_bytes(&vote.message)?,
                 signature: vote.signature.into(),
             }),
             RawSignedConsensusMsg::Proposal(proposal) => {
                 SignedConsensusMsg::Proposal(SignedProposal {
-                    message: Proposal::from_sign_bytes(&proposal.message).unwrap(),
+                    message: Proposal::from_sign_bytes(&proposal.message)?,
                     signature: proposal.signature.into(),
                 })
             }
-        }
+        })
     }
 }
 
@@ -115,6 +120,8 @@ pub struct RawStatus {
     pub peer_id: PeerId,
     pub tip_height: Height,
     pub history_min_height: Height,
+    pub sync_height: Height,
+    pub mode: NodeMode,
 }
 
 impl From<Status<TestContext>> for RawStatus {
@@ -123,6 +130,8 @@ impl From<Status<TestContext>> for RawStatus {
             peer_id: value.peer_id,
             tip_height: value.tip_height,
             history_min_height: value.history_min_height,
+            sync_height: value.sync_height,
+            mode: value.mode,
         }
     }
 }
@@ -133,6 +142,38 @@ impl From<RawStatus> for Status<TestContext> {
             peer_id: value.peer_id,
             tip_height: value.tip_height,
             history_min_height: value.history_min_height,
+            sync_height: value.sync_height,
+            mode: value.mode,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RawAnnouncement {
+    pub peer_id: PeerId,
+    pub height: Height,
+    pub value_id: ValueId,
+    pub certificate_hash: CertificateHash,
+}
+
+impl From<Announcement<TestContext>> for RawAnnouncement {
+    fn from(value: Announcement<TestContext>) -> Self {
+        Self {
+            peer_id: value.peer_id,
+            height: value.height,
+            value_id: value.value_id,
+            certificate_hash: value.certificate_hash,
+        }
+    }
+}
+
+impl From<RawAnnouncement> for Announcement<TestContext> {
+    fn from(value: RawAnnouncement) -> Self {
+        Self {
+            peer_id: value.peer_id,
+            height: value.height,
+            value_id: value.value_id,
+            certificate_hash: value.certificate_hash,
         }
     }
 }
@@ -335,11 +376,82 @@ pub struct RawRoundCertificate {
     pub round_signatures: Vec<RawRoundSignature>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct RawValidatorChange {
+    #[serde(with = "hex::serde")]
+    pub public_key: Vec<u8>,
+    pub voting_power: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RawValidatorSetUpdateSignature {
+    pub address: Address,
+    pub signature: Signature,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RawValidatorSetUpdateCertificate {
+    #[serde(default)]
+    pub chain_id: Option<ChainId>,
+    pub epoch: u64,
+    pub effective_height: Height,
+    pub diff: Vec<RawValidatorChange>,
+    pub signatures: Vec<RawValidatorSetUpdateSignature>,
+}
+
+impl From<ValidatorSetUpdateCertificate<TestContext>> for RawValidatorSetUpdateCertificate {
+    fn from(value: ValidatorSetUpdateCertificate<TestContext>) -> Self {
+        Self {
+            chain_id: value.update.chain_id,
+            epoch: value.update.epoch,
+            effective_height: value.update.effective_height,
+            diff: value
+                .update
+                .diff
+                .into_iter()
+                .map(|change| RawValidatorChange {
+                    public_key: change.public_key,
+                    voting_power: change.voting_power,
+                })
+                .collect(),
+            signatures: value
+                .signatures
+                .into_iter()
+                .map(|sig| RawValidatorSetUpdateSignature {
+                    address: sig.address,
+                    signature: *sig.signature.inner(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<RawValidatorSetUpdateCertificate> for ValidatorSetUpdateCertificate<TestContext> {
+    fn from(value: RawValidatorSetUpdateCertificate) -> Self {
+        let diff = value
+            .diff
+            .into_iter()
+            .map(|change| ValidatorChange::new(change.public_key, change.voting_power))
+            .collect();
+
+        ValidatorSetUpdateCertificate::new(
+            ValidatorSetUpdate::new(value.epoch, value.effective_height, diff)
+                .with_chain_id(value.chain_id),
+            value
+                .signatures
+                .into_iter()
+                .map(|sig| ValidatorSetUpdateSignature::new(sig.address, sig.signature.into()))
+                .collect(),
+        )
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub enum RawLivenessMsg {
     Vote(RawSignedMessage),
     PolkaCertificate(RawPolkaCertificate),
     SkipRoundCertificate(RawRoundCertificate),
+    ValidatorSetUpdate(RawValidatorSetUpdateCertificate),
 }
 
 impl From<LivenessMsg<TestContext>> for RawLivenessMsg {
@@ -353,7 +465,14 @@ impl From<LivenessMsg<TestContext>> for RawLivenessMsg {
                 height: polka.height,
                 round: polka.round,
                 value_id: polka.value_id,
-                polka_signatures: vec![], // Placeholder, implement as needed
+                polka_signatures: polka
+                    .polka_signatures
+                    .into_iter()
+                    .map(|sig| RawPolkaSignature {
+                        address: sig.address,
+                        signature: *sig.signature.inner(),
+                    })
+                    .collect(),
             }),
             LivenessMsg::SkipRoundCertificate(round_cert) => {
                 Self::SkipRoundCertificate(RawRoundCertificate {
@@ -372,15 +491,18 @@ impl From<LivenessMsg<TestContext>> for RawLivenessMsg {
                         .collect(),
                 })
             }
+            LivenessMsg::ValidatorSetUpdate(cert) => Self::ValidatorSetUpdate(cert.into()),
         }
     }
 }
 
-impl From<RawLivenessMsg> for LivenessMsg<TestContext> {
-    fn from(value: RawLivenessMsg) -> Self {
-        match value {
+impl TryFrom<RawLivenessMsg> for LivenessMsg<TestContext> {
+    type Error = ProtoError;
+
+    fn try_from(value: RawLivenessMsg) -> Result<Self, Self::Error> {
+        Ok(match value {
             RawLivenessMsg::Vote(vote) => LivenessMsg::Vote(SignedVote {
-                message: Vote::from_bytes(&vote.message).unwrap(),
+                message: Vote::from_bytes(&vote.message)?,
                 signature: vote.signature.into(),
             }),
             RawLivenessMsg::PolkaCertificate(cert) => {
@@ -415,7 +537,10 @@ impl From<RawLivenessMsg> for LivenessMsg<TestContext> {
                         .collect(),
                 })
             }
-        }
+            RawLivenessMsg::ValidatorSetUpdate(cert) => {
+                LivenessMsg::ValidatorSetUpdate(cert.into())
+            }
+        })
     }
 }
 
@@ -5,9 +5,10 @@ use malachitebft_app::engine::util::streaming::{StreamContent, StreamId, StreamM
 use malachitebft_codec::{Codec, HasEncodedLen};
 use malachitebft_core_consensus::{LivenessMsg, ProposedValue, SignedConsensusMsg};
 use malachitebft_core_types::{
-    CommitCertificate, CommitSignature, NilOrVal, PolkaCertificate, PolkaSignature, Round,
+    ChainId, CommitCertificate, CommitSignature, NilOrVal, PolkaCertificate, PolkaSignature, Round,
     RoundCertificate, RoundCertificateType, RoundSignature, SignedExtension, SignedProposal,
-    SignedVote, ValidatorProof, Validity,
+    SignedVote, ValidatorChange, ValidatorProof, ValidatorSetUpdate, ValidatorSetUpdateCertificate,
+    ValidatorSetUpdateSignature, Validity,
 };
 use malachitebft_proto::{Error as ProtoError, Protobuf};
 use malachitebft_signing_ed25519::Signature;
@@ -61,6 +62,58 @@ impl Codec<Signature> for ProtobufCodec {
     }
 }
 
+impl Codec<Vote> for ProtobufCodec {
+    type Error = ProtoError;
+
+    fn decode(&self, bytes: Bytes) -> Result<Vote, Self::Error> {
+        Protobuf::from_bytes(&bytes)
+    }
+
+    fn encode(&self, msg: &Vote) -> Result<Bytes, Self::Error> {
+        Protobuf::to_bytes(msg)
+    }
+}
+
+impl Codec<Proposal> for ProtobufCodec {
+    type Error = ProtoError;
+
+    fn decode(&self, bytes: Bytes) -> Result<Proposal, Self::Error> {
+        Protobuf::from_bytes(&bytes)
+    }
+
+    fn encode(&self, msg: &Proposal) -> Result<Bytes, Self::Error> {
+        Protobuf::to_bytes(msg)
+    }
+}
+
+/// Vote extensions are opaque bytes, encoded as is.
+impl Codec<Bytes> for ProtobufCodec {
+    type Error = ProtoError;
+
+    fn decode(&self, bytes: Bytes) -> Result<Bytes, Self::Error> {
+        Ok(bytes)
+    }
+
+    fn encode(&self, msg: &Bytes) -> Result<Bytes, Self::Error> {
+        Ok(msg.clone())
+    }
+}
+
+impl Codec<ValidatorSetUpdate<TestContext>> for ProtobufCodec {
+    type Error = ProtoError;
+
+    fn decode(&self, bytes: Bytes) -> Result<ValidatorSetUpdate<TestContext>, Self::Error> {
+        let proto = proto::ValidatorSetUpdate::decode(bytes.as_ref())?;
+        decode_validator_set_update(proto)
+    }
+
+    fn encode(&self, msg: &ValidatorSetUpdate<TestContext>) -> Result<Bytes, Self::Error> {
+        Ok(Bytes::from(
+            encode_validator_set_update(msg).encode_to_vec(),
+        ))
+    }
+}
+
 impl Codec<SignedConsensusMsg<TestContext>> for ProtobufCodec {
     type Error = ProtoError;
 
@@ -183,6 +236,107 @@ pub fn decode_round_certificate(
     })
 }
 
+pub fn encode_validator_set_update(
+    update: &ValidatorSetUpdate<TestContext>,
+) -> proto::ValidatorSetUpdate {
+    proto::ValidatorSetUpdate {
+        chain_id: update
+            .chain_id
+            .as_ref()
+            .map(ChainId::to_string)
+            .unwrap_or_default(),
+        epoch: update.epoch,
+        effective_height: update.effective_height.as_u64(),
+        diff: update
+            .diff
+            .iter()
+            .map(|change| proto::ValidatorChange {
+                public_key: Bytes::from(change.public_key.clone()),
+                voting_power: change.voting_power,
+            })
+            .collect(),
+    }
+}
+
+pub fn decode_validator_set_update(
+    update: proto::ValidatorSetUpdate,
+) -> Result<ValidatorSetUpdate<TestContext>, ProtoError> {
+    let chain_id = match update.chain_id.as_str() {
+        "" => None,
+        chain_id => Some(
+            ChainId::new(chain_id)
+                .map_err(|_| ProtoError::invalid_data::<proto::ValidatorSetUpdate>("chain_id"))?,
+        ),
+    };
+
+    let diff = update
+        .diff
+        .into_iter()
+        .map(|change| ValidatorChange::new(change.public_key.to_vec(), change.voting_power))
+        .collect();
+
+    Ok(
+        ValidatorSetUpdate::new(update.epoch, Height::new(update.effective_height), diff)
+            .with_chain_id(chain_id),
+    )
+}
+
+pub fn encode_validator_set_update_certificate(
+    certificate: &ValidatorSetUpdateCertificate<TestContext>,
+) -> Result<proto::ValidatorSetUpdateCertificate, ProtoError> {
+    Ok(proto::ValidatorSetUpdateCertificate {
+        update: Some(encode_validator_set_update(&certificate.update)),
+        signatures: certificate
+            .signatures
+            .iter()
+            .map(
+                |sig| -> Result<proto::ValidatorSetUpdateSignature, ProtoError> {
+                    Ok(proto::ValidatorSetUpdateSignature {
+                        validator_address: Some(sig.address.to_proto()?),
+                        signature: Some(encode_signature(&sig.signature)),
+                    })
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?,
+    })
+}
+
+pub fn decode_validator_set_update_certificate(
+    certificate: proto::ValidatorSetUpdateCertificate,
+) -> Result<ValidatorSetUpdateCertificate<TestContext>, ProtoError> {
+    let update = certificate.update.ok_or_else(|| {
+        ProtoError::missing_field::<proto::ValidatorSetUpdateCertificate>("update")
+    })?;
+
+    let signatures = certificate
+        .signatures
+        .into_iter()
+        .map(
+            |sig| -> Result<ValidatorSetUpdateSignature<TestContext>, ProtoError> {
+                let address = sig.validator_address.ok_or_else(|| {
+                    ProtoError::missing_field::<proto::ValidatorSetUpdateSignature>(
+                        "validator_address",
+                    )
+                })?;
+
+                let signature = sig.signature.ok_or_else(|| {
+                    ProtoError::missing_field::<proto::ValidatorSetUpdateSignature>("signature")
+                })?;
+
+                Ok(ValidatorSetUpdateSignature::new(
+                    Address::from_proto(address)?,
+                    decode_signature(signature)?,
+                ))
+            },
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ValidatorSetUpdateCertificate::new(
+        decode_validator_set_update(update)?,
+        signatures,
+    ))
+}
+
 impl Codec<LivenessMsg<TestContext>> for ProtobufCodec {
     type Error = ProtoError;
 
@@ -198,6 +352,9 @@ impl Codec<LivenessMsg<TestContext>> for ProtobufCodec {
             Some(proto::liveness_message::Message::RoundCertificate(cert)) => Ok(
                 LivenessMsg::SkipRoundCertificate(decode_round_certificate(cert)?),
             ),
+            Some(proto::liveness_message::Message::ValidatorSetUpdate(cert)) => Ok(
+                LivenessMsg::ValidatorSetUpdate(decode_validator_set_update_certificate(cert)?),
+            ),
             None => Err(ProtoError::missing_field::<proto::LivenessMessage>(
                 "message",
             )),
@@ -233,6 +390,17 @@ impl Codec<LivenessMsg<TestContext>> for ProtobufCodec {
                     .encode_to_vec(),
                 ))
             }
+            LivenessMsg::ValidatorSetUpdate(cert) => {
+                let message = encode_validator_set_update_certificate(cert)?;
+                Ok(Bytes::from(
+                    proto::LivenessMessage {
+                        message: Some(proto::liveness_message::Message::ValidatorSetUpdate(
+                            message,
+                        )),
+                    }
+                    .encode_to_vec(),
+                ))
+            }
         }
     }
 }
@@ -326,9 +494,12 @@ impl Codec<sync::Status<TestContext>> for ProtobufCodec {
             .ok_or_else(|| ProtoError::missing_field::<proto::Status>("peer_id"))?;
 
         Ok(sync::Status {
-            peer_id: PeerId::from_bytes(proto_peer_id.id.as_ref()).unwrap(),
+            peer_id: PeerId::from_bytes(proto_peer_id.id.as_ref())
+                .map_err(|_| ProtoError::invalid_data::<proto::Status>("peer_id"))?,
             tip_height: Height::new(proto.height),
             history_min_height: Height::new(proto.earliest_height),
+            sync_height: Height::new(proto.sync_height),
+            mode: sync::NodeMode::from_is_validator(proto.validator),
         })
     }
 
@@ -339,6 +510,49 @@ impl Codec<sync::Status<TestContext>> for ProtobufCodec {
             }),
             height: msg.tip_height.as_u64(),
             earliest_height: msg.history_min_height.as_u64(),
+            sync_height: msg.sync_height.as_u64(),
+            validator: msg.mode.is_validator(),
+        };
+
+        Ok(Bytes::from(proto.encode_to_vec()))
+    }
+}
+
+impl Codec<sync::Announcement<TestContext>> for ProtobufCodec {
+    type Error = ProtoError;
+
+    fn decode(&self, bytes: Bytes) -> Result<sync::Announcement<TestContext>, Self::Error> {
+        let proto = proto::Announcement::decode(bytes.as_ref())?;
+
+        let proto_peer_id = proto
+            .peer_id
+            .ok_or_else(|| ProtoError::missing_field::<proto::Announcement>("peer_id"))?;
+
+        let value_id = proto
+            .value_id
+            .ok_or_else(|| ProtoError::missing_field::<proto::Announcement>("value_id"))?;
+
+        Ok(sync::Announcement {
+            peer_id: PeerId::from_bytes(proto_peer_id.id.as_ref())
+                .map_err(|_| ProtoError::invalid_data::<proto::Announcement>("peer_id"))?,
+            height: Height::new(proto.height),
+            value_id: ValueId::from_proto(value_id)?,
+            certificate_hash: proto
+                .certificate_hash
+                .as_ref()
+                .try_into()
+                .map_err(|_| ProtoError::invalid_data::<proto::Announcement>("certificate_hash"))?,
+        })
+    }
+
+    fn encode(&self, msg: &sync::Announcement<TestContext>) -> Result<Bytes, Self::Error> {
+        let proto = proto::Announcement {
+            peer_id: Some(proto::PeerId {
+                id: Bytes::from(msg.peer_id.to_bytes()),
+            }),
+            height: msg.height.as_u64(),
+            value_id: Some(msg.value_id.to_proto()?),
+            certificate_hash: Bytes::copy_from_slice(&msg.certificate_hash),
         };
 
         Ok(Bytes::from(proto.encode_to_vec()))
@@ -406,6 +620,18 @@ impl HasEncodedLen<sync::Response<TestContext>> for ProtobufCodec {
     }
 }
 
+impl Codec<sync::RawDecidedValue<TestContext>> for ProtobufCodec {
+    type Error = ProtoError;
+
+    fn decode(&self, bytes: Bytes) -> Result<sync::RawDecidedValue<TestContext>, Self::Error> {
+        decode_synced_value(proto::SyncedValue::decode(bytes)?)
+    }
+
+    fn encode(&self, value: &sync::RawDecidedValue<TestContext>) -> Result<Bytes, Self::Error> {
+        encode_synced_value(value).map(|proto| proto.encode_to_vec().into())
+    }
+}
+
 pub fn decode_sync_response(
     proto_response: proto::SyncResponse,
 ) -> Result<sync::Response<TestContext>, ProtoError> {
@@ -1,9 +1,11 @@
+use std::fmt;
 use std::sync::Arc;
 
 use bytes::Bytes;
 
-use malachitebft_core_types::LinearTimeouts;
-use malachitebft_core_types::{Context, NilOrVal, Round, ValidatorSet as _};
+use malachitebft_core_consensus::proposer::{ProposerSelector, RoundRobin};
+use malachitebft_core_types::{ChainId, Context, NilOrVal, Round};
+use malachitebft_core_types::{LinearTimeouts, SigningScheme, ValidatorSetUpdate};
 
 use crate::address::*;
 use crate::height::*;
@@ -16,9 +18,27 @@ use crate::validator_set::*;
 use crate::value::*;
 use crate::vote::*;
 
-#[derive(Clone, Debug)]
+/// Chain id of the votes and proposals built by a [`TestContext`], unless overridden.
+pub const DEFAULT_CHAIN_ID: &str = "malachite-test";
+
+pub fn default_chain_id() -> ChainId {
+    ChainId::new(DEFAULT_CHAIN_ID).expect("default chain id is valid")
+}
+
+#[derive(Clone)]
 pub struct TestContext {
     middleware: Arc<dyn Middleware>,
+    proposer_selector: Arc<dyn ProposerSelector<TestContext>>,
+    pub(crate) chain_id: ChainId,
+}
+
+impl fmt::Debug for TestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestContext")
+            .field("middleware", &self.middleware)
+            .field("chain_id", &self.chain_id)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Default for TestContext {
@@ -33,7 +53,26 @@ impl TestContext {
     }
 
     pub fn with_middleware(middleware: Arc<dyn Middleware>) -> Self {
-        Self { middleware }
+        Self {
+            middleware,
+            proposer_selector: Arc::new(RoundRobin),
+            chain_id: default_chain_id(),
+        }
+    }
+
+    pub fn with_chain_id(self, chain_id: ChainId) -> Self {
+        Self { chain_id, ..self }
+    }
+
+    /// Select the proposers with the given selector, instead of rotating through the validators.
+    pub fn with_proposer_selector(
+        self,
+        proposer_selector: Arc<dyn ProposerSelector<TestContext>>,
+    ) -> Self {
+        Self {
+            proposer_selector,
+            ..self
+        }
     }
 
     pub fn middleware(&self) -> &Arc<dyn Middleware> {
@@ -46,19 +85,8 @@ impl TestContext {
         height: Height,
         round: Round,
     ) -> &'a Validator {
-        assert!(validator_set.count() > 0);
-        assert!(round != Round::Nil && round.as_i64() >= 0);
-
-        let proposer_index = {
-            let height = height.as_u64() as usize;
-            let round = round.as_i64() as usize;
-
-            (height - 1 + round) % validator_set.count()
-        };
-
-        validator_set
-            .get_by_index(proposer_index)
-            .expect("proposer_index is valid")
+        self.proposer_selector
+            .select_proposer(validator_set, height, round)
     }
 }
 
@@ -84,6 +112,10 @@ impl Context for TestContext {
         self.select_proposer(validator_set, height, round)
     }
 
+    fn chain_id(&self) -> Option<&ChainId> {
+        Some(&self.chain_id)
+    }
+
     fn new_proposal(
         &self,
         height: Height,
@@ -117,4 +149,33 @@ impl Context for TestContext {
         self.middleware
             .new_precommit(self, height, round, value_id, address)
     }
+
+    fn apply_validator_set_update(
+        &self,
+        validator_set: &ValidatorSet,
+        update: &ValidatorSetUpdate<Self>,
+    ) -> Option<ValidatorSet> {
+        let mut validators = validator_set.validators.to_vec();
+
+        for change in &update.diff {
+            let public_key = Ed25519::decode_public_key(&change.public_key).ok()?;
+            validators.retain(|v| v.public_key != public_key);
+
+            if !change.is_removal() {
+                validators.push(Validator::new(public_key, change.voting_power));
+            }
+        }
+
+        if validators.is_empty() {
+            return None;
+        }
+
+        // The validator set must not overflow its total voting power
+        validators
+            .iter()
+            .try_fold(0u64, |acc, v| acc.checked_add(v.voting_power))?;
+
+        validators.sort();
+        Some(ValidatorSet::new(validators))
+    }
 }
@@ -0,0 +1,45 @@
+use sha3::Digest;
+
+pub use malachitebft_core_types::hash::{Blake3, Hasher, Sha256};
+
+/// The Keccak-256 hash function, used by default to derive value ids.
+#[derive(Clone, Debug, Default)]
+pub struct Keccak256(sha3::Keccak256);
+
+impl Hasher for Keccak256 {
+    type Output = [u8; 32];
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        self.0.finalize().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Value, ValueId};
+
+    #[test]
+    fn hashed_value_ids() {
+        let value = Value::new(42);
+
+        let keccak = value.hashed_id::<Keccak256>();
+        let sha256 = value.hashed_id::<Sha256>();
+        let blake3 = value.hashed_id::<Blake3>();
+
+        assert_ne!(keccak, sha256);
+        assert_ne!(keccak, blake3);
+        assert_ne!(sha256, blake3);
+
+        assert_eq!(keccak, Value::new(42).hashed_id::<Keccak256>());
+        assert_ne!(keccak, Value::new(43).hashed_id::<Keccak256>());
+
+        let digest = Sha256::digest(&42u64.to_be_bytes());
+        let expected = u64::from_be_bytes(digest[..8].try_into().unwrap());
+        assert_eq!(sha256, ValueId::new(expected));
+    }
+}
@@ -0,0 +1,231 @@
+//! Layout of the home directory of a node, and its initialization.
+//!
+//! ```text
+//! <home>/
+//! ├── config/          configuration, genesis and key files
+//! ├── wal/
+//! │   └── consensus.wal
+//! ├── db/              storage of the application
+//! └── node.lock        held while a node is running out of this home directory
+//! ```
+//!
+//! [`initialize_home_dir`] creates the layout, and takes the lock file so that two nodes
+//! cannot run out of the same home directory, which would corrupt their WAL and storage.
+//! A home directory which does not exist yet is populated aside and renamed into place,
+//! so that a node interrupted while creating it never leaves a partial layout behind.
+//!
+//! The lock is an advisory lock held by the operating system on the open lock file, which also
+//! records the id of the process holding it. It is released when the [`HomeDirLock`] is dropped,
+//! or by the operating system when the process exits, so that a node killed abruptly, eg. by
+//! `SIGKILL` or a power loss, can be restarted right away. The lock file itself is left in place,
+//! as removing it would let another node lock a new file while a third one still holds the old one.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use fs4::fs_std::FileExt;
+
+/// Name of the directory holding the configuration, genesis and key files
+pub const CONFIG_DIR: &str = "config";
+
+/// Name of the directory holding the consensus WAL
+pub const WAL_DIR: &str = "wal";
+
+/// Name of the consensus WAL file, in the WAL directory
+pub const WAL_FILE: &str = "consensus.wal";
+
+/// Name of the directory holding the storage of the application
+pub const DB_DIR: &str = "db";
+
+/// Name of the lock file held while a node is running
+pub const LOCK_FILE: &str = "node.lock";
+
+/// Directory holding the configuration, genesis and key files
+pub fn config_dir(home_dir: &Path) -> PathBuf {
+    home_dir.join(CONFIG_DIR)
+}
+
+/// Directory holding the consensus WAL
+pub fn wal_dir(home_dir: &Path) -> PathBuf {
+    home_dir.join(WAL_DIR)
+}
+
+/// Path of the consensus WAL
+pub fn wal_path(home_dir: &Path) -> PathBuf {
+    wal_dir(home_dir).join(WAL_FILE)
+}
+
+/// Directory holding the storage of the application
+pub fn db_dir(home_dir: &Path) -> PathBuf {
+    home_dir.join(DB_DIR)
+}
+
+/// Path of the lock file held while a node is running
+pub fn lock_file(home_dir: &Path) -> PathBuf {
+    home_dir.join(LOCK_FILE)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum HomeDirError {
+    #[error("{} exists but is not a directory", .0.display())]
+    NotADirectory(PathBuf),
+
+    #[error(
+        "Home directory is already in use{}, as its lock file {} is locked",
+        pid.map(|pid| format!(" by process {pid}")).unwrap_or_default(),
+        path.display()
+    )]
+    Locked { path: PathBuf, pid: Option<u32> },
+
+    #[error("Failed to initialize {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+}
+
+impl HomeDirError {
+    fn io(path: &Path) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Self::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+/// Lock on a home directory, released when dropped.
+#[derive(Debug)]
+pub struct HomeDirLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl HomeDirLock {
+    /// Take the lock on the given lock file, creating it if needed.
+    ///
+    /// A lock file left behind by a process which exited without releasing it does not
+    /// prevent taking the lock, as its lock was released by the operating system.
+    pub fn acquire(path: PathBuf) -> Result<Self, HomeDirError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(HomeDirError::io(&path))?;
+
+        if !FileExt::try_lock_exclusive(&file).map_err(HomeDirError::io(&path))? {
+            let pid = fs::read_to_string(&path)
+                .ok()
+                .and_then(|pid| pid.trim().parse().ok());
+
+            return Err(HomeDirError::Locked { path, pid });
+        }
+
+        file.set_len(0)
+            .and_then(|()| writeln!(file, "{}", std::process::id()))
+            .and_then(|()| file.sync_all())
+            .map_err(HomeDirError::io(&path))?;
+
+        Ok(Self { path, file })
+    }
+
+    /// Path of the lock file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for HomeDirLock {
+    fn drop(&mut self) {
+        // Closing the file releases the lock as well, but be explicit about it
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// Create the layout of the given home directory if needed, and lock it.
+///
+/// Fails with [`HomeDirError::Locked`] if another node is running out of this home directory,
+/// and with [`HomeDirError::NotADirectory`] if any part of the layout exists but is not a directory.
+pub fn initialize_home_dir(home_dir: &Path) -> Result<HomeDirLock, HomeDirError> {
+    if !home_dir.exists() {
+        create_atomically(home_dir)?;
+    }
+
+    check_dir(home_dir)?;
+
+    // Complete the layout of an existing home directory
+    for dir in [config_dir(home_dir), wal_dir(home_dir), db_dir(home_dir)] {
+        ensure_dir(&dir)?;
+    }
+
+    HomeDirLock::acquire(lock_file(home_dir))
+}
+
+/// Populate the layout in a temporary sibling of the home directory, and rename it into place.
+fn create_atomically(home_dir: &Path) -> Result<(), HomeDirError> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let parent = match home_dir.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    fs::create_dir_all(parent).map_err(HomeDirError::io(parent))?;
+
+    let name = home_dir
+        .file_name()
+        .ok_or_else(|| HomeDirError::NotADirectory(home_dir.to_path_buf()))?;
+
+    let tmp_dir = parent.join(format!(
+        ".{}.tmp-{}-{}",
+        name.to_string_lossy(),
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = populate(&tmp_dir).and_then(|()| match fs::rename(&tmp_dir, home_dir) {
+        Ok(()) => Ok(()),
+        // Another node created the home directory in the meantime
+        Err(_) if home_dir.is_dir() => Ok(()),
+        Err(source) => Err(HomeDirError::Io {
+            path: home_dir.to_path_buf(),
+            source,
+        }),
+    });
+
+    // Nothing is left to clean up if the rename succeeded
+    let _ = fs::remove_dir_all(&tmp_dir);
+
+    result
+}
+
+fn populate(dir: &Path) -> Result<(), HomeDirError> {
+    fs::create_dir(dir).map_err(HomeDirError::io(dir))?;
+
+    for sub_dir in [config_dir(dir), wal_dir(dir), db_dir(dir)] {
+        fs::create_dir(&sub_dir).map_err(HomeDirError::io(&sub_dir))?;
+    }
+
+    Ok(())
+}
+
+fn ensure_dir(dir: &Path) -> Result<(), HomeDirError> {
+    match fs::create_dir(dir) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => check_dir(dir),
+        Err(source) => Err(HomeDirError::Io {
+            path: dir.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+fn check_dir(dir: &Path) -> Result<(), HomeDirError> {
+    let metadata = fs::metadata(dir).map_err(HomeDirError::io(dir))?;
+
+    if !metadata.is_dir() {
+        return Err(HomeDirError::NotADirectory(dir.to_path_buf()));
+    }
+
+    Ok(())
+}
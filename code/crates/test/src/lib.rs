@@ -15,10 +15,13 @@ mod vote;
 
 pub mod byzantine;
 pub mod codec;
+pub mod hash;
+pub mod home_dir;
 pub mod middleware;
 pub mod node;
 pub mod proposer_selector;
 pub mod proto;
+pub mod topology;
 pub mod traits;
 pub mod utils;
 
@@ -1,7 +1,10 @@
 use core::fmt;
+use core::time::Duration;
 
 use malachitebft_core_consensus::{LocallyProposedValue, ProposedValue};
-use malachitebft_core_types::{CommitCertificate, LinearTimeouts, NilOrVal, Round, Validity};
+use malachitebft_core_types::{
+    CommitCertificate, HeightParamsOverride, LinearTimeouts, NilOrVal, Round, TimeoutKind, Validity,
+};
 
 use crate::{Address, Genesis, Height, Proposal, TestContext, ValidatorSet, Value, ValueId, Vote};
 
@@ -25,38 +28,60 @@ pub trait Middleware: fmt::Debug + Send + Sync {
         None
     }
 
-    fn new_proposal(
+    /// Called at the start of every round when timeout overrides are enabled,
+    /// to override the duration of the given kind of timeout for that round
+    fn get_timeout_override(
+        &self,
+        _ctx: &TestContext,
+        _height: Height,
+        _round: Round,
+        _kind: TimeoutKind,
+    ) -> Option<Duration> {
+        None
+    }
+
+    /// Called before starting every height when consensus parameter overrides are enabled,
+    /// to override the consensus parameters of that height
+    fn get_consensus_params(
         &self,
         _ctx: &TestContext,
+        _height: Height,
+    ) -> HeightParamsOverride<TestContext> {
+        HeightParamsOverride::default()
+    }
+
+    fn new_proposal(
+        &self,
+        ctx: &TestContext,
         height: Height,
         round: Round,
         value: Value,
         pol_round: Round,
         address: Address,
     ) -> Proposal {
-        Proposal::new(height, round, value, pol_round, address)
+        Proposal::new(height, round, value, pol_round, address).with_chain_id(ctx.chain_id.clone())
     }
 
     fn new_prevote(
         &self,
-        _ctx: &TestContext,
+        ctx: &TestContext,
         height: Height,
         round: Round,
         value_id: NilOrVal<ValueId>,
         address: Address,
     ) -> Vote {
-        Vote::new_prevote(height, round, value_id, address)
+        Vote::new_prevote(height, round, value_id, address).with_chain_id(ctx.chain_id.clone())
     }
 
     fn new_precommit(
         &self,
-        _ctx: &TestContext,
+        ctx: &TestContext,
         height: Height,
         round: Round,
         value_id: NilOrVal<ValueId>,
         address: Address,
     ) -> Vote {
-        Vote::new_precommit(height, round, value_id, address)
+        Vote::new_precommit(height, round, value_id, address).with_chain_id(ctx.chain_id.clone())
     }
 
     fn on_propose_value(
@@ -104,6 +129,16 @@ pub trait Middleware: fmt::Debug + Send + Sync {
     fn fail_synced_value_decode(&self, _ctx: &TestContext, _height: Height, _round: Round) -> bool {
         false
     }
+
+    /// If true, the synced value is reported as invalid to consensus.
+    fn fail_synced_value_validation(
+        &self,
+        _ctx: &TestContext,
+        _height: Height,
+        _round: Round,
+    ) -> bool {
+        false
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -11,6 +11,8 @@ use serde::Serialize;
 use malachitebft_core_types::{Context, PrivateKey, PublicKey};
 use malachitebft_signing::{Signer, Verifier};
 
+use crate::home_dir::{self, HomeDirError, HomeDirLock};
+
 pub use libp2p_identity::Keypair;
 
 #[async_trait]
@@ -21,6 +23,9 @@ where
 {
     fn subscribe(&self) -> RxEvent<Ctx>;
     async fn kill(&self, reason: Option<String>) -> eyre::Result<()>;
+
+    /// Shut the node down gracefully, waiting for its actors to drain and stop.
+    async fn stop(&self) -> eyre::Result<()>;
 }
 
 #[async_trait]
@@ -39,6 +44,34 @@ pub trait Node {
 
     fn get_home_dir(&self) -> PathBuf;
 
+    /// Directory holding the configuration, genesis and key files of the node
+    fn get_config_dir(&self) -> PathBuf {
+        home_dir::config_dir(&self.get_home_dir())
+    }
+
+    /// Directory holding the consensus WAL of the node
+    fn get_wal_dir(&self) -> PathBuf {
+        home_dir::wal_dir(&self.get_home_dir())
+    }
+
+    /// Path of the consensus WAL of the node
+    fn get_wal_path(&self) -> PathBuf {
+        home_dir::wal_path(&self.get_home_dir())
+    }
+
+    /// Directory holding the storage of the application
+    fn get_db_dir(&self) -> PathBuf {
+        home_dir::db_dir(&self.get_home_dir())
+    }
+
+    /// Create the layout of the home directory if needed, and lock it for as long as
+    /// the returned lock is held, so that no other node can run out of it in the meantime.
+    ///
+    /// See [`home_dir::initialize_home_dir`].
+    fn initialize_home_dir(&self) -> Result<HomeDirLock, HomeDirError> {
+        home_dir::initialize_home_dir(&self.get_home_dir())
+    }
+
     fn load_config(&self) -> eyre::Result<Self::Config>;
 
     fn get_address(&self, pk: &PublicKey<Self::Context>) -> <Self::Context as Context>::Address;
@@ -1,8 +1,8 @@
 use bytes::Bytes;
-use malachitebft_core_types::Round;
+use malachitebft_core_types::{ChainId, Round};
 use malachitebft_proto::{Error as ProtoError, Protobuf};
 
-use crate::{Address, Height, TestContext, Value};
+use crate::{default_chain_id, Address, Height, TestContext, Value};
 
 /// A proposal for a value in a round
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -12,6 +12,7 @@ pub struct Proposal {
     pub value: Value,
     pub pol_round: Round,
     pub validator_address: Address,
+    pub chain_id: ChainId,
 }
 
 impl Proposal {
@@ -28,9 +29,14 @@ impl Proposal {
             value,
             pol_round,
             validator_address,
+            chain_id: default_chain_id(),
         }
     }
 
+    pub fn with_chain_id(self, chain_id: ChainId) -> Self {
+        Self { chain_id, ..self }
+    }
+
     pub fn to_sign_bytes(&self) -> Bytes {
         Protobuf::to_bytes(self).unwrap()
     }
@@ -64,6 +70,10 @@ impl malachitebft_core_types::Proposal<TestContext> for Proposal {
     fn validator_address(&self) -> &Address {
         &self.validator_address
     }
+
+    fn chain_id(&self) -> Option<&ChainId> {
+        Some(&self.chain_id)
+    }
 }
 
 impl Protobuf for Proposal {
@@ -77,6 +87,7 @@ impl Protobuf for Proposal {
             value: Some(self.value.to_proto()?),
             pol_round: self.pol_round.as_u32(),
             validator_address: Some(self.validator_address.to_proto()?),
+            chain_id: self.chain_id.to_string(),
         })
     }
 
@@ -96,6 +107,8 @@ impl Protobuf for Proposal {
                     .validator_address
                     .ok_or_else(|| ProtoError::missing_field::<Self::Proto>("validator_address"))?,
             )?,
+            chain_id: ChainId::new(proto.chain_id)
+                .map_err(|_| ProtoError::invalid_data::<Self::Proto>("chain_id"))?,
         })
     }
 }
@@ -1,7 +1,9 @@
 use async_trait::async_trait;
 use bytes::Bytes;
 
-use malachitebft_core_types::{SignedExtension, SignedProposal, SignedVote, ValidatorProof};
+use malachitebft_core_types::{
+    SignedExtension, SignedProposal, SignedVote, ValidatorProof, ValidatorSetUpdate,
+};
 use malachitebft_signing::{Error, Signer, VerificationResult, Verifier};
 
 use crate::{Proposal, TestContext, Vote};
@@ -48,6 +50,25 @@ impl Verifier<TestContext> for Ed25519Verifier {
         ))
     }
 
+    async fn verify_signed_votes(
+        &self,
+        votes: &[(SignedVote<TestContext>, PublicKey)],
+    ) -> Result<VerificationResult, Error> {
+        let sign_bytes = votes
+            .iter()
+            .map(|(vote, _)| vote.message.to_sign_bytes())
+            .collect::<Vec<_>>();
+
+        let signatures = votes
+            .iter()
+            .zip(&sign_bytes)
+            .map(|((vote, public_key), bytes)| (public_key, bytes.as_ref(), &vote.signature));
+
+        Ok(VerificationResult::from_bool(
+            Ed25519::verify_batch(signatures, rand::thread_rng()).is_ok(),
+        ))
+    }
+
     async fn verify_signed_proposal(
         &self,
         proposal: &Proposal,
@@ -85,6 +106,19 @@ impl Verifier<TestContext> for Ed25519Verifier {
             &public_key,
         )))
     }
+
+    async fn verify_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<TestContext>,
+        signature: &Signature,
+        public_key: &PublicKey,
+    ) -> Result<VerificationResult, Error> {
+        Ok(VerificationResult::from_bool(Self::verify(
+            &update.signing_bytes(),
+            signature,
+            public_key,
+        )))
+    }
 }
 
 /// Message signer backed by an Ed25519 private key.
@@ -125,6 +159,13 @@ impl Verifier<TestContext> for Ed25519Signer {
             .await
     }
 
+    async fn verify_signed_votes(
+        &self,
+        votes: &[(SignedVote<TestContext>, PublicKey)],
+    ) -> Result<VerificationResult, Error> {
+        Ed25519Verifier.verify_signed_votes(votes).await
+    }
+
     async fn verify_signed_proposal(
         &self,
         proposal: &Proposal,
@@ -153,6 +194,17 @@ impl Verifier<TestContext> for Ed25519Signer {
     ) -> Result<VerificationResult, Error> {
         Ed25519Verifier.verify_validator_proof(proof).await
     }
+
+    async fn verify_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<TestContext>,
+        signature: &Signature,
+        public_key: &PublicKey,
+    ) -> Result<VerificationResult, Error> {
+        Ed25519Verifier
+            .verify_validator_set_update(update, signature, public_key)
+            .await
+    }
 }
 
 #[async_trait]
@@ -189,4 +241,11 @@ impl Signer<TestContext> for Ed25519Signer {
         let signature = self.private_key.sign(&preimage);
         Ok(ValidatorProof::new(public_key, peer_id, signature))
     }
+
+    async fn sign_validator_set_update(
+        &self,
+        update: &ValidatorSetUpdate<TestContext>,
+    ) -> Result<Signature, Error> {
+        Ok(self.private_key.sign(&update.signing_bytes()))
+    }
 }
@@ -0,0 +1,150 @@
+//! Topologies of the persistent peers of a testnet.
+
+use core::fmt;
+use core::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::seq::IteratorRandom;
+use rand::SeedableRng;
+
+/// Seed used to generate random topologies, so that every node agrees on the same graph.
+const RANDOM_TOPOLOGY_SEED: u64 = 0x42;
+
+/// Shape of the graph formed by the persistent peers of the nodes of a testnet.
+///
+/// All topologies are symmetric: if node `i` is a persistent peer of node `j`,
+/// then node `j` is a persistent peer of node `i`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Topology {
+    /// Every node is connected to every other node
+    #[default]
+    FullMesh,
+
+    /// Every node is connected to the nodes immediately before and after it
+    Ring,
+
+    /// Every node is connected to the first node, which is connected to every other node
+    Star,
+
+    /// Every node is connected to at least `degree` randomly chosen nodes
+    Random { degree: usize },
+}
+
+impl Topology {
+    /// Indices of the persistent peers of node `index` out of `total` nodes.
+    pub fn peers(&self, index: usize, total: usize) -> Vec<usize> {
+        let others = (0..total).filter(|&j| j != index);
+
+        match *self {
+            Topology::FullMesh => others.collect(),
+
+            Topology::Ring => others
+                .filter(|&j| (index + 1) % total == j || (j + 1) % total == index)
+                .collect(),
+
+            Topology::Star if index == 0 => others.collect(),
+            Topology::Star => others.filter(|&j| j == 0).collect(),
+
+            Topology::Random { degree } => {
+                let mut rng = StdRng::seed_from_u64(RANDOM_TOPOLOGY_SEED);
+
+                // Every node picks its own peers, then links are made symmetric
+                let choices = (0..total)
+                    .map(|i| {
+                        (0..total)
+                            .filter(|&j| j != i)
+                            .choose_multiple(&mut rng, degree)
+                    })
+                    .collect::<Vec<_>>();
+
+                others
+                    .filter(|&j| choices[index].contains(&j) || choices[j].contains(&index))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Topology::FullMesh => write!(f, "full"),
+            Topology::Ring => write!(f, "ring"),
+            Topology::Star => write!(f, "star"),
+            Topology::Random { degree } => write!(f, "random:{degree}"),
+        }
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("random", degree)) => Ok(Topology::Random {
+                degree: degree
+                    .parse()
+                    .map_err(|_| format!("Invalid degree: {degree}"))?,
+            }),
+            Some(_) => Err(format!("Invalid topology: {s}")),
+            None => match s {
+                "full" => Ok(Topology::FullMesh),
+                "ring" => Ok(Topology::Ring),
+                "star" => Ok(Topology::Star),
+                _ => Err(format!("Invalid topology: {s}")),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_symmetric(topology: Topology, total: usize) -> bool {
+        (0..total).all(|i| {
+            topology
+                .peers(i, total)
+                .into_iter()
+                .all(|j| topology.peers(j, total).contains(&i))
+        })
+    }
+
+    #[test]
+    fn topologies() {
+        assert_eq!(Topology::FullMesh.peers(1, 4), vec![0, 2, 3]);
+
+        assert_eq!(Topology::Ring.peers(0, 5), vec![1, 4]);
+        assert_eq!(Topology::Ring.peers(2, 5), vec![1, 3]);
+        assert_eq!(Topology::Ring.peers(0, 2), vec![1]);
+
+        assert_eq!(Topology::Star.peers(0, 4), vec![1, 2, 3]);
+        assert_eq!(Topology::Star.peers(3, 4), vec![0]);
+
+        let random = Topology::Random { degree: 2 };
+        for i in 0..10 {
+            assert!(random.peers(i, 10).len() >= 2);
+        }
+
+        for topology in [Topology::FullMesh, Topology::Ring, Topology::Star, random] {
+            assert!(is_symmetric(topology, 10));
+            assert!(topology.peers(0, 1).is_empty());
+        }
+    }
+
+    #[test]
+    fn parse_topology() {
+        for topology in [
+            Topology::FullMesh,
+            Topology::Ring,
+            Topology::Star,
+            Topology::Random { degree: 3 },
+        ] {
+            assert_eq!(topology.to_string().parse(), Ok(topology));
+        }
+
+        assert!("random".parse::<Topology>().is_err());
+        assert!("random:x".parse::<Topology>().is_err());
+        assert!("mesh".parse::<Topology>().is_err());
+    }
+}
@@ -4,14 +4,34 @@ use malachitebft_config::{DiscoveryConfig, RuntimeConfig, TransportProtocol, Val
 use malachitebft_core_types::{PrivateKey, PublicKey, VotingPower};
 
 use crate::node::Node;
+use crate::topology::Topology;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct MakeConfigSettings {
     pub runtime: RuntimeConfig,
     pub transport: TransportProtocol,
     pub discovery: DiscoveryConfig,
     pub value_sync: ValueSyncConfig,
     pub persistent_peers_only: bool,
+    pub topology: Topology,
+    /// Indices of the nodes used to bootstrap discovery, if enabled.
+    /// When empty, the persistent peers follow the topology instead.
+    pub bootstrap_nodes: Vec<usize>,
+}
+
+impl MakeConfigSettings {
+    /// Indices of the persistent peers of node `index` out of `total` nodes.
+    pub fn persistent_peers(&self, index: usize, total: usize) -> Vec<usize> {
+        if self.discovery.enabled && !self.bootstrap_nodes.is_empty() {
+            self.bootstrap_nodes
+                .iter()
+                .copied()
+                .filter(|&j| j != index && j < total)
+                .collect()
+        } else {
+            self.topology.peers(index, total)
+        }
+    }
 }
 
 pub trait CanMakeConfig: Node {
@@ -44,4 +64,10 @@ pub trait CanMakeGenesis: Node {
         &self,
         validators: Vec<(PublicKey<Self::Context>, VotingPower)>,
     ) -> Self::Genesis;
+
+    /// The validators of the given genesis, in the order they appear in it.
+    fn genesis_validators(
+        &self,
+        genesis: &Self::Genesis,
+    ) -> Vec<(PublicKey<Self::Context>, VotingPower)>;
 }
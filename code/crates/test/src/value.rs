@@ -1,5 +1,6 @@
 use bytes::{Bytes, BytesMut};
 use core::fmt;
+use malachitebft_core_types::hash::Hasher;
 use malachitebft_proto::{Error as ProtoError, Protobuf};
 use serde::{Deserialize, Serialize};
 
@@ -16,6 +17,17 @@ impl ValueId {
     pub const fn as_u64(&self) -> u64 {
         self.0
     }
+
+    /// Derive a value id from the first 8 bytes of the digest computed by the given hasher.
+    pub fn from_hasher<H: Hasher>(hasher: H) -> Self {
+        let digest = hasher.finalize();
+
+        let mut bytes = [0u8; 8];
+        let len = digest.as_ref().len().min(bytes.len());
+        bytes[..len].copy_from_slice(&digest.as_ref()[..len]);
+
+        Self::new(u64::from_be_bytes(bytes))
+    }
 }
 
 impl From<u64> for ValueId {
@@ -77,6 +89,15 @@ impl Value {
         ValueId(self.value)
     }
 
+    /// Derive the id of this value by hashing its contents with the hash function `H`,
+    /// for applications which identify values by their digest rather than by their number.
+    pub fn hashed_id<H: Hasher>(&self) -> ValueId {
+        let mut hasher = H::default();
+        hasher.update(&self.value.to_be_bytes());
+        hasher.update(&self.extensions);
+        ValueId::from_hasher(hasher)
+    }
+
     pub fn size_bytes(&self) -> usize {
         std::mem::size_of_val(&self.value) + self.extensions.len()
     }
@@ -99,12 +120,15 @@ impl Protobuf for Value {
             .value
             .ok_or_else(|| ProtoError::missing_field::<Self::Proto>("value"))?;
 
-        let value = bytes[0..8].try_into().map_err(|_| {
-            ProtoError::Other(format!(
-                "Too few bytes, expected at least {}",
-                u64::BITS / 8
-            ))
-        })?;
+        let value = bytes
+            .get(0..8)
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| {
+                ProtoError::Other(format!(
+                    "Too few bytes, expected at least {}",
+                    u64::BITS / 8
+                ))
+            })?;
 
         let extensions = bytes.slice(8..);
 
@@ -1,9 +1,9 @@
 use bytes::Bytes;
-use malachitebft_core_types::{NilOrVal, Round, SignedExtension, VoteType};
+use malachitebft_core_types::{ChainId, NilOrVal, Round, SignedExtension, VoteType};
 use malachitebft_proto::{Error as ProtoError, Protobuf};
 
 use crate::proto;
-use crate::{Address, Height, TestContext, ValueId};
+use crate::{default_chain_id, Address, Height, TestContext, ValueId};
 
 pub use malachitebft_core_types::Extension;
 
@@ -16,6 +16,7 @@ pub struct Vote {
     pub value: NilOrVal<ValueId>,
     pub validator_address: Address,
     pub extension: Option<SignedExtension<TestContext>>,
+    pub chain_id: ChainId,
 }
 
 impl Vote {
@@ -32,6 +33,7 @@ impl Vote {
             value,
             validator_address,
             extension: None,
+            chain_id: default_chain_id(),
         }
     }
 
@@ -48,9 +50,14 @@ impl Vote {
             value,
             validator_address: address,
             extension: None,
+            chain_id: default_chain_id(),
         }
     }
 
+    pub fn with_chain_id(self, chain_id: ChainId) -> Self {
+        Self { chain_id, ..self }
+    }
+
     pub fn to_sign_bytes(&self) -> Bytes {
         let vote = Self {
             extension: None,
@@ -98,6 +105,10 @@ impl malachitebft_core_types::Vote<TestContext> for Vote {
         self.extension.take()
     }
 
+    fn chain_id(&self) -> Option<&ChainId> {
+        Some(&self.chain_id)
+    }
+
     fn extend(self, extension: SignedExtension<TestContext>) -> Self {
         Self {
             extension: Some(extension),
@@ -125,6 +136,8 @@ impl Protobuf for Vote {
                     .ok_or_else(|| ProtoError::missing_field::<Self::Proto>("validator_address"))?,
             )?,
             extension: Default::default(),
+            chain_id: ChainId::new(proto.chain_id)
+                .map_err(|_| ProtoError::invalid_data::<Self::Proto>("chain_id"))?,
         })
     }
 
@@ -139,6 +152,7 @@ impl Protobuf for Vote {
                 NilOrVal::Val(v) => Some(v.to_proto()?),
             },
             validator_address: Some(self.validator_address.to_proto()?),
+            chain_id: self.chain_id.to_string(),
         })
     }
 }
@@ -13,6 +13,7 @@ use malachitebft_app_channel::app::types::ProposedValue;
 use malachitebft_proto::{Error as ProtoError, Protobuf};
 use malachitebft_test::codec::proto as codec;
 use malachitebft_test::codec::proto::ProtobufCodec;
+use malachitebft_test::hash::{Hasher, Keccak256};
 use malachitebft_test::proto;
 use malachitebft_test::{Height, TestContext, Value, ValueId};
 
@@ -231,6 +232,26 @@ impl<M: StoreMetrics> Db<M> {
         Ok(())
     }
 
+    fn insert_undecided_proposals(
+        &self,
+        proposals: Vec<ProposedValue<TestContext>>,
+    ) -> Result<(), StoreError> {
+        let start = Instant::now();
+        let tx = self.db.begin_write()?;
+        {
+            let mut table = tx.open_table(UNDECIDED_PROPOSALS_TABLE)?;
+            for proposal in proposals {
+                let key = (proposal.height, proposal.round, proposal.value.id());
+                let value = ProtobufCodec.encode(&proposal)?;
+                self.metrics.add_write_bytes(value.len() as u64);
+                table.insert(key, value.to_vec())?;
+            }
+        }
+        tx.commit()?;
+        self.metrics.observe_write_time(start.elapsed());
+        Ok(())
+    }
+
     fn get_pending_proposal_parts(
         &self,
         height: Height,
@@ -265,7 +286,7 @@ impl<M: StoreMetrics> Db<M> {
         let key = (
             parts.height,
             parts.round,
-            Self::generate_value_id_from_parts(&parts),
+            Self::generate_value_id_from_parts::<Keccak256>(&parts),
         );
         let tx = self.db.begin_write()?;
         {
@@ -282,7 +303,7 @@ impl<M: StoreMetrics> Db<M> {
         let key = (
             parts.height,
             parts.round,
-            Self::generate_value_id_from_parts(&parts),
+            Self::generate_value_id_from_parts::<Keccak256>(&parts),
         );
         let value = serde_json::to_vec(&parts)?;
         self.metrics.add_write_bytes(value.len() as u64);
@@ -346,30 +367,25 @@ impl<M: StoreMetrics> Db<M> {
         Ok(())
     }
 
-    // Helper method to generate a unique ValueId from proposal parts
-    pub fn generate_value_id_from_parts(parts: &ProposalParts) -> ValueId {
-        use sha3::{Digest, Keccak256};
-
-        let mut hasher = Keccak256::new();
+    // Helper method to generate a unique ValueId from proposal parts,
+    // using the given hash function
+    pub fn generate_value_id_from_parts<H: Hasher>(parts: &ProposalParts) -> ValueId {
+        let mut hasher = H::default();
 
         // Hash height, round, and proposer
-        hasher.update(parts.height.as_u64().to_be_bytes());
-        hasher.update(parts.round.as_i64().to_be_bytes());
-        hasher.update(parts.proposer.into_inner());
+        hasher.update(&parts.height.as_u64().to_be_bytes());
+        hasher.update(&parts.round.as_i64().to_be_bytes());
+        hasher.update(&parts.proposer.into_inner());
 
         // Hash all the proposal parts content
         for part in &parts.parts {
             if let Some(data) = part.as_data() {
-                hasher.update(data.factor.to_be_bytes());
+                hasher.update(&data.factor.to_be_bytes());
             }
         }
 
-        let hash = hasher.finalize();
-
         // Use first 8 bytes of hash to create ValueId
-        let mut bytes = [0u8; 8];
-        bytes.copy_from_slice(&hash[..8]);
-        ValueId::new(u64::from_be_bytes(bytes))
+        ValueId::from_hasher(hasher)
     }
 
     fn prune(&self, current_height: Height, retain_height: Height) -> Result<(), StoreError> {
@@ -519,6 +535,15 @@ impl<M: StoreMetrics> Store<M> {
         tokio::task::spawn_blocking(move || db.insert_undecided_proposal(value)).await?
     }
 
+    /// Store several undecided proposals at once, in a single transaction.
+    pub async fn store_undecided_proposals(
+        &self,
+        values: Vec<ProposedValue<TestContext>>,
+    ) -> Result<(), StoreError> {
+        let db = Arc::clone(&self.db);
+        tokio::task::spawn_blocking(move || db.insert_undecided_proposals(values)).await?
+    }
+
     pub async fn get_undecided_proposal(
         &self,
         height: Height,
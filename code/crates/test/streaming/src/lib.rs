@@ -77,7 +77,8 @@ impl StreamState {
 
         if msg.is_fin() {
             self.fin_received = true;
-            self.total_messages = msg.sequence as usize + 1;
+            self.total_messages = usize::try_from(msg.sequence)
+                .map_or(usize::MAX, |sequence| sequence.saturating_add(1));
         }
 
         self.buffer.push(msg);
@@ -151,3 +152,100 @@ impl PartStreamsMap {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    use malachitebft_app_channel::app::streaming::StreamContent;
+    use malachitebft_test::ProposalData;
+
+    use super::*;
+
+    fn peer_id(n: u8) -> PeerId {
+        // Identity multihash of 32 bytes, as for an inlined Ed25519 public key
+        PeerId::from_bytes(&[&[0, 32], &[n; 32][..]].concat()).unwrap()
+    }
+
+    fn stream_id(n: u8) -> StreamId {
+        StreamId::new(vec![n].into())
+    }
+
+    fn init() -> ProposalPart {
+        ProposalPart::Init(ProposalInit::new(
+            Height::new(1),
+            Round::new(0),
+            Round::Nil,
+            Address::new([1; 20]),
+        ))
+    }
+
+    fn content() -> impl Strategy<Value = StreamContent<ProposalPart>> {
+        prop_oneof![
+            Just(StreamContent::Data(init())),
+            any::<u64>().prop_map(|factor| StreamContent::Data(ProposalPart::Data(
+                ProposalData::new(factor)
+            ))),
+            Just(StreamContent::Fin),
+        ]
+    }
+
+    fn sequence() -> impl Strategy<Value = Sequence> {
+        prop_oneof![0..8u64, Just(u64::MAX), any::<u64>()]
+    }
+
+    proptest! {
+        /// Messages sent by faulty or malicious peers must never panic the node.
+        #[test]
+        fn arbitrary_messages_do_not_panic(
+            msgs in vec((0..3u8, 0..3u8, sequence(), content()), 0..64)
+        ) {
+            let mut streams = PartStreamsMap::new();
+
+            for (peer, stream, sequence, content) in msgs {
+                let msg = StreamMessage::new(stream_id(stream), sequence, content);
+                let _ = streams.insert(peer_id(peer), msg);
+            }
+        }
+
+        /// A stream is reassembled once all its messages were received, whatever their order,
+        /// and messages received more than once are ignored.
+        #[test]
+        fn streams_are_reassembled_in_any_order(
+            (factors, order) in vec(any::<u64>(), 0..8).prop_flat_map(|factors| {
+                // Every message once, and fewer duplicates than messages so that
+                // the duplicates received after reassembly never form a whole stream
+                let len = factors.len() + 2;
+                let order = vec(0..len, 0..len)
+                    .prop_map(move |dups| (0..len).chain(dups).collect::<Vec<_>>())
+                    .prop_shuffle();
+
+                (Just(factors), order)
+            })
+        ) {
+            let mut msgs = vec![StreamContent::Data(init())];
+            msgs.extend(factors.iter().map(|factor| {
+                StreamContent::Data(ProposalPart::Data(ProposalData::new(*factor)))
+            }));
+            msgs.push(StreamContent::Fin);
+
+            let mut streams = PartStreamsMap::new();
+            let mut received = Vec::new();
+
+            for index in order {
+                let msg = StreamMessage::new(stream_id(0), index as Sequence, msgs[index].clone());
+
+                if let Some(parts) = streams.insert(peer_id(0), msg) {
+                    received.push(parts);
+                }
+            }
+
+            prop_assert_eq!(received.len(), 1);
+
+            let mut expected = vec![init()];
+            expected.extend(factors.into_iter().map(|factor| ProposalPart::Data(ProposalData::new(factor))));
+            prop_assert_eq!(&received[0].parts, &expected);
+        }
+    }
+}
@@ -6,7 +6,7 @@ use malachitebft_engine::util::events::Event;
 use malachitebft_engine_byzantine::{ByzantineConfig, Trigger};
 use malachitebft_test_framework::{Expected, HandlerResult};
 
-use crate::{equivocation, Height, TestBuilder, TestContext};
+use crate::{equivocation, Height, TestBuilder, TestContext, TestParams};
 
 /// Short timeouts so that rounds cycle quickly, mainly for the stall tests.
 #[derive(Copy, Clone, Debug)]
@@ -330,3 +330,121 @@ pub async fn amnesia_makes_progress() {
 
     test.build().run(Duration::from_secs(30)).await;
 }
+
+/// A single validator equivocating its votes is detected by the honest nodes,
+/// which report the evidence when finalizing and keep deciding the same values.
+#[tokio::test]
+pub async fn single_vote_equivocator_is_detected() {
+    const TARGET_HEIGHT: u64 = 5;
+
+    let mut test = TestBuilder::<()>::new();
+
+    // Node 1: Byzantine, equivocates all its votes
+    test.add_node()
+        .with_voting_power(10)
+        .with_middleware(ShortTimeouts)
+        .with_byzantine(ByzantineConfig::new(Some(42)).with_equivocate_votes(Trigger::Always))
+        .start()
+        .wait_until(TARGET_HEIGHT)
+        .success();
+
+    // Nodes 2-4: Honest validators, which must detect the equivocation
+    for _ in 0..3 {
+        test.add_node()
+            .with_voting_power(10)
+            .with_middleware(ShortTimeouts)
+            .start()
+            .expect_misbehavior_evidence()
+            .wait_until(TARGET_HEIGHT)
+            .success();
+    }
+
+    test.build().run(Duration::from_secs(30)).await;
+}
+
+/// A proposer which withholds all the parts of its proposals but the first one
+/// never gets its values decided, but the honest proposers keep consensus moving.
+#[tokio::test]
+pub async fn single_proposal_parts_withholder_makes_progress() {
+    const TARGET_HEIGHT: u64 = 5;
+
+    let mut test = TestBuilder::<()>::new();
+
+    // Node 1: Byzantine, withholds its proposal parts
+    test.add_node()
+        .with_voting_power(10)
+        .with_middleware(ShortTimeouts)
+        .with_byzantine(
+            ByzantineConfig::new(Some(42)).with_withhold_proposal_parts(Trigger::Always),
+        )
+        .start()
+        .wait_until(TARGET_HEIGHT)
+        .success();
+
+    // Nodes 2-4: Honest validators
+    for _ in 0..3 {
+        test.add_node()
+            .with_voting_power(10)
+            .with_middleware(ShortTimeouts)
+            .start()
+            .wait_until(TARGET_HEIGHT)
+            .success();
+    }
+
+    test.build().run(Duration::from_secs(30)).await;
+}
+
+/// A node syncing from peers which corrupt every value they serve rejects
+/// the corrupted values, and still catches up with the values decided by the
+/// honest nodes while it was down.
+#[tokio::test]
+pub async fn corrupted_sync_responses_are_rejected() {
+    const HEIGHT: u64 = 10;
+    const CRASH_HEIGHT: u64 = 2;
+    // The other nodes keep serving values after the syncing node catches up
+    const FINAL_HEIGHT: u64 = HEIGHT + 5;
+
+    let mut test = TestBuilder::<()>::new();
+
+    // Nodes 1-2: Byzantine, corrupt every value of their sync responses
+    for _ in 0..2 {
+        test.add_node()
+            .with_voting_power(10)
+            .with_byzantine(
+                ByzantineConfig::new(Some(42)).with_corrupt_sync_responses(Trigger::Always),
+            )
+            .start()
+            .wait_until(FINAL_HEIGHT)
+            .success();
+    }
+
+    // Node 3: Honest, crashes and loses its database, then syncs from its peers
+    // once the other nodes are well ahead
+    test.add_node()
+        .with_voting_power(5)
+        .start()
+        .wait_until(CRASH_HEIGHT)
+        .crash()
+        .reset_db()
+        .restart_after(Duration::from_secs(10))
+        .expect_invalid_synced_value()
+        .wait_until(HEIGHT)
+        .success();
+
+    // Node 4: Honest
+    test.add_node()
+        .with_voting_power(5)
+        .start()
+        .wait_until(FINAL_HEIGHT)
+        .success();
+
+    test.build()
+        .run_with_params(
+            Duration::from_secs(60),
+            TestParams {
+                enable_value_sync: true,
+                ..Default::default()
+            },
+        )
+        .await;
+}
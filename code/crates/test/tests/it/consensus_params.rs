@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use arc_malachitebft_test::middleware::Middleware;
+use arc_malachitebft_test::{Height, LinearTimeouts, TestContext};
+use malachitebft_core_types::{HeightParamsOverride, ThresholdParam, ThresholdParams};
+use malachitebft_engine::util::events::Event;
+use malachitebft_test_framework::{Expected, HandlerResult};
+
+use crate::TestBuilder;
+
+/// A middleware that lowers the quorum to more than half of the voting power
+/// up to a given height, through the consensus parameters overrides.
+#[derive(Copy, Clone, Debug)]
+struct LowerQuorumUntil(u64);
+
+impl Middleware for LowerQuorumUntil {
+    fn get_timeouts(
+        &self,
+        _ctx: &TestContext,
+        _current_height: Height,
+        _height: Height,
+    ) -> Option<LinearTimeouts> {
+        Some(LinearTimeouts {
+            propose: Duration::from_millis(200),
+            propose_delta: Duration::from_millis(50),
+            prevote: Duration::from_millis(100),
+            prevote_delta: Duration::from_millis(50),
+            precommit: Duration::from_millis(100),
+            precommit_delta: Duration::from_millis(50),
+            rebroadcast: Duration::from_millis(200),
+        })
+    }
+
+    fn get_consensus_params(
+        &self,
+        _ctx: &TestContext,
+        height: Height,
+    ) -> HeightParamsOverride<TestContext> {
+        HeightParamsOverride {
+            threshold_params: (height.as_u64() <= self.0).then_some(ThresholdParams {
+                quorum: ThresholdParam::new(1, 2),
+                ..ThresholdParams::default()
+            }),
+            ..HeightParamsOverride::default()
+        }
+    }
+}
+
+/// Test that the consensus parameters overridden by the application apply to their height only:
+/// with a third of the voting power offline, the other validators decide the heights at which
+/// the quorum is lowered, but the configured quorum is back at the next height, which they
+/// cannot decide without the offline validator.
+#[tokio::test]
+async fn consensus_params_overrides_apply_to_their_height_only() {
+    const LAST_OVERRIDDEN_HEIGHT: u64 = 2;
+    const REBROADCAST_THRESHOLD: usize = 5;
+
+    let mut test = TestBuilder::<usize>::new();
+
+    test.add_node().with_voting_power(10).never_start();
+
+    for _ in 0..2 {
+        test.add_node()
+            .with_voting_power(10)
+            .with_middleware(LowerQuorumUntil(LAST_OVERRIDDEN_HEIGHT))
+            .add_config_modifier(|config| config.consensus.consensus_params_overrides = true)
+            .start()
+            .wait_until(LAST_OVERRIDDEN_HEIGHT)
+            // Stuck at the prevote step of the next height, rebroadcasting its vote
+            .on_event(|event, rebroadcasts| {
+                if let Event::RepublishVote(_) = event {
+                    *rebroadcasts += 1;
+                    if *rebroadcasts >= REBROADCAST_THRESHOLD {
+                        return Ok(HandlerResult::ContinueTest);
+                    }
+                }
+                Ok(HandlerResult::WaitForNextEvent)
+            })
+            .expect_decisions(Expected::Exactly(LAST_OVERRIDDEN_HEIGHT as usize))
+            .success();
+    }
+
+    test.build().run(Duration::from_secs(30)).await
+}
@@ -0,0 +1,247 @@
+//! Golden-path benchmark of the test application.
+//!
+//! Runs a network of 4 validators in-process on loopback and measures the number of heights
+//! decided per second, and the latency of each phase of a height as seen by the first node,
+//! for several block sizes.
+//!
+//! The benchmarks are ignored by default, run them with:
+//!
+//! ```shell
+//! cargo test -p arc-malachitebft-test --release --test it golden_path -- --ignored --test-threads 1
+//! ```
+//!
+//! The results are printed as one JSON object per line, and appended to the file
+//! given in the `MALACHITE_BENCH_OUTPUT` environment variable, if any.
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytesize::ByteSize;
+use rstest::rstest;
+use serde_json::json;
+
+use malachitebft_core_consensus::SignedConsensusMsg;
+use malachitebft_core_types::{Vote, VoteType};
+use malachitebft_test_framework::{Event, HandlerResult, TestParams};
+
+use crate::{TestBuilder, TestContext};
+
+/// Number of validators in the network.
+const NODES: usize = 4;
+
+/// Number of heights to decide, the first one being left out of the results
+/// as it includes the time it takes for the nodes to connect to each other.
+const HEIGHTS: u64 = 21;
+
+/// Timings of the phases of a height, as seen by a node.
+#[derive(Clone, Debug)]
+struct HeightTimings {
+    height: u64,
+    started: Instant,
+    proposed: Option<Instant>,
+    prevoted: Option<Instant>,
+    precommitted: Option<Instant>,
+    decided: Option<Instant>,
+}
+
+impl HeightTimings {
+    fn new(height: u64, started: Instant) -> Self {
+        Self {
+            height,
+            started,
+            proposed: None,
+            prevoted: None,
+            precommitted: None,
+            decided: None,
+        }
+    }
+
+    /// Latencies of the propose, prevote, precommit and commit phases,
+    /// if the height went through all of them.
+    fn phases(&self) -> Option<[Duration; 4]> {
+        let proposed = self.proposed?;
+        let prevoted = self.prevoted?;
+        let precommitted = self.precommitted?;
+        let decided = self.decided?;
+
+        Some([
+            proposed.saturating_duration_since(self.started),
+            prevoted.saturating_duration_since(proposed),
+            precommitted.saturating_duration_since(prevoted),
+            decided.saturating_duration_since(precommitted),
+        ])
+    }
+}
+
+/// Timings of the heights decided by a node, shared with the benchmark
+/// which reports them once the test is over.
+#[derive(Default)]
+struct PhaseTimings {
+    decided: Arc<Mutex<Vec<HeightTimings>>>,
+    current: Option<HeightTimings>,
+}
+
+impl PhaseTimings {
+    fn new(decided: Arc<Mutex<Vec<HeightTimings>>>) -> Self {
+        Self {
+            decided,
+            current: None,
+        }
+    }
+
+    /// Only the first occurrence of each phase in a height is recorded.
+    fn record(&mut self, event: Event<TestContext>, now: Instant) {
+        match event {
+            Event::StartedHeight(height, _) => {
+                self.current = Some(HeightTimings::new(height.as_u64(), now));
+            }
+            Event::ProposedValue(value) => {
+                self.mark(value.height.as_u64(), now, |t| &mut t.proposed)
+            }
+            Event::ReceivedProposedValue(value, _) => {
+                self.mark(value.height.as_u64(), now, |t| &mut t.proposed)
+            }
+            Event::Published(SignedConsensusMsg::Vote(vote)) => match vote.vote_type() {
+                VoteType::Prevote => self.mark(vote.height().as_u64(), now, |t| &mut t.prevoted),
+                VoteType::Precommit => {
+                    self.mark(vote.height().as_u64(), now, |t| &mut t.precommitted)
+                }
+            },
+            Event::Decided { commit_certificate } => {
+                self.mark(commit_certificate.height.as_u64(), now, |t| &mut t.decided);
+
+                if let Some(timings) = self.current.take() {
+                    self.decided.lock().unwrap().push(timings);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn mark(
+        &mut self,
+        height: u64,
+        now: Instant,
+        phase: impl FnOnce(&mut HeightTimings) -> &mut Option<Instant>,
+    ) {
+        if let Some(timings) = self.current.as_mut().filter(|t| t.height == height) {
+            phase(timings).get_or_insert(now);
+        }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Mean, median and maximum of the given latencies, in milliseconds.
+fn summary(mut latencies: Vec<Duration>) -> serde_json::Value {
+    latencies.sort();
+
+    let mean = latencies.iter().sum::<Duration>() / latencies.len().max(1) as u32;
+    let p50 = latencies
+        .get(latencies.len() / 2)
+        .copied()
+        .unwrap_or_default();
+    let max = latencies.last().copied().unwrap_or_default();
+
+    json!({
+        "mean": millis(mean),
+        "p50": millis(p50),
+        "max": millis(max),
+    })
+}
+
+fn report(block_size: ByteSize, heights: &[HeightTimings]) -> serde_json::Value {
+    // Leave out the first height, which includes the time it takes for the nodes to connect
+    let heights = heights.get(1..).unwrap_or_default();
+
+    let decided: Vec<Instant> = heights.iter().filter_map(|t| t.decided).collect();
+    let heights_per_sec = match (decided.first(), decided.last()) {
+        (Some(first), Some(last)) if decided.len() > 1 => {
+            (decided.len() - 1) as f64 / last.duration_since(*first).as_secs_f64()
+        }
+        _ => 0.0,
+    };
+
+    let phases: Vec<[Duration; 4]> = heights.iter().filter_map(|t| t.phases()).collect();
+    let phase = |i: usize| summary(phases.iter().map(|p| p[i]).collect());
+
+    json!({
+        "benchmark": "golden_path",
+        "nodes": NODES,
+        "block_size_bytes": block_size.as_u64(),
+        "heights": heights.len(),
+        "heights_per_sec": heights_per_sec,
+        "phases_ms": {
+            "propose": phase(0),
+            "prevote": phase(1),
+            "precommit": phase(2),
+            "commit": phase(3),
+        },
+    })
+}
+
+fn output(report: &serde_json::Value) {
+    println!("{report}");
+
+    if let Ok(path) = std::env::var("MALACHITE_BENCH_OUTPUT") {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .unwrap_or_else(|e| panic!("Failed to open benchmark output file {path}: {e}"));
+
+        writeln!(file, "{report}").unwrap();
+    }
+}
+
+#[rstest]
+#[case::block_64kib(ByteSize::kib(64))]
+#[case::block_1mib(ByteSize::mib(1))]
+#[case::block_4mib(ByteSize::mib(4))]
+#[tokio::test]
+#[ignore]
+pub async fn golden_path(#[case] block_size: ByteSize) {
+    let decided = Arc::new(Mutex::new(Vec::new()));
+
+    let mut test = TestBuilder::<PhaseTimings>::new();
+
+    test.add_node()
+        .with_state(PhaseTimings::new(Arc::clone(&decided)))
+        .start()
+        .on_event(|event, state| {
+            let done = matches!(
+                &event,
+                Event::Decided { commit_certificate } if commit_certificate.height.as_u64() >= HEIGHTS
+            );
+
+            state.record(event, Instant::now());
+
+            if done {
+                Ok(HandlerResult::ContinueTest)
+            } else {
+                Ok(HandlerResult::WaitForNextEvent)
+            }
+        })
+        .success();
+
+    for _ in 1..NODES {
+        test.add_node().start().wait_until(HEIGHTS + 1).success();
+    }
+
+    test.build()
+        .run_with_params(
+            Duration::from_secs(120),
+            TestParams {
+                block_size,
+                stable_block_times: false,
+                ..Default::default()
+            },
+        )
+        .await;
+
+    let decided = decided.lock().unwrap();
+    output(&report(block_size, &decided));
+}
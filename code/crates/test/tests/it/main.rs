@@ -1,7 +1,9 @@
 mod byzantine_engine;
+mod consensus_params;
 mod equivocation;
 mod finalization;
 mod full_nodes;
+mod golden_path;
 mod liveness;
 mod middlewares;
 mod n3f0;
@@ -10,6 +12,7 @@ mod n3f0_pubsub_protocol;
 mod n3f1;
 mod persistent_peers_only;
 mod reset;
+mod round_alerts;
 mod timeout_updates;
 mod validator_set;
 mod validity_change_on_restart;
@@ -29,14 +32,15 @@ use rand::SeedableRng;
 use tempfile::TempDir;
 
 use malachitebft_signing_ed25519::PrivateKey;
-use malachitebft_test_app::config::Config;
+use malachitebft_test_app::config::{factor_partitioning, Config};
 use malachitebft_test_app::node::{App, Handle};
-use malachitebft_test_framework::HasTestRunner;
+use malachitebft_test_framework::{Clock, HasTestRunner};
 use malachitebft_test_framework::{ConfigModifier, NodeRunner, TestNode};
 
 pub use malachitebft_test_framework::TestBuilder as GenTestBuilder;
 pub use malachitebft_test_framework::{HandlerResult, NodeId, TestParams};
 
+use arc_malachitebft_test::home_dir;
 use arc_malachitebft_test::middleware::Middleware;
 use arc_malachitebft_test::node::Node;
 use arc_malachitebft_test::{Height, TestContext, Validator, ValidatorSet};
@@ -153,17 +157,33 @@ impl NodeRunner<TestContext> for TestRunner {
             start_height: Some(node_info.start_height),
             middleware: Some(Arc::clone(&node_info.middleware)),
             validator: node_info.validator,
+            clock: self
+                .params
+                .clock
+                .clone()
+                .map(|clock| Arc::new(clock) as Arc<dyn Clock>),
         };
 
         app.start().await
     }
 
     async fn reset_db(&self, id: NodeId) -> eyre::Result<()> {
-        let db_dir = self.nodes_info[&id].home_dir.join("db");
+        let db_dir = home_dir::db_dir(&self.nodes_info[&id].home_dir);
         std::fs::remove_dir_all(&db_dir)?;
         std::fs::create_dir_all(&db_dir)?;
         Ok(())
     }
+
+    async fn truncate_wal(&self, id: NodeId) -> eyre::Result<()> {
+        let wal_path = home_dir::wal_path(&self.nodes_info[&id].home_dir);
+        let file = std::fs::OpenOptions::new().write(true).open(&wal_path)?;
+        let len = file.metadata()?.len();
+
+        // The partial entry is dropped when the WAL is opened again
+        file.set_len(len.saturating_sub(1))?;
+        file.sync_all()?;
+        Ok(())
+    }
 }
 
 impl TestRunner {
@@ -190,7 +210,6 @@ impl TestRunner {
             moniker: format!("node-{node}"),
             logging: LoggingConfig::default(),
             consensus: ConsensusConfig {
-                // Current test app does not support proposal-only value payload properly as Init does not include valid_round
                 value_payload: ValuePayload::ProposalAndParts,
                 queue_capacity: 100,
                 p2p: P2pConfig {
@@ -212,6 +231,7 @@ impl TestRunner {
                     },
                     ..Default::default()
                 },
+                partitioning: factor_partitioning(),
                 ..Default::default()
             },
             value_sync: ValueSyncConfig {
@@ -225,6 +245,7 @@ impl TestRunner {
                 listen_addr: format!("127.0.0.1:{}", self.metrics_base_port + i)
                     .parse()
                     .unwrap(),
+                ..Default::default()
             },
             runtime: RuntimeConfig::single_threaded(),
             test: TestConfig::default(),
@@ -26,3 +26,22 @@ pub async fn all_correct_nodes() {
         )
         .await
 }
+
+#[tokio::test]
+pub async fn vote_tally() {
+    const HEIGHT: u64 = 2;
+
+    let mut test = TestBuilder::<()>::new();
+
+    test.add_node()
+        .start()
+        .expect_vote_tally(1, 3, 3)
+        .wait_until(HEIGHT)
+        .success();
+    test.add_node().start().wait_until(HEIGHT).success();
+    test.add_node().start().wait_until(HEIGHT).success();
+
+    test.build()
+        .run_with_params(Duration::from_secs(50), TestParams::default())
+        .await
+}
@@ -39,7 +39,6 @@ pub async fn proposal_and_parts() {
 }
 
 #[tokio::test]
-#[ignore] // Test app does not support proposal-only mode
 pub async fn proposal_only() {
     let params = TestParams {
         value_payload: ValuePayload::ProposalOnly,
@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use malachitebft_test_framework::SimulatedClock;
+
 use crate::{TestBuilder, TestParams};
 
 #[tokio::test]
@@ -25,6 +27,60 @@ pub async fn proposer_fails_to_start() {
     test.build().run(Duration::from_secs(30)).await
 }
 
+/// Test that the validator which fails to start is reported as absent once a height is completed.
+#[tokio::test]
+pub async fn absent_validator_is_reported() {
+    const HEIGHT: u64 = 3;
+
+    let mut test = TestBuilder::<()>::new();
+
+    test.add_node().with_voting_power(1).never_start();
+
+    for _ in 0..2 {
+        test.add_node()
+            .with_voting_power(5)
+            .start()
+            .expect_absent_validators(2, 1)
+            .wait_until(HEIGHT)
+            .success();
+    }
+
+    test.build().run(Duration::from_secs(30)).await
+}
+
+#[tokio::test]
+pub async fn proposer_fails_to_start_with_simulated_clock() {
+    const HEIGHT: u64 = 5;
+
+    let mut test = TestBuilder::<()>::new();
+
+    test.add_node().with_voting_power(1).success();
+
+    test.add_node()
+        .with_voting_power(5)
+        .start()
+        .wait_until(HEIGHT)
+        .success();
+
+    test.add_node()
+        .with_voting_power(5)
+        .start()
+        .wait_until(HEIGHT)
+        .success();
+
+    // Propose timeouts for the rounds of the missing proposer elapse
+    // as soon as the nodes are idle, rather than after several seconds.
+    test.build()
+        .run_with_params(
+            Duration::from_secs(10),
+            TestParams {
+                clock: Some(SimulatedClock::new()),
+                ..Default::default()
+            },
+        )
+        .await
+}
+
 #[tokio::test]
 pub async fn one_node_fails_to_start() {
     const HEIGHT: u64 = 5;
@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use crate::TestBuilder;
+
+/// Test that a node raises a round alert, and that a node halting its participation
+/// at a height which goes through too many rounds resumes at the next height.
+///
+/// The third node starts late, so that the first height cannot be decided
+/// before it joins and goes through several rounds.
+#[tokio::test]
+async fn round_alert_and_halt() {
+    const HEIGHT: u64 = 4;
+
+    let mut test = TestBuilder::<()>::new();
+
+    test.add_node()
+        .with_voting_power(5)
+        .add_config_modifier(|config| config.consensus.max_rounds_alert = Some(1))
+        .start()
+        .expect_round_alert(1)
+        .wait_until(HEIGHT)
+        .success();
+
+    // With a voting power of 1, the other two nodes can decide without this one
+    test.add_node()
+        .with_voting_power(1)
+        .add_config_modifier(|config| {
+            config.consensus.max_rounds_halt = Some(1);
+            config.consensus.notify_round_alerts = true;
+        })
+        .start()
+        .expect_participation_halted(1)
+        .wait_until(HEIGHT)
+        .success();
+
+    test.add_node()
+        .with_voting_power(5)
+        .start_after(1, Duration::from_secs(10))
+        .wait_until(HEIGHT)
+        .success();
+
+    test.build().run(Duration::from_secs(60)).await
+}
@@ -2,6 +2,7 @@ use std::time::Duration;
 
 use arc_malachitebft_test::middleware::Middleware;
 use arc_malachitebft_test::{Height, LinearTimeouts, TestContext};
+use malachitebft_core_types::{Round, TimeoutKind};
 
 use crate::TestBuilder;
 
@@ -182,3 +183,56 @@ async fn gradually_changing_timeouts() {
 
     test.build().run(Duration::from_secs(90)).await
 }
+
+/// A middleware that uses a very long propose timeout,
+/// but overrides it with a short one for every round
+#[derive(Copy, Clone, Debug)]
+struct ProposeTimeoutOverride;
+
+impl Middleware for ProposeTimeoutOverride {
+    fn get_timeouts(
+        &self,
+        _ctx: &TestContext,
+        _current_height: Height,
+        _height: Height,
+    ) -> Option<LinearTimeouts> {
+        Some(LinearTimeouts {
+            propose: Duration::from_secs(60),
+            ..LinearTimeouts::default()
+        })
+    }
+
+    fn get_timeout_override(
+        &self,
+        _ctx: &TestContext,
+        _height: Height,
+        _round: Round,
+        kind: TimeoutKind,
+    ) -> Option<Duration> {
+        (kind == TimeoutKind::Propose).then_some(Duration::from_millis(500))
+    }
+}
+
+/// Test that the timeouts overridden by the application at the start of a round are applied:
+/// without the override, every height proposed by the node which fails to start
+/// would take the full 60s propose timeout.
+#[tokio::test]
+async fn timeout_overrides_per_round() {
+    const HEIGHT: u64 = 5;
+
+    let mut test = TestBuilder::<()>::new();
+
+    test.add_node().with_voting_power(1).success();
+
+    for _ in 0..2 {
+        test.add_node()
+            .with_voting_power(5)
+            .with_middleware(ProposeTimeoutOverride)
+            .add_config_modifier(|config| config.consensus.timeout_overrides = true)
+            .start()
+            .wait_until(HEIGHT)
+            .success();
+    }
+
+    test.build().run(Duration::from_secs(30)).await
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -11,8 +12,9 @@ use arc_malachitebft_test::{Height, TestContext};
 use malachitebft_config::ValuePayload;
 use malachitebft_core_consensus::ProposedValue;
 use malachitebft_core_types::{CommitCertificate, Round};
+use malachitebft_engine::util::events::Event;
 
-use crate::{TestBuilder, TestParams};
+use crate::{HandlerResult, TestBuilder, TestParams};
 
 pub async fn crash_restart_from_start(params: TestParams) {
     const HEIGHT: u64 = 6;
@@ -222,6 +224,59 @@ pub async fn start_late(#[case] status_update_interval: Duration) {
         .await
 }
 
+/// Tests that a validator catching up via sync while taking part in consensus,
+/// which may thus decide the same height both ways, is only notified once about each decision.
+#[tokio::test]
+pub async fn start_late_decides_each_height_once() {
+    const HEIGHT: u64 = 10;
+
+    let mut test = TestBuilder::<BTreeSet<u64>>::new();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(HEIGHT * 2)
+        .success();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(HEIGHT * 2)
+        .success();
+
+    test.add_node()
+        .with_voting_power(5)
+        .start_after(1, Duration::from_secs(5))
+        .on_event(|event, decided| {
+            let Event::Decided { commit_certificate } = event else {
+                return Ok(HandlerResult::WaitForNextEvent);
+            };
+
+            let height = commit_certificate.height.as_u64();
+
+            if !decided.insert(height) {
+                bail!("Node was notified twice about the decision of height {height}");
+            }
+
+            if height >= HEIGHT {
+                Ok(HandlerResult::ContinueTest)
+            } else {
+                Ok(HandlerResult::WaitForNextEvent)
+            }
+        })
+        .success();
+
+    test.build()
+        .run_with_params(
+            Duration::from_secs(30),
+            TestParams {
+                enable_value_sync: true,
+                ..Default::default()
+            },
+        )
+        .await
+}
+
 #[rstest]
 #[case::eager(Duration::ZERO)]
 #[case::interval(Duration::from_secs(1))]
@@ -303,6 +358,48 @@ pub async fn start_late_parallel_requests_with_batching(#[case] status_update_in
         .await
 }
 
+#[rstest]
+#[case::eager(Duration::ZERO)]
+#[case::interval(Duration::from_secs(1))]
+#[tokio::test]
+pub async fn start_late_with_batched_synced_values(#[case] status_update_interval: Duration) {
+    const HEIGHT: u64 = 10;
+
+    let mut test = TestBuilder::<()>::new();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(HEIGHT * 2)
+        .success();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(HEIGHT * 2)
+        .success();
+
+    // Node 3 catches up with responses of 5 values, each processed by the application as a batch
+    test.add_node()
+        .with_voting_power(0)
+        .start_after(1, Duration::from_secs(10))
+        .wait_until(HEIGHT)
+        .success();
+
+    test.build()
+        .run_with_params(
+            Duration::from_secs(30),
+            TestParams {
+                enable_value_sync: true,
+                batch_size: 5,
+                batch_synced_values: true,
+                status_update_interval,
+                ..Default::default()
+            },
+        )
+        .await
+}
+
 #[rstest]
 #[case::eager(Duration::ZERO)]
 #[case::interval(Duration::from_secs(1))]
@@ -419,6 +516,46 @@ pub async fn sync_only_fullnode_without_consensus(#[case] status_update_interval
         .await
 }
 
+#[tokio::test]
+pub async fn sync_only_fullnode_follows_announcements() {
+    const HEIGHT: u64 = 8;
+
+    let mut test = TestBuilder::<()>::new();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(HEIGHT)
+        .success();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(HEIGHT)
+        .success();
+
+    // The full node only learns about the decided values from the announcements of the validators,
+    // as their status updates are sent too rarely for it to keep up within the timeout
+    test.add_node()
+        .full_node()
+        .disable_consensus()
+        .start_after(1, Duration::from_secs(2))
+        .wait_until(HEIGHT)
+        .success();
+
+    test.build()
+        .run_with_params(
+            Duration::from_secs(30),
+            TestParams {
+                enable_value_sync: true,
+                announce_decisions: true,
+                status_update_interval: Duration::from_secs(60),
+                ..Default::default()
+            },
+        )
+        .await
+}
+
 #[derive(Debug)]
 struct ResetHeight {
     reset_height: u64,
@@ -1069,3 +1206,72 @@ pub async fn sync_recovers_from_decode_failure_ok(
     })
     .await
 }
+
+/// Middleware that reports synced values as invalid a configurable number of times.
+#[derive(Debug, Clone)]
+struct FailSyncValidation {
+    remaining_failures: Arc<AtomicU32>,
+}
+
+impl Middleware for FailSyncValidation {
+    fn fail_synced_value_validation(
+        &self,
+        _ctx: &TestContext,
+        _height: Height,
+        _round: Round,
+    ) -> bool {
+        self.remaining_failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+    }
+}
+
+/// Verifies that sync recovers when the application rejects a synced value as invalid.
+/// The engine receives `SyncedValueOutcome::Invalid` and notifies the sync state machine
+/// via InvalidValue, which penalizes the peer and re-requests from a different peer.
+#[tokio::test]
+pub async fn sync_recovers_from_invalid_value() {
+    const HEIGHT: u64 = 6;
+    const CRASH_HEIGHT: u64 = 3;
+
+    let mut test = TestBuilder::<()>::new();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(HEIGHT)
+        .success();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(HEIGHT)
+        .success();
+
+    // Node 3 crashes, resets DB, and restarts with a middleware that rejects the first
+    // synced value. Sync re-requests from a different peer.
+    let middleware = FailSyncValidation {
+        remaining_failures: Arc::new(AtomicU32::new(1)),
+    };
+
+    test.add_node()
+        .with_voting_power(5)
+        .with_middleware(middleware)
+        .start()
+        .wait_until(CRASH_HEIGHT)
+        .crash()
+        .reset_db()
+        .restart_after(Duration::from_secs(5))
+        .wait_until(HEIGHT)
+        .success();
+
+    test.build()
+        .run_with_params(
+            Duration::from_secs(60),
+            TestParams {
+                enable_value_sync: true,
+                ..Default::default()
+            },
+        )
+        .await
+}
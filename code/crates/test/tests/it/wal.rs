@@ -403,6 +403,53 @@ async fn byzantine_proposer_crashes_after_proposing_2(params: TestParams) {
         .await
 }
 
+/// The messages whose WAL entries were lost are not recovered on restart,
+/// and must thus be processed again when peers gossip them.
+///
+/// The node may crash only after it precommitted, in which case the others decide the height
+/// without it, so value sync is left enabled for it to catch up with them.
+#[tokio::test]
+async fn restart_with_truncated_wal() {
+    const CRASH_HEIGHT: u64 = 2;
+    const FINAL_HEIGHT: u64 = CRASH_HEIGHT + 2;
+
+    let mut test = TestBuilder::<()>::new();
+
+    // No height can be decided without this node
+    test.add_node()
+        .with_voting_power(25)
+        .start()
+        .wait_until(CRASH_HEIGHT)
+        .on_vote(|_vote, _state| Ok(HandlerResult::ContinueTest))
+        .crash()
+        .truncate_wal()
+        .restart_after(Duration::from_secs(5))
+        .wait_until(FINAL_HEIGHT)
+        .success();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(FINAL_HEIGHT)
+        .success();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(FINAL_HEIGHT)
+        .success();
+
+    test.build()
+        .run_with_params(
+            Duration::from_secs(60),
+            TestParams {
+                enable_value_sync: true,
+                ..TestParams::default()
+            },
+        )
+        .await
+}
+
 async fn test_multi_rounds(crash_height: u64, restart_after: Duration) {
     let crash_round: u32 = 3;
     let final_height: u64 = crash_height + 2;
@@ -419,6 +466,7 @@ async fn test_multi_rounds(crash_height: u64, restart_after: Duration) {
         .crash()
         .restart_after(restart_after)
         .expect_wal_replay(crash_height)
+        .expect_wal_replay_done(Duration::from_secs(10))
         .wait_until(final_height)
         .success();
 
@@ -445,3 +493,36 @@ async fn multi_rounds_1() {
 async fn multi_rounds_2() {
     test_multi_rounds(3, Duration::from_secs(10)).await
 }
+
+#[tokio::test]
+async fn graceful_shutdown_and_restart() {
+    const STOP_HEIGHT: u64 = 3;
+    const FINAL_HEIGHT: u64 = STOP_HEIGHT + 3;
+
+    let mut test = TestBuilder::<()>::new();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(FINAL_HEIGHT)
+        .success();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(FINAL_HEIGHT)
+        .success();
+
+    test.add_node()
+        .with_voting_power(10)
+        .start()
+        .wait_until(STOP_HEIGHT)
+        // Shut down gracefully, in the middle of the height
+        .stop_after(Duration::from_millis(500))
+        .expect_graceful_shutdown()
+        .restart_after(Duration::from_secs(5))
+        .wait_until(FINAL_HEIGHT)
+        .success();
+
+    test.build().run(Duration::from_secs(60)).await
+}
@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+
+use arc_malachitebft_test::codec::proto::ProtobufCodec;
+use arc_malachitebft_test::{
+    Ed25519Signer, Ed25519Verifier, Height, TestContext, ValidatorSet, Value,
+};
+use malachitebft_app::archive::{
+    self, ArchiveError, ArchiveReader, ArchiveWriter, DecidedValueSink, DecidedValueSource,
+};
+use malachitebft_codec::Codec;
+use malachitebft_core_types::{CommitCertificate, Context, NilOrVal, Round};
+use malachitebft_signing::Signer;
+use malachitebft_sync::RawDecidedValue;
+
+use crate::certificates::make_validators;
+
+#[derive(Default)]
+struct MemoryStore {
+    validator_set: Option<ValidatorSet>,
+    values: BTreeMap<Height, RawDecidedValue<TestContext>>,
+}
+
+#[async_trait]
+impl DecidedValueSource<TestContext> for MemoryStore {
+    async fn history_min_height(&self) -> eyre::Result<Height> {
+        Ok(self.values.keys().next().copied().unwrap_or_default())
+    }
+
+    async fn get_decided_value(
+        &self,
+        height: Height,
+    ) -> eyre::Result<Option<RawDecidedValue<TestContext>>> {
+        Ok(self.values.get(&height).cloned())
+    }
+}
+
+#[async_trait]
+impl DecidedValueSink<TestContext> for MemoryStore {
+    async fn validator_set(&self, _height: Height) -> eyre::Result<Option<ValidatorSet>> {
+        Ok(self.validator_set.clone())
+    }
+
+    async fn store_decided_value(
+        &mut self,
+        certificate: CommitCertificate<TestContext>,
+        value: Value,
+    ) -> eyre::Result<()> {
+        let value_bytes = ProtobufCodec.encode(&value)?;
+
+        self.values.insert(
+            certificate.height,
+            RawDecidedValue {
+                certificate,
+                value_bytes,
+            },
+        );
+
+        Ok(())
+    }
+}
+
+fn decided_value(
+    ctx: &TestContext,
+    signers: &[Ed25519Signer],
+    validator_set: &ValidatorSet,
+    height: u64,
+) -> RawDecidedValue<TestContext> {
+    let height = Height::new(height);
+    let round = Round::new(0);
+    let value = Value::new(height.as_u64() * 10);
+
+    let votes = validator_set
+        .validators
+        .iter()
+        .zip(signers)
+        .map(|(validator, signer)| {
+            let vote =
+                ctx.new_precommit(height, round, NilOrVal::Val(value.id()), validator.address);
+            block_on(signer.sign_vote(vote)).unwrap()
+        })
+        .collect();
+
+    RawDecidedValue {
+        certificate: CommitCertificate::new(height, round, value.id(), votes),
+        value_bytes: ProtobufCodec.encode(&value).unwrap(),
+    }
+}
+
+fn setup(heights: impl IntoIterator<Item = u64>) -> (TestContext, MemoryStore) {
+    let ctx = TestContext::new();
+    let (validators, signers) = make_validators([10, 10, 10], 42);
+    let validator_set = ValidatorSet::new(validators);
+
+    let values = heights
+        .into_iter()
+        .map(|height| {
+            let value = decided_value(&ctx, &signers, &validator_set, height);
+            (value.certificate.height, value)
+        })
+        .collect();
+
+    let store = MemoryStore {
+        validator_set: Some(validator_set),
+        values,
+    };
+
+    (ctx, store)
+}
+
+fn export(
+    store: &MemoryStore,
+    from: Option<u64>,
+    to: Option<u64>,
+) -> Result<Vec<u8>, ArchiveError> {
+    let mut writer = ArchiveWriter::new(Vec::new(), ProtobufCodec)?;
+    block_on(archive::export(
+        store,
+        from.map(Height::new),
+        to.map(Height::new),
+        &mut writer,
+    ))?;
+    writer.finish()
+}
+
+fn import(ctx: &TestContext, bytes: &[u8], sink: &mut MemoryStore) -> Result<u64, ArchiveError> {
+    let mut reader = ArchiveReader::new(bytes, ProtobufCodec)?;
+    block_on(archive::import(
+        ctx,
+        &mut reader,
+        &Ed25519Verifier,
+        Default::default(),
+        sink,
+    ))
+}
+
+#[test]
+fn export_import_roundtrip() {
+    let (ctx, source) = setup(3..=7);
+
+    let bytes = export(&source, None, None).unwrap();
+
+    // Exporting is deterministic
+    assert_eq!(bytes, export(&source, None, None).unwrap());
+
+    let mut sink = MemoryStore {
+        validator_set: source.validator_set.clone(),
+        ..Default::default()
+    };
+
+    assert_eq!(import(&ctx, &bytes, &mut sink).unwrap(), 5);
+    assert_eq!(sink.values, source.values);
+}
+
+#[test]
+fn export_range() {
+    let (_, source) = setup(1..=10);
+
+    let bytes = export(&source, Some(4), Some(6)).unwrap();
+    let reader = ArchiveReader::<TestContext, _, _>::new(bytes.as_slice(), ProtobufCodec).unwrap();
+
+    let heights = reader
+        .map(|value| value.unwrap().certificate.height.as_u64())
+        .collect::<Vec<_>>();
+
+    assert_eq!(heights, vec![4, 5, 6]);
+
+    assert!(matches!(
+        export(&source, Some(8), Some(12)),
+        Err(ArchiveError::MissingValue(_))
+    ));
+}
+
+#[test]
+fn import_detects_corruption() {
+    let (ctx, source) = setup(1..=3);
+    let bytes = export(&source, None, None).unwrap();
+
+    let mut corrupted = bytes.clone();
+    let last = corrupted.len() - 20;
+    corrupted[last] ^= 0xff;
+
+    let mut sink = MemoryStore {
+        validator_set: source.validator_set.clone(),
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        import(&ctx, &corrupted, &mut sink),
+        Err(ArchiveError::ChecksumMismatch { index: 2 })
+    ));
+
+    // Values before the corrupted entry have been imported
+    assert_eq!(sink.values.len(), 2);
+
+    let truncated = &bytes[..bytes.len() - 12];
+    let mut sink = MemoryStore {
+        validator_set: source.validator_set.clone(),
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        import(&ctx, truncated, &mut sink),
+        Err(ArchiveError::Truncated)
+    ));
+}
+
+#[test]
+fn import_verifies_certificates() {
+    let (ctx, source) = setup(1..=3);
+    let bytes = export(&source, None, None).unwrap();
+
+    // Import into a node with a different validator set
+    let (validators, _) = make_validators([10, 10, 10], 1337);
+    let mut sink = MemoryStore {
+        validator_set: Some(ValidatorSet::new(validators)),
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        import(&ctx, &bytes, &mut sink),
+        Err(ArchiveError::InvalidCertificate { .. })
+    ));
+
+    assert!(sink.values.is_empty());
+}
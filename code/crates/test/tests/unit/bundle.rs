@@ -0,0 +1,209 @@
+use futures::executor::block_on;
+
+use arc_malachitebft_test::{Ed25519Signer, Height, TestContext, Value};
+use malachitebft_app::bundle::{
+    self, BundleError, BundleFile, BundleReader, BundleWriter, LastSigned, Manifest, Step,
+};
+use malachitebft_core_consensus::{SignedConsensusMsg, WalEntry};
+use malachitebft_core_types::{Context, NilOrVal, Round};
+use malachitebft_signing::Signer;
+
+use crate::certificates::make_validators;
+
+fn manifest() -> Manifest {
+    Manifest {
+        address: "validator".to_string(),
+        last_signed: Some(LastSigned {
+            height: 12,
+            round: 1,
+            step: Step::Precommit,
+        }),
+    }
+}
+
+fn write_bundle(manifest: &Manifest, files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = BundleWriter::new(Vec::new(), manifest).unwrap();
+
+    for (path, contents) in files {
+        writer.add(path, contents).unwrap();
+    }
+
+    writer.finish().unwrap()
+}
+
+fn read_bundle(bytes: &[u8]) -> Result<(Manifest, Vec<BundleFile>), BundleError> {
+    let reader = BundleReader::new(bytes)?;
+    let manifest = reader.manifest().clone();
+    let files = reader.collect::<Result<Vec<_>, _>>()?;
+
+    Ok((manifest, files))
+}
+
+#[test]
+fn roundtrip() {
+    let files: [(&str, &[u8]); 3] = [
+        ("config/config.toml", b"moniker = \"test-0\""),
+        ("wal/consensus.wal", &[0, 1, 2, 3]),
+        ("wal/empty", &[]),
+    ];
+
+    let bytes = write_bundle(&manifest(), &files);
+    let (manifest, read) = read_bundle(&bytes).unwrap();
+
+    assert_eq!(manifest, self::manifest());
+    assert_eq!(read.len(), files.len());
+
+    for (file, (path, contents)) in read.iter().zip(files) {
+        assert_eq!(file.path, path);
+        assert_eq!(file.contents, contents);
+    }
+}
+
+#[test]
+fn manifest_without_last_signed() {
+    let manifest = Manifest {
+        address: "validator".to_string(),
+        last_signed: None,
+    };
+
+    let bytes = write_bundle(&manifest, &[]);
+    let (read, files) = read_bundle(&bytes).unwrap();
+
+    assert_eq!(read, manifest);
+    assert!(files.is_empty());
+}
+
+#[test]
+fn corrupted_file_is_rejected() {
+    let mut bytes = write_bundle(&manifest(), &[("wal/consensus.wal", &[0; 16])]);
+
+    // Flip a byte of the contents of the file, just before the trailer
+    let index = bytes.len() - 11;
+    bytes[index] ^= 0xff;
+
+    assert!(matches!(
+        read_bundle(&bytes),
+        Err(BundleError::ChecksumMismatch(path)) if path == "wal/consensus.wal"
+    ));
+}
+
+#[test]
+fn truncated_bundle_is_rejected() {
+    let bytes = write_bundle(&manifest(), &[("wal/consensus.wal", &[0; 16])]);
+
+    for length in [4, 16, bytes.len() - 20, bytes.len() - 1] {
+        assert!(matches!(
+            read_bundle(&bytes[..length]),
+            Err(BundleError::Truncated)
+        ));
+    }
+}
+
+#[test]
+fn invalid_header_is_rejected() {
+    let mut bytes = write_bundle(&manifest(), &[]);
+
+    bytes[8..12].copy_from_slice(&2_u32.to_be_bytes());
+    assert!(matches!(
+        read_bundle(&bytes),
+        Err(BundleError::UnsupportedVersion(2))
+    ));
+
+    bytes[0] = b'X';
+    assert!(matches!(
+        read_bundle(&bytes),
+        Err(BundleError::InvalidMagic)
+    ));
+}
+
+#[test]
+fn paths_outside_the_home_directory_are_rejected() {
+    for path in [
+        "",
+        "/etc/passwd",
+        "../config.toml",
+        "wal/../../config.toml",
+        "./config.toml",
+        "wal//consensus.wal",
+        "wal\\consensus.wal",
+    ] {
+        let mut writer = BundleWriter::new(Vec::new(), &manifest()).unwrap();
+
+        assert!(
+            matches!(writer.add(path, b""), Err(BundleError::InvalidPath(_))),
+            "path {path:?} should be rejected"
+        );
+    }
+}
+
+#[test]
+fn last_signed_message_of_the_validator() {
+    let ctx = TestContext::new();
+    let (validators, signers) = make_validators([10, 10], 42);
+    let [ours, theirs] = validators;
+    let [our_signer, their_signer] = signers;
+
+    let value = Value::new(42);
+
+    let vote = |signer: &Ed25519Signer, height: u64, round: u32, precommit: bool, address| {
+        let (height, round) = (Height::new(height), Round::new(round));
+        let value = NilOrVal::Val(value.id());
+
+        let vote = if precommit {
+            ctx.new_precommit(height, round, value, address)
+        } else {
+            ctx.new_prevote(height, round, value, address)
+        };
+
+        WalEntry::ConsensusMsg(SignedConsensusMsg::Vote(
+            block_on(signer.sign_vote(vote)).unwrap(),
+        ))
+    };
+
+    let proposal = |signer: &Ed25519Signer, height: u64, round: u32, address| {
+        let proposal = ctx.new_proposal(
+            Height::new(height),
+            Round::new(round),
+            value.clone(),
+            Round::Nil,
+            address,
+        );
+
+        WalEntry::ConsensusMsg(SignedConsensusMsg::Proposal(
+            block_on(signer.sign_proposal(proposal)).unwrap(),
+        ))
+    };
+
+    let entries = vec![
+        proposal(&our_signer, 3, 0, ours.address),
+        vote(&our_signer, 3, 0, false, ours.address),
+        vote(&our_signer, 3, 1, false, ours.address),
+        vote(&our_signer, 2, 5, true, ours.address),
+        // Messages of other validators are not taken into account
+        vote(&their_signer, 3, 1, true, theirs.address),
+        proposal(&their_signer, 4, 0, theirs.address),
+    ];
+
+    assert_eq!(
+        bundle::last_signed::<TestContext, _>(entries.clone(), &ours.address),
+        Some(LastSigned {
+            height: 3,
+            round: 1,
+            step: Step::Prevote,
+        })
+    );
+
+    assert_eq!(
+        bundle::last_signed::<TestContext, _>(entries, &theirs.address),
+        Some(LastSigned {
+            height: 4,
+            round: 0,
+            step: Step::Propose,
+        })
+    );
+
+    assert_eq!(
+        bundle::last_signed::<TestContext, _>(Vec::new(), &ours.address),
+        None
+    );
+}
@@ -464,3 +464,71 @@ fn commit_certificate_quorum_boundary_just_above_two_thirds_sufficient() {
         .with_votes(0..2, VoteType::Precommit)
         .expect_valid();
 }
+
+/// Batch verification: the signatures of all the certificates are verified at once,
+/// and the certificates which are invalid are still told apart from the valid ones,
+/// with the same error as when verified on their own.
+#[test]
+fn commit_certificates_batch() {
+    let (validators, signers) = make_validators([25, 25, 25, 25], DEFAULT_SEED);
+    let ctx = TestContext::new();
+    let round = Round::new(0);
+    let validator_set = ValidatorSet::new(validators.to_vec());
+
+    let make_certificate = |height: u64| {
+        let height = Height::new(height);
+        let value_id = ValueId::new(height.as_u64());
+
+        let votes = (0..3)
+            .map(|i| {
+                block_on(signers[i].sign_vote(ctx.new_precommit(
+                    height,
+                    round,
+                    NilOrVal::Val(value_id),
+                    validators[i].address,
+                )))
+                .unwrap()
+            })
+            .collect();
+
+        CommitCertificate::new(height, round, value_id, votes)
+    };
+
+    let verify = |certificates: &[CommitCertificate<TestContext>]| {
+        block_on(signers[0].verify_commit_certificates(
+            &ctx,
+            certificates,
+            &validator_set,
+            ThresholdParams::default(),
+        ))
+    };
+
+    assert!(verify(&[]).is_empty());
+
+    let mut certificates: Vec<_> = (1..=5).map(make_certificate).collect();
+
+    let results = verify(&certificates);
+    assert_eq!(results.len(), 5);
+    assert!(results.iter().all(Result::is_ok));
+
+    // Invalid signature at height 2, not enough voting power at height 4
+    certificates[1].commit_signatures[2].signature = Signature::test();
+    certificates[3].commit_signatures.truncate(2);
+
+    let results = verify(&certificates);
+    assert!(results[0].is_ok());
+    assert!(matches!(
+        results[1],
+        Err(CertificateError::InvalidCommitSignature(_))
+    ));
+    assert!(results[2].is_ok());
+    assert_eq!(
+        results[3],
+        Err(CertificateError::NotEnoughVotingPower {
+            signed: 50,
+            total: 100,
+            expected: 67,
+        })
+    );
+    assert!(results[4].is_ok());
+}
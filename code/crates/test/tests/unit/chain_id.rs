@@ -0,0 +1,97 @@
+use futures::executor::block_on;
+use rand::{rngs::StdRng, SeedableRng};
+
+use arc_malachitebft_test::{
+    default_chain_id, Address, Ed25519Signer, Height, Proposal, TestContext, Value, ValueId, Vote,
+};
+use malachitebft_core_types::{ChainId, Context, NilOrVal, Round};
+use malachitebft_proto::Protobuf;
+use malachitebft_signing::{Signer, Verifier};
+use malachitebft_signing_ed25519::PrivateKey;
+
+fn make_signer() -> (Ed25519Signer, Address) {
+    let mut rng = StdRng::seed_from_u64(0xC);
+    let private_key = PrivateKey::generate(&mut rng);
+    let address = Address::from_public_key(&private_key.public_key());
+    (Ed25519Signer::new(private_key), address)
+}
+
+fn other_chain_id() -> ChainId {
+    ChainId::new("other-chain").unwrap()
+}
+
+#[test]
+fn context_builds_messages_for_its_chain() {
+    let (_, address) = make_signer();
+    let ctx = TestContext::new().with_chain_id(other_chain_id());
+    assert_eq!(ctx.chain_id(), Some(&other_chain_id()));
+
+    let vote = ctx.new_precommit(Height::new(1), Round::new(0), NilOrVal::Nil, address);
+    assert_eq!(vote.chain_id, other_chain_id());
+
+    let proposal = ctx.new_proposal(
+        Height::new(1),
+        Round::new(0),
+        Value::new(42),
+        Round::Nil,
+        address,
+    );
+    assert_eq!(proposal.chain_id, other_chain_id());
+}
+
+#[test]
+fn chain_id_is_encoded() {
+    let (_, address) = make_signer();
+
+    let vote = Vote::new_prevote(
+        Height::new(1),
+        Round::new(0),
+        NilOrVal::Val(ValueId::new(42)),
+        address,
+    )
+    .with_chain_id(other_chain_id());
+
+    assert_eq!(Vote::from_sign_bytes(&vote.to_sign_bytes()).unwrap(), vote);
+
+    let proposal = Proposal::new(
+        Height::new(1),
+        Round::new(0),
+        Value::new(42),
+        Round::Nil,
+        address,
+    )
+    .with_chain_id(other_chain_id());
+
+    assert_eq!(
+        Proposal::from_sign_bytes(&proposal.to_sign_bytes()).unwrap(),
+        proposal
+    );
+
+    // Messages without a chain id are rejected
+    let mut proto = vote.to_proto().unwrap();
+    proto.chain_id = String::new();
+    assert!(Vote::from_proto(proto).is_err());
+}
+
+#[test]
+fn signature_does_not_verify_on_another_chain() {
+    let (signer, address) = make_signer();
+    let public_key = signer.private_key().public_key();
+
+    let vote = Vote::new_precommit(
+        Height::new(1),
+        Round::new(0),
+        NilOrVal::Val(ValueId::new(42)),
+        address,
+    );
+    assert_eq!(vote.chain_id, default_chain_id());
+
+    let signed = block_on(signer.sign_vote(vote)).unwrap();
+    let valid =
+        block_on(signer.verify_signed_vote(&signed.message, &signed.signature, &public_key));
+    assert!(valid.unwrap().is_valid());
+
+    let replayed = signed.message.clone().with_chain_id(other_chain_id());
+    let valid = block_on(signer.verify_signed_vote(&replayed, &signed.signature, &public_key));
+    assert!(!valid.unwrap().is_valid());
+}
@@ -0,0 +1,213 @@
+//! Decoding of malformed messages, as sent by faulty or malicious peers.
+//!
+//! Arbitrary bytes, as well as truncated or corrupted encodings of valid messages,
+//! are decoded as each message received from the network. Decoding may fail,
+//! but must never panic.
+
+use core::fmt::Debug;
+
+use bytes::Bytes;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestRng, TestRunner};
+use prost::Message;
+
+use arc_malachitebft_test::codec::json::JsonCodec;
+use arc_malachitebft_test::codec::proto::ProtobufCodec;
+use arc_malachitebft_test::{
+    Address, Height, Proposal, ProposalPart, TestContext, Value, ValueId, Vote,
+};
+use malachitebft_codec::Codec;
+use malachitebft_core_consensus::{LivenessMsg, ProposedValue, SignedConsensusMsg};
+use malachitebft_core_types::ValidatorProof;
+use malachitebft_engine::util::streaming::StreamMessage;
+use malachitebft_proto::Protobuf;
+use malachitebft_sync::{Announcement, RawDecidedValue, Request, Response, Status};
+
+use super::{strategies, WireCodec};
+
+/// Decode the given bytes as each message sent over the wire, ignoring the errors.
+fn decode_wire_msgs<C: WireCodec>(codec: &C, bytes: &Bytes) {
+    let _ = Codec::<SignedConsensusMsg<TestContext>>::decode(codec, bytes.clone());
+    let _ = Codec::<StreamMessage<ProposalPart>>::decode(codec, bytes.clone());
+    let _ = Codec::<Status<TestContext>>::decode(codec, bytes.clone());
+    let _ = Codec::<Announcement<TestContext>>::decode(codec, bytes.clone());
+    let _ = Codec::<Request<TestContext>>::decode(codec, bytes.clone());
+    let _ = Codec::<Response<TestContext>>::decode(codec, bytes.clone());
+    let _ = Codec::<LivenessMsg<TestContext>>::decode(codec, bytes.clone());
+}
+
+/// Decode the given bytes as each Protobuf message of the test context, ignoring the errors.
+fn decode_protobuf_msgs(bytes: &Bytes) {
+    let codec = ProtobufCodec;
+
+    decode_wire_msgs(&codec, bytes);
+
+    let _ = Codec::<ProposedValue<TestContext>>::decode(&codec, bytes.clone());
+    let _ = Codec::<RawDecidedValue<TestContext>>::decode(&codec, bytes.clone());
+    let _ = Codec::<ValidatorProof<TestContext>>::decode(&codec, bytes.clone());
+
+    let _ = Height::from_bytes(bytes);
+    let _ = Address::from_bytes(bytes);
+    let _ = ValueId::from_bytes(bytes);
+    let _ = Value::from_bytes(bytes);
+    let _ = Vote::from_bytes(bytes);
+    let _ = Proposal::from_bytes(bytes);
+    let _ = ProposalPart::from_bytes(bytes);
+}
+
+/// Ways of corrupting the encoding of a valid message.
+#[derive(Clone, Debug)]
+enum Corruption {
+    /// Keep only the given fraction of the bytes
+    Truncate(f64),
+    /// XOR the byte at the given fraction of the length with the given mask
+    Flip(f64, u8),
+    /// Insert the given bytes at the given fraction of the length
+    Insert(f64, Vec<u8>),
+}
+
+impl Corruption {
+    fn apply(&self, bytes: &[u8]) -> Bytes {
+        let at = |fraction: f64| (bytes.len() as f64 * fraction) as usize;
+        let mut bytes = bytes.to_vec();
+
+        match self {
+            Self::Truncate(fraction) => bytes.truncate(at(*fraction)),
+            Self::Flip(fraction, mask) => {
+                if let Some(byte) = bytes.get_mut(at(*fraction)) {
+                    *byte ^= mask;
+                }
+            }
+            Self::Insert(fraction, inserted) => {
+                let index = at(*fraction).min(bytes.len());
+                bytes.splice(index..index, inserted.iter().copied());
+            }
+        }
+
+        Bytes::from(bytes)
+    }
+}
+
+fn corruptions() -> impl Strategy<Value = Vec<Corruption>> {
+    let corruption = prop_oneof![
+        (0.0..1.0).prop_map(Corruption::Truncate),
+        (0.0..1.0, 1..=u8::MAX).prop_map(|(at, mask)| Corruption::Flip(at, mask)),
+        (0.0..=1.0, vec(any::<u8>(), 1..16)).prop_map(|(at, bytes)| Corruption::Insert(at, bytes)),
+    ];
+
+    vec(corruption, 1..4)
+}
+
+/// Run the test with a fixed seed, so that the same cases are generated on every run.
+fn run<T: Debug>(strategy: impl Strategy<Value = T>, test: impl Fn(T)) {
+    let config = Config {
+        failure_persistence: None,
+        cases: 512,
+        ..Config::default()
+    };
+
+    let rng = TestRng::deterministic_rng(config.rng_algorithm);
+    let mut runner = TestRunner::new_with_rng(config, rng);
+
+    if let Err(e) = runner.run(&strategy, |value| {
+        test(value);
+        Ok(())
+    }) {
+        panic!("{e}");
+    }
+}
+
+/// Encode a valid message, corrupt its encoding, and decode the result as every message.
+fn check_corrupted<C, M>(codec: &C, msg: impl Strategy<Value = M>, decode: impl Fn(&Bytes))
+where
+    C: Codec<M>,
+    M: Debug,
+{
+    run((msg, corruptions()), |(msg, corruptions)| {
+        let mut bytes = codec.encode(&msg).expect("valid messages can be encoded");
+
+        for corruption in &corruptions {
+            bytes = corruption.apply(&bytes);
+        }
+
+        decode(&bytes);
+    });
+}
+
+#[test]
+fn protobuf_arbitrary_bytes() {
+    run(vec(any::<u8>(), 0..512), |bytes| {
+        decode_protobuf_msgs(&Bytes::from(bytes))
+    });
+}
+
+#[test]
+fn protobuf_corrupted_msgs() {
+    let codec = ProtobufCodec;
+
+    check_corrupted(
+        &codec,
+        strategies::signed_consensus_msg(),
+        decode_protobuf_msgs,
+    );
+    check_corrupted(&codec, strategies::stream_message(), decode_protobuf_msgs);
+    check_corrupted(&codec, strategies::status(), decode_protobuf_msgs);
+    check_corrupted(&codec, strategies::announcement(), decode_protobuf_msgs);
+    check_corrupted(&codec, strategies::request(), decode_protobuf_msgs);
+    check_corrupted(&codec, strategies::response(), decode_protobuf_msgs);
+    check_corrupted(&codec, strategies::liveness_msg(), decode_protobuf_msgs);
+}
+
+#[test]
+fn json_arbitrary_bytes() {
+    run(vec(any::<u8>(), 0..512), |bytes| {
+        decode_wire_msgs(&JsonCodec, &Bytes::from(bytes))
+    });
+}
+
+#[test]
+fn json_corrupted_msgs() {
+    let codec = JsonCodec;
+    let decode = |bytes: &Bytes| decode_wire_msgs(&codec, bytes);
+
+    check_corrupted(&codec, strategies::signed_consensus_msg(), decode);
+    check_corrupted(&codec, strategies::stream_message(), decode);
+    check_corrupted(&codec, strategies::status(), decode);
+    check_corrupted(&codec, strategies::announcement(), decode);
+    check_corrupted(&codec, strategies::request(), decode);
+    check_corrupted(&codec, strategies::response(), decode);
+    check_corrupted(&codec, strategies::liveness_msg(), decode);
+}
+
+/// Values shorter than the 8 bytes of their number used to panic when decoded.
+#[test]
+fn short_value_is_an_error() {
+    for len in 0..8 {
+        let proto = arc_malachitebft_test::proto::Value {
+            value: Some(Bytes::from(vec![0; len])),
+        };
+
+        assert!(Value::from_proto(proto).is_err());
+    }
+}
+
+/// Statuses with a peer id which is not a valid multihash used to panic when decoded.
+#[test]
+fn status_with_invalid_peer_id_is_an_error() {
+    use arc_malachitebft_test::proto;
+
+    let status = proto::Status {
+        peer_id: Some(proto::PeerId {
+            id: Bytes::from_static(&[0xff, 0xff, 0xff]),
+        }),
+        height: 1,
+        earliest_height: 0,
+        sync_height: 2,
+        validator: false,
+    };
+
+    let result =
+        Codec::<Status<TestContext>>::decode(&ProtobufCodec, status.encode_to_vec().into());
+    assert!(result.is_err());
+}
@@ -0,0 +1,237 @@
+//! Conformance suite for the codecs of the test context.
+//!
+//! Every codec must be able to encode and decode each message sent over the wire
+//! (see [`WireCodec`]), such that:
+//!
+//! - arbitrary messages, generated with `proptest`, decode back to the message which was encoded,
+//!   and encoding is deterministic;
+//! - the golden test vectors from [`vectors`] are encoded exactly as in the fixture files
+//!   stored under `fixtures/<codec>`, so that any change to the wire format is noticed;
+//! - malformed messages, see [`malformed`], fail to decode without panicking.
+//!
+//! The suite is sans-io: codecs are only ever given messages and bytes, without any networking.
+//! To (re)generate the fixtures after a deliberate change to the wire format, run the suite
+//! with the `MALACHITE_UPDATE_FIXTURES` environment variable set, and review the diff.
+
+mod malformed;
+mod strategies;
+pub mod vectors;
+
+use core::fmt::Debug;
+use std::path::PathBuf;
+use std::{env, fs};
+
+use bytes::Bytes;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+
+use arc_malachitebft_test::codec::json::JsonCodec;
+use arc_malachitebft_test::codec::proto::ProtobufCodec;
+use arc_malachitebft_test::{ProposalPart, TestContext};
+use malachitebft_codec::Codec;
+use malachitebft_core_consensus::{LivenessMsg, SignedConsensusMsg};
+use malachitebft_engine::util::streaming::StreamMessage;
+use malachitebft_sync::{Announcement, Request, Response, Status};
+
+const UPDATE_FIXTURES: &str = "MALACHITE_UPDATE_FIXTURES";
+
+/// The messages which a codec must support to be used over the wire.
+trait WireCodec:
+    Codec<SignedConsensusMsg<TestContext>>
+    + Codec<StreamMessage<ProposalPart>>
+    + Codec<Status<TestContext>>
+    + Codec<Announcement<TestContext>>
+    + Codec<Request<TestContext>>
+    + Codec<Response<TestContext>>
+    + Codec<LivenessMsg<TestContext>>
+{
+    /// Name of the directory holding the fixtures of the codec
+    const NAME: &'static str;
+
+    /// Extension of the fixture files of the codec
+    const EXTENSION: &'static str;
+
+    /// Contents of the fixture file for the given encoded message
+    fn to_fixture(bytes: &[u8]) -> String;
+
+    /// Encoded message stored in the given fixture file
+    fn from_fixture(fixture: &str) -> Bytes;
+}
+
+impl WireCodec for JsonCodec {
+    const NAME: &'static str = "json";
+    const EXTENSION: &'static str = "json";
+
+    fn to_fixture(bytes: &[u8]) -> String {
+        format!("{}\n", String::from_utf8_lossy(bytes))
+    }
+
+    fn from_fixture(fixture: &str) -> Bytes {
+        Bytes::copy_from_slice(fixture.trim_end().as_bytes())
+    }
+}
+
+impl WireCodec for ProtobufCodec {
+    const NAME: &'static str = "protobuf";
+    const EXTENSION: &'static str = "hex";
+
+    fn to_fixture(bytes: &[u8]) -> String {
+        format!("{}\n", hex::encode(bytes))
+    }
+
+    fn from_fixture(fixture: &str) -> Bytes {
+        Bytes::from(hex::decode(fixture.trim_end()).expect("fixture is not valid hex"))
+    }
+}
+
+/// Encode the message, check that it decodes back to the same message,
+/// and that encoding the decoded message yields the same bytes.
+fn roundtrip<C, M>(codec: &C, msg: &M) -> Result<Bytes, TestCaseError>
+where
+    C: Codec<M>,
+    M: PartialEq + Debug,
+{
+    let bytes = codec
+        .encode(msg)
+        .map_err(|e| TestCaseError::fail(format!("Failed to encode message: {e}")))?;
+
+    let decoded = codec
+        .decode(bytes.clone())
+        .map_err(|e| TestCaseError::fail(format!("Failed to decode message: {e}")))?;
+
+    prop_assert_eq!(&decoded, msg);
+
+    let reencoded = codec
+        .encode(&decoded)
+        .map_err(|e| TestCaseError::fail(format!("Failed to re-encode message: {e}")))?;
+
+    prop_assert_eq!(reencoded, bytes.clone(), "Encoding is not deterministic");
+
+    Ok(bytes)
+}
+
+fn fixture_path<C: WireCodec>(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/unit/codec/fixtures")
+        .join(C::NAME)
+        .join(name)
+        .with_extension(C::EXTENSION)
+}
+
+/// Check the encoding of a golden test vector against its fixture,
+/// or write the fixture if `MALACHITE_UPDATE_FIXTURES` is set.
+fn check_vector<C, M>(codec: &C, name: &str, msg: &M)
+where
+    C: WireCodec + Codec<M>,
+    M: PartialEq + Debug,
+{
+    let bytes = roundtrip(codec, msg)
+        .unwrap_or_else(|e| panic!("Vector `{name}` does not round-trip with {}: {e}", C::NAME));
+
+    let path = fixture_path::<C>(name);
+
+    if env::var_os(UPDATE_FIXTURES).is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, C::to_fixture(&bytes)).unwrap();
+        return;
+    }
+
+    let fixture = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "Failed to read fixture {}: {e}, run with {UPDATE_FIXTURES}=1 to generate it",
+            path.display()
+        )
+    });
+
+    let expected = C::from_fixture(&fixture);
+    assert_eq!(
+        bytes,
+        expected,
+        "Encoding of vector `{name}` by {} does not match its fixture",
+        C::NAME
+    );
+
+    let decoded = <C as Codec<M>>::decode(codec, expected)
+        .unwrap_or_else(|e| panic!("Failed to decode fixture {}: {e}", path.display()));
+    assert_eq!(
+        &decoded,
+        msg,
+        "Fixture {} decodes to another message",
+        path.display()
+    );
+}
+
+fn check_vectors<C: WireCodec>(codec: &C) {
+    for (name, msg) in vectors::signed_consensus_msgs() {
+        check_vector(codec, name, &msg);
+    }
+
+    for (name, msg) in vectors::stream_messages() {
+        check_vector(codec, name, &msg);
+    }
+
+    for (name, msg) in vectors::statuses() {
+        check_vector(codec, name, &msg);
+    }
+
+    for (name, msg) in vectors::announcements() {
+        check_vector(codec, name, &msg);
+    }
+
+    for (name, msg) in vectors::requests() {
+        check_vector(codec, name, &msg);
+    }
+
+    for (name, msg) in vectors::responses() {
+        check_vector(codec, name, &msg);
+    }
+
+    for (name, msg) in vectors::liveness_msgs() {
+        check_vector(codec, name, &msg);
+    }
+}
+
+fn check_roundtrips<C, M>(codec: &C, strategy: impl Strategy<Value = M>)
+where
+    C: Codec<M>,
+    M: PartialEq + Debug,
+{
+    let mut runner = TestRunner::new(Config {
+        failure_persistence: None,
+        ..Config::default()
+    });
+
+    if let Err(e) = runner.run(&strategy, |msg| roundtrip(codec, &msg).map(drop)) {
+        panic!("{e}");
+    }
+}
+
+fn check_all_roundtrips<C: WireCodec>(codec: &C) {
+    check_roundtrips(codec, strategies::signed_consensus_msg());
+    check_roundtrips(codec, strategies::stream_message());
+    check_roundtrips(codec, strategies::status());
+    check_roundtrips(codec, strategies::announcement());
+    check_roundtrips(codec, strategies::request());
+    check_roundtrips(codec, strategies::response());
+    check_roundtrips(codec, strategies::liveness_msg());
+}
+
+#[test]
+fn json_vectors() {
+    check_vectors(&JsonCodec);
+}
+
+#[test]
+fn json_roundtrips() {
+    check_all_roundtrips(&JsonCodec);
+}
+
+#[test]
+fn protobuf_vectors() {
+    check_vectors(&ProtobufCodec);
+}
+
+#[test]
+fn protobuf_roundtrips() {
+    check_all_roundtrips(&ProtobufCodec);
+}
@@ -0,0 +1,286 @@
+//! Strategies generating arbitrary wire messages of the test context.
+
+use bytes::Bytes;
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+
+use arc_malachitebft_test::{
+    Address, Height, Proposal, ProposalData, ProposalFin, ProposalInit, ProposalPart, TestContext,
+    Value, ValueId, Vote,
+};
+use malachitebft_core_consensus::{LivenessMsg, SignedConsensusMsg};
+use malachitebft_core_types::{
+    ChainId, CommitCertificate, CommitSignature, NilOrVal, PolkaCertificate, PolkaSignature, Round,
+    RoundCertificate, RoundCertificateType, RoundSignature, SignedMessage, ValidatorChange,
+    ValidatorSetUpdate, ValidatorSetUpdateCertificate, ValidatorSetUpdateSignature, VoteType,
+};
+use malachitebft_engine::util::streaming::{StreamContent, StreamId, StreamMessage};
+use malachitebft_signing_ed25519::Signature;
+use malachitebft_sync::{
+    Announcement, NodeMode, PeerId, RawDecidedValue, Request, Response, Status, ValueRequest,
+    ValueResponse,
+};
+
+fn bytes(max_len: usize) -> impl Strategy<Value = Bytes> {
+    vec(any::<u8>(), 0..=max_len).prop_map(Bytes::from)
+}
+
+fn height() -> impl Strategy<Value = Height> {
+    any::<u64>().prop_map(Height::new)
+}
+
+fn round() -> impl Strategy<Value = Round> {
+    any::<u32>().prop_map(Round::new)
+}
+
+fn pol_round() -> impl Strategy<Value = Round> {
+    prop_oneof![Just(Round::Nil), round()]
+}
+
+fn address() -> impl Strategy<Value = Address> {
+    any::<[u8; 20]>().prop_map(Address::new)
+}
+
+fn signature() -> impl Strategy<Value = Signature> {
+    vec(any::<u8>(), 64).prop_map(|bytes| Signature::from_bytes(bytes.try_into().unwrap()))
+}
+
+fn value_id() -> impl Strategy<Value = ValueId> {
+    any::<u64>().prop_map(ValueId::new)
+}
+
+fn nil_or_value_id() -> impl Strategy<Value = NilOrVal<ValueId>> {
+    prop_oneof![Just(NilOrVal::Nil), value_id().prop_map(NilOrVal::Val)]
+}
+
+fn value() -> impl Strategy<Value = Value> {
+    (any::<u64>(), bytes(32)).prop_map(|(value, extensions)| Value { value, extensions })
+}
+
+fn chain_id() -> impl Strategy<Value = ChainId> {
+    "[a-z0-9-]{1,50}".prop_map(|chain_id| ChainId::new(chain_id).unwrap())
+}
+
+fn vote_type() -> impl Strategy<Value = VoteType> {
+    prop_oneof![Just(VoteType::Prevote), Just(VoteType::Precommit)]
+}
+
+/// Votes are sent without their extension, which is not part of the encoding of a vote.
+pub fn vote() -> impl Strategy<Value = Vote> {
+    (
+        vote_type(),
+        height(),
+        round(),
+        nil_or_value_id(),
+        address(),
+        chain_id(),
+    )
+        .prop_map(
+            |(typ, height, round, value, validator_address, chain_id)| Vote {
+                typ,
+                height,
+                round,
+                value,
+                validator_address,
+                extension: None,
+                chain_id,
+            },
+        )
+}
+
+pub fn proposal() -> impl Strategy<Value = Proposal> {
+    (
+        height(),
+        round(),
+        value(),
+        pol_round(),
+        address(),
+        chain_id(),
+    )
+        .prop_map(
+            |(height, round, value, pol_round, validator_address, chain_id)| Proposal {
+                height,
+                round,
+                value,
+                pol_round,
+                validator_address,
+                chain_id,
+            },
+        )
+}
+
+pub fn signed_consensus_msg() -> impl Strategy<Value = SignedConsensusMsg<TestContext>> {
+    prop_oneof![
+        (vote(), signature())
+            .prop_map(|(vote, sig)| SignedConsensusMsg::Vote(SignedMessage::new(vote, sig))),
+        (proposal(), signature()).prop_map(|(proposal, sig)| {
+            SignedConsensusMsg::Proposal(SignedMessage::new(proposal, sig))
+        }),
+    ]
+}
+
+fn proposal_part() -> impl Strategy<Value = ProposalPart> {
+    prop_oneof![
+        (height(), round(), pol_round(), address()).prop_map(
+            |(height, round, pol_round, proposer)| {
+                ProposalPart::Init(ProposalInit::new(height, round, pol_round, proposer))
+            }
+        ),
+        any::<u64>().prop_map(|factor| ProposalPart::Data(ProposalData::new(factor))),
+        signature().prop_map(|sig| ProposalPart::Fin(ProposalFin::new(sig))),
+    ]
+}
+
+pub fn stream_message() -> impl Strategy<Value = StreamMessage<ProposalPart>> {
+    let content = prop_oneof![
+        proposal_part().prop_map(StreamContent::Data),
+        Just(StreamContent::Fin),
+    ];
+
+    (bytes(32), any::<u64>(), content).prop_map(|(stream_id, sequence, content)| {
+        StreamMessage::new(StreamId::new(stream_id), sequence, content)
+    })
+}
+
+fn peer_id() -> impl Strategy<Value = PeerId> {
+    // Identity multihash of 32 bytes, as for an inlined Ed25519 public key
+    any::<[u8; 32]>()
+        .prop_map(|digest| PeerId::from_bytes(&[&[0, 32], &digest[..]].concat()).unwrap())
+}
+
+pub fn status() -> impl Strategy<Value = Status<TestContext>> {
+    (peer_id(), height(), height(), height(), any::<bool>()).prop_map(
+        |(peer_id, tip_height, history_min_height, sync_height, is_validator)| Status {
+            peer_id,
+            tip_height,
+            history_min_height,
+            sync_height,
+            mode: NodeMode::from_is_validator(is_validator),
+        },
+    )
+}
+
+pub fn announcement() -> impl Strategy<Value = Announcement<TestContext>> {
+    (peer_id(), height(), value_id(), any::<[u8; 32]>()).prop_map(
+        |(peer_id, height, value_id, certificate_hash)| Announcement {
+            peer_id,
+            height,
+            value_id,
+            certificate_hash,
+        },
+    )
+}
+
+pub fn request() -> impl Strategy<Value = Request<TestContext>> {
+    (height(), height())
+        .prop_map(|(a, b)| Request::ValueRequest(ValueRequest::new(a.min(b)..=a.max(b))))
+}
+
+fn commit_certificate() -> impl Strategy<Value = CommitCertificate<TestContext>> {
+    let commit_signatures = vec(
+        (address(), signature()).prop_map(|(address, sig)| CommitSignature::new(address, sig)),
+        0..4,
+    );
+
+    (height(), round(), value_id(), commit_signatures).prop_map(
+        |(height, round, value_id, commit_signatures)| CommitCertificate {
+            height,
+            round,
+            value_id,
+            commit_signatures,
+        },
+    )
+}
+
+pub fn response() -> impl Strategy<Value = Response<TestContext>> {
+    let values = vec(
+        (bytes(64), commit_certificate())
+            .prop_map(|(value_bytes, certificate)| RawDecidedValue::new(value_bytes, certificate)),
+        0..4,
+    );
+
+    (height(), values).prop_map(|(start_height, values)| {
+        Response::ValueResponse(ValueResponse::new(start_height, values))
+    })
+}
+
+fn polka_certificate() -> impl Strategy<Value = PolkaCertificate<TestContext>> {
+    let polka_signatures = vec(
+        (address(), signature()).prop_map(|(address, sig)| PolkaSignature::new(address, sig)),
+        0..4,
+    );
+
+    (height(), round(), value_id(), polka_signatures).prop_map(
+        |(height, round, value_id, polka_signatures)| PolkaCertificate {
+            height,
+            round,
+            value_id,
+            polka_signatures,
+        },
+    )
+}
+
+fn round_certificate() -> impl Strategy<Value = RoundCertificate<TestContext>> {
+    let cert_type = prop_oneof![
+        Just(RoundCertificateType::Skip),
+        Just(RoundCertificateType::Precommit),
+    ];
+
+    let round_signatures = vec(
+        (vote_type(), nil_or_value_id(), address(), signature()).prop_map(
+            |(vote_type, value_id, address, sig)| {
+                RoundSignature::new(vote_type, value_id, address, sig)
+            },
+        ),
+        0..4,
+    );
+
+    (height(), round(), cert_type, round_signatures).prop_map(
+        |(height, round, cert_type, round_signatures)| RoundCertificate {
+            height,
+            round,
+            cert_type,
+            round_signatures,
+        },
+    )
+}
+
+fn validator_set_update_certificate(
+) -> impl Strategy<Value = ValidatorSetUpdateCertificate<TestContext>> {
+    let diff = vec(
+        (vec(any::<u8>(), 32), any::<u64>())
+            .prop_map(|(public_key, voting_power)| ValidatorChange::new(public_key, voting_power)),
+        0..4,
+    );
+
+    let signatures = vec(
+        (address(), signature())
+            .prop_map(|(address, sig)| ValidatorSetUpdateSignature::new(address, sig)),
+        0..4,
+    );
+
+    (
+        option::of(chain_id()),
+        any::<u64>(),
+        height(),
+        diff,
+        signatures,
+    )
+        .prop_map(|(chain_id, epoch, effective_height, diff, signatures)| {
+            ValidatorSetUpdateCertificate::new(
+                ValidatorSetUpdate::new(epoch, effective_height, diff).with_chain_id(chain_id),
+                signatures,
+            )
+        })
+}
+
+pub fn liveness_msg() -> impl Strategy<Value = LivenessMsg<TestContext>> {
+    prop_oneof![
+        (vote(), signature())
+            .prop_map(|(vote, sig)| LivenessMsg::Vote(SignedMessage::new(vote, sig))),
+        polka_certificate().prop_map(LivenessMsg::PolkaCertificate),
+        round_certificate().prop_map(LivenessMsg::SkipRoundCertificate),
+        validator_set_update_certificate().prop_map(LivenessMsg::ValidatorSetUpdate),
+    ]
+}
@@ -0,0 +1,255 @@
+//! Golden test vectors, covering each wire message and each of its variants.
+//!
+//! The encoding of each vector by each codec is stored in the `fixtures` directory,
+//! and must not change unless the wire format is deliberately changed.
+
+use bytes::Bytes;
+
+use arc_malachitebft_test::{
+    Address, Height, Proposal, ProposalData, ProposalFin, ProposalInit, ProposalPart, TestContext,
+    Value, ValueId, Vote,
+};
+use malachitebft_core_consensus::{LivenessMsg, SignedConsensusMsg};
+use malachitebft_core_types::{
+    ChainId, CommitCertificate, CommitSignature, NilOrVal, PolkaCertificate, PolkaSignature, Round,
+    RoundCertificate, RoundCertificateType, RoundSignature, SignedMessage, ValidatorChange,
+    ValidatorSetUpdate, ValidatorSetUpdateCertificate, ValidatorSetUpdateSignature, VoteType,
+};
+use malachitebft_engine::util::streaming::{StreamContent, StreamId, StreamMessage};
+use malachitebft_signing_ed25519::Signature;
+use malachitebft_sync::{
+    Announcement, NodeMode, PeerId, RawDecidedValue, Request, Response, Status, ValueRequest,
+    ValueResponse,
+};
+
+fn address(n: u8) -> Address {
+    Address::new([n; 20])
+}
+
+fn signature(n: u8) -> Signature {
+    Signature::from_bytes([n; 64])
+}
+
+fn chain_id() -> ChainId {
+    ChainId::new("conformance-1").unwrap()
+}
+
+fn stream_id() -> StreamId {
+    StreamId::new(Bytes::from_static(&[0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0]))
+}
+
+fn peer_id() -> PeerId {
+    PeerId::from_bytes(&[&[0, 32][..], &[7; 32]].concat()).unwrap()
+}
+
+fn vote() -> Vote {
+    Vote::new_prevote(
+        Height::new(1),
+        Round::new(0),
+        NilOrVal::Val(ValueId::new(42)),
+        address(1),
+    )
+    .with_chain_id(chain_id())
+}
+
+fn commit_certificate(height: u64) -> CommitCertificate<TestContext> {
+    CommitCertificate {
+        height: Height::new(height),
+        round: Round::new(1),
+        value_id: ValueId::new(height * 10),
+        commit_signatures: vec![
+            CommitSignature::new(address(1), signature(1)),
+            CommitSignature::new(address(2), signature(2)),
+        ],
+    }
+}
+
+pub fn signed_consensus_msgs() -> Vec<(&'static str, SignedConsensusMsg<TestContext>)> {
+    let precommit_nil =
+        Vote::new_precommit(Height::new(7), Round::new(3), NilOrVal::Nil, address(2))
+            .with_chain_id(chain_id());
+
+    let proposal = Proposal::new(
+        Height::new(1),
+        Round::new(0),
+        Value::new(42),
+        Round::Nil,
+        address(1),
+    )
+    .with_chain_id(chain_id());
+
+    let proposal_pol_round = Proposal::new(
+        Height::new(12),
+        Round::new(2),
+        Value {
+            value: 9,
+            extensions: Bytes::from_static(b"value extensions"),
+        },
+        Round::new(1),
+        address(4),
+    )
+    .with_chain_id(chain_id());
+
+    vec![
+        (
+            "vote_prevote",
+            SignedConsensusMsg::Vote(SignedMessage::new(vote(), signature(10))),
+        ),
+        (
+            "vote_precommit_nil",
+            SignedConsensusMsg::Vote(SignedMessage::new(precommit_nil, signature(11))),
+        ),
+        (
+            "proposal",
+            SignedConsensusMsg::Proposal(SignedMessage::new(proposal, signature(13))),
+        ),
+        (
+            "proposal_pol_round",
+            SignedConsensusMsg::Proposal(SignedMessage::new(proposal_pol_round, signature(14))),
+        ),
+    ]
+}
+
+pub fn stream_messages() -> Vec<(&'static str, StreamMessage<ProposalPart>)> {
+    let init = ProposalInit::new(Height::new(1), Round::new(0), Round::Nil, address(1));
+
+    vec![
+        (
+            "stream_init",
+            StreamMessage::new(
+                stream_id(),
+                0,
+                StreamContent::Data(ProposalPart::Init(init)),
+            ),
+        ),
+        (
+            "stream_data",
+            StreamMessage::new(
+                stream_id(),
+                1,
+                StreamContent::Data(ProposalPart::Data(ProposalData::new(123456789))),
+            ),
+        ),
+        (
+            "stream_fin",
+            StreamMessage::new(
+                stream_id(),
+                2,
+                StreamContent::Data(ProposalPart::Fin(ProposalFin::new(signature(20)))),
+            ),
+        ),
+        (
+            "stream_end",
+            StreamMessage::new(stream_id(), 3, StreamContent::Fin),
+        ),
+    ]
+}
+
+pub fn statuses() -> Vec<(&'static str, Status<TestContext>)> {
+    vec![(
+        "status",
+        Status {
+            peer_id: peer_id(),
+            tip_height: Height::new(100),
+            history_min_height: Height::new(10),
+            sync_height: Height::new(120),
+            mode: NodeMode::Validator,
+        },
+    )]
+}
+
+pub fn announcements() -> Vec<(&'static str, Announcement<TestContext>)> {
+    vec![(
+        "announcement",
+        Announcement {
+            peer_id: peer_id(),
+            height: Height::new(100),
+            value_id: ValueId::new(30),
+            certificate_hash: [9; 32],
+        },
+    )]
+}
+
+pub fn requests() -> Vec<(&'static str, Request<TestContext>)> {
+    vec![(
+        "request_value",
+        Request::ValueRequest(ValueRequest::new(Height::new(5)..=Height::new(8))),
+    )]
+}
+
+pub fn responses() -> Vec<(&'static str, Response<TestContext>)> {
+    let values = vec![
+        RawDecidedValue::new(Bytes::from_static(b"value 5"), commit_certificate(5)),
+        RawDecidedValue::new(Bytes::from_static(b"value 6"), commit_certificate(6)),
+    ];
+
+    vec![
+        (
+            "response_value",
+            Response::ValueResponse(ValueResponse::new(Height::new(5), values)),
+        ),
+        (
+            "response_empty",
+            Response::ValueResponse(ValueResponse::new(Height::new(5), vec![])),
+        ),
+    ]
+}
+
+pub fn liveness_msgs() -> Vec<(&'static str, LivenessMsg<TestContext>)> {
+    let polka_certificate = PolkaCertificate {
+        height: Height::new(3),
+        round: Round::new(1),
+        value_id: ValueId::new(30),
+        polka_signatures: vec![
+            PolkaSignature::new(address(1), signature(1)),
+            PolkaSignature::new(address(2), signature(2)),
+        ],
+    };
+
+    let round_certificate = RoundCertificate {
+        height: Height::new(3),
+        round: Round::new(2),
+        cert_type: RoundCertificateType::Skip,
+        round_signatures: vec![
+            RoundSignature::new(VoteType::Prevote, NilOrVal::Nil, address(1), signature(1)),
+            RoundSignature::new(
+                VoteType::Precommit,
+                NilOrVal::Val(ValueId::new(30)),
+                address(2),
+                signature(2),
+            ),
+        ],
+    };
+
+    let validator_set_update = ValidatorSetUpdateCertificate::new(
+        ValidatorSetUpdate::new(
+            2,
+            Height::new(50),
+            vec![
+                ValidatorChange::new(vec![5; 32], 10),
+                ValidatorChange::new(vec![6; 32], 0),
+            ],
+        )
+        .with_chain_id(Some(chain_id())),
+        vec![ValidatorSetUpdateSignature::new(address(1), signature(1))],
+    );
+
+    vec![
+        (
+            "liveness_vote",
+            LivenessMsg::Vote(SignedMessage::new(vote(), signature(10))),
+        ),
+        (
+            "liveness_polka_certificate",
+            LivenessMsg::PolkaCertificate(polka_certificate),
+        ),
+        (
+            "liveness_skip_round_certificate",
+            LivenessMsg::SkipRoundCertificate(round_certificate),
+        ),
+        (
+            "liveness_validator_set_update",
+            LivenessMsg::ValidatorSetUpdate(validator_set_update),
+        ),
+    ]
+}
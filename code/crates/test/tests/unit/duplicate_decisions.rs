@@ -0,0 +1,393 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::executor::block_on;
+use ractor::{Actor, ActorProcessingErr, ActorRef, RpcReplyPort};
+use tempfile::TempDir;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::timeout;
+
+use arc_malachitebft_test::codec::proto::ProtobufCodec;
+use arc_malachitebft_test::utils::validators::make_validators_seeded;
+use arc_malachitebft_test::{
+    Address, Ed25519Signer, Ed25519Verifier, Height, TestContext, ValidatorSet, Value, ValueId,
+};
+use malachitebft_app::builder::{ConsensusContext, Engine, EngineBuilder};
+use malachitebft_app::engine::consensus::Msg as ConsensusMsg;
+use malachitebft_app::engine::host::{HostMsg, Next, SyncedValueOutcome};
+use malachitebft_app::engine::network::NetworkIdentity;
+use malachitebft_app::engine::util::events::Event;
+use malachitebft_app::types::core::{
+    CommitCertificate, Context, HeightParams, LinearTimeouts, NilOrVal, Round, Validity,
+    ValueResponse,
+};
+use malachitebft_app::types::{Keypair, LocallyProposedValue, PeerId, ProposedValue};
+use malachitebft_signing::Signer;
+use malachitebft_test_app::config::Config;
+
+/// Value proposed by the node.
+const VALUE: u64 = 42;
+
+/// What the host of the node was asked to do.
+#[derive(Debug)]
+enum Notified {
+    StartedRound(Height, Round),
+    Decided(CommitCertificate<TestContext>),
+    Finalized(Height),
+}
+
+/// Host of the only validator, which proposes [`VALUE`] and reports what it is notified about.
+///
+/// When the first height is finalized, it starts that same height again, as an application
+/// which did not commit the decision yet would, so that the height can be decided again from
+/// a synced value. It does not propose a value for it again, so that only sync can decide it,
+/// and never replies to the later finalizations, so that consensus stays there.
+struct StubHost {
+    validator_set: ValidatorSet,
+    notified: mpsc::UnboundedSender<Notified>,
+}
+
+#[derive(Default)]
+struct StubHostState {
+    finalized: usize,
+    pending_values: Vec<RpcReplyPort<LocallyProposedValue<TestContext>>>,
+    pending_next: Vec<RpcReplyPort<Next<TestContext>>>,
+}
+
+impl StubHost {
+    fn params(&self) -> HeightParams<TestContext> {
+        HeightParams::new(self.validator_set.clone(), LinearTimeouts::default(), None)
+    }
+}
+
+#[async_trait]
+impl Actor for StubHost {
+    type Msg = HostMsg<TestContext>;
+    type State = StubHostState;
+    type Arguments = ();
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _args: (),
+    ) -> Result<StubHostState, ActorProcessingErr> {
+        Ok(StubHostState::default())
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        msg: Self::Msg,
+        state: &mut StubHostState,
+    ) -> Result<(), ActorProcessingErr> {
+        match msg {
+            HostMsg::ConsensusReady { reply_to } => {
+                reply_to.send((Height::new(1), self.params()))?;
+            }
+            HostMsg::StartedRound {
+                height,
+                round,
+                reply_to,
+                ..
+            } => {
+                self.notified.send(Notified::StartedRound(height, round))?;
+                reply_to.send(vec![])?;
+            }
+            HostMsg::GetValue {
+                height,
+                round,
+                reply_to,
+                ..
+            } => {
+                if state.finalized == 0 {
+                    reply_to.send(LocallyProposedValue::new(height, round, Value::new(VALUE)))?;
+                } else {
+                    state.pending_values.push(reply_to);
+                }
+            }
+            HostMsg::ExtendVote { reply_to, .. } => {
+                reply_to.send(None)?;
+            }
+            HostMsg::VerifyVoteExtension { reply_to, .. } => {
+                reply_to.send(Ok(()))?;
+            }
+            HostMsg::Decided {
+                certificate,
+                reply_to,
+                ..
+            } => {
+                self.notified.send(Notified::Decided(certificate))?;
+                reply_to.send(())?;
+            }
+            HostMsg::Finalized {
+                certificate,
+                reply_to,
+                ..
+            } => {
+                self.notified
+                    .send(Notified::Finalized(certificate.height))?;
+
+                state.finalized += 1;
+                if state.finalized == 1 {
+                    reply_to.send(Next::Start(certificate.height, self.params()))?;
+                } else {
+                    state.pending_next.push(reply_to);
+                }
+            }
+            HostMsg::ProcessSyncedValue {
+                height,
+                round,
+                proposer,
+                value_bytes,
+                reply_to,
+            } => {
+                let value = Value::new(u64::from_be_bytes(value_bytes.as_ref().try_into()?));
+
+                reply_to.send(Ok(Some(SyncedValueOutcome::Valid(ProposedValue {
+                    height,
+                    round,
+                    valid_round: Round::Nil,
+                    proposer,
+                    value,
+                    validity: Validity::Valid,
+                }))))?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+struct Node {
+    engine: Engine<TestContext>,
+    host: ActorRef<HostMsg<TestContext>>,
+    address: Address,
+    signer: Ed25519Signer,
+    events: broadcast::Receiver<Event<TestContext>>,
+    notified: mpsc::UnboundedReceiver<Notified>,
+    _home: TempDir,
+}
+
+impl Node {
+    async fn spawn(moniker: &str) -> Self {
+        let home = TempDir::new().unwrap();
+
+        let [(validator, private_key)] = make_validators_seeded([1], 42);
+        let validator_set = ValidatorSet::new(vec![validator.clone()]);
+
+        let (tx_notified, notified) = mpsc::unbounded_channel();
+
+        let host = StubHost::spawn(
+            None,
+            StubHost {
+                validator_set,
+                notified: tx_notified,
+            },
+            (),
+        )
+        .await
+        .unwrap()
+        .0;
+
+        let mut config = Config {
+            moniker: moniker.to_string(),
+            ..Config::default()
+        };
+        config.consensus.p2p.listen_addr = "/ip4/127.0.0.1/tcp/0".parse().unwrap();
+
+        let identity = NetworkIdentity::new(moniker.to_string(), Keypair::generate_ed25519(), None);
+
+        let consensus = ConsensusContext::new_validator(
+            validator.address,
+            Box::new(Ed25519Verifier),
+            Box::new(Ed25519Signer::new(private_key.clone())),
+        );
+
+        let engine = EngineBuilder::new(
+            TestContext::new(),
+            config,
+            ProtobufCodec,
+            identity,
+            home.path().join("wal").join("consensus.wal"),
+            consensus,
+        )
+        .without_sync()
+        .build(host.clone())
+        .await
+        .unwrap();
+
+        let events = engine.events.subscribe();
+
+        Self {
+            engine,
+            host,
+            address: validator.address,
+            signer: Ed25519Signer::new(private_key),
+            events,
+            notified,
+            _home: home,
+        }
+    }
+
+    /// Wait for the host to be notified of something matching the given function.
+    async fn wait_until_notified<T>(&mut self, mut f: impl FnMut(Notified) -> Option<T>) -> T {
+        timeout(Duration::from_secs(30), async {
+            loop {
+                let notified = self.notified.recv().await.expect("host stopped");
+                if let Some(t) = f(notified) {
+                    return t;
+                }
+            }
+        })
+        .await
+        .expect("timed out waiting for the host to be notified")
+    }
+
+    /// Let consensus decide the first height and start it again,
+    /// returning the commit certificate of the decision.
+    async fn decide_and_start_again(&mut self) -> CommitCertificate<TestContext> {
+        let certificate = self
+            .wait_until_notified(|notified| match notified {
+                Notified::Decided(certificate) => Some(certificate),
+                _ => None,
+            })
+            .await;
+
+        assert_eq!(certificate.height, Height::new(1));
+        assert_eq!(certificate.value_id, ValueId::new(VALUE));
+
+        // Consensus notifies the host about the first round of the height once it started it again
+        self.wait_until_notified(|notified| match notified {
+            Notified::StartedRound(height, round) => {
+                (height == Height::new(1) && round == Round::new(0)).then_some(())
+            }
+            Notified::Decided(certificate) => panic!("decided again: {certificate:?}"),
+            _ => None,
+        })
+        .await;
+
+        certificate
+    }
+
+    /// Commit certificate for the given value at the first height, signed by the validator.
+    fn commit_certificate(&self, value_id: ValueId) -> CommitCertificate<TestContext> {
+        let precommit = TestContext::new().new_precommit(
+            Height::new(1),
+            Round::new(0),
+            NilOrVal::Val(value_id),
+            self.address,
+        );
+
+        let precommit = block_on(self.signer.sign_vote(precommit)).unwrap();
+        CommitCertificate::new(Height::new(1), Round::new(0), value_id, vec![precommit])
+    }
+
+    /// Deliver the given value and its commit certificate to consensus, as sync would.
+    fn sync_value(&self, value: u64, certificate: CommitCertificate<TestContext>) {
+        let response = ValueResponse::new(
+            PeerId::random(),
+            Bytes::copy_from_slice(&value.to_be_bytes()),
+            certificate,
+        );
+
+        self.engine
+            .consensus
+            .cast(ConsensusMsg::ProcessSyncResponse(response))
+            .unwrap();
+    }
+
+    /// Events emitted by consensus so far.
+    fn events(&mut self) -> Vec<Event<TestContext>> {
+        std::iter::from_fn(|| match self.events.try_recv() {
+            Ok(event) => Some(event),
+            Err(broadcast::error::TryRecvError::Empty) => None,
+            Err(e) => panic!("failed to receive event: {e}"),
+        })
+        .collect()
+    }
+
+    fn stop(self) {
+        self.engine.node.stop(None);
+        self.host.stop(None);
+    }
+}
+
+/// Consensus decides a height, which is then decided again from a value received via sync.
+/// The application is notified about the decision only once.
+#[tokio::test]
+async fn decision_by_sync_after_consensus_is_suppressed() {
+    let mut node = Node::spawn("duplicate-decision").await;
+
+    let certificate = node.decide_and_start_again().await;
+    node.sync_value(VALUE, certificate);
+
+    // The decision from sync is finalized, without the host being notified about it first
+    let height = node
+        .wait_until_notified(|notified| match notified {
+            Notified::Finalized(height) => Some(height),
+            Notified::Decided(certificate) => panic!("decided again: {certificate:?}"),
+            _ => None,
+        })
+        .await;
+
+    assert_eq!(height, Height::new(1));
+
+    let suppressed = node
+        .events()
+        .into_iter()
+        .filter_map(|event| match event {
+            Event::DuplicateDecisionSuppressed {
+                height, value_id, ..
+            } => Some((height, value_id)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(suppressed, vec![(Height::new(1), ValueId::new(VALUE))]);
+
+    node.stop();
+}
+
+/// Consensus decides a height, which is then decided with another value from a value
+/// received via sync. Consensus stops without notifying the application about it.
+#[tokio::test]
+async fn conflicting_decision_by_sync_halts_consensus() {
+    let mut node = Node::spawn("conflicting-decision").await;
+
+    node.decide_and_start_again().await;
+
+    let conflicting = VALUE + 1;
+    let certificate = node.commit_certificate(ValueId::new(conflicting));
+    node.sync_value(conflicting, certificate);
+
+    let event = timeout(Duration::from_secs(30), async {
+        loop {
+            match node.events.recv().await.unwrap() {
+                event @ Event::ConflictingDecision { .. } => return event,
+                Event::DuplicateDecisionSuppressed { .. } => panic!("decision was suppressed"),
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for the conflicting decision");
+
+    assert!(matches!(
+        event,
+        Event::ConflictingDecision { height, value_id, decided, .. }
+            if height == Height::new(1)
+                && value_id == ValueId::new(conflicting)
+                && decided == ValueId::new(VALUE)
+    ));
+
+    // Consensus hangs, without processing any other message
+    let state = ractor::call_t!(node.engine.consensus, ConsensusMsg::DumpState, 500);
+    assert!(state.is_err());
+
+    // And the host is neither notified about the decision nor asked to finalize it
+    assert!(node.notified.try_recv().is_err());
+
+    node.stop();
+}
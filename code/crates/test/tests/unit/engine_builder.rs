@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ractor::{Actor, ActorCell, ActorProcessingErr, ActorRef, ActorStatus};
+use tempfile::TempDir;
+use tokio::time::sleep;
+
+use arc_malachitebft_test::codec::proto::ProtobufCodec;
+use arc_malachitebft_test::utils::validators::make_validators_seeded;
+use arc_malachitebft_test::{Ed25519Verifier, Height, TestContext, ValidatorSet};
+use malachitebft_app::builder::{ConsensusContext, Engine, EngineBuilder};
+use malachitebft_app::engine::host::HostMsg;
+use malachitebft_app::engine::network::NetworkIdentity;
+use malachitebft_app::types::core::{HeightParams, LinearTimeouts};
+use malachitebft_app::types::Keypair;
+use malachitebft_test_app::config::Config;
+
+/// Host which starts consensus at height 1, with a validator set
+/// the node is not part of, and ignores every other message.
+struct StubHost {
+    validator_set: ValidatorSet,
+}
+
+#[async_trait]
+impl Actor for StubHost {
+    type Msg = HostMsg<TestContext>;
+    type State = ();
+    type Arguments = ();
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _args: (),
+    ) -> Result<(), ActorProcessingErr> {
+        Ok(())
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        msg: Self::Msg,
+        _state: &mut (),
+    ) -> Result<(), ActorProcessingErr> {
+        if let HostMsg::ConsensusReady { reply_to } = msg {
+            let params =
+                HeightParams::new(self.validator_set.clone(), LinearTimeouts::default(), None);
+
+            reply_to.send((Height::new(1), params))?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn build_engine(
+    moniker: &str,
+    home: &TempDir,
+    sync: bool,
+) -> (Engine<TestContext>, ActorRef<HostMsg<TestContext>>) {
+    let validators = make_validators_seeded([1, 1], 42);
+    let validator_set = ValidatorSet::new(
+        validators
+            .iter()
+            .map(|(v, _)| v.clone())
+            .collect::<Vec<_>>(),
+    );
+
+    let host = StubHost::spawn(None, StubHost { validator_set }, ())
+        .await
+        .unwrap()
+        .0;
+
+    let mut config = Config {
+        moniker: moniker.to_string(),
+        ..Config::default()
+    };
+    config.consensus.p2p.listen_addr = "/ip4/127.0.0.1/tcp/0".parse().unwrap();
+
+    let identity = NetworkIdentity::new(moniker.to_string(), Keypair::generate_ed25519(), None);
+
+    // A full node, whose address is not part of the validator set
+    let address = validators[0].0.address;
+    let consensus = ConsensusContext::new_full_node(address, Box::new(Ed25519Verifier));
+
+    let builder = EngineBuilder::new(
+        TestContext::new(),
+        config,
+        ProtobufCodec,
+        identity,
+        home.path().join("wal").join("consensus.wal"),
+        consensus,
+    );
+
+    let builder = if sync {
+        builder
+    } else {
+        builder.without_sync()
+    };
+
+    (builder.build(host.clone()).await.unwrap(), host)
+}
+
+/// Wait for the actor to be done starting, and return its status.
+async fn wait_until_started(actor: &ActorCell) -> ActorStatus {
+    for _ in 0..100 {
+        if actor.get_status() != ActorStatus::Starting {
+            break;
+        }
+
+        sleep(Duration::from_millis(50)).await;
+    }
+
+    actor.get_status()
+}
+
+async fn stop(engine: Engine<TestContext>, host: ActorRef<HostMsg<TestContext>>) {
+    engine.node.stop(None);
+    engine.handle.await.unwrap();
+    host.stop(None);
+}
+
+#[tokio::test]
+async fn engine_builder_spawns_sync_by_default() {
+    let home = TempDir::new().unwrap();
+    let (engine, host) = build_engine("with-sync", &home, true).await;
+
+    assert_eq!(
+        wait_until_started(&engine.network).await,
+        ActorStatus::Running
+    );
+    assert_eq!(
+        wait_until_started(&engine.consensus).await,
+        ActorStatus::Running
+    );
+    assert_eq!(wait_until_started(&engine.wal).await, ActorStatus::Running);
+
+    let sync = engine
+        .sync
+        .as_ref()
+        .expect("sync is enabled in the configuration");
+    assert_eq!(wait_until_started(sync).await, ActorStatus::Running);
+
+    stop(engine, host).await;
+}
+
+#[tokio::test]
+async fn engine_builder_without_sync() {
+    let home = TempDir::new().unwrap();
+    let (engine, host) = build_engine("without-sync", &home, false).await;
+
+    assert_eq!(
+        wait_until_started(&engine.network).await,
+        ActorStatus::Running
+    );
+    assert_eq!(
+        wait_until_started(&engine.consensus).await,
+        ActorStatus::Running
+    );
+    assert_eq!(wait_until_started(&engine.wal).await, ActorStatus::Running);
+    assert!(engine.sync.is_none());
+
+    stop(engine, host).await;
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use arc_malachitebft_test::home_dir::{
+    config_dir, db_dir, initialize_home_dir, lock_file, wal_dir, HomeDirError,
+};
+
+#[test]
+fn initialize_creates_layout_and_locks_home_dir() {
+    let tmp = tempfile::tempdir().unwrap();
+    let home = tmp.path().join("nodes").join("node-0");
+
+    let lock = initialize_home_dir(&home).unwrap();
+
+    assert!(config_dir(&home).is_dir());
+    assert!(wal_dir(&home).is_dir());
+    assert!(db_dir(&home).is_dir());
+    assert_eq!(lock.path(), lock_file(&home));
+
+    // Nothing but the home directory is left in its parent
+    assert_eq!(fs::read_dir(tmp.path().join("nodes")).unwrap().count(), 1);
+
+    // A second node cannot run out of the same home directory
+    let pid = std::process::id();
+    assert!(matches!(
+        initialize_home_dir(&home),
+        Err(HomeDirError::Locked { pid: Some(p), .. }) if p == pid
+    ));
+
+    // Until the first one releases it
+    drop(lock);
+
+    let _lock = initialize_home_dir(&home).unwrap();
+}
+
+#[test]
+fn leftover_lock_file_does_not_block_startup() {
+    let tmp = tempfile::tempdir().unwrap();
+    let home = tmp.path().to_path_buf();
+
+    // Lock file left behind by a node killed without releasing its lock,
+    // whose lock was then released by the operating system
+    initialize_home_dir(&home).unwrap();
+    fs::write(lock_file(&home), b"4194304\n").unwrap();
+
+    let lock = initialize_home_dir(&home).unwrap();
+
+    let pid = fs::read_to_string(lock.path()).unwrap();
+    assert_eq!(pid.trim(), std::process::id().to_string());
+}
+
+const LOCKING_HOME_DIR: &str = "MALACHITE_TEST_LOCKING_HOME_DIR";
+
+#[test]
+fn lock_of_killed_process_is_released() {
+    // Child process: lock the home directory and wait to be killed
+    if let Ok(home) = std::env::var(LOCKING_HOME_DIR) {
+        let _lock = initialize_home_dir(Path::new(&home)).unwrap();
+        std::thread::sleep(Duration::from_secs(60));
+        return;
+    }
+
+    let tmp = tempfile::tempdir().unwrap();
+    let home = tmp.path().to_path_buf();
+
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "home_dir::lock_of_killed_process_is_released"])
+        .env(LOCKING_HOME_DIR, &home)
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let child_pid = child.id();
+    let start = Instant::now();
+
+    // Wait for the child to take the lock, after which it records its process id
+    while fs::read_to_string(lock_file(&home))
+        .ok()
+        .as_deref()
+        .map(str::trim)
+        != Some(child_pid.to_string().as_str())
+    {
+        assert!(
+            start.elapsed() < Duration::from_secs(30),
+            "child never locked"
+        );
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert!(matches!(
+        initialize_home_dir(&home),
+        Err(HomeDirError::Locked { pid: Some(pid), .. }) if pid == child_pid
+    ));
+
+    // Kill the child without giving it a chance to release the lock
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(lock_file(&home).exists());
+
+    let lock = initialize_home_dir(&home).unwrap();
+    let pid = fs::read_to_string(lock.path()).unwrap();
+    assert_eq!(pid.trim(), std::process::id().to_string());
+}
+
+#[test]
+fn initialize_checks_existing_layout() {
+    let tmp = tempfile::tempdir().unwrap();
+    let home = tmp.path().to_path_buf();
+
+    // A missing directory is created, but one replaced by a file is rejected
+    fs::create_dir(config_dir(&home)).unwrap();
+    fs::write(db_dir(&home), b"").unwrap();
+
+    assert!(matches!(
+        initialize_home_dir(&home),
+        Err(HomeDirError::NotADirectory(path)) if path == db_dir(&home)
+    ));
+
+    assert!(wal_dir(&home).is_dir());
+    assert!(!lock_file(&home).exists());
+}
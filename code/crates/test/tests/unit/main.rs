@@ -1,3 +1,16 @@
+mod archive;
+mod bundle;
 mod certificates;
+mod chain_id;
+mod codec;
+mod duplicate_decisions;
+mod engine_builder;
+mod home_dir;
+mod remote_signer;
+mod seen_messages;
+mod sign_guard;
 mod sync;
 mod validator_proof;
+mod validator_set_update;
+mod voting_power_change;
+mod wal;
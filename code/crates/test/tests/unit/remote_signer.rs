@@ -0,0 +1,138 @@
+use bytes::Bytes;
+
+use arc_malachitebft_test::codec::proto::ProtobufCodec;
+use arc_malachitebft_test::{default_chain_id, Ed25519Signer, Height, TestContext, Value};
+use malachitebft_app::sign_guard::SignGuard;
+use malachitebft_core_types::{Context, NilOrVal, Round, ValidatorChange, ValidatorSetUpdate};
+use malachitebft_signer::{
+    AuthKey, RemoteSigner, RemoteSignerConfig, SignerAddress, SignerError, SignerListener,
+    SignerServer,
+};
+use malachitebft_signing::Signer;
+
+use crate::certificates::make_validators;
+
+/// Start a remote signer signing with the given signer on a random port, returning its address.
+async fn start_signer<S>(signer: S, key: AuthKey) -> SignerAddress
+where
+    S: Signer<TestContext> + 'static,
+{
+    let listener = SignerListener::bind(&"tcp://127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap();
+
+    let address = listener.local_addr().unwrap();
+    let server = SignerServer::new(signer, ProtobufCodec, key);
+    tokio::spawn(server.run(listener));
+
+    address
+}
+
+fn remote_signer(address: SignerAddress, key: AuthKey) -> RemoteSigner<TestContext, ProtobufCodec> {
+    RemoteSigner::new(RemoteSignerConfig::new(address, key), ProtobufCodec)
+}
+
+#[tokio::test]
+async fn signatures_match_local_signer() {
+    let ctx = TestContext::new();
+    let ([validator], [local]) = make_validators([10], 42);
+    let key = AuthKey::generate();
+
+    let address = start_signer(Ed25519Signer::new(local.private_key().clone()), key.clone()).await;
+    let remote = remote_signer(address, key);
+
+    remote.ping().await.unwrap();
+
+    let vote = ctx.new_prevote(
+        Height::new(1),
+        Round::new(0),
+        NilOrVal::Val(Value::new(42).id()),
+        validator.address,
+    );
+    assert_eq!(
+        remote.sign_vote(vote.clone()).await.unwrap(),
+        local.sign_vote(vote).await.unwrap()
+    );
+
+    let proposal = ctx.new_proposal(
+        Height::new(1),
+        Round::new(0),
+        Value::new(42),
+        Round::Nil,
+        validator.address,
+    );
+    assert_eq!(
+        remote.sign_proposal(proposal.clone()).await.unwrap(),
+        local.sign_proposal(proposal).await.unwrap()
+    );
+
+    let extension = Bytes::from_static(b"extension");
+    assert_eq!(
+        remote.sign_vote_extension(extension.clone()).await.unwrap(),
+        local.sign_vote_extension(extension).await.unwrap()
+    );
+
+    let proof = remote
+        .sign_validator_proof(vec![1; 32], vec![2; 38])
+        .await
+        .unwrap();
+    assert_eq!(
+        proof,
+        local
+            .sign_validator_proof(vec![1; 32], vec![2; 38])
+            .await
+            .unwrap()
+    );
+
+    let update = ValidatorSetUpdate::new(
+        1,
+        Height::new(5),
+        vec![ValidatorChange::new(vec![1; 32], 10)],
+    )
+    .with_chain_id(Some(default_chain_id()));
+    assert_eq!(
+        remote.sign_validator_set_update(&update).await.unwrap(),
+        local.sign_validator_set_update(&update).await.unwrap()
+    );
+}
+
+#[tokio::test]
+async fn wrong_key_is_rejected() {
+    let ([_], [local]) = make_validators([10], 42);
+
+    let address = start_signer(local, AuthKey::generate()).await;
+    let remote = remote_signer(address, AuthKey::generate());
+
+    assert!(matches!(
+        remote.ping().await,
+        Err(SignerError::Authentication(_))
+    ));
+}
+
+#[tokio::test]
+async fn refusals_of_the_signer_are_returned() {
+    let ctx = TestContext::new();
+    let ([validator], [local]) = make_validators([10], 42);
+    let key = AuthKey::generate();
+
+    let dir = tempfile::tempdir().unwrap();
+    let guard = SignGuard::open(local, dir.path().join("sign_state")).unwrap();
+
+    let address = start_signer(guard, key.clone()).await;
+    let remote = remote_signer(address, key);
+
+    let prevote = |value| {
+        ctx.new_prevote(
+            Height::new(1),
+            Round::new(0),
+            NilOrVal::Val(Value::new(value).id()),
+            validator.address,
+        )
+    };
+
+    remote.sign_vote(prevote(42)).await.unwrap();
+
+    // The signer refuses to equivocate, but signs the same vote again
+    assert!(remote.sign_vote(prevote(43)).await.is_err());
+    remote.sign_vote(prevote(42)).await.unwrap();
+}
@@ -0,0 +1,216 @@
+use std::path::Path;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+use ractor::{Actor, ActorProcessingErr, ActorRef};
+use tempfile::TempDir;
+use tokio::sync::broadcast;
+use tokio::time::timeout;
+
+use arc_malachitebft_test::codec::proto::ProtobufCodec;
+use arc_malachitebft_test::utils::validators::make_validators_seeded;
+use arc_malachitebft_test::{
+    Ed25519Signer, Ed25519Verifier, Height, PrivateKey, TestContext, Validator, ValidatorSet,
+};
+use malachitebft_app::builder::{ConsensusContext, Engine, EngineBuilder};
+use malachitebft_app::engine::consensus::Msg as ConsensusMsg;
+use malachitebft_app::engine::host::HostMsg;
+use malachitebft_app::engine::network::{NetworkEvent, NetworkIdentity};
+use malachitebft_app::engine::util::events::Event;
+use malachitebft_app::types::core::{
+    Context, HeightParams, LinearTimeouts, NilOrVal, Round, SignedVote,
+};
+use malachitebft_app::types::{Keypair, PeerId, SignedConsensusMsg};
+use malachitebft_signing::Signer;
+use malachitebft_test_app::config::Config;
+
+/// Host which starts consensus at height 1, with a validator set of two validators,
+/// so that the node alone cannot decide, and never proposes a value.
+struct StubHost {
+    validator_set: ValidatorSet,
+}
+
+#[async_trait]
+impl Actor for StubHost {
+    type Msg = HostMsg<TestContext>;
+    type State = ();
+    type Arguments = ();
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _args: (),
+    ) -> Result<(), ActorProcessingErr> {
+        Ok(())
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        msg: Self::Msg,
+        _state: &mut (),
+    ) -> Result<(), ActorProcessingErr> {
+        match msg {
+            HostMsg::ConsensusReady { reply_to } => {
+                let params =
+                    HeightParams::new(self.validator_set.clone(), LinearTimeouts::default(), None);
+
+                reply_to.send((Height::new(1), params))?;
+            }
+            HostMsg::StartedRound { reply_to, .. } => {
+                reply_to.send(vec![])?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+struct Node {
+    engine: Engine<TestContext>,
+    host: ActorRef<HostMsg<TestContext>>,
+    events: broadcast::Receiver<Event<TestContext>>,
+}
+
+impl Node {
+    async fn spawn(moniker: &str, wal_path: &Path, validators: &[(Validator, PrivateKey)]) -> Self {
+        let validator_set = ValidatorSet::new(
+            validators
+                .iter()
+                .map(|(v, _)| v.clone())
+                .collect::<Vec<_>>(),
+        );
+
+        let host = StubHost::spawn(None, StubHost { validator_set }, ())
+            .await
+            .unwrap()
+            .0;
+
+        let mut config = Config {
+            moniker: moniker.to_string(),
+            ..Config::default()
+        };
+        config.consensus.p2p.listen_addr = "/ip4/127.0.0.1/tcp/0".parse().unwrap();
+        config.consensus.wal_replay_delay = Duration::ZERO;
+
+        let identity = NetworkIdentity::new(moniker.to_string(), Keypair::generate_ed25519(), None);
+
+        let (validator, private_key) = &validators[0];
+        let consensus = ConsensusContext::new_validator(
+            validator.address,
+            Box::new(Ed25519Verifier),
+            Box::new(Ed25519Signer::new(private_key.clone())),
+        );
+
+        let engine = EngineBuilder::new(
+            TestContext::new(),
+            config,
+            ProtobufCodec,
+            identity,
+            wal_path.to_path_buf(),
+            consensus,
+        )
+        .without_sync()
+        .build(host.clone())
+        .await
+        .unwrap();
+
+        let events = engine.events.subscribe();
+
+        Self {
+            engine,
+            host,
+            events,
+        }
+    }
+
+    /// Deliver the given vote to consensus, as if gossiped by a peer.
+    fn receive(&self, vote: SignedVote<TestContext>) {
+        self.engine
+            .consensus
+            .cast(ConsensusMsg::NetworkEvent(NetworkEvent::Vote(
+                PeerId::random(),
+                vote,
+            )))
+            .unwrap();
+    }
+
+    /// Wait for consensus to receive the given vote,
+    /// returning the votes it received in the meantime.
+    async fn wait_until_received(
+        &mut self,
+        vote: &SignedVote<TestContext>,
+    ) -> Vec<SignedVote<TestContext>> {
+        let mut received = Vec::new();
+
+        timeout(Duration::from_secs(30), async {
+            loop {
+                if let Event::Received(SignedConsensusMsg::Vote(v)) =
+                    self.events.recv().await.unwrap()
+                {
+                    if &v == vote {
+                        return;
+                    }
+
+                    received.push(v);
+                }
+            }
+        })
+        .await
+        .expect("timed out waiting for the vote to be received");
+
+        received
+    }
+
+    async fn stop(self) {
+        self.engine.node.stop(None);
+        self.engine.handle.await.unwrap();
+        self.host.stop(None);
+    }
+}
+
+/// Vote of the given validator for nil at the first height and round, signed by it.
+fn vote_for_nil(
+    (validator, private_key): &(Validator, PrivateKey),
+    precommit: bool,
+) -> SignedVote<TestContext> {
+    let ctx = TestContext::new();
+    let (height, round, address) = (Height::new(1), Round::new(0), validator.address);
+
+    let vote = if precommit {
+        ctx.new_precommit(height, round, NilOrVal::Nil, address)
+    } else {
+        ctx.new_prevote(height, round, NilOrVal::Nil, address)
+    };
+
+    block_on(Ed25519Signer::new(private_key.clone()).sign_vote(vote)).unwrap()
+}
+
+/// A vote processed before a restart within a height is recovered from the WAL,
+/// and dropped when gossiped again after the restart.
+#[tokio::test]
+async fn votes_replayed_from_the_wal_are_dropped_when_received_again() {
+    let home = TempDir::new().unwrap();
+    let wal_path = home.path().join("wal").join("consensus.wal");
+
+    let validators = make_validators_seeded([1, 1], 42);
+    let prevote = vote_for_nil(&validators[1], false);
+    let precommit = vote_for_nil(&validators[1], true);
+
+    let mut node = Node::spawn("before-restart", &wal_path, &validators).await;
+    node.receive(prevote.clone());
+    node.wait_until_received(&prevote).await;
+    node.stop().await;
+
+    let mut node = Node::spawn("after-restart", &wal_path, &validators).await;
+    node.receive(prevote.clone());
+    node.receive(precommit.clone());
+
+    // The prevote is dropped, while the precommit, received after it, is processed
+    let received = node.wait_until_received(&precommit).await;
+    assert!(!received.contains(&prevote), "prevote was processed again");
+
+    node.stop().await;
+}
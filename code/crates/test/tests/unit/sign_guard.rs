@@ -0,0 +1,267 @@
+use std::path::Path;
+
+use futures::executor::block_on;
+
+use arc_malachitebft_test::{Address, Ed25519Signer, Height, TestContext, Value, ValueId};
+use malachitebft_app::bundle::{LastSigned, Step};
+use malachitebft_app::sign_guard::{SignGuard, SignGuardError, SignState};
+use malachitebft_core_types::{Context, NilOrVal, Round};
+use malachitebft_signing::Signer;
+
+use crate::certificates::make_validators;
+
+struct Validator {
+    ctx: TestContext,
+    address: Address,
+    signer: Ed25519Signer,
+}
+
+impl Validator {
+    fn new() -> Self {
+        let ([validator], [signer]) = make_validators([10], 42);
+
+        Self {
+            ctx: TestContext::new(),
+            address: validator.address,
+            signer,
+        }
+    }
+
+    fn signer(&self) -> Ed25519Signer {
+        Ed25519Signer::new(self.signer.private_key().clone())
+    }
+
+    /// Start the validator, or restart it, with the sign state persisted in the given file.
+    fn start(&self, path: &Path) -> SignGuard<TestContext, Ed25519Signer> {
+        SignGuard::open(self.signer(), path).unwrap()
+    }
+
+    fn prevote(
+        &self,
+        guard: &SignGuard<TestContext, Ed25519Signer>,
+        height: u64,
+        round: u32,
+        value: Option<u64>,
+    ) -> bool {
+        let vote = self.ctx.new_prevote(
+            Height::new(height),
+            Round::new(round),
+            value_id(value),
+            self.address,
+        );
+
+        block_on(guard.sign_vote(vote)).is_ok()
+    }
+
+    fn precommit(
+        &self,
+        guard: &SignGuard<TestContext, Ed25519Signer>,
+        height: u64,
+        round: u32,
+        value: Option<u64>,
+    ) -> bool {
+        let vote = self.ctx.new_precommit(
+            Height::new(height),
+            Round::new(round),
+            value_id(value),
+            self.address,
+        );
+
+        block_on(guard.sign_vote(vote)).is_ok()
+    }
+
+    fn propose(
+        &self,
+        guard: &SignGuard<TestContext, Ed25519Signer>,
+        height: u64,
+        round: u32,
+        value: u64,
+    ) -> bool {
+        let proposal = self.ctx.new_proposal(
+            Height::new(height),
+            Round::new(round),
+            Value::new(value),
+            Round::Nil,
+            self.address,
+        );
+
+        block_on(guard.sign_proposal(proposal)).is_ok()
+    }
+}
+
+fn value_id(value: Option<u64>) -> NilOrVal<ValueId> {
+    match value {
+        Some(value) => NilOrVal::Val(Value::new(value).id()),
+        None => NilOrVal::Nil,
+    }
+}
+
+fn at(height: u64, round: i64, step: Step) -> Option<LastSigned> {
+    Some(LastSigned {
+        height,
+        round,
+        step,
+    })
+}
+
+#[test]
+fn signs_the_steps_of_a_round_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sign_state");
+    let validator = Validator::new();
+
+    let guard = validator.start(&path);
+    assert_eq!(block_on(guard.last_signed()), None);
+
+    assert!(validator.propose(&guard, 1, 0, 42));
+    assert!(validator.prevote(&guard, 1, 0, Some(42)));
+    assert!(validator.precommit(&guard, 1, 0, Some(42)));
+    assert!(validator.prevote(&guard, 2, 0, None));
+
+    assert_eq!(block_on(guard.last_signed()), at(2, 0, Step::Prevote));
+
+    // The last signed message is persisted
+    drop(guard);
+    let guard = validator.start(&path);
+    assert_eq!(block_on(guard.last_signed()), at(2, 0, Step::Prevote));
+}
+
+#[test]
+fn restart_in_round_refuses_conflicting_vote() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sign_state");
+    let validator = Validator::new();
+
+    let guard = validator.start(&path);
+    assert!(validator.prevote(&guard, 5, 1, Some(42)));
+
+    // The node crashes before writing the prevote to its WAL, and restarts in the same round
+    drop(guard);
+    let guard = validator.start(&path);
+
+    // Prevoting for another value, or for nil, would be equivocating
+    assert!(!validator.prevote(&guard, 5, 1, Some(43)));
+    assert!(!validator.prevote(&guard, 5, 1, None));
+
+    // Signing the same prevote again is safe
+    assert!(validator.prevote(&guard, 5, 1, Some(42)));
+
+    // The round can then go on
+    assert!(validator.precommit(&guard, 5, 1, Some(42)));
+    assert_eq!(block_on(guard.last_signed()), at(5, 1, Step::Precommit));
+}
+
+#[test]
+fn restart_in_round_refuses_conflicting_proposal() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sign_state");
+    let validator = Validator::new();
+
+    let guard = validator.start(&path);
+    assert!(validator.propose(&guard, 3, 0, 42));
+
+    // After a restart, the application builds another value for the same round
+    drop(guard);
+    let guard = validator.start(&path);
+
+    assert!(!validator.propose(&guard, 3, 0, 43));
+    assert!(validator.propose(&guard, 3, 0, 42));
+
+    assert_eq!(block_on(guard.last_signed()), at(3, 0, Step::Propose));
+}
+
+#[test]
+fn restart_replays_the_messages_of_the_height() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sign_state");
+    let validator = Validator::new();
+
+    let guard = validator.start(&path);
+    assert!(validator.precommit(&guard, 3, 0, Some(42)));
+    assert!(validator.propose(&guard, 4, 0, 42));
+    assert!(validator.prevote(&guard, 4, 0, Some(42)));
+    assert!(validator.precommit(&guard, 4, 0, None));
+    assert!(validator.prevote(&guard, 4, 1, None));
+
+    // The node restarts and replays its WAL, signing the same messages again
+    drop(guard);
+    let guard = validator.start(&path);
+
+    assert!(validator.propose(&guard, 4, 0, 42));
+    assert!(validator.prevote(&guard, 4, 0, Some(42)));
+    assert!(validator.precommit(&guard, 4, 0, None));
+    assert!(validator.prevote(&guard, 4, 1, None));
+
+    // Different messages for the same steps are still refused
+    assert!(!validator.propose(&guard, 4, 0, 43));
+    assert!(!validator.precommit(&guard, 4, 0, Some(42)));
+
+    // Only the messages of the height of the last one are kept
+    assert!(!validator.precommit(&guard, 3, 0, Some(42)));
+
+    assert_eq!(block_on(guard.last_signed()), at(4, 1, Step::Prevote));
+}
+
+#[test]
+fn restart_refuses_earlier_messages() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sign_state");
+    let validator = Validator::new();
+
+    let guard = validator.start(&path);
+    assert!(validator.precommit(&guard, 7, 2, Some(42)));
+
+    drop(guard);
+    let guard = validator.start(&path);
+
+    // Earlier step, round and height
+    assert!(!validator.prevote(&guard, 7, 2, Some(42)));
+    assert!(!validator.precommit(&guard, 7, 1, Some(42)));
+    assert!(!validator.propose(&guard, 6, 9, 42));
+
+    // Refused messages do not change the sign state
+    assert_eq!(block_on(guard.last_signed()), at(7, 2, Step::Precommit));
+
+    // Later round and height
+    assert!(validator.propose(&guard, 7, 3, 43));
+    assert!(validator.prevote(&guard, 8, 0, None));
+}
+
+#[test]
+fn corrupted_sign_state_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sign_state");
+    let validator = Validator::new();
+
+    let guard = validator.start(&path);
+    assert!(validator.prevote(&guard, 1, 0, Some(42)));
+    drop(guard);
+
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[10] ^= 0xff;
+    std::fs::write(&path, &bytes).unwrap();
+
+    assert!(matches!(
+        SignGuard::<TestContext, _>::open(validator.signer(), &path),
+        Err(SignGuardError::Invalid { .. })
+    ));
+}
+
+#[test]
+fn long_signatures_are_persisted_intact() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sign_state");
+
+    let last_signed = at(5, 2, Step::Precommit).unwrap();
+    let state = SignState {
+        last_signed,
+        signatures: [
+            (at(5, 2, Step::Prevote).unwrap(), vec![1; 70_000]),
+            (last_signed, vec![2; 100]),
+        ]
+        .into(),
+    };
+
+    state.save(&path).unwrap();
+    assert_eq!(SignState::load(&path).unwrap(), Some(state));
+}
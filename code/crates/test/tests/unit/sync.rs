@@ -1,6 +1,9 @@
 use arc_malachitebft_test::{Height, TestContext};
-use malachitebft_sync::{PeerId, State, Status};
+use malachitebft_sync::scoring::SyncResult;
+use malachitebft_sync::{Config, NodeMode, PeerExit, PeerId, PeerState, State, Status};
+use rand::{rngs::StdRng, SeedableRng};
 use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
 
 #[test]
 fn filter_peers_by_range_test() {
@@ -73,6 +76,30 @@ fn filter_peers_by_range_test() {
             expected_peers: vec![],
             expected_ranges: vec![],
         },
+        TestCase {
+            name: "one peer has pruned the start of the range",
+            peers: vec![(peer1, 12, 20), (peer2, 10, 20)],
+            range: Height::new(11)..=Height::new(15),
+            exclude_peers: BTreeSet::new(),
+            expected_peers: vec![peer2],
+            expected_ranges: vec![(11, 15)],
+        },
+        TestCase {
+            name: "one peer has pruned the start of a prefix range",
+            peers: vec![(peer1, 12, 20), (peer2, 10, 18)],
+            range: Height::new(11)..=Height::new(25),
+            exclude_peers: BTreeSet::new(),
+            expected_peers: vec![peer2],
+            expected_ranges: vec![(11, 18)],
+        },
+        TestCase {
+            name: "all peers have pruned the start of the range",
+            peers: vec![(peer1, 12, 20), (peer2, 14, 20)],
+            range: Height::new(11)..=Height::new(15),
+            exclude_peers: BTreeSet::new(),
+            expected_peers: vec![],
+            expected_ranges: vec![],
+        },
         TestCase {
             name: "excluding multiple peers leaves none",
             peers: vec![(peer1, 10, 20), (peer2, 10, 20)],
@@ -93,6 +120,8 @@ fn filter_peers_by_range_test() {
                     peer_id: *peer_id,
                     tip_height: Height::new(*max),
                     history_min_height: Height::new(*min),
+                    sync_height: Height::new(*max + 1),
+                    mode: NodeMode::FullNode,
                 },
             );
         }
@@ -147,3 +176,83 @@ fn filter_peers_by_range_test() {
         }
     }
 }
+
+#[test]
+fn filter_peers_by_range_prefers_synced_full_nodes() {
+    let validator = PeerId::random();
+    let full_node = PeerId::random();
+    let syncing_full_node = PeerId::random();
+
+    let status = |peer_id, sync_height, mode| Status::<TestContext> {
+        peer_id,
+        tip_height: Height::new(20),
+        history_min_height: Height::new(1),
+        sync_height: Height::new(sync_height),
+        mode,
+    };
+
+    let mut peers = BTreeMap::from([
+        (validator, status(validator, 21, NodeMode::Validator)),
+        (full_node, status(full_node, 21, NodeMode::FullNode)),
+        (
+            syncing_full_node,
+            status(syncing_full_node, 30, NodeMode::FullNode),
+        ),
+    ]);
+
+    let range = Height::new(5)..=Height::new(10);
+
+    // Full nodes which are not syncing themselves are preferred
+    let filtered = State::<TestContext>::filter_peers_by_range(&peers, &range, &BTreeSet::new());
+    assert_eq!(filtered.keys().collect::<Vec<_>>(), vec![&full_node]);
+
+    // Then validators which are not syncing
+    let filtered =
+        State::<TestContext>::filter_peers_by_range(&peers, &range, &BTreeSet::from([full_node]));
+    assert_eq!(filtered.keys().collect::<Vec<_>>(), vec![&validator]);
+
+    // Syncing peers are only selected when there is no other candidate
+    peers.remove(&validator);
+    let filtered =
+        State::<TestContext>::filter_peers_by_range(&peers, &range, &BTreeSet::from([full_node]));
+    assert_eq!(
+        filtered.keys().collect::<Vec<_>>(),
+        vec![&syncing_full_node]
+    );
+}
+
+/// Peers come and go, and the state kept for them must not grow accordingly.
+#[test]
+fn churned_peers_are_forgotten() {
+    let mut state =
+        State::<TestContext>::new(Box::new(StdRng::seed_from_u64(0)), Config::default());
+
+    for i in 0..10_000u64 {
+        let peer_id = PeerId::random();
+
+        state.peers.insert(
+            peer_id,
+            Status {
+                peer_id,
+                tip_height: Height::new(i + 10),
+                history_min_height: Height::new(i),
+                sync_height: Height::new(i + 11),
+                mode: NodeMode::FullNode,
+            },
+        );
+
+        state
+            .peer_scorer
+            .update_score(peer_id, SyncResult::Success(Duration::from_millis(100)));
+
+        let exit = if i % 10 == 0 {
+            PeerExit::Banned
+        } else {
+            PeerExit::Disconnected
+        };
+
+        state.forget_peer(&peer_id, exit);
+    }
+
+    assert_eq!(state.peer_entries(), 0);
+}
@@ -0,0 +1,216 @@
+use futures::executor::block_on;
+
+use arc_malachitebft_test::utils::validators::make_validators;
+use arc_malachitebft_test::{
+    default_chain_id, Address, Ed25519Signer, Height, PrivateKey, TestContext, ValidatorSet,
+};
+use malachitebft_core_types::{
+    CertificateError, ChainId, Context, ThresholdParams, ValidatorChange, ValidatorSetUpdate,
+    ValidatorSetUpdateCertificate, ValidatorSetUpdateSignature,
+};
+use malachitebft_signing::{Signer, VerifierExt};
+
+fn make_certificate(
+    update: ValidatorSetUpdate<TestContext>,
+    signers: &[&PrivateKey],
+) -> ValidatorSetUpdateCertificate<TestContext> {
+    let signatures = signers
+        .iter()
+        .map(|sk| {
+            let signer = Ed25519Signer::new((*sk).clone());
+            let signature = block_on(signer.sign_validator_set_update(&update)).unwrap();
+            let address = Address::from_public_key(&sk.public_key());
+            ValidatorSetUpdateSignature::new(address, signature)
+        })
+        .collect();
+
+    ValidatorSetUpdateCertificate::new(update, signatures)
+}
+
+fn verify(
+    certificate: &ValidatorSetUpdateCertificate<TestContext>,
+    validator_set: &ValidatorSet,
+) -> Result<(), CertificateError<TestContext>> {
+    let [(_, sk)] = make_validators([1]);
+    let verifier = Ed25519Signer::new(sk);
+
+    block_on(verifier.verify_validator_set_update_certificate(
+        &TestContext::new(),
+        certificate,
+        validator_set,
+        ThresholdParams::default(),
+    ))
+}
+
+/// An update for the chain of the test context.
+fn make_update(
+    epoch: u64,
+    effective_height: u64,
+    diff: Vec<ValidatorChange>,
+) -> ValidatorSetUpdate<TestContext> {
+    ValidatorSetUpdate::new(epoch, Height::new(effective_height), diff)
+        .with_chain_id(Some(default_chain_id()))
+}
+
+#[test]
+fn signing_bytes_depend_on_every_field() {
+    let change = ValidatorChange::new(vec![1; 32], 10);
+    let update = make_update(1, 5, vec![change.clone()]);
+
+    let other_chain = update
+        .clone()
+        .with_chain_id(Some(ChainId::new("other-chain").unwrap()));
+    let no_chain = update.clone().with_chain_id(None);
+    let other_epoch = ValidatorSetUpdate {
+        epoch: 2,
+        ..update.clone()
+    };
+    let other_height = ValidatorSetUpdate {
+        effective_height: Height::new(6),
+        ..update.clone()
+    };
+    let other_diff = ValidatorSetUpdate {
+        diff: vec![ValidatorChange::new(vec![1; 32], 11)],
+        ..update.clone()
+    };
+
+    for other in [other_chain, no_chain, other_epoch, other_height, other_diff] {
+        assert_ne!(update.signing_bytes(), other.signing_bytes());
+    }
+}
+
+#[test]
+fn certificate_with_quorum_is_valid() {
+    let [(v1, sk1), (v2, sk2), (v3, sk3), (v4, _)] = make_validators([10, 10, 10, 10]);
+    let validator_set = ValidatorSet::new([v1, v2, v3, v4]);
+
+    let update = make_update(1, 5, vec![]);
+    let certificate = make_certificate(update, &[&sk1, &sk2, &sk3]);
+
+    assert_eq!(verify(&certificate, &validator_set), Ok(()));
+}
+
+#[test]
+fn certificate_without_quorum_is_invalid() {
+    let [(v1, sk1), (v2, sk2), (v3, _), (v4, _)] = make_validators([10, 10, 10, 10]);
+    let validator_set = ValidatorSet::new([v1, v2, v3, v4]);
+
+    let update = make_update(1, 5, vec![]);
+    let certificate = make_certificate(update, &[&sk1, &sk2]);
+
+    assert!(matches!(
+        verify(&certificate, &validator_set),
+        Err(CertificateError::NotEnoughVotingPower { .. })
+    ));
+}
+
+#[test]
+fn certificate_with_duplicate_or_unknown_signer_is_invalid() {
+    let [(v1, sk1), (v2, sk2), (v3, sk3), (_, sk4)] = make_validators([10, 10, 10, 10]);
+    let validator_set = ValidatorSet::new([v1, v2, v3]);
+
+    let update = make_update(1, 5, vec![]);
+
+    let duplicate = make_certificate(update.clone(), &[&sk1, &sk2, &sk2]);
+    assert!(matches!(
+        verify(&duplicate, &validator_set),
+        Err(CertificateError::DuplicateVote(_))
+    ));
+
+    let unknown = make_certificate(update, &[&sk1, &sk2, &sk3, &sk4]);
+    assert!(matches!(
+        verify(&unknown, &validator_set),
+        Err(CertificateError::UnknownValidator(_))
+    ));
+}
+
+#[test]
+fn certificate_with_tampered_update_is_invalid() {
+    let [(v1, sk1), (v2, sk2), (v3, sk3)] = make_validators([10, 10, 10]);
+    let validator_set = ValidatorSet::new([v1, v2, v3]);
+
+    let update = make_update(1, 5, vec![]);
+    let mut certificate = make_certificate(update, &[&sk1, &sk2, &sk3]);
+    certificate.update.effective_height = Height::new(6);
+
+    assert!(matches!(
+        verify(&certificate, &validator_set),
+        Err(CertificateError::InvalidValidatorSetUpdateSignature(_))
+    ));
+}
+
+#[test]
+fn certificate_for_another_chain_is_invalid() {
+    let [(v1, sk1), (v2, sk2), (v3, sk3)] = make_validators([10, 10, 10]);
+    let validator_set = ValidatorSet::new([v1, v2, v3]);
+
+    // Signed by the same validators, on a chain where they reuse their keys
+    let other_chain = ChainId::new("other-chain").unwrap();
+    let update = make_update(1, 5, vec![]).with_chain_id(Some(other_chain.clone()));
+    let certificate = make_certificate(update, &[&sk1, &sk2, &sk3]);
+
+    assert_eq!(
+        verify(&certificate, &validator_set),
+        Err(CertificateError::ChainIdMismatch {
+            expected: Some(default_chain_id()),
+            actual: Some(other_chain),
+        })
+    );
+
+    // Nor is an update without a chain id accepted on a chain which has one
+    let update = make_update(1, 5, vec![]).with_chain_id(None);
+    let certificate = make_certificate(update, &[&sk1, &sk2, &sk3]);
+
+    assert!(matches!(
+        verify(&certificate, &validator_set),
+        Err(CertificateError::ChainIdMismatch { actual: None, .. })
+    ));
+}
+
+#[test]
+fn apply_update_adds_updates_and_removes_validators() {
+    let [(v1, _), (v2, _), (v3, _), (v4, _)] = make_validators([10, 10, 10, 10]);
+    let validator_set = ValidatorSet::new([v1.clone(), v2.clone(), v3.clone()]);
+
+    let diff = vec![
+        ValidatorChange::new(v1.public_key.as_bytes().to_vec(), 0),
+        ValidatorChange::new(v2.public_key.as_bytes().to_vec(), 25),
+        ValidatorChange::new(v4.public_key.as_bytes().to_vec(), 5),
+    ];
+
+    let update = make_update(1, 5, diff);
+    let updated = TestContext::new()
+        .apply_validator_set_update(&validator_set, &update)
+        .unwrap();
+
+    assert_eq!(updated.len(), 3);
+    assert!(updated.get_by_address(&v1.address).is_none());
+    assert_eq!(
+        updated.get_by_address(&v2.address).unwrap().voting_power,
+        25
+    );
+    assert_eq!(
+        updated.get_by_address(&v3.address).unwrap().voting_power,
+        10
+    );
+    assert_eq!(updated.get_by_address(&v4.address).unwrap().voting_power, 5);
+
+    // Applying the same update twice yields the same validator set
+    let again = TestContext::new()
+        .apply_validator_set_update(&updated, &update)
+        .unwrap();
+    assert_eq!(again, updated);
+}
+
+#[test]
+fn apply_update_removing_every_validator_fails() {
+    let [(v1, _)] = make_validators([10]);
+    let validator_set = ValidatorSet::new([v1.clone()]);
+
+    let diff = vec![ValidatorChange::new(v1.public_key.as_bytes().to_vec(), 0)];
+    let update = make_update(1, 5, diff);
+
+    assert!(TestContext::new()
+        .apply_validator_set_update(&validator_set, &update)
+        .is_none());
+}
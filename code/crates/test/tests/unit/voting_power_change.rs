@@ -0,0 +1,76 @@
+use arc_malachitebft_test::utils::validators::make_validators;
+use arc_malachitebft_test::{TestContext, Validator, ValidatorSet};
+use malachitebft_core_types::{ThresholdParam, VotingPowerChange};
+
+fn change(previous: &ValidatorSet, next: &ValidatorSet) -> VotingPowerChange {
+    VotingPowerChange::between::<TestContext>(previous, next)
+}
+
+fn with_power(validator: &Validator, voting_power: u64) -> Validator {
+    Validator::new(validator.public_key, voting_power)
+}
+
+#[test]
+fn same_validator_set() {
+    let [(v1, _), (v2, _), (v3, _)] = make_validators([1, 1, 1]);
+    let validator_set = ValidatorSet::new([v1, v2, v3]);
+
+    let change = change(&validator_set, &validator_set);
+
+    assert_eq!(change.unchanged, 3);
+    assert_eq!(change.changed(), 0);
+    assert!(!change.exceeds(ThresholdParam::F_PLUS_ONE));
+}
+
+#[test]
+fn one_validator_replaced_out_of_four() {
+    let [(v1, _), (v2, _), (v3, _), (v4, _), (v5, _)] = make_validators([1, 1, 1, 1, 1]);
+    let previous = ValidatorSet::new([v1.clone(), v2.clone(), v3.clone(), v4]);
+    let next = ValidatorSet::new([v1, v2, v3, v5]);
+
+    let change = change(&previous, &next);
+
+    assert_eq!(change.changed(), 1);
+    assert_eq!(change.total, 4);
+    assert!(!change.exceeds(ThresholdParam::F_PLUS_ONE));
+}
+
+#[test]
+fn half_of_the_validators_replaced() {
+    let [(v1, _), (v2, _), (v3, _), (v4, _)] = make_validators([1, 1, 1, 1]);
+    let previous = ValidatorSet::new([v1.clone(), v2]);
+    let next = ValidatorSet::new([v1, v3]);
+
+    let change = change(&previous, &next);
+
+    assert_eq!(change.changed(), 1);
+    assert_eq!(change.total, 2);
+    assert!(change.exceeds(ThresholdParam::F_PLUS_ONE));
+
+    // Adding validators without removing any also changes the voting power
+    let grown = ValidatorSet::new(previous.validators.iter().cloned().chain([v4]));
+    assert_eq!(
+        VotingPowerChange::between::<TestContext>(&previous, &grown).changed(),
+        1
+    );
+}
+
+#[test]
+fn voting_power_shifts_between_the_same_validators() {
+    let [(v1, _), (v2, _), (v3, _)] = make_validators([10, 10, 10]);
+    let previous = ValidatorSet::new([v1.clone(), v2.clone(), v3.clone()]);
+
+    // Exactly a third of the voting power moves to `v1`: not more than a third
+    let next = ValidatorSet::new([with_power(&v1, 20), with_power(&v2, 5), with_power(&v3, 5)]);
+
+    let change = change(&previous, &next);
+
+    assert_eq!(change.unchanged, 20);
+    assert_eq!(change.total, 30);
+    assert!(!change.exceeds(ThresholdParam::F_PLUS_ONE));
+
+    // Any more than that exceeds a third
+    let next = ValidatorSet::new([with_power(&v1, 21), with_power(&v2, 5), with_power(&v3, 4)]);
+    assert!(VotingPowerChange::between::<TestContext>(&previous, &next)
+        .exceeds(ThresholdParam::F_PLUS_ONE));
+}
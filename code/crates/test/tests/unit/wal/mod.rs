@@ -0,0 +1,152 @@
+//! Reading and migrating the WALs written by previous releases.
+//!
+//! The fixtures under `fixtures` are WAL files as written by previous releases,
+//! named after the format version of their entries. They must never be regenerated:
+//! a change to the encoding of the entries must come with a new format version instead.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use arc_malachitebft_test::codec::proto::ProtobufCodec;
+use arc_malachitebft_test::TestContext;
+use malachitebft_app::engine::wal::{log_entries, migrate_log, FormatVersion, WalEntry};
+use malachitebft_app::wal::{Log, MemoryLog, WalStorage};
+use malachitebft_core_consensus::SignedConsensusMsg;
+use malachitebft_core_types::{Round, Timeout, TimeoutKind};
+
+use crate::codec::vectors::signed_consensus_msgs;
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/unit/wal/fixtures")
+        .join(name)
+        .with_extension("wal")
+}
+
+/// Copy the fixture to the given directory, so that the fixture itself is never modified.
+fn open_fixture(name: &str, dir: &Path) -> Log {
+    let path = dir.join(name).with_extension("wal");
+    fs::copy(fixture_path(name), &path).unwrap();
+    Log::open(path).unwrap()
+}
+
+fn consensus_msg(name: &str) -> SignedConsensusMsg<TestContext> {
+    signed_consensus_msgs()
+        .into_iter()
+        .find_map(|(n, msg)| (n == name).then_some(msg))
+        .unwrap()
+}
+
+enum Expected {
+    ConsensusMsg(Box<SignedConsensusMsg<TestContext>>),
+    Timeout(Timeout),
+}
+
+/// The entries of the `v1` fixture.
+fn v1_entries() -> Vec<Expected> {
+    vec![
+        Expected::ConsensusMsg(Box::new(consensus_msg("proposal"))),
+        Expected::Timeout(Timeout::new(Round::new(0), TimeoutKind::Propose)),
+        Expected::ConsensusMsg(Box::new(consensus_msg("vote_prevote"))),
+        Expected::Timeout(Timeout::new(Round::new(0), TimeoutKind::Prevote)),
+        Expected::ConsensusMsg(Box::new(consensus_msg("vote_precommit_nil"))),
+    ]
+}
+
+fn read_entries(log: &mut dyn WalStorage) -> (FormatVersion, Vec<WalEntry<TestContext>>) {
+    let entries = log_entries(log, &ProtobufCodec).unwrap();
+    let version = entries.version();
+    let entries = entries.collect::<Result<Vec<_>, _>>().unwrap();
+
+    (version, entries)
+}
+
+fn assert_entries(entries: &[WalEntry<TestContext>], expected: &[Expected]) {
+    assert_eq!(entries.len(), expected.len());
+
+    for (idx, (entry, expected)) in entries.iter().zip(expected).enumerate() {
+        match (entry, expected) {
+            (WalEntry::ConsensusMsg(msg), Expected::ConsensusMsg(expected)) => {
+                assert_eq!(msg, &**expected, "entry #{idx}")
+            }
+            (WalEntry::Timeout(timeout), Expected::Timeout(expected)) => {
+                assert_eq!(timeout, expected, "entry #{idx}")
+            }
+            (entry, _) => panic!("Unexpected entry #{idx}: {entry:?}"),
+        }
+    }
+}
+
+#[test]
+fn reads_v1_fixture() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut log = open_fixture("v1", dir.path());
+
+    assert_eq!(log.sequence(), 1);
+
+    let (version, entries) = read_entries(&mut log);
+
+    assert_eq!(version, FormatVersion::V1);
+    assert_entries(&entries, &v1_entries());
+}
+
+#[test]
+fn migrates_v1_fixture() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut log = open_fixture("v1", dir.path());
+
+    assert_eq!(
+        migrate_log::<TestContext, _>(&mut log, &ProtobufCodec).unwrap(),
+        FormatVersion::V1
+    );
+
+    let path = log.path().to_owned();
+    drop(log);
+
+    // The migrated WAL is read back from disk at the current version, with the same entries
+    let mut log = Log::open(path).unwrap();
+
+    assert_eq!(log.sequence(), 1);
+    assert_eq!(log.len(), v1_entries().len() + 1);
+
+    let (version, entries) = read_entries(&mut log);
+
+    assert_eq!(version, FormatVersion::CURRENT);
+    assert_entries(&entries, &v1_entries());
+
+    // Migrating again is a no-op
+    assert_eq!(
+        migrate_log::<TestContext, _>(&mut log, &ProtobufCodec).unwrap(),
+        FormatVersion::CURRENT
+    );
+    assert_eq!(log.len(), v1_entries().len() + 1);
+}
+
+#[test]
+fn newer_version_is_rejected() {
+    let mut log = MemoryLog::new();
+
+    // Header of a format version unknown to this release
+    log.append(&[0x00, 0, 0, 0, 99]).unwrap();
+    log.append(&[0x02, 1, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+
+    assert!(log_entries::<TestContext, _>(&mut log, &ProtobufCodec).is_err());
+    assert!(migrate_log::<TestContext, _>(&mut log, &ProtobufCodec).is_err());
+    assert_eq!(log.len(), 2);
+}
+
+#[test]
+fn migration_keeps_undecodable_entries() {
+    let mut log = MemoryLog::new();
+
+    // Commit timeouts could be written by previous releases, but can no longer be decoded
+    log.append(&[0x02, 1, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+    log.append(&[0x02, 4, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+
+    assert!(migrate_log::<TestContext, _>(&mut log, &ProtobufCodec).is_err());
+
+    // The WAL is left untouched
+    let entries = log_entries::<TestContext, _>(&mut log, &ProtobufCodec).unwrap();
+    assert_eq!(entries.version(), FormatVersion::V1);
+    assert_eq!(entries.count(), 2);
+}
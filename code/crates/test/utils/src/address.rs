@@ -0,0 +1,46 @@
+use core::fmt;
+
+use malachitebft_core_types::hash::{Hasher, Sha256};
+
+use crate::PublicKey;
+
+/// The address of a validator, derived from its public key.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Address([u8; Self::LENGTH]);
+
+impl Address {
+    const LENGTH: usize = 20;
+
+    pub const fn new(value: [u8; Self::LENGTH]) -> Self {
+        Self(value)
+    }
+
+    /// The first 20 bytes of the SHA-256 digest of the public key.
+    pub fn from_public_key(public_key: &PublicKey) -> Self {
+        let digest = Sha256::digest(public_key.as_bytes());
+        let mut address = [0; Self::LENGTH];
+        address.copy_from_slice(&digest[..Self::LENGTH]);
+        Self(address)
+    }
+
+    pub fn into_inner(self) -> [u8; Self::LENGTH] {
+        self.0
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0.iter() {
+            write!(f, "{byte:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Address({self})")
+    }
+}
+
+impl malachitebft_core_types::Address for Address {}
@@ -0,0 +1,132 @@
+use core::fmt;
+use core::marker::PhantomData;
+use std::sync::Arc;
+
+use bytes::Bytes;
+
+use malachitebft_core_consensus::proposer::{ProposerSelector, RoundRobin};
+use malachitebft_core_types::{ChainId, Context, LinearTimeouts, NilOrVal, Round, Value};
+
+use crate::{Address, Ed25519, Height, Proposal, ProposalPart, Validator, ValidatorSet, Vote};
+
+/// A context for tests, generic over the type `V` of the values to decide on.
+///
+/// Validators are Ed25519 keys, addresses are derived from their public key,
+/// and proposers are selected in a round-robin fashion unless configured otherwise.
+pub struct MockContext<V> {
+    proposer_selector: Arc<dyn ProposerSelector<Self>>,
+    chain_id: Option<ChainId>,
+    _marker: PhantomData<fn() -> V>,
+}
+
+impl<V> Clone for MockContext<V> {
+    fn clone(&self) -> Self {
+        Self {
+            proposer_selector: Arc::clone(&self.proposer_selector),
+            chain_id: self.chain_id.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<V> fmt::Debug for MockContext<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockContext")
+            .field("chain_id", &self.chain_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<V: Value + 'static> Default for MockContext<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Value + 'static> MockContext<V> {
+    pub fn new() -> Self {
+        Self {
+            proposer_selector: Arc::new(RoundRobin),
+            chain_id: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Include the given chain id in the votes and proposals built by this context.
+    pub fn with_chain_id(self, chain_id: ChainId) -> Self {
+        Self {
+            chain_id: Some(chain_id),
+            ..self
+        }
+    }
+
+    /// Select the proposers with the given selector, instead of rotating through the validators.
+    pub fn with_proposer_selector(
+        self,
+        proposer_selector: Arc<dyn ProposerSelector<Self>>,
+    ) -> Self {
+        Self {
+            proposer_selector,
+            ..self
+        }
+    }
+}
+
+impl<V: Value + 'static> Context for MockContext<V> {
+    type Address = Address;
+    type ProposalPart = ProposalPart;
+    type Height = Height;
+    type Proposal = Proposal<V>;
+    type ValidatorSet = ValidatorSet;
+    type Validator = Validator;
+    type Timeouts = LinearTimeouts;
+    type Value = V;
+    type Vote = Vote<V>;
+    type Extension = Bytes;
+    type SigningScheme = Ed25519;
+
+    fn chain_id(&self) -> Option<&ChainId> {
+        self.chain_id.as_ref()
+    }
+
+    fn select_proposer<'a>(
+        &self,
+        validator_set: &'a ValidatorSet,
+        height: Height,
+        round: Round,
+    ) -> &'a Validator {
+        self.proposer_selector
+            .select_proposer(validator_set, height, round)
+    }
+
+    fn new_proposal(
+        &self,
+        height: Height,
+        round: Round,
+        value: V,
+        pol_round: Round,
+        address: Address,
+    ) -> Proposal<V> {
+        Proposal::new(height, round, value, pol_round, address).with_chain_id(self.chain_id.clone())
+    }
+
+    fn new_prevote(
+        &self,
+        height: Height,
+        round: Round,
+        value_id: NilOrVal<V::Id>,
+        address: Address,
+    ) -> Vote<V> {
+        Vote::new_prevote(height, round, value_id, address).with_chain_id(self.chain_id.clone())
+    }
+
+    fn new_precommit(
+        &self,
+        height: Height,
+        round: Round,
+        value_id: NilOrVal<V::Id>,
+        address: Address,
+    ) -> Vote<V> {
+        Vote::new_precommit(height, round, value_id, address).with_chain_id(self.chain_id.clone())
+    }
+}
@@ -0,0 +1,188 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use malachitebft_core_types::{Context, NilOrVal, Round, Value, VotingPower};
+
+use crate::{Address, Height, MockContext, PrivateKey, Validator, ValidatorSet, Vote};
+
+/// Seed of the keys of the validators of a [`Fixture`], unless overridden.
+pub const DEFAULT_SEED: u64 = 42;
+
+/// A mock context together with a validator set and the private keys of its validators.
+///
+/// The keys are derived deterministically from a seed, so the validators,
+/// and therefore their addresses, are the same across runs.
+/// The validators are kept in the order of the voting powers they were created with,
+/// so that the validator at index `i` has the `i`-th voting power.
+#[derive(Clone, Debug)]
+pub struct Fixture<V> {
+    pub ctx: MockContext<V>,
+    pub validator_set: ValidatorSet,
+    pub private_keys: Vec<PrivateKey>,
+}
+
+impl<V: Value + 'static> Fixture<V> {
+    /// Create a validator set with the given voting powers, with keys derived from [`DEFAULT_SEED`].
+    ///
+    /// # Panics
+    /// If no voting power is given.
+    pub fn new(voting_powers: impl IntoIterator<Item = VotingPower>) -> Self {
+        Self::seeded(voting_powers, DEFAULT_SEED)
+    }
+
+    /// Create a validator set of `count` validators with a voting power of 1 each.
+    ///
+    /// # Panics
+    /// If `count` is zero.
+    pub fn with_equal_power(count: usize) -> Self {
+        Self::new(core::iter::repeat_n(1, count))
+    }
+
+    /// Create a validator set with the given voting powers, with keys derived from the given seed.
+    ///
+    /// # Panics
+    /// If no voting power is given.
+    pub fn seeded(voting_powers: impl IntoIterator<Item = VotingPower>, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let (validators, private_keys): (Vec<_>, Vec<_>) = voting_powers
+            .into_iter()
+            .map(|voting_power| {
+                let private_key = PrivateKey::generate(&mut rng);
+                let validator = Validator::new(private_key.public_key(), voting_power);
+                (validator, private_key)
+            })
+            .unzip();
+
+        Self {
+            ctx: MockContext::new(),
+            validator_set: ValidatorSet::new(validators),
+            private_keys,
+        }
+    }
+
+    /// Use the given context, eg. one with a chain id or a custom proposer selector.
+    pub fn with_context(self, ctx: MockContext<V>) -> Self {
+        Self { ctx, ..self }
+    }
+
+    /// The validator at the given index.
+    ///
+    /// # Panics
+    /// If there is no validator at that index.
+    pub fn validator(&self, index: usize) -> &Validator {
+        self.validator_set
+            .get_by_index(index)
+            .expect("validator index out of bounds")
+    }
+
+    /// The address of the validator at the given index.
+    ///
+    /// # Panics
+    /// If there is no validator at that index.
+    pub fn address(&self, index: usize) -> Address {
+        self.validator(index).address
+    }
+
+    /// The private key of the validator at the given index.
+    ///
+    /// # Panics
+    /// If there is no validator at that index.
+    pub fn private_key(&self, index: usize) -> &PrivateKey {
+        &self.private_keys[index]
+    }
+
+    /// A prevote by the validator at the given index.
+    pub fn prevote(
+        &self,
+        index: usize,
+        height: Height,
+        round: Round,
+        value_id: NilOrVal<V::Id>,
+    ) -> Vote<V> {
+        self.ctx
+            .new_prevote(height, round, value_id, self.address(index))
+    }
+
+    /// A precommit by the validator at the given index.
+    pub fn precommit(
+        &self,
+        index: usize,
+        height: Height,
+        round: Round,
+        value_id: NilOrVal<V::Id>,
+    ) -> Vote<V> {
+        self.ctx
+            .new_precommit(height, round, value_id, self.address(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use malachitebft_core_types::{SignedVote, ThresholdParams};
+    use malachitebft_core_votekeeper::keeper::{Output, VoteKeeper};
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Block(u64);
+
+    impl Value for Block {
+        type Id = u64;
+
+        fn id(&self) -> u64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn keys_are_deterministic() {
+        let a = Fixture::<Block>::new([10, 20, 30]);
+        let b = Fixture::<Block>::new([10, 20, 30]);
+        let c = Fixture::<Block>::seeded([10, 20, 30], DEFAULT_SEED + 1);
+
+        assert_eq!(a.validator_set, b.validator_set);
+        assert_ne!(a.validator_set, c.validator_set);
+    }
+
+    #[test]
+    fn validators_keep_the_order_of_voting_powers() {
+        let fixture = Fixture::<Block>::new([30, 10, 20]);
+
+        let voting_powers: Vec<_> = fixture
+            .validator_set
+            .iter()
+            .map(|v| v.voting_power)
+            .collect();
+        assert_eq!(voting_powers, vec![30, 10, 20]);
+        assert_eq!(fixture.validator_set.total_voting_power(), 60);
+
+        for (index, private_key) in fixture.private_keys.iter().enumerate() {
+            assert_eq!(
+                fixture.validator(index).public_key,
+                private_key.public_key()
+            );
+        }
+    }
+
+    #[test]
+    fn vote_keeper_reaches_quorum() {
+        let fixture = Fixture::<Block>::with_equal_power(4);
+        let (height, round) = (Height::new(1), Round::new(0));
+
+        let mut keeper = VoteKeeper::<MockContext<Block>>::new(
+            fixture.validator_set.clone(),
+            ThresholdParams::default(),
+        );
+
+        let mut outputs = Vec::new();
+
+        for index in 0..3 {
+            let vote = fixture.prevote(index, height, round, NilOrVal::Val(7));
+            let signature = fixture.private_key(index).sign(&[]);
+            outputs.push(keeper.apply_vote(SignedVote::new(vote, signature), round));
+        }
+
+        assert_eq!(outputs, vec![None, None, Some(Output::PolkaValue(7))]);
+    }
+}
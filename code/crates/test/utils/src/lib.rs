@@ -0,0 +1,33 @@
+//! Mock context and fixtures for the unit tests of applications built on Malachite.
+//!
+//! Instead of implementing their own minimal [`Context`](malachitebft_core_types::Context),
+//! applications can use [`MockContext`], which is generic over the type of values to decide on,
+//! together with a [`Fixture`] providing a validator set with deterministic keys and addresses.
+//!
+//! The [`mock_context!`] macro declares aliases for all the types of a mock context
+//! for a given value type, along with a function building a fixture for it.
+
+#![forbid(unsafe_code)]
+#![deny(trivial_casts, trivial_numeric_casts)]
+
+mod address;
+mod context;
+mod fixture;
+mod height;
+mod macros;
+mod proposal;
+mod validator_set;
+mod vote;
+
+pub use crate::address::*;
+pub use crate::context::*;
+pub use crate::fixture::*;
+pub use crate::height::*;
+pub use crate::proposal::*;
+pub use crate::validator_set::*;
+pub use crate::vote::*;
+
+pub use malachitebft_signing_ed25519::{Ed25519, PrivateKey, PublicKey, Signature};
+
+#[doc(hidden)]
+pub use malachitebft_core_types as core_types;
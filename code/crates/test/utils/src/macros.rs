@@ -0,0 +1,112 @@
+/// Declare a module with aliases for the types of a [`MockContext`](crate::MockContext)
+/// deciding on values of the given type, along with a `fixture` function building
+/// a [`Fixture`](crate::Fixture) with the given voting powers.
+///
+/// The value type is resolved from the scope in which the macro is invoked.
+///
+/// ## Example
+///
+/// ```rust
+/// use malachitebft_core_types::{Context, NilOrVal, Round, Value};
+/// use arc_malachitebft_test_utils::{mock_context, Height};
+///
+/// #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// pub struct Block(u64);
+///
+/// impl Value for Block {
+///     type Id = u64;
+///
+///     fn id(&self) -> u64 {
+///         self.0
+///     }
+/// }
+///
+/// mock_context! {
+///     /// Types of the context deciding on blocks
+///     pub mod mock { value = Block }
+/// }
+///
+/// fn main() {
+///     let fixture = mock::fixture([10, 20, 30]);
+///     let (height, round) = (Height::new(1), Round::new(0));
+///
+///     let vote: mock::Vote = fixture.prevote(0, height, round, NilOrVal::Val(1));
+///     assert_eq!(vote.validator_address, fixture.address(0));
+///
+///     let proposer = fixture.ctx.select_proposer(&fixture.validator_set, height, round);
+///     assert_eq!(proposer.address, fixture.address(0));
+/// }
+/// ```
+#[macro_export]
+macro_rules! mock_context {
+    ($(#[$meta:meta])* $vis:vis mod $name:ident { value = $value:ty $(,)? }) => {
+        $(#[$meta])*
+        #[allow(dead_code, unused_imports)]
+        $vis mod $name {
+            use super::*;
+
+            pub use $crate::{Address, Height, ProposalPart, Validator, ValidatorSet};
+
+            pub type Context = $crate::MockContext<$value>;
+            pub type Fixture = $crate::Fixture<$value>;
+            pub type Proposal = $crate::Proposal<$value>;
+            pub type Vote = $crate::Vote<$value>;
+            pub type SignedProposal = $crate::core_types::SignedProposal<Context>;
+            pub type SignedVote = $crate::core_types::SignedVote<Context>;
+
+            /// Build a fixture with the given voting powers, see [`Fixture::new`]($crate::Fixture::new).
+            pub fn fixture(
+                voting_powers: impl ::core::iter::IntoIterator<Item = $crate::core_types::VotingPower>,
+            ) -> Fixture {
+                $crate::Fixture::new(voting_powers)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use malachitebft_core_types::{
+        Context, NilOrVal, Proposal as _, Round, SignedMessage, Value, Vote as _,
+    };
+
+    #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Tx(Vec<u8>);
+
+    impl Value for Tx {
+        type Id = u64;
+
+        fn id(&self) -> u64 {
+            self.0.len() as u64
+        }
+    }
+
+    mock_context! {
+        mod mock { value = Tx }
+    }
+
+    #[test]
+    fn declares_context_for_value_type() {
+        let fixture = mock::fixture([1, 1, 1]);
+        let (height, round) = (mock::Height::new(3), Round::new(1));
+
+        let proposal: mock::Proposal = fixture.ctx.new_proposal(
+            height,
+            round,
+            Tx(vec![1, 2]),
+            Round::Nil,
+            fixture.address(0),
+        );
+        assert_eq!(proposal.value().id(), 2);
+
+        let vote: mock::Vote = fixture.precommit(1, height, round, NilOrVal::Val(2));
+        let signature = fixture.private_key(1).sign(&[]);
+        let signed: mock::SignedVote = SignedMessage::new(vote, signature);
+        assert_eq!(signed.validator_address(), &fixture.address(1));
+
+        let proposer = fixture
+            .ctx
+            .select_proposer(&fixture.validator_set, height, round);
+        assert_eq!(proposer, fixture.validator(0));
+    }
+}
@@ -0,0 +1,97 @@
+use bytes::Bytes;
+
+use malachitebft_core_types::{ChainId, Round, Value};
+
+use crate::{Address, Height, MockContext};
+
+/// A proposal for a value in a round
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proposal<V> {
+    pub height: Height,
+    pub round: Round,
+    pub value: V,
+    pub pol_round: Round,
+    pub validator_address: Address,
+    pub chain_id: Option<ChainId>,
+}
+
+impl<V> Proposal<V> {
+    pub fn new(
+        height: Height,
+        round: Round,
+        value: V,
+        pol_round: Round,
+        validator_address: Address,
+    ) -> Self {
+        Self {
+            height,
+            round,
+            value,
+            pol_round,
+            validator_address,
+            chain_id: None,
+        }
+    }
+
+    pub fn with_chain_id(self, chain_id: Option<ChainId>) -> Self {
+        Self { chain_id, ..self }
+    }
+}
+
+impl<V: Value + 'static> malachitebft_core_types::Proposal<MockContext<V>> for Proposal<V> {
+    fn height(&self) -> Height {
+        self.height
+    }
+
+    fn round(&self) -> Round {
+        self.round
+    }
+
+    fn value(&self) -> &V {
+        &self.value
+    }
+
+    fn take_value(self) -> V {
+        self.value
+    }
+
+    fn pol_round(&self) -> Round {
+        self.pol_round
+    }
+
+    fn validator_address(&self) -> &Address {
+        &self.validator_address
+    }
+
+    fn chain_id(&self) -> Option<&ChainId> {
+        self.chain_id.as_ref()
+    }
+}
+
+/// An opaque chunk of a streamed proposal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProposalPart {
+    pub sequence: u64,
+    pub is_last: bool,
+    pub data: Bytes,
+}
+
+impl ProposalPart {
+    pub fn new(sequence: u64, is_last: bool, data: Bytes) -> Self {
+        Self {
+            sequence,
+            is_last,
+            data,
+        }
+    }
+}
+
+impl<V: Value + 'static> malachitebft_core_types::ProposalPart<MockContext<V>> for ProposalPart {
+    fn is_first(&self) -> bool {
+        self.sequence == 0
+    }
+
+    fn is_last(&self) -> bool {
+        self.is_last
+    }
+}
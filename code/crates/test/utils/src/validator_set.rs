@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use malachitebft_core_types::{Value, VotingPower};
+
+use crate::{Address, MockContext, PublicKey};
+
+/// A validator is a public key and voting power
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Validator {
+    pub address: Address,
+    pub public_key: PublicKey,
+    pub voting_power: VotingPower,
+}
+
+impl Validator {
+    pub fn new(public_key: PublicKey, voting_power: VotingPower) -> Self {
+        Self {
+            address: Address::from_public_key(&public_key),
+            public_key,
+            voting_power,
+        }
+    }
+}
+
+impl<V> malachitebft_core_types::Validator<MockContext<V>> for Validator
+where
+    V: Value + 'static,
+{
+    fn address(&self) -> &Address {
+        &self.address
+    }
+
+    fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    fn voting_power(&self) -> VotingPower {
+        self.voting_power
+    }
+}
+
+/// A validator set, in which the validators are kept in the order they were given.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorSet {
+    pub validators: Arc<Vec<Validator>>,
+}
+
+impl ValidatorSet {
+    /// Create a new validator set from the given validators.
+    ///
+    /// # Panics
+    /// If the validator set is empty, or if its total voting power overflows.
+    pub fn new(validators: impl IntoIterator<Item = Validator>) -> Self {
+        let validators: Vec<_> = validators.into_iter().collect();
+
+        assert!(!validators.is_empty());
+
+        validators
+            .iter()
+            .try_fold(0u64, |acc, v| acc.checked_add(v.voting_power))
+            .expect("total voting power overflow");
+
+        Self {
+            validators: Arc::new(validators),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.validators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Validator> {
+        self.validators.iter()
+    }
+
+    pub fn total_voting_power(&self) -> VotingPower {
+        self.validators.iter().map(|v| v.voting_power).sum()
+    }
+
+    pub fn get_by_index(&self, index: usize) -> Option<&Validator> {
+        self.validators.get(index)
+    }
+
+    pub fn get_by_address(&self, address: &Address) -> Option<&Validator> {
+        self.validators.iter().find(|v| &v.address == address)
+    }
+}
+
+impl<V> malachitebft_core_types::ValidatorSet<MockContext<V>> for ValidatorSet
+where
+    V: Value + 'static,
+{
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn total_voting_power(&self) -> VotingPower {
+        self.total_voting_power()
+    }
+
+    fn get_by_address(&self, address: &Address) -> Option<&Validator> {
+        self.get_by_address(address)
+    }
+
+    fn get_by_index(&self, index: usize) -> Option<&Validator> {
+        self.get_by_index(index)
+    }
+}
@@ -0,0 +1,104 @@
+use derive_where::derive_where;
+
+use malachitebft_core_types::{ChainId, NilOrVal, Round, SignedExtension, Value, VoteType};
+
+use crate::{Address, Height, MockContext};
+
+/// A vote for the id of a value in a round
+#[derive_where(Clone, Debug, PartialEq, Eq)]
+pub struct Vote<V: Value + 'static> {
+    pub typ: VoteType,
+    pub height: Height,
+    pub round: Round,
+    pub value: NilOrVal<V::Id>,
+    pub validator_address: Address,
+    pub extension: Option<SignedExtension<MockContext<V>>>,
+    pub chain_id: Option<ChainId>,
+}
+
+impl<V: Value + 'static> Vote<V> {
+    pub fn new_prevote(
+        height: Height,
+        round: Round,
+        value: NilOrVal<V::Id>,
+        validator_address: Address,
+    ) -> Self {
+        Self::new(VoteType::Prevote, height, round, value, validator_address)
+    }
+
+    pub fn new_precommit(
+        height: Height,
+        round: Round,
+        value: NilOrVal<V::Id>,
+        validator_address: Address,
+    ) -> Self {
+        Self::new(VoteType::Precommit, height, round, value, validator_address)
+    }
+
+    fn new(
+        typ: VoteType,
+        height: Height,
+        round: Round,
+        value: NilOrVal<V::Id>,
+        validator_address: Address,
+    ) -> Self {
+        Self {
+            typ,
+            height,
+            round,
+            value,
+            validator_address,
+            extension: None,
+            chain_id: None,
+        }
+    }
+
+    pub fn with_chain_id(self, chain_id: Option<ChainId>) -> Self {
+        Self { chain_id, ..self }
+    }
+}
+
+impl<V: Value + 'static> malachitebft_core_types::Vote<MockContext<V>> for Vote<V> {
+    fn height(&self) -> Height {
+        self.height
+    }
+
+    fn round(&self) -> Round {
+        self.round
+    }
+
+    fn value(&self) -> &NilOrVal<V::Id> {
+        &self.value
+    }
+
+    fn take_value(self) -> NilOrVal<V::Id> {
+        self.value
+    }
+
+    fn vote_type(&self) -> VoteType {
+        self.typ
+    }
+
+    fn validator_address(&self) -> &Address {
+        &self.validator_address
+    }
+
+    fn extension(&self) -> Option<&SignedExtension<MockContext<V>>> {
+        self.extension.as_ref()
+    }
+
+    fn take_extension(&mut self) -> Option<SignedExtension<MockContext<V>>> {
+        self.extension.take()
+    }
+
+    fn extend(self, extension: SignedExtension<MockContext<V>>) -> Self {
+        Self {
+            extension: Some(extension),
+            ..self
+        }
+    }
+
+    fn chain_id(&self) -> Option<&ChainId> {
+        self.chain_id.as_ref()
+    }
+}
@@ -0,0 +1,133 @@
+//! Encryption of the entries of the Write-Ahead Log (WAL).
+//!
+//! Entries are encrypted with XChaCha20-Poly1305, using a random nonce per entry,
+//! and the sequence number of the log as associated data.
+//!
+//! The encrypted data of an entry has the following format:
+//!
+//! ```text
+//! +-----------------+------------------------------+
+//! |      Nonce      |   Ciphertext and auth tag    |
+//! |   (24 bytes)    |   (variable, +16 bytes tag)  |
+//! +-----------------+------------------------------+
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+/// Size of an encryption key, in bytes
+pub const KEY_SIZE: usize = 32;
+
+/// Size of the nonce prepended to each encrypted entry, in bytes
+const NONCE_SIZE: usize = 24;
+
+/// Key used to encrypt and decrypt the entries of the WAL.
+#[derive(Clone)]
+pub struct EncryptionKey([u8; KEY_SIZE]);
+
+impl EncryptionKey {
+    /// Creates a key from its raw bytes.
+    pub fn from_bytes(bytes: [u8; KEY_SIZE]) -> Self {
+        Self(bytes)
+    }
+
+    /// Parses a key from its hex encoding, ignoring surrounding whitespace.
+    pub fn from_hex(hex: &str) -> io::Result<Self> {
+        let hex = hex.trim();
+
+        if hex.len() != KEY_SIZE * 2 {
+            return Err(invalid_key(format!(
+                "expected {} hex characters, got {}",
+                KEY_SIZE * 2,
+                hex.len()
+            )));
+        }
+
+        let mut bytes = [0; KEY_SIZE];
+
+        for (byte, chunk) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *byte = std::str::from_utf8(chunk)
+                .ok()
+                .and_then(|chunk| u8::from_str_radix(chunk, 16).ok())
+                .ok_or_else(|| invalid_key("invalid hex character".to_string()))?;
+        }
+
+        Ok(Self(bytes))
+    }
+
+    /// Reads a key from a file containing its hex encoding.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_hex(&fs::read_to_string(path)?)
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(&self.0))
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(<redacted>)")
+    }
+}
+
+/// Encrypts the data of an entry of the log with the given sequence number.
+pub(crate) fn encrypt(key: &EncryptionKey, sequence: u64, data: &[u8]) -> io::Result<Vec<u8>> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let ciphertext = key
+        .cipher()
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: data,
+                aad: &sequence.to_be_bytes(),
+            },
+        )
+        .map_err(|_| io::Error::other("Failed to encrypt entry"))?;
+
+    let mut encrypted = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+    encrypted.extend_from_slice(&nonce);
+    encrypted.extend_from_slice(&ciphertext);
+
+    Ok(encrypted)
+}
+
+/// Decrypts the data of an entry of the log with the given sequence number.
+pub(crate) fn decrypt(key: &EncryptionKey, sequence: u64, data: &[u8]) -> io::Result<Vec<u8>> {
+    if data.len() < NONCE_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Encrypted entry is too short",
+        ));
+    }
+
+    let (nonce, ciphertext) = data.split_at(NONCE_SIZE);
+
+    key.cipher()
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &sequence.to_be_bytes(),
+            },
+        )
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Failed to decrypt entry, the encryption key may be wrong",
+            )
+        })
+}
+
+fn invalid_key(reason: String) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid WAL encryption key: {reason}"),
+    )
+}
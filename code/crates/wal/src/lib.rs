@@ -3,14 +3,28 @@
 //! Write-Ahead Log (WAL) implementation
 
 mod file;
+mod memory;
+mod segmented;
 mod storage;
 mod version;
+mod wal_storage;
+
+#[cfg(feature = "encryption")]
+#[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+pub mod encryption;
 
 pub mod log;
 
 pub use file::{Log, LogEntry, LogIter};
+pub use memory::MemoryLog;
+pub use segmented::SegmentedLog;
 pub use storage::Storage;
 pub use version::Version;
+pub use wal_storage::{WalEntries, WalStorage};
+
+#[cfg(feature = "encryption")]
+#[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+pub use encryption::EncryptionKey;
 
 // For use in tests
 #[doc(hidden)]
@@ -3,6 +3,7 @@
 //! # Warning
 //! Not for regular use, use [`crate::Log`] instead.
 
+use std::borrow::Cow;
 use std::io::{self, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 
@@ -11,6 +12,9 @@ use cfg_if::cfg_if;
 use crate::ext::{read_u32, read_u64, read_u8, write_u32, write_u64, write_u8};
 use crate::{Storage, Version};
 
+#[cfg(feature = "encryption")]
+use crate::encryption::{self, EncryptionKey};
+
 /// The maximum size of a single log entry in bytes. (1 GiB)
 const MAX_ENTRY_SIZE: usize = 1024 * 1024 * 1024;
 
@@ -20,10 +24,13 @@ const MAX_ENTRY_SIZE: usize = 1024 * 1024 * 1024;
 ///
 /// ```text
 /// +-----------------|-----------------+----------------+-----------------+
-/// |  Is compressed  |     Length      |      CRC       |      Data       |
+/// |      Flags      |     Length      |      CRC       |      Data       |
 /// |     (1 byte)    |  (8 bytes, BE)  |   (4 bytes)    | ($length bytes) |
 /// +-----------------|-----------------+----------------+-----------------+
 /// ```
+///
+/// The flags tell whether the data is compressed and, from version 2 of the format onwards,
+/// whether it is encrypted. The CRC is computed over the uncompressed, unencrypted data.
 pub struct LogEntry<'a, S> {
     /// Reference to the parent WAL
     log: &'a mut Log<S>,
@@ -33,9 +40,17 @@ impl<S> LogEntry<'_, S>
 where
     S: Storage,
 {
-    /// Reads the compression flag of the current entry
-    fn read_compression_flag(&mut self) -> io::Result<bool> {
-        read_u8(&mut self.log.storage).map(|byte| byte != 0)
+    /// Reads the flags of the current entry, returning whether it is compressed and encrypted
+    fn read_flags(&mut self) -> io::Result<(bool, bool)> {
+        let flags = read_u8(&mut self.log.storage)?;
+
+        match self.log.version {
+            Version::V1 => Ok((flags != 0, false)),
+            Version::V2 => Ok((
+                flags & ENTRY_FLAG_COMPRESSED != 0,
+                flags & ENTRY_FLAG_ENCRYPTED != 0,
+            )),
+        }
     }
 
     /// Reads the length field of the current entry
@@ -59,7 +74,7 @@ where
     /// * `Ok(None)` - If this was the last entry
     /// * `Err` - If an I/O error occurs or the CRC check fails
     pub fn read_to_next<W: Write>(mut self, writer: &mut W) -> io::Result<Option<Self>> {
-        let is_compressed = self.read_compression_flag()?;
+        let (is_compressed, is_encrypted) = self.read_flags()?;
         let length = self.read_length()? as usize;
         let expected_crc = self.read_crc()?;
 
@@ -73,6 +88,10 @@ where
         let mut data = vec![0; length];
         self.log.storage.read_exact(&mut data)?;
 
+        if is_encrypted {
+            data = self.log.decrypt(&data)?;
+        }
+
         #[cfg(not(feature = "compression"))]
         if is_compressed {
             return Err(io::Error::new(
@@ -130,6 +149,9 @@ pub struct Log<S> {
     version: Version,
     sequence: u64,
     len: usize,
+
+    #[cfg(feature = "encryption")]
+    encryption_key: Option<EncryptionKey>,
 }
 
 pub mod constants {
@@ -148,6 +170,9 @@ pub mod constants {
     pub const ENTRY_COMPRESSION_FLAG_SIZE: u64 = size_of::<u8>() as u64;
     pub const ENTRY_HEADER_SIZE: u64 =
         ENTRY_COMPRESSION_FLAG_SIZE + ENTRY_LENGTH_SIZE + ENTRY_CRC_SIZE;
+
+    pub const ENTRY_FLAG_COMPRESSED: u8 = 0b01;
+    pub const ENTRY_FLAG_ENCRYPTED: u8 = 0b10;
 }
 
 use constants::*;
@@ -172,15 +197,6 @@ impl WriteEntry<'_> {
         }
     }
 
-    fn len(&self) -> usize {
-        match self {
-            WriteEntry::Raw(data) => data.len(),
-
-            #[cfg(feature = "compression")]
-            WriteEntry::Compressed { compressed, .. } => compressed.len(),
-        }
-    }
-
     fn uncompressed_crc(&self) -> u32 {
         match self {
             WriteEntry::Raw(data) => compute_crc(data),
@@ -308,6 +324,8 @@ where
                 path,
                 sequence,
                 len,
+                #[cfg(feature = "encryption")]
+                encryption_key: None,
             });
         }
 
@@ -332,6 +350,8 @@ where
             path,
             sequence: 0,
             len: 0,
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
         })
     }
 
@@ -405,20 +425,30 @@ where
     }
 
     fn write_entry(&mut self, entry: WriteEntry<'_>) -> io::Result<()> {
+        let (data, is_encrypted) = self.encrypt(entry.data())?;
+
+        let mut flags = 0;
+        if entry.is_compressed() {
+            flags |= ENTRY_FLAG_COMPRESSED;
+        }
+        if is_encrypted {
+            flags |= ENTRY_FLAG_ENCRYPTED;
+        }
+
         let pos = self.storage.seek(SeekFrom::End(0))?;
 
         let result = || -> io::Result<()> {
-            // Write compression flag
-            write_u8(&mut self.storage, entry.is_compressed() as u8)?;
+            // Write flags
+            write_u8(&mut self.storage, flags)?;
 
-            // Write length of (compressed) data
-            write_u64(&mut self.storage, entry.len() as u64)?;
+            // Write length of (compressed, encrypted) data
+            write_u64(&mut self.storage, data.len() as u64)?;
 
-            // Write CRC of (uncompressed) data
+            // Write CRC of (uncompressed, unencrypted) data
             write_u32(&mut self.storage, entry.uncompressed_crc())?;
 
-            // Write (compressed) entry data
-            self.storage.write_all(entry.data())?;
+            // Write (compressed, encrypted) entry data
+            self.storage.write_all(&data)?;
 
             Ok(())
         }();
@@ -435,6 +465,70 @@ where
         }
     }
 
+    /// Encrypts the entries written from now on with the given key,
+    /// and decrypts the encrypted entries read with it.
+    ///
+    /// Upgrades the log to version 2 of the format, which is required to tell encrypted
+    /// entries apart. Entries written without encryption remain readable.
+    ///
+    /// # Arguments
+    /// * `key` - The key to encrypt and decrypt entries with
+    ///
+    /// # Returns
+    /// * `Ok(())` - Encryption was successfully enabled
+    /// * `Err` - If upgrading the version of the log fails
+    #[cfg(feature = "encryption")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+    pub fn enable_encryption(&mut self, key: EncryptionKey) -> io::Result<()> {
+        if self.version < Version::V2 {
+            self.storage.seek(SeekFrom::Start(VERSION_OFFSET))?;
+            write_u32(&mut self.storage, Version::V2 as u32)?;
+            self.storage.sync_all()?;
+
+            self.version = Version::V2;
+        }
+
+        self.encryption_key = Some(key);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "encryption")]
+    fn encrypt<'a>(&self, data: &'a [u8]) -> io::Result<(Cow<'a, [u8]>, bool)> {
+        match &self.encryption_key {
+            Some(key) => {
+                let encrypted = encryption::encrypt(key, self.sequence, data)?;
+                Ok((Cow::Owned(encrypted), true))
+            }
+            None => Ok((Cow::Borrowed(data), false)),
+        }
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn encrypt<'a>(&self, data: &'a [u8]) -> io::Result<(Cow<'a, [u8]>, bool)> {
+        Ok((Cow::Borrowed(data), false))
+    }
+
+    #[cfg(feature = "encryption")]
+    fn decrypt(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let key = self.encryption_key.as_ref().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Entry is encrypted but no encryption key was provided",
+            )
+        })?;
+
+        encryption::decrypt(key, self.sequence, data)
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn decrypt(&self, _data: &[u8]) -> io::Result<Vec<u8>> {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Entry is encrypted but encryption is disabled",
+        ))
+    }
+
     /// Returns an the first entry in the WAL if it exists.
     ///
     /// # Returns
@@ -596,6 +690,8 @@ where
             version,
             sequence,
             len,
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
         }
     }
 
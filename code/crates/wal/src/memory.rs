@@ -0,0 +1,58 @@
+use std::io;
+
+use crate::wal_storage::{WalEntries, WalStorage};
+
+/// Write-Ahead Log (WAL) keeping its entries in memory.
+///
+/// The entries are lost when the log is dropped, so this must only be used in tests.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryLog {
+    sequence: u64,
+    entries: Vec<Vec<u8>>,
+}
+
+impl MemoryLog {
+    /// Creates an empty log, at sequence 0.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl WalStorage for MemoryLog {
+    fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn size_bytes(&self) -> io::Result<u64> {
+        Ok(self.entries.iter().map(|entry| entry.len() as u64).sum())
+    }
+
+    fn append(&mut self, data: &[u8]) -> io::Result<()> {
+        self.entries.push(data.to_vec());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn entries(&mut self) -> io::Result<WalEntries<'_>> {
+        Ok(Box::new(self.entries.iter().cloned().map(Ok)))
+    }
+
+    fn truncate(&mut self, from_entry: u64) -> io::Result<()> {
+        let len = usize::try_from(from_entry).unwrap_or(usize::MAX);
+        self.entries.truncate(len);
+        Ok(())
+    }
+
+    fn reset(&mut self, sequence: u64) -> io::Result<()> {
+        self.sequence = sequence;
+        self.entries.clear();
+        Ok(())
+    }
+}
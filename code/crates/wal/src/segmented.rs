@@ -0,0 +1,270 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::wal_storage::{WalEntries, WalStorage};
+use crate::Log;
+
+#[cfg(feature = "encryption")]
+use crate::EncryptionKey;
+
+/// Extension of the segment files
+const SEGMENT_EXTENSION: &str = "wal";
+
+/// A sealed segment of a [`SegmentedLog`]
+#[derive(Copy, Clone, Debug)]
+struct Segment {
+    /// Number of entries in the segment
+    len: usize,
+    /// Size of the segment file in bytes
+    size_bytes: u64,
+}
+
+/// Write-Ahead Log (WAL) split into segment files, stored in a directory.
+///
+/// Each segment is a WAL file of its own, in the format of [`crate::Log`], and is named after
+/// its index in the log (`00000000.wal`, `00000001.wal`, ...). Entries are appended to the last
+/// segment, which is sealed once it reaches the maximum segment size. Sealed segments are never
+/// appended to again, and are only ever removed as a whole, when the log is reset or truncated,
+/// so that they can be shipped as-is to an object store.
+#[derive(Debug)]
+pub struct SegmentedLog {
+    dir: PathBuf,
+    max_segment_size: u64,
+    sealed: Vec<Segment>,
+    active: Log,
+
+    #[cfg(feature = "encryption")]
+    encryption_key: Option<EncryptionKey>,
+}
+
+impl SegmentedLog {
+    /// Opens a segmented Write-Ahead Log in the given directory, creating it if needed.
+    ///
+    /// # Arguments
+    /// * `dir` - Directory holding the segment files
+    /// * `max_segment_size` - Size in bytes above which a segment is sealed
+    ///
+    /// # Returns
+    /// * `Ok(SegmentedLog)` - Successfully opened/created WAL
+    /// * `Err` - If file operations fail, or the segments are missing or inconsistent
+    pub fn open(dir: impl AsRef<Path>, max_segment_size: u64) -> io::Result<Self> {
+        let dir = dir.as_ref().to_owned();
+        fs::create_dir_all(&dir)?;
+
+        let mut indices = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == SEGMENT_EXTENSION) {
+                if let Some(index) = segment_index(&path) {
+                    indices.push(index);
+                }
+            }
+        }
+
+        indices.sort_unstable();
+
+        if indices
+            .iter()
+            .enumerate()
+            .any(|(i, index)| i as u64 != *index)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Missing segments in WAL at {}", dir.display()),
+            ));
+        }
+
+        let mut last = indices.len().saturating_sub(1) as u64;
+        let mut active = Log::open(segment_path(&dir, last))?;
+
+        // Remove the empty segment left behind by a crash while sealing the previous one
+        if last > 0 && active.is_empty() {
+            drop(active);
+            fs::remove_file(segment_path(&dir, last))?;
+
+            last -= 1;
+            active = Log::open(segment_path(&dir, last))?;
+        }
+
+        let mut sealed = Vec::with_capacity(last as usize);
+        for index in 0..last {
+            let segment = Log::open(segment_path(&dir, index))?;
+
+            if segment.sequence() != active.sequence() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Segment {} is at sequence {} instead of {}",
+                        segment.path().display(),
+                        segment.sequence(),
+                        active.sequence()
+                    ),
+                ));
+            }
+
+            sealed.push(Segment {
+                len: segment.len(),
+                size_bytes: segment.size_bytes()?,
+            });
+        }
+
+        Ok(Self {
+            dir,
+            max_segment_size,
+            sealed,
+            active,
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
+        })
+    }
+
+    /// Encrypts the entries written from now on with the given key,
+    /// and decrypts the encrypted entries read with it.
+    ///
+    /// See [`crate::log::Log::enable_encryption`].
+    #[cfg(feature = "encryption")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+    pub fn enable_encryption(&mut self, key: EncryptionKey) -> io::Result<()> {
+        self.active.enable_encryption(key.clone())?;
+        self.encryption_key = Some(key);
+        Ok(())
+    }
+
+    /// Returns the directory holding the segment files.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the number of segments, including the one entries are appended to.
+    pub fn segments(&self) -> usize {
+        self.sealed.len() + 1
+    }
+
+    fn open_segment(&self, index: u64) -> io::Result<Log> {
+        #[allow(unused_mut)]
+        let mut log = Log::open(segment_path(&self.dir, index))?;
+
+        #[cfg(feature = "encryption")]
+        if let Some(key) = &self.encryption_key {
+            log.enable_encryption(key.clone())?;
+        }
+
+        Ok(log)
+    }
+
+    /// Seal the active segment, and start appending to a new one.
+    fn seal(&mut self) -> io::Result<()> {
+        self.active.flush()?;
+
+        let segment = Segment {
+            len: self.active.len(),
+            size_bytes: self.active.size_bytes()?,
+        };
+
+        let mut next = self.open_segment(self.sealed.len() as u64 + 1)?;
+        next.reset(self.active.sequence())?;
+
+        self.sealed.push(segment);
+        self.active = next;
+
+        Ok(())
+    }
+
+    /// Make the segment at the given index the active one, removing all segments after it.
+    fn truncate_segments(&mut self, index: usize) -> io::Result<()> {
+        let last = self.sealed.len();
+
+        // Open the new active segment first, since the current one may be the same file
+        if index < last {
+            self.active = self.open_segment(index as u64)?;
+        }
+
+        // Remove the last segments first, so that a crash leaves a prefix of the log behind
+        for i in (index + 1..=last).rev() {
+            fs::remove_file(segment_path(&self.dir, i as u64))?;
+        }
+
+        self.sealed.truncate(index);
+
+        Ok(())
+    }
+}
+
+impl WalStorage for SegmentedLog {
+    fn sequence(&self) -> u64 {
+        self.active.sequence()
+    }
+
+    fn len(&self) -> usize {
+        self.sealed.iter().map(|s| s.len).sum::<usize>() + self.active.len()
+    }
+
+    fn size_bytes(&self) -> io::Result<u64> {
+        let sealed = self.sealed.iter().map(|s| s.size_bytes).sum::<u64>();
+        Ok(sealed + self.active.size_bytes()?)
+    }
+
+    fn append(&mut self, data: &[u8]) -> io::Result<()> {
+        if !self.active.is_empty() && self.active.size_bytes()? >= self.max_segment_size {
+            self.seal()?;
+        }
+
+        self.active.append(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.active.flush()
+    }
+
+    fn entries(&mut self) -> io::Result<WalEntries<'_>> {
+        let mut entries = Vec::with_capacity(self.len());
+
+        for index in 0..self.sealed.len() {
+            let mut segment = self.open_segment(index as u64)?;
+
+            for entry in segment.entries()? {
+                let failed = entry.is_err();
+                entries.push(entry);
+
+                if failed {
+                    return Ok(Box::new(entries.into_iter()));
+                }
+            }
+        }
+
+        Ok(Box::new(entries.into_iter().chain(self.active.entries()?)))
+    }
+
+    fn truncate(&mut self, from_entry: u64) -> io::Result<()> {
+        if from_entry >= self.len() as u64 {
+            return Ok(());
+        }
+
+        let mut offset = 0;
+
+        for (index, segment) in self.sealed.iter().enumerate() {
+            if from_entry < offset + segment.len as u64 {
+                self.truncate_segments(index)?;
+                return self.active.truncate(from_entry - offset);
+            }
+
+            offset += segment.len as u64;
+        }
+
+        self.active.truncate(from_entry - offset)
+    }
+
+    fn reset(&mut self, sequence: u64) -> io::Result<()> {
+        self.truncate_segments(0)?;
+        self.active.reset(sequence)
+    }
+}
+
+fn segment_path(dir: &Path, index: u64) -> PathBuf {
+    dir.join(format!("{index:08}.{SEGMENT_EXTENSION}"))
+}
+
+fn segment_index(path: &Path) -> Option<u64> {
+    path.file_stem()?.to_str()?.parse().ok()
+}
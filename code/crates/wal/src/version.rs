@@ -1,11 +1,14 @@
 /// Version identifier for the Write-Ahead Log (WAL) format
 ///
-/// Currently only supports version 1 (V1) of the format.
+/// Both versions share the same layout, and only differ in how the flags of an entry are read.
 #[repr(u32)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Version {
-    /// Version 1 of the WAL format
+    /// Version 1 of the WAL format, where the flags of an entry only tell whether it is compressed
     V1 = 1,
+
+    /// Version 2 of the WAL format, where the flags of an entry also tell whether it is encrypted
+    V2 = 2,
 }
 
 impl TryFrom<u32> for Version {
@@ -22,6 +25,7 @@ impl TryFrom<u32> for Version {
     fn try_from(value: u32) -> Result<Self, Self::Error> {
         match value {
             1 => Ok(Self::V1),
+            2 => Ok(Self::V2),
             _ => Err(()),
         }
     }
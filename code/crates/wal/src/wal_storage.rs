@@ -0,0 +1,94 @@
+use std::io;
+
+use crate::log::Log;
+use crate::Storage;
+
+/// Iterator over the data of the entries of a Write-Ahead Log
+pub type WalEntries<'a> = Box<dyn Iterator<Item = io::Result<Vec<u8>>> + 'a>;
+
+/// Pluggable storage for the entries of a Write-Ahead Log.
+///
+/// The entries of the log all belong to the same sequence, ie. the height at which they
+/// were written, and are dropped whenever the log is reset to another sequence.
+///
+/// Three implementations are provided:
+/// - [`crate::Log`], a single file, which is the default
+/// - [`crate::MemoryLog`], which keeps the entries in memory, for tests
+/// - [`crate::SegmentedLog`], a directory of segment files which are never modified once
+///   sealed, so that they can be shipped as-is to an object store
+pub trait WalStorage: Send {
+    /// Returns the current sequence number.
+    fn sequence(&self) -> u64;
+
+    /// Returns the number of entries in the log.
+    fn len(&self) -> usize;
+
+    /// Returns whether the log is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the size in bytes taken by the log in its storage.
+    fn size_bytes(&self) -> io::Result<u64>;
+
+    /// Appends a new entry to the log.
+    ///
+    /// The entry is only guaranteed to be persisted once the log is flushed.
+    fn append(&mut self, data: &[u8]) -> io::Result<()>;
+
+    /// Persists all entries appended so far.
+    fn flush(&mut self) -> io::Result<()>;
+
+    /// Returns an iterator over the data of all entries, in the order they were appended.
+    ///
+    /// The iterator stops after the first entry which cannot be read.
+    fn entries(&mut self) -> io::Result<WalEntries<'_>>;
+
+    /// Removes all entries from `from_entry` onwards.
+    /// If `from_entry` is greater than or equal to the number of entries, no action is taken.
+    fn truncate(&mut self, from_entry: u64) -> io::Result<()>;
+
+    /// Removes all entries, and starts over with the given sequence number.
+    fn reset(&mut self, sequence: u64) -> io::Result<()>;
+}
+
+impl<S> WalStorage for Log<S>
+where
+    S: Storage + Send,
+{
+    fn sequence(&self) -> u64 {
+        Log::sequence(self)
+    }
+
+    fn len(&self) -> usize {
+        Log::len(self)
+    }
+
+    fn size_bytes(&self) -> io::Result<u64> {
+        Log::size_bytes(self)
+    }
+
+    fn append(&mut self, data: &[u8]) -> io::Result<()> {
+        Log::append(self, data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Log::flush(self)
+    }
+
+    fn entries(&mut self) -> io::Result<WalEntries<'_>> {
+        if Log::is_empty(self) {
+            return Ok(Box::new(std::iter::empty()));
+        }
+
+        Ok(Box::new(Log::iter(self)?))
+    }
+
+    fn truncate(&mut self, from_entry: u64) -> io::Result<()> {
+        Log::truncate(self, from_entry)
+    }
+
+    fn reset(&mut self, sequence: u64) -> io::Result<()> {
+        Log::reset(self, sequence)
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::Path;
+use std::sync::LazyLock;
+use std::{fs, io};
+
+use testdir::{NumberedDir, NumberedDirBuilder};
+
+use arc_malachitebft_wal::{EncryptionKey, Log, SegmentedLog, Version, WalStorage};
+
+static TESTDIR: LazyLock<NumberedDir> =
+    LazyLock::new(|| NumberedDirBuilder::new("wal".to_string()).create().unwrap());
+
+macro_rules! testwal {
+    () => {{
+        let module_path = ::std::module_path!();
+        let test_name = ::testdir::private::extract_test_name(&module_path);
+        let subdir_path = ::std::path::Path::new(&module_path.replace("::", "/")).join(&test_name);
+        TESTDIR.create_subdir(subdir_path).unwrap().join("wal.log")
+    }};
+}
+
+const ENTRIES: &[&str] = &[
+    "Hello, world!",
+    "Wheeee!",
+    "1234567890",
+    "Lorem ipsum dolor sit amet, consectetur adipiscing elit.",
+];
+
+const KEY_HEX: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+fn key() -> EncryptionKey {
+    EncryptionKey::from_hex(KEY_HEX).unwrap()
+}
+
+fn read_entries(path: &Path, key: Option<EncryptionKey>) -> io::Result<Vec<String>> {
+    let mut wal = Log::open(path)?;
+
+    if let Some(key) = key {
+        wal.enable_encryption(key)?;
+    }
+
+    wal.iter()?
+        .map(|entry| entry.map(|data| String::from_utf8(data).unwrap()))
+        .collect()
+}
+
+#[test]
+fn encrypted_entries_roundtrip() -> io::Result<()> {
+    let path = testwal!();
+
+    {
+        let mut wal = Log::open(&path)?;
+        wal.enable_encryption(key())?;
+        assert_eq!(wal.version(), Version::V2);
+
+        for entry in ENTRIES {
+            wal.append(entry)?;
+        }
+
+        wal.flush()?;
+    }
+
+    // The entries are not stored in clear
+    let contents = fs::read(&path)?;
+    for entry in ENTRIES {
+        let entry = entry.as_bytes();
+        assert!(!contents.windows(entry.len()).any(|window| window == entry));
+    }
+
+    assert_eq!(read_entries(&path, Some(key()))?, ENTRIES);
+
+    Ok(())
+}
+
+#[test]
+fn unencrypted_entries_remain_readable() -> io::Result<()> {
+    let path = testwal!();
+
+    {
+        let mut wal = Log::open(&path)?;
+        assert_eq!(wal.version(), Version::V1);
+
+        wal.append(ENTRIES[0])?;
+        wal.append(ENTRIES[1])?;
+        wal.flush()?;
+    }
+
+    {
+        let mut wal = Log::open(&path)?;
+        wal.enable_encryption(key())?;
+
+        wal.append(ENTRIES[2])?;
+        wal.append(ENTRIES[3])?;
+        wal.flush()?;
+    }
+
+    let wal = Log::open(&path)?;
+    assert_eq!(wal.version(), Version::V2);
+    assert_eq!(wal.len(), ENTRIES.len());
+    drop(wal);
+
+    assert_eq!(read_entries(&path, Some(key()))?, ENTRIES);
+
+    Ok(())
+}
+
+#[test]
+fn encrypted_entries_require_the_key() -> io::Result<()> {
+    let path = testwal!();
+
+    {
+        let mut wal = Log::open(&path)?;
+        wal.enable_encryption(key())?;
+        wal.append(ENTRIES[0])?;
+        wal.flush()?;
+    }
+
+    let err = read_entries(&path, None).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let wrong_key = EncryptionKey::from_bytes([42; 32]);
+    let err = read_entries(&path, Some(wrong_key)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    Ok(())
+}
+
+#[test]
+fn parse_key() {
+    assert!(EncryptionKey::from_hex(&format!("  {KEY_HEX}\n")).is_ok());
+    assert!(EncryptionKey::from_hex(&KEY_HEX[2..]).is_err());
+    assert!(EncryptionKey::from_hex(&KEY_HEX.replace('a', "z")).is_err());
+}
+
+#[test]
+fn encrypted_segments_roundtrip() -> io::Result<()> {
+    let dir = testwal!().with_file_name("segments");
+
+    {
+        let mut wal = SegmentedLog::open(&dir, 64)?;
+        wal.enable_encryption(key())?;
+
+        for entry in ENTRIES {
+            wal.append(entry.as_bytes())?;
+        }
+
+        wal.flush()?;
+        assert!(wal.segments() > 1);
+    }
+
+    let mut wal = SegmentedLog::open(&dir, 64)?;
+    wal.enable_encryption(key())?;
+
+    let entries = wal
+        .entries()?
+        .map(|entry| entry.map(|data| String::from_utf8(data).unwrap()))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    assert_eq!(entries, ENTRIES);
+    drop(wal);
+
+    // Entries cannot be read back without the key
+    let mut wal = SegmentedLog::open(&dir, 64)?;
+    assert!(wal.entries()?.any(|entry| entry.is_err()));
+
+    Ok(())
+}
@@ -1,8 +1,12 @@
 pub mod basic;
 pub mod corruption;
 pub mod crashes;
+pub mod storage;
 pub mod stress;
 pub mod truncation;
 
 #[cfg(all(feature = "compression", not(feature = "force-compression")))]
 pub mod compression;
+
+#[cfg(feature = "encryption")]
+pub mod encryption;
@@ -0,0 +1,177 @@
+use std::fs;
+use std::io;
+use std::sync::LazyLock;
+
+use testdir::{NumberedDir, NumberedDirBuilder};
+
+use arc_malachitebft_wal::{Log, MemoryLog, SegmentedLog, WalStorage};
+
+static TESTDIR: LazyLock<NumberedDir> =
+    LazyLock::new(|| NumberedDirBuilder::new("wal".to_string()).create().unwrap());
+
+macro_rules! testdir {
+    () => {{
+        let module_path = ::std::module_path!();
+        let test_name = ::testdir::private::extract_test_name(&module_path);
+        let subdir_path = ::std::path::Path::new(&module_path.replace("::", "/")).join(&test_name);
+        TESTDIR.create_subdir(subdir_path).unwrap()
+    }};
+}
+
+/// Large enough for a segment to hold a couple of entries of 16 bytes
+const MAX_SEGMENT_SIZE: u64 = 64;
+
+fn entry(i: usize) -> Vec<u8> {
+    format!("entry number {i:>3}").into_bytes()
+}
+
+fn read_all(storage: &mut dyn WalStorage) -> io::Result<Vec<Vec<u8>>> {
+    storage.entries()?.collect()
+}
+
+/// Exercise the operations of the WAL through the storage trait only
+fn check_storage(storage: &mut dyn WalStorage) -> io::Result<()> {
+    storage.reset(1)?;
+    assert_eq!(storage.sequence(), 1);
+    assert!(storage.is_empty());
+    assert!(read_all(storage)?.is_empty());
+
+    let entries = (0..10).map(entry).collect::<Vec<_>>();
+    for entry in &entries {
+        storage.append(entry)?;
+    }
+    storage.flush()?;
+
+    assert_eq!(storage.len(), entries.len());
+    assert_eq!(read_all(storage)?, entries);
+
+    // Truncating past the end is a no-op
+    storage.truncate(20)?;
+    assert_eq!(read_all(storage)?, entries);
+
+    storage.truncate(7)?;
+    assert_eq!(storage.len(), 7);
+    assert_eq!(read_all(storage)?, entries[..7]);
+
+    storage.truncate(3)?;
+    assert_eq!(read_all(storage)?, entries[..3]);
+
+    storage.append(&entry(42))?;
+    assert_eq!(storage.len(), 4);
+    assert_eq!(read_all(storage)?.last(), Some(&entry(42)));
+
+    storage.reset(2)?;
+    assert_eq!(storage.sequence(), 2);
+    assert!(storage.is_empty());
+    assert!(read_all(storage)?.is_empty());
+
+    storage.append(&entry(0))?;
+    assert_eq!(read_all(storage)?, vec![entry(0)]);
+
+    Ok(())
+}
+
+#[test]
+fn file_storage() -> io::Result<()> {
+    let dir = testdir!();
+    let mut log = Log::open(dir.join("wal.log"))?;
+    check_storage(&mut log)
+}
+
+#[test]
+fn memory_storage() -> io::Result<()> {
+    let mut log = MemoryLog::new();
+    check_storage(&mut log)
+}
+
+#[test]
+fn segmented_storage() -> io::Result<()> {
+    let dir = testdir!();
+    let mut log = SegmentedLog::open(&dir, MAX_SEGMENT_SIZE)?;
+    check_storage(&mut log)
+}
+
+#[test]
+fn segmented_storage_seals_segments() -> io::Result<()> {
+    let dir = testdir!().join("segments");
+    let entries = (0..10).map(entry).collect::<Vec<_>>();
+
+    {
+        let mut log = SegmentedLog::open(&dir, MAX_SEGMENT_SIZE)?;
+        log.reset(5)?;
+
+        for entry in &entries {
+            log.append(entry)?;
+        }
+        log.flush()?;
+
+        assert!(log.segments() > 1);
+    }
+
+    let segments = fs::read_dir(&dir)?.count();
+
+    // Reopen the log, and check that all segments are picked up
+    let mut log = SegmentedLog::open(&dir, MAX_SEGMENT_SIZE)?;
+    assert_eq!(log.segments(), segments);
+    assert_eq!(log.sequence(), 5);
+    assert_eq!(log.len(), entries.len());
+    assert_eq!(read_all(&mut log)?, entries);
+
+    // Sealed segments are left untouched when appending
+    let first = fs::read(dir.join("00000000.wal"))?;
+    log.append(&entry(10))?;
+    log.flush()?;
+    assert_eq!(fs::read(dir.join("00000000.wal"))?, first);
+
+    // Truncating within the first segment removes all the others
+    log.truncate(1)?;
+    assert_eq!(log.segments(), 1);
+    assert_eq!(fs::read_dir(&dir)?.count(), 1);
+    assert_eq!(read_all(&mut log)?, entries[..1]);
+
+    // Resetting starts over from a single segment
+    for entry in &entries {
+        log.append(entry)?;
+    }
+    log.reset(6)?;
+    assert_eq!(log.segments(), 1);
+    assert_eq!(fs::read_dir(&dir)?.count(), 1);
+    assert!(log.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn segmented_storage_rejects_missing_segments() -> io::Result<()> {
+    let dir = testdir!().join("segments");
+
+    {
+        let mut log = SegmentedLog::open(&dir, MAX_SEGMENT_SIZE)?;
+        for i in 0..10 {
+            log.append(&entry(i))?;
+        }
+        log.flush()?;
+        assert!(log.segments() > 2);
+    }
+
+    fs::remove_file(dir.join("00000001.wal"))?;
+
+    let result = SegmentedLog::open(&dir, MAX_SEGMENT_SIZE);
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+    Ok(())
+}
+
+#[test]
+fn storage_cannot_be_shared() -> io::Result<()> {
+    let dir = testdir!();
+
+    // Several consensus instances must not write to the same WAL
+    let _log = Log::open(dir.join("consensus.wal"))?;
+    assert!(Log::open(dir.join("consensus.wal")).is_err());
+
+    let _segmented = SegmentedLog::open(dir.join("segments"), MAX_SEGMENT_SIZE)?;
+    assert!(SegmentedLog::open(dir.join("segments"), MAX_SEGMENT_SIZE).is_err());
+
+    Ok(())
+}
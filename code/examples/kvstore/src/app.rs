@@ -0,0 +1,323 @@
+use tracing::{debug, error, info, warn};
+
+use malachitebft_app_channel::app::engine::host::{HeightParams, Next, SyncedValueOutcome};
+use malachitebft_app_channel::app::types::core::utils::height::HeightRangeExt;
+use malachitebft_app_channel::app::types::core::{Round, Validity};
+use malachitebft_app_channel::app::types::sync::RawDecidedValue;
+use malachitebft_app_channel::app::types::{LocallyProposedValue, ProposedValue};
+use malachitebft_app_channel::{AppMsg, Channels};
+use malachitebft_test::{Height, TestContext};
+
+use crate::state::{decode_value, encode_value, State};
+
+/// Returns the parameters of consensus for the given height
+fn height_params(state: &State, height: Height) -> HeightParams<TestContext> {
+    HeightParams::new(
+        state.get_validator_set(height),
+        state.get_timeouts(height),
+        None,
+    )
+}
+
+/// Reports a value received through sync as invalid if it did not pass validation
+fn synced_value_outcome(value: ProposedValue<TestContext>) -> SyncedValueOutcome<TestContext> {
+    if value.validity.is_valid() {
+        SyncedValueOutcome::Valid(value)
+    } else {
+        SyncedValueOutcome::Invalid {
+            reason: "invalid value".to_string(),
+        }
+    }
+}
+
+pub async fn run(state: &mut State, channels: &mut Channels<TestContext>) -> eyre::Result<()> {
+    while let Some(msg) = channels.consensus.recv().await {
+        match msg {
+            AppMsg::ConsensusReady { reply } => {
+                let start_height = state.next_height();
+                state.current_height = start_height;
+
+                info!(%start_height, "Consensus is ready");
+
+                if reply
+                    .send((start_height, height_params(state, start_height)))
+                    .is_err()
+                {
+                    error!("Failed to send ConsensusReady reply");
+                }
+            }
+
+            AppMsg::StartedRound {
+                height,
+                round,
+                proposer,
+                role,
+                reply_value,
+            } => {
+                info!(%height, %round, %proposer, ?role, "Started round");
+
+                state.current_height = height;
+                state.current_round = round;
+
+                // If we already have a value for this round, eg. because we proposed it
+                // before restarting, send it back so that consensus does not wait for it.
+                let values = state
+                    .get_undecided_value(height, round)
+                    .cloned()
+                    .into_iter()
+                    .collect();
+
+                if reply_value.send(values).is_err() {
+                    error!("Failed to send undecided values");
+                }
+            }
+
+            AppMsg::GetValue {
+                height,
+                round,
+                timeout: _,
+                reply,
+            } => {
+                info!(%height, %round, "Consensus is requesting a value to propose");
+
+                // If we have already built a value for this round, we must propose the very same value,
+                // otherwise we build a new block on top of the state as of the last decided height.
+                let previous = state
+                    .get_undecided_value(height, round)
+                    .filter(|value| value.proposer == state.address)
+                    .map(|value| value.value.clone());
+
+                let value = match previous {
+                    Some(value) => LocallyProposedValue::new(height, round, value),
+                    None => state.propose_value(height, round),
+                };
+
+                // In proposal-only mode, the whole value is sent along with the proposal,
+                // so there are no proposal parts to stream.
+                if reply.send(value).is_err() {
+                    error!("Failed to send GetValue reply");
+                }
+            }
+
+            // Only sent in proposal-and-parts mode, in which the parts of the value
+            // proposed again in a later round must be streamed again.
+            AppMsg::RestreamProposal {
+                height,
+                round,
+                value_id,
+                ..
+            } => {
+                warn!(%height, %round, %value_id, "Unexpected request to restream a proposal");
+            }
+
+            AppMsg::CancelGetValue { height, round } => {
+                warn!(%height, %round, "Consensus cancelled the request for a value to propose");
+            }
+
+            // Only sent in proposal-and-parts mode, in which values are streamed as parts
+            AppMsg::ReceivedProposalPart { from, reply, .. } => {
+                warn!(%from, "Unexpected proposal part");
+
+                if reply.send(None).is_err() {
+                    error!("Failed to send ReceivedProposalPart reply");
+                }
+            }
+
+            // The proposal carries the whole value, which we validate by executing its block
+            // on top of the state as of the last decided height.
+            AppMsg::ReceivedProposal {
+                height,
+                round,
+                valid_round,
+                proposer,
+                value,
+                reply,
+            } => {
+                info!(%height, %round, %proposer, value = %value.id(), "Received proposal");
+
+                let value = state.received_value(ProposedValue {
+                    height,
+                    round,
+                    valid_round,
+                    proposer,
+                    value,
+                    validity: Validity::Valid,
+                });
+
+                if reply.send(value).is_err() {
+                    error!("Failed to send ReceivedProposal reply");
+                }
+            }
+
+            AppMsg::Decided {
+                certificate, reply, ..
+            } => {
+                info!(
+                    height = %certificate.height,
+                    round = %certificate.round,
+                    value = %certificate.value_id,
+                    "Consensus has decided on value"
+                );
+
+                if reply.send(()).is_err() {
+                    error!("Failed to send Decided reply");
+                }
+            }
+
+            AppMsg::Finalized {
+                certificate, reply, ..
+            } => {
+                let height = certificate.height;
+
+                let next = match state.commit(certificate) {
+                    Ok(state_hash) => {
+                        info!(%height, %state_hash, keys = state.kv().len(), "Committed block");
+
+                        Next::Start(
+                            state.current_height,
+                            height_params(state, state.current_height),
+                        )
+                    }
+                    Err(e) => {
+                        error!(%height, "Failed to commit decided value, restarting height: {e}");
+                        Next::Restart(height, height_params(state, height))
+                    }
+                };
+
+                if reply.send(next).is_err() {
+                    error!("Failed to send Finalized reply");
+                }
+            }
+
+            AppMsg::ProcessSyncedValue {
+                height,
+                round,
+                proposer,
+                value_bytes,
+                reply,
+            } => {
+                info!(%height, %round, "Processing synced value");
+
+                let outcome = decode_value(value_bytes).map(|value| {
+                    synced_value_outcome(state.received_value(ProposedValue {
+                        height,
+                        round,
+                        valid_round: Round::Nil,
+                        proposer,
+                        value,
+                        validity: Validity::Valid,
+                    }))
+                });
+
+                if reply.send(outcome).is_err() {
+                    error!("Failed to send ProcessSyncedValue reply");
+                }
+            }
+
+            // The blocks of the batch are executed in turn, each on top of the state
+            // reached by executing the previous ones, see `State::received_synced_values`.
+            AppMsg::ProcessSyncedValues { values, reply } => {
+                let values = values
+                    .into_iter()
+                    .map(|raw| {
+                        let height = raw.certificate.height;
+                        let round = raw.certificate.round;
+
+                        let proposer = state
+                            .ctx
+                            .select_proposer(&state.get_validator_set(height), height, round)
+                            .address;
+
+                        decode_value(raw.value_bytes).map(|value| ProposedValue {
+                            height,
+                            round,
+                            valid_round: Round::Nil,
+                            proposer,
+                            value,
+                            validity: Validity::Valid,
+                        })
+                    })
+                    .collect();
+
+                let outcomes = state
+                    .received_synced_values(values)
+                    .into_iter()
+                    .map(|value| value.map(synced_value_outcome))
+                    .collect();
+
+                if reply.send(outcomes).is_err() {
+                    error!("Failed to send ProcessSyncedValues reply");
+                }
+            }
+
+            AppMsg::GetDecidedValues { range, reply } => {
+                let values = range
+                    .iter_heights()
+                    .filter_map(|height| state.get_decided_value(height))
+                    .map(|decided| RawDecidedValue {
+                        certificate: decided.certificate.clone(),
+                        value_bytes: encode_value(&decided.value),
+                    })
+                    .collect();
+
+                if reply.send(values).is_err() {
+                    error!("Failed to send GetDecidedValues reply");
+                }
+            }
+
+            // We keep all decided values, so there is never anything to backfill
+            AppMsg::ProcessBackfilledValues { reply, .. } => {
+                if reply.send(false).is_err() {
+                    error!("Failed to send ProcessBackfilledValues reply");
+                }
+            }
+
+            AppMsg::GetHistoryMinHeight { reply } => {
+                if reply.send(state.earliest_height()).is_err() {
+                    error!("Failed to send GetHistoryMinHeight reply");
+                }
+            }
+
+            AppMsg::GetTimeoutOverrides { reply, .. } => {
+                if reply.send(Default::default()).is_err() {
+                    error!("Failed to send GetTimeoutOverrides reply");
+                }
+            }
+
+            AppMsg::GetConsensusParams { reply, .. } => {
+                if reply.send(Default::default()).is_err() {
+                    error!("Failed to send GetConsensusParams reply");
+                }
+            }
+
+            AppMsg::RoundAlert {
+                height,
+                round,
+                halted,
+            } => {
+                warn!(%height, %round, %halted, "Height is going through too many rounds");
+            }
+
+            AppMsg::Prune { retain_height } => {
+                debug!(%retain_height, "Decided values below the retain height can be pruned");
+            }
+
+            AppMsg::ExtendVote { reply, .. } => {
+                if reply.send(None).is_err() {
+                    error!("Failed to send ExtendVote reply");
+                }
+            }
+
+            AppMsg::VerifyVoteExtension { reply, .. } => {
+                if reply.send(Ok(())).is_err() {
+                    error!("Failed to send VerifyVoteExtension reply");
+                }
+            }
+        }
+    }
+
+    // If we get there, it can only be because the channel we use to receive message
+    // from consensus has been closed, meaning that the consensus actor has died.
+    // We can do nothing but return an error here.
+    Err(eyre::eyre!("Consensus channel closed unexpectedly"))
+}
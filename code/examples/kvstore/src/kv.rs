@@ -0,0 +1,389 @@
+//! The replicated state machine: a key-value store updated by batches of transactions.
+//!
+//! Execution is deterministic: applying the same transactions to the same state
+//! always yields the same state, and thus the same [`StateHash`], on every node.
+//! This is what allows validators to check a proposed [`Block`] by executing it themselves,
+//! and to agree on the state reached after each height by agreeing on the block alone.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use sha3::{Digest, Keccak256};
+
+use malachitebft_test::Value;
+
+/// A transaction updating the key-value store
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Tx {
+    /// Sets the value of a key, inserting the key if it does not exist yet
+    Set { key: String, value: String },
+    /// Removes a key, if it exists
+    Delete { key: String },
+}
+
+/// Hash of the state of the key-value store
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StateHash([u8; 32]);
+
+impl StateHash {
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for StateHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// State of the key-value store
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KvState {
+    entries: BTreeMap<String, String>,
+}
+
+impl KvState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value of the given key, if any
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    /// Returns the number of keys in the store
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the store is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Applies the given transactions, in order
+    pub fn apply(&mut self, txs: &[Tx]) {
+        for tx in txs {
+            match tx {
+                Tx::Set { key, value } => {
+                    self.entries.insert(key.clone(), value.clone());
+                }
+                Tx::Delete { key } => {
+                    self.entries.remove(key);
+                }
+            }
+        }
+    }
+
+    /// Returns the hash of the state reached by applying the given transactions,
+    /// leaving this state untouched.
+    pub fn execute(&self, txs: &[Tx]) -> StateHash {
+        let mut state = self.clone();
+        state.apply(txs);
+        state.hash()
+    }
+
+    /// Hash of the state, over its entries sorted by key.
+    ///
+    /// Keys and values are length-prefixed, so that no two distinct states share the same preimage.
+    pub fn hash(&self) -> StateHash {
+        let mut hasher = Keccak256::new();
+
+        for (key, value) in &self.entries {
+            hasher.update((key.len() as u64).to_be_bytes());
+            hasher.update(key.as_bytes());
+            hasher.update((value.len() as u64).to_be_bytes());
+            hasher.update(value.as_bytes());
+        }
+
+        StateHash(hasher.finalize().into())
+    }
+}
+
+/// Error returned when a value does not carry a valid block
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum BlockError {
+    #[error("unexpected end of block")]
+    UnexpectedEnd,
+
+    #[error("unknown transaction kind {0}")]
+    UnknownTxKind(u8),
+
+    #[error("invalid UTF-8 string")]
+    InvalidUtf8,
+
+    #[error("{0} trailing bytes after block")]
+    TrailingBytes(usize),
+
+    #[error("value id does not match the digest of the block")]
+    IdMismatch,
+
+    #[error("state hash mismatch, expected {expected} but got {actual}")]
+    StateHashMismatch {
+        expected: StateHash,
+        actual: StateHash,
+    },
+}
+
+/// A batch of transactions, together with the hash of the state reached by executing them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub txs: Vec<Tx>,
+    pub state_hash: StateHash,
+}
+
+const TX_SET: u8 = 0;
+const TX_DELETE: u8 = 1;
+
+impl Block {
+    /// Builds a block by executing the given transactions on top of the given state
+    pub fn build(state: &KvState, txs: Vec<Tx>) -> Self {
+        let state_hash = state.execute(&txs);
+        Self { txs, state_hash }
+    }
+
+    /// Checks that executing the transactions of this block on top of the given state
+    /// yields the state hash it carries.
+    pub fn verify(&self, state: &KvState) -> Result<(), BlockError> {
+        let actual = state.execute(&self.txs);
+
+        if actual != self.state_hash {
+            return Err(BlockError::StateHashMismatch {
+                expected: self.state_hash,
+                actual,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Encodes the block as the number of transactions, followed by each transaction
+    /// and the state hash. Strings are prefixed with their length.
+    pub fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::new();
+
+        buf.put_u32(self.txs.len() as u32);
+
+        for tx in &self.txs {
+            match tx {
+                Tx::Set { key, value } => {
+                    buf.put_u8(TX_SET);
+                    put_str(&mut buf, key);
+                    put_str(&mut buf, value);
+                }
+                Tx::Delete { key } => {
+                    buf.put_u8(TX_DELETE);
+                    put_str(&mut buf, key);
+                }
+            }
+        }
+
+        buf.put_slice(self.state_hash.as_bytes());
+        buf.freeze()
+    }
+
+    /// Decodes a block encoded with [`Block::encode`]
+    pub fn decode(mut bytes: &[u8]) -> Result<Self, BlockError> {
+        let buf = &mut bytes;
+
+        let count = u32::from_be_bytes(take_array(buf)?);
+
+        // Do not trust the count for the capacity, as it comes from the network
+        let mut txs = Vec::new();
+
+        for _ in 0..count {
+            let [kind] = take_array(buf)?;
+
+            let tx = match kind {
+                TX_SET => Tx::Set {
+                    key: take_str(buf)?,
+                    value: take_str(buf)?,
+                },
+                TX_DELETE => Tx::Delete {
+                    key: take_str(buf)?,
+                },
+                kind => return Err(BlockError::UnknownTxKind(kind)),
+            };
+
+            txs.push(tx);
+        }
+
+        let state_hash = StateHash(take_array(buf)?);
+
+        if !buf.is_empty() {
+            return Err(BlockError::TrailingBytes(buf.len()));
+        }
+
+        Ok(Self { txs, state_hash })
+    }
+
+    /// Wraps the block into a value to propose.
+    ///
+    /// The encoded block is carried in the extensions of the value, and the value itself
+    /// is set to the first 8 bytes of the digest of the encoded block, so that the id
+    /// of the value, which is what validators vote on, commits to the whole block.
+    pub fn to_value(&self) -> Value {
+        let extensions = self.encode();
+
+        Value {
+            value: digest(&extensions),
+            extensions,
+        }
+    }
+
+    /// Extracts the block carried by a value, checking that the id of the value matches it
+    pub fn from_value(value: &Value) -> Result<Self, BlockError> {
+        if value.value != digest(&value.extensions) {
+            return Err(BlockError::IdMismatch);
+        }
+
+        Self::decode(&value.extensions)
+    }
+}
+
+/// First 8 bytes of the Keccak-256 digest of the given bytes
+fn digest(bytes: &[u8]) -> u64 {
+    let hash = Keccak256::digest(bytes);
+    let mut id = [0; 8];
+    id.copy_from_slice(&hash[..8]);
+    u64::from_be_bytes(id)
+}
+
+fn put_str(buf: &mut BytesMut, s: &str) {
+    buf.put_u32(s.len() as u32);
+    buf.put_slice(s.as_bytes());
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], BlockError> {
+    if buf.len() < len {
+        return Err(BlockError::UnexpectedEnd);
+    }
+
+    let (head, tail) = buf.split_at(len);
+    *buf = tail;
+    Ok(head)
+}
+
+fn take_array<const N: usize>(buf: &mut &[u8]) -> Result<[u8; N], BlockError> {
+    let mut array = [0; N];
+    array.copy_from_slice(take(buf, N)?);
+    Ok(array)
+}
+
+fn take_str(buf: &mut &[u8]) -> Result<String, BlockError> {
+    let len = u32::from_be_bytes(take_array(buf)?) as usize;
+    let bytes = take(buf, len)?;
+
+    String::from_utf8(bytes.to_vec()).map_err(|_| BlockError::InvalidUtf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(key: &str, value: &str) -> Tx {
+        Tx::Set {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn delete(key: &str) -> Tx {
+        Tx::Delete {
+            key: key.to_string(),
+        }
+    }
+
+    #[test]
+    fn execution_is_deterministic() {
+        let txs = vec![set("a", "1"), set("b", "2"), delete("a"), set("c", "3")];
+
+        let mut state = KvState::new();
+        let hash = state.execute(&txs);
+        assert_eq!(state, KvState::new(), "execute must not modify the state");
+
+        state.apply(&txs);
+        assert_eq!(state.hash(), hash);
+        assert_eq!(state.get("a"), None);
+        assert_eq!(state.get("b"), Some("2"));
+        assert_eq!(state.len(), 2);
+
+        // The hash only depends on the resulting entries, not on the order they were set in
+        let mut other = KvState::new();
+        other.apply(&[set("c", "3"), set("b", "2")]);
+        assert_eq!(other.hash(), hash);
+
+        // Length prefixes keep the boundaries between keys and values apart
+        let mut other = KvState::new();
+        other.apply(&[set("b", "23"), set("c", "")]);
+        assert_ne!(other.hash(), hash);
+    }
+
+    #[test]
+    fn block_encode_decode_roundtrip() {
+        let block = Block::build(&KvState::new(), vec![set("k", "v"), delete("k")]);
+        assert_eq!(Block::decode(&block.encode()), Ok(block.clone()));
+
+        let empty = Block::build(&KvState::new(), vec![]);
+        assert_eq!(Block::decode(&empty.encode()), Ok(empty));
+
+        let encoded = block.encode();
+        assert_eq!(
+            Block::decode(&encoded[..encoded.len() - 1]),
+            Err(BlockError::UnexpectedEnd)
+        );
+
+        let mut trailing = encoded.to_vec();
+        trailing.push(0);
+        assert_eq!(Block::decode(&trailing), Err(BlockError::TrailingBytes(1)));
+
+        let mut unknown = encoded.to_vec();
+        unknown[4] = 7;
+        assert_eq!(Block::decode(&unknown), Err(BlockError::UnknownTxKind(7)));
+    }
+
+    #[test]
+    fn block_verify_checks_state_hash() {
+        let mut state = KvState::new();
+        state.apply(&[set("a", "1")]);
+
+        let block = Block::build(&state, vec![set("b", "2")]);
+        assert_eq!(block.verify(&state), Ok(()));
+
+        // The same transactions yield another state on top of another state
+        assert!(matches!(
+            block.verify(&KvState::new()),
+            Err(BlockError::StateHashMismatch { .. })
+        ));
+
+        let forged = Block {
+            txs: vec![set("b", "3")],
+            state_hash: block.state_hash,
+        };
+        assert!(forged.verify(&state).is_err());
+    }
+
+    #[test]
+    fn value_id_commits_to_block() {
+        let block = Block::build(&KvState::new(), vec![set("a", "1")]);
+        let value = block.to_value();
+        assert_eq!(Block::from_value(&value), Ok(block));
+
+        let mut tampered = value.clone();
+        tampered.value += 1;
+        assert_eq!(Block::from_value(&tampered), Err(BlockError::IdMismatch));
+
+        let other = Block::build(&KvState::new(), vec![set("a", "2")]).to_value();
+        assert_ne!(value.id(), other.id());
+    }
+}
@@ -0,0 +1,38 @@
+//! Example application replicating a deterministic key-value store,
+//! running consensus with [`ValuePayload::ProposalOnly`].
+//!
+//! Rather than opaque values, validators agree on a sequence of blocks, each a batch of
+//! transactions setting or deleting keys in the store. As executing a block is deterministic,
+//! every node which executes the same blocks in the same order reaches the same state,
+//! which is what makes this a replicated state machine:
+//!
+//! - the proposer of a round builds a [`Block`](kv::Block) out of pending transactions, executes it
+//!   on top of the state as of the last decided height, and proposes the block together with
+//!   the hash of the resulting state,
+//! - the other validators validate the block by executing it themselves on top of the same state,
+//!   and only deem the value valid if they reach the very same state hash, so that consensus
+//!   never decides on a block whose outcome is disputed,
+//! - once a block is decided, every node executes it and moves on to the next height.
+//!
+//! The value proposed to consensus carries the encoded block, and its id is derived from the
+//! digest of the block, so that validators voting for a value id vote for the whole block,
+//! including the state hash.
+//!
+//! As the proposal carries the whole block, there are no proposal parts to stream, and consensus
+//! never asks to restream a value: when the valid value of a previous round must be proposed again,
+//! the proposal of the later round carries it as well.
+//!
+//! A node lagging behind, eg. after joining late, gets the decided blocks it misses through
+//! value sync, and validates them in the same way before executing them, one height after
+//! another. See [`app::run`] for the handling of [`AppMsg::ProcessSyncedValue`] and
+//! [`AppMsg::ProcessSyncedValues`], and the integration tests of this crate for a scenario
+//! where a node catches up with the others through sync.
+//!
+//! [`ValuePayload::ProposalOnly`]: malachitebft_app_channel::app::config::ValuePayload::ProposalOnly
+//! [`AppMsg::ProcessSyncedValue`]: malachitebft_app_channel::AppMsg::ProcessSyncedValue
+//! [`AppMsg::ProcessSyncedValues`]: malachitebft_app_channel::AppMsg::ProcessSyncedValues
+
+pub mod app;
+pub mod kv;
+pub mod node;
+pub mod state;
@@ -0,0 +1,142 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+use malachitebft_app_channel::app::events::{RxEvent, TxEvent};
+use malachitebft_app_channel::app::types::codec::Codec;
+use malachitebft_app_channel::app::types::Keypair;
+use malachitebft_app_channel::{
+    ConsensusContext, EngineBuilder, EngineHandle, NetworkContext, NetworkIdentity, RequestContext,
+    Signer, SyncContext, WalContext,
+};
+use malachitebft_test::codec::proto::ProtobufCodec;
+use malachitebft_test::middleware::{DefaultMiddleware, Middleware};
+use malachitebft_test::node::NodeHandle;
+use malachitebft_test::{
+    Address, Ed25519Signer, Ed25519Verifier, Genesis, Height, PrivateKey, TestContext,
+};
+use malachitebft_test_app::config::Config;
+
+use crate::state::State;
+
+pub struct Handle {
+    pub app: JoinHandle<()>,
+    pub engine: EngineHandle,
+    pub tx_event: TxEvent<TestContext>,
+}
+
+#[async_trait]
+impl NodeHandle<TestContext> for Handle {
+    fn subscribe(&self) -> RxEvent<TestContext> {
+        self.tx_event.subscribe()
+    }
+
+    async fn kill(&self, _reason: Option<String>) -> eyre::Result<()> {
+        self.engine.actor.kill_and_wait(None).await?;
+        self.app.abort();
+        self.engine.handle.abort();
+        Ok(())
+    }
+
+    async fn stop(&self) -> eyre::Result<()> {
+        self.engine.stop().await?;
+        self.app.abort();
+        Ok(())
+    }
+}
+
+/// A validator node running the example application.
+///
+/// Configuration and keys are provided in-memory, and the decided values are not persisted,
+/// so the node starts over from its start height when restarted.
+#[derive(Clone)]
+pub struct App {
+    pub home_dir: PathBuf,
+    pub config: Config,
+    pub genesis: Genesis,
+    pub private_key: PrivateKey,
+    pub start_height: Height,
+    pub middleware: Option<Arc<dyn Middleware>>,
+}
+
+impl App {
+    pub async fn start(&self) -> eyre::Result<Handle> {
+        let config = self.config.clone();
+
+        let span = tracing::error_span!("node", moniker = %config.moniker);
+        let _guard = span.enter();
+
+        let middleware = self
+            .middleware
+            .clone()
+            .unwrap_or_else(|| Arc::new(DefaultMiddleware));
+
+        let ctx = TestContext::with_middleware(middleware);
+
+        let public_key = self.private_key.public_key();
+        let address = Address::from_public_key(&public_key);
+        let signer = Ed25519Signer::new(self.private_key.clone());
+
+        // Use a separate keypair for the network identity of the node
+        let net_key = PrivateKey::generate(rand::thread_rng());
+        let keypair = Keypair::ed25519_from_bytes(net_key.inner().to_bytes())?;
+
+        let proof = signer
+            .sign_validator_proof(
+                public_key.as_bytes().to_vec(),
+                keypair.public().to_peer_id().to_bytes(),
+            )
+            .await
+            .map_err(|e| eyre::eyre!("Failed to sign validator proof: {e:?}"))?;
+
+        let proof_bytes = ProtobufCodec
+            .encode(&proof)
+            .map_err(|e| eyre::eyre!("Failed to encode validator proof: {e}"))?;
+
+        let identity = NetworkIdentity::new_validator(
+            config.moniker.clone(),
+            keypair,
+            address.to_string(),
+            proof_bytes,
+        );
+
+        let wal_path = self.home_dir.join("wal").join("consensus.wal");
+
+        let (mut channels, engine_handle) = EngineBuilder::new(ctx.clone(), config)
+            .with_default_wal(WalContext::new(wal_path, ProtobufCodec))
+            .with_default_network(NetworkContext::new(identity, ProtobufCodec))
+            .with_default_consensus(ConsensusContext::new_validator(
+                address,
+                Box::new(Ed25519Verifier),
+                Box::new(Ed25519Signer::new(self.private_key.clone())),
+            ))
+            .with_default_sync(SyncContext::new(ProtobufCodec))
+            .with_default_request(RequestContext::new(100))
+            .build()
+            .await?;
+
+        drop(_guard);
+
+        let mut state = State::new(ctx, self.genesis.clone(), address, self.start_height);
+
+        let tx_event = channels.events.clone();
+
+        let app_handle = tokio::spawn(
+            async move {
+                if let Err(e) = crate::app::run(&mut state, &mut channels).await {
+                    tracing::error!("Application has failed with an error: {e}");
+                }
+            }
+            .instrument(span),
+        );
+
+        Ok(Handle {
+            app: app_handle,
+            engine: engine_handle,
+            tx_event,
+        })
+    }
+}
@@ -0,0 +1,327 @@
+//! Internal state of the application.
+//!
+//! Besides the values proposed and decided at each height, the application keeps the state
+//! of the key-value store as of the last decided height. Every value carries a block of
+//! transactions together with the hash of the state reached by executing them on top of
+//! that state, which validators check by executing the block themselves before voting for it.
+//!
+//! Values are kept in memory, as this example focuses on how the state machine is replicated
+//! rather than on how it is persisted. A real application would store the decided blocks and
+//! the state in a database, so that they survive a restart.
+
+use std::collections::BTreeMap;
+
+use bytes::Bytes;
+use eyre::eyre;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tracing::{error, info};
+
+use malachitebft_app_channel::app::types::core::{CommitCertificate, Round, Validity};
+use malachitebft_app_channel::app::types::{LocallyProposedValue, ProposedValue};
+use malachitebft_test::{
+    Address, Genesis, Height, LinearTimeouts, TestContext, ValidatorSet, Value,
+};
+
+use crate::kv::{Block, KvState, StateHash, Tx};
+
+/// Number of distinct keys written by the transactions made up by the proposers,
+/// kept small so that transactions overwrite and delete existing keys.
+const KEYS: usize = 16;
+
+/// Maximum number of transactions in a block
+const MAX_TXS: usize = 8;
+
+/// A value decided by consensus, together with its commit certificate
+#[derive(Clone, Debug)]
+pub struct DecidedValue {
+    pub value: Value,
+    pub certificate: CommitCertificate<TestContext>,
+}
+
+/// Internal state of the application node
+pub struct State {
+    pub ctx: TestContext,
+    pub genesis: Genesis,
+    pub address: Address,
+    pub current_height: Height,
+    pub current_round: Round,
+
+    rng: StdRng,
+
+    /// State of the key-value store after executing the blocks decided so far
+    kv: KvState,
+
+    /// Values proposed by us or received from peers, by height and round
+    undecided_values: BTreeMap<(Height, Round), ProposedValue<TestContext>>,
+
+    /// Values decided so far, by height
+    decided_values: BTreeMap<Height, DecidedValue>,
+}
+
+impl State {
+    /// Creates a new state for the validator with the given address, starting at the given height
+    pub fn new(ctx: TestContext, genesis: Genesis, address: Address, height: Height) -> Self {
+        Self {
+            ctx,
+            genesis,
+            address,
+            current_height: height,
+            current_round: Round::Nil,
+            rng: StdRng::from_entropy(),
+            kv: KvState::new(),
+            undecided_values: BTreeMap::new(),
+            decided_values: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the set of validators for the given height
+    pub fn get_validator_set(&self, height: Height) -> ValidatorSet {
+        self.ctx
+            .middleware()
+            .get_validator_set(&self.ctx, self.current_height, height, &self.genesis)
+            .unwrap_or_else(|| self.genesis.validator_set.clone())
+    }
+
+    /// Returns the timeouts for the given height
+    pub fn get_timeouts(&self, height: Height) -> LinearTimeouts {
+        self.ctx
+            .middleware()
+            .get_timeouts(&self.ctx, self.current_height, height)
+            .unwrap_or_default()
+    }
+
+    /// Returns the height following the last decided one, or the current height if none was decided yet
+    pub fn next_height(&self) -> Height {
+        self.decided_values
+            .last_key_value()
+            .map(|(height, _)| height.increment())
+            .unwrap_or(self.current_height)
+    }
+
+    /// Returns the earliest height for which we have a decided value
+    pub fn earliest_height(&self) -> Height {
+        self.decided_values
+            .first_key_value()
+            .map(|(height, _)| *height)
+            .unwrap_or_default()
+    }
+
+    /// Returns the state of the key-value store as of the last decided height
+    pub fn kv(&self) -> &KvState {
+        &self.kv
+    }
+
+    /// Returns the value decided at the given height, if any
+    pub fn get_decided_value(&self, height: Height) -> Option<&DecidedValue> {
+        self.decided_values.get(&height)
+    }
+
+    /// Returns the value proposed by us or received from a peer at the given height and round, if any
+    pub fn get_undecided_value(
+        &self,
+        height: Height,
+        round: Round,
+    ) -> Option<&ProposedValue<TestContext>> {
+        self.undecided_values.get(&(height, round))
+    }
+
+    /// Builds a new block to propose at the given height and round, out of made up transactions.
+    ///
+    /// A real application would instead take the transactions from its mempool.
+    pub fn propose_value(
+        &mut self,
+        height: Height,
+        round: Round,
+    ) -> LocallyProposedValue<TestContext> {
+        let txs = (0..self.rng.gen_range(1..=MAX_TXS))
+            .map(|_| self.make_tx())
+            .collect();
+
+        let block = Block::build(&self.kv, txs);
+        let value = block.to_value();
+
+        info!(
+            %height, %round,
+            txs = block.txs.len(),
+            state_hash = %block.state_hash,
+            "Built block"
+        );
+
+        let proposed_value = ProposedValue {
+            height,
+            round,
+            valid_round: Round::Nil,
+            proposer: self.address,
+            value: value.clone(),
+            validity: Validity::Valid,
+        };
+
+        self.undecided_values
+            .insert((height, round), proposed_value);
+
+        LocallyProposedValue::new(height, round, value)
+    }
+
+    fn make_tx(&mut self) -> Tx {
+        let key = format!("key-{}", self.rng.gen_range(0..KEYS));
+
+        if self.rng.gen_ratio(1, 4) {
+            Tx::Delete { key }
+        } else {
+            let value = format!("{:x}", self.rng.gen::<u32>());
+            Tx::Set { key, value }
+        }
+    }
+
+    /// Validates a value received from a peer, in a proposal or through sync,
+    /// and stores it if it is valid.
+    ///
+    /// The block carried by the value is executed on top of the state as of the last decided
+    /// height, and the value is only valid if this yields the state hash it carries.
+    pub fn received_value(
+        &mut self,
+        mut value: ProposedValue<TestContext>,
+    ) -> ProposedValue<TestContext> {
+        let result = validate_block(&self.kv, self.current_height, &value).map(|_| ());
+
+        value.validity = self.store_if_valid(&value, result);
+        value
+    }
+
+    /// Validates a batch of values received through sync, ordered by ascending height
+    /// starting at the current height, and stores the ones which are valid.
+    /// Values which could not be decoded are passed as `None`, and are returned as such.
+    ///
+    /// As no block of the batch is decided yet, each one is executed on top of the state
+    /// reached by executing the previous ones. The values following one which could not be
+    /// decoded or is invalid cannot be validated, and are thus deemed invalid as well.
+    pub fn received_synced_values(
+        &mut self,
+        values: Vec<Option<ProposedValue<TestContext>>>,
+    ) -> Vec<Option<ProposedValue<TestContext>>> {
+        let mut kv = self.kv.clone();
+        let mut expected_height = Some(self.current_height);
+
+        values
+            .into_iter()
+            .map(|value| {
+                let Some(mut value) = value else {
+                    expected_height = None;
+                    return None;
+                };
+
+                let result = match expected_height {
+                    Some(height) => validate_block(&kv, height, &value),
+                    None => Err(eyre!("the block of a previous height is invalid")),
+                };
+
+                expected_height = match &result {
+                    Ok(block) => {
+                        kv.apply(&block.txs);
+                        Some(value.height.increment())
+                    }
+                    Err(_) => None,
+                };
+
+                value.validity = self.store_if_valid(&value, result.map(|_| ()));
+                Some(value)
+            })
+            .collect()
+    }
+
+    fn store_if_valid(
+        &mut self,
+        value: &ProposedValue<TestContext>,
+        result: eyre::Result<()>,
+    ) -> Validity {
+        match result {
+            Ok(()) => {
+                self.undecided_values
+                    .insert((value.height, value.round), value.clone());
+
+                Validity::Valid
+            }
+            Err(e) => {
+                error!(
+                    height = %value.height,
+                    round = %value.round,
+                    proposer = %value.proposer,
+                    "Rejecting invalid block: {e}"
+                );
+
+                Validity::Invalid
+            }
+        }
+    }
+
+    /// Commits the value decided by consensus, executes its block, and moves on to the next height
+    pub fn commit(
+        &mut self,
+        certificate: CommitCertificate<TestContext>,
+    ) -> eyre::Result<StateHash> {
+        let height = certificate.height;
+
+        // The value may have been decided in a later round than the one it was first proposed in,
+        // in which case we only know it for that earlier round.
+        let value = self
+            .undecided_values
+            .values()
+            .map(|proposed| &proposed.value)
+            .find(|value| value.id() == certificate.value_id)
+            .cloned()
+            .ok_or_else(|| {
+                eyre!(
+                    "No value {} to commit at height {height}",
+                    certificate.value_id
+                )
+            })?;
+
+        // The value was validated before being stored, so its block always executes to the
+        // state hash it carries, and every node reaches the very same state at this height.
+        let block = Block::from_value(&value)?;
+        self.kv.apply(&block.txs);
+
+        self.decided_values
+            .insert(height, DecidedValue { value, certificate });
+
+        // Values for heights which have been decided are no longer needed
+        self.undecided_values.retain(|(h, _), _| *h > height);
+
+        self.current_height = height.increment();
+        self.current_round = Round::Nil;
+
+        Ok(block.state_hash)
+    }
+}
+
+/// Checks that the value carries a block for the given height which, executed on top
+/// of the given state, yields the state hash it carries, and returns that block.
+fn validate_block(
+    kv: &KvState,
+    height: Height,
+    value: &ProposedValue<TestContext>,
+) -> eyre::Result<Block> {
+    if value.height != height {
+        return Err(eyre!(
+            "expected a block for height {height}, got one for height {}",
+            value.height
+        ));
+    }
+
+    let block = Block::from_value(&value.value)?;
+    block.verify(kv)?;
+
+    Ok(block)
+}
+
+/// Encodes a value to its byte representation, ie. the encoded block it carries
+pub fn encode_value(value: &Value) -> Bytes {
+    value.extensions.clone()
+}
+
+/// Decodes a value from its byte representation, checking that it carries a well-formed block
+pub fn decode_value(bytes: Bytes) -> Option<Value> {
+    let block = Block::decode(&bytes).ok()?;
+    Some(block.to_value())
+}
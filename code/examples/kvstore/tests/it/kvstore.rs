@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use malachitebft_app_channel::app::consensus::SignedConsensusMsg;
+use malachitebft_app_channel::app::engine::util::events::Event;
+use malachitebft_app_channel::app::types::core::{NilOrVal, Proposal as _, Round};
+use malachitebft_example_kvstore::kv::Block;
+use malachitebft_test::middleware::{DefaultMiddleware, Middleware};
+use malachitebft_test::{Address, Height, TestContext, ValueId, Vote};
+
+use crate::{HandlerResult, TestBuilder, TestParams};
+
+/// Every value proposed or received must carry a well-formed block with at least one transaction
+fn check_proposal(event: Event<TestContext>) -> eyre::Result<()> {
+    let (Event::Published(SignedConsensusMsg::Proposal(proposal))
+    | Event::Received(SignedConsensusMsg::Proposal(proposal))) = event
+    else {
+        return Ok(());
+    };
+
+    let block = Block::from_value(proposal.value())?;
+
+    if block.txs.is_empty() {
+        eyre::bail!(
+            "Expected the block proposed at height {} to carry transactions",
+            proposal.height()
+        );
+    }
+
+    Ok(())
+}
+
+/// Validators propose blocks of transactions, which they all validate
+/// by executing them, and decide on every height.
+#[tokio::test]
+pub async fn decide_blocks() {
+    const HEIGHT: u64 = 5;
+
+    let mut test = TestBuilder::<()>::new();
+
+    for _ in 0..4 {
+        test.add_node()
+            .with_voting_power(10)
+            .start()
+            .on_event(|event, _| {
+                if let Event::Decided { commit_certificate } = &event {
+                    if commit_certificate.height == Height::new(HEIGHT) {
+                        return Ok(HandlerResult::ContinueTest);
+                    }
+                }
+
+                check_proposal(event)?;
+                Ok(HandlerResult::WaitForNextEvent)
+            })
+            .success();
+    }
+
+    test.build().run(Duration::from_secs(30)).await;
+}
+
+/// Precommits nil at height 2 and round 0, regardless of the polka seen for the proposed block.
+/// Height 1 is left alone, as its proposal may be published before the nodes are all connected.
+#[derive(Copy, Clone, Debug)]
+struct PrecommitNilInFirstRound;
+
+impl Middleware for PrecommitNilInFirstRound {
+    fn new_precommit(
+        &self,
+        ctx: &TestContext,
+        height: Height,
+        round: Round,
+        value_id: NilOrVal<ValueId>,
+        address: Address,
+    ) -> Vote {
+        let value_id = if height == Height::new(2) && round == Round::new(0) {
+            NilOrVal::Nil
+        } else {
+            value_id
+        };
+
+        DefaultMiddleware.new_precommit(ctx, height, round, value_id, address)
+    }
+}
+
+/// The block proposed in round 0 becomes the valid value of every validator, which the proposer
+/// of round 1 proposes again with round 0 as its POL round. Unlike with proposal parts, there is
+/// nothing to restream: the proposal of round 1 carries the whole block, which is decided in that round.
+#[tokio::test]
+pub async fn repropose_valid_block_in_later_round() {
+    const HEIGHT: u64 = 2;
+
+    // The id of the block proposed in round 0, recorded by each node
+    let mut test = TestBuilder::<Option<ValueId>>::new();
+
+    for _ in 0..4 {
+        test.add_node()
+            .with_voting_power(10)
+            .with_middleware(PrecommitNilInFirstRound)
+            .start()
+            .on_event(|event, proposed| {
+                check_proposal(event.clone())?;
+
+                let (Event::Published(SignedConsensusMsg::Proposal(proposal))
+                | Event::Received(SignedConsensusMsg::Proposal(proposal))) = event
+                else {
+                    return Ok(HandlerResult::WaitForNextEvent);
+                };
+
+                if proposal.height() != Height::new(HEIGHT) {
+                    return Ok(HandlerResult::WaitForNextEvent);
+                }
+
+                if proposal.round() == Round::new(0) {
+                    *proposed = Some(proposal.value().id());
+                    return Ok(HandlerResult::WaitForNextEvent);
+                }
+
+                if proposal.round() != Round::new(1) {
+                    return Ok(HandlerResult::WaitForNextEvent);
+                }
+
+                if proposal.pol_round() != Round::new(0) {
+                    eyre::bail!(
+                        "Expected the proposal of round 1 to have round 0 as its POL round, got {}",
+                        proposal.pol_round()
+                    );
+                }
+
+                if Some(proposal.value().id()) != *proposed {
+                    eyre::bail!("Expected the block of round 0 to be proposed again in round 1");
+                }
+
+                Ok(HandlerResult::ContinueTest)
+            })
+            .on_decided(|certificate, proposed| {
+                if certificate.height != Height::new(HEIGHT) {
+                    return Ok(HandlerResult::WaitForNextEvent);
+                }
+
+                if certificate.round != Round::new(1) {
+                    eyre::bail!(
+                        "Expected height {HEIGHT} to be decided in round 1, got {}",
+                        certificate.round
+                    );
+                }
+
+                if Some(certificate.value_id) != *proposed {
+                    eyre::bail!(
+                        "Expected the block of round 0 to be decided, got {}",
+                        certificate.value_id
+                    );
+                }
+
+                Ok(HandlerResult::ContinueTest)
+            })
+            .wait_until(HEIGHT + 2)
+            .success();
+    }
+
+    test.build().run(Duration::from_secs(30)).await;
+}
+
+/// A validator starting late gets the blocks decided in the meantime through value sync,
+/// executes them one after another, and then takes part in consensus on top of the same state.
+#[tokio::test]
+pub async fn start_late_sync_blocks() {
+    const HEIGHT: u64 = 5;
+
+    let mut test = TestBuilder::<()>::new();
+
+    for _ in 0..3 {
+        test.add_node()
+            .with_voting_power(10)
+            .start()
+            .wait_until(HEIGHT * 2)
+            .success();
+    }
+
+    test.add_node()
+        .with_voting_power(5)
+        .start_after(1, Duration::from_secs(5))
+        .wait_until(HEIGHT * 2)
+        .success();
+
+    test.build()
+        .run_with_params(
+            Duration::from_secs(30),
+            TestParams {
+                enable_value_sync: true,
+                ..Default::default()
+            },
+        )
+        .await
+}
+
+/// Same as [`start_late_sync_blocks`], with the synced values processed in batches,
+/// in which each block is executed on top of the state reached by executing the previous ones.
+#[tokio::test]
+pub async fn start_late_sync_blocks_in_batches() {
+    const HEIGHT: u64 = 5;
+
+    let mut test = TestBuilder::<()>::new();
+
+    for _ in 0..3 {
+        test.add_node()
+            .with_voting_power(10)
+            .start()
+            .wait_until(HEIGHT * 2)
+            .success();
+    }
+
+    test.add_node()
+        .with_voting_power(5)
+        .start_after(1, Duration::from_secs(5))
+        .wait_until(HEIGHT * 2)
+        .success();
+
+    test.build()
+        .run_with_params(
+            Duration::from_secs(30),
+            TestParams {
+                enable_value_sync: true,
+                batch_size: 5,
+                batch_synced_values: true,
+                ..Default::default()
+            },
+        )
+        .await
+}
@@ -0,0 +1,44 @@
+mod kvstore;
+
+use async_trait::async_trait;
+
+use malachitebft_app_channel::app::config::ValuePayload;
+use malachitebft_example_kvstore::node::{App, Handle};
+use malachitebft_test::TestContext;
+use malachitebft_test_app::config::Config;
+use malachitebft_test_framework::{ExampleApp, ExampleNode, ExampleRunner, HasTestRunner};
+
+pub use malachitebft_test_framework::{HandlerResult, NodeId, TestParams};
+
+pub type TestBuilder<S> = malachitebft_test_framework::TestBuilder<TestContext, S>;
+
+pub struct KvStore;
+
+#[async_trait]
+impl ExampleApp for KvStore {
+    type Handle = Handle;
+
+    const NAME: &'static str = "kvstore";
+
+    async fn start(node: ExampleNode) -> eyre::Result<Handle> {
+        let app = App {
+            home_dir: node.home_dir,
+            config: node.config,
+            genesis: node.genesis,
+            private_key: node.private_key,
+            start_height: node.start_height,
+            middleware: Some(node.middleware),
+        };
+
+        app.start().await
+    }
+
+    // The application sends the whole value along with the proposal
+    fn configure(config: &mut Config) {
+        config.consensus.value_payload = ValuePayload::ProposalOnly;
+    }
+}
+
+impl HasTestRunner<KvStore> for TestContext {
+    type Runner = ExampleRunner<KvStore>;
+}
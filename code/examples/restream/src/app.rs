@@ -0,0 +1,354 @@
+use tracing::{debug, error, info, warn};
+
+use malachitebft_app_channel::app::engine::host::{HeightParams, Next, SyncedValueOutcome};
+use malachitebft_app_channel::app::types::core::utils::height::HeightRangeExt;
+use malachitebft_app_channel::app::types::core::{Round, Validity};
+use malachitebft_app_channel::app::types::sync::RawDecidedValue;
+use malachitebft_app_channel::app::types::{LocallyProposedValue, ProposedValue};
+use malachitebft_app_channel::{AppMsg, Channels, NetworkMsg};
+use malachitebft_test::{Height, TestContext};
+
+use crate::state::{decode_value, encode_value, State};
+
+/// Returns the parameters of consensus for the given height
+fn height_params(state: &State, height: Height) -> HeightParams<TestContext> {
+    HeightParams::new(
+        state.get_validator_set(height),
+        state.get_timeouts(height),
+        None,
+    )
+}
+
+/// Reports a value received through sync as invalid if it did not pass validation
+fn synced_value_outcome(value: ProposedValue<TestContext>) -> SyncedValueOutcome<TestContext> {
+    if value.validity.is_valid() {
+        SyncedValueOutcome::Valid(value)
+    } else {
+        SyncedValueOutcome::Invalid {
+            reason: "invalid value".to_string(),
+        }
+    }
+}
+
+pub async fn run(state: &mut State, channels: &mut Channels<TestContext>) -> eyre::Result<()> {
+    while let Some(msg) = channels.consensus.recv().await {
+        match msg {
+            AppMsg::ConsensusReady { reply } => {
+                let start_height = state.next_height();
+                state.current_height = start_height;
+
+                info!(%start_height, "Consensus is ready");
+
+                if reply
+                    .send((start_height, height_params(state, start_height)))
+                    .is_err()
+                {
+                    error!("Failed to send ConsensusReady reply");
+                }
+            }
+
+            AppMsg::StartedRound {
+                height,
+                round,
+                proposer,
+                role,
+                reply_value,
+            } => {
+                info!(%height, %round, %proposer, ?role, "Started round");
+
+                state.current_height = height;
+                state.current_round = round;
+
+                // Validate the parts we received for this height before reaching it,
+                // and send back the values they carry along with any value we already
+                // have for this round, eg. because we proposed it before restarting.
+                let mut values = state.take_pending_values();
+                values.extend(state.get_undecided_value(height, round).cloned());
+
+                if reply_value.send(values).is_err() {
+                    error!("Failed to send undecided values");
+                }
+            }
+
+            AppMsg::GetValue {
+                height,
+                round,
+                timeout: _,
+                reply,
+            } => {
+                info!(%height, %round, "Consensus is requesting a value to propose");
+
+                // If we have already built a value for this round, we must propose the very same value,
+                // otherwise we build a new one. The POL round of that value is always nil: when a value
+                // must be proposed again in a later round, consensus does not ask for a new value but
+                // asks us to restream the existing one instead, see `RestreamProposal` below.
+                let previous = state
+                    .get_undecided_value(height, round)
+                    .filter(|value| value.proposer == state.address)
+                    .map(|value| value.value.clone());
+
+                let (value, stream) = match previous {
+                    Some(value) => {
+                        let parts = state
+                            .get_undecided_parts(height, value.id())
+                            .expect("the parts of our own values are always stored");
+
+                        let stream = state.stream_messages(height, round, parts);
+                        (LocallyProposedValue::new(height, round, value), stream)
+                    }
+                    None => state.propose_value(height, round),
+                };
+
+                if reply.send(value).is_err() {
+                    error!("Failed to send GetValue reply");
+                }
+
+                for msg in stream {
+                    debug!(%height, %round, "Streaming proposal part: {msg:?}");
+
+                    channels
+                        .network
+                        .send(NetworkMsg::PublishProposalPart(msg))
+                        .await?;
+                }
+            }
+
+            // When we are the proposer of a round in which the valid value of a previous round
+            // must be proposed again, consensus proposes that value with the round in which
+            // a polka was seen for it as its POL round, and asks us to restream its parts.
+            //
+            // The value may have been first proposed by another validator, in which case
+            // `round` and `address` are the ones of the proposal being made, while the parts
+            // we stored for the value carry the round and proposer of the original proposal,
+            // together with the signature of that proposer. We replay these parts verbatim
+            // in a new stream, as we could not sign them on behalf of the original proposer.
+            // Peers which missed the original stream assemble the value for `valid_round`
+            // from these parts, which consensus then matches with the proposal for `round`.
+            AppMsg::RestreamProposal {
+                height,
+                round,
+                valid_round,
+                address,
+                value_id,
+            } => {
+                let Some(parts) = state.get_undecided_parts(height, value_id) else {
+                    warn!(%height, %round, %valid_round, %value_id, "No parts to restream for value");
+                    continue;
+                };
+
+                info!(
+                    %height, %round, %valid_round, %address, %value_id,
+                    original_round = %parts.round,
+                    original_proposer = %parts.proposer,
+                    "Restreaming proposal"
+                );
+
+                for msg in state.stream_messages(height, round, parts) {
+                    debug!(%height, %round, "Restreaming proposal part: {msg:?}");
+
+                    channels
+                        .network
+                        .send(NetworkMsg::PublishProposalPart(msg))
+                        .await?;
+                }
+            }
+
+            AppMsg::CancelGetValue { height, round } => {
+                warn!(%height, %round, "Consensus cancelled the request for a value to propose");
+            }
+
+            AppMsg::ReceivedProposalPart { from, part, reply } => {
+                debug!(%from, %part.sequence, "Received proposal part");
+
+                let value = state.received_proposal_part(from, part);
+
+                if reply.send(value).is_err() {
+                    error!("Failed to send ReceivedProposalPart reply");
+                }
+            }
+
+            // Only sent in proposal-only mode, in which there are no parts to restream
+            AppMsg::ReceivedProposal {
+                height,
+                round,
+                valid_round,
+                proposer,
+                value,
+                reply,
+            } => {
+                let value = state.received_value(ProposedValue {
+                    height,
+                    round,
+                    valid_round,
+                    proposer,
+                    value,
+                    validity: Validity::Valid,
+                });
+
+                if reply.send(value).is_err() {
+                    error!("Failed to send ReceivedProposal reply");
+                }
+            }
+
+            AppMsg::Decided {
+                certificate, reply, ..
+            } => {
+                info!(
+                    height = %certificate.height,
+                    round = %certificate.round,
+                    value = %certificate.value_id,
+                    "Consensus has decided on value"
+                );
+
+                if reply.send(()).is_err() {
+                    error!("Failed to send Decided reply");
+                }
+            }
+
+            AppMsg::Finalized {
+                certificate, reply, ..
+            } => {
+                let height = certificate.height;
+
+                let next = match state.commit(certificate) {
+                    Ok(()) => Next::Start(
+                        state.current_height,
+                        height_params(state, state.current_height),
+                    ),
+                    Err(e) => {
+                        error!(%height, "Failed to commit decided value, restarting height: {e}");
+                        Next::Restart(height, height_params(state, height))
+                    }
+                };
+
+                if reply.send(next).is_err() {
+                    error!("Failed to send Finalized reply");
+                }
+            }
+
+            AppMsg::ProcessSyncedValue {
+                height,
+                round,
+                proposer,
+                value_bytes,
+                reply,
+            } => {
+                info!(%height, %round, "Processing synced value");
+
+                let outcome = decode_value(value_bytes).map(|value| {
+                    synced_value_outcome(state.received_value(ProposedValue {
+                        height,
+                        round,
+                        valid_round: Round::Nil,
+                        proposer,
+                        value,
+                        validity: Validity::Valid,
+                    }))
+                });
+
+                if reply.send(outcome).is_err() {
+                    error!("Failed to send ProcessSyncedValue reply");
+                }
+            }
+
+            AppMsg::ProcessSyncedValues { values, reply } => {
+                let outcomes = values
+                    .into_iter()
+                    .map(|raw| {
+                        let height = raw.certificate.height;
+                        let round = raw.certificate.round;
+
+                        let proposer = state
+                            .ctx
+                            .select_proposer(&state.get_validator_set(height), height, round)
+                            .address;
+
+                        decode_value(raw.value_bytes).map(|value| {
+                            synced_value_outcome(state.received_value(ProposedValue {
+                                height,
+                                round,
+                                valid_round: Round::Nil,
+                                proposer,
+                                value,
+                                validity: Validity::Valid,
+                            }))
+                        })
+                    })
+                    .collect();
+
+                if reply.send(outcomes).is_err() {
+                    error!("Failed to send ProcessSyncedValues reply");
+                }
+            }
+
+            AppMsg::GetDecidedValues { range, reply } => {
+                let values = range
+                    .iter_heights()
+                    .filter_map(|height| state.get_decided_value(height))
+                    .map(|decided| RawDecidedValue {
+                        certificate: decided.certificate.clone(),
+                        value_bytes: encode_value(&decided.value),
+                    })
+                    .collect();
+
+                if reply.send(values).is_err() {
+                    error!("Failed to send GetDecidedValues reply");
+                }
+            }
+
+            // We keep all decided values, so there is never anything to backfill
+            AppMsg::ProcessBackfilledValues { reply, .. } => {
+                if reply.send(false).is_err() {
+                    error!("Failed to send ProcessBackfilledValues reply");
+                }
+            }
+
+            AppMsg::GetHistoryMinHeight { reply } => {
+                if reply.send(state.earliest_height()).is_err() {
+                    error!("Failed to send GetHistoryMinHeight reply");
+                }
+            }
+
+            AppMsg::GetTimeoutOverrides { reply, .. } => {
+                if reply.send(Default::default()).is_err() {
+                    error!("Failed to send GetTimeoutOverrides reply");
+                }
+            }
+
+            AppMsg::GetConsensusParams { reply, .. } => {
+                if reply.send(Default::default()).is_err() {
+                    error!("Failed to send GetConsensusParams reply");
+                }
+            }
+
+            AppMsg::RoundAlert {
+                height,
+                round,
+                halted,
+            } => {
+                warn!(%height, %round, %halted, "Height is going through too many rounds");
+            }
+
+            AppMsg::Prune { retain_height } => {
+                debug!(%retain_height, "Decided values below the retain height can be pruned");
+            }
+
+            AppMsg::ExtendVote { reply, .. } => {
+                if reply.send(None).is_err() {
+                    error!("Failed to send ExtendVote reply");
+                }
+            }
+
+            AppMsg::VerifyVoteExtension { reply, .. } => {
+                if reply.send(Ok(())).is_err() {
+                    error!("Failed to send VerifyVoteExtension reply");
+                }
+            }
+        }
+    }
+
+    // If we get there, it can only be because the channel we use to receive message
+    // from consensus has been closed, meaning that the consensus actor has died.
+    // We can do nothing but return an error here.
+    Err(eyre::eyre!("Consensus channel closed unexpectedly"))
+}
@@ -0,0 +1,33 @@
+//! Example application running consensus with [`ValuePayload::ProposalAndParts`],
+//! showing how to handle [`AppMsg::RestreamProposal`].
+//!
+//! When running with proposal parts, the proposer of a round streams the value it proposes
+//! as a sequence of parts, signed by itself, alongside the proposal message. If the validators
+//! see a polka for that value but fail to decide it in that round, the value becomes the valid
+//! value of the round, and the proposer of any later round must propose it again, with the
+//! round of the polka as its POL round (see L15/L18 of the Tendermint algorithm).
+//!
+//! Consensus then asks the application to restream the value with [`AppMsg::RestreamProposal`],
+//! so that the validators which missed the original parts can still get the value.
+//! The parts of the value MUST be replayed exactly as they were signed by their original
+//! proposer, which may be another validator than the one restreaming them:
+//!
+//! - the `Init` part keeps the height, round and proposer of the round in which the value
+//!   was first proposed, which is the POL round of the new proposal,
+//! - the `Fin` part keeps the signature of the original proposer over the parts,
+//!   which the restreaming validator could not produce itself.
+//!
+//! Only the stream itself is new, so that peers do not mistake the restreamed parts for
+//! duplicates of the original stream. On the receiving end, the restreamed parts are assembled
+//! and validated as any other, yielding the value for the POL round, which consensus then
+//! matches with the proposal of the later round.
+//!
+//! See [`app::run`] for the handling of [`AppMsg::RestreamProposal`], and the integration tests
+//! of this crate for a scenario where validators must restream a value from a previous round.
+//!
+//! [`ValuePayload::ProposalAndParts`]: malachitebft_app_channel::app::config::ValuePayload::ProposalAndParts
+//! [`AppMsg::RestreamProposal`]: malachitebft_app_channel::AppMsg::RestreamProposal
+
+pub mod app;
+pub mod node;
+pub mod state;
@@ -0,0 +1,439 @@
+//! Internal state of the application.
+//!
+//! Values are kept in memory, as this example focuses on how proposal parts are streamed
+//! and restreamed rather than on how they are persisted. A real application would store
+//! them in a database, so that they survive a restart.
+
+use std::collections::BTreeMap;
+
+use bytes::Bytes;
+use eyre::eyre;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sha3::Digest;
+use tracing::{debug, error, info};
+
+use malachitebft_app_channel::app::streaming::{StreamContent, StreamId, StreamMessage};
+use malachitebft_app_channel::app::types::codec::Codec;
+use malachitebft_app_channel::app::types::core::{CommitCertificate, Round, Validity};
+use malachitebft_app_channel::app::types::{LocallyProposedValue, PeerId, ProposedValue};
+use malachitebft_test::codec::proto::ProtobufCodec;
+use malachitebft_test::{
+    Address, Ed25519Signer, Genesis, Height, LinearTimeouts, ProposalData, ProposalFin,
+    ProposalInit, ProposalPart, TestContext, ValidatorSet, Value, ValueId,
+};
+use malachitebft_test_streaming::{PartStreamsMap, ProposalParts};
+
+/// A value decided by consensus, together with its commit certificate
+#[derive(Clone, Debug)]
+pub struct DecidedValue {
+    pub value: Value,
+    pub certificate: CommitCertificate<TestContext>,
+}
+
+/// Internal state of the application node
+pub struct State {
+    pub ctx: TestContext,
+    pub genesis: Genesis,
+    pub address: Address,
+    pub current_height: Height,
+    pub current_round: Round,
+
+    signer: Ed25519Signer,
+    streams_map: PartStreamsMap,
+    rng: StdRng,
+
+    /// Values proposed by us or received from peers, by height and round
+    undecided_values: BTreeMap<(Height, Round), ProposedValue<TestContext>>,
+
+    /// Parts of these values, exactly as signed by their original proposer, by height and value id.
+    ///
+    /// These are kept across rounds, so that they can be restreamed
+    /// when the value is proposed again in a later round.
+    undecided_parts: BTreeMap<(Height, ValueId), ProposalParts>,
+
+    /// Parts received for a future height, validated once we reach that height
+    pending_parts: Vec<ProposalParts>,
+
+    /// Values decided so far, by height
+    decided_values: BTreeMap<Height, DecidedValue>,
+}
+
+impl State {
+    /// Creates a new state for the validator with the given address, starting at the given height
+    pub fn new(
+        ctx: TestContext,
+        genesis: Genesis,
+        address: Address,
+        height: Height,
+        signer: Ed25519Signer,
+    ) -> Self {
+        Self {
+            ctx,
+            genesis,
+            address,
+            current_height: height,
+            current_round: Round::Nil,
+            signer,
+            streams_map: PartStreamsMap::new(),
+            rng: StdRng::from_entropy(),
+            undecided_values: BTreeMap::new(),
+            undecided_parts: BTreeMap::new(),
+            pending_parts: Vec::new(),
+            decided_values: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the set of validators for the given height
+    pub fn get_validator_set(&self, height: Height) -> ValidatorSet {
+        self.ctx
+            .middleware()
+            .get_validator_set(&self.ctx, self.current_height, height, &self.genesis)
+            .unwrap_or_else(|| self.genesis.validator_set.clone())
+    }
+
+    /// Returns the timeouts for the given height
+    pub fn get_timeouts(&self, height: Height) -> LinearTimeouts {
+        self.ctx
+            .middleware()
+            .get_timeouts(&self.ctx, self.current_height, height)
+            .unwrap_or_default()
+    }
+
+    /// Returns the height following the last decided one, or the current height if none was decided yet
+    pub fn next_height(&self) -> Height {
+        self.decided_values
+            .last_key_value()
+            .map(|(height, _)| height.increment())
+            .unwrap_or(self.current_height)
+    }
+
+    /// Returns the earliest height for which we have a decided value
+    pub fn earliest_height(&self) -> Height {
+        self.decided_values
+            .first_key_value()
+            .map(|(height, _)| *height)
+            .unwrap_or_default()
+    }
+
+    /// Returns the value decided at the given height, if any
+    pub fn get_decided_value(&self, height: Height) -> Option<&DecidedValue> {
+        self.decided_values.get(&height)
+    }
+
+    /// Returns the value proposed by us or received from a peer at the given height and round, if any
+    pub fn get_undecided_value(
+        &self,
+        height: Height,
+        round: Round,
+    ) -> Option<&ProposedValue<TestContext>> {
+        self.undecided_values.get(&(height, round))
+    }
+
+    /// Returns the parts of the value with the given id, as signed by its original proposer
+    pub fn get_undecided_parts(&self, height: Height, value_id: ValueId) -> Option<&ProposalParts> {
+        self.undecided_parts.get(&(height, value_id))
+    }
+
+    /// Validates the parts received for the current height before we reached it,
+    /// and returns the values they carry.
+    pub fn take_pending_values(&mut self) -> Vec<ProposedValue<TestContext>> {
+        let (current, pending) = std::mem::take(&mut self.pending_parts)
+            .into_iter()
+            .filter(|parts| parts.height >= self.current_height)
+            .partition::<Vec<_>, _>(|parts| parts.height == self.current_height);
+
+        self.pending_parts = pending;
+
+        current
+            .into_iter()
+            .filter_map(|parts| self.store_parts(parts))
+            .collect()
+    }
+
+    /// Makes up a new value to propose at the given height and round,
+    /// and returns it together with the stream of its parts.
+    pub fn propose_value(
+        &mut self,
+        height: Height,
+        round: Round,
+    ) -> (
+        LocallyProposedValue<TestContext>,
+        Vec<StreamMessage<ProposalPart>>,
+    ) {
+        let value = Value::new(self.rng.gen_range(100..=100000));
+
+        // The POL round of a newly built value is always nil, as nobody has seen a polka for it yet.
+        let parts = self.build_parts(height, round, Round::Nil, &value);
+        let messages = self.stream_messages(height, round, &parts);
+
+        let proposed_value = ProposedValue {
+            height,
+            round,
+            valid_round: Round::Nil,
+            proposer: self.address,
+            value: value.clone(),
+            validity: Validity::Valid,
+        };
+
+        self.undecided_values
+            .insert((height, round), proposed_value);
+        self.undecided_parts.insert((height, value.id()), parts);
+
+        (LocallyProposedValue::new(height, round, value), messages)
+    }
+
+    /// Wraps the given parts into a new stream for the given height and round.
+    ///
+    /// The parts themselves are left untouched, so that they can be replayed as-is,
+    /// with their original `Init` part and signature, when restreaming a value.
+    pub fn stream_messages(
+        &self,
+        height: Height,
+        round: Round,
+        parts: &ProposalParts,
+    ) -> Vec<StreamMessage<ProposalPart>> {
+        let stream_id = stream_id(height, round, self.address);
+
+        parts
+            .parts
+            .iter()
+            .cloned()
+            .map(StreamContent::Data)
+            .chain([StreamContent::Fin])
+            .enumerate()
+            .map(|(sequence, content)| {
+                StreamMessage::new(stream_id.clone(), sequence as u64, content)
+            })
+            .collect()
+    }
+
+    /// Processes a part received from a peer, and returns the value
+    /// it carries if it completes a valid proposal for the current height.
+    pub fn received_proposal_part(
+        &mut self,
+        from: PeerId,
+        part: StreamMessage<ProposalPart>,
+    ) -> Option<ProposedValue<TestContext>> {
+        let parts = self.streams_map.insert(from, part)?;
+
+        if parts.height < self.current_height {
+            debug!(%parts.height, %parts.round, "Received outdated proposal, ignoring");
+            return None;
+        }
+
+        if parts.height > self.current_height {
+            debug!(%parts.height, %parts.round, "Received proposal for a future height, keeping it for later");
+            self.pending_parts.push(parts);
+            return None;
+        }
+
+        self.store_parts(parts)
+    }
+
+    /// Validates the given parts, and stores them together with the value they carry.
+    ///
+    /// The parts of a restreamed value are validated against the round in which the value
+    /// was first proposed, as recorded in their `Init` part, which yields the value for that
+    /// round. Consensus then matches it with the proposal of the round being played,
+    /// whose POL round is the round of that value.
+    fn store_parts(&mut self, parts: ProposalParts) -> Option<ProposedValue<TestContext>> {
+        if let Err(e) = self.validate_parts(&parts) {
+            error!(%parts.height, %parts.round, %parts.proposer, "Rejecting invalid proposal: {e}");
+            return None;
+        }
+
+        let init = parts.init()?;
+
+        let value = parts
+            .parts
+            .iter()
+            .filter_map(|part| part.as_data())
+            .fold(0u64, |value, data| value.saturating_add(data.factor));
+
+        let proposed_value: ProposedValue<TestContext> = ProposedValue {
+            height: parts.height,
+            round: parts.round,
+            valid_round: init.pol_round,
+            proposer: parts.proposer,
+            value: Value::new(value),
+            validity: self.ctx.middleware().get_validity(
+                &self.ctx,
+                parts.height,
+                parts.round,
+                &Value::new(value),
+            ),
+        };
+
+        info!(
+            height = %proposed_value.height,
+            round = %proposed_value.round,
+            proposer = %proposed_value.proposer,
+            value = %proposed_value.value.id(),
+            "Received complete proposal"
+        );
+
+        self.undecided_values
+            .insert((parts.height, parts.round), proposed_value.clone());
+        self.undecided_parts
+            .insert((parts.height, proposed_value.value.id()), parts);
+
+        Some(proposed_value)
+    }
+
+    /// Checks that the parts were signed by the proposer of the round they were proposed in
+    fn validate_parts(&self, parts: &ProposalParts) -> eyre::Result<()> {
+        if !parts.round.is_defined() {
+            return Err(eyre!("undefined round"));
+        }
+
+        let validator_set = self.get_validator_set(parts.height);
+
+        let expected = self
+            .ctx
+            .select_proposer(&validator_set, parts.height, parts.round);
+
+        if parts.proposer != expected.address {
+            return Err(eyre!(
+                "wrong proposer, expected {} but got {}",
+                expected.address,
+                parts.proposer
+            ));
+        }
+
+        let init = parts.init().ok_or_else(|| eyre!("missing Init part"))?;
+        let fin = parts.fin().ok_or_else(|| eyre!("missing Fin part"))?;
+
+        let hash = hash_parts(init, parts.parts.iter().filter_map(|part| part.as_data()));
+
+        if !Ed25519Signer::verify(&hash, &fin.signature, &expected.public_key) {
+            return Err(eyre!("invalid signature"));
+        }
+
+        Ok(())
+    }
+
+    /// Validates a value received in full, in a proposal or through sync,
+    /// as a value assembled from proposal parts would be, and stores it if it is valid.
+    pub fn received_value(
+        &mut self,
+        mut value: ProposedValue<TestContext>,
+    ) -> ProposedValue<TestContext> {
+        value.validity =
+            self.ctx
+                .middleware()
+                .get_validity(&self.ctx, value.height, value.round, &value.value);
+
+        if value.validity.is_valid() {
+            self.undecided_values
+                .insert((value.height, value.round), value.clone());
+        }
+
+        value
+    }
+
+    /// Commits the value decided by consensus, and moves on to the next height
+    pub fn commit(&mut self, certificate: CommitCertificate<TestContext>) -> eyre::Result<()> {
+        let height = certificate.height;
+
+        // The value may have been decided in a later round than the one it was first proposed in,
+        // in which case we only know it for that earlier round.
+        let value = self
+            .undecided_values
+            .values()
+            .map(|proposed| &proposed.value)
+            .find(|value| value.id() == certificate.value_id)
+            .cloned()
+            .ok_or_else(|| {
+                eyre!(
+                    "No value {} to commit at height {height}",
+                    certificate.value_id
+                )
+            })?;
+
+        self.decided_values
+            .insert(height, DecidedValue { value, certificate });
+
+        // Values and parts for heights which have been decided are no longer needed
+        self.undecided_values.retain(|(h, _), _| *h > height);
+        self.undecided_parts.retain(|(h, _), _| *h > height);
+
+        self.current_height = height.increment();
+        self.current_round = Round::Nil;
+
+        Ok(())
+    }
+
+    /// Signs the given value as proposed by us at the given height and round
+    fn build_parts(
+        &self,
+        height: Height,
+        round: Round,
+        pol_round: Round,
+        value: &Value,
+    ) -> ProposalParts {
+        let init = ProposalInit::new(height, round, pol_round, self.address);
+
+        // A real application would split the value into chunks of a bounded size,
+        // here we split it into two parts which add up to the value.
+        let data = [value.value / 2, value.value - value.value / 2].map(ProposalData::new);
+
+        let signature = self.signer.sign(&hash_parts(&init, &data));
+
+        let parts = [ProposalPart::Init(init)]
+            .into_iter()
+            .chain(data.into_iter().map(ProposalPart::Data))
+            .chain([ProposalPart::Fin(ProposalFin::new(signature))])
+            .collect();
+
+        ProposalParts {
+            height,
+            round,
+            proposer: self.address,
+            parts,
+        }
+    }
+}
+
+/// Hash of the parts of a proposal, signed by its proposer
+fn hash_parts<'a>(
+    init: &ProposalInit,
+    data: impl IntoIterator<Item = &'a ProposalData>,
+) -> Vec<u8> {
+    let mut hasher = sha3::Keccak256::new();
+
+    hasher.update(init.height.as_u64().to_be_bytes());
+    hasher.update(init.round.as_i64().to_be_bytes());
+    hasher.update(init.pol_round.as_i64().to_be_bytes());
+    hasher.update(init.proposer.into_inner());
+
+    for data in data {
+        hasher.update(data.factor.to_be_bytes());
+    }
+
+    hasher.finalize().to_vec()
+}
+
+/// Identifier of the stream of parts published by the given validator at the given height and round.
+///
+/// Restreaming a value starts a new stream, even when replaying the parts of a value
+/// first streamed by another validator in an earlier round.
+fn stream_id(height: Height, round: Round, address: Address) -> StreamId {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&height.as_u64().to_be_bytes());
+    bytes.extend_from_slice(&round.as_i64().to_be_bytes());
+    bytes.extend_from_slice(&address.into_inner());
+    StreamId::new(bytes.into())
+}
+
+/// Encodes a value to its byte representation
+pub fn encode_value(value: &Value) -> Bytes {
+    ProtobufCodec
+        .encode(value)
+        .expect("encoding a Value (u64 wrapper) should never fail")
+}
+
+/// Decodes a value from its byte representation
+pub fn decode_value(bytes: Bytes) -> Option<Value> {
+    ProtobufCodec.decode(bytes).ok()
+}
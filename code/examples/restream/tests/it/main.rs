@@ -0,0 +1,37 @@
+mod restream;
+
+use async_trait::async_trait;
+
+use malachitebft_example_restream::node::{App, Handle};
+use malachitebft_test::TestContext;
+use malachitebft_test_framework::{ExampleApp, ExampleNode, ExampleRunner, HasTestRunner};
+
+pub use malachitebft_test_framework::{HandlerResult, NodeId, TestParams};
+
+pub type TestBuilder<S> = malachitebft_test_framework::TestBuilder<TestContext, S>;
+
+pub struct Restream;
+
+#[async_trait]
+impl ExampleApp for Restream {
+    type Handle = Handle;
+
+    const NAME: &'static str = "restream";
+
+    async fn start(node: ExampleNode) -> eyre::Result<Handle> {
+        let app = App {
+            home_dir: node.home_dir,
+            config: node.config,
+            genesis: node.genesis,
+            private_key: node.private_key,
+            start_height: node.start_height,
+            middleware: Some(node.middleware),
+        };
+
+        app.start().await
+    }
+}
+
+impl HasTestRunner<Restream> for TestContext {
+    type Runner = ExampleRunner<Restream>;
+}
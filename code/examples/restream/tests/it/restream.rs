@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use malachitebft_app_channel::app::consensus::SignedConsensusMsg;
+use malachitebft_app_channel::app::engine::util::events::Event;
+use malachitebft_app_channel::app::types::core::{NilOrVal, Proposal as _, Round};
+use malachitebft_test::middleware::{DefaultMiddleware, Middleware};
+use malachitebft_test::{Address, Height, TestContext, ValueId, Vote};
+
+use crate::{HandlerResult, TestBuilder};
+
+/// Precommits nil at height 2 and round 0, regardless of the polka seen for the proposed value.
+/// Height 1 is left alone, as its proposal may be published before the nodes are all connected.
+///
+/// As no value can be decided in that round, the value proposed in it becomes the valid value
+/// of every validator, which the proposer of round 1 must then propose again, with round 0 as
+/// its POL round, by restreaming the parts of that value.
+#[derive(Copy, Clone, Debug)]
+struct PrecommitNilInFirstRound;
+
+impl Middleware for PrecommitNilInFirstRound {
+    fn new_precommit(
+        &self,
+        ctx: &TestContext,
+        height: Height,
+        round: Round,
+        value_id: NilOrVal<ValueId>,
+        address: Address,
+    ) -> Vote {
+        let value_id = if height == Height::new(2) && round == Round::new(0) {
+            NilOrVal::Nil
+        } else {
+            value_id
+        };
+
+        DefaultMiddleware.new_precommit(ctx, height, round, value_id, address)
+    }
+}
+
+/// Every validator must decide the value proposed in round 0 in round 1, where it is proposed
+/// again with round 0 as its POL round, by a validator which restreams the parts signed by the
+/// proposer of round 0.
+#[tokio::test]
+pub async fn restream_valid_value_from_previous_round() {
+    const HEIGHT: u64 = 2;
+
+    // The id of the value proposed again in round 1, recorded by each node
+    let mut test = TestBuilder::<Option<ValueId>>::new();
+
+    for _ in 0..4 {
+        test.add_node()
+            .with_voting_power(10)
+            .with_middleware(PrecommitNilInFirstRound)
+            .start()
+            .on_event(|event, reproposed| {
+                let (Event::Published(SignedConsensusMsg::Proposal(proposal))
+                | Event::Received(SignedConsensusMsg::Proposal(proposal))) = event
+                else {
+                    return Ok(HandlerResult::WaitForNextEvent);
+                };
+
+                if proposal.height() != Height::new(HEIGHT) || proposal.round() != Round::new(1) {
+                    return Ok(HandlerResult::WaitForNextEvent);
+                }
+
+                if proposal.pol_round() != Round::new(0) {
+                    eyre::bail!(
+                        "Expected the proposal of round 1 to have round 0 as its POL round, got {}",
+                        proposal.pol_round()
+                    );
+                }
+
+                *reproposed = Some(proposal.value().id());
+                Ok(HandlerResult::ContinueTest)
+            })
+            .on_decided(|certificate, reproposed| {
+                if certificate.height != Height::new(HEIGHT) {
+                    return Ok(HandlerResult::WaitForNextEvent);
+                }
+
+                if certificate.round != Round::new(1) {
+                    eyre::bail!(
+                        "Expected height {HEIGHT} to be decided in round 1, got {}",
+                        certificate.round
+                    );
+                }
+
+                if Some(certificate.value_id) != *reproposed {
+                    eyre::bail!(
+                        "Expected the value proposed again in round 1 to be decided, got {}",
+                        certificate.value_id
+                    );
+                }
+
+                Ok(HandlerResult::ContinueTest)
+            })
+            .wait_until(HEIGHT + 2)
+            .success();
+    }
+
+    test.build().run(Duration::from_secs(30)).await;
+}
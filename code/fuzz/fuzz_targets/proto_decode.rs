@@ -0,0 +1,42 @@
+//! Decode arbitrary bytes as each Protobuf message of the test context.
+//!
+//! Decoding may fail, but must never panic.
+
+#![no_main]
+
+use bytes::Bytes;
+use libfuzzer_sys::fuzz_target;
+
+use malachitebft_codec::Codec;
+use malachitebft_core_consensus::{LivenessMsg, ProposedValue, SignedConsensusMsg};
+use malachitebft_core_types::ValidatorProof;
+use malachitebft_engine::util::streaming::StreamMessage;
+use malachitebft_proto::Protobuf;
+use malachitebft_sync::{RawDecidedValue, Request, Response, Status};
+use malachitebft_test::codec::proto::ProtobufCodec;
+use malachitebft_test::{
+    Address, Height, Proposal, ProposalPart, TestContext, Value, ValueId, Vote,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let codec = ProtobufCodec;
+    let bytes = Bytes::copy_from_slice(data);
+
+    let _ = Codec::<SignedConsensusMsg<TestContext>>::decode(&codec, bytes.clone());
+    let _ = Codec::<StreamMessage<ProposalPart>>::decode(&codec, bytes.clone());
+    let _ = Codec::<Status<TestContext>>::decode(&codec, bytes.clone());
+    let _ = Codec::<Request<TestContext>>::decode(&codec, bytes.clone());
+    let _ = Codec::<Response<TestContext>>::decode(&codec, bytes.clone());
+    let _ = Codec::<LivenessMsg<TestContext>>::decode(&codec, bytes.clone());
+    let _ = Codec::<ProposedValue<TestContext>>::decode(&codec, bytes.clone());
+    let _ = Codec::<RawDecidedValue<TestContext>>::decode(&codec, bytes.clone());
+    let _ = Codec::<ValidatorProof<TestContext>>::decode(&codec, bytes.clone());
+
+    let _ = Height::from_bytes(&bytes);
+    let _ = Address::from_bytes(&bytes);
+    let _ = ValueId::from_bytes(&bytes);
+    let _ = Value::from_bytes(&bytes);
+    let _ = Vote::from_bytes(&bytes);
+    let _ = Proposal::from_bytes(&bytes);
+    let _ = ProposalPart::from_bytes(&bytes);
+});
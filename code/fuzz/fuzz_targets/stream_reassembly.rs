@@ -0,0 +1,75 @@
+//! Feed arbitrary stream messages from a few peers to the reassembly of proposal parts.
+//!
+//! Messages may be dropped or never reassembled, but must never panic the node.
+
+#![no_main]
+
+use libfuzzer_sys::arbitrary::{Result, Unstructured};
+use libfuzzer_sys::fuzz_target;
+
+use malachitebft_core_types::Round;
+use malachitebft_engine::util::streaming::{StreamContent, StreamId, StreamMessage};
+use malachitebft_peer::PeerId;
+use malachitebft_test::{
+    Address, Height, ProposalData, ProposalFin, ProposalInit, ProposalPart, Signature,
+};
+use malachitebft_test_streaming::PartStreamsMap;
+
+fn peer(u: &mut Unstructured) -> Result<PeerId> {
+    // Identity multihash of 32 bytes, as for an inlined Ed25519 public key
+    let n = u.int_in_range(0..=3u8)?;
+    Ok(PeerId::from_bytes(&[&[0, 32], &[n; 32][..]].concat()).expect("valid multihash"))
+}
+
+fn round(u: &mut Unstructured) -> Result<Round> {
+    Ok(match u.arbitrary::<Option<u32>>()? {
+        Some(round) => Round::new(round),
+        None => Round::Nil,
+    })
+}
+
+fn part(u: &mut Unstructured) -> Result<ProposalPart> {
+    Ok(match u.int_in_range(0..=2u8)? {
+        0 => ProposalPart::Init(ProposalInit::new(
+            Height::new(u.arbitrary()?),
+            round(u)?,
+            round(u)?,
+            Address::new(u.arbitrary()?),
+        )),
+        1 => ProposalPart::Data(ProposalData::new(u.arbitrary()?)),
+        _ => ProposalPart::Fin(ProposalFin::new(Signature::from_bytes(u.arbitrary()?))),
+    })
+}
+
+fn message(u: &mut Unstructured) -> Result<StreamMessage<ProposalPart>> {
+    let stream_id = StreamId::new(vec![u.int_in_range(0..=3u8)?].into());
+
+    let sequence = match u.int_in_range(0..=2u8)? {
+        0 => u.int_in_range(0..=16)?,
+        1 => u64::MAX,
+        _ => u.arbitrary()?,
+    };
+
+    let content = if u.arbitrary()? {
+        StreamContent::Data(part(u)?)
+    } else {
+        StreamContent::Fin
+    };
+
+    Ok(StreamMessage::new(stream_id, sequence, content))
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut u = Unstructured::new(data);
+    let mut streams = PartStreamsMap::new();
+
+    while !u.is_empty() {
+        let (Ok(peer_id), Ok(msg)) = (peer(&mut u), message(&mut u)) else {
+            break;
+        };
+
+        if let Some(parts) = streams.insert(peer_id, msg) {
+            assert!(parts.init().is_some());
+        }
+    }
+});